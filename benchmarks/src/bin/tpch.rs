@@ -17,40 +17,16 @@
 
 //! Benchmark derived from TPC-H. This is not an official TPC-H benchmark.
 
-use std::{
-    fs::{self, File},
-    io::Write,
-    iter::Iterator,
-    path::{Path, PathBuf},
-    sync::Arc,
-    time::{Instant, SystemTime},
-};
-
-use datafusion::datasource::{MemTable, TableProvider};
+use std::{path::Path, path::PathBuf, time::Instant};
+
 use datafusion::error::{DataFusionError, Result};
 use datafusion::parquet::basic::Compression;
 use datafusion::parquet::file::properties::WriterProperties;
-use datafusion::physical_plan::display::DisplayableExecutionPlan;
-use datafusion::physical_plan::{collect, displayable};
 use datafusion::prelude::*;
-use datafusion::{
-    arrow::datatypes::{DataType, Field, Schema},
-    datasource::file_format::{csv::CsvFormat, FileFormat},
-    DATAFUSION_VERSION,
-};
-use datafusion::{
-    arrow::record_batch::RecordBatch, datasource::file_format::parquet::ParquetFormat,
-};
-use datafusion::{
-    arrow::util::pretty,
-    datasource::listing::{ListingOptions, ListingTable, ListingTableConfig},
-};
-
-use datafusion::datasource::file_format::csv::DEFAULT_CSV_EXTENSION;
-use datafusion::datasource::file_format::parquet::DEFAULT_PARQUET_EXTENSION;
-use datafusion::datasource::listing::ListingTableUrl;
-use datafusion::execution::context::SessionState;
-use serde::Serialize;
+use datafusion::{arrow::record_batch::RecordBatch, arrow::util::pretty};
+use datafusion_benchmarks::run::BenchmarkRun;
+use datafusion_benchmarks::tpch::datagen::{DataFormat, TpchGenerator};
+use datafusion_benchmarks::tpch::{get_schema, register_tables, run_query, TABLES};
 use structopt::StructOpt;
 
 #[cfg(feature = "snmalloc")]
@@ -131,6 +107,25 @@ struct ConvertOpt {
     batch_size: usize,
 }
 
+#[derive(Debug, StructOpt)]
+struct GenerateOpt {
+    /// Output path
+    #[structopt(parse(from_os_str), required = true, short = "o", long = "output")]
+    output_path: PathBuf,
+
+    /// Scale factor, 1.0 being about 1 GB of data
+    #[structopt(short = "s", long = "scale-factor", default_value = "1")]
+    scale_factor: f64,
+
+    /// Output file format: `csv` or `parquet`
+    #[structopt(short = "f", long = "format", default_value = "parquet")]
+    file_format: DataFormat,
+
+    /// Seed of the random values
+    #[structopt(long = "seed", default_value = "0")]
+    seed: u64,
+}
+
 #[derive(Debug, StructOpt)]
 #[structopt(about = "benchmark command")]
 enum BenchmarkSubCommandOpt {
@@ -143,12 +138,9 @@ enum BenchmarkSubCommandOpt {
 enum TpchOpt {
     Benchmark(BenchmarkSubCommandOpt),
     Convert(ConvertOpt),
+    Generate(GenerateOpt),
 }
 
-const TABLES: &[&str] = &[
-    "part", "supplier", "partsupp", "customer", "orders", "lineitem", "nation", "region",
-];
-
 #[tokio::main]
 async fn main() -> Result<()> {
     use BenchmarkSubCommandOpt::*;
@@ -159,10 +151,10 @@ async fn main() -> Result<()> {
             benchmark_datafusion(opt).await.map(|_| ())
         }
         TpchOpt::Convert(opt) => convert_tbl(opt).await,
+        TpchOpt::Generate(opt) => generate(opt),
     }
 }
 
-#[allow(clippy::await_holding_lock)]
 async fn benchmark_datafusion(opt: DataFusionBenchmarkOpt) -> Result<Vec<RecordBatch>> {
     println!("Running benchmarks with the following options: {:?}", opt);
     let mut benchmark_run = BenchmarkRun::new(opt.query);
@@ -172,167 +164,72 @@ async fn benchmark_datafusion(opt: DataFusionBenchmarkOpt) -> Result<Vec<RecordB
         .with_collect_statistics(!opt.disable_statistics);
     let ctx = SessionContext::with_config(config);
 
-    // register tables
-    for table in TABLES {
-        let table_provider = {
-            let mut session_state = ctx.state.write();
-            get_table(
-                &mut session_state,
-                opt.path.to_str().unwrap(),
-                table,
-                opt.file_format.as_str(),
-                opt.partitions,
-            )
-            .await?
-        };
-
-        if opt.mem_table {
-            println!("Loading table '{}' into memory", table);
-            let start = Instant::now();
-            let memtable =
-                MemTable::load(table_provider, Some(opt.partitions), &ctx.state())
-                    .await?;
-            println!(
-                "Loaded table '{}' into memory in {} ms",
-                table,
-                start.elapsed().as_millis()
-            );
-            ctx.register_table(*table, Arc::new(memtable))?;
-        } else {
-            ctx.register_table(*table, table_provider)?;
-        }
-    }
+    register_tables(
+        &ctx,
+        opt.path.to_str().unwrap(),
+        opt.file_format.as_str(),
+        opt.partitions,
+        opt.mem_table,
+    )
+    .await?;
 
-    let mut millis = vec![];
     // run benchmark
     let mut result: Vec<RecordBatch> = Vec::with_capacity(1);
     for i in 0..opt.iterations {
         let start = Instant::now();
-
-        let sql = &get_query_sql(opt.query)?;
-
-        // query 15 is special, with 3 statements. the second statement is the one from which we
-        // want to capture the results
-        if opt.query == 15 {
-            for (n, query) in sql.iter().enumerate() {
-                if n == 1 {
-                    result = execute_query(&ctx, query, opt.debug).await?;
-                } else {
-                    execute_query(&ctx, query, opt.debug).await?;
-                }
-            }
-        } else {
-            for query in sql {
-                result = execute_query(&ctx, query, opt.debug).await?;
-            }
-        }
-
+        let execution = run_query(&ctx, opt.query, opt.debug).await?;
         let elapsed = start.elapsed().as_secs_f64() * 1000.0;
-        millis.push(elapsed as f64);
-        let row_count = result.iter().map(|b| b.num_rows()).sum();
+        let row_count = execution.row_count();
         println!(
             "Query {} iteration {} took {:.1} ms and returned {} rows",
             opt.query, i, elapsed, row_count
         );
+        if let Some(plans) = &execution.plans {
+            println!("=== Logical plan ===\n{}\n", plans.logical);
+            println!("=== Optimized logical plan ===\n{}\n", plans.optimized);
+            println!("=== Physical plan ===\n{}\n", plans.physical);
+            println!(
+                "=== Physical plan with metrics ===\n{}\n",
+                plans.physical_with_metrics
+            );
+            if !execution.batches.is_empty() {
+                // do not call print_batches if there are no batches as the result is confusing
+                // and makes it look like there is a batch with no columns
+                pretty::print_batches(&execution.batches)?;
+            }
+            benchmark_run.plans = execution.plans;
+        }
         benchmark_run.add_result(elapsed, row_count);
+        result = execution.batches;
     }
 
-    let avg = millis.iter().sum::<f64>() / millis.len() as f64;
-    println!("Query {} avg time: {:.2} ms", opt.query, avg);
+    println!(
+        "Query {} avg time: {:.2} ms",
+        opt.query,
+        benchmark_run.average_elapsed()
+    );
 
     if let Some(path) = &opt.output_path {
-        write_summary_json(&mut benchmark_run, path)?;
+        benchmark_run.write_summary_json(path)?;
     }
 
     Ok(result)
 }
 
-fn write_summary_json(benchmark_run: &mut BenchmarkRun, path: &Path) -> Result<()> {
-    let json =
-        serde_json::to_string_pretty(&benchmark_run).expect("summary is serializable");
-    let filename = format!(
-        "tpch-q{}-{}.json",
-        benchmark_run.query, benchmark_run.start_time
-    );
-    let path = path.join(filename);
-    println!(
-        "Writing summary file to {}",
-        path.as_os_str().to_str().unwrap()
-    );
-    let mut file = File::create(path)?;
-    file.write_all(json.as_bytes())?;
-    Ok(())
-}
-
-/// Get the SQL statements from the specified query file
-fn get_query_sql(query: usize) -> Result<Vec<String>> {
-    if query > 0 && query < 23 {
-        let possibilities = vec![
-            format!("queries/q{}.sql", query),
-            format!("benchmarks/queries/q{}.sql", query),
-        ];
-        let mut errors = vec![];
-        for filename in possibilities {
-            match fs::read_to_string(&filename) {
-                Ok(contents) => {
-                    return Ok(contents
-                        .split(';')
-                        .map(|s| s.trim())
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.to_string())
-                        .collect());
-                }
-                Err(e) => errors.push(format!("{}: {}", filename, e)),
-            };
-        }
-        Err(DataFusionError::Plan(format!(
-            "invalid query. Could not find query: {:?}",
-            errors
-        )))
-    } else {
-        Err(DataFusionError::Plan(
-            "invalid query. Expected value between 1 and 22".to_owned(),
-        ))
-    }
-}
-
-async fn execute_query(
-    ctx: &SessionContext,
-    sql: &str,
-    debug: bool,
-) -> Result<Vec<RecordBatch>> {
-    let plan = ctx.sql(sql).await?;
-    let plan = plan.to_logical_plan()?;
-
-    if debug {
-        println!("=== Logical plan ===\n{:?}\n", plan);
-    }
-
-    if debug {
-        let plan = ctx.optimize(&plan)?;
-        println!("=== Optimized logical plan ===\n{:?}\n", plan);
-    }
-    let physical_plan = ctx.create_physical_plan(&plan).await?;
-    if debug {
-        println!(
-            "=== Physical plan ===\n{}\n",
-            displayable(physical_plan.as_ref()).indent()
-        );
-    }
-    let task_ctx = ctx.task_ctx();
-    let result = collect(physical_plan.clone(), task_ctx).await?;
-    if debug {
+fn generate(opt: GenerateOpt) -> Result<()> {
+    let generator = TpchGenerator::new(opt.scale_factor).with_seed(opt.seed);
+    for table in TABLES {
+        let start = Instant::now();
         println!(
-            "=== Physical plan with metrics ===\n{}\n",
-            DisplayableExecutionPlan::with_metrics(physical_plan.as_ref()).indent()
+            "Generating table '{}' as {} in directory '{}'",
+            table,
+            opt.file_format.extension(),
+            opt.output_path.display()
         );
-        if !result.is_empty() {
-            // do not call print_batches if there are no batches as the result is confusing
-            // and makes it look like there is a batch with no columns
-            pretty::print_batches(&result)?;
-        }
+        generator.write_table(table, &opt.output_path, opt.file_format)?;
+        println!("Generation completed in {} ms", start.elapsed().as_millis());
     }
-    Ok(result)
+    Ok(())
 }
 
 async fn convert_tbl(opt: ConvertOpt) -> Result<()> {
@@ -406,222 +303,25 @@ async fn convert_tbl(opt: ConvertOpt) -> Result<()> {
     Ok(())
 }
 
-async fn get_table(
-    ctx: &mut SessionState,
-    path: &str,
-    table: &str,
-    table_format: &str,
-    target_partitions: usize,
-) -> Result<Arc<dyn TableProvider>> {
-    let (format, path, extension): (Arc<dyn FileFormat>, String, &'static str) =
-        match table_format {
-            // dbgen creates .tbl ('|' delimited) files without header
-            "tbl" => {
-                let path = format!("{}/{}.tbl", path, table);
-
-                let format = CsvFormat::default()
-                    .with_delimiter(b'|')
-                    .with_has_header(false);
-
-                (Arc::new(format), path, ".tbl")
-            }
-            "csv" => {
-                let path = format!("{}/{}", path, table);
-                let format = CsvFormat::default()
-                    .with_delimiter(b',')
-                    .with_has_header(true);
-
-                (Arc::new(format), path, DEFAULT_CSV_EXTENSION)
-            }
-            "parquet" => {
-                let path = format!("{}/{}", path, table);
-                let format = ParquetFormat::default().with_enable_pruning(true);
-
-                (Arc::new(format), path, DEFAULT_PARQUET_EXTENSION)
-            }
-            other => {
-                unimplemented!("Invalid file format '{}'", other);
-            }
-        };
-    let schema = Arc::new(get_schema(table));
-
-    let options = ListingOptions {
-        format,
-        file_extension: extension.to_owned(),
-        target_partitions,
-        collect_stat: ctx.config.collect_statistics,
-        table_partition_cols: vec![],
-    };
-
-    let table_path = ListingTableUrl::parse(path)?;
-    let config = ListingTableConfig::new(table_path).with_listing_options(options);
-
-    let config = if table_format == "parquet" {
-        config.infer_schema(ctx).await?
-    } else {
-        config.with_schema(schema)
-    };
-
-    Ok(Arc::new(ListingTable::try_new(config)?))
-}
-
-fn get_schema(table: &str) -> Schema {
-    // note that the schema intentionally uses signed integers so that any generated Parquet
-    // files can also be used to benchmark tools that only support signed integers, such as
-    // Apache Spark
-
-    match table {
-        "part" => Schema::new(vec![
-            Field::new("p_partkey", DataType::Int64, false),
-            Field::new("p_name", DataType::Utf8, false),
-            Field::new("p_mfgr", DataType::Utf8, false),
-            Field::new("p_brand", DataType::Utf8, false),
-            Field::new("p_type", DataType::Utf8, false),
-            Field::new("p_size", DataType::Int32, false),
-            Field::new("p_container", DataType::Utf8, false),
-            Field::new("p_retailprice", DataType::Decimal128(15, 2), false),
-            Field::new("p_comment", DataType::Utf8, false),
-        ]),
-
-        "supplier" => Schema::new(vec![
-            Field::new("s_suppkey", DataType::Int64, false),
-            Field::new("s_name", DataType::Utf8, false),
-            Field::new("s_address", DataType::Utf8, false),
-            Field::new("s_nationkey", DataType::Int64, false),
-            Field::new("s_phone", DataType::Utf8, false),
-            Field::new("s_acctbal", DataType::Decimal128(15, 2), false),
-            Field::new("s_comment", DataType::Utf8, false),
-        ]),
-
-        "partsupp" => Schema::new(vec![
-            Field::new("ps_partkey", DataType::Int64, false),
-            Field::new("ps_suppkey", DataType::Int64, false),
-            Field::new("ps_availqty", DataType::Int32, false),
-            Field::new("ps_supplycost", DataType::Decimal128(15, 2), false),
-            Field::new("ps_comment", DataType::Utf8, false),
-        ]),
-
-        "customer" => Schema::new(vec![
-            Field::new("c_custkey", DataType::Int64, false),
-            Field::new("c_name", DataType::Utf8, false),
-            Field::new("c_address", DataType::Utf8, false),
-            Field::new("c_nationkey", DataType::Int64, false),
-            Field::new("c_phone", DataType::Utf8, false),
-            Field::new("c_acctbal", DataType::Decimal128(15, 2), false),
-            Field::new("c_mktsegment", DataType::Utf8, false),
-            Field::new("c_comment", DataType::Utf8, false),
-        ]),
-
-        "orders" => Schema::new(vec![
-            Field::new("o_orderkey", DataType::Int64, false),
-            Field::new("o_custkey", DataType::Int64, false),
-            Field::new("o_orderstatus", DataType::Utf8, false),
-            Field::new("o_totalprice", DataType::Decimal128(15, 2), false),
-            Field::new("o_orderdate", DataType::Date32, false),
-            Field::new("o_orderpriority", DataType::Utf8, false),
-            Field::new("o_clerk", DataType::Utf8, false),
-            Field::new("o_shippriority", DataType::Int32, false),
-            Field::new("o_comment", DataType::Utf8, false),
-        ]),
-
-        "lineitem" => Schema::new(vec![
-            Field::new("l_orderkey", DataType::Int64, false),
-            Field::new("l_partkey", DataType::Int64, false),
-            Field::new("l_suppkey", DataType::Int64, false),
-            Field::new("l_linenumber", DataType::Int32, false),
-            Field::new("l_quantity", DataType::Decimal128(15, 2), false),
-            Field::new("l_extendedprice", DataType::Decimal128(15, 2), false),
-            Field::new("l_discount", DataType::Decimal128(15, 2), false),
-            Field::new("l_tax", DataType::Decimal128(15, 2), false),
-            Field::new("l_returnflag", DataType::Utf8, false),
-            Field::new("l_linestatus", DataType::Utf8, false),
-            Field::new("l_shipdate", DataType::Date32, false),
-            Field::new("l_commitdate", DataType::Date32, false),
-            Field::new("l_receiptdate", DataType::Date32, false),
-            Field::new("l_shipinstruct", DataType::Utf8, false),
-            Field::new("l_shipmode", DataType::Utf8, false),
-            Field::new("l_comment", DataType::Utf8, false),
-        ]),
-
-        "nation" => Schema::new(vec![
-            Field::new("n_nationkey", DataType::Int64, false),
-            Field::new("n_name", DataType::Utf8, false),
-            Field::new("n_regionkey", DataType::Int64, false),
-            Field::new("n_comment", DataType::Utf8, false),
-        ]),
-
-        "region" => Schema::new(vec![
-            Field::new("r_regionkey", DataType::Int64, false),
-            Field::new("r_name", DataType::Utf8, false),
-            Field::new("r_comment", DataType::Utf8, false),
-        ]),
-
-        _ => unimplemented!(),
-    }
-}
-
-#[derive(Debug, Serialize)]
-struct BenchmarkRun {
-    /// Benchmark crate version
-    benchmark_version: String,
-    /// DataFusion crate version
-    datafusion_version: String,
-    /// Number of CPU cores
-    num_cpus: usize,
-    /// Start time
-    start_time: u64,
-    /// CLI arguments
-    arguments: Vec<String>,
-    /// query number
-    query: usize,
-    /// list of individual run times and row counts
-    iterations: Vec<QueryResult>,
-}
-
-impl BenchmarkRun {
-    fn new(query: usize) -> Self {
-        Self {
-            benchmark_version: env!("CARGO_PKG_VERSION").to_owned(),
-            datafusion_version: DATAFUSION_VERSION.to_owned(),
-            num_cpus: num_cpus::get(),
-            start_time: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("current time is later than UNIX_EPOCH")
-                .as_secs(),
-            arguments: std::env::args()
-                .skip(1)
-                .into_iter()
-                .collect::<Vec<String>>(),
-            query,
-            iterations: vec![],
-        }
-    }
-
-    fn add_result(&mut self, elapsed: f64, row_count: usize) {
-        self.iterations.push(QueryResult { elapsed, row_count })
-    }
-}
-
-#[derive(Debug, Serialize)]
-struct QueryResult {
-    elapsed: f64,
-    row_count: usize,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
+    use std::fs::File;
     use std::io::{BufRead, BufReader};
     use std::ops::{Div, Mul};
     use std::sync::Arc;
 
     use datafusion::arrow::array::*;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
     use datafusion::arrow::util::display::array_value_to_string;
+    use datafusion::datasource::MemTable;
     use datafusion::logical_expr::expr::Cast;
     use datafusion::logical_expr::Expr;
     use datafusion::logical_expr::Expr::ScalarFunction;
     use datafusion::sql::TableReference;
+    use datafusion_benchmarks::run::run_sql;
+    use datafusion_benchmarks::tpch::get_query_sql;
 
     const QUERY_LIMIT: [Option<usize>; 22] = [
         None,
@@ -1313,7 +1013,7 @@ mod tests {
 
         let sql = &get_query_sql(n)?;
         for query in sql {
-            execute_query(&ctx, query, false).await?;
+            run_sql(&ctx, query, false).await?;
         }
 
         Ok(())
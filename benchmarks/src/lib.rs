@@ -0,0 +1,39 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The DataFusion benchmarks as a library, to run them programmatically
+//! against custom session configurations.
+//!
+//! ```no_run
+//! use datafusion::error::Result;
+//! use datafusion::prelude::*;
+//! use datafusion_benchmarks::tpch::{self, datagen::{DataFormat, TpchGenerator}};
+//!
+//! # async fn example() -> Result<()> {
+//! // generate the tables at scale factor 0.1
+//! TpchGenerator::new(0.1).write_tables("/tmp/tpch".as_ref(), DataFormat::Parquet)?;
+//!
+//! let ctx = SessionContext::with_config(SessionConfig::new().with_target_partitions(4));
+//! tpch::register_tables(&ctx, "/tmp/tpch", "parquet", 4, false).await?;
+//! let execution = tpch::run_query(&ctx, 1, true).await?;
+//! println!("{}", execution.plans.unwrap().physical_with_metrics);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod run;
+pub mod tpch;
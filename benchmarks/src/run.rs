@@ -0,0 +1,174 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Running queries, capturing their plans, and recording the timings of
+//! benchmark runs
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::Result;
+use datafusion::physical_plan::display::DisplayableExecutionPlan;
+use datafusion::physical_plan::{collect, displayable};
+use datafusion::prelude::SessionContext;
+use datafusion::DATAFUSION_VERSION;
+use log::info;
+use serde::Serialize;
+
+/// The plans of a query, formatted when it was run
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryPlans {
+    /// The logical plan, as planned from SQL
+    pub logical: String,
+    /// The optimized logical plan
+    pub optimized: String,
+    /// The physical plan
+    pub physical: String,
+    /// The physical plan with the metrics of its execution
+    pub physical_with_metrics: String,
+}
+
+/// The results of a query, see [`run_sql`]
+#[derive(Debug)]
+pub struct QueryExecution {
+    /// The record batches returned by the query
+    pub batches: Vec<RecordBatch>,
+    /// The plans of the query, when they are captured
+    pub plans: Option<QueryPlans>,
+}
+
+impl QueryExecution {
+    /// The number of rows returned by the query
+    pub fn row_count(&self) -> usize {
+        self.batches.iter().map(|b| b.num_rows()).sum()
+    }
+}
+
+/// Plans and runs the SQL statement `sql`, formatting its plans when
+/// `capture_plans` is true
+pub async fn run_sql(
+    ctx: &SessionContext,
+    sql: &str,
+    capture_plans: bool,
+) -> Result<QueryExecution> {
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let logical = capture_plans.then(|| format!("{:?}", plan));
+    let optimized = match capture_plans {
+        true => Some(format!("{:?}", ctx.optimize(&plan)?)),
+        false => None,
+    };
+
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let physical =
+        capture_plans.then(|| displayable(physical_plan.as_ref()).indent().to_string());
+    let batches = collect(physical_plan.clone(), ctx.task_ctx()).await?;
+
+    let plans = match (logical, optimized, physical) {
+        (Some(logical), Some(optimized), Some(physical)) => Some(QueryPlans {
+            logical,
+            optimized,
+            physical,
+            physical_with_metrics: DisplayableExecutionPlan::with_metrics(
+                physical_plan.as_ref(),
+            )
+            .indent()
+            .to_string(),
+        }),
+        _ => None,
+    };
+    Ok(QueryExecution { batches, plans })
+}
+
+/// The summary of the iterations of a benchmark query
+#[derive(Debug, Serialize)]
+pub struct BenchmarkRun {
+    /// Benchmark crate version
+    pub benchmark_version: String,
+    /// DataFusion crate version
+    pub datafusion_version: String,
+    /// Number of CPU cores
+    pub num_cpus: usize,
+    /// Start time
+    pub start_time: u64,
+    /// CLI arguments
+    pub arguments: Vec<String>,
+    /// query number
+    pub query: usize,
+    /// list of individual run times and row counts
+    pub iterations: Vec<QueryResult>,
+    /// the plans of the query, if captured
+    pub plans: Option<QueryPlans>,
+}
+
+impl BenchmarkRun {
+    /// Creates the summary of the runs of `query`
+    pub fn new(query: usize) -> Self {
+        Self {
+            benchmark_version: env!("CARGO_PKG_VERSION").to_owned(),
+            datafusion_version: DATAFUSION_VERSION.to_owned(),
+            num_cpus: num_cpus::get(),
+            start_time: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("current time is later than UNIX_EPOCH")
+                .as_secs(),
+            arguments: std::env::args()
+                .skip(1)
+                .into_iter()
+                .collect::<Vec<String>>(),
+            query,
+            iterations: vec![],
+            plans: None,
+        }
+    }
+
+    /// Records an iteration that took `elapsed` milliseconds
+    pub fn add_result(&mut self, elapsed: f64, row_count: usize) {
+        self.iterations.push(QueryResult { elapsed, row_count })
+    }
+
+    /// The average time of the iterations, in milliseconds
+    pub fn average_elapsed(&self) -> f64 {
+        self.iterations.iter().map(|r| r.elapsed).sum::<f64>()
+            / self.iterations.len() as f64
+    }
+
+    /// Writes the summary as JSON to a file of the directory `path`
+    pub fn write_summary_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("summary is serializable");
+        let filename = format!("tpch-q{}-{}.json", self.query, self.start_time);
+        let path = path.join(filename);
+        info!(
+            "Writing summary file to {}",
+            path.as_os_str().to_str().unwrap()
+        );
+        let mut file = File::create(path)?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// The time and the row count of an iteration of a query
+#[derive(Debug, Serialize)]
+pub struct QueryResult {
+    /// The time of the iteration, in milliseconds
+    pub elapsed: f64,
+    /// The number of rows returned by the query
+    pub row_count: usize,
+}
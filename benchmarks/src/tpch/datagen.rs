@@ -0,0 +1,953 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Generation of the TPC-H tables without `dbgen`
+//!
+//! The data follows the cardinalities, key relationships and value domains of
+//! the TPC-H specification closely enough for the queries to be meaningful,
+//! but it is not the data of `dbgen`: the answers of the queries differ from
+//! the official ones.
+
+use std::fs::{self, File};
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    ArrayRef, Date32Array, Decimal128Array, Int32Array, Int64Array, StringArray,
+};
+use datafusion::arrow::csv;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::parquet::arrow::ArrowWriter;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::{get_schema, TABLES};
+
+/// The format of the files written by [`TpchGenerator`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// CSV files with a header
+    Csv,
+    /// Parquet files
+    Parquet,
+}
+
+impl DataFormat {
+    /// The extension of the files of this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(DataFormat::Csv),
+            "parquet" => Ok(DataFormat::Parquet),
+            other => Err(DataFusionError::NotImplemented(format!(
+                "Invalid output format: {}",
+                other
+            ))),
+        }
+    }
+}
+
+// days since the epoch of 1992-01-01, 1995-06-17 and 1998-12-31
+const START_DATE: i32 = 8035;
+const CURRENT_DATE: i32 = 9298;
+const END_DATE: i32 = 10591;
+
+const REGIONS: [&str; 5] = ["AFRICA", "AMERICA", "ASIA", "EUROPE", "MIDDLE EAST"];
+
+const NATIONS: [(&str, i64); 25] = [
+    ("ALGERIA", 0),
+    ("ARGENTINA", 1),
+    ("BRAZIL", 1),
+    ("CANADA", 1),
+    ("EGYPT", 4),
+    ("ETHIOPIA", 0),
+    ("FRANCE", 3),
+    ("GERMANY", 3),
+    ("INDIA", 2),
+    ("INDONESIA", 2),
+    ("IRAN", 4),
+    ("IRAQ", 4),
+    ("JAPAN", 2),
+    ("JORDAN", 4),
+    ("KENYA", 0),
+    ("MOROCCO", 0),
+    ("MOZAMBIQUE", 0),
+    ("PERU", 1),
+    ("CHINA", 2),
+    ("ROMANIA", 3),
+    ("SAUDI ARABIA", 4),
+    ("VIETNAM", 2),
+    ("RUSSIA", 3),
+    ("UNITED KINGDOM", 3),
+    ("UNITED STATES", 1),
+];
+
+const COLORS: [&str; 40] = [
+    "almond",
+    "antique",
+    "aquamarine",
+    "azure",
+    "beige",
+    "bisque",
+    "black",
+    "blanched",
+    "blue",
+    "blush",
+    "brown",
+    "burlywood",
+    "chartreuse",
+    "chocolate",
+    "coral",
+    "cornflower",
+    "cream",
+    "cyan",
+    "dark",
+    "deep",
+    "dim",
+    "dodger",
+    "firebrick",
+    "floral",
+    "forest",
+    "frosted",
+    "gainsboro",
+    "ghost",
+    "goldenrod",
+    "green",
+    "grey",
+    "honeydew",
+    "hot",
+    "indian",
+    "ivory",
+    "khaki",
+    "lace",
+    "lavender",
+    "lemon",
+    "lime",
+];
+
+const TYPE_SIZES: [&str; 6] =
+    ["STANDARD", "SMALL", "MEDIUM", "LARGE", "ECONOMY", "PROMO"];
+const TYPE_FINISHES: [&str; 5] =
+    ["ANODIZED", "BURNISHED", "PLATED", "POLISHED", "BRUSHED"];
+const TYPE_MATERIALS: [&str; 5] = ["TIN", "NICKEL", "BRASS", "STEEL", "COPPER"];
+
+const CONTAINER_SIZES: [&str; 5] = ["SM", "LG", "MED", "JUMBO", "WRAP"];
+const CONTAINER_KINDS: [&str; 8] =
+    ["CASE", "BOX", "BAG", "JAR", "PKG", "PACK", "CAN", "DRUM"];
+
+const SEGMENTS: [&str; 5] = [
+    "AUTOMOBILE",
+    "BUILDING",
+    "FURNITURE",
+    "MACHINERY",
+    "HOUSEHOLD",
+];
+
+const PRIORITIES: [&str; 5] =
+    ["1-URGENT", "2-HIGH", "3-MEDIUM", "4-NOT SPECIFIED", "5-LOW"];
+
+const INSTRUCTIONS: [&str; 4] = [
+    "DELIVER IN PERSON",
+    "COLLECT COD",
+    "NONE",
+    "TAKE BACK RETURN",
+];
+
+const SHIP_MODES: [&str; 7] = ["REG AIR", "AIR", "RAIL", "SHIP", "TRUCK", "MAIL", "FOB"];
+
+const WORDS: [&str; 32] = [
+    "furiously",
+    "quickly",
+    "carefully",
+    "blithely",
+    "slyly",
+    "fluffily",
+    "final",
+    "regular",
+    "special",
+    "pending",
+    "express",
+    "ironic",
+    "bold",
+    "even",
+    "unusual",
+    "silent",
+    "packages",
+    "requests",
+    "accounts",
+    "deposits",
+    "foxes",
+    "ideas",
+    "theodolites",
+    "pinto beans",
+    "instructions",
+    "dependencies",
+    "sleep",
+    "wake",
+    "haggle",
+    "nag",
+    "use",
+    "cajole",
+];
+
+/// Generates the TPC-H tables at a scale factor, see the [module
+/// documentation](self)
+///
+/// The generation is deterministic: generators with the same scale factor,
+/// seed and batch size generate the same rows.
+#[derive(Debug, Clone)]
+pub struct TpchGenerator {
+    scale_factor: f64,
+    batch_size: usize,
+    seed: u64,
+}
+
+/// An order with its lines, generated together to keep the `orders` and
+/// `lineitem` tables consistent
+struct Order {
+    key: i64,
+    custkey: i64,
+    status: &'static str,
+    total_price: i128,
+    date: i32,
+    priority: &'static str,
+    clerk: String,
+    comment: String,
+    lines: Vec<LineItem>,
+}
+
+struct LineItem {
+    partkey: i64,
+    suppkey: i64,
+    quantity: i128,
+    extended_price: i128,
+    discount: i128,
+    tax: i128,
+    return_flag: &'static str,
+    line_status: &'static str,
+    ship_date: i32,
+    commit_date: i32,
+    receipt_date: i32,
+    instruction: &'static str,
+    mode: &'static str,
+    comment: String,
+}
+
+impl TpchGenerator {
+    /// Creates a generator of the tables at `scale_factor`, 1.0 being
+    /// about 1 GB of data
+    pub fn new(scale_factor: f64) -> Self {
+        Self {
+            scale_factor,
+            batch_size: 8192,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of rows of the generated batches, the batches of
+    /// `partsupp` and `lineitem` having the rows of that number of parts and
+    /// orders
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the seed of the random values
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The number of rows of `table`, or of orders for `lineitem`
+    fn size(&self, table: &str) -> usize {
+        let scaled = |rows: f64| ((rows * self.scale_factor).round() as usize).max(1);
+        match table {
+            "part" | "partsupp" => scaled(200_000.0),
+            "supplier" => scaled(10_000.0),
+            "customer" => scaled(150_000.0),
+            "orders" | "lineitem" => scaled(1_500_000.0),
+            "nation" => NATIONS.len(),
+            "region" => REGIONS.len(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The random generator of the rows `range` of `table`
+    fn rng(&self, table: &str, range: &Range<usize>) -> StdRng {
+        let table = TABLES.iter().position(|t| *t == table).unwrap() as u64;
+        StdRng::seed_from_u64(
+            self.seed
+                .wrapping_mul(1_000_003)
+                .wrapping_add(table)
+                .wrapping_mul(1_000_003)
+                .wrapping_add(range.start as u64),
+        )
+    }
+
+    /// Returns the batches of the TPC-H table `table`
+    pub fn batches(
+        &self,
+        table: &str,
+    ) -> Result<impl Iterator<Item = Result<RecordBatch>> + '_> {
+        let table = TABLES
+            .iter()
+            .find(|t| **t == table)
+            .copied()
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!("Unknown TPC-H table '{}'", table))
+            })?;
+        let schema = Arc::new(get_schema(table));
+        let size = self.size(table);
+        Ok((0..size).step_by(self.batch_size).map(move |start| {
+            let range = start..size.min(start + self.batch_size);
+            let columns = match table {
+                "part" => self.part(range),
+                "supplier" => self.supplier(range),
+                "partsupp" => self.partsupp(range),
+                "customer" => self.customer(range),
+                "orders" => self.orders_columns(range),
+                "lineitem" => self.lineitem_columns(range),
+                "nation" => self.nation(range),
+                "region" => self.region(range),
+                _ => unreachable!(),
+            }?;
+            Ok(RecordBatch::try_new(schema.clone(), columns)?)
+        }))
+    }
+
+    /// Writes the table `table` into the directory `path/table`, in a single
+    /// file
+    pub fn write_table(
+        &self,
+        table: &str,
+        path: &Path,
+        format: DataFormat,
+    ) -> Result<()> {
+        let batches = self.batches(table)?;
+        let dir = path.join(table);
+        fs::create_dir_all(&dir)?;
+        let file = File::create(dir.join(format!("part-0.{}", format.extension())))?;
+        let schema: SchemaRef = Arc::new(get_schema(table));
+        match format {
+            DataFormat::Csv => {
+                let mut writer = csv::Writer::new(file);
+                for batch in batches {
+                    writer.write(&batch?)?;
+                }
+            }
+            DataFormat::Parquet => {
+                let mut writer = ArrowWriter::try_new(file, schema, None)?;
+                for batch in batches {
+                    writer.write(&batch?)?;
+                }
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all the TPC-H tables into the directory `path`, which can then
+    /// be registered with [`register_tables`](super::register_tables)
+    pub fn write_tables(&self, path: &Path, format: DataFormat) -> Result<()> {
+        for table in TABLES {
+            self.write_table(table, path, format)?;
+        }
+        Ok(())
+    }
+
+    fn part(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("part", &range);
+        let keys = range.map(|i| i as i64 + 1).collect::<Vec<_>>();
+        let mut names = vec![];
+        let mut manufacturers = vec![];
+        let mut brands = vec![];
+        let mut types = vec![];
+        let mut sizes = vec![];
+        let mut containers = vec![];
+        let mut comments = vec![];
+        for _ in &keys {
+            let colors = COLORS.choose_multiple(&mut rng, 5).copied();
+            names.push(colors.collect::<Vec<_>>().join(" "));
+            let manufacturer = rng.gen_range(1..=5);
+            manufacturers.push(format!("Manufacturer#{}", manufacturer));
+            brands.push(format!("Brand#{}{}", manufacturer, rng.gen_range(1..=5)));
+            types.push(format!(
+                "{} {} {}",
+                choose(&mut rng, &TYPE_SIZES),
+                choose(&mut rng, &TYPE_FINISHES),
+                choose(&mut rng, &TYPE_MATERIALS)
+            ));
+            sizes.push(rng.gen_range(1..=50));
+            containers.push(format!(
+                "{} {}",
+                choose(&mut rng, &CONTAINER_SIZES),
+                choose(&mut rng, &CONTAINER_KINDS)
+            ));
+            comments.push(text(&mut rng, 5, 22));
+        }
+        let prices = keys.iter().map(|k| retail_price(*k)).collect();
+        Ok(vec![
+            Arc::new(Int64Array::from(keys)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(manufacturers)),
+            Arc::new(StringArray::from(brands)),
+            Arc::new(StringArray::from(types)),
+            Arc::new(Int32Array::from(sizes)),
+            Arc::new(StringArray::from(containers)),
+            decimal(prices)?,
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+
+    fn supplier(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("supplier", &range);
+        let keys = range.map(|i| i as i64 + 1).collect::<Vec<_>>();
+        let mut names = vec![];
+        let mut addresses = vec![];
+        let mut nations = vec![];
+        let mut phones = vec![];
+        let mut balances = vec![];
+        let mut comments = vec![];
+        for key in &keys {
+            names.push(format!("Supplier#{:09}", key));
+            addresses.push(address(&mut rng));
+            let nation = rng.gen_range(0..NATIONS.len() as i64);
+            nations.push(nation);
+            phones.push(phone(&mut rng, nation));
+            balances.push(rng.gen_range(-99_999..=999_999));
+            let mut comment = text(&mut rng, 25, 100);
+            // like dbgen, a few suppliers have complaints, which query 16 excludes
+            if rng.gen_ratio(1, 200) {
+                comment = format!("Customer {} Complaints", comment);
+            }
+            comments.push(comment);
+        }
+        Ok(vec![
+            Arc::new(Int64Array::from(keys)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(addresses)),
+            Arc::new(Int64Array::from(nations)),
+            Arc::new(StringArray::from(phones)),
+            decimal(balances)?,
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+
+    fn partsupp(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("partsupp", &range);
+        let suppliers = self.size("supplier") as i64;
+        let mut partkeys = vec![];
+        let mut suppkeys = vec![];
+        let mut quantities = vec![];
+        let mut costs = vec![];
+        let mut comments = vec![];
+        for partkey in range.map(|i| i as i64 + 1) {
+            for i in 0..4 {
+                partkeys.push(partkey);
+                suppkeys.push(part_supplier(partkey, i, suppliers));
+                quantities.push(rng.gen_range(1..=9999));
+                costs.push(rng.gen_range(100..=100_000));
+                comments.push(text(&mut rng, 49, 198));
+            }
+        }
+        Ok(vec![
+            Arc::new(Int64Array::from(partkeys)),
+            Arc::new(Int64Array::from(suppkeys)),
+            Arc::new(Int32Array::from(quantities)),
+            decimal(costs)?,
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+
+    fn customer(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("customer", &range);
+        let keys = range.map(|i| i as i64 + 1).collect::<Vec<_>>();
+        let mut names = vec![];
+        let mut addresses = vec![];
+        let mut nations = vec![];
+        let mut phones = vec![];
+        let mut balances = vec![];
+        let mut segments = vec![];
+        let mut comments = vec![];
+        for key in &keys {
+            names.push(format!("Customer#{:09}", key));
+            addresses.push(address(&mut rng));
+            let nation = rng.gen_range(0..NATIONS.len() as i64);
+            nations.push(nation);
+            phones.push(phone(&mut rng, nation));
+            balances.push(rng.gen_range(-99_999..=999_999));
+            segments.push(choose(&mut rng, &SEGMENTS));
+            comments.push(text(&mut rng, 29, 116));
+        }
+        Ok(vec![
+            Arc::new(Int64Array::from(keys)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(addresses)),
+            Arc::new(Int64Array::from(nations)),
+            Arc::new(StringArray::from(phones)),
+            decimal(balances)?,
+            Arc::new(StringArray::from(segments)),
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+
+    /// Generates the orders `range`, with their lines
+    fn orders(&self, range: Range<usize>) -> Vec<Order> {
+        let mut rng = self.rng("orders", &range);
+        let customers = self.size("customer") as i64;
+        let parts = self.size("part") as i64;
+        let suppliers = self.size("supplier") as i64;
+        let clerks = ((1000.0 * self.scale_factor).round() as i64).max(1);
+
+        range
+            .map(|i| {
+                // like dbgen, only the first 8 keys of every 32 are used
+                let key = (i as i64 / 8) * 32 + i as i64 % 8 + 1;
+                // and a third of the customers have no orders
+                let custkey = loop {
+                    let custkey = rng.gen_range(1..=customers);
+                    if custkey % 3 != 0 || customers < 3 {
+                        break custkey;
+                    }
+                };
+                let date = rng.gen_range(START_DATE..=END_DATE - 151);
+
+                let lines = (0..rng.gen_range(1..=7))
+                    .map(|_| {
+                        let partkey = rng.gen_range(1..=parts);
+                        let suppkey =
+                            part_supplier(partkey, rng.gen_range(0..4), suppliers);
+                        let quantity = rng.gen_range(1..=50);
+                        let ship_date = date + rng.gen_range(1..=121);
+                        let receipt_date = ship_date + rng.gen_range(1..=30);
+                        let return_flag = if receipt_date <= CURRENT_DATE {
+                            choose(&mut rng, &["R", "A"])
+                        } else {
+                            "N"
+                        };
+                        LineItem {
+                            partkey,
+                            suppkey,
+                            quantity: quantity * 100,
+                            extended_price: quantity * retail_price(partkey),
+                            discount: rng.gen_range(0..=10),
+                            tax: rng.gen_range(0..=8),
+                            return_flag,
+                            line_status: if ship_date > CURRENT_DATE { "O" } else { "F" },
+                            ship_date,
+                            commit_date: date + rng.gen_range(30..=90),
+                            receipt_date,
+                            instruction: choose(&mut rng, &INSTRUCTIONS),
+                            mode: choose(&mut rng, &SHIP_MODES),
+                            comment: text(&mut rng, 10, 43),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let status = if lines.iter().all(|l| l.line_status == "F") {
+                    "F"
+                } else if lines.iter().all(|l| l.line_status == "O") {
+                    "O"
+                } else {
+                    "P"
+                };
+                // sum(extendedprice * (1 + tax) * (1 - discount)), in cents
+                let total_price = lines
+                    .iter()
+                    .map(|l| {
+                        l.extended_price * (100 + l.tax) * (100 - l.discount) / 10_000
+                    })
+                    .sum();
+                let mut comment = text(&mut rng, 19, 78);
+                // like dbgen, a few orders have special requests, which query 13 excludes
+                if rng.gen_ratio(1, 100) {
+                    comment = format!("{} special requests", comment);
+                }
+
+                Order {
+                    key,
+                    custkey,
+                    status,
+                    total_price,
+                    date,
+                    priority: choose(&mut rng, &PRIORITIES),
+                    clerk: format!("Clerk#{:09}", rng.gen_range(1..=clerks)),
+                    comment,
+                    lines,
+                }
+            })
+            .collect()
+    }
+
+    fn orders_columns(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let orders = self.orders(range);
+        Ok(vec![
+            Arc::new(Int64Array::from_iter_values(orders.iter().map(|o| o.key))),
+            Arc::new(Int64Array::from_iter_values(
+                orders.iter().map(|o| o.custkey),
+            )),
+            Arc::new(
+                orders
+                    .iter()
+                    .map(|o| Some(o.status))
+                    .collect::<StringArray>(),
+            ),
+            decimal(orders.iter().map(|o| o.total_price).collect())?,
+            Arc::new(Date32Array::from(
+                orders.iter().map(|o| o.date).collect::<Vec<_>>(),
+            )),
+            Arc::new(
+                orders
+                    .iter()
+                    .map(|o| Some(o.priority))
+                    .collect::<StringArray>(),
+            ),
+            Arc::new(
+                orders
+                    .iter()
+                    .map(|o| Some(&o.clerk))
+                    .collect::<StringArray>(),
+            ),
+            Arc::new(Int32Array::from(vec![0; orders.len()])),
+            Arc::new(
+                orders
+                    .iter()
+                    .map(|o| Some(&o.comment))
+                    .collect::<StringArray>(),
+            ),
+        ])
+    }
+
+    fn lineitem_columns(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let orders = self.orders(range);
+        let lines = orders
+            .iter()
+            .flat_map(|o| o.lines.iter().enumerate().map(move |(n, l)| (o.key, n, l)))
+            .collect::<Vec<_>>();
+        let strings = |f: fn(&LineItem) -> &str| -> ArrayRef {
+            Arc::new(
+                lines
+                    .iter()
+                    .map(|(_, _, l)| Some(f(l)))
+                    .collect::<StringArray>(),
+            )
+        };
+        let dates = |f: fn(&LineItem) -> i32| -> ArrayRef {
+            Arc::new(Date32Array::from(
+                lines.iter().map(|(_, _, l)| f(l)).collect::<Vec<_>>(),
+            ))
+        };
+        let decimals = |f: fn(&LineItem) -> i128| -> Result<ArrayRef> {
+            decimal(lines.iter().map(|(_, _, l)| f(l)).collect())
+        };
+        Ok(vec![
+            Arc::new(Int64Array::from_iter_values(
+                lines.iter().map(|(k, _, _)| *k),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                lines.iter().map(|(_, _, l)| l.partkey),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                lines.iter().map(|(_, _, l)| l.suppkey),
+            )),
+            Arc::new(Int32Array::from_iter_values(
+                lines.iter().map(|(_, n, _)| *n as i32 + 1),
+            )),
+            decimals(|l| l.quantity)?,
+            decimals(|l| l.extended_price)?,
+            decimals(|l| l.discount)?,
+            decimals(|l| l.tax)?,
+            strings(|l| l.return_flag),
+            strings(|l| l.line_status),
+            dates(|l| l.ship_date),
+            dates(|l| l.commit_date),
+            dates(|l| l.receipt_date),
+            strings(|l| l.instruction),
+            strings(|l| l.mode),
+            strings(|l| l.comment.as_str()),
+        ])
+    }
+
+    fn nation(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("nation", &range);
+        let nations = &NATIONS[range];
+        let comments = nations
+            .iter()
+            .map(|_| text(&mut rng, 31, 114))
+            .collect::<Vec<_>>();
+        Ok(vec![
+            Arc::new(Int64Array::from_iter_values(0..nations.len() as i64)),
+            Arc::new(nations.iter().map(|n| Some(n.0)).collect::<StringArray>()),
+            Arc::new(Int64Array::from_iter_values(nations.iter().map(|n| n.1))),
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+
+    fn region(&self, range: Range<usize>) -> Result<Vec<ArrayRef>> {
+        let mut rng = self.rng("region", &range);
+        let regions = &REGIONS[range];
+        let comments = regions
+            .iter()
+            .map(|_| text(&mut rng, 31, 115))
+            .collect::<Vec<_>>();
+        Ok(vec![
+            Arc::new(Int64Array::from_iter_values(0..regions.len() as i64)),
+            Arc::new(regions.iter().map(|r| Some(*r)).collect::<StringArray>()),
+            Arc::new(StringArray::from(comments)),
+        ])
+    }
+}
+
+/// The retail price of a part, in cents, as defined by the specification
+fn retail_price(partkey: i64) -> i128 {
+    (90_000 + (partkey / 10) % 20_001 + 100 * (partkey % 1_000)) as i128
+}
+
+/// The `i`-th of the 4 suppliers of a part, as defined by the specification
+fn part_supplier(partkey: i64, i: i64, suppliers: i64) -> i64 {
+    (partkey + i * (suppliers / 4 + (partkey - 1) / suppliers)) % suppliers + 1
+}
+
+fn decimal(values: Vec<i128>) -> Result<ArrayRef> {
+    Ok(Arc::new(
+        values
+            .into_iter()
+            .map(Some)
+            .collect::<Decimal128Array>()
+            .with_precision_and_scale(15, 2)?,
+    ))
+}
+
+fn choose<'a>(rng: &mut StdRng, values: &[&'a str]) -> &'a str {
+    values.choose(rng).unwrap()
+}
+
+/// A random text of words, of `min_len` to `max_len` characters
+fn text(rng: &mut StdRng, min_len: usize, max_len: usize) -> String {
+    let len = rng.gen_range(min_len..=max_len);
+    let mut text = String::with_capacity(max_len);
+    while text.len() < len {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(choose(rng, &WORDS));
+    }
+    text.truncate(len);
+    text.trim_end().to_owned()
+}
+
+fn address(rng: &mut StdRng) -> String {
+    const CHARS: &[u8] =
+        b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789,. ";
+    (0..rng.gen_range(10..=40))
+        .map(|_| *CHARS.choose(rng).unwrap() as char)
+        .collect()
+}
+
+/// A phone number, whose country code is derived from the nation like in
+/// the specification, as query 22 relies on it
+fn phone(rng: &mut StdRng, nation: i64) -> String {
+    format!(
+        "{}-{}-{}-{}",
+        nation + 10,
+        rng.gen_range(100..=999),
+        rng.gen_range(100..=999),
+        rng.gen_range(1000..=9999)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tpch::run_query;
+    use datafusion::arrow::array::Array;
+    use datafusion::datasource::MemTable;
+    use datafusion::physical_plan::coalesce_batches::concat_batches;
+    use datafusion::prelude::SessionContext;
+    use std::collections::HashMap;
+
+    fn collect(generator: &TpchGenerator, table: &str) -> RecordBatch {
+        let batches = generator
+            .batches(table)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let schema = Arc::new(get_schema(table));
+        let rows = batches.iter().map(|b| b.num_rows()).sum();
+        concat_batches(&schema, &batches, rows).unwrap()
+    }
+
+    #[test]
+    fn generated_tables() {
+        let generator = TpchGenerator::new(0.001).with_batch_size(64);
+        let expected_rows = [
+            ("part", 200),
+            ("supplier", 10),
+            ("partsupp", 800),
+            ("customer", 150),
+            ("orders", 1500),
+            ("nation", 25),
+            ("region", 5),
+        ];
+        for (table, rows) in expected_rows {
+            let batch = collect(&generator, table);
+            assert_eq!(batch.schema().as_ref(), &get_schema(table), "{}", table);
+            assert_eq!(batch.num_rows(), rows, "{}", table);
+        }
+
+        let lineitem = collect(&generator, "lineitem");
+        assert!((1500..=7 * 1500).contains(&lineitem.num_rows()));
+        assert!(generator.batches("unknown").is_err());
+    }
+
+    #[test]
+    fn lineitem_matches_orders() {
+        let generator = TpchGenerator::new(0.001).with_batch_size(100);
+        let orders = collect(&generator, "orders");
+        let lineitem = collect(&generator, "lineitem");
+
+        let order_keys = orders
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        let statuses = orders
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let mut lines_by_order = HashMap::<i64, Vec<&str>>::new();
+        let line_order_keys = lineitem
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        let line_statuses = lineitem
+            .column(9)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        for i in 0..lineitem.num_rows() {
+            lines_by_order
+                .entry(line_order_keys.value(i))
+                .or_default()
+                .push(line_statuses.value(i));
+        }
+
+        assert_eq!(lines_by_order.len(), orders.num_rows());
+        for i in 0..orders.num_rows() {
+            let lines = &lines_by_order[&order_keys.value(i)];
+            let expected = match statuses.value(i) {
+                "F" | "O" => lines.iter().all(|s| *s == statuses.value(i)),
+                _ => lines.contains(&"F") && lines.contains(&"O"),
+            };
+            assert!(expected, "order {}", order_keys.value(i));
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let a = TpchGenerator::new(0.001).with_seed(7);
+        let b = TpchGenerator::new(0.001).with_seed(7);
+        let c = TpchGenerator::new(0.001).with_seed(8);
+        for table in ["customer", "lineitem"] {
+            assert_eq!(collect(&a, table), collect(&b, table));
+            assert_ne!(collect(&a, table), collect(&c, table));
+        }
+
+        let small_batches = TpchGenerator::new(0.001).with_batch_size(10);
+        let batches = small_batches
+            .batches("part")
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(batches.len(), 20);
+        assert_eq!(batches[3].column(8).len(), 10);
+    }
+
+    #[tokio::test]
+    async fn run_all_queries() -> Result<()> {
+        let generator = TpchGenerator::new(0.001);
+        let ctx = SessionContext::new();
+        for table in TABLES {
+            let batches = generator.batches(table)?.collect::<Result<Vec<_>>>()?;
+            let schema = Arc::new(get_schema(table));
+            let provider = MemTable::try_new(schema, vec![batches])?;
+            ctx.register_table(*table, Arc::new(provider))?;
+        }
+
+        for query in 1..=crate::tpch::QUERY_COUNT {
+            let execution = run_query(&ctx, query, query == 1).await?;
+            assert_eq!(execution.plans.is_some(), query == 1);
+        }
+
+        // all the return flags and line statuses are generated
+        let execution = run_query(&ctx, 1, false).await?;
+        assert_eq!(execution.row_count(), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_and_register() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let generator = TpchGenerator::new(0.001);
+        for format in [DataFormat::Csv, DataFormat::Parquet] {
+            let path = dir.path().join(format.extension());
+            generator.write_tables(&path, format)?;
+
+            let ctx = SessionContext::new();
+            crate::tpch::register_tables(
+                &ctx,
+                path.to_str().unwrap(),
+                format.extension(),
+                1,
+                false,
+            )
+            .await?;
+            let count = ctx
+                .sql("select count(*) from region")
+                .await?
+                .collect()
+                .await?;
+            assert_eq!(
+                datafusion::arrow::util::pretty::pretty_format_batches(&count)?
+                    .to_string(),
+                "+-----------------+\n\
+                 | COUNT(UInt8(1)) |\n\
+                 +-----------------+\n\
+                 | 5               |\n\
+                 +-----------------+"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Benchmark derived from TPC-H. This is not an official TPC-H benchmark.
+
+pub mod datagen;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::datasource::file_format::csv::{CsvFormat, DEFAULT_CSV_EXTENSION};
+use datafusion::datasource::file_format::parquet::{
+    ParquetFormat, DEFAULT_PARQUET_EXTENSION,
+};
+use datafusion::datasource::file_format::FileFormat;
+use datafusion::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::prelude::SessionContext;
+use log::info;
+
+use crate::run::{run_sql, QueryExecution};
+
+/// The names of the TPC-H tables
+pub const TABLES: &[&str] = &[
+    "part", "supplier", "partsupp", "customer", "orders", "lineitem", "nation", "region",
+];
+
+/// The number of TPC-H queries
+pub const QUERY_COUNT: usize = 22;
+
+/// The SQL of the TPC-H queries, from `queries/q{n}.sql`
+const QUERIES: [&str; QUERY_COUNT] = [
+    include_str!("../../queries/q1.sql"),
+    include_str!("../../queries/q2.sql"),
+    include_str!("../../queries/q3.sql"),
+    include_str!("../../queries/q4.sql"),
+    include_str!("../../queries/q5.sql"),
+    include_str!("../../queries/q6.sql"),
+    include_str!("../../queries/q7.sql"),
+    include_str!("../../queries/q8.sql"),
+    include_str!("../../queries/q9.sql"),
+    include_str!("../../queries/q10.sql"),
+    include_str!("../../queries/q11.sql"),
+    include_str!("../../queries/q12.sql"),
+    include_str!("../../queries/q13.sql"),
+    include_str!("../../queries/q14.sql"),
+    include_str!("../../queries/q15.sql"),
+    include_str!("../../queries/q16.sql"),
+    include_str!("../../queries/q17.sql"),
+    include_str!("../../queries/q18.sql"),
+    include_str!("../../queries/q19.sql"),
+    include_str!("../../queries/q20.sql"),
+    include_str!("../../queries/q21.sql"),
+    include_str!("../../queries/q22.sql"),
+];
+
+/// Get the SQL statements of the specified query
+pub fn get_query_sql(query: usize) -> Result<Vec<String>> {
+    if query > 0 && query <= QUERY_COUNT {
+        Ok(QUERIES[query - 1]
+            .split(';')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect())
+    } else {
+        Err(DataFusionError::Plan(format!(
+            "invalid query. Expected value between 1 and {}",
+            QUERY_COUNT
+        )))
+    }
+}
+
+/// Registers the TPC-H tables of the directory `path` in `ctx`, optionally
+/// loading them into memory first
+///
+/// See [`get_table`] for the supported formats.
+#[allow(clippy::await_holding_lock)]
+pub async fn register_tables(
+    ctx: &SessionContext,
+    path: &str,
+    table_format: &str,
+    target_partitions: usize,
+    mem_table: bool,
+) -> Result<()> {
+    for table in TABLES {
+        let table_provider = {
+            let mut session_state = ctx.state.write();
+            get_table(
+                &mut session_state,
+                path,
+                table,
+                table_format,
+                target_partitions,
+            )
+            .await?
+        };
+
+        if mem_table {
+            info!("Loading table '{}' into memory", table);
+            let start = Instant::now();
+            let memtable =
+                MemTable::load(table_provider, Some(target_partitions), &ctx.state())
+                    .await?;
+            info!(
+                "Loaded table '{}' into memory in {} ms",
+                table,
+                start.elapsed().as_millis()
+            );
+            ctx.register_table(*table, Arc::new(memtable))?;
+        } else {
+            ctx.register_table(*table, table_provider)?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the statements of the TPC-H query `query` against the tables
+/// registered in `ctx`, returning the results of the query
+pub async fn run_query(
+    ctx: &SessionContext,
+    query: usize,
+    capture_plans: bool,
+) -> Result<QueryExecution> {
+    let sql = get_query_sql(query)?;
+
+    // query 15 is special, with 3 statements. the second statement is the one from which we
+    // want to capture the results
+    let result_index = if query == 15 { 1 } else { sql.len() - 1 };
+    let mut result = None;
+    for (n, statement) in sql.iter().enumerate() {
+        let execution = run_sql(ctx, statement, capture_plans).await?;
+        if n == result_index {
+            result = Some(execution);
+        }
+    }
+    Ok(result.expect("query has statements"))
+}
+
+/// Creates a provider of the TPC-H table `table` from the directory `path`
+///
+/// The format is either `tbl`, for the files generated by `dbgen`, `csv` or
+/// `parquet`, for the directories written by the `convert` command or
+/// [`datagen::TpchGenerator`].
+pub async fn get_table(
+    ctx: &mut SessionState,
+    path: &str,
+    table: &str,
+    table_format: &str,
+    target_partitions: usize,
+) -> Result<Arc<dyn TableProvider>> {
+    let (format, path, extension): (Arc<dyn FileFormat>, String, &'static str) =
+        match table_format {
+            // dbgen creates .tbl ('|' delimited) files without header
+            "tbl" => {
+                let path = format!("{}/{}.tbl", path, table);
+
+                let format = CsvFormat::default()
+                    .with_delimiter(b'|')
+                    .with_has_header(false);
+
+                (Arc::new(format), path, ".tbl")
+            }
+            "csv" => {
+                let path = format!("{}/{}", path, table);
+                let format = CsvFormat::default()
+                    .with_delimiter(b',')
+                    .with_has_header(true);
+
+                (Arc::new(format), path, DEFAULT_CSV_EXTENSION)
+            }
+            "parquet" => {
+                let path = format!("{}/{}", path, table);
+                let format = ParquetFormat::default().with_enable_pruning(true);
+
+                (Arc::new(format), path, DEFAULT_PARQUET_EXTENSION)
+            }
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Invalid file format '{}'",
+                    other
+                )));
+            }
+        };
+    let schema = Arc::new(get_schema(table));
+
+    let options = ListingOptions {
+        format,
+        file_extension: extension.to_owned(),
+        target_partitions,
+        collect_stat: ctx.config.collect_statistics,
+        table_partition_cols: vec![],
+        use_index: false,
+    };
+
+    let table_path = ListingTableUrl::parse(path)?;
+    let config = ListingTableConfig::new(table_path).with_listing_options(options);
+
+    let config = if table_format == "parquet" {
+        config.infer_schema(ctx).await?
+    } else {
+        config.with_schema(schema)
+    };
+
+    Ok(Arc::new(ListingTable::try_new(config)?))
+}
+
+/// The schema of the TPC-H table `table`
+pub fn get_schema(table: &str) -> Schema {
+    // note that the schema intentionally uses signed integers so that any generated Parquet
+    // files can also be used to benchmark tools that only support signed integers, such as
+    // Apache Spark
+
+    match table {
+        "part" => Schema::new(vec![
+            Field::new("p_partkey", DataType::Int64, false),
+            Field::new("p_name", DataType::Utf8, false),
+            Field::new("p_mfgr", DataType::Utf8, false),
+            Field::new("p_brand", DataType::Utf8, false),
+            Field::new("p_type", DataType::Utf8, false),
+            Field::new("p_size", DataType::Int32, false),
+            Field::new("p_container", DataType::Utf8, false),
+            Field::new("p_retailprice", DataType::Decimal128(15, 2), false),
+            Field::new("p_comment", DataType::Utf8, false),
+        ]),
+
+        "supplier" => Schema::new(vec![
+            Field::new("s_suppkey", DataType::Int64, false),
+            Field::new("s_name", DataType::Utf8, false),
+            Field::new("s_address", DataType::Utf8, false),
+            Field::new("s_nationkey", DataType::Int64, false),
+            Field::new("s_phone", DataType::Utf8, false),
+            Field::new("s_acctbal", DataType::Decimal128(15, 2), false),
+            Field::new("s_comment", DataType::Utf8, false),
+        ]),
+
+        "partsupp" => Schema::new(vec![
+            Field::new("ps_partkey", DataType::Int64, false),
+            Field::new("ps_suppkey", DataType::Int64, false),
+            Field::new("ps_availqty", DataType::Int32, false),
+            Field::new("ps_supplycost", DataType::Decimal128(15, 2), false),
+            Field::new("ps_comment", DataType::Utf8, false),
+        ]),
+
+        "customer" => Schema::new(vec![
+            Field::new("c_custkey", DataType::Int64, false),
+            Field::new("c_name", DataType::Utf8, false),
+            Field::new("c_address", DataType::Utf8, false),
+            Field::new("c_nationkey", DataType::Int64, false),
+            Field::new("c_phone", DataType::Utf8, false),
+            Field::new("c_acctbal", DataType::Decimal128(15, 2), false),
+            Field::new("c_mktsegment", DataType::Utf8, false),
+            Field::new("c_comment", DataType::Utf8, false),
+        ]),
+
+        "orders" => Schema::new(vec![
+            Field::new("o_orderkey", DataType::Int64, false),
+            Field::new("o_custkey", DataType::Int64, false),
+            Field::new("o_orderstatus", DataType::Utf8, false),
+            Field::new("o_totalprice", DataType::Decimal128(15, 2), false),
+            Field::new("o_orderdate", DataType::Date32, false),
+            Field::new("o_orderpriority", DataType::Utf8, false),
+            Field::new("o_clerk", DataType::Utf8, false),
+            Field::new("o_shippriority", DataType::Int32, false),
+            Field::new("o_comment", DataType::Utf8, false),
+        ]),
+
+        "lineitem" => Schema::new(vec![
+            Field::new("l_orderkey", DataType::Int64, false),
+            Field::new("l_partkey", DataType::Int64, false),
+            Field::new("l_suppkey", DataType::Int64, false),
+            Field::new("l_linenumber", DataType::Int32, false),
+            Field::new("l_quantity", DataType::Decimal128(15, 2), false),
+            Field::new("l_extendedprice", DataType::Decimal128(15, 2), false),
+            Field::new("l_discount", DataType::Decimal128(15, 2), false),
+            Field::new("l_tax", DataType::Decimal128(15, 2), false),
+            Field::new("l_returnflag", DataType::Utf8, false),
+            Field::new("l_linestatus", DataType::Utf8, false),
+            Field::new("l_shipdate", DataType::Date32, false),
+            Field::new("l_commitdate", DataType::Date32, false),
+            Field::new("l_receiptdate", DataType::Date32, false),
+            Field::new("l_shipinstruct", DataType::Utf8, false),
+            Field::new("l_shipmode", DataType::Utf8, false),
+            Field::new("l_comment", DataType::Utf8, false),
+        ]),
+
+        "nation" => Schema::new(vec![
+            Field::new("n_nationkey", DataType::Int64, false),
+            Field::new("n_name", DataType::Utf8, false),
+            Field::new("n_regionkey", DataType::Int64, false),
+            Field::new("n_comment", DataType::Utf8, false),
+        ]),
+
+        "region" => Schema::new(vec![
+            Field::new("r_regionkey", DataType::Int64, false),
+            Field::new("r_name", DataType::Utf8, false),
+            Field::new("r_comment", DataType::Utf8, false),
+        ]),
+
+        _ => unimplemented!(),
+    }
+}
@@ -0,0 +1,297 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Catalog that resolves file paths and URLs used as table names, so that
+//! `SELECT * FROM 'data.parquet'` works without a `CREATE EXTERNAL TABLE`
+
+use async_trait::async_trait;
+use datafusion::catalog::async_catalog::TableResolver;
+use datafusion::catalog::catalog::{CatalogList, CatalogProvider};
+use datafusion::catalog::schema::SchemaProvider;
+use datafusion::datasource::listing::{
+    ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use datafusion::datasource::TableProvider;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::TablePolicy;
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// The tables read from files, by path
+type FileTables = Arc<Mutex<HashMap<String, Arc<dyn TableProvider>>>>;
+
+/// Wraps a [`CatalogList`] so that table names which are not registered, but
+/// name a file, a directory or an object store URL, are read as listing
+/// tables whose format is inferred from the extension of the listed files.
+///
+/// The tables are created by the [`TableResolver`] of the catalogs, before
+/// the queries using them are planned. They are created once per path, so
+/// the changes of the schema of the files are only seen by a new catalog.
+pub struct DynamicFileCatalog {
+    inner: Arc<dyn CatalogList>,
+    state: Arc<SessionState>,
+    tables: FileTables,
+}
+
+impl DynamicFileCatalog {
+    /// Creates a catalog list delegating to `inner`, using `state` to reach
+    /// the object stores and infer the schema of files
+    pub fn new(inner: Arc<dyn CatalogList>, state: SessionState) -> Self {
+        Self {
+            inner,
+            state: Arc::new(state),
+            tables: Arc::default(),
+        }
+    }
+}
+
+impl CatalogList for DynamicFileCatalog {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn register_catalog(
+        &self,
+        name: String,
+        catalog: Arc<dyn CatalogProvider>,
+    ) -> Option<Arc<dyn CatalogProvider>> {
+        self.inner.register_catalog(name, catalog)
+    }
+
+    fn catalog_names(&self) -> Vec<String> {
+        self.inner.catalog_names()
+    }
+
+    fn catalog(&self, name: &str) -> Option<Arc<dyn CatalogProvider>> {
+        self.inner.catalog(name).map(|catalog| {
+            Arc::new(DynamicFileCatalogProvider {
+                inner: catalog,
+                state: self.state.clone(),
+                tables: self.tables.clone(),
+            }) as Arc<dyn CatalogProvider>
+        })
+    }
+}
+
+struct DynamicFileCatalogProvider {
+    inner: Arc<dyn CatalogProvider>,
+    state: Arc<SessionState>,
+    tables: FileTables,
+}
+
+impl CatalogProvider for DynamicFileCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        self.inner.schema_names()
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        self.inner.schema(name).map(|schema| {
+            Arc::new(DynamicFileSchemaProvider {
+                inner: schema,
+                tables: self.tables.clone(),
+            }) as Arc<dyn SchemaProvider>
+        })
+    }
+
+    fn register_schema(
+        &self,
+        name: &str,
+        schema: Arc<dyn SchemaProvider>,
+    ) -> Result<Option<Arc<dyn SchemaProvider>>> {
+        self.inner.register_schema(name, schema)
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.inner.location()
+    }
+
+    fn table_resolver(&self) -> Option<&dyn TableResolver> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl TableResolver for DynamicFileCatalogProvider {
+    /// Resolves the tables of the wrapped catalog, then reads `table` as a
+    /// listing table if it is an existing local path or a URL
+    async fn resolve_table(&self, schema: &str, table: &str) -> Result<bool> {
+        if let Some(resolver) = self.inner.table_resolver() {
+            if resolver.resolve_table(schema, table).await? {
+                return Ok(true);
+            }
+        }
+        match self.inner.schema(schema) {
+            Some(inner) if !inner.table_exist(table) => {}
+            _ => return Ok(false),
+        }
+        if self.tables.lock().unwrap().contains_key(table)
+            || (!table.contains("://") && !Path::new(table).exists())
+        {
+            return Ok(false);
+        }
+
+        // files that can't be listed or read, or whose format is unknown, are
+        // reported like any other missing table
+        let config = match ListingTableUrl::parse(table) {
+            Ok(table_path) => {
+                ListingTableConfig::new(table_path).infer(&self.state).await
+            }
+            Err(e) => Err(e),
+        };
+        let provider: Arc<dyn TableProvider> =
+            match config.and_then(ListingTable::try_new) {
+                Ok(provider) => Arc::new(provider),
+                Err(_) => return Ok(false),
+            };
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(table.to_string(), provider);
+        Ok(true)
+    }
+}
+
+struct DynamicFileSchemaProvider {
+    inner: Arc<dyn SchemaProvider>,
+    tables: FileTables,
+}
+
+impl SchemaProvider for DynamicFileSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.inner.table_names()
+    }
+
+    fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
+        self.inner
+            .table(name)
+            .or_else(|| self.tables.lock().unwrap().get(name).cloned())
+    }
+
+    fn register_table(
+        &self,
+        name: String,
+        table: Arc<dyn TableProvider>,
+    ) -> Result<Option<Arc<dyn TableProvider>>> {
+        self.inner.register_table(name, table)
+    }
+
+    fn deregister_table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
+        self.inner.deregister_table(name)
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.inner.table_exist(name)
+    }
+
+    fn table_policy(&self, name: &str) -> Option<Arc<dyn TablePolicy>> {
+        self.inner.table_policy(name)
+    }
+
+    fn register_table_policy(
+        &self,
+        name: String,
+        policy: Arc<dyn TablePolicy>,
+    ) -> Result<Option<Arc<dyn TablePolicy>>> {
+        self.inner.register_table_policy(name, policy)
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.inner.location()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    fn dynamic_context() -> SessionContext {
+        let ctx = SessionContext::new();
+        let inner = ctx.state.read().catalog_list.clone();
+        let catalog_list = Arc::new(DynamicFileCatalog::new(inner, ctx.state()));
+        ctx.state.write().catalog_list = catalog_list;
+        ctx
+    }
+
+    #[tokio::test]
+    async fn query_file_by_path() -> Result<()> {
+        let ctx = dynamic_context();
+        let path = format!(
+            "{}/alltypes_plain.parquet",
+            datafusion::test_util::parquet_test_data()
+        );
+
+        let df = ctx.sql(&format!("SELECT id FROM '{}'", path)).await?;
+        let batches = df.collect().await?;
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 8);
+
+        // the file is not registered as a table
+        let schema = ctx.catalog("datafusion").unwrap().schema("public").unwrap();
+        assert!(!schema.table_names().contains(&path));
+
+        // the table of the file is only created once
+        let table = schema.table(&path).unwrap();
+        assert!(Arc::ptr_eq(&table, &schema.table(&path).unwrap()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_directory_by_path() -> Result<()> {
+        let ctx = dynamic_context();
+        let tmp_dir = tempfile::TempDir::new()?;
+        std::fs::write(tmp_dir.path().join("1.csv"), "a,b\n1,2\n3,4\n")?;
+        std::fs::write(tmp_dir.path().join("2.csv"), "a,b\n5,6\n")?;
+        let path = format!("{}/", tmp_dir.path().display());
+
+        // the format is inferred from the files of the directory
+        let df = ctx.sql(&format!("SELECT a FROM '{}'", path)).await?;
+        let batches = df.collect().await?;
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unknown_format_is_not_found() {
+        let ctx = dynamic_context();
+        // an existing file, which is not in a known format
+        let err = ctx.sql("SELECT * FROM 'Cargo.toml'").await.unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_not_found() {
+        let ctx = dynamic_context();
+        let err = ctx
+            .sql("SELECT * FROM 'does/not/exist.parquet'")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+}
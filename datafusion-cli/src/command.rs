@@ -29,6 +29,7 @@ use datafusion::error::{DataFusionError, Result};
 use datafusion::prelude::SessionContext;
 use std::fs::File;
 use std::io::BufReader;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
@@ -39,12 +40,17 @@ pub enum Command {
     Quit,
     Help,
     ListTables,
+    ListBaseTables,
     DescribeTable(String),
     ListFunctions,
+    ListUserFunctions(Option<String>),
     Include(Option<String>),
     SearchFunctions(String),
     QuietMode(Option<bool>),
     OutputFormat(Option<String>),
+    SetFormat(Option<String>),
+    Output(Option<String>),
+    Timing(Option<bool>),
 }
 
 pub enum OutputFormat {
@@ -69,6 +75,20 @@ impl Command {
                     .print_batches(&batches, now)
                     .map_err(|e| DataFusionError::Execution(e.to_string()))
             }
+            Self::ListBaseTables => {
+                let df = ctx
+                    .sql(
+                        "SELECT table_catalog, table_schema, table_name \
+                         FROM information_schema.tables \
+                         WHERE table_type = 'BASE TABLE' \
+                         ORDER BY table_catalog, table_schema, table_name",
+                    )
+                    .await?;
+                let batches = df.collect().await?;
+                print_options
+                    .print_batches(&batches, now)
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))
+            }
             Self::DescribeTable(name) => {
                 let df = ctx.sql(&format!("SHOW COLUMNS FROM {}", name)).await?;
                 let batches = df.collect().await?;
@@ -84,8 +104,7 @@ impl Command {
                             filename, e
                         ))
                     })?;
-                    exec_from_lines(ctx, &mut BufReader::new(file), print_options).await;
-                    Ok(())
+                    exec_from_lines(ctx, &mut BufReader::new(file), print_options).await
                 } else {
                     Err(DataFusionError::Execution(
                         "Required filename argument is missing".into(),
@@ -111,6 +130,12 @@ impl Command {
                 "Unexpected quit, this should be handled outside".into(),
             )),
             Self::ListFunctions => display_all_functions(),
+            Self::ListUserFunctions(pattern) => {
+                let batch = user_functions_info(ctx, pattern.as_deref())?;
+                print_options
+                    .print_batches(&[batch], now)
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))
+            }
             Self::SearchFunctions(function) => {
                 if let Ok(func) = function.parse::<Function>() {
                     let details = func.function_details()?;
@@ -124,6 +149,33 @@ impl Command {
             Self::OutputFormat(_) => Err(DataFusionError::Execution(
                 "Unexpected change output format, this should be handled outside".into(),
             )),
+            Self::SetFormat(Some(format)) => {
+                OutputFormat::ChangeFormat(format.clone())
+                    .execute(print_options)
+                    .await
+            }
+            Self::SetFormat(None) => {
+                println!("Output format is {:?}.", print_options.format);
+                Ok(())
+            }
+            Self::Output(Some(path)) => {
+                print_options.output = Some(PathBuf::from(path));
+                println!("Writing query results to {}", path);
+                Ok(())
+            }
+            Self::Output(None) => {
+                print_options.output = None;
+                println!("Writing query results to stdout");
+                Ok(())
+            }
+            Self::Timing(timing) => {
+                print_options.timing = timing.unwrap_or(!print_options.timing);
+                println!(
+                    "Timing is {}",
+                    if print_options.timing { "on" } else { "off" }
+                );
+                Ok(())
+            }
         }
     }
 
@@ -131,6 +183,7 @@ impl Command {
         match self {
             Self::Quit => ("\\q", "quit datafusion-cli"),
             Self::ListTables => ("\\d", "list tables"),
+            Self::ListBaseTables => ("\\dt", "list base tables"),
             Self::DescribeTable(_) => ("\\d name", "describe table"),
             Self::Help => ("\\?", "help"),
             Self::Include(_) => {
@@ -138,24 +191,44 @@ impl Command {
             }
             Self::ListFunctions => ("\\h", "function list"),
             Self::SearchFunctions(_) => ("\\h function", "search function"),
+            Self::ListUserFunctions(_) => (
+                "\\df [pattern]",
+                "list user defined functions,\noptionally filtered by name",
+            ),
             Self::QuietMode(_) => ("\\quiet (true|false)?", "print or set quiet mode"),
             Self::OutputFormat(_) => {
                 ("\\pset [NAME [VALUE]]", "set table output option\n(format)")
             }
+            Self::SetFormat(_) => (
+                "\\format [csv|tsv|table|json|nd-json|parquet]",
+                "print or set the output format",
+            ),
+            Self::Output(_) => (
+                "\\o [filename]",
+                "write query results to filename,\nor to stdout if omitted",
+            ),
+            Self::Timing(_) => {
+                ("\\timing [on|off]", "toggle or set printing of query times")
+            }
         }
     }
 }
 
-const ALL_COMMANDS: [Command; 9] = [
+const ALL_COMMANDS: [Command; 14] = [
     Command::ListTables,
+    Command::ListBaseTables,
     Command::DescribeTable(String::new()),
     Command::Quit,
     Command::Help,
     Command::Include(Some(String::new())),
     Command::ListFunctions,
     Command::SearchFunctions(String::new()),
+    Command::ListUserFunctions(None),
     Command::QuietMode(None),
     Command::OutputFormat(None),
+    Command::SetFormat(None),
+    Command::Output(None),
+    Command::Timing(None),
 ];
 
 fn all_commands_info() -> RecordBatch {
@@ -177,6 +250,41 @@ fn all_commands_info() -> RecordBatch {
     .expect("This should not fail")
 }
 
+/// Lists the scalar and aggregate UDFs registered with the session,
+/// keeping only those whose name contains `pattern` if one is given
+fn user_functions_info(
+    ctx: &SessionContext,
+    pattern: Option<&str>,
+) -> Result<RecordBatch> {
+    let state = ctx.state.read();
+    let mut functions = state
+        .scalar_functions
+        .keys()
+        .map(|name| (name.as_str(), "scalar"))
+        .chain(
+            state
+                .aggregate_functions
+                .keys()
+                .map(|name| (name.as_str(), "aggregate")),
+        )
+        .filter(|(name, _)| pattern.map(|p| name.contains(p)).unwrap_or(true))
+        .collect::<Vec<_>>();
+    functions.sort_unstable();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("function_name", DataType::Utf8, false),
+        Field::new("function_type", DataType::Utf8, false),
+    ]));
+    let (names, types): (Vec<&str>, Vec<&str>) = functions.into_iter().unzip();
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(types)),
+        ],
+    )?)
+}
+
 impl FromStr for Command {
     type Err = ();
 
@@ -189,10 +297,12 @@ impl FromStr for Command {
         Ok(match (c, arg) {
             ("q", None) => Self::Quit,
             ("d", None) => Self::ListTables,
+            ("dt", None) => Self::ListBaseTables,
             ("d", Some(name)) => Self::DescribeTable(name.into()),
             ("?", None) => Self::Help,
             ("h", None) => Self::ListFunctions,
             ("h", Some(function)) => Self::SearchFunctions(function.into()),
+            ("df", pattern) => Self::ListUserFunctions(pattern.map(|p| p.to_string())),
             ("i", None) => Self::Include(None),
             ("i", Some(filename)) => Self::Include(Some(filename.to_owned())),
             ("quiet", Some("true" | "t" | "yes" | "y" | "on")) => {
@@ -206,6 +316,15 @@ impl FromStr for Command {
                 Self::OutputFormat(Some(subcommand.to_string()))
             }
             ("pset", None) => Self::OutputFormat(None),
+            ("format", format) => Self::SetFormat(format.map(|f| f.to_string())),
+            ("o", path) => Self::Output(path.map(|p| p.to_string())),
+            ("timing", Some("true" | "t" | "yes" | "y" | "on")) => {
+                Self::Timing(Some(true))
+            }
+            ("timing", Some("false" | "f" | "no" | "n" | "off")) => {
+                Self::Timing(Some(false))
+            }
+            ("timing", None) => Self::Timing(None),
             _ => return Err(()),
         })
     }
@@ -246,3 +365,24 @@ impl OutputFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_catalog_commands() {
+        assert!(matches!("dt".parse(), Ok(Command::ListBaseTables)));
+        assert!(matches!("df".parse(), Ok(Command::ListUserFunctions(None))));
+        assert!(matches!(
+            "df my".parse(),
+            Ok(Command::ListUserFunctions(Some(p))) if p == "my"
+        ));
+        assert!(matches!("timing".parse(), Ok(Command::Timing(None))));
+        assert!(matches!(
+            "timing off".parse(),
+            Ok(Command::Timing(Some(false)))
+        ));
+        assert!("timing maybe".parse::<Command>().is_err());
+    }
+}
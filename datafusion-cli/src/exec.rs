@@ -20,24 +20,31 @@
 use crate::{
     command::{Command, OutputFormat},
     helper::CliHelper,
+    object_storage::register_table_object_store,
     print_options::PrintOptions,
 };
-use datafusion::error::Result;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::LogicalPlan;
 use datafusion::prelude::SessionContext;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
+use std::path::PathBuf;
 use std::time::Instant;
 
 /// run and execute SQL statements and commands from a file, against a context with the given print options
+///
+/// Every statement is run even if an earlier one fails, but an error is returned
+/// afterwards if any of them did, so that scripts exit with a non-zero status.
 pub async fn exec_from_lines(
     ctx: &mut SessionContext,
     reader: &mut BufReader<File>,
     print_options: &PrintOptions,
-) {
+) -> Result<()> {
     let mut query = "".to_owned();
+    let mut failures = 0;
 
     for line in reader.lines() {
         match line {
@@ -48,9 +55,9 @@ pub async fn exec_from_lines(
                 let line = line.trim_end();
                 query.push_str(line);
                 if line.ends_with(';') {
-                    match exec_and_print(ctx, print_options, query).await {
-                        Ok(_) => {}
-                        Err(err) => println!("{:?}", err),
+                    if let Err(err) = exec_and_print(ctx, print_options, query).await {
+                        eprintln!("{:?}", err);
+                        failures += 1;
                     }
                     query = "".to_owned();
                 } else {
@@ -64,26 +71,70 @@ pub async fn exec_from_lines(
     }
 
     // run the left over query if the last statement doesn't contain ‘;’
-    if !query.is_empty() {
-        match exec_and_print(ctx, print_options, query).await {
-            Ok(_) => {}
-            Err(err) => println!("{:?}", err),
+    if !query.trim().is_empty() {
+        if let Err(err) = exec_and_print(ctx, print_options, query).await {
+            eprintln!("{:?}", err);
+            failures += 1;
         }
     }
+
+    statements_failed(failures)
 }
 
+/// run and execute SQL statements and commands from files, failing if any statement fails
 pub async fn exec_from_files(
     files: Vec<String>,
     ctx: &mut SessionContext,
     print_options: &PrintOptions,
-) {
+) -> Result<()> {
     let files = files
         .into_iter()
         .map(|file_path| File::open(file_path).unwrap())
         .collect::<Vec<_>>();
+    let mut result = Ok(());
     for file in files {
         let mut reader = BufReader::new(file);
-        exec_from_lines(ctx, &mut reader, print_options).await;
+        if let Err(err) = exec_from_lines(ctx, &mut reader, print_options).await {
+            result = Err(err);
+        }
+    }
+    result
+}
+
+/// run and execute the given SQL statements, as passed with `--command`,
+/// failing if any of them fails
+pub async fn exec_from_commands(
+    ctx: &mut SessionContext,
+    commands: Vec<String>,
+    print_options: &PrintOptions,
+) -> Result<()> {
+    let mut failures = 0;
+    for sql in commands {
+        if let Err(err) = exec_and_print(ctx, print_options, sql).await {
+            eprintln!("{:?}", err);
+            failures += 1;
+        }
+    }
+    statements_failed(failures)
+}
+
+fn statements_failed(failures: usize) -> Result<()> {
+    match failures {
+        0 => Ok(()),
+        1 => Err(DataFusionError::Execution("1 statement failed".to_string())),
+        n => Err(DataFusionError::Execution(format!(
+            "{} statements failed",
+            n
+        ))),
+    }
+}
+
+/// The file the REPL history is kept in across sessions, `~/.datafusion_history`
+/// or `.history` in the current directory if there is no home directory
+fn history_path() -> PathBuf {
+    match dirs::home_dir() {
+        Some(home) => home.join(".datafusion_history"),
+        None => PathBuf::from(".history"),
     }
 }
 
@@ -94,7 +145,8 @@ pub async fn exec_from_repl(
 ) -> rustyline::Result<()> {
     let mut rl = Editor::<CliHelper>::new()?;
     rl.set_helper(Some(CliHelper::default()));
-    rl.load_history(".history").ok();
+    let history = history_path();
+    rl.load_history(&history).ok();
 
     let mut print_options = print_options.clone();
 
@@ -156,7 +208,7 @@ pub async fn exec_from_repl(
         }
     }
 
-    rl.save_history(".history")
+    rl.save_history(&history)
 }
 
 async fn exec_and_print(
@@ -165,7 +217,11 @@ async fn exec_and_print(
     sql: String,
 ) -> Result<()> {
     let now = Instant::now();
-    let df = ctx.sql(&sql).await?;
+    let mut plan = ctx.create_logical_plan_resolving_tables(&sql).await?;
+    if let LogicalPlan::CreateExternalTable(cmd) = &mut plan {
+        register_table_object_store(ctx, cmd)?;
+    }
+    let df = ctx.execute_logical_plan(plan).await?;
     let results = df.collect().await?;
     print_options.print_batches(&results, now)?;
 
@@ -21,13 +21,14 @@ use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionConfig;
 use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
 use datafusion::prelude::SessionContext;
+use datafusion_cli::catalog::DynamicFileCatalog;
 use datafusion_cli::object_storage::DatafusionCliObjectStoreProvider;
 use datafusion_cli::{
     exec, print_format::PrintFormat, print_options::PrintOptions, DATAFUSION_CLI_VERSION,
 };
 use mimalloc::MiMalloc;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[global_allocator]
@@ -61,19 +62,34 @@ struct Args {
     )]
     file: Vec<String>,
 
+    #[clap(
+        long,
+        multiple_values = true,
+        help = "Execute the given SQL statement(s), then exit",
+        conflicts_with = "file"
+    )]
+    command: Vec<String>,
+
     #[clap(
         short = 'r',
         long,
         multiple_values = true,
         help = "Run the provided files on startup instead of ~/.datafusionrc",
         validator(is_valid_file),
-        conflicts_with = "file"
+        conflicts_with_all = &["file", "command"]
     )]
     rc: Option<Vec<String>>,
 
     #[clap(long, arg_enum, default_value_t = PrintFormat::Table)]
     format: PrintFormat,
 
+    #[clap(
+        short = 'o',
+        long,
+        help = "Write query results to the given file instead of stdout"
+    )]
+    output: Option<String>,
+
     #[clap(
         short,
         long,
@@ -105,10 +121,16 @@ pub async fn main() -> Result<()> {
     let runtime_env = create_runtime_env()?;
     let mut ctx =
         SessionContext::with_config_rt(session_config.clone(), Arc::new(runtime_env));
+    // resolve file paths and URLs used as table names, e.g. `SELECT * FROM 'data.csv'`
+    let catalog_list = ctx.state.read().catalog_list.clone();
+    ctx.state.write().catalog_list =
+        Arc::new(DynamicFileCatalog::new(catalog_list, ctx.state()));
 
     let mut print_options = PrintOptions {
         format: args.format,
         quiet: args.quiet,
+        timing: true,
+        output: args.output.map(PathBuf::from),
     };
 
     let files = args.file;
@@ -127,12 +149,16 @@ pub async fn main() -> Result<()> {
         }
     };
 
-    if !files.is_empty() {
-        exec::exec_from_files(files, &mut ctx, &print_options).await;
-        Ok(())
+    if !args.command.is_empty() {
+        exec::exec_from_commands(&mut ctx, args.command, &print_options).await
+    } else if !files.is_empty() {
+        exec::exec_from_files(files, &mut ctx, &print_options).await
     } else {
         if !rc.is_empty() {
-            exec::exec_from_files(rc, &mut ctx, &print_options).await
+            // errors in the rc files are reported but should not prevent the REPL from starting
+            exec::exec_from_files(rc, &mut ctx, &print_options)
+                .await
+                .ok();
         }
         // TODO maybe we can have thiserror for cli but for now let's keep it simple
         exec::exec_from_repl(&mut ctx, &mut print_options)
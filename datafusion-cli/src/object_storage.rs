@@ -16,16 +16,22 @@
 // under the License.
 
 use datafusion::error::Result;
-use std::{env, str::FromStr, sync::Arc};
+use datafusion::logical_expr::{is_secret_option, CreateExternalTable};
+use datafusion::prelude::SessionContext;
+use std::{collections::HashMap, env, fs, path::PathBuf, str::FromStr, sync::Arc};
 
 use datafusion::{datasource::object_store::ObjectStoreProvider, error::DataFusionError};
-use object_store::{aws::AmazonS3Builder, gcp::GoogleCloudStorageBuilder};
+use object_store::{
+    aws::AmazonS3Builder, azure::MicrosoftAzureBuilder, gcp::GoogleCloudStorageBuilder,
+    http::HttpBuilder, ClientOptions,
+};
 use url::Url;
 
 #[derive(Debug, PartialEq, Eq, clap::ArgEnum, Clone)]
 pub enum ObjectStoreScheme {
     S3,
     GCS,
+    Azure,
 }
 
 impl FromStr for ObjectStoreScheme {
@@ -35,6 +41,7 @@ impl FromStr for ObjectStoreScheme {
         match input {
             "s3" => Ok(ObjectStoreScheme::S3),
             "gcs" => Ok(ObjectStoreScheme::GCS),
+            "az" => Ok(ObjectStoreScheme::Azure),
             _ => Err(DataFusionError::Execution(format!(
                 "Unsupported object store scheme {}",
                 input
@@ -46,37 +53,298 @@ impl FromStr for ObjectStoreScheme {
 #[derive(Debug)]
 pub struct DatafusionCliObjectStoreProvider {}
 
-/// ObjectStoreProvider for S3 and GCS
+/// ObjectStoreProvider for S3, GCS and Azure, configured from the environment
 impl ObjectStoreProvider for DatafusionCliObjectStoreProvider {
     fn get_by_url(&self, url: &Url) -> Result<Arc<dyn object_store::ObjectStore>> {
-        ObjectStoreScheme::from_str(url.scheme()).map(|scheme| match scheme {
-            ObjectStoreScheme::S3 => build_s3_object_store(url),
-            ObjectStoreScheme::GCS => build_gcs_object_store(url),
-        })?
+        build_object_store(url, &HashMap::new())
     }
 }
 
-fn build_s3_object_store(url: &Url) -> Result<Arc<dyn object_store::ObjectStore>> {
+/// Registers an object store for the location of a `CREATE EXTERNAL TABLE`
+/// statement that carries `OPTIONS`, e.g. credentials or a custom endpoint,
+/// and removes the credentials from the options of the statement, so that
+/// they only live in the registered store.
+///
+/// Tables without options, or on local paths, are left to the
+/// [`DatafusionCliObjectStoreProvider`].
+pub fn register_table_object_store(
+    ctx: &SessionContext,
+    cmd: &mut CreateExternalTable,
+) -> Result<()> {
+    if cmd.options.is_empty() {
+        return Ok(());
+    }
+    let url = match Url::parse(&cmd.location) {
+        Ok(url) if ObjectStoreScheme::from_str(url.scheme()).is_ok() => url,
+        _ => return Ok(()),
+    };
+    let store = build_object_store(&url, &cmd.options)?;
+    ctx.runtime_env()
+        .register_object_store(url.scheme(), get_host_name(&url)?, store);
+    cmd.options.retain(|key, _| !is_secret_option(key));
+    Ok(())
+}
+
+fn build_object_store(
+    url: &Url,
+    options: &HashMap<String, String>,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
+    ObjectStoreScheme::from_str(url.scheme()).map(|scheme| match scheme {
+        ObjectStoreScheme::S3 => build_s3_object_store(url, options),
+        ObjectStoreScheme::GCS => build_gcs_object_store(url, options),
+        ObjectStoreScheme::Azure => build_azure_object_store(url, options),
+    })?
+}
+
+fn build_s3_object_store(
+    url: &Url,
+    options: &HashMap<String, String>,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
     let host = get_host_name(url)?;
-    match AmazonS3Builder::from_env().with_bucket_name(host).build() {
+    if is_anonymous(options)? {
+        return build_anonymous_s3_object_store(host, options);
+    }
+    let mut builder = AmazonS3Builder::from_env().with_bucket_name(host);
+
+    // the credentials of a profile of the AWS config files, overridden by the
+    // options given explicitly. A profile only named by `AWS_PROFILE` may
+    // be missing from the files, e.g. when it is resolved by other tools.
+    let profile = match (options.get("profile"), env::var("AWS_PROFILE")) {
+        (Some(profile), _) => read_aws_profile(profile)?,
+        (None, Ok(profile)) => read_aws_profile(&profile).unwrap_or_default(),
+        (None, Err(_)) => HashMap::new(),
+    };
+    for (key, value) in profile {
+        builder = match key.as_str() {
+            "aws_access_key_id" => builder.with_access_key_id(value),
+            "aws_secret_access_key" => builder.with_secret_access_key(value),
+            "aws_session_token" => builder.with_token(value),
+            "region" => builder.with_region(value),
+            _ => builder,
+        };
+    }
+
+    for (key, value) in options {
+        builder = match key.as_str() {
+            "access_key_id" => builder.with_access_key_id(value),
+            "secret_access_key" => builder.with_secret_access_key(value),
+            "session_token" => builder.with_token(value),
+            "region" => builder.with_region(value),
+            "endpoint" => builder.with_endpoint(value),
+            "allow_http" => builder.with_allow_http(parse_bool_option(key, value)?),
+            "profile" | "anonymous" => builder,
+            _ => return Err(unknown_option("s3", key)),
+        };
+    }
+    match builder.build() {
         Ok(s3) => Ok(Arc::new(s3)),
         Err(err) => Err(DataFusionError::Execution(err.to_string())),
     }
 }
 
-fn build_gcs_object_store(url: &Url) -> Result<Arc<dyn object_store::ObjectStore>> {
+/// Builds a store that reads a public S3 bucket with unsigned requests.
+///
+/// The requests are plain HTTP requests, so the objects can be read but not
+/// listed: the tables must point at files rather than directories.
+fn build_anonymous_s3_object_store(
+    bucket: &str,
+    options: &HashMap<String, String>,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
+    let mut region = env::var("AWS_DEFAULT_REGION")
+        .or_else(|_| env::var("AWS_REGION"))
+        .ok();
+    let mut endpoint = None;
+    let mut allow_http = false;
+    for (key, value) in options {
+        match key.as_str() {
+            "region" => region = Some(value.clone()),
+            "endpoint" => endpoint = Some(value.trim_end_matches('/').to_string()),
+            "allow_http" => allow_http = parse_bool_option(key, value)?,
+            "anonymous" => {}
+            "access_key_id" | "secret_access_key" | "session_token" | "profile" => {
+                return Err(anonymous_option("s3", key))
+            }
+            _ => return Err(unknown_option("s3", key)),
+        }
+    }
+    let endpoint = match (endpoint, region) {
+        (Some(endpoint), _) => endpoint,
+        (None, Some(region)) => format!("https://s3.{}.amazonaws.com", region),
+        (None, None) => {
+            return Err(DataFusionError::Execution(
+                "Missing region for anonymous access to s3 object store".to_string(),
+            ))
+        }
+    };
+    build_http_object_store(format!("{}/{}", endpoint, bucket), allow_http)
+}
+
+fn build_http_object_store(
+    url: String,
+    allow_http: bool,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
+    let builder = HttpBuilder::new()
+        .with_url(url)
+        .with_client_options(ClientOptions::new().with_allow_http(allow_http));
+    match builder.build() {
+        Ok(http) => Ok(Arc::new(http)),
+        Err(err) => Err(DataFusionError::Execution(err.to_string())),
+    }
+}
+
+/// Reads the settings of an AWS profile, from the shared credentials file
+/// (`~/.aws/credentials`) and the config file (`~/.aws/config`), whose
+/// locations can be changed with `AWS_SHARED_CREDENTIALS_FILE` and
+/// `AWS_CONFIG_FILE`. The credentials file takes precedence.
+fn read_aws_profile(profile: &str) -> Result<HashMap<String, String>> {
+    let aws_file = |var: &str, name: &str| {
+        env::var(var)
+            .map(PathBuf::from)
+            .ok()
+            .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join(name)))
+            .and_then(|path| fs::read_to_string(path).ok())
+    };
+    let credentials = aws_file("AWS_SHARED_CREDENTIALS_FILE", "credentials");
+    let config = aws_file("AWS_CONFIG_FILE", "config");
+    aws_profile_from_files(profile, credentials.as_deref(), config.as_deref())
+}
+
+fn aws_profile_from_files(
+    profile: &str,
+    credentials: Option<&str>,
+    config: Option<&str>,
+) -> Result<HashMap<String, String>> {
+    // the profiles of the config file, but the default one, are prefixed
+    let config_section = match profile {
+        "default" => "default".to_string(),
+        _ => format!("profile {}", profile),
+    };
+    let from_config = config.and_then(|config| ini_section(config, &config_section));
+    let from_credentials =
+        credentials.and_then(|credentials| ini_section(credentials, profile));
+    if from_config.is_none() && from_credentials.is_none() {
+        return Err(DataFusionError::Execution(format!(
+            "AWS profile '{}' not found in the AWS config files",
+            profile
+        )));
+    }
+    let mut settings = from_config.unwrap_or_default();
+    settings.extend(from_credentials.unwrap_or_default());
+    Ok(settings)
+}
+
+/// The `key = value` pairs of the `[section]` of an INI file, if present
+fn ini_section(contents: &str, section: &str) -> Option<HashMap<String, String>> {
+    let mut settings = None;
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+            if in_section {
+                settings.get_or_insert_with(HashMap::new);
+            }
+        } else if let (true, Some((key, value))) = (in_section, line.split_once('=')) {
+            settings
+                .get_or_insert_with(HashMap::new)
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    settings
+}
+
+fn build_gcs_object_store(
+    url: &Url,
+    options: &HashMap<String, String>,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
     let host = get_host_name(url)?;
+    if is_anonymous(options)? {
+        if let Some(key) = options.keys().find(|key| key.as_str() != "anonymous") {
+            return Err(anonymous_option("gcs", key));
+        }
+        // public buckets are served over the XML API without authentication
+        return build_http_object_store(
+            format!("https://storage.googleapis.com/{}", host),
+            false,
+        );
+    }
     let mut builder = GoogleCloudStorageBuilder::new().with_bucket_name(host);
 
     if let Ok(path) = env::var("GCP_SERVICE_ACCOUNT_PATH") {
         builder = builder.with_service_account_path(path);
     }
+    for (key, value) in options {
+        builder = match key.as_str() {
+            "service_account_path" => builder.with_service_account_path(value),
+            "anonymous" => builder,
+            _ => return Err(unknown_option("gcs", key)),
+        };
+    }
     match builder.build() {
         Ok(gcs) => Ok(Arc::new(gcs)),
         Err(err) => Err(DataFusionError::Execution(err.to_string())),
     }
 }
 
+fn build_azure_object_store(
+    url: &Url,
+    options: &HashMap<String, String>,
+) -> Result<Arc<dyn object_store::ObjectStore>> {
+    let host = get_host_name(url)?;
+    let mut builder = MicrosoftAzureBuilder::new().with_container_name(host);
+
+    if let Ok(account) = env::var("AZURE_STORAGE_ACCOUNT_NAME") {
+        builder = builder.with_account(account);
+    }
+    if let Ok(access_key) = env::var("AZURE_STORAGE_ACCOUNT_KEY") {
+        builder = builder.with_access_key(access_key);
+    }
+    for (key, value) in options {
+        builder = match key.as_str() {
+            "account" => builder.with_account(value),
+            "access_key" => builder.with_access_key(value),
+            "use_emulator" => builder.with_use_emulator(parse_bool_option(key, value)?),
+            _ => return Err(unknown_option("az", key)),
+        };
+    }
+    match builder.build() {
+        Ok(azure) => Ok(Arc::new(azure)),
+        Err(err) => Err(DataFusionError::Execution(err.to_string())),
+    }
+}
+
+fn parse_bool_option(key: &str, value: &str) -> Result<bool> {
+    value.parse().map_err(|_| {
+        DataFusionError::Execution(format!(
+            "Option '{}' expects 'true' or 'false', found '{}'",
+            key, value
+        ))
+    })
+}
+
+fn is_anonymous(options: &HashMap<String, String>) -> Result<bool> {
+    match options.get("anonymous") {
+        Some(value) => parse_bool_option("anonymous", value),
+        None => Ok(false),
+    }
+}
+
+fn anonymous_option(scheme: &str, key: &str) -> DataFusionError {
+    DataFusionError::Execution(format!(
+        "Option '{}' can not be used with anonymous access to {} object store",
+        key, scheme
+    ))
+}
+
+fn unknown_option(scheme: &str, key: &str) -> DataFusionError {
+    DataFusionError::Execution(format!(
+        "Unsupported option '{}' for {} object store",
+        key, scheme
+    ))
+}
+
 fn get_host_name(url: &Url) -> Result<&str> {
     url.host_str().ok_or_else(|| {
         DataFusionError::Execution(format!(
@@ -88,12 +356,15 @@ fn get_host_name(url: &Url) -> Result<&str> {
 
 #[cfg(test)]
 mod tests {
-    use std::{env, str::FromStr};
+    use std::{collections::HashMap, env, str::FromStr};
 
     use datafusion::datasource::object_store::ObjectStoreProvider;
+    use datafusion::error::Result;
+    use datafusion::logical_expr::LogicalPlan;
+    use datafusion::prelude::SessionContext;
     use url::Url;
 
-    use super::DatafusionCliObjectStoreProvider;
+    use super::*;
 
     #[test]
     fn s3_provider_no_host() {
@@ -144,4 +415,135 @@ mod tests {
         assert!(provider.get_by_url(&Url::from_str(s3).unwrap()).is_ok());
         env::remove_var("AWS_REGION");
     }
+
+    #[test]
+    fn s3_options() {
+        let s3 = "s3://bucket_name/path";
+        let options = HashMap::from([
+            ("region".to_string(), "us-east-2".to_string()),
+            ("endpoint".to_string(), "http://localhost:9000".to_string()),
+            ("allow_http".to_string(), "true".to_string()),
+        ]);
+        assert!(build_object_store(&Url::from_str(s3).unwrap(), &options).is_ok());
+
+        let options = HashMap::from([("allow_http".to_string(), "yes".to_string())]);
+        let err = build_object_store(&Url::from_str(s3).unwrap(), &options).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Option 'allow_http' expects 'true' or 'false', found 'yes'"));
+
+        let options = HashMap::from([("bucket".to_string(), "other".to_string())]);
+        let err = build_object_store(&Url::from_str(s3).unwrap(), &options).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported option 'bucket' for s3 object store"));
+    }
+
+    #[test]
+    fn register_store_from_table_options() -> Result<()> {
+        let ctx = SessionContext::new();
+        let sql =
+            "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION 's3://bucket_name/t' \
+                   OPTIONS ('region' 'us-east-2')";
+        let mut plan = ctx.create_logical_plan(sql)?;
+        if let LogicalPlan::CreateExternalTable(cmd) = &mut plan {
+            register_table_object_store(&ctx, cmd)?;
+        } else {
+            panic!("expected a CREATE EXTERNAL TABLE plan");
+        }
+
+        let url = Url::from_str("s3://bucket_name/t").unwrap();
+        assert!(ctx.runtime_env().object_store(&url).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn register_store_strips_secret_options() -> Result<()> {
+        let ctx = SessionContext::new();
+        let sql =
+            "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION 's3://bucket_name/t' \
+                   OPTIONS ('region' 'us-east-2', 'access_key_id' 'id', 'secret_access_key' 'secret')";
+        let mut plan = ctx.create_logical_plan(sql)?;
+        match &mut plan {
+            LogicalPlan::CreateExternalTable(cmd) => {
+                register_table_object_store(&ctx, cmd)?;
+                let expected =
+                    HashMap::from([("region".to_string(), "us-east-2".to_string())]);
+                assert_eq!(cmd.options, expected);
+            }
+            _ => panic!("expected a CREATE EXTERNAL TABLE plan"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn anonymous_options() {
+        let s3 = Url::from_str("s3://bucket_name/path").unwrap();
+        let options = HashMap::from([
+            ("anonymous".to_string(), "true".to_string()),
+            ("region".to_string(), "us-east-2".to_string()),
+        ]);
+        assert!(build_object_store(&s3, &options).is_ok());
+
+        let options = HashMap::from([
+            ("anonymous".to_string(), "true".to_string()),
+            ("region".to_string(), "us-east-2".to_string()),
+            ("secret_access_key".to_string(), "secret".to_string()),
+        ]);
+        let err = build_object_store(&s3, &options).unwrap_err();
+        assert!(err.to_string().contains(
+            "Option 'secret_access_key' can not be used with anonymous access to s3 object store"
+        ));
+
+        let gcs = Url::from_str("gcs://bucket_name/path").unwrap();
+        let options = HashMap::from([("anonymous".to_string(), "true".to_string())]);
+        assert!(build_object_store(&gcs, &options).is_ok());
+    }
+
+    #[test]
+    fn aws_profiles() -> Result<()> {
+        let credentials = "[default]\n\
+            aws_access_key_id = default_id\n\
+            \n\
+            [dev]\n\
+            aws_access_key_id = dev_id\n\
+            aws_secret_access_key = dev_secret\n";
+        let config = "[default]\n\
+            region = us-east-1\n\
+            # comment\n\
+            [profile dev]\n\
+            region = eu-west-1\n\
+            aws_access_key_id = overridden\n\
+            [profile other]\n\
+            region = us-west-2\n";
+
+        let dev = aws_profile_from_files("dev", Some(credentials), Some(config))?;
+        let expected = HashMap::from([
+            ("aws_access_key_id".to_string(), "dev_id".to_string()),
+            (
+                "aws_secret_access_key".to_string(),
+                "dev_secret".to_string(),
+            ),
+            ("region".to_string(), "eu-west-1".to_string()),
+        ]);
+        assert_eq!(dev, expected);
+
+        let default = aws_profile_from_files("default", Some(credentials), Some(config))?;
+        let expected = HashMap::from([
+            ("aws_access_key_id".to_string(), "default_id".to_string()),
+            ("region".to_string(), "us-east-1".to_string()),
+        ]);
+        assert_eq!(default, expected);
+
+        let other = aws_profile_from_files("other", None, Some(config))?;
+        let expected = HashMap::from([("region".to_string(), "us-west-2".to_string())]);
+        assert_eq!(other, expected);
+
+        let err = aws_profile_from_files("missing", Some(credentials), Some(config))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("AWS profile 'missing' not found in the AWS config files"));
+        Ok(())
+    }
 }
@@ -21,6 +21,10 @@ use arrow::json::{ArrayWriter, LineDelimitedWriter};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::pretty;
 use datafusion::error::{DataFusionError, Result};
+use datafusion::parquet::arrow::ArrowWriter;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
 use std::str::FromStr;
 
 /// Allow records to be printed in different formats
@@ -31,6 +35,7 @@ pub enum PrintFormat {
     Table,
     Json,
     NdJson,
+    Parquet,
 }
 
 impl FromStr for PrintFormat {
@@ -73,16 +78,56 @@ impl PrintFormat {
     /// print the batches to stdout using the specified format
     pub fn print_batches(&self, batches: &[RecordBatch]) -> Result<()> {
         match self {
-            Self::Csv => println!("{}", print_batches_with_sep(batches, b',')?),
-            Self::Tsv => println!("{}", print_batches_with_sep(batches, b'\t')?),
             Self::Table => pretty::print_batches(batches)?,
-            Self::Json => println!("{}", batches_to_json!(ArrayWriter, batches)),
-            Self::NdJson => {
-                println!("{}", batches_to_json!(LineDelimitedWriter, batches))
+            Self::Parquet => {
+                return Err(DataFusionError::Execution(
+                    "Parquet output can only be written to a file, set one with \\o path"
+                        .to_string(),
+                ))
             }
+            _ => println!("{}", self.format_batches(batches)?),
         }
         Ok(())
     }
+
+    /// write the batches to the file at `path` using the specified format.
+    /// Text formats are appended to the file, parquet replaces its content.
+    pub fn write_batches(&self, batches: &[RecordBatch], path: &Path) -> Result<()> {
+        match self {
+            Self::Parquet => {
+                let schema = match batches.first() {
+                    Some(batch) => batch.schema(),
+                    None => return Ok(()),
+                };
+                let mut writer = ArrowWriter::try_new(File::create(path)?, schema, None)?;
+                for batch in batches {
+                    writer.write(batch)?;
+                }
+                writer.close()?;
+            }
+            _ => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", self.format_batches(batches)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// format the batches as text using the specified format
+    fn format_batches(&self, batches: &[RecordBatch]) -> Result<String> {
+        Ok(match self {
+            Self::Csv => print_batches_with_sep(batches, b',')?,
+            Self::Tsv => print_batches_with_sep(batches, b'\t')?,
+            Self::Table => pretty::pretty_format_batches(batches)?.to_string(),
+            Self::Json => batches_to_json!(ArrayWriter, batches),
+            Self::NdJson => batches_to_json!(LineDelimitedWriter, batches),
+            Self::Parquet => {
+                return Err(DataFusionError::Execution(
+                    "Parquet is not a text format".to_string(),
+                ))
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +137,7 @@ mod tests {
     use arrow::datatypes::{DataType, Field, Schema};
     use datafusion::from_slice::FromSlice;
     use std::sync::Arc;
+    use tempfile::TempDir;
 
     #[test]
     fn test_print_batches_with_sep() {
@@ -152,4 +198,27 @@ mod tests {
         assert_eq!("{\"a\":1,\"b\":4,\"c\":7}\n{\"a\":2,\"b\":5,\"c\":8}\n{\"a\":3,\"b\":6,\"c\":9}\n", r);
         Ok(())
     }
+
+    #[test]
+    fn test_write_batches_appends_text() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int32Array::from_slice(&[1, 2]))],
+        )?;
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("write_batches.csv");
+
+        PrintFormat::Csv.write_batches(&[batch.clone()], &path)?;
+        PrintFormat::Csv.write_batches(&[batch], &path)?;
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!("a\n1\n2\n\na\n1\n2\n\n", content);
+        Ok(())
+    }
+
+    #[test]
+    fn test_print_parquet_requires_file() {
+        let err = PrintFormat::Parquet.print_batches(&[]).unwrap_err();
+        assert!(err.to_string().contains("\\o path"));
+    }
 }
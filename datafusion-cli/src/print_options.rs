@@ -18,12 +18,17 @@
 use crate::print_format::PrintFormat;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::error::Result;
+use std::path::PathBuf;
 use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub struct PrintOptions {
     pub format: PrintFormat,
     pub quiet: bool,
+    /// whether row counts and query times are printed after each result
+    pub timing: bool,
+    /// file that results are written to instead of stdout, if any
+    pub output: Option<PathBuf>,
 }
 
 fn print_timing_info(row_count: usize, now: Instant) {
@@ -38,13 +43,17 @@ fn print_timing_info(row_count: usize, now: Instant) {
 impl PrintOptions {
     /// print the batches to stdout using the specified format
     pub fn print_batches(&self, batches: &[RecordBatch], now: Instant) -> Result<()> {
+        let print_timing = !self.quiet && self.timing;
         if batches.is_empty() {
-            if !self.quiet {
+            if print_timing {
                 print_timing_info(0, now);
             }
         } else {
-            self.format.print_batches(batches)?;
-            if !self.quiet {
+            match &self.output {
+                Some(path) => self.format.write_batches(batches, path)?,
+                None => self.format.print_batches(batches)?,
+            }
+            if print_timing {
                 let row_count: usize = batches.iter().map(|b| b.num_rows()).sum();
                 print_timing_info(row_count, now);
             }
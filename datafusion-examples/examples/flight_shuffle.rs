@@ -0,0 +1,68 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This example runs a query through the shuffle transport of
+//! [`datafusion_proto::shuffle`]: the stages of the plan write their outputs
+//! to a Flight `DoExchange` service, and the final stage reads them back.
+//!
+//! A real cluster runs the service and the map tasks on different nodes;
+//! they all run in this process here.
+
+use std::net::SocketAddr;
+
+use datafusion::arrow::util::pretty::print_batches;
+use datafusion::error::Result;
+use datafusion::physical_plan::{collect, displayable};
+use datafusion::prelude::*;
+use datafusion_proto::shuffle::{run_stages, ShuffleClient, ShuffleService};
+use tonic::transport::Server;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let addr: SocketAddr = "127.0.0.1:50052".parse().unwrap();
+    // the client retries until the service is listening
+    tokio::spawn(
+        Server::builder()
+            .add_service(ShuffleService::default().into_server())
+            .serve(addr),
+    );
+    let client = ShuffleClient::new(format!("http://{}", addr));
+
+    let ctx = SessionContext::with_config(SessionConfig::new().with_target_partitions(4));
+    let testdata = datafusion::test_util::parquet_test_data();
+    ctx.register_parquet(
+        "alltypes_plain",
+        &format!("{}/alltypes_plain.parquet", testdata),
+        ParquetReadOptions::default(),
+    )
+    .await?;
+
+    let df = ctx
+        .sql(
+            "SELECT bool_col, count(*), sum(id) FROM alltypes_plain \
+             GROUP BY bool_col ORDER BY bool_col",
+        )
+        .await?;
+    let plan = df.create_physical_plan().await?;
+
+    let plan = run_stages(plan, &client, "job-1", ctx.task_ctx()).await?;
+    println!("Final stage:\n{}", displayable(plan.as_ref()).indent());
+
+    let results = collect(plan, ctx.task_ctx()).await?;
+    print_batches(&results)?;
+    Ok(())
+}
@@ -0,0 +1,201 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Constraints, such as primary keys, declared on the columns of a table
+
+use arrow::datatypes::Schema;
+
+use crate::{DataFusionError, Result};
+
+/// A constraint declared on columns of a table, which are identified by
+/// their indices in the table schema.
+///
+/// Key constraints are not enforced: they are assumed to hold by the
+/// optimizer. [`Constraint::Check`] constraints, and the columns of the
+/// primary key not being null, are enforced when rows are inserted into a
+/// table through the session.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// The columns are not null and no two rows have the same values for them
+    PrimaryKey(Vec<usize>),
+    /// No two rows have the same, non null, values for the columns
+    Unique(Vec<usize>),
+    /// Every row whose columns are not null has the same values as a row of
+    /// another table for `referenced_columns`
+    ForeignKey {
+        /// Indices of the referencing columns
+        columns: Vec<usize>,
+        /// Name of the referenced table
+        referenced_table: String,
+        /// Names of the referenced columns, in the order of `columns`
+        referenced_columns: Vec<String>,
+    },
+    /// Every row for which the SQL boolean expression `expr`, on the columns
+    /// of the table, is not false
+    Check {
+        /// Name of the constraint, if declared with `CONSTRAINT <name>`
+        name: Option<String>,
+        /// SQL text of the expression
+        expr: String,
+    },
+}
+
+/// The [`Constraint`]s declared on a table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Constraints {
+    inner: Vec<Constraint>,
+}
+
+impl Constraints {
+    /// Creates constraints from a list of [`Constraint`]s
+    pub fn new(constraints: Vec<Constraint>) -> Self {
+        Self { inner: constraints }
+    }
+
+    /// Creates an empty set of constraints
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if there are no constraints
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the constraints
+    pub fn iter(&self) -> impl Iterator<Item = &Constraint> {
+        self.inner.iter()
+    }
+
+    /// Returns the indices of the columns of the primary key, which can not
+    /// be null
+    pub fn primary_key_columns(&self) -> Vec<usize> {
+        self.inner
+            .iter()
+            .flat_map(|constraint| match constraint {
+                Constraint::PrimaryKey(columns) => columns.clone(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// Checks that the columns of the primary key are columns of `schema`
+    /// that are not nullable
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        for index in self.primary_key_columns() {
+            let field = schema.fields().get(index).ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "Primary key column {} is out of bounds",
+                    index
+                ))
+            })?;
+            if field.is_nullable() {
+                return Err(DataFusionError::Plan(format!(
+                    "Primary key column {} can not be nullable",
+                    field.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the names and SQL expressions of the check constraints
+    pub fn checks(&self) -> impl Iterator<Item = (Option<&str>, &str)> {
+        self.inner.iter().filter_map(|constraint| match constraint {
+            Constraint::Check { name, expr } => Some((name.as_deref(), expr.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Returns the sets of columns of a table with `schema` that identify
+    /// its rows: its primary key, and its unique constraints on columns that
+    /// are not nullable
+    pub fn unique_keys(&self, schema: &Schema) -> Vec<Vec<usize>> {
+        self.inner
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::PrimaryKey(columns) => Some(columns.clone()),
+                Constraint::Unique(columns)
+                    if columns.iter().all(|i| {
+                        schema.fields().get(*i).map_or(false, |f| !f.is_nullable())
+                    }) =>
+                {
+                    Some(columns.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field};
+
+    #[test]
+    fn unique_keys() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("email", DataType::Utf8, true),
+            Field::new("login", DataType::Utf8, false),
+        ]);
+        let constraints = Constraints::new(vec![
+            Constraint::PrimaryKey(vec![0]),
+            Constraint::Unique(vec![1]),
+            Constraint::Unique(vec![2]),
+            Constraint::ForeignKey {
+                columns: vec![2],
+                referenced_table: "users".to_string(),
+                referenced_columns: vec!["login".to_string()],
+            },
+            Constraint::Check {
+                name: Some("id_positive".to_string()),
+                expr: "id > 0".to_string(),
+            },
+        ]);
+        // several rows may have a null email
+        assert_eq!(constraints.unique_keys(&schema), vec![vec![0], vec![2]]);
+        assert!(Constraints::empty().unique_keys(&schema).is_empty());
+        assert_eq!(constraints.primary_key_columns(), vec![0]);
+        assert!(constraints.validate(&schema).is_ok());
+        assert_eq!(
+            constraints.checks().collect::<Vec<_>>(),
+            vec![(Some("id_positive"), "id > 0")]
+        );
+    }
+
+    #[test]
+    fn nullable_primary_key() {
+        let schema = Schema::new(vec![Field::new("id", DataType::Int32, true)]);
+        let err = Constraints::new(vec![Constraint::PrimaryKey(vec![0])])
+            .validate(&schema)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Primary key column id can not be nullable"
+        );
+
+        let err = Constraints::new(vec![Constraint::PrimaryKey(vec![1])])
+            .validate(&schema)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Primary key column 1 is out of bounds"
+        );
+    }
+}
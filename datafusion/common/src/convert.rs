@@ -0,0 +1,351 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversions between Rust values and [`ScalarValue`]s, arrays and record
+//! batches.
+//!
+//! ```
+//! use datafusion_common::convert::{array_from_values, values_from_array};
+//!
+//! let array = array_from_values(vec![Some(1i64), None, Some(3)]).unwrap();
+//! let values: Vec<Option<i64>> = values_from_array(&array).unwrap();
+//! assert_eq!(values, vec![Some(1), None, Some(3)]);
+//! ```
+//!
+//! Rows of a record batch can be converted from and to the values of a
+//! struct implementing [`RecordBatchRow`], which the
+//! [`impl_record_batch_row`](crate::impl_record_batch_row) macro implements
+//! for a struct of [`ColumnValue`] fields.
+
+use crate::{DataFusionError, Result, ScalarValue};
+use arrow::array::{new_empty_array, ArrayRef};
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+/// A Rust type holding the values of an Arrow column, such as `i64` for a
+/// non-nullable `Int64` column, or `Option<i64>` for a nullable one
+pub trait ColumnValue: Sized {
+    /// The data type of the column
+    fn data_type() -> DataType;
+
+    /// Returns true if the values may be null
+    fn is_nullable() -> bool;
+
+    /// Converts the value into a scalar of the data type of the column
+    fn into_scalar(self) -> ScalarValue;
+
+    /// Converts a scalar of the data type of the column into a value
+    fn try_from_scalar(scalar: ScalarValue) -> Result<Self>;
+}
+
+macro_rules! impl_column_value {
+    ($NATIVE:ty, $SCALAR:ident, $DATA_TYPE:expr) => {
+        impl ColumnValue for $NATIVE {
+            fn data_type() -> DataType {
+                $DATA_TYPE
+            }
+
+            fn is_nullable() -> bool {
+                false
+            }
+
+            fn into_scalar(self) -> ScalarValue {
+                ScalarValue::$SCALAR(Some(self))
+            }
+
+            fn try_from_scalar(scalar: ScalarValue) -> Result<Self> {
+                match scalar {
+                    ScalarValue::$SCALAR(Some(value)) => Ok(value),
+                    _ => Err(DataFusionError::Execution(format!(
+                        "Cannot convert {:?} to {}",
+                        scalar,
+                        std::any::type_name::<Self>()
+                    ))),
+                }
+            }
+        }
+
+        impl ColumnValue for Option<$NATIVE> {
+            fn data_type() -> DataType {
+                $DATA_TYPE
+            }
+
+            fn is_nullable() -> bool {
+                true
+            }
+
+            fn into_scalar(self) -> ScalarValue {
+                ScalarValue::$SCALAR(self)
+            }
+
+            fn try_from_scalar(scalar: ScalarValue) -> Result<Self> {
+                match scalar {
+                    ScalarValue::$SCALAR(value) => Ok(value),
+                    _ => Err(DataFusionError::Execution(format!(
+                        "Cannot convert {:?} to {}",
+                        scalar,
+                        std::any::type_name::<Self>()
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+impl_column_value!(bool, Boolean, DataType::Boolean);
+impl_column_value!(i8, Int8, DataType::Int8);
+impl_column_value!(i16, Int16, DataType::Int16);
+impl_column_value!(i32, Int32, DataType::Int32);
+impl_column_value!(i64, Int64, DataType::Int64);
+impl_column_value!(u8, UInt8, DataType::UInt8);
+impl_column_value!(u16, UInt16, DataType::UInt16);
+impl_column_value!(u32, UInt32, DataType::UInt32);
+impl_column_value!(u64, UInt64, DataType::UInt64);
+impl_column_value!(f32, Float32, DataType::Float32);
+impl_column_value!(f64, Float64, DataType::Float64);
+impl_column_value!(String, Utf8, DataType::Utf8);
+impl_column_value!(Vec<u8>, Binary, DataType::Binary);
+
+/// Converts `values` into an array of the data type of `T`
+pub fn array_from_values<T: ColumnValue>(values: Vec<T>) -> Result<ArrayRef> {
+    if values.is_empty() {
+        return Ok(new_empty_array(&T::data_type()));
+    }
+    ScalarValue::iter_to_array(values.into_iter().map(T::into_scalar))
+}
+
+/// Converts the values of `array` into values of `T`. Returns an error if the
+/// array is not of the data type of `T`, or has nulls that `T` cannot hold.
+pub fn values_from_array<T: ColumnValue>(array: &ArrayRef) -> Result<Vec<T>> {
+    if array.data_type() != &T::data_type() {
+        return Err(DataFusionError::Execution(format!(
+            "Cannot convert an array of {} to {}",
+            array.data_type(),
+            std::any::type_name::<T>()
+        )));
+    }
+    (0..array.len())
+        .map(|index| T::try_from_scalar(ScalarValue::try_from_array(array, index)?))
+        .collect()
+}
+
+/// A Rust struct holding the values of a row of a record batch, with a field
+/// per column. It is usually implemented with the
+/// [`impl_record_batch_row`](crate::impl_record_batch_row) macro.
+pub trait RecordBatchRow: Sized {
+    /// The schema of the record batches of the rows
+    fn schema() -> SchemaRef;
+
+    /// Converts the row into the scalars of its columns, in the order of the
+    /// fields of the schema
+    fn into_scalars(self) -> Vec<ScalarValue>;
+
+    /// Converts the scalars of the columns of a row into a row
+    fn try_from_scalars(scalars: Vec<ScalarValue>) -> Result<Self>;
+}
+
+/// Converts `rows` into a record batch
+pub fn record_batch_from_rows<T: RecordBatchRow>(rows: Vec<T>) -> Result<RecordBatch> {
+    let schema = T::schema();
+    let mut columns = vec![Vec::with_capacity(rows.len()); schema.fields().len()];
+    for row in rows {
+        for (column, scalar) in columns.iter_mut().zip(row.into_scalars()) {
+            column.push(scalar);
+        }
+    }
+    let columns = columns
+        .into_iter()
+        .zip(schema.fields())
+        .map(|(scalars, field)| {
+            if scalars.is_empty() {
+                Ok(new_empty_array(field.data_type()))
+            } else {
+                ScalarValue::iter_to_array(scalars)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// Converts the rows of `batch` into values of `T`. Returns an error if the
+/// columns of the batch are not those of the schema of `T`.
+pub fn rows_from_record_batch<T: RecordBatchRow>(batch: &RecordBatch) -> Result<Vec<T>> {
+    let schema = T::schema();
+    let batch_schema = batch.schema();
+    let same_types = schema.fields().len() == batch_schema.fields().len()
+        && schema
+            .fields()
+            .iter()
+            .zip(batch_schema.fields())
+            .all(|(field, batch_field)| field.data_type() == batch_field.data_type());
+    if !same_types {
+        return Err(DataFusionError::Execution(format!(
+            "Cannot convert a record batch of schema {} to {}",
+            batch_schema,
+            std::any::type_name::<T>()
+        )));
+    }
+    (0..batch.num_rows())
+        .map(|index| {
+            let scalars = batch
+                .columns()
+                .iter()
+                .map(|column| ScalarValue::try_from_array(column, index))
+                .collect::<Result<Vec<_>>>()?;
+            T::try_from_scalars(scalars)
+        })
+        .collect()
+}
+
+/// Implements [`RecordBatchRow`](crate::convert::RecordBatchRow) for a struct
+/// whose fields are [`ColumnValue`](crate::convert::ColumnValue)s, with a
+/// column per field named after it.
+///
+/// ```
+/// use datafusion_common::convert::{record_batch_from_rows, rows_from_record_batch};
+/// use datafusion_common::impl_record_batch_row;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Trip {
+///     id: i64,
+///     name: Option<String>,
+/// }
+///
+/// impl_record_batch_row!(Trip { id: i64, name: Option<String> });
+///
+/// let trips = vec![
+///     Trip { id: 1, name: Some("airport".to_string()) },
+///     Trip { id: 2, name: None },
+/// ];
+/// let batch = record_batch_from_rows(trips).unwrap();
+/// assert_eq!(batch.num_rows(), 2);
+///
+/// let trips: Vec<Trip> = rows_from_record_batch(&batch).unwrap();
+/// assert_eq!(trips[1], Trip { id: 2, name: None });
+/// ```
+#[macro_export]
+macro_rules! impl_record_batch_row {
+    ($STRUCT:ident { $($FIELD:ident : $TYPE:ty),* $(,)? }) => {
+        impl $crate::convert::RecordBatchRow for $STRUCT {
+            fn schema() -> $crate::arrow::datatypes::SchemaRef {
+                std::sync::Arc::new($crate::arrow::datatypes::Schema::new(vec![
+                    $($crate::arrow::datatypes::Field::new(
+                        stringify!($FIELD),
+                        <$TYPE as $crate::convert::ColumnValue>::data_type(),
+                        <$TYPE as $crate::convert::ColumnValue>::is_nullable(),
+                    ),)*
+                ]))
+            }
+
+            fn into_scalars(self) -> Vec<$crate::ScalarValue> {
+                vec![
+                    $(<$TYPE as $crate::convert::ColumnValue>::into_scalar(self.$FIELD),)*
+                ]
+            }
+
+            fn try_from_scalars(
+                scalars: Vec<$crate::ScalarValue>,
+            ) -> $crate::Result<Self> {
+                let mut scalars = scalars.into_iter();
+                Ok($STRUCT {
+                    $($FIELD: <$TYPE as $crate::convert::ColumnValue>::try_from_scalar(
+                        scalars.next().ok_or_else(|| {
+                            $crate::DataFusionError::Execution(format!(
+                                "Missing value of {}",
+                                stringify!($FIELD)
+                            ))
+                        })?,
+                    )?,)*
+                })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Measurement {
+        sensor: String,
+        reading: Option<f64>,
+        count: i32,
+    }
+
+    crate::impl_record_batch_row!(Measurement {
+        sensor: String,
+        reading: Option<f64>,
+        count: i32,
+    });
+
+    #[test]
+    fn array_values_roundtrip() -> Result<()> {
+        let values = vec![Some("a".to_string()), None, Some("c".to_string())];
+        let array = array_from_values(values.clone())?;
+        let expected: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("a"), None, Some("c")]));
+        assert_eq!(&array, &expected);
+        assert_eq!(values_from_array::<Option<String>>(&array)?, values);
+
+        // the values must be of the type of the array, and not null
+        assert!(values_from_array::<Option<i64>>(&array).is_err());
+        assert!(values_from_array::<String>(&array).is_err());
+
+        let array = array_from_values(Vec::<u32>::new())?;
+        assert_eq!(array.data_type(), &DataType::UInt32);
+        assert_eq!(array.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn record_batch_rows_roundtrip() -> Result<()> {
+        let rows = vec![
+            Measurement {
+                sensor: "s1".to_string(),
+                reading: Some(1.5),
+                count: 3,
+            },
+            Measurement {
+                sensor: "s2".to_string(),
+                reading: None,
+                count: 0,
+            },
+        ];
+        let batch = record_batch_from_rows(rows.clone())?;
+        assert_eq!(batch.schema(), Measurement::schema());
+        let readings: ArrayRef = Arc::new(Float64Array::from(vec![Some(1.5), None]));
+        assert_eq!(batch.column(1), &readings);
+        let counts: ArrayRef = Arc::new(Int32Array::from(vec![3, 0]));
+        assert_eq!(batch.column(2), &counts);
+        assert_eq!(rows_from_record_batch::<Measurement>(&batch)?, rows);
+
+        let batch = record_batch_from_rows(Vec::<Measurement>::new())?;
+        assert_eq!(batch.num_rows(), 0);
+
+        let other = RecordBatch::try_new(
+            Arc::new(arrow::datatypes::Schema::new(vec![
+                arrow::datatypes::Field::new("count", DataType::Int32, false),
+            ])),
+            vec![Arc::new(Int32Array::from(vec![1]))],
+        )?;
+        assert!(rows_from_record_batch::<Measurement>(&other).is_err());
+        Ok(())
+    }
+}
@@ -85,6 +85,11 @@ pub enum DataFusionError {
     JITError(ModuleError),
     /// Error with additional context
     Context(String, Box<DataFusionError>),
+    /// Error raised while processing data, with a description of where it
+    /// happened, such as the operator, expression, file or rows being
+    /// processed. It displays as the wrapped error: the descriptions are
+    /// returned by [`DataFusionError::runtime_context`].
+    RuntimeContext(String, Box<DataFusionError>),
 }
 
 #[macro_export]
@@ -109,6 +114,68 @@ macro_rules! plan_err {
     };
 }
 
+/// Stable category of a [`DataFusionError`], which programmatic callers can
+/// branch on instead of parsing the error message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// The SQL text could not be parsed
+    Syntax,
+    /// The query is not valid, e.g. an impossible cast or an unknown function
+    Plan,
+    /// A schema-related error, e.g. an unknown or ambiguous column
+    Schema,
+    /// The query uses a feature that is not supported
+    NotImplemented,
+    /// The query ran out of memory or another limited resource
+    ResourcesExhausted,
+    /// The query failed while processing data, e.g. a division by zero
+    Execution,
+    /// Reading or writing files or object stores failed
+    Io,
+    /// An error raised by code outside of DataFusion
+    External,
+    /// An internal invariant of DataFusion was violated
+    Internal,
+}
+
+impl ErrorCode {
+    /// Returns the stable identifier of this error code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Syntax => "SYNTAX",
+            ErrorCode::Plan => "PLAN",
+            ErrorCode::Schema => "SCHEMA",
+            ErrorCode::NotImplemented => "NOT_IMPLEMENTED",
+            ErrorCode::ResourcesExhausted => "RESOURCES_EXHAUSTED",
+            ErrorCode::Execution => "EXECUTION",
+            ErrorCode::Io => "IO",
+            ErrorCode::External => "EXTERNAL",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+
+    /// Returns the SQLSTATE class reported for errors of this category
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            ErrorCode::Syntax => "42601",
+            ErrorCode::Plan => "42000",
+            ErrorCode::Schema => "42000",
+            ErrorCode::NotImplemented => "0A000",
+            ErrorCode::ResourcesExhausted => "53000",
+            ErrorCode::Execution => "22000",
+            ErrorCode::Io => "58030",
+            ErrorCode::External => "58000",
+            ErrorCode::Internal => "XX000",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Schema-related errors
 #[derive(Debug)]
 pub enum SchemaError {
@@ -266,7 +333,10 @@ impl From<GenericError> for DataFusionError {
 impl Display for DataFusionError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match *self {
-            DataFusionError::ArrowError(ref desc) => write!(f, "Arrow error: {}", desc),
+            DataFusionError::ArrowError(ref desc) => {
+                write!(f, "Arrow error: ")?;
+                fmt_arrow_error(desc, f)
+            }
             #[cfg(feature = "parquet")]
             DataFusionError::ParquetError(ref desc) => {
                 write!(f, "Parquet error: {}", desc)
@@ -312,12 +382,144 @@ impl Display for DataFusionError {
             DataFusionError::Context(ref desc, ref err) => {
                 write!(f, "{}\ncaused by\n{}", desc, *err)
             }
+            DataFusionError::RuntimeContext(_, ref err) => write!(f, "{}", *err),
         }
     }
 }
 
+/// Formats `err` as it would be without the runtime contexts it wraps, as
+/// the error the [`DataFusionError`] in them converts to
+fn fmt_arrow_error(err: &ArrowError, f: &mut Formatter) -> std::fmt::Result {
+    let inner = match err {
+        ArrowError::ExternalError(e) => match e.downcast_ref::<DataFusionError>() {
+            Some(DataFusionError::RuntimeContext(_, inner)) => inner.as_ref(),
+            _ => return write!(f, "{}", err),
+        },
+        _ => return write!(f, "{}", err),
+    };
+    match inner {
+        DataFusionError::ArrowError(e) => fmt_arrow_error(e, f),
+        DataFusionError::External(e) => write!(f, "External error: {}", e),
+        other => write!(f, "External error: {}", other),
+    }
+}
+
 impl error::Error for DataFusionError {}
 
+impl DataFusionError {
+    /// Wraps this error in a [`DataFusionError::Context`] describing where it
+    /// happened, such as the operator, expression, file or rows being
+    /// processed
+    pub fn context(self, description: impl Into<String>) -> Self {
+        DataFusionError::Context(description.into(), Box::new(self))
+    }
+
+    /// Wraps this error in a [`DataFusionError::RuntimeContext`] describing
+    /// where it happened, without changing how it displays
+    pub fn with_runtime_context(self, description: impl Into<String>) -> Self {
+        DataFusionError::RuntimeContext(description.into(), Box::new(self))
+    }
+
+    /// Returns the descriptions of the runtime contexts of this error, from
+    /// the outermost to the innermost, including those of the errors passed
+    /// through arrow as external errors
+    pub fn runtime_context(&self) -> Vec<&str> {
+        let mut descriptions = vec![];
+        let mut err = self;
+        loop {
+            err = match err {
+                DataFusionError::RuntimeContext(desc, err) => {
+                    descriptions.push(desc.as_str());
+                    err.as_ref()
+                }
+                DataFusionError::Context(_, err) => err.as_ref(),
+                DataFusionError::ArrowError(ArrowError::ExternalError(err))
+                | DataFusionError::External(err) => {
+                    match err.downcast_ref::<DataFusionError>() {
+                        Some(err) => err,
+                        None => return descriptions,
+                    }
+                }
+                _ => return descriptions,
+            }
+        }
+    }
+
+    /// Returns the innermost error, without the contexts wrapping it,
+    /// including the errors passed through arrow as external errors
+    pub fn find_root(&self) -> &Self {
+        let mut root = self;
+        loop {
+            root = match root {
+                DataFusionError::Context(_, err)
+                | DataFusionError::RuntimeContext(_, err) => err.as_ref(),
+                DataFusionError::ArrowError(ArrowError::ExternalError(err))
+                | DataFusionError::External(err) => {
+                    match err.downcast_ref::<DataFusionError>() {
+                        Some(err) => err,
+                        None => return root,
+                    }
+                }
+                _ => return root,
+            }
+        }
+    }
+}
+
+impl DataFusionError {
+    /// Returns the category of the root cause of this error
+    pub fn code(&self) -> ErrorCode {
+        match self.find_root() {
+            DataFusionError::ArrowError(e) => match e {
+                ArrowError::NotYetImplemented(_) => ErrorCode::NotImplemented,
+                ArrowError::MemoryError(_) => ErrorCode::ResourcesExhausted,
+                ArrowError::IoError(_) => ErrorCode::Io,
+                ArrowError::SchemaError(_) => ErrorCode::Schema,
+                ArrowError::ExternalError(_) => ErrorCode::External,
+                _ => ErrorCode::Execution,
+            },
+            #[cfg(feature = "parquet")]
+            DataFusionError::ParquetError(_) => ErrorCode::Execution,
+            #[cfg(feature = "avro")]
+            DataFusionError::AvroError(_) => ErrorCode::Execution,
+            #[cfg(feature = "object_store")]
+            DataFusionError::ObjectStore(_) => ErrorCode::Io,
+            DataFusionError::IoError(_) => ErrorCode::Io,
+            DataFusionError::SQL(_) => ErrorCode::Syntax,
+            DataFusionError::NotImplemented(_) => ErrorCode::NotImplemented,
+            DataFusionError::Internal(_) => ErrorCode::Internal,
+            DataFusionError::Plan(_) => ErrorCode::Plan,
+            DataFusionError::SchemaError(_) => ErrorCode::Schema,
+            DataFusionError::Execution(_) => ErrorCode::Execution,
+            DataFusionError::ResourcesExhausted(_) => ErrorCode::ResourcesExhausted,
+            DataFusionError::External(_) => ErrorCode::External,
+            #[cfg(feature = "jit")]
+            DataFusionError::JITError(_) => ErrorCode::Internal,
+            // `find_root` never returns a context
+            DataFusionError::Context(_, err)
+            | DataFusionError::RuntimeContext(_, err) => err.code(),
+        }
+    }
+
+    /// Returns the SQLSTATE of this error, for frontends speaking a wire
+    /// protocol that reports it, such as the PostgreSQL protocol
+    pub fn sqlstate(&self) -> &'static str {
+        match self.find_root() {
+            DataFusionError::ArrowError(ArrowError::DivideByZero) => "22012",
+            DataFusionError::ArrowError(
+                ArrowError::CastError(_) | ArrowError::ParseError(_),
+            ) => "22018",
+            DataFusionError::SchemaError(e) => match e {
+                SchemaError::AmbiguousReference { .. } => "42702",
+                SchemaError::DuplicateQualifiedField { .. }
+                | SchemaError::DuplicateUnqualifiedField { .. } => "42701",
+                SchemaError::FieldNotFound { .. } => "42703",
+            },
+            root => root.code().sqlstate(),
+        }
+    }
+}
+
 impl From<DataFusionError> for io::Error {
     fn from(e: DataFusionError) -> Self {
         io::Error::new(io::ErrorKind::Other, e)
@@ -326,8 +528,9 @@ impl From<DataFusionError> for io::Error {
 
 #[cfg(test)]
 mod test {
-    use crate::error::DataFusionError;
+    use crate::error::{DataFusionError, ErrorCode, SchemaError};
     use arrow::error::ArrowError;
+    use sqlparser::parser::ParserError;
 
     #[test]
     fn arrow_error_to_datafusion() {
@@ -344,6 +547,83 @@ mod test {
         assert_eq!(res.to_string(), "Arrow error: Schema error: bar");
     }
 
+    #[test]
+    fn error_context() {
+        let err = DataFusionError::Execution("bad value".to_string())
+            .context("evaluating a@0")
+            .context("reading file a.csv");
+        assert_eq!(
+            err.to_string(),
+            "reading file a.csv\ncaused by\nevaluating a@0\ncaused by\nExecution error: bad value"
+        );
+        assert_eq!(err.find_root().to_string(), "Execution error: bad value");
+
+        // the context is kept through arrow errors
+        let err = DataFusionError::from(ArrowError::from(err));
+        assert_eq!(err.find_root().to_string(), "Execution error: bad value");
+    }
+
+    #[test]
+    fn error_runtime_context() {
+        let err = DataFusionError::ArrowError(ArrowError::DivideByZero)
+            .with_runtime_context("evaluating a@0 / b@1")
+            .with_runtime_context("reading file a.csv");
+        assert_eq!(err.to_string(), "Arrow error: Divide by zero error");
+        assert_eq!(
+            err.runtime_context(),
+            vec!["reading file a.csv", "evaluating a@0 / b@1"]
+        );
+
+        // the errors passed through arrow display as without the contexts
+        let err = DataFusionError::from(ArrowError::from(err));
+        assert_eq!(err.to_string(), "Arrow error: Divide by zero error");
+        assert_eq!(
+            err.runtime_context(),
+            vec!["reading file a.csv", "evaluating a@0 / b@1"]
+        );
+        assert_eq!(err.code(), ErrorCode::Execution);
+
+        let err = DataFusionError::NotImplemented("foo".to_string())
+            .with_runtime_context("evaluating foo()");
+        let err = DataFusionError::from(ArrowError::from(err));
+        assert_eq!(
+            err.to_string(),
+            "Arrow error: External error: This feature is not implemented: foo"
+        );
+        assert_eq!(err.runtime_context(), vec!["evaluating foo()"]);
+    }
+
+    #[test]
+    fn error_code() {
+        let err = DataFusionError::Plan("foo".to_string());
+        assert_eq!(err.code(), ErrorCode::Plan);
+        assert_eq!(err.sqlstate(), "42000");
+
+        // the code of the root cause is reported through contexts and arrow
+        let err = DataFusionError::from(ArrowError::from(
+            DataFusionError::ResourcesExhausted("bar".to_string()).context("sorting"),
+        ));
+        assert_eq!(err.code(), ErrorCode::ResourcesExhausted);
+        assert_eq!(err.code().as_str(), "RESOURCES_EXHAUSTED");
+        assert_eq!(err.sqlstate(), "53000");
+
+        let err = DataFusionError::ArrowError(ArrowError::DivideByZero);
+        assert_eq!(err.code(), ErrorCode::Execution);
+        assert_eq!(err.sqlstate(), "22012");
+
+        let err = DataFusionError::SchemaError(SchemaError::FieldNotFound {
+            qualifier: None,
+            name: "a".to_string(),
+            valid_fields: None,
+        });
+        assert_eq!(err.code(), ErrorCode::Schema);
+        assert_eq!(err.sqlstate(), "42703");
+
+        let err = DataFusionError::SQL(ParserError::ParserError("baz".to_string()));
+        assert_eq!(err.code(), ErrorCode::Syntax);
+        assert_eq!(err.sqlstate(), "42601");
+    }
+
     /// Model what happens when implementing SendableRecrordBatchStream:
     /// DataFusion code needs to return an ArrowError
     #[allow(clippy::try_err)]
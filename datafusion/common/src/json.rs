@@ -0,0 +1,433 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversions between [`ScalarValue`]s or arrays and JSON values.
+//!
+//! Numbers, booleans and strings are converted to the corresponding JSON
+//! values, lists to arrays and structs to objects. Dates, times, timestamps
+//! and intervals are converted to the numbers they are stored as, except
+//! month-day-nano intervals that are converted to strings, as are decimals, so
+//! that no precision is lost. Binary values are converted to arrays of bytes.
+
+use arrow::array::{new_empty_array, ArrayRef};
+use arrow::datatypes::{DataType, Field, IntervalUnit, TimeUnit};
+use serde_json::{Map, Number, Value};
+
+use crate::{DataFusionError, Result, ScalarValue};
+
+impl ScalarValue {
+    /// Converts the scalar into a JSON value. A null scalar is converted to
+    /// [`Value::Null`].
+    pub fn to_json(&self) -> Result<Value> {
+        if self.is_null() {
+            return Ok(Value::Null);
+        }
+        Ok(match self {
+            ScalarValue::Boolean(Some(v)) => Value::Bool(*v),
+            ScalarValue::Int8(Some(v)) => Value::from(*v),
+            ScalarValue::Int16(Some(v)) => Value::from(*v),
+            ScalarValue::Int32(Some(v)) => Value::from(*v),
+            ScalarValue::Int64(Some(v)) => Value::from(*v),
+            ScalarValue::UInt8(Some(v)) => Value::from(*v),
+            ScalarValue::UInt16(Some(v)) => Value::from(*v),
+            ScalarValue::UInt32(Some(v)) => Value::from(*v),
+            ScalarValue::UInt64(Some(v)) => Value::from(*v),
+            ScalarValue::Float32(Some(v)) => float_to_json(*v as f64)?,
+            ScalarValue::Float64(Some(v)) => float_to_json(*v)?,
+            ScalarValue::Decimal128(Some(v), _, scale) => {
+                Value::String(format_decimal(*v, *scale))
+            }
+            ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                Value::String(v.clone())
+            }
+            ScalarValue::Binary(Some(v))
+            | ScalarValue::LargeBinary(Some(v))
+            | ScalarValue::FixedSizeBinary(_, Some(v)) => {
+                Value::Array(v.iter().map(|byte| Value::from(*byte)).collect())
+            }
+            ScalarValue::Date32(Some(v)) | ScalarValue::IntervalYearMonth(Some(v)) => {
+                Value::from(*v)
+            }
+            ScalarValue::Date64(Some(v))
+            | ScalarValue::Time64(Some(v))
+            | ScalarValue::TimestampSecond(Some(v), _)
+            | ScalarValue::TimestampMillisecond(Some(v), _)
+            | ScalarValue::TimestampMicrosecond(Some(v), _)
+            | ScalarValue::TimestampNanosecond(Some(v), _)
+            | ScalarValue::IntervalDayTime(Some(v)) => Value::from(*v),
+            ScalarValue::IntervalMonthDayNano(Some(v)) => Value::String(v.to_string()),
+            ScalarValue::List(Some(values), _) => Value::Array(
+                values
+                    .iter()
+                    .map(|value| value.to_json())
+                    .collect::<Result<_>>()?,
+            ),
+            ScalarValue::Struct(Some(values), fields) => Value::Object(
+                fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| Ok((field.name().clone(), value.to_json()?)))
+                    .collect::<Result<Map<_, _>>>()?,
+            ),
+            ScalarValue::Dictionary(_, value) => value.to_json()?,
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Can't convert {:?} to a JSON value",
+                    other
+                )))
+            }
+        })
+    }
+
+    /// Converts a JSON value into a scalar of type `data_type`, as converted by
+    /// [`ScalarValue::to_json`]. Dates, times, timestamps and decimals may
+    /// also be strings that can be cast to the type, such as `"2022-10-01"`
+    /// for a date.
+    pub fn try_from_json(value: &Value, data_type: &DataType) -> Result<Self> {
+        if value.is_null() {
+            return data_type.try_into();
+        }
+        let mismatch = || {
+            DataFusionError::Execution(format!(
+                "Can't convert the JSON value {} to {:?}",
+                value, data_type
+            ))
+        };
+        macro_rules! integer {
+            ($SCALAR:ident) => {{
+                let v = value.as_i64().ok_or_else(mismatch)?;
+                ScalarValue::$SCALAR(Some(v.try_into().map_err(|_| mismatch())?))
+            }};
+        }
+        macro_rules! unsigned {
+            ($SCALAR:ident) => {{
+                let v = value.as_u64().ok_or_else(mismatch)?;
+                ScalarValue::$SCALAR(Some(v.try_into().map_err(|_| mismatch())?))
+            }};
+        }
+        // a temporal value stored as a number, or a string cast to it
+        let temporal = || -> Result<ScalarValue> {
+            match value {
+                Value::String(s) => ScalarValue::try_from_string(s.clone(), data_type),
+                Value::Number(n) => {
+                    let v = n.as_i64().ok_or_else(mismatch)?;
+                    let v32 = || i32::try_from(v).map_err(|_| mismatch());
+                    Ok(match data_type {
+                        DataType::Date32 => ScalarValue::Date32(Some(v32()?)),
+                        DataType::Date64 => ScalarValue::Date64(Some(v)),
+                        DataType::Time64(_) => ScalarValue::Time64(Some(v)),
+                        DataType::Timestamp(TimeUnit::Second, tz) => {
+                            ScalarValue::TimestampSecond(Some(v), tz.clone())
+                        }
+                        DataType::Timestamp(TimeUnit::Millisecond, tz) => {
+                            ScalarValue::TimestampMillisecond(Some(v), tz.clone())
+                        }
+                        DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+                            ScalarValue::TimestampMicrosecond(Some(v), tz.clone())
+                        }
+                        DataType::Timestamp(TimeUnit::Nanosecond, tz) => {
+                            ScalarValue::TimestampNanosecond(Some(v), tz.clone())
+                        }
+                        DataType::Interval(IntervalUnit::YearMonth) => {
+                            ScalarValue::IntervalYearMonth(Some(v32()?))
+                        }
+                        DataType::Interval(IntervalUnit::DayTime) => {
+                            ScalarValue::IntervalDayTime(Some(v))
+                        }
+                        _ => return Err(mismatch()),
+                    })
+                }
+                _ => Err(mismatch()),
+            }
+        };
+        let bytes = || -> Result<Vec<u8>> {
+            value
+                .as_array()
+                .ok_or_else(mismatch)?
+                .iter()
+                .map(|byte| {
+                    byte.as_u64()
+                        .and_then(|byte| u8::try_from(byte).ok())
+                        .ok_or_else(mismatch)
+                })
+                .collect()
+        };
+
+        Ok(match data_type {
+            DataType::Boolean => {
+                ScalarValue::Boolean(Some(value.as_bool().ok_or_else(mismatch)?))
+            }
+            DataType::Int8 => integer!(Int8),
+            DataType::Int16 => integer!(Int16),
+            DataType::Int32 => integer!(Int32),
+            DataType::Int64 => integer!(Int64),
+            DataType::UInt8 => unsigned!(UInt8),
+            DataType::UInt16 => unsigned!(UInt16),
+            DataType::UInt32 => unsigned!(UInt32),
+            DataType::UInt64 => unsigned!(UInt64),
+            DataType::Float32 => {
+                ScalarValue::Float32(Some(value.as_f64().ok_or_else(mismatch)? as f32))
+            }
+            DataType::Float64 => {
+                ScalarValue::Float64(Some(value.as_f64().ok_or_else(mismatch)?))
+            }
+            DataType::Decimal128(precision, scale) => {
+                let s = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    _ => return Err(mismatch()),
+                };
+                let v = parse_decimal(&s, *scale).ok_or_else(mismatch)?;
+                ScalarValue::Decimal128(Some(v), *precision, *scale)
+            }
+            DataType::Utf8 => {
+                ScalarValue::Utf8(Some(value.as_str().ok_or_else(mismatch)?.to_string()))
+            }
+            DataType::LargeUtf8 => ScalarValue::LargeUtf8(Some(
+                value.as_str().ok_or_else(mismatch)?.to_string(),
+            )),
+            DataType::Binary => ScalarValue::Binary(Some(bytes()?)),
+            DataType::LargeBinary => ScalarValue::LargeBinary(Some(bytes()?)),
+            DataType::FixedSizeBinary(size) => {
+                let bytes = bytes()?;
+                if bytes.len() != *size as usize {
+                    return Err(mismatch());
+                }
+                ScalarValue::FixedSizeBinary(*size, Some(bytes))
+            }
+            DataType::Date32
+            | DataType::Date64
+            | DataType::Time64(_)
+            | DataType::Timestamp(_, _)
+            | DataType::Interval(IntervalUnit::YearMonth)
+            | DataType::Interval(IntervalUnit::DayTime) => temporal()?,
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                let v = value.as_str().and_then(|s| s.parse().ok());
+                ScalarValue::IntervalMonthDayNano(Some(v.ok_or_else(mismatch)?))
+            }
+            DataType::List(field) => {
+                let values = value
+                    .as_array()
+                    .ok_or_else(mismatch)?
+                    .iter()
+                    .map(|value| ScalarValue::try_from_json(value, field.data_type()))
+                    .collect::<Result<Vec<_>>>()?;
+                ScalarValue::new_list(Some(values), field.data_type().clone())
+            }
+            DataType::Struct(fields) => {
+                let object = value.as_object().ok_or_else(mismatch)?;
+                let values = fields
+                    .iter()
+                    .map(|field| {
+                        let value = object.get(field.name()).unwrap_or(&Value::Null);
+                        ScalarValue::try_from_json(value, field.data_type())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                ScalarValue::Struct(Some(values), Box::new(fields.clone()))
+            }
+            DataType::Dictionary(key_type, value_type) => ScalarValue::Dictionary(
+                key_type.clone(),
+                Box::new(ScalarValue::try_from_json(value, value_type)?),
+            ),
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Can't convert a JSON value to {:?}",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+/// Converts the values of `array` into JSON values
+pub fn array_to_json(array: &ArrayRef) -> Result<Vec<Value>> {
+    (0..array.len())
+        .map(|index| ScalarValue::try_from_array(array, index)?.to_json())
+        .collect()
+}
+
+/// Converts JSON values into an array of type `data_type`
+pub fn array_from_json(values: &[Value], data_type: &DataType) -> Result<ArrayRef> {
+    if values.is_empty() {
+        return Ok(new_empty_array(data_type));
+    }
+    ScalarValue::iter_to_array(
+        values
+            .iter()
+            .map(|value| ScalarValue::try_from_json(value, data_type))
+            .collect::<Result<Vec<_>>>()?,
+    )
+}
+
+/// Converts JSON objects into the columns of `fields`, taking the value of
+/// each column from the member named after its field
+pub fn columns_from_json(objects: &[Value], fields: &[Field]) -> Result<Vec<ArrayRef>> {
+    fields
+        .iter()
+        .map(|field| {
+            let values = objects
+                .iter()
+                .map(|object| object.get(field.name()).cloned().unwrap_or(Value::Null))
+                .collect::<Vec<_>>();
+            array_from_json(&values, field.data_type())
+        })
+        .collect()
+}
+
+fn float_to_json(v: f64) -> Result<Value> {
+    Number::from_f64(v).map(Value::Number).ok_or_else(|| {
+        DataFusionError::Execution(format!("Can't convert {} to a JSON number", v))
+    })
+}
+
+fn format_decimal(v: i128, scale: u8) -> String {
+    let scale = scale as usize;
+    let digits = v.unsigned_abs().to_string();
+    let sign = if v < 0 { "-" } else { "" };
+    if scale == 0 {
+        return format!("{}{}", sign, digits);
+    }
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+/// Parses a decimal number with at most `scale` fractional digits into its
+/// value scaled by `10^scale`
+fn parse_decimal(s: &str, scale: u8) -> Option<i128> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let scale = scale as usize;
+    if (integer.is_empty() && fraction.is_empty())
+        || fraction.len() > scale
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let digits = format!("{}{:0<width$}", integer, fraction, width = scale);
+    let v = digits.parse::<i128>().ok()?;
+    Some(if negative { -v } else { v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn scalar_json_roundtrip() -> Result<()> {
+        let struct_type = DataType::Struct(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let cases = vec![
+            (ScalarValue::Boolean(Some(true)), json!(true)),
+            (ScalarValue::Int8(Some(-3)), json!(-3)),
+            (ScalarValue::UInt64(Some(u64::MAX)), json!(u64::MAX)),
+            (ScalarValue::Float64(Some(1.5)), json!(1.5)),
+            (ScalarValue::Int32(None), json!(null)),
+            (ScalarValue::Utf8(Some("foo".to_string())), json!("foo")),
+            (
+                ScalarValue::Decimal128(Some(-12345), 10, 2),
+                json!("-123.45"),
+            ),
+            (ScalarValue::Decimal128(Some(5), 10, 3), json!("0.005")),
+            (ScalarValue::Date32(Some(19266)), json!(19266)),
+            (
+                ScalarValue::TimestampMillisecond(Some(1664582400000), None),
+                json!(1664582400000i64),
+            ),
+            (ScalarValue::Binary(Some(vec![1, 2])), json!([1, 2])),
+            (
+                ScalarValue::new_list(
+                    Some(vec![ScalarValue::Int64(Some(1)), ScalarValue::Int64(None)]),
+                    DataType::Int64,
+                ),
+                json!([1, null]),
+            ),
+            (
+                ScalarValue::try_from_json(&json!({"a": 1, "b": "x"}), &struct_type)?,
+                json!({"a": 1, "b": "x"}),
+            ),
+        ];
+        for (scalar, value) in cases {
+            assert_eq!(scalar.to_json()?, value);
+            assert_eq!(
+                ScalarValue::try_from_json(&value, &scalar.get_datatype())?,
+                scalar
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_from_json() -> Result<()> {
+        assert_eq!(
+            ScalarValue::try_from_json(&json!("2022-10-01"), &DataType::Date32)?,
+            ScalarValue::Date32(Some(19266))
+        );
+        assert_eq!(
+            ScalarValue::try_from_json(&json!(1.5), &DataType::Decimal128(5, 2))?,
+            ScalarValue::Decimal128(Some(150), 5, 2)
+        );
+        // missing members of an object are null
+        let struct_type = DataType::Struct(vec![Field::new("a", DataType::Int32, true)]);
+        assert_eq!(
+            ScalarValue::try_from_json(&json!({}), &struct_type)?,
+            ScalarValue::Struct(
+                Some(vec![ScalarValue::Int32(None)]),
+                Box::new(vec![Field::new("a", DataType::Int32, true)])
+            )
+        );
+
+        assert!(ScalarValue::try_from_json(&json!(300), &DataType::Int8).is_err());
+        assert!(ScalarValue::try_from_json(&json!("1"), &DataType::Int32).is_err());
+        assert!(
+            ScalarValue::try_from_json(&json!("1.234"), &DataType::Decimal128(5, 2))
+                .is_err()
+        );
+        assert!(ScalarValue::Float64(Some(f64::NAN)).to_json().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn array_json_roundtrip() -> Result<()> {
+        let values = vec![json!(1), json!(null), json!(3)];
+        let array = array_from_json(&values, &DataType::Int32)?;
+        let expected: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+        assert_eq!(&array, &expected);
+        assert_eq!(array_to_json(&array)?, values);
+
+        let objects = vec![json!({"a": 1}), json!({"a": 2, "b": 3})];
+        let fields = vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, true),
+        ];
+        let columns = columns_from_json(&objects, &fields)?;
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![None, Some(3)]));
+        assert_eq!(&columns[1], &b);
+        Ok(())
+    }
+}
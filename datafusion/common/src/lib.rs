@@ -17,10 +17,15 @@
 
 pub mod bisect;
 mod column;
+mod constraints;
+pub mod convert;
 pub mod delta;
 mod dfschema;
+pub mod digest;
 mod error;
 pub mod from_slice;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod parsers;
 #[cfg(feature = "pyarrow")]
 mod pyarrow;
@@ -28,9 +33,11 @@ pub mod scalar;
 pub mod stats;
 pub mod test_util;
 
+pub use arrow;
 pub use column::Column;
+pub use constraints::{Constraint, Constraints};
 pub use dfschema::{DFField, DFSchema, DFSchemaRef, ExprSchema, ToDFSchema};
-pub use error::{field_not_found, DataFusionError, Result, SchemaError};
+pub use error::{field_not_found, DataFusionError, ErrorCode, Result, SchemaError};
 pub use parsers::parse_interval;
 pub use scalar::{ScalarType, ScalarValue};
 pub use stats::{ColumnStatistics, Statistics};
@@ -51,11 +51,10 @@ use std::sync::Arc;
 
 type RecordSlice<'a> = &'a [&'a Vec<(String, Value)>];
 
+/// Reads record batches from an Avro container file
 pub struct AvroArrowArrayReader<'a, R: Read> {
     reader: AvroReader<'a, R>,
-    schema: SchemaRef,
-    projection: Option<Vec<String>>,
-    schema_lookup: BTreeMap<String, usize>,
+    builder: AvroRecordBatchBuilder,
 }
 
 impl<'a, R: Read> AvroArrowArrayReader<'a, R> {
@@ -65,26 +64,9 @@ impl<'a, R: Read> AvroArrowArrayReader<'a, R> {
         projection: Option<Vec<String>>,
     ) -> Result<Self> {
         let reader = AvroReader::new(reader)?;
-        let writer_schema = reader.writer_schema().clone();
-        let schema_lookup = Self::schema_lookup(writer_schema)?;
-        Ok(Self {
-            reader,
-            schema,
-            projection,
-            schema_lookup,
-        })
-    }
-
-    pub fn schema_lookup(schema: AvroSchema) -> Result<BTreeMap<String, usize>> {
-        match schema {
-            AvroSchema::Record {
-                lookup: ref schema_lookup,
-                ..
-            } => Ok(schema_lookup.clone()),
-            _ => Err(DataFusionError::ArrowError(SchemaError(
-                "expected avro schema to be a record".to_string(),
-            ))),
-        }
+        let builder =
+            AvroRecordBatchBuilder::try_new(reader.writer_schema(), schema, projection)?;
+        Ok(Self { reader, builder })
     }
 
     /// Read the next batch of records
@@ -110,6 +92,47 @@ impl<'a, R: Read> AvroArrowArrayReader<'a, R> {
             // reached end of file
             return Ok(None);
         }
+        self.builder.build(&rows).map(Some)
+    }
+}
+
+/// Builds record batches of an Arrow schema from the values of Avro records
+pub struct AvroRecordBatchBuilder {
+    schema: SchemaRef,
+    projection: Option<Vec<String>>,
+    schema_lookup: BTreeMap<String, usize>,
+}
+
+impl AvroRecordBatchBuilder {
+    /// Creates a builder of batches of `schema`, or of the columns of
+    /// `projection`, from the records of the Avro schema `writer_schema`
+    pub fn try_new(
+        writer_schema: &AvroSchema,
+        schema: SchemaRef,
+        projection: Option<Vec<String>>,
+    ) -> Result<Self> {
+        let schema_lookup = Self::schema_lookup(writer_schema.clone())?;
+        Ok(Self {
+            schema,
+            projection,
+            schema_lookup,
+        })
+    }
+
+    pub fn schema_lookup(schema: AvroSchema) -> Result<BTreeMap<String, usize>> {
+        match schema {
+            AvroSchema::Record {
+                lookup: ref schema_lookup,
+                ..
+            } => Ok(schema_lookup.clone()),
+            _ => Err(DataFusionError::ArrowError(SchemaError(
+                "expected avro schema to be a record".to_string(),
+            ))),
+        }
+    }
+
+    /// Build a record batch from the fields of the records `rows`
+    pub fn build(&self, rows: &[Vec<(String, Value)>]) -> ArrowResult<RecordBatch> {
         let rows = rows.iter().collect::<Vec<&Vec<(String, Value)>>>();
         let projection = self.projection.clone().unwrap_or_default();
         let arrays =
@@ -124,7 +147,7 @@ impl<'a, R: Read> AvroArrowArrayReader<'a, R> {
                 .collect()
         };
         let projected_schema = Arc::new(Schema::new(projected_fields));
-        arrays.and_then(|arr| RecordBatch::try_new(projected_schema, arr).map(Some))
+        arrays.and_then(|arr| RecordBatch::try_new(projected_schema, arr))
     }
 
     fn build_boolean_array(&self, rows: RecordSlice, col_name: &str) -> ArrayRef {
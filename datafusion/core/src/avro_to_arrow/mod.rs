@@ -23,6 +23,8 @@ mod arrow_array_reader;
 mod reader;
 #[cfg(feature = "avro")]
 mod schema;
+#[cfg(feature = "avro")]
+pub mod schema_registry;
 
 use crate::arrow::datatypes::Schema;
 use crate::error::Result;
@@ -0,0 +1,345 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Decoding of the messages framed in the wire format of the Confluent Schema
+//! Registry, such as the messages of Kafka topics.
+//!
+//! Every message starts with a zero magic byte and the id of the schema it was
+//! written with in the registry, as a big endian 32 bits integer, followed by
+//! the encoded record. A [`ConfluentAvroDecoder`] looks up the writer schemas
+//! of Avro messages in a [`SchemaRegistry`] and reads their records with the
+//! schema of the reader, following the rules of Avro schema resolution: the
+//! fields missing from the writer schema take their default value, the fields
+//! missing from the reader schema are skipped, and numbers are promoted.
+//!
+//! JSON messages are framed in the same format, so their records can be read
+//! with the JSON reader of Arrow after [`decode_header`].
+//!
+//! The client of the REST API of the registry is enabled with the
+//! `schema_registry` feature.
+
+use apache_avro::from_avro_datum;
+use apache_avro::schema::Schema as AvroSchema;
+use apache_avro::types::Value;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use super::arrow_array_reader::AvroRecordBatchBuilder;
+use super::schema::to_arrow_schema;
+use crate::error::{DataFusionError, Result};
+
+/// The magic byte starting the messages
+const MAGIC_BYTE: u8 = 0;
+
+/// The length of the header of the messages: the magic byte and the id
+const HEADER_LEN: usize = 5;
+
+/// A registry of the schemas of the messages, by id
+#[async_trait]
+pub trait SchemaRegistry: Debug + Send + Sync {
+    /// The text of the schema registered with the id `id`
+    async fn schema(&self, id: u32) -> Result<String>;
+
+    /// The id and the text of the latest schema of the subject `subject`
+    async fn latest_schema(&self, subject: &str) -> Result<(u32, String)>;
+}
+
+/// Returns the id of the schema of `message`, and its encoded record
+pub fn decode_header(message: &[u8]) -> Result<(u32, &[u8])> {
+    match message {
+        [MAGIC_BYTE, a, b, c, d, record @ ..] => {
+            Ok((u32::from_be_bytes([*a, *b, *c, *d]), record))
+        }
+        _ if message.len() < HEADER_LEN => Err(DataFusionError::Execution(format!(
+            "Message of {} bytes is too short for the schema registry header",
+            message.len()
+        ))),
+        _ => Err(DataFusionError::Execution(format!(
+            "Unknown magic byte {} of schema registry message",
+            message[0]
+        ))),
+    }
+}
+
+/// Decodes the Avro messages of a topic into record batches of the Arrow
+/// schema of a reader schema
+#[derive(Debug)]
+pub struct ConfluentAvroDecoder {
+    registry: Arc<dyn SchemaRegistry>,
+    reader_schema: AvroSchema,
+    schema: SchemaRef,
+    /// The writer schemas looked up so far, by id
+    writer_schemas: RwLock<HashMap<u32, Arc<AvroSchema>>>,
+}
+
+impl ConfluentAvroDecoder {
+    /// Creates a decoder reading the records with the Avro schema
+    /// `reader_schema`, looking up the writer schemas in `registry`
+    pub fn try_new(
+        registry: Arc<dyn SchemaRegistry>,
+        reader_schema: &str,
+    ) -> Result<Self> {
+        let reader_schema = AvroSchema::parse_str(reader_schema)?;
+        let schema = Arc::new(to_arrow_schema(&reader_schema)?);
+        Ok(Self {
+            registry,
+            reader_schema,
+            schema,
+            writer_schemas: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Creates a decoder reading the records with the latest schema of the
+    /// subject `subject` in `registry`, such as `<topic>-value`
+    pub async fn try_new_for_subject(
+        registry: Arc<dyn SchemaRegistry>,
+        subject: &str,
+    ) -> Result<Self> {
+        let (id, reader_schema) = registry.latest_schema(subject).await?;
+        let decoder = Self::try_new(registry, &reader_schema)?;
+        decoder
+            .writer_schemas
+            .write()
+            .insert(id, Arc::new(decoder.reader_schema.clone()));
+        Ok(decoder)
+    }
+
+    /// The Arrow schema of the decoded record batches
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Decodes the records of `messages` into a record batch
+    pub async fn decode<M: AsRef<[u8]>>(&self, messages: &[M]) -> Result<RecordBatch> {
+        let mut rows = Vec::with_capacity(messages.len());
+        for message in messages {
+            let (id, mut record) = decode_header(message.as_ref())?;
+            let writer_schema = self.writer_schema(id).await?;
+            match from_avro_datum(&writer_schema, &mut record, Some(&self.reader_schema))?
+            {
+                Value::Record(fields) => rows.push(fields),
+                other => {
+                    return Err(DataFusionError::Execution(format!(
+                        "Message needs to be an Avro record, got: {:?}",
+                        other
+                    )))
+                }
+            }
+        }
+        let builder =
+            AvroRecordBatchBuilder::try_new(&self.reader_schema, self.schema(), None)?;
+        Ok(builder.build(&rows)?)
+    }
+
+    /// The writer schema of id `id`, looked up in the registry the first time
+    async fn writer_schema(&self, id: u32) -> Result<Arc<AvroSchema>> {
+        let cached = self.writer_schemas.read().get(&id).cloned();
+        if let Some(schema) = cached {
+            return Ok(schema);
+        }
+        let schema = Arc::new(AvroSchema::parse_str(&self.registry.schema(id).await?)?);
+        self.writer_schemas.write().insert(id, schema.clone());
+        Ok(schema)
+    }
+}
+
+/// A [`SchemaRegistry`] calling the REST API of a Confluent Schema Registry
+#[cfg(feature = "schema_registry")]
+#[derive(Debug, Clone)]
+pub struct ConfluentSchemaRegistryClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "schema_registry")]
+impl ConfluentSchemaRegistryClient {
+    /// Creates a client of the registry at `url`, such as
+    /// `http://localhost:8081`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<serde_json::Value> {
+        let external = |e: reqwest::Error| DataFusionError::External(Box::new(e));
+        self.client
+            .get(format!("{}/{}", self.url, path))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(external)?
+            .json()
+            .await
+            .map_err(external)
+    }
+}
+
+#[cfg(feature = "schema_registry")]
+fn schema_field(response: &serde_json::Value) -> Result<String> {
+    response["schema"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Schema registry response without schema: {}",
+                response
+            ))
+        })
+}
+
+#[cfg(feature = "schema_registry")]
+#[async_trait]
+impl SchemaRegistry for ConfluentSchemaRegistryClient {
+    async fn schema(&self, id: u32) -> Result<String> {
+        let response = self.get(&format!("schemas/ids/{}", id)).await?;
+        schema_field(&response)
+    }
+
+    async fn latest_schema(&self, subject: &str) -> Result<(u32, String)> {
+        let subject = percent_encoding::utf8_percent_encode(
+            subject,
+            percent_encoding::NON_ALPHANUMERIC,
+        );
+        let response = self
+            .get(&format!("subjects/{}/versions/latest", subject))
+            .await?;
+        let id = response["id"].as_u64().ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Schema registry response without id: {}",
+                response
+            ))
+        })?;
+        Ok((id as u32, schema_field(&response)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use apache_avro::to_avro_datum;
+
+    const V1: &str = r#"{
+        "type": "record",
+        "name": "order",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "amount", "type": "int"}
+        ]
+    }"#;
+
+    const V2: &str = r#"{
+        "type": "record",
+        "name": "order",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "amount", "type": "double"},
+            {"name": "currency", "type": "string", "default": "EUR"}
+        ]
+    }"#;
+
+    /// A registry of the schemas `V1` and `V2`
+    #[derive(Debug)]
+    struct MemoryRegistry {}
+
+    #[async_trait]
+    impl SchemaRegistry for MemoryRegistry {
+        async fn schema(&self, id: u32) -> Result<String> {
+            match id {
+                1 => Ok(V1.to_owned()),
+                2 => Ok(V2.to_owned()),
+                _ => Err(DataFusionError::Execution(format!("No schema {}", id))),
+            }
+        }
+
+        async fn latest_schema(&self, _subject: &str) -> Result<(u32, String)> {
+            Ok((2, V2.to_owned()))
+        }
+    }
+
+    fn message(id: u32, schema: &str, fields: Vec<(&str, Value)>) -> Vec<u8> {
+        let schema = AvroSchema::parse_str(schema).unwrap();
+        let record = Value::Record(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        );
+        let mut message = vec![MAGIC_BYTE];
+        message.extend_from_slice(&id.to_be_bytes());
+        message.extend(to_avro_datum(&schema, record).unwrap());
+        message
+    }
+
+    #[tokio::test]
+    async fn decode_evolved_messages() -> Result<()> {
+        let decoder = ConfluentAvroDecoder::try_new_for_subject(
+            Arc::new(MemoryRegistry {}),
+            "orders",
+        )
+        .await?;
+        let messages = vec![
+            message(
+                1,
+                V1,
+                vec![("id", Value::Long(1)), ("amount", Value::Int(10))],
+            ),
+            message(
+                2,
+                V2,
+                vec![
+                    ("id", Value::Long(2)),
+                    ("amount", Value::Double(2.5)),
+                    ("currency", Value::String("USD".to_owned())),
+                ],
+            ),
+        ];
+        let batch = decoder.decode(&messages).await?;
+        let expected = vec![
+            "+----+--------+----------+",
+            "| id | amount | currency |",
+            "+----+--------+----------+",
+            "| 1  | 10     | EUR      |",
+            "| 2  | 2.5    | USD      |",
+            "+----+--------+----------+",
+        ];
+        assert_batches_eq!(expected, &[batch]);
+
+        let err = decoder.decode(&[vec![1, 0, 0, 0, 1]]).await.unwrap_err();
+        assert!(err.to_string().contains("Unknown magic byte 1"));
+        let err = decoder.decode(&[vec![0, 0, 0, 0, 3]]).await.unwrap_err();
+        assert!(err.to_string().contains("No schema 3"));
+        Ok(())
+    }
+
+    #[test]
+    fn short_message() {
+        let err = decode_header(&[0, 0, 1]).unwrap_err();
+        assert!(err.to_string().contains("too short"));
+        assert_eq!(
+            decode_header(&[0, 0, 0, 1, 2, 42]).unwrap(),
+            (258, &[42][..])
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Asynchronous catalogs and schemas, for catalogs whose lookups are remote
+//! calls, such as the Hive metastore or a REST catalog.
+//!
+//! The SQL planner is synchronous, so an asynchronous catalog is registered in
+//! a session as a [`ResolvedCatalogProvider`], serving the schemas and tables
+//! resolved so far. [`SessionContext::sql`] resolves the tables of a query
+//! that the planner does not find with the [`TableResolver`] of their
+//! catalog before planning it again.
+//!
+//! [`SessionContext::sql`]: crate::execution::context::SessionContext::sql
+
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::catalog::catalog::CatalogProvider;
+use crate::catalog::schema::SchemaProvider;
+use crate::datasource::TableProvider;
+use crate::error::Result;
+
+/// Represents a schema whose tables are looked up asynchronously.
+#[async_trait]
+pub trait AsyncSchemaProvider: Sync + Send {
+    /// Returns the schema provider as [`Any`](std::any::Any)
+    /// so that it can be downcast to a specific implementation.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Retrieves the list of available table names in this schema.
+    async fn table_names(&self) -> Result<Vec<String>>;
+
+    /// Retrieves a specific table from the schema by name, provided it exists.
+    async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>>;
+}
+
+/// Represents a catalog whose schemas are looked up asynchronously.
+#[async_trait]
+pub trait AsyncCatalogProvider: Sync + Send {
+    /// Returns the catalog provider as [`Any`](std::any::Any)
+    /// so that it can be downcast to a specific implementation.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Retrieves the list of available schema names in this catalog.
+    async fn schema_names(&self) -> Result<Vec<String>>;
+
+    /// Retrieves a specific schema from the catalog by name, provided it exists.
+    async fn schema(&self, name: &str) -> Result<Option<Arc<dyn AsyncSchemaProvider>>>;
+}
+
+/// Resolves the tables of a [`CatalogProvider`] that can only be looked up
+/// asynchronously, so that the catalog serves them once resolved.
+///
+/// See [`CatalogProvider::table_resolver`].
+#[async_trait]
+pub trait TableResolver: Sync + Send {
+    /// Looks up the table `table` of the schema `schema`, returning whether
+    /// it was found and not resolved before
+    async fn resolve_table(&self, schema: &str, table: &str) -> Result<bool>;
+}
+
+/// Wraps a [`SchemaProvider`] as an [`AsyncSchemaProvider`]
+pub fn schema_as_async(schema: Arc<dyn SchemaProvider>) -> Arc<dyn AsyncSchemaProvider> {
+    Arc::new(SyncSchemaProvider { inner: schema })
+}
+
+/// Wraps a [`CatalogProvider`] as an [`AsyncCatalogProvider`]
+pub fn catalog_as_async(
+    catalog: Arc<dyn CatalogProvider>,
+) -> Arc<dyn AsyncCatalogProvider> {
+    Arc::new(SyncCatalogProvider { inner: catalog })
+}
+
+/// The [`AsyncSchemaProvider`] of a [`SchemaProvider`]
+struct SyncSchemaProvider {
+    inner: Arc<dyn SchemaProvider>,
+}
+
+#[async_trait]
+impl AsyncSchemaProvider for SyncSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self.inner.as_any()
+    }
+
+    async fn table_names(&self) -> Result<Vec<String>> {
+        Ok(self.inner.table_names())
+    }
+
+    async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
+        Ok(self.inner.table(name))
+    }
+}
+
+/// The [`AsyncCatalogProvider`] of a [`CatalogProvider`]
+struct SyncCatalogProvider {
+    inner: Arc<dyn CatalogProvider>,
+}
+
+#[async_trait]
+impl AsyncCatalogProvider for SyncCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self.inner.as_any()
+    }
+
+    async fn schema_names(&self) -> Result<Vec<String>> {
+        Ok(self.inner.schema_names())
+    }
+
+    async fn schema(&self, name: &str) -> Result<Option<Arc<dyn AsyncSchemaProvider>>> {
+        Ok(self.inner.schema(name).map(schema_as_async))
+    }
+}
+
+/// The [`CatalogProvider`] of an [`AsyncCatalogProvider`], serving the
+/// schemas and tables resolved with [`resolve_table`](Self::resolve_table)
+///
+/// Resolved tables are kept until the catalog is deregistered, and the
+/// schemas only list the tables resolved so far.
+pub struct ResolvedCatalogProvider {
+    inner: Arc<dyn AsyncCatalogProvider>,
+    schemas: RwLock<HashMap<String, Arc<ResolvedSchemaProvider>>>,
+}
+
+impl ResolvedCatalogProvider {
+    /// Wraps `inner`, without any resolved schema
+    pub fn new(inner: Arc<dyn AsyncCatalogProvider>) -> Self {
+        Self {
+            inner,
+            schemas: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The wrapped catalog
+    pub fn inner(&self) -> &Arc<dyn AsyncCatalogProvider> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl TableResolver for ResolvedCatalogProvider {
+    /// Looks up the table `table` of the schema `schema` in the wrapped
+    /// catalog, returning whether it was found and not resolved before
+    async fn resolve_table(&self, schema: &str, table: &str) -> Result<bool> {
+        let resolved = self.schemas.read().get(schema).cloned();
+        let resolved = match resolved {
+            Some(resolved) => resolved,
+            None => match self.inner.schema(schema).await? {
+                Some(inner) => self
+                    .schemas
+                    .write()
+                    .entry(schema.to_owned())
+                    .or_insert_with(|| {
+                        Arc::new(ResolvedSchemaProvider {
+                            inner,
+                            tables: RwLock::new(HashMap::new()),
+                        })
+                    })
+                    .clone(),
+                None => return Ok(false),
+            },
+        };
+        if resolved.table_exist(table) {
+            return Ok(false);
+        }
+        match resolved.inner.table(table).await? {
+            Some(provider) => {
+                resolved.tables.write().insert(table.to_owned(), provider);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl CatalogProvider for ResolvedCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        self.schemas.read().keys().cloned().collect()
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        self.schemas
+            .read()
+            .get(name)
+            .map(|schema| schema.clone() as Arc<dyn SchemaProvider>)
+    }
+
+    fn table_resolver(&self) -> Option<&dyn TableResolver> {
+        Some(self)
+    }
+}
+
+/// The [`SchemaProvider`] of an [`AsyncSchemaProvider`] resolved by a
+/// [`ResolvedCatalogProvider`]
+struct ResolvedSchemaProvider {
+    inner: Arc<dyn AsyncSchemaProvider>,
+    tables: RwLock<HashMap<String, Arc<dyn TableProvider>>>,
+}
+
+impl SchemaProvider for ResolvedSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.tables.read().keys().cloned().collect()
+    }
+
+    fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
+        self.tables.read().get(name).cloned()
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.tables.read().contains_key(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::catalog::MemoryCatalogProvider;
+    use crate::catalog::schema::MemorySchemaProvider;
+    use crate::datasource::empty::EmptyTable;
+    use arrow::datatypes::Schema;
+
+    #[tokio::test]
+    async fn resolve_tables() -> Result<()> {
+        let schema = MemorySchemaProvider::new();
+        schema.register_table(
+            "t".to_owned(),
+            Arc::new(EmptyTable::new(Arc::new(Schema::empty()))),
+        )?;
+        let catalog = MemoryCatalogProvider::new();
+        catalog.register_schema("s", Arc::new(schema))?;
+
+        let resolved = ResolvedCatalogProvider::new(catalog_as_async(Arc::new(catalog)));
+        assert!(resolved.schema("s").is_none());
+
+        assert!(resolved.resolve_table("s", "t").await?);
+        assert!(!resolved.resolve_table("s", "t").await?);
+        assert!(!resolved.resolve_table("s", "u").await?);
+        assert!(!resolved.resolve_table("x", "t").await?);
+
+        assert_eq!(resolved.schema_names(), vec!["s"]);
+        let schema = resolved.schema("s").unwrap();
+        assert_eq!(schema.table_names(), vec!["t"]);
+        assert!(schema.table("t").is_some());
+        assert!(!schema.table_exist("u"));
+        Ok(())
+    }
+}
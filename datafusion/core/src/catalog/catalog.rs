@@ -18,6 +18,7 @@
 //! Describes the interface and built-in implementations of catalogs,
 //! representing collections of named schemas.
 
+use crate::catalog::async_catalog::TableResolver;
 use crate::catalog::schema::SchemaProvider;
 use datafusion_common::{DataFusionError, Result};
 use parking_lot::RwLock;
@@ -128,11 +129,25 @@ pub trait CatalogProvider: Sync + Send {
             "Registering new schemas is not supported".to_string(),
         ))
     }
+
+    /// The directory under which the schemas of this catalog store their
+    /// tables by default, if there is one
+    fn location(&self) -> Option<&str> {
+        None
+    }
+
+    /// The resolver of the tables of this catalog that are looked up
+    /// asynchronously, called for the tables of a query that the SQL planner
+    /// does not find before planning it again
+    fn table_resolver(&self) -> Option<&dyn TableResolver> {
+        None
+    }
 }
 
 /// Simple in-memory implementation of a catalog.
 pub struct MemoryCatalogProvider {
     schemas: RwLock<HashMap<String, Arc<dyn SchemaProvider>>>,
+    location: Option<String>,
 }
 
 impl MemoryCatalogProvider {
@@ -140,8 +155,16 @@ impl MemoryCatalogProvider {
     pub fn new() -> Self {
         Self {
             schemas: RwLock::new(HashMap::new()),
+            location: None,
         }
     }
+
+    /// Set the directory under which the schemas of this catalog store their
+    /// tables by default
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
 }
 
 impl CatalogProvider for MemoryCatalogProvider {
@@ -167,6 +190,10 @@ impl CatalogProvider for MemoryCatalogProvider {
         let mut schemas = self.schemas.write();
         Ok(schemas.insert(name.into(), schema))
     }
+
+    fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
 }
 
 #[cfg(test)]
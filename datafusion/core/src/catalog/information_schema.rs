@@ -37,6 +37,7 @@ use crate::datasource::{MemTable, TableProvider};
 use crate::logical_expr::TableType;
 
 use super::{
+    async_catalog::TableResolver,
     catalog::{CatalogList, CatalogProvider},
     schema::SchemaProvider,
 };
@@ -108,6 +109,14 @@ impl CatalogProvider for CatalogWithInformationSchema {
         let catalog = &self.inner;
         catalog.register_schema(name, schema)
     }
+
+    fn location(&self) -> Option<&str> {
+        self.inner.location()
+    }
+
+    fn table_resolver(&self) -> Option<&dyn TableResolver> {
+        self.inner.table_resolver()
+    }
 }
 
 /// Implements the `information_schema` virtual schema and tables
@@ -0,0 +1,161 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A [`MetastoreClient`] reading the tables of the AWS Glue Data Catalog
+
+use async_trait::async_trait;
+use aws_sdk_glue::model::{Column, Table};
+use aws_sdk_glue::types::SdkError;
+use aws_sdk_glue::Client;
+
+use super::{MetastoreClient, MetastoreColumn, MetastoreTable, StorageDescriptor};
+use crate::error::{DataFusionError, Result};
+
+/// A [`MetastoreClient`] of the AWS Glue Data Catalog
+#[derive(Debug, Clone)]
+pub struct GlueClient {
+    client: Client,
+}
+
+impl GlueClient {
+    /// Creates a client of the Data Catalog of the account and region
+    /// configured in the environment
+    pub async fn from_env() -> Self {
+        let config = aws_config::load_from_env().await;
+        Self::new(Client::new(&config))
+    }
+
+    /// Creates a client of the Data Catalog with an AWS Glue client
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl MetastoreClient for GlueClient {
+    async fn databases(&self) -> Result<Vec<String>> {
+        let mut databases = vec![];
+        let mut next_token = None;
+        loop {
+            let output = self
+                .client
+                .get_databases()
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            databases.extend(
+                output
+                    .database_list()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|db| db.name().map(str::to_owned)),
+            );
+            next_token = output.next_token().map(str::to_owned);
+            if next_token.is_none() {
+                return Ok(databases);
+            }
+        }
+    }
+
+    async fn table_names(&self, database: &str) -> Result<Vec<String>> {
+        let mut tables = vec![];
+        let mut next_token = None;
+        loop {
+            let output = self
+                .client
+                .get_tables()
+                .database_name(database)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            tables.extend(
+                output
+                    .table_list()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|table| table.name().map(str::to_owned)),
+            );
+            next_token = output.next_token().map(str::to_owned);
+            if next_token.is_none() {
+                return Ok(tables);
+            }
+        }
+    }
+
+    async fn table(&self, database: &str, name: &str) -> Result<Option<MetastoreTable>> {
+        let output = self
+            .client
+            .get_table()
+            .database_name(database)
+            .name(name)
+            .send()
+            .await;
+        match output {
+            Ok(output) => output.table().map(metastore_table).transpose(),
+            Err(SdkError::ServiceError { err, .. })
+                if err.is_entity_not_found_exception() =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(DataFusionError::External(Box::new(e))),
+        }
+    }
+}
+
+/// The definition of the Glue table `table`
+fn metastore_table(table: &Table) -> Result<MetastoreTable> {
+    let name = table.name().unwrap_or_default().to_owned();
+    let descriptor = table.storage_descriptor().ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "Glue table {} has no storage descriptor, views are not supported",
+            name
+        ))
+    })?;
+    let serde_info = descriptor.serde_info();
+    Ok(MetastoreTable {
+        columns: columns(descriptor.columns()),
+        partition_keys: columns(table.partition_keys()),
+        storage: StorageDescriptor {
+            location: descriptor.location().unwrap_or_default().to_owned(),
+            input_format: descriptor.input_format().map(str::to_owned),
+            serialization_library: serde_info
+                .and_then(|serde| serde.serialization_library())
+                .map(str::to_owned),
+            serde_parameters: serde_info
+                .and_then(|serde| serde.parameters())
+                .cloned()
+                .unwrap_or_default(),
+        },
+        parameters: table.parameters().cloned().unwrap_or_default(),
+        name,
+    })
+}
+
+fn columns(columns: Option<&[Column]>) -> Vec<MetastoreColumn> {
+    columns
+        .unwrap_or_default()
+        .iter()
+        .map(|column| {
+            MetastoreColumn::new(
+                column.name().unwrap_or_default(),
+                column.r#type().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,570 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Catalogs backed by a table metastore, such as the Hive metastore or AWS
+//! Glue, exposing the tables of its databases as [`ListingTable`]s.
+//!
+//! A [`MetastoreClient`] reads the definitions of the tables from the
+//! metastore, and a [`MetastoreCatalogProvider`] maps them to listing tables,
+//! with the columns, partition keys, location and file format of the table.
+//! The client for AWS Glue is enabled with the `glue` feature.
+//!
+//! The values of the partition keys are read from the Hive-style paths of the
+//! files in the location of the table (`key=value`), so the partitions are
+//! not listed from the metastore. As for every listing table, the partition
+//! columns are dictionary encoded strings.
+
+#[cfg(feature = "glue")]
+pub mod glue;
+
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+
+use crate::catalog::async_catalog::{AsyncCatalogProvider, AsyncSchemaProvider};
+use crate::datasource::file_format::avro::AvroFormat;
+use crate::datasource::file_format::csv::CsvFormat;
+use crate::datasource::file_format::json::JsonFormat;
+use crate::datasource::file_format::parquet::ParquetFormat;
+use crate::datasource::file_format::FileFormat;
+use crate::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
+
+/// The table parameter holding the format of the files, set by the crawlers
+/// of AWS Glue
+const CLASSIFICATION: &str = "classification";
+
+/// The table parameter holding the number of header lines of text files
+const SKIP_HEADER_LINE_COUNT: &str = "skip.header.line.count";
+
+/// A column of a table in a metastore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetastoreColumn {
+    /// The name of the column
+    pub name: String,
+    /// The Hive type of the column, such as `bigint` or `decimal(10,2)`
+    pub data_type: String,
+}
+
+impl MetastoreColumn {
+    /// Creates a column of the Hive type `data_type`
+    pub fn new(name: impl Into<String>, data_type: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            data_type: data_type.into(),
+        }
+    }
+}
+
+/// Where and how the files of a table in a metastore are stored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDescriptor {
+    /// The URL or path of the directory of the files
+    pub location: String,
+    /// The Hadoop input format of the files, such as
+    /// `org.apache.hadoop.mapred.TextInputFormat`
+    pub input_format: Option<String>,
+    /// The serialization library of the files, such as
+    /// `org.apache.hadoop.hive.serde2.lazy.LazySimpleSerDe`
+    pub serialization_library: Option<String>,
+    /// The parameters of the serialization library, such as `field.delim`
+    pub serde_parameters: HashMap<String, String>,
+}
+
+/// The definition of a table in a metastore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetastoreTable {
+    /// The name of the table
+    pub name: String,
+    /// The columns stored in the files of the table
+    pub columns: Vec<MetastoreColumn>,
+    /// The partition keys of the table, in the order of the directories
+    pub partition_keys: Vec<MetastoreColumn>,
+    /// Where and how the files of the table are stored
+    pub storage: StorageDescriptor,
+    /// The parameters of the table, such as `classification`
+    pub parameters: HashMap<String, String>,
+}
+
+/// A client reading the definitions of the tables of a metastore
+#[async_trait]
+pub trait MetastoreClient: Debug + Sync + Send {
+    /// The names of the databases of the metastore
+    async fn databases(&self) -> Result<Vec<String>>;
+
+    /// The names of the tables of the database `database`
+    async fn table_names(&self, database: &str) -> Result<Vec<String>>;
+
+    /// The definition of the table `name` of the database `database`, if it
+    /// exists
+    async fn table(&self, database: &str, name: &str) -> Result<Option<MetastoreTable>>;
+}
+
+/// An [`AsyncCatalogProvider`] exposing the databases of a metastore as
+/// schemas, and their tables as [`ListingTable`]s
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use datafusion::catalog::metastore::{MetastoreCatalogProvider, MetastoreClient};
+/// # use datafusion::prelude::SessionContext;
+/// # async fn f(client: Arc<dyn MetastoreClient>) -> datafusion::error::Result<()> {
+/// let ctx = SessionContext::new();
+/// ctx.register_async_catalog("hive", Arc::new(MetastoreCatalogProvider::new(client)));
+/// let df = ctx.sql("SELECT * FROM hive.sales.orders").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MetastoreCatalogProvider {
+    client: Arc<dyn MetastoreClient>,
+    target_partitions: usize,
+}
+
+impl MetastoreCatalogProvider {
+    /// Creates a catalog reading the tables of the metastore of `client`,
+    /// scanned in one partition
+    pub fn new(client: Arc<dyn MetastoreClient>) -> Self {
+        Self {
+            client,
+            target_partitions: 1,
+        }
+    }
+
+    /// Group the files of the tables in at most `target_partitions`
+    /// partitions when they are scanned
+    pub fn with_target_partitions(mut self, target_partitions: usize) -> Self {
+        self.target_partitions = target_partitions;
+        self
+    }
+
+    /// The client of the metastore
+    pub fn client(&self) -> &Arc<dyn MetastoreClient> {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl AsyncCatalogProvider for MetastoreCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn schema_names(&self) -> Result<Vec<String>> {
+        self.client.databases().await
+    }
+
+    async fn schema(&self, name: &str) -> Result<Option<Arc<dyn AsyncSchemaProvider>>> {
+        if !self.client.databases().await?.iter().any(|db| db == name) {
+            return Ok(None);
+        }
+        Ok(Some(Arc::new(MetastoreSchemaProvider {
+            client: self.client.clone(),
+            database: name.to_owned(),
+            target_partitions: self.target_partitions,
+        })))
+    }
+}
+
+/// The [`AsyncSchemaProvider`] of a database of a metastore
+#[derive(Debug)]
+pub struct MetastoreSchemaProvider {
+    client: Arc<dyn MetastoreClient>,
+    database: String,
+    target_partitions: usize,
+}
+
+#[async_trait]
+impl AsyncSchemaProvider for MetastoreSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn table_names(&self) -> Result<Vec<String>> {
+        self.client.table_names(&self.database).await
+    }
+
+    async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
+        match self.client.table(&self.database, name).await? {
+            Some(table) => Ok(Some(Arc::new(listing_table(
+                &table,
+                self.target_partitions,
+            )?))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The [`ListingTable`] reading the files of the metastore table `table`
+pub fn listing_table(
+    table: &MetastoreTable,
+    target_partitions: usize,
+) -> Result<ListingTable> {
+    let fields = table
+        .columns
+        .iter()
+        .map(|column| {
+            Ok(Field::new(
+                &column.name,
+                hive_type_to_arrow(&column.data_type)?,
+                true,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let options = ListingOptions {
+        table_partition_cols: table
+            .partition_keys
+            .iter()
+            .map(|key| key.name.clone())
+            .collect(),
+        target_partitions,
+        ..ListingOptions::new(file_format(table)?)
+    };
+    let config =
+        ListingTableConfig::new(ListingTableUrl::parse(&table.storage.location)?)
+            .with_listing_options(options)
+            .with_schema(Arc::new(Schema::new(fields)));
+    ListingTable::try_new(config)
+}
+
+/// The format of the files of the metastore table `table`, from its
+/// classification, or else from its serialization library or input format
+pub fn file_format(table: &MetastoreTable) -> Result<Arc<dyn FileFormat>> {
+    let storage = &table.storage;
+    let classification = table.parameters.get(CLASSIFICATION);
+    let serde = storage.serialization_library.as_deref().unwrap_or_default();
+    let input_format = storage.input_format.as_deref().unwrap_or_default();
+    let format = match classification {
+        Some(classification) => classification.to_lowercase(),
+        None => [serde, input_format]
+            .iter()
+            .map(|class| class.to_lowercase())
+            .find_map(|class| {
+                [
+                    "parquet",
+                    "avro",
+                    "orc",
+                    "json",
+                    "csv",
+                    "lazysimpleserde",
+                    "text",
+                ]
+                .into_iter()
+                .find(|format| class.contains(format))
+            })
+            .unwrap_or_default()
+            .to_owned(),
+    };
+
+    let format: Arc<dyn FileFormat> = match format.as_str() {
+        "parquet" => Arc::new(ParquetFormat::default()),
+        "avro" => Arc::new(AvroFormat::default()),
+        "json" => Arc::new(JsonFormat::default()),
+        "csv" | "lazysimpleserde" | "text" => {
+            // the delimiter of OpenCSVSerde and of tables classified without
+            // a serialization library is a comma, and the one of
+            // LazySimpleSerDe is ^A, unless set by the table
+            let default_delimiter = if serde.contains("OpenCSVSerde")
+                || (serde.is_empty() && classification.is_some())
+            {
+                ","
+            } else {
+                "\u{1}"
+            };
+            let delimiter = ["separatorChar", "field.delim"]
+                .iter()
+                .find_map(|key| storage.serde_parameters.get(*key))
+                .map(|delimiter| delimiter.as_str())
+                .unwrap_or(default_delimiter);
+            let delimiter = match delimiter.as_bytes() {
+                [delimiter] => *delimiter,
+                _ => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported delimiter {:?} of metastore table {}",
+                        delimiter, table.name
+                    )))
+                }
+            };
+            let has_header = match table.parameters.get(SKIP_HEADER_LINE_COUNT) {
+                None => false,
+                Some(count) if count == "0" => false,
+                Some(count) if count == "1" => true,
+                Some(count) => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported {} {} of metastore table {}",
+                        SKIP_HEADER_LINE_COUNT, count, table.name
+                    )))
+                }
+            };
+            Arc::new(
+                CsvFormat::default()
+                    .with_delimiter(delimiter)
+                    .with_has_header(has_header),
+            )
+        }
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported file format of metastore table {}: classification {:?}, \
+                serialization library {:?}, input format {:?}",
+                table.name, classification, serde, input_format
+            )))
+        }
+    };
+    Ok(format)
+}
+
+/// The Arrow type of the primitive Hive type `hive_type`
+pub fn hive_type_to_arrow(hive_type: &str) -> Result<DataType> {
+    let hive_type = hive_type.trim().to_lowercase();
+    let data_type = match hive_type.as_str() {
+        "boolean" => DataType::Boolean,
+        "tinyint" => DataType::Int8,
+        "smallint" => DataType::Int16,
+        "int" | "integer" => DataType::Int32,
+        "bigint" => DataType::Int64,
+        "float" => DataType::Float32,
+        "double" | "double precision" => DataType::Float64,
+        "string" => DataType::Utf8,
+        "binary" => DataType::Binary,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        "decimal" => DataType::Decimal128(10, 0),
+        _ if hive_type.starts_with("varchar(") || hive_type.starts_with("char(") => {
+            DataType::Utf8
+        }
+        _ if hive_type.starts_with("decimal(") && hive_type.ends_with(')') => {
+            let args = &hive_type["decimal(".len()..hive_type.len() - 1];
+            let (precision, scale) = args.split_once(',').unwrap_or((args, "0"));
+            match (precision.trim().parse(), scale.trim().parse()) {
+                (Ok(precision), Ok(scale)) => DataType::Decimal128(precision, scale),
+                _ => {
+                    return Err(DataFusionError::Plan(format!(
+                        "Invalid Hive type {}",
+                        hive_type
+                    )))
+                }
+            }
+        }
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported Hive type {}",
+                hive_type
+            )))
+        }
+    };
+    Ok(data_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::prelude::SessionContext;
+    use std::fs;
+
+    /// A metastore holding the tables of its databases in memory
+    #[derive(Debug, Default)]
+    struct MemoryMetastore {
+        databases: HashMap<String, Vec<MetastoreTable>>,
+    }
+
+    #[async_trait]
+    impl MetastoreClient for MemoryMetastore {
+        async fn databases(&self) -> Result<Vec<String>> {
+            Ok(self.databases.keys().cloned().collect())
+        }
+
+        async fn table_names(&self, database: &str) -> Result<Vec<String>> {
+            Ok(self
+                .databases
+                .get(database)
+                .map(|tables| tables.iter().map(|t| t.name.clone()).collect())
+                .unwrap_or_default())
+        }
+
+        async fn table(
+            &self,
+            database: &str,
+            name: &str,
+        ) -> Result<Option<MetastoreTable>> {
+            Ok(self
+                .databases
+                .get(database)
+                .and_then(|tables| tables.iter().find(|t| t.name == name))
+                .cloned())
+        }
+    }
+
+    fn table(storage: StorageDescriptor, parameters: &[(&str, &str)]) -> MetastoreTable {
+        MetastoreTable {
+            name: "t".to_owned(),
+            columns: vec![],
+            partition_keys: vec![],
+            storage,
+            parameters: parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    fn serde(library: &str, parameters: &[(&str, &str)]) -> StorageDescriptor {
+        StorageDescriptor {
+            serialization_library: Some(library.to_owned()),
+            serde_parameters: parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn csv_format(table: &MetastoreTable) -> (u8, bool) {
+        let format = file_format(table).unwrap();
+        let csv = format.as_any().downcast_ref::<CsvFormat>().unwrap();
+        (csv.delimiter(), csv.has_header())
+    }
+
+    #[test]
+    fn hive_types() -> Result<()> {
+        assert_eq!(hive_type_to_arrow("BIGINT")?, DataType::Int64);
+        assert_eq!(hive_type_to_arrow("varchar(20)")?, DataType::Utf8);
+        assert_eq!(
+            hive_type_to_arrow("decimal(12, 2)")?,
+            DataType::Decimal128(12, 2)
+        );
+        assert_eq!(
+            hive_type_to_arrow("timestamp")?,
+            DataType::Timestamp(TimeUnit::Nanosecond, None)
+        );
+        assert!(hive_type_to_arrow("decimal(x)").is_err());
+        assert!(hive_type_to_arrow("array<int>").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn file_formats() {
+        let parquet = table(
+            serde(
+                "org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe",
+                &[],
+            ),
+            &[],
+        );
+        let format = file_format(&parquet).unwrap();
+        assert!(format.as_any().is::<ParquetFormat>());
+
+        let json = table(serde("org.openx.data.jsonserde.JsonSerDe", &[]), &[]);
+        let format = file_format(&json).unwrap();
+        assert!(format.as_any().is::<JsonFormat>());
+
+        let text = table(
+            serde("org.apache.hadoop.hive.serde2.lazy.LazySimpleSerDe", &[]),
+            &[],
+        );
+        assert_eq!(csv_format(&text), (1, false));
+
+        let text = table(
+            serde(
+                "org.apache.hadoop.hive.serde2.lazy.LazySimpleSerDe",
+                &[("field.delim", "|")],
+            ),
+            &[(SKIP_HEADER_LINE_COUNT, "1")],
+        );
+        assert_eq!(csv_format(&text), (b'|', true));
+
+        let csv = table(
+            serde("org.apache.hadoop.hive.serde2.OpenCSVSerde", &[]),
+            &[],
+        );
+        assert_eq!(csv_format(&csv), (b',', false));
+
+        let classified = table(StorageDescriptor::default(), &[(CLASSIFICATION, "CSV")]);
+        assert_eq!(csv_format(&classified), (b',', false));
+
+        let orc = table(serde("org.apache.hadoop.hive.ql.io.orc.OrcSerde", &[]), &[]);
+        let err = file_format(&orc).unwrap_err();
+        assert!(err.to_string().contains("Unsupported file format"));
+    }
+
+    #[tokio::test]
+    async fn query_metastore_table() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for (year, content) in [("2021", "1|a\n2|b\n"), ("2022", "3|c\n")] {
+            let partition = dir.path().join(format!("year={}", year));
+            fs::create_dir(&partition)?;
+            fs::write(partition.join("part-0"), content)?;
+        }
+
+        let orders = MetastoreTable {
+            name: "orders".to_owned(),
+            columns: vec![
+                MetastoreColumn::new("id", "bigint"),
+                MetastoreColumn::new("item", "string"),
+            ],
+            partition_keys: vec![MetastoreColumn::new("year", "string")],
+            storage: StorageDescriptor {
+                location: dir.path().to_str().unwrap().to_owned(),
+                input_format: Some("org.apache.hadoop.mapred.TextInputFormat".to_owned()),
+                serialization_library: Some(
+                    "org.apache.hadoop.hive.serde2.lazy.LazySimpleSerDe".to_owned(),
+                ),
+                serde_parameters: HashMap::from([(
+                    "field.delim".to_owned(),
+                    "|".to_owned(),
+                )]),
+            },
+            parameters: HashMap::new(),
+        };
+        let metastore = MemoryMetastore {
+            databases: HashMap::from([("sales".to_owned(), vec![orders])]),
+        };
+
+        let ctx = SessionContext::new();
+        ctx.register_async_catalog(
+            "hive",
+            Arc::new(MetastoreCatalogProvider::new(Arc::new(metastore))),
+        );
+        let batches = ctx
+            .sql("SELECT id, item, year FROM hive.sales.orders WHERE id > 1")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+----+------+------+",
+            "| id | item | year |",
+            "+----+------+------+",
+            "| 2  | b    | 2021 |",
+            "| 3  | c    | 2022 |",
+            "+----+------+------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        let err = ctx
+            .sql("SELECT * FROM hive.sales.missing")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"));
+        Ok(())
+    }
+}
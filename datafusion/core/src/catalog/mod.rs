@@ -19,8 +19,10 @@
 //! of table namespacing concepts, including catalogs and schemas.
 
 #![allow(clippy::module_inception)]
+pub mod async_catalog;
 pub mod catalog;
 pub mod information_schema;
+pub mod metastore;
 pub mod schema;
 
 pub use datafusion_sql::{ResolvedTableReference, TableReference};
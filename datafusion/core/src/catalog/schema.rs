@@ -23,8 +23,10 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::datasource::policy::with_table_policy;
 use crate::datasource::TableProvider;
 use crate::error::{DataFusionError, Result};
+use crate::logical_expr::TablePolicy;
 
 /// Represents a schema, comprising a number of named tables.
 pub trait SchemaProvider: Sync + Send {
@@ -52,6 +54,7 @@ pub trait SchemaProvider: Sync + Send {
     }
 
     /// If supported by the implementation, removes an existing table from this schema and returns it.
+    /// The policy registered for the table, if any, is removed as well.
     /// If no table of that name exists, returns Ok(None).
     #[allow(unused_variables)]
     fn deregister_table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
@@ -64,11 +67,44 @@ pub trait SchemaProvider: Sync + Send {
     /// If no matched table in the schema provider, return false.
     /// Otherwise, return true.
     fn table_exist(&self, name: &str) -> bool;
+
+    /// Retrieves the policy enforced on every scan of a table, if there is one.
+    ///
+    /// The tables returned by [`Self::table`] should enforce their policy,
+    /// as a [`PolicyTable`](crate::datasource::PolicyTable), otherwise the
+    /// [`SessionContext`](crate::execution::context::SessionContext) only
+    /// enforces it on the tables it looks up by name.
+    #[allow(unused_variables)]
+    fn table_policy(&self, name: &str) -> Option<Arc<dyn TablePolicy>> {
+        None
+    }
+
+    /// If supported by the implementation, sets the policy enforced on every
+    /// scan of a table, returning the policy it replaces.
+    /// The policy is removed when the table is deregistered.
+    #[allow(unused_variables)]
+    fn register_table_policy(
+        &self,
+        name: String,
+        policy: Arc<dyn TablePolicy>,
+    ) -> Result<Option<Arc<dyn TablePolicy>>> {
+        Err(DataFusionError::Execution(
+            "schema provider does not support table policies".to_owned(),
+        ))
+    }
+
+    /// The directory of the tables created in this schema without a
+    /// location, if there is one
+    fn location(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Simple in-memory implementation of a schema.
 pub struct MemorySchemaProvider {
     tables: RwLock<HashMap<String, Arc<dyn TableProvider>>>,
+    policies: RwLock<HashMap<String, Arc<dyn TablePolicy>>>,
+    location: Option<String>,
 }
 
 impl MemorySchemaProvider {
@@ -76,8 +112,17 @@ impl MemorySchemaProvider {
     pub fn new() -> Self {
         Self {
             tables: RwLock::new(HashMap::new()),
+            policies: RwLock::new(HashMap::new()),
+            location: None,
         }
     }
+
+    /// Set the directory of the tables created in this schema without a
+    /// location
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
 }
 
 impl Default for MemorySchemaProvider {
@@ -97,8 +142,8 @@ impl SchemaProvider for MemorySchemaProvider {
     }
 
     fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
-        let tables = self.tables.read();
-        tables.get(name).cloned()
+        let table = self.tables.read().get(name).cloned()?;
+        Some(with_table_policy(self, name, table))
     }
 
     fn register_table(
@@ -118,6 +163,7 @@ impl SchemaProvider for MemorySchemaProvider {
 
     fn deregister_table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
         let mut tables = self.tables.write();
+        self.policies.write().remove(name);
         Ok(tables.remove(name))
     }
 
@@ -125,20 +171,44 @@ impl SchemaProvider for MemorySchemaProvider {
         let tables = self.tables.read();
         tables.contains_key(name)
     }
+
+    fn table_policy(&self, name: &str) -> Option<Arc<dyn TablePolicy>> {
+        let policies = self.policies.read();
+        policies.get(name).cloned()
+    }
+
+    fn register_table_policy(
+        &self,
+        name: String,
+        policy: Arc<dyn TablePolicy>,
+    ) -> Result<Option<Arc<dyn TablePolicy>>> {
+        let mut policies = self.policies.write();
+        Ok(policies.insert(name, policy))
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use arrow::datatypes::Schema;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
 
     use crate::assert_batches_eq;
     use crate::catalog::catalog::{CatalogProvider, MemoryCatalogProvider};
     use crate::catalog::schema::{MemorySchemaProvider, SchemaProvider};
     use crate::datasource::empty::EmptyTable;
     use crate::datasource::listing::{ListingTable, ListingTableConfig, ListingTableUrl};
+    use crate::datasource::MemTable;
+    use crate::error::Result;
+    use crate::logical_expr::{col, lit, Expr, TablePolicy};
     use crate::prelude::SessionContext;
+    use std::collections::HashMap;
 
     #[tokio::test]
     async fn test_mem_provider() {
@@ -216,4 +286,145 @@ mod tests {
         ];
         assert_batches_eq!(expected, &actual);
     }
+
+    /// Only shows the rows of one region, with the `ssn` column redacted
+    struct RegionPolicy {}
+
+    impl TablePolicy for RegionPolicy {
+        fn filters(&self) -> Result<Vec<Expr>> {
+            Ok(vec![col("region").eq(lit("EU"))])
+        }
+
+        fn column_masks(&self) -> Result<HashMap<String, Expr>> {
+            Ok(HashMap::from([("ssn".to_string(), lit("***"))]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_table_policy() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("region", DataType::Utf8, false),
+            Field::new("ssn", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["EU", "US", "EU"])),
+                Arc::new(StringArray::from(vec!["111", "222", "333"])),
+            ],
+        )?;
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))?;
+        assert!(ctx
+            .register_table_policy("t", Arc::new(RegionPolicy {}))?
+            .is_none());
+
+        let expected = vec![
+            "+----+--------+-----+",
+            "| id | region | ssn |",
+            "+----+--------+-----+",
+            "| 1  | EU     | *** |",
+            "| 3  | EU     | *** |",
+            "+----+--------+-----+",
+        ];
+        let actual = ctx
+            .sql("SELECT * FROM t ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        assert_batches_eq!(expected, &actual);
+        let actual = ctx.table("t")?.sort(vec![col("id").sort(true, false)])?;
+        assert_batches_eq!(expected, &actual.collect().await?);
+
+        // the predicates of queries only see the masked columns of visible rows
+        let actual = ctx
+            .sql("SELECT x.id FROM t AS x WHERE x.ssn = '333' OR region = 'US'")
+            .await?
+            .collect()
+            .await?;
+        assert_batches_eq!(vec!["++", "++"], &actual);
+
+        // the tables looked up in the catalog enforce the policy
+        let table = ctx
+            .catalog("datafusion")
+            .unwrap()
+            .schema("public")
+            .unwrap()
+            .table("t")
+            .unwrap();
+        let actual = ctx
+            .read_table(table)?
+            .sort(vec![col("id").sort(true, false)])?;
+        assert_batches_eq!(expected, &actual.collect().await?);
+
+        // the predicates of queries are not pushed down to the scans
+        let plan = ctx
+            .sql("SELECT id FROM t WHERE ssn = '111'")
+            .await?
+            .to_logical_plan()?;
+        let formatted = format!("{:?}", plan);
+        assert!(
+            formatted.contains("Filter: t.ssn = Utf8(\"111\")"),
+            "{}",
+            formatted
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_table_policy_after_deregister() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("region", DataType::Utf8, false),
+            Field::new("ssn", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["EU", "US"])),
+                Arc::new(StringArray::from(vec!["111", "222"])),
+            ],
+        )?;
+        let table = || MemTable::try_new(schema.clone(), vec![vec![batch.clone()]]);
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(table()?))?;
+        ctx.register_table_policy("t", Arc::new(RegionPolicy {}))?;
+
+        // replacing the table keeps its policy
+        ctx.sql("CREATE OR REPLACE TABLE t AS SELECT * FROM t")
+            .await?
+            .collect()
+            .await?;
+        let actual = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        let expected = vec![
+            "+----+--------+-----+",
+            "| id | region | ssn |",
+            "+----+--------+-----+",
+            "| 1  | EU     | *** |",
+            "+----+--------+-----+",
+        ];
+        assert_batches_eq!(expected, &actual);
+
+        // a table registered again under the same name has no policy
+        assert!(ctx.deregister_table("t")?.is_some());
+        ctx.register_table("t", Arc::new(table()?))?;
+        let actual = ctx
+            .sql("SELECT * FROM t ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+----+--------+-----+",
+            "| id | region | ssn |",
+            "+----+--------+-----+",
+            "| 1  | EU     | 111 |",
+            "| 2  | US     | 222 |",
+            "+----+--------+-----+",
+        ];
+        assert_batches_eq!(expected, &actual);
+        Ok(())
+    }
 }
@@ -49,6 +49,9 @@ pub const OPT_COALESCE_TARGET_BATCH_SIZE: &str =
 /// Configuration option "datafusion.execution.time_zone"
 pub const OPT_TIME_ZONE: &str = "datafusion.execution.time_zone";
 
+/// Configuration option "datafusion.execution.output_coercion"
+pub const OPT_OUTPUT_COERCION: &str = "datafusion.execution.output_coercion";
+
 /// Configuration option "datafusion.execution.parquet.pushdown_filters"
 pub const OPT_PARQUET_PUSHDOWN_FILTERS: &str =
     "datafusion.execution.parquet.pushdown_filters";
@@ -65,9 +68,75 @@ pub const OPT_PARQUET_ENABLE_PAGE_INDEX: &str =
 pub const OPT_OPTIMIZER_SKIP_FAILED_RULES: &str =
     "datafusion.optimizer.skip_failed_rules";
 
+/// Configuration option "datafusion.optimizer.rely_on_constraints"
+pub const OPT_OPTIMIZER_RELY_ON_CONSTRAINTS: &str =
+    "datafusion.optimizer.rely_on_constraints";
+
+/// Configuration option "datafusion.optimizer.materialized_view_rewrite"
+pub const OPT_MATERIALIZED_VIEW_REWRITE: &str =
+    "datafusion.optimizer.materialized_view_rewrite";
+
+/// Configuration option "datafusion.optimizer.nested_projection_pushdown"
+pub const OPT_NESTED_PROJECTION_PUSHDOWN: &str =
+    "datafusion.optimizer.nested_projection_pushdown";
+
+/// Configuration option "datafusion.optimizer.physical_projection_pushdown"
+pub const OPT_PHYSICAL_PROJECTION_PUSHDOWN: &str =
+    "datafusion.optimizer.physical_projection_pushdown";
+
+/// Configuration option "datafusion.optimizer.eliminate_sort_repartition"
+pub const OPT_ELIMINATE_SORT_REPARTITION: &str =
+    "datafusion.optimizer.eliminate_sort_repartition";
+
 /// Configuration option "datafusion.optimizer.max_passes"
 pub const OPT_OPTIMIZER_MAX_PASSES: &str = "datafusion.optimizer.max_passes";
 
+/// Configuration option "datafusion.execution.adaptive.enabled"
+pub const OPT_ADAPTIVE_ENABLED: &str = "datafusion.execution.adaptive.enabled";
+
+/// Configuration option "datafusion.execution.adaptive.broadcast_threshold"
+pub const OPT_ADAPTIVE_BROADCAST_THRESHOLD: &str =
+    "datafusion.execution.adaptive.broadcast_threshold";
+
+/// Configuration option "datafusion.execution.skip_partial_aggregation_probe_rows"
+pub const OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS: &str =
+    "datafusion.execution.skip_partial_aggregation_probe_rows";
+
+/// Configuration option "datafusion.execution.skip_partial_aggregation_probe_ratio_threshold"
+pub const OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD: &str =
+    "datafusion.execution.skip_partial_aggregation_probe_ratio_threshold";
+
+/// Configuration option "datafusion.execution.object_store.max_retries"
+pub const OPT_OBJECT_STORE_MAX_RETRIES: &str =
+    "datafusion.execution.object_store.max_retries";
+
+/// Configuration option "datafusion.execution.object_store.retry_backoff_ms"
+pub const OPT_OBJECT_STORE_RETRY_BACKOFF_MS: &str =
+    "datafusion.execution.object_store.retry_backoff_ms";
+
+/// Configuration option "datafusion.execution.skip_corrupt_files"
+pub const OPT_SKIP_CORRUPT_FILES: &str = "datafusion.execution.skip_corrupt_files";
+
+/// Configuration option "datafusion.execution.use_file_indexes"
+pub const OPT_USE_FILE_INDEXES: &str = "datafusion.execution.use_file_indexes";
+
+/// Configuration option "datafusion.execution.check_constraints.rejects_table"
+pub const OPT_CHECK_CONSTRAINTS_REJECTS_TABLE: &str =
+    "datafusion.execution.check_constraints.rejects_table";
+
+/// Configuration option "datafusion.execution.check_constraints.validate_scans"
+pub const OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS: &str =
+    "datafusion.execution.check_constraints.validate_scans";
+
+/// Configuration option "datafusion.execution.random_seed"
+pub const OPT_RANDOM_SEED: &str = "datafusion.execution.random_seed";
+
+/// Configuration option "datafusion.sql_parser.max_depth"
+pub const OPT_SQL_PARSER_MAX_DEPTH: &str = "datafusion.sql_parser.max_depth";
+
+/// Configuration option "datafusion.catalog.search_path"
+pub const OPT_SEARCH_PATH: &str = "datafusion.catalog.search_path";
+
 /// Definition of a configuration option
 pub struct ConfigDefinition {
     /// key used to identifier this configuration option
@@ -140,6 +209,20 @@ impl ConfigDefinition {
         )
     }
 
+    /// Create a configuration option definition with a f64 value
+    pub fn new_f64(
+        key: impl Into<String>,
+        description: impl Into<String>,
+        default_value: f64,
+    ) -> Self {
+        Self::new(
+            key,
+            description,
+            DataType::Float64,
+            ScalarValue::Float64(Some(default_value)),
+        )
+    }
+
     /// Create a configuration option definition with a string value
     pub fn new_string(
         key: impl Into<String>,
@@ -212,12 +295,113 @@ impl BuiltInConfigs {
                           configuration setting '{}'.", OPT_COALESCE_BATCHES),
                  4096,
             ),
+            ConfigDefinition::new_bool(
+                OPT_ADAPTIVE_ENABLED,
+                "When set to true, queries are executed one stage at a time, materializing the \
+                output of each repartition and re-optimizing the rest of the plan using the \
+                observed row counts before continuing.",
+                false,
+            ),
+            ConfigDefinition::new_u64(
+                OPT_ADAPTIVE_BROADCAST_THRESHOLD,
+                format!("Maximum number of rows of a completed join input for the join to be \
+                switched from a partitioned hash join to one that collects that input to \
+                every partition. Only used when '{}' is enabled.", OPT_ADAPTIVE_ENABLED),
+                10000,
+            ),
+            ConfigDefinition::new_u64(
+                OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS,
+                format!("Number of input rows a partial aggregation aggregates before comparing \
+                its number of groups to its number of input rows, using the configuration \
+                setting '{}'.", OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD),
+                100000,
+            ),
+            ConfigDefinition::new_f64(
+                OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD,
+                "Ratio of the number of groups to the number of input rows of a partial \
+                aggregation above which it stops aggregating rows across batches, and passes \
+                its input rows on to the final aggregation as partial states, or only \
+                aggregates the rows of each batch if one of its aggregates cannot convert \
+                rows. Values above 1 never skip the partial aggregation.",
+                0.8,
+            ),
+            ConfigDefinition::new_u64(
+                OPT_OBJECT_STORE_MAX_RETRIES,
+                "Number of times the scans retry a read of an object store failing with a \
+                transient error, such as a 503 response of S3, a throttling response or a \
+                timeout, before failing the query. Missing files, denied accesses and the \
+                other errors are not retried.",
+                3,
+            ),
+            ConfigDefinition::new_u64(
+                OPT_OBJECT_STORE_RETRY_BACKOFF_MS,
+                format!("Milliseconds the scans wait before the first retry of a read of an \
+                object store, doubled for each next retry. Only used when '{}' is not 0.",
+                OPT_OBJECT_STORE_MAX_RETRIES),
+                100,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_SKIP_CORRUPT_FILES,
+                "When set to true, the scans skip the files they fail to open or read, such as \
+                missing or corrupt files, instead of failing the query, and count them in their \
+                files_skipped metric. The rows read from a file before it fails are dropped \
+                with it, as the batches of each file are held back until it is fully read.",
+                false,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_USE_FILE_INDEXES,
+                "When set to true, the scans of listing tables consult the index files built by \
+                ANALYZE TABLE to skip the files that contain no rows matching their filters. \
+                The index of a file modified since ANALYZE TABLE is ignored.",
+                false,
+            ),
+            ConfigDefinition::new(
+                OPT_CHECK_CONSTRAINTS_REJECTS_TABLE,
+                "Name of the table receiving the rows which violate the check constraints of \
+                the tables they are inserted into, with the same columns as those tables. \
+                When not set, inserting rows violating a check constraint fails.",
+                DataType::Utf8,
+                ScalarValue::Utf8(None),
+            ),
+            ConfigDefinition::new_bool(
+                OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS,
+                "When set to true, the scans of tables with check constraints count the rows \
+                violating them in their check_violations metric, labeled with the constraint. \
+                Only the constraints on the scanned columns are validated.",
+                false,
+            ),
+            ConfigDefinition::new(
+                OPT_RANDOM_SEED,
+                "Seed of the random values of the queries, making volatile random functions \
+                such as random() return the same values each time a query runs in a single \
+                partition. When not set, the values are different for each query.",
+                DataType::UInt64,
+                ScalarValue::UInt64(None),
+            ),
+            ConfigDefinition::new_bool(
+                OPT_OUTPUT_COERCION,
+                "When set to true, the results of the queries are converted into types that \
+                clients with a limited support of Arrow, such as ODBC drivers, can read: \
+                dictionaries are unpacked, large strings and binaries become strings and \
+                binaries, and lists, structs and maps become JSON strings.",
+                false,
+            ),
+            ConfigDefinition::new(
+                OPT_SEARCH_PATH,
+                "Comma-separated list of the schemas of the default catalog where unqualified \
+                table names are looked up, in order. Tables are created in the first of them \
+                that exists. When not set, only the default schema is used.",
+                DataType::Utf8,
+                ScalarValue::Utf8(None),
+            ),
             ConfigDefinition::new_string(
                 OPT_TIME_ZONE,
-                "The session time zone which some function require \
-                e.g. EXTRACT(HOUR from SOME_TIME) shift the underline datetime according to the time zone,
-                then extract the hour.",
-                "UTC".into()
+                "The time zone of the session, set with SET TIME ZONE: a name of the IANA time \
+                zone database such as 'Asia/Tokyo', or an offset such as '+09:00'. The current \
+                date and time are read in it, date_trunc truncates timestamps with a time zone \
+                at its boundaries, and timestamps without a time zone are cast into ones with \
+                a time zone as its local times.",
+                "UTC".into(),
             ),
             ConfigDefinition::new_bool(
                 OPT_PARQUET_PUSHDOWN_FILTERS,
@@ -244,12 +428,55 @@ impl BuiltInConfigs {
                 messages if any optimization rules produce errors and then proceed to the next \
                 rule. When set to false, any rules that produce errors will cause the query to fail.",
                 true
+            ),
+            ConfigDefinition::new_bool(
+                OPT_OPTIMIZER_RELY_ON_CONSTRAINTS,
+                "When set to true, the optimizer relies on the PRIMARY KEY, UNIQUE and FOREIGN \
+                KEY constraints of the tables to remove DISTINCT, GROUP BY and joins. The \
+                constraints are not enforced, so the results are wrong for data violating \
+                them.",
+                false,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_MATERIALIZED_VIEW_REWRITE,
+                "When set to true, the optimizer rewrites the parts of the queries computing \
+                the results of a materialized view to read the view instead, as long as the \
+                files of the listing tables read by the view did not change since it was \
+                refreshed. The views reading other tables are never used.",
+                false,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_NESTED_PROJECTION_PUSHDOWN,
+                "When set to true, the physical optimizer pushes the struct fields accessed by \
+                the queries down to the Parquet scans, which then only read these fields.",
+                true,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_PHYSICAL_PROJECTION_PUSHDOWN,
+                "When set to true, the physical optimizer merges adjacent projections, removes \
+                the projections which do not change their input, and pushes the projections of \
+                columns down to the file scans.",
+                true,
+            ),
+            ConfigDefinition::new_bool(
+                OPT_ELIMINATE_SORT_REPARTITION,
+                "When set to true, the physical optimizer removes the sorts and the hash \
+                repartitions whose requirements are already satisfied by the ordering, the \
+                partitioning and the equivalent columns of their input.",
+                true,
             ),
              ConfigDefinition::new_u64(
                  OPT_OPTIMIZER_MAX_PASSES,
                  "Number of times that the optimizer will attempt to optimize the plan",
                  3
-             )]
+             ),
+            ConfigDefinition::new_u64(
+                OPT_SQL_PARSER_MAX_DEPTH,
+                "Maximum nesting of the parentheses and expressions of the SQL statements. \
+                 Parsing and planning the statements nested deeper fail with an error instead \
+                 of overflowing the stack.",
+                datafusion_sql::parser::DEFAULT_MAX_DEPTH as u64,
+            )]
         }
     }
 
@@ -360,6 +587,11 @@ impl ConfigOptions {
         self.set(key, ScalarValue::UInt64(Some(value)))
     }
 
+    /// set a `f64` configuration option
+    pub fn set_f64(&mut self, key: &str, value: f64) {
+        self.set(key, ScalarValue::Float64(Some(value)))
+    }
+
     /// get a configuration option
     pub fn get(&self, key: &str) -> Option<ScalarValue> {
         self.options.get(key).cloned()
@@ -375,6 +607,11 @@ impl ConfigOptions {
         get_conf_value!(self, UInt64, key, "u64")
     }
 
+    /// get a f64 configuration option
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        get_conf_value!(self, Float64, key, "f64")
+    }
+
     /// get a string configuration option
     pub fn get_string(&self, key: &str) -> Option<String> {
         get_conf_value!(self, Utf8, key, "string")
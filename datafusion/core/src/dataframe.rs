@@ -17,31 +17,42 @@
 
 //! DataFrame API for building and executing query plans.
 
+use crate::arrow::compute::SortOptions;
 use crate::arrow::datatypes::Schema;
 use crate::arrow::datatypes::SchemaRef;
 use crate::arrow::record_batch::RecordBatch;
 use crate::arrow::util::pretty;
+use crate::config::OPT_ADAPTIVE_ENABLED;
+use crate::datasource::file_format::file_type::FileType;
+use crate::datasource::listing::bucket::{plan_to_bucketed, Bucketing};
 use crate::datasource::{MemTable, TableProvider};
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::execution::{
+    audit::QueryAudit,
     context::{SessionState, TaskContext},
     FunctionRegistry,
 };
 use crate::logical_expr::{
-    col, utils::find_window_exprs, Expr, JoinType, LogicalPlan, LogicalPlanBuilder,
-    Partitioning, TableType,
+    col, utils::find_window_exprs, DedupKeep, Expr, JoinType, LogicalPlan,
+    LogicalPlanBuilder, Partitioning, TableType,
 };
+use crate::physical_optimizer::adaptive::{execute_adaptive, execute_adaptive_stream};
 use crate::physical_plan::file_format::{plan_to_csv, plan_to_json, plan_to_parquet};
+use crate::physical_plan::planner::create_physical_sort_expr;
+use crate::physical_plan::sorts::sort::SortExec;
 use crate::physical_plan::SendableRecordBatchStream;
 use crate::physical_plan::{collect, collect_partitioned};
 use crate::physical_plan::{execute_stream, execute_stream_partitioned, ExecutionPlan};
 use crate::prelude::SessionContext;
+use crate::pretty::{pretty_format_batches_with_options, FormatOptions};
+use crate::rows::Rows;
 use async_trait::async_trait;
-use datafusion_common::{Column, DFSchema};
+use datafusion_common::{Column, DFSchema, ScalarValue};
 use datafusion_expr::TableProviderFilterPushDown;
 use parking_lot::RwLock;
 use parquet::file::properties::WriterProperties;
 use std::any::Any;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// DataFrame represents a logical set of rows with the same named columns.
@@ -73,6 +84,11 @@ use std::sync::Arc;
 pub struct DataFrame {
     session_state: Arc<RwLock<SessionState>>,
     plan: LogicalPlan,
+    /// SQL text the plan was created from, reported to the audit hook
+    sql: Option<String>,
+    /// Whether the executions are reported to the audit hook, which they
+    /// are not for the results of the statements already reported
+    audit: bool,
 }
 
 impl DataFrame {
@@ -81,30 +97,78 @@ impl DataFrame {
         Self {
             session_state,
             plan: plan.clone(),
+            sql: None,
+            audit: true,
         }
     }
 
+    /// Returns a copy of this DataFrame recording that it was planned from `sql`
+    pub(crate) fn with_sql(&self, sql: &str) -> Self {
+        Self {
+            session_state: self.session_state.clone(),
+            plan: self.plan.clone(),
+            sql: Some(sql.to_owned()),
+            audit: self.audit,
+        }
+    }
+
+    /// Returns this DataFrame without reporting its executions to the audit
+    /// hook
+    pub(crate) fn without_audit(mut self) -> Self {
+        self.audit = false;
+        self
+    }
+
+    /// Starts auditing an execution of this DataFrame, if the session has an
+    /// audit hook
+    fn start_audit(&self) -> Option<Arc<QueryAudit>> {
+        if !self.audit {
+            return None;
+        }
+        QueryAudit::start(
+            &self.session_state.read(),
+            self.sql.as_deref(),
+            Some(&self.plan),
+        )
+    }
+
     /// Create a physical plan
     pub async fn create_physical_plan(&self) -> Result<Arc<dyn ExecutionPlan>> {
-        // this function is copied from SessionContext function of the
-        // same name
-        let state_cloned = {
-            let mut state = self.session_state.write();
-            state.execution_props.start_execution();
-
-            // We need to clone `state` to release the lock that is not `Send`. We could
-            // make the lock `Send` by using `tokio::sync::Mutex`, but that would require to
-            // propagate async even to the `LogicalPlan` building methods.
-            // Cloning `state` here is fine as we then pass it as immutable `&state`, which
-            // means that we avoid write consistency issues as the cloned version will not
-            // be written to. As for eventual modifications that would be applied to the
-            // original state after it has been cloned, they will not be picked up by the
-            // clone but that is okay, as it is equivalent to postponing the state update
-            // by keeping the lock until the end of the function scope.
-            state.clone()
-        };
+        Ok(self.plan_statement().await?.0)
+    }
+
+    /// Creates the physical plan of an execution of this DataFrame, with the
+    /// snapshot of the session state it is executed with, see
+    /// [`SessionState::start_statement`]
+    async fn plan_statement(&self) -> Result<(Arc<dyn ExecutionPlan>, SessionState)> {
+        // the snapshot releases the lock that is not `Send`
+        let state = self.session_state.read().start_statement()?;
+        let plan = state.create_physical_plan(&self.plan).await?;
+        Ok((plan, state))
+    }
 
-        state_cloned.create_physical_plan(&self.plan).await
+    /// Returns true if adaptive execution is enabled
+    fn adaptive(&self, task_ctx: &TaskContext) -> bool {
+        task_ctx
+            .session_config()
+            .config_options
+            .read()
+            .get_bool(OPT_ADAPTIVE_ENABLED)
+            .unwrap_or_default()
+    }
+
+    /// Execute the stages of `plan` ahead of the rest of the plan when adaptive
+    /// execution is enabled, see [execute_adaptive]
+    async fn execute_stages(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        task_ctx: Arc<TaskContext>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if self.adaptive(&task_ctx) {
+            execute_adaptive(plan, task_ctx).await
+        } else {
+            Ok(plan)
+        }
     }
 
     /// Filter the DataFrame by column. Returns a new DataFrame only containing the
@@ -299,6 +363,39 @@ impl DataFrame {
         )))
     }
 
+    /// Keep a single row of each group of rows with the same values of the
+    /// expressions `on`: the first or the last row of the group in the order
+    /// of the sort expressions `order_by`. When `order_by` is empty, the row is
+    /// the first or the last one read, which is unspecified when the input is
+    /// read in parallel.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::logical_expr::DedupKeep;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+    /// // the row with the greatest `c` of each `a`
+    /// let df = df.dedup(vec![col("a")], DedupKeep::Last, vec![col("c").sort(true, false)])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dedup(
+        &self,
+        on: Vec<Expr>,
+        keep: DedupKeep,
+        order_by: Vec<Expr>,
+    ) -> Result<Arc<DataFrame>> {
+        Ok(Arc::new(DataFrame::new(
+            self.session_state.clone(),
+            &LogicalPlanBuilder::from(self.plan.clone())
+                .dedup(on, keep, order_by)?
+                .build()?,
+        )))
+    }
+
     /// Sort the DataFrame by the specified sorting expressions. Any expression can be turned into
     /// a sort expression by calling its [sort](../logical_plan/enum.Expr.html#method.sort) method.
     ///
@@ -402,9 +499,40 @@ impl DataFrame {
     /// # }
     /// ```
     pub async fn collect(&self) -> Result<Vec<RecordBatch>> {
-        let plan = self.create_physical_plan().await?;
-        let task_ctx = Arc::new(TaskContext::from(&self.session_state.read().clone()));
-        collect(plan, task_ctx).await
+        let audit = self.start_audit();
+        let result = async {
+            let (plan, state) = self.plan_statement().await?;
+            let task_ctx = Arc::new(TaskContext::from(&state));
+            let plan = self.execute_stages(plan, task_ctx.clone()).await?;
+            collect(plan, task_ctx).await
+        }
+        .await;
+        if let Some(audit) = audit {
+            audit.record(&result, |batches| {
+                batches.iter().map(|b| b.num_rows()).sum()
+            });
+        }
+        result
+    }
+
+    /// Executes this DataFrame and collects all results into [`Rows`], to
+    /// read them one row at a time.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+    /// for row in &df.rows().await? {
+    ///     let a: i64 = row.get_by_name("a")?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rows(&self) -> Result<Rows> {
+        Ok(Rows::new(self.collect().await?))
     }
 
     /// Print results.
@@ -443,6 +571,31 @@ impl DataFrame {
         Ok(pretty::print_batches(&results)?)
     }
 
+    /// Print results, formatted as configured by `options`, such as markdown
+    /// tables with a maximum column width.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// use datafusion::pretty::{FormatOptions, TableFormat};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+    /// let options = FormatOptions::new()
+    ///     .with_format(TableFormat::Markdown)
+    ///     .with_max_column_width(20)
+    ///     .with_row_count(true);
+    /// df.show_with_options(&options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn show_with_options(&self, options: &FormatOptions) -> Result<()> {
+        let results = self.collect().await?;
+        println!("{}", pretty_format_batches_with_options(&results, options)?);
+        Ok(())
+    }
+
     /// Executes this DataFrame and returns a stream over a single partition
     ///
     /// ```
@@ -457,9 +610,24 @@ impl DataFrame {
     /// # }
     /// ```
     pub async fn execute_stream(&self) -> Result<SendableRecordBatchStream> {
-        let plan = self.create_physical_plan().await?;
-        let task_ctx = Arc::new(TaskContext::from(&self.session_state.read().clone()));
-        execute_stream(plan, task_ctx).await
+        let audit = self.start_audit();
+        let result = async {
+            let (plan, state) = self.plan_statement().await?;
+            let task_ctx = Arc::new(TaskContext::from(&state));
+            if self.adaptive(&task_ctx) {
+                Ok(execute_adaptive_stream(plan, task_ctx))
+            } else {
+                execute_stream(plan, task_ctx).await
+            }
+        }
+        .await;
+        match audit {
+            Some(audit) => {
+                audit.record(&result, |_| 0);
+                Ok(audit.stream(result?))
+            }
+            None => result,
+        }
     }
 
     /// Executes this DataFrame and collects all results into a vector of vector of RecordBatch
@@ -477,9 +645,20 @@ impl DataFrame {
     /// # }
     /// ```
     pub async fn collect_partitioned(&self) -> Result<Vec<Vec<RecordBatch>>> {
-        let plan = self.create_physical_plan().await?;
-        let task_ctx = Arc::new(TaskContext::from(&self.session_state.read().clone()));
-        collect_partitioned(plan, task_ctx).await
+        let audit = self.start_audit();
+        let result = async {
+            let (plan, state) = self.plan_statement().await?;
+            let task_ctx = Arc::new(TaskContext::from(&state));
+            let plan = self.execute_stages(plan, task_ctx.clone()).await?;
+            collect_partitioned(plan, task_ctx).await
+        }
+        .await;
+        if let Some(audit) = audit {
+            audit.record(&result, |partitions| {
+                partitions.iter().flatten().map(|b| b.num_rows()).sum()
+            });
+        }
+        result
     }
 
     /// Executes this DataFrame and returns one stream per partition.
@@ -498,9 +677,22 @@ impl DataFrame {
     pub async fn execute_stream_partitioned(
         &self,
     ) -> Result<Vec<SendableRecordBatchStream>> {
-        let plan = self.create_physical_plan().await?;
-        let task_ctx = Arc::new(TaskContext::from(&self.session_state.read().clone()));
-        execute_stream_partitioned(plan, task_ctx).await
+        let audit = self.start_audit();
+        let result = async {
+            let (plan, state) = self.plan_statement().await?;
+            let task_ctx = Arc::new(TaskContext::from(&state));
+            let plan = self.execute_stages(plan, task_ctx.clone()).await?;
+            execute_stream_partitioned(plan, task_ctx).await
+        }
+        .await;
+        match audit {
+            Some(audit) => {
+                audit.record(&result, |_| 0);
+                // reported once the streams of all partitions are dropped
+                Ok(result?.into_iter().map(|s| audit.stream(s)).collect())
+            }
+            None => result,
+        }
     }
 
     /// Returns the schema describing the output of this DataFrame in terms of columns returned,
@@ -529,7 +721,7 @@ impl DataFrame {
     /// Return the optimized logical plan represented by this DataFrame.
     pub fn to_logical_plan(&self) -> Result<LogicalPlan> {
         // Optimize the plan first for better UX
-        let state = self.session_state.read().clone();
+        let state = self.session_state.read().start_statement()?;
         state.optimize(&self.plan)
     }
 
@@ -555,6 +747,36 @@ impl DataFrame {
         Ok(Arc::new(DataFrame::new(self.session_state.clone(), &plan)))
     }
 
+    /// Replace the placeholders of the plan, created with [`placeholder`](crate::prelude::placeholder),
+    /// with literals of the values bound to their ids.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::arrow::datatypes::DataType;
+    /// # use datafusion::scalar::ScalarValue;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+    /// let df = df
+    ///     .filter(col("a").gt(placeholder("min_a", DataType::Int64)))?
+    ///     .with_param_values(vec![("min_a", ScalarValue::Int64(Some(1)))])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_param_values(
+        &self,
+        param_values: Vec<(&str, ScalarValue)>,
+    ) -> Result<Arc<DataFrame>> {
+        let param_values = param_values
+            .into_iter()
+            .map(|(id, value)| (id.to_string(), value))
+            .collect::<HashMap<_, _>>();
+        let plan = self.plan.with_param_values(&param_values)?;
+        Ok(Arc::new(DataFrame::new(self.session_state.clone(), &plan)))
+    }
+
     /// Return a `FunctionRegistry` used to plan udf's calls
     ///
     /// ```
@@ -621,8 +843,7 @@ impl DataFrame {
 
     /// Write a `DataFrame` to a CSV file.
     pub async fn write_csv(&self, path: &str) -> Result<()> {
-        let plan = self.create_physical_plan().await?;
-        let state = self.session_state.read().clone();
+        let (plan, state) = self.plan_statement().await?;
         plan_to_csv(&state, plan, path).await
     }
 
@@ -632,18 +853,70 @@ impl DataFrame {
         path: &str,
         writer_properties: Option<WriterProperties>,
     ) -> Result<()> {
-        let plan = self.create_physical_plan().await?;
-        let state = self.session_state.read().clone();
+        let (plan, state) = self.plan_statement().await?;
         plan_to_parquet(&state, plan, path, writer_properties).await
     }
 
     /// Executes a query and writes the results to a partitioned JSON file.
     pub async fn write_json(&self, path: impl AsRef<str>) -> Result<()> {
-        let plan = self.create_physical_plan().await?;
-        let state = self.session_state.read().clone();
+        let (plan, state) = self.plan_statement().await?;
         plan_to_json(&state, plan, path).await
     }
 
+    /// Executes a query and writes the results to a directory of files of
+    /// type `file_type`, with the rows hashed into the buckets of
+    /// `bucketing`, that an external table `CLUSTERED BY` the bucket columns
+    /// can read.
+    pub async fn write_bucketed(
+        &self,
+        path: impl AsRef<str>,
+        file_type: FileType,
+        bucketing: &Bucketing,
+    ) -> Result<()> {
+        let (plan, state) = self.plan_statement().await?;
+        plan_to_bucketed(&state, plan, path, file_type, bucketing).await
+    }
+
+    /// Executes a query and writes the results to a directory of files of
+    /// type `file_type`, one per partition, with the rows of each file sorted
+    /// on the `cluster_by` expressions, such as `zorder(a, b)`, so that the
+    /// statistics of the files prune the scans filtering on any of their
+    /// columns.
+    pub async fn write_clustered(
+        &self,
+        path: impl AsRef<str>,
+        file_type: FileType,
+        cluster_by: Vec<Expr>,
+    ) -> Result<()> {
+        let (plan, state) = self.plan_statement().await?;
+        let expr = cluster_by
+            .iter()
+            .map(|e| {
+                create_physical_sort_expr(
+                    e,
+                    self.plan.schema(),
+                    &plan.schema(),
+                    SortOptions::default(),
+                    &state.execution_props,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // each file is sorted on its own, the query is not
+        let plan = if expr.is_empty() {
+            plan
+        } else {
+            Arc::new(SortExec::new_with_partitioning(expr, plan, true, None))
+        };
+        match file_type {
+            FileType::CSV => plan_to_csv(&state, plan, path).await,
+            FileType::PARQUET => plan_to_parquet(&state, plan, path, None).await,
+            FileType::JSON => plan_to_json(&state, plan, path).await,
+            FileType::AVRO => Err(DataFusionError::NotImplemented(
+                "Writing clustered AVRO files is not supported".to_string(),
+            )),
+        }
+    }
+
     /// Add an additional column to the DataFrame.
     ///
     /// ```
@@ -842,15 +1115,16 @@ mod tests {
     use std::vec;
 
     use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::execution::context::{SessionConfig, SessionContext};
     use crate::execution::options::{CsvReadOptions, ParquetReadOptions};
     use crate::physical_plan::ColumnarValue;
     use crate::test_util;
     use crate::test_util::parquet_test_data;
-    use crate::{assert_batches_sorted_eq, execution::context::SessionContext};
     use arrow::array::Int32Array;
     use arrow::datatypes::DataType;
     use datafusion_expr::{
-        avg, cast, count, count_distinct, create_udf, lit, max, min, sum,
+        avg, cast, count, count_distinct, create_udf, lit, max, min, placeholder, sum,
         BuiltInWindowFunction, ScalarFunctionImplementation, Volatility, WindowFunction,
     };
 
@@ -897,6 +1171,7 @@ mod tests {
             partition_by: vec![col("aggregate_test_100.c2")],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let t2 = t.select(vec![col("c1"), first_row])?;
         let plan = t2.plan.clone();
@@ -982,6 +1257,27 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn adaptive_join() -> Result<()> {
+        let sql = "SELECT a.c1, count(*) FROM aggregate_test_100 a \
+                   JOIN aggregate_test_100 b ON a.c1 = b.c1 GROUP BY a.c1";
+        let config = || SessionConfig::new().with_target_partitions(4);
+
+        let mut ctx = SessionContext::with_config(config());
+        register_aggregate_csv(&mut ctx, "aggregate_test_100").await?;
+        let expected = ctx.sql(sql).await?.collect().await?;
+
+        let mut ctx =
+            SessionContext::with_config(config().set_bool(OPT_ADAPTIVE_ENABLED, true));
+        register_aggregate_csv(&mut ctx, "aggregate_test_100").await?;
+        let actual = ctx.sql(sql).await?.collect().await?;
+
+        let expected = pretty::pretty_format_batches(&expected)?.to_string();
+        let expected: Vec<&str> = expected.lines().collect();
+        assert_batches_sorted_eq!(expected, &actual);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn limit() -> Result<()> {
         // build query using Table API
@@ -1020,6 +1316,35 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn with_param_values() -> Result<()> {
+        let df = test_table()
+            .await?
+            .select_columns(&["c1", "c2"])?
+            .filter(col("c2").eq(placeholder("c2", DataType::UInt32)))?
+            .with_param_values(vec![("c2", ScalarValue::UInt32(Some(5)))])?;
+        let plan = df.plan.clone();
+
+        let expected = test_table()
+            .await?
+            .select_columns(&["c1", "c2"])?
+            .filter(col("c2").eq(lit(5u32)))?;
+
+        assert_same_plan(&plan, &expected.plan);
+
+        let err = test_table()
+            .await?
+            .filter(col("c2").eq(placeholder("c2", DataType::UInt32)))?
+            .with_param_values(vec![("c2", ScalarValue::Int64(Some(5)))])
+            .unwrap_err();
+        assert_eq!(
+            "Error during planning: Placeholder $c2 expected a value of type UInt32, got Int64",
+            err.to_string()
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn registry() -> Result<()> {
         let mut ctx = SessionContext::new();
@@ -1162,6 +1487,19 @@ mod tests {
         ctx.create_logical_plan(sql)
     }
 
+    #[tokio::test]
+    async fn rows() -> Result<()> {
+        let df = test_table()
+            .await?
+            .aggregate(vec![], vec![count(col("c1")), max(col("c2"))])?;
+        let rows = df.rows().await?;
+        assert_eq!(rows.len(), 1);
+        let row = rows.iter().next().unwrap();
+        assert_eq!(row.get::<i64>(0)?, 100);
+        assert_eq!(row.get::<Option<u32>>(1)?, Some(5));
+        Ok(())
+    }
+
     async fn test_table_with_name(name: &str) -> Result<Arc<DataFrame>> {
         let mut ctx = SessionContext::new();
         register_aggregate_csv(&mut ctx, name).await?;
@@ -1487,6 +1825,41 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn dedup() -> Result<()> {
+        let t = test_table().await?;
+        let expected = t
+            .aggregate(vec![col("c1")], vec![max(col("c3")).alias("c3")])?
+            .collect()
+            .await?;
+        let expected = pretty::pretty_format_batches(&expected)?.to_string();
+        let expected = expected.lines().collect::<Vec<_>>();
+
+        // the row with the greatest c3 of each c1
+        let first = t
+            .dedup(
+                vec![col("c1")],
+                DedupKeep::First,
+                vec![col("c3").sort(false, true)],
+            )?
+            .select_columns(&["c1", "c3"])?
+            .collect()
+            .await?;
+        assert_batches_sorted_eq!(expected, &first);
+
+        let last = t
+            .dedup(
+                vec![col("c1")],
+                DedupKeep::Last,
+                vec![col("c3").sort(true, false)],
+            )?
+            .select_columns(&["c1", "c3"])?
+            .collect()
+            .await?;
+        assert_batches_sorted_eq!(expected, &last);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn cache_test() -> Result<()> {
         let df = test_table()
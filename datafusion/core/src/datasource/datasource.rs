@@ -21,15 +21,15 @@ use std::any::Any;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use datafusion_common::Statistics;
+use datafusion_common::{Constraints, Statistics};
 use datafusion_expr::LogicalPlan;
 pub use datafusion_expr::{TableProviderFilterPushDown, TableType};
 
 use crate::arrow::datatypes::SchemaRef;
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::execution::context::SessionState;
 use crate::logical_expr::Expr;
-use crate::physical_plan::ExecutionPlan;
+use crate::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
 
 /// Source table
 #[async_trait]
@@ -49,6 +49,11 @@ pub trait TableProvider: Sync + Send {
         None
     }
 
+    /// Get the constraints, such as the primary key, declared on this table.
+    fn constraints(&self) -> Option<&Constraints> {
+        None
+    }
+
     /// Get the Logical Plan of this table, if available.
     fn get_logical_plan(&self) -> Option<&LogicalPlan> {
         None
@@ -70,6 +75,26 @@ pub trait TableProvider: Sync + Send {
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>>;
 
+    /// Create an ExecutionPlan that will scan the table, with every partition
+    /// returning its rows ordered by the sort expressions `ordering`.
+    /// If `limit` is set, each partition needs to return *at least* the first
+    /// `limit` rows in that order, which lets providers backed by an index
+    /// answer top-k queries without scanning the whole table.
+    ///
+    /// This is only called with orderings the provider accepted in
+    /// [`supports_sort_pushdown`](Self::supports_sort_pushdown). The default
+    /// implementation calls [`scan`](Self::scan).
+    async fn scan_ordered(
+        &self,
+        ctx: &SessionState,
+        projection: &Option<Vec<usize>>,
+        filters: &[Expr],
+        _ordering: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.scan(ctx, projection, filters, limit).await
+    }
+
     /// Tests whether the table provider can make use of a filter expression
     /// to optimise data retrieval.
     fn supports_filter_pushdown(
@@ -79,10 +104,32 @@ pub trait TableProvider: Sync + Send {
         Ok(TableProviderFilterPushDown::Unsupported)
     }
 
+    /// Tests whether the table provider can return its rows ordered by the
+    /// sort expressions `sort_exprs`, in which case ordered scans are planned
+    /// with [`scan_ordered`](Self::scan_ordered).
+    fn supports_sort_pushdown(&self, _sort_exprs: &[Expr]) -> Result<bool> {
+        Ok(false)
+    }
+
     /// Get statistics for this table, if available
     fn statistics(&self) -> Option<Statistics> {
         None
     }
+
+    /// Appends the record batches of `input`, which have the schema of the
+    /// table, to the table and returns the number of rows inserted.
+    ///
+    /// The default implementation returns an error, as tables are read only
+    /// unless their provider supports insertion.
+    async fn insert_into(
+        &self,
+        _ctx: &SessionState,
+        _input: SendableRecordBatchStream,
+    ) -> Result<usize> {
+        Err(DataFusionError::NotImplemented(
+            "Insertion is not supported by this table".to_string(),
+        ))
+    }
 }
 
 /// A factory which creates [`TableProvider`]s at runtime given a URL.
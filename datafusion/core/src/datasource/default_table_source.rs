@@ -19,7 +19,7 @@
 
 use crate::datasource::TableProvider;
 use arrow::datatypes::SchemaRef;
-use datafusion_common::DataFusionError;
+use datafusion_common::{Constraints, DataFusionError};
 use datafusion_expr::{Expr, TableProviderFilterPushDown, TableSource};
 use std::any::Any;
 use std::sync::Arc;
@@ -52,6 +52,11 @@ impl TableSource for DefaultTableSource {
         self.table_provider.schema()
     }
 
+    /// Get the constraints, such as the primary key, declared on this table.
+    fn constraints(&self) -> Option<&Constraints> {
+        self.table_provider.constraints()
+    }
+
     /// Tests whether the table provider can make use of a filter expression
     /// to optimise data retrieval.
     fn supports_filter_pushdown(
@@ -61,6 +66,15 @@ impl TableSource for DefaultTableSource {
         self.table_provider.supports_filter_pushdown(filter)
     }
 
+    /// Tests whether the table provider can return its rows ordered by the
+    /// sort expressions `sort_exprs`.
+    fn supports_sort_pushdown(
+        &self,
+        sort_exprs: &[Expr],
+    ) -> datafusion_common::Result<bool> {
+        self.table_provider.supports_sort_pushdown(sort_exprs)
+    }
+
     fn get_logical_plan(&self) -> Option<&datafusion_expr::LogicalPlan> {
         self.table_provider.get_logical_plan()
     }
@@ -0,0 +1,497 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Bucketed listing tables.
+//!
+//! The rows of a bucketed table are hashed on its bucket columns into a fixed
+//! number of buckets, and each file of the table holds the rows of a single
+//! bucket, whose number is part of the file name (see [`bucket_file_name`]).
+//! A [`ListingTable`] with a [`Bucketing`] skips the buckets that can not
+//! contain the values of the equality predicates on the bucket columns, and
+//! reads the files of bucket `i` in its partition `i`, so that two tables
+//! bucketed alike can be joined on their bucket columns without a shuffle.
+//!
+//! [`ListingTable`]: super::ListingTable
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use arrow::array::UInt32Array;
+use arrow::compute::take;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use arrow::{csv, json};
+use datafusion_common::ScalarValue;
+use datafusion_expr::{BinaryExpr, Expr, Operator};
+use datafusion_optimizer::utils::split_conjunction;
+use futures::StreamExt;
+use object_store::path::Path;
+use parquet::arrow::ArrowWriter;
+use tokio::task::{self, JoinHandle};
+
+use super::index::hash_value;
+use super::PartitionedFile;
+use crate::datasource::file_format::file_type::{FileType, GetExt};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::{SessionState, TaskContext};
+use crate::physical_plan::ExecutionPlan;
+
+/// Maximum number of combinations of values of the bucket columns for which
+/// the buckets to scan are computed, beyond which all the buckets are scanned
+const MAX_BUCKET_COMBINATIONS: usize = 1024;
+
+/// The bucketing of a table: its rows are hashed on `columns` into
+/// `num_buckets` buckets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucketing {
+    /// The bucket columns
+    pub columns: Vec<String>,
+    /// The number of buckets
+    pub num_buckets: usize,
+}
+
+impl Bucketing {
+    /// Create a new bucketing on `columns` into `num_buckets` buckets
+    pub fn new(columns: Vec<String>, num_buckets: usize) -> Self {
+        Self {
+            columns,
+            num_buckets,
+        }
+    }
+}
+
+/// Returns the bucket of the row whose bucket columns have the `values`
+pub fn bucket_of(values: &[ScalarValue], num_buckets: usize) -> usize {
+    let hash = values.iter().fold(0u64, |hash, value| {
+        let value_hash = if value.is_null() {
+            0
+        } else {
+            hash_value(&canonical_value(value))
+        };
+        hash.wrapping_mul(31).wrapping_add(value_hash)
+    });
+    (hash % num_buckets as u64) as usize
+}
+
+/// Returns the value hashed for `value`, so that the equal values have the
+/// same representation: `-0.0` is hashed as `0.0`, and all the NaNs alike
+fn canonical_value(value: &ScalarValue) -> ScalarValue {
+    match value {
+        ScalarValue::Float32(Some(v)) if *v == 0.0 => ScalarValue::Float32(Some(0.0)),
+        ScalarValue::Float32(Some(v)) if v.is_nan() => {
+            ScalarValue::Float32(Some(f32::NAN))
+        }
+        ScalarValue::Float64(Some(v)) if *v == 0.0 => ScalarValue::Float64(Some(0.0)),
+        ScalarValue::Float64(Some(v)) if v.is_nan() => {
+            ScalarValue::Float64(Some(f64::NAN))
+        }
+        ScalarValue::Dictionary(_, value) => canonical_value(value),
+        value => value.clone(),
+    }
+}
+
+/// Returns the name of the file holding the rows of `bucket` written by the
+/// output partition `partition` of a plan, such as `bucket-00003-part-1.csv`
+pub fn bucket_file_name(bucket: usize, partition: usize, extension: &str) -> String {
+    format!("bucket-{:05}-part-{}{}", bucket, partition, extension)
+}
+
+/// Returns the bucket of the file at `location`, or `None` if its name is
+/// not the name of a bucket file
+pub fn file_bucket(location: &Path) -> Option<usize> {
+    let name = location.filename()?.strip_prefix("bucket-")?;
+    let (bucket, rest) = name.split_once('-')?;
+    if !rest.starts_with("part-") {
+        return None;
+    }
+    bucket.parse().ok()
+}
+
+/// Returns the buckets that may contain rows matching the `filters`, or
+/// `None` if all of them may, when the filters do not restrict each bucket
+/// column to a few literal values of the type of the column.
+pub(crate) fn pruned_buckets(
+    bucketing: &Bucketing,
+    schema: &Schema,
+    filters: &[Expr],
+) -> Option<HashSet<usize>> {
+    let mut combinations: Vec<Vec<ScalarValue>> = vec![vec![]];
+    for column in &bucketing.columns {
+        let literals = column_literals(column, schema, filters)?;
+        combinations = combinations
+            .iter()
+            .flat_map(|values| {
+                literals.iter().map(move |literal| {
+                    let mut values = values.clone();
+                    values.push(literal.clone());
+                    values
+                })
+            })
+            .collect();
+        if combinations.len() > MAX_BUCKET_COMBINATIONS {
+            return None;
+        }
+    }
+    Some(
+        combinations
+            .iter()
+            .map(|values| bucket_of(values, bucketing.num_buckets))
+            .collect(),
+    )
+}
+
+/// Returns the values that `column` is restricted to by an equality or an
+/// `IN` list of one of the conjuncts of the `filters`
+fn column_literals(
+    column: &str,
+    schema: &Schema,
+    filters: &[Expr],
+) -> Option<Vec<ScalarValue>> {
+    let data_type = schema.field_with_name(column).ok()?.data_type();
+    let literal_of_column = |expr: &Expr| match expr {
+        Expr::Literal(value) if value.get_datatype() == *data_type => Some(value.clone()),
+        _ => None,
+    };
+    let is_column = |expr: &Expr| matches!(expr, Expr::Column(c) if c.name == column);
+
+    filters
+        .iter()
+        .flat_map(split_conjunction)
+        .find_map(|expr| match expr {
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) => {
+                if is_column(left.as_ref()) {
+                    literal_of_column(right.as_ref()).map(|value| vec![value])
+                } else if is_column(right.as_ref()) {
+                    literal_of_column(left.as_ref()).map(|value| vec![value])
+                } else {
+                    None
+                }
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated: false,
+            } if is_column(expr.as_ref()) => list.iter().map(literal_of_column).collect(),
+            _ => None,
+        })
+}
+
+/// Groups the `files` by bucket, returning exactly `num_buckets` groups, or
+/// gives the files back if one of them is not a bucket file of the table
+pub(crate) fn group_by_bucket(
+    files: Vec<PartitionedFile>,
+    num_buckets: usize,
+) -> std::result::Result<Vec<Vec<PartitionedFile>>, Vec<PartitionedFile>> {
+    let bucket_of_file = |file: &PartitionedFile| {
+        file_bucket(&file.object_meta.location).filter(|bucket| *bucket < num_buckets)
+    };
+    if !files.iter().all(|file| bucket_of_file(file).is_some()) {
+        return Err(files);
+    }
+
+    let mut groups = vec![vec![]; num_buckets];
+    for file in files {
+        let bucket = bucket_of_file(&file).unwrap();
+        groups[bucket].push(file);
+    }
+    Ok(groups)
+}
+
+/// Splits the rows of `batch` by bucket of their values of the columns at
+/// `indices`
+fn split_by_bucket(
+    batch: &RecordBatch,
+    indices: &[usize],
+    num_buckets: usize,
+) -> Result<Vec<(usize, RecordBatch)>> {
+    let mut rows: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+    for row in 0..batch.num_rows() {
+        let values = indices
+            .iter()
+            .map(|i| ScalarValue::try_from_array(batch.column(*i), row))
+            .collect::<Result<Vec<_>>>()?;
+        rows.entry(bucket_of(&values, num_buckets))
+            .or_default()
+            .push(row as u32);
+    }
+
+    rows.into_iter()
+        .map(|(bucket, rows)| {
+            let rows = UInt32Array::from(rows);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|column| take(column.as_ref(), &rows, None))
+                .collect::<ArrowResult<Vec<_>>>()?;
+            Ok((bucket, RecordBatch::try_new(batch.schema(), columns)?))
+        })
+        .collect()
+}
+
+/// The writer of the file of a bucket
+enum BucketWriter {
+    Csv(csv::Writer<fs::File>),
+    Json(json::LineDelimitedWriter<fs::File>),
+    Parquet(ArrowWriter<fs::File>),
+}
+
+impl BucketWriter {
+    fn try_new(file_type: &FileType, file: fs::File, schema: SchemaRef) -> Result<Self> {
+        match file_type {
+            FileType::CSV => Ok(Self::Csv(csv::Writer::new(file))),
+            FileType::JSON => Ok(Self::Json(json::LineDelimitedWriter::new(file))),
+            FileType::PARQUET => {
+                Ok(Self::Parquet(ArrowWriter::try_new(file, schema, None)?))
+            }
+            FileType::AVRO => Err(DataFusionError::NotImplemented(
+                "Writing bucketed AVRO files is not supported".to_string(),
+            )),
+        }
+    }
+
+    fn write(&mut self, batch: RecordBatch) -> Result<()> {
+        match self {
+            Self::Csv(writer) => writer.write(&batch)?,
+            Self::Json(writer) => writer.write(batch)?,
+            Self::Parquet(writer) => writer.write(&batch)?,
+        }
+        Ok(())
+    }
+
+    fn close(self) -> Result<()> {
+        match self {
+            Self::Csv(_) => {}
+            Self::Json(mut writer) => writer.finish()?,
+            Self::Parquet(writer) => {
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Executes `plan` and writes its rows into the directory `path`, hashed into
+/// the buckets of `bucketing`: each output partition of the plan writes one
+/// file of type `file_type` per bucket it has rows for, named by
+/// [`bucket_file_name`]
+pub async fn plan_to_bucketed(
+    state: &SessionState,
+    plan: Arc<dyn ExecutionPlan>,
+    path: impl AsRef<str>,
+    file_type: FileType,
+    bucketing: &Bucketing,
+) -> Result<()> {
+    let path = path.as_ref();
+    if bucketing.columns.is_empty() || bucketing.num_buckets == 0 {
+        return Err(DataFusionError::Plan(
+            "A bucketing requires at least one column and one bucket".to_string(),
+        ));
+    }
+    let schema = plan.schema();
+    let indices = bucketing
+        .columns
+        .iter()
+        .map(|column| Ok(schema.index_of(column)?))
+        .collect::<Result<Vec<_>>>()?;
+    // fail before creating the directory
+    if file_type == FileType::AVRO {
+        return Err(DataFusionError::NotImplemented(
+            "Writing bucketed AVRO files is not supported".to_string(),
+        ));
+    }
+
+    // create directory to contain the files of the buckets
+    let fs_path = std::path::Path::new(path);
+    if let Err(e) = fs::create_dir(fs_path) {
+        return Err(DataFusionError::Execution(format!(
+            "Could not create directory {}: {:?}",
+            path, e
+        )));
+    }
+
+    let mut tasks = vec![];
+    for i in 0..plan.output_partitioning().partition_count() {
+        let task_ctx = Arc::new(TaskContext::from(state));
+        let mut stream = plan.execute(i, task_ctx)?;
+        let (fs_path, file_type, schema) =
+            (fs_path.to_owned(), file_type.clone(), schema.clone());
+        let (indices, num_buckets) = (indices.clone(), bucketing.num_buckets);
+        let handle: JoinHandle<Result<()>> = task::spawn(async move {
+            let mut writers = BTreeMap::new();
+            while let Some(batch) = stream.next().await {
+                for (bucket, batch) in split_by_bucket(&batch?, &indices, num_buckets)? {
+                    let writer = match writers.entry(bucket) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            let filename =
+                                bucket_file_name(bucket, i, &file_type.get_ext());
+                            let file = fs::File::create(fs_path.join(filename))?;
+                            entry.insert(BucketWriter::try_new(
+                                &file_type,
+                                file,
+                                schema.clone(),
+                            )?)
+                        }
+                    };
+                    writer.write(batch)?;
+                }
+            }
+            writers.into_values().try_for_each(BucketWriter::close)
+        });
+        tasks.push(handle);
+    }
+    futures::future::join_all(tasks)
+        .await
+        .into_iter()
+        .try_for_each(|result| {
+            result.map_err(|e| DataFusionError::Execution(format!("{}", e)))?
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field};
+    use datafusion_expr::{col, in_list, lit};
+    use object_store::ObjectMeta;
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+        ])
+    }
+
+    fn partitioned_file(name: &str) -> PartitionedFile {
+        PartitionedFile {
+            object_meta: ObjectMeta {
+                location: Path::from(format!("table/{}", name)),
+                last_modified: chrono::Utc::now(),
+                size: 0,
+            },
+            partition_values: vec![],
+            range: None,
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn bucket_file_names() {
+        let name = bucket_file_name(3, 1, ".csv");
+        assert_eq!(name, "bucket-00003-part-1.csv");
+        assert_eq!(file_bucket(&Path::from(format!("table/{}", name))), Some(3));
+        assert_eq!(file_bucket(&Path::from("table/part-1.csv")), None);
+        assert_eq!(file_bucket(&Path::from("table/bucket-x-part-1.csv")), None);
+        assert_eq!(file_bucket(&Path::from("table/bucket-00003.csv")), None);
+    }
+
+    #[test]
+    fn bucket_of_is_stable() {
+        let values = [ScalarValue::Int32(Some(42)), ScalarValue::from("x")];
+        let bucket = bucket_of(&values, 8);
+        assert!(bucket < 8);
+        assert_eq!(bucket_of(&values, 8), bucket);
+        assert_eq!(bucket_of(&[ScalarValue::Int32(None)], 8), 0);
+
+        // equal values are in the same bucket
+        let num_buckets = 1 << 20;
+        assert_eq!(
+            bucket_of(&[ScalarValue::Float64(Some(-0.0))], num_buckets),
+            bucket_of(&[ScalarValue::Float64(Some(0.0))], num_buckets)
+        );
+        assert_eq!(
+            bucket_of(&[ScalarValue::Float32(Some(-0.0))], num_buckets),
+            bucket_of(&[ScalarValue::Float32(Some(0.0))], num_buckets)
+        );
+        assert_eq!(
+            bucket_of(&[ScalarValue::Float64(Some(-f64::NAN))], num_buckets),
+            bucket_of(&[ScalarValue::Float64(Some(f64::NAN))], num_buckets)
+        );
+    }
+
+    #[test]
+    fn prune_buckets() {
+        let bucketing = Bucketing::new(vec!["a".to_string()], 16);
+        let schema = schema();
+
+        let filters = vec![col("a").eq(lit(1i32)).and(col("b").eq(lit("x")))];
+        let expected = HashSet::from([bucket_of(&[ScalarValue::Int32(Some(1))], 16)]);
+        assert_eq!(
+            pruned_buckets(&bucketing, &schema, &filters),
+            Some(expected)
+        );
+
+        let filters = vec![in_list(col("a"), vec![lit(1i32), lit(2i32)], false)];
+        let expected = HashSet::from([
+            bucket_of(&[ScalarValue::Int32(Some(1))], 16),
+            bucket_of(&[ScalarValue::Int32(Some(2))], 16),
+        ]);
+        assert_eq!(
+            pruned_buckets(&bucketing, &schema, &filters),
+            Some(expected)
+        );
+
+        // not an equality, or a literal of another type
+        let filters = vec![col("a").gt(lit(1i32)), col("a").eq(lit(1i64))];
+        assert_eq!(pruned_buckets(&bucketing, &schema, &filters), None);
+
+        // the second bucket column is not restricted
+        let bucketing = Bucketing::new(vec!["a".to_string(), "b".to_string()], 16);
+        let filters = vec![col("a").eq(lit(1i32))];
+        assert_eq!(pruned_buckets(&bucketing, &schema, &filters), None);
+    }
+
+    #[test]
+    fn group_files_by_bucket() {
+        let files = vec![
+            partitioned_file("bucket-00001-part-0.csv"),
+            partitioned_file("bucket-00000-part-0.csv"),
+            partitioned_file("bucket-00001-part-1.csv"),
+        ];
+        let groups = group_by_bucket(files, 3).unwrap();
+        let names = groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|file| file.object_meta.location.filename().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                vec!["bucket-00000-part-0.csv"],
+                vec!["bucket-00001-part-0.csv", "bucket-00001-part-1.csv"],
+                vec![],
+            ]
+        );
+
+        // a file that is not a bucket file, or of a bucket out of range
+        let files = vec![partitioned_file("part-0.csv")];
+        assert!(group_by_bucket(files, 3).is_err());
+        let files = vec![partitioned_file("bucket-00003-part-0.csv")];
+        assert!(group_by_bucket(files, 3).is_err());
+    }
+}
@@ -0,0 +1,633 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Secondary indexes stored next to the files of a listing table.
+//!
+//! The index of a file is a sidecar file named after it with the
+//! [`INDEX_FILE_SUFFIX`] suffix. It holds the number of rows of the file and,
+//! for each column, its minimum and maximum values, its number of nulls and
+//! a bloom filter of its values. A [`ListingTable`] consults the indexes
+//! before opening the files, to skip the files that contain no matching rows
+//! for formats without statistics of their own, such as CSV or JSON.
+//!
+//! The indexes are built by `ANALYZE TABLE`. An index records the size and
+//! the modification time of its file, and is ignored once the file changes.
+//!
+//! [`ListingTable`]: super::ListingTable
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use ahash::HashSet;
+use arrow::array::{ArrayRef, BinaryArray, Int64Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::{reader::FileReader, writer::FileWriter};
+use arrow::record_batch::RecordBatch;
+use datafusion_common::{Column, ScalarValue};
+use datafusion_expr::{Accumulator, BinaryExpr, Expr, Operator};
+use datafusion_optimizer::utils::split_conjunction;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_optimizer::pruning::{PruningPredicate, PruningStatistics};
+use crate::physical_plan::expressions::{MaxAccumulator, MinAccumulator};
+
+/// The suffix of the index file of a data file
+pub const INDEX_FILE_SUFFIX: &str = ".dfindex";
+
+/// Number of bloom filter bits per distinct value, for a false positive
+/// rate of about 1%
+const BLOOM_BITS_PER_VALUE: usize = 10;
+
+/// Number of hash functions of the bloom filters
+const BLOOM_NUM_HASHES: u32 = 7;
+
+/// Returns the location of the index of the file at `location`
+pub fn index_path(location: &Path) -> Path {
+    Path::from(format!("{}{}", location, INDEX_FILE_SUFFIX))
+}
+
+/// The index of a single column of a file
+#[derive(Debug, Clone)]
+pub struct ColumnIndex {
+    /// The minimum value of the column
+    pub min: ScalarValue,
+    /// The maximum value of the column
+    pub max: ScalarValue,
+    /// The number of null values of the column
+    pub null_count: usize,
+    /// A bloom filter of the values of the column, if its type supports one
+    pub bloom: Option<BloomFilter>,
+}
+
+/// The index of a file, see the [module level documentation](self)
+#[derive(Debug, Clone)]
+pub struct FileIndex {
+    /// The size of the indexed file, to detect an index that is out of date
+    pub file_size: usize,
+    /// The modification time of the indexed file, in nanoseconds since the
+    /// epoch, to detect an index that is out of date
+    pub last_modified: i64,
+    /// The number of rows of the file
+    pub num_rows: usize,
+    /// The index of each indexed column, by name
+    pub columns: HashMap<String, ColumnIndex>,
+}
+
+impl FileIndex {
+    /// Reads the index of the file at `location`, returning `None` if the
+    /// file has no index
+    pub async fn read(store: &dyn ObjectStore, location: &Path) -> Result<Option<Self>> {
+        match store.get(&index_path(location)).await {
+            Ok(result) => Self::from_ipc(&result.bytes().await?).map(Some),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes this index as the index of the file at `location`
+    pub async fn write(&self, store: &dyn ObjectStore, location: &Path) -> Result<()> {
+        let bytes = self.to_ipc()?;
+        store.put(&index_path(location), bytes.into()).await?;
+        Ok(())
+    }
+
+    /// Returns true if this index was built from the current version of
+    /// `file`, false if the file was rewritten since
+    pub fn is_up_to_date(&self, file: &ObjectMeta) -> bool {
+        self.file_size == file.size
+            && self.last_modified == file.last_modified.timestamp_nanos()
+    }
+
+    /// Returns false if the file can not contain rows matching all the
+    /// `filters`, either from the minimum and maximum values of its columns,
+    /// checked with `predicate`, or from the bloom filters of the columns
+    /// compared to literals.
+    pub fn may_match(
+        &self,
+        predicate: Option<&PruningPredicate>,
+        filters: &[Expr],
+    ) -> Result<bool> {
+        if let Some(predicate) = predicate {
+            if !predicate.prune(self)?[0] {
+                return Ok(false);
+            }
+        }
+        Ok(filters
+            .iter()
+            .flat_map(split_conjunction)
+            .all(|expr| self.bloom_may_match(expr)))
+    }
+
+    /// Returns false if the bloom filters show that no row matches `expr`
+    fn bloom_may_match(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(column), Expr::Literal(value))
+                | (Expr::Literal(value), Expr::Column(column)) => {
+                    self.bloom_may_contain(column, value)
+                }
+                _ => true,
+            },
+            Expr::InList {
+                expr,
+                list,
+                negated: false,
+            } => match expr.as_ref() {
+                Expr::Column(column) => list.iter().any(|item| match item {
+                    Expr::Literal(value) => self.bloom_may_contain(column, value),
+                    _ => true,
+                }),
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    /// Returns false if the bloom filter of `column` shows that it does not
+    /// contain `value`
+    fn bloom_may_contain(&self, column: &Column, value: &ScalarValue) -> bool {
+        let bloom = match self.columns.get(&column.name) {
+            Some(ColumnIndex {
+                min,
+                bloom: Some(bloom),
+                ..
+            }) => {
+                // the values are hashed from their representation, which
+                // depends on their type
+                if value.is_null() || value.get_datatype() != min.get_datatype() {
+                    return true;
+                }
+                bloom
+            }
+            _ => return true,
+        };
+        bloom.may_contain(value)
+    }
+
+    /// Serializes this index as an Arrow IPC file of a single row, with
+    /// `<column>.min`, `<column>.max`, `<column>.null_count` and
+    /// `<column>.bloom` fields for each indexed column
+    fn to_ipc(&self) -> Result<Vec<u8>> {
+        let mut fields = vec![
+            Field::new("file_size", DataType::UInt64, false),
+            Field::new("last_modified", DataType::Int64, false),
+            Field::new("num_rows", DataType::UInt64, false),
+        ];
+        let mut arrays: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from(vec![self.file_size as u64])),
+            Arc::new(Int64Array::from(vec![self.last_modified])),
+            Arc::new(UInt64Array::from(vec![self.num_rows as u64])),
+        ];
+
+        let mut names = self.columns.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let index = &self.columns[name];
+            let data_type = index.min.get_datatype();
+            fields.push(Field::new(
+                &format!("{}.min", name),
+                data_type.clone(),
+                true,
+            ));
+            fields.push(Field::new(&format!("{}.max", name), data_type, true));
+            fields.push(Field::new(
+                &format!("{}.null_count", name),
+                DataType::UInt64,
+                false,
+            ));
+            fields.push(Field::new(
+                &format!("{}.bloom", name),
+                DataType::Binary,
+                true,
+            ));
+            arrays.push(index.min.to_array());
+            arrays.push(index.max.to_array());
+            arrays.push(Arc::new(UInt64Array::from(vec![index.null_count as u64])));
+            let bloom = index.bloom.as_ref().map(BloomFilter::to_bytes);
+            arrays.push(Arc::new(BinaryArray::from(vec![bloom.as_deref()])));
+        }
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+        let mut writer = FileWriter::try_new(vec![], &schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(writer.into_inner()?)
+    }
+
+    /// Deserializes an index serialized by [`Self::to_ipc`]
+    fn from_ipc(bytes: &[u8]) -> Result<Self> {
+        let mut reader = FileReader::try_new(Cursor::new(bytes), None)?;
+        let batch = match reader.next() {
+            Some(batch) => batch?,
+            None => {
+                return Err(DataFusionError::Execution(
+                    "Invalid file index: no record batch".to_string(),
+                ))
+            }
+        };
+        if batch.num_rows() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "Invalid file index: expected 1 row, got {}",
+                batch.num_rows()
+            )));
+        }
+
+        let scalar = |name: &str| -> Result<ScalarValue> {
+            let schema = batch.schema();
+            let index = schema.index_of(name)?;
+            ScalarValue::try_from_array(batch.column(index), 0)
+        };
+        let count = |name: &str| -> Result<usize> {
+            match scalar(name)? {
+                ScalarValue::UInt64(Some(count)) => Ok(count as usize),
+                other => Err(DataFusionError::Execution(format!(
+                    "Invalid file index: {} is {:?}",
+                    name, other
+                ))),
+            }
+        };
+
+        let last_modified = match scalar("last_modified")? {
+            ScalarValue::Int64(Some(last_modified)) => last_modified,
+            other => {
+                return Err(DataFusionError::Execution(format!(
+                    "Invalid file index: last_modified is {:?}",
+                    other
+                )))
+            }
+        };
+
+        let mut columns = HashMap::new();
+        for field in batch.schema().fields() {
+            let name = match field.name().strip_suffix(".min") {
+                Some(name) => name,
+                None => continue,
+            };
+            let bloom = match scalar(&format!("{}.bloom", name))? {
+                ScalarValue::Binary(Some(bytes)) => {
+                    Some(BloomFilter::from_bytes(&bytes)?)
+                }
+                _ => None,
+            };
+            columns.insert(
+                name.to_string(),
+                ColumnIndex {
+                    min: scalar(field.name())?,
+                    max: scalar(&format!("{}.max", name))?,
+                    null_count: count(&format!("{}.null_count", name))?,
+                    bloom,
+                },
+            );
+        }
+
+        Ok(Self {
+            file_size: count("file_size")?,
+            last_modified,
+            num_rows: count("num_rows")?,
+            columns,
+        })
+    }
+}
+
+impl PruningStatistics for FileIndex {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.columns
+            .get(&column.name)
+            .map(|index| index.min.to_array())
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.columns
+            .get(&column.name)
+            .map(|index| index.max.to_array())
+    }
+
+    fn num_containers(&self) -> usize {
+        1
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        self.columns.get(&column.name).map(|index| {
+            Arc::new(UInt64Array::from(vec![index.null_count as u64])) as ArrayRef
+        })
+    }
+}
+
+/// Builds the [`FileIndex`] of a file from its record batches, either when
+/// the file is written or by reading it back
+pub struct FileIndexBuilder {
+    /// The schema of the file
+    schema: SchemaRef,
+    /// The number of rows seen so far
+    num_rows: usize,
+    /// The state of each column of the schema, `None` for the columns that
+    /// can not be indexed
+    columns: Vec<Option<ColumnIndexBuilder>>,
+}
+
+/// The state of the index of a column while it is built
+struct ColumnIndexBuilder {
+    min: MinAccumulator,
+    max: MaxAccumulator,
+    null_count: usize,
+    /// The hashes of the distinct values, if the column has a bloom filter
+    hashes: Option<HashSet<u64>>,
+}
+
+impl FileIndexBuilder {
+    /// Creates a builder for the index of a file with `schema`
+    pub fn new(schema: SchemaRef) -> Self {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let data_type = field.data_type();
+                match (
+                    MinAccumulator::try_new(data_type),
+                    MaxAccumulator::try_new(data_type),
+                ) {
+                    (Ok(min), Ok(max)) => Some(ColumnIndexBuilder {
+                        min,
+                        max,
+                        null_count: 0,
+                        hashes: supports_bloom_filter(data_type).then(HashSet::default),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect();
+        Self {
+            schema,
+            num_rows: 0,
+            columns,
+        }
+    }
+
+    /// Adds the rows of `batch` to the index
+    pub fn update(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.num_rows += batch.num_rows();
+        for (column, array) in self.columns.iter_mut().zip(batch.columns()) {
+            let column = match column {
+                Some(column) => column,
+                None => continue,
+            };
+            column.min.update_batch(&[array.clone()])?;
+            column.max.update_batch(&[array.clone()])?;
+            column.null_count += array.null_count();
+            if let Some(hashes) = &mut column.hashes {
+                for row in 0..array.len() {
+                    if array.is_valid(row) {
+                        let value = ScalarValue::try_from_array(array, row)?;
+                        hashes.insert(hash_value(&value));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the index of `file`
+    pub fn finish(self, file: &ObjectMeta) -> Result<FileIndex> {
+        let mut columns = HashMap::new();
+        for (field, column) in self.schema.fields().iter().zip(self.columns) {
+            let column = match column {
+                Some(column) => column,
+                None => continue,
+            };
+            columns.insert(
+                field.name().clone(),
+                ColumnIndex {
+                    min: column.min.evaluate()?,
+                    max: column.max.evaluate()?,
+                    null_count: column.null_count,
+                    bloom: column
+                        .hashes
+                        .map(|hashes| BloomFilter::from_hashes(&hashes)),
+                },
+            );
+        }
+        Ok(FileIndex {
+            file_size: file.size,
+            last_modified: file.last_modified.timestamp_nanos(),
+            num_rows: self.num_rows,
+            columns,
+        })
+    }
+}
+
+/// Returns true for the types whose values are indexed in bloom filters:
+/// the types with an exact equality and a canonical representation
+fn supports_bloom_filter(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Date32
+            | DataType::Date64
+            | DataType::Timestamp(_, _)
+            | DataType::Decimal128(_, _)
+    )
+}
+
+/// Hashes the representation of a non null value with the 64 bits FNV-1a
+/// function, which unlike the hasher of the standard library is stable
+/// across releases, as required for the hashes stored in index files
+pub(crate) fn hash_value(value: &ScalarValue) -> u64 {
+    value
+        .to_string()
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// A bloom filter of the values of a column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    /// The bits of the filter
+    bits: Vec<u64>,
+    /// The number of bits set for each value
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Creates the filter of the values with `hashes`
+    fn from_hashes(hashes: &HashSet<u64>) -> Self {
+        let num_bits = (hashes.len() * BLOOM_BITS_PER_VALUE).max(64);
+        let mut filter = Self {
+            bits: vec![0; (num_bits + 63) / 64],
+            num_hashes: BLOOM_NUM_HASHES,
+        };
+        for hash in hashes {
+            for bit in filter.bit_indices(*hash) {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+
+    /// Returns false if the filter does not contain `value`
+    pub fn may_contain(&self, value: &ScalarValue) -> bool {
+        self.bit_indices(hash_value(value))
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Returns the bits of the value with `hash`, derived from the two
+    /// halves of the hash by double hashing
+    fn bit_indices(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_bits = self.bits.len() as u64 * 64;
+        let (h1, h2) = (hash & 0xffffffff, hash >> 32);
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    /// Serializes the filter as the number of hashes followed by the bits,
+    /// in little endian
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.bits.len() * 8);
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes a filter serialized by [`Self::to_bytes`]
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 12 || (bytes.len() - 4) % 8 != 0 {
+            return Err(DataFusionError::Execution(format!(
+                "Invalid bloom filter of {} bytes",
+                bytes.len()
+            )));
+        }
+        let num_hashes = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        let bits = bytes[4..]
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        Ok(Self { bits, num_hashes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use chrono::{TimeZone, Utc};
+    use datafusion_expr::{col, lit};
+    use object_store::memory::InMemory;
+
+    fn file_index() -> Result<FileIndex> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let mut builder = FileIndexBuilder::new(schema.clone());
+        for (a, b) in [
+            (vec![Some(5), None, Some(10)], vec!["x", "y", "z"]),
+            (vec![Some(20), Some(7)], vec!["y", "w"]),
+        ] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(a)),
+                    Arc::new(StringArray::from(b)),
+                ],
+            )?;
+            builder.update(&batch)?;
+        }
+        builder.finish(&ObjectMeta {
+            location: Path::from("data/file.csv"),
+            last_modified: Utc.timestamp(1_664_000_000, 0),
+            size: 100,
+        })
+    }
+
+    #[tokio::test]
+    async fn index_round_trip() -> Result<()> {
+        let index = file_index()?;
+        assert_eq!(index.num_rows, 5);
+        let a = &index.columns["a"];
+        assert_eq!(a.min, ScalarValue::Int32(Some(5)));
+        assert_eq!(a.max, ScalarValue::Int32(Some(20)));
+        assert_eq!(a.null_count, 1);
+
+        let store = InMemory::new();
+        let location = Path::from("data/file.csv");
+        assert!(FileIndex::read(&store, &location).await?.is_none());
+        index.write(&store, &location).await?;
+        store.head(&Path::from("data/file.csv.dfindex")).await?;
+
+        let read = FileIndex::read(&store, &location).await?.unwrap();
+        assert_eq!(read.file_size, 100);
+        assert_eq!(read.last_modified, 1_664_000_000_000_000_000);
+        assert_eq!(read.num_rows, 5);
+        for name in ["a", "b"] {
+            let (expected, actual) = (&index.columns[name], &read.columns[name]);
+            assert_eq!(expected.min, actual.min);
+            assert_eq!(expected.max, actual.max);
+            assert_eq!(expected.null_count, actual.null_count);
+            assert_eq!(expected.bloom, actual.bloom);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn index_min_max_pruning() -> Result<()> {
+        let index = file_index()?;
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let may_match = |expr: Expr| -> Result<bool> {
+            let predicate = PruningPredicate::try_new(expr.clone(), schema.clone())?;
+            index.may_match(Some(&predicate), &[expr])
+        };
+        assert!(may_match(col("a").gt(lit(15)))?);
+        assert!(!may_match(col("a").gt(lit(20)))?);
+        assert!(!may_match(col("a").lt(lit(5)))?);
+        Ok(())
+    }
+
+    #[test]
+    fn index_bloom_filter_pruning() -> Result<()> {
+        let index = file_index()?;
+        for value in ["w", "x", "y", "z"] {
+            assert!(index.may_match(None, &[col("b").eq(lit(value))])?);
+        }
+        // within the min and max values, but not in the file
+        assert!(!index.may_match(None, &[lit("xx").eq(col("b"))])?);
+        assert!(!index.may_match(None, &[col("b").in_list(vec![lit("v")], false)])?);
+        assert!(index.may_match(None, &[col("b").in_list(vec![lit("w")], false)])?);
+        assert!(!index.may_match(None, &[col("a").eq(lit(6)).and(lit(true))])?);
+        // literals of another type are not checked
+        assert!(index.may_match(None, &[col("a").eq(lit(6i64))])?);
+        Ok(())
+    }
+}
@@ -18,7 +18,9 @@
 //! A table that uses the `ObjectStore` listing capability
 //! to get the list of files to process.
 
+pub mod bucket;
 mod helpers;
+pub mod index;
 mod table;
 mod url;
 
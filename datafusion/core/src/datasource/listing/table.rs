@@ -23,7 +23,10 @@ use std::{any::Any, sync::Arc};
 
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use async_trait::async_trait;
+use datafusion_common::Constraints;
+use datafusion_optimizer::utils::conjunction;
 use futures::{future, stream, StreamExt, TryStreamExt};
+use log::debug;
 use object_store::path::Path;
 use object_store::ObjectMeta;
 use parking_lot::RwLock;
@@ -38,18 +41,25 @@ use crate::datasource::{
     listing::ListingTableUrl,
     TableProvider, TableType,
 };
+use crate::execution::context::TaskContext;
 use crate::logical_expr::TableProviderFilterPushDown;
+use crate::physical_optimizer::pruning::PruningPredicate;
 use crate::{
     error::{DataFusionError, Result},
     execution::context::SessionState,
     logical_expr::Expr,
     physical_plan::{
+        bucketed::BucketedExec,
         empty::EmptyExec,
+        execute_stream,
+        expressions::Column,
         file_format::{FileScanConfig, DEFAULT_PARTITION_COLUMN_DATATYPE},
         project_schema, ExecutionPlan, Statistics,
     },
 };
 
+use super::bucket::{file_bucket, group_by_bucket, pruned_buckets, Bucketing};
+use super::index::{FileIndex, FileIndexBuilder};
 use super::PartitionedFile;
 
 use super::helpers::{expr_applicable_for_cols, pruned_partition_list, split_files};
@@ -163,6 +173,7 @@ impl ListingTableConfig {
             file_extension,
             target_partitions: ctx.config.target_partitions,
             table_partition_cols: vec![],
+            use_index: false,
         };
 
         Ok(Self {
@@ -221,6 +232,11 @@ pub struct ListingOptions {
     /// Group files to avoid that the number of partitions exceeds
     /// this limit
     pub target_partitions: usize,
+    /// Set true to consult the index files of the data files, built with
+    /// [`ListingTable::build_index`], to skip the files that contain no rows
+    /// matching the filters of a scan. The indexes are also consulted when
+    /// the `datafusion.execution.use_file_indexes` option is set.
+    pub use_index: bool,
 }
 
 impl ListingOptions {
@@ -230,6 +246,7 @@ impl ListingOptions {
     /// - no input partition to discover
     /// - one target partition
     /// - stat collection
+    /// - no index files
     pub fn new(format: Arc<dyn FileFormat>) -> Self {
         Self {
             file_extension: String::new(),
@@ -237,6 +254,7 @@ impl ListingOptions {
             table_partition_cols: vec![],
             collect_stat: true,
             target_partitions: 1,
+            use_index: false,
         }
     }
 
@@ -302,6 +320,11 @@ pub struct ListingTable {
     table_schema: SchemaRef,
     options: ListingOptions,
     definition: Option<String>,
+    constraints: Constraints,
+    bucketing: Option<Bucketing>,
+    /// The statistics of the table, used instead of the statistics of its
+    /// files
+    statistics: Option<Statistics>,
     collected_statistics: StatisticsCache,
 }
 
@@ -338,6 +361,9 @@ impl ListingTable {
             table_schema: Arc::new(Schema::new(table_fields)),
             options,
             definition: None,
+            constraints: Constraints::empty(),
+            bucketing: None,
+            statistics: None,
             collected_statistics: Default::default(),
         };
 
@@ -350,6 +376,41 @@ impl ListingTable {
         self
     }
 
+    /// Specify the constraints declared on this table
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Specify the bucketing of the files of this table, whose names must
+    /// hold their bucket as the files written by
+    /// [`plan_to_bucketed`](super::bucket::plan_to_bucketed)
+    pub fn with_bucketing(mut self, bucketing: Bucketing) -> Self {
+        self.bucketing = Some(bucketing);
+        self
+    }
+
+    /// Specify the statistics of this table, such as known when it was
+    /// serialized, which its scans use instead of collecting the statistics
+    /// of its files
+    ///
+    /// The column statistics are ignored unless there is one for each
+    /// column of the table, including its partitioning columns.
+    pub fn with_statistics(mut self, mut statistics: Statistics) -> Self {
+        let num_columns = self.table_schema.fields().len();
+        if matches!(&statistics.column_statistics, Some(columns) if columns.len() != num_columns)
+        {
+            statistics.column_statistics = None;
+        }
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// Get the bucketing of the files of this table, if any
+    pub fn bucketing(&self) -> Option<&Bucketing> {
+        self.bucketing.as_ref()
+    }
+
     /// Get paths ref
     pub fn table_paths(&self) -> &Vec<ListingTableUrl> {
         &self.table_paths
@@ -375,6 +436,10 @@ impl TableProvider for ListingTable {
         TableType::Base
     }
 
+    fn statistics(&self) -> Option<Statistics> {
+        self.statistics.clone()
+    }
+
     async fn scan(
         &self,
         ctx: &SessionState,
@@ -382,18 +447,19 @@ impl TableProvider for ListingTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let (partitioned_file_lists, statistics) =
+        let (partitioned_file_lists, statistics, grouped_by_bucket) =
             self.list_files_for_scan(ctx, filters, limit).await?;
 
         // if no files need to be read, return an `EmptyExec`
-        if partitioned_file_lists.is_empty() {
+        if partitioned_file_lists.iter().all(Vec::is_empty) {
             let schema = self.schema();
             let projected_schema = project_schema(&schema, projection.as_ref())?;
             return Ok(Arc::new(EmptyExec::new(false, projected_schema)));
         }
 
         // create the execution plan
-        self.options
+        let plan = self
+            .options
             .format
             .create_physical_plan(
                 FileScanConfig {
@@ -408,7 +474,27 @@ impl TableProvider for ListingTable {
                 },
                 filters,
             )
-            .await
+            .await?;
+
+        // the partition `i` of the plan reads the bucket `i`
+        match &self.bucketing {
+            Some(bucketing) if grouped_by_bucket => {
+                let schema = plan.schema();
+                let columns = bucketing
+                    .columns
+                    .iter()
+                    .map(|name| Some(Column::new(name, schema.index_of(name).ok()?)))
+                    .collect::<Option<Vec<_>>>();
+                Ok(match columns {
+                    Some(columns) => {
+                        Arc::new(BucketedExec::new(plan, columns, bucketing.num_buckets))
+                    }
+                    // a bucket column is not projected
+                    None => plan,
+                })
+            }
+            _ => Ok(plan),
+        }
     }
 
     fn supports_filter_pushdown(
@@ -428,18 +514,24 @@ impl TableProvider for ListingTable {
     fn get_table_definition(&self) -> Option<&str> {
         self.definition.as_deref()
     }
+
+    fn constraints(&self) -> Option<&Constraints> {
+        Some(&self.constraints)
+    }
 }
 
 impl ListingTable {
     /// Get the list of files for a scan as well as the file level statistics.
     /// The list is grouped to let the execution plan know how the files should
-    /// be distributed to different threads / executors.
+    /// be distributed to different threads / executors. The files of a
+    /// bucketed table are grouped by bucket, as told by the returned flag,
+    /// unless some of them are not bucket files.
     async fn list_files_for_scan<'a>(
         &'a self,
         ctx: &'a SessionState,
         filters: &'a [Expr],
         limit: Option<usize>,
-    ) -> Result<(Vec<Vec<PartitionedFile>>, Statistics)> {
+    ) -> Result<(Vec<Vec<PartitionedFile>>, Statistics, bool)> {
         let store = ctx
             .runtime_env
             .object_store(&self.table_paths.get(0).unwrap())?;
@@ -457,10 +549,52 @@ impl ListingTable {
 
         let file_list = stream::iter(file_list).flatten();
 
+        // skip the buckets that can not contain matching rows
+        let buckets = self
+            .bucketing
+            .as_ref()
+            .and_then(|bucketing| pruned_buckets(bucketing, &self.file_schema, filters));
+
+        // skip the files whose index shows that they contain no matching rows
+        let use_index = self.options.use_index || ctx.config.use_file_indexes();
+        let index_predicate = match use_index {
+            true => self.index_predicate(filters),
+            false => None,
+        };
+        let file_list = file_list.try_filter_map(|part_file| {
+            let (store, index_predicate, buckets) = (&store, &index_predicate, &buckets);
+            async move {
+                if let (Some(buckets), Some(bucket)) =
+                    (buckets, file_bucket(&part_file.object_meta.location))
+                {
+                    if !buckets.contains(&bucket) {
+                        return Ok(None);
+                    }
+                }
+                if use_index {
+                    let index =
+                        FileIndex::read(store.as_ref(), &part_file.object_meta.location)
+                            .await?;
+                    match index {
+                        // an index built before the file was rewritten is ignored
+                        Some(index)
+                            if index.is_up_to_date(&part_file.object_meta)
+                                && !index
+                                    .may_match(index_predicate.as_ref(), filters)? =>
+                        {
+                            return Ok(None)
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Some(part_file))
+            }
+        });
+
         // collect the statistics if required by the config
         let files = file_list.then(|part_file| async {
             let part_file = part_file?;
-            let statistics = if self.options.collect_stat {
+            let statistics = if self.options.collect_stat && self.statistics.is_none() {
                 match self.collected_statistics.get(&part_file.object_meta) {
                     Some(statistics) => statistics,
                     None => {
@@ -486,11 +620,99 @@ impl ListingTable {
 
         let (files, statistics) =
             get_statistics_with_limit(files, self.schema(), limit).await?;
+        let statistics = match &self.statistics {
+            // the filters and the limit may skip some files of the table
+            Some(table_statistics) => Statistics {
+                is_exact: table_statistics.is_exact
+                    && filters.is_empty()
+                    && limit.is_none(),
+                ..table_statistics.clone()
+            },
+            None => statistics,
+        };
+
+        let (file_groups, grouped_by_bucket) = match &self.bucketing {
+            Some(bucketing) => match group_by_bucket(files, bucketing.num_buckets) {
+                Ok(groups) => (groups, true),
+                Err(files) => (split_files(files, self.options.target_partitions), false),
+            },
+            None => (split_files(files, self.options.target_partitions), false),
+        };
+        Ok((file_groups, statistics, grouped_by_bucket))
+    }
+
+    /// Returns the predicate checked against the minimum and maximum values
+    /// of the index files, built from the `filters` on the columns of the
+    /// files
+    fn index_predicate(&self, filters: &[Expr]) -> Option<PruningPredicate> {
+        let file_cols = self
+            .file_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        let filters = filters
+            .iter()
+            .filter(|filter| expr_applicable_for_cols(&file_cols, filter))
+            .cloned();
+        let predicate = conjunction(filters)?;
+        match PruningPredicate::try_new(predicate, self.file_schema.clone()) {
+            Ok(predicate) => Some(predicate),
+            Err(e) => {
+                debug!("Could not create the pruning predicate of the index: {}", e);
+                None
+            }
+        }
+    }
 
-        Ok((
-            split_files(files, self.options.target_partitions),
-            statistics,
-        ))
+    /// Builds the index file of each data file of the table, writing it next
+    /// to the data file, and returns the number of indexed files. This is what
+    /// `ANALYZE TABLE` runs. The indexes are consulted by the scans of the
+    /// tables created with [`ListingOptions::use_index`] set, or of all the
+    /// listing tables when the `datafusion.execution.use_file_indexes` option
+    /// is set.
+    pub async fn build_index(&self, ctx: &SessionState) -> Result<usize> {
+        let store = ctx
+            .runtime_env
+            .object_store(&self.table_paths.get(0).unwrap())?;
+        let task_ctx = Arc::new(TaskContext::from(ctx));
+
+        let mut num_files = 0;
+        for table_path in &self.table_paths {
+            let files: Vec<_> = table_path
+                .list_all_files(store.as_ref(), &self.options.file_extension)
+                .try_collect()
+                .await?;
+            for object_meta in files {
+                let plan = self
+                    .options
+                    .format
+                    .create_physical_plan(
+                        FileScanConfig {
+                            object_store_url: table_path.object_store(),
+                            file_schema: Arc::clone(&self.file_schema),
+                            file_groups: vec![vec![object_meta.clone().into()]],
+                            statistics: Statistics::default(),
+                            projection: None,
+                            limit: None,
+                            table_partition_cols: vec![],
+                            config_options: ctx.config.config_options(),
+                        },
+                        &[],
+                    )
+                    .await?;
+
+                let mut builder = FileIndexBuilder::new(Arc::clone(&self.file_schema));
+                let mut stream = execute_stream(plan, task_ctx.clone()).await?;
+                while let Some(batch) = stream.next().await {
+                    builder.update(&batch?)?;
+                }
+                let index = builder.finish(&object_meta)?;
+                index.write(store.as_ref(), &object_meta.location).await?;
+                num_files += 1;
+            }
+        }
+        Ok(num_files)
     }
 }
 
@@ -505,6 +727,7 @@ mod tests {
     };
     use arrow::datatypes::DataType;
     use chrono::DateTime;
+    use object_store::{memory::InMemory, ObjectStore};
 
     use super::*;
 
@@ -552,6 +775,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn scan_with_table_statistics() -> Result<()> {
+        let testdata = crate::test_util::parquet_test_data();
+        let filename = format!("{}/{}", testdata, "alltypes_plain.parquet");
+        let table_path = ListingTableUrl::parse(filename).unwrap();
+
+        let ctx = SessionContext::new();
+        let state = ctx.state();
+
+        let opt = ListingOptions::new(Arc::new(ParquetFormat::default()));
+        let schema = opt.infer_schema(&state, &table_path).await?;
+        let config = ListingTableConfig::new(table_path)
+            .with_listing_options(opt)
+            .with_schema(schema);
+
+        // the column statistics of another schema are ignored
+        let statistics = Statistics {
+            num_rows: Some(100),
+            total_byte_size: Some(1000),
+            column_statistics: Some(vec![Default::default()]),
+            is_exact: true,
+        };
+        let table = ListingTable::try_new(config)?.with_statistics(statistics);
+        assert_eq!(
+            table.statistics(),
+            Some(Statistics {
+                num_rows: Some(100),
+                total_byte_size: Some(1000),
+                column_statistics: None,
+                is_exact: true,
+            })
+        );
+
+        let exec = table.scan(&state, &None, &[], None).await?;
+        assert_eq!(exec.statistics().num_rows, Some(100));
+        assert!(exec.statistics().is_exact);
+        let exec = table.scan(&state, &None, &[], Some(1)).await?;
+        assert!(!exec.statistics().is_exact);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn read_empty_table() -> Result<()> {
         let ctx = SessionContext::new();
@@ -564,6 +829,7 @@ mod tests {
             table_partition_cols: vec![String::from("p1")],
             target_partitions: 4,
             collect_stat: true,
+            use_index: false,
         };
 
         let table_path = ListingTableUrl::parse("test:///table/").unwrap();
@@ -766,6 +1032,7 @@ mod tests {
             table_partition_cols: vec![],
             target_partitions,
             collect_stat: true,
+            use_index: false,
         };
 
         let schema = Schema::new(vec![Field::new("a", DataType::Boolean, false)]);
@@ -777,7 +1044,8 @@ mod tests {
 
         let table = ListingTable::try_new(config)?;
 
-        let (file_list, _) = table.list_files_for_scan(&ctx.state(), &[], None).await?;
+        let (file_list, _, _) =
+            table.list_files_for_scan(&ctx.state(), &[], None).await?;
 
         assert_eq!(file_list.len(), output_partitioning);
 
@@ -803,6 +1071,7 @@ mod tests {
             table_partition_cols: vec![],
             target_partitions,
             collect_stat: true,
+            use_index: false,
         };
 
         let schema = Schema::new(vec![Field::new("a", DataType::Boolean, false)]);
@@ -817,13 +1086,84 @@ mod tests {
 
         let table = ListingTable::try_new(config)?;
 
-        let (file_list, _) = table.list_files_for_scan(&ctx.state(), &[], None).await?;
+        let (file_list, _, _) =
+            table.list_files_for_scan(&ctx.state(), &[], None).await?;
 
         assert_eq!(file_list.len(), output_partitioning);
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn prune_files_with_index() -> Result<()> {
+        let ctx = SessionContext::new();
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("table/1.csv"), b"a\n1\n2\n3\n".to_vec().into())
+            .await?;
+        store
+            .put(&Path::from("table/2.csv"), b"a\n10\n12\n".to_vec().into())
+            .await?;
+        ctx.runtime_env()
+            .register_object_store("test", "", store.clone());
+
+        // no file extension, so that only the exclusion of the index files
+        // keeps them out of the table
+        let opt = ListingOptions {
+            file_extension: "".to_owned(),
+            format: Arc::new(CsvFormat::default()),
+            table_partition_cols: vec![],
+            target_partitions: 1,
+            collect_stat: false,
+            use_index: true,
+        };
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, false)]);
+        let table_path = ListingTableUrl::parse("test:///table/").unwrap();
+        let config = ListingTableConfig::new(table_path)
+            .with_listing_options(opt)
+            .with_schema(Arc::new(schema));
+        let table = ListingTable::try_new(config)?;
+
+        let num_files = |filter: Expr| {
+            let (table, state) = (&table, ctx.state());
+            async move {
+                let (file_list, _, _) =
+                    table.list_files_for_scan(&state, &[filter], None).await?;
+                Ok(file_list.iter().map(Vec::len).sum::<usize>()) as Result<usize>
+            }
+        };
+
+        // without index files, all the files are scanned
+        assert_eq!(num_files(col("a").eq(lit(11i64))).await?, 2);
+
+        assert_eq!(table.build_index(&ctx.state()).await?, 2);
+        assert_eq!(table.build_index(&ctx.state()).await?, 2);
+
+        // pruned with the minimum and maximum values
+        assert_eq!(num_files(col("a").gt(lit(5i64))).await?, 1);
+        assert_eq!(num_files(col("a").eq(lit(2i64))).await?, 1);
+        assert_eq!(num_files(col("a").eq(lit(5i64))).await?, 0);
+        // pruned with the bloom filter of 2.csv
+        assert_eq!(num_files(col("a").eq(lit(11i64))).await?, 0);
+
+        // the index of a rewritten file is ignored
+        store
+            .put(&Path::from("table/2.csv"), b"a\n11\n".to_vec().into())
+            .await?;
+        assert_eq!(num_files(col("a").eq(lit(11i64))).await?, 1);
+
+        // even when the rewritten file keeps its size
+        assert_eq!(table.build_index(&ctx.state()).await?, 2);
+        assert_eq!(num_files(col("a").eq(lit(13i64))).await?, 0);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        store
+            .put(&Path::from("table/2.csv"), b"a\n13\n".to_vec().into())
+            .await?;
+        assert_eq!(num_files(col("a").eq(lit(13i64))).await?, 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_statistics_cache() {
         let meta = ObjectMeta {
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::datasource::listing::index::INDEX_FILE_SUFFIX;
 use crate::datasource::object_store::ObjectStoreUrl;
 use datafusion_common::{DataFusionError, Result};
 use futures::stream::BoxStream;
@@ -153,7 +154,9 @@ impl ListingTableUrl {
         list.map_err(Into::into)
             .try_filter(move |meta| {
                 let path = &meta.location;
-                let extension_match = path.as_ref().ends_with(file_extension);
+                // the index files of the data files are never listed
+                let extension_match = path.as_ref().ends_with(file_extension)
+                    && !path.as_ref().ends_with(INDEX_FILE_SUFFIX);
                 let glob_match = match &self.glob {
                     Some(glob) => match self.strip_prefix(path) {
                         Some(mut segments) => {
@@ -0,0 +1,126 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Detection of the changes of the tables read by materialized views, which
+//! make their stored results stale.
+//!
+//! The changes are detected from the files of the listing tables read by a
+//! view, directly or through views. The changes of other tables can not be
+//! detected, so the results of the views reading them are always stale.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use datafusion_expr::expr_visitor::{ExprVisitable, ExpressionVisitor, Recursion};
+use futures::TryStreamExt;
+
+use crate::datasource::default_table_source::source_as_provider;
+use crate::datasource::listing::ListingTable;
+use crate::datasource::{TableProvider, ViewTable};
+use crate::error::Result;
+use crate::execution::context::SessionState;
+use crate::logical_expr::{Expr, LogicalPlan};
+
+/// The files of the listing tables read by a view, by URL, with their size
+/// and modification time in nanoseconds since the epoch
+pub(super) type SourceFiles = BTreeMap<String, (usize, i64)>;
+
+/// List the files of the listing tables read by `plan`, or return `None` if
+/// it reads other tables
+pub(super) async fn list_source_files(
+    state: &SessionState,
+    plan: &LogicalPlan,
+) -> Result<Option<SourceFiles>> {
+    let mut tables = vec![];
+    if !scanned_tables(plan, &mut tables) {
+        return Ok(None);
+    }
+
+    let mut files = SourceFiles::new();
+    for table in tables {
+        let table = table
+            .as_any()
+            .downcast_ref::<ListingTable>()
+            .expect("listing table");
+        for table_path in table.table_paths() {
+            let store = state.runtime_env.object_store(table_path)?;
+            let metas = table_path
+                .list_all_files(store.as_ref(), &table.options().file_extension)
+                .try_collect::<Vec<_>>()
+                .await?;
+            let store_url = table_path.object_store();
+            for meta in metas {
+                let url = format!("{}{}", store_url.as_str(), meta.location);
+                files.insert(url, (meta.size, meta.last_modified.timestamp_nanos()));
+            }
+        }
+    }
+    Ok(Some(files))
+}
+
+/// Collect the listing tables scanned by `plan` and its subqueries in
+/// `tables`, returning false if it scans other tables
+fn scanned_tables(plan: &LogicalPlan, tables: &mut Vec<Arc<dyn TableProvider>>) -> bool {
+    if let LogicalPlan::TableScan(scan) = plan {
+        let table = match source_as_provider(&scan.source) {
+            Ok(table) => table,
+            Err(_) => return false,
+        };
+        if table.as_any().is::<ListingTable>() {
+            tables.push(table);
+            return true;
+        }
+        return match table.as_any().downcast_ref::<ViewTable>() {
+            Some(view) => scanned_tables(view.logical_plan(), tables),
+            None => false,
+        };
+    }
+
+    let mut subqueries = vec![];
+    for expr in plan.expressions() {
+        let visitor = SubqueryVisitor {
+            subqueries: &mut subqueries,
+        };
+        if expr.accept(visitor).is_err() {
+            return false;
+        }
+    }
+    plan.inputs()
+        .into_iter()
+        .chain(subqueries.iter().map(AsRef::as_ref))
+        .all(|input| scanned_tables(input, tables))
+}
+
+/// The `ExpressionVisitor` collecting the plans of the subqueries of an
+/// expression
+struct SubqueryVisitor<'a> {
+    subqueries: &'a mut Vec<Arc<LogicalPlan>>,
+}
+
+impl ExpressionVisitor for SubqueryVisitor<'_> {
+    fn pre_visit(self, expr: &Expr) -> Result<Recursion<Self>> {
+        match expr {
+            Expr::Exists { subquery, .. }
+            | Expr::InSubquery { subquery, .. }
+            | Expr::ScalarSubquery(subquery) => {
+                self.subqueries.push(subquery.subquery.clone())
+            }
+            _ => {}
+        }
+        Ok(Recursion::Continue(self))
+    }
+}
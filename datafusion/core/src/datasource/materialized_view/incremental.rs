@@ -0,0 +1,358 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Incremental maintenance of materialized views over append-only listing
+//! tables.
+//!
+//! The results of projections and filters of a listing table are the union of
+//! their results over each file of the table, so refreshing such a view only
+//! appends its results over the files added since the last refresh. The
+//! results of `SUM`, `MIN`, `MAX` and `COUNT` aggregations of such plans are
+//! merged with the aggregates over the added files instead.
+//!
+//! The files read by the last computation of a view are recorded, with their
+//! sizes, in a snapshot manifest stored with its results. When one of them
+//! was removed or changed size, the table was not only appended to, and the
+//! results are computed again.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::datatypes::Schema;
+use datafusion_common::DataFusionError;
+use datafusion_expr::expr_visitor::{ExprVisitable, ExpressionVisitor, Recursion};
+use datafusion_expr::utils::from_plan;
+use datafusion_expr::{
+    cast, AggregateFunction, EmptyRelation, LogicalPlanBuilder, Projection,
+    SubqueryAlias, TableScan, Volatility,
+};
+use futures::TryStreamExt;
+
+use crate::datasource::default_table_source::source_as_provider;
+use crate::datasource::listing::{ListingTable, ListingTableConfig, ListingTableUrl};
+use crate::datasource::{provider_as_source, TableProvider};
+use crate::error::Result;
+use crate::execution::context::SessionState;
+use crate::logical_expr::{Expr, LogicalPlan};
+
+use super::{rollup_function, view_aggregate};
+
+/// The name of the snapshot manifest, in the directory of the results
+const SNAPSHOT_FILE: &str = "_snapshot";
+
+/// The files of a listing table, by URL, with their sizes
+pub(super) type Snapshot = BTreeMap<String, usize>;
+
+/// How the results of a view are updated with its results over added files
+pub(super) enum Maintenance {
+    /// The results are appended to the stored ones
+    Append,
+    /// The results are aggregated again with the stored ones, grouped by the
+    /// columns of the view without a function, and rolling up the others with
+    /// their function
+    Merge(Vec<Option<AggregateFunction>>),
+}
+
+/// The incremental maintenance of a view reading an append-only listing table
+pub(super) struct IncrementalView {
+    maintenance: Maintenance,
+    /// The listing table read by the view
+    source: Arc<dyn TableProvider>,
+}
+
+impl IncrementalView {
+    /// The incremental maintenance of the view computing `plan`, if it can be
+    /// maintained incrementally
+    pub(super) fn try_new(plan: &LogicalPlan) -> Option<Self> {
+        if let Some(source) = appendable_source(plan) {
+            return Some(Self {
+                maintenance: Maintenance::Append,
+                source,
+            });
+        }
+
+        let (aggregate, indices) = view_aggregate(plan)?;
+        if aggregate
+            .group_expr
+            .iter()
+            .any(|e| matches!(e, Expr::GroupingSet(_)))
+        {
+            return None;
+        }
+        let source = appendable_source(&aggregate.input)?;
+
+        // the function merging every column of the view, or none for the
+        // grouping columns, which must all be columns of the view
+        let group_len = aggregate.group_expr.len();
+        let mut functions = vec![None; plan.schema().fields().len()];
+        for (i, index) in indices.into_iter().enumerate() {
+            let function = if i < group_len {
+                None
+            } else {
+                Some(rollup_function(&aggregate.aggr_expr[i - group_len])?)
+            };
+            match index {
+                Some(index) => functions[index] = Some(function),
+                None if function.is_none() => return None,
+                None => {}
+            }
+        }
+        let functions = functions.into_iter().collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            maintenance: Maintenance::Merge(functions),
+            source,
+        })
+    }
+
+    /// How the results of the view are updated
+    pub(super) fn maintenance(&self) -> &Maintenance {
+        &self.maintenance
+    }
+
+    fn listing_table(&self) -> &ListingTable {
+        self.source
+            .as_any()
+            .downcast_ref::<ListingTable>()
+            .expect("listing table")
+    }
+
+    /// List the files of the table read by the view
+    pub(super) async fn list_files(&self, state: &SessionState) -> Result<Snapshot> {
+        let table = self.listing_table();
+        let mut files = Snapshot::new();
+        for table_path in table.table_paths() {
+            let store = state.runtime_env.object_store(table_path)?;
+            let metas = table_path
+                .list_all_files(store.as_ref(), &table.options().file_extension)
+                .try_collect::<Vec<_>>()
+                .await?;
+            let store_url = table_path.object_store();
+            for meta in metas {
+                let url = format!("{}{}", store_url.as_str(), meta.location);
+                files.insert(url, meta.size);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Rewrite `plan`, the plan of the view, to only read `files` of its table
+    pub(super) fn with_files(
+        &self,
+        plan: &LogicalPlan,
+        files: &Snapshot,
+    ) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::TableScan(scan) => {
+                if files.is_empty() {
+                    return Ok(LogicalPlan::EmptyRelation(EmptyRelation {
+                        produce_one_row: false,
+                        schema: scan.projected_schema.clone(),
+                    }));
+                }
+                let table = self.listing_table();
+                let table_paths = files
+                    .keys()
+                    .map(ListingTableUrl::parse)
+                    .collect::<Result<Vec<_>>>()?;
+                let config = ListingTableConfig::new_with_multi_paths(table_paths)
+                    .with_listing_options(table.options().clone())
+                    .with_schema(table.schema());
+                let source = provider_as_source(Arc::new(ListingTable::try_new(config)?));
+                Ok(LogicalPlan::TableScan(TableScan {
+                    source,
+                    ..scan.clone()
+                }))
+            }
+            _ => {
+                let expr = plan.expressions();
+                let new_inputs = plan
+                    .inputs()
+                    .into_iter()
+                    .map(|input| self.with_files(input, files))
+                    .collect::<Result<Vec<_>>>()?;
+                from_plan(plan, &expr, &new_inputs)
+            }
+        }
+    }
+}
+
+/// The listing table read by `plan`, if the results of `plan` are the union of
+/// its results over each file of the table
+fn appendable_source(plan: &LogicalPlan) -> Option<Arc<dyn TableProvider>> {
+    match plan {
+        LogicalPlan::Projection(Projection { expr, input, .. })
+            if expr.iter().all(is_row_wise) =>
+        {
+            appendable_source(input)
+        }
+        LogicalPlan::Filter(filter) if is_row_wise(filter.predicate()) => {
+            appendable_source(filter.input())
+        }
+        LogicalPlan::SubqueryAlias(SubqueryAlias { input, .. }) => {
+            appendable_source(input)
+        }
+        LogicalPlan::TableScan(scan) if scan.fetch.is_none() => {
+            let table = source_as_provider(&scan.source).ok()?;
+            // the values of partition columns come from the paths of the files
+            // in the table
+            let appendable = matches!(
+                table.as_any().downcast_ref::<ListingTable>(),
+                Some(listing) if listing.options().table_partition_cols.is_empty()
+            );
+            appendable.then(|| table)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the value of `expr` for a row only depends on the row
+fn is_row_wise(expr: &Expr) -> bool {
+    let mut row_wise = true;
+    expr.accept(RowWiseVisitor {
+        row_wise: &mut row_wise,
+    })
+    .map(|_| row_wise)
+    .unwrap_or(false)
+}
+
+/// The `ExpressionVisitor` for `is_row_wise`, rejecting subqueries and the
+/// functions that are not immutable
+struct RowWiseVisitor<'a> {
+    row_wise: &'a mut bool,
+}
+
+impl ExpressionVisitor for RowWiseVisitor<'_> {
+    fn pre_visit(self, expr: &Expr) -> Result<Recursion<Self>> {
+        let row_wise = match expr {
+            Expr::Exists { .. } | Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => {
+                false
+            }
+            Expr::ScalarFunction { fun, .. } => fun.volatility() == Volatility::Immutable,
+            Expr::ScalarUDF { fun, .. } => {
+                fun.signature.volatility == Volatility::Immutable
+            }
+            _ => true,
+        };
+        if row_wise {
+            Ok(Recursion::Continue(self))
+        } else {
+            *self.row_wise = false;
+            Ok(Recursion::Stop(self))
+        }
+    }
+}
+
+/// The plan merging the stored results of a view, `stored`, with its results
+/// over the added files, `delta`, rolling up the columns with `functions`,
+/// and producing the columns of `schema`
+pub(super) fn merge_plan(
+    stored: LogicalPlan,
+    delta: LogicalPlan,
+    functions: &[Option<AggregateFunction>],
+    schema: &Schema,
+) -> Result<LogicalPlan> {
+    let union = LogicalPlanBuilder::from(stored).union(delta)?.build()?;
+    let columns = union
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| Expr::Column(field.qualified_column()))
+        .collect::<Vec<_>>();
+    let group_expr = functions
+        .iter()
+        .zip(&columns)
+        .filter(|(function, _)| function.is_none())
+        .map(|(_, column)| column.clone())
+        .collect::<Vec<_>>();
+    let aggr_expr = functions
+        .iter()
+        .zip(&columns)
+        .filter_map(|(function, column)| {
+            function.as_ref().map(|fun| Expr::AggregateFunction {
+                fun: fun.clone(),
+                args: vec![column.clone()],
+                distinct: false,
+                filter: None,
+            })
+        })
+        .collect::<Vec<_>>();
+    let group_len = group_expr.len();
+    let aggregate = LogicalPlanBuilder::from(union)
+        .aggregate(group_expr, aggr_expr)?
+        .build()?;
+
+    // the grouping columns come first in the aggregation, so the columns are
+    // reordered as in the view, with the names and types of the view
+    let (mut group_index, mut aggr_index) = (0, group_len);
+    let expr = functions
+        .iter()
+        .zip(schema.fields())
+        .map(|(function, field)| {
+            let index = match function {
+                None => &mut group_index,
+                Some(_) => &mut aggr_index,
+            };
+            let column = aggregate.schema().field(*index);
+            *index += 1;
+            let expr = Expr::Column(column.qualified_column());
+            if column.data_type() != field.data_type() {
+                cast(expr, field.data_type().clone()).alias(field.name())
+            } else {
+                expr.alias(field.name())
+            }
+        })
+        .collect::<Vec<_>>();
+    LogicalPlanBuilder::from(aggregate).project(expr)?.build()
+}
+
+/// Read the snapshot stored in the directory `dir`, if there is one
+pub(super) fn read_snapshot(dir: &str) -> Result<Option<Snapshot>> {
+    let path = Path::new(dir).join(SNAPSHOT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut snapshot = Snapshot::new();
+    for line in fs::read_to_string(path)?.lines() {
+        let (size, url) = line
+            .split_once(' ')
+            .and_then(|(size, url)| Some((size.parse().ok()?, url)))
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Invalid materialized view snapshot line: {}",
+                    line
+                ))
+            })?;
+        snapshot.insert(url.to_owned(), size);
+    }
+    Ok(Some(snapshot))
+}
+
+/// Write `snapshot` to the directory `dir`, replacing the previous one
+pub(super) fn write_snapshot(dir: &str, snapshot: &Snapshot) -> Result<()> {
+    let content = snapshot
+        .iter()
+        .map(|(url, size)| format!("{} {}\n", size, url))
+        .collect::<String>();
+    let path = Path::new(dir).join(SNAPSHOT_FILE);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
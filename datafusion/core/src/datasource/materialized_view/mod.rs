@@ -0,0 +1,725 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Materialized view data source, which stores the results of a LogicalPlan
+//! as Parquet files, and the optimizer rule answering queries from them.
+
+mod freshness;
+mod incremental;
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{any::Any, fs, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion_common::{Column, DFSchemaRef};
+use datafusion_expr::expr_rewriter::replace_col;
+use datafusion_expr::utils::{expr_to_columns, from_plan};
+use datafusion_expr::{
+    cast, Aggregate, AggregateFunction, LogicalPlanBuilder, Projection,
+    TableProviderFilterPushDown,
+};
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    catalog::catalog::CatalogList,
+    error::Result,
+    logical_expr::{Expr, LogicalPlan},
+    optimizer::optimizer::{OptimizerConfig, OptimizerRule},
+    physical_plan::{file_format::plan_to_parquet, ExecutionPlan},
+};
+
+use crate::datasource::file_format::parquet::ParquetFormat;
+use crate::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use crate::datasource::{provider_as_source, TableProvider, TableType};
+use crate::execution::context::SessionState;
+
+use self::freshness::{list_source_files, SourceFiles};
+use self::incremental::{
+    merge_plan, read_snapshot, write_snapshot, IncrementalView, Maintenance, Snapshot,
+};
+
+/// An implementation of `TableProvider` that stores the results of another
+/// logical plan, which are only recomputed when the view is refreshed.
+///
+/// Queries computing the same results as the view, or aggregations that can
+/// be rolled up from the results of the view, are rewritten to read the view
+/// by the [`MaterializedViewRewrite`] rule, as long as the files of the
+/// listing tables read by the view did not change since it was refreshed.
+///
+/// Views projecting and filtering a listing table without partition columns,
+/// possibly aggregating it with `SUM`, `MIN`, `MAX` and `COUNT`, are refreshed
+/// incrementally, only reading the files added to the table since the last
+/// refresh, as long as no file was removed or changed size.
+pub struct MaterializedView {
+    /// LogicalPlan of the view
+    logical_plan: LogicalPlan,
+    /// SQL used to create the view, if available
+    definition: Option<String>,
+    /// Directory of the Parquet files storing the results
+    location: String,
+    /// Whether the directory was created in the temporary directory, and is
+    /// removed with the view
+    temporary: bool,
+    /// Table reading the Parquet files
+    table: Arc<ListingTable>,
+    /// How the view is refreshed incrementally, if it can be
+    incremental: Option<IncrementalView>,
+    /// The files of the tables read by the view when its results were
+    /// computed, or `None` if it reads tables whose changes can not be
+    /// detected
+    source_files: Mutex<Option<SourceFiles>>,
+    /// Whether the files of the tables read by the view were unchanged when
+    /// last checked, so that the view can answer queries
+    fresh: AtomicBool,
+}
+
+impl MaterializedView {
+    /// Create a new view, computing the results of `logical_plan` and
+    /// storing them in the directory `location`, which must not exist, or in
+    /// a new temporary directory.
+    pub async fn try_create(
+        state: &SessionState,
+        logical_plan: LogicalPlan,
+        location: Option<String>,
+        definition: Option<String>,
+    ) -> Result<Self> {
+        let (location, temporary) = match location {
+            Some(location) => (location, false),
+            None => {
+                let path = std::env::temp_dir()
+                    .join(format!("datafusion-mv-{}", Uuid::new_v4()));
+                (path.to_string_lossy().into_owned(), true)
+            }
+        };
+        let incremental = IncrementalView::try_new(&logical_plan);
+        // the files are listed first, so that the files changed while the
+        // results are computed make them stale
+        let source_files = list_source_files(state, &logical_plan).await?;
+        compute(state, &logical_plan, incremental.as_ref(), &location).await?;
+
+        let schema: SchemaRef =
+            Arc::new(logical_plan.schema().as_ref().to_owned().into());
+        let options = ListingOptions {
+            file_extension: ".parquet".to_owned(),
+            target_partitions: state.config.target_partitions,
+            ..ListingOptions::new(Arc::new(ParquetFormat::default()))
+        };
+        let config = ListingTableConfig::new(ListingTableUrl::parse(&location)?)
+            .with_listing_options(options)
+            .with_schema(schema);
+        let table = Arc::new(ListingTable::try_new(config)?);
+
+        Ok(Self {
+            logical_plan,
+            definition,
+            location,
+            temporary,
+            table,
+            incremental,
+            fresh: AtomicBool::new(source_files.is_some()),
+            source_files: Mutex::new(source_files),
+        })
+    }
+
+    /// Get definition ref
+    pub fn definition(&self) -> &Option<String> {
+        &self.definition
+    }
+
+    /// Get logical_plan ref
+    pub fn logical_plan(&self) -> &LogicalPlan {
+        &self.logical_plan
+    }
+
+    /// Get the directory storing the results of the view
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Whether the view is refreshed incrementally
+    pub fn is_incremental(&self) -> bool {
+        self.incremental.is_some()
+    }
+
+    /// Whether the results of the view were up to date when last checked by
+    /// [`Self::check_freshness`] or refreshed
+    pub fn is_fresh(&self) -> bool {
+        self.fresh.load(Ordering::Relaxed)
+    }
+
+    /// Check whether the results of the view are up to date, as no file of
+    /// the listing tables it reads was added, removed or changed since they
+    /// were computed. The results of the views reading other tables are
+    /// never up to date.
+    pub async fn check_freshness(&self, state: &SessionState) -> Result<bool> {
+        let previous = self.source_files.lock().clone();
+        let fresh = match previous {
+            Some(previous) => {
+                list_source_files(state, &self.logical_plan).await? == Some(previous)
+            }
+            None => false,
+        };
+        self.fresh.store(fresh, Ordering::Relaxed);
+        Ok(fresh)
+    }
+
+    /// Update the results of the view, replacing the stored ones once all of
+    /// them are computed.
+    ///
+    /// The results of incremental views are only computed over the files
+    /// added to their table since the last refresh, unless files were removed
+    /// or changed, and the others are recomputed.
+    pub async fn refresh(&self, state: &SessionState) -> Result<()> {
+        let source_files = list_source_files(state, &self.logical_plan).await?;
+        self.refresh_results(state).await?;
+        self.fresh.store(source_files.is_some(), Ordering::Relaxed);
+        *self.source_files.lock() = source_files;
+        Ok(())
+    }
+
+    /// Update the results of the view, incrementally if possible
+    async fn refresh_results(&self, state: &SessionState) -> Result<()> {
+        if let Some(incremental) = &self.incremental {
+            if let Some(previous) = read_snapshot(&self.location)? {
+                let files = incremental.list_files(state).await?;
+                let append_only = previous
+                    .iter()
+                    .all(|(url, size)| files.get(url) == Some(size));
+                if append_only {
+                    let added = files
+                        .iter()
+                        .filter(|(url, _)| !previous.contains_key(*url))
+                        .map(|(url, size)| (url.clone(), *size))
+                        .collect::<Snapshot>();
+                    return self
+                        .refresh_incremental(state, incremental, &files, &added)
+                        .await;
+                }
+            }
+        }
+
+        let staging = self.staging_location();
+        let result = compute(
+            state,
+            &self.logical_plan,
+            self.incremental.as_ref(),
+            &staging,
+        )
+        .await;
+        self.replace_results(result, &staging)
+    }
+
+    /// Update the results of the view with its results over the `added` files
+    /// of its table, which now has `files`
+    async fn refresh_incremental(
+        &self,
+        state: &SessionState,
+        incremental: &IncrementalView,
+        files: &Snapshot,
+        added: &Snapshot,
+    ) -> Result<()> {
+        if added.is_empty() {
+            return Ok(());
+        }
+        let delta = incremental.with_files(&self.logical_plan, added)?;
+        let staging = self.staging_location();
+        match incremental.maintenance() {
+            Maintenance::Append => {
+                if let Err(e) = materialize(state, &delta, &staging).await {
+                    let _ = fs::remove_dir_all(&staging);
+                    return Err(e);
+                }
+                // the files of every refresh are named differently
+                let prefix = Uuid::new_v4();
+                for entry in fs::read_dir(&staging)? {
+                    let entry = entry?;
+                    let name =
+                        format!("{}-{}", prefix, entry.file_name().to_string_lossy());
+                    fs::rename(entry.path(), Path::new(&self.location).join(name))?;
+                }
+                fs::remove_dir(&staging)?;
+                write_snapshot(&self.location, files)
+            }
+            Maintenance::Merge(functions) => {
+                let stored = LogicalPlanBuilder::scan(
+                    "stored",
+                    provider_as_source(self.table.clone()),
+                    None,
+                )?
+                .build()?;
+                let plan = merge_plan(stored, delta, functions, &self.table.schema())?;
+                let result = async {
+                    materialize(state, &plan, &staging).await?;
+                    write_snapshot(&staging, files)
+                }
+                .await;
+                self.replace_results(result, &staging)
+            }
+        }
+    }
+
+    /// A new directory next to the results, computing their replacement
+    fn staging_location(&self) -> String {
+        format!("{}.refresh-{}", self.location, Uuid::new_v4())
+    }
+
+    /// Replace the results with the ones computed in `staging`, or remove
+    /// them if their computation failed.
+    ///
+    /// The directories are swapped by renaming them, so that the results are
+    /// always complete: the previous results are moved aside, replaced by the
+    /// new ones and only then removed, or moved back if the replacement fails.
+    fn replace_results(&self, result: Result<()>, staging: &str) -> Result<()> {
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(staging);
+            return Err(e);
+        }
+        let previous = format!("{}.previous-{}", self.location, Uuid::new_v4());
+        if let Err(e) = fs::rename(&self.location, &previous) {
+            let _ = fs::remove_dir_all(staging);
+            return Err(e.into());
+        }
+        if let Err(e) = fs::rename(staging, &self.location) {
+            let _ = fs::rename(&previous, &self.location);
+            let _ = fs::remove_dir_all(staging);
+            return Err(e.into());
+        }
+        fs::remove_dir_all(&previous)?;
+        Ok(())
+    }
+}
+
+impl Drop for MaterializedView {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.location);
+        }
+    }
+}
+
+/// Compute the results of the view computing `logical_plan` in the directory
+/// `path`
+async fn compute(
+    state: &SessionState,
+    logical_plan: &LogicalPlan,
+    incremental: Option<&IncrementalView>,
+    path: &str,
+) -> Result<()> {
+    match incremental {
+        Some(incremental) => {
+            // the files are listed first, and only they are read, so that the
+            // next refresh reads the files added in the meantime
+            let files = incremental.list_files(state).await?;
+            let plan = incremental.with_files(logical_plan, &files)?;
+            materialize(state, &plan, path).await?;
+            write_snapshot(path, &files)
+        }
+        None => materialize(state, logical_plan, path).await,
+    }
+}
+
+/// Execute `logical_plan`, writing its results to the directory `path`
+async fn materialize(
+    state: &SessionState,
+    logical_plan: &LogicalPlan,
+    path: &str,
+) -> Result<()> {
+    // views are computed from their sources, not from the stored results of
+    // views, which could be their own
+    let mut state = state.start_statement()?;
+    state
+        .optimizer
+        .rules
+        .retain(|rule| rule.name() != MaterializedViewRewrite::NAME);
+    let plan = state.create_physical_plan(logical_plan).await?;
+    plan_to_parquet(&state, plan, path, None).await
+}
+
+#[async_trait]
+impl TableProvider for MaterializedView {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    fn get_table_definition(&self) -> Option<&str> {
+        self.definition.as_deref()
+    }
+
+    fn supports_filter_pushdown(
+        &self,
+        filter: &Expr,
+    ) -> Result<TableProviderFilterPushDown> {
+        self.table.supports_filter_pushdown(filter)
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: &Option<Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.table.scan(state, projection, filters, limit).await
+    }
+}
+
+/// Optimizer rule reading the results of materialized views instead of
+/// computing them again.
+///
+/// A part of a query is replaced by a scan of a view when:
+/// * it is the logical plan of the view, or
+/// * it is an aggregation of the input of the view's aggregation, possibly
+///   filtered on grouping columns of the view, grouped by a subset of the
+///   view's grouping expressions, and only computing `SUM`, `MIN`, `MAX` and
+///   `COUNT` aggregates that the view computes, in which case the aggregates
+///   are rolled up from the view's results.
+///
+/// The views are the materialized views registered in the catalogs, so the
+/// rule is meant to run first, while the query is planned the same way as
+/// the views were. Only the views found up to date by their last check,
+/// which [`SessionState::create_physical_plan`] runs before optimizing the
+/// plan, are used.
+///
+/// The rule only runs when the `datafusion.optimizer.materialized_view_rewrite`
+/// option is set.
+pub struct MaterializedViewRewrite {
+    catalog_list: Arc<dyn CatalogList>,
+}
+
+impl MaterializedViewRewrite {
+    pub(crate) const NAME: &'static str = "materialized_view_rewrite";
+
+    /// Create a new rule using the materialized views of `catalog_list`
+    pub fn new(catalog_list: Arc<dyn CatalogList>) -> Self {
+        Self { catalog_list }
+    }
+}
+
+/// Check whether the materialized views of the catalogs of `state` are up to
+/// date, so that only those answer queries
+pub(crate) async fn check_materialized_views(state: &SessionState) -> Result<()> {
+    for (_, table) in materialized_views(state.catalog_list.as_ref()) {
+        let view = table
+            .as_any()
+            .downcast_ref::<MaterializedView>()
+            .expect("materialized view");
+        view.check_freshness(state).await?;
+    }
+    Ok(())
+}
+
+/// The materialized views of the catalogs, with their names
+fn materialized_views(
+    catalog_list: &dyn CatalogList,
+) -> Vec<(String, Arc<dyn TableProvider>)> {
+    let mut views = vec![];
+    for catalog_name in catalog_list.catalog_names() {
+        let catalog = match catalog_list.catalog(&catalog_name) {
+            Some(catalog) => catalog,
+            None => continue,
+        };
+        for schema_name in catalog.schema_names() {
+            if schema_name == "information_schema" {
+                continue;
+            }
+            let schema = match catalog.schema(&schema_name) {
+                Some(schema) => schema,
+                None => continue,
+            };
+            for table_name in schema.table_names() {
+                if let Some(table) = schema.table(&table_name) {
+                    if table.as_any().is::<MaterializedView>() {
+                        views.push((table_name, table));
+                    }
+                }
+            }
+        }
+    }
+    views
+}
+
+impl OptimizerRule for MaterializedViewRewrite {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        _optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        let views = materialized_views(self.catalog_list.as_ref())
+            .into_iter()
+            .filter(|(_, table)| {
+                let view = table.as_any().downcast_ref::<MaterializedView>();
+                view.map_or(false, MaterializedView::is_fresh)
+            })
+            .collect::<Vec<_>>();
+        if views.is_empty() {
+            return Ok(plan.clone());
+        }
+        rewrite(plan, &views)
+    }
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+}
+
+fn rewrite(
+    plan: &LogicalPlan,
+    views: &[(String, Arc<dyn TableProvider>)],
+) -> Result<LogicalPlan> {
+    for (name, table) in views {
+        let view = table
+            .as_any()
+            .downcast_ref::<MaterializedView>()
+            .expect("materialized view");
+        if same_plan(plan, view.logical_plan()) {
+            return scan_view(name, table, plan.schema());
+        }
+        if let Some(plan) = rollup(plan, name, table, view.logical_plan())? {
+            return Ok(plan);
+        }
+    }
+
+    let expr = plan.expressions();
+    let new_inputs = plan
+        .inputs()
+        .into_iter()
+        .map(|input| rewrite(input, views))
+        .collect::<Result<Vec<_>>>()?;
+    from_plan(plan, &expr, &new_inputs)
+}
+
+/// Whether two plans compute the same results
+fn same_plan(plan: &LogicalPlan, other: &LogicalPlan) -> bool {
+    plan.schema() == other.schema() && format!("{:?}", plan) == format!("{:?}", other)
+}
+
+/// Read the whole view, with the columns of `schema`
+fn scan_view(
+    name: &str,
+    table: &Arc<dyn TableProvider>,
+    schema: &DFSchemaRef,
+) -> Result<LogicalPlan> {
+    let scan = LogicalPlanBuilder::scan(name, provider_as_source(table.clone()), None)?
+        .build()?;
+    project_to_schema(scan, schema)
+}
+
+/// Project the columns of `plan` to the columns of `schema`, which have the
+/// same order and possibly other names and types
+fn project_to_schema(plan: LogicalPlan, schema: &DFSchemaRef) -> Result<LogicalPlan> {
+    let expr = plan
+        .schema()
+        .fields()
+        .iter()
+        .zip(schema.fields())
+        .map(|(field, expected)| {
+            let column = Expr::Column(field.qualified_column());
+            if field.data_type() != expected.data_type() {
+                cast(column, expected.data_type().clone()).alias(expected.name())
+            } else if field.name() != expected.name() {
+                // the physical plan names its columns after the expressions
+                column.alias(expected.name())
+            } else {
+                column
+            }
+        })
+        .collect();
+    Ok(LogicalPlan::Projection(Projection::try_new_with_schema(
+        expr,
+        Arc::new(plan),
+        schema.clone(),
+        None,
+    )?))
+}
+
+/// The aggregation of a view, and for every output column of the
+/// aggregation, the index of the column of the view holding it
+fn view_aggregate(plan: &LogicalPlan) -> Option<(&Aggregate, Vec<Option<usize>>)> {
+    match plan {
+        LogicalPlan::Aggregate(aggregate) => Some((
+            aggregate,
+            (0..aggregate.schema.fields().len()).map(Some).collect(),
+        )),
+        LogicalPlan::Projection(Projection { expr, input, .. }) => match input.as_ref() {
+            LogicalPlan::Aggregate(aggregate) => {
+                let indices = aggregate
+                    .schema
+                    .fields()
+                    .iter()
+                    .map(|field| {
+                        let column = field.qualified_column();
+                        expr.iter().position(|e| match e {
+                            Expr::Column(c) => c == &column,
+                            Expr::Alias(e, _) => {
+                                matches!(e.as_ref(), Expr::Column(c) if c == &column)
+                            }
+                            _ => false,
+                        })
+                    })
+                    .collect();
+                Some((aggregate, indices))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Roll up the aggregation `plan` from the results of a view, if the view's
+/// aggregation is finer
+fn rollup(
+    plan: &LogicalPlan,
+    name: &str,
+    table: &Arc<dyn TableProvider>,
+    view_plan: &LogicalPlan,
+) -> Result<Option<LogicalPlan>> {
+    let aggregate = match plan {
+        LogicalPlan::Aggregate(aggregate) => aggregate,
+        _ => return Ok(None),
+    };
+    let (view_aggregate, indices) = match view_aggregate(view_plan) {
+        Some(view_aggregate) => view_aggregate,
+        None => return Ok(None),
+    };
+    let is_grouping_set = |e: &Expr| matches!(e, Expr::GroupingSet(_));
+    if aggregate.group_expr.iter().any(is_grouping_set)
+        || view_aggregate.group_expr.iter().any(is_grouping_set)
+    {
+        return Ok(None);
+    }
+
+    let predicate = if same_plan(&aggregate.input, &view_aggregate.input) {
+        None
+    } else {
+        match aggregate.input.as_ref() {
+            LogicalPlan::Filter(filter)
+                if same_plan(filter.input(), &view_aggregate.input) =>
+            {
+                Some(filter.predicate())
+            }
+            _ => return Ok(None),
+        }
+    };
+
+    let scan = LogicalPlanBuilder::scan(name, provider_as_source(table.clone()), None)?
+        .build()?;
+    let view_expr = view_aggregate
+        .group_expr
+        .iter()
+        .chain(&view_aggregate.aggr_expr)
+        .collect::<Vec<_>>();
+    // the column of the view holding the value of an expression of the
+    // view's aggregation
+    let view_column = |e: &Expr| {
+        view_expr
+            .iter()
+            .position(|view_e| *view_e == e)
+            .and_then(|i| indices[i])
+            .map(|i| scan.schema().field(i).qualified_column())
+    };
+
+    let mut group_expr = vec![];
+    for e in &aggregate.group_expr {
+        match view_column(e) {
+            Some(column) => group_expr.push(Expr::Column(column)),
+            None => return Ok(None),
+        }
+    }
+
+    let mut aggr_expr = vec![];
+    for e in &aggregate.aggr_expr {
+        let fun = match rollup_function(e) {
+            Some(fun) => fun,
+            None => return Ok(None),
+        };
+        // the sum of no counts is null instead of 0
+        let is_count = matches!(
+            e,
+            Expr::AggregateFunction {
+                fun: AggregateFunction::Count,
+                ..
+            }
+        );
+        if is_count && aggregate.group_expr.is_empty() {
+            return Ok(None);
+        }
+        match view_column(e) {
+            Some(column) => aggr_expr.push(Expr::AggregateFunction {
+                fun,
+                args: vec![Expr::Column(column)],
+                distinct: false,
+                filter: None,
+            }),
+            None => return Ok(None),
+        }
+    }
+
+    let mut builder = LogicalPlanBuilder::from(scan.clone());
+    if let Some(predicate) = predicate {
+        // the predicate can only use grouping columns of the view
+        let mut columns = HashSet::new();
+        expr_to_columns(predicate, &mut columns)?;
+        let mut replacements = vec![];
+        for column in columns {
+            match view_column(&Expr::Column(column.clone())) {
+                Some(view_column) => replacements.push((column, view_column)),
+                None => return Ok(None),
+            }
+        }
+        let replace_map = replacements
+            .iter()
+            .map(|(column, view_column)| (column, view_column))
+            .collect::<HashMap<&Column, &Column>>();
+        builder = builder.filter(replace_col(predicate.clone(), &replace_map)?)?;
+    }
+    let plan = builder.aggregate(group_expr, aggr_expr)?.build()?;
+    project_to_schema(plan, &aggregate.schema).map(Some)
+}
+
+/// The function rolling up the results of the aggregate expression `e` over
+/// parts of its input, if there is one
+fn rollup_function(e: &Expr) -> Option<AggregateFunction> {
+    match e {
+        Expr::AggregateFunction {
+            fun,
+            distinct: false,
+            filter: None,
+            ..
+        } => match fun {
+            AggregateFunction::Sum | AggregateFunction::Min | AggregateFunction::Max => {
+                Some(fun.clone())
+            }
+            AggregateFunction::Count => Some(AggregateFunction::Sum),
+            _ => None,
+        },
+        _ => None,
+    }
+}
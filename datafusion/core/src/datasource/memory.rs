@@ -26,6 +26,8 @@ use std::sync::Arc;
 use arrow::datatypes::SchemaRef;
 use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
+use datafusion_common::Constraints;
+use parking_lot::RwLock;
 
 use crate::datasource::{TableProvider, TableType};
 use crate::error::{DataFusionError, Result};
@@ -33,13 +35,14 @@ use crate::execution::context::{SessionState, TaskContext};
 use crate::logical_expr::Expr;
 use crate::physical_plan::common;
 use crate::physical_plan::memory::MemoryExec;
-use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::{repartition::RepartitionExec, Partitioning};
+use crate::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
 
 /// In-memory table
 pub struct MemTable {
     schema: SchemaRef,
-    batches: Vec<Vec<RecordBatch>>,
+    batches: RwLock<Vec<Vec<RecordBatch>>>,
+    constraints: Constraints,
 }
 
 impl MemTable {
@@ -52,7 +55,8 @@ impl MemTable {
         {
             Ok(Self {
                 schema,
-                batches: partitions,
+                batches: RwLock::new(partitions),
+                constraints: Constraints::empty(),
             })
         } else {
             Err(DataFusionError::Plan(
@@ -61,6 +65,17 @@ impl MemTable {
         }
     }
 
+    /// Specify the constraints declared on this table
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Get the record batches of each partition of this table
+    pub fn batches(&self) -> Vec<Vec<RecordBatch>> {
+        self.batches.read().clone()
+    }
+
     /// Create a mem table by reading from another data source
     pub async fn load(
         t: Arc<dyn TableProvider>,
@@ -132,6 +147,10 @@ impl TableProvider for MemTable {
         TableType::Base
     }
 
+    fn constraints(&self) -> Option<&Constraints> {
+        Some(&self.constraints)
+    }
+
     async fn scan(
         &self,
         _ctx: &SessionState,
@@ -140,17 +159,44 @@ impl TableProvider for MemTable {
         _limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         Ok(Arc::new(MemoryExec::try_new(
-            &self.batches.clone(),
+            &self.batches.read(),
             self.schema(),
             projection.clone(),
         )?))
     }
+
+    /// Appends the batches to the partitions of the table in turn, once all
+    /// of them are read, so that failed insertions leave the table unchanged
+    async fn insert_into(
+        &self,
+        _ctx: &SessionState,
+        input: SendableRecordBatchStream,
+    ) -> Result<usize> {
+        if !self.schema.contains(&input.schema()) {
+            return Err(DataFusionError::Plan(
+                "Mismatch between schema and batches".to_string(),
+            ));
+        }
+        let batches = common::collect(input).await?;
+        let num_rows = batches.iter().map(|batch| batch.num_rows()).sum();
+
+        let mut partitions = self.batches.write();
+        if partitions.is_empty() {
+            partitions.push(vec![]);
+        }
+        let num_partitions = partitions.len();
+        for (i, batch) in batches.into_iter().enumerate() {
+            partitions[i % num_partitions].push(batch);
+        }
+        Ok(num_rows)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::from_slice::FromSlice;
+    use crate::physical_plan::memory::MemoryStream;
     use crate::prelude::SessionContext;
     use arrow::array::Int32Array;
     use arrow::datatypes::{DataType, Field, Schema};
@@ -390,4 +436,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_insert_into() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = |values: &[i32]| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from_slice(values))],
+            )
+        };
+        let provider =
+            MemTable::try_new(schema.clone(), vec![vec![batch(&[1])?], vec![]])?;
+
+        let input = MemoryStream::try_new(
+            vec![batch(&[2, 3])?, batch(&[4])?, batch(&[5])?],
+            schema.clone(),
+            None,
+        )?;
+        let num_rows = provider
+            .insert_into(&session_ctx.state(), Box::pin(input))
+            .await?;
+        assert_eq!(num_rows, 4);
+        let partition_sizes = provider
+            .batches()
+            .iter()
+            .map(|batches| batches.len())
+            .collect::<Vec<_>>();
+        assert_eq!(partition_sizes, vec![3, 1]);
+
+        // batches of another schema are rejected
+        let other_schema =
+            Arc::new(Schema::new(vec![Field::new("b", DataType::Utf8, false)]));
+        let input = MemoryStream::try_new(vec![], other_schema, None)?;
+        let err = provider
+            .insert_into(&session_ctx.state(), Box::pin(input))
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Mismatch between schema and batches"));
+        Ok(())
+    }
 }
@@ -23,8 +23,10 @@ pub mod default_table_source;
 pub mod empty;
 pub mod file_format;
 pub mod listing;
+pub mod materialized_view;
 pub mod memory;
 pub mod object_store;
+pub mod policy;
 pub mod view;
 
 use futures::Stream;
@@ -34,7 +36,9 @@ pub use self::default_table_source::{
     provider_as_source, source_as_provider, DefaultTableSource,
 };
 use self::listing::PartitionedFile;
+pub use self::materialized_view::MaterializedView;
 pub use self::memory::MemTable;
+pub use self::policy::PolicyTable;
 pub use self::view::ViewTable;
 use crate::arrow::datatypes::{Schema, SchemaRef};
 use crate::error::Result;
@@ -0,0 +1,163 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Table data source enforcing the [`TablePolicy`] of another table
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion_common::Column;
+use datafusion_expr::{and, LogicalPlanBuilder, TablePolicy, UNNAMED_TABLE};
+
+use crate::{
+    error::{DataFusionError, Result},
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::ExecutionPlan,
+};
+
+use crate::catalog::schema::SchemaProvider;
+use crate::datasource::{provider_as_source, TableProvider, TableType};
+use crate::execution::context::SessionState;
+
+/// An implementation of `TableProvider` that scans another table through
+/// the filters and column masks of a [`TablePolicy`].
+///
+/// The policy is applied by the scans of the table, so the plans reading it
+/// can not be rewritten to evaluate their own predicates before the filters
+/// of the policy, or on the unmasked columns: no filter is pushed down to the
+/// table.
+pub struct PolicyTable {
+    table: Arc<dyn TableProvider>,
+    policy: Arc<dyn TablePolicy>,
+    /// The schema of the table, with the types of the masks of the masked
+    /// columns
+    schema: SchemaRef,
+}
+
+impl PolicyTable {
+    /// Create a table scanning `table` through `policy`
+    pub fn new(table: Arc<dyn TableProvider>, policy: Arc<dyn TablePolicy>) -> Self {
+        // the invalid policies fail the scans of the table
+        let schema = policy_plan(&table, policy.as_ref(), None, false)
+            .map(|plan| Arc::new(Schema::from(plan.schema().as_ref())))
+            .unwrap_or_else(|_| table.schema());
+        Self {
+            table,
+            policy,
+            schema,
+        }
+    }
+
+    /// The table scanned through the policy
+    pub fn table(&self) -> &Arc<dyn TableProvider> {
+        &self.table
+    }
+
+    /// The policy enforced on the scans of the table
+    pub fn policy(&self) -> &Arc<dyn TablePolicy> {
+        &self.policy
+    }
+}
+
+/// The table `name` of `schema`, scanned through the policy of the table if
+/// it has one and `table` does not enforce it yet
+pub(crate) fn with_table_policy(
+    schema: &dyn SchemaProvider,
+    name: &str,
+    table: Arc<dyn TableProvider>,
+) -> Arc<dyn TableProvider> {
+    if table.as_any().is::<PolicyTable>() {
+        return table;
+    }
+    match schema.table_policy(name) {
+        Some(policy) => Arc::new(PolicyTable::new(table, policy)),
+        None => table,
+    }
+}
+
+/// The plan scanning the `projection` of `table` through `policy`, without
+/// its filters unless `filter` is set
+fn policy_plan(
+    table: &Arc<dyn TableProvider>,
+    policy: &dyn TablePolicy,
+    projection: Option<&[usize]>,
+    filter: bool,
+) -> Result<LogicalPlan> {
+    let table_schema = table.schema();
+    let masks = policy.column_masks()?;
+    if let Some(name) = masks
+        .keys()
+        .find(|name| table_schema.field_with_name(name).is_err())
+    {
+        return Err(DataFusionError::Plan(format!(
+            "Column mask for unknown column '{}' of the table",
+            name
+        )));
+    }
+
+    let mut builder =
+        LogicalPlanBuilder::scan(UNNAMED_TABLE, provider_as_source(table.clone()), None)?;
+    if filter {
+        if let Some(predicate) = policy.filters()?.into_iter().reduce(and) {
+            builder = builder.filter(predicate)?;
+        }
+    }
+    let indices = match projection {
+        Some(projection) => projection.to_vec(),
+        None => (0..table_schema.fields().len()).collect(),
+    };
+    let expr = indices.into_iter().map(|i| {
+        let name = table_schema.field(i).name();
+        match masks.get(name) {
+            Some(mask) => mask.clone().alias(name),
+            None => Expr::Column(Column::from_name(name)),
+        }
+    });
+    builder.project(expr)?.build()
+}
+
+#[async_trait]
+impl TableProvider for PolicyTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table.table_type()
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: &Option<Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let plan = policy_plan(
+            &self.table,
+            self.policy.as_ref(),
+            projection.as_deref(),
+            true,
+        )?;
+        state.create_physical_plan(&plan).await
+    }
+}
@@ -108,9 +108,13 @@ impl TableProvider for ViewTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        // clone state and start_execution so that now() works in views
+        // the state is the snapshot of the statement scanning the view, so
+        // that now() evaluates to the same time in the view as in the
+        // statement
         let mut state_cloned = state.clone();
-        state_cloned.execution_props.start_execution();
+        state_cloned
+            .execution_props
+            .set_random_seed(state.config.random_seed());
         let plan = if let Some(projection) = projection {
             // avoiding adding a redundant projection (e.g. SELECT * FROM view)
             let current_projection =
@@ -0,0 +1,364 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Audit logging of the statements executed by a session
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use datafusion_common::digest::stable_digest;
+use futures::StreamExt;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::error::Result;
+use crate::execution::context::SessionState;
+use crate::logical_expr::expr_rewriter::{ExprRewritable, ExprRewriter};
+use crate::logical_expr::utils::from_plan;
+use crate::logical_expr::{Expr, LogicalPlan};
+use crate::physical_plan::stream::RecordBatchStreamAdapter;
+use crate::physical_plan::SendableRecordBatchStream;
+use crate::scalar::ScalarValue;
+
+/// A statement executed by a session, or that failed to be planned, as
+/// reported to its [`QueryAuditHook`]
+#[derive(Debug, Clone)]
+pub struct QueryAuditEvent {
+    /// Id of the session that executed the statement
+    pub session_id: String,
+    /// SQL text of the statement, if it was planned from SQL, with the values
+    /// of its `OPTIONS` redacted, see [`redact_options`]
+    pub sql: Option<String>,
+    /// Fingerprint of the logical plan of the statement, see
+    /// [`plan_fingerprint`], or an empty string if it failed to be planned
+    pub plan_fingerprint: String,
+    /// Tags set on the session when the query was executed
+    pub tags: HashMap<String, String>,
+    /// Time from the start of the execution until its results were consumed
+    pub duration: Duration,
+    /// Number of rows returned
+    pub rows: usize,
+    /// Whether the planning or the execution failed
+    pub failed: bool,
+}
+
+/// Hook invoked once for every query executed through a [`DataFrame`],
+/// after its results have been consumed or dropped, for every DDL statement
+/// once it has run, and for every SQL statement that failed to be planned.
+///
+/// [`DataFrame`]: crate::dataframe::DataFrame
+pub trait QueryAuditHook: Send + Sync {
+    /// Records an executed statement
+    fn on_query(&self, event: QueryAuditEvent);
+}
+
+/// Returns a fingerprint identifying the shape of `plan`, which is the same
+/// for plans that only differ in the values of their literals, such as
+/// `SELECT * FROM t WHERE id = 1` and `SELECT * FROM t WHERE id = 2`.
+///
+/// The fingerprint is the [`stable_digest`] of the plan, so fingerprints
+/// are the same in every process running the same version of DataFusion.
+pub fn plan_fingerprint(plan: &LogicalPlan) -> String {
+    let normalized = normalize_literals(plan).unwrap_or_else(|_| plan.clone());
+    let digest = stable_digest(format!("{}", normalized.display_indent()).as_bytes());
+    format!("{:016x}", digest)
+}
+
+/// Returns `sql` with the values of the `OPTIONS` of its DDL statements,
+/// such as the credentials of an external table, replaced by `'******'`.
+///
+/// Returns `None` if `sql` can't be tokenized, as its options can't be
+/// found.
+pub fn redact_options(sql: &str) -> Option<String> {
+    let dialect = GenericDialect {};
+    let mut tokens = Tokenizer::new(&dialect, sql).tokenize().ok()?;
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_options = match &tokens[i] {
+            Token::Word(w) => w.value.eq_ignore_ascii_case("OPTIONS"),
+            _ => false,
+        };
+        i += 1;
+        if !is_options {
+            continue;
+        }
+        // the key value pairs of the options, between parentheses
+        match tokens[i..]
+            .iter()
+            .position(|t| !matches!(t, Token::Whitespace(_)))
+        {
+            Some(offset) if tokens[i + offset] == Token::LParen => i += offset + 1,
+            _ => continue,
+        }
+        let mut is_value = false;
+        while i < tokens.len() && tokens[i] != Token::RParen {
+            match tokens[i] {
+                Token::Whitespace(_) => {}
+                Token::Comma => is_value = false,
+                _ if is_value => {
+                    tokens[i] = Token::SingleQuotedString("******".to_string())
+                }
+                _ => is_value = true,
+            }
+            i += 1;
+        }
+    }
+    Some(tokens.iter().map(|t| t.to_string()).collect())
+}
+
+/// Replaces every literal of `plan` with a null of the same type
+fn normalize_literals(plan: &LogicalPlan) -> Result<LogicalPlan> {
+    struct LiteralNormalizer {}
+
+    impl ExprRewriter for LiteralNormalizer {
+        fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+            match expr {
+                Expr::Literal(value) => Ok(Expr::Literal(
+                    ScalarValue::try_from(&value.get_datatype()).unwrap_or(value),
+                )),
+                _ => Ok(expr),
+            }
+        }
+    }
+
+    if let LogicalPlan::Explain(_) | LogicalPlan::Analyze(_) = plan {
+        return Ok(plan.clone());
+    }
+    let inputs = plan
+        .inputs()
+        .into_iter()
+        .map(normalize_literals)
+        .collect::<Result<Vec<_>>>()?;
+    let expr = plan
+        .expressions()
+        .into_iter()
+        .map(|e| e.rewrite(&mut LiteralNormalizer {}))
+        .collect::<Result<Vec<_>>>()?;
+    from_plan(plan, &expr, &inputs)
+}
+
+/// An execution in progress, reported to the hook once dropped
+pub(crate) struct QueryAudit {
+    hook: Arc<dyn QueryAuditHook>,
+    session_id: String,
+    sql: Option<String>,
+    plan_fingerprint: String,
+    tags: HashMap<String, String>,
+    start: Instant,
+    rows: AtomicUsize,
+    failed: AtomicBool,
+}
+
+impl QueryAudit {
+    /// Starts auditing the execution of `plan`, or of a statement that
+    /// failed to be planned if it is `None`, if `state` has an audit hook
+    pub(crate) fn start(
+        state: &SessionState,
+        sql: Option<&str>,
+        plan: Option<&LogicalPlan>,
+    ) -> Option<Arc<Self>> {
+        let hook = state.audit_hook.clone()?;
+        Some(Arc::new(Self {
+            hook,
+            session_id: state.session_id.clone(),
+            sql: sql.and_then(redact_options),
+            plan_fingerprint: plan.map(plan_fingerprint).unwrap_or_default(),
+            tags: state.session_tags.clone(),
+            start: Instant::now(),
+            rows: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+        }))
+    }
+
+    /// Records the outcome of a part of the execution
+    pub(crate) fn record<T>(&self, result: &Result<T>, rows: impl FnOnce(&T) -> usize) {
+        match result {
+            Ok(value) => {
+                self.rows.fetch_add(rows(value), Ordering::Relaxed);
+            }
+            Err(_) => self.failed.store(true, Ordering::Relaxed),
+        }
+    }
+
+    /// Records that the statement failed
+    pub(crate) fn fail(&self) {
+        self.failed.store(true, Ordering::Relaxed)
+    }
+
+    /// Wraps `stream` so that the rows it returns are recorded
+    pub(crate) fn stream(
+        self: &Arc<Self>,
+        stream: SendableRecordBatchStream,
+    ) -> SendableRecordBatchStream {
+        let audit = self.clone();
+        let schema = stream.schema();
+        Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            stream.inspect(move |batch| match batch {
+                Ok(batch) => {
+                    audit.rows.fetch_add(batch.num_rows(), Ordering::Relaxed);
+                }
+                Err(_) => audit.failed.store(true, Ordering::Relaxed),
+            }),
+        ))
+    }
+}
+
+impl Drop for QueryAudit {
+    fn drop(&mut self) {
+        self.hook.on_query(QueryAuditEvent {
+            session_id: std::mem::take(&mut self.session_id),
+            sql: self.sql.take(),
+            plan_fingerprint: std::mem::take(&mut self.plan_fingerprint),
+            tags: std::mem::take(&mut self.tags),
+            duration: self.start.elapsed(),
+            rows: *self.rows.get_mut(),
+            failed: *self.failed.get_mut(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::SessionContext;
+    use parking_lot::Mutex;
+
+    #[derive(Default)]
+    struct RecordingHook {
+        events: Mutex<Vec<QueryAuditEvent>>,
+    }
+
+    impl QueryAuditHook for RecordingHook {
+        fn on_query(&self, event: QueryAuditEvent) {
+            self.events.lock().push(event)
+        }
+    }
+
+    #[tokio::test]
+    async fn audit_queries() -> Result<()> {
+        let hook = Arc::new(RecordingHook::default());
+        let ctx = SessionContext::with_state(
+            SessionContext::new()
+                .state()
+                .with_query_audit_hook(hook.clone()),
+        );
+        ctx.set_session_tag("user", "alice");
+
+        ctx.sql("SELECT * FROM (VALUES (1), (2), (3)) AS t(a) WHERE a > 1")
+            .await?
+            .collect()
+            .await?;
+        let mut stream = ctx
+            .sql("SELECT * FROM (VALUES (1), (2), (3)) AS t(a) WHERE a > 2")
+            .await?
+            .execute_stream()
+            .await?;
+        while stream.next().await.is_some() {}
+        drop(stream);
+
+        let events = hook.events.lock();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].sql.as_deref(),
+            Some("SELECT * FROM (VALUES (1), (2), (3)) AS t(a) WHERE a > 1")
+        );
+        assert_eq!(events[0].rows, 2);
+        assert_eq!(events[1].rows, 1);
+        assert!(!events[0].failed);
+        assert_eq!(events[0].tags["user"], "alice");
+        assert_eq!(events[0].session_id, ctx.session_id());
+        // only the literals differ
+        assert_eq!(events[0].plan_fingerprint, events[1].plan_fingerprint);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn audit_ddl_and_planning_failures() -> Result<()> {
+        let hook = Arc::new(RecordingHook::default());
+        let ctx = SessionContext::with_state(
+            SessionContext::new()
+                .state()
+                .with_query_audit_hook(hook.clone()),
+        );
+
+        // reported once run, even if the empty result is not executed
+        ctx.sql("CREATE TABLE t AS SELECT * FROM (VALUES (1), (2)) AS v(a)")
+            .await?;
+        ctx.sql("CREATE VIEW t_view AS SELECT a FROM t")
+            .await?
+            .collect()
+            .await?;
+        ctx.sql("CREATE TABLE t AS SELECT 1")
+            .await
+            .expect_err("table already exists");
+        ctx.sql("SELECT b FROM t")
+            .await
+            .expect_err("unknown column");
+        ctx.sql("SELEC 1").await.expect_err("syntax error");
+
+        let events = hook.events.lock();
+        let summary = events
+            .iter()
+            .map(|e| (e.sql.as_deref().unwrap(), e.failed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "CREATE TABLE t AS SELECT * FROM (VALUES (1), (2)) AS v(a)",
+                    false
+                ),
+                ("CREATE VIEW t_view AS SELECT a FROM t", false),
+                ("CREATE TABLE t AS SELECT 1", true),
+                ("SELECT b FROM t", true),
+                ("SELEC 1", true),
+            ]
+        );
+        assert_ne!(events[2].plan_fingerprint, "");
+        assert_eq!(events[3].plan_fingerprint, "");
+        Ok(())
+    }
+
+    #[test]
+    fn redact_ddl_options() {
+        let sql = "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 's3://bucket/t.csv' \
+                   OPTIONS ('region' 'us-east-2', 'secret_access_key' 'abc')";
+        assert_eq!(
+            redact_options(sql).unwrap(),
+            "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 's3://bucket/t.csv' \
+             OPTIONS ('region' '******', 'secret_access_key' '******')"
+        );
+
+        let sql = "SELECT options FROM t WHERE a = 'x'";
+        assert_eq!(redact_options(sql).unwrap(), sql);
+        assert_eq!(redact_options("SELECT 'unterminated"), None);
+    }
+
+    #[tokio::test]
+    async fn fingerprint_depends_on_plan_shape() -> Result<()> {
+        let ctx = SessionContext::new();
+        let fingerprint = |sql: &str| -> Result<String> {
+            Ok(plan_fingerprint(&ctx.create_logical_plan(sql)?))
+        };
+        assert_eq!(fingerprint("SELECT 1 + 2")?, fingerprint("SELECT 3 + 4")?);
+        assert_ne!(fingerprint("SELECT 1 + 2")?, fingerprint("SELECT 1 - 2")?);
+        Ok(())
+    }
+}
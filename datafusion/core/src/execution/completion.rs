@@ -0,0 +1,520 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Completion candidates for partially typed SQL, for building SQL editors.
+//!
+//! The SQL text is usually incomplete while it is typed, so instead of being
+//! parsed it is tokenized, and the candidates are found from the tokens
+//! around the cursor and the relations named after `FROM` and `JOIN`.
+
+use std::sync::Arc;
+
+use datafusion_expr::{
+    aggregate_function, function, AggregateFunction, BuiltinScalarFunction, Signature,
+    TypeSignature,
+};
+use datafusion_sql::TableReference;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Token, Tokenizer, Word};
+use strum::IntoEnumIterator;
+
+use crate::catalog::schema::SchemaProvider;
+use crate::execution::context::SessionState;
+
+/// What a [`CompletionCandidate`] refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompletionKind {
+    /// A column of a relation in scope
+    Column,
+    /// A table, or the alias of a relation in scope
+    Table,
+    /// A schema of the default catalog
+    Schema,
+    /// A scalar or aggregate function
+    Function,
+}
+
+/// A completion candidate for the word typed at the cursor
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompletionCandidate {
+    /// The text completing the word
+    pub label: String,
+    /// What the candidate refers to
+    pub kind: CompletionKind,
+    /// The type of a column, the signature of a function or the name of the
+    /// table of an alias
+    pub detail: String,
+}
+
+impl CompletionCandidate {
+    fn new(
+        label: impl Into<String>,
+        kind: CompletionKind,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// What is expected at the cursor
+#[derive(Debug, PartialEq)]
+enum Context {
+    /// A table name, after `FROM` or `JOIN`
+    Table,
+    /// An expression, such as in the SELECT list or the WHERE clause
+    Expression,
+    /// Nothing that can be completed from the catalog
+    None,
+}
+
+/// A relation named in a FROM clause
+#[derive(Debug)]
+struct Relation {
+    name: String,
+    alias: Option<String>,
+}
+
+fn tokenize(sql: &str) -> Option<Vec<Token>> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql).tokenize().ok()?;
+    Some(
+        tokens
+            .into_iter()
+            .filter(|token| !matches!(token, Token::Whitespace(_) | Token::EOF))
+            .collect(),
+    )
+}
+
+/// The name a word refers to, which is lower cased unless it is quoted, as
+/// in the SQL planner
+fn ident(word: &Word) -> String {
+    match word.quote_style {
+        Some(_) => word.value.clone(),
+        None => word.value.to_ascii_lowercase(),
+    }
+}
+
+fn is_keyword(token: &Token, keyword: Keyword) -> bool {
+    matches!(token, Token::Word(word) if word.quote_style.is_none() && word.keyword == keyword)
+}
+
+/// Finds what is expected after `tokens`
+fn context(tokens: &[Token]) -> Context {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().rev() {
+        match token {
+            Token::RParen => depth += 1,
+            Token::LParen if depth == 0 => return Context::Expression,
+            Token::LParen => depth -= 1,
+            Token::Word(word) if depth == 0 && word.quote_style.is_none() => {
+                match word.keyword {
+                    Keyword::FROM | Keyword::JOIN => {
+                        // after the first relation of the clause, only a list
+                        // separator starts a new one
+                        let follows_separator = i + 1 == tokens.len()
+                            || matches!(tokens.last(), Some(Token::Comma));
+                        return if follows_separator {
+                            Context::Table
+                        } else {
+                            Context::None
+                        };
+                    }
+                    Keyword::SELECT
+                    | Keyword::WHERE
+                    | Keyword::ON
+                    | Keyword::BY
+                    | Keyword::HAVING
+                    | Keyword::AND
+                    | Keyword::OR
+                    | Keyword::NOT
+                    | Keyword::WHEN
+                    | Keyword::THEN
+                    | Keyword::ELSE
+                    | Keyword::CASE
+                    | Keyword::DISTINCT => return Context::Expression,
+                    Keyword::LIMIT | Keyword::OFFSET | Keyword::AS => {
+                        return Context::None
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Context::None
+}
+
+/// Finds the relations named after `FROM` and `JOIN` in `tokens`
+fn relations(tokens: &[Token]) -> Vec<Relation> {
+    let mut relations = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        if !(is_keyword(&tokens[i], Keyword::FROM)
+            || is_keyword(&tokens[i], Keyword::JOIN))
+        {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        loop {
+            let mut parts = vec![];
+            while let Some(Token::Word(word)) = tokens.get(i) {
+                if word.quote_style.is_none() && word.keyword != Keyword::NoKeyword {
+                    break;
+                }
+                parts.push(ident(word));
+                i += 1;
+                if matches!(tokens.get(i), Some(Token::Period)) {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            if parts.is_empty() {
+                break;
+            }
+            if matches!(tokens.get(i), Some(token) if is_keyword(token, Keyword::AS)) {
+                i += 1;
+            }
+            let alias = match tokens.get(i) {
+                Some(Token::Word(word))
+                    if word.quote_style.is_some()
+                        || word.keyword == Keyword::NoKeyword =>
+                {
+                    i += 1;
+                    Some(ident(word))
+                }
+                _ => None,
+            };
+            relations.push(Relation {
+                name: parts.join("."),
+                alias,
+            });
+            if matches!(tokens.get(i), Some(Token::Comma)) {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+    }
+    relations
+}
+
+/// Describes the arguments accepted by a function
+fn signature_detail(name: &str, signature: &TypeSignature) -> String {
+    let types = |types: &[arrow::datatypes::DataType]| {
+        types
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+    match signature {
+        TypeSignature::Exact(args) => format!(
+            "{}({})",
+            name,
+            args.iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeSignature::Uniform(n, args) => {
+            format!("{}({})", name, vec![types(args); *n].join(", "))
+        }
+        TypeSignature::Variadic(args) => format!("{}({}, ...)", name, types(args)),
+        TypeSignature::VariadicEqual => format!("{}(T, ...)", name),
+        TypeSignature::VariadicAny => format!("{}(any, ...)", name),
+        TypeSignature::Any(n) => format!("{}({})", name, vec!["any"; *n].join(", ")),
+        TypeSignature::OneOf(signatures) => signatures
+            .iter()
+            .map(|signature| signature_detail(name, signature))
+            .collect::<Vec<_>>()
+            .join(" or "),
+    }
+}
+
+fn function_candidate(name: &str, signature: &Signature) -> CompletionCandidate {
+    CompletionCandidate::new(
+        name,
+        CompletionKind::Function,
+        signature_detail(name, &signature.type_signature),
+    )
+}
+
+/// The schema `name`, or the default schema, of the default catalog. The
+/// name may be qualified by a catalog.
+fn schema(state: &SessionState, name: Option<&str>) -> Option<Arc<dyn SchemaProvider>> {
+    let table_ref = match name {
+        None => TableReference::Bare { table: "" },
+        Some(name) => match name.split_once('.') {
+            None => TableReference::Partial {
+                schema: name,
+                table: "",
+            },
+            Some((catalog, schema)) => TableReference::Full {
+                catalog,
+                schema,
+                table: "",
+            },
+        },
+    };
+    state.schema_for_ref(table_ref).ok()
+}
+
+/// Returns the completion candidates for the word ending at the byte offset
+/// `cursor` of `sql`: the tables of the catalog after `FROM` and `JOIN`, and
+/// the columns of the relations of the query and the functions elsewhere.
+///
+/// The candidates start with the part of the word before the cursor, compared
+/// without case. A word qualified by a relation, such as `t.a`, is completed
+/// with the columns of the relation, and one qualified by a schema, after
+/// `FROM`, with its tables.
+pub fn complete(
+    state: &SessionState,
+    sql: &str,
+    cursor: usize,
+) -> Vec<CompletionCandidate> {
+    let mut cursor = cursor.min(sql.len());
+    while !sql.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let before = &sql[..cursor];
+    let mut tokens = match tokenize(before) {
+        Some(tokens) => tokens,
+        // inside a string literal
+        None => return vec![],
+    };
+
+    // the word typed at the cursor, and its qualifiers
+    let ends_with_word = before
+        .chars()
+        .last()
+        .map(|c| c.is_alphanumeric() || c == '_' || c == '"')
+        .unwrap_or(false);
+    let prefix = match tokens.last() {
+        Some(Token::Word(word)) if ends_with_word => {
+            let prefix = word.value.to_lowercase();
+            tokens.pop();
+            prefix
+        }
+        _ => String::new(),
+    };
+    let mut qualifier = vec![];
+    while matches!(tokens.last(), Some(Token::Period)) {
+        match tokens.get(tokens.len().wrapping_sub(2)) {
+            Some(Token::Word(word)) => {
+                qualifier.insert(0, ident(word));
+                tokens.truncate(tokens.len() - 2);
+            }
+            _ => return vec![],
+        }
+    }
+    let qualifier = (!qualifier.is_empty()).then(|| qualifier.join("."));
+
+    let mut candidates = vec![];
+    match context(&tokens) {
+        Context::Table => {
+            if let Some(schema) = schema(state, qualifier.as_deref()) {
+                for table in schema.table_names() {
+                    candidates.push(CompletionCandidate::new(
+                        table.clone(),
+                        CompletionKind::Table,
+                        table,
+                    ));
+                }
+            }
+            if qualifier.is_none() {
+                let catalog = state.resolve_table_ref("").catalog;
+                if let Some(catalog) = state.catalog_list.catalog(catalog) {
+                    for schema in catalog.schema_names() {
+                        candidates.push(CompletionCandidate::new(
+                            schema.clone(),
+                            CompletionKind::Schema,
+                            schema,
+                        ));
+                    }
+                }
+            }
+        }
+        Context::Expression => {
+            // the relations may be named after the cursor, as in the SELECT list
+            let all_tokens = tokenize(sql).unwrap_or_else(|| tokens.clone());
+            for relation in relations(&all_tokens) {
+                let matches_qualifier = match &qualifier {
+                    Some(qualifier) => {
+                        relation.alias.as_ref() == Some(qualifier)
+                            || (relation.alias.is_none()
+                                && (&relation.name == qualifier
+                                    || relation
+                                        .name
+                                        .ends_with(&format!(".{}", qualifier))))
+                    }
+                    None => true,
+                };
+                if !matches_qualifier {
+                    continue;
+                }
+                let table_ref = TableReference::from(relation.name.as_str());
+                let resolved = state.resolve_table_ref(table_ref);
+                let table = state
+                    .schema_for_ref(table_ref)
+                    .ok()
+                    .and_then(|schema| schema.table(resolved.table));
+                if let Some(table) = table {
+                    for field in table.schema().fields() {
+                        candidates.push(CompletionCandidate::new(
+                            field.name().clone(),
+                            CompletionKind::Column,
+                            field.data_type().to_string(),
+                        ));
+                    }
+                }
+                if qualifier.is_none() {
+                    let label = relation.alias.unwrap_or_else(|| relation.name.clone());
+                    candidates.push(CompletionCandidate::new(
+                        label,
+                        CompletionKind::Table,
+                        relation.name,
+                    ));
+                }
+            }
+            if qualifier.is_none() {
+                for fun in BuiltinScalarFunction::iter() {
+                    candidates
+                        .push(function_candidate(fun.name(), &function::signature(&fun)));
+                }
+                for fun in AggregateFunction::iter() {
+                    candidates.push(function_candidate(
+                        fun.name(),
+                        &aggregate_function::signature(&fun),
+                    ));
+                }
+                for udf in state.scalar_functions.values() {
+                    candidates.push(function_candidate(&udf.name, &udf.signature));
+                }
+                for udaf in state.aggregate_functions.values() {
+                    candidates.push(function_candidate(&udaf.name, &udaf.signature));
+                }
+            }
+        }
+        Context::None => {}
+    }
+
+    candidates.retain(|candidate| candidate.label.to_lowercase().starts_with(&prefix));
+    candidates.sort_by(|a, b| (a.kind, &a.label).cmp(&(b.kind, &b.label)));
+    candidates.dedup();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::SessionContext;
+    use crate::test_util::aggr_test_schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn labels(candidates: &[CompletionCandidate], kind: CompletionKind) -> Vec<&str> {
+        candidates
+            .iter()
+            .filter(|c| c.kind == kind)
+            .map(|c| c.label.as_str())
+            .collect()
+    }
+
+    fn complete_at_end(ctx: &SessionContext, sql: &str) -> Vec<CompletionCandidate> {
+        ctx.complete_sql(sql, sql.len())
+    }
+
+    fn context() -> SessionContext {
+        let ctx = SessionContext::new();
+        let batch = RecordBatch::new_empty(aggr_test_schema());
+        ctx.register_batch("aggregate_test_100", batch.clone())
+            .unwrap();
+        ctx.register_batch("agg", batch).unwrap();
+        ctx
+    }
+
+    #[test]
+    fn complete_tables() {
+        let ctx = context();
+        let candidates = complete_at_end(&ctx, "SELECT * FROM ag");
+        assert_eq!(
+            labels(&candidates, CompletionKind::Table),
+            vec!["agg", "aggregate_test_100"]
+        );
+
+        let candidates = complete_at_end(&ctx, "SELECT * FROM agg, public.aggr");
+        assert_eq!(
+            labels(&candidates, CompletionKind::Table),
+            vec!["aggregate_test_100"]
+        );
+        assert!(labels(&candidates, CompletionKind::Schema).is_empty());
+
+        let candidates = complete_at_end(&ctx, "SELECT * FROM ");
+        assert_eq!(labels(&candidates, CompletionKind::Schema), vec!["public"]);
+
+        // an alias is being typed
+        assert!(complete_at_end(&ctx, "SELECT * FROM agg a").is_empty());
+    }
+
+    #[test]
+    fn complete_columns_and_functions() {
+        let ctx = context();
+
+        // the relations are named after the cursor
+        let sql = "SELECT c1, c FROM agg WHERE c2 > 1";
+        let candidates = ctx.complete_sql(sql, "SELECT c1, c".len());
+        assert_eq!(
+            labels(&candidates, CompletionKind::Column),
+            vec![
+                "c1", "c10", "c11", "c12", "c13", "c2", "c3", "c4", "c5", "c6", "c7",
+                "c8", "c9"
+            ]
+        );
+        let c1 = &candidates[0];
+        assert_eq!(c1.detail, "Utf8");
+        let functions = labels(&candidates, CompletionKind::Function);
+        assert!(functions.contains(&"character_length"), "{:?}", functions);
+        assert!(functions.contains(&"count"), "{:?}", functions);
+
+        let candidates = complete_at_end(&ctx, "SELECT sq");
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].detail, "sqrt(Float64 | Float32)");
+
+        // qualified by an alias
+        let sql = "SELECT x.c1 FROM aggregate_test_100 AS x JOIN agg ON x.c1";
+        let candidates = ctx.complete_sql(sql, "SELECT x.c1".len());
+        assert_eq!(
+            candidates,
+            vec![
+                CompletionCandidate::new("c1", CompletionKind::Column, "Utf8"),
+                CompletionCandidate::new("c10", CompletionKind::Column, "UInt64"),
+                CompletionCandidate::new("c11", CompletionKind::Column, "Float32"),
+                CompletionCandidate::new("c12", CompletionKind::Column, "Float64"),
+                CompletionCandidate::new("c13", CompletionKind::Column, "Utf8"),
+            ]
+        );
+
+        // inside a string literal
+        assert!(complete_at_end(&ctx, "SELECT 'c").is_empty());
+    }
+}
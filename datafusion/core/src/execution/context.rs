@@ -18,27 +18,35 @@
 //! SessionContext contains methods for registering data sources and executing queries
 use crate::{
     catalog::{
+        async_catalog::{AsyncCatalogProvider, ResolvedCatalogProvider, TableResolver},
         catalog::{CatalogList, MemoryCatalogList},
         information_schema::CatalogWithInformationSchema,
     },
-    datasource::listing::{ListingOptions, ListingTable},
+    datasource::listing::{bucket::Bucketing, ListingOptions, ListingTable},
     datasource::{
         file_format::{
             avro::AvroFormat, csv::CsvFormat, json::JsonFormat, parquet::ParquetFormat,
             FileFormat,
         },
-        MemTable, ViewTable,
+        materialized_view::{check_materialized_views, MaterializedViewRewrite},
+        MaterializedView, MemTable, ViewTable,
     },
     logical_expr::{PlanType, ToStringifiedPlan},
     optimizer::optimizer::Optimizer,
     physical_optimizer::{
         aggregate_statistics::AggregateStatistics,
-        hash_build_probe_order::HashBuildProbeOrder, optimizer::PhysicalOptimizerRule,
+        hash_build_probe_order::HashBuildProbeOrder,
+        nested_projection::NestedProjection,
+        optimizer::PhysicalOptimizerRule,
+        plan_rewriter::{ExecutionPlanRewriter, RewriteExecutionPlan},
+        projection_pushdown::ProjectionPushdown,
     },
 };
 pub use datafusion_physical_expr::execution_props::ExecutionProps;
+use datafusion_physical_expr::time_zone::SessionTimeZone;
 use datafusion_physical_expr::var_provider::is_system_variables;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::cell::RefCell;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{
@@ -51,8 +59,10 @@ use std::{
     fmt::Debug,
 };
 
-use arrow::datatypes::{DataType, SchemaRef};
+use arrow::datatypes::{DataType, Schema, SchemaRef};
 use arrow::record_batch::RecordBatch;
+use bytes::Bytes;
+use futures::Stream;
 
 use crate::catalog::{
     catalog::{CatalogProvider, MemoryCatalogProvider},
@@ -61,40 +71,59 @@ use crate::catalog::{
 use crate::dataframe::DataFrame;
 use crate::datasource::{
     listing::{ListingTableConfig, ListingTableUrl},
+    policy::with_table_policy,
     provider_as_source, TableProvider,
 };
 use crate::error::{DataFusionError, Result};
 use crate::logical_expr::{
-    CreateCatalog, CreateCatalogSchema, CreateExternalTable, CreateMemoryTable,
-    CreateView, DropTable, DropView, Explain, LogicalPlan, LogicalPlanBuilder,
-    TableSource, TableType, UNNAMED_TABLE,
+    AnalyzeTable, CreateCatalog, CreateCatalogSchema, CreateExternalTable,
+    CreateMemoryTable, CreateView, DropTable, DropView, Explain, LogicalPlan,
+    LogicalPlanBuilder, RefreshMaterializedView, SetVariable, TablePolicy, TableSource,
+    TableType, UNNAMED_TABLE,
 };
 use crate::optimizer::optimizer::{OptimizerConfig, OptimizerRule};
 use datafusion_sql::{ResolvedTableReference, TableReference};
 
+use crate::execution::copy::read_copy_data;
+use crate::physical_optimizer::bucketed_join::BucketedJoin;
 use crate::physical_optimizer::coalesce_batches::CoalesceBatches;
+use crate::physical_optimizer::combine_partial_final_agg::CombinePartialFinalAggregate;
+use crate::physical_optimizer::eliminate_sort_repartition::EliminateSortRepartition;
 use crate::physical_optimizer::merge_exec::AddCoalescePartitionsExec;
+use crate::physical_optimizer::output_coercion::OutputCoercion;
 use crate::physical_optimizer::repartition::Repartition;
 
 use crate::config::{
-    ConfigOptions, OPT_BATCH_SIZE, OPT_COALESCE_BATCHES, OPT_COALESCE_TARGET_BATCH_SIZE,
-    OPT_FILTER_NULL_JOIN_KEYS, OPT_OPTIMIZER_MAX_PASSES, OPT_OPTIMIZER_SKIP_FAILED_RULES,
+    ConfigOptions, OPT_BATCH_SIZE, OPT_CHECK_CONSTRAINTS_REJECTS_TABLE,
+    OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS, OPT_COALESCE_BATCHES,
+    OPT_COALESCE_TARGET_BATCH_SIZE, OPT_ELIMINATE_SORT_REPARTITION,
+    OPT_FILTER_NULL_JOIN_KEYS, OPT_MATERIALIZED_VIEW_REWRITE,
+    OPT_NESTED_PROJECTION_PUSHDOWN, OPT_OPTIMIZER_MAX_PASSES,
+    OPT_OPTIMIZER_RELY_ON_CONSTRAINTS, OPT_OPTIMIZER_SKIP_FAILED_RULES,
+    OPT_OUTPUT_COERCION, OPT_PHYSICAL_PROJECTION_PUSHDOWN, OPT_RANDOM_SEED,
+    OPT_SEARCH_PATH, OPT_SQL_PARSER_MAX_DEPTH, OPT_TIME_ZONE, OPT_USE_FILE_INDEXES,
 };
 use crate::datasource::file_format::file_type::{FileCompressionType, FileType};
+use crate::execution::audit::{QueryAudit, QueryAuditHook};
+use crate::execution::completion::{self, CompletionCandidate};
 use crate::execution::{runtime_env::RuntimeEnv, FunctionRegistry};
+use crate::physical_plan::check_constraints::CheckConstraints;
 use crate::physical_plan::file_format::{plan_to_csv, plan_to_json, plan_to_parquet};
+use crate::physical_plan::memory::MemoryStream;
 use crate::physical_plan::planner::DefaultPhysicalPlanner;
 use crate::physical_plan::udaf::AggregateUDF;
 use crate::physical_plan::udf::ScalarUDF;
-use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::PhysicalPlanner;
+use crate::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
 use crate::variable::{VarProvider, VarType};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use datafusion_common::ScalarValue;
 use datafusion_sql::{
-    parser::DFParser,
+    diagnostic::with_source_location,
+    parser::{DFParser, Statement as DFStatement},
     planner::{ContextProvider, SqlToRel},
+    sqlparser::dialect::GenericDialect,
 };
 use parquet::file::properties::WriterProperties;
 
@@ -217,6 +246,15 @@ impl SessionContext {
         self.session_id.clone()
     }
 
+    /// Sets a tag describing this session, such as the user it runs queries
+    /// for, that is reported with every query to the [`QueryAuditHook`]
+    pub fn set_session_tag(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.state
+            .write()
+            .session_tags
+            .insert(key.into(), value.into());
+    }
+
     /// Return a copied version of config for this Session
     pub fn copied_config(&self) -> SessionConfig {
         self.state.read().config.clone()
@@ -227,7 +265,69 @@ impl SessionContext {
     /// This method is `async` because queries of type `CREATE EXTERNAL TABLE`
     /// might require the schema to be inferred.
     pub async fn sql(&self, sql: &str) -> Result<Arc<DataFrame>> {
-        let plan = self.create_logical_plan(sql)?;
+        let plan = match self.create_logical_plan_resolving_tables(sql).await {
+            Ok(plan) => plan,
+            Err(e) => {
+                // the statements failing to be planned are reported right away
+                if let Some(audit) =
+                    QueryAudit::start(&self.state.read(), Some(sql), None)
+                {
+                    audit.fail();
+                }
+                return Err(e);
+            }
+        };
+        self.execute_audited_plan(plan, Some(sql)).await
+    }
+
+    /// Creates a [`DataFrame`] that will execute the given logical plan,
+    /// running DDL statements such as `CREATE EXTERNAL TABLE` eagerly.
+    pub async fn execute_logical_plan(
+        &self,
+        plan: LogicalPlan,
+    ) -> Result<Arc<DataFrame>> {
+        self.execute_audited_plan(plan, None).await
+    }
+
+    /// [`Self::execute_logical_plan`] of a plan created from `sql`, if any:
+    /// the statements run eagerly are reported to the [`QueryAuditHook`]
+    /// once run, the queries when the returned DataFrame is executed
+    async fn execute_audited_plan(
+        &self,
+        plan: LogicalPlan,
+        sql: Option<&str>,
+    ) -> Result<Arc<DataFrame>> {
+        let eager = matches!(
+            plan,
+            LogicalPlan::CreateExternalTable(_)
+                | LogicalPlan::CreateMemoryTable(_)
+                | LogicalPlan::CreateView(_)
+                | LogicalPlan::DropTable(_)
+                | LogicalPlan::DropView(_)
+                | LogicalPlan::RefreshMaterializedView(_)
+                | LogicalPlan::AnalyzeTable(_)
+                | LogicalPlan::SetVariable(_)
+                | LogicalPlan::CreateCatalogSchema(_)
+                | LogicalPlan::CreateCatalog(_)
+        );
+        if !eager {
+            let df = DataFrame::new(self.state.clone(), &plan);
+            return Ok(Arc::new(match sql {
+                Some(sql) => df.with_sql(sql),
+                None => df,
+            }));
+        }
+
+        let audit = QueryAudit::start(&self.state.read(), sql, Some(&plan));
+        let result = self.run_statement(plan).await;
+        if let Some(audit) = audit {
+            audit.record(&result, |_| 0);
+        }
+        result
+    }
+
+    /// Runs the DDL statement `plan`, returning an empty DataFrame
+    async fn run_statement(&self, plan: LogicalPlan) -> Result<Arc<DataFrame>> {
         match plan {
             LogicalPlan::CreateExternalTable(cmd) => match cmd.file_type.as_str() {
                 "PARQUET" | "CSV" | "JSON" | "AVRO" => {
@@ -242,14 +342,14 @@ impl SessionContext {
                 if_not_exists,
                 or_replace,
             }) => {
-                let table = self.table(name.as_str());
+                let table = self.table_for_create(name.as_str())?;
 
                 match (if_not_exists, or_replace, table) {
-                    (true, false, Ok(_)) => self.return_empty_dataframe(),
-                    (false, true, Ok(_)) => {
-                        self.deregister_table(name.as_str())?;
-                        let physical =
-                            Arc::new(DataFrame::new(self.state.clone(), &input));
+                    (true, false, Some(_)) => self.return_empty_dataframe(),
+                    (false, true, Some(_)) => {
+                        let physical = Arc::new(
+                            DataFrame::new(self.state.clone(), &input).without_audit(),
+                        );
 
                         let batches: Vec<_> = physical.collect_partitioned().await?;
                         let table = Arc::new(MemTable::try_new(
@@ -257,15 +357,16 @@ impl SessionContext {
                             batches,
                         )?);
 
-                        self.register_table(name.as_str(), table)?;
+                        self.replace_table(name.as_str(), table)?;
                         self.return_empty_dataframe()
                     }
-                    (true, true, Ok(_)) => Err(DataFusionError::Internal(
+                    (true, true, Some(_)) => Err(DataFusionError::Internal(
                         "'IF NOT EXISTS' cannot coexist with 'REPLACE'".to_string(),
                     )),
-                    (_, _, Err(_)) => {
-                        let physical =
-                            Arc::new(DataFrame::new(self.state.clone(), &input));
+                    (_, _, None) => {
+                        let physical = Arc::new(
+                            DataFrame::new(self.state.clone(), &input).without_audit(),
+                        );
 
                         let batches: Vec<_> = physical.collect_partitioned().await?;
                         let table = Arc::new(MemTable::try_new(
@@ -276,7 +377,7 @@ impl SessionContext {
                         self.register_table(name.as_str(), table)?;
                         self.return_empty_dataframe()
                     }
-                    (false, false, Ok(_)) => Err(DataFusionError::Execution(format!(
+                    (false, false, Some(_)) => Err(DataFusionError::Execution(format!(
                         "Table '{:?}' already exists",
                         name
                     ))),
@@ -288,26 +389,29 @@ impl SessionContext {
                 input,
                 or_replace,
                 definition,
+                materialized,
+                location,
             }) => {
-                let view = self.table(name.as_str());
+                let view = self.table_for_create(name.as_str())?;
 
                 match (or_replace, view) {
-                    (true, Ok(_)) => {
-                        self.deregister_table(name.as_str())?;
-                        let table =
-                            Arc::new(ViewTable::try_new((*input).clone(), definition)?);
+                    (true, Some(_)) => {
+                        let table = self
+                            .create_view_table(&input, definition, materialized, location)
+                            .await?;
 
-                        self.register_table(name.as_str(), table)?;
+                        self.replace_table(name.as_str(), table)?;
                         self.return_empty_dataframe()
                     }
-                    (_, Err(_)) => {
-                        let table =
-                            Arc::new(ViewTable::try_new((*input).clone(), definition)?);
+                    (_, None) => {
+                        let table = self
+                            .create_view_table(&input, definition, materialized, location)
+                            .await?;
 
                         self.register_table(name.as_str(), table)?;
                         self.return_empty_dataframe()
                     }
-                    (false, Ok(_)) => Err(DataFusionError::Execution(format!(
+                    (false, Some(_)) => Err(DataFusionError::Execution(format!(
                         "Table '{:?}' already exists",
                         name
                     ))),
@@ -341,26 +445,101 @@ impl SessionContext {
                     ))),
                 }
             }
+
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView {
+                name,
+                ..
+            }) => {
+                let table_ref: TableReference = name.as_str().into();
+                let table = self
+                    .state
+                    .read()
+                    .schema_for_ref(table_ref)?
+                    .table(table_ref.table());
+                let view = table
+                    .as_ref()
+                    .and_then(|table| table.as_any().downcast_ref::<MaterializedView>())
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Materialized view {:?} doesn't exist.",
+                            name
+                        ))
+                    })?;
+
+                let state = self.state.read().clone();
+                view.refresh(&state).await?;
+                self.return_empty_dataframe()
+            }
+
+            LogicalPlan::AnalyzeTable(AnalyzeTable { name, .. }) => {
+                let table_ref: TableReference = name.as_str().into();
+                let table = self
+                    .state
+                    .read()
+                    .schema_for_ref(table_ref)?
+                    .table(table_ref.table())
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Table {:?} doesn't exist.",
+                            name
+                        ))
+                    })?;
+                let table = table
+                    .as_any()
+                    .downcast_ref::<ListingTable>()
+                    .ok_or_else(|| {
+                        DataFusionError::NotImplemented(format!(
+                            "ANALYZE TABLE is only supported for listing tables, {:?} is not one",
+                            name
+                        ))
+                    })?;
+
+                let state = self.state.read().clone();
+                table.build_index(&state).await?;
+                self.return_empty_dataframe()
+            }
+
+            LogicalPlan::SetVariable(SetVariable {
+                variable, value, ..
+            }) => {
+                let config_options = self.state.read().config.config_options.clone();
+                let mut config_options = config_options.write();
+                let old_value = config_options.get(&variable).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Unknown configuration option {:?}",
+                        variable
+                    ))
+                })?;
+                if variable == OPT_TIME_ZONE {
+                    value.parse::<SessionTimeZone>()?;
+                }
+                // the value is converted to the type of the option
+                let new_value =
+                    ScalarValue::try_from_string(value, &old_value.get_datatype())?;
+                config_options.set(&variable, new_value);
+                self.return_empty_dataframe()
+            }
             LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
                 schema_name,
                 if_not_exists,
+                location,
                 ..
             }) => {
-                // sqlparser doesnt accept database / catalog as parameter to CREATE SCHEMA
-                // so for now, we default to default catalog
+                // schemas without a catalog are created in the default catalog
+                let default_catalog = self.copied_config().default_catalog;
                 let tokens: Vec<&str> = schema_name.split('.').collect();
-                let (catalog, schema_name) = match tokens.len() {
-                    1 => Ok((DEFAULT_CATALOG, schema_name.as_str())),
+                let (catalog_name, schema_name) = match tokens.len() {
+                    1 => Ok((default_catalog.as_str(), schema_name.as_str())),
                     2 => Ok((tokens[0], tokens[1])),
                     _ => Err(DataFusionError::Execution(format!(
                         "Unable to parse catalog from {}",
                         schema_name
                     ))),
                 }?;
-                let catalog = self.catalog(catalog).ok_or_else(|| {
+                let catalog = self.catalog(catalog_name).ok_or_else(|| {
                     DataFusionError::Execution(format!(
                         "Missing '{}' catalog",
-                        DEFAULT_CATALOG
+                        catalog_name
                     ))
                 })?;
 
@@ -369,8 +548,18 @@ impl SessionContext {
                 match (if_not_exists, schema) {
                     (true, Some(_)) => self.return_empty_dataframe(),
                     (true, None) | (false, None) => {
-                        let schema = Arc::new(MemorySchemaProvider::new());
-                        catalog.register_schema(schema_name, schema)?;
+                        // the tables are stored in the directory of the schema
+                        // in the directory of its catalog by default
+                        let location = location.or_else(|| {
+                            catalog
+                                .location()
+                                .map(|location| child_location(location, schema_name))
+                        });
+                        let mut schema = MemorySchemaProvider::new();
+                        if let Some(location) = location {
+                            schema = schema.with_location(location);
+                        }
+                        catalog.register_schema(schema_name, Arc::new(schema))?;
                         self.return_empty_dataframe()
                     }
                     (false, Some(_)) => Err(DataFusionError::Execution(format!(
@@ -382,6 +571,7 @@ impl SessionContext {
             LogicalPlan::CreateCatalog(CreateCatalog {
                 catalog_name,
                 if_not_exists,
+                location,
                 ..
             }) => {
                 let catalog = self.catalog(catalog_name.as_str());
@@ -389,11 +579,11 @@ impl SessionContext {
                 match (if_not_exists, catalog) {
                     (true, Some(_)) => self.return_empty_dataframe(),
                     (true, None) | (false, None) => {
-                        let new_catalog = Arc::new(MemoryCatalogProvider::new());
-                        self.state
-                            .write()
-                            .catalog_list
-                            .register_catalog(catalog_name, new_catalog);
+                        let mut new_catalog = MemoryCatalogProvider::new();
+                        if let Some(location) = location {
+                            new_catalog = new_catalog.with_location(location);
+                        }
+                        self.register_catalog(catalog_name, Arc::new(new_catalog));
                         self.return_empty_dataframe()
                     }
                     (false, Some(_)) => Err(DataFusionError::Execution(format!(
@@ -403,14 +593,38 @@ impl SessionContext {
                 }
             }
 
-            plan => Ok(Arc::new(DataFrame::new(self.state.clone(), &plan))),
+            plan => Err(DataFusionError::Internal(format!(
+                "Unsupported DDL statement {:?}",
+                plan
+            ))),
+        }
+    }
+
+    async fn create_view_table(
+        &self,
+        input: &LogicalPlan,
+        definition: Option<String>,
+        materialized: bool,
+        location: Option<String>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if materialized {
+            let state = self.state.read().clone();
+            let view =
+                MaterializedView::try_create(&state, input.clone(), location, definition)
+                    .await?;
+            Ok(Arc::new(view))
+        } else {
+            Ok(Arc::new(ViewTable::try_new(input.clone(), definition)?))
         }
     }
 
     // return an empty dataframe
     fn return_empty_dataframe(&self) -> Result<Arc<DataFrame>> {
         let plan = LogicalPlanBuilder::empty(false).build()?;
-        Ok(Arc::new(DataFrame::new(self.state.clone(), &plan)))
+        // the statement returning it is already audited
+        Ok(Arc::new(
+            DataFrame::new(self.state.clone(), &plan).without_audit(),
+        ))
     }
 
     async fn create_custom_table(
@@ -429,10 +643,10 @@ impl SessionContext {
                     cmd.file_type
                 ))
             })?;
-        let table = (*factory).create(cmd.location.as_str()).await?;
+        let location = self.table_location(cmd)?;
+        let table = (*factory).create(location.as_str()).await?;
         self.register_table(cmd.name.as_str(), table)?;
-        let plan = LogicalPlanBuilder::empty(false).build()?;
-        Ok(Arc::new(DataFrame::new(self.state.clone(), &plan)))
+        self.return_empty_dataframe()
     }
 
     async fn create_listing_table(
@@ -471,10 +685,10 @@ impl SessionContext {
             ),
         };
 
-        let table = self.table(cmd.name.as_str());
+        let table = self.table_for_create(cmd.name.as_str())?;
         match (cmd.if_not_exists, table) {
-            (true, Ok(_)) => self.return_empty_dataframe(),
-            (_, Err(_)) => {
+            (true, Some(_)) => self.return_empty_dataframe(),
+            (_, None) => {
                 // TODO make schema in CreateExternalTable optional instead of empty
                 let provided_schema = if cmd.schema.fields().is_empty() {
                     None
@@ -487,24 +701,93 @@ impl SessionContext {
                     file_extension: file_extension.to_owned(),
                     target_partitions: self.copied_config().target_partitions,
                     table_partition_cols: cmd.table_partition_cols.clone(),
+                    use_index: false,
                 };
-                self.register_listing_table(
-                    cmd.name.as_str(),
-                    cmd.location.clone(),
-                    options,
-                    provided_schema,
-                    cmd.definition.clone(),
-                )
-                .await?;
+                let table_path = ListingTableUrl::parse(self.table_location(cmd)?)?;
+                let resolved_schema = match provided_schema {
+                    None => options.infer_schema(&self.state(), &table_path).await?,
+                    Some(s) => s,
+                };
+                let config = ListingTableConfig::new(table_path)
+                    .with_listing_options(options)
+                    .with_schema(resolved_schema);
+                let mut table = ListingTable::try_new(config)?
+                    .with_definition(cmd.definition.clone())
+                    .with_constraints(cmd.constraints.clone());
+                cmd.constraints.validate(&table.schema())?;
+                if !cmd.bucket_cols.is_empty() {
+                    // the buckets are computed from the values in the files
+                    if let Some(col) = cmd.bucket_cols.iter().find(|col| {
+                        cmd.table_partition_cols.contains(col)
+                            || table.schema().field_with_name(col).is_err()
+                    }) {
+                        return Err(DataFusionError::Plan(format!(
+                            "Bucket column '{}' is not a column of the files of the table",
+                            col
+                        )));
+                    }
+                    if cmd.num_buckets == 0 {
+                        return Err(DataFusionError::Plan(
+                            "A bucketed table requires at least one bucket".to_string(),
+                        ));
+                    }
+                    table = table.with_bucketing(Bucketing::new(
+                        cmd.bucket_cols.clone(),
+                        cmd.num_buckets,
+                    ));
+                }
+                self.register_table(cmd.name.as_str(), Arc::new(table))?;
                 self.return_empty_dataframe()
             }
-            (false, Ok(_)) => Err(DataFusionError::Execution(format!(
+            (false, Some(_)) => Err(DataFusionError::Execution(format!(
                 "Table '{:?}' already exists",
                 cmd.name
             ))),
         }
     }
 
+    /// The location of the table created by `cmd`, which is the directory of
+    /// the table in the directory of its schema when `cmd` has none
+    fn table_location(&self, cmd: &CreateExternalTable) -> Result<String> {
+        if !cmd.location.is_empty() {
+            return Ok(cmd.location.clone());
+        }
+        let table_ref = TableReference::from(cmd.name.as_str());
+        let schema = self.state.read().schema_for_new_ref(table_ref)?;
+        let location = schema.location().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "Table '{}' requires a location, as its schema has none",
+                cmd.name
+            ))
+        })?;
+        Ok(child_location(location, table_ref.table()))
+    }
+
+    /// The table named `name` in the schema where a table of this name is
+    /// created, which may hide a table of a later schema of the search path
+    fn table_for_create(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
+        let table_ref = TableReference::from(name);
+        Ok(self
+            .state
+            .read()
+            .schema_for_new_ref(table_ref)?
+            .table(table_ref.table()))
+    }
+
+    /// Replaces the table named `name` created by a `CREATE OR REPLACE`
+    /// statement, keeping the policy registered for the replaced table
+    fn replace_table(&self, name: &str, table: Arc<dyn TableProvider>) -> Result<()> {
+        let table_ref = TableReference::from(name);
+        let schema = self.state.read().schema_for_new_ref(table_ref)?;
+        let policy = schema.table_policy(table_ref.table());
+        schema.deregister_table(table_ref.table())?;
+        schema.register_table(table_ref.table().to_owned(), table)?;
+        if let Some(policy) = policy {
+            schema.register_table_policy(table_ref.table().to_owned(), policy)?;
+        }
+        Ok(())
+    }
+
     fn find_and_deregister<'a>(
         &self,
         table_ref: impl Into<TableReference<'a>>,
@@ -527,20 +810,113 @@ impl SessionContext {
     }
     /// Creates a logical plan.
     ///
+    /// Errors about a column, and type errors of the SELECT list, point at
+    /// their location in `sql`.
+    ///
     /// This function is intended for internal use and should not be called directly.
     pub fn create_logical_plan(&self, sql: &str) -> Result<LogicalPlan> {
-        let mut statements = DFParser::parse_sql(sql)?;
+        let state = self.state.read().clone();
+        let max_depth = state.config.sql_max_depth();
+        let statement = parse_single_statement(sql, max_depth)?;
+
+        // create a query planner
+        let query_planner = SqlToRel::new(&state)
+            .with_max_depth(max_depth)
+            .with_source(sql);
+        query_planner
+            .statement_to_plan(statement)
+            .map_err(|e| with_source_location(e, sql))
+    }
+
+    /// Creates a logical plan like [`create_logical_plan`], resolving the
+    /// tables not found by the planner with the [`TableResolver`] of their
+    /// catalog, such as the catalogs registered with
+    /// [`register_async_catalog`], before planning again.
+    ///
+    /// [`create_logical_plan`]: SessionContext::create_logical_plan
+    /// [`register_async_catalog`]: SessionContext::register_async_catalog
+    pub async fn create_logical_plan_resolving_tables(
+        &self,
+        sql: &str,
+    ) -> Result<LogicalPlan> {
+        let max_depth = self.state.read().config.sql_max_depth();
+        let statement = parse_single_statement(sql, max_depth)?;
+        loop {
+            let state = self.state.read().clone();
+            let (result, missing) = {
+                let recorder = MissingTableRecorder::new(&state);
+                let result = SqlToRel::new(&recorder)
+                    .with_max_depth(max_depth)
+                    .with_source(sql)
+                    .statement_to_plan(statement.clone());
+                (result, recorder.missing.into_inner())
+            };
+            let err = match result {
+                Ok(plan) => return Ok(plan),
+                Err(e) => with_source_location(e, sql),
+            };
+
+            // every round resolves at least one more table, or gives up
+            let mut resolved = false;
+            for (catalog, schema, table) in &missing {
+                let table_ref = match (catalog, schema) {
+                    (Some(catalog), Some(schema)) => TableReference::Full {
+                        catalog,
+                        schema,
+                        table,
+                    },
+                    (None, Some(schema)) => TableReference::Partial { schema, table },
+                    _ => TableReference::Bare { table },
+                };
+                resolved |= state.resolve_table(table_ref).await?;
+            }
+            if !resolved {
+                return Err(err);
+            }
+        }
+    }
+
+    /// Creates a logical plan, reporting all the errors found in `sql`
+    /// instead of stopping at the first one, for tools validating queries
+    /// as they are typed.
+    ///
+    /// See [`SqlToRel::statement_to_plan_with_recovery`] for the errors
+    /// that are collected. Syntax errors are reported alone.
+    pub fn create_logical_plan_with_recovery(
+        &self,
+        sql: &str,
+    ) -> std::result::Result<LogicalPlan, Vec<DataFusionError>> {
+        let state = self.state.read().clone();
+        let max_depth = state.config.sql_max_depth();
+        let mut statements =
+            DFParser::parse_sql_with_max_depth(sql, &GenericDialect {}, max_depth)
+                .map_err(|e| vec![DataFusionError::from(e)])?;
 
         if statements.len() != 1 {
-            return Err(DataFusionError::NotImplemented(
+            return Err(vec![DataFusionError::NotImplemented(
                 "The context currently only supports a single SQL statement".to_string(),
-            ));
+            )]);
         }
 
-        // create a query planner
-        let state = self.state.read().clone();
-        let query_planner = SqlToRel::new(&state);
-        query_planner.statement_to_plan(statements.pop_front().unwrap())
+        let query_planner = SqlToRel::new(&state)
+            .with_max_depth(max_depth)
+            .with_source(sql);
+        query_planner
+            .statement_to_plan_with_recovery(statements.pop_front().unwrap())
+            .map_err(|errors| {
+                errors
+                    .into_iter()
+                    .map(|e| with_source_location(e, sql))
+                    .collect()
+            })
+    }
+
+    /// Returns the completion candidates for the word ending at the byte
+    /// offset `cursor` of the partially typed `sql`, for SQL editors.
+    ///
+    /// See [`completion::complete`] for the candidates returned.
+    pub fn complete_sql(&self, sql: &str, cursor: usize) -> Vec<CompletionCandidate> {
+        completion::complete(&self.state.read(), sql, cursor)
     }
 
     /// Registers a variable provider within this context.
@@ -860,6 +1236,22 @@ impl SessionContext {
         state.catalog_list.register_catalog(name, catalog)
     }
 
+    /// Registers an [`AsyncCatalogProvider`] as a catalog, wrapped in a
+    /// [`ResolvedCatalogProvider`] whose tables are resolved by [`sql`]
+    /// before planning the queries reading them.
+    ///
+    /// Returns the [`CatalogProvider`] previously registered for this
+    /// name, if any
+    ///
+    /// [`sql`]: SessionContext::sql
+    pub fn register_async_catalog(
+        &self,
+        name: impl Into<String>,
+        catalog: Arc<dyn AsyncCatalogProvider>,
+    ) -> Option<Arc<dyn CatalogProvider>> {
+        self.register_catalog(name, Arc::new(ResolvedCatalogProvider::new(catalog)))
+    }
+
     /// Retrieves a [`CatalogProvider`] instance by name
     pub fn catalog(&self, name: &str) -> Option<Arc<dyn CatalogProvider>> {
         self.state.read().catalog_list.catalog(name)
@@ -878,11 +1270,30 @@ impl SessionContext {
         let table_ref = table_ref.into();
         self.state
             .read()
-            .schema_for_ref(table_ref)?
+            .schema_for_new_ref(table_ref)?
             .register_table(table_ref.table().to_owned(), provider)
     }
 
-    /// Deregisters the given table.
+    /// Registers a [`TablePolicy`] whose filters and column masks are
+    /// applied to every scan of the table, whether it is referenced from
+    /// SQL statements, through [`SessionContext::table`] or looked up in the
+    /// catalog, by scanning it as a [`PolicyTable`](crate::datasource::PolicyTable).
+    ///
+    /// Returns the policy previously registered for this reference, if any
+    pub fn register_table_policy<'a>(
+        &'a self,
+        table_ref: impl Into<TableReference<'a>>,
+        policy: Arc<dyn TablePolicy>,
+    ) -> Result<Option<Arc<dyn TablePolicy>>> {
+        let table_ref = table_ref.into();
+        self.state
+            .read()
+            .schema_for_ref(table_ref)?
+            .register_table_policy(table_ref.table().to_owned(), policy)
+    }
+
+    /// Deregisters the given table, along with its [`TablePolicy`], so that
+    /// a table registered later under the same name is not restricted by it.
     ///
     /// Returns the registered provider, if any
     pub fn deregister_table<'a>(
@@ -923,10 +1334,12 @@ impl SessionContext {
         let table_ref = table_ref.into();
         let schema = self.state.read().schema_for_ref(table_ref)?;
         match schema.table(table_ref.table()) {
-            Some(ref provider) => {
+            Some(provider) => {
+                let provider =
+                    with_table_policy(schema.as_ref(), table_ref.table(), provider);
                 let plan = LogicalPlanBuilder::scan(
                     table_ref.table(),
-                    provider_as_source(Arc::clone(provider)),
+                    provider_as_source(provider),
                     None,
                 )?
                 .build()?;
@@ -962,7 +1375,7 @@ impl SessionContext {
 
     /// Optimizes the logical plan by applying optimizer rules.
     pub fn optimize(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
-        self.state.read().optimize(plan)
+        self.state.read().start_statement()?.optimize(plan)
     }
 
     /// Creates a physical plan from a logical plan.
@@ -970,23 +1383,9 @@ impl SessionContext {
         &self,
         logical_plan: &LogicalPlan,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let state_cloned = {
-            let mut state = self.state.write();
-            state.execution_props.start_execution();
-
-            // We need to clone `state` to release the lock that is not `Send`. We could
-            // make the lock `Send` by using `tokio::sync::Mutex`, but that would require to
-            // propagate async even to the `LogicalPlan` building methods.
-            // Cloning `state` here is fine as we then pass it as immutable `&state`, which
-            // means that we avoid write consistency issues as the cloned version will not
-            // be written to. As for eventual modifications that would be applied to the
-            // original state after it has been cloned, they will not be picked up by the
-            // clone but that is okay, as it is equivalent to postponing the state update
-            // by keeping the lock until the end of the function scope.
-            state.clone()
-        };
-
-        state_cloned.create_physical_plan(logical_plan).await
+        // the snapshot releases the lock that is not `Send`
+        let state = self.state.read().start_statement()?;
+        state.create_physical_plan(logical_plan).await
     }
 
     /// Executes a query and writes the results to a partitioned CSV file.
@@ -1020,6 +1419,112 @@ impl SessionContext {
         plan_to_parquet(&state, plan, path, writer_properties).await
     }
 
+    /// Loads the data sent by a client for the statement
+    /// `COPY <table> [(<columns>)] FROM STDIN [WITH (<options>)]` into its
+    /// table, and returns the number of rows inserted.
+    ///
+    /// The chunks of bytes of `data` are read as CSV or newline delimited
+    /// JSON, depending on the `FORMAT` option, with the types of the columns
+    /// of the table, and inserted like [`insert_into`](Self::insert_into).
+    pub async fn copy_from<S>(&self, sql: &str, data: S) -> Result<usize>
+    where
+        S: Stream<Item = Result<Bytes>> + Send + 'static,
+    {
+        let max_depth = self.state.read().config.sql_max_depth();
+        let copy = match parse_single_statement(sql, max_depth)? {
+            DFStatement::CopyFrom(copy) => copy,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "Expected a COPY ... FROM STDIN statement, got: {}",
+                    sql
+                )))
+            }
+        };
+        let state = self.state();
+        let table_ref = TableReference::from(copy.table_name.as_str());
+        let provider = state
+            .schema_for_ref(table_ref)?
+            .table(table_ref.table())
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!("No table named '{}'", copy.table_name))
+            })?;
+        let input =
+            read_copy_data(&copy, provider.schema(), state.config.batch_size(), data)?;
+        self.insert_checked(&state, provider, input).await
+    }
+
+    /// Inserts the record batches of `input`, which have the schema of the
+    /// table `table_ref`, into the table with [`TableProvider::insert_into`],
+    /// and returns the number of rows inserted.
+    ///
+    /// The rows are checked against the check constraints of the table. The
+    /// insertion fails if some of them violate a constraint, unless the
+    /// `datafusion.execution.check_constraints.rejects_table` option names a
+    /// table, into which these rows are inserted instead.
+    ///
+    /// The rejected rows are buffered in memory and only inserted into the
+    /// rejects table once the valid rows have been inserted. The two
+    /// insertions are not atomic: if the second one fails, the valid rows
+    /// remain in the table.
+    pub async fn insert_into<'a>(
+        &self,
+        table_ref: impl Into<TableReference<'a>>,
+        input: SendableRecordBatchStream,
+    ) -> Result<usize> {
+        let table_ref = table_ref.into();
+        let state = self.state();
+        let provider = state
+            .schema_for_ref(table_ref)?
+            .table(table_ref.table())
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!("No table named '{}'", table_ref.table()))
+            })?;
+        self.insert_checked(&state, provider, input).await
+    }
+
+    /// Inserts `input` into `provider`, enforcing the check constraints of
+    /// the table
+    async fn insert_checked(
+        &self,
+        state: &SessionState,
+        provider: Arc<dyn TableProvider>,
+        input: SendableRecordBatchStream,
+    ) -> Result<usize> {
+        let checks = match provider.constraints() {
+            Some(constraints) => CheckConstraints::try_new(
+                constraints,
+                &provider.schema(),
+                input.schema(),
+                state,
+            )?,
+            None => CheckConstraints::default(),
+        };
+        if checks.is_empty() {
+            return provider.insert_into(state, input).await;
+        }
+
+        // Resolve the rejects table before writing anything, so that a
+        // missing or incompatible table does not fail the statement after
+        // the valid rows have been inserted
+        let schema = input.schema();
+        let rejects = match state.config.check_constraints_rejects_table() {
+            Some(table) => Some(rejects_provider(state, &table, &schema)?),
+            None => None,
+        };
+        let rejected = rejects.as_ref().map(|_| Arc::new(Mutex::new(vec![])));
+        let input = checks.enforce(input, rejected.clone());
+        let num_rows = provider.insert_into(state, input).await?;
+
+        if let (Some(rejects), Some(rejected)) = (rejects, rejected) {
+            let batches = std::mem::take(&mut *rejected.lock());
+            if !batches.is_empty() {
+                let input = MemoryStream::try_new(batches, schema, None)?;
+                rejects.insert_into(state, Box::pin(input)).await?;
+            }
+        }
+        Ok(num_rows)
+    }
+
     /// Get a new TaskContext to run in this session
     pub fn task_ctx(&self) -> Arc<TaskContext> {
         Arc::new(TaskContext::from(self))
@@ -1045,6 +1550,106 @@ impl FunctionRegistry for SessionContext {
     }
 }
 
+/// The directory `name` in the directory `location`
+fn child_location(location: &str, name: &str) -> String {
+    format!("{}/{}/", location.trim_end_matches('/'), name)
+}
+
+/// Parse `sql`, which must hold a single statement nested at most
+/// `max_depth` levels
+fn parse_single_statement(sql: &str, max_depth: usize) -> Result<DFStatement> {
+    let mut statements =
+        DFParser::parse_sql_with_max_depth(sql, &GenericDialect {}, max_depth)?;
+
+    if statements.len() != 1 {
+        return Err(DataFusionError::NotImplemented(
+            "The context currently only supports a single SQL statement".to_string(),
+        ));
+    }
+    Ok(statements.pop_front().unwrap())
+}
+
+/// Returns the table named `table` of `state` into which the rows of
+/// `schema` violating check constraints are inserted
+fn rejects_provider(
+    state: &SessionState,
+    table: &str,
+    schema: &Schema,
+) -> Result<Arc<dyn TableProvider>> {
+    let table_ref = TableReference::from(table);
+    let provider = state
+        .schema_for_ref(table_ref)?
+        .table(table_ref.table())
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "No table named '{}' for the rows violating check constraints",
+                table
+            ))
+        })?;
+    let rejects_schema = provider.schema();
+    let compatible = rejects_schema.fields().len() == schema.fields().len()
+        && rejects_schema
+            .fields()
+            .iter()
+            .zip(schema.fields())
+            .all(|(a, b)| a.data_type() == b.data_type());
+    if !compatible {
+        return Err(DataFusionError::Plan(format!(
+            "Table '{}' for the rows violating check constraints has schema {:?}, \
+            which does not match the inserted rows {:?}",
+            table, rejects_schema, schema
+        )));
+    }
+    Ok(provider)
+}
+
+/// The [`ContextProvider`] of a [`SessionState`], recording the references
+/// of the tables it does not find as their optional catalog, optional schema
+/// and table names
+struct MissingTableRecorder<'a> {
+    state: &'a SessionState,
+    missing: RefCell<Vec<(Option<String>, Option<String>, String)>>,
+}
+
+impl<'a> MissingTableRecorder<'a> {
+    fn new(state: &'a SessionState) -> Self {
+        Self {
+            state,
+            missing: RefCell::new(vec![]),
+        }
+    }
+}
+
+impl ContextProvider for MissingTableRecorder<'_> {
+    fn get_table_provider(&self, name: TableReference) -> Result<Arc<dyn TableSource>> {
+        self.state.get_table_provider(name).map_err(|e| {
+            let (catalog, schema) = match name {
+                TableReference::Full {
+                    catalog, schema, ..
+                } => (Some(catalog.to_owned()), Some(schema.to_owned())),
+                TableReference::Partial { schema, .. } => (None, Some(schema.to_owned())),
+                TableReference::Bare { .. } => (None, None),
+            };
+            self.missing
+                .borrow_mut()
+                .push((catalog, schema, name.table().to_owned()));
+            e
+        })
+    }
+
+    fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        self.state.get_function_meta(name)
+    }
+
+    fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
+        self.state.get_aggregate_meta(name)
+    }
+
+    fn get_variable_type(&self, variable_names: &[String]) -> Option<DataType> {
+        self.state.get_variable_type(variable_names)
+    }
+}
+
 /// A planner used to add extensions to DataFusion logical and physical plans.
 #[async_trait]
 pub trait QueryPlanner {
@@ -1204,6 +1809,11 @@ impl SessionConfig {
         self.set(key, ScalarValue::UInt64(Some(value)))
     }
 
+    /// Set a generic `f64` configuration option
+    pub fn set_f64(self, key: &str, value: f64) -> Self {
+        self.set(key, ScalarValue::Float64(Some(value)))
+    }
+
     /// Customize batch size
     pub fn with_batch_size(self, n: usize) -> Self {
         // batch size must be greater than zero
@@ -1211,6 +1821,34 @@ impl SessionConfig {
         self.set_u64(OPT_BATCH_SIZE, n.try_into().unwrap())
     }
 
+    /// Customize the maximum nesting of the parsed and planned SQL statements
+    pub fn with_sql_max_depth(self, max_depth: usize) -> Self {
+        self.set_u64(OPT_SQL_PARSER_MAX_DEPTH, max_depth.try_into().unwrap())
+    }
+
+    /// Customize the seed of the random values of the queries, which makes
+    /// `random()` deterministic when the queries run in a single partition,
+    /// see [`Self::with_target_partitions`]
+    pub fn with_random_seed(self, seed: u64) -> Self {
+        self.set_u64(OPT_RANDOM_SEED, seed)
+    }
+
+    /// Customize the time zone of the session, in which `current_date()` and
+    /// `current_time()` are read, timestamps with a time zone are truncated,
+    /// and timestamps without one are interpreted when cast into one: a name
+    /// of the IANA time zone database such as `Asia/Tokyo`, or an offset such
+    /// as `+09:00`
+    pub fn with_time_zone(self, time_zone: impl Into<String>) -> Self {
+        self.set(OPT_TIME_ZONE, ScalarValue::Utf8(Some(time_zone.into())))
+    }
+
+    /// Enables or disables the conversion of the results of the queries into
+    /// types that clients such as ODBC drivers can read, see
+    /// [`OutputCoercion`]
+    pub fn with_output_coercion(self, enabled: bool) -> Self {
+        self.set_bool(OPT_OUTPUT_COERCION, enabled)
+    }
+
     /// Customize target_partitions
     pub fn with_target_partitions(mut self, n: usize) -> Self {
         // partition count must be greater than zero
@@ -1230,6 +1868,20 @@ impl SessionConfig {
         self
     }
 
+    /// Selects the schemas of the default catalog where unqualified table names
+    /// are looked up, in order, instead of the default schema
+    pub fn with_search_path<S: AsRef<str>>(
+        self,
+        schemas: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let search_path = schemas
+            .into_iter()
+            .map(|schema| schema.as_ref().to_owned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.set(OPT_SEARCH_PATH, ScalarValue::Utf8(Some(search_path)))
+    }
+
     /// Controls whether the default catalog and schema will be automatically created
     pub fn create_default_catalog_and_schema(mut self, create: bool) -> Self {
         self.create_default_catalog_and_schema = create;
@@ -1282,6 +1934,132 @@ impl SessionConfig {
             .unwrap()
     }
 
+    /// Get the name of the default catalog
+    pub fn default_catalog(&self) -> &str {
+        &self.default_catalog
+    }
+
+    /// Get the name of the default schema
+    pub fn default_schema(&self) -> &str {
+        &self.default_schema
+    }
+
+    /// Whether the default catalog and schema are created automatically
+    pub fn creates_default_catalog_and_schema(&self) -> bool {
+        self.create_default_catalog_and_schema
+    }
+
+    /// Whether the `information_schema` virtual tables are enabled
+    pub fn information_schema(&self) -> bool {
+        self.information_schema
+    }
+
+    /// Get the maximum nesting of the parsed and planned SQL statements
+    pub fn sql_max_depth(&self) -> usize {
+        self.config_options
+            .read()
+            .get_u64(OPT_SQL_PARSER_MAX_DEPTH)
+            .unwrap_or(datafusion_sql::parser::DEFAULT_MAX_DEPTH as u64)
+            .try_into()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Get the configured seed of the random values of the queries, if any
+    pub fn random_seed(&self) -> Option<u64> {
+        self.config_options.read().get_u64(OPT_RANDOM_SEED)
+    }
+
+    /// Get the name of the table receiving the rows which violate the check
+    /// constraints of the tables they are inserted into, if any
+    pub fn check_constraints_rejects_table(&self) -> Option<String> {
+        self.config_options
+            .read()
+            .get_string(OPT_CHECK_CONSTRAINTS_REJECTS_TABLE)
+            .filter(|table| !table.is_empty())
+    }
+
+    /// Whether the scans count the rows violating the check constraints of
+    /// their tables
+    pub fn validate_check_constraints(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS)
+            .unwrap_or_default()
+    }
+
+    /// Whether the optimizer answers queries from the results of materialized
+    /// views
+    pub fn materialized_view_rewrite(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_MATERIALIZED_VIEW_REWRITE)
+            .unwrap_or_default()
+    }
+
+    /// Whether the Parquet scans only read the struct fields accessed by the
+    /// queries
+    pub fn nested_projection_pushdown(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_NESTED_PROJECTION_PUSHDOWN)
+            .unwrap_or_default()
+    }
+
+    /// Whether the physical optimizer merges and removes redundant
+    /// projections
+    pub fn physical_projection_pushdown(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_PHYSICAL_PROJECTION_PUSHDOWN)
+            .unwrap_or_default()
+    }
+
+    /// Whether the physical optimizer removes the sorts and the hash
+    /// repartitions already satisfied by their input
+    pub fn eliminate_sort_repartition(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_ELIMINATE_SORT_REPARTITION)
+            .unwrap_or_default()
+    }
+
+    /// Whether the scans of listing tables consult the index files of their
+    /// data files
+    pub fn use_file_indexes(&self) -> bool {
+        self.config_options
+            .read()
+            .get_bool(OPT_USE_FILE_INDEXES)
+            .unwrap_or_default()
+    }
+
+    /// Get the time zone of the session, or an error if the time zone option
+    /// is not a valid time zone
+    pub fn time_zone(&self) -> Result<SessionTimeZone> {
+        match self.config_options.read().get_string(OPT_TIME_ZONE) {
+            Some(time_zone) => time_zone.parse(),
+            None => Ok(SessionTimeZone::default()),
+        }
+    }
+
+    /// Get the schemas of the default catalog where unqualified table names
+    /// are looked up, in order: the schemas of the search path option, or the
+    /// default schema when it is not set
+    pub fn search_path(&self) -> Vec<String> {
+        let search_path = self.config_options.read().get_string(OPT_SEARCH_PATH);
+        let schemas = search_path
+            .iter()
+            .flat_map(|search_path| search_path.split(','))
+            .map(|schema| schema.trim())
+            .filter(|schema| !schema.is_empty())
+            .map(|schema| schema.to_owned())
+            .collect::<Vec<_>>();
+        if schemas.is_empty() {
+            vec![self.default_schema.clone()]
+        } else {
+            schemas
+        }
+    }
+
     /// Convert configuration options to name-value pairs with values
     /// converted to strings.
     ///
@@ -1330,6 +2108,15 @@ impl SessionConfig {
         self.config_options.clone()
     }
 
+    /// Returns a copy of this configuration whose options are not shared
+    /// with it, such that the options set afterwards, such as with `SET`,
+    /// do not change the copy
+    pub fn snapshot(&self) -> Self {
+        let mut config = self.clone();
+        config.config_options = Arc::new(RwLock::new(self.config_options.read().clone()));
+        config
+    }
+
     /// Add extensions.
     ///
     /// Extensions can be used to attach extra data to the session config -- e.g. tracing information or caches.
@@ -1422,6 +2209,11 @@ pub struct SessionState {
     pub execution_props: ExecutionProps,
     /// Runtime environment
     pub runtime_env: Arc<RuntimeEnv>,
+    /// Hook reporting the queries executed in this session, if any
+    pub audit_hook: Option<Arc<dyn QueryAuditHook>>,
+    /// Tags describing this session, such as the user it runs queries
+    /// for, that are reported to the [`QueryAuditHook`]
+    pub session_tags: HashMap<String, String>,
 }
 
 impl Debug for SessionState {
@@ -1478,6 +2270,9 @@ impl SessionState {
         let mut physical_optimizers: Vec<Arc<dyn PhysicalOptimizerRule + Sync + Send>> = vec![
             Arc::new(AggregateStatistics::new()),
             Arc::new(HashBuildProbeOrder::new()),
+            Arc::new(NestedProjection::new()),
+            Arc::new(ProjectionPushdown::new()),
+            Arc::new(EliminateSortRepartition::new()),
         ];
         if config
             .config_options
@@ -1496,11 +2291,20 @@ impl SessionState {
             )));
         }
         physical_optimizers.push(Arc::new(Repartition::new()));
+        physical_optimizers.push(Arc::new(BucketedJoin::new()));
+        physical_optimizers.push(Arc::new(CombinePartialFinalAggregate::new()));
         physical_optimizers.push(Arc::new(AddCoalescePartitionsExec::new()));
+        physical_optimizers.push(Arc::new(OutputCoercion::new()));
+
+        let mut optimizer = Optimizer::new(&optimizer_config);
+        optimizer.rules.insert(
+            0,
+            Arc::new(MaterializedViewRewrite::new(catalog_list.clone())),
+        );
 
         SessionState {
             session_id,
-            optimizer: Optimizer::new(&optimizer_config),
+            optimizer,
             physical_optimizers,
             query_planner: Arc::new(DefaultQueryPlanner {}),
             catalog_list,
@@ -1509,10 +2313,12 @@ impl SessionState {
             config,
             execution_props: ExecutionProps::new(),
             runtime_env: runtime,
+            audit_hook: None,
+            session_tags: HashMap::new(),
         }
     }
 
-    fn resolve_table_ref<'a>(
+    pub(crate) fn resolve_table_ref<'a>(
         &'a self,
         table_ref: impl Into<TableReference<'a>>,
     ) -> ResolvedTableReference<'a> {
@@ -1521,26 +2327,95 @@ impl SessionState {
             .resolve(&self.config.default_catalog, &self.config.default_schema)
     }
 
-    fn schema_for_ref<'a>(
+    /// The schema holding the table of `table_ref`. Unqualified names are
+    /// looked up in the schemas of the search path, and resolve to the
+    /// current schema when none of them holds the table.
+    pub(crate) fn schema_for_ref<'a>(
+        &'a self,
+        table_ref: impl Into<TableReference<'a>>,
+    ) -> Result<Arc<dyn SchemaProvider>> {
+        self.resolve_schema(table_ref, true)
+            .map(|(_, schema)| schema)
+    }
+
+    /// The schema where the table of `table_ref` is created. Unqualified
+    /// names resolve to the current schema, the first schema of the search
+    /// path that exists.
+    pub(crate) fn schema_for_new_ref<'a>(
         &'a self,
         table_ref: impl Into<TableReference<'a>>,
     ) -> Result<Arc<dyn SchemaProvider>> {
+        self.resolve_schema(table_ref, false)
+            .map(|(_, schema)| schema)
+    }
+
+    /// Resolve the schema of `table_ref` with its name, looking up the table
+    /// of unqualified names in the schemas of the search path if `lookup` is
+    /// set
+    fn resolve_schema<'a>(
+        &'a self,
+        table_ref: impl Into<TableReference<'a>>,
+        lookup: bool,
+    ) -> Result<(String, Arc<dyn SchemaProvider>)> {
+        let table_ref = table_ref.into();
         let resolved_ref = self.resolve_table_ref(table_ref);
-        self.catalog_list
-            .catalog(resolved_ref.catalog)
-            .ok_or_else(|| {
-                DataFusionError::Plan(format!(
-                    "failed to resolve catalog: {}",
-                    resolved_ref.catalog
-                ))
-            })?
-            .schema(resolved_ref.schema)
-            .ok_or_else(|| {
-                DataFusionError::Plan(format!(
-                    "failed to resolve schema: {}",
-                    resolved_ref.schema
-                ))
-            })
+        let catalog =
+            self.catalog_list
+                .catalog(resolved_ref.catalog)
+                .ok_or_else(|| {
+                    DataFusionError::Plan(format!(
+                        "failed to resolve catalog: {}",
+                        resolved_ref.catalog
+                    ))
+                })?;
+        let search_path = match table_ref {
+            TableReference::Bare { .. } => self.config.search_path(),
+            _ => vec![resolved_ref.schema.to_owned()],
+        };
+        let schemas = search_path
+            .iter()
+            .filter_map(|name| Some((name.clone(), catalog.schema(name)?)))
+            .collect::<Vec<_>>();
+        if lookup {
+            if let Some(found) = schemas
+                .iter()
+                .find(|(_, schema)| schema.table_exist(resolved_ref.table))
+            {
+                return Ok(found.clone());
+            }
+        }
+        schemas.into_iter().next().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "failed to resolve schema: {}",
+                search_path.join(", ")
+            ))
+        })
+    }
+
+    /// Resolve the table of `table_ref` with the [`TableResolver`] of its
+    /// catalog, if it has one, looking up unqualified names in the schemas of
+    /// the search path. Returns whether the table was found and not resolved
+    /// before.
+    pub async fn resolve_table(&self, table_ref: TableReference<'_>) -> Result<bool> {
+        let resolved_ref = self.resolve_table_ref(table_ref);
+        let catalog = match self.catalog_list.catalog(resolved_ref.catalog) {
+            Some(catalog) => catalog,
+            None => return Ok(false),
+        };
+        let resolver = match catalog.table_resolver() {
+            Some(resolver) => resolver,
+            None => return Ok(false),
+        };
+        let search_path = match table_ref {
+            TableReference::Bare { .. } => self.config.search_path(),
+            _ => vec![resolved_ref.schema.to_owned()],
+        };
+        for schema in &search_path {
+            if resolver.resolve_table(schema, resolved_ref.table).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 
     /// Replace the default query planner
@@ -1552,6 +2427,12 @@ impl SessionState {
         self
     }
 
+    /// Set the hook reporting the queries executed in this session
+    pub fn with_query_audit_hook(mut self, audit_hook: Arc<dyn QueryAuditHook>) -> Self {
+        self.audit_hook = Some(audit_hook);
+        self
+    }
+
     /// Replace the optimizer rules
     pub fn with_optimizer_rules(
         mut self,
@@ -1588,7 +2469,49 @@ impl SessionState {
         self
     }
 
+    /// Adds a new [`ExecutionPlanRewriter`], such as the one of an
+    /// accelerator backend, which runs after the physical optimizer rules
+    /// added before it, including the default rules
+    pub fn add_execution_plan_rewriter(
+        self,
+        rewriter: Arc<dyn ExecutionPlanRewriter>,
+    ) -> Self {
+        self.add_physical_optimizer_rule(Arc::new(RewriteExecutionPlan::new(rewriter)))
+    }
+
+    /// Snapshots this state at the start of the execution of a statement
+    ///
+    /// The snapshot reads the current time once, to which all the `now()` of
+    /// the statement evaluate, including in its views and after the rewrites
+    /// of the optimizer, and copies the configuration options, such that the
+    /// options set while the statement runs do not change its execution. The
+    /// statement is planned with the snapshot, and executed with a
+    /// [`TaskContext`] created from it. The snapshot only answers queries from
+    /// materialized views when the `datafusion.optimizer.materialized_view_rewrite`
+    /// option is set.
+    pub fn start_statement(&self) -> Result<SessionState> {
+        let mut state = self.clone();
+        state.config = self.config.snapshot();
+        state.execution_props.start_execution();
+        state
+            .execution_props
+            .set_random_seed(state.config.random_seed());
+        state
+            .execution_props
+            .set_time_zone(state.config.time_zone()?);
+        if !state.config.materialized_view_rewrite() {
+            state
+                .optimizer
+                .rules
+                .retain(|rule| rule.name() != MaterializedViewRewrite::NAME);
+        }
+        Ok(state)
+    }
+
     /// Optimizes the logical plan by applying optimizer rules.
+    ///
+    /// The plan is optimized with the [`ExecutionProps`] of this state, such
+    /// as a snapshot of [`Self::start_statement`].
     pub fn optimize(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
         let mut optimizer_config = OptimizerConfig::new()
             .with_skip_failing_rules(
@@ -1605,9 +2528,17 @@ impl SessionState {
                     .get_u64(OPT_OPTIMIZER_MAX_PASSES)
                     .unwrap_or_default() as u8,
             )
+            .with_rely_on_constraints(
+                self.config
+                    .config_options
+                    .read()
+                    .get_bool(OPT_OPTIMIZER_RELY_ON_CONSTRAINTS)
+                    .unwrap_or_default(),
+            )
             .with_query_execution_start_time(
                 self.execution_props.query_execution_start_time,
-            );
+            )
+            .with_time_zone(self.config.time_zone()?);
 
         if let LogicalPlan::Explain(e) = plan {
             let mut stringified_plans = e.stringified_plans.clone();
@@ -1636,11 +2567,23 @@ impl SessionState {
     }
 
     /// Creates a physical plan from a logical plan.
+    ///
+    /// The plan is created with the [`ExecutionProps`] of this state, such
+    /// as a snapshot of [`Self::start_statement`].
     pub async fn create_physical_plan(
         &self,
         logical_plan: &LogicalPlan,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         let planner = self.query_planner.clone();
+        if self
+            .optimizer
+            .rules
+            .iter()
+            .any(|rule| rule.name() == MaterializedViewRewrite::NAME)
+        {
+            // only the materialized views that are up to date answer queries
+            check_materialized_views(self).await?;
+        }
         let logical_plan = self.optimize(logical_plan)?;
         planner.create_physical_plan(&logical_plan, self).await
     }
@@ -1649,14 +2592,16 @@ impl SessionState {
 impl ContextProvider for SessionState {
     fn get_table_provider(&self, name: TableReference) -> Result<Arc<dyn TableSource>> {
         let resolved_ref = self.resolve_table_ref(name);
-        match self.schema_for_ref(resolved_ref) {
-            Ok(schema) => {
+        match self.resolve_schema(name, true) {
+            Ok((schema_name, schema)) => {
                 let provider = schema.table(resolved_ref.table).ok_or_else(|| {
                     DataFusionError::Plan(format!(
                         "'{}.{}.{}' not found",
-                        resolved_ref.catalog, resolved_ref.schema, resolved_ref.table
+                        resolved_ref.catalog, schema_name, resolved_ref.table
                     ))
                 })?;
+                let provider =
+                    with_table_policy(schema.as_ref(), resolved_ref.table, provider);
                 Ok(provider_as_source(provider))
             }
             Err(e) => Err(e),
@@ -1818,14 +2763,15 @@ impl TaskContext {
     }
 }
 
-/// Create a new task context instance from SessionContext
+/// Create a new task context instance from SessionContext, with a snapshot of its
+/// configuration
 impl From<&SessionContext> for TaskContext {
     fn from(session: &SessionContext) -> Self {
         let session_id = session.session_id.clone();
         let (config, scalar_functions, aggregate_functions) = {
             let session_state = session.state.read();
             (
-                session_state.config.clone(),
+                session_state.config.snapshot(),
                 session_state.scalar_functions.clone(),
                 session_state.aggregate_functions.clone(),
             )
@@ -1842,11 +2788,12 @@ impl From<&SessionContext> for TaskContext {
     }
 }
 
-/// Create a new task context instance from SessionState
+/// Create a new task context instance from SessionState, with a snapshot of its
+/// configuration
 impl From<&SessionState> for TaskContext {
     fn from(state: &SessionState) -> Self {
         let session_id = state.session_id.clone();
-        let config = state.config.clone();
+        let config = state.config.snapshot();
         let scalar_functions = state.scalar_functions.clone();
         let aggregate_functions = state.aggregate_functions.clone();
         let runtime = state.runtime_env.clone();
@@ -1902,6 +2849,7 @@ mod tests {
     use arrow::datatypes::*;
     use arrow::record_batch::RecordBatch;
     use async_trait::async_trait;
+    use datafusion_common::{Constraint, Constraints};
     use datafusion_expr::{create_udaf, create_udf, Expr, Volatility};
     use datafusion_physical_expr::functions::make_scalar_function;
     use std::fs::File;
@@ -2322,6 +3270,225 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn copy_from_stdin() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t AS SELECT 1 AS a, 'x' AS b").await?;
+
+        let data = futures::stream::iter(vec![
+            Ok(Bytes::from("a|b\n2|y\n")),
+            Ok(Bytes::from("3|z\n")),
+        ]);
+        let num_rows = ctx
+            .copy_from(
+                "COPY t FROM STDIN WITH (FORMAT CSV, HEADER, DELIMITER '|')",
+                data,
+            )
+            .await?;
+        assert_eq!(num_rows, 2);
+        let data =
+            futures::stream::iter(vec![Ok(Bytes::from("{\"a\": 4, \"b\": \"w\"}\n"))]);
+        ctx.copy_from("COPY t (a, b) FROM STDIN (FORMAT JSON)", data)
+            .await?;
+
+        let results = ctx
+            .sql("SELECT * FROM t ORDER BY a")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 | x |",
+            "| 2 | y |",
+            "| 3 | z |",
+            "| 4 | w |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &results);
+
+        // the data can only be sent through the API
+        let err = ctx.sql("COPY t FROM STDIN").await.unwrap_err();
+        assert!(err.to_string().contains("use SessionContext::copy_from"));
+        let err = ctx
+            .copy_from("SELECT * FROM t", futures::stream::empty())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Expected a COPY ... FROM STDIN"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn copy_from_with_check_constraints() -> Result<()> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let table = MemTable::try_new(schema.clone(), vec![])?.with_constraints(
+            Constraints::new(vec![Constraint::Check {
+                name: Some("a_positive".to_string()),
+                expr: "a > 0".to_string(),
+            }]),
+        );
+        ctx.register_table("t", Arc::new(table))?;
+        ctx.register_table("rejects", Arc::new(MemTable::try_new(schema, vec![])?))?;
+
+        // the rows violating the constraint fail the insertion
+        let data = || {
+            futures::stream::iter(vec![Ok(Bytes::from(
+                "1,x
+-2,y
+,z
+",
+            ))])
+        };
+        let err = ctx
+            .copy_from("COPY t FROM STDIN", data())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Check constraint a_positive is violated by 1 inserted rows"),
+            "{}",
+            err
+        );
+
+        // a missing rejects table fails the insertion before any row is written
+        ctx.sql("SET datafusion.execution.check_constraints.rejects_table = 'missing'")
+            .await?;
+        let err = ctx
+            .copy_from("COPY t FROM STDIN", data())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("No table named 'missing'"),
+            "{}",
+            err
+        );
+        let results = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        assert_eq!(results.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+
+        // or are inserted into the rejects table
+        ctx.sql("SET datafusion.execution.check_constraints.rejects_table = 'rejects'")
+            .await?;
+        let num_rows = ctx.copy_from("COPY t FROM STDIN", data()).await?;
+        assert_eq!(num_rows, 2);
+
+        let results = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        let expected = vec![
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 | x |",
+            "|   | z |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &results);
+        let results = ctx.sql("SELECT * FROM rejects").await?.collect().await?;
+        let expected = vec![
+            "+----+---+",
+            "| a  | b |",
+            "+----+---+",
+            "| -2 | y |",
+            "+----+---+",
+        ];
+        assert_batches_eq!(expected, &results);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn statement_snapshot() -> Result<()> {
+        let ctx = SessionContext::new();
+        let state = ctx.state.read().start_statement()?;
+        let task_ctx = ctx.task_ctx();
+
+        // the options set after the start of a statement do not change it
+        ctx.sql("SET datafusion.execution.batch_size = 10").await?;
+        assert_eq!(state.config.batch_size(), 8192);
+        assert_eq!(task_ctx.session_config().batch_size(), 8192);
+        assert_eq!(ctx.copied_config().batch_size(), 10);
+
+        // the optimizer and the physical planner read the same time
+        let plan = ctx.create_logical_plan("SELECT now() AS a, now() AS b")?;
+        let optimized = format!("{:?}", state.optimize(&plan)?);
+        let nanos = state
+            .execution_props
+            .query_execution_start_time
+            .timestamp_nanos();
+        assert!(optimized.contains(&format!("TimestampNanosecond({}", nanos)));
+        let physical_plan = state.create_physical_plan(&plan).await?;
+        let task_ctx = Arc::new(TaskContext::from(&state));
+        let batches = crate::physical_plan::collect(physical_plan, task_ctx).await?;
+        let column = arrow::array::as_primitive_array::<TimestampNanosecondType>(
+            batches[0].column(0),
+        );
+        assert_eq!(column.value(0), nanos);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sql_search_path() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE SCHEMA tenant").await?;
+        ctx.sql("CREATE SCHEMA shared").await?;
+        ctx.sql("CREATE TABLE shared.t AS VALUES (1)").await?;
+        ctx.sql("CREATE TABLE u AS VALUES (2)").await?;
+
+        // unqualified names are looked up in the default schema
+        let err = ctx.sql("SELECT * FROM t").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: 'datafusion.public.t' not found"
+        );
+
+        ctx.sql("SET search_path = missing, tenant, shared, public")
+            .await?;
+        assert_eq!(
+            ctx.state().config.search_path(),
+            vec!["missing", "tenant", "shared", "public"]
+        );
+        let expected = vec![
+            "+---------+",
+            "| column1 |",
+            "+---------+",
+            "| 1       |",
+            "+---------+",
+        ];
+        let results = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        assert_batches_eq!(expected, &results);
+        let results = ctx.sql("SELECT * FROM u").await?.collect().await?;
+        let expected_u = vec![
+            "+---------+",
+            "| column1 |",
+            "+---------+",
+            "| 2       |",
+            "+---------+",
+        ];
+        assert_batches_eq!(expected_u, &results);
+
+        // tables are created in the first existing schema, hiding the others
+        ctx.sql("CREATE TABLE t AS VALUES (3)").await?;
+        let tenant = ctx.catalog("datafusion").unwrap().schema("tenant").unwrap();
+        assert!(tenant.table_exist("t"));
+        let results = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        let expected_tenant = vec![
+            "+---------+",
+            "| column1 |",
+            "+---------+",
+            "| 3       |",
+            "+---------+",
+        ];
+        assert_batches_eq!(expected_tenant, &results);
+
+        ctx.sql("DROP TABLE t").await?;
+        assert!(!tenant.table_exist("t"));
+        let results = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        assert_batches_eq!(expected, &results);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn sql_create_catalog() -> Result<()> {
         // the information schema used to introduce cyclic Arcs
@@ -0,0 +1,265 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading of the data sent by the clients of `COPY ... FROM STDIN`
+//! statements, such as the frontends of wire protocols, into record batches
+//! that can be inserted into a table
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, ArrayRef};
+use arrow::csv;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::json;
+use arrow::json::reader::DecoderOptions;
+use arrow::record_batch::RecordBatch;
+use bytes::{Buf, Bytes};
+use futures::{Stream, StreamExt, TryStreamExt};
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::file_format::newline_delimited_stream;
+use crate::physical_plan::stream::RecordBatchStreamAdapter;
+use crate::physical_plan::SendableRecordBatchStream;
+use datafusion_sql::parser::CopyFrom;
+
+/// Reads the data of the statement `copy` from the chunks of bytes of `data`
+/// into batches of the schema `table_schema` of its table, of at most
+/// `batch_size` rows.
+///
+/// The data holds the columns of the statement, in its order, or all the
+/// columns of the table. The columns it does not hold are filled with nulls,
+/// and need to be nullable.
+pub fn read_copy_data<S>(
+    copy: &CopyFrom,
+    table_schema: SchemaRef,
+    batch_size: usize,
+    data: S,
+) -> Result<SendableRecordBatchStream>
+where
+    S: Stream<Item = Result<Bytes>> + Send + 'static,
+{
+    let indices = column_indices(copy, &table_schema)?;
+    let data_schema = Arc::new(table_schema.project(&indices)?);
+
+    let has_header = copy.has_header;
+    let delimiter = copy.delimiter as u8;
+    let json = match copy.file_type.as_str() {
+        "CSV" => false,
+        "NDJSON" => true,
+        file_type => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "COPY FROM STDIN does not support the format {}",
+                file_type
+            )))
+        }
+    };
+
+    let mut first_chunk = true;
+    let schema = data_schema.clone();
+    let batches = newline_delimited_stream(data.boxed())
+        .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+        .map_ok(move |bytes| {
+            let batches: Box<dyn Iterator<Item = ArrowResult<RecordBatch>> + Send> =
+                if json {
+                    let options = DecoderOptions::new().with_batch_size(batch_size);
+                    Box::new(json::Reader::new(bytes.reader(), schema.clone(), options))
+                } else {
+                    Box::new(csv::Reader::new(
+                        bytes.reader(),
+                        schema.clone(),
+                        has_header && first_chunk,
+                        Some(delimiter),
+                        batch_size,
+                        None,
+                        None,
+                        None,
+                    ))
+                };
+            first_chunk = false;
+            futures::stream::iter(batches)
+        })
+        .try_flatten();
+
+    let output_schema = table_schema.clone();
+    let batches = batches.map(move |batch| {
+        batch.and_then(|batch| table_batch(&batch, &output_schema, &indices))
+    });
+    Ok(Box::pin(RecordBatchStreamAdapter::new(
+        table_schema,
+        batches,
+    )))
+}
+
+/// The indices in `table_schema` of the columns of the data of `copy`
+fn column_indices(copy: &CopyFrom, table_schema: &Schema) -> Result<Vec<usize>> {
+    if copy.columns.is_empty() {
+        return Ok((0..table_schema.fields().len()).collect());
+    }
+    let mut indices = Vec::with_capacity(copy.columns.len());
+    for column in &copy.columns {
+        let index = table_schema.index_of(column).map_err(|_| {
+            DataFusionError::Plan(format!(
+                "Column '{}' of COPY not found in table '{}'",
+                column, copy.table_name
+            ))
+        })?;
+        if indices.contains(&index) {
+            return Err(DataFusionError::Plan(format!(
+                "Column '{}' specified more than once in COPY",
+                column
+            )));
+        }
+        indices.push(index);
+    }
+
+    let copied = indices.iter().collect::<HashSet<_>>();
+    for (index, field) in table_schema.fields().iter().enumerate() {
+        if !copied.contains(&index) && !field.is_nullable() {
+            return Err(DataFusionError::Plan(format!(
+                "Non-nullable column '{}' of table '{}' is missing from COPY",
+                field.name(),
+                copy.table_name
+            )));
+        }
+    }
+    Ok(indices)
+}
+
+/// Converts a batch of the data into a batch of the table, whose column
+/// `indices[i]` is the column `i` of the data
+fn table_batch(
+    batch: &RecordBatch,
+    table_schema: &SchemaRef,
+    indices: &[usize],
+) -> ArrowResult<RecordBatch> {
+    let columns = table_schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let column = match indices.iter().position(|i| *i == index) {
+                Some(position) => batch.column(position).clone(),
+                None => new_null_array(field.data_type(), batch.num_rows()),
+            };
+            if !field.is_nullable() && column.null_count() > 0 {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "COPY data has null values for non-nullable column '{}'",
+                    field.name()
+                )));
+            }
+            Ok(column)
+        })
+        .collect::<ArrowResult<Vec<ArrayRef>>>()?;
+    RecordBatch::try_new(table_schema.clone(), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::common::collect;
+    use arrow::datatypes::{DataType, Field};
+
+    fn copy(columns: &[&str], file_type: &str, has_header: bool) -> CopyFrom {
+        CopyFrom {
+            table_name: "t".to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            file_type: file_type.to_string(),
+            has_header,
+            delimiter: ',',
+        }
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]))
+    }
+
+    fn chunks(chunks: &[&'static str]) -> impl Stream<Item = Result<Bytes>> {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+            .collect::<Vec<_>>();
+        futures::stream::iter(chunks)
+    }
+
+    #[tokio::test]
+    async fn read_csv_chunks() -> Result<()> {
+        let stream = read_copy_data(
+            &copy(&["b", "a"], "CSV", true),
+            schema(),
+            2,
+            chunks(&["b,a\nx,1\ny", ",2\nz,3\n"]),
+        )?;
+        let expected = vec![
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 | x |",
+            "| 2 | y |",
+            "| 3 | z |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &collect(stream).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_json_without_nullable_columns() -> Result<()> {
+        let stream = read_copy_data(
+            &copy(&["a"], "NDJSON", false),
+            schema(),
+            1024,
+            chunks(&["{\"a\": 1}\n{\"a\"", ": 2}\n"]),
+        )?;
+        let expected = vec![
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 |   |",
+            "| 2 |   |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &collect(stream).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn validate_against_table_schema() -> Result<()> {
+        let err =
+            read_copy_data(&copy(&["b"], "CSV", false), schema(), 1024, chunks(&[]))
+                .unwrap_err();
+        assert!(err.to_string().contains("Non-nullable column 'a'"));
+
+        let err =
+            read_copy_data(&copy(&["c"], "CSV", false), schema(), 1024, chunks(&[]))
+                .unwrap_err();
+        assert!(err.to_string().contains("Column 'c' of COPY not found"));
+
+        let stream =
+            read_copy_data(&copy(&[], "CSV", false), schema(), 1024, chunks(&[",x\n"]))?;
+        let err = collect(stream).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("null values for non-nullable column 'a'"));
+        Ok(())
+    }
+}
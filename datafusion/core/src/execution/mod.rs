@@ -40,7 +40,10 @@
 //! In particular it is the state passed to [`crate::physical_plan::ExecutionPlan::execute`]
 //!
 
+pub mod audit;
+pub mod completion;
 pub mod context;
+pub mod copy;
 pub mod disk_manager;
 pub mod memory_manager;
 pub mod options;
@@ -223,7 +223,10 @@ pub mod error;
 pub mod execution;
 pub mod physical_optimizer;
 pub mod physical_plan;
+pub mod plan_diff;
 pub mod prelude;
+pub mod pretty;
+pub mod rows;
 pub mod scalar;
 #[cfg(feature = "scheduler")]
 pub mod scheduler;
@@ -0,0 +1,324 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Adaptive execution: runs a physical plan one stage at a time and
+//! re-optimizes the rest of the plan using the row counts observed for the
+//! completed stages.
+use std::sync::Arc;
+
+use arrow::error::ArrowError;
+use futures::TryStreamExt;
+use log::debug;
+
+use crate::config::OPT_ADAPTIVE_BROADCAST_THRESHOLD;
+use crate::error::Result;
+use crate::execution::context::{SessionConfig, TaskContext};
+use crate::logical_expr::JoinType;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::joins::{HashJoinExec, PartitionMode};
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::stream::RecordBatchStreamAdapter;
+use crate::physical_plan::{
+    displayable, execute_stream, materialize_subtree, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream,
+};
+
+use super::hash_build_probe_order::HashBuildProbeOrder;
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+
+/// Execute the stages of `plan` bottom-up, where a stage is the subtree below
+/// a [RepartitionExec], and return the plan with every stage replaced by its
+/// results.
+///
+/// After each stage completes, the statistics-based optimizer rules are run
+/// again on the plan, so that they see the exact row counts of the completed
+/// stages:
+///
+/// * [HashBuildProbeOrder] swaps join inputs so that the smaller one is built
+/// * [AdaptiveJoinSelection] turns partitioned inner joins with a small input
+///   into joins that collect that input, skipping the shuffle of the other one
+///
+/// The results of a stage keep its partitioning.
+pub async fn execute_adaptive(
+    plan: Arc<dyn ExecutionPlan>,
+    context: Arc<TaskContext>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let session_config = context.session_config();
+    let rules: Vec<Arc<dyn PhysicalOptimizerRule + Send + Sync>> = vec![
+        Arc::new(HashBuildProbeOrder::new()),
+        Arc::new(AdaptiveJoinSelection::new()),
+    ];
+
+    let mut plan = plan;
+    while let Some(path) = next_stage(&plan) {
+        plan = materialize_subtree(plan, &path, context.clone()).await?;
+        for rule in &rules {
+            plan = rule.optimize(plan, &session_config)?;
+        }
+        debug!(
+            "Physical plan after completing a stage:\n{}\n",
+            displayable(plan.as_ref()).indent()
+        );
+    }
+    Ok(plan)
+}
+
+/// Execute `plan` as [execute_adaptive] does, returning at once a stream of
+/// the output of the plan.
+///
+/// The stages are executed when the stream is first polled, and the output
+/// of the rest of the plan is then streamed as it is produced.
+pub fn execute_adaptive_stream(
+    plan: Arc<dyn ExecutionPlan>,
+    context: Arc<TaskContext>,
+) -> SendableRecordBatchStream {
+    let schema = plan.schema();
+    let stream = futures::stream::once(async move {
+        let plan = execute_adaptive(plan, context.clone()).await?;
+        execute_stream(plan, context).await
+    })
+    .map_err(ArrowError::from)
+    .try_flatten();
+    Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+}
+
+/// Returns the child indices leading to the first [RepartitionExec] in
+/// post-order, i.e. a stage with no incomplete stage below it
+fn next_stage(plan: &Arc<dyn ExecutionPlan>) -> Option<Vec<usize>> {
+    for (index, child) in plan.children().iter().enumerate() {
+        if let Some(mut path) = next_stage(child) {
+            path.insert(0, index);
+            return Some(path);
+        }
+    }
+    if plan.as_any().is::<RepartitionExec>() {
+        Some(vec![])
+    } else {
+        None
+    }
+}
+
+/// AdaptiveJoinSelection turns a partitioned inner hash join whose build
+/// (left) side is known to have at most
+/// `datafusion.execution.adaptive.broadcast_threshold` rows into a join that
+/// collects the build side, removing the hash repartitioning of both inputs.
+///
+/// The row count must be exact, as it is for the completed stages of
+/// [execute_adaptive].
+#[derive(Default)]
+pub struct AdaptiveJoinSelection {}
+
+impl AdaptiveJoinSelection {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for AdaptiveJoinSelection {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        session_config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let plan = optimize_children(self, plan, session_config)?;
+        if let Some(hash_join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+            let threshold = session_config
+                .config_options
+                .read()
+                .get_u64(OPT_ADAPTIVE_BROADCAST_THRESHOLD)
+                .unwrap_or_default() as usize;
+            let left_stats = hash_join.left().statistics();
+            let small_left = left_stats.is_exact
+                && matches!(left_stats.num_rows, Some(rows) if rows <= threshold);
+
+            if *hash_join.partition_mode() == PartitionMode::Partitioned
+                && *hash_join.join_type() == JoinType::Inner
+                && small_left
+            {
+                return Ok(Arc::new(HashJoinExec::try_new(
+                    remove_hash_repartition(hash_join.left()),
+                    remove_hash_repartition(hash_join.right()),
+                    hash_join.on().to_vec(),
+                    hash_join.filter().clone(),
+                    hash_join.join_type(),
+                    PartitionMode::CollectLeft,
+                    hash_join.null_equals_null(),
+                )?));
+            }
+        }
+        Ok(plan)
+    }
+
+    fn name(&self) -> &str {
+        "adaptive_join_selection"
+    }
+}
+
+/// Returns the input of a hash [RepartitionExec], possibly below a
+/// [CoalesceBatchesExec], or `plan` itself otherwise
+fn remove_hash_repartition(plan: &Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+    if let Some(coalesce) = plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+        let input = remove_hash_repartition(coalesce.input());
+        if !Arc::ptr_eq(&input, coalesce.input()) {
+            return input;
+        }
+    }
+    match plan.as_any().downcast_ref::<RepartitionExec>() {
+        Some(repartition)
+            if matches!(repartition.partitioning(), Partitioning::Hash(_, _)) =>
+        {
+            repartition.input().clone()
+        }
+        _ => plan.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::expressions::{col, Column};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::projection::ProjectionExec;
+    use crate::prelude::SessionContext;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    fn memory_exec(name: &str, values: Vec<i32>) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new(name, DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(values))],
+        )?;
+        Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?))
+    }
+
+    fn hash_repartition(
+        input: Arc<dyn ExecutionPlan>,
+        name: &str,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let expr = col(name, &input.schema())?;
+        Ok(Arc::new(RepartitionExec::try_new(
+            input,
+            Partitioning::Hash(vec![expr], 4),
+        )?))
+    }
+
+    #[tokio::test]
+    async fn collect_small_join_side() -> Result<()> {
+        let config = SessionConfig::new().set_u64(OPT_ADAPTIVE_BROADCAST_THRESHOLD, 5);
+        let session_ctx = SessionContext::with_config(config);
+        let task_ctx = session_ctx.task_ctx();
+
+        let left = hash_repartition(memory_exec("a", vec![1, 2, 3])?, "a")?;
+        let right = hash_repartition(memory_exec("b", (0..10).collect())?, "b")?;
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(HashJoinExec::try_new(
+            left,
+            right,
+            vec![(Column::new("a", 0), Column::new("b", 0))],
+            None,
+            &JoinType::Inner,
+            PartitionMode::Partitioned,
+            &false,
+        )?);
+
+        let adaptive = execute_adaptive(plan.clone(), task_ctx.clone()).await?;
+        let join = adaptive
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .expect("hash join");
+        assert_eq!(*join.partition_mode(), PartitionMode::CollectLeft);
+        assert!(join.left().as_any().is::<MemoryExec>());
+        // the right input is no longer shuffled
+        assert!(join.right().as_any().is::<MemoryExec>());
+        assert_eq!(join.right().output_partitioning().partition_count(), 1);
+
+        let expected = vec![
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 | 1 |",
+            "| 2 | 2 |",
+            "| 3 | 3 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &collect(plan, task_ctx.clone()).await?);
+        assert_batches_sorted_eq!(expected, &collect(adaptive, task_ctx).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_large_join_side_partitioned() -> Result<()> {
+        let config = SessionConfig::new().set_u64(OPT_ADAPTIVE_BROADCAST_THRESHOLD, 2);
+        let session_ctx = SessionContext::with_config(config);
+
+        let left = hash_repartition(memory_exec("a", vec![1, 2, 3])?, "a")?;
+        let right = hash_repartition(memory_exec("b", (0..10).collect())?, "b")?;
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(HashJoinExec::try_new(
+            left,
+            right,
+            vec![(Column::new("a", 0), Column::new("b", 0))],
+            None,
+            &JoinType::Inner,
+            PartitionMode::Partitioned,
+            &false,
+        )?);
+
+        let adaptive = execute_adaptive(plan, session_ctx.task_ctx()).await?;
+        let join = adaptive
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .expect("hash join");
+        assert_eq!(*join.partition_mode(), PartitionMode::Partitioned);
+        assert_eq!(join.left().output_partitioning().partition_count(), 4);
+        assert_eq!(join.right().output_partitioning().partition_count(), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_round_robin_stage_partitioning() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+        let input = memory_exec("a", vec![1, 2, 3])?;
+        let schema = input.schema();
+        let repartition = Arc::new(RepartitionExec::try_new(
+            input,
+            Partitioning::RoundRobinBatch(4),
+        )?);
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(ProjectionExec::try_new(
+            vec![(col("a", &schema)?, "a".to_string())],
+            repartition,
+        )?);
+
+        let adaptive = execute_adaptive(plan.clone(), task_ctx.clone()).await?;
+        assert_eq!(adaptive.output_partitioning().partition_count(), 4);
+
+        let expected = vec![
+            "+---+", "| a |", "+---+", "| 1 |", "| 2 |", "| 3 |", "+---+",
+        ];
+        let stream = execute_adaptive_stream(plan, task_ctx);
+        assert_eq!(stream.schema(), schema);
+        let batches = stream.try_collect::<Vec<_>>().await?;
+        assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! BucketedJoin optimizer rule that joins two tables bucketed alike on their
+//! bucket columns without repartitioning them
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::physical_plan::bucketed::BucketedExec;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::expressions::Column;
+use crate::physical_plan::filter::FilterExec;
+use crate::physical_plan::joins::{HashJoinExec, PartitionMode};
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::{with_new_children_if_necessary, ExecutionPlan, Partitioning};
+
+/// Optimizer rule that removes the hash [`RepartitionExec`]s of the inputs of
+/// a partitioned [`HashJoinExec`] when both inputs read a [`BucketedExec`]
+/// with the same number of buckets, bucketed on the join keys.
+///
+/// The partition `i` of both inputs then holds the rows of the bucket `i`, so
+/// that the rows with equal keys are in the same partition of both inputs.
+/// The inputs may filter the rows of the buckets, and the round robin
+/// repartitions between them and the buckets are removed as well.
+#[derive(Default)]
+pub struct BucketedJoin {}
+
+impl BucketedJoin {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for BucketedJoin {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let plan = optimize_children(self, plan, config)?;
+        let join = match plan.as_any().downcast_ref::<HashJoinExec>() {
+            Some(join) if *join.partition_mode() == PartitionMode::Partitioned => join,
+            _ => return Ok(plan),
+        };
+
+        let (left_keys, right_keys): (Vec<_>, Vec<_>) = join.on().iter().cloned().unzip();
+        let same_key_types = left_keys.iter().zip(&right_keys).all(|(left, right)| {
+            join.left().schema().field(left.index()).data_type()
+                == join.right().schema().field(right.index()).data_type()
+        });
+        if !same_key_types {
+            return Ok(plan);
+        }
+
+        let left = bucketed_input(join.left(), &left_keys)?;
+        let right = bucketed_input(join.right(), &right_keys)?;
+        match (left, right) {
+            (Some((left, left_buckets)), Some((right, right_buckets)))
+                if left_buckets == right_buckets =>
+            {
+                Ok(Arc::new(HashJoinExec::try_new(
+                    left,
+                    right,
+                    join.on().to_vec(),
+                    join.filter().clone(),
+                    join.join_type(),
+                    PartitionMode::Partitioned,
+                    join.null_equals_null(),
+                )?))
+            }
+            _ => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "bucketed_join"
+    }
+}
+
+/// Returns the input of the hash [`RepartitionExec`] of a join input, without
+/// its round robin repartitions, and its number of buckets, if it reads a
+/// [`BucketedExec`] bucketed on the join `keys`
+fn bucketed_input(
+    plan: &Arc<dyn ExecutionPlan>,
+    keys: &[Column],
+) -> Result<Option<(Arc<dyn ExecutionPlan>, usize)>> {
+    let plan = match plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+        Some(coalesce) => coalesce.input(),
+        None => plan,
+    };
+    let input = match plan.as_any().downcast_ref::<RepartitionExec>() {
+        Some(repartition)
+            if matches!(repartition.partitioning(), Partitioning::Hash(_, _)) =>
+        {
+            repartition.input()
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(match remove_round_robin(input)? {
+        Some((input, columns, num_buckets)) if columns == keys => {
+            Some((input, num_buckets))
+        }
+        _ => None,
+    })
+}
+
+/// Returns `plan` without its round robin repartitions, with the bucket
+/// columns and the number of buckets of the [`BucketedExec`] it reads, if it
+/// only filters the rows of the buckets
+fn remove_round_robin(
+    plan: &Arc<dyn ExecutionPlan>,
+) -> Result<Option<(Arc<dyn ExecutionPlan>, Vec<Column>, usize)>> {
+    let any = plan.as_any();
+    if let Some(bucketed) = any.downcast_ref::<BucketedExec>() {
+        return Ok(Some((
+            plan.clone(),
+            bucketed.columns().to_vec(),
+            bucketed.num_buckets(),
+        )));
+    }
+    if let Some(repartition) = any.downcast_ref::<RepartitionExec>() {
+        return match repartition.partitioning() {
+            Partitioning::RoundRobinBatch(_) => remove_round_robin(repartition.input()),
+            _ => Ok(None),
+        };
+    }
+    if any.is::<FilterExec>() || any.is::<CoalesceBatchesExec>() {
+        return match remove_round_robin(&plan.children()[0])? {
+            Some((input, columns, num_buckets)) => Ok(Some((
+                with_new_children_if_necessary(plan.clone(), vec![input])?,
+                columns,
+                num_buckets,
+            ))),
+            None => Ok(None),
+        };
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::displayable;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::joins::utils::JoinOn;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use datafusion_expr::{JoinType, Operator};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]))
+    }
+
+    fn bucketed_exec(name: &str, num_buckets: usize) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = Arc::new(MemoryExec::try_new(
+            &vec![vec![]; num_buckets],
+            schema(),
+            None,
+        )?);
+        let column = Column::new_with_schema(name, &schema())?;
+        Ok(Arc::new(BucketedExec::new(
+            input,
+            vec![column],
+            num_buckets,
+        )))
+    }
+
+    fn filter_round_robin(
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = Arc::new(RepartitionExec::try_new(
+            input,
+            Partitioning::RoundRobinBatch(4),
+        )?);
+        let predicate = binary(col("b", &schema())?, Operator::Gt, lit(1i32), &schema())?;
+        Ok(Arc::new(FilterExec::try_new(predicate, input)?))
+    }
+
+    fn hash_join(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        name: &str,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let hash_repartition = |input: Arc<dyn ExecutionPlan>| {
+            let partitioning = Partitioning::Hash(vec![col(name, &schema())?], 4);
+            Ok(Arc::new(RepartitionExec::try_new(input, partitioning)?))
+                as Result<Arc<dyn ExecutionPlan>>
+        };
+        let column = Column::new_with_schema(name, &schema())?;
+        let on: JoinOn = vec![(column.clone(), column)];
+        Ok(Arc::new(HashJoinExec::try_new(
+            hash_repartition(left)?,
+            hash_repartition(right)?,
+            on,
+            None,
+            &JoinType::Inner,
+            PartitionMode::Partitioned,
+            &false,
+        )?))
+    }
+
+    fn optimized_lines(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<String>> {
+        let optimized = BucketedJoin::new().optimize(plan, &SessionConfig::new())?;
+        let actual = format!("{}", displayable(optimized.as_ref()).indent());
+        Ok(actual.trim().lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn join_buckets_without_repartition() -> Result<()> {
+        let left = bucketed_exec("a", 2)?;
+        let right = filter_round_robin(bucketed_exec("a", 2)?)?;
+        let plan = hash_join(left, right, "a")?;
+        let expected = [
+            "HashJoinExec: mode=Partitioned, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"a\", index: 0 })]",
+            "  BucketedExec: columns=[a@0], num_buckets=2",
+            "    MemoryExec: partitions=2, partition_sizes=[0, 0]",
+            "  FilterExec: b@1 > 1",
+            "    BucketedExec: columns=[a@0], num_buckets=2",
+            "      MemoryExec: partitions=2, partition_sizes=[0, 0]",
+        ];
+        assert_eq!(optimized_lines(plan)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_repartition_of_other_bucketing() -> Result<()> {
+        // different numbers of buckets
+        let plan = hash_join(bucketed_exec("a", 2)?, bucketed_exec("a", 3)?, "a")?;
+        let expected = [
+            "HashJoinExec: mode=Partitioned, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"a\", index: 0 })]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    BucketedExec: columns=[a@0], num_buckets=2",
+            "      MemoryExec: partitions=2, partition_sizes=[0, 0]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    BucketedExec: columns=[a@0], num_buckets=3",
+            "      MemoryExec: partitions=3, partition_sizes=[0, 0, 0]",
+        ];
+        assert_eq!(optimized_lines(plan)?, expected);
+
+        // bucketed on another column than the join key
+        let plan = hash_join(bucketed_exec("a", 2)?, bucketed_exec("b", 2)?, "a")?;
+        let expected = [
+            "HashJoinExec: mode=Partitioned, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"a\", index: 0 })]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    BucketedExec: columns=[a@0], num_buckets=2",
+            "      MemoryExec: partitions=2, partition_sizes=[0, 0]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    BucketedExec: columns=[b@1], num_buckets=2",
+            "      MemoryExec: partitions=2, partition_sizes=[0, 0]",
+        ];
+        assert_eq!(optimized_lines(plan)?, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! CombinePartialFinalAggregate optimizer rule that replaces a partial and a
+//! final aggregate of a single partition with a single aggregate
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::physical_plan::aggregates::{AggregateExec, AggregateMode};
+use crate::physical_plan::ExecutionPlan;
+
+/// Optimizer rule that combines a final [`AggregateExec`] and the partial
+/// [`AggregateExec`] it reads into a single [`AggregateExec`] in
+/// [`AggregateMode::Single`] mode, when the input of the partial aggregate
+/// is a single partition.
+///
+/// The final aggregate then has nothing to merge, so that the groups are
+/// hashed once and the accumulators are evaluated without building their
+/// intermediate states.
+#[derive(Default)]
+pub struct CombinePartialFinalAggregate {}
+
+impl CombinePartialFinalAggregate {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for CombinePartialFinalAggregate {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let plan = optimize_children(self, plan, config)?;
+        let final_agg = match plan.as_any().downcast_ref::<AggregateExec>() {
+            Some(agg)
+                if matches!(
+                    agg.mode(),
+                    AggregateMode::Final | AggregateMode::FinalPartitioned
+                ) =>
+            {
+                agg
+            }
+            _ => return Ok(plan),
+        };
+        match final_agg.input().as_any().downcast_ref::<AggregateExec>() {
+            Some(partial_agg)
+                if partial_agg.mode() == &AggregateMode::Partial
+                    && partial_agg.input().output_partitioning().partition_count()
+                        == 1 =>
+            {
+                Ok(Arc::new(AggregateExec::try_new(
+                    AggregateMode::Single,
+                    partial_agg.group_expr().clone(),
+                    partial_agg.aggr_expr().to_vec(),
+                    partial_agg.input().clone(),
+                    partial_agg.input_schema(),
+                )?))
+            }
+            _ => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "combine_partial_final_agg"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::aggregates::PhysicalGroupBy;
+    use crate::physical_plan::expressions::{col, Avg, Count};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, displayable, AggregateExpr};
+    use crate::prelude::SessionContext;
+    use crate::{assert_batches_sorted_eq, test::build_table_i32};
+    use arrow::datatypes::DataType;
+
+    fn memory_exec(num_partitions: usize) -> Result<Arc<dyn ExecutionPlan>> {
+        let batch = build_table_i32(
+            ("a", &vec![1, 2, 1, 3]),
+            ("b", &vec![10, 20, 30, 40]),
+            ("c", &vec![5, 6, 7, 8]),
+        );
+        let schema = batch.schema();
+        let partitions = vec![vec![batch]; num_partitions];
+        Ok(Arc::new(MemoryExec::try_new(&partitions, schema, None)?))
+    }
+
+    fn partial_final_aggregate(
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = input.schema();
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![
+            Arc::new(Count::new(
+                col("b", &schema)?,
+                "COUNT(b)".to_string(),
+                DataType::Int64,
+            )),
+            Arc::new(Avg::new(
+                col("c", &schema)?,
+                "AVG(c)".to_string(),
+                DataType::Float64,
+            )),
+        ];
+        let group_by =
+            PhysicalGroupBy::new_single(vec![(col("a", &schema)?, "a".to_string())]);
+        let partial = Arc::new(AggregateExec::try_new(
+            AggregateMode::Partial,
+            group_by,
+            aggregates.clone(),
+            input,
+            schema.clone(),
+        )?);
+        let final_group_by = PhysicalGroupBy::new_single(
+            partial
+                .output_group_expr()
+                .into_iter()
+                .map(|expr| (expr, "a".to_string()))
+                .collect(),
+        );
+        Ok(Arc::new(AggregateExec::try_new(
+            AggregateMode::Final,
+            final_group_by,
+            aggregates,
+            partial,
+            schema,
+        )?))
+    }
+
+    fn plan_lines(plan: &Arc<dyn ExecutionPlan>) -> Vec<String> {
+        let actual = format!("{}", displayable(plan.as_ref()).indent());
+        actual.trim().lines().map(String::from).collect()
+    }
+
+    #[tokio::test]
+    async fn combine_single_partition_aggregates() -> Result<()> {
+        let plan = partial_final_aggregate(memory_exec(1)?)?;
+        let optimized =
+            CombinePartialFinalAggregate::new().optimize(plan, &SessionConfig::new())?;
+        let expected = [
+            "AggregateExec: mode=Single, gby=[a@0 as a], aggr=[COUNT(b), AVG(c)]",
+            "  MemoryExec: partitions=1, partition_sizes=[1]",
+        ];
+        assert_eq!(plan_lines(&optimized), expected);
+
+        let session_ctx = SessionContext::new();
+        let result = collect(optimized, session_ctx.task_ctx()).await?;
+        let expected = vec![
+            "+---+----------+--------+",
+            "| a | COUNT(b) | AVG(c) |",
+            "+---+----------+--------+",
+            "| 1 | 2        | 6      |",
+            "| 2 | 1        | 6      |",
+            "| 3 | 1        | 8      |",
+            "+---+----------+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_multiple_partition_aggregates() -> Result<()> {
+        let plan = partial_final_aggregate(memory_exec(2)?)?;
+        let optimized =
+            CombinePartialFinalAggregate::new().optimize(plan, &SessionConfig::new())?;
+        let expected = [
+            "AggregateExec: mode=Final, gby=[a@0 as a], aggr=[COUNT(b), AVG(c)]",
+            "  AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[COUNT(b), AVG(c)]",
+            "    MemoryExec: partitions=2, partition_sizes=[1, 1]",
+        ];
+        assert_eq!(plan_lines(&optimized), expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! EliminateSortRepartition optimizer rule that removes the [`SortExec`]s and
+//! hash [`RepartitionExec`]s whose input is already sorted or partitioned as
+//! required
+
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::sorts::sort::SortExec;
+use crate::physical_plan::{ExecutionPlan, Partitioning};
+use datafusion_physical_expr::utils::ordering_satisfy;
+
+/// Optimizer rule that removes
+///
+/// * a [`SortExec`] whose input is already sorted by its sort expressions,
+///   without a fetch, and that does not merge the partitions of its input
+/// * a [`RepartitionExec`] hash partitioning its input on the expressions its
+///   input is already hash partitioned on, into as many partitions
+///
+/// The expressions are compared using the equivalence properties of the
+/// input, so that a sort or partitioning on a column is also one on the
+/// columns equal to it, such as the other key of an inner join.
+#[derive(Default)]
+pub struct EliminateSortRepartition {}
+
+impl EliminateSortRepartition {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for EliminateSortRepartition {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.eliminate_sort_repartition() {
+            return Ok(plan);
+        }
+        let plan = optimize_children(self, plan, config)?;
+
+        if let Some(sort) = plan.as_any().downcast_ref::<SortExec>() {
+            let input = sort.input();
+            let merges_partitions = !sort.preserve_partitioning()
+                && input.output_partitioning().partition_count() > 1;
+            if sort.fetch().is_none()
+                && !merges_partitions
+                && ordering_satisfy(
+                    input.output_ordering(),
+                    Some(sort.expr()),
+                    &input.equivalence_properties(),
+                )
+            {
+                return Ok(input.clone());
+            }
+        } else if let Some(repartition) = plan.as_any().downcast_ref::<RepartitionExec>()
+        {
+            let input = repartition.input();
+            if matches!(repartition.partitioning(), Partitioning::Hash(_, _))
+                && input
+                    .output_partitioning()
+                    .satisfy(repartition.partitioning(), &input.equivalence_properties())
+            {
+                return Ok(input.clone());
+            }
+        }
+        Ok(plan)
+    }
+
+    fn name(&self) -> &str {
+        "eliminate_sort_repartition"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OPT_ELIMINATE_SORT_REPARTITION;
+    use crate::physical_plan::displayable;
+    use crate::physical_plan::expressions::{
+        binary, cast, col, lit, Column, PhysicalSortExpr,
+    };
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::joins::{utils::JoinOn, HashJoinExec, PartitionMode};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::projection::ProjectionExec;
+    use arrow::compute::SortOptions;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use datafusion_expr::{JoinType, Operator};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+            Field::new("c", DataType::Int32, false),
+        ]))
+    }
+
+    fn memory_exec(partitions: usize) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(MemoryExec::try_new(
+            &vec![vec![]; partitions],
+            schema(),
+            None,
+        )?))
+    }
+
+    fn sort_exec(
+        name: &str,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let expr = vec![PhysicalSortExpr {
+            expr: col(name, &input.schema())?,
+            options: SortOptions::default(),
+        }];
+        Ok(Arc::new(SortExec::try_new(expr, input, None)?))
+    }
+
+    fn filter_equal(
+        left: &str,
+        right: &str,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = input.schema();
+        let predicate = binary(
+            col(left, &schema)?,
+            Operator::Eq,
+            col(right, &schema)?,
+            &schema,
+        )?;
+        Ok(Arc::new(FilterExec::try_new(predicate, input)?))
+    }
+
+    fn hash_repartition(
+        name: &str,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let partitioning = Partitioning::Hash(vec![col(name, &input.schema())?], 4);
+        Ok(Arc::new(RepartitionExec::try_new(input, partitioning)?))
+    }
+
+    fn assert_optimized(plan: Arc<dyn ExecutionPlan>, expected: &[&str]) -> Result<()> {
+        let optimized =
+            EliminateSortRepartition::new().optimize(plan, &SessionConfig::new())?;
+        let actual = format!("{}", displayable(optimized.as_ref()).indent());
+        assert_eq!(actual.trim().lines().collect::<Vec<_>>(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn remove_sort_on_equal_column() -> Result<()> {
+        let plan = sort_exec("a", memory_exec(1)?)?;
+        let plan = sort_exec("b", filter_equal("a", "b", plan)?)?;
+        let expected = [
+            "FilterExec: a@0 = b@1",
+            "  SortExec: [a@0 ASC]",
+            "    MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)?;
+
+        // c is not equal to a
+        let plan = sort_exec("a", memory_exec(1)?)?;
+        let plan = sort_exec("c", filter_equal("a", "b", plan)?)?;
+        let expected = [
+            "SortExec: [c@2 ASC]",
+            "  FilterExec: a@0 = b@1",
+            "    SortExec: [a@0 ASC]",
+            "      MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)
+    }
+
+    #[test]
+    fn disabled() -> Result<()> {
+        let plan = sort_exec("a", memory_exec(1)?)?;
+        let plan = sort_exec("b", filter_equal("a", "b", plan)?)?;
+        let config = SessionConfig::new().set_bool(OPT_ELIMINATE_SORT_REPARTITION, false);
+        let optimized =
+            EliminateSortRepartition::new().optimize(plan.clone(), &config)?;
+        assert!(Arc::ptr_eq(&plan, &optimized));
+        Ok(())
+    }
+
+    #[test]
+    fn remove_sort_on_alias() -> Result<()> {
+        let input = sort_exec("a", memory_exec(1)?)?;
+        let projection = Arc::new(ProjectionExec::try_new(
+            vec![
+                (col("b", &schema())?, "b".to_string()),
+                (col("a", &schema())?, "x".to_string()),
+            ],
+            input,
+        )?);
+        let plan = sort_exec("x", projection)?;
+        let expected = [
+            "ProjectionExec: expr=[b@1 as b, a@0 as x]",
+            "  SortExec: [a@0 ASC]",
+            "    MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)
+    }
+
+    #[test]
+    fn remove_sort_on_increasing_expression() -> Result<()> {
+        let input = sort_exec("a", memory_exec(1)?)?;
+        let schema = schema();
+        let projection = Arc::new(ProjectionExec::try_new(
+            vec![
+                (
+                    binary(
+                        cast(col("a", &schema)?, &schema, DataType::Int64)?,
+                        Operator::Plus,
+                        lit(1i64),
+                        &schema,
+                    )?,
+                    "x".to_string(),
+                ),
+                (col("b", &schema)?, "b".to_string()),
+            ],
+            input,
+        )?);
+        let plan = sort_exec("x", projection.clone())?;
+        let expected = [
+            "ProjectionExec: expr=[CAST(a@0 AS Int64) + 1 as x, b@1 as b]",
+            "  SortExec: [a@0 ASC]",
+            "    MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)?;
+
+        let plan = sort_exec("b", projection)?;
+        let expected = [
+            "SortExec: [b@1 ASC]",
+            "  ProjectionExec: expr=[CAST(a@0 AS Int64) + 1 as x, b@1 as b]",
+            "    SortExec: [a@0 ASC]",
+            "      MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)
+    }
+
+    #[test]
+    fn keep_sort_merging_partitions() -> Result<()> {
+        let input = Arc::new(SortExec::new_with_partitioning(
+            vec![PhysicalSortExpr {
+                expr: col("a", &schema())?,
+                options: SortOptions::default(),
+            }],
+            memory_exec(2)?,
+            true,
+            None,
+        ));
+        let plan = sort_exec("a", input)?;
+        let expected = [
+            "SortExec: [a@0 ASC]",
+            "  SortExec: [a@0 ASC]",
+            "    MemoryExec: partitions=2, partition_sizes=[0, 0]",
+        ];
+        assert_optimized(plan, &expected)
+    }
+
+    #[test]
+    fn remove_repartition_on_join_key() -> Result<()> {
+        let left = hash_repartition("a", memory_exec(1)?)?;
+        let right = hash_repartition("a", memory_exec(1)?)?;
+        let on: JoinOn = vec![(Column::new("a", 0), Column::new("a", 0))];
+        let join = Arc::new(HashJoinExec::try_new(
+            left,
+            right,
+            on,
+            None,
+            &JoinType::Inner,
+            PartitionMode::Partitioned,
+            &false,
+        )?);
+
+        // both keys are equal after the join, and the rows are partitioned on
+        // the right key
+        let plan = hash_repartition("a", join.clone())?;
+        let expected = [
+            "HashJoinExec: mode=Partitioned, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"a\", index: 0 })]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    MemoryExec: partitions=1, partition_sizes=[0]",
+            "  RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "    MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)?;
+
+        let plan = hash_repartition("b", join)?;
+        let expected = [
+            "RepartitionExec: partitioning=Hash([Column { name: \"b\", index: 1 }], 4)",
+            "  HashJoinExec: mode=Partitioned, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"a\", index: 0 })]",
+            "    RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "      MemoryExec: partitions=1, partition_sizes=[0]",
+            "    RepartitionExec: partitioning=Hash([Column { name: \"a\", index: 0 }], 4)",
+            "      MemoryExec: partitions=1, partition_sizes=[0]",
+        ];
+        assert_optimized(plan, &expected)
+    }
+}
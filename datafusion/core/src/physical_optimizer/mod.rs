@@ -18,13 +18,22 @@
 //! This module contains a query optimizer that operates against a physical plan and applies
 //! rules to a physical plan, such as "Repartition".
 
+pub mod adaptive;
 pub mod aggregate_statistics;
+pub mod bucketed_join;
 pub mod coalesce_batches;
+pub mod combine_partial_final_agg;
+pub mod eliminate_sort_repartition;
 pub mod hash_build_probe_order;
 pub mod merge_exec;
+pub mod nested_projection;
 pub mod optimizer;
+pub mod output_coercion;
+pub mod plan_rewriter;
+pub mod projection_pushdown;
 pub mod pruning;
 pub mod repartition;
 mod utils;
 
 pub use optimizer::PhysicalOptimizerRule;
+pub use plan_rewriter::ExecutionPlanRewriter;
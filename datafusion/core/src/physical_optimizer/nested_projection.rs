@@ -0,0 +1,307 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! NestedProjection optimizer rule that limits the fields a [`ParquetExec`]
+//! reads from struct columns to the ones accessed by the plan above it
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::physical_plan::expressions::{Column, GetIndexedFieldExpr};
+use crate::physical_plan::file_format::ParquetExec;
+use crate::physical_plan::filter::FilterExec;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::{with_new_children_if_necessary, ExecutionPlan, PhysicalExpr};
+use crate::scalar::ScalarValue;
+
+/// Optimizer rule that pushes the struct fields accessed by a
+/// [`ProjectionExec`], and any [`FilterExec`]s below it, into the
+/// [`ParquetExec`] they read from, so that only the required leaf
+/// columns of nested data are read and decoded.
+#[derive(Default)]
+pub struct NestedProjection {}
+
+impl NestedProjection {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for NestedProjection {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.nested_projection_pushdown() {
+            return Ok(plan);
+        }
+        if let Some(projection) = plan.as_any().downcast_ref::<ProjectionExec>() {
+            let mut exprs = projection
+                .expr()
+                .iter()
+                .map(|(expr, _)| expr.clone())
+                .collect::<Vec<_>>();
+            if let Some(input) = push_down_field_paths(projection.input(), &mut exprs)? {
+                return with_new_children_if_necessary(plan, vec![input]);
+            }
+        }
+        optimize_children(self, plan, config)
+    }
+
+    fn name(&self) -> &str {
+        "nested_projection"
+    }
+}
+
+/// Walks down the [`FilterExec`]s below a projection to a [`ParquetExec`],
+/// adding their predicates to `exprs`, and returns the plan with the scan
+/// restricted to the struct fields `exprs` access, or `None` if there is no
+/// such scan or no column is only accessed through its fields.
+fn push_down_field_paths(
+    plan: &Arc<dyn ExecutionPlan>,
+    exprs: &mut Vec<Arc<dyn PhysicalExpr>>,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    if let Some(filter) = plan.as_any().downcast_ref::<FilterExec>() {
+        exprs.push(filter.predicate().clone());
+        return match push_down_field_paths(filter.input(), exprs)? {
+            Some(input) => Ok(Some(with_new_children_if_necessary(
+                plan.clone(),
+                vec![input],
+            )?)),
+            None => Ok(None),
+        };
+    }
+
+    let parquet = match plan.as_any().downcast_ref::<ParquetExec>() {
+        Some(parquet) => parquet,
+        None => return Ok(None),
+    };
+
+    let mut field_paths = HashMap::new();
+    for expr in exprs.iter() {
+        collect_field_paths(expr, &mut field_paths);
+    }
+
+    let schema = parquet.schema();
+    let mut nested_projection = field_paths
+        .into_iter()
+        .filter_map(|(index, paths)| {
+            let column = schema.field(index).name();
+            paths.map(|paths| {
+                paths.into_iter().map(move |path| {
+                    std::iter::once(column.clone()).chain(path).collect()
+                })
+            })
+        })
+        .flatten()
+        .collect::<Vec<Vec<String>>>();
+    if nested_projection.is_empty() {
+        return Ok(None);
+    }
+    // keep the plan display stable
+    nested_projection.sort();
+    nested_projection.dedup();
+
+    Ok(Some(Arc::new(
+        parquet.clone().with_nested_projection(nested_projection),
+    )))
+}
+
+/// Records, for each column index referenced by `expr`, the struct field
+/// paths the column is accessed through, or `None` if it is used as a whole
+fn collect_field_paths(
+    expr: &Arc<dyn PhysicalExpr>,
+    field_paths: &mut HashMap<usize, Option<Vec<Vec<String>>>>,
+) {
+    if let Some((column, path)) = field_path(expr) {
+        if let Some(paths) = field_paths
+            .entry(column.index())
+            .or_insert_with(|| Some(vec![]))
+        {
+            paths.push(path);
+        }
+    } else if let Some(column) = expr.as_any().downcast_ref::<Column>() {
+        field_paths.insert(column.index(), None);
+    } else {
+        for child in expr.children() {
+            collect_field_paths(&child, field_paths);
+        }
+    }
+}
+
+/// Returns the column and the field names of a chain of struct field
+/// accesses such as `s['a']['b']`
+fn field_path(expr: &Arc<dyn PhysicalExpr>) -> Option<(&Column, Vec<String>)> {
+    let get_field = expr.as_any().downcast_ref::<GetIndexedFieldExpr>()?;
+    let name = match get_field.key() {
+        ScalarValue::Utf8(Some(name)) => name.clone(),
+        _ => return None,
+    };
+    let (column, mut path) = match get_field.arg().as_any().downcast_ref::<Column>() {
+        Some(column) => (column, vec![]),
+        None => field_path(get_field.arg())?,
+    };
+    path.push(name);
+    Some((column, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::{collect, displayable};
+    use crate::prelude::{ParquetReadOptions, SessionContext};
+    use arrow::array::{ArrayRef, Float64Array, Int32Array, StringArray, StructArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use datafusion_expr::Operator;
+    use parquet::arrow::ArrowWriter;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn geo_field() -> Field {
+        Field::new(
+            "geo",
+            DataType::Struct(vec![
+                Field::new("lat", DataType::Float64, true),
+                Field::new("lon", DataType::Float64, true),
+            ]),
+            true,
+        )
+    }
+
+    fn meta_field() -> Field {
+        Field::new(
+            "meta",
+            DataType::Struct(vec![
+                Field::new("country", DataType::Utf8, true),
+                geo_field(),
+            ]),
+            true,
+        )
+    }
+
+    fn struct_schema() -> Schema {
+        Schema::new(vec![Field::new("id", DataType::Int32, false), meta_field()])
+    }
+
+    fn get_field(arg: Arc<dyn PhysicalExpr>, name: &str) -> Arc<dyn PhysicalExpr> {
+        Arc::new(GetIndexedFieldExpr::new(arg, ScalarValue::from(name)))
+    }
+
+    #[test]
+    fn field_paths() -> Result<()> {
+        let schema = struct_schema();
+        let meta = col("meta", &schema)?;
+        let lat = get_field(get_field(meta.clone(), "geo"), "lat");
+        let country = get_field(meta.clone(), "country");
+        let predicate = binary(country, Operator::Eq, lit("JP"), &schema)?;
+
+        let mut paths = HashMap::new();
+        collect_field_paths(&lat, &mut paths);
+        collect_field_paths(&predicate, &mut paths);
+        collect_field_paths(&col("id", &schema)?, &mut paths);
+        assert_eq!(
+            paths,
+            HashMap::from([
+                (0, None),
+                (
+                    1,
+                    Some(vec![
+                        vec!["geo".to_string(), "lat".to_string()],
+                        vec!["country".to_string()]
+                    ])
+                ),
+            ])
+        );
+
+        // the whole struct is needed as soon as it is used directly
+        collect_field_paths(&meta, &mut paths);
+        assert_eq!(paths[&1], None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_nested_fields() -> Result<()> {
+        let schema = Arc::new(struct_schema());
+        let geo = StructArray::from(vec![
+            (
+                Field::new("lat", DataType::Float64, true),
+                Arc::new(Float64Array::from(vec![35.6, 48.8])) as ArrayRef,
+            ),
+            (
+                Field::new("lon", DataType::Float64, true),
+                Arc::new(Float64Array::from(vec![139.6, 2.3])) as ArrayRef,
+            ),
+        ]);
+        let meta = StructArray::from(vec![
+            (
+                Field::new("country", DataType::Utf8, true),
+                Arc::new(StringArray::from(vec!["JP", "FR"])) as ArrayRef,
+            ),
+            (geo_field(), Arc::new(geo) as ArrayRef),
+        ]);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2])), Arc::new(meta)],
+        )?;
+
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("nested.parquet");
+        let mut writer = ArrowWriter::try_new(File::create(&path)?, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let ctx = SessionContext::new();
+        ctx.register_parquet("t", path.to_str().unwrap(), ParquetReadOptions::default())
+            .await?;
+        let sql =
+            "SELECT id, meta['geo']['lat'] AS lat FROM t WHERE meta['country'] = 'JP'";
+        let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+        let displayed = format!("{}", displayable(plan.as_ref()).indent());
+        assert!(
+            displayed.contains("nested_projection=[meta.country, meta.geo.lat]"),
+            "{}",
+            displayed
+        );
+
+        // the whole structs are read when the rule is disabled
+        ctx.sql("SET datafusion.optimizer.nested_projection_pushdown = false")
+            .await?;
+        let unpushed = ctx.sql(sql).await?.create_physical_plan().await?;
+        let displayed = format!("{}", displayable(unpushed.as_ref()).indent());
+        assert!(!displayed.contains("nested_projection"), "{}", displayed);
+
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let expected = vec![
+            "+----+------+",
+            "| id | lat  |",
+            "+----+------+",
+            "| 1  | 35.6 |",
+            "+----+------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+}
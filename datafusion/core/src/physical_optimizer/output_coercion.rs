@@ -0,0 +1,430 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! OutputCoercion converts the columns of the results of a query into types
+//! that clients such as ODBC drivers and BI tools support
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, FixedSizeListArray, GenericListArray, LargeStringArray, MapArray,
+    OffsetSizeTrait, StringArray, StringBuilder, StructArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+
+use crate::config::OPT_OUTPUT_COERCION;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::logical_expr::ColumnarValue;
+use crate::physical_optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::expressions::{
+    CastExpr, Column, DEFAULT_DATAFUSION_CAST_OPTIONS,
+};
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::{ExecutionPlan, PhysicalExpr};
+use datafusion_physical_expr::down_cast_any_ref;
+
+/// Optimizer rule that projects the output of a plan onto types that clients
+/// with a limited understanding of Arrow can read, when the
+/// `datafusion.execution.output_coercion` option is set:
+///
+/// * dictionaries are unpacked into their values
+/// * large strings and binaries become strings and binaries
+/// * lists, structs and maps become strings holding their values as JSON
+#[derive(Default)]
+pub struct OutputCoercion {}
+
+impl OutputCoercion {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for OutputCoercion {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let enabled = config
+            .config_options
+            .read()
+            .get_bool(OPT_OUTPUT_COERCION)
+            .unwrap_or_default();
+        let schema = plan.schema();
+        if !enabled
+            || schema
+                .fields()
+                .iter()
+                .all(|field| coerced_type(field.data_type()).is_none())
+        {
+            return Ok(plan);
+        }
+
+        let exprs = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let column: Arc<dyn PhysicalExpr> =
+                    Arc::new(Column::new(field.name(), index));
+                let expr = match coerced_type(field.data_type()) {
+                    None => column,
+                    Some(DataType::Utf8) if is_nested(field.data_type()) => {
+                        Arc::new(ToJsonExpr::new(column))
+                    }
+                    Some(data_type) => Arc::new(CastExpr::new(
+                        column,
+                        data_type,
+                        DEFAULT_DATAFUSION_CAST_OPTIONS,
+                    )),
+                };
+                (expr, field.name().clone())
+            })
+            .collect();
+        Ok(Arc::new(ProjectionExec::try_new(exprs, plan)?))
+    }
+
+    fn name(&self) -> &str {
+        "output_coercion"
+    }
+}
+
+/// The type the output columns of type `data_type` are converted to, if any
+pub fn coerced_type(data_type: &DataType) -> Option<DataType> {
+    match data_type {
+        DataType::Dictionary(_, value_type) => {
+            Some(coerced_type(value_type).unwrap_or_else(|| *value_type.clone()))
+        }
+        DataType::LargeUtf8 => Some(DataType::Utf8),
+        DataType::LargeBinary => Some(DataType::Binary),
+        _ if is_nested(data_type) => Some(DataType::Utf8),
+        _ => None,
+    }
+}
+
+fn is_nested(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::FixedSizeList(_, _)
+        | DataType::Struct(_)
+        | DataType::Map(_, _) => true,
+        DataType::Dictionary(_, value_type) => is_nested(value_type),
+        _ => false,
+    }
+}
+
+/// Renders the values of nested types as JSON strings
+#[derive(Debug)]
+struct ToJsonExpr {
+    expr: Arc<dyn PhysicalExpr>,
+}
+
+impl ToJsonExpr {
+    fn new(expr: Arc<dyn PhysicalExpr>) -> Self {
+        Self { expr }
+    }
+}
+
+impl fmt::Display for ToJsonExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TO_JSON({})", self.expr)
+    }
+}
+
+impl PhysicalExpr for ToJsonExpr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        self.expr.nullable(input_schema)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let array = self.expr.evaluate(batch)?.into_array(batch.num_rows());
+        let mut builder = StringBuilder::with_capacity(array.len(), 1024);
+        let mut json = String::new();
+        for row in 0..array.len() {
+            if array.is_null(row) {
+                builder.append_null();
+            } else {
+                json.clear();
+                write_json(&array, row, &mut json)?;
+                builder.append_value(&json);
+            }
+        }
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Ok(Arc::new(ToJsonExpr::new(children[0].clone())))
+    }
+}
+
+impl PartialEq<dyn Any> for ToJsonExpr {
+    fn eq(&self, other: &dyn Any) -> bool {
+        down_cast_any_ref(other)
+            .downcast_ref::<Self>()
+            .map(|x| self.expr.eq(&x.expr))
+            .unwrap_or(false)
+    }
+}
+
+/// Appends the value of `array` at `row` to `out` as JSON
+fn write_json(array: &ArrayRef, row: usize, out: &mut String) -> Result<()> {
+    if array.is_null(row) {
+        out.push_str("null");
+        return Ok(());
+    }
+    match array.data_type() {
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Decimal128(_, _) => {
+            out.push_str(&array_value_to_string(array, row)?);
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            let value = array_value_to_string(array, row)?;
+            // NaN and infinities have no JSON representation
+            if value.parse::<f64>().map(f64::is_finite).unwrap_or(false) {
+                out.push_str(&value);
+            } else {
+                write_json_string(&value, out);
+            }
+        }
+        DataType::Utf8 => {
+            let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+            write_json_string(array.value(row), out);
+        }
+        DataType::LargeUtf8 => {
+            let array = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            write_json_string(array.value(row), out);
+        }
+        DataType::List(_) => write_json_list::<i32>(array, row, out)?,
+        DataType::LargeList(_) => write_json_list::<i64>(array, row, out)?,
+        DataType::FixedSizeList(_, _) => {
+            let array = array.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            write_json_array(&array.value(row), out)?;
+        }
+        DataType::Struct(fields) => {
+            let array = array.as_any().downcast_ref::<StructArray>().unwrap();
+            out.push('{');
+            for (index, field) in fields.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_json_string(field.name(), out);
+                out.push(':');
+                write_json(array.column(index), row, out)?;
+            }
+            out.push('}');
+        }
+        DataType::Map(_, _) => {
+            let array = array.as_any().downcast_ref::<MapArray>().unwrap();
+            let entries = array.value(row);
+            let entries = entries.as_any().downcast_ref::<StructArray>().unwrap();
+            out.push('{');
+            for entry in 0..entries.len() {
+                if entry > 0 {
+                    out.push(',');
+                }
+                // JSON object keys are strings, whatever the type of the map keys
+                let key = entries.column(0);
+                match key.data_type() {
+                    DataType::Utf8 | DataType::LargeUtf8 => {
+                        write_json(key, entry, out)?;
+                    }
+                    _ => write_json_string(&array_value_to_string(key, entry)?, out),
+                }
+                out.push(':');
+                write_json(entries.column(1), entry, out)?;
+            }
+            out.push('}');
+        }
+        DataType::Dictionary(_, value_type) => {
+            let value = cast(&array.slice(row, 1), value_type)?;
+            write_json(&value, 0, out)?;
+        }
+        _ => write_json_string(&array_value_to_string(array, row)?, out),
+    }
+    Ok(())
+}
+
+fn write_json_list<O: OffsetSizeTrait>(
+    array: &ArrayRef,
+    row: usize,
+    out: &mut String,
+) -> Result<()> {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericListArray<O>>()
+        .unwrap();
+    write_json_array(&array.value(row), out)
+}
+
+fn write_json_array(values: &ArrayRef, out: &mut String) -> Result<()> {
+    out.push('[');
+    for index in 0..values.len() {
+        if index > 0 {
+            out.push(',');
+        }
+        write_json(values, index, out)?;
+    }
+    out.push(']');
+    Ok(())
+}
+
+fn write_json_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::prelude::SessionContext;
+    use arrow::array::{DictionaryArray, Int32Array, Int32Builder, ListBuilder};
+    use arrow::datatypes::{Field, Int8Type};
+
+    fn exotic_plan() -> Result<Arc<dyn ExecutionPlan>> {
+        let dict: DictionaryArray<Int8Type> =
+            vec![Some("a"), None, Some("a")].into_iter().collect();
+        let large = LargeStringArray::from(vec![Some("x\"y"), Some("z"), None]);
+        let mut list = ListBuilder::new(Int32Builder::new());
+        list.values().append_value(1);
+        list.values().append_null();
+        list.append(true);
+        list.append(false);
+        list.append(true);
+        let list = list.finish();
+        let list_type = list.data_type().clone();
+        let ints: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["p", "q", "r"]));
+        let structs = StructArray::from(vec![
+            (Field::new("i", DataType::Int32, false), ints.clone()),
+            (Field::new("s", DataType::Utf8, false), strings),
+        ]);
+        let struct_type = structs.data_type().clone();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("d", dict.data_type().clone(), true),
+            Field::new("l", DataType::LargeUtf8, true),
+            Field::new("list", list_type, true),
+            Field::new("s", struct_type, false),
+            Field::new("i", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(dict),
+                Arc::new(large),
+                Arc::new(list),
+                Arc::new(structs),
+                ints,
+            ],
+        )?;
+        Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?))
+    }
+
+    #[tokio::test]
+    async fn coerce_output() -> Result<()> {
+        let config = SessionConfig::new().with_output_coercion(true);
+        let session_ctx = SessionContext::with_config(config.clone());
+        let plan = OutputCoercion::new().optimize(exotic_plan()?, &config)?;
+
+        let schema = plan.schema();
+        let types = schema
+            .fields()
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![DataType::Utf8; 4]
+                .into_iter()
+                .chain(std::iter::once(DataType::Int32))
+                .collect::<Vec<_>>()
+        );
+
+        let expected = vec![
+            "+---+-----+----------+-----------------+---+",
+            "| d | l   | list     | s               | i |",
+            "+---+-----+----------+-----------------+---+",
+            "| a | x\"y | [1,null] | {\"i\":1,\"s\":\"p\"} | 1 |",
+            "|   | z   |          | {\"i\":2,\"s\":\"q\"} | 2 |",
+            "| a |     | []       | {\"i\":3,\"s\":\"r\"} | 3 |",
+            "+---+-----+----------+-----------------+---+",
+        ];
+        assert_batches_eq!(expected, &collect(plan, session_ctx.task_ctx()).await?);
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_by_default() -> Result<()> {
+        let plan = exotic_plan()?;
+        let optimized =
+            OutputCoercion::new().optimize(plan.clone(), &SessionConfig::new())?;
+        assert!(Arc::ptr_eq(&plan, &optimized));
+        Ok(())
+    }
+
+    #[test]
+    fn json_strings() {
+        let mut out = String::new();
+        write_json_string("a\"b\\c\nd\u{1}", &mut out);
+        assert_eq!(out, r#""a\"b\\c\nd\u0001""#);
+    }
+}
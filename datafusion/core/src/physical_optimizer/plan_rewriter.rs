@@ -0,0 +1,187 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! The [`ExecutionPlanRewriter`] extension point of the physical optimizer,
+//! through which backends, such as accelerators, substitute their operators
+//! for the subtrees of plans they support
+
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::SessionConfig;
+use crate::physical_plan::ExecutionPlan;
+
+/// Substitutes other operators, computing the same results, for the
+/// subtrees of physical plans, such as the operators of a GPU backend for
+/// the subtrees it can execute
+///
+/// Rewriters are added to a session with
+/// [`SessionState::add_execution_plan_rewriter`](crate::execution::context::SessionState::add_execution_plan_rewriter),
+/// and visit the nodes of the optimized plans from the root down. The
+/// operators of a backend can pass the
+/// [`DeviceValue`](crate::logical_expr::DeviceValue)s of their device
+/// between them, and return record batches in host memory to the others.
+pub trait ExecutionPlanRewriter: Send + Sync {
+    /// A human readable name for this rewriter
+    fn name(&self) -> &str;
+
+    /// Returns the replacement of the subtree rooted at `plan`, or `None` to
+    /// keep `plan` and rewrite its children
+    ///
+    /// The replacement must have the same schema as `plan`. Its children
+    /// are not rewritten.
+    fn rewrite(
+        &self,
+        plan: &Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>>;
+}
+
+/// Optimizer rule that substitutes the replacements of an
+/// [`ExecutionPlanRewriter`] for the largest subtrees it rewrites
+pub struct RewriteExecutionPlan {
+    rewriter: Arc<dyn ExecutionPlanRewriter>,
+}
+
+impl RewriteExecutionPlan {
+    /// Create a rule rewriting plans with `rewriter`
+    pub fn new(rewriter: Arc<dyn ExecutionPlanRewriter>) -> Self {
+        Self { rewriter }
+    }
+}
+
+impl PhysicalOptimizerRule for RewriteExecutionPlan {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match self.rewriter.rewrite(&plan, config)? {
+            Some(replacement) => {
+                if replacement.schema() != plan.schema() {
+                    return Err(DataFusionError::Internal(format!(
+                        "ExecutionPlanRewriter {} replaced a plan of schema {:?} by a plan of schema {:?}",
+                        self.rewriter.name(),
+                        plan.schema(),
+                        replacement.schema()
+                    )));
+                }
+                Ok(replacement)
+            }
+            None => optimize_children(self, plan, config),
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.rewriter.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::displayable;
+    use crate::physical_plan::empty::EmptyExec;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::projection::ProjectionExec;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Replaces filters by empty plans, counting the plans it visits
+    #[derive(Default)]
+    struct RemoveFilters {
+        visited: AtomicUsize,
+    }
+
+    impl ExecutionPlanRewriter for RemoveFilters {
+        fn name(&self) -> &str {
+            "remove_filters"
+        }
+
+        fn rewrite(
+            &self,
+            plan: &Arc<dyn ExecutionPlan>,
+            _config: &SessionConfig,
+        ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+            self.visited.fetch_add(1, Ordering::SeqCst);
+            Ok(plan.as_any().downcast_ref::<FilterExec>().map(
+                |filter| -> Arc<dyn ExecutionPlan> {
+                    Arc::new(EmptyExec::new(false, filter.schema()))
+                },
+            ))
+        }
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![Field::new("a", DataType::Boolean, false)]))
+    }
+
+    #[test]
+    fn substitute_subtree() -> Result<()> {
+        let input: Arc<dyn ExecutionPlan> = Arc::new(EmptyExec::new(true, schema()));
+        let filter = Arc::new(FilterExec::try_new(col("a", &schema())?, input)?);
+        let plan = Arc::new(ProjectionExec::try_new(
+            vec![(col("a", &schema())?, "a".to_string())],
+            filter,
+        )?);
+
+        let rewriter = Arc::new(RemoveFilters::default());
+        let rule = RewriteExecutionPlan::new(rewriter.clone());
+        let optimized = rule.optimize(plan, &SessionConfig::new())?;
+        let actual = format!("{}", displayable(optimized.as_ref()).indent());
+        let expected = [
+            "ProjectionExec: expr=[a@0 as a]",
+            "  EmptyExec: produce_one_row=false",
+        ];
+        assert_eq!(actual.trim().lines().collect::<Vec<_>>(), expected);
+        // the input of the filter is not visited
+        assert_eq!(rewriter.visited.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_other_schema() -> Result<()> {
+        struct ChangeSchema {}
+
+        impl ExecutionPlanRewriter for ChangeSchema {
+            fn name(&self) -> &str {
+                "change_schema"
+            }
+
+            fn rewrite(
+                &self,
+                _plan: &Arc<dyn ExecutionPlan>,
+                _config: &SessionConfig,
+            ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+                Ok(Some(Arc::new(EmptyExec::new(
+                    true,
+                    Arc::new(Schema::empty()),
+                ))))
+            }
+        }
+
+        let plan = Arc::new(EmptyExec::new(true, schema()));
+        let rule = RewriteExecutionPlan::new(Arc::new(ChangeSchema {}));
+        let err = rule.optimize(plan, &SessionConfig::new()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("ExecutionPlanRewriter change_schema"));
+        Ok(())
+    }
+}
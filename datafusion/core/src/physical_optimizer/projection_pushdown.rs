@@ -0,0 +1,397 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! ProjectionPushdown optimizer rule that removes the [`ProjectionExec`]s
+//! which only copy the batches of their input
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::SessionConfig;
+use crate::physical_expr::rewrite::TreeNodeRewritable;
+use crate::physical_plan::expressions::{Column, Literal};
+use crate::physical_plan::file_format::{CsvExec, ParquetExec};
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::{ExecutionPlan, PhysicalExpr};
+
+/// Optimizer rule that
+///
+/// * merges a [`ProjectionExec`] into the [`ProjectionExec`] below it, unless
+///   this would evaluate an expression of the latter more than once
+/// * removes a [`ProjectionExec`] that outputs the columns of its input
+///   unchanged
+/// * pushes a [`ProjectionExec`] that only selects or reorders columns into
+///   the scan below it, for the file formats that can read the columns in any
+///   order ([`ParquetExec`] and [`CsvExec`])
+#[derive(Default)]
+pub struct ProjectionPushdown {}
+
+impl ProjectionPushdown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for ProjectionPushdown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &SessionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.physical_projection_pushdown() {
+            return Ok(plan);
+        }
+        let plan = optimize_children(self, plan, config)?;
+        match plan.as_any().downcast_ref::<ProjectionExec>() {
+            Some(projection) => remove_projection(projection).map(|p| p.unwrap_or(plan)),
+            None => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "projection_pushdown"
+    }
+}
+
+/// Returns the plan computing `projection` with fewer [`ProjectionExec`]s,
+/// or `None` if there is none
+fn remove_projection(
+    projection: &ProjectionExec,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    let merged = merge_projections(projection)?;
+    let projection = merged.as_ref().unwrap_or(projection);
+    let input = projection.input();
+
+    if let Some(columns) = selected_columns(projection) {
+        let input_schema = input.schema();
+        if columns.len() == input_schema.fields().len()
+            && columns.iter().enumerate().all(|(i, c)| i == *c)
+        {
+            return Ok(Some(input.clone()));
+        }
+
+        let unique = {
+            let mut sorted = columns.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            sorted.len() == columns.len()
+        };
+        if unique {
+            if let Some(scan) = input.as_any().downcast_ref::<ParquetExec>() {
+                let projection =
+                    scan_projection(scan.base_config().projection.as_ref(), &columns);
+                return Ok(Some(Arc::new(scan.clone().with_projection(projection))));
+            }
+            if let Some(scan) = input.as_any().downcast_ref::<CsvExec>() {
+                let projection =
+                    scan_projection(scan.base_config().projection.as_ref(), &columns);
+                return Ok(Some(Arc::new(scan.clone().with_projection(projection))));
+            }
+        }
+    }
+
+    Ok(merged.map(|p| Arc::new(p) as Arc<dyn ExecutionPlan>))
+}
+
+/// Returns `projection` merged with the [`ProjectionExec`] below it, or
+/// `None` if there is no such projection or an expression of it that is not
+/// a column or literal is used more than once
+fn merge_projections(projection: &ProjectionExec) -> Result<Option<ProjectionExec>> {
+    let child = match projection.input().as_any().downcast_ref::<ProjectionExec>() {
+        Some(child) => child,
+        None => return Ok(None),
+    };
+
+    let mut uses = HashMap::new();
+    for (expr, _) in projection.expr() {
+        collect_column_uses(expr, &mut uses);
+    }
+    let duplicates_work = uses.iter().any(|(index, count)| {
+        let expr = &child.expr()[*index].0;
+        *count > 1
+            && expr.as_any().downcast_ref::<Column>().is_none()
+            && expr.as_any().downcast_ref::<Literal>().is_none()
+    });
+    if duplicates_work {
+        return Ok(None);
+    }
+
+    let expr = projection
+        .expr()
+        .iter()
+        .map(|(expr, name)| {
+            let expr = expr.clone().transform(&|e| {
+                e.as_any()
+                    .downcast_ref::<Column>()
+                    .map(|c| child.expr()[c.index()].0.clone())
+            })?;
+            Ok((expr, name.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(ProjectionExec::try_new(expr, child.input().clone())?))
+}
+
+/// Counts the references to each column index of the input in `expr`
+fn collect_column_uses(expr: &Arc<dyn PhysicalExpr>, uses: &mut HashMap<usize, usize>) {
+    if let Some(column) = expr.as_any().downcast_ref::<Column>() {
+        *uses.entry(column.index()).or_default() += 1;
+    } else {
+        for child in expr.children() {
+            collect_column_uses(&child, uses);
+        }
+    }
+}
+
+/// Returns the input column indices output by `projection` if it only
+/// outputs input columns under their own names
+fn selected_columns(projection: &ProjectionExec) -> Option<Vec<usize>> {
+    let input_schema = projection.input().schema();
+    projection
+        .expr()
+        .iter()
+        .map(|(expr, name)| {
+            let column = expr.as_any().downcast_ref::<Column>()?;
+            if input_schema.field(column.index()).name() == name {
+                Some(column.index())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the table column indices a scan with `projection` reads to
+/// output its `columns`
+fn scan_projection(projection: Option<&Vec<usize>>, columns: &[usize]) -> Vec<usize> {
+    match projection {
+        Some(projection) => columns.iter().map(|c| projection[*c]).collect(),
+        None => columns.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::config::{ConfigOptions, OPT_PHYSICAL_PROJECTION_PUSHDOWN};
+    use crate::datasource::listing::PartitionedFile;
+    use crate::datasource::object_store::ObjectStoreUrl;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::file_format::FileScanConfig;
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::{collect, displayable, Statistics};
+    use crate::prelude::{CsvReadOptions, SessionContext};
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use datafusion_expr::Operator;
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+            Field::new("c", DataType::Int32, false),
+        ]))
+    }
+
+    fn parquet_exec(projection: Option<Vec<usize>>) -> Arc<dyn ExecutionPlan> {
+        Arc::new(ParquetExec::new(
+            FileScanConfig {
+                object_store_url: ObjectStoreUrl::parse("test:///").unwrap(),
+                file_schema: schema(),
+                file_groups: vec![vec![PartitionedFile::new("x".to_string(), 100)]],
+                statistics: Statistics::default(),
+                projection,
+                limit: None,
+                table_partition_cols: vec![],
+                config_options: ConfigOptions::new().into_shareable(),
+            },
+            None,
+            None,
+        ))
+    }
+
+    fn projection_exec(
+        input: Arc<dyn ExecutionPlan>,
+        expr: Vec<(Arc<dyn PhysicalExpr>, &str)>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let expr = expr
+            .into_iter()
+            .map(|(expr, name)| (expr, name.to_string()))
+            .collect();
+        Ok(Arc::new(ProjectionExec::try_new(expr, input)?))
+    }
+
+    fn assert_optimized(plan: Arc<dyn ExecutionPlan>, expected: &[&str]) -> Result<()> {
+        let schema = plan.schema();
+        let optimized =
+            ProjectionPushdown::new().optimize(plan, &SessionConfig::new())?;
+        let actual = format!("{}", displayable(optimized.as_ref()).indent());
+        assert_eq!(actual.trim().lines().collect::<Vec<_>>(), expected);
+        assert_eq!(schema, optimized.schema());
+        Ok(())
+    }
+
+    #[test]
+    fn push_down_into_scan() -> Result<()> {
+        let scan = parquet_exec(Some(vec![0, 2]));
+        let plan = projection_exec(
+            scan.clone(),
+            vec![
+                (col("c", &scan.schema())?, "c"),
+                (col("a", &scan.schema())?, "a"),
+            ],
+        )?;
+        assert_optimized(
+            plan,
+            &["ParquetExec: limit=None, partitions=[x], projection=[c, a]"],
+        )?;
+
+        // renaming a column needs a projection
+        let plan = projection_exec(scan.clone(), vec![(col("c", &scan.schema())?, "x")])?;
+        assert_optimized(
+            plan,
+            &[
+                "ProjectionExec: expr=[c@1 as x]",
+                "  ParquetExec: limit=None, partitions=[x], projection=[a, c]",
+            ],
+        )
+    }
+
+    #[test]
+    fn disabled() -> Result<()> {
+        let scan = parquet_exec(Some(vec![0, 2]));
+        let plan = projection_exec(scan.clone(), vec![(col("c", &scan.schema())?, "c")])?;
+        let config =
+            SessionConfig::new().set_bool(OPT_PHYSICAL_PROJECTION_PUSHDOWN, false);
+        let optimized = ProjectionPushdown::new().optimize(plan.clone(), &config)?;
+        assert!(Arc::ptr_eq(&plan, &optimized));
+        Ok(())
+    }
+
+    #[test]
+    fn remove_unchanged_columns() -> Result<()> {
+        let scan = parquet_exec(None);
+        let schema = scan.schema();
+        let predicate = binary(col("a", &schema)?, Operator::Gt, lit(1i32), &schema)?;
+        let filter: Arc<dyn ExecutionPlan> =
+            Arc::new(FilterExec::try_new(predicate, scan)?);
+        let plan = projection_exec(
+            filter,
+            vec![
+                (col("a", &schema)?, "a"),
+                (col("b", &schema)?, "b"),
+                (col("c", &schema)?, "c"),
+            ],
+        )?;
+        assert_optimized(
+            plan,
+            &[
+                "FilterExec: a@0 > 1",
+                "  ParquetExec: limit=None, partitions=[x], projection=[a, b, c]",
+            ],
+        )
+    }
+
+    #[test]
+    fn merge_adjacent_projections() -> Result<()> {
+        let scan = parquet_exec(None);
+        let schema = scan.schema();
+        let sum = binary(
+            col("a", &schema)?,
+            Operator::Plus,
+            col("b", &schema)?,
+            &schema,
+        )?;
+        let inner = projection_exec(
+            scan,
+            vec![(sum, "sum"), (lit(1i32), "one"), (col("c", &schema)?, "c")],
+        )?;
+        let schema = inner.schema();
+        let plan = projection_exec(
+            inner.clone(),
+            vec![
+                (col("sum", &schema)?, "total"),
+                (
+                    binary(
+                        col("one", &schema)?,
+                        Operator::Plus,
+                        col("one", &schema)?,
+                        &schema,
+                    )?,
+                    "two",
+                ),
+            ],
+        )?;
+        assert_optimized(
+            plan,
+            &[
+                "ProjectionExec: expr=[a@0 + b@1 as total, 1 + 1 as two]",
+                "  ParquetExec: limit=None, partitions=[x], projection=[a, b, c]",
+            ],
+        )?;
+
+        // the sum is only computed once
+        let plan = projection_exec(
+            inner,
+            vec![(
+                binary(
+                    col("sum", &schema)?,
+                    Operator::Plus,
+                    col("sum", &schema)?,
+                    &schema,
+                )?,
+                "double",
+            )],
+        )?;
+        assert_optimized(
+            plan,
+            &[
+                "ProjectionExec: expr=[sum@0 + sum@0 as double]",
+                "  ProjectionExec: expr=[a@0 + b@1 as sum, 1 as one, c@2 as c]",
+                "    ParquetExec: limit=None, partitions=[x], projection=[a, b, c]",
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn read_reordered_columns() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("example", "tests/example.csv", CsvReadOptions::new())
+            .await?;
+        let plan = ctx
+            .sql("SELECT c, a FROM example")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let displayed = format!("{}", displayable(plan.as_ref()).indent());
+        assert!(!displayed.contains("ProjectionExec"), "{}", displayed);
+
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let expected = vec![
+            "+---+---+",
+            "| c | a |",
+            "+---+---+",
+            "| 3 | 1 |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+}
@@ -46,7 +46,7 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use datafusion_common::{downcast_value, ScalarValue};
-use datafusion_expr::expr::{BinaryExpr, Cast};
+use datafusion_expr::expr::{BinaryExpr, Cast, GetIndexedField};
 use datafusion_expr::expr_rewriter::{ExprRewritable, ExprRewriter};
 use datafusion_expr::utils::expr_to_columns;
 use datafusion_expr::{binary_expr, cast, try_cast, ExprSchemable};
@@ -126,11 +126,18 @@ impl PruningPredicate {
     /// For example, the filter expression `(column / 2) = 4` becomes
     /// the pruning predicate
     /// `(column_min / 2) <= 4 && 4 <= (column_max / 2))`
+    ///
+    /// Fields of struct columns, such as `meta['country']`, are
+    /// referred to by their path (`meta.country`) when the statistics
+    /// are requested from [`PruningStatistics`]
     pub fn try_new(expr: Expr, schema: SchemaRef) -> Result<Self> {
         // build predicate expression once
         let mut required_columns = RequiredStatColumns::new();
-        let logical_predicate_expr =
-            build_predicate_expression(&expr, schema.as_ref(), &mut required_columns)?;
+        let logical_predicate_expr = build_predicate_expression(
+            &rewrite_struct_field_access(expr.clone())?,
+            &schema_with_struct_fields(schema.as_ref()),
+            &mut required_columns,
+        )?;
         let stat_fields = required_columns
             .iter()
             .map(|(_, _, f)| f.clone())
@@ -641,6 +648,83 @@ fn rewrite_column_expr(
     })
 }
 
+/// Rewrites accesses to struct fields with constant names, such as
+/// `meta['geo']['lat']`, into references to a column named by the path
+/// of the field (`meta.geo.lat`), as that is the granularity at which
+/// statistics are kept for nested data
+fn rewrite_struct_field_access(e: Expr) -> Result<Expr> {
+    struct FieldAccessRewriter {}
+
+    impl ExprRewriter for FieldAccessRewriter {
+        fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+            match expr {
+                Expr::GetIndexedField(GetIndexedField {
+                    expr: inner,
+                    key: ScalarValue::Utf8(Some(name)),
+                }) => match *inner {
+                    Expr::Column(c) => Ok(Expr::Column(Column {
+                        relation: c.relation,
+                        name: format!("{}.{}", c.name, name),
+                    })),
+                    inner => Ok(Expr::GetIndexedField(GetIndexedField::new(
+                        Box::new(inner),
+                        ScalarValue::Utf8(Some(name)),
+                    ))),
+                },
+                _ => Ok(expr),
+            }
+        }
+    }
+
+    e.rewrite(&mut FieldAccessRewriter {})
+}
+
+/// Returns `schema` with an additional top level field for each field
+/// nested in its struct columns, named by the path of that field (see
+/// [`rewrite_struct_field_access`])
+fn schema_with_struct_fields(schema: &Schema) -> Schema {
+    fn add_nested(prefix: &str, field: &Field, fields: &mut Vec<Field>) {
+        if let DataType::Struct(children) = field.data_type() {
+            for child in children {
+                let name = format!("{}.{}", prefix, child.name());
+                add_nested(&name, child, fields);
+                fields.push(Field::new(
+                    &name,
+                    child.data_type().clone(),
+                    child.is_nullable(),
+                ));
+            }
+        }
+    }
+
+    let mut fields = schema.fields().clone();
+    for field in schema.fields() {
+        add_nested(field.name(), field, &mut fields);
+    }
+    // a top level column with the same name as a path takes precedence
+    let mut names = HashSet::new();
+    fields.retain(|f| names.insert(f.name().clone()));
+    Schema::new(fields)
+}
+
+/// Returns the field of `schema` named `name` or, if there is none, the
+/// field nested in a struct column whose path is `name` (e.g.
+/// `meta.country`)
+pub(crate) fn field_with_path<'a>(schema: &'a Schema, name: &str) -> Option<&'a Field> {
+    if let Ok(field) = schema.field_with_name(name) {
+        return Some(field);
+    }
+    let mut parts = name.split('.');
+    let mut field = schema.field_with_name(parts.next()?).ok()?;
+    for part in parts {
+        field = match field.data_type() {
+            DataType::Struct(children) => children.iter().find(|f| f.name() == part)?,
+            _ => return None,
+        };
+    }
+    Some(field)
+}
+
 fn reverse_operator(op: Operator) -> Operator {
     match op {
         Operator::Lt => Operator::Gt,
@@ -1808,6 +1892,58 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn prune_struct_field() {
+        let geo = Field::new(
+            "geo",
+            DataType::Struct(vec![Field::new("lat", DataType::Float64, true)]),
+            true,
+        );
+        let meta = Field::new(
+            "meta",
+            DataType::Struct(vec![Field::new("country", DataType::Utf8, true), geo]),
+            true,
+        );
+        let schema = Arc::new(Schema::new(vec![meta]));
+        let field = |expr: Expr, name: &str| {
+            Expr::GetIndexedField(GetIndexedField::new(
+                Box::new(expr),
+                ScalarValue::from(name),
+            ))
+        };
+
+        let statistics = TestStatistics::new()
+            .with(
+                "meta.country",
+                ContainerStats::new_utf8(
+                    vec![Some("FR"), Some("JP"), None], // min
+                    vec![Some("FR"), Some("US"), None], // max
+                ),
+            )
+            .with(
+                "meta.geo.lat",
+                ContainerStats::new_i64(
+                    vec![Some(40), Some(-30), None], // min
+                    vec![Some(50), Some(10), None],  // max
+                ),
+            );
+
+        // meta['country'] = 'JP' ==> only the second container may match
+        let expr = field(col("meta"), "country").eq(lit("JP"));
+        let p = PruningPredicate::try_new(expr, schema.clone()).unwrap();
+        assert_eq!(p.prune(&statistics).unwrap(), vec![false, true, true]);
+
+        // meta['geo']['lat'] > 45.0 ==> only the first container may match
+        let expr = field(field(col("meta"), "geo"), "lat").gt(lit(45.0));
+        let p = PruningPredicate::try_new(expr, schema.clone()).unwrap();
+        assert_eq!(p.prune(&statistics).unwrap(), vec![true, false, true]);
+
+        // the struct as a whole has no statistics
+        let expr = col("meta").is_null();
+        let p = PruningPredicate::try_new(expr, schema).unwrap();
+        assert_eq!(p.prune(&statistics).unwrap(), vec![true, true, true]);
+    }
+
     /// Creates setup for boolean chunk pruning
     ///
     /// For predicate "b1" (boolean expr)
@@ -30,6 +30,7 @@ use futures::{
 use crate::error::Result;
 use crate::physical_plan::aggregates::{
     evaluate_group_by, evaluate_many, AccumulatorItem, AggregateMode, PhysicalGroupBy,
+    SkipAggregationProbe,
 };
 use crate::physical_plan::hash_utils::create_hashes;
 use crate::physical_plan::metrics::{BaselineMetrics, RecordOutput};
@@ -85,6 +86,8 @@ pub(crate) struct GroupedHashAggregateStream {
 
     baseline_metrics: BaselineMetrics,
     random_state: RandomState,
+    /// decides when a partial aggregation outputs its groups after each batch
+    skip_probe: Option<SkipAggregationProbe>,
     finished: bool,
 }
 
@@ -97,6 +100,7 @@ impl GroupedHashAggregateStream {
         aggr_expr: Vec<Arc<dyn AggregateExpr>>,
         input: SendableRecordBatchStream,
         baseline_metrics: BaselineMetrics,
+        skip_probe: Option<SkipAggregationProbe>,
     ) -> Result<Self> {
         let timer = baseline_metrics.elapsed_compute().timer();
 
@@ -118,6 +122,7 @@ impl GroupedHashAggregateStream {
             aggregate_expressions,
             accumulators: Default::default(),
             random_state: Default::default(),
+            skip_probe,
             finished: false,
         })
     }
@@ -135,12 +140,43 @@ impl Stream for GroupedHashAggregateStream {
             return Poll::Ready(None);
         }
 
+        this.baseline_metrics.record_poll_start();
         let elapsed_compute = this.baseline_metrics.elapsed_compute();
 
         loop {
-            let result = match ready!(this.input.poll_next_unpin(cx)) {
+            let poll = this.input.poll_next_unpin(cx);
+            let result = match ready!(this.baseline_metrics.record_input_poll(poll)) {
                 Some(Ok(batch)) => {
                     let timer = elapsed_compute.timer();
+                    let converted = match &mut this.skip_probe {
+                        Some(probe) => probe.convert_to_state(
+                            &this.group_by,
+                            &this.aggr_expr,
+                            &this.aggregate_expressions,
+                            &batch,
+                            &this.schema,
+                        ),
+                        None => Ok(None),
+                    };
+                    match converted {
+                        Ok(Some(states)) => {
+                            // the partial aggregation is skipped
+                            timer.done();
+                            return Poll::Ready(Some(Ok(
+                                states.record_output(&this.baseline_metrics)
+                            )));
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            timer.done();
+                            this.finished = true;
+                            return Poll::Ready(Some(Err(ArrowError::ExternalError(
+                                Box::new(e),
+                            ))));
+                        }
+                    }
+
+                    let num_rows = batch.num_rows();
                     let result = group_aggregate_batch(
                         &this.mode,
                         &this.random_state,
@@ -151,11 +187,34 @@ impl Stream for GroupedHashAggregateStream {
                         &this.aggregate_expressions,
                     );
 
-                    timer.done();
+                    let num_groups = this.accumulators.group_states.len();
+                    let skip = match &mut this.skip_probe {
+                        Some(probe) => probe.update(num_rows, num_groups),
+                        None => false,
+                    };
 
                     match result {
-                        Ok(_) => continue,
-                        Err(e) => Err(ArrowError::ExternalError(Box::new(e))),
+                        Ok(_) if skip && num_groups > 0 => {
+                            // output the groups and start over
+                            let result = create_batch_from_map(
+                                &this.mode,
+                                &this.accumulators,
+                                this.group_by.expr.len(),
+                                &this.schema,
+                            )
+                            .record_output(&this.baseline_metrics);
+                            this.accumulators = Default::default();
+                            timer.done();
+                            return Poll::Ready(Some(result));
+                        }
+                        Ok(_) => {
+                            timer.done();
+                            continue;
+                        }
+                        Err(e) => {
+                            timer.done();
+                            Err(ArrowError::ExternalError(Box::new(e)))
+                        }
                     }
                 }
                 Some(Err(e)) => Err(e),
@@ -326,7 +385,9 @@ fn group_aggregate_batch(
                         )
                     })
                     .try_for_each(|(accumulator, values)| match mode {
-                        AggregateMode::Partial => accumulator.update_batch(&values),
+                        AggregateMode::Partial | AggregateMode::Single => {
+                            accumulator.update_batch(&values)
+                        }
                         AggregateMode::FinalPartitioned | AggregateMode::Final => {
                             // note: the aggregation here is over states, not values, thus the merge
                             accumulator.merge_batch(&values)
@@ -405,7 +466,9 @@ fn create_batch_from_map(
                 acc_data_types.push(state.len());
             }
         }
-        AggregateMode::Final | AggregateMode::FinalPartitioned => {
+        AggregateMode::Final
+        | AggregateMode::FinalPartitioned
+        | AggregateMode::Single => {
             acc_data_types = vec![1; accs.len()];
         }
     }
@@ -437,7 +500,9 @@ fn create_batch_from_map(
 
                     columns.push(res);
                 }
-                AggregateMode::Final | AggregateMode::FinalPartitioned => {
+                AggregateMode::Final
+                | AggregateMode::FinalPartitioned
+                | AggregateMode::Single => {
                     let res = ScalarValue::iter_to_array(
                         accumulators.group_states.iter().map(|group_state| {
                             group_state.accumulator_set[x].evaluate().unwrap()
@@ -17,26 +17,35 @@
 
 //! Aggregates functionalities
 
-use crate::execution::context::TaskContext;
+use crate::config::{
+    OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD,
+    OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS,
+};
+use crate::execution::context::{SessionConfig, TaskContext};
 use crate::physical_plan::aggregates::hash::GroupedHashAggregateStream;
 use crate::physical_plan::aggregates::no_grouping::AggregateStream;
+use crate::physical_plan::coalesce_batches::concat_batches;
 use crate::physical_plan::metrics::{
-    BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet,
+    self, BaselineMetrics, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
 };
 use crate::physical_plan::{
-    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    DisplayFormatType, Distribution, EquivalenceProperties, ExecutionPlan, Partitioning,
     SendableRecordBatchStream, Statistics,
 };
 use arrow::array::ArrayRef;
 use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 use datafusion_common::Result;
 use datafusion_expr::Accumulator;
+use datafusion_physical_expr::equivalence::project_equivalence_properties;
 use datafusion_physical_expr::expressions::Column;
+use datafusion_physical_expr::utils::normalize_expr_with_equivalence_properties;
 use datafusion_physical_expr::{
     expressions, AggregateExpr, PhysicalExpr, PhysicalSortExpr,
 };
 use std::any::Any;
+use std::collections::HashMap;
 
 use std::sync::Arc;
 
@@ -64,6 +73,13 @@ pub enum AggregateMode {
     /// with Hash repartitioning on the group keys. If a group key is
     /// duplicated, duplicate groups would be produced
     FinalPartitioned,
+    /// Applies the entire aggregation in a single operator, in place of a
+    /// partial aggregate followed by a final aggregate, so that the rows are
+    /// hashed once and no intermediate state is built.
+    ///
+    /// This requires that the input is a single partition, like the
+    /// `Final` mode
+    Single,
 }
 
 /// Represents `GROUP BY` clause in the plan (including the more general GROUPING SET)
@@ -255,20 +271,71 @@ impl ExecutionPlan for AggregateExec {
 
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
-        self.input.output_partitioning()
+        match self.input.output_partitioning() {
+            // a hash partitioning on group expressions holds on their output
+            // columns, unless grouping sets set some of them to null
+            Partitioning::Hash(exprs, n) => {
+                let input_eq = self.input.equivalence_properties();
+                let output_exprs = self.output_group_expr();
+                exprs
+                    .iter()
+                    .map(|expr| {
+                        let expr = normalize_expr_with_equivalence_properties(
+                            expr.clone(),
+                            input_eq.classes(),
+                        );
+                        self.group_by.expr.iter().zip(&output_exprs).find_map(
+                            |((group_expr, _), output_expr)| {
+                                normalize_expr_with_equivalence_properties(
+                                    group_expr.clone(),
+                                    input_eq.classes(),
+                                )
+                                .eq(&expr)
+                                .then(|| output_expr.clone())
+                            },
+                        )
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|_| !self.group_by.contains_null())
+                    .map(|exprs| Partitioning::Hash(exprs, n))
+                    .unwrap_or(Partitioning::UnknownPartitioning(n))
+            }
+            partitioning => partitioning,
+        }
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
         None
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        let mut new_properties = EquivalenceProperties::new(self.schema());
+        if !self.group_by.contains_null() {
+            let mut alias_map: HashMap<Column, Vec<Column>> = HashMap::new();
+            for (index, (expr, name)) in self.group_by.expr.iter().enumerate() {
+                if let Some(column) = expr.as_any().downcast_ref::<Column>() {
+                    alias_map
+                        .entry(column.clone())
+                        .or_default()
+                        .push(Column::new(name, index));
+                }
+            }
+            project_equivalence_properties(
+                self.input.equivalence_properties(),
+                &alias_map,
+                &mut new_properties,
+            );
+        }
+        new_properties
+    }
+
     fn required_child_distribution(&self) -> Distribution {
         match &self.mode {
             AggregateMode::Partial => Distribution::UnspecifiedDistribution,
             AggregateMode::FinalPartitioned => Distribution::HashPartitioned(
                 self.group_by.expr.iter().map(|x| x.0.clone()).collect(),
             ),
-            AggregateMode::Final => Distribution::SinglePartition,
+            AggregateMode::Final | AggregateMode::Single => Distribution::SinglePartition,
         }
     }
 
@@ -298,7 +365,18 @@ impl ExecutionPlan for AggregateExec {
         partition: usize,
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
-        let batch_size = context.session_config().batch_size();
+        let session_config = context.session_config();
+        let batch_size = session_config.batch_size();
+        let skip_probe = (self.mode == AggregateMode::Partial
+            && !self.group_by.expr.is_empty())
+        .then(|| {
+            SkipAggregationProbe::new(
+                &session_config,
+                &self.aggr_expr,
+                &self.metrics,
+                partition,
+            )
+        });
         let input = self.input.execute(partition, context)?;
 
         let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
@@ -309,6 +387,7 @@ impl ExecutionPlan for AggregateExec {
                 self.schema.clone(),
                 self.aggr_expr.clone(),
                 input,
+                self.input.output_ordering(),
                 baseline_metrics,
             )?))
         } else if self.row_aggregate_supported() {
@@ -320,6 +399,7 @@ impl ExecutionPlan for AggregateExec {
                 input,
                 baseline_metrics,
                 batch_size,
+                skip_probe,
             )?))
         } else {
             Ok(Box::pin(GroupedHashAggregateStream::new(
@@ -329,6 +409,7 @@ impl ExecutionPlan for AggregateExec {
                 self.aggr_expr.clone(),
                 input,
                 baseline_metrics,
+                skip_probe,
             )?))
         }
     }
@@ -412,7 +493,9 @@ impl ExecutionPlan for AggregateExec {
         // TODO stats: aggr expression:
         // - aggregations somtimes also preserve invariants such as min, max...
         match self.mode {
-            AggregateMode::Final | AggregateMode::FinalPartitioned
+            AggregateMode::Final
+            | AggregateMode::FinalPartitioned
+            | AggregateMode::Single
                 if self.group_by.expr.is_empty() =>
             {
                 Statistics {
@@ -452,7 +535,9 @@ fn create_schema(
                 fields.extend(expr.state_fields()?.iter().cloned())
             }
         }
-        AggregateMode::Final | AggregateMode::FinalPartitioned => {
+        AggregateMode::Final
+        | AggregateMode::FinalPartitioned
+        | AggregateMode::Single => {
             // in final mode, the field with the final result of the accumulator
             for expr in aggr_expr {
                 fields.push(expr.field()?)
@@ -470,7 +555,7 @@ fn group_schema(schema: &Schema, group_count: usize) -> SchemaRef {
 
 /// returns physical expressions to evaluate against a batch
 /// The expressions are different depending on `mode`:
-/// * Partial: AggregateExpr::expressions
+/// * Partial and Single: AggregateExpr::expressions
 /// * Final: columns of `AggregateExpr::state_fields()`
 fn aggregate_expressions(
     aggr_expr: &[Arc<dyn AggregateExpr>],
@@ -478,7 +563,7 @@ fn aggregate_expressions(
     col_idx_base: usize,
 ) -> datafusion_common::Result<Vec<Vec<Arc<dyn PhysicalExpr>>>> {
     match mode {
-        AggregateMode::Partial => {
+        AggregateMode::Partial | AggregateMode::Single => {
             Ok(aggr_expr.iter().map(|agg| agg.expressions()).collect())
         }
         // in this mode, we build the merge expressions of the aggregation
@@ -514,6 +599,118 @@ fn merge_expressions(
         .collect::<Vec<_>>())
 }
 
+/// Decides when a partial aggregation stops aggregating the rows of its input
+/// across batches, because its number of groups is close to its number of
+/// input rows, so that it barely reduces the rows sent to the final
+/// aggregation while it holds all of them in memory.
+///
+/// Once skipped, the partial aggregation outputs the groups aggregated so far,
+/// then converts each following input row straight into the partial state of
+/// a group of its own, without hashing it, when all its aggregates support it
+/// (see [`AggregateExpr::convert_to_state`]). Otherwise, it outputs its groups
+/// after each input batch and starts over. Both are valid partial aggregations.
+pub(crate) struct SkipAggregationProbe {
+    /// Number of input rows aggregated before the ratio is checked
+    probe_rows: usize,
+    /// Ratio of the number of groups to the number of input rows above which
+    /// the partial aggregation is skipped
+    ratio_threshold: f64,
+    /// Number of input rows aggregated until the ratio is checked
+    input_rows: usize,
+    /// Whether the ratio was checked
+    is_locked: bool,
+    /// Whether the partial aggregation is skipped
+    should_skip: bool,
+    /// Whether all the aggregates can convert the input rows into partial
+    /// states, once the partial aggregation is skipped
+    convert_to_state: bool,
+    /// Number of input rows aggregated one batch at a time, or converted
+    /// into partial states
+    skipped_aggregation_rows: metrics::Count,
+}
+
+impl SkipAggregationProbe {
+    fn new(
+        config: &SessionConfig,
+        aggr_expr: &[Arc<dyn AggregateExpr>],
+        metrics: &ExecutionPlanMetricsSet,
+        partition: usize,
+    ) -> Self {
+        let config_options = config.config_options();
+        let config_options = config_options.read();
+        Self {
+            probe_rows: config_options
+                .get_u64(OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS)
+                .unwrap_or_default() as usize,
+            ratio_threshold: config_options
+                .get_f64(OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD)
+                .unwrap_or(1.0),
+            input_rows: 0,
+            is_locked: false,
+            should_skip: false,
+            convert_to_state: aggr_expr
+                .iter()
+                .all(|expr| expr.convert_to_state_supported()),
+            skipped_aggregation_rows: MetricBuilder::new(metrics)
+                .counter("skipped_aggregation_rows", partition),
+        }
+    }
+
+    /// Updates the probe after a batch of `num_rows` input rows is aggregated,
+    /// with the number of groups aggregated so far. Returns true if the
+    /// groups should be output, as the partial aggregation is skipped.
+    pub(crate) fn update(&mut self, num_rows: usize, num_groups: usize) -> bool {
+        if !self.is_locked {
+            self.input_rows += num_rows;
+            if self.input_rows >= self.probe_rows {
+                self.should_skip =
+                    num_groups as f64 > self.input_rows as f64 * self.ratio_threshold;
+                self.is_locked = true;
+            }
+        } else if self.should_skip {
+            self.skipped_aggregation_rows.add(num_rows);
+        }
+        self.should_skip
+    }
+
+    /// Converts the rows of `batch` straight into the partial states of
+    /// groups holding a single row each, laid out as `schema`, once the
+    /// partial aggregation is skipped. Returns `None` while the rows must
+    /// still be aggregated.
+    pub(crate) fn convert_to_state(
+        &mut self,
+        group_by: &PhysicalGroupBy,
+        aggr_expr: &[Arc<dyn AggregateExpr>],
+        aggregate_expressions: &[Vec<Arc<dyn PhysicalExpr>>],
+        batch: &RecordBatch,
+        schema: &SchemaRef,
+    ) -> Result<Option<RecordBatch>> {
+        if !(self.is_locked && self.should_skip && self.convert_to_state) {
+            return Ok(None);
+        }
+        let aggr_input_values = evaluate_many(aggregate_expressions, batch)?;
+        let states = aggr_expr
+            .iter()
+            .zip(aggr_input_values.iter())
+            .map(|(expr, values)| expr.convert_to_state(values))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        // each grouping set outputs all the rows
+        let batches = evaluate_group_by(group_by, batch)?
+            .into_iter()
+            .map(|mut columns| {
+                columns.extend(states.iter().cloned());
+                RecordBatch::try_new(schema.clone(), columns)
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+        let num_rows = batch.num_rows() * batches.len();
+        self.skipped_aggregation_rows.add(batch.num_rows());
+        Ok(Some(concat_batches(schema, &batches, num_rows)?))
+    }
+}
+
 pub(crate) type AccumulatorItem = Box<dyn Accumulator>;
 pub(crate) type AccumulatorItemV2 = Box<dyn RowAccumulator>;
 
@@ -547,7 +744,7 @@ fn create_accumulators_v2(
 }
 
 /// returns a vector of ArrayRefs, where each entry corresponds to either the
-/// final value (mode = Final) or states (mode = Partial)
+/// final value (mode = Final or Single) or states (mode = Partial)
 fn finalize_aggregation(
     accumulators: &[AccumulatorItem],
     mode: &AggregateMode,
@@ -566,7 +763,9 @@ fn finalize_aggregation(
                 .collect::<datafusion_common::Result<Vec<_>>>()?;
             Ok(a.iter().flatten().cloned().collect::<Vec<_>>())
         }
-        AggregateMode::Final | AggregateMode::FinalPartitioned => {
+        AggregateMode::Final
+        | AggregateMode::FinalPartitioned
+        | AggregateMode::Single => {
             // merge the state to the final value
             accumulators
                 .iter()
@@ -640,16 +839,22 @@ fn evaluate_group_by(
 
 #[cfg(test)]
 mod tests {
-    use crate::execution::context::TaskContext;
+    use crate::config::{
+        OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD,
+        OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS,
+    };
+    use crate::execution::context::{SessionConfig, TaskContext};
     use crate::from_slice::FromSlice;
     use crate::physical_plan::aggregates::{
         AggregateExec, AggregateMode, PhysicalGroupBy,
     };
-    use crate::physical_plan::expressions::{col, Avg};
+    use crate::physical_plan::expressions::{col, Avg, DistinctCount, Max, Min};
+    use crate::physical_plan::memory::MemoryExec;
     use crate::test::assert_is_pending;
     use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
     use crate::{assert_batches_sorted_eq, physical_plan::common};
     use arrow::array::{Float64Array, UInt32Array};
+    use arrow::compute::SortOptions;
     use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
     use arrow::error::Result as ArrowResult;
     use arrow::record_batch::RecordBatch;
@@ -1032,6 +1237,231 @@ mod tests {
         check_grouping_sets(input).await
     }
 
+    #[tokio::test]
+    async fn min_of_sorted_input_without_groups() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::UInt32, true)]));
+        let batch = |values: Vec<Option<u32>>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(UInt32Array::from(values))],
+            )
+        };
+        // the second batch breaks the declared ordering, to show that it is
+        // not read once the first non null value is found
+        let batches = vec![batch(vec![None, Some(2), Some(3)])?, batch(vec![Some(1)])?];
+        let ordering = vec![PhysicalSortExpr {
+            expr: col("a", &schema)?,
+            options: SortOptions {
+                descending: false,
+                nulls_first: true,
+            },
+        }];
+        let min: Arc<dyn AggregateExpr> = Arc::new(Min::new(
+            col("a", &schema)?,
+            "MIN(a)".to_string(),
+            DataType::UInt32,
+        ));
+        let max: Arc<dyn AggregateExpr> = Arc::new(Max::new(
+            col("a", &schema)?,
+            "MAX(a)".to_string(),
+            DataType::UInt32,
+        ));
+
+        let session_ctx = SessionContext::new();
+        let cases = vec![
+            (
+                vec![min.clone()],
+                vec![
+                    "+--------+",
+                    "| MIN(a) |",
+                    "+--------+",
+                    "| 2      |",
+                    "+--------+",
+                ],
+            ),
+            // MAX needs the whole input
+            (
+                vec![min, max],
+                vec![
+                    "+--------+--------+",
+                    "| MIN(a) | MAX(a) |",
+                    "+--------+--------+",
+                    "| 1      | 3      |",
+                    "+--------+--------+",
+                ],
+            ),
+        ];
+        for (aggregates, expected) in cases {
+            let input = Arc::new(
+                MemoryExec::try_new(&[batches.clone()], schema.clone(), None)?
+                    .with_sort_information(ordering.clone()),
+            );
+            let aggregate = Arc::new(AggregateExec::try_new(
+                AggregateMode::Single,
+                PhysicalGroupBy::default(),
+                aggregates,
+                input,
+                schema.clone(),
+            )?);
+            let result =
+                common::collect(aggregate.execute(0, session_ctx.task_ctx())?).await?;
+            assert_batches_sorted_eq!(expected, &result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn count_rows_without_groups() -> Result<()> {
+        let input: Arc<dyn ExecutionPlan> =
+            Arc::new(TestYieldingExec { yield_first: true });
+        let input_schema = input.schema();
+
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![
+            Arc::new(Count::new(
+                lit(1i8),
+                "COUNT(1)".to_string(),
+                DataType::Int64,
+            )),
+            Arc::new(Count::new(
+                lit(ScalarValue::Int8(None)),
+                "COUNT(NULL)".to_string(),
+                DataType::Int64,
+            )),
+            Arc::new(Max::new(
+                col("a", &input_schema)?,
+                "MAX(a)".to_string(),
+                DataType::UInt32,
+            )),
+        ];
+
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+
+        let partial_aggregate = Arc::new(AggregateExec::try_new(
+            AggregateMode::Partial,
+            PhysicalGroupBy::default(),
+            aggregates.clone(),
+            input,
+            input_schema.clone(),
+        )?);
+        let final_aggregate = Arc::new(AggregateExec::try_new(
+            AggregateMode::Final,
+            PhysicalGroupBy::default(),
+            aggregates,
+            partial_aggregate,
+            input_schema,
+        )?);
+
+        let result = common::collect(final_aggregate.execute(0, task_ctx)?).await?;
+        let expected = vec![
+            "+----------+-------------+--------+",
+            "| COUNT(1) | COUNT(NULL) | MAX(a) |",
+            "+----------+-------------+--------+",
+            "| 8        | 0           | 4      |",
+            "+----------+-------------+--------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+        Ok(())
+    }
+
+    /// runs a partial aggregation skipped after its first batch, which
+    /// outputs `partial_rows` rows, and the final aggregation of its output
+    async fn check_skip_partial_aggregation(
+        aggregates: Vec<Arc<dyn AggregateExpr>>,
+        partial_rows: usize,
+        expected: Vec<&str>,
+    ) -> Result<()> {
+        let input: Arc<dyn ExecutionPlan> =
+            Arc::new(TestYieldingExec { yield_first: false });
+        let input_schema = input.schema();
+        let grouping_set = PhysicalGroupBy::new_single(vec![(
+            col("a", &input_schema)?,
+            "a".to_string(),
+        )]);
+
+        let config = SessionConfig::new()
+            .set_u64(OPT_SKIP_PARTIAL_AGGREGATION_PROBE_ROWS, 1)
+            .set_f64(OPT_SKIP_PARTIAL_AGGREGATION_PROBE_RATIO_THRESHOLD, 0.1);
+        let task_ctx = SessionContext::with_config(config).task_ctx();
+
+        let partial_aggregate = Arc::new(AggregateExec::try_new(
+            AggregateMode::Partial,
+            grouping_set,
+            aggregates.clone(),
+            input,
+            input_schema.clone(),
+        )?);
+        let result =
+            common::collect(partial_aggregate.execute(0, task_ctx.clone())?).await?;
+        assert_eq!(
+            result.iter().map(|b| b.num_rows()).sum::<usize>(),
+            partial_rows
+        );
+        let metrics = partial_aggregate.metrics().unwrap();
+        let skipped_rows = metrics
+            .sum(|metric| metric.value().name() == "skipped_aggregation_rows")
+            .unwrap();
+        assert_eq!(skipped_rows.as_usize(), 4);
+
+        let final_grouping_set = PhysicalGroupBy::new_single(vec![(
+            col("a", &input_schema)?,
+            "a".to_string(),
+        )]);
+        let merged_aggregate = Arc::new(AggregateExec::try_new(
+            AggregateMode::Final,
+            final_grouping_set,
+            aggregates,
+            Arc::new(CoalescePartitionsExec::new(partial_aggregate)),
+            input_schema,
+        )?);
+        let result = common::collect(merged_aggregate.execute(0, task_ctx)?).await?;
+        assert_batches_sorted_eq!(expected, &result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_partial_aggregation() -> Result<()> {
+        let schema = some_data().0;
+
+        // aggregated in row format, the rows of the second batch are
+        // converted into partial states
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![Arc::new(Avg::new(
+            col("b", &schema)?,
+            "AVG(b)".to_string(),
+            DataType::Float64,
+        ))];
+        let expected = vec![
+            "+---+--------------------+",
+            "| a | AVG(b)             |",
+            "+---+--------------------+",
+            "| 2 | 1                  |",
+            "| 3 | 2.3333333333333335 |",
+            "| 4 | 3.6666666666666665 |",
+            "+---+--------------------+",
+        ];
+        check_skip_partial_aggregation(aggregates, 7, expected).await?;
+
+        // the groups of both batches are output separately, as the rows
+        // of a distinct count cannot be converted into partial states
+
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![Arc::new(DistinctCount::new(
+            vec![DataType::Float64],
+            vec![col("b", &schema)?],
+            "COUNT(DISTINCT b)".to_string(),
+            DataType::Int64,
+        ))];
+        let expected = vec![
+            "+---+-------------------+",
+            "| a | COUNT(DISTINCT b) |",
+            "+---+-------------------+",
+            "| 2 | 1                 |",
+            "| 3 | 2                 |",
+            "| 4 | 2                 |",
+            "+---+-------------------+",
+        ];
+        check_skip_partial_aggregation(aggregates, 6, expected).await
+    }
+
     #[tokio::test]
     async fn test_drop_cancel_without_groups() -> Result<()> {
         let session_ctx = SessionContext::new();
@@ -23,11 +23,13 @@ use crate::physical_plan::aggregates::{
 };
 use crate::physical_plan::metrics::{BaselineMetrics, RecordOutput};
 use crate::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use arrow::array::{ArrayRef, Int64Array};
 use arrow::datatypes::SchemaRef;
 use arrow::error::{ArrowError, Result as ArrowResult};
 use arrow::record_batch::RecordBatch;
 use datafusion_common::Result;
-use datafusion_physical_expr::{AggregateExpr, PhysicalExpr};
+use datafusion_physical_expr::expressions::{Column, Count, Literal, Max, Min};
+use datafusion_physical_expr::{AggregateExpr, PhysicalExpr, PhysicalSortExpr};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
@@ -44,6 +46,16 @@ pub(crate) struct AggregateStream {
     baseline_metrics: BaselineMetrics,
     aggregate_expressions: Vec<Vec<Arc<dyn PhysicalExpr>>>,
     accumulators: Vec<AccumulatorItem>,
+    /// Whether each aggregate counts all the input rows, like `COUNT(*)` in
+    /// partial or single mode. Its argument is not evaluated, the accumulator
+    /// is given the number of input rows once they are all read.
+    count_rows: Vec<bool>,
+    /// The number of input rows
+    num_rows: usize,
+    /// Whether all the aggregates take the first non null value of their
+    /// argument, like `MIN(a)` of an input sorted by `a` in ascending order.
+    /// The input is then only read until they all have one.
+    first_values: bool,
     finished: bool,
 }
 
@@ -54,10 +66,23 @@ impl AggregateStream {
         schema: SchemaRef,
         aggr_expr: Vec<Arc<dyn AggregateExpr>>,
         input: SendableRecordBatchStream,
+        input_ordering: Option<&[PhysicalSortExpr]>,
         baseline_metrics: BaselineMetrics,
     ) -> datafusion_common::Result<Self> {
         let aggregate_expressions = aggregate_expressions(&aggr_expr, &mode, 0)?;
         let accumulators = create_accumulators(&aggr_expr)?;
+        let count_rows = aggr_expr
+            .iter()
+            .map(|expr| {
+                matches!(mode, AggregateMode::Partial | AggregateMode::Single)
+                    && counts_all_rows(expr.as_ref())
+            })
+            .collect();
+        let first_values = matches!(mode, AggregateMode::Partial | AggregateMode::Single)
+            && !aggr_expr.is_empty()
+            && aggr_expr
+                .iter()
+                .all(|expr| takes_first_value(expr.as_ref(), input_ordering));
 
         Ok(Self {
             schema,
@@ -66,9 +91,42 @@ impl AggregateStream {
             baseline_metrics,
             aggregate_expressions,
             accumulators,
+            count_rows,
+            num_rows: 0,
+            first_values,
             finished: false,
         })
     }
+
+    /// Whether the result is known before the end of the input, as all the
+    /// aggregates take the first non null value of their argument and have
+    /// found it
+    fn answered(&self) -> Result<bool> {
+        if !self.first_values {
+            return Ok(false);
+        }
+        for accumulator in &self.accumulators {
+            for state in accumulator.state()? {
+                if state.as_scalar()?.is_null() {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Computes the output batch from the accumulators
+    fn finalize(&mut self) -> ArrowResult<RecordBatch> {
+        let timer = self.baseline_metrics.elapsed_compute().timer();
+        let result =
+            count_all_rows(&mut self.accumulators, &self.count_rows, self.num_rows)
+                .and_then(|_| finalize_aggregation(&self.accumulators, &self.mode))
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+                .and_then(|columns| RecordBatch::try_new(self.schema.clone(), columns))
+                .record_output(&self.baseline_metrics);
+        timer.done();
+        result
+    }
 }
 
 impl Stream for AggregateStream {
@@ -83,40 +141,34 @@ impl Stream for AggregateStream {
             return Poll::Ready(None);
         }
 
+        this.baseline_metrics.record_poll_start();
         let elapsed_compute = this.baseline_metrics.elapsed_compute();
 
         loop {
-            let result = match ready!(this.input.poll_next_unpin(cx)) {
+            let poll = this.input.poll_next_unpin(cx);
+            let result = match ready!(this.baseline_metrics.record_input_poll(poll)) {
                 Some(Ok(batch)) => {
                     let timer = elapsed_compute.timer();
+                    this.num_rows += batch.num_rows();
                     let result = aggregate_batch(
                         &this.mode,
                         &batch,
                         &mut this.accumulators,
                         &this.aggregate_expressions,
+                        &this.count_rows,
                     );
 
                     timer.done();
 
-                    match result {
-                        Ok(_) => continue,
+                    match result.and_then(|_| this.answered()) {
+                        Ok(false) => continue,
+                        // the rest of the input can not change the result
+                        Ok(true) => this.finalize(),
                         Err(e) => Err(ArrowError::ExternalError(Box::new(e))),
                     }
                 }
                 Some(Err(e)) => Err(e),
-                None => {
-                    this.finished = true;
-                    let timer = this.baseline_metrics.elapsed_compute().timer();
-                    let result = finalize_aggregation(&this.accumulators, &this.mode)
-                        .map_err(|e| ArrowError::ExternalError(Box::new(e)))
-                        .and_then(|columns| {
-                            RecordBatch::try_new(this.schema.clone(), columns)
-                        })
-                        .record_output(&this.baseline_metrics);
-
-                    timer.done();
-                    result
-                }
+                None => this.finalize(),
             };
 
             this.finished = true;
@@ -137,6 +189,7 @@ fn aggregate_batch(
     batch: &RecordBatch,
     accumulators: &mut [AccumulatorItem],
     expressions: &[Vec<Arc<dyn PhysicalExpr>>],
+    count_rows: &[bool],
 ) -> Result<()> {
     // 1.1 iterate accumulators and respective expressions together
     // 1.2 evaluate expressions
@@ -146,7 +199,9 @@ fn aggregate_batch(
     accumulators
         .iter_mut()
         .zip(expressions)
-        .try_for_each(|(accum, expr)| {
+        .zip(count_rows)
+        .filter(|(_, count_rows)| !**count_rows)
+        .try_for_each(|((accum, expr), _)| {
             // 1.2
             let values = &expr
                 .iter()
@@ -156,10 +211,70 @@ fn aggregate_batch(
 
             // 1.3
             match mode {
-                AggregateMode::Partial => accum.update_batch(values),
+                AggregateMode::Partial | AggregateMode::Single => {
+                    accum.update_batch(values)
+                }
                 AggregateMode::Final | AggregateMode::FinalPartitioned => {
                     accum.merge_batch(values)
                 }
             }
         })
 }
+
+/// Returns true if `expr` counts all the rows, as its arguments are non null
+/// literals, like `COUNT(*)`
+fn counts_all_rows(expr: &dyn AggregateExpr) -> bool {
+    expr.as_any().is::<Count>()
+        && expr.expressions().iter().all(|arg| {
+            matches!(
+                arg.as_any().downcast_ref::<Literal>(),
+                Some(literal) if !literal.value().is_null()
+            )
+        })
+}
+
+/// Merges the number of input rows into the accumulators counting all the
+/// rows, as a partial count
+fn count_all_rows(
+    accumulators: &mut [AccumulatorItem],
+    count_rows: &[bool],
+    num_rows: usize,
+) -> Result<()> {
+    let counts: ArrayRef = Arc::new(Int64Array::from(vec![num_rows as i64]));
+    accumulators
+        .iter_mut()
+        .zip(count_rows)
+        .filter(|(_, count_rows)| **count_rows)
+        .try_for_each(|(accum, _)| accum.merge_batch(&[counts.clone()]))
+}
+
+/// Returns true if `expr` is the first non null value of its argument in an
+/// input sorted by `ordering`: a `MIN` of the column the input is sorted by
+/// in ascending order, or a `MAX` of the column it is sorted by in
+/// descending order
+fn takes_first_value(
+    expr: &dyn AggregateExpr,
+    ordering: Option<&[PhysicalSortExpr]>,
+) -> bool {
+    let sort = match ordering.and_then(|ordering| ordering.first()) {
+        Some(sort) => sort,
+        None => return false,
+    };
+    let descending = if expr.as_any().is::<Min>() {
+        false
+    } else if expr.as_any().is::<Max>() {
+        true
+    } else {
+        return false;
+    };
+    let args = expr.expressions();
+    sort.options.descending == descending
+        && args.len() == 1
+        && matches!(
+            (
+                args[0].as_any().downcast_ref::<Column>(),
+                sort.expr.as_any().downcast_ref::<Column>(),
+            ),
+            (Some(arg), Some(sort_column)) if arg == sort_column
+        )
+}
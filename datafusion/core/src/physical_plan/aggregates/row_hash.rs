@@ -30,7 +30,7 @@ use futures::{
 use crate::error::Result;
 use crate::physical_plan::aggregates::{
     evaluate_group_by, evaluate_many, group_schema, AccumulatorItemV2, AggregateMode,
-    PhysicalGroupBy,
+    PhysicalGroupBy, SkipAggregationProbe,
 };
 use crate::physical_plan::hash_utils::create_row_hashes;
 use crate::physical_plan::metrics::{BaselineMetrics, RecordOutput};
@@ -76,6 +76,7 @@ pub(crate) struct GroupedHashAggregateStreamV2 {
     aggr_state: AggregationState,
     aggregate_expressions: Vec<Vec<Arc<dyn PhysicalExpr>>>,
 
+    aggr_expr: Vec<Arc<dyn AggregateExpr>>,
     group_by: PhysicalGroupBy,
     accumulators: Vec<AccumulatorItemV2>,
 
@@ -90,6 +91,11 @@ pub(crate) struct GroupedHashAggregateStreamV2 {
     /// if the result is chunked into batches,
     /// last offset is preserved for continuation.
     row_group_skip_position: usize,
+    /// decides when a partial aggregation outputs its groups after each batch
+    skip_probe: Option<SkipAggregationProbe>,
+    /// whether the groups aggregated so far are being output, before starting
+    /// over with the next batch
+    flushing: bool,
 }
 
 fn aggr_state_schema(aggr_expr: &[Arc<dyn AggregateExpr>]) -> Result<SchemaRef> {
@@ -110,6 +116,7 @@ impl GroupedHashAggregateStreamV2 {
         input: SendableRecordBatchStream,
         baseline_metrics: BaselineMetrics,
         batch_size: usize,
+        skip_probe: Option<SkipAggregationProbe>,
     ) -> Result<Self> {
         let timer = baseline_metrics.elapsed_compute().timer();
 
@@ -131,6 +138,7 @@ impl GroupedHashAggregateStreamV2 {
             schema,
             mode,
             input,
+            aggr_expr,
             group_by,
             accumulators,
             group_schema,
@@ -142,6 +150,8 @@ impl GroupedHashAggregateStreamV2 {
             random_state: Default::default(),
             batch_size,
             row_group_skip_position: 0,
+            skip_probe,
+            flushing: false,
         })
     }
 }
@@ -155,13 +165,75 @@ impl Stream for GroupedHashAggregateStreamV2 {
     ) -> Poll<Option<Self::Item>> {
         let this = &mut *self;
 
+        this.baseline_metrics.record_poll_start();
         let elapsed_compute = this.baseline_metrics.elapsed_compute();
 
         loop {
+            if this.flushing {
+                if this.row_group_skip_position < this.aggr_state.group_states.len() {
+                    let timer = elapsed_compute.timer();
+                    let result = create_batch_from_map(
+                        &this.mode,
+                        &this.group_schema,
+                        &this.aggr_schema,
+                        this.batch_size,
+                        this.row_group_skip_position,
+                        &mut this.aggr_state,
+                        &mut this.accumulators,
+                        &this.schema,
+                    );
+                    timer.done();
+
+                    this.row_group_skip_position += this.batch_size;
+                    match result {
+                        Ok(Some(result)) => {
+                            return Poll::Ready(Some(Ok(
+                                result.record_output(&this.baseline_metrics)
+                            )))
+                        }
+                        Ok(None) => {}
+                        Err(error) => return Poll::Ready(Some(Err(error))),
+                    }
+                }
+                // all the groups are output, start over
+                this.aggr_state = Default::default();
+                this.row_group_skip_position = 0;
+                this.flushing = false;
+            }
+
+            let poll = this.input.poll_next_unpin(cx);
             let result: ArrowResult<Option<RecordBatch>> =
-                match ready!(this.input.poll_next_unpin(cx)) {
+                match ready!(this.baseline_metrics.record_input_poll(poll)) {
                     Some(Ok(batch)) => {
                         let timer = elapsed_compute.timer();
+                        let converted = match &mut this.skip_probe {
+                            Some(probe) => probe.convert_to_state(
+                                &this.group_by,
+                                &this.aggr_expr,
+                                &this.aggregate_expressions,
+                                &batch,
+                                &this.schema,
+                            ),
+                            None => Ok(None),
+                        };
+                        match converted {
+                            Ok(Some(states)) => {
+                                // the partial aggregation is skipped
+                                timer.done();
+                                return Poll::Ready(Some(Ok(
+                                    states.record_output(&this.baseline_metrics)
+                                )));
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                timer.done();
+                                return Poll::Ready(Some(Err(
+                                    ArrowError::ExternalError(Box::new(e)),
+                                )));
+                            }
+                        }
+
+                        let num_rows = batch.num_rows();
                         let result = group_aggregate_batch(
                             &this.mode,
                             &this.random_state,
@@ -177,7 +249,13 @@ impl Stream for GroupedHashAggregateStreamV2 {
                         timer.done();
 
                         match result {
-                            Ok(_) => continue,
+                            Ok(_) => {
+                                if let Some(probe) = &mut this.skip_probe {
+                                    let num_groups = this.aggr_state.group_states.len();
+                                    this.flushing = probe.update(num_rows, num_groups);
+                                }
+                                continue;
+                            }
                             Err(e) => Err(ArrowError::ExternalError(Box::new(e))),
                         }
                     }
@@ -357,7 +435,7 @@ fn group_aggregate_batch(
                         state_accessor
                             .point_to(0, group_state.aggregation_buffer.as_mut_slice());
                         match mode {
-                            AggregateMode::Partial => {
+                            AggregateMode::Partial | AggregateMode::Single => {
                                 accumulator.update_batch(&values, &mut state_accessor)
                             }
                             AggregateMode::FinalPartitioned | AggregateMode::Final => {
@@ -471,7 +549,9 @@ fn create_batch_from_map(
             aggr_schema,
             RowType::WordAligned,
         )),
-        AggregateMode::Final | AggregateMode::FinalPartitioned => {
+        AggregateMode::Final
+        | AggregateMode::FinalPartitioned
+        | AggregateMode::Single => {
             let mut results: Vec<Vec<ScalarValue>> = vec![vec![]; accumulators.len()];
             for buffer in state_buffers.iter_mut() {
                 state_accessor.point_to(0, buffer);
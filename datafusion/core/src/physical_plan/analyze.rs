@@ -55,6 +55,16 @@ impl AnalyzeExec {
             schema,
         }
     }
+
+    /// Whether the per-operator metrics are shown in detail
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
 }
 
 impl ExecutionPlan for AnalyzeExec {
@@ -0,0 +1,164 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! BucketedExec marks the scan of a bucketed table, whose partition `i`
+//! contains exactly the rows of bucket `i`
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::execution::context::TaskContext;
+use crate::physical_plan::expressions::{Column, PhysicalSortExpr};
+use crate::physical_plan::{
+    DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use arrow::datatypes::SchemaRef;
+
+/// Execution plan that passes through the rows of its input, whose partition
+/// `i` contains exactly the rows of the bucket `i` of a table bucketed on
+/// `columns` into `num_buckets` buckets.
+///
+/// The input is never repartitioned, so that two bucketed inputs with the
+/// same bucketing can be joined partition by partition on the bucket
+/// columns, without shuffling their rows.
+#[derive(Debug)]
+pub struct BucketedExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The bucket columns, in the schema of the input
+    columns: Vec<Column>,
+    /// The number of buckets, and of partitions of the input
+    num_buckets: usize,
+}
+
+impl BucketedExec {
+    /// Create a new BucketedExec
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        columns: Vec<Column>,
+        num_buckets: usize,
+    ) -> Self {
+        Self {
+            input,
+            columns,
+            num_buckets,
+        }
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The bucket columns, in the schema of the input
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// The number of buckets
+    pub fn num_buckets(&self) -> usize {
+        self.num_buckets
+    }
+}
+
+impl ExecutionPlan for BucketedExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        // the buckets are not hash partitioned with the hash function of
+        // `RepartitionExec`, so they can not satisfy a hash partitioning
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
+    fn relies_on_input_order(&self) -> bool {
+        false
+    }
+
+    fn maintains_input_order(&self) -> bool {
+        true
+    }
+
+    fn benefits_from_input_partitioning(&self) -> bool {
+        // repartitioning the input would mix the buckets
+        false
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(BucketedExec::new(
+            children[0].clone(),
+            self.columns.clone(),
+            self.num_buckets,
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        self.input.execute(partition, context)
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let columns = self
+                    .columns
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "BucketedExec: columns=[{}], num_buckets={}",
+                    columns.join(", "),
+                    self.num_buckets
+                )
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
@@ -0,0 +1,498 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Enforcement of the check constraints of tables: when rows are inserted
+//! into them, and when they are scanned in validation mode
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::BooleanArray;
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::{Column, Constraints, ToDFSchema};
+use datafusion_expr::utils::expr_to_columns;
+use datafusion_expr::Expr;
+use datafusion_optimizer::simplify_expressions::{ExprSimplifier, SimplifyContext};
+use datafusion_sql::parser::DFParser;
+use datafusion_sql::planner::SqlToRel;
+use futures::StreamExt;
+use log::debug;
+use parking_lot::Mutex;
+use sqlparser::dialect::GenericDialect;
+
+use super::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+};
+use super::stream::RecordBatchStreamAdapter;
+use super::{
+    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::{SessionState, TaskContext};
+use crate::physical_expr::create_physical_expr;
+use crate::physical_plan::expressions::PhysicalSortExpr;
+
+/// The [`Constraint::Check`](datafusion_common::Constraint::Check)
+/// constraints of a table, and the columns of its primary key not being null,
+/// compiled to be evaluated on record batches
+#[derive(Debug, Clone, Default)]
+pub struct CheckConstraints {
+    /// The name of each constraint, or its expression when it has no name,
+    /// and its compiled expression
+    checks: Vec<(String, Arc<dyn PhysicalExpr>)>,
+}
+
+impl CheckConstraints {
+    /// Create the constraints from the label and the compiled expression of
+    /// each of them
+    pub fn new(checks: Vec<(String, Arc<dyn PhysicalExpr>)>) -> Self {
+        Self { checks }
+    }
+
+    /// Compiles the check constraints of a table with `table_schema`, to
+    /// evaluate them on batches with `schema`
+    ///
+    /// `schema` may have a subset of the columns of the table, such as the
+    /// output of a scan with a projection, in which case the constraints on
+    /// the other columns are left out.
+    pub fn try_new(
+        constraints: &Constraints,
+        table_schema: &Schema,
+        schema: SchemaRef,
+        state: &SessionState,
+    ) -> Result<Self> {
+        let max_depth = state.config.sql_max_depth();
+        let planner = SqlToRel::new(state).with_max_depth(max_depth);
+        let table_schema = table_schema.clone().to_dfschema()?;
+        let df_schema = schema.as_ref().clone().to_dfschema_ref()?;
+        let simplifier = ExprSimplifier::new(
+            SimplifyContext::new(&state.execution_props).with_schema(df_schema.clone()),
+        );
+
+        let mut checks = vec![];
+        for (name, sql) in constraints.checks() {
+            let sql_expr = DFParser::parse_sql_expr_with_max_depth(
+                sql,
+                &GenericDialect {},
+                max_depth,
+            )?;
+            let expr =
+                planner.sql_to_rex(sql_expr, &table_schema, &mut HashMap::new())?;
+            let mut columns = HashSet::new();
+            expr_to_columns(&expr, &mut columns)?;
+            if !columns
+                .iter()
+                .all(|column| schema.field_with_name(&column.name).is_ok())
+            {
+                continue;
+            }
+            let expr = simplifier.coerce(expr, df_schema.clone())?;
+            let expr =
+                create_physical_expr(&expr, &df_schema, &schema, &state.execution_props)?;
+            let label = name.unwrap_or(sql).to_string();
+            checks.push((label, expr));
+        }
+
+        // the columns of the primary key can not be null
+        for index in constraints.primary_key_columns() {
+            let name = table_schema.field(index).name();
+            if schema.field_with_name(name).is_err() {
+                continue;
+            }
+            let expr = Expr::Column(Column::from_name(name)).is_not_null();
+            let expr =
+                create_physical_expr(&expr, &df_schema, &schema, &state.execution_props)?;
+            checks.push((format!("{} IS NOT NULL", name), expr));
+        }
+        Ok(Self { checks })
+    }
+
+    /// Returns true if there are no constraints to check
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// The names of the constraints, or their expressions when they have
+    /// no name
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.checks.iter().map(|(label, _)| label.as_str())
+    }
+
+    /// The label and the compiled expression of each constraint
+    pub fn checks(&self) -> &[(String, Arc<dyn PhysicalExpr>)] {
+        &self.checks
+    }
+
+    /// Returns, for each constraint, whether each row of `batch` violates it
+    ///
+    /// As in SQL, a row violates a constraint when its expression is false,
+    /// not when it is null.
+    fn violations(&self, batch: &RecordBatch) -> Result<Vec<Vec<bool>>> {
+        self.checks
+            .iter()
+            .map(|(label, expr)| {
+                let values = expr.evaluate(batch)?.into_array(batch.num_rows());
+                let values =
+                    values
+                        .as_any()
+                        .downcast_ref::<BooleanArray>()
+                        .ok_or_else(|| {
+                            DataFusionError::Plan(format!(
+                                "Check constraint {} must be a boolean expression",
+                                label
+                            ))
+                        })?;
+                Ok(values.iter().map(|value| value == Some(false)).collect())
+            })
+            .collect()
+    }
+
+    /// Returns the number of rows of `batch` violating each constraint, in
+    /// the order of [`labels`](Self::labels)
+    pub fn count_violations(&self, batch: &RecordBatch) -> Result<Vec<usize>> {
+        Ok(self
+            .violations(batch)?
+            .iter()
+            .map(|violated| violated.iter().filter(|v| **v).count())
+            .collect())
+    }
+
+    /// Splits `batch` into the rows satisfying all the constraints and the
+    /// rows violating at least one of them
+    pub fn split(&self, batch: &RecordBatch) -> Result<(RecordBatch, RecordBatch)> {
+        let mut rejected = vec![false; batch.num_rows()];
+        for violated in self.violations(batch)? {
+            for (rejected, violated) in rejected.iter_mut().zip(violated) {
+                *rejected |= violated;
+            }
+        }
+        let valid = rejected.iter().map(|r| !r).collect::<Vec<_>>();
+        Ok((
+            filter_record_batch(batch, &BooleanArray::from(valid))?,
+            filter_record_batch(batch, &BooleanArray::from(rejected))?,
+        ))
+    }
+
+    /// Checks the batches of `input`, which are inserted into a table
+    ///
+    /// The returned stream fails at the first batch with rows violating a
+    /// constraint, unless `rejected` collects these rows, in which case it
+    /// returns the other rows.
+    pub fn enforce(
+        self,
+        input: SendableRecordBatchStream,
+        rejected: Option<Arc<Mutex<Vec<RecordBatch>>>>,
+    ) -> SendableRecordBatchStream {
+        let schema = input.schema();
+        let stream = input.map(move |batch| {
+            let batch = batch?;
+            let (valid, invalid) = self.split(&batch)?;
+            if invalid.num_rows() > 0 {
+                match &rejected {
+                    Some(rejected) => rejected.lock().push(invalid),
+                    None => {
+                        let counts = self.count_violations(&invalid)?;
+                        let (label, count) = self
+                            .labels()
+                            .zip(counts)
+                            .find(|(_, count)| *count > 0)
+                            .expect("rejected rows violate a constraint");
+                        return Err(ArrowError::from(DataFusionError::Execution(
+                            format!(
+                                "Check constraint {} is violated by {} inserted rows",
+                                label, count
+                            ),
+                        )));
+                    }
+                }
+            }
+            Ok(valid)
+        });
+        Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+    }
+}
+
+/// Counts the rows of the scan of a table violating its check constraints,
+/// passing its batches on unchanged
+#[derive(Debug)]
+pub struct CheckConstraintsExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The constraints of the table
+    checks: CheckConstraints,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl CheckConstraintsExec {
+    /// Create a CheckConstraintsExec validating the batches of `input`
+    pub fn new(input: Arc<dyn ExecutionPlan>, checks: CheckConstraints) -> Self {
+        Self {
+            input,
+            checks,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The validated constraints
+    pub fn checks(&self) -> &CheckConstraints {
+        &self.checks
+    }
+}
+
+impl ExecutionPlan for CheckConstraintsExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn maintains_input_order(&self) -> bool {
+        true
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(CheckConstraintsExec::new(
+            children[0].clone(),
+            self.checks.clone(),
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        debug!("Start CheckConstraintsExec::execute for partition {} of context session_id {} and task_id {:?}", partition, context.session_id(), context.task_id());
+        let mut input = self.input.execute(partition, context)?;
+        let schema = input.schema();
+        let checks = self.checks.clone();
+        let violations = checks
+            .labels()
+            .map(|label| {
+                MetricBuilder::new(&self.metrics)
+                    .with_new_label("constraint", label.to_string())
+                    .counter("check_violations", partition)
+            })
+            .collect::<Vec<Count>>();
+        let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+        let stream = futures::stream::poll_fn(move |cx| {
+            baseline_metrics.record_poll_start();
+            let poll = input.poll_next_unpin(cx);
+            let poll = baseline_metrics.record_input_poll(poll).map(|batch| {
+                batch.map(|batch| {
+                    let batch = batch?;
+                    let timer = baseline_metrics.elapsed_compute().timer();
+                    for (count, violations) in checks
+                        .count_violations(&batch)?
+                        .into_iter()
+                        .zip(&violations)
+                    {
+                        violations.add(count);
+                    }
+                    timer.done();
+                    Ok(batch)
+                })
+            });
+            baseline_metrics.record_poll(poll)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let checks = self.checks.labels().collect::<Vec<_>>().join(", ");
+                write!(f, "CheckConstraintsExec: checks=[{}]", checks)
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_slice::FromSlice;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::{MemoryExec, MemoryStream};
+    use crate::prelude::SessionContext;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field};
+    use datafusion_common::Constraint;
+
+    fn table() -> Result<(SchemaRef, RecordBatch, Constraints)> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(-3), Some(4)])),
+                Arc::new(Int32Array::from_slice(&[10, 20, 30, 1])),
+            ],
+        )?;
+        let constraints = Constraints::new(vec![
+            Constraint::Check {
+                name: None,
+                expr: "a > 0".to_string(),
+            },
+            Constraint::Check {
+                name: Some("a_below_b".to_string()),
+                expr: "a < b".to_string(),
+            },
+        ]);
+        Ok((schema, batch, constraints))
+    }
+
+    #[tokio::test]
+    async fn split_rows() -> Result<()> {
+        let (schema, batch, constraints) = table()?;
+        let state = SessionContext::new().state();
+        let checks =
+            CheckConstraints::try_new(&constraints, &schema, schema.clone(), &state)?;
+        assert_eq!(
+            checks.labels().collect::<Vec<_>>(),
+            vec!["a > 0", "a_below_b"]
+        );
+        // null values satisfy the constraints
+        assert_eq!(checks.count_violations(&batch)?, vec![1, 1]);
+
+        let (valid, rejected) = checks.split(&batch)?;
+        let a = valid
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(a, &Int32Array::from(vec![Some(1), None]));
+        assert_eq!(rejected.num_rows(), 2);
+
+        // the constraints on columns which are not projected are left out
+        let projected = Arc::new(schema.project(&[0])?);
+        let checks = CheckConstraints::try_new(&constraints, &schema, projected, &state)?;
+        assert_eq!(checks.labels().collect::<Vec<_>>(), vec!["a > 0"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn primary_key_not_null() -> Result<()> {
+        let (schema, batch, _) = table()?;
+        let state = SessionContext::new().state();
+        let constraints = Constraints::new(vec![Constraint::PrimaryKey(vec![0])]);
+        let checks =
+            CheckConstraints::try_new(&constraints, &schema, schema.clone(), &state)?;
+        assert_eq!(checks.labels().collect::<Vec<_>>(), vec!["a IS NOT NULL"]);
+        assert_eq!(checks.count_violations(&batch)?, vec![1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn enforce() -> Result<()> {
+        let (schema, batch, constraints) = table()?;
+        let state = SessionContext::new().state();
+        let checks =
+            CheckConstraints::try_new(&constraints, &schema, schema.clone(), &state)?;
+        let input = || -> Result<SendableRecordBatchStream> {
+            Ok(Box::pin(MemoryStream::try_new(
+                vec![batch.clone()],
+                schema.clone(),
+                None,
+            )?))
+        };
+
+        let err = common::collect(checks.clone().enforce(input()?, None))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Check constraint a > 0 is violated by 1 inserted rows"),
+            "{}",
+            err
+        );
+
+        let rejected = Arc::new(Mutex::new(vec![]));
+        let valid =
+            common::collect(checks.enforce(input()?, Some(rejected.clone()))).await?;
+        assert_eq!(valid[0].num_rows(), 2);
+        assert_eq!(rejected.lock()[0].num_rows(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn count_scan_violations() -> Result<()> {
+        let (schema, batch, constraints) = table()?;
+        let session_ctx = SessionContext::new();
+        let checks = CheckConstraints::try_new(
+            &constraints,
+            &schema,
+            schema.clone(),
+            &session_ctx.state(),
+        )?;
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        let exec = CheckConstraintsExec::new(input, checks);
+
+        let batches = common::collect(exec.execute(0, session_ctx.task_ctx())?).await?;
+        assert_eq!(batches[0].num_rows(), 4);
+        let metrics = exec.metrics().unwrap();
+        let violations = metrics
+            .sum(|metric| metric.value().name() == "check_violations")
+            .unwrap();
+        assert_eq!(violations.as_usize(), 2);
+        assert_eq!(metrics.output_rows(), Some(4));
+        Ok(())
+    }
+}
@@ -25,8 +25,8 @@ use std::task::{Context, Poll};
 
 use crate::error::Result;
 use crate::physical_plan::{
-    DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
-    SendableRecordBatchStream,
+    DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning,
+    RecordBatchStream, SendableRecordBatchStream,
 };
 
 use crate::execution::context::TaskContext;
@@ -100,6 +100,10 @@ impl ExecutionPlan for CoalesceBatchesExec {
         None
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn relies_on_input_order(&self) -> bool {
         false
     }
@@ -179,6 +183,7 @@ impl Stream for CoalesceBatchesStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         let poll = self.poll_next_inner(cx);
         self.baseline_metrics.record_poll(poll)
     }
@@ -204,6 +209,7 @@ impl CoalesceBatchesStream {
         }
         loop {
             let input_batch = self.input.poll_next_unpin(cx);
+            let input_batch = self.baseline_metrics.record_input_poll(input_batch);
             match input_batch {
                 Poll::Ready(x) => match x {
                     Some(Ok(ref batch)) => {
@@ -303,7 +309,6 @@ mod tests {
     use crate::config::{OPT_COALESCE_BATCHES, OPT_COALESCE_TARGET_BATCH_SIZE};
     use crate::datasource::MemTable;
     use crate::physical_plan::filter::FilterExec;
-    use crate::physical_plan::projection::ProjectionExec;
     use crate::physical_plan::{memory::MemoryExec, repartition::RepartitionExec};
     use crate::prelude::{SessionConfig, SessionContext};
     use crate::test::create_vec_batches;
@@ -315,12 +320,7 @@ mod tests {
             SessionConfig::new().set_u64(OPT_COALESCE_TARGET_BATCH_SIZE, 1234),
         );
         let plan = create_physical_plan(ctx).await?;
-        let projection = plan.as_any().downcast_ref::<ProjectionExec>().unwrap();
-        let coalesce = projection
-            .input()
-            .as_any()
-            .downcast_ref::<CoalesceBatchesExec>()
-            .unwrap();
+        let coalesce = plan.as_any().downcast_ref::<CoalesceBatchesExec>().unwrap();
         assert_eq!(1234, coalesce.target_batch_size);
         Ok(())
     }
@@ -331,13 +331,9 @@ mod tests {
             SessionConfig::new().set_bool(OPT_COALESCE_BATCHES, false),
         );
         let plan = create_physical_plan(ctx).await?;
-        let projection = plan.as_any().downcast_ref::<ProjectionExec>().unwrap();
-        // projection should directly wrap filter with no coalesce step
-        let _filter = projection
-            .input()
-            .as_any()
-            .downcast_ref::<FilterExec>()
-            .unwrap();
+        // the unchanged columns are not projected, and the filter is not
+        // wrapped in a coalesce step
+        let _filter = plan.as_any().downcast_ref::<FilterExec>().unwrap();
         Ok(())
     }
 
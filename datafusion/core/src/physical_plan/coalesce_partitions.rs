@@ -33,7 +33,9 @@ use super::expressions::PhysicalSortExpr;
 use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
 use super::{RecordBatchStream, Statistics};
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+use crate::physical_plan::{
+    DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning,
+};
 
 use super::SendableRecordBatchStream;
 use crate::execution::context::TaskContext;
@@ -87,6 +89,10 @@ impl ExecutionPlan for CoalescePartitionsExec {
         None
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn relies_on_input_order(&self) -> bool {
         false
     }
@@ -191,7 +197,9 @@ impl Stream for MergeStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         let poll = self.input.poll_recv(cx);
+        let poll = self.baseline_metrics.record_input_poll(poll);
         self.baseline_metrics.record_poll(poll)
     }
 }
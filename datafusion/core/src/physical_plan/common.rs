@@ -72,6 +72,7 @@ impl Stream for SizedRecordBatchStream {
         mut self: std::pin::Pin<&mut Self>,
         _: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.metrics.record_poll_start();
         let poll = Poll::Ready(if self.index < self.batches.len() {
             self.index += 1;
             Some(Ok(self.batches[self.index - 1].as_ref().clone()))
@@ -122,6 +123,57 @@ pub(crate) fn combine_batches(
     }
 }
 
+/// Splits the output batches of an operator into batches of at most
+/// `batch_size` rows, so that operators which may produce more rows than
+/// their input batches, like joins, keep the batch size an upper bound.
+///
+/// The split batches are slices of the output batch, and share its buffers.
+#[derive(Debug)]
+pub(crate) struct BatchSplitter {
+    /// The maximum number of rows of the split batches
+    batch_size: usize,
+    /// The output batch whose rows are not all returned yet
+    batch: Option<RecordBatch>,
+    /// The offset of the next rows of `batch` to return
+    offset: usize,
+}
+
+impl BatchSplitter {
+    /// Create a new BatchSplitter for batches of at most `batch_size` rows
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            batch: None,
+            offset: 0,
+        }
+    }
+
+    /// Returns the first rows of `batch`, keeping the next rows to be returned
+    /// by [`Self::next_batch`]
+    pub fn split(&mut self, batch: RecordBatch) -> RecordBatch {
+        if batch.num_rows() <= self.batch_size {
+            self.batch = None;
+            return batch;
+        }
+        self.batch = Some(batch);
+        self.offset = 0;
+        self.next_batch()
+            .expect("the batch has more rows than the batch size")
+    }
+
+    /// Returns the next rows of the last split batch, if any
+    pub fn next_batch(&mut self) -> Option<RecordBatch> {
+        let batch = self.batch.as_ref()?;
+        let length = self.batch_size.min(batch.num_rows() - self.offset);
+        let slice = batch.slice(self.offset, length);
+        self.offset += length;
+        if self.offset == batch.num_rows() {
+            self.batch = None;
+        }
+        Some(slice)
+    }
+}
+
 /// Recursively builds a list of files in a directory with a given extension
 pub fn build_checked_file_list(dir: &str, ext: &str) -> Result<Vec<String>> {
     let mut filenames: Vec<String> = Vec::new();
@@ -343,6 +395,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_batch_splitter() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "f32",
+            DataType::Float32,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Float32Array::from_slice(&[1., 2., 3., 4., 5.]))],
+        )?;
+
+        let mut splitter = BatchSplitter::new(2);
+        let mut sizes = vec![splitter.split(batch.clone()).num_rows()];
+        while let Some(slice) = splitter.next_batch() {
+            sizes.push(slice.num_rows());
+        }
+        assert_eq!(sizes, vec![2, 2, 1]);
+
+        // batches that fit are returned as they are
+        let mut splitter = BatchSplitter::new(5);
+        assert_eq!(splitter.split(batch).num_rows(), 5);
+        assert!(splitter.next_batch().is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_compute_record_batch_statistics_empty() -> Result<()> {
         let schema = Arc::new(Schema::new(vec![
@@ -0,0 +1,739 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! DedupExec keeps the first or the last row of each group of rows of its
+//! input with the same keys.
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::array::{ArrayRef, BooleanArray, UInt32Array};
+use arrow::compute::kernels::partition::lexicographical_partition_ranges;
+use arrow::compute::{filter_record_batch, take, SortColumn};
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use datafusion_common::ScalarValue;
+use datafusion_physical_expr::utils::expr_list_eq_any_order;
+use futures::stream::{Stream, StreamExt};
+use log::debug;
+
+use super::common::batch_byte_size;
+use super::expressions::PhysicalSortExpr;
+use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::TaskContext;
+use crate::execution::memory_manager::ConsumerType;
+use crate::execution::runtime_env::RuntimeEnv;
+use crate::execution::{MemoryConsumer, MemoryConsumerId, MemoryManager};
+use crate::logical_expr::DedupKeep;
+use crate::physical_plan::{
+    DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PhysicalExpr,
+};
+
+/// DedupExec keeps a single row of each group of rows of its input with the
+/// same values of the `on` expressions: the first or the last row of the group
+/// in the order of `order_by`, or in the order of the input when `order_by` is
+/// empty.
+///
+/// When the input is sorted on the keys, the groups are contiguous and each
+/// row is emitted as soon as the next group starts. Otherwise, the keys of the
+/// rows are hashed, and the last rows are only emitted at the end of the input:
+/// the input batches are then buffered, and accounted for in the memory
+/// manager.
+#[derive(Debug)]
+pub struct DedupExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The expressions of the keys
+    on: Vec<Arc<dyn PhysicalExpr>>,
+    /// Which row of each group to keep
+    keep: DedupKeep,
+    /// The order of the rows within each group
+    order_by: Vec<PhysicalSortExpr>,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DedupExec {
+    /// Create a DedupExec on an input
+    ///
+    /// When `order_by` is not empty, the input must be sorted on the keys, in
+    /// any order and direction, followed by `order_by`.
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        on: Vec<Arc<dyn PhysicalExpr>>,
+        keep: DedupKeep,
+        order_by: Vec<PhysicalSortExpr>,
+    ) -> Self {
+        Self {
+            input,
+            on,
+            keep,
+            order_by,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The expressions of the keys
+    pub fn on(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.on
+    }
+
+    /// Which row of each group is kept
+    pub fn keep(&self) -> DedupKeep {
+        self.keep
+    }
+
+    /// The order of the rows within each group
+    pub fn order_by(&self) -> &[PhysicalSortExpr] {
+        &self.order_by
+    }
+
+    /// Returns true if the input is sorted on the keys followed by
+    /// `order_by`, so that the groups of rows are contiguous
+    pub fn sorted_on_keys(&self) -> bool {
+        let ordering = match self.input.output_ordering() {
+            Some(ordering) if ordering.len() >= self.on.len() + self.order_by.len() => {
+                ordering
+            }
+            _ => return false,
+        };
+        let (keys, order) = ordering.split_at(self.on.len());
+        let keys = keys.iter().map(|e| e.expr.clone()).collect::<Vec<_>>();
+        expr_list_eq_any_order(&keys, &self.on)
+            && order.iter().zip(&self.order_by).all(|(a, b)| a == b)
+    }
+}
+
+impl ExecutionPlan for DedupExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        // the rows of a group must all be in the same partition
+        match self.input.output_partitioning() {
+            Partitioning::Hash(exprs, _) if expr_list_eq_any_order(&exprs, &self.on) => {
+                Distribution::HashPartitioned(self.on.clone())
+            }
+            _ => Distribution::SinglePartition,
+        }
+    }
+
+    fn maintains_input_order(&self) -> bool {
+        true
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(DedupExec::new(
+            children[0].clone(),
+            self.on.clone(),
+            self.keep,
+            self.order_by.clone(),
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        debug!("Start DedupExec::execute for partition {} of context session_id {} and task_id {:?}", partition, context.session_id(), context.task_id());
+        let sorted = self.sorted_on_keys();
+        if !sorted && !self.order_by.is_empty() {
+            return Err(DataFusionError::Internal(format!(
+                "DedupExec ordered by {:?} requires its input to be sorted on the keys followed by the order",
+                self.order_by
+            )));
+        }
+        let state = match (sorted, self.keep) {
+            (true, _) => DedupState::Sorted {
+                key: None,
+                last: None,
+            },
+            (false, DedupKeep::First) => DedupState::HashFirst(HashSet::new()),
+            (false, DedupKeep::Last) => DedupState::HashLast {
+                rows: HashMap::new(),
+                batches: vec![],
+                memory: DedupMemoryConsumer::new(partition, context.runtime_env()),
+            },
+        };
+        Ok(Box::pin(DedupStream {
+            input: self.input.execute(partition, context)?,
+            on: self.on.clone(),
+            keep: self.keep,
+            state,
+            output: VecDeque::new(),
+            finished: false,
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let on = self
+                    .on
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "DedupExec: on=[{}], keep={}", on, self.keep)?;
+                if !self.order_by.is_empty() {
+                    let order_by = self
+                        .order_by
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(f, ", order_by=[{}]", order_by)?;
+                }
+                let mode = if self.sorted_on_keys() {
+                    "Sorted"
+                } else {
+                    "Hash"
+                };
+                write!(f, ", mode={}", mode)
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// The rows of the input seen by a [`DedupStream`]
+enum DedupState {
+    /// The input is sorted on the keys: the key of the current group and,
+    /// when keeping the last rows, its last row so far
+    Sorted {
+        key: Option<Vec<ScalarValue>>,
+        last: Option<RecordBatch>,
+    },
+    /// The keys of the rows kept so far
+    HashFirst(HashSet<Vec<ScalarValue>>),
+    /// The batch and the row index of the last row of each key
+    HashLast {
+        rows: HashMap<Vec<ScalarValue>, (usize, u32)>,
+        batches: Vec<RecordBatch>,
+        memory: DedupMemoryConsumer,
+    },
+}
+
+/// The memory consumer accounting for the input batches buffered by a
+/// [`DedupStream`] keeping the last rows of an input not sorted on the keys.
+/// The batches are only released at the end of the input, and can not be
+/// spilled.
+struct DedupMemoryConsumer {
+    id: MemoryConsumerId,
+    runtime: Arc<RuntimeEnv>,
+    used: AtomicUsize,
+}
+
+impl DedupMemoryConsumer {
+    fn new(partition: usize, runtime: Arc<RuntimeEnv>) -> Self {
+        Self {
+            id: MemoryConsumerId::new(partition),
+            runtime,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Accounts for `size` more bytes of buffered batches
+    fn track(&self, size: usize) {
+        self.used.fetch_add(size, Ordering::SeqCst);
+        self.runtime.grow_tracker_usage(size);
+    }
+
+    /// Releases the memory of all the buffered batches
+    fn release(&self) {
+        let used = self.used.swap(0, Ordering::SeqCst);
+        self.runtime.shrink_tracker_usage(used);
+    }
+}
+
+impl Drop for DedupMemoryConsumer {
+    fn drop(&mut self) {
+        self.runtime.drop_consumer(self.id(), self.mem_used());
+    }
+}
+
+#[async_trait]
+impl MemoryConsumer for DedupMemoryConsumer {
+    fn name(&self) -> String {
+        "DedupExec".to_owned()
+    }
+
+    fn id(&self) -> &MemoryConsumerId {
+        &self.id
+    }
+
+    fn memory_manager(&self) -> Arc<MemoryManager> {
+        self.runtime.memory_manager.clone()
+    }
+
+    fn type_(&self) -> &ConsumerType {
+        &ConsumerType::Tracking
+    }
+
+    async fn spill(&self) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn mem_used(&self) -> usize {
+        self.used.load(Ordering::SeqCst)
+    }
+}
+
+/// A stream that keeps a single row of each group of rows of its input
+struct DedupStream {
+    /// The input stream
+    input: SendableRecordBatchStream,
+    /// The expressions of the keys
+    on: Vec<Arc<dyn PhysicalExpr>>,
+    /// Which row of each group to keep
+    keep: DedupKeep,
+    /// The rows seen so far
+    state: DedupState,
+    /// The batches ready to be returned
+    output: VecDeque<RecordBatch>,
+    /// Whether the input is exhausted
+    finished: bool,
+    /// runtime metrics recording
+    baseline_metrics: BaselineMetrics,
+}
+
+impl DedupStream {
+    /// Evaluates the keys of the rows of `batch`
+    fn keys(&self, batch: &RecordBatch) -> ArrowResult<Vec<ArrayRef>> {
+        Ok(self
+            .on
+            .iter()
+            .map(|expr| {
+                expr.evaluate(batch)
+                    .map(|value| value.into_array(batch.num_rows()))
+            })
+            .collect::<Result<Vec<_>>>()?)
+    }
+
+    /// Deduplicates the rows of `batch`, adding the rows known to be kept to
+    /// the output
+    fn push(&mut self, batch: RecordBatch) -> ArrowResult<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let keys = self.keys(&batch)?;
+        match &mut self.state {
+            DedupState::Sorted { key, last } => {
+                let sort_columns = keys
+                    .iter()
+                    .map(|values| SortColumn {
+                        values: values.clone(),
+                        options: None,
+                    })
+                    .collect::<Vec<_>>();
+                let ranges =
+                    lexicographical_partition_ranges(&sort_columns)?.collect::<Vec<_>>();
+
+                // only the first group of the batch may continue the current one
+                let first_key = row_key(&keys, 0)?;
+                let continues = key.as_ref() == Some(&first_key);
+                let mut indices = vec![];
+                match self.keep {
+                    DedupKeep::First => {
+                        let skip = if continues { 1 } else { 0 };
+                        indices.extend(ranges[skip..].iter().map(|r| r.start as u32));
+                    }
+                    DedupKeep::Last => {
+                        if let Some(last) = last.take() {
+                            if !continues {
+                                self.output.push_back(last);
+                            }
+                        }
+                        let (pending, completed) = ranges.split_last().unwrap();
+                        indices.extend(completed.iter().map(|r| r.end as u32 - 1));
+                        *last = Some(batch.slice(pending.end - 1, 1));
+                    }
+                }
+                *key = Some(row_key(&keys, batch.num_rows() - 1)?);
+                if !indices.is_empty() {
+                    self.output
+                        .push_back(take_rows(&batch, &UInt32Array::from(indices))?);
+                }
+            }
+            DedupState::HashFirst(seen) => {
+                let mask = (0..batch.num_rows())
+                    .map(|row| Ok(Some(seen.insert(row_key(&keys, row)?))))
+                    .collect::<ArrowResult<BooleanArray>>()?;
+                let batch = filter_record_batch(&batch, &mask)?;
+                if batch.num_rows() > 0 {
+                    self.output.push_back(batch);
+                }
+            }
+            DedupState::HashLast {
+                rows,
+                batches,
+                memory,
+            } => {
+                for row in 0..batch.num_rows() {
+                    rows.insert(row_key(&keys, row)?, (batches.len(), row as u32));
+                }
+                memory.track(batch_byte_size(&batch));
+                self.baseline_metrics.mem_used().set(memory.mem_used());
+                batches.push(batch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the rows kept but not yet emitted at the end of the input to the
+    /// output
+    fn finish(&mut self) -> ArrowResult<()> {
+        match &mut self.state {
+            DedupState::Sorted { last, .. } => {
+                if let Some(last) = last.take() {
+                    self.output.push_back(last);
+                }
+            }
+            DedupState::HashFirst(_) => {}
+            DedupState::HashLast {
+                rows,
+                batches,
+                memory,
+            } => {
+                // the rows are emitted in the order of the input
+                let mut indices = vec![vec![]; batches.len()];
+                for (batch, row) in rows.values() {
+                    indices[*batch].push(*row);
+                }
+                for (batch, mut indices) in batches.iter().zip(indices) {
+                    if !indices.is_empty() {
+                        indices.sort_unstable();
+                        self.output
+                            .push_back(take_rows(batch, &UInt32Array::from(indices))?);
+                    }
+                }
+                rows.clear();
+                batches.clear();
+                memory.release();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The values of the keys of the row `row`
+fn row_key(keys: &[ArrayRef], row: usize) -> ArrowResult<Vec<ScalarValue>> {
+    Ok(keys
+        .iter()
+        .map(|array| ScalarValue::try_from_array(array, row))
+        .collect::<Result<Vec<_>>>()?)
+}
+
+/// Takes the rows at `indices` of `batch`
+fn take_rows(batch: &RecordBatch, indices: &UInt32Array) -> ArrowResult<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|array| take(array.as_ref(), indices, None))
+        .collect::<ArrowResult<Vec<_>>>()?;
+    RecordBatch::try_new(batch.schema(), columns)
+}
+
+impl Stream for DedupStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
+        loop {
+            if let Some(batch) = self.output.pop_front() {
+                return self
+                    .baseline_metrics
+                    .record_poll(Poll::Ready(Some(Ok(batch))));
+            }
+            if self.finished {
+                return self.baseline_metrics.record_poll(Poll::Ready(None));
+            }
+            let poll = self.input.poll_next_unpin(cx);
+            let poll = self.baseline_metrics.record_input_poll(poll);
+            let result = match poll {
+                Poll::Ready(Some(Ok(batch))) => {
+                    let timer = self.baseline_metrics.elapsed_compute().timer();
+                    let result = self.push(batch);
+                    timer.done();
+                    result
+                }
+                Poll::Ready(None) => {
+                    self.finished = true;
+                    let timer = self.baseline_metrics.elapsed_compute().timer();
+                    let result = self.finish();
+                    timer.done();
+                    result
+                }
+                Poll::Ready(Some(Err(e))) => Err(e),
+                Poll::Pending => return Poll::Pending,
+            };
+            if let Err(e) = result {
+                self.finished = true;
+                return self.baseline_metrics.record_poll(Poll::Ready(Some(Err(e))));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // at most the number of record batches of the input
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl RecordBatchStream for DedupStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::metrics::MetricValue;
+    use crate::physical_plan::sorts::sort::SortExec;
+    use crate::prelude::{SessionConfig, SessionContext};
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::compute::SortOptions;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// The rows of (key, value, ts), in two batches so that the groups
+    /// span batches
+    fn input() -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Utf8, true),
+            Field::new("value", DataType::Int32, false),
+            Field::new("ts", DataType::Int32, false),
+        ]));
+        let batch = |keys: Vec<Option<&str>>, values: Vec<i32>, ts: Vec<i32>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(keys)),
+                    Arc::new(Int32Array::from(values)),
+                    Arc::new(Int32Array::from(ts)),
+                ],
+            )
+        };
+        let batches = vec![
+            batch(
+                vec![Some("b"), Some("a"), None, Some("b")],
+                vec![1, 2, 3, 4],
+                vec![3, 1, 1, 2],
+            )?,
+            batch(
+                vec![Some("a"), None, Some("c"), Some("b")],
+                vec![5, 6, 7, 8],
+                vec![2, 2, 1, 1],
+            )?,
+        ];
+        Ok(Arc::new(MemoryExec::try_new(&[batches], schema, None)?))
+    }
+
+    /// Sorts the input on the key then `ts`, returning the sort expression of
+    /// `ts`
+    fn sorted_input(
+        descending: bool,
+    ) -> Result<(Arc<dyn ExecutionPlan>, PhysicalSortExpr)> {
+        let input = input()?;
+        let schema = input.schema();
+        let ts = PhysicalSortExpr {
+            expr: col("ts", &schema)?,
+            options: SortOptions {
+                descending,
+                nulls_first: false,
+            },
+        };
+        let sort = vec![
+            PhysicalSortExpr {
+                expr: col("key", &schema)?,
+                options: SortOptions::default(),
+            },
+            ts.clone(),
+        ];
+        Ok((Arc::new(SortExec::try_new(sort, input, None)?), ts))
+    }
+
+    async fn dedup(exec: DedupExec) -> Result<Vec<RecordBatch>> {
+        // small batches, so that the groups span batches
+        let session_ctx =
+            SessionContext::with_config(SessionConfig::new().with_batch_size(3));
+        collect(Arc::new(exec), session_ctx.task_ctx()).await
+    }
+
+    #[tokio::test]
+    async fn dedup_hash_first() -> Result<()> {
+        let input = input()?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = DedupExec::new(input, on, DedupKeep::First, vec![]);
+        assert!(!exec.sorted_on_keys());
+        let expected = vec![
+            "+-----+-------+----+",
+            "| key | value | ts |",
+            "+-----+-------+----+",
+            "| b   | 1     | 3  |",
+            "| a   | 2     | 1  |",
+            "|     | 3     | 1  |",
+            "| c   | 7     | 1  |",
+            "+-----+-------+----+",
+        ];
+        assert_batches_eq!(expected, &dedup(exec).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup_hash_last() -> Result<()> {
+        let input = input()?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = DedupExec::new(input, on, DedupKeep::Last, vec![]);
+        let expected = vec![
+            "+-----+-------+----+",
+            "| key | value | ts |",
+            "+-----+-------+----+",
+            "| a   | 5     | 2  |",
+            "|     | 6     | 2  |",
+            "| c   | 7     | 1  |",
+            "| b   | 8     | 1  |",
+            "+-----+-------+----+",
+        ];
+        assert_batches_eq!(expected, &dedup(exec).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup_hash_last_memory() -> Result<()> {
+        let input = input()?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = Arc::new(DedupExec::new(input, on, DedupKeep::Last, vec![]));
+        let session_ctx = SessionContext::new();
+        collect(exec.clone(), session_ctx.task_ctx()).await?;
+
+        // the buffered input batches were accounted for
+        let mem_used = exec
+            .metrics()
+            .unwrap()
+            .sum(|m| matches!(m.value(), MetricValue::CurrentMemoryUsage(_)))
+            .map(|v| v.as_usize());
+        assert!(mem_used.unwrap() > 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup_sorted_first() -> Result<()> {
+        let (input, ts) = sorted_input(true)?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = DedupExec::new(input, on, DedupKeep::First, vec![ts]);
+        assert!(exec.sorted_on_keys());
+        let expected = vec![
+            "+-----+-------+----+",
+            "| key | value | ts |",
+            "+-----+-------+----+",
+            "|     | 6     | 2  |",
+            "| a   | 5     | 2  |",
+            "| b   | 1     | 3  |",
+            "| c   | 7     | 1  |",
+            "+-----+-------+----+",
+        ];
+        assert_batches_eq!(expected, &dedup(exec).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup_sorted_last() -> Result<()> {
+        let (input, ts) = sorted_input(true)?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = DedupExec::new(input, on, DedupKeep::Last, vec![ts]);
+        let expected = vec![
+            "+-----+-------+----+",
+            "| key | value | ts |",
+            "+-----+-------+----+",
+            "|     | 3     | 1  |",
+            "| a   | 2     | 1  |",
+            "| b   | 8     | 1  |",
+            "| c   | 7     | 1  |",
+            "+-----+-------+----+",
+        ];
+        assert_batches_eq!(expected, &dedup(exec).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup_unsorted_input() -> Result<()> {
+        let (_, ts) = sorted_input(false)?;
+        let input = input()?;
+        let on = vec![col("key", &input.schema())?];
+        let exec = DedupExec::new(input, on, DedupKeep::First, vec![ts]);
+        let err = dedup(exec).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("requires its input to be sorted on the keys"));
+        Ok(())
+    }
+}
@@ -83,6 +83,15 @@ impl CsvExec {
     pub fn base_config(&self) -> &FileScanConfig {
         &self.base_config
     }
+    /// Read the columns of the table at the indices of `projection`, in its
+    /// order, instead of the ones of [`FileScanConfig::projection`]
+    pub fn with_projection(mut self, projection: Vec<usize>) -> Self {
+        self.base_config.projection = Some(projection);
+        let (projected_schema, projected_statistics) = self.base_config.project();
+        self.projected_schema = projected_schema;
+        self.projected_statistics = projected_statistics;
+        self
+    }
     /// true if the first line of each file is a header
     pub fn has_header(&self) -> bool {
         self.has_header
@@ -91,6 +100,10 @@ impl CsvExec {
     pub fn delimiter(&self) -> u8 {
         self.delimiter
     }
+    /// The compression of the files
+    pub fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type.clone()
+    }
 }
 
 impl ExecutionPlan for CsvExec {
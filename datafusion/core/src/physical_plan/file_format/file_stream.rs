@@ -25,25 +25,31 @@ use std::collections::VecDeque;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use arrow::datatypes::SchemaRef;
-use arrow::{error::Result as ArrowResult, record_batch::RecordBatch};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
 use futures::future::BoxFuture;
 use futures::stream::BoxStream;
 use futures::{ready, FutureExt, Stream, StreamExt};
+use log::warn;
 use object_store::ObjectStore;
 
 use datafusion_common::ScalarValue;
 
+use crate::config::{
+    OPT_OBJECT_STORE_MAX_RETRIES, OPT_OBJECT_STORE_RETRY_BACKOFF_MS,
+    OPT_SKIP_CORRUPT_FILES,
+};
 use crate::datasource::listing::PartitionedFile;
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::execution::context::TaskContext;
 use crate::physical_plan::file_format::{
-    FileMeta, FileScanConfig, PartitionColumnProjector,
+    FileMeta, FileScanConfig, PartitionColumnProjector, RetryObjectStore,
 };
 use crate::physical_plan::metrics::{
-    BaselineMetrics, ExecutionPlanMetricsSet, MetricBuilder, Time,
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, Time,
 };
 use crate::physical_plan::RecordBatchStream;
 
@@ -81,6 +87,19 @@ pub struct FileStream<F: FileOpener> {
     pc_projector: PartitionColumnProjector,
     /// the store from which to source the files.
     object_store: Arc<dyn ObjectStore>,
+    /// The path of the file being read, reported in errors
+    file_path: String,
+    /// The number of rows read from the file being read, reported in errors
+    file_rows: usize,
+    /// Whether the files failing to open or read are skipped instead of
+    /// failing the stream
+    skip_corrupt_files: bool,
+    /// The batches read from the file being read, held back until it is
+    /// fully read when corrupt files are skipped, so that the rows of a file
+    /// failing midway are dropped with it
+    file_batches: Vec<RecordBatch>,
+    /// The batches of the files fully read, to return
+    ready_batches: VecDeque<RecordBatch>,
     /// The stream state
     state: FileStreamState,
     /// File stream specific metrics
@@ -140,6 +159,10 @@ struct FileStreamMetrics {
     pub time_scanning: StartableTime,
     /// Time elapsed for data decompression + decoding
     pub time_processing: StartableTime,
+    /// Number of files skipped as they failed to open or read
+    pub files_skipped: Count,
+    /// Number of reads of the object store retried after transient errors
+    pub object_store_retries: Count,
 }
 
 impl FileStreamMetrics {
@@ -162,10 +185,18 @@ impl FileStreamMetrics {
             start: None,
         };
 
+        let files_skipped =
+            MetricBuilder::new(metrics).counter("files_skipped", partition);
+
+        let object_store_retries =
+            MetricBuilder::new(metrics).counter("object_store_retries", partition);
+
         Self {
             time_opening,
             time_scanning,
             time_processing,
+            files_skipped,
+            object_store_retries,
         }
     }
 }
@@ -187,9 +218,29 @@ impl<F: FileOpener> FileStream<F> {
 
         let files = config.file_groups[partition].clone();
 
-        let object_store = context
+        let file_stream_metrics = FileStreamMetrics::new(&metrics, partition);
+
+        let options = config.config_options.read();
+        let max_retries = options
+            .get_u64(OPT_OBJECT_STORE_MAX_RETRIES)
+            .unwrap_or_default() as usize;
+        let backoff = Duration::from_millis(
+            options
+                .get_u64(OPT_OBJECT_STORE_RETRY_BACKOFF_MS)
+                .unwrap_or_default(),
+        );
+        let skip_corrupt_files =
+            options.get_bool(OPT_SKIP_CORRUPT_FILES).unwrap_or_default();
+
+        let mut object_store = context
             .runtime_env()
             .object_store(&config.object_store_url)?;
+        if max_retries > 0 {
+            object_store = Arc::new(
+                RetryObjectStore::new(object_store, max_retries, backoff)
+                    .with_retry_count(file_stream_metrics.object_store_retries.clone()),
+            );
+        }
 
         Ok(Self {
             file_iter: files.into(),
@@ -198,17 +249,62 @@ impl<F: FileOpener> FileStream<F> {
             file_reader,
             pc_projector,
             object_store,
+            file_path: String::new(),
+            file_rows: 0,
+            skip_corrupt_files,
+            file_batches: vec![],
+            ready_batches: VecDeque::new(),
             state: FileStreamState::Idle,
-            file_stream_metrics: FileStreamMetrics::new(&metrics, partition),
+            file_stream_metrics,
             baseline_metrics: BaselineMetrics::new(&metrics, partition),
         })
     }
 
+    /// The context of the errors opening the file being read
+    fn open_error_context(&self) -> String {
+        format!("FileStream: opening file {}", self.file_path)
+    }
+
+    /// Skips the file being read after `error`, dropping the rows already
+    /// read from it, when corrupt files are skipped
+    fn skip_file(&mut self, error: &DataFusionError) {
+        warn!(
+            "Skipping file {}, dropping the {} rows read from it: {}",
+            self.file_path, self.file_rows, error
+        );
+        self.file_stream_metrics.files_skipped.add(1);
+        self.file_batches.clear();
+        self.state = FileStreamState::Idle;
+    }
+
+    /// Truncates `batch` to the remaining number of rows to return, if
+    /// there is a limit
+    fn apply_limit(&mut self, batch: RecordBatch) -> RecordBatch {
+        match &mut self.remain {
+            Some(remain) => {
+                if *remain > batch.num_rows() {
+                    *remain -= batch.num_rows();
+                    batch
+                } else {
+                    let batch = batch.slice(0, *remain);
+                    self.state = FileStreamState::Limit;
+                    self.ready_batches.clear();
+                    *remain = 0;
+                    batch
+                }
+            }
+            None => batch,
+        }
+    }
+
     fn poll_inner(
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<Option<ArrowResult<RecordBatch>>> {
         loop {
+            if let Some(batch) = self.ready_batches.pop_front() {
+                return Poll::Ready(Some(Ok(self.apply_limit(batch))));
+            }
             match &mut self.state {
                 FileStreamState::Idle => {
                     let part_file = match self.file_iter.pop_front() {
@@ -216,6 +312,8 @@ impl<F: FileOpener> FileStream<F> {
                         None => return Poll::Ready(None),
                     };
 
+                    self.file_path = part_file.object_meta.location.to_string();
+                    self.file_rows = 0;
                     let file_meta = FileMeta {
                         object_meta: part_file.object_meta,
                         range: part_file.range,
@@ -232,6 +330,12 @@ impl<F: FileOpener> FileStream<F> {
                             }
                         }
                         Err(e) => {
+                            let e = e.with_runtime_context(self.open_error_context());
+                            self.file_stream_metrics.time_opening.stop();
+                            if self.skip_corrupt_files {
+                                self.skip_file(&e);
+                                continue;
+                            }
                             self.state = FileStreamState::Error;
                             return Poll::Ready(Some(Err(e.into())));
                         }
@@ -250,6 +354,12 @@ impl<F: FileOpener> FileStream<F> {
                         };
                     }
                     Err(e) => {
+                        let e = e.with_runtime_context(self.open_error_context());
+                        self.file_stream_metrics.time_opening.stop();
+                        if self.skip_corrupt_files {
+                            self.skip_file(&e);
+                            continue;
+                        }
                         self.state = FileStreamState::Error;
                         return Poll::Ready(Some(Err(e.into())));
                     }
@@ -257,34 +367,50 @@ impl<F: FileOpener> FileStream<F> {
                 FileStreamState::Scan {
                     reader,
                     partition_values,
-                } => match ready!(reader.poll_next_unpin(cx)) {
+                } => match ready!(self
+                    .baseline_metrics
+                    .record_input_poll(reader.poll_next_unpin(cx)))
+                {
                     Some(result) => {
                         self.file_stream_metrics.time_scanning.stop();
+                        let result = match result {
+                            Ok(batch) => Ok(batch),
+                            Err(e) => {
+                                let e = DataFusionError::from(e).with_runtime_context(
+                                    format!(
+                                        "FileStream: reading file {} after {} rows",
+                                        self.file_path, self.file_rows
+                                    ),
+                                );
+                                if self.skip_corrupt_files {
+                                    self.skip_file(&e);
+                                    continue;
+                                }
+                                Err(ArrowError::from(e))
+                            }
+                        };
                         let result = result
-                            .and_then(|b| self.pc_projector.project(b, partition_values))
-                            .map(|batch| match &mut self.remain {
-                                Some(remain) => {
-                                    if *remain > batch.num_rows() {
-                                        *remain -= batch.num_rows();
-                                        batch
-                                    } else {
-                                        let batch = batch.slice(0, *remain);
-                                        self.state = FileStreamState::Limit;
-                                        *remain = 0;
-                                        batch
-                                    }
+                            .and_then(|b| self.pc_projector.project(b, partition_values));
+                        let result = match result {
+                            Ok(batch) => {
+                                self.file_rows += batch.num_rows();
+                                if self.skip_corrupt_files {
+                                    self.file_batches.push(batch);
+                                    continue;
                                 }
-                                None => batch,
-                            });
-
-                        if result.is_err() {
-                            self.state = FileStreamState::Error
-                        }
+                                Ok(self.apply_limit(batch))
+                            }
+                            Err(e) => {
+                                self.state = FileStreamState::Error;
+                                Err(e)
+                            }
+                        };
 
                         return Poll::Ready(Some(result));
                     }
                     None => {
                         self.file_stream_metrics.time_scanning.stop();
+                        self.ready_batches.extend(self.file_batches.drain(..));
                         self.state = FileStreamState::Idle;
                     }
                 },
@@ -303,6 +429,7 @@ impl<F: FileOpener> Stream for FileStream<F> {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         self.file_stream_metrics.time_processing.start();
         let result = self.poll_inner(cx);
         self.file_stream_metrics.time_processing.stop();
@@ -332,6 +459,8 @@ mod tests {
 
     struct TestOpener {
         records: Vec<RecordBatch>,
+        /// The message of an error following the records, if any
+        error: Option<String>,
     }
 
     impl FileOpener for TestOpener {
@@ -340,18 +469,39 @@ mod tests {
             _store: Arc<dyn ObjectStore>,
             _file_meta: FileMeta,
         ) -> Result<FileOpenFuture> {
-            let iterator = self.records.clone().into_iter().map(Ok);
+            let error = self
+                .error
+                .clone()
+                .map(|message| Err(ArrowError::ComputeError(message)));
+            let iterator = self.records.clone().into_iter().map(Ok).chain(error);
             let stream = futures::stream::iter(iterator).boxed();
             Ok(futures::future::ready(Ok(stream)).boxed())
         }
     }
 
     /// helper that creates a stream of 2 files with the same pair of batches in each ([0,1,2] and [0,1])
-    async fn create_and_collect(limit: Option<usize>) -> Vec<RecordBatch> {
+    fn create_stream(
+        limit: Option<usize>,
+        error: Option<String>,
+    ) -> FileStream<TestOpener> {
+        create_stream_with_options(
+            limit,
+            error,
+            ConfigOptions::new(),
+            ExecutionPlanMetricsSet::new(),
+        )
+    }
+
+    fn create_stream_with_options(
+        limit: Option<usize>,
+        error: Option<String>,
+        options: ConfigOptions,
+        metrics: ExecutionPlanMetricsSet,
+    ) -> FileStream<TestOpener> {
         let records = vec![make_partition(3), make_partition(2)];
         let file_schema = records[0].schema();
 
-        let reader = TestOpener { records };
+        let reader = TestOpener { records, error };
 
         let ctx = SessionContext::new();
         register_test_store(&ctx, &[("mock_file1", 10), ("mock_file2", 20)]);
@@ -367,19 +517,14 @@ mod tests {
             projection: None,
             limit,
             table_partition_cols: vec![],
-            config_options: ConfigOptions::new().into_shareable(),
+            config_options: options.into_shareable(),
         };
 
-        let file_stream = FileStream::new(
-            &config,
-            0,
-            ctx.task_ctx(),
-            reader,
-            ExecutionPlanMetricsSet::new(),
-        )
-        .unwrap();
+        FileStream::new(&config, 0, ctx.task_ctx(), reader, metrics).unwrap()
+    }
 
-        file_stream
+    async fn create_and_collect(limit: Option<usize>) -> Vec<RecordBatch> {
+        create_stream(limit, None)
             .map(|b| b.expect("No error expected in stream"))
             .collect::<Vec<_>>()
             .await
@@ -448,4 +593,74 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn error_with_file_context() -> Result<()> {
+        let mut results = create_stream(None, Some("bad value".to_string()))
+            .collect::<Vec<_>>()
+            .await;
+        // the error stops the stream after the batches of the first file
+        assert_eq!(results.len(), 3);
+        let err = DataFusionError::from(results.pop().unwrap().unwrap_err());
+        assert_eq!(err.to_string(), "Arrow error: Compute error: bad value");
+        assert_eq!(
+            err.runtime_context(),
+            vec!["FileStream: reading file mock_file1 after 5 rows"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_corrupt_files() -> Result<()> {
+        let mut options = ConfigOptions::new();
+        options.set_bool(OPT_SKIP_CORRUPT_FILES, true);
+        let metrics = ExecutionPlanMetricsSet::new();
+        let batches = create_stream_with_options(
+            None,
+            Some("bad value".to_string()),
+            options,
+            metrics.clone(),
+        )
+        .map(|b| b.expect("Errors are skipped"))
+        .collect::<Vec<_>>()
+        .await;
+        // the batches of both files before their errors are dropped
+        assert!(batches.is_empty());
+        let skipped = metrics
+            .clone_inner()
+            .sum(|metric| metric.value().name() == "files_skipped")
+            .map(|value| value.as_usize());
+        assert_eq!(skipped, Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_corrupt_files_with_limit() -> Result<()> {
+        let mut options = ConfigOptions::new();
+        options.set_bool(OPT_SKIP_CORRUPT_FILES, true);
+        let batches = create_stream_with_options(
+            Some(6),
+            None,
+            options,
+            ExecutionPlanMetricsSet::new(),
+        )
+        .map(|b| b.expect("No error expected in stream"))
+        .collect::<Vec<_>>()
+        .await;
+        // the batches of the files are returned once they are fully read
+        #[rustfmt::skip]
+        crate::assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 0 |",
+            "| 1 |",
+            "| 2 |",
+            "| 0 |",
+            "| 1 |",
+            "| 0 |",
+            "+---+",
+        ], &batches);
+        Ok(())
+    }
 }
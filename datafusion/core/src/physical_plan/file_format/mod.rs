@@ -25,12 +25,14 @@ mod delimited_stream;
 mod file_stream;
 mod json;
 mod parquet;
+mod retry_store;
 mod row_filter;
 
 pub(crate) use self::csv::plan_to_csv;
 pub use self::csv::CsvExec;
 pub(crate) use self::parquet::plan_to_parquet;
 pub use self::parquet::{ParquetExec, ParquetFileMetrics, ParquetFileReaderFactory};
+pub use self::retry_store::RetryObjectStore;
 use arrow::{
     array::{ArrayData, ArrayRef, DictionaryArray},
     buffer::Buffer,
@@ -39,6 +41,7 @@ use arrow::{
     record_batch::RecordBatch,
 };
 pub use avro::AvroExec;
+pub(crate) use delimited_stream::newline_delimited_stream;
 pub use file_stream::{FileOpenFuture, FileOpener, FileStream};
 pub(crate) use json::plan_to_json;
 pub use json::NdJsonExec;
@@ -50,7 +53,7 @@ use crate::{
     error::{DataFusionError, Result},
     scalar::ScalarValue,
 };
-use arrow::array::{new_null_array, UInt16BufferBuilder};
+use arrow::array::{make_array, new_null_array, StructArray, UInt16BufferBuilder};
 use arrow::record_batch::RecordBatchOptions;
 use lazy_static::lazy_static;
 use log::info;
@@ -291,7 +294,10 @@ impl SchemaAdapter {
             if let Some((batch_idx, _name)) =
                 batch_schema.column_with_name(table_field.name().as_str())
             {
-                cols.push(batch_cols[batch_idx].clone());
+                cols.push(fill_missing_struct_fields(
+                    batch_cols[batch_idx].clone(),
+                    table_field.data_type(),
+                )?);
             } else {
                 cols.push(new_null_array(table_field.data_type(), batch_rows))
             }
@@ -310,6 +316,46 @@ impl SchemaAdapter {
     }
 }
 
+/// Adds the fields of the `target` struct type that are missing from `array`,
+/// e.g. because they were not read from the file, as null columns. Arrays of
+/// any other type are returned unchanged.
+fn fill_missing_struct_fields(array: ArrayRef, target: &DataType) -> Result<ArrayRef> {
+    let target_fields = match (array.data_type(), target) {
+        (DataType::Struct(_), DataType::Struct(fields))
+            if array.data_type() != target =>
+        {
+            fields
+        }
+        _ => return Ok(array),
+    };
+    let struct_array = array
+        .as_any()
+        .downcast_ref::<StructArray>()
+        .expect("struct array");
+    let data = array.data();
+
+    let child_data = target_fields
+        .iter()
+        .map(|field| {
+            let child = match struct_array.column_by_name(field.name()) {
+                Some(child) => {
+                    fill_missing_struct_fields(child.clone(), field.data_type())?
+                }
+                None => new_null_array(field.data_type(), data.offset() + data.len()),
+            };
+            Ok(child.data().clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let filled = ArrayData::builder(target.clone())
+        .len(data.len())
+        .offset(data.offset())
+        .null_bit_buffer(data.null_buffer().cloned())
+        .child_data(child_data)
+        .build()?;
+    Ok(make_array(filled))
+}
+
 /// A helper that projects partition columns into the file record batches.
 ///
 /// One interesting trick is the usage of a cache for the key buffers of the partition column
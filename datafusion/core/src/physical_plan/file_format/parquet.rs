@@ -37,7 +37,7 @@ use crate::physical_plan::file_format::FileMeta;
 use crate::{
     error::{DataFusionError, Result},
     execution::context::{SessionState, TaskContext},
-    physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
+    physical_optimizer::pruning::{field_with_path, PruningPredicate, PruningStatistics},
     physical_plan::{
         expressions::PhysicalSortExpr,
         file_format::{FileScanConfig, SchemaAdapter},
@@ -73,7 +73,7 @@ use parquet::file::{
     statistics::Statistics as ParquetStatistics,
 };
 use parquet::format::PageLocation;
-use parquet::schema::types::ColumnDescriptor;
+use parquet::schema::types::{ColumnDescriptor, SchemaDescriptor};
 
 /// Execution plan for scanning one or more Parquet partitions
 #[derive(Debug, Clone)]
@@ -89,6 +89,8 @@ pub struct ParquetExec {
     metadata_size_hint: Option<usize>,
     /// Optional user defined parquet file reader factory
     parquet_file_reader_factory: Option<Arc<dyn ParquetFileReaderFactory>>,
+    /// Paths of the struct fields to read, see [`Self::with_nested_projection`]
+    nested_projection: Vec<Vec<String>>,
 }
 
 impl ParquetExec {
@@ -129,6 +131,7 @@ impl ParquetExec {
             pruning_predicate,
             metadata_size_hint,
             parquet_file_reader_factory: None,
+            nested_projection: vec![],
         }
     }
 
@@ -142,6 +145,11 @@ impl ParquetExec {
         self.pruning_predicate.as_ref()
     }
 
+    /// Optional hint for the size of the parquet metadata
+    pub fn metadata_size_hint(&self) -> Option<usize> {
+        self.metadata_size_hint
+    }
+
     /// Optional user defined parquet file reader factory.
     ///
     /// `ParquetFileReaderFactory` complements `TableProvider`, It enables users to provide custom
@@ -157,6 +165,31 @@ impl ParquetExec {
         self
     }
 
+    /// Read the columns of the table at the indices of `projection`, in its
+    /// order, instead of the ones of [`FileScanConfig::projection`]
+    pub fn with_projection(mut self, projection: Vec<usize>) -> Self {
+        self.base_config.projection = Some(projection);
+        let (projected_schema, projected_statistics) = self.base_config.project();
+        self.projected_schema = projected_schema;
+        self.projected_statistics = projected_statistics;
+        self
+    }
+
+    /// Only read the given fields of struct columns, as paths of field names
+    /// starting with the name of the column, e.g. `["meta", "country"]`.
+    ///
+    /// Struct columns without any path are read in full. The fields that are
+    /// not read are filled with nulls, so the output schema does not change.
+    pub fn with_nested_projection(mut self, nested_projection: Vec<Vec<String>>) -> Self {
+        self.nested_projection = nested_projection;
+        self
+    }
+
+    /// Return the value described in [`Self::with_nested_projection`]
+    pub fn nested_projection(&self) -> &[Vec<String>] {
+        &self.nested_projection
+    }
+
     /// If true, any filter [`Expr`]s on the scan will converted to a
     /// [`RowFilter`](parquet::arrow::arrow_reader::RowFilter) in the
     /// `ParquetRecordBatchStream`. These filters are applied by the
@@ -336,6 +369,7 @@ impl ExecutionPlan for ParquetExec {
             pushdown_filters: self.pushdown_filters(),
             reorder_filters: self.reorder_filters(),
             enable_page_index: self.enable_page_index(),
+            nested_projection: Arc::from(self.nested_projection.clone()),
         };
 
         let stream = FileStream::new(
@@ -364,7 +398,7 @@ impl ExecutionPlan for ParquetExec {
                         super::FileGroupsDisplay(&self.base_config.file_groups),
                         pre.predicate_expr(),
                         super::ProjectSchemaDisplay(&self.projected_schema),
-                    )
+                    )?;
                 } else {
                     write!(
                         f,
@@ -372,8 +406,17 @@ impl ExecutionPlan for ParquetExec {
                         self.base_config.limit,
                         super::FileGroupsDisplay(&self.base_config.file_groups),
                         super::ProjectSchemaDisplay(&self.projected_schema),
-                    )
+                    )?;
+                }
+                if !self.nested_projection.is_empty() {
+                    let paths = self
+                        .nested_projection
+                        .iter()
+                        .map(|path| path.join("."))
+                        .collect::<Vec<_>>();
+                    write!(f, ", nested_projection=[{}]", paths.join(", "))?;
                 }
+                Ok(())
             }
         }
     }
@@ -400,6 +443,7 @@ struct ParquetOpener {
     pushdown_filters: bool,
     reorder_filters: bool,
     enable_page_index: bool,
+    nested_projection: Arc<[Vec<String>]>,
 }
 
 impl FileOpener for ParquetOpener {
@@ -432,6 +476,7 @@ impl FileOpener for ParquetOpener {
         let reorder_predicates = self.reorder_filters;
         let pushdown_filters = self.pushdown_filters;
         let enable_page_index = self.enable_page_index;
+        let nested_projection = self.nested_projection.clone();
 
         Ok(Box::pin(async move {
             let options = ArrowReaderOptions::new().with_page_index(enable_page_index);
@@ -441,10 +486,18 @@ impl FileOpener for ParquetOpener {
             let adapted_projections =
                 schema_adapter.map_projections(builder.schema(), &projection)?;
 
-            let mask = ProjectionMask::roots(
-                builder.parquet_schema(),
-                adapted_projections.iter().cloned(),
-            );
+            let mask = if nested_projection.is_empty() {
+                ProjectionMask::roots(
+                    builder.parquet_schema(),
+                    adapted_projections.iter().cloned(),
+                )
+            } else {
+                nested_projection_mask(
+                    builder.parquet_schema(),
+                    &adapted_projections,
+                    &nested_projection,
+                )
+            };
 
             if let Some(predicate) = pushdown_filters
                 .then(|| pruning_predicate.as_ref().map(|p| p.logical_expr()))
@@ -521,6 +574,38 @@ impl FileOpener for ParquetOpener {
     }
 }
 
+/// Selects the leaves of the `roots` columns that are within one of the
+/// `nested_projection` paths, or all leaves of a column that has no path or
+/// whose paths match none of its leaves
+fn nested_projection_mask(
+    schema: &SchemaDescriptor,
+    roots: &[usize],
+    nested_projection: &[Vec<String>],
+) -> ProjectionMask {
+    let mut leaves = vec![];
+    for root in roots {
+        let root_leaves = (0..schema.num_columns())
+            .filter(|leaf| schema.get_column_root_idx(*leaf) == *root)
+            .collect::<Vec<_>>();
+        let selected = root_leaves
+            .iter()
+            .filter(|leaf| {
+                let leaf_path = schema.column(**leaf).path().parts();
+                nested_projection
+                    .iter()
+                    .any(|path| leaf_path.starts_with(path))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            leaves.extend(root_leaves);
+        } else {
+            leaves.extend(selected);
+        }
+    }
+    ProjectionMask::leaves(schema, leaves)
+}
+
 // Check PruningPredicates just work on one column.
 fn check_page_index_push_down_valid(predicate: &Option<PruningPredicate>) -> bool {
     if let Some(predicate) = predicate {
@@ -785,13 +870,12 @@ macro_rules! get_statistic {
 // Extract the min or max value calling `func` or `bytes_func` on the ParquetStatistics as appropriate
 macro_rules! get_min_max_values {
     ($self:expr, $column:expr, $func:ident, $bytes_func:ident) => {{
-        let (_column_index, field) =
-            if let Some((v, f)) = $self.parquet_schema.column_with_name(&$column.name) {
-                (v, f)
-            } else {
-                // Named column was not present
-                return None;
-            };
+        let field = if let Some(f) = field_with_path($self.parquet_schema, &$column.name) {
+            f
+        } else {
+            // Named column was not present
+            return None;
+        };
 
         let data_type = field.data_type();
         // The result may be None, because DataFusion doesn't have support for ScalarValues of the column type
@@ -800,7 +884,7 @@ macro_rules! get_min_max_values {
         $self.row_group_metadata
             .columns()
             .iter()
-            .find(|c| c.column_descr().name() == &$column.name)
+            .find(|c| c.column_descr().path().string() == $column.name)
             .and_then(|c| if c.statistics().is_some() {Some((c.statistics().unwrap(), c.column_descr()))} else {None})
             .map(|(stats, column_descr)|
                 {
@@ -822,7 +906,7 @@ macro_rules! get_null_count_values {
                 .row_group_metadata
                 .columns()
                 .iter()
-                .find(|c| c.column_descr().name() == &$column.name)
+                .find(|c| c.column_descr().path().string() == $column.name)
             {
                 col.statistics().map(|s| s.null_count())
             } else {
@@ -1178,7 +1262,7 @@ mod tests {
     use arrow::datatypes::DataType::Decimal128;
     use arrow::record_batch::RecordBatch;
     use arrow::{
-        array::{Int64Array, Int8Array, StringArray},
+        array::{Int64Array, Int8Array, StringArray, StructArray},
         datatypes::{DataType, Field},
     };
     use chrono::{TimeZone, Utc};
@@ -1467,6 +1551,38 @@ mod tests {
         assert_batches_sorted_eq!(expected, &read);
     }
 
+    #[tokio::test]
+    async fn struct_field_filter_with_filter_pushdown() {
+        use datafusion_expr::expr::GetIndexedField;
+
+        let c1: ArrayRef = Arc::new(StringArray::from(vec!["a", "b", "c"]));
+        let meta: ArrayRef = Arc::new(StructArray::from(vec![
+            (
+                Field::new("id", DataType::Int32, true),
+                Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef,
+            ),
+            (
+                Field::new("name", DataType::Utf8, true),
+                Arc::new(StringArray::from(vec!["x", "y", "z"])) as ArrayRef,
+            ),
+        ]));
+        let batch = create_batch(vec![("c1", c1), ("meta", meta)]);
+
+        // the statistics can't prune the single row group: the rows are
+        // filtered by the row filter, which reads the `meta.id` leaf
+        let meta_id = Expr::GetIndexedField(GetIndexedField::new(
+            Box::new(col("meta")),
+            ScalarValue::from("id"),
+        ));
+        let filter = meta_id.gt_eq(lit(2)).and(col("c1").not_eq(lit("c")));
+        let read =
+            round_trip_to_parquet(vec![batch], Some(vec![0]), None, Some(filter), true)
+                .await
+                .unwrap();
+        let expected = vec!["+----+", "| c1 |", "+----+", "| b  |", "+----+"];
+        assert_batches_sorted_eq!(expected, &read);
+    }
+
     #[tokio::test]
     async fn evolved_schema_projection() {
         let c1: ArrayRef =
@@ -2086,6 +2202,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn row_group_pruning_predicate_struct_field() {
+        use datafusion_expr::{col, lit, GetIndexedField};
+        use parquet::schema::types::Type as SchemaType;
+        // meta['id'] > 15 => meta.id_max > 15
+        let expr = Expr::GetIndexedField(GetIndexedField::new(
+            Box::new(col("meta")),
+            ScalarValue::from("id"),
+        ))
+        .gt(lit(15));
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "meta",
+                DataType::Struct(vec![Field::new("id", DataType::Int32, false)]),
+                false,
+            ),
+        ]);
+        let pruning_predicate =
+            PruningPredicate::try_new(expr, Arc::new(schema)).unwrap();
+
+        // the leaf `meta.id` has the same name as the top level `id`
+        let id = Arc::new(
+            SchemaType::primitive_type_builder("id", PhysicalType::INT32)
+                .build()
+                .unwrap(),
+        );
+        let meta = Arc::new(
+            SchemaType::group_type_builder("meta")
+                .with_fields(&mut vec![id.clone()])
+                .build()
+                .unwrap(),
+        );
+        let schema = SchemaType::group_type_builder("schema")
+            .with_fields(&mut vec![id, meta])
+            .build()
+            .unwrap();
+        let schema_descr = Arc::new(SchemaDescriptor::new(Arc::new(schema)));
+        let rgm1 = get_row_group_meta_data(
+            &schema_descr,
+            vec![
+                ParquetStatistics::int32(Some(11), Some(20), None, 0, false),
+                ParquetStatistics::int32(Some(1), Some(10), None, 0, false),
+            ],
+        );
+        let rgm2 = get_row_group_meta_data(
+            &schema_descr,
+            vec![
+                ParquetStatistics::int32(Some(1), Some(10), None, 0, false),
+                ParquetStatistics::int32(Some(11), Some(20), None, 0, false),
+            ],
+        );
+
+        let metrics = parquet_file_metrics();
+        assert_eq!(
+            prune_row_groups(&[rgm1, rgm2], None, Some(pruning_predicate), &metrics),
+            vec![1]
+        );
+    }
+
     #[test]
     fn row_group_pruning_predicate_decimal_type() {
         // For the decimal data type, parquet can use `INT32`, `INT64`, `BYTE_ARRAY`, `FIXED_LENGTH_BYTE_ARRAY` to
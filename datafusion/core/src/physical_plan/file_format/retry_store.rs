@@ -0,0 +1,383 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! An [`ObjectStore`] retrying the reads failing with transient errors
+
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use log::warn;
+use object_store::path::Path;
+use object_store::{GetResult, ListResult, ObjectMeta, ObjectStore};
+use object_store::{MultipartId, Result};
+use tokio::io::AsyncWrite;
+
+use crate::physical_plan::metrics::Count;
+
+/// Wraps an [`ObjectStore`] and retries its reads failing with transient
+/// errors, such as the 503 responses of cloud object stores, waiting longer
+/// before each retry
+///
+/// The `get`, `get_range` and `head` requests failing with a transient
+/// [`object_store::Error::Generic`] error, such as a server error, a
+/// throttling response, a timeout or a connection failure, are retried.
+/// Denied accesses, missing files, invalid paths, writes and the errors of
+/// the stream of a `get` are not.
+#[derive(Debug)]
+pub struct RetryObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    max_retries: usize,
+    backoff: Duration,
+    retries: Count,
+}
+
+impl RetryObjectStore {
+    /// Retries the reads of `inner` up to `max_retries` times, waiting
+    /// `backoff` before the first retry, and twice as long as before the
+    /// previous retry before the next ones
+    pub fn new(
+        inner: Arc<dyn ObjectStore>,
+        max_retries: usize,
+        backoff: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            max_retries,
+            backoff,
+            retries: Count::new(),
+        }
+    }
+
+    /// Counts the retries with `retries`, such as a metric of the scan
+    /// reading the store
+    pub fn with_retry_count(mut self, retries: Count) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// The number of retried requests
+    pub fn retries(&self) -> usize {
+        self.retries.value()
+    }
+
+    async fn retry<T, F, Fut>(&self, location: &Path, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = self.backoff;
+        let mut retries = 0;
+        loop {
+            match request().await {
+                Err(e) if retries < self.max_retries && is_transient(&e) => {
+                    retries += 1;
+                    self.retries.add(1);
+                    warn!(
+                        "Retrying read of {} in {:?} ({} of {}): {}",
+                        location, backoff, retries, self.max_retries, e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// The messages of the transient errors of the object stores, lowercased:
+/// the server errors, the throttling responses, the timeouts and the
+/// connection failures
+const TRANSIENT_ERRORS: &[&str] = &[
+    "server error",
+    "500 internal server error",
+    "502 bad gateway",
+    "503 service unavailable",
+    "429 too many requests",
+    "slowdown",
+    "timed out",
+    "timeout",
+    "connection reset",
+    "connection closed",
+    "connection refused",
+    "broken pipe",
+    "error sending request",
+];
+
+/// Whether `error` is transient, such that the request may succeed if
+/// retried
+///
+/// The object stores report the failed requests as
+/// [`object_store::Error::Generic`] errors, whose source is private to
+/// each store, so the errors are classified by the messages of their
+/// sources: the client errors, such as the 403 responses to requests with
+/// invalid credentials, are not transient.
+fn is_transient(error: &object_store::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = match error {
+        object_store::Error::Generic { source, .. } => Some(source.as_ref()),
+        _ => return false,
+    };
+    while let Some(error) = source {
+        let message = error.to_string().to_lowercase();
+        if TRANSIENT_ERRORS.iter().any(|m| message.contains(m)) {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl Display for RetryObjectStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RetryObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for RetryObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.retry(location, || self.inner.get(location)).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.retry(location, || self.inner.get_range(location, range.clone()))
+            .await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.retry(location, || self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails the first `failures` requests reading a file with `message`
+    #[derive(Debug)]
+    struct FlakyStore {
+        inner: InMemory,
+        failures: AtomicUsize,
+        message: &'static str,
+    }
+
+    impl FlakyStore {
+        fn fail(&self) -> Result<()> {
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures == 0 {
+                return Ok(());
+            }
+            self.failures.store(failures - 1, Ordering::SeqCst);
+            Err(object_store::Error::Generic {
+                store: "flaky",
+                source: self.message.into(),
+            })
+        }
+    }
+
+    impl Display for FlakyStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyStore")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for FlakyStore {
+        async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+            self.inner.put(location, bytes).await
+        }
+
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.inner.put_multipart(location).await
+        }
+
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> Result<()> {
+            self.inner.abort_multipart(location, multipart_id).await
+        }
+
+        async fn get(&self, location: &Path) -> Result<GetResult> {
+            self.fail()?;
+            self.inner.get(location).await
+        }
+
+        async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+            self.fail()?;
+            self.inner.get_range(location, range).await
+        }
+
+        async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+            self.fail()?;
+            self.inner.head(location).await
+        }
+
+        async fn delete(&self, location: &Path) -> Result<()> {
+            self.inner.delete(location).await
+        }
+
+        async fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+            self.inner.list(prefix).await
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    fn flaky_store(failures: usize) -> Arc<FlakyStore> {
+        flaky_store_with_message(failures, "503 Service Unavailable")
+    }
+
+    fn flaky_store_with_message(
+        failures: usize,
+        message: &'static str,
+    ) -> Arc<FlakyStore> {
+        Arc::new(FlakyStore {
+            inner: InMemory::new(),
+            failures: AtomicUsize::new(failures),
+            message,
+        })
+    }
+
+    #[tokio::test]
+    async fn retry_transient_errors() {
+        let location = Path::parse("test").unwrap();
+        let flaky = flaky_store(0);
+        flaky.put(&location, Bytes::from("hello")).await.unwrap();
+
+        flaky.failures.store(2, Ordering::SeqCst);
+        let store = RetryObjectStore::new(flaky.clone(), 2, Duration::ZERO);
+        let bytes = store.get_range(&location, 1..3).await.unwrap();
+        assert_eq!(bytes, Bytes::from("el"));
+        assert_eq!(store.retries(), 2);
+
+        flaky.failures.store(3, Ordering::SeqCst);
+        let err = store.head(&location).await.unwrap_err();
+        assert!(err.to_string().contains("503 Service Unavailable"));
+        assert_eq!(store.retries(), 4);
+    }
+
+    #[tokio::test]
+    async fn do_not_retry_denied_accesses() {
+        let location = Path::parse("test").unwrap();
+        let flaky = flaky_store_with_message(
+            0,
+            "HTTP status client error (403 Forbidden) for url (https://bucket/test)",
+        );
+        flaky.put(&location, Bytes::from("hello")).await.unwrap();
+
+        flaky.failures.store(1, Ordering::SeqCst);
+        let store = RetryObjectStore::new(flaky, 2, Duration::ZERO);
+        let err = store.get_range(&location, 1..3).await.unwrap_err();
+        assert!(err.to_string().contains("403 Forbidden"));
+        assert_eq!(store.retries(), 0);
+    }
+
+    #[test]
+    fn transient_errors() {
+        let error = |message: &'static str| object_store::Error::Generic {
+            store: "S3",
+            source: message.into(),
+        };
+        assert!(is_transient(&error(
+            "HTTP status server error (503 Service Unavailable) for url (https://b/t)"
+        )));
+        assert!(is_transient(&error("operation timed out")));
+        assert!(is_transient(&error("429 Too Many Requests")));
+        assert!(!is_transient(&error("401 Unauthorized")));
+        assert!(!is_transient(&error("Missing region")));
+        assert!(!is_transient(&object_store::Error::NotFound {
+            path: "t".to_string(),
+            source: "404".into(),
+        }));
+    }
+
+    #[tokio::test]
+    async fn do_not_retry_missing_files() {
+        let store = RetryObjectStore::new(flaky_store(0), 2, Duration::ZERO);
+        let location = Path::parse("missing").unwrap();
+        let err = store.get(&location).await.unwrap_err();
+        assert!(matches!(err, object_store::Error::NotFound { .. }));
+        assert_eq!(store.retries(), 0);
+    }
+}
@@ -20,6 +20,7 @@ use arrow::datatypes::{DataType, Field, Schema};
 use arrow::error::{ArrowError, Result as ArrowResult};
 use arrow::record_batch::RecordBatch;
 use datafusion_common::{Column, DataFusionError, Result, ScalarValue, ToDFSchema};
+use datafusion_expr::expr::GetIndexedField;
 use datafusion_expr::expr_rewriter::{ExprRewritable, ExprRewriter, RewriteRecursion};
 
 use datafusion_expr::Expr;
@@ -29,6 +30,7 @@ use datafusion_physical_expr::{create_physical_expr, PhysicalExpr};
 use parquet::arrow::arrow_reader::{ArrowPredicate, RowFilter};
 use parquet::arrow::ProjectionMask;
 use parquet::file::metadata::ParquetMetaData;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 /// This module contains utilities for enabling the pushdown of DataFusion filter predicates (which
@@ -51,7 +53,8 @@ use std::sync::Arc;
 ///     1. Recursively break conjunctions into separate predicates. An expression like `a = 1 AND (b = 2 AND c = 3)` would be
 ///        separated into the expressions `a = 1`, `b = 2`, and `c = 3`.
 ///     2. Determine whether each predicate is suitable as an `ArrowPredicate`. As long as the predicate does not reference any projected columns
-///        or columns with non-primitive types, then it is considered suitable.
+///        or columns with non-primitive types, then it is considered suitable. The primitive fields of struct columns, accessed by name
+///        as in `s['a']['b']`, are read from their own leaf columns.
 ///     3. Determine, for each predicate, the total compressed size of all columns required to evaluate the predicate.
 ///     4. Determine, for each predicate, whether all columns required to evaluate the expression are sorted.
 ///     5. Re-order the predicate by total size (from step 3).
@@ -71,22 +74,20 @@ pub(crate) struct DatafusionArrowPredicate {
 impl DatafusionArrowPredicate {
     pub fn try_new(
         candidate: FilterCandidate,
-        schema: &Schema,
         metadata: &ParquetMetaData,
     ) -> Result<Self> {
         let props = ExecutionProps::default();
 
-        let schema = schema.project(&candidate.projection)?;
-        let df_schema = schema.clone().to_dfschema()?;
+        let df_schema = candidate.schema.clone().to_dfschema()?;
 
         let physical_expr =
-            create_physical_expr(&candidate.expr, &df_schema, &schema, &props)?;
+            create_physical_expr(&candidate.expr, &df_schema, &candidate.schema, &props)?;
 
         Ok(Self {
             physical_expr,
-            projection: ProjectionMask::roots(
+            projection: ProjectionMask::leaves(
                 metadata.file_metadata().schema_descr(),
-                candidate.projection,
+                candidate.leaves,
             ),
         })
     }
@@ -127,7 +128,11 @@ pub(crate) struct FilterCandidate {
     expr: Expr,
     required_bytes: usize,
     can_use_index: bool,
-    projection: Vec<usize>,
+    /// The parquet leaf columns read to evaluate the expression
+    leaves: Vec<usize>,
+    /// The schema of the batches read from `leaves`, in which the struct
+    /// columns only have the fields read
+    schema: Schema,
 }
 
 /// Helper to build a `FilterCandidate`. This will do several things
@@ -141,7 +146,9 @@ struct FilterCandidateBuilder<'a> {
     expr: Expr,
     file_schema: &'a Schema,
     table_schema: &'a Schema,
-    required_column_indices: Vec<usize>,
+    /// The paths of the columns and struct fields read by the expression,
+    /// e.g. `["c"]` or `["s", "a", "b"]`
+    required_paths: BTreeSet<Vec<String>>,
     non_primitive_columns: bool,
     projected_columns: bool,
 }
@@ -152,7 +159,7 @@ impl<'a> FilterCandidateBuilder<'a> {
             expr,
             file_schema,
             table_schema,
-            required_column_indices: vec![],
+            required_paths: BTreeSet::new(),
             non_primitive_columns: false,
             projected_columns: false,
         }
@@ -166,27 +173,55 @@ impl<'a> FilterCandidateBuilder<'a> {
         let expr = expr.rewrite(&mut self)?;
 
         if self.non_primitive_columns || self.projected_columns {
-            Ok(None)
-        } else {
-            let required_bytes =
-                size_of_columns(&self.required_column_indices, metadata)?;
-            let can_use_index = columns_sorted(&self.required_column_indices, metadata)?;
-
-            Ok(Some(FilterCandidate {
-                expr,
-                required_bytes,
-                can_use_index,
-                projection: self.required_column_indices,
-            }))
+            return Ok(None);
         }
+
+        let paths: BTreeSet<String> = self
+            .required_paths
+            .iter()
+            .map(|path| path.join("."))
+            .collect();
+        let schema_descr = metadata.file_metadata().schema_descr();
+        let leaves: Vec<usize> = (0..schema_descr.num_columns())
+            .filter(|idx| paths.contains(&schema_descr.column(*idx).path().string()))
+            .collect();
+        if leaves.len() != paths.len() {
+            // the parquet schema does not have a leaf for each path
+            return Ok(None);
+        }
+
+        let required_bytes = size_of_columns(&leaves, metadata)?;
+        let can_use_index = columns_sorted(&leaves, metadata)?;
+
+        Ok(Some(FilterCandidate {
+            expr,
+            required_bytes,
+            can_use_index,
+            leaves,
+            schema: project_paths(self.file_schema, &self.required_paths),
+        }))
     }
 }
 
 impl<'a> ExprRewriter for FilterCandidateBuilder<'a> {
     fn pre_visit(&mut self, expr: &Expr) -> Result<RewriteRecursion> {
+        if let Some(path) = struct_field_path(expr) {
+            if self.file_schema.index_of(&path[0]).is_ok() {
+                // the field is read from its leaf, which must be primitive and
+                // present in the file
+                match nested_field(self.file_schema, &path) {
+                    Some(field) if is_primitive_field(field) => {
+                        self.required_paths.insert(path);
+                    }
+                    _ => self.non_primitive_columns = true,
+                }
+                return Ok(RewriteRecursion::Stop);
+            }
+        }
+
         if let Expr::Column(column) = expr {
             if let Ok(idx) = self.file_schema.index_of(&column.name) {
-                self.required_column_indices.push(idx);
+                self.required_paths.insert(vec![column.name.clone()]);
 
                 if !is_primitive_field(self.file_schema.field(idx)) {
                     self.non_primitive_columns = true;
@@ -222,6 +257,82 @@ impl<'a> ExprRewriter for FilterCandidateBuilder<'a> {
     }
 }
 
+/// Returns the path of the struct field accessed by `expr`, such as
+/// `["s", "a", "b"]` for `s['a']['b']`, if `expr` accesses a field of a
+/// struct column by name
+fn struct_field_path(expr: &Expr) -> Option<Vec<String>> {
+    match expr {
+        Expr::GetIndexedField(GetIndexedField {
+            expr,
+            key: ScalarValue::Utf8(Some(name)),
+        }) => {
+            let mut path = match expr.as_ref() {
+                Expr::Column(column) => vec![column.name.clone()],
+                expr => struct_field_path(expr)?,
+            };
+            path.push(name.clone());
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the field of `schema` at `path`
+fn nested_field<'a>(schema: &'a Schema, path: &[String]) -> Option<&'a Field> {
+    let mut field = schema.field_with_name(&path[0]).ok()?;
+    for name in &path[1..] {
+        field = match field.data_type() {
+            DataType::Struct(children) => children.iter().find(|f| f.name() == name)?,
+            _ => return None,
+        };
+    }
+    Some(field)
+}
+
+/// Projects `schema` on the columns and struct fields at `paths`, the
+/// struct columns keeping only the fields at `paths`, as they are read from
+/// parquet leaf columns
+fn project_paths(schema: &Schema, paths: &BTreeSet<Vec<String>>) -> Schema {
+    fn project_field(field: &Field, paths: &[&[String]]) -> Field {
+        match field.data_type() {
+            DataType::Struct(children) if paths.iter().all(|path| !path.is_empty()) => {
+                let children = children
+                    .iter()
+                    .filter_map(|child| {
+                        let child_paths: Vec<&[String]> = paths
+                            .iter()
+                            .filter(|path| &path[0] == child.name())
+                            .map(|path| &path[1..])
+                            .collect();
+                        (!child_paths.is_empty())
+                            .then(|| project_field(child, &child_paths))
+                    })
+                    .collect();
+                Field::new(
+                    field.name(),
+                    DataType::Struct(children),
+                    field.is_nullable(),
+                )
+            }
+            _ => field.clone(),
+        }
+    }
+
+    let fields = schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let paths: Vec<&[String]> = paths
+                .iter()
+                .filter(|path| &path[0] == field.name())
+                .map(|path| &path[1..])
+                .collect();
+            (!paths.is_empty()).then(|| project_field(field, &paths))
+        })
+        .collect();
+    Schema::new(fields)
+}
+
 /// Calculate the total compressed size of all `Column's required for
 /// predicate `Expr`. This should represent the total amount of file IO
 /// required to evaluate the predicate.
@@ -280,15 +391,13 @@ pub fn build_row_filter(
         let mut filters: Vec<Box<dyn ArrowPredicate>> = vec![];
 
         for candidate in indexed_candidates {
-            let filter =
-                DatafusionArrowPredicate::try_new(candidate, file_schema, metadata)?;
+            let filter = DatafusionArrowPredicate::try_new(candidate, metadata)?;
 
             filters.push(Box::new(filter));
         }
 
         for candidate in other_candidates {
-            let filter =
-                DatafusionArrowPredicate::try_new(candidate, file_schema, metadata)?;
+            let filter = DatafusionArrowPredicate::try_new(candidate, metadata)?;
 
             filters.push(Box::new(filter));
         }
@@ -297,8 +406,7 @@ pub fn build_row_filter(
     } else {
         let mut filters: Vec<Box<dyn ArrowPredicate>> = vec![];
         for candidate in candidates {
-            let filter =
-                DatafusionArrowPredicate::try_new(candidate, file_schema, metadata)?;
+            let filter = DatafusionArrowPredicate::try_new(candidate, metadata)?;
 
             filters.push(Box::new(filter));
         }
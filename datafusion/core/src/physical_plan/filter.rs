@@ -23,18 +23,22 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use super::expressions::PhysicalSortExpr;
+use super::expressions::{BinaryExpr, Column, PhysicalSortExpr};
 use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet},
-    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning, PhysicalExpr,
 };
 use arrow::array::BooleanArray;
 use arrow::compute::filter_record_batch;
-use arrow::datatypes::{DataType, SchemaRef};
-use arrow::error::Result as ArrowResult;
+use arrow::datatypes::{DataType, Schema, SchemaRef};
+use arrow::error::{ArrowError, Result as ArrowResult};
 use arrow::record_batch::RecordBatch;
+use datafusion_expr::Operator;
+use datafusion_physical_expr::intervals::{
+    ExprIntervalGraph, Interval, PropagationResult,
+};
 
 use log::debug;
 
@@ -108,6 +112,15 @@ impl ExecutionPlan for FilterExec {
         self.input.output_ordering()
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        // the columns compared for equality by the predicate are equal in the output
+        let mut input_properties = self.input.equivalence_properties();
+        for (left, right) in equal_columns(&self.predicate) {
+            input_properties.add_equal_conditions((left, right));
+        }
+        input_properties
+    }
+
     fn maintains_input_order(&self) -> bool {
         // tell optimizer this operator doesn't reorder its input
         true
@@ -138,6 +151,8 @@ impl ExecutionPlan for FilterExec {
             schema: self.input.schema(),
             predicate: self.predicate.clone(),
             input: self.input.execute(partition, context)?,
+            partition,
+            num_rows: 0,
             baseline_metrics,
         }))
     }
@@ -158,9 +173,103 @@ impl ExecutionPlan for FilterExec {
         Some(self.metrics.clone_inner())
     }
 
-    /// The output statistics of a filtering operation are unknown
+    /// The output statistics of a filtering operation are estimated from the
+    /// bounds of the input columns, narrowed to the values that may satisfy the
+    /// predicate. They are unknown if the predicate does not refer to a
+    /// bounded column.
     fn statistics(&self) -> Statistics {
-        Statistics::default()
+        estimate_filter_statistics(
+            &self.predicate,
+            &self.input.schema(),
+            self.input.statistics(),
+        )
+        .unwrap_or_default()
+    }
+}
+
+/// Estimates the statistics of the rows of `input_stats` that satisfy
+/// `predicate`, assuming the values of each column are uniformly distributed
+/// within its bounds and independent of the other columns
+fn estimate_filter_statistics(
+    predicate: &Arc<dyn PhysicalExpr>,
+    schema: &Schema,
+    input_stats: Statistics,
+) -> Result<Statistics> {
+    let mut graph = ExprIntervalGraph::try_new(predicate.clone(), schema)?;
+    let mut column_intervals = vec![];
+    if let Some(column_statistics) = &input_stats.column_statistics {
+        for (i, stats) in column_statistics.iter().enumerate() {
+            if let (Some(min), Some(max)) = (&stats.min_value, &stats.max_value) {
+                let column: Arc<dyn PhysicalExpr> =
+                    Arc::new(Column::new(schema.field(i).name(), i));
+                let interval = Interval::new(min.clone(), max.clone());
+                if interval.is_bounded()
+                    && graph.assign_interval(&column, interval.clone())
+                {
+                    column_intervals.push((i, column, interval));
+                }
+            }
+        }
+    }
+
+    if graph.update_ranges(Interval::certainly_true())? == PropagationResult::Infeasible {
+        // no row satisfies the predicate within the bounds, which are only
+        // used for an estimate, as the interval arithmetic is approximate
+        return Ok(Statistics {
+            num_rows: Some(0),
+            total_byte_size: Some(0),
+            column_statistics: None,
+            is_exact: false,
+        });
+    }
+    let mut column_statistics = match input_stats.column_statistics {
+        Some(column_statistics) if !column_intervals.is_empty() => column_statistics,
+        _ => return Ok(Statistics::default()),
+    };
+
+    let mut selectivity = 1.0;
+    for (i, column, interval) in column_intervals {
+        let narrowed = match graph.interval(&column) {
+            Some(narrowed) => narrowed,
+            None => continue,
+        };
+        let fraction = interval.fraction_in(narrowed).unwrap_or(1.0);
+        selectivity *= fraction;
+        let stats = &mut column_statistics[i];
+        stats.min_value = Some(narrowed.lower.clone());
+        stats.max_value = Some(narrowed.upper.clone());
+        stats.distinct_count = stats
+            .distinct_count
+            .map(|count| (count as f64 * fraction).ceil() as usize);
+    }
+    let estimate = |value: usize| (value as f64 * selectivity).ceil() as usize;
+    Ok(Statistics {
+        num_rows: input_stats.num_rows.map(estimate),
+        total_byte_size: input_stats.total_byte_size.map(estimate),
+        column_statistics: Some(column_statistics),
+        is_exact: false,
+    })
+}
+
+/// Returns the pairs of columns compared with `=` by the conjuncts of `predicate`
+fn equal_columns(predicate: &Arc<dyn PhysicalExpr>) -> Vec<(&Column, &Column)> {
+    match predicate.as_any().downcast_ref::<BinaryExpr>() {
+        Some(binary) => match binary.op() {
+            Operator::And => {
+                let mut columns = equal_columns(binary.left());
+                columns.extend(equal_columns(binary.right()));
+                columns
+            }
+            Operator::Eq => match (
+                binary.left().as_any().downcast_ref::<Column>(),
+                binary.right().as_any().downcast_ref::<Column>(),
+            ) {
+                (Some(left), Some(right)) => vec![(left, right)],
+                _ => vec![],
+            },
+            _ => vec![],
+        },
+        None => vec![],
     }
 }
 
@@ -173,6 +282,10 @@ struct FilterExecStream {
     predicate: Arc<dyn PhysicalExpr>,
     /// The input partition to filter.
     input: SendableRecordBatchStream,
+    /// The partition of the input, reported in errors
+    partition: usize,
+    /// The number of input rows before the current batch, reported in errors
+    num_rows: usize,
     /// runtime metrics recording
     baseline_metrics: BaselineMetrics,
 }
@@ -207,10 +320,22 @@ impl Stream for FilterExecStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        let poll = self.input.poll_next_unpin(cx).map(|x| match x {
+        self.baseline_metrics.record_poll_start();
+        let poll = self.input.poll_next_unpin(cx);
+        let poll = self.baseline_metrics.record_input_poll(poll);
+        let poll = poll.map(|x| match x {
             Some(Ok(batch)) => {
+                let start = self.num_rows;
+                self.num_rows += batch.num_rows();
                 let timer = self.baseline_metrics.elapsed_compute().timer();
-                let filtered_batch = batch_filter(&batch, &self.predicate);
+                let filtered_batch = batch_filter(&batch, &self.predicate).map_err(|e| {
+                    ArrowError::from(DataFusionError::from(e).with_runtime_context(
+                        format!(
+                            "FilterExec: evaluating {} on rows {}..{} of partition {}",
+                            self.predicate, start, self.num_rows, self.partition
+                        ),
+                    ))
+                });
                 timer.done();
                 Some(filtered_batch)
             }
@@ -240,7 +365,10 @@ mod tests {
     use crate::physical_plan::{collect, with_new_children_if_necessary};
     use crate::prelude::SessionContext;
     use crate::test;
+    use crate::test::exec::StatisticsExec;
     use crate::test_util;
+    use arrow::datatypes::Field;
+    use datafusion_common::{ColumnStatistics, ScalarValue};
     use datafusion_expr::Operator;
     use std::iter::Iterator;
 
@@ -295,4 +423,67 @@ mod tests {
 
         Ok(())
     }
+
+    fn statistics_exec(min: i32, max: i32, is_exact: bool) -> Arc<dyn ExecutionPlan> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        Arc::new(StatisticsExec::new(
+            Statistics {
+                num_rows: Some(100),
+                total_byte_size: Some(400),
+                column_statistics: Some(vec![ColumnStatistics {
+                    min_value: Some(ScalarValue::Int32(Some(min))),
+                    max_value: Some(ScalarValue::Int32(Some(max))),
+                    ..Default::default()
+                }]),
+                is_exact,
+            },
+            schema,
+        ))
+    }
+
+    #[test]
+    fn filter_statistics() -> Result<()> {
+        // a in [1, 100], a <= 25
+        let input = statistics_exec(1, 100, true);
+        let schema = input.schema();
+        let predicate = binary(col("a", &schema)?, Operator::LtEq, lit(25i32), &schema)?;
+        let filter = FilterExec::try_new(predicate, input)?;
+        let statistics = filter.statistics();
+        assert_eq!(statistics.num_rows, Some(25));
+        assert_eq!(statistics.total_byte_size, Some(100));
+        assert!(!statistics.is_exact);
+        assert_eq!(
+            statistics.column_statistics,
+            Some(vec![ColumnStatistics {
+                min_value: Some(ScalarValue::Int32(Some(1))),
+                max_value: Some(ScalarValue::Int32(Some(25))),
+                ..Default::default()
+            }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn filter_statistics_always_false() -> Result<()> {
+        // a in [1, 100], a > 10 AND a < 5
+        let input = statistics_exec(1, 100, true);
+        let schema = input.schema();
+        let predicate = binary(
+            binary(col("a", &schema)?, Operator::Gt, lit(10i32), &schema)?,
+            Operator::And,
+            binary(col("a", &schema)?, Operator::Lt, lit(5i32), &schema)?,
+            &schema,
+        )?;
+        let filter = FilterExec::try_new(predicate, input)?;
+        let statistics = filter.statistics();
+        assert_eq!(statistics.num_rows, Some(0));
+        assert!(!statistics.is_exact);
+
+        // a > 0 holds for all the values of a
+        let predicate = binary(col("a", &schema)?, Operator::Gt, lit(0i32), &schema)?;
+        let input = statistics_exec(1, 100, true);
+        let filter = FilterExec::try_new(predicate, input)?;
+        assert_eq!(filter.statistics().num_rows, Some(100));
+        Ok(())
+    }
 }
@@ -27,10 +27,12 @@ use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
 use crate::execution::context::TaskContext;
+use crate::logical_expr::JoinType;
 use crate::physical_plan::{
     coalesce_batches::concat_batches, coalesce_partitions::CoalescePartitionsExec,
-    ColumnStatistics, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
-    SendableRecordBatchStream, Statistics,
+    common::BatchSplitter, ColumnStatistics, DisplayFormatType, EquivalenceProperties,
+    ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    Statistics,
 };
 use crate::{error::Result, scalar::ScalarValue};
 use async_trait::async_trait;
@@ -38,7 +40,10 @@ use datafusion_physical_expr::PhysicalSortExpr;
 use log::debug;
 use std::time::Instant;
 
-use super::utils::{check_join_is_valid, OnceAsync, OnceFut};
+use super::utils::{
+    adjust_right_output_partitioning, check_join_is_valid,
+    combine_join_equivalence_properties, OnceAsync, OnceFut,
+};
 
 /// Data of the left side
 type JoinLeftData = RecordBatch;
@@ -154,13 +159,30 @@ impl ExecutionPlan for CrossJoinExec {
     }
 
     fn output_partitioning(&self) -> Partitioning {
-        self.right.output_partitioning()
+        let left_columns_len = self.left.schema().fields().len();
+        adjust_right_output_partitioning(
+            self.right.output_partitioning(),
+            &JoinType::Inner,
+            left_columns_len,
+        )
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
         None
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        let left_columns_len = self.left.schema().fields().len();
+        combine_join_equivalence_properties(
+            JoinType::Inner,
+            self.left.equivalence_properties(),
+            self.right.equivalence_properties(),
+            left_columns_len,
+            &[],
+            self.schema(),
+        )
+    }
+
     fn relies_on_input_order(&self) -> bool {
         false
     }
@@ -170,6 +192,7 @@ impl ExecutionPlan for CrossJoinExec {
         partition: usize,
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
+        let batch_size = context.session_config().batch_size();
         let stream = self.right.execute(partition, context.clone())?;
 
         let left_fut = self
@@ -187,6 +210,7 @@ impl ExecutionPlan for CrossJoinExec {
             num_output_batches: 0,
             num_output_rows: 0,
             join_time: 0,
+            output: BatchSplitter::new(batch_size),
         }))
     }
 
@@ -298,6 +322,8 @@ struct CrossJoinStream {
     num_output_rows: usize,
     /// total time for joining probe-side batches to the build-side batches
     join_time: usize,
+    /// Splits the joined batches into batches of at most `batch_size` rows
+    output: BatchSplitter,
 }
 
 impl RecordBatchStream for CrossJoinStream {
@@ -340,6 +366,10 @@ impl Stream for CrossJoinStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
+        if let Some(batch) = self.output.next_batch() {
+            self.num_output_batches += 1;
+            return Poll::Ready(Some(Ok(batch)));
+        }
         self.poll_next_impl(cx)
     }
 }
@@ -375,7 +405,7 @@ impl CrossJoinStream {
                 self.num_output_rows += batch.num_rows();
             }
             self.left_index += 1;
-            return Poll::Ready(Some(result));
+            return Poll::Ready(Some(result.map(|batch| self.output.split(batch))));
         }
         self.left_index = 0;
         self.right
@@ -397,7 +427,7 @@ impl CrossJoinStream {
                     let mut right_batch = self.right_batch.lock();
                     *right_batch = Some(batch);
 
-                    Some(result)
+                    Some(result.map(|batch| self.output.split(batch)))
                 }
                 other => {
                     debug!(
@@ -418,6 +448,37 @@ impl CrossJoinStream {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::prelude::{SessionConfig, SessionContext};
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field};
+
+    fn build_table(name: &str, values: Vec<i32>) -> Arc<dyn ExecutionPlan> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new(name, DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(values))],
+        )
+        .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    #[tokio::test]
+    async fn split_batches() -> Result<()> {
+        let session_ctx =
+            SessionContext::with_config(SessionConfig::new().with_batch_size(2));
+        let left = build_table("a", vec![1, 2]);
+        let right = build_table("b", vec![1, 2, 3, 4, 5]);
+        let join = CrossJoinExec::try_new(left, right)?;
+
+        // the 5 rows joined to each left row
+        let batches = common::collect(join.execute(0, session_ctx.task_ctx())?).await?;
+        let num_rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(num_rows, vec![2, 2, 1, 2, 2, 1]);
+        Ok(())
+    }
 
     #[tokio::test]
     async fn test_stats_cartesian_product() {
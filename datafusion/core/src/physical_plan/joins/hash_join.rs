@@ -58,16 +58,18 @@ use hashbrown::raw::RawTable;
 use crate::physical_plan::{
     coalesce_batches::concat_batches,
     coalesce_partitions::CoalescePartitionsExec,
+    common::BatchSplitter,
     expressions::Column,
     expressions::PhysicalSortExpr,
     hash_utils::create_hashes,
     joins::utils::{
-        build_join_schema, check_join_is_valid, estimate_join_statistics, ColumnIndex,
+        adjust_right_output_partitioning, build_join_schema, check_join_is_valid,
+        combine_join_equivalence_properties, estimate_join_statistics, ColumnIndex,
         JoinFilter, JoinOn, JoinSide,
     },
     metrics::{self, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet},
-    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr, RecordBatchStream,
-    SendableRecordBatchStream, Statistics,
+    DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning, PhysicalExpr,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
 };
 
 use crate::error::{DataFusionError, Result};
@@ -290,13 +292,30 @@ impl ExecutionPlan for HashJoinExec {
     }
 
     fn output_partitioning(&self) -> Partitioning {
-        self.right.output_partitioning()
+        let left_columns_len = self.left.schema().fields().len();
+        adjust_right_output_partitioning(
+            self.right.output_partitioning(),
+            &self.join_type,
+            left_columns_len,
+        )
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
         None
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        let left_columns_len = self.left.schema().fields().len();
+        combine_join_equivalence_properties(
+            self.join_type,
+            self.left.equivalence_properties(),
+            self.right.equivalence_properties(),
+            left_columns_len,
+            self.on(),
+            self.schema(),
+        )
+    }
+
     fn relies_on_input_order(&self) -> bool {
         false
     }
@@ -329,6 +348,7 @@ impl ExecutionPlan for HashJoinExec {
 
         // we have the batches and the hash map with their keys. We can how create a stream
         // over the right that uses this information to issue new batches.
+        let batch_size = context.session_config().batch_size();
         let right_stream = self.right.execute(partition, context)?;
 
         Ok(Box::pin(HashJoinStream {
@@ -345,6 +365,7 @@ impl ExecutionPlan for HashJoinExec {
             join_metrics: HashJoinMetrics::new(partition, &self.metrics),
             null_equals_null: self.null_equals_null,
             is_exhausted: false,
+            output: BatchSplitter::new(batch_size),
         }))
     }
 
@@ -560,6 +581,8 @@ struct HashJoinStream {
     column_indices: Vec<ColumnIndex>,
     /// If null_equals_null is true, null == null else null != null
     null_equals_null: bool,
+    /// Splits the joined batches into batches of at most `batch_size` rows
+    output: BatchSplitter,
 }
 
 impl RecordBatchStream for HashJoinStream {
@@ -1384,7 +1407,7 @@ impl HashJoinStream {
                             JoinType::Inner | JoinType::Right | JoinType::RightSemi => {}
                         }
                     }
-                    Some(result.map(|x| x.0))
+                    Some(result.map(|(batch, _)| self.output.split(batch)))
                 }
                 other => {
                     let timer = self.join_metrics.join_time.timer();
@@ -1413,7 +1436,7 @@ impl HashJoinStream {
                             }
                             timer.done();
                             self.is_exhausted = true;
-                            return Some(result);
+                            return Some(result.map(|batch| self.output.split(batch)));
                         }
                         JoinType::Left
                         | JoinType::Full
@@ -1437,6 +1460,10 @@ impl Stream for HashJoinStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
+        if let Some(batch) = self.output.next_batch() {
+            self.join_metrics.output_batches.add(1);
+            return Poll::Ready(Some(Ok(batch)));
+        }
         self.poll_next_impl(cx)
     }
 }
@@ -1455,7 +1482,7 @@ mod tests {
     use datafusion_expr::Operator;
 
     use super::*;
-    use crate::prelude::SessionContext;
+    use crate::prelude::{SessionConfig, SessionContext};
     use std::sync::Arc;
 
     fn build_table(
@@ -1621,6 +1648,34 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn join_split_batches() -> Result<()> {
+        let session_ctx =
+            SessionContext::with_config(SessionConfig::new().with_batch_size(2));
+        let task_ctx = session_ctx.task_ctx();
+        let left = build_table(
+            ("a1", &vec![1, 2, 3, 4]),
+            ("b1", &vec![5, 5, 5, 6]),
+            ("c1", &vec![7, 8, 9, 10]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 20]),
+            ("b1", &vec![5, 5]),
+            ("c2", &vec![70, 80]),
+        );
+        let on = vec![(
+            Column::new_with_schema("b1", &left.schema())?,
+            Column::new_with_schema("b1", &right.schema())?,
+        )];
+
+        // the 6 joined rows of the right batch and the unmatched left row
+        let (_, batches) =
+            join_collect(left, right, on, &JoinType::Left, false, task_ctx).await?;
+        let num_rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(num_rows, vec![2, 2, 2, 1]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn partitioned_join_inner_one() -> Result<()> {
         let session_ctx = SessionContext::new();
@@ -43,12 +43,13 @@ use crate::physical_plan::common::combine_batches;
 use crate::physical_plan::expressions::Column;
 use crate::physical_plan::expressions::PhysicalSortExpr;
 use crate::physical_plan::joins::utils::{
-    build_join_schema, check_join_is_valid, JoinOn,
+    adjust_right_output_partitioning, build_join_schema, check_join_is_valid,
+    combine_join_equivalence_properties, JoinOn,
 };
 use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
 use crate::physical_plan::{
-    metrics, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
-    SendableRecordBatchStream, Statistics,
+    metrics, DisplayFormatType, EquivalenceProperties, ExecutionPlan, Partitioning,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
 };
 
 /// join execution plan executes partitions in parallel and combines them into a set of
@@ -118,6 +119,36 @@ impl SortMergeJoinExec {
             null_equals_null,
         })
     }
+
+    /// left (build) side
+    pub fn left(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.left
+    }
+
+    /// right (probe) side
+    pub fn right(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.right
+    }
+
+    /// Set of common columns used to join on
+    pub fn on(&self) -> &[(Column, Column)] {
+        &self.on
+    }
+
+    /// How the join is performed
+    pub fn join_type(&self) -> JoinType {
+        self.join_type
+    }
+
+    /// Sort options of the join columns, in the order of [`Self::on`]
+    pub fn sort_options(&self) -> &[SortOptions] {
+        &self.sort_options
+    }
+
+    /// If null_equals_null is true, null == null else null != null
+    pub fn null_equals_null(&self) -> bool {
+        self.null_equals_null
+    }
 }
 
 impl ExecutionPlan for SortMergeJoinExec {
@@ -130,7 +161,12 @@ impl ExecutionPlan for SortMergeJoinExec {
     }
 
     fn output_partitioning(&self) -> Partitioning {
-        self.right.output_partitioning()
+        let left_columns_len = self.left.schema().fields().len();
+        adjust_right_output_partitioning(
+            self.right.output_partitioning(),
+            &self.join_type,
+            left_columns_len,
+        )
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
@@ -144,6 +180,18 @@ impl ExecutionPlan for SortMergeJoinExec {
         }
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        let left_columns_len = self.left.schema().fields().len();
+        combine_join_equivalence_properties(
+            self.join_type,
+            self.left.equivalence_properties(),
+            self.right.equivalence_properties(),
+            left_columns_len,
+            &self.on,
+            self.schema(),
+        )
+    }
+
     fn relies_on_input_order(&self) -> bool {
         true
     }
@@ -20,10 +20,12 @@
 use crate::error::{DataFusionError, Result};
 use crate::logical_expr::JoinType;
 use crate::physical_plan::expressions::Column;
-use arrow::datatypes::{Field, Schema};
+use arrow::datatypes::{Field, Schema, SchemaRef};
 use arrow::error::ArrowError;
 use datafusion_common::ScalarValue;
-use datafusion_physical_expr::PhysicalExpr;
+use datafusion_physical_expr::intervals::Interval;
+use datafusion_physical_expr::rewrite::TreeNodeRewritable;
+use datafusion_physical_expr::{EquivalenceProperties, EquivalentClass, PhysicalExpr};
 use futures::future::{BoxFuture, Shared};
 use futures::{ready, FutureExt};
 use parking_lot::Mutex;
@@ -33,7 +35,7 @@ use std::future::Future;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use crate::physical_plan::{ColumnStatistics, ExecutionPlan, Statistics};
+use crate::physical_plan::{ColumnStatistics, ExecutionPlan, Partitioning, Statistics};
 
 /// The on clause of the join, as vector of (left, right) columns.
 pub type JoinOn = Vec<(Column, Column)>;
@@ -257,6 +259,92 @@ pub fn build_join_schema(
     (Schema::new(fields), column_indices)
 }
 
+/// Returns the output partitioning of a join that outputs the rows of each
+/// partition of its right input in the same partition. A hash partitioning of
+/// the right input holds on the output when no right rows are padded with
+/// nulls, on the right columns moved after the left columns.
+pub fn adjust_right_output_partitioning(
+    right_partitioning: Partitioning,
+    join_type: &JoinType,
+    left_columns_len: usize,
+) -> Partitioning {
+    match (right_partitioning, join_type) {
+        (Partitioning::Hash(exprs, size), JoinType::Inner | JoinType::Right) => {
+            let new_exprs = exprs
+                .into_iter()
+                .map(|expr| {
+                    expr.transform_down(&|e| {
+                        e.as_any().downcast_ref::<Column>().map(|col| {
+                            Arc::new(Column::new(
+                                col.name(),
+                                left_columns_len + col.index(),
+                            )) as Arc<dyn PhysicalExpr>
+                        })
+                    })
+                })
+                .collect::<Result<Vec<_>>>();
+            match new_exprs {
+                Ok(new_exprs) => Partitioning::Hash(new_exprs, size),
+                Err(_) => Partitioning::UnknownPartitioning(size),
+            }
+        }
+        (Partitioning::Hash(exprs, size), JoinType::RightSemi) => {
+            Partitioning::Hash(exprs, size)
+        }
+        (Partitioning::Hash(_, size), _) => Partitioning::UnknownPartitioning(size),
+        (right_partitioning, _) => right_partitioning,
+    }
+}
+
+/// Combine the equivalence properties of the inputs of a join: the columns of
+/// the right input are moved after the columns of the left input, and the keys
+/// of an inner join are equal in its output
+pub fn combine_join_equivalence_properties(
+    join_type: JoinType,
+    left_properties: EquivalenceProperties,
+    right_properties: EquivalenceProperties,
+    left_columns_len: usize,
+    on: JoinOnRef,
+    schema: SchemaRef,
+) -> EquivalenceProperties {
+    let mut new_properties = EquivalenceProperties::new(schema);
+    match join_type {
+        JoinType::Inner | JoinType::Left | JoinType::Full | JoinType::Right => {
+            new_properties.extend(left_properties.classes().to_vec());
+            let new_right_properties = right_properties
+                .classes()
+                .iter()
+                .map(|class| {
+                    let new_columns = class
+                        .iter()
+                        .map(|column| {
+                            Column::new(column.name(), left_columns_len + column.index())
+                        })
+                        .collect::<Vec<_>>();
+                    EquivalentClass::new(
+                        new_columns[0].clone(),
+                        new_columns[1..].to_vec(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            new_properties.extend(new_right_properties);
+        }
+        JoinType::LeftSemi | JoinType::LeftAnti => {
+            new_properties.extend(left_properties.classes().to_vec())
+        }
+        JoinType::RightSemi => new_properties.extend(right_properties.classes().to_vec()),
+    }
+
+    if join_type == JoinType::Inner {
+        for (left_column, right_column) in on {
+            let new_right_column =
+                Column::new(right_column.name(), left_columns_len + right_column.index());
+            new_properties.add_equal_conditions((left_column, &new_right_column));
+        }
+    }
+    new_properties
+}
+
 /// A [`OnceAsync`] can be used to run an async closure once, with subsequent calls
 /// to [`OnceAsync::once`] returning a [`OnceFut`] to the same asynchronous computation
 ///
@@ -397,12 +485,40 @@ fn estimate_join_cardinality(
                 _ => unreachable!(),
             };
 
+            // The join keys of the matched rows are in the intersection of
+            // the ranges of both sides, so the bounds of the keys of a side can
+            // be narrowed to it when its rows are only output if matched. The
+            // other statistics are combined as they are, which might yield
+            // subpar results (although it is true, esp regarding min/max).
+            let (narrow_left, narrow_right) = match join_type {
+                JoinType::Inner => (true, true),
+                JoinType::Left => (false, true),
+                JoinType::Right => (true, false),
+                _ => (false, false),
+            };
+            let mut all_left_col_stats = all_left_col_stats;
+            let mut all_right_col_stats = all_right_col_stats;
+            for (left, right) in on {
+                let range = join_key_range(
+                    &all_left_col_stats[left.index()],
+                    &all_right_col_stats[right.index()],
+                );
+                if let Some(range) = range {
+                    if narrow_left {
+                        let stats = &mut all_left_col_stats[left.index()];
+                        stats.min_value = Some(range.lower.clone());
+                        stats.max_value = Some(range.upper.clone());
+                    }
+                    if narrow_right {
+                        let stats = &mut all_right_col_stats[right.index()];
+                        stats.min_value = Some(range.lower);
+                        stats.max_value = Some(range.upper);
+                    }
+                }
+            }
+
             Some(PartialJoinStatistics {
                 num_rows: cardinality,
-                // We don't do anything specific here, just combine the existing
-                // statistics which might yield subpar results (although it is
-                // true, esp regarding min/max). For a better estimation, we need
-                // filter selectivity analysis first.
                 column_statistics: all_left_col_stats
                     .into_iter()
                     .chain(all_right_col_stats.into_iter())
@@ -416,6 +532,17 @@ fn estimate_join_cardinality(
     }
 }
 
+/// Returns the intersection of the ranges of two join keys, if both are known
+/// and overlap
+fn join_key_range(left: &ColumnStatistics, right: &ColumnStatistics) -> Option<Interval> {
+    let left_range = Interval::new(left.min_value.clone()?, left.max_value.clone()?);
+    let right_range = Interval::new(right.min_value.clone()?, right.max_value.clone()?);
+    if !left_range.is_bounded() || !right_range.is_bounded() {
+        return None;
+    }
+    left_range.intersect(&right_range).ok().flatten()
+}
+
 /// Estimate the inner join cardinality by using the basic building blocks of
 /// column-level statistics and the total row count. This is a very naive and
 /// a very conservative implementation that can quickly give up if there is not
@@ -873,9 +1000,24 @@ mod tests {
                 partial_join_stats.clone().map(|s| s.num_rows),
                 expected_cardinality
             );
+
+            // The keys of both sides are narrowed to the intersection of
+            // their ranges, if they overlap
+            let mut expected_col_stats = [left_col_stats, right_col_stats].concat();
+            if let (Some(left_min), Some(left_max), Some(right_min), Some(right_max)) =
+                (left_info.1, left_info.2, right_info.1, right_info.2)
+            {
+                let (min, max) = (left_min.max(right_min), left_max.min(right_max));
+                if min <= max {
+                    for stats in expected_col_stats.iter_mut() {
+                        stats.min_value = Some(ScalarValue::Int64(Some(min)));
+                        stats.max_value = Some(ScalarValue::Int64(Some(max)));
+                    }
+                }
+            }
             assert_eq!(
                 partial_join_stats.map(|s| s.column_statistics),
-                expected_cardinality.map(|_| [left_col_stats, right_col_stats].concat())
+                expected_cardinality.map(|_| expected_col_stats)
             );
         }
         Ok(())
@@ -969,6 +1111,13 @@ mod tests {
             create_column_stats(Some(0), Some(100), None),
         ];
 
+        // The matched values of d are in the range of b
+        let narrowed_right_col_stats = vec![
+            create_column_stats(Some(0), Some(100), Some(50)),
+            create_column_stats(Some(0), Some(500), Some(2500)),
+            create_column_stats(Some(0), Some(100), None),
+        ];
+
         for (join_type, expected_num_rows) in cases {
             let join_on = vec![
                 (Column::new("a", 0), Column::new("c", 0)),
@@ -983,9 +1132,13 @@ mod tests {
             )
             .unwrap();
             assert_eq!(partial_join_stats.num_rows, expected_num_rows);
+            let expected_right_col_stats = match join_type {
+                JoinType::Inner | JoinType::Left => narrowed_right_col_stats.clone(),
+                _ => right_col_stats.clone(),
+            };
             assert_eq!(
                 partial_join_stats.column_statistics,
-                [left_col_stats.clone(), right_col_stats.clone()].concat()
+                [left_col_stats.clone(), expected_right_col_stats].concat()
             );
         }
 
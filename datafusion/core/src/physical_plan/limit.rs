@@ -27,7 +27,7 @@ use std::task::{Context, Poll};
 
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    DisplayFormatType, Distribution, EquivalenceProperties, ExecutionPlan, Partitioning,
 };
 use arrow::array::ArrayRef;
 use arrow::compute::limit;
@@ -123,6 +123,10 @@ impl ExecutionPlan for GlobalLimitExec {
         self.input.output_ordering()
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn with_new_children(
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -291,6 +295,10 @@ impl ExecutionPlan for LocalLimitExec {
         }
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn with_new_children(
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -470,6 +478,7 @@ impl Stream for LimitStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         let fetch_started = self.current_skipped == self.skip;
         let poll = match &mut self.input {
             Some(input) => {
@@ -478,6 +487,7 @@ impl Stream for LimitStream {
                 } else {
                     self.poll_and_skip(cx)
                 };
+                let poll = self.baseline_metrics.record_input_poll(poll);
 
                 poll.map(|x| match x {
                     Some(Ok(batch)) => Ok(self.stream_limit(batch)).transpose(),
@@ -46,6 +46,10 @@ pub struct MemoryExec {
     projected_schema: SchemaRef,
     /// Optional projection
     projection: Option<Vec<usize>>,
+    /// The partitioning of the batches, if known
+    partitioning: Option<Partitioning>,
+    /// The ordering of the batches of each partition, if known
+    sort_information: Option<Vec<PhysicalSortExpr>>,
 }
 
 impl fmt::Debug for MemoryExec {
@@ -74,11 +78,13 @@ impl ExecutionPlan for MemoryExec {
 
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
-        Partitioning::UnknownPartitioning(self.partitions.len())
+        self.partitioning
+            .clone()
+            .unwrap_or(Partitioning::UnknownPartitioning(self.partitions.len()))
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
-        None
+        self.sort_information.as_deref()
     }
 
     fn relies_on_input_order(&self) -> bool {
@@ -150,8 +156,50 @@ impl MemoryExec {
             schema,
             projected_schema,
             projection,
+            partitioning: None,
+            sort_information: None,
         })
     }
+
+    /// Declare the partitioning of the batches, such as the hash partitioning
+    /// of the results of a [`RepartitionExec`](super::repartition::RepartitionExec),
+    /// which must have as many partitions as the batches
+    pub fn with_partitioning(mut self, partitioning: Partitioning) -> Result<Self> {
+        if partitioning.partition_count() != self.partitions.len() {
+            return Err(DataFusionError::Plan(format!(
+                "MemoryExec has {} partitions, which can not be partitioned by {:?}",
+                self.partitions.len(),
+                partitioning
+            )));
+        }
+        self.partitioning = Some(partitioning);
+        Ok(self)
+    }
+
+    /// Declare the ordering of the batches of each partition, on the columns
+    /// of the projected schema
+    pub fn with_sort_information(
+        mut self,
+        sort_information: Vec<PhysicalSortExpr>,
+    ) -> Self {
+        self.sort_information = Some(sort_information);
+        self
+    }
+
+    /// The partitions of record batches to read
+    pub fn partitions(&self) -> &[Vec<RecordBatch>] {
+        &self.partitions
+    }
+
+    /// The schema of the data before the projection is applied
+    pub fn original_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// The optional projection of the columns of [`Self::original_schema`]
+    pub fn projection(&self) -> &Option<Vec<usize>> {
+        &self.projection
+    }
 }
 
 /// Iterator over batches
@@ -18,8 +18,10 @@
 //! Metrics common for almost all operators
 
 use std::task::Poll;
+use std::time::Instant;
 
 use arrow::{error::ArrowError, record_batch::RecordBatch};
+use parking_lot::Mutex;
 
 use super::{Count, ExecutionPlanMetricsSet, Gauge, MetricBuilder, Time, Timestamp};
 
@@ -42,8 +44,18 @@ use super::{Count, ExecutionPlanMetricsSet, Gauge, MetricBuilder, Time, Timestam
 /// // when operator is finished:
 /// baseline_metrics.done();
 /// ```
+///
+/// Operators that pull from a child stream should also call
+/// [`Self::record_poll_start`] when polled and route polls of their input
+/// through [`Self::record_input_poll`], so that the time the operator spends
+/// waiting on its input (`input_wait`) and on its consumer (`output_wait`) is
+/// visible. Comparing those across operators shows where a pipeline is
+/// bottlenecked.
 #[derive(Debug)]
 pub struct BaselineMetrics {
+    /// start_time is set when the metrics are created
+    start_time: Timestamp,
+
     /// end_time is set when `ExecutionMetrics::done()` is called
     end_time: Timestamp,
 
@@ -61,6 +73,22 @@ pub struct BaselineMetrics {
 
     /// output rows: the total output rows
     output_rows: Count,
+
+    /// output rows per second between start and end time, set on completion
+    output_rate: Gauge,
+
+    /// amount of time the operator waited for its input to produce data
+    input_wait: Time,
+
+    /// amount of time between the operator producing a batch and its
+    /// consumer asking for the next one
+    output_wait: Time,
+
+    /// when the input last returned `Poll::Pending`, if it has not been ready since
+    input_pending_since: Mutex<Option<Instant>>,
+
+    /// when the last output rows were recorded, if not yet polled again
+    output_ready_since: Mutex<Option<Instant>>,
 }
 
 impl BaselineMetrics {
@@ -70,12 +98,19 @@ impl BaselineMetrics {
         start_time.record();
 
         Self {
+            start_time,
             end_time: MetricBuilder::new(metrics).end_timestamp(partition),
             elapsed_compute: MetricBuilder::new(metrics).elapsed_compute(partition),
             spill_count: MetricBuilder::new(metrics).spill_count(partition),
             spilled_bytes: MetricBuilder::new(metrics).spilled_bytes(partition),
             mem_used: MetricBuilder::new(metrics).mem_used(partition),
             output_rows: MetricBuilder::new(metrics).output_rows(partition),
+            output_rate: MetricBuilder::new(metrics).gauge("output_rate", partition),
+            input_wait: MetricBuilder::new(metrics).subset_time("input_wait", partition),
+            output_wait: MetricBuilder::new(metrics)
+                .subset_time("output_wait", partition),
+            input_pending_since: Mutex::new(None),
+            output_ready_since: Mutex::new(None),
         }
     }
 
@@ -110,6 +145,48 @@ impl BaselineMetrics {
         &self.output_rows
     }
 
+    /// return the metric for the output rows produced per second, which is
+    /// only set once the operator is done
+    pub fn output_rate(&self) -> &Gauge {
+        &self.output_rate
+    }
+
+    /// return the metric for the time spent waiting on the input
+    pub fn input_wait(&self) -> &Time {
+        &self.input_wait
+    }
+
+    /// return the metric for the time spent waiting on the consumer
+    pub fn output_wait(&self) -> &Time {
+        &self.output_wait
+    }
+
+    /// Records that the consumer polled the operator for its next output,
+    /// adding the time since the previous output batch to `output_wait`.
+    pub fn record_poll_start(&self) {
+        if let Some(ready_since) = self.output_ready_since.lock().take() {
+            self.output_wait.add_elapsed(ready_since);
+        }
+    }
+
+    /// Process a poll result of the operator's input, adding the time between
+    /// the input returning `Poll::Pending` and it becoming ready to
+    /// `input_wait`, and returning the same poll result
+    pub fn record_input_poll<T>(&self, poll: Poll<T>) -> Poll<T> {
+        let mut pending_since = self.input_pending_since.lock();
+        match &poll {
+            Poll::Pending => {
+                pending_since.get_or_insert_with(Instant::now);
+            }
+            Poll::Ready(_) => {
+                if let Some(start) = pending_since.take() {
+                    self.input_wait.add_elapsed(start);
+                }
+            }
+        }
+        poll
+    }
+
     /// Records the fact that this operator's execution is complete
     /// (recording the `end_time` metric).
     ///
@@ -118,7 +195,21 @@ impl BaselineMetrics {
     /// completion, as async streams may not be dropped immediately
     /// depending on the consumer.
     pub fn done(&self) {
-        self.end_time.record()
+        self.end_time.record();
+        self.record_output_rate();
+    }
+
+    /// Set `output_rate` from the output rows and the start and end time
+    fn record_output_rate(&self) {
+        if let (Some(start), Some(end)) = (self.start_time.value(), self.end_time.value())
+        {
+            let nanos = (end - start).num_nanoseconds().unwrap_or(i64::MAX);
+            if nanos > 0 {
+                let rate =
+                    self.output_rows.value() as u128 * 1_000_000_000 / nanos as u128;
+                self.output_rate.set(rate as usize);
+            }
+        }
     }
 
     /// Record that some number of rows have been produced as output
@@ -127,12 +218,13 @@ impl BaselineMetrics {
     /// batch output for other thing
     pub fn record_output(&self, num_rows: usize) {
         self.output_rows.add(num_rows);
+        *self.output_ready_since.lock() = Some(Instant::now());
     }
 
     /// If not previously recorded `done()`, record
     pub fn try_done(&self) {
         if self.end_time.value().is_none() {
-            self.end_time.record()
+            self.done()
         }
     }
 
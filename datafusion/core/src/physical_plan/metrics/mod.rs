@@ -389,8 +389,11 @@ impl Display for Label {
 
 #[cfg(test)]
 mod tests {
+    use std::task::Poll;
     use std::time::Duration;
 
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
     use chrono::{TimeZone, Utc};
 
     use super::*;
@@ -651,4 +654,38 @@ mod tests {
         let metrics = metrics.sorted_for_display();
         assert_eq!("output_rows, elapsed_compute, the_counter, the_time, start_timestamp, end_timestamp", metric_names(&metrics));
     }
+
+    #[test]
+    fn test_baseline_wait_metrics() {
+        let metrics = ExecutionPlanMetricsSet::new();
+        let baseline = BaselineMetrics::new(&metrics, 0);
+
+        // input was not ready for a while
+        assert!(baseline.record_input_poll(Poll::<()>::Pending).is_pending());
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(baseline.record_input_poll(Poll::Ready(())).is_ready());
+        assert!(baseline.input_wait().value() >= 2_000_000);
+
+        // output was not consumed for a while
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let batch = RecordBatch::new_empty(Arc::new(schema));
+        let _ = baseline.record_poll(Poll::Ready(Some(Ok(batch))));
+        std::thread::sleep(Duration::from_millis(2));
+        baseline.record_poll_start();
+        let output_wait = baseline.output_wait().value();
+        assert!(output_wait >= 2_000_000);
+
+        // output recorded by the operator without going through record_poll
+        baseline.record_output(1);
+        std::thread::sleep(Duration::from_millis(2));
+        baseline.record_poll_start();
+        assert!(baseline.output_wait().value() >= output_wait + 2_000_000);
+
+        baseline.done();
+        let metrics = metrics.clone_inner();
+        let names = metrics.iter().map(|m| m.value().name()).collect::<Vec<_>>();
+        assert!(names.contains(&"input_wait"));
+        assert!(names.contains(&"output_wait"));
+        assert!(names.contains(&"output_rate"));
+    }
 }
@@ -89,6 +89,21 @@ impl MemTrackingMetrics {
         self.metrics.output_rows()
     }
 
+    /// Records that the consumer polled the operator for its next output
+    ///
+    /// See [`BaselineMetrics::record_poll_start`]
+    pub fn record_poll_start(&self) {
+        self.metrics.record_poll_start()
+    }
+
+    /// Process a poll result of the operator's input, returning the same poll
+    /// result
+    ///
+    /// See [`BaselineMetrics::record_input_poll`]
+    pub fn record_input_poll<T>(&self, poll: Poll<T>) -> Poll<T> {
+        self.metrics.record_input_poll(poll)
+    }
+
     /// Records the fact that this operator's execution is complete
     /// (recording the `end_time` metric).
     ///
@@ -122,6 +122,18 @@ pub trait ExecutionPlan: Debug + Send + Sync {
     /// have any particular output order here
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]>;
 
+    /// Get the equivalence properties of this plan: the columns of its
+    /// output that always have equal values, such as the keys of an inner
+    /// join or a column and its alias in a projection.
+    ///
+    /// An ordering or partitioning on a column is also an ordering or
+    /// partitioning on the columns equivalent to it.
+    ///
+    /// The default implementation returns no equivalent columns
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        EquivalenceProperties::new(self.schema())
+    }
+
     /// Specifies the data distribution requirements of all the
     /// children for this operator
     fn required_child_distribution(&self) -> Distribution {
@@ -287,15 +299,14 @@ pub fn with_new_children_if_necessary(
 ///   let normalized = Path::from_filesystem_path(working_directory).unwrap();
 ///   let plan_string = plan_string.replace(normalized.as_ref(), "WORKING_DIR");
 ///
-///   assert_eq!("ProjectionExec: expr=[a@0 as a]\
-///              \n  CoalesceBatchesExec: target_batch_size=4096\
-///              \n    FilterExec: a@0 < 5\
-///              \n      RepartitionExec: partitioning=RoundRobinBatch(3)\
-///              \n        CsvExec: files=[WORKING_DIR/tests/example.csv], has_header=true, limit=None, projection=[a]",
+///   assert_eq!("CoalesceBatchesExec: target_batch_size=4096\
+///              \n  FilterExec: a@0 < 5\
+///              \n    RepartitionExec: partitioning=RoundRobinBatch(3)\
+///              \n      CsvExec: files=[WORKING_DIR/tests/example.csv], has_header=true, limit=None, projection=[a]",
 ///               plan_string.trim());
 ///
 ///   let one_line = format!("{}", displayable_plan.one_line());
-///   assert_eq!("ProjectionExec: expr=[a@0 as a]", one_line.trim());
+///   assert_eq!("CoalesceBatchesExec: target_batch_size=4096", one_line.trim());
 /// }
 /// ```
 ///
@@ -438,6 +449,55 @@ pub async fn execute_stream_partitioned(
     Ok(streams)
 }
 
+/// Execute the subtree of `plan` found by following the child indices in
+/// `path` from the root, and return a copy of `plan` in which that subtree is
+/// replaced by a [memory::MemoryExec] holding its results.
+///
+/// The materialized node keeps the schema, the partitioning and the ordering
+/// of the subtree it replaces, so the rest of the plan can be re-optimized or
+/// inspected against the intermediate results. An empty `path` materializes
+/// the whole plan.
+pub async fn materialize_subtree(
+    plan: Arc<dyn ExecutionPlan>,
+    path: &[usize],
+    context: Arc<TaskContext>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    // walk down to the subtree, remembering the ancestors to rebuild
+    let mut ancestors = Vec::with_capacity(path.len());
+    let mut node = plan;
+    for (depth, &index) in path.iter().enumerate() {
+        let children = node.children();
+        let child = children.get(index).cloned().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "Invalid subtree path {:?}: node at depth {} has {} children, no child {}",
+                path,
+                depth,
+                children.len(),
+                index
+            ))
+        })?;
+        ancestors.push((node, children, index));
+        node = child;
+    }
+
+    let schema = node.schema();
+    let partitioning = node.output_partitioning();
+    let ordering = node.output_ordering().map(|ordering| ordering.to_vec());
+    let partitions = collect_partitioned(node, context).await?;
+    let mut memory = memory::MemoryExec::try_new(&partitions, schema, None)?
+        .with_partitioning(partitioning)?;
+    if let Some(ordering) = ordering {
+        memory = memory.with_sort_information(ordering);
+    }
+    let mut node: Arc<dyn ExecutionPlan> = Arc::new(memory);
+
+    while let Some((parent, mut children, index)) = ancestors.pop() {
+        children[index] = node;
+        node = with_new_children_if_necessary(parent, children)?;
+    }
+    Ok(node)
+}
+
 /// Partitioning schemes supported by operators.
 #[derive(Debug, Clone)]
 pub enum Partitioning {
@@ -458,6 +518,43 @@ impl Partitioning {
             RoundRobinBatch(n) | Hash(_, n) | UnknownPartitioning(n) => *n,
         }
     }
+
+    /// Returns true if the rows are already distributed as the `required`
+    /// partitioning would distribute them, given the equivalence properties
+    /// of the partitioned plan
+    pub fn satisfy(
+        &self,
+        required: &Partitioning,
+        eq_properties: &EquivalenceProperties,
+    ) -> bool {
+        use Partitioning::*;
+        match (self, required) {
+            (_, Hash(_, 1)) => self.partition_count() == 1,
+            (Hash(exprs, n), Hash(required_exprs, required_n)) if n == required_n => {
+                expr_list_eq_strict_order(exprs, required_exprs)
+                    || expr_list_eq_strict_order(
+                        &normalize_exprs(exprs, eq_properties),
+                        &normalize_exprs(required_exprs, eq_properties),
+                    )
+            }
+            _ => false,
+        }
+    }
+}
+
+fn normalize_exprs(
+    exprs: &[Arc<dyn PhysicalExpr>],
+    eq_properties: &EquivalenceProperties,
+) -> Vec<Arc<dyn PhysicalExpr>> {
+    exprs
+        .iter()
+        .map(|expr| {
+            normalize_expr_with_equivalence_properties(
+                expr.clone(),
+                eq_properties.classes(),
+            )
+        })
+        .collect()
 }
 
 /// Distribution schemes
@@ -472,8 +569,13 @@ pub enum Distribution {
     HashPartitioned(Vec<Arc<dyn PhysicalExpr>>),
 }
 
+use datafusion_physical_expr::utils::{
+    expr_list_eq_strict_order, normalize_expr_with_equivalence_properties,
+};
 pub use datafusion_physical_expr::window::WindowExpr;
-pub use datafusion_physical_expr::{AggregateExpr, PhysicalExpr};
+pub use datafusion_physical_expr::{
+    AggregateExpr, EquivalenceProperties, EquivalentClass, PhysicalExpr,
+};
 
 /// Applies an optional projection to a [`SchemaRef`], returning the
 /// projected schema
@@ -517,9 +619,12 @@ pub fn project_schema(
 
 pub mod aggregates;
 pub mod analyze;
+pub mod bucketed;
+pub mod check_constraints;
 pub mod coalesce_batches;
 pub mod coalesce_partitions;
 pub mod common;
+pub mod dedup;
 pub mod display;
 pub mod empty;
 pub mod explain;
@@ -533,12 +638,128 @@ pub mod metrics;
 pub mod planner;
 pub mod projection;
 pub mod repartition;
+pub mod scalar_subquery;
 pub mod sorts;
 pub mod stream;
 pub mod udaf;
 pub mod union;
+pub mod unnest;
 pub mod values;
+#[cfg(feature = "wasm")]
+pub mod wasm_udf;
 pub mod windows;
 
 use crate::execution::context::TaskContext;
 pub use datafusion_physical_expr::{expressions, functions, type_coercion, udf};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::projection::ProjectionExec;
+    use crate::prelude::SessionContext;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_expr::Operator;
+
+    fn plan_lines(plan: &Arc<dyn ExecutionPlan>) -> Vec<String> {
+        displayable(plan.as_ref())
+            .indent()
+            .to_string()
+            .trim()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn materialize_filter_subtree() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        };
+        let partitions = vec![vec![batch(vec![1, 2, 3])?], vec![batch(vec![4, 5])?]];
+        let input = Arc::new(MemoryExec::try_new(&partitions, schema.clone(), None)?);
+        let predicate = binary(col("a", &schema)?, Operator::Gt, lit(1i32), &schema)?;
+        let filter = Arc::new(FilterExec::try_new(predicate, input)?);
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(ProjectionExec::try_new(
+            vec![(col("a", &schema)?, "a".to_string())],
+            filter,
+        )?);
+
+        let materialized =
+            materialize_subtree(plan.clone(), &[0], task_ctx.clone()).await?;
+        assert_eq!(
+            plan_lines(&materialized),
+            vec![
+                "ProjectionExec: expr=[a@0 as a]",
+                "  MemoryExec: partitions=2, partition_sizes=[1, 1]",
+            ]
+        );
+
+        let expected = vec![
+            "+---+", "| a |", "+---+", "| 2 |", "| 3 |", "| 4 |", "| 5 |", "+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &collect(plan, task_ctx.clone()).await?);
+        assert_batches_sorted_eq!(expected, &collect(materialized, task_ctx).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn materialize_keeps_partitioning_and_ordering() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![3, 1, 2]))],
+        )?;
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?);
+        let repartition = Arc::new(repartition::RepartitionExec::try_new(
+            input,
+            Partitioning::Hash(vec![col("a", &schema)?], 2),
+        )?);
+        let sort_expr = vec![PhysicalSortExpr {
+            expr: col("a", &schema)?,
+            options: Default::default(),
+        }];
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(sorts::sort::SortExec::new_with_partitioning(
+                sort_expr.clone(),
+                repartition,
+                true,
+                None,
+            ));
+
+        let materialized = materialize_subtree(plan, &[], session_ctx.task_ctx()).await?;
+        let partitioning = Partitioning::Hash(vec![col("a", &schema)?], 2);
+        assert_eq!(
+            format!("{:?}", materialized.output_partitioning()),
+            format!("{:?}", partitioning)
+        );
+        assert_eq!(materialized.output_ordering(), Some(sort_expr.as_slice()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn materialize_invalid_path() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[vec![]], schema, None)?);
+
+        let err = materialize_subtree(plan, &[0], session_ctx.task_ctx())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Invalid subtree path [0]: node at depth 0 has 0 children, no child 0"
+        );
+        Ok(())
+    }
+}
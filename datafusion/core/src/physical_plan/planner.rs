@@ -28,8 +28,8 @@ use crate::datasource::source_as_provider;
 use crate::execution::context::{ExecutionProps, SessionState};
 use crate::logical_expr::utils::generate_sort_key;
 use crate::logical_expr::{
-    Aggregate, Distinct, EmptyRelation, Join, Projection, Sort, SubqueryAlias, TableScan,
-    Window,
+    Aggregate, Dedup, Distinct, EmptyRelation, Join, Projection, Sort, SubqueryAlias,
+    TableScan, Unnest, Window,
 };
 use crate::logical_expr::{
     CrossJoin, Expr, LogicalPlan, Partitioning as LogicalPartitioning, PlanType,
@@ -39,6 +39,8 @@ use crate::logical_expr::{Limit, Values};
 use crate::physical_expr::create_physical_expr;
 use crate::physical_optimizer::optimizer::PhysicalOptimizerRule;
 use crate::physical_plan::aggregates::{AggregateExec, AggregateMode, PhysicalGroupBy};
+use crate::physical_plan::check_constraints::{CheckConstraints, CheckConstraintsExec};
+use crate::physical_plan::dedup::DedupExec;
 use crate::physical_plan::explain::ExplainExec;
 use crate::physical_plan::expressions::{Column, PhysicalSortExpr};
 use crate::physical_plan::filter::FilterExec;
@@ -47,7 +49,9 @@ use crate::physical_plan::joins::HashJoinExec;
 use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
 use crate::physical_plan::projection::ProjectionExec;
 use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::scalar_subquery::ScalarSubqueryExec;
 use crate::physical_plan::sorts::sort::SortExec;
+use crate::physical_plan::unnest::UnnestExec;
 use crate::physical_plan::windows::WindowAggExec;
 use crate::physical_plan::{joins::utils as join_utils, Partitioning};
 use crate::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr, WindowExpr};
@@ -63,10 +67,16 @@ use datafusion_expr::expr::{
     Between, BinaryExpr, Cast, GetIndexedField, GroupingSet, Like,
 };
 use datafusion_expr::expr_rewriter::unnormalize_cols;
-use datafusion_expr::utils::{expand_wildcard, expr_to_columns};
+use datafusion_expr::utils::{
+    expand_wildcard, expr_to_columns, find_scalar_subquery_exprs,
+};
 use datafusion_expr::{WindowFrame, WindowFrameBound, WindowFrameUnits};
+use datafusion_optimizer::share_scalar_subqueries::is_correlated;
 use datafusion_optimizer::utils::unalias;
-use datafusion_physical_expr::expressions::Literal;
+use datafusion_physical_expr::expressions::{
+    memoize_constants, Literal, ScalarSubqueryValue,
+};
+use datafusion_physical_expr::utils::ordering_satisfy;
 use datafusion_sql::utils::window_expr_common_partition_keys;
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt, TryStreamExt};
@@ -109,6 +119,7 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
         Expr::Alias(_, name) => Ok(name.clone()),
         Expr::ScalarVariable(_, variable_names) => Ok(variable_names.join(".")),
         Expr::Literal(value) => Ok(format!("{:?}", value)),
+        Expr::Placeholder { id, .. } => Ok(format!("${}", id)),
         Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
             let left = create_physical_name(left, false)?;
             let right = create_physical_name(right, false)?;
@@ -257,9 +268,9 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
         Expr::InSubquery { .. } => Err(DataFusionError::NotImplemented(
             "IN subquery is not yet supported in the physical plan".to_string(),
         )),
-        Expr::ScalarSubquery(_) => Err(DataFusionError::NotImplemented(
-            "Scalar subqueries are not yet supported in the physical plan".to_string(),
-        )),
+        Expr::ScalarSubquery(subquery) => {
+            Ok(subquery.subquery.schema().field(0).name().clone())
+        }
         Expr::Between(Between {
             expr,
             negated,
@@ -436,12 +447,14 @@ impl PhysicalPlanner for DefaultPhysicalPlanner {
         input_schema: &Schema,
         session_state: &SessionState,
     ) -> Result<Arc<dyn PhysicalExpr>> {
-        create_physical_expr(
+        // the constant subexpressions surviving the optimizer, such as in
+        // the expressions of extension nodes, are evaluated once per operator
+        memoize_constants(create_physical_expr(
             expr,
             input_dfschema,
             input_schema,
             &session_state.execution_props,
-        )
+        )?)
     }
 }
 
@@ -456,6 +469,43 @@ impl DefaultPhysicalPlanner {
         Self { extension_planners }
     }
 
+    /// Plans the uncorrelated scalar subqueries of `exprs`, which are
+    /// evaluated on the output of `input_exec`, and registers their values in
+    /// the execution props of `session_state`. Returns `input_exec`, wrapped
+    /// in a [`ScalarSubqueryExec`] executing the subqueries if there are any.
+    async fn plan_scalar_subqueries(
+        &self,
+        exprs: &[Expr],
+        input_exec: Arc<dyn ExecutionPlan>,
+        session_state: &SessionState,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut subqueries: Vec<(Arc<dyn ExecutionPlan>, Arc<ScalarSubqueryValue>)> =
+            vec![];
+        for expr in find_scalar_subquery_exprs(exprs) {
+            let subquery = match expr {
+                Expr::ScalarSubquery(subquery) => subquery.subquery,
+                _ => continue,
+            };
+            if is_correlated(&subquery)? {
+                continue;
+            }
+            let value = session_state
+                .execution_props
+                .register_scalar_subquery(&subquery);
+            if subqueries.iter().any(|(_, v)| Arc::ptr_eq(v, &value)) {
+                continue;
+            }
+            let plan = session_state.optimize(&subquery)?;
+            let plan = self.create_physical_plan(&plan, session_state).await?;
+            subqueries.push((plan, value));
+        }
+        if subqueries.is_empty() {
+            Ok(input_exec)
+        } else {
+            Ok(Arc::new(ScalarSubqueryExec::new(input_exec, subqueries)))
+        }
+    }
+
     /// Create a physical plan from a logical plan
     fn create_initial_plan<'a>(
         &'a self,
@@ -468,6 +518,7 @@ impl DefaultPhysicalPlanner {
                     source,
                     projection,
                     filters,
+                    ordering,
                     fetch,
                     ..
                 }) => {
@@ -477,7 +528,30 @@ impl DefaultPhysicalPlanner {
                     // referred to in the query
                     let filters = unnormalize_cols(filters.iter().cloned());
                     let unaliased: Vec<Expr> = filters.into_iter().map(unalias).collect();
-                    source.scan(session_state, projection, &unaliased, *fetch).await
+                    let scan = if ordering.is_empty() {
+                        source.scan(session_state, projection, &unaliased, *fetch).await?
+                    } else {
+                        let ordering = unnormalize_cols(ordering.iter().cloned());
+                        source
+                            .scan_ordered(session_state, projection, &unaliased, &ordering, *fetch)
+                            .await?
+                    };
+                    match source.constraints() {
+                        Some(constraints) if session_state.config.validate_check_constraints() => {
+                            let checks = CheckConstraints::try_new(
+                                constraints,
+                                &source.schema(),
+                                scan.schema(),
+                                session_state,
+                            )?;
+                            if checks.is_empty() {
+                                Ok(scan)
+                            } else {
+                                Ok(Arc::new(CheckConstraintsExec::new(scan, checks)))
+                            }
+                        }
+                        _ => Ok(scan),
+                    }
                 }
                 LogicalPlan::Values(Values {
                     values,
@@ -513,12 +587,74 @@ impl DefaultPhysicalPlanner {
                     }
 
                     let input_exec = self.create_initial_plan(input, session_state).await?;
+                    let input_exec = self.plan_scalar_subqueries(window_expr, input_exec, session_state).await?;
+                    let logical_input_schema = input.schema();
+                    let physical_input_schema = input_exec.schema();
+
+                    let get_sort_keys = |expr: &Expr| match expr {
+                        Expr::WindowFunction {
+                            ref partition_by,
+                            ref order_by,
+                            ..
+                        } => generate_sort_key(partition_by, order_by),
+                        Expr::Alias(expr, _) => {
+                            // Convert &Box<T> to &T
+                            match &**expr {
+                                Expr::WindowFunction {
+                                    ref partition_by,
+                                    ref order_by,
+                                    ..} => generate_sort_key(partition_by, order_by),
+                                _ => unreachable!(),
+                            }
+                        }
+                        _ => unreachable!(),
+                    };
+                    let sort_keys = get_sort_keys(&window_expr[0]);
+                    if window_expr.len() > 1 {
+                        debug_assert!(
+                            window_expr[1..]
+                                .iter()
+                                .all(|expr| get_sort_keys(expr) == sort_keys),
+                            "all window expressions shall have the same sort keys, as guaranteed by logical planning"
+                        );
+                    }
+                    let sort_keys = sort_keys
+                        .iter()
+                        .map(|e| match e {
+                            Expr::Sort {
+                                expr,
+                                asc,
+                                nulls_first,
+                            } => create_physical_sort_expr(
+                                expr,
+                                logical_input_schema,
+                                &physical_input_schema,
+                                SortOptions {
+                                    descending: !*asc,
+                                    nulls_first: *nulls_first,
+                                },
+                                &session_state.execution_props,
+                            ),
+                            _ => unreachable!(),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    // an input of a single partition already sorted on the sort
+                    // keys is neither repartitioned nor sorted again
+                    let input_sorted = sort_keys.is_empty()
+                        || (input_exec.output_partitioning().partition_count() == 1
+                            && ordering_satisfy(
+                                input_exec.output_ordering(),
+                                Some(&sort_keys),
+                                &input_exec.equivalence_properties(),
+                            ));
 
                     // at this moment we are guaranteed by the logical planner
                     // to have all the window_expr to have equal sort key
                     let partition_keys = window_expr_common_partition_keys(window_expr)?;
 
                     let can_repartition = !partition_keys.is_empty()
+                        && !input_sorted
                         && session_state.config.target_partitions > 1
                         && session_state.config.repartition_windows;
 
@@ -546,60 +682,9 @@ impl DefaultPhysicalPlanner {
                     };
 
                     // add a sort phase
-                    let get_sort_keys = |expr: &Expr| match expr {
-                        Expr::WindowFunction {
-                            ref partition_by,
-                            ref order_by,
-                            ..
-                        } => generate_sort_key(partition_by, order_by),
-                        Expr::Alias(expr, _) => {
-                            // Convert &Box<T> to &T
-                            match &**expr {
-                                Expr::WindowFunction {
-                                    ref partition_by,
-                                    ref order_by,
-                                    ..} => generate_sort_key(partition_by, order_by),
-                                _ => unreachable!(),
-                            }
-                        }
-                        _ => unreachable!(),
-                    };
-                    let sort_keys = get_sort_keys(&window_expr[0]);
-                    if window_expr.len() > 1 {
-                        debug_assert!(
-                            window_expr[1..]
-                                .iter()
-                                .all(|expr| get_sort_keys(expr) == sort_keys),
-                            "all window expressions shall have the same sort keys, as guaranteed by logical planning"
-                        );
-                    }
-
-                    let logical_input_schema = input.schema();
-
-                    let input_exec = if sort_keys.is_empty() {
+                    let input_exec = if input_sorted {
                         input_exec
                     } else {
-                        let physical_input_schema = input_exec.schema();
-                        let sort_keys = sort_keys
-                            .iter()
-                            .map(|e| match e {
-                                Expr::Sort {
-                                    expr,
-                                    asc,
-                                    nulls_first,
-                                } => create_physical_sort_expr(
-                                    expr,
-                                    logical_input_schema,
-                                    &physical_input_schema,
-                                    SortOptions {
-                                        descending: !*asc,
-                                        nulls_first: *nulls_first,
-                                    },
-                                    &session_state.execution_props,
-                                ),
-                                _ => unreachable!(),
-                            })
-                            .collect::<Result<Vec<_>>>()?;
                         Arc::new(if can_repartition {
                             SortExec::new_with_partitioning(sort_keys, input_exec, true, None)
                         } else {
@@ -634,6 +719,8 @@ impl DefaultPhysicalPlanner {
                 }) => {
                     // Initially need to perform the aggregate and then merge the partitions
                     let input_exec = self.create_initial_plan(input, session_state).await?;
+                    let subquery_exprs = group_expr.iter().chain(aggr_expr).cloned().collect::<Vec<_>>();
+                    let input_exec = self.plan_scalar_subqueries(&subquery_exprs, input_exec, session_state).await?;
                     let physical_input_schema = input_exec.schema();
                     let logical_input_schema = input.as_ref().schema();
 
@@ -717,8 +804,90 @@ impl DefaultPhysicalPlanner {
                     )?);
                     Ok(self.create_initial_plan(&aggregate, session_state).await?)
                 }
+                LogicalPlan::Unnest(Unnest { input, column, schema }) => {
+                    let input_exec = self.create_initial_plan(input, session_state).await?;
+                    let index = input.schema().index_of_column(column)?;
+                    let column = Column::new(&column.name, index);
+                    let schema = SchemaRef::new(schema.as_ref().to_owned().into());
+                    Ok(Arc::new(UnnestExec::try_new(input_exec, column, schema)?))
+                }
+                LogicalPlan::Dedup(Dedup { input, on, keep, order_by }) => {
+                    let input_exec = self.create_initial_plan(input, session_state).await?;
+                    let logical_input_schema = input.schema();
+                    let physical_input_schema = input_exec.schema();
+                    let on = on
+                        .iter()
+                        .map(|e| {
+                            self.create_physical_expr(
+                                e,
+                                logical_input_schema,
+                                &physical_input_schema,
+                                session_state,
+                            )
+                        })
+                        .collect::<Result<Vec<Arc<dyn PhysicalExpr>>>>()?;
+                    let order_by = order_by
+                        .iter()
+                        .map(|e| match e {
+                            Expr::Sort {
+                                expr,
+                                asc,
+                                nulls_first,
+                            } => create_physical_sort_expr(
+                                expr,
+                                logical_input_schema,
+                                &physical_input_schema,
+                                SortOptions {
+                                    descending: !*asc,
+                                    nulls_first: *nulls_first,
+                                },
+                                &session_state.execution_props,
+                            ),
+                            _ => Err(DataFusionError::Plan(
+                                "Dedup can only be ordered by sort expressions".to_string(),
+                            )),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    // the rows with the same keys must be in the same partition
+                    let can_repartition = session_state.config.target_partitions > 1
+                        && session_state.config.repartition_aggregations;
+                    let input_exec = if can_repartition {
+                        Arc::new(RepartitionExec::try_new(
+                            input_exec,
+                            Partitioning::Hash(
+                                on.clone(),
+                                session_state.config.target_partitions,
+                            ),
+                        )?)
+                    } else {
+                        input_exec
+                    };
+
+                    // sort the rows of each key in the order of `order_by`, so
+                    // that the rows to keep are found while streaming
+                    let input_exec = if order_by.is_empty() {
+                        input_exec
+                    } else {
+                        let sort_keys = on
+                            .iter()
+                            .map(|expr| PhysicalSortExpr {
+                                expr: expr.clone(),
+                                options: SortOptions::default(),
+                            })
+                            .chain(order_by.iter().cloned())
+                            .collect();
+                        Arc::new(if can_repartition {
+                            SortExec::new_with_partitioning(sort_keys, input_exec, true, None)
+                        } else {
+                            SortExec::try_new(sort_keys, input_exec, None)?
+                        })
+                    };
+                    Ok(Arc::new(DedupExec::new(input_exec, on, *keep, order_by)))
+                }
                 LogicalPlan::Projection(Projection { input, expr, .. }) => {
                     let input_exec = self.create_initial_plan(input, session_state).await?;
+                    let input_exec = self.plan_scalar_subqueries(expr, input_exec, session_state).await?;
                     let input_schema = input.as_ref().schema();
 
                     let physical_exprs = expr
@@ -771,6 +940,9 @@ impl DefaultPhysicalPlanner {
                 }
                 LogicalPlan::Filter(filter) => {
                     let physical_input = self.create_initial_plan(filter.input(), session_state).await?;
+                    let physical_input = self
+                        .plan_scalar_subqueries(&[filter.predicate().clone()], physical_input, session_state)
+                        .await?;
                     let input_schema = physical_input.as_ref().schema();
                     let input_dfschema = filter.input().schema();
 
@@ -825,6 +997,7 @@ impl DefaultPhysicalPlanner {
                 }
                 LogicalPlan::Sort(Sort { expr, input, fetch, .. }) => {
                     let physical_input = self.create_initial_plan(input, session_state).await?;
+                    let physical_input = self.plan_scalar_subqueries(expr, physical_input, session_state).await?;
                     let input_schema = physical_input.as_ref().schema();
                     let input_dfschema = input.as_ref().schema();
                     let sort_expr = expr
@@ -877,6 +1050,12 @@ impl DefaultPhysicalPlanner {
                 }) => {
                     let left_df_schema = left.schema();
                     let physical_left = self.create_initial_plan(left, session_state).await?;
+                    // the subqueries of the filter are executed before the left side is
+                    // built, hence before the filter is evaluated
+                    let physical_left = match filter {
+                        Some(filter) => self.plan_scalar_subqueries(&[filter.clone()], physical_left, session_state).await?,
+                        None => physical_left,
+                    };
                     let right_df_schema = right.schema();
                     let physical_right = self.create_initial_plan(right, session_state).await?;
                     let join_on = keys
@@ -1088,6 +1267,39 @@ impl DefaultPhysicalPlanner {
                         "Unsupported logical plan: DropView".to_string(),
                     ))
                 }
+                LogicalPlan::RefreshMaterializedView(_) => {
+                    // There is no default plan for "REFRESH MATERIALIZED VIEW".
+                    // It must be handled at a higher level (so
+                    // that the view can be found in the context)
+                    Err(DataFusionError::Internal(
+                        "Unsupported logical plan: RefreshMaterializedView".to_string(),
+                    ))
+                }
+                LogicalPlan::AnalyzeTable(_) => {
+                    // There is no default plan for "ANALYZE TABLE".
+                    // It must be handled at a higher level (so
+                    // that the table can be found in the context)
+                    Err(DataFusionError::Internal(
+                        "Unsupported logical plan: AnalyzeTable".to_string(),
+                    ))
+                }
+                LogicalPlan::SetVariable(_) => {
+                    // There is no default plan for "SET variable".
+                    // It must be handled at a higher level (so
+                    // that the option can be set in the session)
+                    Err(DataFusionError::Internal(
+                        "Unsupported logical plan: SetVariable".to_string(),
+                    ))
+                }
+                LogicalPlan::Prepare(_) => {
+                    // A prepared statement is executed once its parameters
+                    // are bound, which replaces it by the plan of its statement
+                    Err(DataFusionError::NotImplemented(
+                        "Unsupported logical plan: Prepare, bind its parameters with \
+                         LogicalPlan::with_param_values"
+                            .to_string(),
+                    ))
+                }
                 LogicalPlan::CreateView(_) => {
                     // There is no default plan for "CREATE VIEW".
                     // It must be handled at a higher level (so
@@ -1446,6 +1658,7 @@ pub fn create_window_expr_with_name(
             partition_by,
             order_by,
             window_frame,
+            ignore_nulls,
         } => {
             let args = args
                 .iter()
@@ -1513,6 +1726,7 @@ pub fn create_window_expr_with_name(
                 &partition_by,
                 &order_by,
                 window_frame,
+                *ignore_nulls,
                 physical_input_schema,
             )
         }
@@ -1760,7 +1974,10 @@ mod tests {
     use arrow::record_batch::RecordBatch;
     use datafusion_common::assert_contains;
     use datafusion_common::{DFField, DFSchema, DFSchemaRef};
-    use datafusion_expr::{col, lit, sum, Extension, GroupingSet, LogicalPlanBuilder};
+    use datafusion_expr::{
+        col, lit, sum, AggregateFunction, Extension, GroupingSet, LogicalPlanBuilder,
+        WindowFunction,
+    };
     use fmt::Debug;
     use std::collections::HashMap;
     use std::convert::TryFrom;
@@ -1926,6 +2143,50 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn window_over_sorted_input() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]);
+        let window_expr = Expr::WindowFunction {
+            fun: WindowFunction::AggregateFunction(AggregateFunction::Sum),
+            args: vec![col("b")],
+            partition_by: vec![col("a")],
+            order_by: vec![col("b").sort(true, false)],
+            window_frame: None,
+            ignore_nulls: false,
+        };
+        let mut session_state = make_session_state();
+        session_state.config.target_partitions = 4;
+        let planner = DefaultPhysicalPlanner::default();
+
+        // the input sorted on the partition and order keys is used as is
+        let logical_plan = scan_empty(Some("test"), &schema, None)?
+            .sort(vec![col("a").sort(true, true), col("b").sort(true, false)])?
+            .window(vec![window_expr.clone()])?
+            .build()?;
+        let plan = planner
+            .create_initial_plan(&logical_plan, &session_state)
+            .await?;
+        let plan = displayable(plan.as_ref()).indent().to_string();
+        assert_eq!(plan.matches("SortExec").count(), 1, "{}", plan);
+        assert!(!plan.contains("RepartitionExec"), "{}", plan);
+
+        // the input sorted on the order key alone is repartitioned and sorted
+        let logical_plan = scan_empty(Some("test"), &schema, None)?
+            .sort(vec![col("b").sort(true, false)])?
+            .window(vec![window_expr])?
+            .build()?;
+        let plan = planner
+            .create_initial_plan(&logical_plan, &session_state)
+            .await?;
+        let plan = displayable(plan.as_ref()).indent().to_string();
+        assert_eq!(plan.matches("SortExec").count(), 2, "{}", plan);
+        assert!(plan.contains("RepartitionExec"), "{}", plan);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn errors() -> Result<()> {
         let bool_expr = col("c1").eq(col("c1"));
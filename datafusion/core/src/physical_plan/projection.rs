@@ -21,18 +21,21 @@
 //! projection expressions. `SELECT` without `FROM` will only evaluate expressions.
 
 use std::any::Any;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::error::Result;
 use crate::physical_plan::{
-    ColumnStatistics, DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    ColumnStatistics, DisplayFormatType, EquivalenceProperties, ExecutionPlan,
+    Partitioning, PhysicalExpr,
 };
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
+use datafusion_physical_expr::equivalence::project_equivalence_properties;
+use datafusion_physical_expr::utils::ordered_by;
 use log::debug;
 
 use super::expressions::{Column, PhysicalSortExpr};
@@ -51,6 +54,10 @@ pub struct ProjectionExec {
     schema: SchemaRef,
     /// The input plan
     input: Arc<dyn ExecutionPlan>,
+    /// The output columns of each input column projected without change
+    alias_map: HashMap<Column, Vec<Column>>,
+    /// The output ordering, on the output columns
+    output_ordering: Option<Vec<PhysicalSortExpr>>,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
 }
@@ -82,10 +89,58 @@ impl ProjectionExec {
             input_schema.metadata().clone(),
         ));
 
+        let mut alias_map: HashMap<Column, Vec<Column>> = HashMap::new();
+        for (i, (expr, name)) in expr.iter().enumerate() {
+            if let Some(column) = expr.as_any().downcast_ref::<Column>() {
+                alias_map
+                    .entry(column.clone())
+                    .or_default()
+                    .push(Column::new(name, i));
+            }
+        }
+
+        // the input ordering holds on the output for its leading projected
+        // expressions, and for increasing functions of them such as
+        // `date_trunc('day', ts)`, up to the first one that is not strictly
+        // increasing
+        let input_eq = input.equivalence_properties();
+        let output_ordering = input.output_ordering().and_then(|ordering| {
+            let mut output_ordering = vec![];
+            for sort_expr in ordering {
+                let mut output = None;
+                for (i, (e, name)) in expr.iter().enumerate() {
+                    match ordered_by(e, &sort_expr.expr, &input_eq) {
+                        Some(true) => {
+                            output = Some((Column::new(name, i), true));
+                            break;
+                        }
+                        Some(false) if output.is_none() => {
+                            output = Some((Column::new(name, i), false));
+                        }
+                        _ => {}
+                    }
+                }
+                let (column, strict) = match output {
+                    Some(output) => output,
+                    None => break,
+                };
+                output_ordering.push(PhysicalSortExpr {
+                    expr: Arc::new(column),
+                    options: sort_expr.options,
+                });
+                if !strict {
+                    break;
+                }
+            }
+            (!output_ordering.is_empty()).then(|| output_ordering)
+        });
+
         Ok(Self {
             expr,
             schema,
             input: input.clone(),
+            alias_map,
+            output_ordering,
             metrics: ExecutionPlanMetricsSet::new(),
         })
     }
@@ -118,11 +173,37 @@ impl ExecutionPlan for ProjectionExec {
 
     /// Get the output partitioning of this plan
     fn output_partitioning(&self) -> Partitioning {
-        self.input.output_partitioning()
+        match self.input.output_partitioning() {
+            Partitioning::Hash(exprs, n) => {
+                // the hash partitioning holds if all its columns are projected
+                let input_eq = self.input.equivalence_properties();
+                exprs
+                    .iter()
+                    .map(|expr| {
+                        let column = expr.as_any().downcast_ref::<Column>()?;
+                        let output = project_column(column, &self.alias_map, &input_eq)?;
+                        Some(Arc::new(output) as Arc<dyn PhysicalExpr>)
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(|exprs| Partitioning::Hash(exprs, n))
+                    .unwrap_or(Partitioning::UnknownPartitioning(n))
+            }
+            partitioning => partitioning,
+        }
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
-        self.input.output_ordering()
+        self.output_ordering.as_deref()
+    }
+
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        let mut new_properties = EquivalenceProperties::new(self.schema());
+        project_equivalence_properties(
+            self.input.equivalence_properties(),
+            &self.alias_map,
+            &mut new_properties,
+        );
+        new_properties
     }
 
     fn maintains_input_order(&self) -> bool {
@@ -154,6 +235,8 @@ impl ExecutionPlan for ProjectionExec {
             schema: self.schema.clone(),
             expr: self.expr.iter().map(|x| x.0.clone()).collect(),
             input: self.input.execute(partition, context)?,
+            partition,
+            num_rows: 0,
             baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
         }))
     }
@@ -213,6 +296,26 @@ fn get_field_metadata(
         .and_then(|f| f.metadata().cloned())
 }
 
+/// Returns the output column projected from `column`, or from an input column
+/// equivalent to it
+fn project_column(
+    column: &Column,
+    alias_map: &HashMap<Column, Vec<Column>>,
+    input_eq: &EquivalenceProperties,
+) -> Option<Column> {
+    if let Some(outputs) = alias_map.get(column) {
+        return Some(outputs[0].clone());
+    }
+    let class = input_eq
+        .classes()
+        .iter()
+        .find(|class| class.contains(column))?;
+    class
+        .iter()
+        .find_map(|column| alias_map.get(column))
+        .map(|outputs| outputs[0].clone())
+}
+
 fn stats_projection(
     stats: Statistics,
     exprs: impl Iterator<Item = Arc<dyn PhysicalExpr>>,
@@ -241,13 +344,22 @@ fn stats_projection(
 }
 
 impl ProjectionStream {
-    fn batch_project(&self, batch: &RecordBatch) -> ArrowResult<RecordBatch> {
+    fn batch_project(&mut self, batch: &RecordBatch) -> ArrowResult<RecordBatch> {
         // records time on drop
         let _timer = self.baseline_metrics.elapsed_compute().timer();
+        let start = self.num_rows;
+        self.num_rows += batch.num_rows();
         let arrays = self
             .expr
             .iter()
-            .map(|expr| expr.evaluate(batch))
+            .map(|expr| {
+                expr.evaluate(batch).map_err(|e| {
+                    e.with_runtime_context(format!(
+                        "ProjectionExec: evaluating {} on rows {}..{} of partition {}",
+                        expr, start, self.num_rows, self.partition
+                    ))
+                })
+            })
             .map(|r| r.map(|v| v.into_array(batch.num_rows())))
             .collect::<Result<Vec<_>>>()?;
 
@@ -260,6 +372,10 @@ struct ProjectionStream {
     schema: SchemaRef,
     expr: Vec<Arc<dyn PhysicalExpr>>,
     input: SendableRecordBatchStream,
+    /// The partition of the input, reported in errors
+    partition: usize,
+    /// The number of input rows before the current batch, reported in errors
+    num_rows: usize,
     baseline_metrics: BaselineMetrics,
 }
 
@@ -270,7 +386,10 @@ impl Stream for ProjectionStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        let poll = self.input.poll_next_unpin(cx).map(|x| match x {
+        self.baseline_metrics.record_poll_start();
+        let poll = self.input.poll_next_unpin(cx);
+        let poll = self.baseline_metrics.record_input_poll(poll);
+        let poll = poll.map(|x| match x {
             Some(Ok(batch)) => Some(self.batch_project(&batch)),
             other => other,
         });
@@ -295,11 +414,14 @@ impl RecordBatchStream for ProjectionStream {
 mod tests {
 
     use super::*;
+    use crate::physical_plan::collect;
     use crate::physical_plan::expressions::{self, col};
+    use crate::physical_plan::memory::MemoryExec;
     use crate::prelude::SessionContext;
     use crate::scalar::ScalarValue;
     use crate::test::{self};
     use crate::test_util;
+    use datafusion_expr::Operator;
     use futures::future;
 
     #[tokio::test]
@@ -341,6 +463,41 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn project_error_context() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+        let batch = test::build_table_i32(
+            ("a", &vec![1, 2]),
+            ("b", &vec![1, 0]),
+            ("c", &vec![0, 0]),
+        );
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone(), batch]],
+            schema.clone(),
+            None,
+        )?);
+        let modulo = expressions::binary(
+            col("a", &schema)?,
+            Operator::Modulo,
+            col("b", &schema)?,
+            &schema,
+        )?;
+        let projection = Arc::new(ProjectionExec::try_new(
+            vec![(modulo, "a % b".to_string())],
+            input,
+        )?);
+
+        let err = collect(projection, task_ctx).await.unwrap_err();
+        assert_eq!(err.to_string(), "Arrow error: Divide by zero error");
+        assert_eq!(
+            err.runtime_context(),
+            vec!["ProjectionExec: evaluating a@0 % b@1 on rows 0..2 of partition 0"]
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_stats_projection_columns_only() {
         let source = Statistics {
@@ -0,0 +1,269 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! ScalarSubqueryExec executes the uncorrelated scalar subqueries of the
+//! expressions of its parent, before streaming its input.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use datafusion_common::ScalarValue;
+use datafusion_physical_expr::expressions::ScalarSubqueryValue;
+use futures::{StreamExt, TryStreamExt};
+
+use super::expressions::PhysicalSortExpr;
+use super::stream::RecordBatchStreamAdapter;
+use super::{
+    collect, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    Statistics,
+};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::TaskContext;
+
+/// ScalarSubqueryExec executes the uncorrelated scalar subqueries of the
+/// expressions of its parent, such as a
+/// [`ProjectionExec`](super::projection::ProjectionExec), and streams its
+/// input unchanged.
+///
+/// The subqueries are executed when the first partition of the input is
+/// polled, and set the [`ScalarSubqueryValue`]s the expressions evaluate
+/// to. The references to the same subquery in a query, in this or other
+/// operators, share its value: the subquery is executed once per execution
+/// of the plan, that is per [`TaskContext`], by the first operator to run,
+/// while the others wait for its value.
+#[derive(Debug)]
+pub struct ScalarSubqueryExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The plans of the subqueries, with their values
+    subqueries: Vec<(Arc<dyn ExecutionPlan>, Arc<ScalarSubqueryValue>)>,
+}
+
+impl ScalarSubqueryExec {
+    /// Create a ScalarSubqueryExec executing the single column plans of
+    /// `subqueries` into their values before streaming `input`
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        subqueries: Vec<(Arc<dyn ExecutionPlan>, Arc<ScalarSubqueryValue>)>,
+    ) -> Self {
+        Self { input, subqueries }
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The plans of the subqueries, with their values
+    pub fn subqueries(&self) -> &[(Arc<dyn ExecutionPlan>, Arc<ScalarSubqueryValue>)] {
+        &self.subqueries
+    }
+}
+
+impl ExecutionPlan for ScalarSubqueryExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn relies_on_input_order(&self) -> bool {
+        false
+    }
+
+    fn maintains_input_order(&self) -> bool {
+        true
+    }
+
+    /// The input, followed by the plans of the subqueries
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        let mut children = vec![self.input.clone()];
+        children.extend(self.subqueries.iter().map(|(plan, _)| plan.clone()));
+        children
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != self.subqueries.len() + 1 {
+            return Err(DataFusionError::Internal(
+                "ScalarSubqueryExec wrong number of children".to_string(),
+            ));
+        }
+        let subqueries = children
+            .split_off(1)
+            .into_iter()
+            .zip(self.subqueries.iter())
+            .map(|(plan, (_, value))| (plan, value.clone()))
+            .collect();
+        Ok(Arc::new(Self::new(children.remove(0), subqueries)))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.clone();
+        let subqueries = self.subqueries.clone();
+        let stream = futures::stream::once(async move {
+            // the values are bound to the task context of this execution
+            let execution: Arc<dyn Any + Send + Sync> = context.clone();
+            for (plan, value) in &subqueries {
+                value
+                    .get_or_try_init(&execution, || {
+                        execute_subquery(plan.clone(), context.clone())
+                    })
+                    .await?;
+            }
+            input.execute(partition, context)
+        })
+        .map_err(ArrowError::from)
+        .try_flatten();
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.boxed(),
+        )))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "ScalarSubqueryExec: subqueries={}",
+                    self.subqueries.len()
+                )
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
+
+/// Executes the plan of a scalar subquery, which returns at most one row of
+/// a single column, into its value
+async fn execute_subquery(
+    plan: Arc<dyn ExecutionPlan>,
+    context: Arc<TaskContext>,
+) -> Result<ScalarValue> {
+    let data_type = plan.schema().field(0).data_type().clone();
+    let batches = collect(plan, context).await?;
+    let mut rows = batches.iter().filter(|batch| batch.num_rows() > 0);
+    match (rows.next(), rows.next()) {
+        (None, _) => ScalarValue::try_from(&data_type),
+        (Some(batch), None) if batch.num_rows() == 1 => {
+            ScalarValue::try_from_array(batch.column(0), 0)
+        }
+        _ => Err(DataFusionError::Execution(
+            "Scalar subquery returned more than one row".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::prelude::SessionContext;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    /// A plan of the partitions of the batches of `values`
+    fn int_exec(values: Vec<Vec<Vec<i32>>>) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, true)]));
+        let partitions = values
+            .into_iter()
+            .map(|batches| {
+                batches
+                    .into_iter()
+                    .map(|values| {
+                        RecordBatch::try_new(
+                            schema.clone(),
+                            vec![Arc::new(Int32Array::from(values))],
+                        )
+                        .map_err(DataFusionError::from)
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Arc::new(MemoryExec::try_new(&partitions, schema, None)?))
+    }
+
+    #[tokio::test]
+    async fn execute_subqueries_once() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+        let input = int_exec(vec![vec![vec![1, 2]], vec![vec![3]]])?;
+        let value = Arc::new(ScalarSubqueryValue::new());
+        let empty = Arc::new(ScalarSubqueryValue::new());
+        let exec = Arc::new(ScalarSubqueryExec::new(
+            input,
+            vec![
+                (int_exec(vec![vec![vec![], vec![42]]])?, value.clone()),
+                (int_exec(vec![vec![]])?, empty.clone()),
+            ],
+        ));
+        assert_eq!(exec.children().len(), 3);
+
+        for partition in 0..2 {
+            let stream = exec.execute(partition, task_ctx.clone())?;
+            assert_eq!(common::collect(stream).await?.len(), 1);
+        }
+        assert_eq!(value.get(), Some(ScalarValue::Int32(Some(42))));
+        assert_eq!(empty.get(), Some(ScalarValue::Int32(None)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn subquery_more_than_one_row() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let exec = ScalarSubqueryExec::new(
+            int_exec(vec![vec![vec![1]]])?,
+            vec![(
+                int_exec(vec![vec![vec![1], vec![2]]])?,
+                Arc::new(ScalarSubqueryValue::new()),
+            )],
+        );
+        let stream = exec.execute(0, session_ctx.task_ctx())?;
+        let err = common::collect(stream).await.unwrap_err();
+        assert!(err.to_string().contains("more than one row"), "{}", err);
+        Ok(())
+    }
+}
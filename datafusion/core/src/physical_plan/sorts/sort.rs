@@ -34,8 +34,9 @@ use crate::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeStrea
 use crate::physical_plan::sorts::SortedStream;
 use crate::physical_plan::stream::{RecordBatchReceiverStream, RecordBatchStreamAdapter};
 use crate::physical_plan::{
-    DisplayFormatType, Distribution, EmptyRecordBatchStream, ExecutionPlan, Partitioning,
-    RecordBatchStream, SendableRecordBatchStream, Statistics,
+    DisplayFormatType, Distribution, EmptyRecordBatchStream, EquivalenceProperties,
+    ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    Statistics,
 };
 use crate::prelude::SessionConfig;
 use arrow::array::{make_array, Array, ArrayRef, MutableArrayData};
@@ -549,6 +550,7 @@ impl Stream for SortedSizedRecordBatchStream {
         mut self: std::pin::Pin<&mut Self>,
         _: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.metrics.record_poll_start();
         match self.sorted_iter.next() {
             None => Poll::Ready(None),
             Some(slices) => {
@@ -768,6 +770,10 @@ impl ExecutionPlan for SortExec {
         Some(&self.expr)
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn with_new_children(
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -919,7 +925,11 @@ async fn do_sort(
         fetch,
     );
     context.runtime_env().register_requester(sorter.id());
-    while let Some(batch) = input.next().await {
+    while let Some(batch) = futures::future::poll_fn(|cx| {
+        tracking_metrics.record_input_poll(input.poll_next_unpin(cx))
+    })
+    .await
+    {
         let batch = batch?;
         sorter.insert_batch(batch, &tracking_metrics).await?;
     }
@@ -46,8 +46,8 @@ use crate::physical_plan::sorts::{RowIndex, SortKeyCursor, SortedStream};
 use crate::physical_plan::stream::RecordBatchReceiverStream;
 use crate::physical_plan::{
     common::spawn_execution, expressions::PhysicalSortExpr, DisplayFormatType,
-    Distribution, ExecutionPlan, Partitioning, PhysicalExpr, RecordBatchStream,
-    SendableRecordBatchStream, Statistics,
+    Distribution, EquivalenceProperties, ExecutionPlan, Partitioning, PhysicalExpr,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
 };
 
 /// Sort preserving merge execution plan
@@ -135,6 +135,10 @@ impl ExecutionPlan for SortPreservingMergeExec {
         Some(&self.expr)
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         vec![self.input.clone()]
     }
@@ -389,7 +393,8 @@ impl SortPreservingMergeStream {
             }
 
             // Fetch a new input record and create a cursor from it
-            match futures::ready!(stream.poll_next_unpin(cx)) {
+            let poll = stream.poll_next_unpin(cx);
+            match futures::ready!(self.tracking_metrics.record_input_poll(poll)) {
                 None => return Poll::Ready(Ok(())),
                 Some(Err(e)) => {
                     return Poll::Ready(Err(e));
@@ -532,6 +537,7 @@ impl Stream for SortPreservingMergeStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.tracking_metrics.record_poll_start();
         let poll = self.poll_next_inner(cx);
         self.tracking_metrics.record_poll(poll)
     }
@@ -223,7 +223,9 @@ impl futures::Stream for ObservedStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         let poll = self.inner.poll_next_unpin(cx);
+        let poll = self.baseline_metrics.record_input_poll(poll);
         self.baseline_metrics.record_poll(poll)
     }
 }
@@ -0,0 +1,368 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! UnnestExec produces a row for each element of a list column of its input,
+//! repeating the values of the other columns of the row.
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::array::{
+    Array, ArrayRef, GenericListArray, OffsetSizeTrait, UInt32Array, UInt64Array,
+};
+use arrow::compute::take;
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use futures::stream::{Stream, StreamExt};
+use log::debug;
+
+use super::common::BatchSplitter;
+use super::expressions::{Column, PhysicalSortExpr};
+use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::TaskContext;
+use crate::physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning};
+
+/// UnnestExec produces a row for each element of the list `column` of its
+/// input, with the values of the other columns repeated. Rows whose list is
+/// null or empty produce no output rows.
+#[derive(Debug)]
+pub struct UnnestExec {
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The output schema, with the list column replaced by its elements
+    schema: SchemaRef,
+    /// The list column to unnest
+    column: Column,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl UnnestExec {
+    /// Create an UnnestExec on an input, producing `schema`
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        column: Column,
+        schema: SchemaRef,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        match input_schema.field(column.index()).data_type() {
+            DataType::List(_) | DataType::LargeList(_) => Ok(Self {
+                input,
+                schema,
+                column,
+                metrics: ExecutionPlanMetricsSet::new(),
+            }),
+            other => Err(DataFusionError::Plan(format!(
+                "Unnest requires a list column, {} has type {:?}",
+                column, other
+            ))),
+        }
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The list column to unnest
+    pub fn column(&self) -> &Column {
+        &self.column
+    }
+}
+
+impl ExecutionPlan for UnnestExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn relies_on_input_order(&self) -> bool {
+        false
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(UnnestExec::try_new(
+            children[0].clone(),
+            self.column.clone(),
+            self.schema.clone(),
+        )?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        debug!("Start UnnestExec::execute for partition {} of context session_id {} and task_id {:?}", partition, context.session_id(), context.task_id());
+        let batch_size = context.session_config().batch_size();
+        Ok(Box::pin(UnnestStream {
+            input: self.input.execute(partition, context)?,
+            schema: self.schema.clone(),
+            column: self.column.clone(),
+            output: BatchSplitter::new(batch_size),
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "UnnestExec: {}", self.column)
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// A stream that unnests the list column of the batches of its input
+struct UnnestStream {
+    /// The input stream
+    input: SendableRecordBatchStream,
+    /// The output schema
+    schema: SchemaRef,
+    /// The list column to unnest
+    column: Column,
+    /// Splits the unnested batches into batches of at most `batch_size` rows
+    output: BatchSplitter,
+    /// runtime metrics recording
+    baseline_metrics: BaselineMetrics,
+}
+
+impl Stream for UnnestStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
+        if let Some(batch) = self.output.next_batch() {
+            return self
+                .baseline_metrics
+                .record_poll(Poll::Ready(Some(Ok(batch))));
+        }
+        let poll = self.input.poll_next_unpin(cx);
+        let poll = self.baseline_metrics.record_input_poll(poll);
+        let poll = poll.map(|x| match x {
+            Some(Ok(batch)) => {
+                let timer = self.baseline_metrics.elapsed_compute().timer();
+                let unnested = unnest_batch(&batch, &self.schema, &self.column)
+                    .map(|batch| self.output.split(batch));
+                timer.done();
+                Some(unnested)
+            }
+            other => other,
+        });
+        self.baseline_metrics.record_poll(poll)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // at least the number of record batches of the input
+        (self.input.size_hint().0, None)
+    }
+}
+
+impl RecordBatchStream for UnnestStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+fn unnest_batch(
+    batch: &RecordBatch,
+    schema: &SchemaRef,
+    column: &Column,
+) -> ArrowResult<RecordBatch> {
+    let list_array = batch.column(column.index());
+    match list_array.data_type() {
+        DataType::List(_) => unnest_list::<i32>(batch, schema, column.index()),
+        DataType::LargeList(_) => unnest_list::<i64>(batch, schema, column.index()),
+        other => Err(ArrowError::ComputeError(format!(
+            "Unnest requires a list column, {} has type {:?}",
+            column, other
+        ))),
+    }
+}
+
+/// Unnests the list column at `index` of `batch`, taking the elements of the
+/// lists and, for the other columns, the row of each element
+fn unnest_list<O: OffsetSizeTrait>(
+    batch: &RecordBatch,
+    schema: &SchemaRef,
+    index: usize,
+) -> ArrowResult<RecordBatch> {
+    let list_array = batch
+        .column(index)
+        .as_any()
+        .downcast_ref::<GenericListArray<O>>()
+        .ok_or_else(|| {
+            ArrowError::ComputeError("Failed to downcast the list column".to_string())
+        })?;
+
+    let offsets = list_array.value_offsets();
+    let mut row_indices = vec![];
+    let mut value_indices = vec![];
+    for row in 0..list_array.len() {
+        if list_array.is_null(row) {
+            continue;
+        }
+        let start = offsets[row].to_usize().unwrap();
+        let end = offsets[row + 1].to_usize().unwrap();
+        for value in start..end {
+            row_indices.push(row as u32);
+            value_indices.push(value as u64);
+        }
+    }
+    let row_indices = UInt32Array::from(row_indices);
+    let value_indices = UInt64Array::from(value_indices);
+
+    let columns = batch
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, array)| {
+            if i == index {
+                take(list_array.values().as_ref(), &value_indices, None)
+            } else {
+                take(array.as_ref(), &row_indices, None)
+            }
+        })
+        .collect::<ArrowResult<Vec<ArrayRef>>>()?;
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::prelude::{SessionConfig, SessionContext};
+    use arrow::array::{ListArray, StringArray};
+    use arrow::datatypes::{Field, Int32Type, Schema};
+
+    fn unnest_exec() -> Result<Arc<UnnestExec>> {
+        let input_schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new(
+                "values",
+                DataType::List(Box::new(Field::new("item", DataType::Int32, true))),
+                true,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            input_schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c", "d"])),
+                Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                    Some(vec![Some(1), Some(2)]),
+                    None,
+                    Some(vec![]),
+                    Some(vec![Some(3), None]),
+                ])),
+            ],
+        )?;
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], input_schema, None)?);
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("values", DataType::Int32, true),
+        ]));
+        Ok(Arc::new(UnnestExec::try_new(
+            input,
+            Column::new("values", 1),
+            schema,
+        )?))
+    }
+
+    #[tokio::test]
+    async fn unnest_list_column() -> Result<()> {
+        let session_ctx = SessionContext::new();
+        let task_ctx = session_ctx.task_ctx();
+        let batches = collect(unnest_exec()?, task_ctx).await?;
+        let expected = vec![
+            "+------+--------+",
+            "| name | values |",
+            "+------+--------+",
+            "| a    | 1      |",
+            "| a    | 2      |",
+            "| d    | 3      |",
+            "| d    |        |",
+            "+------+--------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unnest_split_batches() -> Result<()> {
+        let session_ctx =
+            SessionContext::with_config(SessionConfig::new().with_batch_size(3));
+        let task_ctx = session_ctx.task_ctx();
+        let batches = collect(unnest_exec()?, task_ctx).await?;
+        let num_rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(num_rows, vec![3, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn unnest_non_list_column() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let input = Arc::new(MemoryExec::try_new(&[vec![]], schema.clone(), None)?);
+        let err = UnnestExec::try_new(input, Column::new("a", 0), schema).unwrap_err();
+        assert_eq!(
+            "Error during planning: Unnest requires a list column, a@0 has type Int32",
+            err.to_string()
+        );
+        Ok(())
+    }
+}
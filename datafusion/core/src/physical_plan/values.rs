@@ -89,8 +89,26 @@ impl ValuesExec {
         Ok(Self { schema, data })
     }
 
+    /// Create a new values exec from the record batches of its data
+    pub fn try_new_from_batches(
+        schema: SchemaRef,
+        data: Vec<RecordBatch>,
+    ) -> Result<Self> {
+        if data.is_empty() {
+            return Err(DataFusionError::Plan("Values list cannot be empty".into()));
+        }
+        if let Some(batch) = data.iter().find(|batch| batch.schema() != schema) {
+            return Err(DataFusionError::Plan(format!(
+                "Values batch schema {:?} does not match the schema {:?}",
+                batch.schema(),
+                schema
+            )));
+        }
+        Ok(Self { schema, data })
+    }
+
     /// provides the data
-    fn data(&self) -> Vec<RecordBatch> {
+    pub fn data(&self) -> Vec<RecordBatch> {
         self.data.clone()
     }
 }
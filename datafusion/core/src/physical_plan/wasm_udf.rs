@@ -0,0 +1,329 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Scalar UDFs implemented by functions of WebAssembly modules
+
+use std::sync::Arc;
+
+use arrow::array::{
+    as_primitive_array, ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array,
+};
+use arrow::datatypes::{DataType, Float32Type, Float64Type, Int32Type, Int64Type};
+use datafusion_expr::{
+    ReturnTypeFunction, ScalarFunctionImplementation, ScalarUDF, Signature, Volatility,
+    WasmUdfBody,
+};
+use wasmtime::{
+    Config, Engine, ExternType, Instance, Module, Store, StoreLimitsBuilder, Val, ValType,
+};
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::functions::make_scalar_function;
+
+/// The limits of the resources used by the functions of a WebAssembly UDF,
+/// which make a call fail instead of running forever or exhausting the
+/// memory of the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmUdfLimits {
+    /// The fuel, roughly a number of WebAssembly instructions, that the
+    /// function may consume per row of a batch
+    pub fuel_per_row: u64,
+    /// The maximum size in bytes of the linear memory of the module
+    pub max_memory: usize,
+}
+
+impl Default for WasmUdfLimits {
+    fn default() -> Self {
+        Self {
+            fuel_per_row: 1_000_000,
+            max_memory: 64 * 1024 * 1024,
+        }
+    }
+}
+
+fn wasm_error(e: impl std::fmt::Display) -> DataFusionError {
+    DataFusionError::Execution(format!("WebAssembly error: {}", e))
+}
+
+fn data_type(value_type: &ValType) -> Result<DataType> {
+    match value_type {
+        ValType::I32 => Ok(DataType::Int32),
+        ValType::I64 => Ok(DataType::Int64),
+        ValType::F32 => Ok(DataType::Float32),
+        ValType::F64 => Ok(DataType::Float64),
+        other => Err(DataFusionError::Plan(format!(
+            "WebAssembly UDFs can not take or return values of type {}",
+            other
+        ))),
+    }
+}
+
+/// Creates the scalar UDF `name`, calling the function `body.export` of the
+/// WebAssembly module `body.module` for each row.
+///
+/// The function must take and return a single value of the types `i32`,
+/// `i64`, `f32` and `f64`, which are the Arrow types `Int32`, `Int64`,
+/// `Float32` and `Float64`. The UDF returns null for the rows where any
+/// argument is null. The module must not import anything.
+///
+/// The UDF keeps its body, so that it is serialized with it. The calls are
+/// limited by the default [`WasmUdfLimits`].
+pub fn create_wasm_udf(name: &str, body: WasmUdfBody) -> Result<ScalarUDF> {
+    create_wasm_udf_with_limits(name, body, WasmUdfLimits::default())
+}
+
+/// Creates the scalar UDF `name` like [`create_wasm_udf`], whose calls fail
+/// once they exceed `limits`
+pub fn create_wasm_udf_with_limits(
+    name: &str,
+    body: WasmUdfBody,
+    limits: WasmUdfLimits,
+) -> Result<ScalarUDF> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).map_err(wasm_error)?;
+    let module = Module::new(&engine, &body.module).map_err(wasm_error)?;
+    let func_type = match module.get_export(&body.export) {
+        Some(ExternType::Func(func_type)) => func_type,
+        _ => {
+            return Err(DataFusionError::Plan(format!(
+                "WebAssembly module of UDF {} does not export a function named {}",
+                name, body.export
+            )))
+        }
+    };
+    let params = func_type.params().collect::<Vec<_>>();
+    let arg_types = params.iter().map(data_type).collect::<Result<Vec<_>>>()?;
+    let result = match func_type.results().collect::<Vec<_>>().as_slice() {
+        [result] => result.clone(),
+        _ => {
+            return Err(DataFusionError::Plan(format!(
+                "WebAssembly function {} must return a single value",
+                body.export
+            )))
+        }
+    };
+    let return_type = Arc::new(data_type(&result)?);
+
+    let export = body.export.clone();
+    let fun: ScalarFunctionImplementation =
+        make_scalar_function(move |args: &[ArrayRef]| {
+            // a function without parameters gets a null array with the row count
+            let num_rows = args.first().map(|array| array.len()).unwrap_or(1);
+
+            // each batch runs in its own instance of the module, with the fuel
+            // of its rows
+            let store_limits = StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory)
+                .instances(1)
+                .build();
+            let mut store = Store::new(&engine, store_limits);
+            store.limiter(|store_limits| store_limits);
+            store
+                .add_fuel(limits.fuel_per_row.saturating_mul(num_rows as u64))
+                .map_err(wasm_error)?;
+            let instance = Instance::new(&mut store, &module, &[]).map_err(wasm_error)?;
+            let func = instance
+                .get_func(&mut store, &export)
+                .ok_or_else(|| wasm_error(format!("missing function {}", export)))?;
+
+            let args = &args[..params.len()];
+            let mut inputs = Vec::with_capacity(params.len());
+            let mut output = [Val::I32(0)];
+            let mut results = Vec::with_capacity(num_rows);
+            for row in 0..num_rows {
+                if args.iter().any(|array| array.is_null(row)) {
+                    results.push(None);
+                    continue;
+                }
+                inputs.clear();
+                inputs.extend(args.iter().zip(&params).map(|(array, param)| {
+                    match param {
+                        ValType::I32 => Val::I32(
+                            as_primitive_array::<Int32Type>(array.as_ref()).value(row),
+                        ),
+                        ValType::I64 => Val::I64(
+                            as_primitive_array::<Int64Type>(array.as_ref()).value(row),
+                        ),
+                        ValType::F32 => Val::F32(
+                            as_primitive_array::<Float32Type>(array.as_ref())
+                                .value(row)
+                                .to_bits(),
+                        ),
+                        _ => Val::F64(
+                            as_primitive_array::<Float64Type>(array.as_ref())
+                                .value(row)
+                                .to_bits(),
+                        ),
+                    }
+                }));
+                func.call(&mut store, &inputs, &mut output)
+                    .map_err(wasm_error)?;
+                results.push(Some(output[0].clone()));
+            }
+
+            let array: ArrayRef = match result {
+                ValType::I32 => Arc::new(
+                    results
+                        .iter()
+                        .map(|v| v.as_ref().and_then(Val::i32))
+                        .collect::<Int32Array>(),
+                ),
+                ValType::I64 => Arc::new(
+                    results
+                        .iter()
+                        .map(|v| v.as_ref().and_then(Val::i64))
+                        .collect::<Int64Array>(),
+                ),
+                ValType::F32 => Arc::new(
+                    results
+                        .iter()
+                        .map(|v| v.as_ref().and_then(Val::f32))
+                        .collect::<Float32Array>(),
+                ),
+                _ => Arc::new(
+                    results
+                        .iter()
+                        .map(|v| v.as_ref().and_then(Val::f64))
+                        .collect::<Float64Array>(),
+                ),
+            };
+            Ok(array)
+        });
+    let return_type: ReturnTypeFunction = Arc::new(move |_| Ok(Arc::clone(&return_type)));
+
+    Ok(ScalarUDF::new(
+        name,
+        &Signature::exact(arg_types, Volatility::Immutable),
+        &return_type,
+        &fun,
+    )
+    .with_wasm_body(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::WASM_ADD_MODULE as ADD_MODULE;
+    use datafusion_expr::ColumnarValue;
+
+    /// `(func (export "spin") (param i64) (result i64) (loop br 0) local.get 0)`
+    const SPIN_MODULE: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x06, 0x01, 0x60, 0x01, 0x7e, 0x01, 0x7e, // types
+        0x03, 0x02, 0x01, 0x00, // functions
+        0x07, 0x08, 0x01, 0x04, 0x73, 0x70, 0x69, 0x6e, 0x00, 0x00, // exports
+        0x0a, 0x0b, 0x01, 0x09, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b, 0x20, 0x00,
+        0x0b, // code
+    ];
+
+    /// The add module with a memory of 2 pages of 64 KiB: `(memory 2)`
+    fn add_module_with_memory() -> Vec<u8> {
+        // the memory section follows the header, types and functions sections
+        let functions_end = 8 + 9 + 4;
+        [
+            &ADD_MODULE[..functions_end],
+            &[0x05, 0x03, 0x01, 0x00, 0x02], // memories
+            &ADD_MODULE[functions_end..],
+        ]
+        .concat()
+    }
+
+    fn call(udf: &ScalarUDF, args: Vec<ArrayRef>) -> Result<ArrayRef> {
+        let args = args
+            .into_iter()
+            .map(ColumnarValue::Array)
+            .collect::<Vec<_>>();
+        match (udf.fun)(&args)? {
+            ColumnarValue::Array(array) => Ok(array),
+            ColumnarValue::Scalar(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn wasm_udf() -> Result<()> {
+        let body = WasmUdfBody {
+            module: ADD_MODULE.to_vec(),
+            export: "add".to_string(),
+        };
+        let udf = create_wasm_udf("add", body.clone())?;
+        assert_eq!(
+            udf.signature,
+            Signature::exact(
+                vec![DataType::Int64, DataType::Int64],
+                Volatility::Immutable
+            )
+        );
+        assert_eq!((udf.return_type)(&[])?.as_ref(), &DataType::Int64);
+        assert_eq!(udf.wasm_body.as_deref(), Some(&body));
+
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(3)]));
+        let b: ArrayRef = Arc::new(Int64Array::from(vec![Some(10), Some(20), Some(30)]));
+        let result = call(&udf, vec![a, b])?;
+        assert_eq!(
+            as_primitive_array::<Int64Type>(result.as_ref()),
+            &Int64Array::from(vec![Some(11), None, Some(33)])
+        );
+
+        let body = WasmUdfBody {
+            module: ADD_MODULE.to_vec(),
+            export: "sub".to_string(),
+        };
+        let err = create_wasm_udf("sub", body).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("does not export a function named sub"));
+        Ok(())
+    }
+    #[test]
+    fn wasm_udf_limits() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+
+        // a function running forever fails once it consumed its fuel
+        let body = WasmUdfBody {
+            module: SPIN_MODULE.to_vec(),
+            export: "spin".to_string(),
+        };
+        let limits = WasmUdfLimits {
+            fuel_per_row: 10_000,
+            ..Default::default()
+        };
+        let udf = create_wasm_udf_with_limits("spin", body, limits)?;
+        let err = call(&udf, vec![a.clone()]).unwrap_err();
+        assert!(err.to_string().contains("WebAssembly error"), "{}", err);
+
+        // a module with a memory is instantiated within the default limits
+        let body = WasmUdfBody {
+            module: add_module_with_memory(),
+            export: "add".to_string(),
+        };
+        let udf = create_wasm_udf("add", body.clone())?;
+        let result = call(&udf, vec![a.clone(), a.clone()])?;
+        assert_eq!(
+            as_primitive_array::<Int64Type>(result.as_ref()),
+            &Int64Array::from(vec![2, 4])
+        );
+        // but not with a smaller memory than its own
+        let limits = WasmUdfLimits {
+            max_memory: 64 * 1024,
+            ..Default::default()
+        };
+        let udf = create_wasm_udf_with_limits("add", body, limits)?;
+        assert!(call(&udf, vec![a.clone(), a]).is_err());
+        Ok(())
+    }
+}
@@ -22,7 +22,7 @@ use crate::physical_plan::{
     aggregates,
     expressions::{
         cume_dist, dense_rank, lag, lead, percent_rank, rank, Literal, NthValue,
-        PhysicalSortExpr, RowNumber,
+        PhysicalSortExpr, RowNumber, WindowShift,
     },
     type_coercion::coerce,
     PhysicalExpr,
@@ -52,8 +52,22 @@ pub fn create_window_expr(
     partition_by: &[Arc<dyn PhysicalExpr>],
     order_by: &[PhysicalSortExpr],
     window_frame: Option<Arc<WindowFrame>>,
+    ignore_nulls: bool,
     input_schema: &Schema,
 ) -> Result<Arc<dyn WindowExpr>> {
+    if ignore_nulls
+        && !matches!(
+            fun,
+            WindowFunction::BuiltInWindowFunction(
+                BuiltInWindowFunction::Lag | BuiltInWindowFunction::Lead
+            )
+        )
+    {
+        return Err(DataFusionError::NotImplemented(format!(
+            "IGNORE NULLS is not supported for window function {}",
+            fun
+        )));
+    }
     Ok(match fun {
         WindowFunction::AggregateFunction(fun) => Arc::new(AggregateWindowExpr::new(
             aggregates::create_aggregate_expr(fun, false, args, input_schema, name)?,
@@ -62,7 +76,7 @@ pub fn create_window_expr(
             window_frame,
         )),
         WindowFunction::BuiltInWindowFunction(fun) => Arc::new(BuiltInWindowExpr::new(
-            create_built_in_window_expr(fun, args, input_schema, name)?,
+            create_built_in_window_expr(fun, args, ignore_nulls, input_schema, name)?,
             partition_by,
             order_by,
         )),
@@ -88,9 +102,45 @@ fn get_scalar_value_from_args(
     })
 }
 
+/// Create the shift expression of `lag`/`lead`. The default value may be any
+/// expression, which is then evaluated against the current row.
+fn create_window_shift(
+    fun: &BuiltInWindowFunction,
+    args: &[Arc<dyn PhysicalExpr>],
+    ignore_nulls: bool,
+    input_schema: &Schema,
+    name: String,
+) -> Result<WindowShift> {
+    let coerced_args = coerce(args, input_schema, &signature_for_built_in(fun))?;
+    let arg = coerced_args[0].clone();
+    let data_type = args[0].data_type(input_schema)?;
+    let shift_offset = get_scalar_value_from_args(&coerced_args, 1)?
+        .map(|v| v.try_into())
+        .and_then(|v| v.ok());
+    let default_expr = coerced_args.get(2).cloned();
+    let default_value = match &default_expr {
+        Some(expr) => expr
+            .as_any()
+            .downcast_ref::<Literal>()
+            .map(|literal| literal.value().clone()),
+        None => None,
+    };
+    let shift = if *fun == BuiltInWindowFunction::Lag {
+        lag(name, data_type, arg, shift_offset, default_value.clone())
+    } else {
+        lead(name, data_type, arg, shift_offset, default_value.clone())
+    };
+    let shift = match default_expr {
+        Some(expr) if default_value.is_none() => shift.with_default_expr(expr),
+        _ => shift,
+    };
+    Ok(shift.with_ignore_nulls(ignore_nulls))
+}
+
 fn create_built_in_window_expr(
     fun: &BuiltInWindowFunction,
     args: &[Arc<dyn PhysicalExpr>],
+    ignore_nulls: bool,
     input_schema: &Schema,
     name: String,
 ) -> Result<Arc<dyn BuiltInWindowFunctionExpr>> {
@@ -100,26 +150,9 @@ fn create_built_in_window_expr(
         BuiltInWindowFunction::DenseRank => Arc::new(dense_rank(name)),
         BuiltInWindowFunction::PercentRank => Arc::new(percent_rank(name)),
         BuiltInWindowFunction::CumeDist => Arc::new(cume_dist(name)),
-        BuiltInWindowFunction::Lag => {
-            let coerced_args = coerce(args, input_schema, &signature_for_built_in(fun))?;
-            let arg = coerced_args[0].clone();
-            let data_type = args[0].data_type(input_schema)?;
-            let shift_offset = get_scalar_value_from_args(&coerced_args, 1)?
-                .map(|v| v.try_into())
-                .and_then(|v| v.ok());
-            let default_value = get_scalar_value_from_args(&coerced_args, 2)?;
-            Arc::new(lag(name, data_type, arg, shift_offset, default_value))
-        }
-        BuiltInWindowFunction::Lead => {
-            let coerced_args = coerce(args, input_schema, &signature_for_built_in(fun))?;
-            let arg = coerced_args[0].clone();
-            let data_type = args[0].data_type(input_schema)?;
-            let shift_offset = get_scalar_value_from_args(&coerced_args, 1)?
-                .map(|v| v.try_into())
-                .and_then(|v| v.ok());
-            let default_value = get_scalar_value_from_args(&coerced_args, 2)?;
-            Arc::new(lead(name, data_type, arg, shift_offset, default_value))
-        }
+        BuiltInWindowFunction::Lag | BuiltInWindowFunction::Lead => Arc::new(
+            create_window_shift(fun, args, ignore_nulls, input_schema, name)?,
+        ),
         BuiltInWindowFunction::NthValue => {
             let coerced_args = coerce(args, input_schema, &signature_for_built_in(fun))?;
             let arg = coerced_args[0].clone();
@@ -160,14 +193,18 @@ fn create_built_in_window_expr(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::from_slice::FromSlice;
     use crate::physical_plan::aggregates::AggregateFunction;
     use crate::physical_plan::expressions::col;
     use crate::physical_plan::file_format::CsvExec;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeExec;
     use crate::physical_plan::{collect, ExecutionPlan};
-    use crate::prelude::SessionContext;
+    use crate::prelude::{SessionConfig, SessionContext};
     use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
     use crate::test::{self, assert_is_pending};
     use arrow::array::*;
+    use arrow::compute::SortOptions;
     use arrow::datatypes::{DataType, Field, SchemaRef};
     use arrow::record_batch::RecordBatch;
     use futures::FutureExt;
@@ -193,6 +230,7 @@ mod tests {
                     &[],
                     &[],
                     Some(Arc::new(WindowFrame::default())),
+                    false,
                     schema.as_ref(),
                 )?,
                 create_window_expr(
@@ -202,6 +240,7 @@ mod tests {
                     &[],
                     &[],
                     Some(Arc::new(WindowFrame::default())),
+                    false,
                     schema.as_ref(),
                 )?,
                 create_window_expr(
@@ -211,6 +250,7 @@ mod tests {
                     &[],
                     &[],
                     Some(Arc::new(WindowFrame::default())),
+                    false,
                     schema.as_ref(),
                 )?,
             ],
@@ -240,6 +280,62 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn window_function_partition_streaming() -> Result<()> {
+        let session_ctx =
+            SessionContext::with_config(SessionConfig::new().with_batch_size(2));
+        let task_ctx = session_ctx.task_ctx();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_slice(&[1, 1, 1, 2, 3, 3])),
+                Arc::new(Int32Array::from_slice(&[1, 2, 3, 4, 5, 6])),
+            ],
+        )?;
+        let memory = Arc::new(MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?);
+        let sort_exprs = vec![PhysicalSortExpr {
+            expr: col("a", &schema)?,
+            options: SortOptions::default(),
+        }];
+        // the merge re-emits the sorted input in batches of two rows
+        let input = Arc::new(SortPreservingMergeExec::new(sort_exprs, memory));
+
+        let window_exec = Arc::new(WindowAggExec::try_new(
+            vec![create_window_expr(
+                &WindowFunction::AggregateFunction(AggregateFunction::Sum),
+                "sum".to_owned(),
+                &[col("b", &schema)?],
+                &[col("a", &schema)?],
+                &[],
+                Some(Arc::new(WindowFrame::default())),
+                false,
+                schema.as_ref(),
+            )?],
+            input,
+            schema.clone(),
+        )?);
+        assert!(window_exec.partition_streaming());
+
+        let result: Vec<RecordBatch> = collect(window_exec, task_ctx).await?;
+        // one output batch per completed partition
+        assert_eq!(result.len(), 3);
+
+        let sums = result
+            .iter()
+            .flat_map(|batch| {
+                let sum: &Int64Array = as_primitive_array(batch.column(0));
+                sum.values().to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sums, vec![6, 6, 6, 4, 11, 11]);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_drop_cancel() -> Result<()> {
         let session_ctx = SessionContext::new();
@@ -257,6 +353,7 @@ mod tests {
                 &[],
                 &[],
                 Some(Arc::new(WindowFrame::default())),
+                false,
                 schema.as_ref(),
             )?],
             blocking_exec,
@@ -24,15 +24,20 @@ use crate::physical_plan::metrics::{
     BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet,
 };
 use crate::physical_plan::{
-    common, ColumnStatistics, DisplayFormatType, Distribution, ExecutionPlan,
-    Partitioning, RecordBatchStream, SendableRecordBatchStream, Statistics, WindowExpr,
+    common, ColumnStatistics, DisplayFormatType, Distribution, EquivalenceProperties,
+    ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    Statistics, WindowExpr,
 };
+use arrow::compute::kernels::partition::lexicographical_partition_ranges;
 use arrow::{
     array::ArrayRef,
     datatypes::{Schema, SchemaRef},
     error::{ArrowError, Result as ArrowResult},
     record_batch::RecordBatch,
 };
+use datafusion_physical_expr::utils::{
+    expr_list_eq_any_order, expr_list_eq_strict_order,
+};
 use futures::stream::Stream;
 use futures::{ready, StreamExt};
 use std::any::Any;
@@ -87,6 +92,34 @@ impl WindowAggExec {
     pub fn input_schema(&self) -> SchemaRef {
         self.input_schema.clone()
     }
+
+    /// Returns true if the input is already sorted on the (shared) partition keys
+    /// of all window expressions. In that case each window partition can be
+    /// evaluated and emitted as soon as the first row of the next partition is
+    /// seen, so only a single partition needs to be buffered at a time.
+    pub fn partition_streaming(&self) -> bool {
+        let partition_by = match self.window_expr.first() {
+            Some(expr) if !expr.partition_by().is_empty() => expr.partition_by(),
+            _ => return false,
+        };
+        if !self
+            .window_expr
+            .iter()
+            .all(|expr| expr_list_eq_strict_order(expr.partition_by(), partition_by))
+        {
+            return false;
+        }
+        match self.input.output_ordering() {
+            Some(ordering) if ordering.len() >= partition_by.len() => {
+                let leading = ordering[..partition_by.len()]
+                    .iter()
+                    .map(|sort_expr| sort_expr.expr.clone())
+                    .collect::<Vec<_>>();
+                expr_list_eq_any_order(&leading, partition_by)
+            }
+            _ => false,
+        }
+    }
 }
 
 impl ExecutionPlan for WindowAggExec {
@@ -115,6 +148,13 @@ impl ExecutionPlan for WindowAggExec {
         self.input.output_ordering()
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        // the window columns follow the unchanged input columns
+        let mut new_properties = EquivalenceProperties::new(self.schema());
+        new_properties.extend(self.input.equivalence_properties().classes().to_vec());
+        new_properties
+    }
+
     fn maintains_input_order(&self) -> bool {
         true
     }
@@ -157,6 +197,7 @@ impl ExecutionPlan for WindowAggExec {
             self.window_expr.clone(),
             input,
             BaselineMetrics::new(&self.metrics, partition),
+            self.partition_streaming(),
         ));
         Ok(stream)
     }
@@ -236,6 +277,9 @@ pub struct WindowAggStream {
     finished: bool,
     window_expr: Vec<Arc<dyn WindowExpr>>,
     baseline_metrics: BaselineMetrics,
+    /// If true the input is sorted on the window partition keys and completed
+    /// partitions are emitted incrementally instead of buffering all input
+    partition_streaming: bool,
 }
 
 impl WindowAggStream {
@@ -245,6 +289,7 @@ impl WindowAggStream {
         window_expr: Vec<Arc<dyn WindowExpr>>,
         input: SendableRecordBatchStream,
         baseline_metrics: BaselineMetrics,
+        partition_streaming: bool,
     ) -> Self {
         Self {
             schema,
@@ -253,14 +298,56 @@ impl WindowAggStream {
             finished: false,
             window_expr,
             baseline_metrics,
+            partition_streaming,
         }
     }
 
-    fn compute_aggregates(&self) -> ArrowResult<RecordBatch> {
+    /// Buffer `batch` and, if it starts a new window partition, compute and return
+    /// the window aggregates of all the partitions buffered before it.
+    fn push_sorted_batch(
+        &mut self,
+        batch: RecordBatch,
+    ) -> ArrowResult<Option<RecordBatch>> {
+        // prepend the last buffered row so a boundary exactly at the start of
+        // `batch` is detected too
+        let (probe, offset) = match self.batches.last() {
+            Some(last) => {
+                let tail = last.slice(last.num_rows() - 1, 1);
+                let probe =
+                    common::combine_batches(&[tail, batch.clone()], batch.schema())?
+                        .expect("non empty batches");
+                (probe, 1)
+            }
+            None => (batch.clone(), 0),
+        };
+        let partition_columns = self.window_expr[0]
+            .partition_columns(&probe)
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+        let last_partition_start = lexicographical_partition_ranges(&partition_columns)?
+            .last()
+            .map(|range| range.start)
+            .unwrap_or(0);
+
+        if last_partition_start == 0 {
+            self.batches.push(batch);
+            return Ok(None);
+        }
+
+        let split = last_partition_start - offset;
+        let mut completed = std::mem::take(&mut self.batches);
+        if split > 0 {
+            completed.push(batch.slice(0, split));
+        }
+        self.batches
+            .push(batch.slice(split, batch.num_rows() - split));
+        self.compute_aggregates(&completed).map(Some)
+    }
+
+    fn compute_aggregates(&self, batches: &[RecordBatch]) -> ArrowResult<RecordBatch> {
         // record compute time on drop
         let _timer = self.baseline_metrics.elapsed_compute().timer();
 
-        let batch = common::combine_batches(&self.batches, self.input.schema())?;
+        let batch = common::combine_batches(batches, self.input.schema())?;
         if let Some(batch) = batch {
             // calculate window cols
             let mut columns = compute_window_aggregates(&self.window_expr, &batch)
@@ -284,6 +371,7 @@ impl Stream for WindowAggStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        self.baseline_metrics.record_poll_start();
         let poll = self.poll_next_inner(cx);
         self.baseline_metrics.record_poll(poll)
     }
@@ -300,13 +388,31 @@ impl WindowAggStream {
         }
 
         loop {
-            let result = match ready!(self.input.poll_next_unpin(cx)) {
+            let poll = self.input.poll_next_unpin(cx);
+            let result = match ready!(self.baseline_metrics.record_input_poll(poll)) {
+                Some(Ok(batch)) if self.partition_streaming => {
+                    if batch.num_rows() == 0 {
+                        continue;
+                    }
+                    match self.push_sorted_batch(batch) {
+                        Ok(Some(batch)) => return Poll::Ready(Some(Ok(batch))),
+                        Ok(None) => continue,
+                        Err(e) => Err(e),
+                    }
+                }
                 Some(Ok(batch)) => {
                     self.batches.push(batch);
                     continue;
                 }
                 Some(Err(e)) => Err(e),
-                None => self.compute_aggregates(),
+                None if self.partition_streaming && self.batches.is_empty() => {
+                    self.finished = true;
+                    return Poll::Ready(None);
+                }
+                None => {
+                    let batches = std::mem::take(&mut self.batches);
+                    self.compute_aggregates(&batches)
+                }
             };
 
             self.finished = true;
@@ -0,0 +1,466 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Structural diff of logical and physical plans, see [`plan_diff`]
+//!
+//! ```
+//! use datafusion::plan_diff::plan_diff;
+//! # use datafusion::error::Result;
+//! # use datafusion::prelude::*;
+//! # fn main() -> Result<()> {
+//! let ctx = SessionContext::new();
+//! let a = ctx.create_logical_plan("SELECT 1 + 2")?;
+//! let b = ctx.optimize(&a)?;
+//!
+//! // the optimizer folded the constant expression of the projection
+//! let diff = plan_diff(&a, &b);
+//! assert!(!diff.is_empty());
+//! println!("{}", diff);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::logical_expr::LogicalPlan;
+use crate::physical_plan::{displayable, ExecutionPlan};
+
+/// The tree of the nodes of a plan compared by [`plan_diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanTree {
+    /// The single line description of the node, without its inputs
+    pub description: String,
+    /// The inputs of the node
+    pub children: Vec<PlanTree>,
+}
+
+impl PlanTree {
+    /// The kind of the node, such as `Filter` or `FilterExec`: nodes of the
+    /// same kind are changed rather than replaced by another node
+    fn kind(&self) -> &str {
+        match self.description.split_once(':') {
+            Some((kind, _)) => kind,
+            None => &self.description,
+        }
+    }
+}
+
+impl From<&LogicalPlan> for PlanTree {
+    fn from(plan: &LogicalPlan) -> Self {
+        Self {
+            description: plan.display().to_string(),
+            children: plan.inputs().into_iter().map(PlanTree::from).collect(),
+        }
+    }
+}
+
+impl From<&dyn ExecutionPlan> for PlanTree {
+    fn from(plan: &dyn ExecutionPlan) -> Self {
+        Self {
+            description: displayable(plan)
+                .one_line()
+                .to_string()
+                .trim_end()
+                .to_string(),
+            children: plan
+                .children()
+                .iter()
+                .map(|child| PlanTree::from(child.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+impl From<&Arc<dyn ExecutionPlan>> for PlanTree {
+    fn from(plan: &Arc<dyn ExecutionPlan>) -> Self {
+        PlanTree::from(plan.as_ref())
+    }
+}
+
+/// A difference between two plans
+///
+/// The path of a node is the index of each of the inputs leading to it from
+/// the root of its plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanChange {
+    /// A node of the second plan missing from the first one
+    Added {
+        /// The path of the node in the second plan
+        path: Vec<usize>,
+        /// The description of the node
+        node: String,
+    },
+    /// A node of the first plan missing from the second one
+    Removed {
+        /// The path of the node in the first plan
+        path: Vec<usize>,
+        /// The description of the node
+        node: String,
+    },
+    /// A node whose description is different in the two plans
+    Changed {
+        /// The path of the node in the second plan
+        path: Vec<usize>,
+        /// The description of the node in the first plan
+        before: String,
+        /// The description of the node in the second plan
+        after: String,
+    },
+}
+
+/// The structural diff of two plans, see [`plan_diff`]
+///
+/// It is displayed as the merged tree of the nodes of the two plans, where
+/// the removed nodes are marked with `-`, the added ones with `+`, and the
+/// changed ones appear twice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanDiff {
+    /// The differences, in the order of the nodes in the plans
+    pub changes: Vec<PlanChange>,
+    /// The lines of the merged tree
+    lines: Vec<(char, usize, String)>,
+}
+
+impl PlanDiff {
+    /// Returns true if the plans are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for PlanDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (marker, indent, description) in &self.lines {
+            writeln!(
+                f,
+                "{} {:indent$}{}",
+                marker,
+                "",
+                description,
+                indent = indent * 2
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the node-level differences between the plans `a` and `b`, which
+/// may be [`LogicalPlan`]s or [`ExecutionPlan`]s.
+///
+/// Nodes are matched by their position and kind. A node found between a
+/// node and its input in only one of the plans, such as a filter moved by
+/// the optimizer, is reported as added or removed, and the nodes around
+/// it are still matched.
+pub fn plan_diff(a: impl Into<PlanTree>, b: impl Into<PlanTree>) -> PlanDiff {
+    let mut differ = Differ {
+        changes: vec![],
+        lines: vec![],
+    };
+    differ.diff(&a.into(), &b.into(), &mut vec![], &mut vec![], 0);
+    PlanDiff {
+        changes: differ.changes,
+        lines: differ.lines,
+    }
+}
+
+struct Differ {
+    changes: Vec<PlanChange>,
+    lines: Vec<(char, usize, String)>,
+}
+
+impl Differ {
+    fn diff(
+        &mut self,
+        a: &PlanTree,
+        b: &PlanTree,
+        a_path: &mut Vec<usize>,
+        b_path: &mut Vec<usize>,
+        depth: usize,
+    ) {
+        if a.kind() == b.kind() {
+            if a.description == b.description {
+                self.lines.push((' ', depth, b.description.clone()));
+            } else {
+                self.lines.push(('-', depth, a.description.clone()));
+                self.lines.push(('+', depth, b.description.clone()));
+                self.changes.push(PlanChange::Changed {
+                    path: b_path.clone(),
+                    before: a.description.clone(),
+                    after: b.description.clone(),
+                });
+            }
+            self.diff_children(&a.children, &b.children, a_path, b_path, depth + 1);
+        } else if let Some(k) = b.children.iter().position(|c| c.kind() == a.kind()) {
+            // b is inserted above a
+            self.added(b, b_path, depth);
+            for (i, child) in b.children.iter().enumerate() {
+                b_path.push(i);
+                if i == k {
+                    self.diff(a, child, a_path, b_path, depth + 1);
+                } else {
+                    self.added_tree(child, b_path, depth + 1);
+                }
+                b_path.pop();
+            }
+        } else if let Some(k) = a.children.iter().position(|c| c.kind() == b.kind()) {
+            // a is removed from above b
+            self.removed(a, a_path, depth);
+            for (i, child) in a.children.iter().enumerate() {
+                a_path.push(i);
+                if i == k {
+                    self.diff(child, b, a_path, b_path, depth + 1);
+                } else {
+                    self.removed_tree(child, a_path, depth + 1);
+                }
+                a_path.pop();
+            }
+        } else {
+            // a is replaced by b
+            self.removed(a, a_path, depth);
+            self.added(b, b_path, depth);
+            self.diff_children(&a.children, &b.children, a_path, b_path, depth + 1);
+        }
+    }
+
+    /// Diffs the inputs `a` and `b` of the nodes at `a_path` and `b_path`
+    fn diff_children(
+        &mut self,
+        a: &[PlanTree],
+        b: &[PlanTree],
+        a_path: &mut Vec<usize>,
+        b_path: &mut Vec<usize>,
+        depth: usize,
+    ) {
+        let pairs = align(a, b);
+        let mut a_next = 0;
+        let mut b_next = 0;
+        for (i, j) in pairs.into_iter().chain(Some((a.len(), b.len()))) {
+            // the unmatched children before the pair
+            for (k, child) in a.iter().enumerate().take(i).skip(a_next) {
+                a_path.push(k);
+                self.removed_tree(child, a_path, depth);
+                a_path.pop();
+            }
+            for (k, child) in b.iter().enumerate().take(j).skip(b_next) {
+                b_path.push(k);
+                self.added_tree(child, b_path, depth);
+                b_path.pop();
+            }
+            if i < a.len() && j < b.len() {
+                a_path.push(i);
+                b_path.push(j);
+                self.diff(&a[i], &b[j], a_path, b_path, depth);
+                a_path.pop();
+                b_path.pop();
+            }
+            a_next = i + 1;
+            b_next = j + 1;
+        }
+    }
+
+    fn added(&mut self, node: &PlanTree, path: &[usize], depth: usize) {
+        self.lines.push(('+', depth, node.description.clone()));
+        self.changes.push(PlanChange::Added {
+            path: path.to_vec(),
+            node: node.description.clone(),
+        });
+    }
+
+    fn removed(&mut self, node: &PlanTree, path: &[usize], depth: usize) {
+        self.lines.push(('-', depth, node.description.clone()));
+        self.changes.push(PlanChange::Removed {
+            path: path.to_vec(),
+            node: node.description.clone(),
+        });
+    }
+
+    fn added_tree(&mut self, node: &PlanTree, path: &mut Vec<usize>, depth: usize) {
+        self.added(node, path, depth);
+        for (i, child) in node.children.iter().enumerate() {
+            path.push(i);
+            self.added_tree(child, path, depth + 1);
+            path.pop();
+        }
+    }
+
+    fn removed_tree(&mut self, node: &PlanTree, path: &mut Vec<usize>, depth: usize) {
+        self.removed(node, path, depth);
+        for (i, child) in node.children.iter().enumerate() {
+            path.push(i);
+            self.removed_tree(child, path, depth + 1);
+            path.pop();
+        }
+    }
+}
+
+/// Pairs the inputs of two nodes: by position when there are as many of
+/// them, and otherwise by the longest common subsequence of their kinds
+fn align(a: &[PlanTree], b: &[PlanTree]) -> Vec<(usize, usize)> {
+    if a.len() == b.len() {
+        return (0..a.len()).map(|i| (i, i)).collect();
+    }
+    // lengths[i][j] is the length of the common subsequence of a[i..] and b[j..]
+    let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i].kind() == b[j].kind() {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].kind() == b[j].kind() {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::prelude::{SessionConfig, SessionContext};
+    use crate::test_util::aggr_test_schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn context() -> Result<SessionContext> {
+        let ctx =
+            SessionContext::with_config(SessionConfig::new().with_target_partitions(1));
+        ctx.register_batch("t", RecordBatch::new_empty(aggr_test_schema()))?;
+        ctx.register_batch("u", RecordBatch::new_empty(aggr_test_schema()))?;
+        Ok(ctx)
+    }
+
+    #[test]
+    fn same_plans() -> Result<()> {
+        let ctx = context()?;
+        let plan = ctx.create_logical_plan("SELECT c1 FROM t WHERE c2 > 1")?;
+        let diff = plan_diff(&plan, &plan);
+        assert!(diff.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "  Projection: t.c1\
+           \n    Filter: t.c2 > Int64(1)\
+           \n      TableScan: t\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn changed_node() -> Result<()> {
+        let ctx = context()?;
+        let a = ctx.create_logical_plan("SELECT c1 FROM t WHERE c2 > 1")?;
+        let b = ctx.create_logical_plan("SELECT c1 FROM t WHERE c2 > 2")?;
+        let diff = plan_diff(&a, &b);
+        assert_eq!(
+            diff.changes,
+            vec![PlanChange::Changed {
+                path: vec![0],
+                before: "Filter: t.c2 > Int64(1)".to_string(),
+                after: "Filter: t.c2 > Int64(2)".to_string(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn removed_and_added_nodes() -> Result<()> {
+        let ctx = context()?;
+        let a = ctx.create_logical_plan("SELECT c1 FROM t WHERE c2 > 1")?;
+        let b = ctx.create_logical_plan("SELECT c1 FROM t LIMIT 10")?;
+        let diff = plan_diff(&a, &b);
+        assert_eq!(
+            diff.to_string(),
+            "+ Limit: skip=0, fetch=10\
+           \n    Projection: t.c1\
+           \n-     Filter: t.c2 > Int64(1)\
+           \n        TableScan: t\n"
+        );
+        assert_eq!(
+            diff.changes,
+            vec![
+                PlanChange::Added {
+                    path: vec![],
+                    node: "Limit: skip=0, fetch=10".to_string(),
+                },
+                PlanChange::Removed {
+                    path: vec![0],
+                    node: "Filter: t.c2 > Int64(1)".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn union_inputs() -> Result<()> {
+        let ctx = context()?;
+        let a = ctx.create_logical_plan("SELECT c1 FROM t UNION ALL SELECT c1 FROM u")?;
+        let b = ctx.create_logical_plan(
+            "SELECT c1 FROM t UNION ALL SELECT c1 FROM u UNION ALL SELECT c1 FROM t",
+        )?;
+        let diff = plan_diff(&a, &b);
+        assert_eq!(
+            diff.changes,
+            vec![
+                PlanChange::Added {
+                    path: vec![2],
+                    node: "Projection: t.c1".to_string(),
+                },
+                PlanChange::Added {
+                    path: vec![2, 0],
+                    node: "TableScan: t".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn physical_plans() -> Result<()> {
+        let ctx = context()?;
+        let a = ctx
+            .create_physical_plan(&ctx.create_logical_plan("SELECT c1 FROM t")?)
+            .await?;
+        let b = ctx
+            .create_physical_plan(&ctx.create_logical_plan("SELECT c2 FROM t")?)
+            .await?;
+        assert!(plan_diff(&a, &a).is_empty());
+        let diff = plan_diff(&a, &b);
+        assert_eq!(
+            diff.changes,
+            vec![PlanChange::Changed {
+                path: vec![],
+                before: "ProjectionExec: expr=[c1@0 as c1]".to_string(),
+                after: "ProjectionExec: expr=[c2@0 as c2]".to_string(),
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Formatting of record batches as text tables, like
+//! [`arrow::util::pretty`], with options for the display of query results in
+//! terminals, notebooks and generated documentation.
+//!
+//! ```
+//! use datafusion::pretty::{pretty_format_batches_with_options, FormatOptions, TableFormat};
+//! # use datafusion::arrow::array::Int32Array;
+//! # use datafusion::arrow::datatypes::{DataType, Field, Schema};
+//! # use datafusion::arrow::record_batch::RecordBatch;
+//! # use std::sync::Arc;
+//! # let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+//! # let batch = RecordBatch::try_new(
+//! #     schema,
+//! #     vec![Arc::new(Int32Array::from(vec![Some(1), None]))],
+//! # ).unwrap();
+//!
+//! let options = FormatOptions::new()
+//!     .with_format(TableFormat::Markdown)
+//!     .with_null("NULL")
+//!     .with_row_count(true);
+//! let table = pretty_format_batches_with_options(&[batch], &options).unwrap();
+//! assert_eq!(table, "| a    |\n|------|\n| 1    |\n| NULL |\n\n2 rows");
+//! ```
+
+use std::fmt::Write;
+
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+
+use crate::error::Result;
+
+/// The marker ending the values truncated to the maximum column width
+const TRUNCATION_MARKER: char = '…';
+
+/// The format of a text table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// A table bordered with ASCII characters, as printed by
+    /// [`arrow::util::pretty`]
+    Ascii,
+    /// A GitHub flavored markdown table
+    Markdown,
+    /// An HTML `<table>` element
+    Html,
+}
+
+/// The options of [`pretty_format_batches_with_options`]
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    format: TableFormat,
+    max_column_width: Option<usize>,
+    null: String,
+    row_count: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            format: TableFormat::Ascii,
+            max_column_width: None,
+            null: String::new(),
+            row_count: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Options formatting ASCII tables like [`arrow::util::pretty`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Formats the tables in `format`
+    pub fn with_format(mut self, format: TableFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Truncates the values, and the names of the columns, longer than
+    /// `max_column_width` characters, ending them with `…`
+    pub fn with_max_column_width(mut self, max_column_width: usize) -> Self {
+        self.max_column_width = Some(max_column_width.max(1));
+        self
+    }
+
+    /// Renders the null values as `null`, instead of an empty cell
+    pub fn with_null(mut self, null: impl Into<String>) -> Self {
+        self.null = null.into();
+        self
+    }
+
+    /// Whether to follow the table with the number of its rows
+    pub fn with_row_count(mut self, row_count: bool) -> Self {
+        self.row_count = row_count;
+        self
+    }
+}
+
+/// Formats `batches` as a text table, as configured by `options`
+pub fn pretty_format_batches_with_options(
+    batches: &[RecordBatch],
+    options: &FormatOptions,
+) -> Result<String> {
+    let header = match batches.first() {
+        Some(batch) => batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| truncate(field.name(), options.max_column_width))
+            .collect(),
+        None => vec![],
+    };
+
+    let mut rows = vec![];
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            let cells = batch
+                .columns()
+                .iter()
+                .map(|column| {
+                    let value = if column.is_null(row) {
+                        options.null.clone()
+                    } else {
+                        array_value_to_string(column, row)?
+                    };
+                    Ok(truncate(&value, options.max_column_width))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(cells);
+        }
+    }
+
+    let mut table = match options.format {
+        TableFormat::Ascii => ascii_table(&header, &rows),
+        TableFormat::Markdown => markdown_table(&header, &rows),
+        TableFormat::Html => html_table(&header, &rows),
+    };
+    if options.row_count {
+        let separator = match options.format {
+            TableFormat::Html => "\n<p>",
+            _ => "\n\n",
+        };
+        table.push_str(separator);
+        match rows.len() {
+            1 => table.push_str("1 row"),
+            n => write!(table, "{} rows", n).unwrap(),
+        }
+        if options.format == TableFormat::Html {
+            table.push_str("</p>");
+        }
+    }
+    Ok(table)
+}
+
+/// Truncates `value` to `max_width` characters, if any
+fn truncate(value: &str, max_width: Option<usize>) -> String {
+    match max_width {
+        Some(max_width) if value.chars().count() > max_width => value
+            .chars()
+            .take(max_width - 1)
+            .chain(std::iter::once(TRUNCATION_MARKER))
+            .collect(),
+        _ => value.to_owned(),
+    }
+}
+
+/// The widths of the columns of a table, in characters
+fn column_widths(header: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(name.chars().count()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Appends the line of the cells of a row of the table, padded to `widths`
+fn write_row(table: &mut String, cells: &[String], widths: &[usize]) {
+    table.push('|');
+    for (cell, width) in cells.iter().zip(widths) {
+        let padding = width - cell.chars().count();
+        write!(table, " {}{} |", cell, " ".repeat(padding)).unwrap();
+    }
+    table.push('\n');
+}
+
+fn ascii_table(header: &[String], rows: &[Vec<String>]) -> String {
+    if header.is_empty() {
+        return "++\n++".to_owned();
+    }
+    let widths = column_widths(header, rows);
+    let mut border = String::from("+");
+    for width in &widths {
+        border.push_str(&"-".repeat(width + 2));
+        border.push('+');
+    }
+    border.push('\n');
+
+    let mut table = border.clone();
+    write_row(&mut table, header, &widths);
+    table.push_str(&border);
+    for row in rows {
+        write_row(&mut table, row, &widths);
+    }
+    table.push_str(&border);
+    table.truncate(table.len() - 1);
+    table
+}
+
+fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let escape = |cell: &String| cell.replace('|', "\\|");
+    let header = header.iter().map(escape).collect::<Vec<_>>();
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(escape).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let widths = column_widths(&header, &rows);
+
+    let mut table = String::new();
+    if header.is_empty() {
+        return table;
+    }
+    write_row(&mut table, &header, &widths);
+    table.push('|');
+    for width in &widths {
+        table.push_str(&"-".repeat(width + 2));
+        table.push('|');
+    }
+    table.push('\n');
+    for row in &rows {
+        write_row(&mut table, row, &widths);
+    }
+    table.truncate(table.len() - 1);
+    table
+}
+
+fn html_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut table = String::from("<table>\n<thead>\n<tr>");
+    for name in header {
+        write!(table, "<th>{}</th>", escape_html(name)).unwrap();
+    }
+    table.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in rows {
+        table.push_str("<tr>");
+        for cell in row {
+            write!(table, "<td>{}</td>", escape_html(cell)).unwrap();
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</tbody>\n</table>");
+    table
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::util::pretty::pretty_format_batches;
+    use std::sync::Arc;
+
+    fn batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("description", DataType::Utf8, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+                Arc::new(StringArray::from(vec![
+                    Some("short"),
+                    Some("a <long> | description"),
+                    None,
+                ])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ascii_like_arrow() -> Result<()> {
+        let batches = [batch(), batch()];
+        let table = pretty_format_batches_with_options(&batches, &FormatOptions::new())?;
+        assert_eq!(table, pretty_format_batches(&batches)?.to_string());
+
+        let table = pretty_format_batches_with_options(&[], &FormatOptions::new())?;
+        assert_eq!(table, pretty_format_batches(&[])?.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn ascii_options() -> Result<()> {
+        let options = FormatOptions::new()
+            .with_max_column_width(8)
+            .with_null("NULL")
+            .with_row_count(true);
+        let table = pretty_format_batches_with_options(&[batch()], &options)?;
+        let expected = vec![
+            "+------+----------+",
+            "| id   | descrip… |",
+            "+------+----------+",
+            "| 1    | short    |",
+            "| NULL | a <long… |",
+            "| 3    | NULL     |",
+            "+------+----------+",
+            "",
+            "3 rows",
+        ];
+        assert_eq!(table, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn markdown() -> Result<()> {
+        let options = FormatOptions::new().with_format(TableFormat::Markdown);
+        let table = pretty_format_batches_with_options(&[batch()], &options)?;
+        let expected = vec![
+            "| id | description             |",
+            "|----|-------------------------|",
+            "| 1  | short                   |",
+            "|    | a <long> \\| description |",
+            "| 3  |                         |",
+        ];
+        assert_eq!(table, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn html() -> Result<()> {
+        let options = FormatOptions::new()
+            .with_format(TableFormat::Html)
+            .with_null("NULL")
+            .with_row_count(true);
+        let table = pretty_format_batches_with_options(&[batch()], &options)?;
+        let expected = vec![
+            "<table>",
+            "<thead>",
+            "<tr><th>id</th><th>description</th></tr>",
+            "</thead>",
+            "<tbody>",
+            "<tr><td>1</td><td>short</td></tr>",
+            "<tr><td>NULL</td><td>a &lt;long&gt; | description</td></tr>",
+            "<tr><td>3</td><td>NULL</td></tr>",
+            "</tbody>",
+            "</table>",
+            "<p>3 rows</p>",
+        ];
+        assert_eq!(table, expected.join("\n"));
+        Ok(())
+    }
+}
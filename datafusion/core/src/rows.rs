@@ -0,0 +1,486 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Row oriented access to the record batches of query results, for
+//! application code reading the results one row at a time.
+//!
+//! The values of a [`Row`] are read as Rust values of the types implementing
+//! [`Accessor`], which downcast the column of the value to the array of its
+//! Arrow type. Null values can only be read as an `Option`.
+//!
+//! ```
+//! # use datafusion::prelude::*;
+//! # use datafusion::error::Result;
+//! # #[tokio::main]
+//! # async fn main() -> Result<()> {
+//! let ctx = SessionContext::new();
+//! let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+//! let rows = df.rows().await?;
+//! for row in &rows {
+//!     let a: i64 = row.get(0)?;
+//!     let b: Option<i64> = row.get_by_name("b")?;
+//!     println!("{} {:?}", a, b);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The batches of a stream, such as the one of
+//! [`DataFrame::execute_stream`](crate::dataframe::DataFrame::execute_stream),
+//! are read with a [`RowIterator`] over every batch.
+
+use arrow::array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, Date32Array, Date64Array, Float32Array,
+    Float64Array, Int16Array, Int32Array, Int64Array, Int8Array, LargeBinaryArray,
+    LargeStringArray, StringArray, TimestampMicrosecondArray, TimestampMillisecondArray,
+    TimestampNanosecondArray, TimestampSecondArray, UInt16Array, UInt32Array,
+    UInt64Array, UInt8Array,
+};
+use arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::error::{DataFusionError, Result};
+use datafusion_common::ScalarValue;
+
+/// Reads the values of the columns of some Arrow types as Rust values of
+/// type `Self`
+pub trait Accessor<'a>: Sized {
+    /// Returns the value of `array` at `index`, or an error if its Arrow type
+    /// cannot be read as `Self`
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self>;
+}
+
+/// Null values are read as `None`
+impl<'a, T: Accessor<'a>> Accessor<'a> for Option<T> {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        if array.is_null(index) {
+            Ok(None)
+        } else {
+            T::get(array, index).map(Some)
+        }
+    }
+}
+
+/// Any value is read as a [`ScalarValue`], including nulls
+impl<'a> Accessor<'a> for ScalarValue {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        ScalarValue::try_from_array(array, index)
+    }
+}
+
+/// The error of reading a value of `array` as the Rust type `type_name`
+fn type_mismatch(array: &ArrayRef, type_name: &str) -> DataFusionError {
+    DataFusionError::Execution(format!(
+        "Value of type {:?} cannot be read as {}",
+        array.data_type(),
+        type_name
+    ))
+}
+
+/// Downcasts `array` to `A`, checking that its value at `index` is not null
+fn downcast<'a, A: 'static>(
+    array: &'a ArrayRef,
+    index: usize,
+    type_name: &str,
+) -> Result<&'a A> {
+    if array.is_null(index) {
+        return Err(DataFusionError::Execution(format!(
+            "Null value cannot be read as {}, read it as an Option",
+            type_name
+        )));
+    }
+    array
+        .as_any()
+        .downcast_ref::<A>()
+        .ok_or_else(|| type_mismatch(array, type_name))
+}
+
+macro_rules! primitive_accessor {
+    ($NATIVE:ty, $ARRAY:ty) => {
+        impl<'a> Accessor<'a> for $NATIVE {
+            fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+                Ok(downcast::<$ARRAY>(array, index, stringify!($NATIVE))?.value(index))
+            }
+        }
+    };
+}
+
+primitive_accessor!(bool, BooleanArray);
+primitive_accessor!(i8, Int8Array);
+primitive_accessor!(i16, Int16Array);
+primitive_accessor!(i32, Int32Array);
+primitive_accessor!(i64, Int64Array);
+primitive_accessor!(u8, UInt8Array);
+primitive_accessor!(u16, UInt16Array);
+primitive_accessor!(u32, UInt32Array);
+primitive_accessor!(u64, UInt64Array);
+primitive_accessor!(f32, Float32Array);
+primitive_accessor!(f64, Float64Array);
+
+/// Returns the value of the string `array` at `index`
+fn str_value<'a>(array: &'a ArrayRef, index: usize, type_name: &str) -> Result<&'a str> {
+    match array.data_type() {
+        DataType::LargeUtf8 => {
+            Ok(downcast::<LargeStringArray>(array, index, type_name)?.value(index))
+        }
+        _ => Ok(downcast::<StringArray>(array, index, type_name)?.value(index)),
+    }
+}
+
+/// Returns the value of the binary `array` at `index`
+fn bytes_value<'a>(
+    array: &'a ArrayRef,
+    index: usize,
+    type_name: &str,
+) -> Result<&'a [u8]> {
+    match array.data_type() {
+        DataType::LargeBinary => {
+            Ok(downcast::<LargeBinaryArray>(array, index, type_name)?.value(index))
+        }
+        _ => Ok(downcast::<BinaryArray>(array, index, type_name)?.value(index)),
+    }
+}
+
+impl<'a> Accessor<'a> for &'a str {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        str_value(array, index, "&str")
+    }
+}
+
+impl<'a> Accessor<'a> for String {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        str_value(array, index, "String").map(str::to_owned)
+    }
+}
+
+impl<'a> Accessor<'a> for &'a [u8] {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        bytes_value(array, index, "&[u8]")
+    }
+}
+
+impl<'a> Accessor<'a> for Vec<u8> {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        bytes_value(array, index, "Vec<u8>").map(<[u8]>::to_vec)
+    }
+}
+
+impl<'a> Accessor<'a> for NaiveDate {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        let date =
+            match array.data_type() {
+                DataType::Date32 => downcast::<Date32Array>(array, index, "NaiveDate")?
+                    .value_as_date(index),
+                DataType::Date64 => downcast::<Date64Array>(array, index, "NaiveDate")?
+                    .value_as_date(index),
+                _ => None,
+            };
+        date.ok_or_else(|| type_mismatch(array, "NaiveDate"))
+    }
+}
+
+impl<'a> Accessor<'a> for NaiveDateTime {
+    fn get(array: &'a ArrayRef, index: usize) -> Result<Self> {
+        let type_name = "NaiveDateTime";
+        let datetime = match array.data_type() {
+            DataType::Timestamp(TimeUnit::Second, _) => {
+                downcast::<TimestampSecondArray>(array, index, type_name)?
+                    .value_as_datetime(index)
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                downcast::<TimestampMillisecondArray>(array, index, type_name)?
+                    .value_as_datetime(index)
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                downcast::<TimestampMicrosecondArray>(array, index, type_name)?
+                    .value_as_datetime(index)
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                downcast::<TimestampNanosecondArray>(array, index, type_name)?
+                    .value_as_datetime(index)
+            }
+            DataType::Date64 => {
+                downcast::<Date64Array>(array, index, type_name)?.value_as_datetime(index)
+            }
+            _ => None,
+        };
+        datetime.ok_or_else(|| type_mismatch(array, type_name))
+    }
+}
+
+/// A view of the row at some index of a record batch
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    batch: &'a RecordBatch,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    /// Creates a view of the row `index` of `batch`
+    pub fn new(batch: &'a RecordBatch, index: usize) -> Self {
+        assert!(
+            index < batch.num_rows(),
+            "Row {} out of bounds of batch of {} rows",
+            index,
+            batch.num_rows()
+        );
+        Self { batch, index }
+    }
+
+    /// The schema of the row
+    pub fn schema(&self) -> SchemaRef {
+        self.batch.schema()
+    }
+
+    /// The number of values of the row
+    pub fn len(&self) -> usize {
+        self.batch.num_columns()
+    }
+
+    /// Whether the row has no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of the column `column` as a `T`
+    pub fn get<T: Accessor<'a>>(&self, column: usize) -> Result<T> {
+        if column >= self.len() {
+            return Err(DataFusionError::Execution(format!(
+                "Column {} out of bounds of row of {} columns",
+                column,
+                self.len()
+            )));
+        }
+        T::get(self.batch.column(column), self.index).map_err(|e| {
+            let name = self.batch.schema().field(column).name().clone();
+            DataFusionError::Execution(format!("Column '{}': {}", name, strip(e)))
+        })
+    }
+
+    /// Returns the value of the column named `name` as a `T`
+    pub fn get_by_name<T: Accessor<'a>>(&self, name: &str) -> Result<T> {
+        let column = self.batch.schema().index_of(name)?;
+        self.get(column)
+    }
+}
+
+/// The message of the execution error `e`, without its prefix
+fn strip(e: DataFusionError) -> String {
+    match e {
+        DataFusionError::Execution(message) => message,
+        e => e.to_string(),
+    }
+}
+
+/// An iterator over the rows of a sequence of record batches
+#[derive(Debug, Clone)]
+pub struct RowIterator<'a> {
+    batches: &'a [RecordBatch],
+    /// The index of the current batch in `batches`
+    batch: usize,
+    /// The index of the next row in the current batch
+    index: usize,
+}
+
+impl<'a> RowIterator<'a> {
+    /// Creates an iterator over the rows of `batches`, in order
+    pub fn new(batches: &'a [RecordBatch]) -> Self {
+        Self {
+            batches,
+            batch: 0,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for RowIterator<'a> {
+    type Item = Row<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(batch) = self.batches.get(self.batch) {
+            if self.index < batch.num_rows() {
+                self.index += 1;
+                return Some(Row::new(batch, self.index - 1));
+            }
+            self.batch += 1;
+            self.index = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .batches
+            .iter()
+            .skip(self.batch)
+            .map(|batch| batch.num_rows())
+            .sum::<usize>()
+            - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for RowIterator<'a> {}
+
+/// The collected record batches of a query, read one row at a time
+#[derive(Debug, Clone)]
+pub struct Rows {
+    batches: Vec<RecordBatch>,
+}
+
+impl Rows {
+    /// Creates the rows of `batches`
+    pub fn new(batches: Vec<RecordBatch>) -> Self {
+        Self { batches }
+    }
+
+    /// An iterator over the rows
+    pub fn iter(&self) -> RowIterator<'_> {
+        RowIterator::new(&self.batches)
+    }
+
+    /// The number of rows
+    pub fn len(&self) -> usize {
+        self.batches.iter().map(|batch| batch.num_rows()).sum()
+    }
+
+    /// Whether there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The record batches of the rows
+    pub fn batches(&self) -> &[RecordBatch] {
+        &self.batches
+    }
+
+    /// Returns the record batches of the rows
+    pub fn into_batches(self) -> Vec<RecordBatch> {
+        self.batches
+    }
+}
+
+impl<'a> IntoIterator for &'a Rows {
+    type Item = Row<'a>;
+    type IntoIter = RowIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(ids: Vec<i64>, names: Vec<Option<&str>>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn iterate_rows() -> Result<()> {
+        let rows = Rows::new(vec![
+            batch(vec![1, 2], vec![Some("a"), None]),
+            batch(vec![], vec![]),
+            batch(vec![3], vec![Some("c")]),
+        ]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows.iter().len(), 3);
+
+        let mut values = vec![];
+        for row in &rows {
+            let id: i64 = row.get(0)?;
+            let name: Option<&str> = row.get_by_name("name")?;
+            values.push((id, name.map(str::to_owned)));
+        }
+        assert_eq!(
+            values,
+            vec![
+                (1, Some("a".to_owned())),
+                (2, None),
+                (3, Some("c".to_owned()))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn access_errors() {
+        let batch = batch(vec![1], vec![None]);
+        let row = Row::new(&batch, 0);
+
+        let err = row.get::<i32>(0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: Column 'id': Value of type Int64 cannot be read as i32"
+        );
+        let err = row.get::<String>(1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: Column 'name': Null value cannot be read as String, \
+             read it as an Option"
+        );
+        assert!(row.get::<i64>(2).is_err());
+        assert!(row.get_by_name::<i64>("missing").is_err());
+
+        assert_eq!(
+            row.get::<ScalarValue>(0).unwrap(),
+            ScalarValue::Int64(Some(1))
+        );
+        assert_eq!(row.get::<ScalarValue>(1).unwrap(), ScalarValue::Utf8(None));
+    }
+
+    #[test]
+    fn access_temporal_values() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("d", DataType::Date32, false),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Date32Array::from(vec![19000])),
+                Arc::new(TimestampMillisecondArray::from(vec![1_000])),
+            ],
+        )?;
+        let row = Row::new(&batch, 0);
+        assert_eq!(row.get::<NaiveDate>(0)?, NaiveDate::from_ymd(2022, 1, 8));
+        assert_eq!(
+            row.get::<NaiveDateTime>(1)?,
+            NaiveDateTime::from_timestamp(1, 0)
+        );
+        Ok(())
+    }
+}
@@ -32,8 +32,8 @@ use crate::execution::context::TaskContext;
 use crate::physical_plan::expressions::PhysicalSortExpr;
 use crate::physical_plan::metrics::MetricsSet;
 use crate::physical_plan::{
-    displayable, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
-    RecordBatchStream, SendableRecordBatchStream, Statistics,
+    displayable, DisplayFormatType, Distribution, EquivalenceProperties, ExecutionPlan,
+    Partitioning, RecordBatchStream, SendableRecordBatchStream, Statistics,
 };
 
 use crate::scheduler::pipeline::Pipeline;
@@ -235,6 +235,10 @@ impl ExecutionPlan for ProxyExecutionPlan {
         self.inner.output_ordering()
     }
 
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.inner.equivalence_properties()
+    }
+
     fn required_child_distribution(&self) -> Distribution {
         self.inner.required_child_distribution()
     }
@@ -329,6 +329,18 @@ impl TableProvider for TestTableProvider {
     }
 }
 
+/// The binary of the WebAssembly module
+/// `(func (export "add") (param i64 i64) (result i64) local.get 0 local.get 1 i64.add)`,
+/// for the tests of the WebAssembly UDFs
+#[cfg(feature = "wasm")]
+pub const WASM_ADD_MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+    0x01, 0x07, 0x01, 0x60, 0x02, 0x7e, 0x7e, 0x01, 0x7e, // types
+    0x03, 0x02, 0x01, 0x00, // functions
+    0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, // exports
+    0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x7c, 0x0b, // code
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
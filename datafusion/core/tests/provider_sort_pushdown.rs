@@ -0,0 +1,157 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use arrow::array::Int32Array;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use datafusion::assert_batches_eq;
+use datafusion::datasource::datasource::{TableProvider, TableType};
+use datafusion::error::Result;
+use datafusion::execution::context::{SessionContext, SessionState};
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use std::sync::{Arc, Mutex};
+
+/// A table of timestamps stored in ascending order, like an index of a
+/// time-series store, recording the scans it is asked for
+struct TimeSeriesProvider {
+    batch: RecordBatch,
+    /// The ordering and limit of every scan
+    scans: Mutex<Vec<(Vec<Expr>, Option<usize>)>>,
+}
+
+impl TimeSeriesProvider {
+    fn new(num_rows: i32) -> Result<Self> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new("ts", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int32Array::from(
+                (0..num_rows).collect::<Vec<_>>(),
+            ))],
+        )?;
+        Ok(Self {
+            batch,
+            scans: Mutex::new(vec![]),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for TimeSeriesProvider {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.batch.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: &Option<Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.scans.lock().unwrap().push((vec![], limit));
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![self.batch.clone()]],
+            self.schema(),
+            projection.clone(),
+        )?))
+    }
+
+    async fn scan_ordered(
+        &self,
+        _state: &SessionState,
+        projection: &Option<Vec<usize>>,
+        _filters: &[Expr],
+        ordering: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.scans.lock().unwrap().push((ordering.to_vec(), limit));
+        // the rows are stored in order, so the first rows are the top-k
+        let num_rows = self.batch.num_rows();
+        let num_rows = limit.map(|limit| limit.min(num_rows)).unwrap_or(num_rows);
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![self.batch.slice(0, num_rows)]],
+            self.schema(),
+            projection.clone(),
+        )?))
+    }
+
+    fn supports_sort_pushdown(&self, sort_exprs: &[Expr]) -> Result<bool> {
+        Ok(matches!(
+            sort_exprs,
+            [Expr::Sort { expr, asc: true, .. }]
+                if matches!(expr.as_ref(), Expr::Column(c) if c.name == "ts")
+        ))
+    }
+}
+
+#[tokio::test]
+async fn sort_limit_pushed_into_provider() -> Result<()> {
+    let provider = Arc::new(TimeSeriesProvider::new(100)?);
+    let ctx = SessionContext::new();
+    ctx.register_table("t", provider.clone())?;
+
+    let results = ctx
+        .sql("SELECT ts FROM t ORDER BY ts LIMIT 3")
+        .await?
+        .collect()
+        .await?;
+    let expected = vec![
+        "+----+", "| ts |", "+----+", "| 0  |", "| 1  |", "| 2  |", "+----+",
+    ];
+    assert_batches_eq!(expected, &results);
+
+    let scans = provider.scans.lock().unwrap();
+    assert_eq!(scans.len(), 1);
+    let (ordering, limit) = &scans[0];
+    assert_eq!(format!("{:?}", ordering), "[ts ASC NULLS LAST]");
+    assert_eq!(*limit, Some(3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsupported_sort_scans_whole_table() -> Result<()> {
+    let provider = Arc::new(TimeSeriesProvider::new(100)?);
+    let ctx = SessionContext::new();
+    ctx.register_table("t", provider.clone())?;
+
+    let results = ctx
+        .sql("SELECT ts FROM t ORDER BY ts DESC LIMIT 3")
+        .await?
+        .collect()
+        .await?;
+    let expected = vec![
+        "+----+", "| ts |", "+----+", "| 99 |", "| 98 |", "| 97 |", "+----+",
+    ];
+    assert_batches_eq!(expected, &results);
+
+    let scans = provider.scans.lock().unwrap();
+    assert_eq!(scans.len(), 1);
+    assert_eq!(scans[0], (vec![], None));
+    Ok(())
+}
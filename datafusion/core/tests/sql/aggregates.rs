@@ -922,7 +922,7 @@ async fn csv_query_approx_percentile_cont_with_weight() -> Result<()> {
     )
     .await
     .unwrap_err();
-    assert_eq!(results.to_string(), "Error during planning: The function ApproxPercentileContWithWeight does not support inputs of type Utf8.");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The function ApproxPercentileContWithWeight does not support inputs of type Utf8.");
 
     let results = plan_and_collect(
         &ctx,
@@ -930,7 +930,7 @@ async fn csv_query_approx_percentile_cont_with_weight() -> Result<()> {
     )
     .await
     .unwrap_err();
-    assert_eq!(results.to_string(), "Error during planning: The weight argument for ApproxPercentileContWithWeight does not support inputs of type Utf8.");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The weight argument for ApproxPercentileContWithWeight does not support inputs of type Utf8.");
 
     let results = plan_and_collect(
         &ctx,
@@ -938,7 +938,7 @@ async fn csv_query_approx_percentile_cont_with_weight() -> Result<()> {
     )
     .await
     .unwrap_err();
-    assert_eq!(results.to_string(), "Error during planning: The percentile argument for ApproxPercentileContWithWeight must be Float64, not Utf8.");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The percentile argument for ApproxPercentileContWithWeight must be Float64, not Utf8.");
 
     Ok(())
 }
@@ -978,7 +978,7 @@ async fn csv_query_approx_percentile_cont_with_histogram_bins() -> Result<()> {
     )
     .await
     .unwrap_err();
-    assert_eq!(results.to_string(), "Error during planning: The percentile sample points count for ApproxPercentileCont must be integer, not Utf8.");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The percentile sample points count for ApproxPercentileCont must be integer, not Utf8.");
 
     let results = plan_and_collect(
         &ctx,
@@ -986,7 +986,7 @@ async fn csv_query_approx_percentile_cont_with_histogram_bins() -> Result<()> {
     )
     .await
     .unwrap_err();
-    assert_eq!(results.to_string(), "Error during planning: The percentile sample points count for ApproxPercentileCont must be integer, not Float64.");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The percentile sample points count for ApproxPercentileCont must be integer, not Float64.");
 
     Ok(())
 }
@@ -1432,7 +1432,7 @@ async fn aggregate_timestamps_sum() -> Result<()> {
     .await
     .unwrap_err();
 
-    assert_eq!(results.to_string(), "Error during planning: The function Sum does not support inputs of type Timestamp(Nanosecond, None).");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The function Sum does not support inputs of type Timestamp(Nanosecond, None).");
 
     Ok(())
 }
@@ -1518,7 +1518,7 @@ async fn aggregate_timestamps_avg() -> Result<()> {
     .await
     .unwrap_err();
 
-    assert_eq!(results.to_string(), "Error during planning: The function Avg does not support inputs of type Timestamp(Nanosecond, None).");
+    assert_eq!(results.find_root().to_string(), "Error during planning: The function Avg does not support inputs of type Timestamp(Nanosecond, None).");
     Ok(())
 }
 
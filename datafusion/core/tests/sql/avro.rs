@@ -149,12 +149,11 @@ async fn avro_explain() {
         ],
         vec![
             "physical_plan",
-            "ProjectionExec: expr=[COUNT(UInt8(1))@0 as COUNT(UInt8(1))]\
-            \n  AggregateExec: mode=Final, gby=[], aggr=[COUNT(UInt8(1))]\
-            \n    CoalescePartitionsExec\
-            \n      AggregateExec: mode=Partial, gby=[], aggr=[COUNT(UInt8(1))]\
-            \n        RepartitionExec: partitioning=RoundRobinBatch(NUM_CORES)\
-            \n          AvroExec: files=[ARROW_TEST_DATA/avro/alltypes_plain.avro], limit=None\
+            "AggregateExec: mode=Final, gby=[], aggr=[COUNT(UInt8(1))]\
+            \n  CoalescePartitionsExec\
+            \n    AggregateExec: mode=Partial, gby=[], aggr=[COUNT(UInt8(1))]\
+            \n      RepartitionExec: partitioning=RoundRobinBatch(NUM_CORES)\
+            \n        AvroExec: files=[ARROW_TEST_DATA/avro/alltypes_plain.avro], limit=None\
             \n",
         ],
     ];
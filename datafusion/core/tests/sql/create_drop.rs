@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::io::Write;
 
 use datafusion::datasource::datasource::TableProviderFactory;
+use datafusion::datasource::MaterializedView;
 use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
 use datafusion::test_util::TestTableFactory;
 use tempfile::TempDir;
@@ -434,3 +435,474 @@ async fn create_csv_table_empty_file() -> Result<()> {
 async fn plan_and_collect(ctx: &SessionContext, sql: &str) -> Result<Vec<RecordBatch>> {
     ctx.sql(sql).await?.collect().await
 }
+
+#[tokio::test]
+async fn create_external_table_with_constraints() -> Result<()> {
+    let ctx = SessionContext::new();
+    let tmp_dir = TempDir::new()?;
+    let customers = tmp_dir.path().join("customers.csv");
+    std::fs::write(&customers, "1,alice\n2,bob\n")?;
+    let orders = tmp_dir.path().join("orders.csv");
+    std::fs::write(&orders, "10,1\n11,1\n12,2\n")?;
+
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE customers (id INT PRIMARY KEY, name VARCHAR) \
+         STORED AS CSV LOCATION '{}'",
+        customers.display()
+    ))
+    .await?;
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE orders (id INT, customer_id INT NOT NULL, \
+         PRIMARY KEY (id), FOREIGN KEY (customer_id) REFERENCES customers(id)) \
+         STORED AS CSV LOCATION '{}'",
+        orders.display()
+    ))
+    .await?;
+
+    // the constraints are not enforced, so they are not relied on by default
+    let distinct_sql = "SELECT DISTINCT id, name FROM customers";
+    let plan = ctx.sql(distinct_sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("Distinct"), "{}", formatted);
+
+    let sql = "SELECT orders.id FROM orders \
+               JOIN customers ON orders.customer_id = customers.id ORDER BY orders.id";
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("Join"), "{}", formatted);
+
+    ctx.sql("SET datafusion.optimizer.rely_on_constraints = true")
+        .await?;
+
+    // the primary key makes the rows distinct
+    let plan = ctx.sql(distinct_sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(!formatted.contains("Distinct"), "{}", formatted);
+
+    // every order has exactly one customer
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(!formatted.contains("Join"), "{}", formatted);
+
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+----+", "| id |", "+----+", "| 10 |", "| 11 |", "| 12 |", "+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn validate_check_constraints_of_scans() -> Result<()> {
+    let ctx = SessionContext::new();
+    let tmp_dir = TempDir::new()?;
+    let orders = tmp_dir.path().join("orders.csv");
+    std::fs::write(&orders, "10,5,3\n11,-1,2\n12,7,9\n13,,1\n")?;
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE orders (id INT, amount INT, \
+         discount INT CHECK (discount < 5), \
+         CONSTRAINT amount_positive CHECK (amount > 0)) \
+         STORED AS CSV LOCATION '{}'",
+        orders.display()
+    ))
+    .await?;
+
+    // the scans are not validated by default
+    let sql = "EXPLAIN ANALYZE SELECT id, amount FROM orders";
+    let formatted =
+        arrow::util::pretty::pretty_format_batches(&execute_to_batches(&ctx, sql).await)?
+            .to_string();
+    assert_not_contains!(&formatted, "CheckConstraintsExec");
+
+    ctx.sql("SET datafusion.execution.check_constraints.validate_scans = true")
+        .await?;
+    let formatted =
+        arrow::util::pretty::pretty_format_batches(&execute_to_batches(&ctx, sql).await)?
+            .to_string();
+    // only the constraints on the scanned columns are validated
+    assert_contains!(&formatted, "CheckConstraintsExec: checks=[amount_positive]");
+    assert_contains!(&formatted, "check_violations{constraint=amount_positive}=1");
+
+    // the rows are returned unchanged
+    let actual = execute_to_batches(&ctx, "SELECT COUNT(*) FROM orders").await;
+    let expected = vec![
+        "+-----------------+",
+        "| COUNT(UInt8(1)) |",
+        "+-----------------+",
+        "| 4               |",
+        "+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn analyze_table_builds_file_indexes() -> Result<()> {
+    let ctx = SessionContext::new();
+    let tmp_dir = TempDir::new()?;
+    let orders = tmp_dir.path().join("orders");
+    std::fs::create_dir_all(&orders)?;
+    std::fs::write(orders.join("1.csv"), "1,3\n2,5\n")?;
+    std::fs::write(orders.join("2.csv"), "10,7\n12,9\n")?;
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE orders (id INT, amount INT) STORED AS CSV LOCATION '{}'",
+        orders.display()
+    ))
+    .await?;
+
+    ctx.sql("ANALYZE TABLE orders").await?;
+    assert!(orders.join("1.csv.dfindex").exists());
+    assert!(orders.join("2.csv.dfindex").exists());
+
+    // the indexes are only consulted when the option is set
+    let sql = "EXPLAIN SELECT id FROM orders WHERE id = 12";
+    let formatted =
+        arrow::util::pretty::pretty_format_batches(&execute_to_batches(&ctx, sql).await)?
+            .to_string();
+    assert_contains!(&formatted, "1.csv");
+
+    ctx.sql("SET datafusion.execution.use_file_indexes = true")
+        .await?;
+    let formatted =
+        arrow::util::pretty::pretty_format_batches(&execute_to_batches(&ctx, sql).await)?
+            .to_string();
+    assert_not_contains!(&formatted, "1.csv");
+    assert_contains!(&formatted, "2.csv");
+
+    let actual =
+        execute_to_batches(&ctx, "SELECT id, amount FROM orders WHERE id = 12").await;
+    let expected = vec![
+        "+----+--------+",
+        "| id | amount |",
+        "+----+--------+",
+        "| 12 | 9      |",
+        "+----+--------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // only listing tables can be analyzed
+    ctx.sql("CREATE TABLE totals AS SELECT SUM(amount) FROM orders")
+        .await?;
+    let err = ctx.sql("ANALYZE TABLE totals").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "This feature is not implemented: ANALYZE TABLE is only supported for listing tables, \"totals\" is not one"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_materialized_view() -> Result<()> {
+    let ctx = SessionContext::new();
+    let tmp_dir = TempDir::new()?;
+    let sales = tmp_dir.path().join("sales");
+    std::fs::create_dir(&sales)?;
+    std::fs::write(sales.join("1.csv"), "a,x,1\na,y,2\nb,x,3\n")?;
+
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE sales (region VARCHAR, product VARCHAR, amount BIGINT) \
+         STORED AS CSV LOCATION '{}'",
+        sales.display()
+    ))
+    .await?;
+    let location = tmp_dir.path().join("mv");
+    ctx.sql(&format!(
+        "CREATE MATERIALIZED VIEW mv WITH (location = '{}') AS \
+         SELECT region, product, SUM(amount), COUNT(amount) FROM sales \
+         GROUP BY region, product",
+        location.display()
+    ))
+    .await?;
+    assert!(location.join("part-0.parquet").exists());
+
+    // the queries only read the views when enabled
+    let sql = "SELECT region, product, SUM(amount), COUNT(amount) FROM sales \
+               GROUP BY region, product";
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: sales"), "{}", formatted);
+
+    // the query of the view reads the view
+    ctx.sql("SET datafusion.optimizer.materialized_view_rewrite = true")
+        .await?;
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: mv"), "{}", formatted);
+    assert!(!formatted.contains("TableScan: sales"), "{}", formatted);
+
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+--------+---------+-------------------+---------------------+",
+        "| region | product | SUM(sales.amount) | COUNT(sales.amount) |",
+        "+--------+---------+-------------------+---------------------+",
+        "| a      | x       | 1                 | 1                   |",
+        "| a      | y       | 2                 | 1                   |",
+        "| b      | x       | 3                 | 1                   |",
+        "+--------+---------+-------------------+---------------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    // coarser aggregations are rolled up from the view
+    let sql = "SELECT region, SUM(amount), COUNT(amount), MAX(amount) FROM sales \
+               WHERE region = 'a' GROUP BY region";
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: sales"), "{}", formatted);
+
+    let sql = "SELECT region, SUM(amount), COUNT(amount) FROM sales \
+               WHERE region = 'a' GROUP BY region";
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: mv"), "{}", formatted);
+    assert!(!formatted.contains("TableScan: sales"), "{}", formatted);
+
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+--------+-------------------+---------------------+",
+        "| region | SUM(sales.amount) | COUNT(sales.amount) |",
+        "+--------+-------------------+---------------------+",
+        "| a      | 3                 | 2                   |",
+        "+--------+-------------------+---------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the view keeps its results until it is refreshed, but the queries do
+    // not read its stale results
+    std::fs::write(sales.join("2.csv"), "a,x,10\n")?;
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+--------+-------------------+---------------------+",
+        "| region | SUM(sales.amount) | COUNT(sales.amount) |",
+        "+--------+-------------------+---------------------+",
+        "| a      | 13                | 3                   |",
+        "+--------+-------------------+---------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: sales"), "{}", formatted);
+    let view = ctx.table("mv")?;
+    assert_eq!(
+        view.collect()
+            .await?
+            .iter()
+            .map(|b| b.num_rows())
+            .sum::<usize>(),
+        3
+    );
+
+    ctx.sql("REFRESH MATERIALIZED VIEW mv").await?;
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: mv"), "{}", formatted);
+    let actual = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &actual);
+
+    // materialized views are dropped like views
+    ctx.sql("DROP VIEW mv").await?;
+    let plan = ctx.sql(sql).await?.to_logical_plan()?;
+    let formatted = format!("{:?}", plan);
+    assert!(formatted.contains("TableScan: sales"), "{}", formatted);
+
+    let err = ctx
+        .sql("REFRESH MATERIALIZED VIEW sales")
+        .await
+        .expect_err("sales is not a materialized view");
+    assert!(err
+        .to_string()
+        .contains("Materialized view \"sales\" doesn't exist."));
+    Ok(())
+}
+
+#[tokio::test]
+async fn refresh_materialized_view_incrementally() -> Result<()> {
+    let ctx = SessionContext::new();
+    let tmp_dir = TempDir::new()?;
+    let sales = tmp_dir.path().join("sales");
+    std::fs::create_dir(&sales)?;
+    std::fs::write(sales.join("1.csv"), "a,x,1\na,y,2\nb,x,3\n")?;
+
+    ctx.sql(&format!(
+        "CREATE EXTERNAL TABLE sales (region VARCHAR, product VARCHAR, amount BIGINT) \
+         STORED AS CSV LOCATION '{}'",
+        sales.display()
+    ))
+    .await?;
+    let totals = tmp_dir.path().join("totals");
+    ctx.sql(&format!(
+        "CREATE MATERIALIZED VIEW totals WITH (location = '{}') AS \
+         SELECT region, SUM(amount) AS total, COUNT(*) AS n FROM sales GROUP BY region",
+        totals.display()
+    ))
+    .await?;
+    let big_sales = tmp_dir.path().join("big_sales");
+    ctx.sql(&format!(
+        "CREATE MATERIALIZED VIEW big_sales WITH (location = '{}') AS \
+         SELECT region, amount FROM sales WHERE amount > 1",
+        big_sales.display()
+    ))
+    .await?;
+    let view = ctx
+        .catalog("datafusion")
+        .unwrap()
+        .schema("public")
+        .unwrap()
+        .table("totals")
+        .unwrap();
+    let view = view.as_any().downcast_ref::<MaterializedView>().unwrap();
+    assert!(view.is_incremental());
+
+    let parquet_files = |dir: &std::path::Path| -> Result<usize> {
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir)? {
+            if entry?.path().extension().map_or(false, |e| e == "parquet") {
+                count += 1;
+            }
+        }
+        Ok(count)
+    };
+    let big_sales_files = parquet_files(&big_sales)?;
+
+    // a file of the same size is not read again, so only the added file is
+    std::fs::write(sales.join("1.csv"), "a,x,5\na,y,2\nb,x,3\n")?;
+    std::fs::write(sales.join("2.csv"), "a,x,10\nc,z,4\n")?;
+    ctx.sql("REFRESH MATERIALIZED VIEW totals").await?;
+    ctx.sql("REFRESH MATERIALIZED VIEW big_sales").await?;
+
+    let actual = execute_to_batches(&ctx, "SELECT * FROM totals").await;
+    let expected = vec![
+        "+--------+-------+---+",
+        "| region | total | n |",
+        "+--------+-------+---+",
+        "| a      | 13    | 3 |",
+        "| b      | 3     | 1 |",
+        "| c      | 4     | 1 |",
+        "+--------+-------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    // the results over the added file are appended to the stored ones
+    assert!(big_sales.join("part-0.parquet").exists());
+    assert!(parquet_files(&big_sales)? > big_sales_files);
+    let actual = execute_to_batches(&ctx, "SELECT * FROM big_sales").await;
+    let expected = vec![
+        "+--------+--------+",
+        "| region | amount |",
+        "+--------+--------+",
+        "| a      | 10     |",
+        "| a      | 2      |",
+        "| b      | 3      |",
+        "| c      | 4      |",
+        "+--------+--------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    // removing a file recomputes the results
+    std::fs::remove_file(sales.join("2.csv"))?;
+    ctx.sql("REFRESH MATERIALIZED VIEW totals").await?;
+    let actual = execute_to_batches(&ctx, "SELECT * FROM totals").await;
+    let expected = vec![
+        "+--------+-------+---+",
+        "| region | total | n |",
+        "+--------+-------+---+",
+        "| a      | 7     | 2 |",
+        "| b      | 3     | 1 |",
+        "+--------+-------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_tables_in_catalog_with_location() -> Result<()> {
+    let ctx =
+        SessionContext::with_config(SessionConfig::new().with_information_schema(true));
+    let tmp_dir = TempDir::new()?;
+    let orders = tmp_dir.path().join("eu").join("orders");
+    std::fs::create_dir_all(&orders)?;
+    std::fs::write(orders.join("1.csv"), "1,10.5\n2,3.25\n")?;
+
+    ctx.sql(&format!(
+        "CREATE DATABASE Sales LOCATION '{}'",
+        tmp_dir.path().display()
+    ))
+    .await?;
+    ctx.sql("CREATE SCHEMA sales.EU").await?;
+    ctx.sql("CREATE SCHEMA IF NOT EXISTS sales.eu").await?;
+
+    // the table is read from the directory named after it in its schema
+    ctx.sql(
+        "CREATE EXTERNAL TABLE Sales.EU.Orders (id INT, amount DOUBLE) STORED AS CSV",
+    )
+    .await?;
+    let actual = execute_to_batches(
+        &ctx,
+        "SELECT sales.eu.orders.id, amount FROM sales.eu.orders ORDER BY id",
+    )
+    .await;
+    let expected = vec![
+        "+----+--------+",
+        "| id | amount |",
+        "+----+--------+",
+        "| 1  | 10.5   |",
+        "| 2  | 3.25   |",
+        "+----+--------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    ctx.sql(
+        "CREATE TABLE sales.eu.big AS SELECT * FROM sales.eu.orders WHERE amount > 5",
+    )
+    .await?;
+    ctx.sql("CREATE VIEW sales.eu.v AS SELECT id FROM sales.eu.big")
+        .await?;
+    let actual = execute_to_batches(
+        &ctx,
+        "SELECT table_catalog, table_schema, table_name, table_type \
+         FROM sales.information_schema.tables WHERE table_schema = 'eu'",
+    )
+    .await;
+    let expected = vec![
+        "+---------------+--------------+------------+------------+",
+        "| table_catalog | table_schema | table_name | table_type |",
+        "+---------------+--------------+------------+------------+",
+        "| sales         | eu           | big        | BASE TABLE |",
+        "| sales         | eu           | orders     | BASE TABLE |",
+        "| sales         | eu           | v          | VIEW       |",
+        "+---------------+--------------+------------+------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    let actual = execute_to_batches(&ctx, "DESCRIBE sales.eu.orders").await;
+    let expected = vec![
+        "+-------------+-----------+-------------+",
+        "| column_name | data_type | is_nullable |",
+        "+-------------+-----------+-------------+",
+        "| amount      | Float64   | YES         |",
+        "| id          | Int32     | YES         |",
+        "+-------------+-----------+-------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    ctx.sql("DROP VIEW sales.eu.v").await?;
+    ctx.sql("DROP TABLE Sales.EU.Big").await?;
+    let schema = ctx.catalog("sales").unwrap().schema("eu").unwrap();
+    assert_eq!(schema.table_names(), vec!["orders"]);
+
+    // tables are only created without a location in schemas with one
+    let err = ctx
+        .sql("CREATE EXTERNAL TABLE t (a INT) STORED AS CSV")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: Table 't' requires a location, as its schema has none"
+    );
+
+    let err = ctx.sql("CREATE SCHEMA missing.s").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Missing 'missing' catalog"
+    );
+    Ok(())
+}
@@ -28,6 +28,34 @@ async fn csv_query_error() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn planning_error_locations() -> Result<()> {
+    let ctx = SessionContext::new();
+    register_aggregate_csv(&ctx).await?;
+
+    // the type error points at the expression of the SELECT list
+    let sql = "SELECT c1, sum(c1) FROM aggregate_test_100 GROUP BY c1";
+    let err = ctx.create_logical_plan(sql).unwrap_err();
+    assert_eq!(
+        err.find_root().to_string(),
+        "Error during planning: The function Sum does not support inputs of type Utf8."
+    );
+    assert_contains!(
+        err.to_string(),
+        "1 | SELECT c1, sum(c1) FROM aggregate_test_100 GROUP BY c1\n  |            ^^^^^^^"
+    );
+
+    // the unknown column points at the reference in the failing expression,
+    // not at the alias of the same name
+    let sql = "SELECT c2 AS x FROM aggregate_test_100 WHERE c2 > 1 AND x > 1";
+    let err = ctx.create_logical_plan(sql).unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "1 | SELECT c2 AS x FROM aggregate_test_100 WHERE c2 > 1 AND x > 1\n  |                                                         ^"
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_cast_expressions_error() -> Result<()> {
     // sin(utf8) should error
@@ -61,7 +89,7 @@ async fn test_aggregation_with_bad_arguments() -> Result<()> {
     let logical_plan = ctx.create_logical_plan(sql);
     let err = logical_plan.unwrap_err();
     assert_eq!(
-        err.to_string(),
+        err.find_root().to_string(),
         DataFusionError::Plan(
             "The function Count expects 1 arguments, but 0 were provided".to_string()
         )
@@ -173,3 +201,77 @@ async fn unsupported_sql_returns_error() -> Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn report_all_planning_errors() -> Result<()> {
+    let ctx = SessionContext::new();
+    register_aggregate_csv(&ctx).await?;
+    let sql = "SELECT c1, c99, sin(c1)\nFROM aggregate_test_100\nWHERE c100 > 1";
+    let errors = ctx
+        .create_logical_plan_with_recovery(sql)
+        .expect_err("query should have failed");
+    let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert_contains!(&errors[0], "3 | WHERE c100 > 1\n  |       ^^^^");
+    assert_contains!(
+        &errors[1],
+        "1 | SELECT c1, c99, sin(c1)\n  |            ^^^"
+    );
+    assert_contains!(&errors[2], "Coercion from [Utf8]");
+    assert_contains!(
+        &errors[2],
+        "1 | SELECT c1, c99, sin(c1)\n  |                 ^^^^^^^"
+    );
+
+    // syntax errors are reported alone
+    let errors = ctx
+        .create_logical_plan_with_recovery("SELECT c99 FROM")
+        .expect_err("query should have failed");
+    assert_eq!(errors.len(), 1);
+
+    let sql = "SELECT c1 FROM aggregate_test_100 WHERE c2 > 1";
+    assert_eq!(
+        format!("{:?}", ctx.create_logical_plan_with_recovery(sql).unwrap()),
+        format!("{:?}", ctx.create_logical_plan(sql)?)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn sql_nested_deeper_than_max_depth() -> Result<()> {
+    let ctx = SessionContext::new();
+    let sql = format!("SELECT {}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    let err = ctx.create_logical_plan(&sql).unwrap_err();
+    assert_contains!(err.to_string(), "nested deeper than the limit of 64 levels");
+
+    let ctx = SessionContext::with_config(SessionConfig::new().with_sql_max_depth(8));
+    ctx.create_logical_plan("SELECT (((1)))")?;
+    let err = ctx
+        .create_logical_plan("SELECT ((((((((((1))))))))))")
+        .unwrap_err();
+    assert_contains!(err.to_string(), "nested deeper than the limit of 8 levels");
+    // the nesting limit of the parser is also the one of the planner
+    let err = ctx
+        .create_logical_plan("SELECT abs(abs(abs(abs(abs(abs(abs(abs(1))))))))")
+        .unwrap_err();
+    assert_contains!(err.to_string(), "nested deeper than the limit of 8 levels");
+    Ok(())
+}
+
+#[tokio::test]
+async fn long_chain_of_binary_operators() -> Result<()> {
+    let ctx = SessionContext::with_config(SessionConfig::new().with_sql_max_depth(8));
+    register_aggregate_csv(&ctx).await?;
+    let predicate = (0..200)
+        .map(|i| format!("c3 = {}", i))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let sql = format!(
+        "SELECT count(*) FROM aggregate_test_100 WHERE {}",
+        predicate
+    );
+    let actual = execute(&ctx, &sql).await;
+    let sql = "SELECT count(*) FROM aggregate_test_100 WHERE c3 BETWEEN 0 AND 199";
+    assert_eq!(actual, execute(&ctx, sql).await);
+    Ok(())
+}
@@ -1179,6 +1179,41 @@ async fn test_random_expression() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_seeded_random_expression() -> Result<()> {
+    let sql = "SELECT random() r1, random() r2 FROM (VALUES (1), (2), (3)) t";
+    let seeded =
+        |seed| SessionContext::with_config(SessionConfig::new().with_random_seed(seed));
+    let actual = execute(&seeded(42), sql).await;
+    // the same seed gives the same values in each run
+    assert_eq!(actual, execute(&seeded(42), sql).await);
+    assert_ne!(actual, execute(&seeded(7), sql).await);
+    assert_ne!(actual[0], actual[1]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seeded_random_expression_single_partition() -> Result<()> {
+    // the partitions of a query share its generator, so the values are only
+    // reproducible when the query runs in a single partition
+    let sql = "SELECT c1, random() FROM aggregate_test_100 WHERE c3 > 0";
+    let config = || {
+        SessionConfig::new()
+            .with_target_partitions(1)
+            .with_batch_size(8)
+            .with_random_seed(42)
+    };
+    let ctx = SessionContext::with_config(config());
+    register_aggregate_csv(&ctx).await?;
+    let actual = execute(&ctx, sql).await;
+    assert!(actual.len() > 8);
+
+    let ctx = SessionContext::with_config(config());
+    register_aggregate_csv(&ctx).await?;
+    assert_eq!(actual, execute(&ctx, sql).await);
+    Ok(())
+}
+
 #[tokio::test]
 async fn case_with_bool_type_result() -> Result<()> {
     let ctx = SessionContext::new();
@@ -159,6 +159,25 @@ async fn query_array_scalar() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn query_array_scalar_with_output_coercion() -> Result<()> {
+    let ctx =
+        SessionContext::with_config(SessionConfig::new().with_output_coercion(true));
+
+    let sql = "SELECT make_array(1, 2, 3) AS a, make_array('x', 'y') AS b";
+    let actual = execute_to_batches(&ctx, sql).await;
+    assert_eq!(actual[0].schema().field(0).data_type(), &DataType::Utf8);
+    let expected = vec![
+        "+---------+-----------+",
+        "| a       | b         |",
+        "+---------+-----------+",
+        "| [1,2,3] | [\"x\",\"y\"] |",
+        "+---------+-----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn coalesce_static_empty_value() -> Result<()> {
     let ctx = SessionContext::new();
@@ -485,6 +485,42 @@ async fn csv_group_by_date() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn group_by_date_min_max_timestamp() -> Result<()> {
+    let ctx = SessionContext::new();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("date", DataType::Date32, false),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+    ]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Date32Array::from(vec![100, 101, 100, 101, 101])),
+            Arc::new(TimestampMillisecondArray::from(vec![
+                Some(86_400_000),
+                Some(172_800_000),
+                Some(3_600_000),
+                None,
+                Some(7_200_000),
+            ])),
+        ],
+    )?;
+
+    ctx.register_batch("dates", data)?;
+    let sql = "SELECT date, MIN(ts), MAX(ts) FROM dates GROUP BY date";
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+------------+---------------------+---------------------+",
+        "| date       | MIN(dates.ts)       | MAX(dates.ts)       |",
+        "+------------+---------------------+---------------------+",
+        "| 1970-04-11 | 1970-01-01 01:00:00 | 1970-01-02 00:00:00 |",
+        "| 1970-04-12 | 1970-01-01 02:00:00 | 1970-01-03 00:00:00 |",
+        "+------------+---------------------+---------------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn group_by_date_trunc() -> Result<()> {
     let tmp_dir = TempDir::new()?;
@@ -746,3 +782,47 @@ async fn csv_query_group_by_order_by_avg_group_by_substr() -> Result<()> {
     assert_batches_sorted_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn group_by_primary_key() -> Result<()> {
+    let ctx = SessionContext::new();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("amount", DataType::Int32, false),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int32Array::from_slice(&[1, 2, 3])),
+            Arc::new(StringArray::from_slice(&["a", "b", "a"])),
+            Arc::new(Int32Array::from_slice(&[10, 20, 30])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![batch]])?
+        .with_constraints(Constraints::new(vec![Constraint::PrimaryKey(vec![0])]));
+    ctx.register_table("t", Arc::new(table))?;
+
+    // the name is determined by the primary key
+    let sql = "SELECT id, name, SUM(amount) FROM t GROUP BY id ORDER BY id";
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+----+------+---------------+",
+        "| id | name | SUM(t.amount) |",
+        "+----+------+---------------+",
+        "| 1  | a    | 10            |",
+        "| 2  | b    | 20            |",
+        "| 3  | a    | 30            |",
+        "+----+------+---------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // but the id is not determined by the name
+    let sql = "SELECT name, id FROM t GROUP BY name";
+    let err = ctx.create_logical_plan(sql).unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Projection references non-aggregate values"
+    );
+    Ok(())
+}
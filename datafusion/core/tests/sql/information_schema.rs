@@ -695,22 +695,41 @@ async fn show_all() {
 
     // Has all the default values, should be in order by name
     let expected = vec![
-        "+-------------------------------------------------+---------+",
-        "| name                                            | setting |",
-        "+-------------------------------------------------+---------+",
-        "| datafusion.execution.batch_size                 | 8192    |",
-        "| datafusion.execution.coalesce_batches           | true    |",
-        "| datafusion.execution.coalesce_target_batch_size | 4096    |",
-        "| datafusion.execution.parquet.enable_page_index  | false   |",
-        "| datafusion.execution.parquet.pushdown_filters   | false   |",
-        "| datafusion.execution.parquet.reorder_filters    | false   |",
-        "| datafusion.execution.time_zone                  | UTC     |",
-        "| datafusion.explain.logical_plan_only            | false   |",
-        "| datafusion.explain.physical_plan_only           | false   |",
-        "| datafusion.optimizer.filter_null_join_keys      | false   |",
-        "| datafusion.optimizer.max_passes                 | 3       |",
-        "| datafusion.optimizer.skip_failed_rules          | true    |",
-        "+-------------------------------------------------+---------+",
+        "+---------------------------------------------------------------------+---------+",
+        "| name                                                                | setting |",
+        "+---------------------------------------------------------------------+---------+",
+        "| datafusion.catalog.search_path                                      | NULL    |",
+        "| datafusion.execution.adaptive.broadcast_threshold                   | 10000   |",
+        "| datafusion.execution.adaptive.enabled                               | false   |",
+        "| datafusion.execution.batch_size                                     | 8192    |",
+        "| datafusion.execution.check_constraints.rejects_table                | NULL    |",
+        "| datafusion.execution.check_constraints.validate_scans               | false   |",
+        "| datafusion.execution.coalesce_batches                               | true    |",
+        "| datafusion.execution.coalesce_target_batch_size                     | 4096    |",
+        "| datafusion.execution.object_store.max_retries                       | 3       |",
+        "| datafusion.execution.object_store.retry_backoff_ms                  | 100     |",
+        "| datafusion.execution.output_coercion                                | false   |",
+        "| datafusion.execution.parquet.enable_page_index                      | false   |",
+        "| datafusion.execution.parquet.pushdown_filters                       | false   |",
+        "| datafusion.execution.parquet.reorder_filters                        | false   |",
+        "| datafusion.execution.random_seed                                    | NULL    |",
+        "| datafusion.execution.skip_corrupt_files                             | false   |",
+        "| datafusion.execution.skip_partial_aggregation_probe_ratio_threshold | 0.8     |",
+        "| datafusion.execution.skip_partial_aggregation_probe_rows            | 100000  |",
+        "| datafusion.execution.time_zone                                      | UTC     |",
+        "| datafusion.execution.use_file_indexes                               | false   |",
+        "| datafusion.explain.logical_plan_only                                | false   |",
+        "| datafusion.explain.physical_plan_only                               | false   |",
+        "| datafusion.optimizer.eliminate_sort_repartition                     | true    |",
+        "| datafusion.optimizer.filter_null_join_keys                          | false   |",
+        "| datafusion.optimizer.materialized_view_rewrite                      | false   |",
+        "| datafusion.optimizer.max_passes                                     | 3       |",
+        "| datafusion.optimizer.nested_projection_pushdown                     | true    |",
+        "| datafusion.optimizer.physical_projection_pushdown                   | true    |",
+        "| datafusion.optimizer.rely_on_constraints                            | false   |",
+        "| datafusion.optimizer.skip_failed_rules                              | true    |",
+        "| datafusion.sql_parser.max_depth                                     | 64      |",
+        "+---------------------------------------------------------------------+---------+",
     ];
 
     assert_batches_eq!(expected, &results);
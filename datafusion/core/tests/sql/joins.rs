@@ -16,6 +16,8 @@
 // under the License.
 
 use super::*;
+use datafusion::datasource::file_format::file_type::FileType;
+use datafusion::datasource::listing::bucket::Bucketing;
 use datafusion::from_slice::FromSlice;
 
 #[tokio::test]
@@ -1765,3 +1767,82 @@ async fn reduce_full_join_to_inner_join() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn join_bucketed_tables() -> Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let ctx = SessionContext::with_config(SessionConfig::new().with_target_partitions(4));
+    let bucketing = Bucketing::new(vec!["id".to_string()], 3);
+    for (name, values) in [
+        ("l", "(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')"),
+        ("r", "(2, 'x'), (3, 'y'), (5, 'z')"),
+    ] {
+        let path = tmp_dir.path().join(name);
+        let path = path.to_str().unwrap();
+        ctx.sql(&format!(
+            "SELECT column1 AS id, column2 AS {}_name FROM (VALUES {})",
+            name, values
+        ))
+        .await?
+        .write_bucketed(path, FileType::CSV, &bucketing)
+        .await?;
+        ctx.sql(&format!(
+            "CREATE EXTERNAL TABLE {} (id BIGINT, {}_name VARCHAR) STORED AS CSV \
+             WITH HEADER ROW CLUSTERED BY (id) INTO 3 BUCKETS LOCATION '{}'",
+            name, name, path
+        ))
+        .await?;
+    }
+
+    // the buckets are joined without repartitioning the rows
+    let sql = "SELECT l.id, l_name, r_name FROM l JOIN r ON l.id = r.id";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(plan.as_ref()).indent().to_string();
+    assert_contains!(&formatted, "BucketedExec: columns=[id@0], num_buckets=3");
+    assert_not_contains!(&formatted, "partitioning=Hash");
+
+    let expected = vec![
+        "+----+--------+--------+",
+        "| id | l_name | r_name |",
+        "+----+--------+--------+",
+        "| 2  | b      | x      |",
+        "| 3  | c      | y      |",
+        "+----+--------+--------+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_sorted_eq!(expected, &results);
+
+    // only the bucket of the value is read
+    let sql = "SELECT l_name FROM l WHERE id = 3";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(plan.as_ref()).indent().to_string();
+    assert_eq!(formatted.matches("bucket-").count(), 1);
+
+    let expected = vec![
+        "+--------+",
+        "| l_name |",
+        "+--------+",
+        "| c      |",
+        "+--------+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bucketed_table_unknown_column() -> Result<()> {
+    let ctx = SessionContext::new();
+    let sql = "CREATE EXTERNAL TABLE t (a INT) STORED AS CSV \
+               CLUSTERED BY (b) INTO 3 BUCKETS LOCATION 'tests/empty.csv'";
+    let err = ctx.sql(sql).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: Bucket column 'b' is not a column of the files of the table"
+    );
+    Ok(())
+}
@@ -40,7 +40,7 @@ use datafusion::{
     physical_plan::ColumnarValue,
 };
 use datafusion::{execution::context::SessionContext, physical_plan::displayable};
-use datafusion_common::{assert_contains, assert_not_contains};
+use datafusion_common::{assert_contains, assert_not_contains, Constraint, Constraints};
 use datafusion_expr::Volatility;
 use object_store::path::Path;
 use std::fs::File;
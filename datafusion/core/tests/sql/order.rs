@@ -16,6 +16,7 @@
 // under the License.
 
 use super::*;
+use datafusion::datasource::file_format::file_type::FileType;
 use test_utils::{batches_to_vec, partitions_to_sorted_vec};
 
 #[tokio::test]
@@ -232,3 +233,36 @@ async fn sort_with_lots_of_repetition_values() -> Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn write_clustered_by_zorder() -> Result<()> {
+    let ctx = SessionContext::with_config(SessionConfig::new().with_target_partitions(1));
+    let tmp_dir = TempDir::new()?;
+    let path = tmp_dir.path().join("clustered");
+    let path = path.to_str().unwrap();
+    ctx.sql(
+        "SELECT column1 AS x, column2 AS y \
+         FROM (VALUES (3, 0), (0, 1), (1, 1), (0, 0), (2, 0), (1, 0))",
+    )
+    .await?
+    .write_clustered(path, FileType::CSV, vec![zorder(vec![col("x"), col("y")])])
+    .await?;
+
+    ctx.register_csv("t", path, CsvReadOptions::new()).await?;
+    let actual = execute_to_batches(&ctx, "SELECT * FROM t").await;
+    // the points of each quadrant are contiguous in the file
+    let expected = vec![
+        "+---+---+",
+        "| x | y |",
+        "+---+---+",
+        "| 0 | 0 |",
+        "| 0 | 1 |",
+        "| 1 | 0 |",
+        "| 1 | 1 |",
+        "| 2 | 0 |",
+        "| 3 | 0 |",
+        "+---+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
@@ -69,6 +69,11 @@ async fn qualified_table_references_and_fields() -> Result<()> {
         error.to_string(),
         "No field named 'f1.c1'. Valid fields are 'test.f.c1', 'test.test.c2'"
     );
+    // the error points at the column in the query
+    assert_contains!(
+        error.to_string(),
+        "1 | SELECT f1.c1 from test\n  |        ^^^^^"
+    );
 
     // however, enclosing it in double quotes is ok
     let sql = r#"SELECT "f.c1" from test"#;
@@ -138,3 +143,46 @@ async fn test_partial_qualified_name() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn async_catalog_table_references() -> Result<()> {
+    use datafusion::catalog::async_catalog::catalog_as_async;
+    use datafusion::catalog::catalog::{CatalogProvider, MemoryCatalogProvider};
+    use datafusion::catalog::schema::{MemorySchemaProvider, SchemaProvider};
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "a",
+        Arc::new(Int32Array::from_slice(&[1, 2, 3])) as ArrayRef,
+    )])?;
+    let schema = MemorySchemaProvider::new();
+    for name in ["t", "u"] {
+        let table = MemTable::try_new(batch.schema(), vec![vec![batch.clone()]])?;
+        schema.register_table(name.to_owned(), Arc::new(table))?;
+    }
+    let catalog = MemoryCatalogProvider::new();
+    catalog.register_schema("s", Arc::new(schema))?;
+
+    let ctx = SessionContext::new();
+    ctx.register_async_catalog("remote", catalog_as_async(Arc::new(catalog)));
+
+    // the tables are resolved before planning the query
+    let sql = "SELECT COUNT(*) FROM remote.s.t JOIN remote.s.u ON t.a = u.a";
+    assert!(ctx.create_logical_plan(sql).is_err());
+    let actual = ctx.sql(sql).await?.collect().await?;
+    let expected = vec![
+        "+-----------------+",
+        "| COUNT(UInt8(1)) |",
+        "+-----------------+",
+        "| 3               |",
+        "+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    let resolved = ctx.catalog("remote").unwrap().schema("s").unwrap();
+    let mut table_names = resolved.table_names();
+    table_names.sort();
+    assert_eq!(table_names, vec!["t", "u"]);
+
+    let err = ctx.sql("SELECT * FROM remote.s.v").await.unwrap_err();
+    assert_contains!(err.to_string(), "'remote.s.v' not found");
+    Ok(())
+}
@@ -470,3 +470,138 @@ order by value desc;
 
     Ok(())
 }
+
+#[tokio::test]
+async fn uncorrelated_scalar_subqueries() -> Result<()> {
+    let ctx = SessionContext::new();
+    ctx.register_table("t", table_with_sequence(1, 5)?)?;
+
+    // the identical subqueries are executed once
+    let sql =
+        "SELECT i, i - (SELECT max(i) FROM t) AS d, (SELECT max(i) FROM t) * 10 AS m \
+               FROM t WHERE i > 3 ORDER BY i";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(plan.as_ref()).indent().to_string();
+    assert_eq!(
+        formatted.matches("ScalarSubqueryExec").count(),
+        1,
+        "{}",
+        formatted
+    );
+    assert_contains!(&formatted, "ScalarSubqueryExec: subqueries=1");
+
+    let expected = vec![
+        "+---+----+----+",
+        "| i | d  | m  |",
+        "+---+----+----+",
+        "| 4 | -1 | 50 |",
+        "| 5 | 0  | 50 |",
+        "+---+----+----+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+
+    let sql = "SELECT i FROM t WHERE i > (SELECT avg(i) FROM t) OR i = 1 ORDER BY i";
+    let expected = vec![
+        "+---+", //
+        "| i |", "+---+", "| 1 |", "| 4 |", "| 5 |", "+---+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+
+    let sql = "SELECT i, (SELECT i FROM t) AS j FROM t";
+    let err = try_execute_to_batches(&ctx, sql).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Scalar subquery returned more than one row"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn uncorrelated_scalar_subqueries_in_other_operators() -> Result<()> {
+    let ctx = SessionContext::new();
+    ctx.register_table("t", table_with_sequence(1, 5)?)?;
+
+    // aggregate arguments
+    let sql = "SELECT sum(i * (SELECT max(i) FROM t)) AS s FROM t";
+    let expected = vec!["+----+", "| s  |", "+----+", "| 75 |", "+----+"];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+
+    // sort keys
+    let sql = "SELECT i FROM t ORDER BY abs(i - (SELECT avg(i) FROM t)), i";
+    let expected = vec![
+        "+---+", //
+        "| i |", "+---+", "| 3 |", "| 2 |", "| 4 |", "| 1 |", "| 5 |", "+---+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+
+    // window function arguments
+    let sql = "SELECT i, sum(i * (SELECT max(i) FROM t)) OVER (ORDER BY i) AS s \
+               FROM t WHERE i < 3 ORDER BY i";
+    let expected = vec![
+        "+---+----+",
+        "| i | s  |",
+        "+---+----+",
+        "| 1 | 5  |",
+        "| 2 | 15 |",
+        "+---+----+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+
+    // join filters
+    let sql = "SELECT t1.i FROM t t1 JOIN t t2 \
+               ON t1.i = t2.i AND t1.i + t2.i > (SELECT max(i) FROM t) ORDER BY t1.i";
+    let expected = vec![
+        "+---+", //
+        "| i |", "+---+", "| 3 |", "| 4 |", "| 5 |", "+---+",
+    ];
+    let results = execute_to_batches(&ctx, sql).await;
+    assert_batches_eq!(expected, &results);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uncorrelated_scalar_subquery_executed_per_execution() -> Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let path = tmp_dir.path().join("t.csv");
+    std::fs::write(&path, "i\n1\n2\n")?;
+    let ctx = SessionContext::new();
+    ctx.register_csv("t", path.to_str().unwrap(), CsvReadOptions::new())
+        .await?;
+
+    let sql = "SELECT i, (SELECT max(i) FROM t) AS m FROM t ORDER BY i";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+
+    let expected = vec![
+        "+---+---+",
+        "| i | m |",
+        "+---+---+",
+        "| 1 | 2 |",
+        "| 2 | 2 |",
+        "+---+---+",
+    ];
+    let results = collect(plan.clone(), ctx.task_ctx()).await?;
+    assert_batches_eq!(expected, &results);
+
+    // the file changes between the executions of the same plan, of the same size
+    std::fs::write(&path, "i\n3\n4\n")?;
+    let expected = vec![
+        "+---+---+",
+        "| i | m |",
+        "+---+---+",
+        "| 3 | 4 |",
+        "| 4 | 4 |",
+        "+---+---+",
+    ];
+    let results = collect(plan, ctx.task_ctx()).await?;
+    assert_batches_eq!(expected, &results);
+    Ok(())
+}
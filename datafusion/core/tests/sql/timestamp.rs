@@ -523,6 +523,21 @@ async fn test_now_in_view() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_now_in_view_and_statement() -> Result<()> {
+    let ctx = SessionContext::new();
+    ctx.sql("create view test_now as select now() as t")
+        .await?
+        .collect()
+        .await?;
+
+    let df = ctx.sql("select t = now() as same from test_now").await?;
+    let expected = vec!["+------+", "| same |", "+------+", "| true |", "+------+"];
+    assert_batches_eq!(expected, &df.collect().await?);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn timestamp_minmax() -> Result<()> {
     let ctx = SessionContext::new();
@@ -1619,3 +1634,47 @@ async fn test_cast_to_timetz_should_not_work() -> Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn set_time_zone() -> Result<()> {
+    let ctx = SessionContext::new();
+    let table_a = make_timestamp_table::<TimestampNanosecondType>()?;
+    ctx.register_table("table_a", table_a)?;
+    ctx.sql("SET TIME ZONE 'Asia/Tokyo'").await?;
+
+    // timestamps without a time zone are local times of the session, and
+    // timestamps with one are truncated at its midnights
+    let sql =
+        "SELECT ts::timestamptz AS instant, date_trunc('day', ts::timestamptz) AS day \
+               FROM table_a";
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+----------------------------+---------------------+",
+        "| instant                    | day                 |",
+        "+----------------------------+---------------------+",
+        "| 2020-09-08 04:42:29.190855 | 2020-09-07 15:00:00 |",
+        "| 2020-09-08 03:42:29.190855 | 2020-09-07 15:00:00 |",
+        "| 2020-09-08 02:42:29.190855 | 2020-09-07 15:00:00 |",
+        "+----------------------------+---------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT arrow_typeof(current_date) AS d, arrow_typeof(current_time()) AS t";
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+--------+--------------------+",
+        "| d      | t                  |",
+        "+--------+--------------------+",
+        "| Date32 | Time64(Nanosecond) |",
+        "+--------+--------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // invalid time zones are rejected, keeping the time zone of the session
+    let err = ctx.sql("SET TIME ZONE 'Mars/Olympus'").await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Invalid time zone 'Mars/Olympus', expected a name"));
+    assert_eq!(ctx.copied_config().time_zone()?.to_string(), "Asia/Tokyo");
+    Ok(())
+}
@@ -451,6 +451,24 @@ async fn window_in_expression() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn window_lag_ignore_nulls() -> Result<()> {
+    let ctx = SessionContext::new();
+    let sql = "select idx, lag(amount) ignore nulls over (order by idx) as prev, lag(amount) respect nulls over (order by idx) as prev_or_null from (values (1, 100), (2, null), (3, 300)) as t (idx, amount)";
+    let actual = execute_to_batches(&ctx, sql).await;
+    let expected = vec![
+        "+-----+------+--------------+",
+        "| idx | prev | prev_or_null |",
+        "+-----+------+--------------+",
+        "| 1   |      |              |",
+        "| 2   | 100  | 100          |",
+        "| 3   | 100  |              |",
+        "+-----+------+--------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn window_with_agg_in_expression() -> Result<()> {
     let ctx = SessionContext::new();
@@ -21,9 +21,10 @@ use crate::{type_coercion::aggregates::*, Signature, TypeSignature, Volatility};
 use arrow::datatypes::{DataType, Field};
 use datafusion_common::{DataFusionError, Result};
 use std::{fmt, str::FromStr};
+use strum_macros::EnumIter;
 
 /// Enum of all built-in aggregate functions
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash, EnumIter)]
 pub enum AggregateFunction {
     /// count
     Count,
@@ -65,6 +66,35 @@ pub enum AggregateFunction {
     Grouping,
 }
 
+impl AggregateFunction {
+    /// Returns the name of the function in SQL
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Median => "median",
+            AggregateFunction::ApproxDistinct => "approx_distinct",
+            AggregateFunction::ArrayAgg => "array_agg",
+            AggregateFunction::Variance => "var",
+            AggregateFunction::VariancePop => "var_pop",
+            AggregateFunction::Stddev => "stddev",
+            AggregateFunction::StddevPop => "stddev_pop",
+            AggregateFunction::Covariance => "covar",
+            AggregateFunction::CovariancePop => "covar_pop",
+            AggregateFunction::Correlation => "corr",
+            AggregateFunction::ApproxPercentileCont => "approx_percentile_cont",
+            AggregateFunction::ApproxPercentileContWithWeight => {
+                "approx_percentile_cont_with_weight"
+            }
+            AggregateFunction::ApproxMedian => "approx_median",
+            AggregateFunction::Grouping => "grouping",
+        }
+    }
+}
+
 impl fmt::Display for AggregateFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // uppercase of the debug.
@@ -223,3 +253,16 @@ pub fn signature(fun: &AggregateFunction) -> Signature {
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn names_parse_back() {
+        for fun in AggregateFunction::iter() {
+            assert_eq!(AggregateFunction::from_str(fun.name()).unwrap(), fun);
+        }
+    }
+}
@@ -21,9 +21,10 @@ use crate::Volatility;
 use datafusion_common::{DataFusionError, Result};
 use std::fmt;
 use std::str::FromStr;
+use strum_macros::EnumIter;
 
 /// Enum of all built-in scalar functions
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumIter)]
 pub enum BuiltinScalarFunction {
     // math functions
     /// abs
@@ -156,6 +157,10 @@ pub enum BuiltinScalarFunction {
     FromUnixtime,
     ///now
     Now,
+    /// current_date
+    CurrentDate,
+    /// current_time
+    CurrentTime,
     /// translate
     Translate,
     /// trim
@@ -168,15 +173,99 @@ pub enum BuiltinScalarFunction {
     Struct,
     /// arrow_typeof
     ArrowTypeof,
+    /// zorder
+    ZOrder,
 }
 
 impl BuiltinScalarFunction {
+    /// Returns the name of the function in SQL
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinScalarFunction::Abs => "abs",
+            BuiltinScalarFunction::Acos => "acos",
+            BuiltinScalarFunction::Asin => "asin",
+            BuiltinScalarFunction::Atan => "atan",
+            BuiltinScalarFunction::Atan2 => "atan2",
+            BuiltinScalarFunction::Ceil => "ceil",
+            BuiltinScalarFunction::Coalesce => "coalesce",
+            BuiltinScalarFunction::Cos => "cos",
+            BuiltinScalarFunction::Digest => "digest",
+            BuiltinScalarFunction::Exp => "exp",
+            BuiltinScalarFunction::Floor => "floor",
+            BuiltinScalarFunction::Ln => "ln",
+            BuiltinScalarFunction::Log => "log",
+            BuiltinScalarFunction::Log10 => "log10",
+            BuiltinScalarFunction::Log2 => "log2",
+            BuiltinScalarFunction::Power => "power",
+            BuiltinScalarFunction::Round => "round",
+            BuiltinScalarFunction::Signum => "signum",
+            BuiltinScalarFunction::Sin => "sin",
+            BuiltinScalarFunction::Sqrt => "sqrt",
+            BuiltinScalarFunction::Tan => "tan",
+            BuiltinScalarFunction::Trunc => "trunc",
+            BuiltinScalarFunction::MakeArray => "make_array",
+            BuiltinScalarFunction::Ascii => "ascii",
+            BuiltinScalarFunction::BitLength => "bit_length",
+            BuiltinScalarFunction::Btrim => "btrim",
+            BuiltinScalarFunction::CharacterLength => "character_length",
+            BuiltinScalarFunction::Chr => "chr",
+            BuiltinScalarFunction::Concat => "concat",
+            BuiltinScalarFunction::ConcatWithSeparator => "concat_ws",
+            BuiltinScalarFunction::DatePart => "date_part",
+            BuiltinScalarFunction::DateTrunc => "date_trunc",
+            BuiltinScalarFunction::DateBin => "date_bin",
+            BuiltinScalarFunction::InitCap => "initcap",
+            BuiltinScalarFunction::Left => "left",
+            BuiltinScalarFunction::Lpad => "lpad",
+            BuiltinScalarFunction::Lower => "lower",
+            BuiltinScalarFunction::Ltrim => "ltrim",
+            BuiltinScalarFunction::MD5 => "md5",
+            BuiltinScalarFunction::NullIf => "nullif",
+            BuiltinScalarFunction::OctetLength => "octet_length",
+            BuiltinScalarFunction::Random => "random",
+            BuiltinScalarFunction::RegexpReplace => "regexp_replace",
+            BuiltinScalarFunction::Repeat => "repeat",
+            BuiltinScalarFunction::Replace => "replace",
+            BuiltinScalarFunction::Reverse => "reverse",
+            BuiltinScalarFunction::Right => "right",
+            BuiltinScalarFunction::Rpad => "rpad",
+            BuiltinScalarFunction::Rtrim => "rtrim",
+            BuiltinScalarFunction::SHA224 => "sha224",
+            BuiltinScalarFunction::SHA256 => "sha256",
+            BuiltinScalarFunction::SHA384 => "sha384",
+            BuiltinScalarFunction::SHA512 => "sha512",
+            BuiltinScalarFunction::SplitPart => "split_part",
+            BuiltinScalarFunction::StartsWith => "starts_with",
+            BuiltinScalarFunction::Strpos => "strpos",
+            BuiltinScalarFunction::Substr => "substr",
+            BuiltinScalarFunction::ToHex => "to_hex",
+            BuiltinScalarFunction::ToTimestamp => "to_timestamp",
+            BuiltinScalarFunction::ToTimestampMillis => "to_timestamp_millis",
+            BuiltinScalarFunction::ToTimestampMicros => "to_timestamp_micros",
+            BuiltinScalarFunction::ToTimestampSeconds => "to_timestamp_seconds",
+            BuiltinScalarFunction::FromUnixtime => "from_unixtime",
+            BuiltinScalarFunction::Now => "now",
+            BuiltinScalarFunction::CurrentDate => "current_date",
+            BuiltinScalarFunction::CurrentTime => "current_time",
+            BuiltinScalarFunction::Translate => "translate",
+            BuiltinScalarFunction::Trim => "trim",
+            BuiltinScalarFunction::Upper => "upper",
+            BuiltinScalarFunction::RegexpMatch => "regexp_match",
+            BuiltinScalarFunction::Struct => "struct",
+            BuiltinScalarFunction::ArrowTypeof => "arrow_typeof",
+            BuiltinScalarFunction::ZOrder => "zorder",
+        }
+    }
+
     /// an allowlist of functions to take zero arguments, so that they will get special treatment
     /// while executing.
     pub fn supports_zero_argument(&self) -> bool {
         matches!(
             self,
-            BuiltinScalarFunction::Random | BuiltinScalarFunction::Now
+            BuiltinScalarFunction::Random
+                | BuiltinScalarFunction::Now
+                | BuiltinScalarFunction::CurrentDate
+                | BuiltinScalarFunction::CurrentTime
         )
     }
     /// Returns the [Volatility] of the builtin function.
@@ -251,9 +340,12 @@ impl BuiltinScalarFunction {
             BuiltinScalarFunction::Struct => Volatility::Immutable,
             BuiltinScalarFunction::FromUnixtime => Volatility::Immutable,
             BuiltinScalarFunction::ArrowTypeof => Volatility::Immutable,
+            BuiltinScalarFunction::ZOrder => Volatility::Immutable,
 
             // Stable builtin functions
             BuiltinScalarFunction::Now => Volatility::Stable,
+            BuiltinScalarFunction::CurrentDate => Volatility::Stable,
+            BuiltinScalarFunction::CurrentTime => Volatility::Stable,
 
             // Volatile builtin functions
             BuiltinScalarFunction::Random => Volatility::Volatile,
@@ -344,6 +436,8 @@ impl FromStr for BuiltinScalarFunction {
             "to_timestamp_micros" => BuiltinScalarFunction::ToTimestampMicros,
             "to_timestamp_seconds" => BuiltinScalarFunction::ToTimestampSeconds,
             "now" => BuiltinScalarFunction::Now,
+            "current_date" => BuiltinScalarFunction::CurrentDate,
+            "current_time" => BuiltinScalarFunction::CurrentTime,
             "translate" => BuiltinScalarFunction::Translate,
             "trim" => BuiltinScalarFunction::Trim,
             "upper" => BuiltinScalarFunction::Upper,
@@ -351,6 +445,7 @@ impl FromStr for BuiltinScalarFunction {
             "struct" => BuiltinScalarFunction::Struct,
             "from_unixtime" => BuiltinScalarFunction::FromUnixtime,
             "arrow_typeof" => BuiltinScalarFunction::ArrowTypeof,
+            "zorder" => BuiltinScalarFunction::ZOrder,
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
@@ -360,3 +455,16 @@ impl FromStr for BuiltinScalarFunction {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn names_parse_back() {
+        for fun in BuiltinScalarFunction::iter() {
+            assert_eq!(BuiltinScalarFunction::from_str(fun.name()).unwrap(), fun);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+//! Values in the memory of accelerators, such as GPUs, that can be used
+//! where a [`ColumnarValue`] is expected after a copy to host memory
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow::array::ArrayRef;
+use arrow::datatypes::DataType;
+use datafusion_common::Result;
+
+use crate::ColumnarValue;
+
+/// An array in the memory of a device, such as a CUDA or Metal buffer,
+/// implemented by the backend of the device
+pub trait DeviceArray: Debug + Send + Sync {
+    /// Returns the array as [`Any`] so that it can be downcast to the array
+    /// type of its backend
+    fn as_any(&self) -> &dyn Any;
+
+    /// The name of the device holding the array, such as `cuda:0`
+    fn device(&self) -> &str;
+
+    /// The data type of the array
+    fn data_type(&self) -> &DataType;
+
+    /// The number of values of the array
+    fn len(&self) -> usize;
+
+    /// Returns true if the array has no values
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies the array to host memory
+    fn to_host(&self) -> Result<ArrayRef>;
+}
+
+/// The result of evaluating an expression on a device: either a
+/// [`ColumnarValue`] in host memory or a [`DeviceArray`]
+///
+/// Operators of a backend pass device values between them without copying
+/// them, and convert them to [`ColumnarValue`] at the boundaries with the
+/// other operators.
+#[derive(Clone, Debug)]
+pub enum DeviceValue {
+    /// A value in host memory
+    Host(ColumnarValue),
+    /// An array in the memory of a device
+    Device(Arc<dyn DeviceArray>),
+}
+
+impl DeviceValue {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            DeviceValue::Host(value) => value.data_type(),
+            DeviceValue::Device(array) => array.data_type().clone(),
+        }
+    }
+
+    /// The name of the device holding the value, or `None` for values in
+    /// host memory
+    pub fn device(&self) -> Option<&str> {
+        match self {
+            DeviceValue::Host(_) => None,
+            DeviceValue::Device(array) => Some(array.device()),
+        }
+    }
+
+    /// Convert a device value into a [`ColumnarValue`], copying
+    /// [`Self::Device`] arrays to host memory
+    pub fn into_columnar_value(self) -> Result<ColumnarValue> {
+        match self {
+            DeviceValue::Host(value) => Ok(value),
+            DeviceValue::Device(array) => Ok(ColumnarValue::Array(array.to_host()?)),
+        }
+    }
+
+    /// Convert a device value into an ArrayRef in host memory, see
+    /// [`ColumnarValue::into_array`]
+    pub fn into_array(self, num_rows: usize) -> Result<ArrayRef> {
+        Ok(self.into_columnar_value()?.into_array(num_rows))
+    }
+}
+
+impl From<ColumnarValue> for DeviceValue {
+    fn from(value: ColumnarValue) -> Self {
+        DeviceValue::Host(value)
+    }
+}
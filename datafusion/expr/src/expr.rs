@@ -198,6 +198,8 @@ pub enum Expr {
         order_by: Vec<Expr>,
         /// Window frame
         window_frame: Option<window_frame::WindowFrame>,
+        /// Whether null input values are skipped (`IGNORE NULLS`)
+        ignore_nulls: bool,
     },
     /// aggregate function
     AggregateUDF {
@@ -242,6 +244,14 @@ pub enum Expr {
     /// List of grouping set expressions. Only valid in the context of an aggregate
     /// GROUP BY expression list
     GroupingSet(GroupingSet),
+    /// A place holder for a value of type `data_type`, bound to a literal with
+    /// `LogicalPlan::with_param_values`, such as `$min_ts`
+    Placeholder {
+        /// The name of the parameter, without the leading `$`
+        id: String,
+        /// The type of the value
+        data_type: DataType,
+    },
 }
 
 /// Binary expression
@@ -526,6 +536,7 @@ impl Expr {
             Expr::Literal(..) => "Literal",
             Expr::Negative(..) => "Negative",
             Expr::Not(..) => "Not",
+            Expr::Placeholder { .. } => "Placeholder",
             Expr::QualifiedWildcard { .. } => "QualifiedWildcard",
             Expr::ScalarFunction { .. } => "ScalarFunction",
             Expr::ScalarSubquery { .. } => "ScalarSubquery",
@@ -732,6 +743,7 @@ impl fmt::Debug for Expr {
             Expr::Column(c) => write!(f, "{}", c),
             Expr::ScalarVariable(_, var_names) => write!(f, "{}", var_names.join(".")),
             Expr::Literal(v) => write!(f, "{:?}", v),
+            Expr::Placeholder { id, .. } => write!(f, "${}", id),
             Expr::Case(case) => {
                 write!(f, "CASE ")?;
                 if let Some(e) = &case.expr {
@@ -809,8 +821,12 @@ impl fmt::Debug for Expr {
                 partition_by,
                 order_by,
                 window_frame,
+                ignore_nulls,
             } => {
                 fmt_function(f, &fun.to_string(), false, args, false)?;
+                if *ignore_nulls {
+                    write!(f, " IGNORE NULLS")?;
+                }
                 if !partition_by.is_empty() {
                     write!(f, " PARTITION BY {:?}", partition_by)?;
                 }
@@ -1015,6 +1031,7 @@ fn create_name(e: &Expr) -> Result<String> {
         Expr::Column(c) => Ok(c.flat_name()),
         Expr::ScalarVariable(_, variable_names) => Ok(variable_names.join(".")),
         Expr::Literal(value) => Ok(format!("{:?}", value)),
+        Expr::Placeholder { id, .. } => Ok(format!("${}", id)),
         Expr::BinaryExpr(binary_expr) => {
             let left = create_name(binary_expr.left.as_ref())?;
             let right = create_name(binary_expr.right.as_ref())?;
@@ -1168,9 +1185,13 @@ fn create_name(e: &Expr) -> Result<String> {
             window_frame,
             partition_by,
             order_by,
+            ignore_nulls,
         } => {
             let mut parts: Vec<String> =
                 vec![create_function_name(&fun.to_string(), false, args)?];
+            if *ignore_nulls {
+                parts.push("IGNORE NULLS".to_string());
+            }
             if !partition_by.is_empty() {
                 parts.push(format!("PARTITION BY {:?}", partition_by));
             }
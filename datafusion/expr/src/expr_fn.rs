@@ -257,6 +257,15 @@ pub fn rollup(exprs: Vec<Expr>) -> Expr {
     Expr::GroupingSet(GroupingSet::Rollup(exprs))
 }
 
+/// Create a placeholder `$id` of type `data_type`, to be replaced by a
+/// literal value when the plan is bound with parameter values
+pub fn placeholder(id: impl Into<String>, data_type: DataType) -> Expr {
+    Expr::Placeholder {
+        id: id.into(),
+        data_type,
+    }
+}
+
 /// Create a cast expression
 pub fn cast(expr: Expr, data_type: DataType) -> Expr {
     Expr::Cast(Cast::new(Box::new(expr), data_type))
@@ -414,6 +423,7 @@ nary_scalar_expr!(Btrim, btrim);
 //there is a func concat_ws before, so use concat_ws_expr as name.c
 nary_scalar_expr!(ConcatWithSeparator, concat_ws_expr);
 nary_scalar_expr!(Concat, concat_expr);
+nary_scalar_expr!(ZOrder, zorder);
 
 // date functions
 scalar_expr!(DatePart, date_part, part, date);
@@ -452,6 +462,24 @@ pub fn now() -> Expr {
     }
 }
 
+/// Returns the current date in the time zone of the session, using the same value for all
+/// instances of current_date() in same statement.
+pub fn current_date() -> Expr {
+    Expr::ScalarFunction {
+        fun: BuiltinScalarFunction::CurrentDate,
+        args: vec![],
+    }
+}
+
+/// Returns the current time of day in the time zone of the session, using the same value for
+/// all instances of current_time() in same statement.
+pub fn current_time() -> Expr {
+    Expr::ScalarFunction {
+        fun: BuiltinScalarFunction::CurrentTime,
+        args: vec![],
+    }
+}
+
 /// Create a CASE WHEN statement with literal WHEN expressions for comparison to the base expression.
 pub fn case(expr: Expr) -> CaseBuilder {
     CaseBuilder::new(Some(Box::new(expr)), vec![], vec![], None)
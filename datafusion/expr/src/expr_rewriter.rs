@@ -123,9 +123,51 @@ impl ExprRewritable for Expr {
             Expr::ScalarSubquery(_) => self.clone(),
             Expr::ScalarVariable(ty, names) => Expr::ScalarVariable(ty, names),
             Expr::Literal(value) => Expr::Literal(value),
+            Expr::Placeholder { id, data_type } => Expr::Placeholder { id, data_type },
             Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                // the binary expressions along the left operands, such as the
+                // chain of `a OR b OR c ...`, are rewritten without recursion
+                let mut chain = vec![];
+                let mut current = *left;
+                let left = loop {
+                    let recursion = if matches!(current, Expr::BinaryExpr(_)) {
+                        rewriter.pre_visit(&current)?
+                    } else {
+                        break current.rewrite(rewriter)?;
+                    };
+                    match (recursion, current) {
+                        (RewriteRecursion::Mutate, expr) => {
+                            break rewriter.mutate(expr)?
+                        }
+                        (RewriteRecursion::Stop, expr) => break expr,
+                        (recursion, Expr::BinaryExpr(BinaryExpr { left, op, right })) => {
+                            let need_mutate =
+                                matches!(recursion, RewriteRecursion::Continue);
+                            chain.push((need_mutate, op, right));
+                            current = *left;
+                        }
+                        (_, expr) => {
+                            unreachable!("{:?} is a binary expression", expr)
+                        }
+                    }
+                };
+                let left = chain.into_iter().rev().try_fold(
+                    left,
+                    |left, (need_mutate, op, right)| {
+                        let expr = Expr::BinaryExpr(BinaryExpr::new(
+                            Box::new(left),
+                            op,
+                            rewrite_boxed(right, rewriter)?,
+                        ));
+                        if need_mutate {
+                            rewriter.mutate(expr)
+                        } else {
+                            Ok(expr)
+                        }
+                    },
+                )?;
                 Expr::BinaryExpr(BinaryExpr::new(
-                    rewrite_boxed(left, rewriter)?,
+                    Box::new(left),
                     op,
                     rewrite_boxed(right, rewriter)?,
                 ))
@@ -233,12 +275,14 @@ impl ExprRewritable for Expr {
                 partition_by,
                 order_by,
                 window_frame,
+                ignore_nulls,
             } => Expr::WindowFunction {
                 args: rewrite_vec(args, rewriter)?,
                 fun,
                 partition_by: rewrite_vec(partition_by, rewriter)?,
                 order_by: rewrite_vec(order_by, rewriter)?,
                 window_frame,
+                ignore_nulls,
             },
             Expr::AggregateFunction {
                 args,
@@ -709,4 +753,28 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn rewriter_visit_chain() {
+        let mut rewriter = RecordingRewriter::default();
+        (col("a") + col("b") + col("c"))
+            .rewrite(&mut rewriter)
+            .unwrap();
+
+        assert_eq!(
+            rewriter.v,
+            vec![
+                "Previsited a + b + c",
+                "Previsited a + b",
+                "Previsited a",
+                "Mutated a",
+                "Previsited b",
+                "Mutated b",
+                "Mutated a + b",
+                "Previsited c",
+                "Mutated c",
+                "Mutated a + b + c",
+            ]
+        )
+    }
 }
@@ -59,6 +59,7 @@ impl ExprSchemable for Expr {
             }
             Expr::Column(c) => Ok(schema.data_type(c)?.clone()),
             Expr::ScalarVariable(ty, _) => Ok(ty.clone()),
+            Expr::Placeholder { data_type, .. } => Ok(data_type.clone()),
             Expr::Literal(l) => Ok(l.get_datatype()),
             Expr::Case(case) => case.when_then_expr[0].1.get_type(schema),
             Expr::Cast(Cast { data_type, .. }) | Expr::TryCast { data_type, .. } => {
@@ -184,6 +185,7 @@ impl ExprSchemable for Expr {
             }
             Expr::Cast(Cast { expr, .. }) => expr.nullable(input_schema),
             Expr::ScalarVariable(_, _)
+            | Expr::Placeholder { .. }
             | Expr::TryCast { .. }
             | Expr::ScalarFunction { .. }
             | Expr::ScalarUDF { .. }
@@ -130,12 +130,42 @@ impl ExprVisitable for Expr {
             Expr::Column(_)
             | Expr::ScalarVariable(_, _)
             | Expr::Literal(_)
+            | Expr::Placeholder { .. }
             | Expr::Exists { .. }
             | Expr::ScalarSubquery(_)
             | Expr::Wildcard
             | Expr::QualifiedWildcard { .. } => Ok(visitor),
             Expr::BinaryExpr(BinaryExpr { left, right, .. }) => {
-                let visitor = left.accept(visitor)?;
+                // the binary expressions along the left operands, such as the
+                // chain of `a OR b OR c ...`, are visited without recursion
+                let mut chain = vec![];
+                let mut current = left.as_ref();
+                let mut visitor = visitor;
+                loop {
+                    match current {
+                        Expr::BinaryExpr(BinaryExpr { left, right, .. }) => {
+                            match visitor.pre_visit(current)? {
+                                Recursion::Continue(v) => {
+                                    visitor = v;
+                                    chain.push((current, right));
+                                    current = left.as_ref();
+                                }
+                                Recursion::Stop(v) => {
+                                    visitor = v;
+                                    break;
+                                }
+                            }
+                        }
+                        _ => {
+                            visitor = current.accept(visitor)?;
+                            break;
+                        }
+                    }
+                }
+                for (expr, right) in chain.into_iter().rev() {
+                    visitor = right.accept(visitor)?;
+                    visitor = visitor.post_visit(expr)?;
+                }
                 right.accept(visitor)
             }
             Expr::Like(Like { expr, pattern, .. }) => {
@@ -31,6 +31,7 @@ pub mod array_expressions;
 mod built_in_function;
 mod columnar_value;
 pub mod conditional_expressions;
+mod device_value;
 pub mod expr;
 pub mod expr_fn;
 pub mod expr_rewriter;
@@ -56,6 +57,7 @@ pub use accumulator::{Accumulator, AggregateState};
 pub use aggregate_function::AggregateFunction;
 pub use built_in_function::BuiltinScalarFunction;
 pub use columnar_value::{ColumnarValue, NullColumnarValue};
+pub use device_value::{DeviceArray, DeviceValue};
 pub use expr::{
     Between, BinaryExpr, Case, Cast, Expr, GetIndexedField, GroupingSet, Like,
 };
@@ -68,18 +70,21 @@ pub use function::{
 pub use literal::{lit, lit_timestamp_nano, Literal, TimestampLiteral};
 pub use logical_plan::{
     builder::{build_join_schema, union_with_alias, UNNAMED_TABLE},
-    Aggregate, CreateCatalog, CreateCatalogSchema, CreateExternalTable,
-    CreateMemoryTable, CreateView, CrossJoin, Distinct, DropTable, DropView,
-    EmptyRelation, Explain, Extension, Filter, Join, JoinConstraint, JoinType, Limit,
-    LogicalPlan, LogicalPlanBuilder, Partitioning, PlanType, PlanVisitor, Projection,
-    Repartition, Sort, StringifiedPlan, Subquery, SubqueryAlias, TableScan,
-    ToStringifiedPlan, Union, UserDefinedLogicalNode, Values, Window,
+    is_secret_option, Aggregate, AnalyzeTable, CreateCatalog, CreateCatalogSchema,
+    CreateExternalTable, CreateMemoryTable, CreateView, CrossJoin, Dedup, DedupKeep,
+    Distinct, DropTable, DropView, EmptyRelation, Explain, Extension, Filter, Join,
+    JoinConstraint, JoinType, Limit, LogicalPlan, LogicalPlanBuilder, Partitioning,
+    PlanType, PlanVisitor, Prepare, Projection, RefreshMaterializedView, Repartition,
+    SetVariable, Sort, StringifiedPlan, Subquery, SubqueryAlias, TableScan,
+    ToStringifiedPlan, Union, Unnest, UserDefinedLogicalNode, Values, Window,
 };
 pub use nullif::SUPPORTED_NULLIF_TYPES;
 pub use operator::Operator;
 pub use signature::{Signature, TypeSignature, Volatility};
-pub use table_source::{TableProviderFilterPushDown, TableSource, TableType};
+pub use table_source::{
+    TablePolicy, TableProviderFilterPushDown, TableSource, TableType,
+};
 pub use udaf::AggregateUDF;
-pub use udf::ScalarUDF;
+pub use udf::{ScalarUDF, WasmUdfBody};
 pub use window_frame::{WindowFrame, WindowFrameBound, WindowFrameUnits};
 pub use window_function::{BuiltInWindowFunction, WindowFunction};
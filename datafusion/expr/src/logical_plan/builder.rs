@@ -25,21 +25,22 @@ use crate::utils::{columnize_expr, exprlist_to_fields, from_plan};
 use crate::{and, binary_expr, Operator};
 use crate::{
     logical_plan::{
-        Aggregate, Analyze, CrossJoin, Distinct, EmptyRelation, Explain, Filter, Join,
-        JoinConstraint, JoinType, Limit, LogicalPlan, Partitioning, PlanType, Projection,
-        Repartition, Sort, SubqueryAlias, TableScan, ToStringifiedPlan, Union, Values,
-        Window,
+        Aggregate, Analyze, CrossJoin, Dedup, DedupKeep, Distinct, EmptyRelation,
+        Explain, Filter, Join, JoinConstraint, JoinType, Limit, LogicalPlan,
+        Partitioning, PlanType, Projection, Repartition, Sort, SubqueryAlias, TableScan,
+        ToStringifiedPlan, Union, Unnest, Values, Window,
     },
     utils::{
         can_hash, expand_qualified_wildcard, expand_wildcard, expr_to_columns,
         group_window_expr_by_sort_keys,
     },
+    window_function::{BuiltInWindowFunction, WindowFunction},
     Expr, ExprSchemable, TableSource,
 };
 use arrow::datatypes::{DataType, Schema, SchemaRef};
 use datafusion_common::{
-    Column, DFField, DFSchema, DFSchemaRef, DataFusionError, Result, ScalarValue,
-    ToDFSchema,
+    Column, Constraints, DFField, DFSchema, DFSchemaRef, DataFusionError, Result,
+    ScalarValue, ToDFSchema,
 };
 use std::any::Any;
 use std::convert::TryFrom;
@@ -51,6 +52,10 @@ use std::{
 /// Default table name for unnamed table
 pub const UNNAMED_TABLE: &str = "?table?";
 
+/// Name of the row number column computed by
+/// [`LogicalPlanBuilder::distinct_on`]
+const DISTINCT_ON_ROW_NUMBER: &str = "__distinct_on_row_number";
+
 /// Builder for logical plans
 ///
 /// ```
@@ -237,6 +242,7 @@ impl LogicalPlanBuilder {
             projected_schema: Arc::new(projected_schema),
             projection,
             filters,
+            ordering: vec![],
             fetch: None,
         });
         Ok(Self::from(table_scan))
@@ -449,6 +455,66 @@ impl LogicalPlanBuilder {
         })))
     }
 
+    /// Keep the first row, by the sort expressions `sort_expr`, of each group
+    /// of rows with the same values of `on_expr`, and project it on
+    /// `select_expr`, like `SELECT DISTINCT ON (on_expr) select_expr ...
+    /// ORDER BY sort_expr`. Which row of a group is kept is unspecified
+    /// without `sort_expr`.
+    pub fn distinct_on(
+        &self,
+        on_expr: Vec<Expr>,
+        select_expr: Vec<Expr>,
+        sort_expr: Option<Vec<Expr>>,
+    ) -> Result<Self> {
+        if on_expr.is_empty() {
+            return Err(DataFusionError::Plan(
+                "DISTINCT ON requires at least one expression".to_string(),
+            ));
+        }
+        let row_number = Expr::WindowFunction {
+            fun: WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+            args: vec![],
+            partition_by: normalize_cols(on_expr, &self.plan)?,
+            order_by: normalize_cols(sort_expr.unwrap_or_default(), &self.plan)?,
+            window_frame: None,
+            ignore_nulls: false,
+        }
+        .alias(DISTINCT_ON_ROW_NUMBER);
+        self.window(vec![row_number])?
+            .filter(
+                Expr::Column(Column::from_name(DISTINCT_ON_ROW_NUMBER))
+                    .eq(Expr::Literal(ScalarValue::UInt64(Some(1)))),
+            )?
+            .project(select_expr)
+    }
+
+    /// Keep a single row of each group of rows with the same values of `on`:
+    /// the first or the last one, as chosen by `keep`, in the order of the
+    /// sort expressions `order_by`, or in the order of the input without them
+    pub fn dedup(
+        &self,
+        on: Vec<Expr>,
+        keep: DedupKeep,
+        order_by: Vec<Expr>,
+    ) -> Result<Self> {
+        Ok(Self::from(LogicalPlan::Dedup(Dedup::try_new(
+            Arc::new(self.plan.clone()),
+            normalize_cols(on, &self.plan)?,
+            keep,
+            normalize_cols(order_by, &self.plan)?,
+        )?)))
+    }
+
+    /// Unnest the list column `column`, producing a row for each element of
+    /// its lists
+    pub fn unnest_column(&self, column: impl Into<Column>) -> Result<Self> {
+        let column = Self::normalize(&self.plan, column.into())?;
+        Ok(Self::from(LogicalPlan::Unnest(Unnest::try_new(
+            Arc::new(self.plan.clone()),
+            column,
+        )?)))
+    }
+
     /// Apply a join with on constraint.
     ///
     /// Filter expression expected to contain non-equality predicates that can not be pushed
@@ -1014,7 +1080,7 @@ pub fn table_scan(
     projection: Option<Vec<usize>>,
 ) -> Result<LogicalPlanBuilder> {
     let table_schema = Arc::new(table_schema.clone());
-    let table_source = Arc::new(LogicalTableSource { table_schema });
+    let table_source = Arc::new(LogicalTableSource::new(table_schema));
     LogicalPlanBuilder::scan(name.unwrap_or(UNNAMED_TABLE), table_source, projection)
 }
 
@@ -1023,12 +1089,22 @@ pub fn table_scan(
 /// DefaultTableSource.
 pub struct LogicalTableSource {
     table_schema: SchemaRef,
+    constraints: Constraints,
 }
 
 impl LogicalTableSource {
     /// Create a new LogicalTableSource
     pub fn new(table_schema: SchemaRef) -> Self {
-        Self { table_schema }
+        Self {
+            table_schema,
+            constraints: Constraints::empty(),
+        }
+    }
+
+    /// Specify the constraints declared on the table
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
     }
 }
 
@@ -1040,6 +1116,10 @@ impl TableSource for LogicalTableSource {
     fn schema(&self) -> SchemaRef {
         self.table_schema.clone()
     }
+
+    fn constraints(&self) -> Option<&Constraints> {
+        Some(&self.constraints)
+    }
 }
 
 #[cfg(test)]
@@ -1433,4 +1513,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn plan_builder_distinct_on() -> Result<()> {
+        let plan = table_scan(
+            Some("employee_csv"),
+            &employee_schema(),
+            Some(vec![0, 3, 4]),
+        )?
+        .distinct_on(
+            vec![col("state")],
+            vec![col("id"), col("state")],
+            Some(vec![col("salary").sort(false, true)]),
+        )?
+        .build()?;
+
+        let expected = "Projection: employee_csv.id, employee_csv.state\
+        \n  Filter: __distinct_on_row_number = UInt64(1)\
+        \n    WindowAggr: windowExpr=[[ROW_NUMBER() PARTITION BY [employee_csv.state] ORDER BY [employee_csv.salary DESC NULLS FIRST] AS __distinct_on_row_number]]\
+        \n      TableScan: employee_csv projection=[id, state, salary]";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let err = table_scan(Some("employee_csv"), &employee_schema(), None)?
+            .distinct_on(vec![], vec![col("id")], None)
+            .unwrap_err();
+        assert_eq!(
+            "Error during planning: DISTINCT ON requires at least one expression",
+            err.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_dedup() -> Result<()> {
+        let plan = table_scan(Some("employee_csv"), &employee_schema(), None)?
+            .dedup(
+                vec![col("state")],
+                DedupKeep::Last,
+                vec![col("salary").sort(true, false)],
+            )?
+            .build()?;
+
+        let expected = "Dedup: on=[[employee_csv.state]], keep=Last, orderBy=[[employee_csv.salary ASC NULLS LAST]]\
+        \n  TableScan: employee_csv";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let err = table_scan(Some("employee_csv"), &employee_schema(), None)?
+            .dedup(vec![col("state")], DedupKeep::First, vec![col("salary")])
+            .unwrap_err();
+        assert_eq!(
+            "Error during planning: Dedup can only be ordered by sort expressions, got employee_csv.salary",
+            err.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plan_builder_unnest() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "tags",
+                DataType::List(Box::new(Field::new("item", DataType::Utf8, true))),
+                false,
+            ),
+        ]);
+        let plan = table_scan(Some("test"), &schema, None)?
+            .unnest_column("tags")?
+            .build()?;
+
+        let expected = "Unnest: test.tags\
+        \n  TableScan: test";
+        assert_eq!(expected, format!("{:?}", plan));
+
+        let field = plan.schema().field(1);
+        assert_eq!(field.qualified_name(), "test.tags");
+        assert_eq!(field.data_type(), &DataType::Utf8);
+        assert!(field.is_nullable());
+
+        let err = table_scan(Some("test"), &schema, None)?
+            .unnest_column("id")
+            .unwrap_err();
+        assert_eq!(
+            "Error during planning: Unnest requires a list column, test.id has type Int32",
+            err.to_string()
+        );
+        Ok(())
+    }
 }
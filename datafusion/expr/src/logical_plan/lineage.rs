@@ -0,0 +1,373 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Column level lineage of logical plans
+
+use crate::logical_plan::{
+    Aggregate, Join, JoinType, LogicalPlan, PlanVisitor, Projection, TableScan, Window,
+};
+use crate::utils::{expr_to_columns, grouping_set_to_exprlist};
+use crate::Expr;
+use datafusion_common::{Column, DFField, DFSchema, DataFusionError, Result};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+/// A column of a table read by a plan
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceColumn {
+    /// The name of the table, as in its [`TableScan`]
+    pub table: String,
+    /// The name of the column in the table
+    pub column: String,
+}
+
+impl fmt::Display for SourceColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.table, self.column)
+    }
+}
+
+/// The lineage of an output column of a plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLineage {
+    /// The output column
+    pub column: Column,
+    /// The table columns the values of the output column are computed from
+    pub sources: BTreeSet<SourceColumn>,
+    /// The expressions computing the output column from its sources,
+    /// innermost first. References to columns are not included.
+    pub transformations: Vec<Expr>,
+}
+
+impl ColumnLineage {
+    fn new(column: Column) -> Self {
+        Self {
+            column,
+            sources: BTreeSet::new(),
+            transformations: vec![],
+        }
+    }
+
+    /// Adds the sources and transformations of `other`
+    fn merge(&mut self, other: &ColumnLineage) {
+        self.sources.extend(other.sources.iter().cloned());
+        for expr in &other.transformations {
+            if !self.transformations.contains(expr) {
+                self.transformations.push(expr.clone());
+            }
+        }
+    }
+}
+
+/// The lineage of each output column of a plan, see [`lineage`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLineageGraph {
+    /// The lineage of the columns of the schema of the plan, in order
+    pub columns: Vec<ColumnLineage>,
+}
+
+impl ColumnLineageGraph {
+    /// The lineage of the output column `name`, which may be qualified
+    pub fn column(&self, name: &str) -> Option<&ColumnLineage> {
+        self.columns
+            .iter()
+            .find(|lineage| lineage.column.flat_name() == name)
+            .or_else(|| {
+                self.columns
+                    .iter()
+                    .find(|lineage| lineage.column.name == name)
+            })
+    }
+}
+
+/// Returns the lineage of each output column of `plan`: the table columns
+/// it is computed from, and the expressions computing it.
+///
+/// Only the expressions producing the values of a column are part of its
+/// lineage, not the filters, join conditions and sort expressions selecting
+/// and ordering the rows. The columns of user defined plans are matched by
+/// name with the columns of their inputs.
+pub fn lineage(plan: &LogicalPlan) -> Result<ColumnLineageGraph> {
+    let mut visitor = LineageVisitor::default();
+    plan.accept(&mut visitor)?;
+    let columns = visitor.lineages.pop().ok_or_else(|| {
+        DataFusionError::Internal("No lineage computed for the plan".to_string())
+    })?;
+    Ok(ColumnLineageGraph { columns })
+}
+
+/// Computes the lineage of each plan after the lineage of its inputs
+#[derive(Default)]
+struct LineageVisitor {
+    /// The lineage of the visited plans whose parent is not visited yet
+    lineages: Vec<Vec<ColumnLineage>>,
+    /// The length of `lineages` when each plan being visited was entered
+    starts: Vec<usize>,
+}
+
+impl PlanVisitor for LineageVisitor {
+    type Error = DataFusionError;
+
+    fn pre_visit(&mut self, _plan: &LogicalPlan) -> Result<bool> {
+        self.starts.push(self.lineages.len());
+        Ok(true)
+    }
+
+    fn post_visit(&mut self, plan: &LogicalPlan) -> Result<bool> {
+        let start = self.starts.pop().ok_or_else(|| {
+            DataFusionError::Internal("Unbalanced lineage visit".to_string())
+        })?;
+        let mut visited = self.lineages.split_off(start);
+        // the subqueries of expressions are visited before the inputs
+        let inputs = visited.split_off(visited.len().saturating_sub(plan.inputs().len()));
+        self.lineages.push(plan_lineage(plan, &inputs)?);
+        Ok(true)
+    }
+}
+
+/// Computes the lineage of the columns of `plan` from the lineage of the
+/// columns of its `inputs`
+fn plan_lineage(
+    plan: &LogicalPlan,
+    inputs: &[Vec<ColumnLineage>],
+) -> Result<Vec<ColumnLineage>> {
+    let fields = plan.schema().fields();
+    let columns = match plan {
+        LogicalPlan::TableScan(TableScan { table_name, .. }) => Some(
+            fields
+                .iter()
+                .map(|field| {
+                    let mut lineage = ColumnLineage::new(field.qualified_column());
+                    lineage.sources.insert(SourceColumn {
+                        table: table_name.clone(),
+                        column: field.name().clone(),
+                    });
+                    lineage
+                })
+                .collect(),
+        ),
+        LogicalPlan::Projection(Projection { expr, input, .. }) => {
+            Some(exprs_lineage(expr, fields, input.schema(), &inputs[0])?)
+        }
+        LogicalPlan::Window(Window {
+            window_expr, input, ..
+        }) => {
+            let mut columns = inputs[0].clone();
+            let window_fields = &fields[columns.len().min(fields.len())..];
+            columns.extend(exprs_lineage(
+                window_expr,
+                window_fields,
+                input.schema(),
+                &inputs[0],
+            )?);
+            Some(columns)
+        }
+        LogicalPlan::Aggregate(Aggregate {
+            group_expr,
+            aggr_expr,
+            input,
+            ..
+        }) => {
+            let mut exprs = grouping_set_to_exprlist(group_expr)?;
+            exprs.extend(aggr_expr.iter().cloned());
+            Some(exprs_lineage(&exprs, fields, input.schema(), &inputs[0])?)
+        }
+        LogicalPlan::Join(Join { join_type, .. }) => match join_type {
+            JoinType::LeftSemi | JoinType::LeftAnti => Some(inputs[0].clone()),
+            JoinType::RightSemi => Some(inputs[1].clone()),
+            _ => Some(inputs.concat()),
+        },
+        LogicalPlan::CrossJoin(_) => Some(inputs.concat()),
+        LogicalPlan::Union(_) => {
+            let mut columns = fields
+                .iter()
+                .map(|field| ColumnLineage::new(field.qualified_column()))
+                .collect::<Vec<_>>();
+            for input in inputs {
+                for (lineage, input_lineage) in columns.iter_mut().zip(input) {
+                    lineage.merge(input_lineage);
+                }
+            }
+            Some(columns)
+        }
+        LogicalPlan::Filter(_)
+        | LogicalPlan::Sort(_)
+        | LogicalPlan::Limit(_)
+        | LogicalPlan::Repartition(_)
+        | LogicalPlan::Distinct(_)
+        | LogicalPlan::Unnest(_)
+        | LogicalPlan::Dedup(_)
+        | LogicalPlan::Subquery(_)
+        | LogicalPlan::SubqueryAlias(_) => Some(inputs[0].clone()),
+        _ => None,
+    };
+
+    match columns {
+        Some(columns) if columns.len() == fields.len() => Ok(columns
+            .into_iter()
+            .zip(fields)
+            .map(|(mut lineage, field)| {
+                lineage.column = field.qualified_column();
+                lineage
+            })
+            .collect()),
+        _ => Ok(fields
+            .iter()
+            .map(|field| {
+                let column = field.qualified_column();
+                let mut lineage = ColumnLineage::new(column.clone());
+                let input_lineage = inputs
+                    .iter()
+                    .flatten()
+                    .find(|input| input.column == column)
+                    .or_else(|| {
+                        inputs
+                            .iter()
+                            .flatten()
+                            .find(|input| input.column.name == column.name)
+                    });
+                if let Some(input_lineage) = input_lineage {
+                    lineage.merge(input_lineage);
+                }
+                lineage
+            })
+            .collect()),
+    }
+}
+
+/// Computes the lineage of the columns `fields` computed by `exprs` from an
+/// input with the schema `input_schema` and the column lineage `input`
+fn exprs_lineage(
+    exprs: &[Expr],
+    fields: &[DFField],
+    input_schema: &DFSchema,
+    input: &[ColumnLineage],
+) -> Result<Vec<ColumnLineage>> {
+    exprs
+        .iter()
+        .zip(fields)
+        .map(|(expr, field)| {
+            let mut lineage = ColumnLineage::new(field.qualified_column());
+            let mut columns = HashSet::new();
+            expr_to_columns(expr, &mut columns)?;
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort_by_key(|column| column.flat_name());
+            for column in columns {
+                // outer references of correlated subqueries are not in the input
+                if let Ok(index) = input_schema.index_of_column(&column) {
+                    lineage.merge(&input[index]);
+                }
+            }
+            if !is_column_reference(expr) {
+                lineage.transformations.push(expr.clone());
+            }
+            Ok(lineage)
+        })
+        .collect()
+}
+
+fn is_column_reference(expr: &Expr) -> bool {
+    match expr {
+        Expr::Column(_) => true,
+        Expr::Alias(expr, _) => is_column_reference(expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::table_scan;
+    use crate::{col, lit, sum};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+            Field::new("c", DataType::Utf8, false),
+        ])
+    }
+
+    fn sources(graph: &ColumnLineageGraph, name: &str) -> Vec<String> {
+        graph
+            .column(name)
+            .unwrap()
+            .sources
+            .iter()
+            .map(|source| source.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn projection_lineage() -> Result<()> {
+        let plan = table_scan(Some("t"), &schema(), None)?
+            .filter(col("c").eq(lit("x")))?
+            .project(vec![col("a"), (col("a") + col("b")).alias("sum_ab")])?
+            .build()?;
+        let graph = lineage(&plan)?;
+
+        assert_eq!(graph.columns.len(), 2);
+        assert_eq!(sources(&graph, "t.a"), vec!["t.a"]);
+        assert!(graph.column("a").unwrap().transformations.is_empty());
+        assert_eq!(sources(&graph, "sum_ab"), vec!["t.a", "t.b"]);
+        assert_eq!(
+            graph.column("sum_ab").unwrap().transformations,
+            vec![(col("t.a") + col("t.b")).alias("sum_ab")]
+        );
+        Ok(())
+    }
+
+    fn sum_column() -> Expr {
+        Expr::Column(Column::from_name("SUM(u.b)"))
+    }
+
+    #[test]
+    fn aggregate_and_join_lineage() -> Result<()> {
+        let right = table_scan(Some("u"), &schema(), None)?.build()?;
+        let plan = table_scan(Some("t"), &schema(), None)?
+            .join(&right, JoinType::Inner, (vec!["a"], vec!["a"]), None)?
+            .aggregate(vec![col("t.c")], vec![sum(col("u.b"))])?
+            .project(vec![col("t.c"), (sum_column() * lit(2)).alias("total")])?
+            .build()?;
+        let graph = lineage(&plan)?;
+
+        assert_eq!(sources(&graph, "c"), vec!["t.c"]);
+        assert_eq!(sources(&graph, "total"), vec!["u.b"]);
+        assert_eq!(
+            graph.column("total").unwrap().transformations,
+            vec![sum(col("u.b")), (sum_column() * lit(2)).alias("total")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn union_lineage() -> Result<()> {
+        let right = table_scan(Some("u"), &schema(), None)?
+            .project(vec![col("b")])?
+            .build()?;
+        let plan = table_scan(Some("t"), &schema(), None)?
+            .project(vec![col("a")])?
+            .union(right)?
+            .build()?;
+        let graph = lineage(&plan)?;
+
+        assert_eq!(graph.columns.len(), 1);
+        assert_eq!(sources(&graph, "a"), vec!["t.a", "u.b"]);
+        Ok(())
+    }
+}
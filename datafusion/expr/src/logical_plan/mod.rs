@@ -18,18 +18,22 @@
 pub mod builder;
 pub mod display;
 mod extension;
+pub mod lineage;
 mod plan;
 
 pub use builder::{table_scan, LogicalPlanBuilder};
 pub use plan::{
-    Aggregate, Analyze, CreateCatalog, CreateCatalogSchema, CreateExternalTable,
-    CreateMemoryTable, CreateView, CrossJoin, Distinct, DropTable, DropView,
-    EmptyRelation, Explain, Extension, Filter, Join, JoinConstraint, JoinType, Limit,
-    LogicalPlan, Partitioning, PlanType, PlanVisitor, Projection, Repartition, Sort,
-    StringifiedPlan, Subquery, SubqueryAlias, TableScan, ToStringifiedPlan, Union,
-    Values, Window,
+    is_secret_option, Aggregate, Analyze, AnalyzeTable, CreateCatalog,
+    CreateCatalogSchema, CreateExternalTable, CreateMemoryTable, CreateView, CrossJoin,
+    Dedup, DedupKeep, Distinct, DropTable, DropView, EmptyRelation, Explain, Extension,
+    Filter, Join, JoinConstraint, JoinType, Limit, LogicalPlan, Partitioning, PlanType,
+    PlanVisitor, Prepare, Projection, RefreshMaterializedView, Repartition, SetVariable,
+    Sort, StringifiedPlan, Subquery, SubqueryAlias, TableScan, ToStringifiedPlan, Union,
+    Unnest, Values, Window,
 };
 
 pub use display::display_schema;
 
+pub use lineage::{lineage, ColumnLineage, ColumnLineageGraph, SourceColumn};
+
 pub use extension::UserDefinedLogicalNode;
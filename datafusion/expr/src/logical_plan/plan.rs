@@ -16,17 +16,21 @@
 // under the License.
 
 use crate::expr::BinaryExpr;
+use crate::expr_rewriter::{ExprRewritable, ExprRewriter};
 ///! Logical plan types
 use crate::logical_plan::builder::validate_unique_names;
 use crate::logical_plan::display::{GraphvizVisitor, IndentVisitor};
 use crate::logical_plan::extension::UserDefinedLogicalNode;
 use crate::utils::{
-    exprlist_to_fields, grouping_set_expr_count, grouping_set_to_exprlist,
+    exprlist_to_fields, from_plan, grouping_set_expr_count, grouping_set_to_exprlist,
 };
 use crate::{Expr, ExprSchemable, TableProviderFilterPushDown, TableSource};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use datafusion_common::{plan_err, Column, DFSchema, DFSchemaRef, DataFusionError};
-use std::collections::HashSet;
+use datafusion_common::{
+    plan_err, Column, Constraints, DFField, DFSchema, DFSchemaRef, DataFusionError,
+    ScalarValue,
+};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
@@ -92,6 +96,15 @@ pub enum LogicalPlan {
     DropTable(DropTable),
     /// Drops a view.
     DropView(DropView),
+    /// Refreshes a materialized view.
+    RefreshMaterializedView(RefreshMaterializedView),
+    /// Builds the index files of the data files of a table.
+    AnalyzeTable(AnalyzeTable),
+    /// Sets a configuration option of the session.
+    SetVariable(SetVariable),
+    /// Prepares a statement, whose placeholders are bound with
+    /// [`LogicalPlan::with_param_values`] before executing it.
+    Prepare(Prepare),
     /// Values expression. See
     /// [Postgres VALUES](https://www.postgresql.org/docs/current/queries-values.html)
     /// documentation for more details.
@@ -106,6 +119,10 @@ pub enum LogicalPlan {
     Extension(Extension),
     /// Remove duplicate rows from the input
     Distinct(Distinct),
+    /// Produces a row for each element of a list column of its input
+    Unnest(Unnest),
+    /// Keeps a single row of each group of rows with the same keys
+    Dedup(Dedup),
 }
 
 impl LogicalPlan {
@@ -120,6 +137,8 @@ impl LogicalPlan {
             LogicalPlan::Projection(Projection { schema, .. }) => schema,
             LogicalPlan::Filter(Filter { input, .. }) => input.schema(),
             LogicalPlan::Distinct(Distinct { input }) => input.schema(),
+            LogicalPlan::Unnest(Unnest { schema, .. }) => schema,
+            LogicalPlan::Dedup(Dedup { input, .. }) => input.schema(),
             LogicalPlan::Window(Window { schema, .. }) => schema,
             LogicalPlan::Aggregate(Aggregate { schema, .. }) => schema,
             LogicalPlan::Sort(Sort { input, .. }) => input.schema(),
@@ -137,13 +156,20 @@ impl LogicalPlan {
             LogicalPlan::Extension(extension) => extension.node.schema(),
             LogicalPlan::Union(Union { schema, .. }) => schema,
             LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
-            | LogicalPlan::CreateView(CreateView { input, .. }) => input.schema(),
+            | LogicalPlan::CreateView(CreateView { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => input.schema(),
             LogicalPlan::CreateCatalogSchema(CreateCatalogSchema { schema, .. }) => {
                 schema
             }
             LogicalPlan::CreateCatalog(CreateCatalog { schema, .. }) => schema,
             LogicalPlan::DropTable(DropTable { schema, .. }) => schema,
             LogicalPlan::DropView(DropView { schema, .. }) => schema,
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView {
+                schema,
+                ..
+            }) => schema,
+            LogicalPlan::AnalyzeTable(AnalyzeTable { schema, .. }) => schema,
+            LogicalPlan::SetVariable(SetVariable { schema, .. }) => schema,
         }
     }
 
@@ -156,7 +182,8 @@ impl LogicalPlan {
             LogicalPlan::Values(Values { schema, .. }) => vec![schema],
             LogicalPlan::Window(Window { input, schema, .. })
             | LogicalPlan::Projection(Projection { input, schema, .. })
-            | LogicalPlan::Aggregate(Aggregate { input, schema, .. }) => {
+            | LogicalPlan::Aggregate(Aggregate { input, schema, .. })
+            | LogicalPlan::Unnest(Unnest { input, schema, .. }) => {
                 let mut schemas = input.all_schemas();
                 schemas.insert(0, schema);
                 schemas
@@ -198,9 +225,15 @@ impl LogicalPlan {
             | LogicalPlan::Sort(Sort { input, .. })
             | LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
             | LogicalPlan::CreateView(CreateView { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. })
             | LogicalPlan::Filter(Filter { input, .. }) => input.all_schemas(),
             LogicalPlan::Distinct(Distinct { input, .. }) => input.all_schemas(),
-            LogicalPlan::DropTable(_) | LogicalPlan::DropView(_) => vec![],
+            LogicalPlan::Dedup(Dedup { input, .. }) => input.all_schemas(),
+            LogicalPlan::DropTable(_)
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshMaterializedView(_)
+            | LogicalPlan::AnalyzeTable(_)
+            | LogicalPlan::SetVariable(_) => vec![],
         }
     }
 
@@ -247,6 +280,12 @@ impl LogicalPlan {
                 )
                 .collect(),
             LogicalPlan::Sort(Sort { expr, .. }) => expr.clone(),
+            LogicalPlan::Unnest(Unnest { column, .. }) => {
+                vec![Expr::Column(column.clone())]
+            }
+            LogicalPlan::Dedup(Dedup { on, order_by, .. }) => {
+                on.iter().chain(order_by.iter()).cloned().collect()
+            }
             LogicalPlan::Extension(extension) => extension.node.expressions(),
             // plans without expressions
             LogicalPlan::TableScan { .. }
@@ -261,6 +300,10 @@ impl LogicalPlan {
             | LogicalPlan::CreateCatalog(_)
             | LogicalPlan::DropTable(_)
             | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshMaterializedView(_)
+            | LogicalPlan::AnalyzeTable(_)
+            | LogicalPlan::SetVariable(_)
+            | LogicalPlan::Prepare(_)
             | LogicalPlan::CrossJoin(_)
             | LogicalPlan::Analyze { .. }
             | LogicalPlan::Explain { .. }
@@ -291,10 +334,13 @@ impl LogicalPlan {
                 inputs.iter().map(|arc| arc.as_ref()).collect()
             }
             LogicalPlan::Distinct(Distinct { input }) => vec![input],
+            LogicalPlan::Unnest(Unnest { input, .. }) => vec![input],
+            LogicalPlan::Dedup(Dedup { input, .. }) => vec![input],
             LogicalPlan::Explain(explain) => vec![&explain.plan],
             LogicalPlan::Analyze(analyze) => vec![&analyze.input],
             LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
-            | LogicalPlan::CreateView(CreateView { input, .. }) => {
+            | LogicalPlan::CreateView(CreateView { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => {
                 vec![input]
             }
             // plans without inputs
@@ -305,7 +351,10 @@ impl LogicalPlan {
             | LogicalPlan::CreateCatalogSchema(_)
             | LogicalPlan::CreateCatalog(_)
             | LogicalPlan::DropTable(_)
-            | LogicalPlan::DropView(_) => vec![],
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshMaterializedView(_)
+            | LogicalPlan::AnalyzeTable(_)
+            | LogicalPlan::SetVariable(_) => vec![],
         }
     }
 
@@ -342,6 +391,105 @@ impl LogicalPlan {
         self.accept(&mut visitor)?;
         Ok(visitor.using_columns)
     }
+
+    /// Returns a copy of this plan with every [`Expr::Placeholder`], including
+    /// those in subqueries, replaced by a literal of the value bound to its id
+    /// in `param_values`.
+    ///
+    /// Returns an error if a placeholder has no value, or a value of a type
+    /// other than the placeholder's. A `ScalarValue::Null` value is bound as a
+    /// null of the placeholder's type.
+    ///
+    /// A [`Prepare`] plan is replaced by the plan of its statement, whose
+    /// parameters are bound to the values of their positions, `"1"`, `"2"`...
+    pub fn with_param_values(
+        &self,
+        param_values: &HashMap<String, ScalarValue>,
+    ) -> Result<LogicalPlan, DataFusionError> {
+        match self {
+            LogicalPlan::Prepare(prepare) => {
+                prepare.input.with_param_values(param_values)
+            }
+            LogicalPlan::Explain(explain) => Ok(LogicalPlan::Explain(Explain {
+                verbose: explain.verbose,
+                plan: Arc::new(explain.plan.with_param_values(param_values)?),
+                stringified_plans: explain.stringified_plans.clone(),
+                schema: explain.schema.clone(),
+            })),
+            _ => {
+                let mut rewriter = PlaceholderRewriter { param_values };
+                let exprs = self
+                    .expressions()
+                    .into_iter()
+                    .map(|expr| expr.rewrite(&mut rewriter))
+                    .collect::<Result<Vec<_>, DataFusionError>>()?;
+                let inputs = self
+                    .inputs()
+                    .into_iter()
+                    .map(|input| input.with_param_values(param_values))
+                    .collect::<Result<Vec<_>, DataFusionError>>()?;
+                from_plan(self, &exprs, &inputs)
+            }
+        }
+    }
+}
+
+/// Replaces placeholders by the literal values bound to them
+struct PlaceholderRewriter<'a> {
+    param_values: &'a HashMap<String, ScalarValue>,
+}
+
+impl<'a> PlaceholderRewriter<'a> {
+    fn bind_subquery(&self, subquery: Subquery) -> Result<Subquery, DataFusionError> {
+        Ok(Subquery {
+            subquery: Arc::new(subquery.subquery.with_param_values(self.param_values)?),
+        })
+    }
+}
+
+impl<'a> ExprRewriter for PlaceholderRewriter<'a> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr, DataFusionError> {
+        match expr {
+            Expr::Placeholder { id, data_type } => {
+                let value = self.param_values.get(&id).ok_or_else(|| {
+                    DataFusionError::Plan(format!(
+                        "No value found for placeholder ${}",
+                        id
+                    ))
+                })?;
+                let value = match value {
+                    ScalarValue::Null => ScalarValue::try_from(&data_type)?,
+                    value if value.get_datatype() == data_type => value.clone(),
+                    value => {
+                        return Err(DataFusionError::Plan(format!(
+                            "Placeholder ${} expected a value of type {:?}, got {:?}",
+                            id,
+                            data_type,
+                            value.get_datatype()
+                        )))
+                    }
+                };
+                Ok(Expr::Literal(value))
+            }
+            Expr::Exists { subquery, negated } => Ok(Expr::Exists {
+                subquery: self.bind_subquery(subquery)?,
+                negated,
+            }),
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => Ok(Expr::InSubquery {
+                expr,
+                subquery: self.bind_subquery(subquery)?,
+                negated,
+            }),
+            Expr::ScalarSubquery(subquery) => {
+                Ok(Expr::ScalarSubquery(self.bind_subquery(subquery)?))
+            }
+            expr => Ok(expr),
+        }
+    }
 }
 
 /// Trait that implements the [Visitor
@@ -426,6 +574,8 @@ impl LogicalPlan {
                 true
             }
             LogicalPlan::Distinct(Distinct { input }) => input.accept(visitor)?,
+            LogicalPlan::Unnest(Unnest { input, .. }) => input.accept(visitor)?,
+            LogicalPlan::Dedup(Dedup { input, .. }) => input.accept(visitor)?,
             LogicalPlan::Limit(Limit { input, .. }) => input.accept(visitor)?,
             LogicalPlan::Subquery(Subquery { subquery, .. }) => {
                 subquery.accept(visitor)?
@@ -434,9 +584,8 @@ impl LogicalPlan {
                 input.accept(visitor)?
             }
             LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
-            | LogicalPlan::CreateView(CreateView { input, .. }) => {
-                input.accept(visitor)?
-            }
+            | LogicalPlan::CreateView(CreateView { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => input.accept(visitor)?,
             LogicalPlan::Extension(extension) => {
                 for input in extension.node.inputs() {
                     if !input.accept(visitor)? {
@@ -455,7 +604,10 @@ impl LogicalPlan {
             | LogicalPlan::CreateCatalogSchema(_)
             | LogicalPlan::CreateCatalog(_)
             | LogicalPlan::DropTable(_)
-            | LogicalPlan::DropView(_) => true,
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshMaterializedView(_)
+            | LogicalPlan::AnalyzeTable(_)
+            | LogicalPlan::SetVariable(_) => true,
         };
         if !recurse {
             return Ok(false);
@@ -714,6 +866,7 @@ impl LogicalPlan {
                         ref table_name,
                         ref projection,
                         ref filters,
+                        ref ordering,
                         ref fetch,
                         ..
                     }) => {
@@ -767,6 +920,10 @@ impl LogicalPlan {
                             }
                         }
 
+                        if !ordering.is_empty() {
+                            write!(f, ", ordering={:?}", ordering)?;
+                        }
+
                         if let Some(n) = fetch {
                             write!(f, ", fetch={}", n)?;
                         }
@@ -915,8 +1072,14 @@ impl LogicalPlan {
                     }) => {
                         write!(f, "CreateMemoryTable: {:?}", name)
                     }
-                    LogicalPlan::CreateView(CreateView { name, .. }) => {
-                        write!(f, "CreateView: {:?}", name)
+                    LogicalPlan::CreateView(CreateView {
+                        name, materialized, ..
+                    }) => {
+                        if *materialized {
+                            write!(f, "CreateMaterializedView: {:?}", name)
+                        } else {
+                            write!(f, "CreateView: {:?}", name)
+                        }
                     }
                     LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
                         schema_name,
@@ -939,9 +1102,40 @@ impl LogicalPlan {
                     }) => {
                         write!(f, "DropView: {:?} if not exist:={}", name, if_exists)
                     }
+                    LogicalPlan::RefreshMaterializedView(RefreshMaterializedView {
+                        name,
+                        ..
+                    }) => {
+                        write!(f, "RefreshMaterializedView: {:?}", name)
+                    }
+                    LogicalPlan::AnalyzeTable(AnalyzeTable { name, .. }) => {
+                        write!(f, "AnalyzeTable: {:?}", name)
+                    }
+                    LogicalPlan::SetVariable(SetVariable {
+                        variable, value, ..
+                    }) => {
+                        write!(f, "SetVariable: set {:?} to {:?}", variable, value)
+                    }
+                    LogicalPlan::Prepare(Prepare {
+                        name, data_types, ..
+                    }) => {
+                        write!(f, "Prepare: {:?} {:?}", name, data_types)
+                    }
                     LogicalPlan::Distinct(Distinct { .. }) => {
                         write!(f, "Distinct:")
                     }
+                    LogicalPlan::Unnest(Unnest { column, .. }) => {
+                        write!(f, "Unnest: {}", column)
+                    }
+                    LogicalPlan::Dedup(Dedup {
+                        on, keep, order_by, ..
+                    }) => {
+                        write!(f, "Dedup: on=[{:?}], keep={}", on, keep)?;
+                        if !order_by.is_empty() {
+                            write!(f, ", orderBy=[{:?}]", order_by)?;
+                        }
+                        Ok(())
+                    }
                     LogicalPlan::Explain { .. } => write!(f, "Explain"),
                     LogicalPlan::Analyze { .. } => write!(f, "Analyze"),
                     LogicalPlan::Union(_) => write!(f, "Union"),
@@ -1015,6 +1209,9 @@ pub struct CreateCatalog {
     pub catalog_name: String,
     /// Do nothing (except issuing a notice) if a schema with the same name already exists
     pub if_not_exists: bool,
+    /// The directory under which the schemas of the catalog store their
+    /// tables by default
+    pub location: Option<String>,
     /// Empty schema
     pub schema: DFSchemaRef,
 }
@@ -1026,6 +1223,8 @@ pub struct CreateCatalogSchema {
     pub schema_name: String,
     /// Do nothing (except issuing a notice) if a schema with the same name already exists
     pub if_not_exists: bool,
+    /// The directory of the tables created in the schema without a location
+    pub location: Option<String>,
     /// Empty schema
     pub schema: DFSchemaRef,
 }
@@ -1052,6 +1251,48 @@ pub struct DropView {
     pub schema: DFSchemaRef,
 }
 
+/// Recomputes the contents of a materialized view.
+#[derive(Clone)]
+pub struct RefreshMaterializedView {
+    /// The view name
+    pub name: String,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
+/// Builds the index files of the data files of a table.
+#[derive(Clone)]
+pub struct AnalyzeTable {
+    /// The table name
+    pub name: String,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
+/// Sets a configuration option of the session.
+#[derive(Clone)]
+pub struct SetVariable {
+    /// The option name
+    pub variable: String,
+    /// The option value
+    pub value: String,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
+/// Prepares a statement, such as `PREPARE q(INT) AS SELECT * FROM t WHERE a = $1`.
+///
+/// The placeholders of the statement are named by their position, from `1`.
+#[derive(Clone)]
+pub struct Prepare {
+    /// The statement name
+    pub name: String,
+    /// The types of the parameters, in order
+    pub data_types: Vec<DataType>,
+    /// The logical plan of the statement
+    pub input: Arc<LogicalPlan>,
+}
+
 /// Produces no rows: An empty relation with an empty schema
 #[derive(Clone)]
 pub struct EmptyRelation {
@@ -1227,6 +1468,8 @@ pub struct TableScan {
     pub projected_schema: DFSchemaRef,
     /// Optional expressions to be used as filters by the table provider
     pub filters: Vec<Expr>,
+    /// Optional sort expressions the table provider returns the rows ordered by
+    pub ordering: Vec<Expr>,
     /// Optional number of rows to read
     pub fetch: Option<usize>,
 }
@@ -1286,6 +1529,12 @@ pub struct CreateView {
     pub or_replace: bool,
     /// SQL used to create the view, if available
     pub definition: Option<String>,
+    /// Whether the results of the view are computed once and stored, until
+    /// refreshed, instead of being recomputed by every query
+    pub materialized: bool,
+    /// Where the results of a materialized view are stored, if not in a
+    /// temporary directory
+    pub location: Option<String>,
 }
 
 /// Creates an external table.
@@ -1293,6 +1542,8 @@ pub struct CreateView {
 pub struct CreateExternalTable {
     /// The table schema
     pub schema: DFSchemaRef,
+    /// Constraints declared on the columns of the table
+    pub constraints: Constraints,
     /// The table name
     pub name: String,
     /// The physical location
@@ -1305,12 +1556,44 @@ pub struct CreateExternalTable {
     pub delimiter: char,
     /// Partition Columns
     pub table_partition_cols: Vec<String>,
+    /// Bucket columns, that the rows of the files are hashed on
+    pub bucket_cols: Vec<String>,
+    /// Number of buckets of the rows, 0 if the table is not bucketed
+    pub num_buckets: usize,
     /// Option to not error if table already exists
     pub if_not_exists: bool,
     /// SQL used to create the table, if available
     pub definition: Option<String>,
     /// File compression type (GZIP, BZIP2)
     pub file_compression_type: String,
+    /// Table(provider) specific options. The [secret options] are left out
+    /// when the plan is serialized.
+    ///
+    /// [secret options]: is_secret_option
+    pub options: HashMap<String, String>,
+}
+
+impl CreateExternalTable {
+    /// The options of the table without the [secret options], to serialize
+    /// or display them
+    ///
+    /// [secret options]: is_secret_option
+    pub fn public_options(&self) -> HashMap<String, String> {
+        self.options
+            .iter()
+            .filter(|(key, _)| !is_secret_option(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Whether the option `key` of a `CREATE EXTERNAL TABLE` statement holds a
+/// credential, such as a secret key, a token or a password
+pub fn is_secret_option(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["secret", "token", "password", "access_key", "credential"]
+        .iter()
+        .any(|secret| key.contains(secret))
 }
 
 /// Produces a relation with string representations of
@@ -1365,6 +1648,121 @@ pub struct Distinct {
     pub input: Arc<LogicalPlan>,
 }
 
+/// Which row of each group of rows with the same keys a [`Dedup`] keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DedupKeep {
+    /// Keep the first row of each group
+    First,
+    /// Keep the last row of each group
+    Last,
+}
+
+impl Display for DedupKeep {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let keep = match self {
+            DedupKeep::First => "First",
+            DedupKeep::Last => "Last",
+        };
+        write!(f, "{}", keep)
+    }
+}
+
+/// Keeps a single row of each group of rows of the input with the same
+/// values of the `on` expressions: the first or the last one in the order of
+/// the `order_by` expressions or, without them, in the order of the input.
+#[derive(Clone)]
+pub struct Dedup {
+    /// The incoming logical plan
+    pub input: Arc<LogicalPlan>,
+    /// The expressions whose values identify the duplicate rows
+    pub on: Vec<Expr>,
+    /// Which row of each group of duplicates to keep
+    pub keep: DedupKeep,
+    /// The sort expressions ordering the duplicates, if any
+    pub order_by: Vec<Expr>,
+}
+
+impl Dedup {
+    /// Create a new dedup operator of `input`
+    pub fn try_new(
+        input: Arc<LogicalPlan>,
+        on: Vec<Expr>,
+        keep: DedupKeep,
+        order_by: Vec<Expr>,
+    ) -> datafusion_common::Result<Self> {
+        if on.is_empty() {
+            return Err(DataFusionError::Plan(
+                "Dedup requires at least one expression".to_string(),
+            ));
+        }
+        if let Some(expr) = order_by.iter().find(|e| !matches!(e, Expr::Sort { .. })) {
+            return Err(DataFusionError::Plan(format!(
+                "Dedup can only be ordered by sort expressions, got {:?}",
+                expr
+            )));
+        }
+        Ok(Self {
+            input,
+            on,
+            keep,
+            order_by,
+        })
+    }
+}
+
+/// Produces a row for each element of a list column of its input, with the
+/// values of the other columns repeated. Rows whose list is null or empty
+/// produce no rows.
+#[derive(Clone)]
+pub struct Unnest {
+    /// The incoming logical plan
+    pub input: Arc<LogicalPlan>,
+    /// The list column to unnest
+    pub column: Column,
+    /// The output schema, with the list column replaced by its elements
+    pub schema: DFSchemaRef,
+}
+
+impl Unnest {
+    /// Create a new unnest operator of the list `column` of `input`
+    pub fn try_new(
+        input: Arc<LogicalPlan>,
+        column: Column,
+    ) -> datafusion_common::Result<Self> {
+        let input_schema = input.schema();
+        let index = input_schema.index_of_column(&column)?;
+        let fields = input_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if i != index {
+                    return Ok(field.clone());
+                }
+                match field.data_type() {
+                    DataType::List(item) | DataType::LargeList(item) => Ok(DFField::new(
+                        field.qualifier().map(|s| s.as_str()),
+                        field.name(),
+                        item.data_type().clone(),
+                        true,
+                    )),
+                    other => Err(DataFusionError::Plan(format!(
+                        "Unnest requires a list column, {} has type {:?}",
+                        column, other
+                    ))),
+                }
+            })
+            .collect::<datafusion_common::Result<Vec<_>>>()?;
+        let schema =
+            DFSchema::new_with_metadata(fields, input_schema.metadata().clone())?;
+        Ok(Self {
+            input,
+            column,
+            schema: Arc::new(schema),
+        })
+    }
+}
+
 /// Aggregates its input based on a set of grouping and aggregate
 /// expressions (e.g. SUM).
 #[derive(Clone)]
@@ -1610,7 +2008,7 @@ pub trait ToStringifiedPlan {
 mod tests {
     use super::*;
     use crate::logical_plan::table_scan;
-    use crate::{col, in_subquery, lit};
+    use crate::{col, in_subquery, lit, placeholder};
     use arrow::datatypes::{DataType, Field, Schema};
     use datafusion_common::DFSchema;
     use datafusion_common::Result;
@@ -1948,6 +2346,66 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn bind_param_values() -> Result<()> {
+        let subquery =
+            table_scan(Some("employee_csv"), &employee_schema(), Some(vec![3]))?
+                .filter(col("state").eq(placeholder("state", DataType::Utf8)))?
+                .build()?;
+        let plan =
+            table_scan(Some("employee_csv"), &employee_schema(), Some(vec![0, 3]))?
+                .filter(in_subquery(col("state"), Arc::new(subquery)))?
+                .filter(col("id").gt(placeholder("min_id", DataType::Int32)))?
+                .build()?;
+
+        let param_values = HashMap::from([
+            (
+                "state".to_string(),
+                ScalarValue::Utf8(Some("CO".to_string())),
+            ),
+            ("min_id".to_string(), ScalarValue::Int32(Some(10))),
+        ]);
+        let expected = "Filter: employee_csv.id > Int32(10)\
+        \n  Filter: employee_csv.state IN (<subquery>)\
+        \n    Subquery:\
+        \n      Filter: employee_csv.state = Utf8(\"CO\")\
+        \n        TableScan: employee_csv projection=[state]\
+        \n    TableScan: employee_csv projection=[id, state]";
+        let bound = plan.with_param_values(&param_values)?;
+        assert_eq!(expected, format!("{}", bound.display_indent()));
+
+        // a null is bound as a null of the placeholder's type
+        let param_values = HashMap::from([
+            ("state".to_string(), ScalarValue::Null),
+            ("min_id".to_string(), ScalarValue::Null),
+        ]);
+        let bound = plan.with_param_values(&param_values)?;
+        assert!(format!("{}", bound.display_indent())
+            .starts_with("Filter: employee_csv.id > Int32(NULL)"));
+
+        let param_values =
+            HashMap::from([("min_id".to_string(), ScalarValue::Int32(Some(10)))]);
+        let err = plan.with_param_values(&param_values).unwrap_err();
+        assert_eq!(
+            "Error during planning: No value found for placeholder $state",
+            err.to_string()
+        );
+
+        let param_values = HashMap::from([
+            (
+                "state".to_string(),
+                ScalarValue::Utf8(Some("CO".to_string())),
+            ),
+            ("min_id".to_string(), ScalarValue::Int64(Some(10))),
+        ]);
+        let err = plan.with_param_values(&param_values).unwrap_err();
+        assert_eq!(
+            "Error during planning: Placeholder $min_id expected a value of type Int32, got Int64",
+            err.to_string()
+        );
+        Ok(())
+    }
+
     fn test_plan() -> LogicalPlan {
         let schema = Schema::new(vec![
             Field::new("id", DataType::Int32, false),
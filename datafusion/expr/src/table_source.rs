@@ -17,7 +17,9 @@
 
 use crate::{Expr, LogicalPlan};
 use arrow::datatypes::SchemaRef;
+use datafusion_common::Constraints;
 use std::any::Any;
+use std::collections::HashMap;
 
 ///! Table source
 
@@ -68,6 +70,11 @@ pub trait TableSource: Sync + Send {
         TableType::Base
     }
 
+    /// Get the constraints, such as the primary key, declared on this table.
+    fn constraints(&self) -> Option<&Constraints> {
+        None
+    }
+
     /// Tests whether the table provider can make use of a filter expression
     /// to optimise data retrieval.
     fn supports_filter_pushdown(
@@ -77,8 +84,37 @@ pub trait TableSource: Sync + Send {
         Ok(TableProviderFilterPushDown::Unsupported)
     }
 
+    /// Tests whether the table provider can return its rows ordered by the
+    /// sort expressions `sort_exprs`, so that an ordering and a limit can be
+    /// pushed down into the scan.
+    fn supports_sort_pushdown(
+        &self,
+        _sort_exprs: &[Expr],
+    ) -> datafusion_common::Result<bool> {
+        Ok(false)
+    }
+
     /// Get the Logical plan of this table provider, if available.
     fn get_logical_plan(&self) -> Option<&LogicalPlan> {
         None
     }
 }
+
+/// A policy enforced on every scan of a table, such as row-level security or
+/// the masking of sensitive columns. Policies are looked up in the catalog
+/// while queries are planned, so embedders can enforce them without
+/// rewriting the SQL they receive.
+pub trait TablePolicy: Sync + Send {
+    /// Filters that every row read from the table must satisfy, in terms of
+    /// the columns of the table
+    fn filters(&self) -> datafusion_common::Result<Vec<Expr>> {
+        Ok(vec![])
+    }
+
+    /// Expressions that replace the values of masked columns, by column
+    /// name. They are evaluated on the unmasked columns of the rows that
+    /// passed the [`filters`](Self::filters).
+    fn column_masks(&self) -> datafusion_common::Result<HashMap<String, Expr>> {
+        Ok(HashMap::new())
+    }
+}
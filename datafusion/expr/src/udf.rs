@@ -41,6 +41,18 @@ pub struct ScalarUDF {
     /// the batch's row count (so that the generative zero-argument function can know
     /// the result array size).
     pub fun: ScalarFunctionImplementation,
+    /// The WebAssembly module `fun` runs, if any, which is serialized with
+    /// the UDF so that it can be rebuilt without a registry
+    pub wasm_body: Option<Arc<WasmUdfBody>>,
+}
+
+/// A function exported by a WebAssembly module, implementing a [`ScalarUDF`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WasmUdfBody {
+    /// The binary WebAssembly module
+    pub module: Vec<u8>,
+    /// The name of the function called for each row
+    pub export: String,
 }
 
 impl Debug for ScalarUDF {
@@ -81,9 +93,16 @@ impl ScalarUDF {
             signature: signature.clone(),
             return_type: return_type.clone(),
             fun: fun.clone(),
+            wasm_body: None,
         }
     }
 
+    /// Records the WebAssembly module run by this UDF
+    pub fn with_wasm_body(mut self, wasm_body: WasmUdfBody) -> Self {
+        self.wasm_body = Some(Arc::new(wasm_body));
+        self
+    }
+
     /// creates a logical expression with a call of the UDF
     /// This utility allows using the UDF without requiring access to the registry.
     pub fn call(&self, args: Vec<Expr>) -> Expr {
@@ -21,9 +21,9 @@ use crate::expr_rewriter::{ExprRewritable, ExprRewriter, RewriteRecursion};
 use crate::expr_visitor::{ExprVisitable, ExpressionVisitor, Recursion};
 use crate::logical_plan::builder::build_join_schema;
 use crate::logical_plan::{
-    Aggregate, Analyze, CreateMemoryTable, CreateView, Distinct, Extension, Filter, Join,
-    Limit, Partitioning, Projection, Repartition, Sort, Subquery, SubqueryAlias, Union,
-    Values, Window,
+    Aggregate, Analyze, CreateMemoryTable, CreateView, Dedup, Distinct, Extension,
+    Filter, Join, JoinType, Limit, Partitioning, Prepare, Projection, Repartition, Sort,
+    Subquery, SubqueryAlias, Union, Unnest, Values, Window,
 };
 use crate::{Expr, ExprSchemable, LogicalPlan, LogicalPlanBuilder};
 use arrow::datatypes::{DataType, TimeUnit};
@@ -95,6 +95,7 @@ impl ExpressionVisitor for ColumnNameVisitor<'_> {
             }
             Expr::Alias(_, _)
             | Expr::Literal(_)
+            | Expr::Placeholder { .. }
             | Expr::BinaryExpr { .. }
             | Expr::Like { .. }
             | Expr::ILike { .. }
@@ -270,6 +271,14 @@ pub fn find_window_exprs(exprs: &[Expr]) -> Vec<Expr> {
     })
 }
 
+/// Collect all deeply nested `Expr::ScalarSubquery`, in order of occurrence
+/// (depth first). Subqueries are never equal, so none of them is omitted.
+pub fn find_scalar_subquery_exprs(exprs: &[Expr]) -> Vec<Expr> {
+    find_exprs_in_exprs(exprs, &|nested_expr| {
+        matches!(nested_expr, Expr::ScalarSubquery(_))
+    })
+}
+
 /// Search the provided `Expr`'s, and all of their nested `Expr`, for any that
 /// pass the provided test. The returned `Expr`'s are deduplicated and returned
 /// in order of appearance (depth first).
@@ -532,16 +541,27 @@ pub fn from_plan(
             if_not_exists: *if_not_exists,
             or_replace: *or_replace,
         })),
+        LogicalPlan::Prepare(Prepare {
+            name, data_types, ..
+        }) => Ok(LogicalPlan::Prepare(Prepare {
+            name: name.clone(),
+            data_types: data_types.clone(),
+            input: Arc::new(inputs[0].clone()),
+        })),
         LogicalPlan::CreateView(CreateView {
             name,
             or_replace,
             definition,
+            materialized,
+            location,
             ..
         }) => Ok(LogicalPlan::CreateView(CreateView {
             input: Arc::new(inputs[0].clone()),
             name: name.clone(),
             or_replace: *or_replace,
             definition: definition.clone(),
+            materialized: *materialized,
+            location: location.clone(),
         })),
         LogicalPlan::Extension(e) => Ok(LogicalPlan::Extension(Extension {
             node: e.node.from_template(expr, inputs),
@@ -556,6 +576,17 @@ pub fn from_plan(
         LogicalPlan::Distinct(Distinct { .. }) => Ok(LogicalPlan::Distinct(Distinct {
             input: Arc::new(inputs[0].clone()),
         })),
+        LogicalPlan::Unnest(Unnest { column, .. }) => Ok(LogicalPlan::Unnest(
+            Unnest::try_new(Arc::new(inputs[0].clone()), column.clone())?,
+        )),
+        LogicalPlan::Dedup(Dedup { on, keep, .. }) => {
+            Ok(LogicalPlan::Dedup(Dedup::try_new(
+                Arc::new(inputs[0].clone()),
+                expr[..on.len()].to_vec(),
+                *keep,
+                expr[on.len()..].to_vec(),
+            )?))
+        }
         LogicalPlan::Analyze(a) => {
             assert!(expr.is_empty());
             assert_eq!(inputs.len(), 1);
@@ -584,6 +615,9 @@ pub fn from_plan(
         | LogicalPlan::CreateExternalTable(_)
         | LogicalPlan::DropTable(_)
         | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshMaterializedView(_)
+        | LogicalPlan::AnalyzeTable(_)
+        | LogicalPlan::SetVariable(_)
         | LogicalPlan::CreateCatalogSchema(_)
         | LogicalPlan::CreateCatalog(_) => {
             // All of these plan types have no inputs / exprs so should not be called
@@ -769,10 +803,248 @@ pub fn can_hash(data_type: &DataType) -> bool {
     }
 }
 
+/// A set of columns whose values determine the values of other columns: rows
+/// with the same values for the `source` columns have the same values for the
+/// `target` columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionalDependence {
+    /// Indices of the determining columns
+    pub source: Vec<usize>,
+    /// Indices of the determined columns
+    pub target: Vec<usize>,
+}
+
+impl FunctionalDependence {
+    fn new(source: Vec<usize>, target: Vec<usize>) -> Self {
+        Self { source, target }
+    }
+
+    /// Maps the columns through `index`, dropping the dependence if one of its
+    /// source columns does not survive
+    fn map(self, index: impl Fn(usize) -> Option<usize>) -> Option<Self> {
+        let source = self
+            .source
+            .into_iter()
+            .map(&index)
+            .collect::<Option<Vec<_>>>()?;
+        let target = self.target.into_iter().filter_map(&index).collect();
+        Some(Self { source, target })
+    }
+}
+
+/// Returns the functional dependencies between the columns of `plan`, as
+/// indices into its schema, derived from the
+/// [`Constraints`](datafusion_common::Constraints) of the tables it scans
+/// and from its aggregations.
+pub fn functional_dependencies(plan: &LogicalPlan) -> Vec<FunctionalDependence> {
+    let all_columns = |plan: &LogicalPlan| (0..plan.schema().fields().len()).collect();
+
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            let keys = match scan.source.constraints() {
+                Some(constraints) => constraints.unique_keys(&scan.source.schema()),
+                None => return vec![],
+            };
+            keys.into_iter()
+                .filter_map(|key| {
+                    let dependence = FunctionalDependence::new(key, all_columns(plan));
+                    match &scan.projection {
+                        Some(projection) => {
+                            dependence.map(|i| projection.iter().position(|p| *p == i))
+                        }
+                        None => Some(dependence),
+                    }
+                })
+                .collect()
+        }
+        LogicalPlan::Projection(Projection { expr, input, .. }) => {
+            let input_schema = input.schema();
+            let columns = expr
+                .iter()
+                .map(|e| match e {
+                    Expr::Column(c) => input_schema.index_of_column(c).ok(),
+                    Expr::Alias(e, _) => match e.as_ref() {
+                        Expr::Column(c) => input_schema.index_of_column(c).ok(),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            functional_dependencies(input)
+                .into_iter()
+                .filter_map(|dependence| {
+                    dependence.map(|i| columns.iter().position(|c| *c == Some(i)))
+                })
+                .collect()
+        }
+        LogicalPlan::Filter(filter) => functional_dependencies(filter.input()),
+        LogicalPlan::Sort(Sort { input, .. })
+        | LogicalPlan::Limit(Limit { input, .. })
+        | LogicalPlan::Repartition(Repartition { input, .. })
+        | LogicalPlan::SubqueryAlias(SubqueryAlias { input, .. }) => {
+            functional_dependencies(input)
+        }
+        // rows padded with nulls by outer joins have nulls for both the source
+        // and the target columns, so the dependencies of each side still hold
+        LogicalPlan::Join(Join {
+            left,
+            right,
+            join_type,
+            ..
+        }) => {
+            let left_len = left.schema().fields().len();
+            let left_dependencies = functional_dependencies(left);
+            let right_dependencies = || {
+                functional_dependencies(right)
+                    .into_iter()
+                    .filter_map(|dependence| dependence.map(|i| Some(left_len + i)))
+            };
+            match join_type {
+                JoinType::LeftSemi | JoinType::LeftAnti => left_dependencies,
+                JoinType::RightSemi => functional_dependencies(right),
+                JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full => {
+                    left_dependencies
+                        .into_iter()
+                        .chain(right_dependencies())
+                        .collect()
+                }
+            }
+        }
+        LogicalPlan::Distinct(Distinct { input }) => {
+            let mut dependencies = functional_dependencies(input);
+            dependencies.push(FunctionalDependence::new(
+                all_columns(input),
+                all_columns(input),
+            ));
+            dependencies
+        }
+        LogicalPlan::Aggregate(Aggregate { group_expr, .. })
+            if !matches!(group_expr.first(), Some(Expr::GroupingSet(_))) =>
+        {
+            vec![FunctionalDependence::new(
+                (0..group_expr.len()).collect(),
+                all_columns(plan),
+            )]
+        }
+        _ => vec![],
+    }
+}
+
+/// Returns the indices of the columns of `plan` determined by the columns
+/// `source`, including themselves
+pub fn determined_columns(plan: &LogicalPlan, source: &[usize]) -> HashSet<usize> {
+    closure(&functional_dependencies(plan), source)
+}
+
+/// Returns the columns determined by `source` through `dependencies`
+fn closure(dependencies: &[FunctionalDependence], source: &[usize]) -> HashSet<usize> {
+    let mut determined = source.iter().cloned().collect::<HashSet<_>>();
+    loop {
+        let len = determined.len();
+        for dependence in dependencies {
+            if dependence.source.iter().all(|i| determined.contains(i)) {
+                determined.extend(dependence.target.iter().cloned());
+            }
+        }
+        if determined.len() == len {
+            return determined;
+        }
+    }
+}
+
+/// Returns the sets of columns, as indices into the schema of `plan`, whose
+/// values identify each row `plan` produces, as derived from the
+/// [`Constraints`](datafusion_common::Constraints) of the tables it scans.
+///
+/// An empty set means `plan` produces at most one row.
+pub fn unique_keys(plan: &LogicalPlan) -> Vec<Vec<usize>> {
+    let len = plan.schema().fields().len();
+    let dependencies = functional_dependencies(plan);
+    dependencies
+        .iter()
+        .filter(|dependence| closure(&dependencies, &dependence.source).len() == len)
+        .map(|dependence| dependence.source.clone())
+        .collect()
+}
+
+/// Returns the columns, as indices into the schema of `plan`, which can not be
+/// null because of the enforced [`Constraints`](datafusion_common::Constraints)
+/// of the tables it scans: the columns of their primary keys.
+///
+/// The nullability of the fields of the schemas is not enforced, so it is not
+/// taken into account.
+pub fn not_null_columns(plan: &LogicalPlan) -> Vec<usize> {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            let columns = match scan.source.constraints() {
+                Some(constraints) => constraints.primary_key_columns(),
+                None => return vec![],
+            };
+            match &scan.projection {
+                Some(projection) => columns
+                    .into_iter()
+                    .filter_map(|i| projection.iter().position(|p| *p == i))
+                    .collect(),
+                None => columns,
+            }
+        }
+        LogicalPlan::Projection(Projection { expr, input, .. }) => {
+            let input_schema = input.schema();
+            let not_null = not_null_columns(input);
+            expr.iter()
+                .enumerate()
+                .filter_map(|(i, e)| {
+                    let column = match e {
+                        Expr::Column(c) => c,
+                        Expr::Alias(e, _) => match e.as_ref() {
+                            Expr::Column(c) => c,
+                            _ => return None,
+                        },
+                        _ => return None,
+                    };
+                    let index = input_schema.index_of_column(column).ok()?;
+                    not_null.contains(&index).then_some(i)
+                })
+                .collect()
+        }
+        LogicalPlan::Filter(filter) => not_null_columns(filter.input()),
+        LogicalPlan::Sort(Sort { input, .. })
+        | LogicalPlan::Limit(Limit { input, .. })
+        | LogicalPlan::Repartition(Repartition { input, .. })
+        | LogicalPlan::SubqueryAlias(SubqueryAlias { input, .. })
+        | LogicalPlan::Distinct(Distinct { input }) => not_null_columns(input),
+        LogicalPlan::Join(Join {
+            left,
+            right,
+            join_type,
+            ..
+        }) => {
+            // the columns of the side(s) padded with nulls may be null
+            let left_len = left.schema().fields().len();
+            let left_columns = || not_null_columns(left).into_iter();
+            let right_columns = || not_null_columns(right).into_iter();
+            match join_type {
+                JoinType::Inner => left_columns()
+                    .chain(right_columns().map(|i| i + left_len))
+                    .collect(),
+                JoinType::Left => left_columns().collect(),
+                JoinType::Right => right_columns().map(|i| i + left_len).collect(),
+                JoinType::Full => vec![],
+                JoinType::LeftSemi | JoinType::LeftAnti => left_columns().collect(),
+                JoinType::RightSemi => right_columns().collect(),
+            }
+        }
+        _ => vec![],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{col, AggregateFunction, WindowFunction};
+    use crate::logical_plan::builder::LogicalTableSource;
+    use crate::{col, count, AggregateFunction, WindowFunction};
+    use arrow::datatypes::{Field, Schema};
+    use datafusion_common::{Constraint, Constraints};
 
     #[test]
     fn test_group_window_expr_by_sort_keys_empty_case() -> Result<()> {
@@ -790,6 +1062,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let max2 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Max),
@@ -797,6 +1070,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let min3 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Min),
@@ -804,6 +1078,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let sum4 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Sum),
@@ -811,6 +1086,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let exprs = &[max1.clone(), max2.clone(), min3.clone(), sum4.clone()];
         let result = group_window_expr_by_sort_keys(exprs)?;
@@ -844,6 +1120,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![age_asc.clone(), name_desc.clone()],
             window_frame: None,
+            ignore_nulls: false,
         };
         let max2 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Max),
@@ -851,6 +1128,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![],
             window_frame: None,
+            ignore_nulls: false,
         };
         let min3 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Min),
@@ -858,6 +1136,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![age_asc.clone(), name_desc.clone()],
             window_frame: None,
+            ignore_nulls: false,
         };
         let sum4 = Expr::WindowFunction {
             fun: WindowFunction::AggregateFunction(AggregateFunction::Sum),
@@ -865,6 +1144,7 @@ mod tests {
             partition_by: vec![],
             order_by: vec![name_desc.clone(), age_asc.clone(), created_at_desc.clone()],
             window_frame: None,
+            ignore_nulls: false,
         };
         // FIXME use as_ref
         let exprs = &[max1.clone(), max2.clone(), min3.clone(), sum4.clone()];
@@ -903,6 +1183,7 @@ mod tests {
                     },
                 ],
                 window_frame: None,
+                ignore_nulls: false,
             },
             Expr::WindowFunction {
                 fun: WindowFunction::AggregateFunction(AggregateFunction::Sum),
@@ -926,6 +1207,7 @@ mod tests {
                     },
                 ],
                 window_frame: None,
+                ignore_nulls: false,
             },
         ];
         let expected = vec![
@@ -949,4 +1231,102 @@ mod tests {
         assert_eq!(expected, result);
         Ok(())
     }
+
+    #[test]
+    fn test_unique_keys() -> Result<()> {
+        let scan = || {
+            let schema = Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("name", DataType::Utf8, true),
+            ]);
+            let source = LogicalTableSource::new(Arc::new(schema)).with_constraints(
+                Constraints::new(vec![Constraint::PrimaryKey(vec![0])]),
+            );
+            LogicalPlanBuilder::scan("t", Arc::new(source), None)
+        };
+
+        let plan = scan()?
+            .project(vec![col("name"), col("id").alias("key")])?
+            .build()?;
+        assert_eq!(unique_keys(&plan), vec![vec![1]]);
+
+        let plan = scan()?.project(vec![col("name")])?.build()?;
+        assert!(unique_keys(&plan).is_empty());
+
+        let plan = scan()?
+            .aggregate(vec![col("name")], vec![count(col("id"))])?
+            .build()?;
+        assert_eq!(unique_keys(&plan), vec![vec![0]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_null_columns() -> Result<()> {
+        let scan = |name: &str| {
+            let schema = Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("name", DataType::Utf8, false),
+            ]);
+            let source = LogicalTableSource::new(Arc::new(schema)).with_constraints(
+                Constraints::new(vec![Constraint::PrimaryKey(vec![0])]),
+            );
+            LogicalPlanBuilder::scan(name, Arc::new(source), None)
+        };
+
+        // the nullability of `name` is not enforced
+        let plan = scan("t")?
+            .project(vec![col("name"), col("id").alias("key")])?
+            .build()?;
+        assert_eq!(not_null_columns(&plan), vec![1]);
+
+        let join = |join_type| -> Result<LogicalPlan> {
+            scan("l")?
+                .join(
+                    &scan("r")?.build()?,
+                    join_type,
+                    (vec!["l.id"], vec!["r.id"]),
+                    None,
+                )?
+                .build()
+        };
+        assert_eq!(not_null_columns(&join(JoinType::Inner)?), vec![0, 2]);
+        assert_eq!(not_null_columns(&join(JoinType::Left)?), vec![0]);
+        assert_eq!(not_null_columns(&join(JoinType::Right)?), vec![2]);
+        assert!(not_null_columns(&join(JoinType::Full)?).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_functional_dependencies_through_join() -> Result<()> {
+        let scan = |name: &str| {
+            let schema = Schema::new(vec![
+                Field::new(&format!("{}_id", name), DataType::Int32, false),
+                Field::new(&format!("{}_name", name), DataType::Utf8, true),
+            ]);
+            let source = LogicalTableSource::new(Arc::new(schema)).with_constraints(
+                Constraints::new(vec![Constraint::PrimaryKey(vec![0])]),
+            );
+            LogicalPlanBuilder::scan(name, Arc::new(source), None)?.build()
+        };
+
+        let plan = LogicalPlanBuilder::from(scan("a")?)
+            .join(
+                &scan("b")?,
+                JoinType::Left,
+                (vec!["a_id"], vec!["b_id"]),
+                None,
+            )?
+            .build()?;
+        assert_eq!(
+            functional_dependencies(&plan),
+            vec![
+                FunctionalDependence::new(vec![0], vec![0, 1]),
+                FunctionalDependence::new(vec![2], vec![2, 3]),
+            ]
+        );
+        assert_eq!(determined_columns(&plan, &[2]), HashSet::from([2, 3]));
+        // neither side identifies the rows of the join
+        assert!(unique_keys(&plan).is_empty());
+        Ok(())
+    }
 }
@@ -226,7 +226,13 @@ fn optimize(
         | LogicalPlan::CreateCatalog(_)
         | LogicalPlan::DropTable(_)
         | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshMaterializedView(_)
+        | LogicalPlan::AnalyzeTable(_)
+        | LogicalPlan::SetVariable(_)
+        | LogicalPlan::Prepare(_)
         | LogicalPlan::Distinct(_)
+        | LogicalPlan::Unnest(_)
+        | LogicalPlan::Dedup(_)
         | LogicalPlan::Extension { .. } => {
             // apply the optimization to all inputs of the plan
             let expr = plan.expressions();
@@ -385,7 +391,11 @@ enum VisitRecord {
 
 impl ExprIdentifierVisitor<'_> {
     fn desc_expr(expr: &Expr) -> String {
-        format!("{}", expr)
+        match expr {
+            // subqueries are displayed without their plans
+            Expr::ScalarSubquery(subquery) => format!("({:?})", subquery.subquery),
+            _ => format!("{}", expr),
+        }
     }
 
     /// Find the first `EnterMark` in the stack, and accumulates every `ExprItem`
@@ -428,6 +438,7 @@ impl ExpressionVisitor for ExprIdentifierVisitor<'_> {
             Expr::Literal(..)
                 | Expr::Column(..)
                 | Expr::ScalarVariable(..)
+                | Expr::Placeholder { .. }
                 | Expr::Alias(..)
                 | Expr::Sort { .. }
                 | Expr::Wildcard
@@ -0,0 +1,235 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to remove `DISTINCT` and `GROUP BY` on a plan whose rows
+//! are already distinct, such as a projection including the primary key of a
+//! table.
+use crate::utils::optimize_children;
+use crate::{OptimizerConfig, OptimizerRule};
+use datafusion_common::Result;
+use datafusion_expr::{
+    aggregate_function::AggregateFunction,
+    logical_plan::{Aggregate, Distinct, LogicalPlan, Projection},
+    utils::unique_keys,
+    Expr,
+};
+use std::collections::HashSet;
+
+/// Optimization rule that removes
+///
+/// * a [LogicalPlan::Distinct] whose input has a unique key, as derived from
+///   the constraints of the scanned tables
+/// * a [LogicalPlan::Aggregate] grouping by columns including a unique key of
+///   its input, whose aggregates are only `MIN` and `MAX`: each group is a
+///   single row, so the aggregate is replaced by a projection
+///
+/// The constraints of the tables are not enforced, so the rule only applies
+/// when [`OptimizerConfig::rely_on_constraints`] is set.
+#[derive(Default)]
+pub struct EliminateDistinct;
+
+impl EliminateDistinct {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for EliminateDistinct {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        if !optimizer_config.rely_on_constraints() {
+            return Ok(plan.clone());
+        }
+        match plan {
+            LogicalPlan::Distinct(Distinct { input })
+                if !unique_keys(input).is_empty() =>
+            {
+                self.optimize(input, optimizer_config)
+            }
+            LogicalPlan::Aggregate(aggregate) => {
+                match aggregate_to_projection(aggregate)? {
+                    Some(plan) => optimize_children(self, &plan, optimizer_config),
+                    None => optimize_children(self, plan, optimizer_config),
+                }
+            }
+            _ => optimize_children(self, plan, optimizer_config),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "eliminate_distinct"
+    }
+}
+
+/// Returns a projection computing the same rows as `aggregate` if each of its
+/// groups is a single row of its input, or `None` otherwise
+fn aggregate_to_projection(aggregate: &Aggregate) -> Result<Option<LogicalPlan>> {
+    if aggregate.group_expr.is_empty() {
+        return Ok(None);
+    }
+
+    let input_schema = aggregate.input.schema();
+    let mut group_columns = HashSet::new();
+    for expr in &aggregate.group_expr {
+        match expr {
+            Expr::Column(c) => match input_schema.index_of_column(c) {
+                Ok(index) => group_columns.insert(index),
+                Err(_) => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+    }
+    if !unique_keys(&aggregate.input)
+        .iter()
+        .any(|key| key.iter().all(|i| group_columns.contains(i)))
+    {
+        return Ok(None);
+    }
+
+    // the minimum and maximum of a single value are that value
+    let mut expr = aggregate.group_expr.clone();
+    let aggr_fields = &aggregate.schema.fields()[aggregate.group_expr.len()..];
+    for (aggr_expr, field) in aggregate.aggr_expr.iter().zip(aggr_fields) {
+        match aggr_expr {
+            Expr::AggregateFunction {
+                fun: AggregateFunction::Min | AggregateFunction::Max,
+                args,
+                filter: None,
+                ..
+            } if args.len() == 1 => expr.push(args[0].clone().alias(field.name())),
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(LogicalPlan::Projection(
+        Projection::try_new_with_schema(
+            expr,
+            aggregate.input.clone(),
+            aggregate.schema.clone(),
+            None,
+        )?,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_common::{Constraint, Constraints};
+    use datafusion_expr::{col, count, max, LogicalPlanBuilder};
+
+    fn scan() -> Result<LogicalPlanBuilder> {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("email", DataType::Utf8, true),
+            Field::new("name", DataType::Utf8, false),
+        ]);
+        scan_with_constraints(
+            "users",
+            schema,
+            Constraints::new(vec![
+                Constraint::PrimaryKey(vec![0]),
+                Constraint::Unique(vec![1]),
+            ]),
+        )
+    }
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let optimized_plan = EliminateDistinct::new()
+            .optimize(
+                plan,
+                &mut OptimizerConfig::new().with_rely_on_constraints(true),
+            )
+            .expect("failed to optimize plan");
+        assert_eq!(format!("{:?}", optimized_plan), expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn distinct_with_primary_key() -> Result<()> {
+        let plan = scan()?
+            .project(vec![col("id"), col("name")])?
+            .distinct()?
+            .build()?;
+        let expected = "Projection: users.id, users.name\
+        \n  TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn distinct_without_key() -> Result<()> {
+        // several users may have the same name, or no email
+        let plan = scan()?
+            .project(vec![col("email"), col("name")])?
+            .distinct()?
+            .build()?;
+        let expected = "Distinct:\
+        \n  Projection: users.email, users.name\
+        \n    TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn nested_distinct() -> Result<()> {
+        let plan = scan()?
+            .project(vec![col("name")])?
+            .distinct()?
+            .distinct()?
+            .build()?;
+        let expected = "Distinct:\
+        \n  Projection: users.name\
+        \n    TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_on_primary_key() -> Result<()> {
+        let plan = scan()?
+            .aggregate(vec![col("id")], vec![max(col("name"))])?
+            .build()?;
+        let expected = "Projection: users.id, users.name AS MAX(users.name)\
+        \n  TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+
+        // a count is not the value of the single row
+        let plan = scan()?
+            .aggregate(vec![col("id")], vec![count(col("name"))])?
+            .build()?;
+        let expected = "Aggregate: groupBy=[[users.id]], aggr=[[COUNT(users.name)]]\
+        \n  TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_without_key() -> Result<()> {
+        let plan = scan()?
+            .aggregate(vec![col("name")], vec![max(col("id"))])?
+            .build()?;
+        let expected = "Aggregate: groupBy=[[users.name]], aggr=[[MAX(users.id)]]\
+        \n  TableScan: users";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
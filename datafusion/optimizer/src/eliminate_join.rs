@@ -0,0 +1,513 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to remove joins which neither filter nor duplicate the
+//! rows of one of their inputs, such as inner joins on a foreign key to the
+//! primary key of a table when no column of that table is used.
+use crate::utils::optimize_children;
+use crate::{OptimizerConfig, OptimizerRule};
+use datafusion_common::{Constraint, Result};
+use datafusion_expr::{
+    logical_plan::{Join, JoinType, LogicalPlan, Projection, SubqueryAlias, TableScan},
+    utils::{exprlist_to_columns, unique_keys},
+    Expr,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Optimization rule that removes
+///
+/// * inner joins on a non nullable foreign key referencing the primary key of
+///   a table whose columns are not used, such as in
+///   `SELECT orders.* FROM orders JOIN customers ON orders.customer_id = customers.id`:
+///   every row of `orders` matches exactly one row of `customers`
+/// * left joins on a unique key of their right input whose columns are not
+///   used: every left row is kept, and matches at most one right row
+/// * self joins on a unique key of a table, such as in
+///   `SELECT a.x, b.y FROM t a JOIN t b ON a.id = b.id`: every row only
+///   matches itself
+///
+/// The constraints of the tables are not enforced, so the rule only applies
+/// when [`OptimizerConfig::rely_on_constraints`] is set.
+#[derive(Default)]
+pub struct EliminateJoin;
+
+impl EliminateJoin {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for EliminateJoin {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        if !optimizer_config.rely_on_constraints() {
+            return Ok(plan.clone());
+        }
+        match plan {
+            LogicalPlan::Projection(projection) => {
+                if let LogicalPlan::Join(join) = projection.input.as_ref() {
+                    if is_redundant_join(projection, join)? {
+                        let plan =
+                            LogicalPlan::Projection(Projection::try_new_with_schema(
+                                projection.expr.clone(),
+                                join.left.clone(),
+                                projection.schema.clone(),
+                                projection.alias.clone(),
+                            )?);
+                        return optimize_children(self, &plan, optimizer_config);
+                    }
+                }
+            }
+            LogicalPlan::Join(join) => {
+                if let Some(plan) = collapse_self_join(join)? {
+                    return optimize_children(self, &plan, optimizer_config);
+                }
+            }
+            _ => {}
+        }
+        optimize_children(self, plan, optimizer_config)
+    }
+
+    fn name(&self) -> &str {
+        "eliminate_join"
+    }
+}
+
+/// Returns true if `join` neither filters nor duplicates the rows of its
+/// left input, whose columns are the only ones used by `projection`
+fn is_redundant_join(projection: &Projection, join: &Join) -> Result<bool> {
+    if join.on.is_empty() {
+        return Ok(false);
+    }
+
+    let mut columns = HashSet::new();
+    exprlist_to_columns(&projection.expr, &mut columns)?;
+    let (left_schema, right_schema) = (join.left.schema(), join.right.schema());
+    if !columns.iter().all(|c| {
+        left_schema.index_of_column(c).is_ok() && right_schema.index_of_column(c).is_err()
+    }) {
+        return Ok(false);
+    }
+
+    match join.join_type {
+        // the join filter can only remove matches, which are at most one
+        JoinType::Left => Ok(is_unique_on_join_keys(join)),
+        JoinType::Inner if join.filter.is_none() => is_foreign_key_join(join),
+        _ => Ok(false),
+    }
+}
+
+/// Returns true if every row of the left input of `join` matches at most one
+/// row of its right input
+fn is_unique_on_join_keys(join: &Join) -> bool {
+    let right_schema = join.right.schema();
+    let right_keys = join
+        .on
+        .iter()
+        .filter_map(|(_, r)| right_schema.index_of_column(r).ok())
+        .collect::<HashSet<_>>();
+    unique_keys(&join.right)
+        .iter()
+        .any(|key| key.iter().all(|i| right_keys.contains(i)))
+}
+
+/// Returns true if `join` is on a non nullable foreign key of its left input
+/// referencing a unique key of its right input, so that every left row
+/// matches exactly one right row
+fn is_foreign_key_join(join: &Join) -> Result<bool> {
+    let (left_schema, right_schema) = (join.left.schema(), join.right.schema());
+
+    // filters on the left side keep the foreign key valid, while filters
+    // on the right side could remove the referenced rows
+    let (left, right) = match (find_scan(&join.left, true), find_scan(&join.right, false))
+    {
+        (Some(left), Some(right))
+            if right.filters.is_empty() && right.fetch.is_none() =>
+        {
+            (left, right)
+        }
+        _ => return Ok(false),
+    };
+
+    // the join keys, as (left table column index, right table column name)
+    let mut keys = HashSet::new();
+    for (l, r) in &join.on {
+        let (l, r) = match (
+            left_schema.index_of_column(l),
+            right_schema.index_of_column(r),
+        ) {
+            (Ok(l), Ok(r)) => (l, r),
+            _ => return Ok(false),
+        };
+        let r = right_schema.field(r).name().clone();
+        keys.insert((table_index(left, l), r));
+    }
+
+    let left_constraints = match left.source.constraints() {
+        Some(constraints) => constraints,
+        None => return Ok(false),
+    };
+    let left_table_schema = left.source.schema();
+    let references_right = left_constraints.iter().any(|constraint| match constraint {
+        Constraint::ForeignKey {
+            columns,
+            referenced_table,
+            referenced_columns,
+        } => {
+            same_table(referenced_table, &right.table_name)
+                && columns
+                    .iter()
+                    .all(|i| !left_table_schema.field(*i).is_nullable())
+                && columns
+                    .iter()
+                    .cloned()
+                    .zip(referenced_columns.iter().cloned())
+                    .collect::<HashSet<_>>()
+                    == keys
+        }
+        _ => false,
+    });
+    if !references_right {
+        return Ok(false);
+    }
+
+    // every left row matches at most one right row
+    let right_table_schema = right.source.schema();
+    let right_columns = keys
+        .iter()
+        .filter_map(|(_, name)| right_table_schema.index_of(name).ok())
+        .collect::<HashSet<_>>();
+    Ok(right
+        .source
+        .constraints()
+        .map(|constraints| {
+            constraints
+                .unique_keys(&right_table_schema)
+                .iter()
+                .any(|key| key.iter().all(|i| right_columns.contains(i)))
+        })
+        .unwrap_or(false))
+}
+
+/// Replaces an inner join of a table with itself on a unique key by a
+/// projection of its left input, or returns `None` if `join` is not such a
+/// join
+fn collapse_self_join(join: &Join) -> Result<Option<LogicalPlan>> {
+    if join.join_type != JoinType::Inner || join.filter.is_some() || join.on.is_empty() {
+        return Ok(None);
+    }
+    let (left, right) =
+        match (find_scan(&join.left, false), find_scan(&join.right, false)) {
+            (Some(left), Some(right))
+                if left.table_name == right.table_name
+                    && left.filters.is_empty()
+                    && right.filters.is_empty()
+                    && left.fetch.is_none()
+                    && right.fetch.is_none() =>
+            {
+                (left, right)
+            }
+            _ => return Ok(None),
+        };
+
+    // the table columns both sides are joined on
+    let (left_schema, right_schema) = (join.left.schema(), join.right.schema());
+    let mut columns = HashSet::new();
+    for (l, r) in &join.on {
+        match (
+            left_schema.index_of_column(l),
+            right_schema.index_of_column(r),
+        ) {
+            (Ok(l), Ok(r)) if table_index(left, l) == table_index(right, r) => {
+                columns.insert(table_index(left, l));
+            }
+            _ => return Ok(None),
+        }
+    }
+    let table_schema = left.source.schema();
+    let is_unique = left
+        .source
+        .constraints()
+        .map(|constraints| {
+            constraints
+                .unique_keys(&table_schema)
+                .iter()
+                .any(|key| key.iter().all(|i| columns.contains(i)))
+        })
+        .unwrap_or(false);
+    if !is_unique {
+        return Ok(None);
+    }
+
+    // the columns of the right side are read from the same row on the left
+    let left_fields = left_schema.fields().len();
+    let mut expr = left_schema
+        .fields()
+        .iter()
+        .map(|f| Expr::Column(f.qualified_column()))
+        .collect::<Vec<_>>();
+    for r in 0..right_schema.fields().len() {
+        let index = table_index(right, r);
+        match (0..left_fields).find(|l| table_index(left, *l) == index) {
+            Some(l) => expr.push(Expr::Column(left_schema.field(l).qualified_column())),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(LogicalPlan::Projection(
+        Projection::try_new_with_schema(
+            expr,
+            join.left.clone(),
+            join.schema.clone(),
+            None,
+        )?,
+    )))
+}
+
+/// Returns the [`TableScan`] read by `plan` through aliases, and filters if
+/// `through_filters` is true
+fn find_scan(plan: &Arc<LogicalPlan>, through_filters: bool) -> Option<&TableScan> {
+    match plan.as_ref() {
+        LogicalPlan::TableScan(scan) => Some(scan),
+        LogicalPlan::SubqueryAlias(SubqueryAlias { input, .. }) => {
+            find_scan(input, through_filters)
+        }
+        LogicalPlan::Filter(filter) if through_filters => {
+            find_scan(filter.input(), through_filters)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the index in the table schema of the `index`th column of `scan`
+fn table_index(scan: &TableScan, index: usize) -> usize {
+    match &scan.projection {
+        Some(projection) => projection[index],
+        None => index,
+    }
+}
+
+/// Returns true if the table names are equal, ignoring any catalog or schema
+fn same_table(a: &str, b: &str) -> bool {
+    let table = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+    table(a) == table(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_common::{Column, Constraints};
+    use datafusion_expr::{col, LogicalPlanBuilder};
+
+    fn customers() -> Result<LogicalPlan> {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, false),
+        ]);
+        scan_with_constraints(
+            "customers",
+            schema,
+            Constraints::new(vec![Constraint::PrimaryKey(vec![0])]),
+        )?
+        .build()
+    }
+
+    fn orders(nullable_customer: bool) -> Result<LogicalPlan> {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("customer_id", DataType::Int32, nullable_customer),
+        ]);
+        scan_with_constraints(
+            "orders",
+            schema,
+            Constraints::new(vec![
+                Constraint::PrimaryKey(vec![0]),
+                Constraint::ForeignKey {
+                    columns: vec![1],
+                    referenced_table: "customers".to_string(),
+                    referenced_columns: vec!["id".to_string()],
+                },
+            ]),
+        )?
+        .build()
+    }
+
+    fn join_orders_customers(
+        orders: LogicalPlan,
+        projection: Vec<&str>,
+    ) -> Result<LogicalPlan> {
+        LogicalPlanBuilder::from(orders)
+            .join(
+                &customers()?,
+                JoinType::Inner,
+                (
+                    vec![Column::from_qualified_name("orders.customer_id")],
+                    vec![Column::from_qualified_name("customers.id")],
+                ),
+                None,
+            )?
+            .project(projection.into_iter().map(col))?
+            .build()
+    }
+
+    fn left_join_customers(projection: Vec<&str>) -> Result<LogicalPlan> {
+        LogicalPlanBuilder::from(orders(true)?)
+            .join(
+                &customers()?,
+                JoinType::Left,
+                (
+                    vec![Column::from_qualified_name("orders.customer_id")],
+                    vec![Column::from_qualified_name("customers.id")],
+                ),
+                None,
+            )?
+            .project(projection.into_iter().map(col))?
+            .build()
+    }
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let optimized_plan = EliminateJoin::new()
+            .optimize(
+                plan,
+                &mut OptimizerConfig::new().with_rely_on_constraints(true),
+            )
+            .expect("failed to optimize plan");
+        assert_eq!(format!("{:?}", optimized_plan), expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn join_on_foreign_key() -> Result<()> {
+        let plan = join_orders_customers(orders(false)?, vec!["orders.id"])?;
+        let expected = "Projection: orders.id\
+        \n  TableScan: orders";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn join_using_referenced_columns() -> Result<()> {
+        let plan = join_orders_customers(orders(false)?, vec!["customers.name"])?;
+        let expected = "Projection: customers.name\
+        \n  Inner Join: orders.customer_id = customers.id\
+        \n    TableScan: orders\
+        \n    TableScan: customers";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn join_on_nullable_foreign_key() -> Result<()> {
+        // orders without a customer are removed by the join
+        let plan = join_orders_customers(orders(true)?, vec!["orders.id"])?;
+        let expected = "Projection: orders.id\
+        \n  Inner Join: orders.customer_id = customers.id\
+        \n    TableScan: orders\
+        \n    TableScan: customers";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn left_join_on_unique_key() -> Result<()> {
+        // orders without a customer are kept by the join
+        let plan = left_join_customers(vec!["orders.id"])?;
+        let expected = "Projection: orders.id\
+        \n  TableScan: orders";
+        assert_optimized_plan_eq(&plan, expected);
+
+        let plan = left_join_customers(vec!["orders.id", "customers.name"])?;
+        let expected = "Projection: orders.id, customers.name\
+        \n  Left Join: orders.customer_id = customers.id\
+        \n    TableScan: orders\
+        \n    TableScan: customers";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn left_join_on_non_unique_key() -> Result<()> {
+        // a customer may have several orders
+        let plan = LogicalPlanBuilder::from(customers()?)
+            .join(
+                &orders(false)?,
+                JoinType::Left,
+                (
+                    vec![Column::from_qualified_name("customers.id")],
+                    vec![Column::from_qualified_name("orders.customer_id")],
+                ),
+                None,
+            )?
+            .project(vec![col("customers.name")])?
+            .build()?;
+        let expected = "Projection: customers.name\
+        \n  Left Join: customers.id = orders.customer_id\
+        \n    TableScan: customers\
+        \n    TableScan: orders";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn self_join_on_primary_key() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(customers()?)
+            .alias("a")?
+            .join(
+                &LogicalPlanBuilder::from(customers()?).alias("b")?.build()?,
+                JoinType::Inner,
+                (
+                    vec![Column::from_qualified_name("a.id")],
+                    vec![Column::from_qualified_name("b.id")],
+                ),
+                None,
+            )?
+            .project(vec![col("a.id"), col("b.name")])?
+            .build()?;
+        let expected = "Projection: a.id, b.name\
+        \n  Projection: a.id, a.name, a.id, a.name\
+        \n    SubqueryAlias: a\
+        \n      TableScan: customers";
+        assert_optimized_plan_eq(&plan, expected);
+
+        // rows with the same name may differ
+        let plan = LogicalPlanBuilder::from(customers()?)
+            .alias("a")?
+            .join(
+                &LogicalPlanBuilder::from(customers()?).alias("b")?.build()?,
+                JoinType::Inner,
+                (
+                    vec![Column::from_qualified_name("a.name")],
+                    vec![Column::from_qualified_name("b.name")],
+                ),
+                None,
+            )?
+            .build()?;
+        let expected = "Inner Join: a.name = b.name\
+        \n  SubqueryAlias: a\
+        \n    TableScan: customers\
+        \n  SubqueryAlias: b\
+        \n    TableScan: customers";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
@@ -18,14 +18,14 @@ use crate::{utils, OptimizerConfig, OptimizerRule};
 use datafusion_common::{Column, DFSchema, DataFusionError, Result};
 use datafusion_expr::{
     and, col,
-    expr::BinaryExpr,
+    expr::{BinaryExpr, GroupingSet},
     expr_rewriter::{replace_col, ExprRewritable, ExprRewriter},
     logical_plan::{
         Aggregate, CrossJoin, Join, JoinType, Limit, LogicalPlan, Projection, TableScan,
-        Union,
+        Union, Window,
     },
     or,
-    utils::{expr_to_columns, exprlist_to_columns, from_plan},
+    utils::{expr_to_columns, exprlist_to_columns, from_plan, grouping_set_to_exprlist},
     Expr, Operator, TableProviderFilterPushDown,
 };
 use std::collections::{HashMap, HashSet};
@@ -525,6 +525,55 @@ fn optimize_join(
     }
 }
 
+/// Pushes the predicates of `state` that only reference `pushable` columns
+/// down to the input of `plan`, with the columns of `replace_map` replaced,
+/// and adds a [LogicalPlan::Filter] above `plan` for the other predicates.
+fn push_down_partially(
+    state: State,
+    pushable: impl Fn(&Column) -> bool,
+    replace_map: &HashMap<String, Expr>,
+    plan: &LogicalPlan,
+) -> Result<LogicalPlan> {
+    let (to_push, to_keep): (Vec<_>, Vec<_>) = state
+        .filters
+        .into_iter()
+        .partition(|(_, columns)| !columns.is_empty() && columns.iter().all(&pushable));
+
+    let mut push_state = State::default();
+    for (predicate, _) in to_push {
+        let predicate = replace_cols_by_name(predicate, replace_map)?;
+        let mut columns = HashSet::new();
+        expr_to_columns(&predicate, &mut columns)?;
+        push_state.filters.push((predicate, columns));
+    }
+    let plan = push_down(&push_state, plan)?;
+
+    if to_keep.is_empty() {
+        Ok(plan)
+    } else {
+        let predicates = to_keep.iter().map(|(p, _)| p).collect::<Vec<_>>();
+        utils::add_filter(plan, &predicates)
+    }
+}
+
+/// Returns the expressions that are part of every set of `grouping_set`
+fn common_grouping_exprs(grouping_set: &GroupingSet) -> Vec<Expr> {
+    match grouping_set {
+        GroupingSet::GroupingSets(sets) => sets
+            .first()
+            .map(|first| {
+                first
+                    .iter()
+                    .filter(|e| sets.iter().all(|set| set.contains(e)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default(),
+        // both include the empty grouping set
+        GroupingSet::Rollup(_) | GroupingSet::Cube(_) => vec![],
+    }
+}
+
 fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
     match plan {
         LogicalPlan::Explain { .. } => {
@@ -586,51 +635,126 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
             let new_input = optimize(input, state)?;
             Ok(from_plan(plan, expr, &[new_input])?)
         }
-        LogicalPlan::Aggregate(Aggregate { aggr_expr, .. }) => {
+        LogicalPlan::Aggregate(Aggregate {
+            group_expr,
+            aggr_expr,
+            schema,
+            ..
+        }) => {
             // An aggregate's aggreagate columns are _not_ filter-commutable => collect these:
             // * columns whose aggregation expression depends on
             // * the aggregation columns themselves
-
-            // construct set of columns that `aggr_expr` depends on
+            // * the grouping keys that some grouping sets replace by nulls
             let mut used_columns = HashSet::new();
             exprlist_to_columns(aggr_expr, &mut used_columns)?;
 
-            let agg_columns = aggr_expr
+            let group_exprs = grouping_set_to_exprlist(group_expr)?;
+            let common_exprs = match group_expr.first() {
+                Some(Expr::GroupingSet(grouping_set)) => {
+                    common_grouping_exprs(grouping_set)
+                }
+                _ => group_exprs.clone(),
+            };
+
+            // A filter on the other grouping keys selects whole groups, so it
+            // commutes with the aggregate once the keys are replaced by their
+            // expressions
+            let mut replace_map = HashMap::new();
+            for (i, field) in schema.fields().iter().enumerate() {
+                match group_exprs.get(i) {
+                    Some(expr) if common_exprs.contains(expr) => {
+                        let expr = match expr {
+                            Expr::Alias(expr, _) => expr.as_ref().clone(),
+                            expr => expr.clone(),
+                        };
+                        // Convert both qualified and unqualified fields
+                        replace_map.insert(field.name().clone(), expr.clone());
+                        replace_map.insert(field.qualified_name(), expr);
+                    }
+                    _ => {
+                        used_columns.insert(field.qualified_column());
+                        used_columns.insert(Column::from_name(field.name()));
+                    }
+                }
+            }
+
+            push_down_partially(state, |c| !used_columns.contains(c), &replace_map, plan)
+        }
+        LogicalPlan::Window(Window { window_expr, .. }) => {
+            // A filter on the partition keys of all the window functions selects
+            // whole partitions, so it commutes with the window
+            let pushable = window_expr
                 .iter()
-                .map(|x| Ok(Column::from_name(x.display_name()?)))
-                .collect::<Result<HashSet<_>>>()?;
-            used_columns.extend(agg_columns);
+                .map(|expr| {
+                    let expr = match expr {
+                        Expr::Alias(expr, _) => expr.as_ref(),
+                        expr => expr,
+                    };
+                    match expr {
+                        Expr::WindowFunction { partition_by, .. } => partition_by
+                            .iter()
+                            .filter_map(|e| match e {
+                                Expr::Column(c) => Some(c.clone()),
+                                _ => None,
+                            })
+                            .collect::<HashSet<_>>(),
+                        _ => HashSet::new(),
+                    }
+                })
+                .reduce(|a, b| a.intersection(&b).cloned().collect())
+                .unwrap_or_default();
 
-            issue_filters(state, used_columns, plan)
+            push_down_partially(state, |c| pushable.contains(c), &HashMap::new(), plan)
         }
-        LogicalPlan::Sort { .. } => {
-            // sort is filter-commutable
+        LogicalPlan::Sort { .. } | LogicalPlan::Distinct { .. } => {
+            // sort and distinct are filter-commutable
             push_down(&state, plan)
         }
-        LogicalPlan::Union(Union {
-            inputs: _,
-            schema,
-            alias: _,
-        }) => {
-            // union changing all qualifiers while building logical plan so we need
-            // to rewrite filters to push unqualified columns to inputs
-            let projection = schema
-                .fields()
+        LogicalPlan::Union(Union { inputs, schema, .. }) => {
+            // union outputs the columns of each input by position, under the names
+            // of the first input, so filters are rewritten for each input, with
+            // unqualified columns unless their name is ambiguous in the input
+            let new_inputs = inputs
                 .iter()
-                .map(|field| (field.qualified_name(), col(field.name())))
-                .collect::<HashMap<_, _>>();
+                .map(|input| {
+                    let input_schema = input.schema();
+                    let projection = schema
+                        .fields()
+                        .iter()
+                        .zip(input_schema.fields())
+                        .flat_map(|(field, input_field)| {
+                            let name = input_field.name();
+                            let ambiguous = input_schema
+                                .fields()
+                                .iter()
+                                .filter(|f| f.name() == name)
+                                .count()
+                                > 1;
+                            let expr = if ambiguous {
+                                Expr::Column(input_field.qualified_column())
+                            } else {
+                                col(name)
+                            };
+                            [
+                                (field.name().clone(), expr.clone()),
+                                (field.qualified_name(), expr),
+                            ]
+                        })
+                        .collect::<HashMap<_, _>>();
 
-            // rewriting predicate expressions using unqualified names as replacements
-            if !projection.is_empty() {
-                for (predicate, columns) in state.filters.iter_mut() {
-                    *predicate = replace_cols_by_name(predicate.clone(), &projection)?;
+                    let mut state = state.clone();
+                    for (predicate, columns) in state.filters.iter_mut() {
+                        *predicate =
+                            replace_cols_by_name(predicate.clone(), &projection)?;
 
-                    columns.clear();
-                    expr_to_columns(predicate, columns)?;
-                }
-            }
+                        columns.clear();
+                        expr_to_columns(predicate, columns)?;
+                    }
+                    optimize(input, state)
+                })
+                .collect::<Result<Vec<_>>>()?;
 
-            push_down(&state, plan)
+            from_plan(plan, &plan.expressions(), &new_inputs)
         }
         LogicalPlan::Limit(Limit { input, .. }) => {
             // limit is _not_ filter-commutable => collect all columns from its input
@@ -737,6 +861,7 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
             filters,
             projection,
             table_name,
+            ordering,
             fetch,
         }) => {
             let mut used_columns = HashSet::new();
@@ -773,6 +898,7 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                     projected_schema: projected_schema.clone(),
                     table_name: table_name.clone(),
                     filters: new_filters,
+                    ordering: ordering.clone(),
                     fetch: *fetch,
                 }),
             )
@@ -837,13 +963,14 @@ fn replace_cols_by_name(e: Expr, replace_map: &HashMap<String, Expr>) -> Result<
 mod tests {
     use super::*;
     use crate::test::*;
-    use arrow::datatypes::SchemaRef;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
     use async_trait::async_trait;
     use datafusion_common::DFSchema;
     use datafusion_expr::{
         and, col, in_list, in_subquery, lit,
         logical_plan::{builder::union_with_alias, JoinType},
-        sum, Expr, LogicalPlanBuilder, Operator, TableSource, TableType,
+        sum, BuiltInWindowFunction, Expr, LogicalPlanBuilder, Operator, TableSource,
+        TableType, WindowFunction,
     };
     use std::sync::Arc;
 
@@ -1173,6 +1300,130 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn filter_move_agg_expr() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .aggregate(
+                vec![(col("a") + col("b")).alias("ab")],
+                vec![sum(col("c")).alias("total")],
+            )?
+            .filter(col("ab").gt(lit(10i64)).and(col("total").gt(lit(5i64))))?
+            .build()?;
+        // filter of the key is rewritten with its expression, as in a HAVING clause
+        let expected = "\
+            Filter: total > Int64(5)\
+            \n  Aggregate: groupBy=[[test.a + test.b AS ab]], aggr=[[SUM(test.c) AS total]]\
+            \n    Filter: test.a + test.b > Int64(10)\
+            \n      TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_grouping_sets() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let grouping_set = Expr::GroupingSet(GroupingSet::GroupingSets(vec![
+            vec![col("a"), col("b")],
+            vec![col("a")],
+        ]));
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .aggregate(vec![grouping_set], vec![sum(col("c"))])?
+            .filter(col("a").eq(lit(1i64)).and(col("b").is_null()))?
+            .build()?;
+        // `b` is null in the rows of the second grouping set
+        let expected = "\
+            Filter: test.b IS NULL\
+            \n  Aggregate: groupBy=[[GROUPING SETS ((test.a, test.b), (test.a))]], aggr=[[SUM(test.c)]]\
+            \n    Filter: test.a = Int64(1)\
+            \n      TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .aggregate(
+                vec![Expr::GroupingSet(GroupingSet::Rollup(vec![col("a")]))],
+                vec![sum(col("c"))],
+            )?
+            .filter(col("a").is_null())?
+            .build()?;
+        let expected = "\
+            Filter: test.a IS NULL\
+            \n  Aggregate: groupBy=[[ROLLUP (test.a)]], aggr=[[SUM(test.c)]]\
+            \n    TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_window_partition_keys() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let row_number = |partition_by: Vec<Expr>| Expr::WindowFunction {
+            fun: WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+            args: vec![],
+            partition_by,
+            order_by: vec![],
+            window_frame: None,
+            ignore_nulls: false,
+        };
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .window(vec![
+                row_number(vec![col("a"), col("b")]),
+                row_number(vec![col("a")]),
+            ])?
+            .filter(col("a").eq(lit(1i64)).and(col("b").eq(lit(2i64))))?
+            .build()?;
+        // only the key of all partitions selects whole partitions of both
+        let expected = "\
+            Filter: test.b = Int64(2)\
+            \n  WindowAggr: windowExpr=[[ROW_NUMBER() PARTITION BY [test.a, test.b], ROW_NUMBER() PARTITION BY [test.a]]]\
+            \n    Filter: test.a = Int64(1)\
+            \n      TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_distinct() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .project(vec![col("a"), col("b")])?
+            .distinct()?
+            .filter(col("a").eq(lit(1i64)))?
+            .build()?;
+        let expected = "\
+            Distinct:\
+            \n  Projection: test.a, test.b\
+            \n    Filter: test.a = Int64(1)\
+            \n      TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn union_with_different_names() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("x", DataType::UInt32, false),
+            Field::new("y", DataType::UInt32, false),
+            Field::new("z", DataType::UInt32, false),
+        ]);
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .union(scan_empty(Some("test2"), &schema, None)?.build()?)?
+            .distinct()?
+            .filter(col("b").eq(lit(1i64)))?
+            .build()?;
+        // union takes the names of its first input
+        let expected = "\
+            Distinct:\
+            \n  Union\
+            \n    Filter: b = Int64(1)\
+            \n      TableScan: test\
+            \n    Filter: y = Int64(1)\
+            \n      TableScan: test2";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
     #[test]
     fn union_all() -> Result<()> {
         let table_scan = test_table_scan()?;
@@ -1977,6 +2228,7 @@ mod tests {
             )?),
             projection: None,
             source: Arc::new(test_provider),
+            ordering: vec![],
             fetch: None,
         });
 
@@ -2050,6 +2302,7 @@ mod tests {
             )?),
             projection: Some(vec![0]),
             source: Arc::new(test_provider),
+            ordering: vec![],
             fetch: None,
         });
 
@@ -39,16 +39,17 @@ impl InlineTableScan {
 /// Inline
 fn inline_table_scan(plan: &LogicalPlan) -> Result<LogicalPlan> {
     match plan {
-        // Match only on scans without filter / projection / fetch
+        // Match only on scans without filter / ordering / projection / fetch
         // Views and DataFrames won't have those added
         // during the early stage of planning
         LogicalPlan::TableScan(TableScan {
             source,
             table_name,
             filters,
+            ordering,
             fetch: None,
             ..
-        }) if filters.is_empty() => {
+        }) if filters.is_empty() && ordering.is_empty() => {
             if let Some(sub_plan) = source.get_logical_plan() {
                 // Recursively apply optimization
                 let plan = inline_table_scan(sub_plan)?;
@@ -18,7 +18,9 @@
 pub mod common_subexpr_eliminate;
 pub mod decorrelate_where_exists;
 pub mod decorrelate_where_in;
+pub mod eliminate_distinct;
 pub mod eliminate_filter;
+pub mod eliminate_join;
 pub mod eliminate_limit;
 pub mod filter_null_join_keys;
 pub mod filter_push_down;
@@ -29,8 +31,10 @@ pub mod projection_push_down;
 pub mod reduce_cross_join;
 pub mod reduce_outer_join;
 pub mod scalar_subquery_to_join;
+pub mod share_scalar_subqueries;
 pub mod simplify_expressions;
 pub mod single_distinct_to_groupby;
+pub mod sort_push_down;
 pub mod subquery_filter_to_join;
 pub mod type_coercion;
 pub mod utils;
@@ -128,6 +128,7 @@ fn limit_push_down(
                 source,
                 projection,
                 filters,
+                ordering,
                 fetch,
                 projected_schema,
             }),
@@ -143,6 +144,7 @@ fn limit_push_down(
                 source: source.clone(),
                 projection: projection.clone(),
                 filters: filters.clone(),
+                ordering: ordering.clone(),
                 fetch: fetch
                     .map(|x| std::cmp::min(x, ancestor_fetch))
                     .or(Some(ancestor_fetch)),
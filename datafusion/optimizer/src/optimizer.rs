@@ -20,7 +20,9 @@
 use crate::common_subexpr_eliminate::CommonSubexprEliminate;
 use crate::decorrelate_where_exists::DecorrelateWhereExists;
 use crate::decorrelate_where_in::DecorrelateWhereIn;
+use crate::eliminate_distinct::EliminateDistinct;
 use crate::eliminate_filter::EliminateFilter;
+use crate::eliminate_join::EliminateJoin;
 use crate::eliminate_limit::EliminateLimit;
 use crate::filter_null_join_keys::FilterNullJoinKeys;
 use crate::filter_push_down::FilterPushDown;
@@ -31,14 +33,17 @@ use crate::reduce_cross_join::ReduceCrossJoin;
 use crate::reduce_outer_join::ReduceOuterJoin;
 use crate::rewrite_disjunctive_predicate::RewriteDisjunctivePredicate;
 use crate::scalar_subquery_to_join::ScalarSubqueryToJoin;
+use crate::share_scalar_subqueries::ShareScalarSubqueries;
 use crate::simplify_expressions::SimplifyExpressions;
 use crate::single_distinct_to_groupby::SingleDistinctToGroupBy;
+use crate::sort_push_down::SortPushDown;
 use crate::subquery_filter_to_join::SubqueryFilterToJoin;
 use crate::type_coercion::TypeCoercion;
 use crate::unwrap_cast_in_comparison::UnwrapCastInComparison;
 use chrono::{DateTime, Utc};
 use datafusion_common::{DataFusionError, Result};
 use datafusion_expr::logical_plan::LogicalPlan;
+use datafusion_physical_expr::time_zone::SessionTimeZone;
 use log::{debug, trace, warn};
 use std::sync::Arc;
 use std::time::Instant;
@@ -65,6 +70,9 @@ pub struct OptimizerConfig {
     /// Query execution start time that can be used to rewrite
     /// expressions such as `now()` to use a literal value instead
     query_execution_start_time: DateTime<Utc>,
+    /// Time zone of the session, in which expressions such as
+    /// `current_date()` are evaluated
+    time_zone: SessionTimeZone,
     /// id generator for optimizer passes
     // TODO this should not be on the config,
     // it should be its own 'OptimizerState' or something)
@@ -75,6 +83,9 @@ pub struct OptimizerConfig {
     filter_null_keys: bool,
     /// Maximum number of times to run optimizer against a plan
     max_passes: u8,
+    /// Whether the rules may rely on the constraints of the tables, which are
+    /// not enforced
+    rely_on_constraints: bool,
 }
 
 impl OptimizerConfig {
@@ -82,10 +93,12 @@ impl OptimizerConfig {
     pub fn new() -> Self {
         Self {
             query_execution_start_time: chrono::Utc::now(),
+            time_zone: SessionTimeZone::default(),
             next_id: 0, // useful for generating things like unique subquery aliases
             skip_failing_rules: true,
             filter_null_keys: true,
             max_passes: 3,
+            rely_on_constraints: false,
         }
     }
 
@@ -105,6 +118,12 @@ impl OptimizerConfig {
         self
     }
 
+    /// Specify the time zone of the session
+    pub fn with_time_zone(mut self, time_zone: SessionTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
     /// Specify whether the optimizer should skip rules that produce
     /// errors, or fail the query
     pub fn with_skip_failing_rules(mut self, b: bool) -> Self {
@@ -118,6 +137,13 @@ impl OptimizerConfig {
         self
     }
 
+    /// Specify whether the rules may rely on the primary key, unique and
+    /// foreign key constraints of the tables, which are not enforced
+    pub fn with_rely_on_constraints(mut self, rely_on_constraints: bool) -> Self {
+        self.rely_on_constraints = rely_on_constraints;
+        self
+    }
+
     /// Generate the next ID needed
     pub fn next_id(&mut self) -> usize {
         self.next_id += 1;
@@ -129,6 +155,16 @@ impl OptimizerConfig {
     pub fn query_execution_start_time(&self) -> DateTime<Utc> {
         self.query_execution_start_time
     }
+
+    /// Return the time zone of the session
+    pub fn time_zone(&self) -> SessionTimeZone {
+        self.time_zone
+    }
+
+    /// Return whether the rules may rely on the constraints of the tables
+    pub fn rely_on_constraints(&self) -> bool {
+        self.rely_on_constraints
+    }
 }
 
 impl Default for OptimizerConfig {
@@ -163,8 +199,10 @@ impl Optimizer {
             Arc::new(SimplifyExpressions::new()),
             Arc::new(EliminateFilter::new()),
             Arc::new(ReduceCrossJoin::new()),
+            Arc::new(EliminateJoin::new()),
             Arc::new(CommonSubexprEliminate::new()),
             Arc::new(EliminateLimit::new()),
+            Arc::new(EliminateDistinct::new()),
             Arc::new(RewriteDisjunctivePredicate::new()),
         ];
         if config.filter_null_keys {
@@ -173,6 +211,7 @@ impl Optimizer {
         rules.push(Arc::new(ReduceOuterJoin::new()));
         rules.push(Arc::new(FilterPushDown::new()));
         rules.push(Arc::new(LimitPushDown::new()));
+        rules.push(Arc::new(SortPushDown::new()));
         rules.push(Arc::new(SingleDistinctToGroupBy::new()));
 
         // The previous optimizations added expressions and projections,
@@ -181,6 +220,7 @@ impl Optimizer {
         rules.push(Arc::new(UnwrapCastInComparison::new()));
         rules.push(Arc::new(CommonSubexprEliminate::new()));
         rules.push(Arc::new(ProjectionPushDown::new()));
+        rules.push(Arc::new(ShareScalarSubqueries::new()));
 
         Self::with_rules(rules)
     }
@@ -348,6 +348,7 @@ fn optimize_plan(
             table_name,
             source,
             filters,
+            ordering,
             fetch: limit,
             ..
         }) => {
@@ -364,6 +365,7 @@ fn optimize_plan(
                 projection: Some(projection),
                 projected_schema,
                 filters: filters.clone(),
+                ordering: ordering.clone(),
                 fetch: *limit,
             }))
         }
@@ -485,8 +487,14 @@ fn optimize_plan(
         | LogicalPlan::CreateCatalog(_)
         | LogicalPlan::DropTable(_)
         | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshMaterializedView(_)
+        | LogicalPlan::AnalyzeTable(_)
+        | LogicalPlan::SetVariable(_)
+        | LogicalPlan::Prepare(_)
         | LogicalPlan::CrossJoin(_)
         | LogicalPlan::Distinct(_)
+        | LogicalPlan::Unnest(_)
+        | LogicalPlan::Dedup(_)
         | LogicalPlan::Extension { .. } => {
             let expr = plan.expressions();
             // collect all required columns by this plan
@@ -20,12 +20,12 @@ use crate::{OptimizerConfig, OptimizerRule};
 use datafusion_common::{Column, DFSchema, Result};
 use datafusion_expr::{
     expr::BinaryExpr,
-    logical_plan::{Filter, Join, JoinType, LogicalPlan, Projection},
+    logical_plan::{Filter, Join, JoinType, LogicalPlan, Projection, SubqueryAlias},
     utils::from_plan,
 };
 use datafusion_expr::{Expr, Operator};
 
-use datafusion_expr::expr::Cast;
+use datafusion_expr::expr::{Between, Cast, Like};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -90,15 +90,26 @@ fn reduce_outer_join(
                     )?),
                 )?))
             }
-            _ => Ok(LogicalPlan::Filter(Filter::try_new(
-                filter.predicate().clone(),
-                Arc::new(reduce_outer_join(
-                    _optimizer,
-                    filter.input(),
+            _ => {
+                // without the sides of a join, no columns are taken from an OR
+                let empty_schema = Arc::new(DFSchema::empty());
+                extract_nonnullable_columns(
+                    filter.predicate(),
                     nonnullable_cols,
-                    _optimizer_config,
-                )?),
-            )?)),
+                    &empty_schema,
+                    &empty_schema,
+                    true,
+                )?;
+                Ok(LogicalPlan::Filter(Filter::try_new(
+                    filter.predicate().clone(),
+                    Arc::new(reduce_outer_join(
+                        _optimizer,
+                        filter.input(),
+                        nonnullable_cols,
+                        _optimizer_config,
+                    )?),
+                )?))
+            }
         },
         LogicalPlan::Join(join) => {
             let mut new_join_type = join.join_type;
@@ -142,16 +153,62 @@ fn reduce_outer_join(
                 };
             }
 
+            // rows of an input that is not preserved by the join are dropped
+            // when its join keys or the join filter are null, so those columns
+            // can also reduce outer joins below that input.
+            let (left_rejects_nulls, right_rejects_nulls) = match new_join_type {
+                JoinType::Inner | JoinType::LeftSemi | JoinType::RightSemi => {
+                    (true, true)
+                }
+                JoinType::Left | JoinType::LeftAnti => (false, true),
+                JoinType::Right => (true, false),
+                JoinType::Full => (false, false),
+            };
+            let mut left_nonnullable_cols = nonnullable_cols.clone();
+            let mut right_nonnullable_cols = nonnullable_cols.clone();
+            if !join.null_equals_null {
+                for (left_col, right_col) in &join.on {
+                    if left_rejects_nulls {
+                        left_nonnullable_cols.push(left_col.clone());
+                    }
+                    if right_rejects_nulls {
+                        right_nonnullable_cols.push(right_col.clone());
+                    }
+                }
+            }
+            if let Some(filter) = &join.filter {
+                let mut filter_cols = vec![];
+                extract_nonnullable_columns(
+                    filter,
+                    &mut filter_cols,
+                    join.left.schema(),
+                    join.right.schema(),
+                    true,
+                )?;
+                for col in filter_cols {
+                    if left_rejects_nulls
+                        && join.left.schema().field_from_column(&col).is_ok()
+                    {
+                        left_nonnullable_cols.push(col.clone());
+                    }
+                    if right_rejects_nulls
+                        && join.right.schema().field_from_column(&col).is_ok()
+                    {
+                        right_nonnullable_cols.push(col);
+                    }
+                }
+            }
+
             let left_plan = reduce_outer_join(
                 _optimizer,
                 &join.left,
-                &mut nonnullable_cols.clone(),
+                &mut left_nonnullable_cols,
                 _optimizer_config,
             )?;
             let right_plan = reduce_outer_join(
                 _optimizer,
                 &join.right,
-                &mut nonnullable_cols.clone(),
+                &mut right_nonnullable_cols,
                 _optimizer_config,
             )?;
 
@@ -187,12 +244,16 @@ fn reduce_outer_join(
                 })
                 .collect::<HashMap<_, _>>();
 
-            // re-write all Columns based on this projection
-            for col in nonnullable_cols.iter_mut() {
-                if let Some(Expr::Column(column)) = projection.get(&col.flat_name()) {
-                    *col = column.clone();
-                }
-            }
+            // re-write all Columns based on this projection, a null result of
+            // any other expression does not imply a null input
+            *nonnullable_cols = nonnullable_cols
+                .iter()
+                .filter_map(|col| match projection.get(&col.flat_name()) {
+                    Some(Expr::Column(column)) => Some(column.clone()),
+                    Some(_) => None,
+                    None => Some(col.clone()),
+                })
+                .collect();
 
             // optimize inner
             let new_input = reduce_outer_join(
@@ -204,28 +265,61 @@ fn reduce_outer_join(
 
             from_plan(plan, expr, &[new_input])
         }
-        _ => {
-            let expr = plan.expressions();
-
-            // apply the optimization to all inputs of the plan
-            let inputs = plan.inputs();
-            let new_inputs = inputs
+        LogicalPlan::SubqueryAlias(SubqueryAlias { input, schema, .. }) => {
+            // re-write all Columns to the fields of the aliased input
+            let input_schema = input.schema();
+            *nonnullable_cols = nonnullable_cols
                 .iter()
-                .map(|plan| {
-                    reduce_outer_join(
-                        _optimizer,
-                        plan,
-                        nonnullable_cols,
-                        _optimizer_config,
-                    )
+                .filter_map(|col| {
+                    schema
+                        .index_of_column(col)
+                        .ok()
+                        .map(|i| input_schema.field(i).qualified_column())
                 })
-                .collect::<Result<Vec<_>>>()?;
+                .collect();
 
-            from_plan(plan, &expr, &new_inputs)
+            let new_input = reduce_outer_join(
+                _optimizer,
+                input,
+                nonnullable_cols,
+                _optimizer_config,
+            )?;
+
+            from_plan(plan, &plan.expressions(), &[new_input])
+        }
+        // these plans neither change nor depend on the rows removed by a filter
+        // above them, so the filter still rejects nulls of their input
+        LogicalPlan::Sort(_) | LogicalPlan::Distinct(_) | LogicalPlan::Repartition(_) => {
+            optimize_inputs(_optimizer, plan, nonnullable_cols, _optimizer_config)
         }
+        _ => optimize_inputs(_optimizer, plan, &mut vec![], _optimizer_config),
     }
 }
 
+/// Apply the optimization to all inputs of the plan
+fn optimize_inputs(
+    _optimizer: &ReduceOuterJoin,
+    plan: &LogicalPlan,
+    nonnullable_cols: &mut Vec<Column>,
+    _optimizer_config: &OptimizerConfig,
+) -> Result<LogicalPlan> {
+    let expr = plan.expressions();
+    let new_inputs = plan
+        .inputs()
+        .iter()
+        .map(|plan| {
+            reduce_outer_join(
+                _optimizer,
+                plan,
+                &mut nonnullable_cols.clone(),
+                _optimizer_config,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    from_plan(plan, &expr, &new_inputs)
+}
+
 /// Recursively traversese expr, if expr returns false when
 /// any inputs are null, treats columns of both sides as nonnullable columns.
 ///
@@ -233,6 +327,10 @@ fn reduce_outer_join(
 /// For or expr, if one of sub exprs returns true, discards all columns from or expr.
 /// For IS NOT NULL/NOT expr, always returns false for NULL input.
 ///     extracts columns from these exprs.
+/// For IS TRUE/IS FALSE/IS NOT UNKNOWN expr, returns false for NULL input,
+///     extracts columns only at the top level, as their negation returns true.
+/// For BETWEEN/IN/LIKE expr, returns NULL for NULL input, extracts columns
+///     from the tested expr.
 /// For all other exprs, fall through
 fn extract_nonnullable_columns(
     expr: &Expr,
@@ -352,8 +450,25 @@ fn extract_nonnullable_columns(
                 false,
             )
         }
+        Expr::IsTrue(arg) | Expr::IsFalse(arg) | Expr::IsNotUnknown(arg) => {
+            if !top_level {
+                return Ok(());
+            }
+            extract_nonnullable_columns(
+                arg,
+                nonnullable_cols,
+                left_schema,
+                right_schema,
+                false,
+            )
+        }
         Expr::Cast(Cast { expr, data_type: _ })
-        | Expr::TryCast { expr, data_type: _ } => extract_nonnullable_columns(
+        | Expr::TryCast { expr, data_type: _ }
+        | Expr::Between(Between { expr, .. })
+        | Expr::InList { expr, .. }
+        | Expr::Like(Like { expr, .. })
+        | Expr::ILike(Like { expr, .. })
+        | Expr::SimilarTo(Like { expr, .. }) => extract_nonnullable_columns(
             expr,
             nonnullable_cols,
             left_schema,
@@ -522,4 +637,131 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn reduce_left_with_in_list() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+
+        // reduce to inner join
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(
+                &t2,
+                JoinType::Left,
+                (vec![Column::from_name("a")], vec![Column::from_name("a")]),
+                None,
+            )?
+            .filter(col("t2.b").in_list(vec![lit(1u32), lit(2u32)], true))?
+            .build()?;
+        let expected = "\
+        Filter: t2.b NOT IN ([UInt32(1), UInt32(2)])\
+        \n  Inner Join: t1.a = t2.a\
+        \n    TableScan: t1\
+        \n    TableScan: t2";
+        assert_optimized_plan_eq(&plan, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reduce_left_with_join_keys() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let t3 = test_table_scan_with_name("t3")?;
+
+        // the keys of the inner join reject null rows of the left join
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(
+                &t2,
+                JoinType::Left,
+                (vec![Column::from_name("a")], vec![Column::from_name("a")]),
+                None,
+            )?
+            .join(
+                &t3,
+                JoinType::Inner,
+                (
+                    vec![Column::from_qualified_name("t2.b")],
+                    vec![Column::from_qualified_name("t3.b")],
+                ),
+                None,
+            )?
+            .build()?;
+        let expected = "\
+        Inner Join: t2.b = t3.b\
+        \n  Inner Join: t1.a = t2.a\
+        \n    TableScan: t1\
+        \n    TableScan: t2\
+        \n  TableScan: t3";
+        assert_optimized_plan_eq(&plan, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reduce_left_through_subquery_alias() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(
+                &t2,
+                JoinType::Left,
+                (vec![Column::from_name("a")], vec![Column::from_name("a")]),
+                None,
+            )?
+            .project(vec![col("t1.a"), col("t2.b")])?
+            .alias("s")?
+            .filter(col("s.b").gt(lit(1u32)))?
+            .build()?;
+        let expected = "\
+        Filter: s.b > UInt32(1)\
+        \n  SubqueryAlias: s\
+        \n    Projection: t1.a, t2.b\
+        \n      Inner Join: t1.a = t2.a\
+        \n        TableScan: t1\
+        \n        TableScan: t2";
+        assert_optimized_plan_eq(&plan, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_reduce_through_limit() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let t3 = test_table_scan_with_name("t3")?;
+
+        // the limit may keep different rows when null rows are removed below it
+        let left_join = LogicalPlanBuilder::from(t1)
+            .join(
+                &t2,
+                JoinType::Left,
+                (vec![Column::from_name("a")], vec![Column::from_name("a")]),
+                None,
+            )?
+            .limit(0, Some(1))?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(t3)
+            .join(
+                &left_join,
+                JoinType::Inner,
+                (
+                    vec![Column::from_qualified_name("t3.b")],
+                    vec![Column::from_qualified_name("t2.b")],
+                ),
+                None,
+            )?
+            .build()?;
+        let expected = "\
+        Inner Join: t3.b = t2.b\
+        \n  TableScan: t3\
+        \n  Limit: skip=0, fetch=1\
+        \n    Left Join: t1.a = t2.a\
+        \n      TableScan: t1\
+        \n      TableScan: t2";
+        assert_optimized_plan_eq(&plan, expected);
+
+        Ok(())
+    }
 }
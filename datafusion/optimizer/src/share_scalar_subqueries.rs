@@ -0,0 +1,236 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule sharing the plan of identical uncorrelated scalar
+//! subqueries, such that the physical plan executes each of them once.
+use crate::{OptimizerConfig, OptimizerRule};
+use datafusion_common::{Column, Result};
+use datafusion_expr::expr_rewriter::{ExprRewritable, ExprRewriter};
+use datafusion_expr::expr_visitor::{ExprVisitable, ExpressionVisitor, Recursion};
+use datafusion_expr::logical_plan::Subquery;
+use datafusion_expr::utils::{expr_to_columns, from_plan};
+use datafusion_expr::{Expr, LogicalPlan};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Optimization rule that replaces the plans of the uncorrelated scalar
+/// subqueries of a plan by the same [`Arc`] for identical subqueries, such
+/// as `(SELECT max(b) FROM t)` in:
+///
+/// ```text
+/// SELECT a - (SELECT max(b) FROM t), a / (SELECT max(b) FROM t) FROM t
+/// ```
+///
+/// The physical planner executes the subqueries sharing a plan once per
+/// query, and their references evaluate to its value.
+#[derive(Default)]
+pub struct ShareScalarSubqueries;
+
+impl ShareScalarSubqueries {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for ShareScalarSubqueries {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        _optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        let mut rewriter = SubqueryRewriter {
+            subqueries: HashMap::new(),
+        };
+        rewriter.share_subqueries(plan)
+    }
+
+    fn name(&self) -> &str {
+        "share_scalar_subqueries"
+    }
+}
+
+/// Rewrites the uncorrelated scalar subqueries of a plan, with the shared
+/// plans of the subqueries by their displayed plans
+struct SubqueryRewriter {
+    subqueries: HashMap<String, Arc<LogicalPlan>>,
+}
+
+impl SubqueryRewriter {
+    fn share_subqueries(&mut self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        let inputs = plan
+            .inputs()
+            .into_iter()
+            .map(|input| self.share_subqueries(input))
+            .collect::<Result<Vec<_>>>()?;
+        let exprs = plan
+            .expressions()
+            .into_iter()
+            .map(|expr| expr.rewrite(self))
+            .collect::<Result<Vec<_>>>()?;
+        from_plan(plan, &exprs, &inputs)
+    }
+}
+
+impl ExprRewriter for SubqueryRewriter {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match expr {
+            Expr::ScalarSubquery(subquery) if !is_correlated(&subquery.subquery)? => {
+                let plan = self.share_subqueries(&subquery.subquery)?;
+                let key = format!("{}", plan.display_indent_schema());
+                let subquery = self
+                    .subqueries
+                    .entry(key)
+                    .or_insert_with(|| Arc::new(plan))
+                    .clone();
+                Ok(Expr::ScalarSubquery(Subquery { subquery }))
+            }
+            expr => Ok(expr),
+        }
+    }
+}
+
+/// Returns true if `plan`, the plan of a subquery, refers to the columns of
+/// an outer query
+pub fn is_correlated(plan: &LogicalPlan) -> Result<bool> {
+    Ok(!outer_columns(plan)?.is_empty())
+}
+
+/// The columns referred to by the nodes of `plan`, or by the plans of their
+/// subqueries, which are not columns of the nodes or of their inputs
+fn outer_columns(plan: &LogicalPlan) -> Result<HashSet<Column>> {
+    let mut columns = HashSet::new();
+    for input in plan.inputs() {
+        columns.extend(outer_columns(input)?);
+    }
+    // the filters of a scan may refer to the columns it does not project
+    if let LogicalPlan::TableScan(_) = plan {
+        return Ok(columns);
+    }
+
+    let mut schemas = vec![plan.schema()];
+    schemas.extend(plan.inputs().into_iter().map(|input| input.schema()));
+    for expr in plan.expressions() {
+        let mut expr_columns = HashSet::new();
+        expr_to_columns(&expr, &mut expr_columns)?;
+        for subquery in expr.accept(SubqueryCollector::default())?.subqueries {
+            expr_columns.extend(outer_columns(&subquery)?);
+        }
+        columns.extend(expr_columns.into_iter().filter(|column| {
+            !schemas
+                .iter()
+                .any(|schema| schema.field_from_column(column).is_ok())
+        }));
+    }
+    Ok(columns)
+}
+
+/// Collects the plans of the subqueries of an expression
+#[derive(Default)]
+struct SubqueryCollector {
+    subqueries: Vec<Arc<LogicalPlan>>,
+}
+
+impl ExpressionVisitor for SubqueryCollector {
+    fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+        match expr {
+            Expr::ScalarSubquery(subquery)
+            | Expr::Exists { subquery, .. }
+            | Expr::InSubquery { subquery, .. } => {
+                self.subqueries.push(subquery.subquery.clone())
+            }
+            _ => {}
+        }
+        Ok(Recursion::Continue(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use datafusion_expr::{col, logical_plan::LogicalPlanBuilder, max, scalar_subquery};
+
+    fn max_subquery(filter: Option<Expr>) -> Result<Arc<LogicalPlan>> {
+        let mut builder = LogicalPlanBuilder::from(scan_tpch_table("orders"));
+        if let Some(filter) = filter {
+            builder = builder.filter(filter)?;
+        }
+        Ok(Arc::new(
+            builder
+                .aggregate(Vec::<Expr>::new(), vec![max(col("orders.o_custkey"))])?
+                .build()?,
+        ))
+    }
+
+    fn subquery_plans(plan: &LogicalPlan) -> Vec<Arc<LogicalPlan>> {
+        plan.expressions()
+            .iter()
+            .flat_map(|expr| {
+                expr.accept(SubqueryCollector::default())
+                    .unwrap()
+                    .subqueries
+            })
+            .collect()
+    }
+
+    #[test]
+    fn share_identical_subqueries() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(scan_tpch_table("customer"))
+            .project(vec![
+                scalar_subquery(max_subquery(None)?).alias("a"),
+                scalar_subquery(max_subquery(None)?).alias("b"),
+                scalar_subquery(max_subquery(Some(
+                    col("orders.o_totalprice").is_not_null(),
+                ))?)
+                .alias("c"),
+            ])?
+            .build()?;
+
+        let optimized =
+            ShareScalarSubqueries::new().optimize(&plan, &mut OptimizerConfig::new())?;
+        assert_eq!(
+            format!("{}", optimized.display_indent_schema()),
+            format!("{}", plan.display_indent_schema())
+        );
+        let subqueries = subquery_plans(&optimized);
+        assert_eq!(subqueries.len(), 3);
+        assert!(Arc::ptr_eq(&subqueries[0], &subqueries[1]));
+        assert!(!Arc::ptr_eq(&subqueries[0], &subqueries[2]));
+        Ok(())
+    }
+
+    #[test]
+    fn correlated_subqueries() -> Result<()> {
+        let correlated =
+            || max_subquery(Some(col("orders.o_custkey").eq(col("customer.c_custkey"))));
+        assert!(is_correlated(&correlated()?)?);
+        assert!(!is_correlated(&max_subquery(None)?)?);
+
+        let plan = LogicalPlanBuilder::from(scan_tpch_table("customer"))
+            .project(vec![
+                scalar_subquery(correlated()?).alias("a"),
+                scalar_subquery(correlated()?).alias("b"),
+            ])?
+            .build()?;
+        let optimized =
+            ShareScalarSubqueries::new().optimize(&plan, &mut OptimizerConfig::new())?;
+        let subqueries = subquery_plans(&optimized);
+        assert!(!Arc::ptr_eq(&subqueries[0], &subqueries[1]));
+        Ok(())
+    }
+}
@@ -17,8 +17,10 @@
 
 //! Structs and traits to provide the information needed for expression simplification.
 
+use std::collections::HashSet;
+
 use arrow::datatypes::DataType;
-use datafusion_common::{DFSchemaRef, DataFusionError, Result};
+use datafusion_common::{Column, DFSchemaRef, DataFusionError, Result};
 use datafusion_expr::{Expr, ExprSchemable};
 use datafusion_physical_expr::execution_props::ExecutionProps;
 
@@ -36,6 +38,13 @@ pub trait SimplifyInfo {
     /// returns true of this expr is nullable (could possibly be NULL)
     fn nullable(&self, expr: &Expr) -> Result<bool>;
 
+    /// returns true if this expr can not be NULL because of an enforced
+    /// constraint, unlike a field of a schema that is merely declared as not
+    /// nullable
+    fn is_enforced_not_null(&self, _expr: &Expr) -> bool {
+        false
+    }
+
     /// Returns details needed for partial expression evaluation
     fn execution_props(&self) -> &ExecutionProps;
 }
@@ -74,6 +83,7 @@ pub trait SimplifyInfo {
 /// ```
 pub struct SimplifyContext<'a> {
     schemas: Vec<DFSchemaRef>,
+    not_null_columns: HashSet<Column>,
     props: &'a ExecutionProps,
 }
 
@@ -82,6 +92,7 @@ impl<'a> SimplifyContext<'a> {
     pub fn new(props: &'a ExecutionProps) -> Self {
         Self {
             schemas: vec![],
+            not_null_columns: HashSet::new(),
             props,
         }
     }
@@ -91,6 +102,16 @@ impl<'a> SimplifyContext<'a> {
         self.schemas.push(schema);
         self
     }
+
+    /// Register columns which can not be NULL because of enforced
+    /// constraints, such as the primary keys of the scanned tables
+    pub fn with_not_null_columns(
+        mut self,
+        columns: impl IntoIterator<Item = Column>,
+    ) -> Self {
+        self.not_null_columns.extend(columns);
+        self
+    }
 }
 
 impl<'a> SimplifyInfo for SimplifyContext<'a> {
@@ -124,6 +145,13 @@ impl<'a> SimplifyInfo for SimplifyContext<'a> {
             })
     }
 
+    fn is_enforced_not_null(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Column(column) => self.not_null_columns.contains(column),
+            _ => false,
+        }
+    }
+
     fn execution_props(&self) -> &ExecutionProps {
         self.props
     }
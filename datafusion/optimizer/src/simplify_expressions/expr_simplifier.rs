@@ -243,6 +243,7 @@ impl<'a> ConstEvaluator<'a> {
             | Expr::AggregateFunction { .. }
             | Expr::AggregateUDF { .. }
             | Expr::ScalarVariable(_, _)
+            | Expr::Placeholder { .. }
             | Expr::Column(_)
             | Expr::Exists { .. }
             | Expr::InSubquery { .. }
@@ -622,6 +623,17 @@ impl<'a, S: SimplifyInfo> ExprRewriter for Simplifier<'a, S> {
             //
             Expr::Not(inner) => negate_clause(*inner),
 
+            //
+            // Rules for IsNull / IsNotNull
+            //
+
+            // A IS NULL --> false (if A can not be null)
+            // A IS NOT NULL --> true (if A can not be null)
+            // the nullability of the schemas is not enforced, unlike the
+            // constraints of the tables
+            Expr::IsNull(expr) if info.is_enforced_not_null(&expr) => lit(false),
+            Expr::IsNotNull(expr) if info.is_enforced_not_null(&expr) => lit(true),
+
             //
             // Rules for Case
             //
@@ -727,7 +739,7 @@ mod tests {
         datatypes::{DataType, Field, Schema},
     };
     use chrono::{DateTime, TimeZone, Utc};
-    use datafusion_common::{DFField, ToDFSchema};
+    use datafusion_common::{Column, DFField, ToDFSchema};
     use datafusion_expr::*;
     use datafusion_physical_expr::{
         execution_props::ExecutionProps, functions::make_scalar_function,
@@ -822,6 +834,8 @@ mod tests {
     ) {
         let execution_props = ExecutionProps {
             query_execution_start_time: *date_time,
+            random_generator: None,
+            time_zone: Default::default(),
             var_providers: None,
         };
 
@@ -1520,6 +1534,29 @@ mod tests {
         assert_eq!(simplify(col("c2").not().not().not()), col("c2").not(),);
     }
 
+    #[test]
+    fn simplify_expr_null_check() {
+        let execution_props = ExecutionProps::new();
+        let simplifier = ExprSimplifier::new(
+            SimplifyContext::new(&execution_props)
+                .with_schema(expr_test_schema())
+                .with_not_null_columns(vec![Column::from_name("c1")]),
+        );
+        let simplify = |expr| simplifier.simplify(expr).unwrap();
+
+        assert_eq!(simplify(col("c1").is_null()), lit(false));
+        assert_eq!(simplify(col("c1").is_not_null()), lit(true));
+        // the field is declared as not nullable, which is not enforced
+        assert_eq!(
+            simplify(col("c1_non_null").is_null()),
+            col("c1_non_null").is_null()
+        );
+        assert_eq!(
+            simplify(col("c1_non_null").is_not_null()),
+            col("c1_non_null").is_not_null()
+        );
+    }
+
     #[test]
     fn simplify_expr_null_comparison() {
         // x = null is always null
@@ -20,7 +20,10 @@
 use super::{ExprSimplifier, SimplifyContext};
 use crate::{OptimizerConfig, OptimizerRule};
 use datafusion_common::Result;
-use datafusion_expr::{logical_plan::LogicalPlan, utils::from_plan};
+use datafusion_expr::{
+    logical_plan::LogicalPlan,
+    utils::{from_plan, not_null_columns},
+};
 use datafusion_physical_expr::execution_props::ExecutionProps;
 
 /// Optimizer Pass that simplifies [`LogicalPlan`]s by rewriting
@@ -51,6 +54,7 @@ impl OptimizerRule for SimplifyExpressions {
         let mut execution_props = ExecutionProps::new();
         execution_props.query_execution_start_time =
             optimizer_config.query_execution_start_time();
+        execution_props.time_zone = optimizer_config.time_zone();
         self.optimize_internal(plan, &execution_props)
     }
 }
@@ -72,6 +76,15 @@ impl SimplifyExpressions {
             .fold(SimplifyContext::new(execution_props), |context, schema| {
                 context.with_schema(schema.clone())
             });
+        // the expressions are evaluated on the rows of the inputs
+        let info = plan.inputs().into_iter().fold(info, |context, input| {
+            let schema = input.schema();
+            context.with_not_null_columns(
+                not_null_columns(input)
+                    .into_iter()
+                    .map(|i| schema.field(i).qualified_column()),
+            )
+        });
 
         let simplifier = ExprSimplifier::new(info);
 
@@ -124,9 +137,10 @@ mod tests {
     };
 
     use super::*;
+    use crate::test::scan_with_constraints;
     use arrow::datatypes::{DataType, Field, Schema};
     use chrono::{DateTime, TimeZone, Utc};
-    use datafusion_common::ScalarValue;
+    use datafusion_common::{Constraint, Constraints, ScalarValue};
     use datafusion_expr::{or, Between, BinaryExpr, Cast, Operator};
 
     use datafusion_expr::logical_plan::table_scan;
@@ -698,6 +712,33 @@ mod tests {
         assert_optimized_plan_eq(&plan, expected);
     }
 
+    #[test]
+    fn simplify_null_check_on_primary_key() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Boolean, false),
+            Field::new("d", DataType::UInt32, false),
+        ]);
+        let table_scan = scan_with_constraints(
+            "test",
+            schema,
+            Constraints::new(vec![Constraint::PrimaryKey(vec![1])]),
+        )
+        .expect("creating scan")
+        .build()
+        .expect("building plan");
+
+        // only the primary key is known not to be null
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .filter(col("d").is_null().or(col("a").is_null()))
+            .unwrap()
+            .build()
+            .unwrap();
+        let expected = "Filter: test.a IS NULL\
+        \n  TableScan: test";
+
+        assert_optimized_plan_eq(&plan, expected);
+    }
+
     #[test]
     fn simplify_not_in() {
         let table_scan = test_table_scan();
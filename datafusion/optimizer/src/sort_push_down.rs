@@ -0,0 +1,226 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to push down the ordering and the limit of a sort into the
+//! table scan below it, for table providers that can return sorted rows
+use crate::{utils, OptimizerConfig, OptimizerRule};
+use datafusion_common::Result;
+use datafusion_expr::{
+    logical_plan::{LogicalPlan, Projection, Sort, TableScan},
+    Expr,
+};
+use std::sync::Arc;
+
+/// Optimization rule that pushes the sort expressions and the fetch of a
+/// [`Sort`] into the [`TableScan`] directly below it, possibly through a
+/// projection of columns, when the table source supports sort pushdown.
+///
+/// The sort itself is kept, so that the plan remains correct when the scan
+/// returns several sorted partitions; the physical optimizer removes it when
+/// the scan already produces the requested ordering.
+#[derive(Default)]
+pub struct SortPushDown {}
+
+impl SortPushDown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for SortPushDown {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        let plan = utils::optimize_children(self, plan, optimizer_config)?;
+        let (expr, input, fetch) = match &plan {
+            LogicalPlan::Sort(Sort { expr, input, fetch }) => (expr, input, *fetch),
+            _ => return Ok(plan),
+        };
+
+        let new_input = match input.as_ref() {
+            LogicalPlan::TableScan(scan) => {
+                push_down_to_scan(scan, expr, fetch)?.map(LogicalPlan::TableScan)
+            }
+            LogicalPlan::Projection(Projection {
+                expr: proj_expr,
+                input: proj_input,
+                schema,
+                alias,
+            }) if proj_expr.iter().all(|e| matches!(e, Expr::Column(_))) => {
+                match proj_input.as_ref() {
+                    LogicalPlan::TableScan(scan) => {
+                        match push_down_to_scan(scan, expr, fetch)? {
+                            Some(scan) => Some(LogicalPlan::Projection(
+                                Projection::try_new_with_schema(
+                                    proj_expr.clone(),
+                                    Arc::new(LogicalPlan::TableScan(scan)),
+                                    schema.clone(),
+                                    alias.clone(),
+                                )?,
+                            )),
+                            None => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Ok(match new_input {
+            Some(input) => LogicalPlan::Sort(Sort {
+                expr: expr.clone(),
+                input: Arc::new(input),
+                fetch,
+            }),
+            None => plan,
+        })
+    }
+
+    fn name(&self) -> &str {
+        "sort_push_down"
+    }
+}
+
+/// Returns `scan` with the ordering `sort_exprs` and the limit `fetch`, or
+/// `None` if they can not be pushed into it
+fn push_down_to_scan(
+    scan: &TableScan,
+    sort_exprs: &[Expr],
+    fetch: Option<usize>,
+) -> Result<Option<TableScan>> {
+    // the scan was already ordered, or limited without an ordering
+    if !scan.ordering.is_empty() || scan.fetch.is_some() {
+        return Ok(None);
+    }
+
+    let sorts_scan_columns = sort_exprs.iter().all(|sort_expr| match sort_expr {
+        Expr::Sort { expr, .. } => match expr.as_ref() {
+            Expr::Column(column) => scan.projected_schema.index_of_column(column).is_ok(),
+            _ => false,
+        },
+        _ => false,
+    });
+    if !sorts_scan_columns || !scan.source.supports_sort_pushdown(sort_exprs)? {
+        return Ok(None);
+    }
+
+    Ok(Some(TableScan {
+        ordering: sort_exprs.to_vec(),
+        fetch,
+        ..scan.clone()
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use datafusion_expr::{col, LogicalPlanBuilder, TableSource};
+
+    /// A table source that returns its rows ordered by `a`
+    struct SortedSource {}
+
+    impl TableSource for SortedSource {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("a", DataType::UInt32, false),
+                Field::new("b", DataType::UInt32, false),
+            ]))
+        }
+
+        fn supports_sort_pushdown(&self, sort_exprs: &[Expr]) -> Result<bool> {
+            Ok(sort_exprs == [col("test.a").sort(true, false)])
+        }
+    }
+
+    fn sorted_scan() -> Result<LogicalPlanBuilder> {
+        LogicalPlanBuilder::scan("test", Arc::new(SortedSource {}), None)
+    }
+
+    fn sort(input: LogicalPlan, expr: Expr, fetch: Option<usize>) -> LogicalPlan {
+        LogicalPlan::Sort(Sort {
+            expr: vec![expr],
+            input: Arc::new(input),
+            fetch,
+        })
+    }
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = SortPushDown::new();
+        let optimized_plan = rule
+            .optimize(plan, &mut OptimizerConfig::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+    }
+
+    #[test]
+    fn sort_pushdown_with_fetch() -> Result<()> {
+        let plan = sort(
+            sorted_scan()?.build()?,
+            col("test.a").sort(true, false),
+            Some(10),
+        );
+
+        let expected = "Sort: test.a ASC NULLS LAST, fetch=10\
+        \n  TableScan: test, ordering=[test.a ASC NULLS LAST], fetch=10";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn sort_pushdown_through_projection() -> Result<()> {
+        let input = sorted_scan()?.project(vec![col("b"), col("a")])?.build()?;
+        let plan = sort(input, col("test.a").sort(true, false), None);
+
+        let expected = "Sort: test.a ASC NULLS LAST\
+        \n  Projection: test.b, test.a\
+        \n    TableScan: test, ordering=[test.a ASC NULLS LAST]";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn sort_pushdown_unsupported() -> Result<()> {
+        // the source is not sorted descending
+        let plan = sort(
+            sorted_scan()?.build()?,
+            col("test.a").sort(false, true),
+            Some(10),
+        );
+        let expected = "Sort: test.a DESC NULLS FIRST, fetch=10\
+        \n  TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+
+        // the projection renames the column
+        let input = sorted_scan()?.project(vec![col("b").alias("a")])?.build()?;
+        let plan = sort(input, col("a").sort(true, false), Some(10));
+        let expected = "Sort: a ASC NULLS LAST, fetch=10\
+        \n  Projection: test.b AS a\
+        \n    TableScan: test";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
@@ -17,7 +17,8 @@
 
 use crate::{OptimizerConfig, OptimizerRule};
 use arrow::datatypes::{DataType, Field, Schema};
-use datafusion_common::Result;
+use datafusion_common::{Constraints, Result};
+use datafusion_expr::logical_plan::builder::LogicalTableSource;
 use datafusion_expr::{col, logical_plan::table_scan, LogicalPlan, LogicalPlanBuilder};
 use std::sync::Arc;
 
@@ -47,6 +48,17 @@ pub fn scan_empty(
     table_scan(name, table_schema, projection)
 }
 
+/// Scan an empty data source declaring `constraints`, mainly used in tests
+pub fn scan_with_constraints(
+    name: &str,
+    table_schema: Schema,
+    constraints: Constraints,
+) -> Result<LogicalPlanBuilder> {
+    let source =
+        LogicalTableSource::new(Arc::new(table_schema)).with_constraints(constraints);
+    LogicalPlanBuilder::scan(name, Arc::new(source), None)
+}
+
 pub fn assert_fields_eq(plan: &LogicalPlan, expected: Vec<&str>) {
     let actual: Vec<String> = plan
         .schema()
@@ -419,6 +419,7 @@ impl ExprRewriter for TypeCoercionRewriter {
                 partition_by,
                 order_by,
                 window_frame,
+                ignore_nulls,
             } => {
                 let window_frame =
                     get_coerced_window_frame(window_frame, &self.schema, &order_by)?;
@@ -428,6 +429,7 @@ impl ExprRewriter for TypeCoercionRewriter {
                     partition_by,
                     order_by,
                     window_frame,
+                    ignore_nulls,
                 };
                 Ok(expr)
             }
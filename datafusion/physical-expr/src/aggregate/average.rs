@@ -23,6 +23,7 @@ use std::sync::Arc;
 
 use crate::aggregate::row_accumulator::RowAccumulator;
 use crate::aggregate::sum;
+use crate::aggregate::utils::non_null_counts;
 use crate::expressions::format_state_name;
 use crate::{AggregateExpr, PhysicalExpr};
 use arrow::compute;
@@ -129,6 +130,17 @@ impl AggregateExpr for Avg {
             self.data_type.clone(),
         )))
     }
+
+    fn convert_to_state_supported(&self) -> bool {
+        true
+    }
+
+    fn convert_to_state(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        Ok(vec![
+            non_null_counts(&values[0]),
+            compute::cast(&values[0], &self.data_type)?,
+        ])
+    }
 }
 
 /// An accumulator to compute the average
@@ -32,6 +32,7 @@ use datafusion_common::{DataFusionError, Result};
 use datafusion_expr::{Accumulator, AggregateState};
 use datafusion_row::accessor::RowAccessor;
 
+use crate::aggregate::utils::non_null_counts;
 use crate::expressions::format_state_name;
 
 /// COUNT aggregate expression
@@ -98,6 +99,15 @@ impl AggregateExpr for Count {
         true
     }
 
+    fn convert_to_state_supported(&self) -> bool {
+        true
+    }
+
+    fn convert_to_state(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        let counts = non_null_counts(&values[0]);
+        Ok(vec![compute::cast(&counts, &self.data_type)?])
+    }
+
     fn create_row_accumulator(
         &self,
         start_index: usize,
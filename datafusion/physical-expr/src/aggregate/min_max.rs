@@ -128,6 +128,9 @@ impl AggregateExpr for Max {
                 | DataType::Int64
                 | DataType::Float32
                 | DataType::Float64
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, None)
         )
     }
 
@@ -140,6 +143,14 @@ impl AggregateExpr for Max {
             self.data_type.clone(),
         )))
     }
+
+    fn convert_to_state_supported(&self) -> bool {
+        true
+    }
+
+    fn convert_to_state(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        Ok(vec![compute::cast(&values[0], &self.data_type)?])
+    }
 }
 
 // Statically-typed version of min/max(array) -> ScalarValue for string types.
@@ -466,6 +477,16 @@ macro_rules! min_max_v2 {
             ScalarValue::Int8(rhs) => {
                 typed_min_max_v2!($INDEX, $ACC, rhs, i8, $OP)
             }
+            ScalarValue::Date32(rhs) => {
+                typed_min_max_v2!($INDEX, $ACC, rhs, i32, $OP)
+            }
+            ScalarValue::Date64(rhs)
+            | ScalarValue::TimestampSecond(rhs, _)
+            | ScalarValue::TimestampMillisecond(rhs, _)
+            | ScalarValue::TimestampMicrosecond(rhs, _)
+            | ScalarValue::TimestampNanosecond(rhs, _) => {
+                typed_min_max_v2!($INDEX, $ACC, rhs, i64, $OP)
+            }
             e => {
                 return Err(DataFusionError::Internal(format!(
                     "MIN/MAX is not expected to receive scalars of incompatible types {:?}",
@@ -644,6 +665,9 @@ impl AggregateExpr for Min {
                 | DataType::Int64
                 | DataType::Float32
                 | DataType::Float64
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, None)
         )
     }
 
@@ -656,6 +680,14 @@ impl AggregateExpr for Min {
             self.data_type.clone(),
         )))
     }
+
+    fn convert_to_state_supported(&self) -> bool {
+        true
+    }
+
+    fn convert_to_state(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        Ok(vec![compute::cast(&values[0], &self.data_type)?])
+    }
 }
 
 /// An accumulator to compute the minimum value
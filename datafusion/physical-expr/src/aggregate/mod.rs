@@ -17,6 +17,7 @@
 
 use crate::aggregate::row_accumulator::RowAccumulator;
 use crate::PhysicalExpr;
+use arrow::array::ArrayRef;
 use arrow::datatypes::Field;
 use datafusion_common::{DataFusionError, Result};
 use datafusion_expr::Accumulator;
@@ -101,4 +102,23 @@ pub trait AggregateExpr: Send + Sync + Debug {
             self
         )))
     }
+
+    /// If the partial state of a group holding a single row can be computed
+    /// from the values of this row, see [`Self::convert_to_state`]
+    fn convert_to_state_supported(&self) -> bool {
+        false
+    }
+
+    /// Converts each row of `values`, the evaluated [`Self::expressions`],
+    /// into the partial state of a group holding only this row, described by
+    /// [`Self::state_fields`].
+    ///
+    /// Partial aggregations whose groups barely reduce their input use it to
+    /// output their input rows as partial states, without aggregating them.
+    fn convert_to_state(&self, _values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        Err(DataFusionError::NotImplemented(format!(
+            "Converting the values to partial states hasn't been implemented for {:?} yet",
+            self
+        )))
+    }
 }
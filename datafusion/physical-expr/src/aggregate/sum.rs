@@ -35,6 +35,7 @@ use datafusion_common::{downcast_value, DataFusionError, Result, ScalarValue};
 use datafusion_expr::{Accumulator, AggregateState};
 
 use crate::aggregate::row_accumulator::RowAccumulator;
+use crate::aggregate::utils::non_null_counts;
 use crate::expressions::format_state_name;
 use arrow::array::Array;
 use arrow::array::Decimal128Array;
@@ -132,6 +133,18 @@ impl AggregateExpr for Sum {
             self.data_type.clone(),
         )))
     }
+
+    fn convert_to_state_supported(&self) -> bool {
+        true
+    }
+
+    fn convert_to_state(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        // the sum of a single value is the value itself, null if it is null
+        Ok(vec![
+            cast(&values[0], &self.data_type)?,
+            non_null_counts(&values[0]),
+        ])
+    }
 }
 
 #[derive(Debug)]
@@ -428,6 +441,19 @@ mod tests {
         generic_test_op!(a, DataType::Int32, Sum, ScalarValue::from(13i32))
     }
 
+    #[test]
+    fn sum_i32_convert_to_state() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let sum = Sum::new(col("a", &schema)?, "SUM(a)", DataType::Int64);
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+        let states = sum.convert_to_state(&[a])?;
+        let expected: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(3)]));
+        assert_eq!(&expected, &states[0]);
+        let expected: ArrayRef = Arc::new(UInt64Array::from(vec![1, 0, 1]));
+        assert_eq!(&expected, &states[1]);
+        Ok(())
+    }
+
     #[test]
     fn sum_i32_all_nulls() -> Result<()> {
         let a: ArrayRef = Arc::new(Int32Array::from(vec![None, None]));
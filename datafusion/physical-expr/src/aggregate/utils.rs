@@ -17,9 +17,10 @@
 
 //! Utilities used in aggregates
 
-use arrow::array::ArrayRef;
+use arrow::array::{Array, ArrayRef, UInt64Array};
 use datafusion_common::{Result, ScalarValue};
 use datafusion_expr::Accumulator;
+use std::sync::Arc;
 
 /// Extract scalar values from an accumulator. This can return an error if the accumulator
 /// has any non-scalar values.
@@ -46,3 +47,11 @@ pub fn get_accum_scalar_values_as_arrays(
         })
         .collect::<Result<Vec<_>>>()
 }
+
+/// The number of non-null values of each row of `array`, which is the count
+/// of the partial state of a group holding only this row
+pub(crate) fn non_null_counts(array: &ArrayRef) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(
+        (0..array.len()).map(|i| array.is_valid(i) as u64),
+    ))
+}
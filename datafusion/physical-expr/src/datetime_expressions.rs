@@ -17,6 +17,7 @@
 
 //! DateTime expressions
 
+use crate::time_zone::SessionTimeZone;
 use arrow::{
     array::{Array, ArrayRef, GenericStringArray, OffsetSizeTrait, PrimitiveArray},
     compute::kernels::cast_utils::string_to_timestamp_nanos,
@@ -184,6 +185,37 @@ pub fn make_now(
     }
 }
 
+/// Create an implementation of `current_date()` that always returns the
+/// date of the specified timestamp in the time zone `time_zone`.
+///
+/// Like `now()`, the date is the same wherever it appears within a single
+/// statement.
+pub fn make_current_date(
+    now_ts: DateTime<Utc>,
+    time_zone: SessionTimeZone,
+) -> impl Fn(&[ColumnarValue]) -> Result<ColumnarValue> {
+    let date = time_zone.to_local(&now_ts.naive_utc()).date();
+    let days = Some((date - NaiveDate::from_ymd(1970, 1, 1)).num_days() as i32);
+    move |_arg| Ok(ColumnarValue::Scalar(ScalarValue::Date32(days)))
+}
+
+/// Create an implementation of `current_time()` that always returns the
+/// time of day of the specified timestamp in the time zone `time_zone`.
+///
+/// Like `now()`, the time is the same wherever it appears within a single
+/// statement.
+pub fn make_current_time(
+    now_ts: DateTime<Utc>,
+    time_zone: SessionTimeZone,
+) -> impl Fn(&[ColumnarValue]) -> Result<ColumnarValue> {
+    let time = time_zone.to_local(&now_ts.naive_utc()).time();
+    let nanos = Some(
+        time.num_seconds_from_midnight() as i64 * 1_000_000_000
+            + time.nanosecond() as i64,
+    );
+    move |_arg| Ok(ColumnarValue::Scalar(ScalarValue::Time64(nanos)))
+}
+
 fn quarter_month(date: &NaiveDateTime) -> u32 {
     1 + 3 * ((date.month() - 1) / 3)
 }
@@ -233,8 +265,40 @@ fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
     Ok(value.unwrap().timestamp_nanos())
 }
 
-/// date_trunc SQL function
+/// Truncates the instant `value` at the `granularity` of the local date and
+/// time of the time zone `time_zone`
+fn date_trunc_zoned(
+    granularity: &str,
+    value: i64,
+    time_zone: &SessionTimeZone,
+) -> Result<i64> {
+    let local = time_zone.to_local(&timestamp_ns_to_datetime(value));
+    let truncated = date_trunc_single(granularity, local.timestamp_nanos())?;
+    Ok(time_zone
+        .to_utc(&timestamp_ns_to_datetime(truncated))?
+        .timestamp_nanos())
+}
+
+/// date_trunc SQL function, truncating the instants of zoned timestamps in
+/// UTC
 pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    date_trunc_in(args, &SessionTimeZone::default())
+}
+
+/// Create an implementation of `date_trunc()` that truncates the instants of
+/// zoned timestamps at the boundaries of the local dates and times of the
+/// time zone `time_zone`, such as its midnights. Naive timestamps are
+/// truncated as they are.
+pub fn make_date_trunc(
+    time_zone: SessionTimeZone,
+) -> impl Fn(&[ColumnarValue]) -> Result<ColumnarValue> {
+    move |args| date_trunc_in(args, &time_zone)
+}
+
+fn date_trunc_in(
+    args: &[ColumnarValue],
+    time_zone: &SessionTimeZone,
+) -> Result<ColumnarValue> {
     let (granularity, array) = (&args[0], &args[1]);
 
     let granularity =
@@ -246,24 +310,32 @@ pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
             ));
         };
 
-    let f = |x: Option<i64>| x.map(|x| date_trunc_single(granularity, x)).transpose();
+    let truncate = |x: i64, tz_opt: &Option<String>| match tz_opt {
+        Some(_) => date_trunc_zoned(granularity, x, time_zone),
+        None => date_trunc_single(granularity, x),
+    };
 
     Ok(match array {
         ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(v, tz_opt)) => {
             ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(
-                (f)(*v)?,
+                v.map(|x| truncate(x, tz_opt)).transpose()?,
                 tz_opt.clone(),
             ))
         }
         ColumnarValue::Array(array) => {
+            let tz_opt = match array.data_type() {
+                DataType::Timestamp(_, tz_opt) => tz_opt.clone(),
+                _ => None,
+            };
             let array = array
                 .as_any()
                 .downcast_ref::<TimestampNanosecondArray>()
                 .unwrap();
             let array = array
                 .iter()
-                .map(f)
-                .collect::<Result<TimestampNanosecondArray>>()?;
+                .map(|x| x.map(|x| truncate(x, &tz_opt)).transpose())
+                .collect::<Result<TimestampNanosecondArray>>()?
+                .with_timezone_opt(tz_opt);
 
             ColumnarValue::Array(Arc::new(array))
         }
@@ -604,6 +676,83 @@ mod tests {
         });
     }
 
+    #[test]
+    fn date_trunc_in_time_zone() -> Result<()> {
+        let time_zone = "Asia/Tokyo".parse::<SessionTimeZone>()?;
+        let date_trunc = make_date_trunc(time_zone);
+        let timestamp = |s, tz: Option<&str>| {
+            ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(
+                Some(string_to_timestamp_nanos(s).unwrap()),
+                tz.map(str::to_owned),
+            ))
+        };
+        let granularity = ColumnarValue::Scalar(ScalarValue::Utf8(Some("day".into())));
+
+        // 2020-09-08T13:42:29Z is 22:42:29 on September 8 in Tokyo, whose
+        // midnight is at 15:00 UTC the day before
+        let zoned = timestamp("2020-09-08T13:42:29Z", Some("UTC"));
+        let result = date_trunc(&[granularity.clone(), zoned])?;
+        let expected = timestamp("2020-09-07T15:00:00Z", Some("UTC"));
+        assert_eq!(format!("{:?}", result), format!("{:?}", expected));
+
+        // naive timestamps are truncated as they are
+        let naive = timestamp("2020-09-08T13:42:29Z", None);
+        let result = date_trunc(&[granularity.clone(), naive])?;
+        let expected = timestamp("2020-09-08T00:00:00Z", None);
+        assert_eq!(format!("{:?}", result), format!("{:?}", expected));
+
+        let array = TimestampNanosecondArray::from(vec![
+            string_to_timestamp_nanos("2020-09-08T14:59:59Z").unwrap(),
+            string_to_timestamp_nanos("2020-09-08T15:00:00Z").unwrap(),
+        ])
+        .with_timezone_opt(Some("UTC".to_owned()));
+        let result = date_trunc(&[granularity, ColumnarValue::Array(Arc::new(array))])?;
+        let expected = TimestampNanosecondArray::from(vec![
+            string_to_timestamp_nanos("2020-09-07T15:00:00Z").unwrap(),
+            string_to_timestamp_nanos("2020-09-08T15:00:00Z").unwrap(),
+        ])
+        .with_timezone_opt(Some("UTC".to_owned()));
+        match result {
+            ColumnarValue::Array(result) => {
+                assert_eq!(&expected as &dyn Array, result.as_ref())
+            }
+            _ => panic!("Expected a columnar array"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn current_date_and_time_in_time_zone() -> Result<()> {
+        let now = DateTime::<Utc>::from_utc(
+            NaiveDate::from_ymd(2022, 10, 31).and_hms(20, 30, 0),
+            Utc,
+        );
+        let time_zone = "+09:00".parse::<SessionTimeZone>()?;
+
+        let current_date = make_current_date(now, time_zone)(&[])?;
+        let expected = NaiveDate::from_ymd(2022, 11, 1) - NaiveDate::from_ymd(1970, 1, 1);
+        assert_eq!(
+            format!("{:?}", current_date),
+            format!(
+                "{:?}",
+                ColumnarValue::Scalar(ScalarValue::Date32(Some(
+                    expected.num_days() as i32
+                )))
+            )
+        );
+
+        let current_time = make_current_time(now, time_zone)(&[])?;
+        let expected = (5 * 3600 + 30 * 60) * 1_000_000_000_i64;
+        assert_eq!(
+            format!("{:?}", current_time),
+            format!(
+                "{:?}",
+                ColumnarValue::Scalar(ScalarValue::Time64(Some(expected)))
+            )
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_date_bin_single() {
         use chrono::Duration;
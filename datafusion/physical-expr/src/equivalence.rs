@@ -0,0 +1,252 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Equivalence properties of the columns of an execution plan output
+
+use crate::expressions::Column;
+
+use arrow::datatypes::SchemaRef;
+
+use std::collections::{HashMap, HashSet};
+
+/// The columns of an execution plan output that always have equal values,
+/// grouped into [`EquivalentClass`]es. For example, the output of an inner
+/// join on `a = b` has equal values in `a` and `b`, as does the output of a
+/// projection of `a` and `a AS b`.
+#[derive(Debug, Clone)]
+pub struct EquivalenceProperties {
+    classes: Vec<EquivalentClass>,
+    schema: SchemaRef,
+}
+
+impl EquivalenceProperties {
+    /// Create properties without any equivalent columns
+    pub fn new(schema: SchemaRef) -> Self {
+        EquivalenceProperties {
+            classes: vec![],
+            schema,
+        }
+    }
+
+    /// The classes of equivalent columns
+    pub fn classes(&self) -> &[EquivalentClass] {
+        &self.classes
+    }
+
+    /// The schema of the execution plan output
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Add the equalities of each class
+    pub fn extend<I: IntoIterator<Item = EquivalentClass>>(&mut self, iter: I) {
+        for class in iter {
+            for column in class.iter() {
+                if column != class.head() {
+                    self.add_equal_conditions((class.head(), column));
+                }
+            }
+        }
+    }
+
+    /// Add the equality of two columns, merging their classes if needed
+    pub fn add_equal_conditions(&mut self, new_conditions: (&Column, &Column)) {
+        let (left, right) = new_conditions;
+        if left == right {
+            return;
+        }
+        let left_idx = self.classes.iter().position(|class| class.contains(left));
+        let right_idx = self.classes.iter().position(|class| class.contains(right));
+        match (left_idx, right_idx) {
+            (Some(left_idx), Some(right_idx)) if left_idx != right_idx => {
+                let right_class = self.classes.swap_remove(right_idx);
+                // the left class moved if it was the last one
+                let left_idx = if left_idx == self.classes.len() {
+                    right_idx
+                } else {
+                    left_idx
+                };
+                for column in right_class.iter() {
+                    self.classes[left_idx].insert(column.clone());
+                }
+            }
+            (Some(_), Some(_)) => {}
+            (Some(idx), None) => {
+                self.classes[idx].insert(right.clone());
+            }
+            (None, Some(idx)) => {
+                self.classes[idx].insert(left.clone());
+            }
+            (None, None) => {
+                self.classes
+                    .push(EquivalentClass::new(left.clone(), vec![right.clone()]));
+            }
+        }
+    }
+}
+
+/// A set of columns with equal values. The `head` is the column that the
+/// other columns are replaced with when comparing expressions.
+#[derive(Debug, Clone)]
+pub struct EquivalentClass {
+    head: Column,
+    others: HashSet<Column>,
+}
+
+impl EquivalentClass {
+    /// Create a class of `head` and the `others` columns
+    pub fn new(head: Column, others: Vec<Column>) -> Self {
+        let others = others.into_iter().filter(|c| *c != head).collect();
+        EquivalentClass { head, others }
+    }
+
+    /// The column representing the class
+    pub fn head(&self) -> &Column {
+        &self.head
+    }
+
+    /// The columns of the class other than the head
+    pub fn others(&self) -> &HashSet<Column> {
+        &self.others
+    }
+
+    /// Returns true if `column` is in the class
+    pub fn contains(&self, column: &Column) -> bool {
+        self.head == *column || self.others.contains(column)
+    }
+
+    /// Add `column` to the class, returning true if it was not in the class
+    pub fn insert(&mut self, column: Column) -> bool {
+        self.head != column && self.others.insert(column)
+    }
+
+    /// Iterate over all the columns of the class, starting with the head
+    pub fn iter(&self) -> impl Iterator<Item = &'_ Column> {
+        std::iter::once(&self.head).chain(self.others.iter())
+    }
+
+    /// The number of columns in the class
+    pub fn len(&self) -> usize {
+        self.others.len() + 1
+    }
+
+    /// Returns true if the class has no other column than its head
+    pub fn is_empty(&self) -> bool {
+        self.others.is_empty()
+    }
+}
+
+/// Derive the equivalence properties of a projection from those of its
+/// input. `alias_map` maps each input column to the output columns it is
+/// projected to: those are equal to each other, and to the output columns of
+/// the input columns equal to it.
+pub fn project_equivalence_properties(
+    input_eq: EquivalenceProperties,
+    alias_map: &HashMap<Column, Vec<Column>>,
+    output_eq: &mut EquivalenceProperties,
+) {
+    let mut classes = vec![];
+    for class in input_eq.classes() {
+        let outputs = class
+            .iter()
+            .filter_map(|column| alias_map.get(column))
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        if outputs.len() > 1 {
+            classes.push(EquivalentClass::new(
+                outputs[0].clone(),
+                outputs[1..].to_vec(),
+            ));
+        }
+    }
+    for (column, outputs) in alias_map {
+        let in_class = input_eq
+            .classes()
+            .iter()
+            .any(|class| class.contains(column));
+        if !in_class && outputs.len() > 1 {
+            classes.push(EquivalentClass::new(
+                outputs[0].clone(),
+                outputs[1..].to_vec(),
+            ));
+        }
+    }
+    output_eq.extend(classes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_common::Result;
+    use std::sync::Arc;
+
+    fn schema(names: &[&str]) -> SchemaRef {
+        Arc::new(Schema::new(
+            names
+                .iter()
+                .map(|name| Field::new(name, DataType::Int64, true))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn add_equal_conditions_merges_classes() -> Result<()> {
+        let mut eq_properties = EquivalenceProperties::new(schema(&["a", "b", "c", "d"]));
+        let a = Column::new("a", 0);
+        let b = Column::new("b", 1);
+        let c = Column::new("c", 2);
+        let d = Column::new("d", 3);
+
+        eq_properties.add_equal_conditions((&a, &b));
+        eq_properties.add_equal_conditions((&c, &d));
+        assert_eq!(eq_properties.classes().len(), 2);
+
+        eq_properties.add_equal_conditions((&b, &c));
+        assert_eq!(eq_properties.classes().len(), 1);
+        let class = &eq_properties.classes()[0];
+        assert_eq!(class.len(), 4);
+        assert!([&a, &b, &c, &d].iter().all(|column| class.contains(column)));
+        Ok(())
+    }
+
+    #[test]
+    fn project_equivalence_properties_with_alias() -> Result<()> {
+        let input_schema = schema(&["a", "b", "c"]);
+        let mut input_eq = EquivalenceProperties::new(input_schema);
+        input_eq.add_equal_conditions((&Column::new("a", 0), &Column::new("b", 1)));
+
+        // SELECT a AS a1, a AS a2, c
+        let output_schema = schema(&["a1", "a2", "c"]);
+        let mut alias_map = HashMap::new();
+        alias_map.insert(
+            Column::new("a", 0),
+            vec![Column::new("a1", 0), Column::new("a2", 1)],
+        );
+        alias_map.insert(Column::new("c", 2), vec![Column::new("c", 2)]);
+
+        let mut output_eq = EquivalenceProperties::new(output_schema);
+        project_equivalence_properties(input_eq, &alias_map, &mut output_eq);
+        assert_eq!(output_eq.classes().len(), 1);
+        let class = &output_eq.classes()[0];
+        assert_eq!(class.len(), 2);
+        assert!(class.contains(&Column::new("a1", 0)));
+        assert!(class.contains(&Column::new("a2", 1)));
+        Ok(())
+    }
+}
@@ -15,23 +15,44 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::expressions::ScalarSubqueryValue;
+use crate::time_zone::SessionTimeZone;
 use crate::var_provider::{VarProvider, VarType};
 use chrono::{DateTime, Utc};
+use datafusion_expr::LogicalPlan;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
+
+/// The values of the uncorrelated scalar subqueries of a statement, with the
+/// plans of the subqueries. The plans are weak references, whose addresses
+/// are not reused while they are registered.
+type ScalarSubqueries = Vec<(Weak<LogicalPlan>, Arc<ScalarSubqueryValue>)>;
 
 /// Holds per-execution properties and data (such as starting timestamps, etc).
-/// An instance of this struct is created each time a [`LogicalPlan`] is prepared for
-/// execution (optimized). If the same plan is optimized multiple times, a new
-/// `ExecutionProps` is created each time.
+/// An instance of this struct is snapshotted at the start of the execution of
+/// each statement, and shared by the optimization and the physical planning of
+/// all its plans, including the plans of the views it scans, such that `now()`
+/// evaluates to the same time in the whole statement. If the same plan is
+/// executed multiple times, a new `ExecutionProps` is created each time.
 ///
 /// It is important that this structure be cheap to create as it is
 /// done so during predicate pruning and expression simplification
 #[derive(Clone)]
 pub struct ExecutionProps {
     pub query_execution_start_time: DateTime<Utc>,
+    /// The generator of the random values of the query, if seeded, which
+    /// makes `random()` deterministic in plans with a single partition
+    pub random_generator: Option<Arc<Mutex<StdRng>>>,
+    /// The time zone of the session, in which the current date and time are
+    /// read, and the timestamps truncated
+    pub time_zone: SessionTimeZone,
     /// providers for scalar variables
     pub var_providers: Option<HashMap<VarType, Arc<dyn VarProvider + Send + Sync>>>,
+    /// The values of the uncorrelated scalar subqueries of the statement,
+    /// shared by all the references to the same subquery plan
+    pub scalar_subqueries: Arc<Mutex<ScalarSubqueries>>,
 }
 
 impl Default for ExecutionProps {
@@ -45,13 +66,33 @@ impl ExecutionProps {
     pub fn new() -> Self {
         ExecutionProps {
             query_execution_start_time: chrono::Utc::now(),
+            random_generator: None,
+            time_zone: SessionTimeZone::default(),
             var_providers: None,
+            scalar_subqueries: Arc::default(),
         }
     }
 
-    /// Marks the execution of query started timestamp
+    /// Marks the execution of query started timestamp, and forgets the
+    /// values of the scalar subqueries of the previous query
     pub fn start_execution(&mut self) -> &Self {
         self.query_execution_start_time = chrono::Utc::now();
+        self.scalar_subqueries = Arc::default();
+        &*self
+    }
+
+    /// Sets the seed of the random values of the query, creating a new
+    /// generator so that each query draws the same values. `None` draws
+    /// non-reproducible values
+    pub fn set_random_seed(&mut self, seed: Option<u64>) -> &Self {
+        self.random_generator =
+            seed.map(|seed| Arc::new(Mutex::new(StdRng::seed_from_u64(seed))));
+        &*self
+    }
+
+    /// Sets the time zone of the session
+    pub fn set_time_zone(&mut self, time_zone: SessionTimeZone) -> &Self {
+        self.time_zone = time_zone;
         &*self
     }
 
@@ -71,6 +112,40 @@ impl ExecutionProps {
         old_provider
     }
 
+    /// Registers the uncorrelated scalar subquery `plan`, returning the value
+    /// shared by all the references to `plan` in the statement
+    pub fn register_scalar_subquery(
+        &self,
+        plan: &Arc<LogicalPlan>,
+    ) -> Arc<ScalarSubqueryValue> {
+        let mut subqueries = self.scalar_subqueries.lock().unwrap();
+        subqueries.retain(|(subquery, _)| subquery.strong_count() > 0);
+        match subqueries
+            .iter()
+            .find(|(subquery, _)| subquery.as_ptr() == Arc::as_ptr(plan))
+        {
+            Some((_, value)) => value.clone(),
+            None => {
+                let value = Arc::new(ScalarSubqueryValue::new());
+                subqueries.push((Arc::downgrade(plan), value.clone()));
+                value
+            }
+        }
+    }
+
+    /// Returns the value of the scalar subquery `plan`, if registered
+    pub fn scalar_subquery_value(
+        &self,
+        plan: &Arc<LogicalPlan>,
+    ) -> Option<Arc<ScalarSubqueryValue>> {
+        self.scalar_subqueries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(subquery, _)| subquery.as_ptr() == Arc::as_ptr(plan))
+            .map(|(_, value)| value.clone())
+    }
+
     /// Returns the provider for the var_type, if any
     pub fn get_var_provider(
         &self,
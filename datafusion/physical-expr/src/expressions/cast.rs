@@ -20,12 +20,18 @@ use std::fmt;
 use std::sync::Arc;
 
 use crate::physical_expr::down_cast_any_ref;
+use crate::time_zone::SessionTimeZone;
 use crate::PhysicalExpr;
+use arrow::array::{as_primitive_array, ArrayRef, PrimitiveArray};
 use arrow::compute;
 use arrow::compute::kernels;
 use arrow::compute::CastOptions;
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{
+    ArrowTimestampType, DataType, Schema, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
 use arrow::record_batch::RecordBatch;
+use chrono::NaiveDateTime;
 use compute::can_cast_types;
 use datafusion_common::ScalarValue;
 use datafusion_common::{DataFusionError, Result};
@@ -43,6 +49,9 @@ pub struct CastExpr {
     cast_type: DataType,
     /// Cast options
     cast_options: CastOptions,
+    /// The time zone of the local times of naive timestamps cast into zoned
+    /// ones, if not UTC
+    time_zone: Option<SessionTimeZone>,
 }
 
 impl CastExpr {
@@ -56,9 +65,17 @@ impl CastExpr {
             expr,
             cast_type,
             cast_options,
+            time_zone: None,
         }
     }
 
+    /// Reads the values of naive timestamps cast into zoned ones as local
+    /// times of `time_zone`, instead of UTC
+    pub fn with_time_zone(mut self, time_zone: SessionTimeZone) -> Self {
+        self.time_zone = Some(time_zone);
+        self
+    }
+
     /// The expression to cast
     pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
         &self.expr
@@ -68,11 +85,24 @@ impl CastExpr {
     pub fn cast_type(&self) -> &DataType {
         &self.cast_type
     }
+
+    /// The time zone of the local times of naive timestamps cast into zoned
+    /// ones, if not UTC
+    pub fn time_zone(&self) -> Option<SessionTimeZone> {
+        self.time_zone
+    }
 }
 
 impl fmt::Display for CastExpr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "CAST({} AS {:?})", self.expr, self.cast_type)
+        match &self.time_zone {
+            Some(time_zone) => write!(
+                f,
+                "CAST({} AT TIME ZONE '{}' AS {:?})",
+                self.expr, time_zone, self.cast_type
+            ),
+            None => write!(f, "CAST({} AS {:?})", self.expr, self.cast_type),
+        }
     }
 }
 
@@ -92,7 +122,11 @@ impl PhysicalExpr for CastExpr {
 
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
         let value = self.expr.evaluate(batch)?;
-        cast_column(&value, &self.cast_type, &self.cast_options)
+        let value = cast_column(&value, &self.cast_type, &self.cast_options)?;
+        match &self.time_zone {
+            Some(time_zone) => local_to_utc(value, time_zone),
+            None => Ok(value),
+        }
     }
 
     fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
@@ -103,13 +137,15 @@ impl PhysicalExpr for CastExpr {
         self: Arc<Self>,
         children: Vec<Arc<dyn PhysicalExpr>>,
     ) -> Result<Arc<dyn PhysicalExpr>> {
-        Ok(Arc::new(CastExpr::new(
+        let mut cast = CastExpr::new(
             children[0].clone(),
             self.cast_type.clone(),
             CastOptions {
                 safe: self.cast_options.safe,
             },
-        )))
+        );
+        cast.time_zone = self.time_zone;
+        Ok(Arc::new(cast))
     }
 }
 
@@ -121,6 +157,7 @@ impl PartialEq<dyn Any> for CastExpr {
                 self.expr.eq(&x.expr)
                     && self.cast_type == x.cast_type
                     && self.cast_options.safe == x.cast_options.safe
+                    && self.time_zone == x.time_zone
             })
             .unwrap_or(false)
     }
@@ -169,6 +206,105 @@ pub fn cast_with_options(
     }
 }
 
+/// Converts the zoned timestamps of `value`, cast from naive timestamps, from
+/// local times of `time_zone` into UTC instants
+fn local_to_utc(
+    value: ColumnarValue,
+    time_zone: &SessionTimeZone,
+) -> Result<ColumnarValue> {
+    match value {
+        ColumnarValue::Array(array) => {
+            Ok(ColumnarValue::Array(local_array_to_utc(&array, time_zone)?))
+        }
+        ColumnarValue::Scalar(scalar) => {
+            let array = local_array_to_utc(&scalar.to_array(), time_zone)?;
+            Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+                &array, 0,
+            )?))
+        }
+    }
+}
+
+fn local_array_to_utc(array: &ArrayRef, time_zone: &SessionTimeZone) -> Result<ArrayRef> {
+    match array.data_type() {
+        DataType::Timestamp(TimeUnit::Second, tz_opt) => {
+            local_timestamps_to_utc::<TimestampSecondType>(array, tz_opt, 1, time_zone)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, tz_opt) => {
+            local_timestamps_to_utc::<TimestampMillisecondType>(
+                array, tz_opt, 1_000, time_zone,
+            )
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, tz_opt) => {
+            local_timestamps_to_utc::<TimestampMicrosecondType>(
+                array, tz_opt, 1_000_000, time_zone,
+            )
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, tz_opt) => {
+            local_timestamps_to_utc::<TimestampNanosecondType>(
+                array,
+                tz_opt,
+                1_000_000_000,
+                time_zone,
+            )
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Cannot read values of type {:?} as local times",
+            other
+        ))),
+    }
+}
+
+/// Converts the timestamps of `array`, of `units_per_second` units per
+/// second, from local times of `time_zone` into UTC instants
+fn local_timestamps_to_utc<T: ArrowTimestampType>(
+    array: &ArrayRef,
+    tz_opt: &Option<String>,
+    units_per_second: i64,
+    time_zone: &SessionTimeZone,
+) -> Result<ArrayRef> {
+    let nanos_per_unit = 1_000_000_000 / units_per_second;
+    let to_utc = |value: i64| -> Result<i64> {
+        let seconds = value.div_euclid(units_per_second);
+        let nanos = (value.rem_euclid(units_per_second) * nanos_per_unit) as u32;
+        let local =
+            NaiveDateTime::from_timestamp_opt(seconds, nanos).ok_or_else(|| {
+                DataFusionError::Execution(format!("Timestamp {} out of range", value))
+            })?;
+        let offset = (local - time_zone.to_utc(&local)?).num_seconds();
+        Ok(value - offset * units_per_second)
+    };
+    let array = as_primitive_array::<T>(array)
+        .iter()
+        .map(|value| value.map(to_utc).transpose())
+        .collect::<Result<PrimitiveArray<T>>>()?
+        .with_timezone_opt(tz_opt.clone());
+    Ok(Arc::new(array))
+}
+
+/// Return a PhysicalExpression representing `expr` casted to
+/// `cast_type`, if any casting is needed, reading the values of naive
+/// timestamps cast into zoned ones as local times of `time_zone`.
+pub fn cast_in_time_zone(
+    expr: Arc<dyn PhysicalExpr>,
+    input_schema: &Schema,
+    cast_type: DataType,
+    time_zone: SessionTimeZone,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    let expr_type = expr.data_type(input_schema)?;
+    match (&expr_type, &cast_type) {
+        (DataType::Timestamp(_, None), DataType::Timestamp(_, Some(_)))
+            if time_zone != SessionTimeZone::default() =>
+        {
+            Ok(Arc::new(
+                CastExpr::new(expr, cast_type, DEFAULT_DATAFUSION_CAST_OPTIONS)
+                    .with_time_zone(time_zone),
+            ))
+        }
+        _ => cast(expr, input_schema, cast_type),
+    }
+}
+
 /// Return a PhysicalExpression representing `expr` casted to
 /// `cast_type`, if any casting is needed.
 ///
@@ -194,7 +330,7 @@ mod tests {
         array::{
             Array, Decimal128Array, Float32Array, Float64Array, Int16Array, Int32Array,
             Int64Array, Int8Array, StringArray, Time64NanosecondArray,
-            TimestampNanosecondArray, UInt32Array,
+            TimestampNanosecondArray, TimestampSecondArray, UInt32Array,
         },
         datatypes::*,
     };
@@ -633,6 +769,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn cast_naive_timestamps_in_time_zone() -> Result<()> {
+        let schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Timestamp(TimeUnit::Second, None),
+            true,
+        )]);
+        // 2022-06-01T09:00:00 and 2022-12-01T09:00:00
+        let a =
+            TimestampSecondArray::from(vec![Some(1654074000), None, Some(1669885200)]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        let cast_type = DataType::Timestamp(TimeUnit::Second, Some("UTC".to_owned()));
+        let time_zone = "Europe/Paris".parse()?;
+        let expression =
+            cast_in_time_zone(col("a", &schema)?, &schema, cast_type.clone(), time_zone)?;
+        assert_eq!(
+            format!("{}", expression),
+            "CAST(a@0 AT TIME ZONE 'Europe/Paris' AS \
+             Timestamp(Second, Some(\"UTC\")))"
+        );
+
+        let result = expression.evaluate(&batch)?.into_array(batch.num_rows());
+        assert_eq!(result.data_type(), &cast_type);
+        let result = result
+            .as_any()
+            .downcast_ref::<TimestampSecondArray>()
+            .unwrap();
+        // Paris is at UTC+2 in summer and UTC+1 in winter
+        assert_eq!(
+            result.iter().collect::<Vec<_>>(),
+            vec![Some(1654074000 - 7200), None, Some(1669885200 - 3600)]
+        );
+
+        // UTC sessions cast as arrow does
+        let expression = cast_in_time_zone(
+            col("a", &schema)?,
+            &schema,
+            cast_type,
+            SessionTimeZone::default(),
+        )?;
+        assert_eq!(
+            format!("{}", expression),
+            "CAST(a@0 AS Timestamp(Second, Some(\"UTC\")))"
+        );
+        Ok(())
+    }
+
     #[test]
     fn invalid_cast() {
         // Ensure a useful error happens at plan time if invalid casts are used
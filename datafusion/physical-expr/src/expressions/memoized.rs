@@ -0,0 +1,283 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Memoization of constant subexpressions, evaluated once instead of for
+//! each batch
+
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use datafusion_common::{Result, ScalarValue};
+use datafusion_expr::{ColumnarValue, Volatility};
+
+use super::{
+    BinaryExpr, CaseExpr, CastExpr, DateTimeIntervalExpr, GetIndexedFieldExpr,
+    InListExpr, IsNotNullExpr, IsNullExpr, Literal, NegativeExpr, NotExpr, TryCastExpr,
+};
+use crate::physical_expr::{down_cast_any_ref, with_new_children_if_necessary};
+use crate::{PhysicalExpr, ScalarFunctionExpr};
+
+/// Evaluates a constant expression, which refers to no column, such as
+/// `2 * 3`, once, and then to its value for every batch
+///
+/// The expression is evaluated on a batch of one row the first time it is
+/// evaluated. If it fails, or does not evaluate to a single value, it is
+/// evaluated for every batch instead.
+#[derive(Debug)]
+pub struct MemoizedExpr {
+    expr: Arc<dyn PhysicalExpr>,
+    /// `None` until the first evaluation, then the value of the expression,
+    /// or `None` if it has no single value
+    value: Mutex<Option<Option<ScalarValue>>>,
+}
+
+impl MemoizedExpr {
+    /// Create an expression memoizing the value of the constant `expr`
+    pub fn new(expr: Arc<dyn PhysicalExpr>) -> Self {
+        Self {
+            expr,
+            value: Mutex::new(None),
+        }
+    }
+
+    /// The memoized expression
+    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+
+    /// The value of the expression, evaluated the first time
+    fn value(&self) -> Option<ScalarValue> {
+        let mut value = self.value.lock().unwrap();
+        value.get_or_insert_with(|| self.evaluate_once()).clone()
+    }
+
+    fn evaluate_once(&self) -> Option<ScalarValue> {
+        let options = RecordBatchOptions::new().with_row_count(Some(1));
+        let batch = RecordBatch::try_new_with_options(
+            Arc::new(Schema::empty()),
+            vec![],
+            &options,
+        )
+        .ok()?;
+        match self.expr.evaluate(&batch).ok()? {
+            ColumnarValue::Scalar(value) => Some(value),
+            ColumnarValue::Array(array) if array.len() == 1 => {
+                ScalarValue::try_from_array(&array, 0).ok()
+            }
+            ColumnarValue::Array(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for MemoizedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+impl PhysicalExpr for MemoizedExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, input_schema: &Schema) -> Result<DataType> {
+        self.expr.data_type(input_schema)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        self.expr.nullable(input_schema)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        match self.value() {
+            Some(value) => Ok(ColumnarValue::Scalar(value)),
+            None => self.expr.evaluate(batch),
+        }
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Ok(Arc::new(MemoizedExpr::new(children[0].clone())))
+    }
+}
+
+impl PartialEq<dyn Any> for MemoizedExpr {
+    fn eq(&self, other: &dyn Any) -> bool {
+        down_cast_any_ref(other)
+            .downcast_ref::<Self>()
+            .map(|x| self.expr.eq(&x.expr))
+            .unwrap_or(false)
+    }
+}
+
+/// Rewrites `expr` such that each of its largest constant subexpressions,
+/// other than literals, is evaluated once by a [`MemoizedExpr`]
+///
+/// A subexpression is constant if it only consists of literals, and of
+/// operators and functions which are not [`Volatility::Volatile`]. Each
+/// rewritten expression memoizes its own values, so an operator rewriting
+/// its expressions evaluates their constant subexpressions once per
+/// operator instance.
+pub fn memoize_constants(expr: Arc<dyn PhysicalExpr>) -> Result<Arc<dyn PhysicalExpr>> {
+    let (expr, constant) = memoize(expr)?;
+    Ok(if constant { memoized(expr) } else { expr })
+}
+
+/// Memoizes the largest constant subexpressions of the children of `expr`,
+/// returning whether `expr` is constant itself, in which case it is
+/// returned unchanged
+fn memoize(expr: Arc<dyn PhysicalExpr>) -> Result<(Arc<dyn PhysicalExpr>, bool)> {
+    let children = expr
+        .children()
+        .into_iter()
+        .map(memoize)
+        .collect::<Result<Vec<_>>>()?;
+    if is_deterministic(expr.as_ref()) && children.iter().all(|(_, constant)| *constant) {
+        return Ok((expr, true));
+    }
+    if children.is_empty() {
+        return Ok((expr, false));
+    }
+    let children = children
+        .into_iter()
+        .map(|(child, constant)| if constant { memoized(child) } else { child })
+        .collect();
+    Ok((with_new_children_if_necessary(expr, children)?, false))
+}
+
+/// Memoizes the constant `expr`, unless it is a literal
+fn memoized(expr: Arc<dyn PhysicalExpr>) -> Arc<dyn PhysicalExpr> {
+    if expr.as_any().downcast_ref::<Literal>().is_some() {
+        expr
+    } else {
+        Arc::new(MemoizedExpr::new(expr))
+    }
+}
+
+/// Returns true if `expr` evaluates to the same values for the same values
+/// of its children, whatever the batch
+fn is_deterministic(expr: &dyn PhysicalExpr) -> bool {
+    let any = expr.as_any();
+    match any.downcast_ref::<ScalarFunctionExpr>() {
+        Some(function) => function.volatility() != Volatility::Volatile,
+        None => {
+            any.is::<Literal>()
+                || any.is::<BinaryExpr>()
+                || any.is::<CastExpr>()
+                || any.is::<TryCastExpr>()
+                || any.is::<NotExpr>()
+                || any.is::<NegativeExpr>()
+                || any.is::<IsNullExpr>()
+                || any.is::<IsNotNullExpr>()
+                || any.is::<CaseExpr>()
+                || any.is::<InListExpr>()
+                || any.is::<DateTimeIntervalExpr>()
+                || any.is::<GetIndexedFieldExpr>()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::{col, lit};
+    use arrow::array::Int64Array;
+    use arrow::datatypes::Field;
+    use datafusion_expr::Operator;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn memoize_constant_subexpressions() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, false)]);
+        let constant: Arc<dyn PhysicalExpr> = Arc::new(BinaryExpr::new(
+            lit(ScalarValue::Int64(Some(2))),
+            Operator::Multiply,
+            lit(ScalarValue::Int64(Some(3))),
+        ));
+        let expr: Arc<dyn PhysicalExpr> = Arc::new(BinaryExpr::new(
+            col("a", &schema)?,
+            Operator::Plus,
+            constant.clone(),
+        ));
+
+        let memoized = memoize_constants(expr.clone())?;
+        assert_eq!(format!("{}", memoized), format!("{}", expr));
+        let children = memoized.children();
+        assert!(children[0]
+            .as_any()
+            .downcast_ref::<MemoizedExpr>()
+            .is_none());
+        let constant = children[1].as_any().downcast_ref::<MemoizedExpr>().unwrap();
+        assert_eq!(constant.value(), Some(ScalarValue::Int64(Some(6))));
+
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Int64Array::from(vec![1, 2]))],
+        )?;
+        let result = memoized.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(result, &Int64Array::from(vec![7, 8]));
+
+        // literals are not memoized
+        let literal = lit(ScalarValue::Int64(Some(1)));
+        assert!(Arc::ptr_eq(&memoize_constants(literal.clone())?, &literal));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_once() -> Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let function = |volatility| {
+            let calls = calls.clone();
+            let fun: datafusion_expr::ScalarFunctionImplementation =
+                Arc::new(move |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(ColumnarValue::Scalar(ScalarValue::Int64(Some(42))))
+                });
+            let expr: Arc<dyn PhysicalExpr> = Arc::new(
+                ScalarFunctionExpr::new("f", fun, vec![], &DataType::Int64)
+                    .with_volatility(volatility),
+            );
+            expr
+        };
+        let batch = RecordBatch::new_empty(Arc::new(Schema::empty()));
+
+        let immutable = memoize_constants(function(Volatility::Immutable))?;
+        for _ in 0..3 {
+            immutable.evaluate(&batch)?;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let volatile = memoize_constants(function(Volatility::Volatile))?;
+        assert!(volatile.as_any().downcast_ref::<MemoizedExpr>().is_none());
+        for _ in 0..3 {
+            volatile.evaluate(&batch)?;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        Ok(())
+    }
+}
@@ -28,10 +28,12 @@ mod in_list;
 mod is_not_null;
 mod is_null;
 mod literal;
+mod memoized;
 mod negative;
 mod no_op;
 mod not;
 mod nullif;
+mod scalar_subquery;
 mod try_cast;
 
 /// Module with some convenient methods used in expression building
@@ -61,16 +63,17 @@ pub use crate::aggregate::sum::Sum;
 pub use crate::aggregate::sum_distinct::DistinctSum;
 pub use crate::aggregate::variance::{Variance, VariancePop};
 
-pub use crate::window::cume_dist::cume_dist;
-pub use crate::window::lead_lag::{lag, lead};
-pub use crate::window::nth_value::NthValue;
-pub use crate::window::rank::{dense_rank, percent_rank, rank};
+pub use crate::window::cume_dist::{cume_dist, CumeDist};
+pub use crate::window::lead_lag::{lag, lead, WindowShift};
+pub use crate::window::nth_value::{NthValue, NthValueKind};
+pub use crate::window::rank::{dense_rank, percent_rank, rank, Rank, RankType};
 pub use crate::window::row_number::RowNumber;
 
 pub use binary::{binary, BinaryExpr};
 pub use case::{case, CaseExpr};
 pub use cast::{
-    cast, cast_column, cast_with_options, CastExpr, DEFAULT_DATAFUSION_CAST_OPTIONS,
+    cast, cast_column, cast_in_time_zone, cast_with_options, CastExpr,
+    DEFAULT_DATAFUSION_CAST_OPTIONS,
 };
 pub use column::{col, Column};
 pub use datetime::DateTimeIntervalExpr;
@@ -79,10 +82,12 @@ pub use in_list::{in_list, InListExpr};
 pub use is_not_null::{is_not_null, IsNotNullExpr};
 pub use is_null::{is_null, IsNullExpr};
 pub use literal::{lit, Literal};
+pub use memoized::{memoize_constants, MemoizedExpr};
 pub use negative::{negative, NegativeExpr};
 pub use no_op::NoOp;
 pub use not::{not, NotExpr};
 pub use nullif::nullif_func;
+pub use scalar_subquery::{ScalarSubqueryExpr, ScalarSubqueryValue};
 pub use try_cast::{try_cast, TryCastExpr};
 
 /// returns the name of the state
@@ -0,0 +1,224 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Uncorrelated scalar subquery expressions, evaluating to the value of a
+//! subquery executed once per query
+
+use std::any::Any;
+use std::future::Future;
+use std::sync::{Arc, RwLock, Weak};
+
+use arrow::{
+    datatypes::{DataType, Schema},
+    record_batch::RecordBatch,
+};
+
+use crate::physical_expr::down_cast_any_ref;
+use crate::PhysicalExpr;
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+use datafusion_expr::ColumnarValue;
+
+/// The value of an uncorrelated scalar subquery, shared by all the
+/// references to the subquery in a query, such that the subquery is executed
+/// once per execution of the query.
+///
+/// The value is bound to the execution that computed it, identified by its
+/// task context: executing the plan again with another context executes the
+/// subquery again. Concurrent executions of the same plan with different
+/// contexts are not supported, as the expressions evaluate to the value of the
+/// latest execution.
+#[derive(Debug, Default)]
+pub struct ScalarSubqueryValue {
+    value: RwLock<Option<ExecutedValue>>,
+    /// Held while the subquery executes, so that the references waiting for
+    /// the value do not execute it again
+    execution: futures::lock::Mutex<()>,
+}
+
+#[derive(Debug)]
+struct ExecutedValue {
+    /// The context of the execution that computed the value. The weak
+    /// reference keeps its allocation, so that another context can't be
+    /// mistaken for it.
+    execution: Weak<dyn Any + Send + Sync>,
+    value: ScalarValue,
+}
+
+impl ScalarSubqueryValue {
+    /// Create a value not computed yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value, if the subquery was executed
+    pub fn get(&self) -> Option<ScalarValue> {
+        self.value
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|executed| executed.value.clone())
+    }
+
+    /// Returns the value, computing it with `execute` if the subquery was
+    /// not executed yet by `execution`, the task context of the execution.
+    /// Concurrent calls wait for the first one to compute the value, and a
+    /// failed execution is retried by the next call.
+    pub async fn get_or_try_init<F, Fut>(
+        &self,
+        execution: &Arc<dyn Any + Send + Sync>,
+        execute: F,
+    ) -> Result<ScalarValue>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ScalarValue>>,
+    {
+        let _execution = self.execution.lock().await;
+        if let Some(executed) = self.value.read().unwrap().as_ref() {
+            if executed.execution.ptr_eq(&Arc::downgrade(execution)) {
+                return Ok(executed.value.clone());
+            }
+        }
+        let value = execute().await?;
+        *self.value.write().unwrap() = Some(ExecutedValue {
+            execution: Arc::downgrade(execution),
+            value: value.clone(),
+        });
+        Ok(value)
+    }
+}
+
+/// Evaluates to the value of an uncorrelated scalar subquery, which is
+/// executed before the expression is evaluated, by the operator of the
+/// expression's input
+#[derive(Debug)]
+pub struct ScalarSubqueryExpr {
+    data_type: DataType,
+    value: Arc<ScalarSubqueryValue>,
+}
+
+impl ScalarSubqueryExpr {
+    /// Create an expression evaluating to `value`, of type `data_type`
+    pub fn new(data_type: DataType, value: Arc<ScalarSubqueryValue>) -> Self {
+        Self { data_type, value }
+    }
+
+    /// The value of the subquery
+    pub fn value(&self) -> &Arc<ScalarSubqueryValue> {
+        &self.value
+    }
+}
+
+impl std::fmt::Display for ScalarSubqueryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "(<subquery>)")
+    }
+}
+
+impl PhysicalExpr for ScalarSubqueryExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(self.data_type.clone())
+    }
+
+    fn nullable(&self, _input_schema: &Schema) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn evaluate(&self, _batch: &RecordBatch) -> Result<ColumnarValue> {
+        match self.value.get() {
+            Some(value) => Ok(ColumnarValue::Scalar(value)),
+            None => Err(DataFusionError::Internal(
+                "Scalar subquery evaluated before its execution".to_string(),
+            )),
+        }
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Ok(self)
+    }
+}
+
+impl PartialEq<dyn Any> for ScalarSubqueryExpr {
+    fn eq(&self, other: &dyn Any) -> bool {
+        down_cast_any_ref(other)
+            .downcast_ref::<Self>()
+            .map(|x| Arc::ptr_eq(&self.value, &x.value))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::Field;
+
+    #[test]
+    fn evaluate_once_executed() -> Result<()> {
+        let value = Arc::new(ScalarSubqueryValue::new());
+        let expr = ScalarSubqueryExpr::new(DataType::Int64, value.clone());
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let batch = RecordBatch::new_empty(schema);
+        assert!(expr.evaluate(&batch).is_err());
+
+        let execution: Arc<dyn Any + Send + Sync> = Arc::new(());
+        let mut executions = 0;
+        for _ in 0..2 {
+            let result =
+                futures::executor::block_on(value.get_or_try_init(&execution, || {
+                    executions += 1;
+                    async { Ok(ScalarValue::Int64(Some(42))) }
+                }))?;
+            assert_eq!(result, ScalarValue::Int64(Some(42)));
+        }
+        assert_eq!(executions, 1);
+
+        match expr.evaluate(&batch)? {
+            ColumnarValue::Scalar(value) => {
+                assert_eq!(value, ScalarValue::Int64(Some(42)))
+            }
+            _ => panic!("expected a scalar"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn execute_again_in_another_execution() -> Result<()> {
+        let value = ScalarSubqueryValue::new();
+        for i in 0..2 {
+            let execution: Arc<dyn Any + Send + Sync> = Arc::new(i);
+            let result = futures::executor::block_on(
+                value.get_or_try_init(&execution, || async move {
+                    Ok(ScalarValue::Int64(Some(i)))
+                }),
+            )?;
+            assert_eq!(result, ScalarValue::Int64(Some(i)));
+        }
+        assert_eq!(value.get(), Some(ScalarValue::Int64(Some(1))));
+        Ok(())
+    }
+}
@@ -34,8 +34,8 @@ use crate::execution_props::ExecutionProps;
 use crate::{
     array_expressions, conditional_expressions, datetime_expressions,
     expressions::{cast_column, nullif_func, DEFAULT_DATAFUSION_CAST_OPTIONS},
-    math_expressions, string_expressions, struct_expressions, PhysicalExpr,
-    ScalarFunctionExpr,
+    math_expressions, string_expressions, struct_expressions, zorder_expressions,
+    PhysicalExpr, ScalarFunctionExpr,
 };
 use arrow::{
     array::ArrayRef,
@@ -179,12 +179,15 @@ pub fn create_physical_expr(
         _ => create_physical_fun(fun, execution_props)?,
     };
 
-    Ok(Arc::new(ScalarFunctionExpr::new(
-        &format!("{}", fun),
-        fun_expr,
-        input_phy_exprs.to_vec(),
-        &data_type,
-    )))
+    Ok(Arc::new(
+        ScalarFunctionExpr::new(
+            &format!("{}", fun),
+            fun_expr,
+            input_phy_exprs.to_vec(),
+            &data_type,
+        )
+        .with_volatility(fun.volatility()),
+    ))
 }
 
 #[cfg(feature = "crypto_expressions")]
@@ -333,7 +336,11 @@ pub fn create_physical_fun(
         BuiltinScalarFunction::Ln => Arc::new(math_expressions::ln),
         BuiltinScalarFunction::Log10 => Arc::new(math_expressions::log10),
         BuiltinScalarFunction::Log2 => Arc::new(math_expressions::log2),
-        BuiltinScalarFunction::Random => Arc::new(math_expressions::random),
+        BuiltinScalarFunction::Random => match &execution_props.random_generator {
+            // bind the seeded generator of the query at plan time
+            Some(rng) => Arc::new(math_expressions::make_seeded_random(rng.clone())),
+            None => Arc::new(math_expressions::random),
+        },
         BuiltinScalarFunction::Round => Arc::new(math_expressions::round),
         BuiltinScalarFunction::Signum => Arc::new(math_expressions::signum),
         BuiltinScalarFunction::Sin => Arc::new(math_expressions::sin),
@@ -350,6 +357,7 @@ pub fn create_physical_fun(
         // string functions
         BuiltinScalarFunction::MakeArray => Arc::new(array_expressions::array),
         BuiltinScalarFunction::Struct => Arc::new(struct_expressions::struct_expr),
+        BuiltinScalarFunction::ZOrder => Arc::new(zorder_expressions::zorder),
         BuiltinScalarFunction::Ascii => Arc::new(|args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::ascii::<i32>)(args)
@@ -419,7 +427,9 @@ pub fn create_physical_fun(
             Arc::new(|args| make_scalar_function(string_expressions::concat_ws)(args))
         }
         BuiltinScalarFunction::DatePart => Arc::new(datetime_expressions::date_part),
-        BuiltinScalarFunction::DateTrunc => Arc::new(datetime_expressions::date_trunc),
+        BuiltinScalarFunction::DateTrunc => Arc::new(
+            datetime_expressions::make_date_trunc(execution_props.time_zone),
+        ),
         BuiltinScalarFunction::DateBin => Arc::new(datetime_expressions::date_bin),
         BuiltinScalarFunction::Now => {
             // bind value for now at plan time
@@ -427,6 +437,20 @@ pub fn create_physical_fun(
                 execution_props.query_execution_start_time,
             ))
         }
+        BuiltinScalarFunction::CurrentDate => {
+            // bind value for current_date at plan time
+            Arc::new(datetime_expressions::make_current_date(
+                execution_props.query_execution_start_time,
+                execution_props.time_zone,
+            ))
+        }
+        BuiltinScalarFunction::CurrentTime => {
+            // bind value for current_time at plan time
+            Arc::new(datetime_expressions::make_current_time(
+                execution_props.query_execution_start_time,
+                execution_props.time_zone,
+            ))
+        }
         BuiltinScalarFunction::InitCap => Arc::new(|args| match args[0].data_type() {
             DataType::Utf8 => {
                 make_scalar_function(string_expressions::initcap::<i32>)(args)
@@ -2748,7 +2772,12 @@ mod tests {
         let execution_props = ExecutionProps::new();
         let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
 
-        let funs = [BuiltinScalarFunction::Now, BuiltinScalarFunction::Random];
+        let funs = [
+            BuiltinScalarFunction::Now,
+            BuiltinScalarFunction::CurrentDate,
+            BuiltinScalarFunction::CurrentTime,
+            BuiltinScalarFunction::Random,
+        ];
 
         for fun in funs.iter() {
             create_physical_expr_with_type_coercion(fun, &[], &schema, &execution_props)?;
@@ -0,0 +1,437 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Constraint propagation solver computing the bounds of the values of the
+//! sub-expressions of an expression, given the bounds of its columns and of
+//! its result.
+//!
+//! The bounds are first evaluated bottom-up, from the leaves of the
+//! expression to its root, with [`ExprIntervalGraph::evaluate_bounds`]. The
+//! bounds required of the root, such as `[true, true]` for the predicate of a
+//! filter, are then propagated top-down with
+//! [`ExprIntervalGraph::update_ranges`], narrowing the bounds of the
+//! children of each node. For example, `a + b > 10` with `a` in `[0, 5]`
+//! narrows `b` to `[5, NULL]`.
+
+use std::sync::Arc;
+
+use arrow::datatypes::Schema;
+use datafusion_common::Result;
+use datafusion_expr::Operator;
+
+use super::Interval;
+use crate::expressions::{BinaryExpr, Literal, NotExpr};
+use crate::PhysicalExpr;
+
+/// The result of propagating the bounds of the root of an
+/// [`ExprIntervalGraph`] to its leaves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationResult {
+    /// No value of the leaves satisfies the bounds of the root, such as for
+    /// `a > 10 AND a < 5`
+    Infeasible,
+    /// The bounds of the nodes were narrowed to the values that may satisfy
+    /// the bounds of the root
+    Success,
+}
+
+/// A node of an [`ExprIntervalGraph`]
+#[derive(Debug, Clone)]
+struct ExprIntervalGraphNode {
+    expr: Arc<dyn PhysicalExpr>,
+    /// The indexes of the nodes of the children of `expr`, if it is
+    /// supported by the solver. The other expressions are leaves.
+    children: Vec<usize>,
+    interval: Interval,
+}
+
+/// The graph of the sub-expressions of an expression with the bounds of
+/// their values. Equal sub-expressions, such as the occurrences of a column,
+/// share the same node, so that the bounds derived for one occurrence hold
+/// for the others.
+///
+/// The supported expressions are the comparisons, `+`, `-`, `AND`, `OR` and
+/// `NOT`. The other expressions are leaves, unbounded unless their bounds are
+/// assigned with [`ExprIntervalGraph::assign_interval`], except literals that
+/// are bounded by their value.
+#[derive(Debug, Clone)]
+pub struct ExprIntervalGraph {
+    /// The nodes, each after the nodes of its children; the root is last
+    nodes: Vec<ExprIntervalGraphNode>,
+}
+
+impl ExprIntervalGraph {
+    /// Create the graph of `expr`, evaluated on batches of `schema`
+    pub fn try_new(expr: Arc<dyn PhysicalExpr>, schema: &Schema) -> Result<Self> {
+        let mut graph = ExprIntervalGraph { nodes: vec![] };
+        graph.add_node(expr, schema)?;
+        Ok(graph)
+    }
+
+    fn add_node(
+        &mut self,
+        expr: Arc<dyn PhysicalExpr>,
+        schema: &Schema,
+    ) -> Result<usize> {
+        if let Some(index) = self.node_index(&expr) {
+            return Ok(index);
+        }
+        let children = if is_supported(&expr) {
+            expr.children()
+                .into_iter()
+                .map(|child| self.add_node(child, schema))
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            vec![]
+        };
+        let interval = match expr.as_any().downcast_ref::<Literal>() {
+            Some(literal) if !literal.value().is_null() => {
+                Interval::singleton(literal.value().clone())
+            }
+            _ => Interval::unbounded(&expr.data_type(schema)?)?,
+        };
+        self.nodes.push(ExprIntervalGraphNode {
+            expr,
+            children,
+            interval,
+        });
+        Ok(self.nodes.len() - 1)
+    }
+
+    fn node_index(&self, expr: &Arc<dyn PhysicalExpr>) -> Option<usize> {
+        self.nodes.iter().position(|node| node.expr.eq(expr))
+    }
+
+    /// Returns the bounds of `expr` if it is a node of the graph
+    pub fn interval(&self, expr: &Arc<dyn PhysicalExpr>) -> Option<&Interval> {
+        self.node_index(expr)
+            .map(|index| &self.nodes[index].interval)
+    }
+
+    /// Returns the sub-expressions of the graph with their bounds
+    pub fn intervals(&self) -> impl Iterator<Item = (&Arc<dyn PhysicalExpr>, &Interval)> {
+        self.nodes.iter().map(|node| (&node.expr, &node.interval))
+    }
+
+    /// Sets the bounds of `expr`, such as those of a column given by its
+    /// statistics. Returns false if `expr` is not a node of the graph.
+    pub fn assign_interval(
+        &mut self,
+        expr: &Arc<dyn PhysicalExpr>,
+        interval: Interval,
+    ) -> bool {
+        match self.node_index(expr) {
+            Some(index) => {
+                self.nodes[index].interval = interval;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Computes the bounds of each node from the bounds of its children, and
+    /// returns the bounds of the root
+    pub fn evaluate_bounds(&mut self) -> Result<&Interval> {
+        for index in 0..self.nodes.len() {
+            let node = &self.nodes[index];
+            if node.children.is_empty() {
+                continue;
+            }
+            let children = node
+                .children
+                .iter()
+                .map(|child| &self.nodes[*child].interval)
+                .collect::<Vec<_>>();
+            let interval = evaluate_node(&node.expr, &children)?;
+            if let Some(interval) = interval {
+                self.nodes[index].interval = interval;
+            }
+        }
+        Ok(&self.nodes[self.nodes.len() - 1].interval)
+    }
+
+    /// Narrows the bounds of the nodes to the values for which the root may
+    /// be in `root_interval`. The bounds of the nodes are evaluated first.
+    pub fn update_ranges(
+        &mut self,
+        root_interval: Interval,
+    ) -> Result<PropagationResult> {
+        self.evaluate_bounds()?;
+        let root = self.nodes.len() - 1;
+        match self.nodes[root].interval.intersect(&root_interval)? {
+            Some(interval) => self.nodes[root].interval = interval,
+            None => return Ok(PropagationResult::Infeasible),
+        }
+        // each node is after its parents in reverse order, so that a node
+        // shared by several parents is narrowed by all of them before its
+        // own children are
+        for index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[index];
+            if node.children.is_empty() {
+                continue;
+            }
+            let children = node
+                .children
+                .iter()
+                .map(|child| &self.nodes[*child].interval)
+                .collect::<Vec<_>>();
+            let narrowed = match propagate_node(&node.expr, &node.interval, &children)? {
+                Some(narrowed) => narrowed,
+                None => return Ok(PropagationResult::Infeasible),
+            };
+            let children = node.children.clone();
+            for (child, interval) in children.into_iter().zip(narrowed) {
+                self.nodes[child].interval = interval;
+            }
+        }
+        Ok(PropagationResult::Success)
+    }
+}
+
+fn is_supported(expr: &Arc<dyn PhysicalExpr>) -> bool {
+    if let Some(binary) = expr.as_any().downcast_ref::<BinaryExpr>() {
+        matches!(
+            binary.op(),
+            Operator::Eq
+                | Operator::Gt
+                | Operator::GtEq
+                | Operator::Lt
+                | Operator::LtEq
+                | Operator::Plus
+                | Operator::Minus
+                | Operator::And
+                | Operator::Or
+        )
+    } else {
+        expr.as_any().is::<NotExpr>()
+    }
+}
+
+/// Returns the bounds of `expr` from those of its children, or `None` if they
+/// are of different types
+fn evaluate_node(
+    expr: &Arc<dyn PhysicalExpr>,
+    children: &[&Interval],
+) -> Result<Option<Interval>> {
+    if expr.as_any().is::<NotExpr>() {
+        return Ok(Some(children[0].not()));
+    }
+    let binary = match expr.as_any().downcast_ref::<BinaryExpr>() {
+        Some(binary) => binary,
+        None => return Ok(None),
+    };
+    let (left, right) = (children[0], children[1]);
+    if left.get_datatype() != right.get_datatype() {
+        return Ok(None);
+    }
+    Ok(Some(match binary.op() {
+        Operator::Eq => left.equal(right),
+        Operator::Gt => left.gt(right),
+        Operator::GtEq => left.gt_eq(right),
+        Operator::Lt => left.lt(right),
+        Operator::LtEq => left.lt_eq(right),
+        Operator::Plus => left.add(right)?,
+        Operator::Minus => left.sub(right)?,
+        Operator::And => left.and(right),
+        Operator::Or => left.or(right),
+        _ => return Ok(None),
+    }))
+}
+
+/// Returns the bounds of the children of `expr` narrowed to the values for
+/// which `expr` may be in `interval`, or `None` if there are no such values
+fn propagate_node(
+    expr: &Arc<dyn PhysicalExpr>,
+    interval: &Interval,
+    children: &[&Interval],
+) -> Result<Option<Vec<Interval>>> {
+    if expr.as_any().is::<NotExpr>() {
+        return Ok(children[0]
+            .intersect(&interval.not())?
+            .map(|child| vec![child]));
+    }
+    let binary = match expr.as_any().downcast_ref::<BinaryExpr>() {
+        Some(binary) => binary,
+        None => return Ok(Some(children.iter().map(|c| (*c).clone()).collect())),
+    };
+    let (left, right) = (children[0], children[1]);
+    if left.get_datatype() != right.get_datatype() {
+        return Ok(Some(vec![left.clone(), right.clone()]));
+    }
+    let unbounded = Interval::unbounded(&left.get_datatype())?;
+    // the bounds of the children such that `left >= right`
+    let at_least = |left: &Interval, right: &Interval| -> Result<Option<Vec<Interval>>> {
+        let left_bound = Interval::new(right.lower.clone(), unbounded.upper.clone());
+        let right_bound = Interval::new(unbounded.lower.clone(), left.upper.clone());
+        Ok(left
+            .intersect(&left_bound)?
+            .zip(right.intersect(&right_bound)?)
+            .map(|(left, right)| vec![left, right]))
+    };
+    let narrowed = match binary.op() {
+        Operator::And if interval.is_certainly_true() => left
+            .intersect(interval)?
+            .zip(right.intersect(interval)?)
+            .map(|(left, right)| vec![left, right]),
+        Operator::Or if interval.is_certainly_false() => left
+            .intersect(interval)?
+            .zip(right.intersect(interval)?)
+            .map(|(left, right)| vec![left, right]),
+        Operator::Gt | Operator::GtEq if interval.is_certainly_true() => {
+            at_least(left, right)?
+        }
+        Operator::Lt | Operator::LtEq if interval.is_certainly_false() => {
+            at_least(left, right)?
+        }
+        Operator::Lt | Operator::LtEq if interval.is_certainly_true() => {
+            at_least(right, left)?.map(|mut children| {
+                children.swap(0, 1);
+                children
+            })
+        }
+        Operator::Gt | Operator::GtEq if interval.is_certainly_false() => {
+            at_least(right, left)?.map(|mut children| {
+                children.swap(0, 1);
+                children
+            })
+        }
+        Operator::Eq if interval.is_certainly_true() => left
+            .intersect(right)?
+            .map(|common| vec![common.clone(), common]),
+        // left = interval - right, right = interval - left
+        Operator::Plus => left
+            .intersect(&interval.sub(right)?)?
+            .zip(right.intersect(&interval.sub(left)?)?)
+            .map(|(left, right)| vec![left, right]),
+        // left = interval + right, right = left - interval
+        Operator::Minus => left
+            .intersect(&interval.add(right)?)?
+            .zip(right.intersect(&left.sub(interval)?)?)
+            .map(|(left, right)| vec![left, right]),
+        _ => Some(vec![left.clone(), right.clone()]),
+    };
+    Ok(narrowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::{binary, col, lit, not};
+    use arrow::datatypes::{DataType, Field};
+    use datafusion_common::ScalarValue;
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ])
+    }
+
+    fn interval(lower: Option<i64>, upper: Option<i64>) -> Interval {
+        Interval::new(ScalarValue::Int64(lower), ScalarValue::Int64(upper))
+    }
+
+    fn build_graph(
+        expr: Arc<dyn PhysicalExpr>,
+        a: Interval,
+        b: Interval,
+    ) -> Result<ExprIntervalGraph> {
+        let schema = schema();
+        let mut graph = ExprIntervalGraph::try_new(expr, &schema)?;
+        graph.assign_interval(&col("a", &schema)?, a);
+        graph.assign_interval(&col("b", &schema)?, b);
+        Ok(graph)
+    }
+
+    #[test]
+    fn evaluate_bounds() -> Result<()> {
+        let schema = schema();
+        let a = col("a", &schema)?;
+        let b = col("b", &schema)?;
+
+        // a + b > 20 with a in [0, 10] and b in [0, 10]
+        let expr = binary(
+            binary(a.clone(), Operator::Plus, b.clone(), &schema)?,
+            Operator::Gt,
+            lit(20i64),
+            &schema,
+        )?;
+        let mut graph = build_graph(
+            expr,
+            interval(Some(0), Some(10)),
+            interval(Some(0), Some(10)),
+        )?;
+        assert!(graph.evaluate_bounds()?.is_certainly_false());
+
+        // NOT (a < 5) OR b = 3 with a in [5, 10]
+        let expr = binary(
+            not(binary(a, Operator::Lt, lit(5i64), &schema)?)?,
+            Operator::Or,
+            binary(b, Operator::Eq, lit(3i64), &schema)?,
+            &schema,
+        )?;
+        let mut graph =
+            build_graph(expr, interval(Some(5), Some(10)), interval(None, None))?;
+        assert!(graph.evaluate_bounds()?.is_certainly_true());
+        Ok(())
+    }
+
+    #[test]
+    fn update_ranges() -> Result<()> {
+        let schema = schema();
+        let a = col("a", &schema)?;
+        let b = col("b", &schema)?;
+
+        // a + b > 10 AND a <= 4 narrows a to [0, 4] and b to [6, 20]
+        let expr = binary(
+            binary(
+                binary(a.clone(), Operator::Plus, b.clone(), &schema)?,
+                Operator::Gt,
+                lit(10i64),
+                &schema,
+            )?,
+            Operator::And,
+            binary(a.clone(), Operator::LtEq, lit(4i64), &schema)?,
+            &schema,
+        )?;
+        let mut graph = build_graph(
+            expr,
+            interval(Some(0), Some(10)),
+            interval(Some(0), Some(20)),
+        )?;
+        assert_eq!(
+            graph.update_ranges(Interval::certainly_true())?,
+            PropagationResult::Success
+        );
+        assert_eq!(graph.interval(&a), Some(&interval(Some(0), Some(4))));
+        assert_eq!(graph.interval(&b), Some(&interval(Some(6), Some(20))));
+
+        // a > 10 AND a < 5 is never true
+        let expr = binary(
+            binary(a.clone(), Operator::Gt, lit(10i64), &schema)?,
+            Operator::And,
+            binary(a, Operator::Lt, lit(5i64), &schema)?,
+            &schema,
+        )?;
+        let mut graph = build_graph(expr, interval(None, None), interval(None, None))?;
+        assert_eq!(
+            graph.update_ranges(Interval::certainly_true())?,
+            PropagationResult::Infeasible
+        );
+        Ok(())
+    }
+}
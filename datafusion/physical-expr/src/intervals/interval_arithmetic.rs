@@ -0,0 +1,399 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Interval arithmetic on the bounds of the values of an expression
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use arrow::datatypes::DataType;
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+
+/// The closed range `[lower, upper]` of the values an expression can take. A
+/// null bound is unbounded, so that `[NULL, 10]` holds all the values up to
+/// 10. The result of a predicate is a boolean interval: `[true, true]` if it
+/// is certainly true, `[false, false]` if it is certainly false and
+/// `[false, true]` otherwise.
+///
+/// The bounds are those of the non-null values: a predicate that is certainly
+/// true, such as `a >= 0` for `a` in `[0, 10]`, is null for a null `a`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    /// The lower bound, or a null value if unbounded
+    pub lower: ScalarValue,
+    /// The upper bound, or a null value if unbounded
+    pub upper: ScalarValue,
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lower, self.upper)
+    }
+}
+
+impl Interval {
+    /// Create the interval `[lower, upper]`
+    pub fn new(lower: ScalarValue, upper: ScalarValue) -> Self {
+        Interval { lower, upper }
+    }
+
+    /// The interval of all the values of `data_type`
+    pub fn unbounded(data_type: &DataType) -> Result<Self> {
+        if data_type == &DataType::Boolean {
+            return Ok(Self::uncertain());
+        }
+        let null = ScalarValue::try_from(data_type)?;
+        Ok(Interval::new(null.clone(), null))
+    }
+
+    /// The interval holding the single value `value`
+    pub fn singleton(value: ScalarValue) -> Self {
+        Interval::new(value.clone(), value)
+    }
+
+    /// The result of a predicate that is always true
+    pub fn certainly_true() -> Self {
+        Self::singleton(ScalarValue::Boolean(Some(true)))
+    }
+
+    /// The result of a predicate that is always false
+    pub fn certainly_false() -> Self {
+        Self::singleton(ScalarValue::Boolean(Some(false)))
+    }
+
+    /// The result of a predicate that may be true or false
+    pub fn uncertain() -> Self {
+        Interval::new(
+            ScalarValue::Boolean(Some(false)),
+            ScalarValue::Boolean(Some(true)),
+        )
+    }
+
+    /// The data type of the bounds
+    pub fn get_datatype(&self) -> DataType {
+        self.lower.get_datatype()
+    }
+
+    /// Returns true if this is the result of a predicate that is always true
+    pub fn is_certainly_true(&self) -> bool {
+        self == &Self::certainly_true()
+    }
+
+    /// Returns true if this is the result of a predicate that is always false
+    pub fn is_certainly_false(&self) -> bool {
+        self == &Self::certainly_false()
+    }
+
+    /// Returns true if neither bound is unbounded
+    pub fn is_bounded(&self) -> bool {
+        !self.lower.is_null() && !self.upper.is_null()
+    }
+
+    /// Returns the interval of the values in both `self` and `other`, or
+    /// `None` if they have no value in common
+    pub fn intersect(&self, other: &Interval) -> Result<Option<Interval>> {
+        check_same_type(self, other)?;
+        let lower = match cmp_bounds(&self.lower, &other.lower) {
+            Some(Ordering::Less) => other.lower.clone(),
+            _ if self.lower.is_null() => other.lower.clone(),
+            _ if other.lower.is_null() => self.lower.clone(),
+            _ => self.lower.clone(),
+        };
+        let upper = match cmp_bounds(&self.upper, &other.upper) {
+            Some(Ordering::Greater) => other.upper.clone(),
+            _ if self.upper.is_null() => other.upper.clone(),
+            _ if other.upper.is_null() => self.upper.clone(),
+            _ => self.upper.clone(),
+        };
+        if matches!(cmp_bounds(&lower, &upper), Some(Ordering::Greater)) {
+            return Ok(None);
+        }
+        Ok(Some(Interval::new(lower, upper)))
+    }
+
+    /// The interval of `a + b` for the values `a` of `self` and `b` of `other`
+    pub fn add(&self, other: &Interval) -> Result<Interval> {
+        check_same_type(self, other)?;
+        Ok(Interval::new(
+            bound_op(&self.lower, &other.lower, Operation::Add)?,
+            bound_op(&self.upper, &other.upper, Operation::Add)?,
+        ))
+    }
+
+    /// The interval of `a - b` for the values `a` of `self` and `b` of `other`
+    pub fn sub(&self, other: &Interval) -> Result<Interval> {
+        check_same_type(self, other)?;
+        Ok(Interval::new(
+            bound_op(&self.lower, &other.upper, Operation::Sub)?,
+            bound_op(&self.upper, &other.lower, Operation::Sub)?,
+        ))
+    }
+
+    /// The result of `a > b` for the values `a` of `self` and `b` of `other`
+    pub fn gt(&self, other: &Interval) -> Interval {
+        if matches!(
+            cmp_bounds(&self.lower, &other.upper),
+            Some(Ordering::Greater)
+        ) {
+            Self::certainly_true()
+        } else if matches!(
+            cmp_bounds(&self.upper, &other.lower),
+            Some(Ordering::Less | Ordering::Equal)
+        ) {
+            Self::certainly_false()
+        } else {
+            Self::uncertain()
+        }
+    }
+
+    /// The result of `a >= b` for the values `a` of `self` and `b` of `other`
+    pub fn gt_eq(&self, other: &Interval) -> Interval {
+        other.gt(self).not()
+    }
+
+    /// The result of `a < b` for the values `a` of `self` and `b` of `other`
+    pub fn lt(&self, other: &Interval) -> Interval {
+        other.gt(self)
+    }
+
+    /// The result of `a <= b` for the values `a` of `self` and `b` of `other`
+    pub fn lt_eq(&self, other: &Interval) -> Interval {
+        self.gt(other).not()
+    }
+
+    /// The result of `a = b` for the values `a` of `self` and `b` of `other`
+    pub fn equal(&self, other: &Interval) -> Interval {
+        if matches!(
+            cmp_bounds(&self.lower, &other.upper),
+            Some(Ordering::Greater)
+        ) || matches!(cmp_bounds(&self.upper, &other.lower), Some(Ordering::Less))
+        {
+            Self::certainly_false()
+        } else if self.is_bounded()
+            && self.lower == self.upper
+            && other.lower == other.upper
+            && self.lower == other.lower
+        {
+            Self::certainly_true()
+        } else {
+            Self::uncertain()
+        }
+    }
+
+    /// The result of `a AND b` for the booleans `a` of `self` and `b` of `other`
+    pub fn and(&self, other: &Interval) -> Interval {
+        if self.is_certainly_false() || other.is_certainly_false() {
+            Self::certainly_false()
+        } else if self.is_certainly_true() && other.is_certainly_true() {
+            Self::certainly_true()
+        } else {
+            Self::uncertain()
+        }
+    }
+
+    /// The result of `a OR b` for the booleans `a` of `self` and `b` of `other`
+    pub fn or(&self, other: &Interval) -> Interval {
+        if self.is_certainly_true() || other.is_certainly_true() {
+            Self::certainly_true()
+        } else if self.is_certainly_false() && other.is_certainly_false() {
+            Self::certainly_false()
+        } else {
+            Self::uncertain()
+        }
+    }
+
+    /// The result of `NOT a` for the booleans `a` of `self`
+    pub fn not(&self) -> Interval {
+        if self.is_certainly_true() {
+            Self::certainly_false()
+        } else if self.is_certainly_false() {
+            Self::certainly_true()
+        } else {
+            Self::uncertain()
+        }
+    }
+
+    /// The fraction of the values of `self` that are in `narrowed`, assuming
+    /// they are uniformly distributed, or `None` if either interval is not
+    /// bounded or the distance between its bounds is unknown
+    pub fn fraction_in(&self, narrowed: &Interval) -> Option<f64> {
+        if !self.is_bounded() || !narrowed.is_bounded() {
+            return None;
+        }
+        // the bounds are inclusive, so the distance is one less than the range
+        let range = self.upper.distance(&self.lower)? + 1;
+        let narrowed_range = narrowed.upper.distance(&narrowed.lower)? + 1;
+        Some((narrowed_range as f64 / range as f64).min(1.0))
+    }
+}
+
+/// Compares two bounds, or returns `None` if either is unbounded
+fn cmp_bounds(left: &ScalarValue, right: &ScalarValue) -> Option<Ordering> {
+    if left.is_null() || right.is_null() {
+        return None;
+    }
+    left.partial_cmp(right)
+}
+
+fn check_same_type(left: &Interval, right: &Interval) -> Result<()> {
+    let left_type = left.get_datatype();
+    let right_type = right.get_datatype();
+    if left_type != right_type {
+        return Err(DataFusionError::Internal(format!(
+            "Intervals must have the same data type, got {} and {}",
+            left_type, right_type
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Operation {
+    Add,
+    Sub,
+}
+
+/// Applies `op` to two bounds. The result is unbounded if either bound is, or
+/// if the result overflows or cannot be computed for the type of the bounds.
+fn bound_op(
+    left: &ScalarValue,
+    right: &ScalarValue,
+    op: Operation,
+) -> Result<ScalarValue> {
+    macro_rules! checked_op {
+        ($LEFT:expr, $RIGHT:expr, $SCALAR:ident) => {
+            ScalarValue::$SCALAR(match op {
+                Operation::Add => $LEFT.checked_add(*$RIGHT),
+                Operation::Sub => $LEFT.checked_sub(*$RIGHT),
+            })
+        };
+    }
+    macro_rules! float_op {
+        ($LEFT:expr, $RIGHT:expr, $SCALAR:ident) => {
+            ScalarValue::$SCALAR(Some(match op {
+                Operation::Add => $LEFT + $RIGHT,
+                Operation::Sub => $LEFT - $RIGHT,
+            }))
+        };
+    }
+    Ok(match (left, right) {
+        (ScalarValue::Int8(Some(l)), ScalarValue::Int8(Some(r))) => {
+            checked_op!(l, r, Int8)
+        }
+        (ScalarValue::Int16(Some(l)), ScalarValue::Int16(Some(r))) => {
+            checked_op!(l, r, Int16)
+        }
+        (ScalarValue::Int32(Some(l)), ScalarValue::Int32(Some(r))) => {
+            checked_op!(l, r, Int32)
+        }
+        (ScalarValue::Int64(Some(l)), ScalarValue::Int64(Some(r))) => {
+            checked_op!(l, r, Int64)
+        }
+        (ScalarValue::UInt8(Some(l)), ScalarValue::UInt8(Some(r))) => {
+            checked_op!(l, r, UInt8)
+        }
+        (ScalarValue::UInt16(Some(l)), ScalarValue::UInt16(Some(r))) => {
+            checked_op!(l, r, UInt16)
+        }
+        (ScalarValue::UInt32(Some(l)), ScalarValue::UInt32(Some(r))) => {
+            checked_op!(l, r, UInt32)
+        }
+        (ScalarValue::UInt64(Some(l)), ScalarValue::UInt64(Some(r))) => {
+            checked_op!(l, r, UInt64)
+        }
+        (ScalarValue::Float32(Some(l)), ScalarValue::Float32(Some(r))) => {
+            float_op!(l, r, Float32)
+        }
+        (ScalarValue::Float64(Some(l)), ScalarValue::Float64(Some(r))) => {
+            float_op!(l, r, Float64)
+        }
+        _ => ScalarValue::try_from(&left.get_datatype())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(lower: Option<i64>, upper: Option<i64>) -> Interval {
+        Interval::new(ScalarValue::Int64(lower), ScalarValue::Int64(upper))
+    }
+
+    #[test]
+    fn intersect() -> Result<()> {
+        let cases = vec![
+            (
+                interval(Some(0), Some(10)),
+                interval(Some(5), Some(20)),
+                Some(interval(Some(5), Some(10))),
+            ),
+            (
+                interval(None, Some(10)),
+                interval(Some(5), None),
+                Some(interval(Some(5), Some(10))),
+            ),
+            (
+                interval(None, None),
+                interval(None, None),
+                Some(interval(None, None)),
+            ),
+            (interval(Some(0), Some(4)), interval(Some(5), None), None),
+        ];
+        for (left, right, expected) in cases {
+            assert_eq!(left.intersect(&right)?, expected);
+            assert_eq!(right.intersect(&left)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic() -> Result<()> {
+        let left = interval(Some(0), Some(10));
+        let right = interval(Some(-5), None);
+        assert_eq!(left.add(&right)?, interval(Some(-5), None));
+        assert_eq!(left.sub(&right)?, interval(None, Some(15)));
+
+        // overflows are unbounded
+        let max = interval(Some(0), Some(i64::MAX));
+        assert_eq!(max.add(&left)?, interval(Some(0), None));
+        Ok(())
+    }
+
+    #[test]
+    fn comparisons() {
+        let left = interval(Some(0), Some(10));
+        assert!(left.gt(&interval(Some(-5), Some(-1))).is_certainly_true());
+        assert!(left.gt(&interval(Some(10), None)).is_certainly_false());
+        assert_eq!(left.gt(&interval(Some(5), Some(5))), Interval::uncertain());
+        assert!(left.gt_eq(&interval(None, Some(0))).is_certainly_true());
+        assert!(left.lt(&interval(Some(0), Some(0))).is_certainly_false());
+        assert!(left.equal(&interval(Some(11), None)).is_certainly_false());
+        assert_eq!(
+            left.equal(&interval(Some(5), Some(5))),
+            Interval::uncertain()
+        );
+        let five = interval(Some(5), Some(5));
+        assert!(five.equal(&five).is_certainly_true());
+    }
+
+    #[test]
+    fn fraction_in() {
+        let range = interval(Some(0), Some(99));
+        assert_eq!(range.fraction_in(&interval(Some(50), Some(99))), Some(0.5));
+        assert_eq!(range.fraction_in(&interval(Some(50), None)), None);
+    }
+}
@@ -21,9 +21,11 @@ pub mod conditional_expressions;
 #[cfg(feature = "crypto_expressions")]
 pub mod crypto_expressions;
 pub mod datetime_expressions;
+pub mod equivalence;
 pub mod execution_props;
 pub mod expressions;
 pub mod functions;
+pub mod intervals;
 pub mod math_expressions;
 mod physical_expr;
 pub mod planner;
@@ -34,6 +36,7 @@ mod scalar_function;
 mod sort_expr;
 pub mod string_expressions;
 pub mod struct_expressions;
+pub mod time_zone;
 pub mod type_coercion;
 pub mod udf;
 #[cfg(feature = "unicode_expressions")]
@@ -41,11 +44,15 @@ pub mod unicode_expressions;
 pub mod utils;
 pub mod var_provider;
 pub mod window;
+pub mod zorder_expressions;
 
 // reexport this to maintain compatibility with anything that used from_slice previously
 pub use aggregate::AggregateExpr;
 pub use datafusion_common::from_slice;
-pub use physical_expr::{ExprBoundaries, PhysicalExpr, PhysicalExprStats};
+pub use equivalence::{EquivalenceProperties, EquivalentClass};
+pub use physical_expr::{
+    down_cast_any_ref, ExprBoundaries, PhysicalExpr, PhysicalExprStats,
+};
 pub use planner::create_physical_expr;
 pub use scalar_function::ScalarFunctionExpr;
 pub use sort_expr::PhysicalSortExpr;
@@ -23,10 +23,11 @@ use arrow::datatypes::DataType;
 use datafusion_common::ScalarValue;
 use datafusion_common::{DataFusionError, Result};
 use datafusion_expr::ColumnarValue;
+use rand::rngs::StdRng;
 use rand::{thread_rng, Rng};
 use std::any::type_name;
 use std::iter;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 macro_rules! downcast_compute_op {
     ($ARRAY:expr, $NAME:expr, $FUNC:ident, $TYPE:ident) => {{
@@ -133,20 +134,45 @@ math_unary_function!("ln", ln);
 math_unary_function!("log2", log2);
 math_unary_function!("log10", log10);
 
-/// random SQL function
-pub fn random(args: &[ColumnarValue]) -> Result<ColumnarValue> {
-    let len: usize = match &args[0] {
-        ColumnarValue::Array(array) => array.len(),
-        _ => {
-            return Err(DataFusionError::Internal(
-                "Expect random function to take no param".to_string(),
-            ))
-        }
-    };
-    let mut rng = thread_rng();
+/// Returns the number of rows of the argument of `random`
+fn random_len(args: &[ColumnarValue]) -> Result<usize> {
+    match &args[0] {
+        ColumnarValue::Array(array) => Ok(array.len()),
+        _ => Err(DataFusionError::Internal(
+            "Expect random function to take no param".to_string(),
+        )),
+    }
+}
+
+/// Returns an array of `len` random values in `[0, 1)` drawn from `rng`
+fn random_array(rng: &mut impl Rng, len: usize) -> ColumnarValue {
     let values = iter::repeat_with(|| rng.gen_range(0.0..1.0)).take(len);
     let array = Float64Array::from_iter_values(values);
-    Ok(ColumnarValue::Array(Arc::new(array)))
+    ColumnarValue::Array(Arc::new(array))
+}
+
+/// random SQL function
+pub fn random(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let len = random_len(args)?;
+    Ok(random_array(&mut thread_rng(), len))
+}
+
+/// random SQL function whose values are drawn from `rng`, the seeded
+/// generator of a query, so that the values of the query are the same each
+/// time it runs, as long as its batches are evaluated in the same order.
+///
+/// All the partitions of the query draw from the same generator, so the
+/// values are only reproducible when the plan runs in a single partition,
+/// e.g. with `target_partitions` set to 1 and a single file per table: the
+/// partitions of a parallel plan take their values in the order in which
+/// they are scheduled
+pub fn make_seeded_random(
+    rng: Arc<Mutex<StdRng>>,
+) -> impl Fn(&[ColumnarValue]) -> Result<ColumnarValue> {
+    move |args| {
+        let len = random_len(args)?;
+        Ok(random_array(&mut *rng.lock().unwrap(), len))
+    }
 }
 
 pub fn power(args: &[ArrayRef]) -> Result<ArrayRef> {
@@ -207,6 +233,7 @@ pub fn atan2(args: &[ArrayRef]) -> Result<ArrayRef> {
 mod tests {
 
     use super::*;
+    use crate::execution_props::ExecutionProps;
     use arrow::array::{Array, Float64Array, NullArray};
 
     #[test]
@@ -219,6 +246,31 @@ mod tests {
         assert!(0.0 <= floats.value(0) && floats.value(0) < 1.0);
     }
 
+    #[test]
+    fn test_seeded_random_expression() {
+        let args = vec![ColumnarValue::Array(Arc::new(NullArray::new(3)))];
+        let values = |random: &dyn Fn(&[ColumnarValue]) -> Result<ColumnarValue>| {
+            let array = random(&args).expect("fail").into_array(3);
+            let floats = array.as_any().downcast_ref::<Float64Array>().expect("fail");
+            floats.values().to_vec()
+        };
+
+        let seeded = |seed| {
+            let mut props = ExecutionProps::new();
+            props.set_random_seed(Some(seed));
+            make_seeded_random(props.random_generator.unwrap())
+        };
+
+        // the same seed draws the same values, and the next batches draw
+        // other values
+        let random = seeded(42);
+        let first = values(&random);
+        assert_eq!(first, values(&seeded(42)));
+        assert_ne!(first, values(&random));
+        assert_ne!(first, values(&seeded(43)));
+        assert!(first.iter().all(|v| (0.0..1.0).contains(v)));
+    }
+
     #[test]
     fn test_atan2_f64() {
         let args: Vec<ArrayRef> = vec![
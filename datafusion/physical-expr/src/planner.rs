@@ -20,6 +20,7 @@ use crate::{
     execution_props::ExecutionProps,
     expressions::{
         self, binary, Column, DateTimeIntervalExpr, GetIndexedFieldExpr, Literal,
+        ScalarSubqueryExpr,
     },
     functions, udf,
     var_provider::VarType,
@@ -278,10 +279,11 @@ pub fn create_physical_expr(
                 };
             Ok(expressions::case(expr, when_then_expr, else_expr)?)
         }
-        Expr::Cast(Cast { expr, data_type }) => expressions::cast(
+        Expr::Cast(Cast { expr, data_type }) => expressions::cast_in_time_zone(
             create_physical_expr(expr, input_dfschema, input_schema, execution_props)?,
             input_schema,
             data_type.clone(),
+            execution_props.time_zone,
         ),
         Expr::TryCast { expr, data_type } => expressions::try_cast(
             create_physical_expr(expr, input_dfschema, input_schema, execution_props)?,
@@ -414,6 +416,24 @@ pub fn create_physical_expr(
                 expressions::in_list(value_expr, list_exprs, negated, input_schema)
             }
         },
+        Expr::ScalarSubquery(subquery) => {
+            match execution_props.scalar_subquery_value(&subquery.subquery) {
+                Some(value) => {
+                    let data_type =
+                        subquery.subquery.schema().field(0).data_type().clone();
+                    Ok(Arc::new(ScalarSubqueryExpr::new(data_type, value)))
+                }
+                None => Err(DataFusionError::NotImplemented(
+                    "Physical plan only supports uncorrelated scalar subqueries \
+                     in projections, filters, aggregates, sorts, windows and join filters"
+                        .to_string(),
+                )),
+            }
+        }
+        Expr::Placeholder { id, .. } => Err(DataFusionError::Plan(format!(
+            "No value found for placeholder ${}, bind it with with_param_values",
+            id
+        ))),
         other => Err(DataFusionError::NotImplemented(format!(
             "Physical plan does not support logical expression {:?}",
             other
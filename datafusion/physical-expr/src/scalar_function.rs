@@ -39,6 +39,7 @@ use datafusion_expr::BuiltinScalarFunction;
 use datafusion_expr::ColumnarValue;
 pub use datafusion_expr::NullColumnarValue;
 use datafusion_expr::ScalarFunctionImplementation;
+use datafusion_expr::Volatility;
 use std::any::Any;
 use std::fmt::Debug;
 use std::fmt::{self, Formatter};
@@ -50,6 +51,7 @@ pub struct ScalarFunctionExpr {
     name: String,
     args: Vec<Arc<dyn PhysicalExpr>>,
     return_type: DataType,
+    volatility: Volatility,
 }
 
 impl Debug for ScalarFunctionExpr {
@@ -59,12 +61,14 @@ impl Debug for ScalarFunctionExpr {
             .field("name", &self.name)
             .field("args", &self.args)
             .field("return_type", &self.return_type)
+            .field("volatility", &self.volatility)
             .finish()
     }
 }
 
 impl ScalarFunctionExpr {
-    /// Create a new Scalar function
+    /// Create a new Scalar function, which is assumed to be
+    /// [`Volatility::Volatile`] unless set by [`Self::with_volatility`]
     pub fn new(
         name: &str,
         fun: ScalarFunctionImplementation,
@@ -76,9 +80,16 @@ impl ScalarFunctionExpr {
             name: name.to_owned(),
             args,
             return_type: return_type.clone(),
+            volatility: Volatility::Volatile,
         }
     }
 
+    /// Set the volatility of the function
+    pub fn with_volatility(mut self, volatility: Volatility) -> Self {
+        self.volatility = volatility;
+        self
+    }
+
     /// Get the scalar function implementation
     pub fn fun(&self) -> &ScalarFunctionImplementation {
         &self.fun
@@ -98,6 +109,11 @@ impl ScalarFunctionExpr {
     pub fn return_type(&self) -> &DataType {
         &self.return_type
     }
+
+    /// The volatility of the function
+    pub fn volatility(&self) -> Volatility {
+        self.volatility
+    }
 }
 
 impl fmt::Display for ScalarFunctionExpr {
@@ -156,12 +172,15 @@ impl PhysicalExpr for ScalarFunctionExpr {
         self: Arc<Self>,
         children: Vec<Arc<dyn PhysicalExpr>>,
     ) -> Result<Arc<dyn PhysicalExpr>> {
-        Ok(Arc::new(ScalarFunctionExpr::new(
-            &self.name,
-            self.fun.clone(),
-            children,
-            self.return_type(),
-        )))
+        Ok(Arc::new(
+            ScalarFunctionExpr::new(
+                &self.name,
+                self.fun.clone(),
+                children,
+                self.return_type(),
+            )
+            .with_volatility(self.volatility),
+        ))
     }
 }
 
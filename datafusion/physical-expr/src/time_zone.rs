@@ -0,0 +1,191 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The time zone of a session, in which the date and time functions read the
+//! local dates and times of instants
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use datafusion_common::{DataFusionError, Result};
+
+/// The time zone of a session, set with `SET TIME ZONE`: a name of the IANA
+/// time zone database, such as `Asia/Tokyo`, or a fixed offset from UTC,
+/// such as `+09:00`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionTimeZone {
+    /// A time zone of the IANA database, whose offset changes with daylight
+    /// saving time
+    Named(Tz),
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl Default for SessionTimeZone {
+    fn default() -> Self {
+        Self::Named(Tz::UTC)
+    }
+}
+
+impl FromStr for SessionTimeZone {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.eq_ignore_ascii_case("UTC") {
+            return Ok(Self::default());
+        }
+        if let Some(offset) = parse_offset(s) {
+            return Ok(Self::Fixed(offset));
+        }
+        s.parse::<Tz>().map(Self::Named).map_err(|_| {
+            DataFusionError::Plan(format!(
+                "Invalid time zone '{}', expected a name such as 'Asia/Tokyo' \
+                 or an offset such as '+09:00'",
+                s
+            ))
+        })
+    }
+}
+
+impl fmt::Display for SessionTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Parses an offset from UTC such as `+09`, `+0930` or `-05:00`
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let s = &s[1..];
+    let (hours, minutes) = match s.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if s.len() == 4 => s.split_at(2),
+        None => (s, "0"),
+    };
+    let is_number =
+        |s: &str| (1..=2).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(hours) || !is_number(minutes) {
+        return None;
+    }
+    let (hours, minutes) = (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+impl SessionTimeZone {
+    /// The offset from UTC of the time zone at the UTC date and time `utc`
+    pub fn offset_at(&self, utc: &NaiveDateTime) -> FixedOffset {
+        match self {
+            Self::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+            Self::Fixed(offset) => *offset,
+        }
+    }
+
+    /// Converts the UTC date and time `utc` into the local date and time of
+    /// the time zone
+    pub fn to_local(&self, utc: &NaiveDateTime) -> NaiveDateTime {
+        *utc + self.offset_at(utc)
+    }
+
+    /// Converts the local date and time `local` of the time zone into the
+    /// UTC date and time
+    ///
+    /// Local times repeated when the clocks go back are the earliest of
+    /// their instants. Local times skipped when the clocks go forward do not
+    /// exist, and are an error.
+    pub fn to_utc(&self, local: &NaiveDateTime) -> Result<NaiveDateTime> {
+        let offset = match self {
+            Self::Named(tz) => match tz.offset_from_local_datetime(local) {
+                LocalResult::Single(offset) | LocalResult::Ambiguous(offset, _) => {
+                    offset.fix()
+                }
+                LocalResult::None => {
+                    return Err(DataFusionError::Execution(format!(
+                        "Local time {} does not exist in time zone {}",
+                        local, self
+                    )))
+                }
+            },
+            Self::Fixed(offset) => *offset,
+        };
+        Ok(*local - offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn parse_time_zones() -> Result<()> {
+        assert_eq!(
+            "utc".parse::<SessionTimeZone>()?,
+            SessionTimeZone::default()
+        );
+        assert_eq!(
+            "Asia/Tokyo".parse::<SessionTimeZone>()?,
+            SessionTimeZone::Named(Tz::Asia__Tokyo)
+        );
+        for (offset, seconds) in [("+09:00", 32400), ("-0530", -19800), ("+9", 32400)] {
+            assert_eq!(
+                offset.parse::<SessionTimeZone>()?,
+                SessionTimeZone::Fixed(FixedOffset::east(seconds))
+            );
+        }
+        for invalid in ["Mars/Olympus", "+09:60", "+", "09:00"] {
+            assert!(invalid.parse::<SessionTimeZone>().is_err(), "{}", invalid);
+        }
+        assert_eq!(
+            "America/New_York".parse::<SessionTimeZone>()?.to_string(),
+            "America/New_York"
+        );
+        assert_eq!("-0530".parse::<SessionTimeZone>()?.to_string(), "-05:30");
+        Ok(())
+    }
+
+    #[test]
+    fn convert_daylight_saving_times() -> Result<()> {
+        let tz = "America/New_York".parse::<SessionTimeZone>()?;
+        let date = |d, h, m| NaiveDate::from_ymd(2022, 11, d).and_hms(h, m, 0);
+
+        // EDT, UTC-4
+        assert_eq!(tz.to_local(&date(1, 12, 0)), date(1, 8, 0));
+        // EST, UTC-5
+        assert_eq!(tz.to_local(&date(7, 12, 0)), date(7, 7, 0));
+        // 01:30 happens twice on November 6, the first time in EDT
+        assert_eq!(tz.to_utc(&date(6, 1, 30))?, date(6, 5, 30));
+
+        let date = |h, m| NaiveDate::from_ymd(2022, 3, 13).and_hms(h, m, 0);
+        // 02:30 never happens on March 13
+        let err = tz.to_utc(&date(2, 30)).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+        assert_eq!(tz.to_utc(&date(3, 30))?, date(7, 30));
+        Ok(())
+    }
+}
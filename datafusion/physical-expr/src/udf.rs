@@ -34,10 +34,13 @@ pub fn create_physical_expr(
         .map(|e| e.data_type(input_schema))
         .collect::<Result<Vec<_>>>()?;
 
-    Ok(Arc::new(ScalarFunctionExpr::new(
-        &fun.name,
-        fun.fun.clone(),
-        input_phy_exprs.to_vec(),
-        (fun.return_type)(&input_exprs_types)?.as_ref(),
-    )))
+    Ok(Arc::new(
+        ScalarFunctionExpr::new(
+            &fun.name,
+            fun.fun.clone(),
+            input_phy_exprs.to_vec(),
+            (fun.return_type)(&input_exprs_types)?.as_ref(),
+        )
+        .with_volatility(fun.signature.volatility),
+    ))
 }
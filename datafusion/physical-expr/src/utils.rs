@@ -15,8 +15,17 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::equivalence::{EquivalenceProperties, EquivalentClass};
+use crate::expressions::{
+    BinaryExpr, CastExpr, Column, DateTimeIntervalExpr, Literal, TryCastExpr,
+};
+use crate::rewrite::TreeNodeRewritable;
 use crate::PhysicalExpr;
 use crate::PhysicalSortExpr;
+use crate::ScalarFunctionExpr;
+use arrow::datatypes::{DataType, Schema, TimeUnit};
+use datafusion_common::ScalarValue;
+use datafusion_expr::{BuiltinScalarFunction, Operator};
 
 use std::sync::Arc;
 
@@ -65,13 +74,272 @@ pub fn sort_expr_list_eq_strict_order(
     list1.len() == list2.len() && list1.iter().zip(list2.iter()).all(|(e1, e2)| e1.eq(e2))
 }
 
+/// Replace the columns of `expr` by the head of their equivalent class, so
+/// that equivalent expressions compare equal after normalization
+pub fn normalize_expr_with_equivalence_properties(
+    expr: Arc<dyn PhysicalExpr>,
+    eq_properties: &[EquivalentClass],
+) -> Arc<dyn PhysicalExpr> {
+    expr.clone()
+        .transform(&|expr| {
+            let column = expr.as_any().downcast_ref::<Column>()?;
+            eq_properties
+                .iter()
+                .find(|class| class.contains(column))
+                .map(|class| Arc::new(class.head().clone()) as Arc<dyn PhysicalExpr>)
+        })
+        .unwrap_or(expr)
+}
+
+/// Normalize the expression of a sort expression, see
+/// [`normalize_expr_with_equivalence_properties`]
+pub fn normalize_sort_expr_with_equivalence_properties(
+    sort_expr: PhysicalSortExpr,
+    eq_properties: &[EquivalentClass],
+) -> PhysicalSortExpr {
+    PhysicalSortExpr {
+        expr: normalize_expr_with_equivalence_properties(sort_expr.expr, eq_properties),
+        options: sort_expr.options,
+    }
+}
+
+/// Returns true if the `provided` ordering sorts the rows by the `required`
+/// ordering, once the columns of both are normalized with `eq_properties`.
+///
+/// That is the case if `required` is a prefix of `provided`, where each
+/// required expression may also be an increasing function of the provided
+/// expression, such as `date_trunc('day', ts)` of `ts`. A function that maps
+/// several values to the same one, like `date_trunc`, can only be the last
+/// required expression.
+pub fn ordering_satisfy(
+    provided: Option<&[PhysicalSortExpr]>,
+    required: Option<&[PhysicalSortExpr]>,
+    eq_properties: &EquivalenceProperties,
+) -> bool {
+    match (provided, required) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(provided), Some(required)) => {
+            if required.len() > provided.len() {
+                return false;
+            }
+            for (i, (req, prov)) in required.iter().zip(provided.iter()).enumerate() {
+                if req.options != prov.options {
+                    return false;
+                }
+                match ordered_by(&req.expr, &prov.expr, eq_properties) {
+                    Some(true) => {}
+                    Some(false) => return i + 1 == required.len(),
+                    None => return false,
+                }
+            }
+            true
+        }
+    }
+}
+
+/// Returns whether `expr` increases with `sort_expr`, comparing columns with
+/// `eq_properties`: `Some(true)` if it is strictly increasing, as `sort_expr`
+/// itself or `sort_expr + 1`, `Some(false)` if it maps several values to the
+/// same one, as `CAST(sort_expr AS DATE)`, and `None` otherwise
+pub fn ordered_by(
+    expr: &Arc<dyn PhysicalExpr>,
+    sort_expr: &Arc<dyn PhysicalExpr>,
+    eq_properties: &EquivalenceProperties,
+) -> Option<bool> {
+    let normalized =
+        normalize_expr_with_equivalence_properties(expr.clone(), eq_properties.classes());
+    let normalized_sort_expr = normalize_expr_with_equivalence_properties(
+        sort_expr.clone(),
+        eq_properties.classes(),
+    );
+    if normalized.eq(&normalized_sort_expr) {
+        return Some(true);
+    }
+    let (input, strict) = monotonic_input(expr, &eq_properties.schema())?;
+    let input_strict = ordered_by(&input, sort_expr, eq_properties)?;
+    Some(strict && input_strict)
+}
+
+/// Returns the input that `expr` is an increasing function of, and whether it
+/// is strictly increasing, for
+///
+/// * the addition of a constant, or the subtraction of a constant from it, as
+///   `a + 1` or `ts - INTERVAL '1' DAY`, when it can not overflow. Integer
+///   arithmetic wraps around on overflow, so it is only increasing when the
+///   range of the input, such as the one of a narrower integer cast to the
+///   type of the result, leaves room for the constant. Adding a finite
+///   constant to a float is not strictly increasing, as the result is rounded,
+///   and adding intervals of months is not either, as months have different
+///   lengths. The interval arithmetic of dates and timestamps fails on
+///   overflow.
+/// * the cast of a timestamp or date to a coarser unit, or to a finer one
+///   when it can not overflow, as `CAST(ts AS DATE)`, or the cast of a number
+///   to a wider type
+/// * `date_trunc` with a constant granularity
+fn monotonic_input(
+    expr: &Arc<dyn PhysicalExpr>,
+    schema: &Schema,
+) -> Option<(Arc<dyn PhysicalExpr>, bool)> {
+    let any = expr.as_any();
+    if let Some(binary) = any.downcast_ref::<BinaryExpr>() {
+        let left_literal = binary.left().as_any().downcast_ref::<Literal>();
+        let right_literal = binary.right().as_any().downcast_ref::<Literal>();
+        let (input, constant, negate) = match (binary.op(), left_literal, right_literal) {
+            (Operator::Plus, None, Some(constant)) => (binary.left(), constant, false),
+            (Operator::Plus, Some(constant), None) => (binary.right(), constant, false),
+            (Operator::Minus, None, Some(constant)) => (binary.left(), constant, true),
+            _ => return None,
+        };
+        let strict = monotonic_addition(
+            input,
+            constant.value(),
+            negate,
+            &binary.data_type(schema).ok()?,
+            schema,
+        )?;
+        return Some((input.clone(), strict));
+    }
+    if let Some(datetime) = any.downcast_ref::<DateTimeIntervalExpr>() {
+        let interval = datetime.rhs().as_any().downcast_ref::<Literal>()?;
+        let strict = matches!(
+            (datetime.lhs().data_type(schema).ok()?, interval.value()),
+            (DataType::Timestamp(_, _), ScalarValue::IntervalDayTime(_))
+        );
+        return Some((datetime.lhs().clone(), strict));
+    }
+    if let Some(cast) = any.downcast_ref::<CastExpr>() {
+        let strict =
+            monotonic_cast(&cast.expr().data_type(schema).ok()?, cast.cast_type())?;
+        return Some((cast.expr().clone(), strict));
+    }
+    if let Some(cast) = any.downcast_ref::<TryCastExpr>() {
+        let strict =
+            monotonic_cast(&cast.expr().data_type(schema).ok()?, cast.cast_type())?;
+        return Some((cast.expr().clone(), strict));
+    }
+    if let Some(function) = any.downcast_ref::<ScalarFunctionExpr>() {
+        let args = function.args();
+        if matches!(
+            function.name().parse::<BuiltinScalarFunction>(),
+            Ok(BuiltinScalarFunction::DateTrunc)
+        ) && args.len() == 2
+            && args[0].as_any().is::<Literal>()
+        {
+            return Some((args[1].clone(), false));
+        }
+    }
+    None
+}
+
+/// Returns whether adding `constant`, or subtracting it if `negate` is set,
+/// to `input` to get a value of type `result_type` is strictly increasing, or
+/// `None` if it may not preserve the order of the values
+fn monotonic_addition(
+    input: &Arc<dyn PhysicalExpr>,
+    constant: &ScalarValue,
+    negate: bool,
+    result_type: &DataType,
+    schema: &Schema,
+) -> Option<bool> {
+    match constant {
+        ScalarValue::Float32(Some(v)) if v.is_finite() => return Some(false),
+        ScalarValue::Float64(Some(v)) if v.is_finite() => return Some(false),
+        _ => {}
+    }
+    let constant = match constant {
+        ScalarValue::Int8(Some(v)) => *v as i128,
+        ScalarValue::Int16(Some(v)) => *v as i128,
+        ScalarValue::Int32(Some(v)) => *v as i128,
+        ScalarValue::Int64(Some(v)) => *v as i128,
+        ScalarValue::UInt8(Some(v)) => *v as i128,
+        ScalarValue::UInt16(Some(v)) => *v as i128,
+        ScalarValue::UInt32(Some(v)) => *v as i128,
+        ScalarValue::UInt64(Some(v)) => *v as i128,
+        _ => return None,
+    };
+    let constant = if negate { -constant } else { constant };
+
+    // the range of the input is the one of its type, unless it is a cast
+    // from a narrower integer type
+    let input_type = match input.as_any().downcast_ref::<CastExpr>() {
+        Some(cast) => cast.expr().data_type(schema).ok()?,
+        None => input.data_type(schema).ok()?,
+    };
+    let (input_min, input_max) = integer_range(&input_type)?;
+    let (min, max) = integer_range(result_type)?;
+    (input_min + constant >= min && input_max + constant <= max).then_some(true)
+}
+
+/// Returns the smallest and the largest values of an integer type
+fn integer_range(data_type: &DataType) -> Option<(i128, i128)> {
+    use DataType::*;
+    Some(match data_type {
+        Int8 => (i8::MIN as i128, i8::MAX as i128),
+        Int16 => (i16::MIN as i128, i16::MAX as i128),
+        Int32 => (i32::MIN as i128, i32::MAX as i128),
+        Int64 => (i64::MIN as i128, i64::MAX as i128),
+        UInt8 => (0, u8::MAX as i128),
+        UInt16 => (0, u16::MAX as i128),
+        UInt32 => (0, u32::MAX as i128),
+        UInt64 => (0, u64::MAX as i128),
+        _ => return None,
+    })
+}
+
+/// Returns whether a cast from `from` to `to` is strictly increasing, or
+/// `None` if it does not preserve the order of the values
+///
+/// The casts of timestamps and dates to finer units multiply their values,
+/// which wraps around on overflow: they are only taken into account when all
+/// the values of `from` can be represented in `to`.
+fn monotonic_cast(from: &DataType, to: &DataType) -> Option<bool> {
+    use DataType::*;
+    /// The number of units of a day of a date or timestamp type
+    fn units_per_day(data_type: &DataType) -> Option<i128> {
+        Some(match data_type {
+            Date32 => 1,
+            Date64 | Timestamp(TimeUnit::Millisecond, _) => 86_400_000,
+            Timestamp(TimeUnit::Second, _) => 86_400,
+            Timestamp(TimeUnit::Microsecond, _) => 86_400_000_000,
+            Timestamp(TimeUnit::Nanosecond, _) => 86_400_000_000_000,
+            _ => return None,
+        })
+    }
+    match (from, to) {
+        (Date32 | Date64 | Timestamp(_, _), Date32 | Date64 | Timestamp(_, _)) => {
+            let from_units = units_per_day(from)?;
+            let to_units = units_per_day(to)?;
+            if to_units < from_units || matches!((from, to), (Timestamp(_, _), Date64)) {
+                return Some(false);
+            }
+            let max = match from {
+                Date32 => i32::MAX as i128,
+                _ => i64::MAX as i128,
+            };
+            (max * (to_units / from_units) <= i64::MAX as i128).then_some(true)
+        }
+        (Int8, Int16 | Int32 | Int64)
+        | (Int16, Int32 | Int64)
+        | (Int32, Int64)
+        | (UInt8, UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64)
+        | (UInt16, UInt32 | UInt64 | Int32 | Int64)
+        | (UInt32, UInt64 | Int64)
+        | (Float32, Float64) => Some(true),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::expressions::Column;
+    use crate::execution_props::ExecutionProps;
+    use crate::expressions::{binary, cast, col, lit, Column};
+    use crate::functions::create_physical_expr;
     use crate::PhysicalSortExpr;
     use arrow::compute::SortOptions;
+    use arrow::datatypes::Field;
     use datafusion_common::Result;
 
     use std::sync::Arc;
@@ -202,4 +470,173 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn ordering_satisfy_with_equivalence() -> Result<()> {
+        let sort_expr = |name: &str, index: usize| PhysicalSortExpr {
+            expr: Arc::new(Column::new(name, index)),
+            options: SortOptions::default(),
+        };
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+            Field::new("c", DataType::Int64, true),
+        ]));
+        let provided = vec![sort_expr("a", 0), sort_expr("c", 2)];
+        let mut eq_properties = EquivalenceProperties::new(schema.clone());
+        eq_properties.add_equal_conditions((&Column::new("a", 0), &Column::new("b", 1)));
+        let empty_eq_properties = EquivalenceProperties::new(schema);
+
+        let required = vec![sort_expr("b", 1)];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &empty_eq_properties
+        ));
+
+        let required = vec![sort_expr("b", 1), sort_expr("c", 2), sort_expr("a", 0)];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+        assert!(ordering_satisfy(Some(&provided), None, &eq_properties));
+        assert!(!ordering_satisfy(None, Some(&provided), &eq_properties));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ordering_satisfy_with_monotonic_functions() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ts", DataType::Timestamp(TimeUnit::Nanosecond, None), true),
+            Field::new("c", DataType::Int64, true),
+            Field::new("i", DataType::Int32, true),
+            Field::new("f", DataType::Float64, true),
+            Field::new("ts_secs", DataType::Timestamp(TimeUnit::Second, None), true),
+        ]));
+        let eq_properties = EquivalenceProperties::new(schema.clone());
+        let sort_expr = |expr: Arc<dyn PhysicalExpr>| PhysicalSortExpr {
+            expr,
+            options: SortOptions::default(),
+        };
+        let ts = col("ts", &schema)?;
+        let c = col("c", &schema)?;
+        let f = col("f", &schema)?;
+        let provided = vec![sort_expr(ts.clone()), sort_expr(c.clone())];
+
+        // ts + INTERVAL '1' DAY is strictly increasing in ts
+        let ts_plus_day: Arc<dyn PhysicalExpr> = Arc::new(DateTimeIntervalExpr::try_new(
+            ts.clone(),
+            Operator::Plus,
+            lit(ScalarValue::IntervalDayTime(Some(1 << 32))),
+            &schema,
+        )?);
+        let required = vec![sort_expr(ts_plus_day), sort_expr(c.clone())];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        // CAST(ts AS DATE) maps several timestamps to the same date
+        let ts_date = cast(ts.clone(), &schema, DataType::Date32)?;
+        let required = vec![sort_expr(ts_date.clone())];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+        let required = vec![sort_expr(ts_date), sort_expr(c.clone())];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        let ts_day = create_physical_expr(
+            &BuiltinScalarFunction::DateTrunc,
+            &[lit(ScalarValue::Utf8(Some("day".to_string()))), ts.clone()],
+            &schema,
+            &ExecutionProps::new(),
+        )?;
+        let required = vec![sort_expr(ts_day)];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        // 1 - c decreases with c
+        let one_minus_c = binary(lit(1i64), Operator::Minus, c.clone(), &schema)?;
+        let provided = vec![sort_expr(c.clone())];
+        let required = vec![sort_expr(one_minus_c)];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+        // c + 1 wraps around for the largest value of c
+        let c_plus_one = binary(c, Operator::Plus, lit(1i64), &schema)?;
+        let required = vec![sort_expr(c_plus_one)];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        // CAST(i AS BIGINT) + 1 can not overflow
+        let i = col("i", &schema)?;
+        let i_plus_one = binary(
+            cast(i.clone(), &schema, DataType::Int64)?,
+            Operator::Plus,
+            lit(1i64),
+            &schema,
+        )?;
+        let provided = vec![sort_expr(i), sort_expr(f.clone())];
+        let required = vec![sort_expr(i_plus_one), sort_expr(f.clone())];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        // f + 0.5 is rounded, so it may be equal for different values of f
+        let f_plus_half = binary(f.clone(), Operator::Plus, lit(0.5f64), &schema)?;
+        let provided = vec![sort_expr(f), sort_expr(ts.clone())];
+        let required = vec![sort_expr(f_plus_half.clone())];
+        assert!(ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+        let required = vec![sort_expr(f_plus_half), sort_expr(ts.clone())];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        // casting a timestamp in seconds to nanoseconds may overflow
+        let ts_secs = col("ts_secs", &schema)?;
+        let ts_nanos = cast(
+            ts_secs.clone(),
+            &schema,
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        )?;
+        let provided = vec![sort_expr(ts_secs)];
+        let required = vec![sort_expr(ts_nanos)];
+        assert!(!ordering_satisfy(
+            Some(&provided),
+            Some(&required),
+            &eq_properties
+        ));
+
+        Ok(())
+    }
 }
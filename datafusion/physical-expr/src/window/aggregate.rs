@@ -62,6 +62,16 @@ impl AggregateWindowExpr {
         }
     }
 
+    /// The aggregate function evaluated over the window
+    pub fn get_aggregate_expr(&self) -> &Arc<dyn AggregateExpr> {
+        &self.aggregate
+    }
+
+    /// The window frame, if any
+    pub fn get_window_frame(&self) -> &Option<Arc<WindowFrame>> {
+        &self.window_frame
+    }
+
     /// create a new accumulator based on the underlying aggregation function
     fn create_accumulator(&self) -> Result<AggregateWindowAccumulator> {
         let accumulator = self.aggregate.create_accumulator()?;
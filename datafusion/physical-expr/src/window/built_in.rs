@@ -49,6 +49,11 @@ impl BuiltInWindowExpr {
             order_by: order_by.to_vec(),
         }
     }
+
+    /// The built-in window function evaluated by this expression
+    pub fn get_built_in_func_expr(&self) -> &Arc<dyn BuiltInWindowFunctionExpr> {
+        &self.expr
+    }
 }
 
 impl WindowExpr for BuiltInWindowExpr {
@@ -21,8 +21,8 @@
 use crate::window::partition_evaluator::PartitionEvaluator;
 use crate::window::BuiltInWindowFunctionExpr;
 use crate::PhysicalExpr;
-use arrow::array::ArrayRef;
-use arrow::compute::cast;
+use arrow::array::{ArrayRef, UInt32Array};
+use arrow::compute::{cast, concat, take};
 use arrow::datatypes::{DataType, Field};
 use arrow::record_batch::RecordBatch;
 use datafusion_common::ScalarValue;
@@ -40,6 +40,41 @@ pub struct WindowShift {
     shift_offset: i64,
     expr: Arc<dyn PhysicalExpr>,
     default_value: Option<ScalarValue>,
+    /// non constant default, evaluated against the current row
+    default_expr: Option<Arc<dyn PhysicalExpr>>,
+    ignore_nulls: bool,
+}
+
+impl WindowShift {
+    /// Use `expr`, evaluated against the current row, as the default value for
+    /// rows whose shifted position falls outside of the partition. Takes
+    /// precedence over any constant default value.
+    pub fn with_default_expr(mut self, expr: Arc<dyn PhysicalExpr>) -> Self {
+        self.default_expr = Some(expr);
+        self
+    }
+
+    /// Skip null input values when counting the offset (`IGNORE NULLS`)
+    pub fn with_ignore_nulls(mut self, ignore_nulls: bool) -> Self {
+        self.ignore_nulls = ignore_nulls;
+        self
+    }
+
+    /// The offset of the row to take the value from, positive for `lag`
+    /// and negative for `lead`
+    pub fn shift_offset(&self) -> i64 {
+        self.shift_offset
+    }
+
+    /// The constant default value
+    pub fn default_value(&self) -> Option<&ScalarValue> {
+        self.default_value.as_ref()
+    }
+
+    /// Whether null input values are skipped when counting the offset
+    pub fn ignore_nulls(&self) -> bool {
+        self.ignore_nulls
+    }
 }
 
 /// lead() window function
@@ -56,6 +91,8 @@ pub fn lead(
         shift_offset: shift_offset.map(|v| v.neg()).unwrap_or(-1),
         expr,
         default_value,
+        default_expr: None,
+        ignore_nulls: false,
     }
 }
 
@@ -73,6 +110,8 @@ pub fn lag(
         shift_offset: shift_offset.unwrap_or(1),
         expr,
         default_value,
+        default_expr: None,
+        ignore_nulls: false,
     }
 }
 
@@ -88,7 +127,9 @@ impl BuiltInWindowFunctionExpr for WindowShift {
     }
 
     fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
-        vec![self.expr.clone()]
+        let mut expressions = vec![self.expr.clone()];
+        expressions.extend(self.default_expr.clone());
+        expressions
     }
 
     fn name(&self) -> &str {
@@ -99,24 +140,32 @@ impl BuiltInWindowFunctionExpr for WindowShift {
         &self,
         batch: &RecordBatch,
     ) -> Result<Box<dyn PartitionEvaluator>> {
-        let values = self
+        let mut values = self
             .expressions()
             .iter()
             .map(|e| e.evaluate(batch))
             .map(|r| r.map(|v| v.into_array(batch.num_rows())))
             .collect::<Result<Vec<_>>>()?;
+        if let [value, default] = values.as_mut_slice() {
+            if default.data_type() != value.data_type() {
+                *default = cast(default, value.data_type())?;
+            }
+        }
         Ok(Box::new(WindowShiftEvaluator {
             shift_offset: self.shift_offset,
             values,
             default_value: self.default_value.clone(),
+            ignore_nulls: self.ignore_nulls,
         }))
     }
 }
 
 pub(crate) struct WindowShiftEvaluator {
     shift_offset: i64,
+    /// the shifted values, followed by the per row default values if any
     values: Vec<ArrayRef>,
     default_value: Option<ScalarValue>,
+    ignore_nulls: bool,
 }
 
 fn create_empty_array(
@@ -142,8 +191,6 @@ fn shift_with_default_value(
     offset: i64,
     value: &Option<ScalarValue>,
 ) -> Result<ArrayRef> {
-    use arrow::compute::concat;
-
     let value_len = array.len() as i64;
     if offset == 0 {
         Ok(arrow::array::make_array(array.data_ref().clone()))
@@ -168,11 +215,72 @@ fn shift_with_default_value(
     }
 }
 
+/// For every row of `array` find the index of the row `offset` rows away, where a
+/// positive offset looks backwards (lag) and a negative one forwards (lead).
+/// Returns `None` for rows whose target falls outside of the array. If
+/// `ignore_nulls` is set, null values are not counted when moving.
+fn shift_indices(
+    array: &ArrayRef,
+    offset: i64,
+    ignore_nulls: bool,
+) -> Vec<Option<usize>> {
+    let len = array.len();
+    if !ignore_nulls || offset == 0 {
+        return (0..len)
+            .map(|i| {
+                (i as i64)
+                    .checked_sub(offset)
+                    .filter(|j| (0..len as i64).contains(j))
+                    .map(|j| j as usize)
+            })
+            .collect();
+    }
+
+    let valid = (0..len).filter(|i| array.is_valid(*i)).collect::<Vec<_>>();
+    (0..len)
+        .map(|i| {
+            if offset > 0 {
+                // number of non null values strictly before row `i`
+                let before = valid.partition_point(|j| *j < i);
+                before
+                    .checked_sub(offset.unsigned_abs() as usize)
+                    .map(|p| valid[p])
+            } else {
+                // first non null value strictly after row `i`
+                let after = valid.partition_point(|j| *j <= i);
+                after
+                    .checked_add(offset.unsigned_abs() as usize - 1)
+                    .and_then(|p| valid.get(p).copied())
+            }
+        })
+        .collect()
+}
+
 impl PartitionEvaluator for WindowShiftEvaluator {
     fn evaluate_partition(&self, partition: Range<usize>) -> Result<ArrayRef> {
-        let value = &self.values[0];
-        let value = value.slice(partition.start, partition.end - partition.start);
-        shift_with_default_value(&value, self.shift_offset, &self.default_value)
+        let len = partition.end - partition.start;
+        let value = self.values[0].slice(partition.start, len);
+        let default = match self.values.get(1) {
+            Some(default) => default.slice(partition.start, len),
+            None if !self.ignore_nulls => {
+                return shift_with_default_value(
+                    &value,
+                    self.shift_offset,
+                    &self.default_value,
+                )
+            }
+            None => create_empty_array(&self.default_value, value.data_type(), len)?,
+        };
+
+        // pick every output value either from the shifted input or, if that is
+        // out of bounds, from the default of the same row
+        let candidates = concat(&[value.as_ref(), default.as_ref()])?;
+        let indices = shift_indices(&value, self.shift_offset, self.ignore_nulls)
+            .into_iter()
+            .enumerate()
+            .map(|(i, j)| Some(j.unwrap_or(len + i) as u32))
+            .collect::<UInt32Array>();
+        take(&candidates, &indices, None).map_err(DataFusionError::ArrowError)
     }
 }
 
@@ -265,4 +373,56 @@ mod tests {
         )?;
         Ok(())
     }
+
+    #[test]
+    fn lead_lag_ignore_nulls_and_default_expr() -> Result<()> {
+        let arr: ArrayRef = Arc::new(Int32Array::from(vec![
+            Some(1),
+            None,
+            Some(3),
+            None,
+            None,
+            Some(6),
+        ]));
+        let defaults: ArrayRef = Arc::new(Int64Array::from(vec![10, 20, 30, 40, 50, 60]));
+        let schema = Schema::new(vec![
+            Field::new("arr", DataType::Int32, true),
+            Field::new("defaults", DataType::Int64, false),
+        ]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![arr, defaults])?;
+        let evaluate = |expr: WindowShift| -> Result<Int32Array> {
+            let result = expr.create_evaluator(&batch)?.evaluate(vec![0..6])?;
+            Ok(result[0]
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap()
+                .clone())
+        };
+        let column = || Arc::new(Column::new("arr", 0));
+        let default_column = || Arc::new(Column::new("defaults", 1));
+
+        let result = evaluate(
+            lag("lag".to_owned(), DataType::Int32, column(), None, None)
+                .with_ignore_nulls(true),
+        )?;
+        let expected = vec![None, Some(1), Some(1), Some(3), Some(3), Some(3)];
+        assert_eq!(expected.into_iter().collect::<Int32Array>(), result);
+
+        let result = evaluate(
+            lead("lead".to_owned(), DataType::Int32, column(), Some(1), None)
+                .with_ignore_nulls(true)
+                .with_default_expr(default_column()),
+        )?;
+        let expected = vec![Some(3), Some(3), Some(6), Some(6), Some(6), Some(60)];
+        assert_eq!(expected.into_iter().collect::<Int32Array>(), result);
+
+        // a negative lag offset looks forward like lead
+        let result = evaluate(
+            lag("lag".to_owned(), DataType::Int32, column(), Some(-2), None)
+                .with_default_expr(default_column()),
+        )?;
+        let expected = vec![Some(3), None, None, Some(6), Some(50), Some(60)];
+        assert_eq!(expected.into_iter().collect::<Int32Array>(), result);
+        Ok(())
+    }
 }
@@ -33,10 +33,13 @@ use std::ops::Range;
 use std::sync::Arc;
 
 /// nth_value kind
-#[derive(Debug, Copy, Clone)]
-enum NthValueKind {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NthValueKind {
+    /// `first_value()`
     First,
+    /// `last_value()`
     Last,
+    /// `nth_value()`, with n starting at 1
     Nth(u32),
 }
 
@@ -97,6 +100,11 @@ impl NthValue {
             }),
         }
     }
+
+    /// The value that is taken from the window frame
+    pub fn kind(&self) -> NthValueKind {
+        self.kind
+    }
 }
 
 impl BuiltInWindowFunctionExpr for NthValue {
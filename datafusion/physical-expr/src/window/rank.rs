@@ -38,13 +38,24 @@ pub struct Rank {
     rank_type: RankType,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub(crate) enum RankType {
+/// The kind of rank computed by [`Rank`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RankType {
+    /// `rank()`, with gaps after ties
     Basic,
+    /// `dense_rank()`, without gaps after ties
     Dense,
+    /// `percent_rank()`, the relative rank between 0 and 1
     Percent,
 }
 
+impl Rank {
+    /// The kind of rank
+    pub fn rank_type(&self) -> RankType {
+        self.rank_type
+    }
+}
+
 /// Create a rank window function
 pub fn rank(name: String) -> Rank {
     Rank {
@@ -0,0 +1,287 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Z-order expressions
+//!
+//! `zorder(c1, c2, ...)` maps the values of its arguments to a point of a
+//! Z-order curve, by interleaving the bits of order preserving keys of the
+//! values. Sorting rows by their Z-order value clusters them on all the
+//! arguments at once, so that the files written from the sorted rows have
+//! narrow ranges of values for each of the columns.
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use datafusion_common::{downcast_value, DataFusionError, Result, ScalarValue};
+use datafusion_expr::ColumnarValue;
+use std::sync::Arc;
+
+/// The sign bit of the keys of the signed values
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Returns the keys of the values of a signed integer array
+fn signed_keys<T>(array: &ArrayRef) -> Result<Vec<u64>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i64>,
+{
+    let array = downcast_value!(array, PrimitiveArray, T);
+    Ok(array
+        .iter()
+        .map(|v| {
+            v.map_or(0, |v| {
+                let v: i64 = v.into();
+                v as u64 ^ SIGN_BIT
+            })
+        })
+        .collect())
+}
+
+/// Returns the keys of the values of an unsigned integer array
+fn unsigned_keys<T>(array: &ArrayRef) -> Result<Vec<u64>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<u64>,
+{
+    let array = downcast_value!(array, PrimitiveArray, T);
+    Ok(array.iter().map(|v| v.map_or(0, Into::into)).collect())
+}
+
+/// Returns the keys of the values of a float array
+fn float_keys<T>(array: &ArrayRef) -> Result<Vec<u64>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<f64>,
+{
+    let array = downcast_value!(array, PrimitiveArray, T);
+    Ok(array
+        .iter()
+        .map(|v| v.map_or(0, |v| float_key(v.into())))
+        .collect())
+}
+
+/// Returns the keys of the values of a string or binary array
+macro_rules! bytes_keys {
+    ($ARRAY:expr, $TYPE:ident, $OFFSET:ty) => {{
+        let array = downcast_value!($ARRAY, $TYPE, $OFFSET);
+        array
+            .iter()
+            .map(|v| v.map_or(0, |v| bytes_key(v.as_ref())))
+            .collect()
+    }};
+}
+
+/// Returns a key of `value` whose unsigned order is the order of the floats
+fn float_key(value: f64) -> u64 {
+    let bits = value.to_bits();
+    if bits & SIGN_BIT != 0 {
+        !bits
+    } else {
+        bits | SIGN_BIT
+    }
+}
+
+/// Returns a key made of the first 8 bytes of `value`, whose order is the
+/// order of the prefixes of the values
+fn bytes_key(value: &[u8]) -> u64 {
+    let mut prefix = [0u8; 8];
+    let len = value.len().min(8);
+    prefix[..len].copy_from_slice(&value[..len]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Returns the order preserving keys of the values of `array`, nulls having
+/// the smallest key
+fn zorder_keys(array: &ArrayRef) -> Result<Vec<u64>> {
+    Ok(match array.data_type() {
+        DataType::Null => vec![0; array.len()],
+        DataType::Boolean => {
+            let array = downcast_value!(array, BooleanArray);
+            array
+                .iter()
+                .map(|v| v.map_or(0, |v| v as u64 + 1))
+                .collect()
+        }
+        DataType::Int8 => signed_keys::<Int8Type>(array)?,
+        DataType::Int16 => signed_keys::<Int16Type>(array)?,
+        DataType::Int32 => signed_keys::<Int32Type>(array)?,
+        DataType::Int64 => signed_keys::<Int64Type>(array)?,
+        DataType::Date32 => signed_keys::<Date32Type>(array)?,
+        DataType::Date64 => signed_keys::<Date64Type>(array)?,
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            signed_keys::<TimestampSecondType>(array)?
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            signed_keys::<TimestampMillisecondType>(array)?
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            signed_keys::<TimestampMicrosecondType>(array)?
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            signed_keys::<TimestampNanosecondType>(array)?
+        }
+        DataType::UInt8 => unsigned_keys::<UInt8Type>(array)?,
+        DataType::UInt16 => unsigned_keys::<UInt16Type>(array)?,
+        DataType::UInt32 => unsigned_keys::<UInt32Type>(array)?,
+        DataType::UInt64 => unsigned_keys::<UInt64Type>(array)?,
+        DataType::Float32 => float_keys::<Float32Type>(array)?,
+        DataType::Float64 => float_keys::<Float64Type>(array)?,
+        DataType::Utf8 => bytes_keys!(array, GenericStringArray, i32),
+        DataType::LargeUtf8 => bytes_keys!(array, GenericStringArray, i64),
+        DataType::Binary => bytes_keys!(array, GenericBinaryArray, i32),
+        DataType::LargeBinary => bytes_keys!(array, GenericBinaryArray, i64),
+        other => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "zorder is not implemented for type '{:?}'",
+                other
+            )))
+        }
+    })
+}
+
+/// Interleaves the bits of the keys of each row, from the most significant
+/// bit of the key of the first argument to the least significant bit of the
+/// key of the last argument, into a binary value of 8 bytes per argument
+fn interleave(keys: &[Vec<u64>], num_rows: usize) -> BinaryArray {
+    let num_bits = 64 * keys.len();
+    let mut builder = BinaryBuilder::with_capacity(num_rows, num_rows * num_bits / 8);
+    let mut value = vec![0u8; num_bits / 8];
+    for row in 0..num_rows {
+        value.iter_mut().for_each(|byte| *byte = 0);
+        for bit in 0..64 {
+            for (i, column) in keys.iter().enumerate() {
+                if column[row] & (1 << (63 - bit)) != 0 {
+                    let position = bit * keys.len() + i;
+                    value[position / 8] |= 0x80 >> (position % 8);
+                }
+            }
+        }
+        builder.append_value(&value);
+    }
+    builder.finish()
+}
+
+/// Returns the Z-order value of the rows of its arguments, as a binary value
+/// that sorts the rows along a Z-order curve
+pub fn zorder(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    if args.is_empty() {
+        return Err(DataFusionError::Plan(
+            "zorder requires at least one argument".to_string(),
+        ));
+    }
+
+    let num_rows = args.iter().find_map(|arg| match arg {
+        ColumnarValue::Array(array) => Some(array.len()),
+        ColumnarValue::Scalar(_) => None,
+    });
+    let arrays = args
+        .iter()
+        .map(|arg| match arg {
+            ColumnarValue::Array(array) => array.clone(),
+            ColumnarValue::Scalar(scalar) => {
+                scalar.to_array_of_size(num_rows.unwrap_or(1))
+            }
+        })
+        .collect::<Vec<_>>();
+    let keys = arrays.iter().map(zorder_keys).collect::<Result<Vec<_>>>()?;
+
+    let result: ArrayRef = Arc::new(interleave(&keys, num_rows.unwrap_or(1)));
+    Ok(match num_rows {
+        Some(_) => ColumnarValue::Array(result),
+        None => ColumnarValue::Scalar(ScalarValue::try_from_array(&result, 0)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zorder_values(args: Vec<ArrayRef>) -> Result<Vec<Vec<u8>>> {
+        let args = args
+            .into_iter()
+            .map(ColumnarValue::Array)
+            .collect::<Vec<_>>();
+        let result = zorder(&args)?.into_array(1);
+        let result = downcast_value!(result, BinaryArray);
+        Ok(result.iter().map(|v| v.unwrap().to_vec()).collect())
+    }
+
+    #[test]
+    fn zorder_interleaves_bits() -> Result<()> {
+        let values = zorder_values(vec![
+            Arc::new(UInt64Array::from(vec![SIGN_BIT, 0, 3])),
+            Arc::new(UInt64Array::from(vec![0, SIGN_BIT, 1])),
+        ])?;
+        let mut expected = vec![vec![0u8; 16]; 3];
+        expected[0][0] = 0x80;
+        expected[1][0] = 0x40;
+        // the lowest bits of both keys, and the second lowest of the first
+        expected[2][15] = 0x0b;
+        assert_eq!(values, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn zorder_preserves_order() -> Result<()> {
+        // the order of a single argument is preserved
+        let values = zorder_values(vec![Arc::new(Int32Array::from(vec![
+            None,
+            Some(-10),
+            Some(-1),
+            Some(0),
+            Some(7),
+        ]))])?;
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+
+        let values = zorder_values(vec![Arc::new(Float64Array::from(vec![
+            f64::NEG_INFINITY,
+            -2.5,
+            -0.5,
+            0.0,
+            0.5,
+            f64::INFINITY,
+        ]))])?;
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+
+        let values =
+            zorder_values(vec![Arc::new(StringArray::from(vec!["", "a", "ab", "b"]))])?;
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn zorder_clusters_quadrants() -> Result<()> {
+        // the points of the lower left quadrant come before the others
+        let values = zorder_values(vec![
+            Arc::new(Int64Array::from(vec![0, 0, 1, 1, 2, 3])),
+            Arc::new(Int64Array::from(vec![0, 1, 0, 1, 0, 0])),
+        ])?;
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn zorder_unsupported_type() {
+        let args = vec![ColumnarValue::Array(Arc::new(Time32SecondArray::from(
+            vec![1],
+        )))];
+        let err = zorder(&args).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "This feature is not implemented: zorder is not implemented for type 'Time32(Second)'"
+        );
+    }
+}
@@ -44,6 +44,13 @@ fn build() -> Result<(), String> {
         .file_descriptor_set_path(&descriptor_path)
         .compile_well_known_types()
         .extern_path(".google.protobuf", "::pbjson_types")
+        // maps are encoded in the order of their keys, see `logical_plan_digest`
+        .btree_map(["."])
+        // serialized with serde by `PostcardCodec`
+        .type_attribute(
+            ".datafusion",
+            "#[cfg_attr(feature = \"postcard\", derive(serde::Serialize, serde::Deserialize))]",
+        )
         .compile_protos(&["proto/datafusion.proto"], &["proto"])
         .map_err(|e| format!("protobuf compilation failed: {}", e))?;
 
@@ -56,6 +63,7 @@ fn build() -> Result<(), String> {
             "Cannot register descriptors {:?}",
             &descriptor_set
         ))
+        .btree_map(["."])
         .build(&[".datafusion"])
         .map_err(|e| format!("pbjson compilation failed: {}", e))?;
 
@@ -64,6 +72,8 @@ fn build() -> Result<(), String> {
 
     std::fs::copy(prost, "src/generated/prost.rs").unwrap();
     std::fs::copy(pbjson, "src/generated/pbjson.rs").unwrap();
+    // read by `upgrade_plan_bytes` to find the messages of old plans
+    std::fs::copy(descriptor_path, "src/generated/datafusion_descriptor.bin").unwrap();
 
     Ok(())
 }
@@ -0,0 +1,313 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Bundles of logical plans with the record batches of the in-memory tables
+//! they scan
+//!
+//! A bundle is the header of serialized logical plans, with its own magic
+//! bytes, followed by a [`protobuf::LogicalPlanBundle`]: the plan, and the
+//! batches of each [`MemTable`] it scans as Arrow IPC streams. The scans of
+//! the plan refer to the tables by their index in the bundle, so that a
+//! table scanned several times, such as by a self join, is stored once.
+
+use std::fmt::{self, Debug};
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::physical_plan::Statistics;
+use datafusion::prelude::SessionContext;
+use datafusion_common::{DataFusionError, Result};
+use datafusion_expr::{Extension, LogicalPlan};
+use prost::bytes::Bytes;
+use prost::Message;
+
+use super::limits::decode_error;
+use super::{DefaultExtensionCodec, LOGICAL_PLAN_HEADER_LEN, LOGICAL_PLAN_WIRE_VERSION};
+use crate::logical_plan::{
+    mem_table_from_proto, mem_table_to_proto, AsLogicalPlan, LogicalExtensionCodec,
+};
+use crate::protobuf;
+use crate::protobuf::bundle_table_provider_node::Provider;
+
+/// The magic bytes starting the serialized bytes of a bundle
+const BUNDLE_MAGIC: &[u8; 4] = b"DFPB";
+
+/// Serialize a LogicalPlan together with the record batches of the
+/// in-memory tables it scans, as one framed byte stream
+///
+/// Unlike [`logical_plan_to_bytes`](super::logical_plan_to_bytes), which
+/// encodes the batches of each scan of a [`MemTable`], the bundle stores the
+/// batches of each table once.
+pub fn logical_plan_to_bundle(plan: &LogicalPlan) -> Result<Bytes> {
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_to_bundle_with_extension_codec(plan, &extension_codec)
+}
+
+/// Serialize a LogicalPlan together with the record batches of the
+/// in-memory tables it scans, encoding its other extension nodes and table
+/// providers with `extension_codec`
+pub fn logical_plan_to_bundle_with_extension_codec(
+    plan: &LogicalPlan,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Bytes> {
+    let encoder = BundleEncoder {
+        inner: extension_codec,
+        tables: Mutex::new(vec![]),
+    };
+    let plan = protobuf::LogicalPlanNode::try_from_logical_plan(plan, &encoder)?;
+    let tables = encoder
+        .tables
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|(_, table)| table)
+        .collect();
+    let bundle = protobuf::LogicalPlanBundle {
+        plan: Some(plan),
+        tables,
+    };
+
+    let len = bundle.encoded_len();
+    let mut buffer = Vec::with_capacity(LOGICAL_PLAN_HEADER_LEN + len);
+    buffer.extend_from_slice(BUNDLE_MAGIC);
+    buffer.extend_from_slice(&LOGICAL_PLAN_WIRE_VERSION.to_le_bytes());
+    buffer.extend_from_slice(&(len as u64).to_le_bytes());
+    bundle.encode(&mut buffer).map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding plan bundle as bytes: {}", e))
+    })?;
+    Ok(buffer.into())
+}
+
+/// Deserialize a LogicalPlan from the output of [`logical_plan_to_bundle`],
+/// whose scans of in-memory tables read the record batches of the bundle
+pub fn logical_plan_from_bundle(
+    bytes: &[u8],
+    ctx: &SessionContext,
+) -> Result<LogicalPlan> {
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_from_bundle_with_extension_codec(bytes, ctx, &extension_codec)
+}
+
+/// Deserialize a LogicalPlan from the output of
+/// [`logical_plan_to_bundle_with_extension_codec`] with the same
+/// `extension_codec`
+pub fn logical_plan_from_bundle_with_extension_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan> {
+    let bytes = check_bundle_header(bytes)?;
+    let bundle = protobuf::LogicalPlanBundle::decode(bytes)
+        .map_err(|e| decode_error("plan bundle", e))?;
+    let tables = bundle
+        .tables
+        .iter()
+        .map(|table| {
+            let table: Arc<dyn TableProvider> = Arc::new(mem_table_from_proto(table)?);
+            Ok(table)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let decoder = BundleDecoder {
+        inner: extension_codec,
+        tables,
+    };
+    bundle
+        .plan
+        .ok_or_else(|| {
+            DataFusionError::Plan(
+                "Error decoding plan bundle: the bundle has no plan".to_string(),
+            )
+        })?
+        .try_into_logical_plan(ctx, &decoder)
+}
+
+/// Checks the header of a bundle, returning the encoded bundle following it
+fn check_bundle_header(bytes: &[u8]) -> Result<&[u8]> {
+    if bytes.len() < LOGICAL_PLAN_HEADER_LEN || !bytes.starts_with(BUNDLE_MAGIC) {
+        return Err(DataFusionError::Plan(
+            "Error decoding plan bundle: bytes are not a serialized DataFusion plan bundle"
+                .to_string(),
+        ));
+    }
+    let mut version = [0; 4];
+    version.copy_from_slice(&bytes[4..8]);
+    let version = u32::from_le_bytes(version);
+    if version != LOGICAL_PLAN_WIRE_VERSION {
+        return Err(DataFusionError::Plan(format!(
+            "Error decoding plan bundle: incompatible version {} of the plan format, \
+             expected version {}; the bundle was serialized by a different release of DataFusion",
+            version, LOGICAL_PLAN_WIRE_VERSION
+        )));
+    }
+    let mut len = [0; 8];
+    len.copy_from_slice(&bytes[8..LOGICAL_PLAN_HEADER_LEN]);
+    let len = u64::from_le_bytes(len);
+    let bytes = &bytes[LOGICAL_PLAN_HEADER_LEN..];
+    if bytes.len() as u64 != len {
+        return Err(DataFusionError::Plan(format!(
+            "Error decoding plan bundle: expected {} bytes after the header, found {}",
+            len,
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+/// Encodes the scans of in-memory tables as references to the tables of the
+/// bundle, and the other nodes with the codec `inner`
+#[derive(Debug)]
+struct BundleEncoder<'a> {
+    inner: &'a dyn LogicalExtensionCodec,
+    /// The tables of the bundle, with the addresses of their providers
+    tables: Mutex<Vec<(usize, protobuf::MemTableNode)>>,
+}
+
+impl LogicalExtensionCodec for BundleEncoder<'_> {
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: &[LogicalPlan],
+        ctx: &SessionContext,
+    ) -> Result<Extension> {
+        self.inner.try_decode(buf, inputs, ctx)
+    }
+
+    fn try_encode(&self, node: &Extension, buf: &mut Vec<u8>) -> Result<()> {
+        self.inner.try_encode(node, buf)
+    }
+
+    fn try_decode_table_provider(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>> {
+        self.inner.try_decode_table_provider(buf, schema, ctx)
+    }
+
+    fn try_encode_table_provider(
+        &self,
+        node: Arc<dyn TableProvider>,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        let provider = match node.as_any().downcast_ref::<MemTable>() {
+            Some(mem_table) => {
+                let address = Arc::as_ptr(&node) as *const () as usize;
+                let mut tables = self.tables.lock().unwrap();
+                let index = match tables.iter().position(|(a, _)| *a == address) {
+                    Some(index) => index,
+                    None => {
+                        tables.push((address, mem_table_to_proto(mem_table)?));
+                        tables.len() - 1
+                    }
+                };
+                Provider::Table(index as u32)
+            }
+            None => {
+                let mut custom = vec![];
+                self.inner.try_encode_table_provider(node, &mut custom)?;
+                Provider::Custom(custom)
+            }
+        };
+        protobuf::BundleTableProviderNode {
+            provider: Some(provider),
+        }
+        .encode(buf)
+        .map_err(|e| {
+            DataFusionError::Internal(format!("Error encoding table provider: {}", e))
+        })
+    }
+}
+
+/// Decodes the references to the tables of a bundle, and the other nodes
+/// with the codec `inner`
+struct BundleDecoder<'a> {
+    inner: &'a dyn LogicalExtensionCodec,
+    tables: Vec<Arc<dyn TableProvider>>,
+}
+
+impl Debug for BundleDecoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundleDecoder")
+            .field("inner", &self.inner)
+            .field("tables", &self.tables.len())
+            .finish()
+    }
+}
+
+impl LogicalExtensionCodec for BundleDecoder<'_> {
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: &[LogicalPlan],
+        ctx: &SessionContext,
+    ) -> Result<Extension> {
+        self.inner.try_decode(buf, inputs, ctx)
+    }
+
+    fn try_encode(&self, node: &Extension, buf: &mut Vec<u8>) -> Result<()> {
+        self.inner.try_encode(node, buf)
+    }
+
+    fn try_decode_table_provider(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>> {
+        self.try_decode_table_provider_with_statistics(buf, schema, None, ctx)
+    }
+
+    fn try_decode_table_provider_with_statistics(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        statistics: Option<Statistics>,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let node = protobuf::BundleTableProviderNode::decode(buf).map_err(|e| {
+            DataFusionError::Internal(format!("Error decoding table provider: {}", e))
+        })?;
+        match node.provider {
+            Some(Provider::Table(index)) => {
+                self.tables.get(index as usize).cloned().ok_or_else(|| {
+                    DataFusionError::Internal(format!(
+                        "Error decoding plan bundle: missing table {}",
+                        index
+                    ))
+                })
+            }
+            Some(Provider::Custom(custom)) => {
+                self.inner.try_decode_table_provider_with_statistics(
+                    &custom, schema, statistics, ctx,
+                )
+            }
+            None => Err(DataFusionError::Internal(
+                "Missing required field in protobuf".to_string(),
+            )),
+        }
+    }
+
+    fn try_encode_table_provider(
+        &self,
+        node: Arc<dyn TableProvider>,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        self.inner.try_encode_table_provider(node, buf)
+    }
+}
@@ -0,0 +1,132 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The wire formats of the protobuf representation of plans
+
+use std::fmt::Debug;
+
+use datafusion_common::{DataFusionError, Result};
+use prost::bytes::{Bytes, BytesMut};
+use prost::Message;
+
+use super::limits::decode_error;
+use crate::protobuf;
+
+/// Encodes the protobuf representation of plans to/from bytes, in some wire
+/// format
+///
+/// [`ProstCodec`] encodes them as protobuf, the format of the functions of
+/// [`crate::bytes`] without a codec. `PostcardCodec` encodes them in the
+/// compact postcard format, which can only be decoded by the same release of
+/// DataFusion.
+///
+/// The same codec needs to be used to decode the plans it encoded.
+pub trait PlanCodec: Debug + Send + Sync {
+    /// Encodes the protobuf representation of a logical plan
+    fn encode_logical_plan(&self, node: &protobuf::LogicalPlanNode) -> Result<Bytes>;
+
+    /// Decodes the output of [`Self::encode_logical_plan`]
+    fn decode_logical_plan(&self, bytes: &[u8]) -> Result<protobuf::LogicalPlanNode>;
+
+    /// Encodes the protobuf representation of a physical plan
+    fn encode_physical_plan(&self, node: &protobuf::PhysicalPlanNode) -> Result<Bytes>;
+
+    /// Decodes the output of [`Self::encode_physical_plan`]
+    fn decode_physical_plan(&self, bytes: &[u8]) -> Result<protobuf::PhysicalPlanNode>;
+}
+
+/// A [`PlanCodec`] encoding plans as protobuf, with prost
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProstCodec {}
+
+impl ProstCodec {
+    fn encode(message: &impl Message) -> Result<Bytes> {
+        let mut buffer = BytesMut::with_capacity(message.encoded_len());
+        message.encode(&mut buffer).map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+        })?;
+        Ok(buffer.into())
+    }
+
+    fn decode<M: Message + Default>(bytes: &[u8]) -> Result<M> {
+        M::decode(bytes).map_err(|e| decode_error("expr", e))
+    }
+}
+
+impl PlanCodec for ProstCodec {
+    fn encode_logical_plan(&self, node: &protobuf::LogicalPlanNode) -> Result<Bytes> {
+        Self::encode(node)
+    }
+
+    fn decode_logical_plan(&self, bytes: &[u8]) -> Result<protobuf::LogicalPlanNode> {
+        Self::decode(bytes)
+    }
+
+    fn encode_physical_plan(&self, node: &protobuf::PhysicalPlanNode) -> Result<Bytes> {
+        Self::encode(node)
+    }
+
+    fn decode_physical_plan(&self, bytes: &[u8]) -> Result<protobuf::PhysicalPlanNode> {
+        Self::decode(bytes)
+    }
+}
+
+/// A [`PlanCodec`] encoding plans in the postcard format, requires the
+/// `postcard` feature
+///
+/// Unlike protobuf, postcard does not encode the tags of the fields, so the
+/// plans are typically smaller and faster to encode, but the fields of the
+/// messages cannot change between the releases encoding and decoding them.
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostcardCodec {}
+
+#[cfg(feature = "postcard")]
+impl PostcardCodec {
+    fn encode(message: &impl serde::Serialize) -> Result<Bytes> {
+        postcard::to_allocvec(message)
+            .map(Bytes::from)
+            .map_err(|e| {
+                DataFusionError::Plan(format!("Error encoding plan as postcard: {}", e))
+            })
+    }
+
+    fn decode<'a, M: serde::Deserialize<'a>>(bytes: &'a [u8]) -> Result<M> {
+        postcard::from_bytes(bytes).map_err(|e| {
+            DataFusionError::Plan(format!("Error decoding plan as postcard: {}", e))
+        })
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl PlanCodec for PostcardCodec {
+    fn encode_logical_plan(&self, node: &protobuf::LogicalPlanNode) -> Result<Bytes> {
+        Self::encode(node)
+    }
+
+    fn decode_logical_plan(&self, bytes: &[u8]) -> Result<protobuf::LogicalPlanNode> {
+        Self::decode(bytes)
+    }
+
+    fn encode_physical_plan(&self, node: &protobuf::PhysicalPlanNode) -> Result<Bytes> {
+        Self::encode(node)
+    }
+
+    fn decode_physical_plan(&self, bytes: &[u8]) -> Result<protobuf::PhysicalPlanNode> {
+        Self::decode(bytes)
+    }
+}
@@ -0,0 +1,170 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Compression of the bytes of serialized plans
+//!
+//! Compressed plans are zstd or LZ4 frames, recognized by the magic bytes
+//! starting the frames. Neither can start the bytes of an uncompressed plan:
+//! logical plans start with their own magic bytes, and the first byte of a
+//! physical plan is the tag of a message field of the protobuf, or the 0 or 1
+//! of its optional plan type in the postcard format of `PostcardCodec`.
+
+use std::borrow::Cow;
+
+use datafusion_common::{DataFusionError, Result};
+use prost::bytes::Bytes;
+
+/// The magic bytes starting a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The magic bytes starting an LZ4 frame
+const LZ4_MAGIC: [u8; 4] = [0x04, 0x22, 0x4D, 0x18];
+
+/// The compression of the bytes of serialized plans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// The plans are not compressed
+    None,
+    /// Zstandard compression at the given level, from 1 to 22, requires the
+    /// `zstd` feature
+    Zstd(i32),
+    /// LZ4 frame compression, requires the `lz4` feature
+    Lz4,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Whether `head`, the first 4 bytes or more of a serialized plan, start a
+/// compressed frame
+pub(crate) fn is_compressed(head: &[u8]) -> bool {
+    head.starts_with(&ZSTD_MAGIC) || head.starts_with(&LZ4_MAGIC)
+}
+
+/// Compresses the bytes of a serialized plan with `compression`
+pub(crate) fn compress(bytes: Bytes, compression: Compression) -> Result<Bytes> {
+    match compression {
+        Compression::None => Ok(bytes),
+        Compression::Zstd(level) => zstd_compress(&bytes, level).map(Bytes::from),
+        Compression::Lz4 => lz4_compress(&bytes).map(Bytes::from),
+    }
+}
+
+/// Decompresses the bytes of a serialized plan, if they are compressed
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
+    decompress_with_limit(bytes, None)
+}
+
+/// Decompresses the bytes of a serialized plan, if they are compressed,
+/// failing with [`DataFusionError::ResourcesExhausted`] if they decompress
+/// into more than `max_size` bytes
+///
+/// The decompression stops after `max_size` bytes, such that small frames
+/// decompressing into huge plans do not exhaust the memory.
+pub(crate) fn decompress_with_limit(
+    bytes: &[u8],
+    max_size: Option<usize>,
+) -> Result<Cow<'_, [u8]>> {
+    // one more byte than the limit, to tell the plans exceeding it
+    let limit = max_size.map_or(u64::MAX, |max_size| max_size as u64 + 1);
+    let output = if bytes.starts_with(&ZSTD_MAGIC) {
+        zstd_decompress(bytes, limit)?
+    } else if bytes.starts_with(&LZ4_MAGIC) {
+        lz4_decompress(bytes, limit)?
+    } else {
+        return Ok(Cow::Borrowed(bytes));
+    };
+    match max_size {
+        Some(max_size) if output.len() > max_size => {
+            Err(DataFusionError::ResourcesExhausted(format!(
+                "Compressed plan of {} bytes decompresses into more than the limit of {} bytes",
+                bytes.len(),
+                max_size
+            )))
+        }
+        _ => Ok(Cow::Owned(output)),
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(bytes: &[u8], level: i32) -> Result<Vec<u8>> {
+    Ok(zstd::bulk::compress(bytes, level)?)
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(bytes: &[u8], limit: u64) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut output = Vec::new();
+    zstd::stream::read::Decoder::new(bytes)?
+        .take(limit)
+        .read_to_end(&mut output)?;
+    Ok(output)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_bytes: &[u8], _level: i32) -> Result<Vec<u8>> {
+    Err(feature_required("zstd"))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_bytes: &[u8], _limit: u64) -> Result<Vec<u8>> {
+    Err(feature_required("zstd"))
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_compress(bytes: &[u8]) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = lz4::EncoderBuilder::new().build(Vec::new())?;
+    encoder.write_all(bytes)?;
+    let (output, result) = encoder.finish();
+    result?;
+    Ok(output)
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_decompress(bytes: &[u8], limit: u64) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut output = Vec::new();
+    lz4::Decoder::new(bytes)?
+        .take(limit)
+        .read_to_end(&mut output)?;
+    Ok(output)
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_compress(_bytes: &[u8]) -> Result<Vec<u8>> {
+    Err(feature_required("lz4"))
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_decompress(_bytes: &[u8], _limit: u64) -> Result<Vec<u8>> {
+    Err(feature_required("lz4"))
+}
+
+#[cfg(any(not(feature = "zstd"), not(feature = "lz4")))]
+fn feature_required(feature: &str) -> DataFusionError {
+    DataFusionError::NotImplemented(format!(
+        "{} compression of serialized plans requires the {} feature of datafusion-proto",
+        feature, feature
+    ))
+}
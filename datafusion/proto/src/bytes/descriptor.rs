@@ -0,0 +1,142 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The descriptors of the protobuf messages generated from
+//! `datafusion.proto`, to walk encoded messages without decoding them
+
+use std::collections::HashMap;
+
+use datafusion_common::{DataFusionError, Result};
+use lazy_static::lazy_static;
+use prost::encoding::{decode_key, decode_varint, WireType};
+use prost::Message;
+#[cfg(feature = "text-format")]
+use prost_reflect::DescriptorPool;
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{DescriptorProto, FileDescriptorSet};
+
+/// The descriptors of the protobuf messages of the current plan format
+const DESCRIPTOR_SET: &[u8] = include_bytes!("../generated/datafusion_descriptor.bin");
+
+lazy_static! {
+    /// The fields of the messages whose values are messages, with the names
+    /// of their messages, by the fully qualified names of the messages
+    pub(crate) static ref MESSAGE_FIELDS: HashMap<String, HashMap<u32, String>> =
+        message_fields();
+}
+
+#[cfg(feature = "text-format")]
+lazy_static! {
+    /// The descriptors of the messages, to convert them to and from the
+    /// protobuf text format
+    pub(crate) static ref DESCRIPTOR_POOL: DescriptorPool =
+        DescriptorPool::decode(DESCRIPTOR_SET)
+            .expect("the generated descriptors of datafusion.proto are valid");
+}
+
+fn message_fields() -> HashMap<String, HashMap<u32, String>> {
+    let descriptors = FileDescriptorSet::decode(DESCRIPTOR_SET)
+        .expect("the generated descriptors of datafusion.proto are valid");
+    let mut messages = HashMap::new();
+    for file in &descriptors.file {
+        let prefix = match file.package() {
+            "" => String::new(),
+            package => format!(".{}", package),
+        };
+        for message in &file.message_type {
+            add_message_fields(&prefix, message, &mut messages);
+        }
+    }
+    messages
+}
+
+fn add_message_fields(
+    prefix: &str,
+    message: &DescriptorProto,
+    messages: &mut HashMap<String, HashMap<u32, String>>,
+) {
+    let name = format!("{}.{}", prefix, message.name());
+    let fields = message
+        .field
+        .iter()
+        .filter(|field| field.r#type() == Type::Message)
+        .map(|field| (field.number() as u32, field.type_name().to_string()))
+        .collect();
+    for nested in &message.nested_type {
+        add_message_fields(&name, nested, messages);
+    }
+    messages.insert(name, fields);
+}
+
+/// Checks that the messages nested in the message `name` encoded as `bytes`
+/// are not nested deeper than `recursion_limit` levels, as counted by the
+/// recursion limit of the protobuf decoder
+///
+/// Fails with [`DataFusionError::ResourcesExhausted`] for the messages
+/// nested too deep, and lets the decoder report malformed messages.
+pub(crate) fn check_recursion_limit(
+    name: &str,
+    bytes: &[u8],
+    recursion_limit: usize,
+) -> Result<()> {
+    if nested_within(name, bytes, recursion_limit) {
+        Ok(())
+    } else {
+        Err(DataFusionError::ResourcesExhausted(format!(
+            "Serialized protobuf messages are nested deeper than the recursion limit of {} levels",
+            recursion_limit
+        )))
+    }
+}
+
+/// Returns false if the messages nested in the message `name` encoded as
+/// `bytes` exceed `levels` levels
+fn nested_within(name: &str, mut bytes: &[u8], levels: usize) -> bool {
+    let fields = MESSAGE_FIELDS.get(name);
+    while !bytes.is_empty() {
+        let (tag, wire_type) = match decode_key(&mut bytes) {
+            Ok(key) => key,
+            Err(_) => return true,
+        };
+        let len = match wire_type {
+            WireType::Varint => match decode_varint(&mut bytes) {
+                Ok(_) => 0,
+                Err(_) => return true,
+            },
+            WireType::SixtyFourBit => 8,
+            WireType::ThirtyTwoBit => 4,
+            WireType::LengthDelimited => match decode_varint(&mut bytes) {
+                Ok(len) => len as usize,
+                Err(_) => return true,
+            },
+            WireType::StartGroup | WireType::EndGroup => return true,
+        };
+        if len > bytes.len() {
+            return true;
+        }
+        let (value, rest) = bytes.split_at(len);
+        bytes = rest;
+        if wire_type == WireType::LengthDelimited {
+            if let Some(field_message) = fields.and_then(|fields| fields.get(&tag)) {
+                if levels == 0 || !nested_within(field_message, value, levels - 1) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,467 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Limits of the plans deserialized from the bytes of untrusted clients
+
+use datafusion_common::{DataFusionError, Result};
+
+use crate::protobuf;
+
+/// The recursion limit of the protobuf decoder: the maximum number of
+/// levels of messages nested in a decoded message
+///
+/// This is the default limit of
+/// [`Serializeable::from_bytes_with_recursion_limit`](super::Serializeable::from_bytes_with_recursion_limit),
+/// which honors higher limits only when the `no-recursion-limit` feature
+/// lifts the limit of the decoder.
+pub const PROTOBUF_RECURSION_LIMIT: usize = 100;
+
+/// The limits of the plans deserialized by
+/// [`logical_plan_from_bytes_with_limits`](super::logical_plan_from_bytes_with_limits)
+/// and
+/// [`physical_plan_from_bytes_with_limits`](super::physical_plan_from_bytes_with_limits),
+/// which fail with [`DataFusionError::ResourcesExhausted`] for the plans
+/// exceeding them
+///
+/// ```
+/// use datafusion_proto::bytes::DeserializeLimits;
+///
+/// let limits = DeserializeLimits::new()
+///     .with_max_size(1 << 20)
+///     .with_max_depth(64)
+///     .with_max_nodes(1000);
+/// assert_eq!(limits.max_size(), Some(1 << 20));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeserializeLimits {
+    max_size: Option<usize>,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+}
+
+impl DeserializeLimits {
+    /// Limits accepting any plan
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the serialized plans to `max_size` bytes, both before and
+    /// after their decompression
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Limits the nesting of the expressions of logical plans to
+    /// `max_depth` levels, such as 3 for `a + (b * 2)`
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Limits the plans to `max_nodes` nodes, including the nodes of the
+    /// subqueries of logical plans
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// The maximum size of the serialized plans, in bytes
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// The maximum nesting of the expressions of logical plans
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// The maximum number of nodes of the plans
+    pub fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    /// Checks the size of the bytes of a serialized plan
+    pub(crate) fn check_size(&self, size: usize) -> Result<()> {
+        match self.max_size {
+            Some(max_size) if size > max_size => {
+                Err(DataFusionError::ResourcesExhausted(format!(
+                    "Serialized plan of {} bytes exceeds the limit of {} bytes",
+                    size, max_size
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the number of nodes and the nesting of the expressions of a
+    /// decoded logical plan, before it is converted
+    pub(crate) fn check_logical_plan(
+        &self,
+        plan: &protobuf::LogicalPlanNode,
+    ) -> Result<()> {
+        let mut checker = Checker {
+            limits: self,
+            nodes: 0,
+        };
+        checker.logical_plan(plan)
+    }
+
+    /// Checks the number of nodes of a decoded physical plan, before it is
+    /// converted
+    pub(crate) fn check_physical_plan(
+        &self,
+        plan: &protobuf::PhysicalPlanNode,
+    ) -> Result<()> {
+        let mut checker = Checker {
+            limits: self,
+            nodes: 0,
+        };
+        checker.physical_plan(plan)
+    }
+}
+
+/// The error of decoding the protobuf message of `what`, such as `"expr"`,
+/// which is [`DataFusionError::ResourcesExhausted`] for the messages nested
+/// deeper than the recursion limit of the protobuf decoder
+pub(crate) fn decode_error(what: &str, e: prost::DecodeError) -> DataFusionError {
+    let message = format!("Error decoding {} as protobuf: {}", what, e);
+    if message.contains("recursion limit reached") {
+        DataFusionError::ResourcesExhausted(format!(
+            "{}: the serialized plan is nested deeper than the protobuf decoder supports",
+            message
+        ))
+    } else {
+        DataFusionError::Plan(message)
+    }
+}
+
+/// Counts the nodes of a decoded plan and measures the nesting of its
+/// expressions, failing as soon as they exceed the limits
+///
+/// The nodes are those of the plan it converts into, including the nodes of
+/// the subqueries and of the plans of the views scanned, and the expressions
+/// are nested as once converted.
+struct Checker<'a> {
+    limits: &'a DeserializeLimits,
+    nodes: usize,
+}
+
+impl Checker<'_> {
+    fn node(&mut self) -> Result<()> {
+        self.nodes += 1;
+        match self.limits.max_nodes {
+            Some(max_nodes) if self.nodes > max_nodes => {
+                Err(DataFusionError::ResourcesExhausted(format!(
+                    "Serialized plan exceeds the limit of {} nodes",
+                    max_nodes
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn logical_plan(&mut self, plan: &protobuf::LogicalPlanNode) -> Result<()> {
+        use protobuf::logical_plan_node::LogicalPlanType;
+
+        self.node()?;
+        let plan_type = match &plan.logical_plan_type {
+            Some(plan_type) => plan_type,
+            None => return Ok(()),
+        };
+        let (inputs, exprs): (Vec<&Option<Box<_>>>, Vec<&protobuf::LogicalExprNode>) =
+            match plan_type {
+                LogicalPlanType::ListingScan(scan) => {
+                    (vec![], scan.filters.iter().collect())
+                }
+                LogicalPlanType::CustomScan(scan) => {
+                    (vec![], scan.filters.iter().collect())
+                }
+                LogicalPlanType::ViewScan(scan) => (vec![&scan.input], vec![]),
+                LogicalPlanType::Projection(projection) => {
+                    (vec![&projection.input], projection.expr.iter().collect())
+                }
+                LogicalPlanType::Selection(selection) => (
+                    vec![&selection.input],
+                    selection.expr.as_deref().into_iter().collect(),
+                ),
+                LogicalPlanType::Limit(limit) => (vec![&limit.input], vec![]),
+                LogicalPlanType::Aggregate(aggregate) => (
+                    vec![&aggregate.input],
+                    aggregate
+                        .group_expr
+                        .iter()
+                        .chain(&aggregate.aggr_expr)
+                        .collect(),
+                ),
+                LogicalPlanType::Join(join) => (
+                    vec![&join.left, &join.right],
+                    join.filter.as_deref().into_iter().collect(),
+                ),
+                LogicalPlanType::Sort(sort) => {
+                    (vec![&sort.input], sort.expr.iter().collect())
+                }
+                LogicalPlanType::Repartition(repartition) => {
+                    use protobuf::repartition_node::PartitionMethod;
+                    let exprs = match &repartition.partition_method {
+                        Some(PartitionMethod::Hash(hash)) => {
+                            hash.hash_expr.iter().collect()
+                        }
+                        _ => vec![],
+                    };
+                    (vec![&repartition.input], exprs)
+                }
+                LogicalPlanType::Explain(explain) => (vec![&explain.input], vec![]),
+                LogicalPlanType::Window(window) => {
+                    (vec![&window.input], window.window_expr.iter().collect())
+                }
+                LogicalPlanType::Analyze(analyze) => (vec![&analyze.input], vec![]),
+                LogicalPlanType::CrossJoin(join) => {
+                    (vec![&join.left, &join.right], vec![])
+                }
+                LogicalPlanType::Values(values) => {
+                    (vec![], values.values_list.iter().collect())
+                }
+                LogicalPlanType::Extension(extension) => {
+                    for input in &extension.inputs {
+                        self.logical_plan(input)?;
+                    }
+                    (vec![], vec![])
+                }
+                LogicalPlanType::Union(union) => {
+                    for input in &union.inputs {
+                        self.logical_plan(input)?;
+                    }
+                    (vec![], vec![])
+                }
+                LogicalPlanType::SubqueryAlias(alias) => (vec![&alias.input], vec![]),
+                LogicalPlanType::CreateView(view) => (vec![&view.input], vec![]),
+                LogicalPlanType::Distinct(distinct) => (vec![&distinct.input], vec![]),
+                LogicalPlanType::Unnest(unnest) => (vec![&unnest.input], vec![]),
+                LogicalPlanType::Subquery(subquery) => (vec![&subquery.subquery], vec![]),
+                LogicalPlanType::Dedup(dedup) => (
+                    vec![&dedup.input],
+                    dedup.on.iter().chain(&dedup.order_by).collect(),
+                ),
+                LogicalPlanType::Prepare(prepare) => (vec![&prepare.input], vec![]),
+                LogicalPlanType::CreateMemoryTable(table) => (vec![&table.input], vec![]),
+                LogicalPlanType::EmptyRelation(_)
+                | LogicalPlanType::CreateExternalTable(_)
+                | LogicalPlanType::CreateCatalogSchema(_)
+                | LogicalPlanType::CreateCatalog(_)
+                | LogicalPlanType::DropTable(_)
+                | LogicalPlanType::DropView(_)
+                | LogicalPlanType::RefreshMaterializedView(_)
+                | LogicalPlanType::SetVariable(_)
+                | LogicalPlanType::AnalyzeTable(_) => (vec![], vec![]),
+            };
+        for expr in exprs {
+            self.expr(expr, 1)?;
+        }
+        for input in inputs.into_iter().flatten() {
+            self.logical_plan(input)?;
+        }
+        Ok(())
+    }
+
+    /// Checks `expr`, nested `depth` levels deep in the expressions of its
+    /// plan
+    fn expr(&mut self, expr: &protobuf::LogicalExprNode, depth: usize) -> Result<()> {
+        use protobuf::logical_expr_node::ExprType;
+
+        if let Some(max_depth) = self.limits.max_depth {
+            if depth > max_depth {
+                return Err(DataFusionError::ResourcesExhausted(format!(
+                    "Serialized plan has expressions nested deeper than the limit of {} levels",
+                    max_depth
+                )));
+            }
+        }
+        let expr_type = match &expr.expr_type {
+            Some(expr_type) => expr_type,
+            None => return Ok(()),
+        };
+        let children: Vec<&protobuf::LogicalExprNode> = match expr_type {
+            ExprType::BinaryExpr(binary) => {
+                // the operands convert into a left-deep tree of binary
+                // expressions, so the first two are the deepest
+                let len = binary.operands.len();
+                for (i, operand) in binary.operands.iter().enumerate() {
+                    self.expr(operand, depth + len - i.max(1))?;
+                }
+                vec![]
+            }
+            ExprType::Alias(alias) => alias.expr.as_deref().into_iter().collect(),
+            ExprType::AggregateExpr(aggregate) => aggregate
+                .expr
+                .iter()
+                .chain(aggregate.filter.as_deref())
+                .collect(),
+            ExprType::IsNullExpr(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsNotNullExpr(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::NotExpr(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::Between(between) => [&between.expr, &between.low, &between.high]
+                .into_iter()
+                .flat_map(Option::as_deref)
+                .collect(),
+            ExprType::Case(case) => {
+                let mut children: Vec<_> = case.expr.as_deref().into_iter().collect();
+                for when_then in &case.when_then_expr {
+                    children.extend(&when_then.when_expr);
+                    children.extend(&when_then.then_expr);
+                }
+                children.extend(case.else_expr.as_deref());
+                children
+            }
+            ExprType::Cast(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::Sort(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::Negative(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::InList(in_list) => in_list
+                .expr
+                .as_deref()
+                .into_iter()
+                .chain(&in_list.list)
+                .collect(),
+            ExprType::ScalarFunction(function) => function.args.iter().collect(),
+            ExprType::TryCast(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::WindowExpr(window) => window
+                .expr
+                .as_deref()
+                .into_iter()
+                .chain(&window.partition_by)
+                .chain(&window.order_by)
+                .chain(&window.extra_args)
+                .collect(),
+            ExprType::AggregateUdfExpr(aggregate) => aggregate
+                .args
+                .iter()
+                .chain(aggregate.filter.as_deref())
+                .collect(),
+            ExprType::ScalarUdfExpr(function) => function.args.iter().collect(),
+            ExprType::GetIndexedField(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::GroupingSet(set) => set.expr.iter().flat_map(|l| &l.expr).collect(),
+            ExprType::Cube(cube) => cube.expr.iter().collect(),
+            ExprType::Rollup(rollup) => rollup.expr.iter().collect(),
+            ExprType::IsTrue(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsFalse(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsUnknown(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsNotTrue(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsNotFalse(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::IsNotUnknown(e) => e.expr.as_deref().into_iter().collect(),
+            ExprType::Like(like) => [&like.expr, &like.pattern]
+                .into_iter()
+                .flat_map(Option::as_deref)
+                .collect(),
+            ExprType::Ilike(like) => [&like.expr, &like.pattern]
+                .into_iter()
+                .flat_map(Option::as_deref)
+                .collect(),
+            ExprType::SimilarTo(like) => [&like.expr, &like.pattern]
+                .into_iter()
+                .flat_map(Option::as_deref)
+                .collect(),
+            // the subqueries are plans of their own, with their own nesting
+            ExprType::Exists(exists) => {
+                if let Some(subquery) = &exists.subquery {
+                    self.logical_plan(subquery)?;
+                }
+                vec![]
+            }
+            ExprType::InSubquery(in_subquery) => {
+                if let Some(subquery) = &in_subquery.subquery {
+                    self.logical_plan(subquery)?;
+                }
+                in_subquery.expr.as_deref().into_iter().collect()
+            }
+            ExprType::ScalarSubquery(subquery) => {
+                if let Some(subquery) = &subquery.subquery {
+                    self.logical_plan(subquery)?;
+                }
+                vec![]
+            }
+            ExprType::Column(_)
+            | ExprType::Literal(_)
+            | ExprType::Wildcard(_)
+            | ExprType::Placeholder(_) => vec![],
+        };
+        for child in children {
+            self.expr(child, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn physical_plan(&mut self, plan: &protobuf::PhysicalPlanNode) -> Result<()> {
+        use protobuf::physical_plan_node::PhysicalPlanType;
+
+        self.node()?;
+        let plan_type = match &plan.physical_plan_type {
+            Some(plan_type) => plan_type,
+            None => return Ok(()),
+        };
+        let inputs = match plan_type {
+            PhysicalPlanType::Projection(exec) => vec![&exec.input],
+            PhysicalPlanType::GlobalLimit(exec) => vec![&exec.input],
+            PhysicalPlanType::LocalLimit(exec) => vec![&exec.input],
+            PhysicalPlanType::Aggregate(exec) => vec![&exec.input],
+            PhysicalPlanType::HashJoin(exec) => vec![&exec.left, &exec.right],
+            PhysicalPlanType::Sort(exec) => vec![&exec.input],
+            PhysicalPlanType::CoalesceBatches(exec) => vec![&exec.input],
+            PhysicalPlanType::Filter(exec) => vec![&exec.input],
+            PhysicalPlanType::Merge(exec) => vec![&exec.input],
+            PhysicalPlanType::Repartition(exec) => vec![&exec.input],
+            PhysicalPlanType::Window(exec) => vec![&exec.input],
+            PhysicalPlanType::CrossJoin(exec) => vec![&exec.left, &exec.right],
+            PhysicalPlanType::SortPreservingMerge(exec) => vec![&exec.input],
+            PhysicalPlanType::Analyze(exec) => vec![&exec.input],
+            PhysicalPlanType::SortMergeJoin(exec) => vec![&exec.left, &exec.right],
+            PhysicalPlanType::Unnest(exec) => vec![&exec.input],
+            PhysicalPlanType::Bucketed(exec) => vec![&exec.input],
+            PhysicalPlanType::Dedup(exec) => vec![&exec.input],
+            PhysicalPlanType::CheckConstraints(exec) => vec![&exec.input],
+            PhysicalPlanType::ScalarSubquery(exec) => {
+                for subquery in exec.subqueries.iter().flat_map(|s| &s.plan) {
+                    self.physical_plan(subquery)?;
+                }
+                vec![&exec.input]
+            }
+            PhysicalPlanType::Extension(exec) => {
+                for input in &exec.inputs {
+                    self.physical_plan(input)?;
+                }
+                vec![]
+            }
+            PhysicalPlanType::Union(exec) => {
+                for input in &exec.inputs {
+                    self.physical_plan(input)?;
+                }
+                vec![]
+            }
+            PhysicalPlanType::ParquetScan(_)
+            | PhysicalPlanType::CsvScan(_)
+            | PhysicalPlanType::Empty(_)
+            | PhysicalPlanType::AvroScan(_)
+            | PhysicalPlanType::Explain(_)
+            | PhysicalPlanType::JsonScan(_)
+            | PhysicalPlanType::Values(_)
+            | PhysicalPlanType::Memory(_) => vec![],
+        };
+        for input in inputs.into_iter().flatten() {
+            self.physical_plan(input)?;
+        }
+        Ok(())
+    }
+}
@@ -16,29 +16,144 @@
 // under the License.
 
 //! Serialization / Deserialization to Bytes
-use crate::logical_plan::{AsLogicalPlan, LogicalExtensionCodec};
+use crate::logical_plan::{
+    AsLogicalPlan, DefaultLogicalExtensionCodec, LogicalExtensionCodec,
+    PhysicalExtensionCodec,
+};
+use crate::physical_plan::{AsExecutionPlan, DefaultPhysicalExtensionCodec};
 use crate::{from_proto::parse_expr, protobuf};
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{Schema, SchemaRef};
+use datafusion::catalog::catalog::MemoryCatalogProvider;
+use datafusion::catalog::schema::{MemorySchemaProvider, SchemaProvider};
 use datafusion::datasource::TableProvider;
-use datafusion::physical_plan::functions::make_scalar_function;
-use datafusion_common::{DataFusionError, Result};
-use datafusion_expr::{
-    create_udaf, create_udf, Expr, Extension, LogicalPlan, Volatility,
-};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::plan_diff::{plan_diff, PlanDiff};
+use datafusion_common::digest::{sha256, stable_digest};
+use datafusion_common::{DFSchema, DFSchemaRef, DataFusionError, Result, ScalarValue};
+use datafusion_expr::{Expr, Extension, LogicalPlan};
 use prost::{
     bytes::{Bytes, BytesMut},
+    encoding::encode_varint,
     Message,
 };
+use std::io::{Read, Write};
 use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 // Reexport Bytes which appears in the API
 use datafusion::execution::registry::FunctionRegistry;
-use datafusion::prelude::SessionContext;
+use datafusion::prelude::{SessionConfig, SessionContext};
 
+mod bundle;
+mod codec;
+mod compression;
+mod descriptor;
+mod limits;
 mod registry;
+mod stream;
+mod upgrade;
+
+pub use bundle::{
+    logical_plan_from_bundle, logical_plan_from_bundle_with_extension_codec,
+    logical_plan_to_bundle, logical_plan_to_bundle_with_extension_codec,
+};
+#[cfg(feature = "postcard")]
+pub use codec::PostcardCodec;
+pub use codec::{PlanCodec, ProstCodec};
+pub use compression::Compression;
+use descriptor::check_recursion_limit;
+use limits::decode_error;
+pub use limits::{DeserializeLimits, PROTOBUF_RECURSION_LIMIT};
+
+use stream::{
+    decode_from_async_reader, encode_to_async_writer, read_length_prefix, ReaderBuf,
+    WriterBuf,
+};
+pub use upgrade::upgrade_plan_bytes;
+
+/// The name of the schemas of the information schema, which are not serialized
+/// with the catalogs
+const INFORMATION_SCHEMA: &str = "information_schema";
+
+/// The magic bytes starting the serialized bytes of a [`LogicalPlan`]
+const LOGICAL_PLAN_MAGIC: &[u8; 4] = b"DFLP";
+
+/// The version of the format of the serialized bytes of a [`LogicalPlan`],
+/// which is incremented when plans serialized by one release of DataFusion
+/// can not be read by another.
+pub const LOGICAL_PLAN_WIRE_VERSION: u32 = 2;
+
+/// The length of the header of serialized logical plans: the magic bytes,
+/// the wire version and the length of the encoded plan
+const LOGICAL_PLAN_HEADER_LEN: usize = 16;
+
+/// The header of a serialized logical plan of `len` encoded bytes
+fn logical_plan_header(len: usize) -> [u8; LOGICAL_PLAN_HEADER_LEN] {
+    let mut header = [0; LOGICAL_PLAN_HEADER_LEN];
+    header[..4].copy_from_slice(LOGICAL_PLAN_MAGIC);
+    header[4..8].copy_from_slice(&LOGICAL_PLAN_WIRE_VERSION.to_le_bytes());
+    header[8..].copy_from_slice(&(len as u64).to_le_bytes());
+    header
+}
+
+/// Checks the magic bytes and the wire version of the header of a serialized
+/// logical plan, returning the length of the encoded plan following it
+fn check_logical_plan_header(header: &[u8]) -> Result<u64> {
+    if header.len() < LOGICAL_PLAN_HEADER_LEN || !header.starts_with(LOGICAL_PLAN_MAGIC) {
+        return Err(DataFusionError::Plan(
+            "Error decoding logical plan: bytes are not a serialized DataFusion logical plan"
+                .to_string(),
+        ));
+    }
+    let mut version = [0; 4];
+    version.copy_from_slice(&header[4..8]);
+    let version = u32::from_le_bytes(version);
+    if version != LOGICAL_PLAN_WIRE_VERSION {
+        return Err(DataFusionError::Plan(format!(
+            "Error decoding logical plan: incompatible version {} of the plan format, \
+             expected version {}; the plan was serialized by a different release of DataFusion",
+            version, LOGICAL_PLAN_WIRE_VERSION
+        )));
+    }
+    let mut len = [0; 8];
+    len.copy_from_slice(&header[8..LOGICAL_PLAN_HEADER_LEN]);
+    Ok(u64::from_le_bytes(len))
+}
 
-/// Encodes something (such as [`Expr`]) to/from a stream of
-/// bytes.
+/// The options of the serialization of plans to bytes, see
+/// [`logical_plan_to_bytes_with_options`]
+///
+/// ```
+/// use datafusion_proto::bytes::{Compression, SerializeOptions};
+///
+/// let options = SerializeOptions::new().with_compression(Compression::Zstd(3));
+/// assert_eq!(options.compression(), Compression::Zstd(3));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    compression: Compression,
+}
+
+impl SerializeOptions {
+    /// Options serializing uncompressed plans
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compress the serialized plans with `compression`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The compression of the serialized plans
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+}
+
+/// Encodes something (such as [`Expr`] or [`ScalarValue`]) to/from a
+/// stream of bytes.
 ///
 /// ```
 /// use datafusion_expr::{col, lit, Expr};
@@ -55,13 +170,25 @@ mod registry;
 /// assert_eq!(expr, decoded_expr);
 /// ```
 pub trait Serializeable: Sized {
-    /// Convert `self` to an opaque byt stream
+    /// Convert `self` to an opaque byt stream, failing with
+    /// [`DataFusionError::ResourcesExhausted`] if [`from_bytes`] could not
+    /// decode it, as its protobuf messages are nested deeper than
+    /// [`PROTOBUF_RECURSION_LIMIT`]
     fn to_bytes(&self) -> Result<Bytes>;
 
+    /// Convert `self` to an opaque byt stream without checking the nesting
+    /// of its protobuf messages, for trusted pipelines which only serialize
+    /// objects known to be decodable
+    fn to_bytes_unchecked(&self) -> Result<Bytes> {
+        self.to_bytes()
+    }
+
     /// Convert `bytes` (the output of [`to_bytes`] back into an
     /// object. This will error if the serialized bytes contain any
     /// user defined functions, in which case use
-    /// [`from_bytes_with_registry`]
+    /// [`from_bytes_with_registry`], unless they are scalar UDFs
+    /// implemented by an embedded WebAssembly module and the `wasm`
+    /// feature is enabled
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
         Self::from_bytes_with_registry(bytes, &registry::NoRegistry {})
     }
@@ -72,11 +199,53 @@ pub trait Serializeable: Sized {
     fn from_bytes_with_registry(
         bytes: &[u8],
         registry: &dyn FunctionRegistry,
+    ) -> Result<Self> {
+        Self::from_bytes_with_recursion_limit(bytes, registry, PROTOBUF_RECURSION_LIMIT)
+    }
+
+    /// Convert `bytes` (the output of [`to_bytes`] back into an
+    /// object resolving user defined functions with the specified
+    /// `registry`, failing with [`DataFusionError::ResourcesExhausted`] if
+    /// its protobuf messages are nested deeper than `recursion_limit` levels
+    ///
+    /// Limits above [`PROTOBUF_RECURSION_LIMIT`] require the
+    /// `no-recursion-limit` feature, which lifts the limit of the protobuf
+    /// decoder. Decoding deeply nested messages needs as much stack.
+    fn from_bytes_with_recursion_limit(
+        bytes: &[u8],
+        registry: &dyn FunctionRegistry,
+        recursion_limit: usize,
     ) -> Result<Self>;
 }
 
+/// Decodes the protobuf message `name` encoded as `bytes`, nested at most
+/// `recursion_limit` levels deep
+fn decode_with_recursion_limit<M: Message + Default>(
+    what: &str,
+    name: &str,
+    bytes: &[u8],
+    recursion_limit: usize,
+) -> Result<M> {
+    // the decoder checks its own limit
+    if recursion_limit < PROTOBUF_RECURSION_LIMIT || cfg!(feature = "no-recursion-limit")
+    {
+        check_recursion_limit(name, bytes, recursion_limit)?;
+    }
+    M::decode(bytes).map_err(|e| decode_error(what, e))
+}
+
 impl Serializeable for Expr {
     fn to_bytes(&self) -> Result<Bytes> {
+        let bytes = self.to_bytes_unchecked()?;
+        check_recursion_limit(
+            ".datafusion.LogicalExprNode",
+            &bytes,
+            PROTOBUF_RECURSION_LIMIT,
+        )?;
+        Ok(bytes)
+    }
+
+    fn to_bytes_unchecked(&self) -> Result<Bytes> {
         let mut buffer = BytesMut::new();
         let protobuf: protobuf::LogicalExprNode = self.try_into().map_err(|e| {
             DataFusionError::Plan(format!("Error encoding expr as protobuf: {}", e))
@@ -86,62 +255,220 @@ impl Serializeable for Expr {
             DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
         })?;
 
-        let bytes: Bytes = buffer.into();
-
-        // the produced byte stream may lead to "recursion limit" errors, see
-        // https://github.com/apache/arrow-datafusion/issues/3968
-        // Until the underlying prost issue ( https://github.com/tokio-rs/prost/issues/736 ) is fixed, we try to
-        // deserialize the data here and check for errors.
-        //
-        // Need to provide some placeholder registry because the stream may contain UDFs
-        struct PlaceHolderRegistry;
-
-        impl FunctionRegistry for PlaceHolderRegistry {
-            fn udfs(&self) -> std::collections::HashSet<String> {
-                std::collections::HashSet::default()
-            }
+        Ok(buffer.into())
+    }
 
-            fn udf(&self, name: &str) -> Result<Arc<datafusion_expr::ScalarUDF>> {
-                Ok(Arc::new(create_udf(
-                    name,
-                    vec![],
-                    Arc::new(arrow::datatypes::DataType::Null),
-                    Volatility::Immutable,
-                    make_scalar_function(|_| unimplemented!()),
-                )))
-            }
+    fn from_bytes_with_recursion_limit(
+        bytes: &[u8],
+        registry: &dyn FunctionRegistry,
+        recursion_limit: usize,
+    ) -> Result<Self> {
+        let protobuf: protobuf::LogicalExprNode = decode_with_recursion_limit(
+            "expr",
+            ".datafusion.LogicalExprNode",
+            bytes,
+            recursion_limit,
+        )?;
 
-            fn udaf(&self, name: &str) -> Result<Arc<datafusion_expr::AggregateUDF>> {
-                Ok(Arc::new(create_udaf(
-                    name,
-                    arrow::datatypes::DataType::Null,
-                    Arc::new(arrow::datatypes::DataType::Null),
-                    Volatility::Immutable,
-                    Arc::new(|_| unimplemented!()),
-                    Arc::new(vec![]),
-                )))
-            }
-        }
-        Expr::from_bytes_with_registry(&bytes, &PlaceHolderRegistry)?;
+        parse_expr(&protobuf, registry).map_err(|e| {
+            DataFusionError::Plan(format!("Error parsing protobuf into Expr: {}", e))
+        })
+    }
+}
 
+impl Serializeable for ScalarValue {
+    fn to_bytes(&self) -> Result<Bytes> {
+        let bytes = self.to_bytes_unchecked()?;
+        check_recursion_limit(
+            ".datafusion.ScalarValue",
+            &bytes,
+            PROTOBUF_RECURSION_LIMIT,
+        )?;
         Ok(bytes)
     }
 
-    fn from_bytes_with_registry(
+    fn to_bytes_unchecked(&self) -> Result<Bytes> {
+        let protobuf: protobuf::ScalarValue = self.try_into().map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding scalar as protobuf: {}", e))
+        })?;
+        let mut buffer = BytesMut::new();
+        protobuf.encode(&mut buffer).map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+        })?;
+        Ok(buffer.into())
+    }
+
+    /// Scalars do not contain functions, so `registry` is not used
+    fn from_bytes_with_recursion_limit(
         bytes: &[u8],
-        registry: &dyn FunctionRegistry,
+        _registry: &dyn FunctionRegistry,
+        recursion_limit: usize,
     ) -> Result<Self> {
-        let protobuf = protobuf::LogicalExprNode::decode(bytes).map_err(|e| {
-            DataFusionError::Plan(format!("Error decoding expr as protobuf: {}", e))
+        let protobuf: protobuf::ScalarValue = decode_with_recursion_limit(
+            "scalar",
+            ".datafusion.ScalarValue",
+            bytes,
+            recursion_limit,
+        )?;
+        (&protobuf).try_into().map_err(|e| {
+            DataFusionError::Plan(format!(
+                "Error parsing protobuf into ScalarValue: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// Encodes something (such as [`Expr`] or [`ScalarValue`]) to/from
+/// human-readable JSON, the JSON mapping of its protobuf representation.
+///
+/// ```
+/// use datafusion_expr::{col, lit, Expr};
+/// use datafusion_proto::bytes::JsonSerializeable;
+///
+/// let expr = col("a").lt(lit(5i32));
+///
+/// // Convert it to JSON, which can be stored in configuration files
+/// let json = expr.to_json().unwrap();
+///
+/// let decoded_expr = Expr::from_json(&json).unwrap();
+/// assert_eq!(expr, decoded_expr);
+/// ```
+#[cfg(feature = "json")]
+pub trait JsonSerializeable: Sized {
+    /// Convert `self` to JSON
+    fn to_json(&self) -> Result<String>;
+
+    /// Convert `json` (the output of [`to_json`]) back into an object.
+    /// This will error if the JSON contains any user defined functions,
+    /// in which case use [`from_json_with_registry`]
+    fn from_json(json: &str) -> Result<Self> {
+        Self::from_json_with_registry(json, &registry::NoRegistry {})
+    }
+
+    /// Convert `json` (the output of [`to_json`]) back into an object
+    /// resolving user defined functions with the specified `registry`
+    fn from_json_with_registry(
+        json: &str,
+        registry: &dyn FunctionRegistry,
+    ) -> Result<Self>;
+}
+
+#[cfg(feature = "json")]
+impl JsonSerializeable for Expr {
+    fn to_json(&self) -> Result<String> {
+        let protobuf: protobuf::LogicalExprNode = self.try_into().map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding expr as protobuf: {}", e))
         })?;
+        serde_json::to_string(&protobuf).map_err(|e| {
+            DataFusionError::Plan(format!("Error serializing expr as json: {}", e))
+        })
+    }
 
+    fn from_json_with_registry(
+        json: &str,
+        registry: &dyn FunctionRegistry,
+    ) -> Result<Self> {
+        let protobuf: protobuf::LogicalExprNode =
+            serde_json::from_str(json).map_err(|e| {
+                DataFusionError::Plan(format!(
+                    "Error deserializing expr from json: {}",
+                    e
+                ))
+            })?;
         parse_expr(&protobuf, registry).map_err(|e| {
             DataFusionError::Plan(format!("Error parsing protobuf into Expr: {}", e))
         })
     }
 }
 
+#[cfg(feature = "json")]
+impl JsonSerializeable for ScalarValue {
+    fn to_json(&self) -> Result<String> {
+        let protobuf: protobuf::ScalarValue = self.try_into().map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding scalar as protobuf: {}", e))
+        })?;
+        serde_json::to_string(&protobuf).map_err(|e| {
+            DataFusionError::Plan(format!("Error serializing scalar as json: {}", e))
+        })
+    }
+
+    /// Scalars do not contain functions, so `registry` is not used
+    fn from_json_with_registry(
+        json: &str,
+        _registry: &dyn FunctionRegistry,
+    ) -> Result<Self> {
+        let protobuf: protobuf::ScalarValue =
+            serde_json::from_str(json).map_err(|e| {
+                DataFusionError::Plan(format!(
+                    "Error deserializing scalar from json: {}",
+                    e
+                ))
+            })?;
+        (&protobuf).try_into().map_err(|e| {
+            DataFusionError::Plan(format!(
+                "Error parsing protobuf into ScalarValue: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// Serialize an Expr as json
+#[cfg(feature = "json")]
+pub fn expr_to_json(expr: &Expr) -> Result<String> {
+    expr.to_json()
+}
+
+/// Deserialize an Expr from json, resolving user defined functions with
+/// `registry`
+#[cfg(feature = "json")]
+pub fn expr_from_json(json: &str, registry: &dyn FunctionRegistry) -> Result<Expr> {
+    Expr::from_json_with_registry(json, registry)
+}
+
+/// Serialize an Expr to the async `writer`, prefixed with its length as by
+/// [`Message::encode_length_delimited`], so that several expressions can be
+/// written to the same stream
+///
+/// The expression is written in small pieces, without holding all its
+/// bytes in memory. Must be called within a Tokio runtime.
+pub async fn expr_to_async_writer<W>(expr: &Expr, writer: &mut W) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let protobuf: protobuf::LogicalExprNode = expr.try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding expr as protobuf: {}", e))
+    })?;
+    let mut prefix = Vec::with_capacity(10);
+    encode_varint(protobuf.encoded_len() as u64, &mut prefix);
+    writer.write_all(&prefix).await?;
+    encode_to_async_writer(protobuf, writer).await
+}
+
+/// Deserialize an Expr from the async `reader`, reading the output of
+/// [`expr_to_async_writer`] in small pieces, and resolving user defined
+/// functions with `registry`
+pub async fn expr_from_async_reader<R>(
+    reader: &mut R,
+    registry: &(dyn FunctionRegistry + Sync),
+) -> Result<Expr>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let len = read_length_prefix(reader, "expr").await?;
+    let protobuf: protobuf::LogicalExprNode =
+        decode_from_async_reader(reader, vec![], len, "expr").await?;
+    parse_expr(&protobuf, registry).map_err(|e| {
+        DataFusionError::Plan(format!("Error parsing protobuf into Expr: {}", e))
+    })
+}
+
 /// Serialize a LogicalPlan as bytes
+///
+/// The bytes start with a header naming the [`LOGICAL_PLAN_WIRE_VERSION`] of
+/// their format, so that [`logical_plan_from_bytes`] reports the plans
+/// serialized by an incompatible release of DataFusion.
 pub fn logical_plan_to_bytes(plan: &LogicalPlan) -> Result<Bytes> {
     let extension_codec = DefaultExtensionCodec {};
     logical_plan_to_bytes_with_extension_codec(plan, &extension_codec)
@@ -160,18 +487,194 @@ pub fn logical_plan_to_json(plan: &LogicalPlan) -> Result<String> {
         .map_err(|e| DataFusionError::Plan(format!("Error serializing plan: {}", e)))
 }
 
+/// Serialize a LogicalPlan in the protobuf text format, which can be read
+/// and edited by hand, such as to debug captured plans
+///
+/// Unlike [`logical_plan_to_bytes`], the text has no header naming the
+/// version of the plan format.
+///
+/// ```
+/// use datafusion::prelude::SessionContext;
+/// use datafusion_expr::{lit, LogicalPlanBuilder};
+/// use datafusion_proto::bytes::{logical_plan_from_text_proto, logical_plan_to_text_proto};
+///
+/// let plan = LogicalPlanBuilder::empty(true)
+///     .project(vec![lit(1i64)])
+///     .unwrap()
+///     .build()
+///     .unwrap();
+/// let text = logical_plan_to_text_proto(&plan).unwrap();
+/// assert!(text.contains("int64_value: 1"));
+///
+/// let edited = text.replace("int64_value: 1", "int64_value: 2");
+/// let plan = logical_plan_from_text_proto(&edited, &SessionContext::new()).unwrap();
+/// assert_eq!(format!("{:?}", plan), "Projection: Int64(2)\n  EmptyRelation");
+/// ```
+#[cfg(feature = "text-format")]
+pub fn logical_plan_to_text_proto(plan: &LogicalPlan) -> Result<String> {
+    let extension_codec = DefaultExtensionCodec {};
+    let protobuf =
+        protobuf::LogicalPlanNode::try_from_logical_plan(plan, &extension_codec)?;
+    let message = prost_reflect::DynamicMessage::decode(
+        logical_plan_descriptor()?,
+        protobuf.encode_to_vec().as_slice(),
+    )
+    .map_err(|e| {
+        DataFusionError::Internal(format!("Error converting plan to text proto: {}", e))
+    })?;
+    let options = prost_reflect::text_format::FormatOptions::new().pretty(true);
+    Ok(message.to_text_format_with_options(&options))
+}
+
+/// Deserialize a LogicalPlan from the protobuf text format, such as the
+/// output of [`logical_plan_to_text_proto`]
+#[cfg(feature = "text-format")]
+pub fn logical_plan_from_text_proto(
+    text: &str,
+    ctx: &SessionContext,
+) -> Result<LogicalPlan> {
+    let message = prost_reflect::DynamicMessage::parse_text_format(
+        logical_plan_descriptor()?,
+        text,
+    )
+    .map_err(|e| DataFusionError::Plan(format!("Error parsing text proto: {}", e)))?;
+    let protobuf = protobuf::LogicalPlanNode::decode(message.encode_to_vec().as_slice())
+        .map_err(|e| decode_error("logical plan", e))?;
+    let extension_codec = DefaultExtensionCodec {};
+    protobuf.try_into_logical_plan(ctx, &extension_codec)
+}
+
+#[cfg(feature = "text-format")]
+fn logical_plan_descriptor() -> Result<prost_reflect::MessageDescriptor> {
+    descriptor::DESCRIPTOR_POOL
+        .get_message_by_name("datafusion.LogicalPlanNode")
+        .ok_or_else(|| {
+            DataFusionError::Internal(
+                "LogicalPlanNode is missing from the protobuf descriptors".to_string(),
+            )
+        })
+}
+
 /// Serialize a LogicalPlan as bytes, using the provided extension codec
 pub fn logical_plan_to_bytes_with_extension_codec(
     plan: &LogicalPlan,
     extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Bytes> {
+    logical_plan_to_bytes_with_codec(plan, extension_codec, &ProstCodec {})
+}
+
+/// Serialize a LogicalPlan as bytes in the wire format of `codec`, such as
+/// the compact format of `PostcardCodec`
+///
+/// The plan can only be deserialized with the same codec, by
+/// [`logical_plan_from_bytes_with_codec`].
+pub fn logical_plan_to_bytes_with_codec(
+    plan: &LogicalPlan,
+    extension_codec: &dyn LogicalExtensionCodec,
+    codec: &dyn PlanCodec,
 ) -> Result<Bytes> {
     let protobuf =
         protobuf::LogicalPlanNode::try_from_logical_plan(plan, extension_codec)?;
-    let mut buffer = BytesMut::new();
-    protobuf.encode(&mut buffer).map_err(|e| {
+    let encoded = codec.encode_logical_plan(&protobuf)?;
+    let header = logical_plan_header(encoded.len());
+    let mut buffer = BytesMut::with_capacity(header.len() + encoded.len());
+    buffer.extend_from_slice(&header);
+    buffer.extend_from_slice(&encoded);
+    Ok(buffer.into())
+}
+
+/// The SHA-256 digest of a LogicalPlan, such as to key a cache of plans
+///
+/// The digest is computed over the protobuf encoding of the plan, whose maps
+/// are encoded in the order of their keys, so that equal plans have the same
+/// digest in every process, whatever the compression and the header of their
+/// serialized bytes.
+pub fn logical_plan_sha256(plan: &LogicalPlan) -> Result<[u8; 32]> {
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_sha256_with_extension_codec(plan, &extension_codec)
+}
+
+/// The SHA-256 digest of a LogicalPlan, using the provided extension codec
+pub fn logical_plan_sha256_with_extension_codec(
+    plan: &LogicalPlan,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<[u8; 32]> {
+    let protobuf =
+        protobuf::LogicalPlanNode::try_from_logical_plan(plan, extension_codec)?;
+    Ok(sha256(&protobuf.encode_to_vec()))
+}
+
+/// A 64 bits digest of a LogicalPlan: the first bytes of its
+/// [`logical_plan_sha256`] digest
+pub fn logical_plan_digest(plan: &LogicalPlan) -> Result<u64> {
+    let extension_codec = DefaultExtensionCodec {};
+    let protobuf =
+        protobuf::LogicalPlanNode::try_from_logical_plan(plan, &extension_codec)?;
+    Ok(stable_digest(&protobuf.encode_to_vec()))
+}
+
+/// The structural diff of two serialized plans, such as the plans stored to
+/// track the regressions of queries
+///
+/// The plans are both logical plans or both physical plans, serialized by
+/// the functions of this module with the default codecs, and possibly
+/// compressed. Their user defined functions and tables are resolved with
+/// `ctx`. See [`plan_diff`] for the reported differences.
+pub fn diff_plans(a: &[u8], b: &[u8], ctx: &SessionContext) -> Result<PlanDiff> {
+    match (is_logical_plan(a)?, is_logical_plan(b)?) {
+        (true, true) => Ok(plan_diff(
+            &logical_plan_from_bytes(a, ctx)?,
+            &logical_plan_from_bytes(b, ctx)?,
+        )),
+        (false, false) => Ok(plan_diff(
+            &physical_plan_from_bytes(a, ctx)?,
+            &physical_plan_from_bytes(b, ctx)?,
+        )),
+        _ => Err(DataFusionError::Plan(
+            "Cannot diff a logical plan with a physical plan".to_string(),
+        )),
+    }
+}
+
+/// Whether `bytes` are a serialized logical plan rather than a physical plan
+fn is_logical_plan(bytes: &[u8]) -> Result<bool> {
+    Ok(compression::decompress(bytes)?.starts_with(LOGICAL_PLAN_MAGIC))
+}
+
+/// Serialize a LogicalPlan as bytes, compressed as configured by `options`
+///
+/// [`logical_plan_from_bytes`] recognizes and decompresses compressed plans.
+pub fn logical_plan_to_bytes_with_options(
+    plan: &LogicalPlan,
+    options: &SerializeOptions,
+) -> Result<Bytes> {
+    compression::compress(logical_plan_to_bytes(plan)?, options.compression)
+}
+
+/// Serialize a LogicalPlan to `writer`, in the format of
+/// [`logical_plan_to_bytes`]
+///
+/// The plan is written in small pieces, without holding all its bytes in
+/// memory, which makes a difference for very large plans.
+pub fn logical_plan_to_writer(plan: &LogicalPlan, writer: &mut impl Write) -> Result<()> {
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_to_writer_with_extension_codec(plan, writer, &extension_codec)
+}
+
+/// Serialize a LogicalPlan to `writer`, using the provided extension codec
+pub fn logical_plan_to_writer_with_extension_codec(
+    plan: &LogicalPlan,
+    writer: &mut impl Write,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<()> {
+    let protobuf =
+        protobuf::LogicalPlanNode::try_from_logical_plan(plan, extension_codec)?;
+    writer.write_all(&logical_plan_header(protobuf.encoded_len()))?;
+    let mut buf = WriterBuf::new(writer);
+    protobuf.encode(&mut buf).map_err(|e| {
         DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
     })?;
-    Ok(buffer.into())
+    Ok(buf.finish()?)
 }
 
 /// Deserialize a LogicalPlan from json
@@ -183,7 +686,8 @@ pub fn logical_plan_from_json(json: &str, ctx: &SessionContext) -> Result<Logica
     back.try_into_logical_plan(ctx, &extension_codec)
 }
 
-/// Deserialize a LogicalPlan from bytes
+/// Deserialize a LogicalPlan from bytes, which must have been serialized
+/// with the same [`LOGICAL_PLAN_WIRE_VERSION`]
 pub fn logical_plan_from_bytes(
     bytes: &[u8],
     ctx: &SessionContext,
@@ -192,78 +696,1075 @@ pub fn logical_plan_from_bytes(
     logical_plan_from_bytes_with_extension_codec(bytes, ctx, &extension_codec)
 }
 
-/// Deserialize a LogicalPlan from bytes
+/// Deserialize a LogicalPlan from bytes, decompressing them if they were
+/// compressed by [`logical_plan_to_bytes_with_options`]
 pub fn logical_plan_from_bytes_with_extension_codec(
     bytes: &[u8],
     ctx: &SessionContext,
     extension_codec: &dyn LogicalExtensionCodec,
 ) -> Result<LogicalPlan> {
-    let protobuf = protobuf::LogicalPlanNode::decode(bytes).map_err(|e| {
-        DataFusionError::Plan(format!("Error decoding expr as protobuf: {}", e))
-    })?;
-    protobuf.try_into_logical_plan(ctx, extension_codec)
+    logical_plan_from_bytes_with_codec(bytes, ctx, extension_codec, &ProstCodec {})
 }
 
-#[derive(Debug)]
-struct DefaultExtensionCodec {}
-
-impl LogicalExtensionCodec for DefaultExtensionCodec {
-    fn try_decode(
-        &self,
-        _buf: &[u8],
-        _inputs: &[LogicalPlan],
-        _ctx: &SessionContext,
-    ) -> Result<Extension> {
-        Err(DataFusionError::NotImplemented(
-            "No extension codec provided".to_string(),
-        ))
-    }
+/// Deserialize a LogicalPlan from bytes serialized by
+/// [`logical_plan_to_bytes_with_codec`] with the same `codec`
+pub fn logical_plan_from_bytes_with_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+    codec: &dyn PlanCodec,
+) -> Result<LogicalPlan> {
+    decode_logical_plan(
+        bytes,
+        ctx,
+        extension_codec,
+        codec,
+        &DeserializeLimits::new(),
+    )
+}
 
-    fn try_encode(&self, _node: &Extension, _buf: &mut Vec<u8>) -> Result<()> {
-        Err(DataFusionError::NotImplemented(
-            "No extension codec provided".to_string(),
-        ))
-    }
+/// Deserialize a LogicalPlan from the bytes of an untrusted client, failing
+/// with [`DataFusionError::ResourcesExhausted`] if the bytes or the plan
+/// exceed `limits`
+///
+/// The size of the bytes is checked before they are decoded, and the number
+/// of nodes and the nesting of the expressions of the plan once decoded,
+/// before it is converted.
+pub fn logical_plan_from_bytes_with_limits(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan> {
+    let extension_codec = DefaultExtensionCodec {};
+    decode_logical_plan(bytes, ctx, &extension_codec, &ProstCodec {}, limits)
+}
 
-    fn try_decode_table_provider(
-        &self,
-        _buf: &[u8],
-        _schema: SchemaRef,
-        _ctx: &SessionContext,
-    ) -> std::result::Result<Arc<dyn TableProvider>, DataFusionError> {
-        Err(DataFusionError::NotImplemented(
-            "No codec provided to for TableProviders".to_string(),
-        ))
+fn decode_logical_plan(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+    codec: &dyn PlanCodec,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan> {
+    limits.check_size(bytes.len())?;
+    let bytes = compression::decompress_with_limit(bytes, limits.max_size())?;
+    let len = check_logical_plan_header(&bytes)?;
+    let bytes = &bytes[LOGICAL_PLAN_HEADER_LEN..];
+    if bytes.len() as u64 != len {
+        return Err(DataFusionError::Plan(format!(
+            "Error decoding logical plan: expected {} bytes after the header, found {}",
+            len,
+            bytes.len()
+        )));
     }
+    let protobuf = codec.decode_logical_plan(bytes)?;
+    limits.check_logical_plan(&protobuf)?;
+    protobuf.try_into_logical_plan(ctx, extension_codec)
+}
 
-    fn try_encode_table_provider(
-        &self,
-        _node: Arc<dyn TableProvider>,
-        _buf: &mut Vec<u8>,
-    ) -> std::result::Result<(), DataFusionError> {
-        Err(DataFusionError::NotImplemented(
-            "No codec provided to for TableProviders".to_string(),
-        ))
-    }
+/// Deserialize a LogicalPlan from `reader`, reading the output of
+/// [`logical_plan_to_writer`] or [`logical_plan_to_bytes`] in small pieces
+///
+/// The plans compressed by [`logical_plan_to_bytes_with_options`] are also
+/// recognized, but read to the end of `reader`, as their frames do not tell
+/// their length.
+pub fn logical_plan_from_reader(
+    reader: &mut impl Read,
+    ctx: &SessionContext,
+) -> Result<LogicalPlan> {
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_from_reader_with_extension_codec(reader, ctx, &extension_codec)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use arrow::{array::ArrayRef, datatypes::DataType};
-    use datafusion::physical_plan::functions::make_scalar_function;
-    use datafusion::prelude::SessionContext;
-    use datafusion_expr::{col, create_udf, lit, Volatility};
-    use std::sync::Arc;
+/// Deserialize a LogicalPlan from `reader`, using the provided extension
+/// codec
+pub fn logical_plan_from_reader_with_extension_codec(
+    reader: &mut impl Read,
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan> {
+    read_logical_plan(reader, ctx, extension_codec, &DeserializeLimits::new())
+}
 
-    #[test]
-    #[should_panic(
+/// Deserialize a LogicalPlan from the `reader` of an untrusted client,
+/// failing with [`DataFusionError::ResourcesExhausted`] if the plan exceeds
+/// `limits`, as [`logical_plan_from_bytes_with_limits`]
+pub fn logical_plan_from_reader_with_limits(
+    reader: &mut impl Read,
+    ctx: &SessionContext,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan> {
+    let extension_codec = DefaultExtensionCodec {};
+    read_logical_plan(reader, ctx, &extension_codec, limits)
+}
+
+fn read_logical_plan(
+    reader: &mut impl Read,
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan> {
+    let mut header = Vec::with_capacity(LOGICAL_PLAN_HEADER_LEN);
+    reader
+        .by_ref()
+        .take(LOGICAL_PLAN_MAGIC.len() as u64)
+        .read_to_end(&mut header)?;
+    if compression::is_compressed(&header) {
+        let limit = compressed_read_limit(limits, header.len());
+        reader.take(limit).read_to_end(&mut header)?;
+        return decode_logical_plan(
+            &header,
+            ctx,
+            extension_codec,
+            &ProstCodec {},
+            limits,
+        );
+    }
+
+    reader
+        .by_ref()
+        .take((LOGICAL_PLAN_HEADER_LEN - header.len()) as u64)
+        .read_to_end(&mut header)?;
+    let len = check_logical_plan_header(&header)?;
+    limits.check_size(LOGICAL_PLAN_HEADER_LEN.saturating_add(len as usize))?;
+    let mut buf = ReaderBuf::new(reader, len);
+    let protobuf = protobuf::LogicalPlanNode::decode(&mut buf);
+    buf.finish()?;
+    let protobuf = protobuf.map_err(|e| decode_error("logical plan", e))?;
+    limits.check_logical_plan(&protobuf)?;
+    protobuf.try_into_logical_plan(ctx, extension_codec)
+}
+
+/// The number of bytes to read after the `read` first bytes of a compressed
+/// plan, one more than `limits` allow, to tell the plans exceeding them
+fn compressed_read_limit(limits: &DeserializeLimits, read: usize) -> u64 {
+    limits.max_size().map_or(u64::MAX, |max_size| {
+        (max_size as u64 + 1).saturating_sub(read as u64)
+    })
+}
+
+/// Serialize a LogicalPlan to the async `writer`, in the format of
+/// [`logical_plan_to_bytes`]
+///
+/// The plan is written in small pieces, without holding all its bytes in
+/// memory. Must be called within a Tokio runtime.
+pub async fn logical_plan_to_async_writer<W>(
+    plan: &LogicalPlan,
+    writer: &mut W,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_to_async_writer_with_extension_codec(plan, writer, &extension_codec)
+        .await
+}
+
+/// Serialize a LogicalPlan to the async `writer`, using the provided
+/// extension codec
+pub async fn logical_plan_to_async_writer_with_extension_codec<W>(
+    plan: &LogicalPlan,
+    writer: &mut W,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let protobuf =
+        protobuf::LogicalPlanNode::try_from_logical_plan(plan, extension_codec)?;
+    writer
+        .write_all(&logical_plan_header(protobuf.encoded_len()))
+        .await?;
+    encode_to_async_writer(protobuf, writer).await
+}
+
+/// Deserialize a LogicalPlan from the async `reader`, reading the output of
+/// [`logical_plan_to_async_writer`] or [`logical_plan_to_bytes`] in small
+/// pieces
+///
+/// Only the bytes of the plan are read, so that the reader can be read on
+/// after the plan, unless the plan was compressed by
+/// [`logical_plan_to_bytes_with_options`]: the compressed plans are read to
+/// the end of `reader`. Must be called within a Tokio runtime.
+pub async fn logical_plan_from_async_reader<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+) -> Result<LogicalPlan>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let extension_codec = DefaultExtensionCodec {};
+    logical_plan_from_async_reader_with_extension_codec(reader, ctx, &extension_codec)
+        .await
+}
+
+/// Deserialize a LogicalPlan from the async `reader`, using the provided
+/// extension codec
+pub async fn logical_plan_from_async_reader_with_extension_codec<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    read_logical_plan_async(reader, ctx, extension_codec, &DeserializeLimits::new()).await
+}
+
+/// Deserialize a LogicalPlan from the async `reader` of an untrusted client,
+/// failing with [`DataFusionError::ResourcesExhausted`] if the plan exceeds
+/// `limits`, as [`logical_plan_from_bytes_with_limits`]
+pub async fn logical_plan_from_async_reader_with_limits<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let extension_codec = DefaultExtensionCodec {};
+    read_logical_plan_async(reader, ctx, &extension_codec, limits).await
+}
+
+async fn read_logical_plan_async<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+    limits: &DeserializeLimits,
+) -> Result<LogicalPlan>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let mut header = vec![0; LOGICAL_PLAN_MAGIC.len()];
+    reader.read_exact(&mut header).await?;
+    if compression::is_compressed(&header) {
+        let limit = compressed_read_limit(limits, header.len());
+        (&mut *reader).take(limit).read_to_end(&mut header).await?;
+        return decode_logical_plan(
+            &header,
+            ctx,
+            extension_codec,
+            &ProstCodec {},
+            limits,
+        );
+    }
+
+    header.resize(LOGICAL_PLAN_HEADER_LEN, 0);
+    reader
+        .read_exact(&mut header[LOGICAL_PLAN_MAGIC.len()..])
+        .await?;
+    let len = check_logical_plan_header(&header)?;
+    limits.check_size(LOGICAL_PLAN_HEADER_LEN.saturating_add(len as usize))?;
+    let protobuf: protobuf::LogicalPlanNode =
+        decode_from_async_reader(reader, vec![], len, "logical plan").await?;
+    limits.check_logical_plan(&protobuf)?;
+    protobuf.try_into_logical_plan(ctx, extension_codec)
+}
+
+/// Serialize a PhysicalPlan as bytes
+pub fn physical_plan_to_bytes(plan: Arc<dyn ExecutionPlan>) -> Result<Bytes> {
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    physical_plan_to_bytes_with_extension_codec(plan, &extension_codec)
+}
+
+/// Serialize a PhysicalPlan as json
+#[cfg(feature = "json")]
+pub fn physical_plan_to_json(plan: Arc<dyn ExecutionPlan>) -> Result<String> {
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    let protobuf =
+        protobuf::PhysicalPlanNode::try_from_physical_plan(plan, &extension_codec)
+            .map_err(|e| {
+                DataFusionError::Plan(format!("Error serializing plan: {}", e))
+            })?;
+    serde_json::to_string(&protobuf)
+        .map_err(|e| DataFusionError::Plan(format!("Error serializing plan: {}", e)))
+}
+
+/// Serialize a PhysicalPlan as bytes, using the provided extension codec
+pub fn physical_plan_to_bytes_with_extension_codec(
+    plan: Arc<dyn ExecutionPlan>,
+    extension_codec: &dyn PhysicalExtensionCodec,
+) -> Result<Bytes> {
+    physical_plan_to_bytes_with_codec(plan, extension_codec, &ProstCodec {})
+}
+
+/// Serialize a PhysicalPlan as bytes in the wire format of `codec`
+///
+/// The plan can only be deserialized with the same codec, by
+/// [`physical_plan_from_bytes_with_codec`].
+pub fn physical_plan_to_bytes_with_codec(
+    plan: Arc<dyn ExecutionPlan>,
+    extension_codec: &dyn PhysicalExtensionCodec,
+    codec: &dyn PlanCodec,
+) -> Result<Bytes> {
+    let protobuf =
+        protobuf::PhysicalPlanNode::try_from_physical_plan(plan, extension_codec)?;
+    codec.encode_physical_plan(&protobuf)
+}
+
+/// Serialize a PhysicalPlan as bytes, compressed as configured by `options`
+///
+/// [`physical_plan_from_bytes`] recognizes and decompresses compressed plans.
+pub fn physical_plan_to_bytes_with_options(
+    plan: Arc<dyn ExecutionPlan>,
+    options: &SerializeOptions,
+) -> Result<Bytes> {
+    compression::compress(physical_plan_to_bytes(plan)?, options.compression)
+}
+
+/// Deserialize a PhysicalPlan from json
+#[cfg(feature = "json")]
+pub fn physical_plan_from_json(
+    json: &str,
+    ctx: &SessionContext,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let back: protobuf::PhysicalPlanNode = serde_json::from_str(json)
+        .map_err(|e| DataFusionError::Plan(format!("Error serializing plan: {}", e)))?;
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    back.try_into_physical_plan(ctx, &extension_codec)
+}
+
+/// Deserialize a PhysicalPlan from bytes
+pub fn physical_plan_from_bytes(
+    bytes: &[u8],
+    ctx: &SessionContext,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    physical_plan_from_bytes_with_extension_codec(bytes, ctx, &extension_codec)
+}
+
+/// Deserialize a PhysicalPlan from bytes, decompressing them if they were
+/// compressed by [`physical_plan_to_bytes_with_options`]
+pub fn physical_plan_from_bytes_with_extension_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn PhysicalExtensionCodec,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    physical_plan_from_bytes_with_codec(bytes, ctx, extension_codec, &ProstCodec {})
+}
+
+/// Deserialize a PhysicalPlan from bytes serialized by
+/// [`physical_plan_to_bytes_with_codec`] with the same `codec`
+pub fn physical_plan_from_bytes_with_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn PhysicalExtensionCodec,
+    codec: &dyn PlanCodec,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    decode_physical_plan(
+        bytes,
+        ctx,
+        extension_codec,
+        codec,
+        &DeserializeLimits::new(),
+    )
+}
+
+/// Deserialize a PhysicalPlan from the bytes of an untrusted client, failing
+/// with [`DataFusionError::ResourcesExhausted`] if the bytes or the plan
+/// exceed `limits`
+///
+/// Only the size of the bytes and the number of nodes of physical plans are
+/// limited: the nesting of their expressions is not checked, beyond the
+/// recursion limit of the protobuf decoder.
+pub fn physical_plan_from_bytes_with_limits(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    limits: &DeserializeLimits,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    decode_physical_plan(bytes, ctx, &extension_codec, &ProstCodec {}, limits)
+}
+
+fn decode_physical_plan(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn PhysicalExtensionCodec,
+    codec: &dyn PlanCodec,
+    limits: &DeserializeLimits,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    limits.check_size(bytes.len())?;
+    let bytes = compression::decompress_with_limit(bytes, limits.max_size())?;
+    let protobuf = codec.decode_physical_plan(&bytes)?;
+    limits.check_physical_plan(&protobuf)?;
+    protobuf.try_into_physical_plan(ctx, extension_codec)
+}
+
+/// Serialize a PhysicalPlan to the async `writer`, prefixed with its length
+/// as by [`Message::encode_length_delimited`], so that several plans can be
+/// written to the same stream
+///
+/// The plan is written in small pieces, without holding all its bytes in
+/// memory. Must be called within a Tokio runtime.
+pub async fn physical_plan_to_async_writer<W>(
+    plan: Arc<dyn ExecutionPlan>,
+    writer: &mut W,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    physical_plan_to_async_writer_with_extension_codec(plan, writer, &extension_codec)
+        .await
+}
+
+/// Serialize a PhysicalPlan to the async `writer`, using the provided
+/// extension codec
+pub async fn physical_plan_to_async_writer_with_extension_codec<W>(
+    plan: Arc<dyn ExecutionPlan>,
+    writer: &mut W,
+    extension_codec: &dyn PhysicalExtensionCodec,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let protobuf =
+        protobuf::PhysicalPlanNode::try_from_physical_plan(plan, extension_codec)?;
+    let mut prefix = Vec::with_capacity(10);
+    encode_varint(protobuf.encoded_len() as u64, &mut prefix);
+    writer.write_all(&prefix).await?;
+    encode_to_async_writer(protobuf, writer).await
+}
+
+/// Deserialize a PhysicalPlan from the async `reader`, reading the output
+/// of [`physical_plan_to_async_writer`] in small pieces
+///
+/// The plans written after their length are recognized and decompressed if
+/// they were compressed by [`physical_plan_to_bytes_with_options`].
+pub async fn physical_plan_from_async_reader<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+) -> Result<Arc<dyn ExecutionPlan>>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    physical_plan_from_async_reader_with_extension_codec(reader, ctx, &extension_codec)
+        .await
+}
+
+/// Deserialize a PhysicalPlan from the async `reader`, using the provided
+/// extension codec
+pub async fn physical_plan_from_async_reader_with_extension_codec<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    extension_codec: &dyn PhysicalExtensionCodec,
+) -> Result<Arc<dyn ExecutionPlan>>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    read_physical_plan_async(reader, ctx, extension_codec, &DeserializeLimits::new())
+        .await
+}
+
+/// Deserialize a PhysicalPlan from the async `reader` of an untrusted
+/// client, failing with [`DataFusionError::ResourcesExhausted`] if the plan
+/// exceeds `limits`, as [`physical_plan_from_bytes_with_limits`]
+pub async fn physical_plan_from_async_reader_with_limits<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    limits: &DeserializeLimits,
+) -> Result<Arc<dyn ExecutionPlan>>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let extension_codec = DefaultPhysicalExtensionCodec {};
+    read_physical_plan_async(reader, ctx, &extension_codec, limits).await
+}
+
+async fn read_physical_plan_async<R>(
+    reader: &mut R,
+    ctx: &SessionContext,
+    extension_codec: &dyn PhysicalExtensionCodec,
+    limits: &DeserializeLimits,
+) -> Result<Arc<dyn ExecutionPlan>>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let len = read_length_prefix(reader, "physical plan").await?;
+    limits.check_size(usize::try_from(len).unwrap_or(usize::MAX))?;
+    let mut head = vec![0; len.min(4) as usize];
+    reader.read_exact(&mut head).await?;
+    if compression::is_compressed(&head) {
+        let mut bytes = head;
+        (&mut *reader)
+            .take(len - bytes.len() as u64)
+            .read_to_end(&mut bytes)
+            .await?;
+        if (bytes.len() as u64) < len {
+            return Err(DataFusionError::IoError(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{} bytes missing at the end of the stream",
+                    len - bytes.len() as u64
+                ),
+            )));
+        }
+        return decode_physical_plan(
+            &bytes,
+            ctx,
+            extension_codec,
+            &ProstCodec {},
+            limits,
+        );
+    }
+
+    let protobuf: protobuf::PhysicalPlanNode =
+        decode_from_async_reader(reader, head, len, "physical plan").await?;
+    limits.check_physical_plan(&protobuf)?;
+    protobuf.try_into_physical_plan(ctx, extension_codec)
+}
+
+/// Serialize an Arrow schema as bytes, such as the schema of a table
+///
+/// The metadata of the schema is not serialized.
+pub fn schema_to_bytes(schema: &Schema) -> Result<Bytes> {
+    let protobuf: protobuf::Schema = schema.try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding schema as protobuf: {}", e))
+    })?;
+    let mut buffer = BytesMut::new();
+    protobuf.encode(&mut buffer).map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+    })?;
+    Ok(buffer.into())
+}
+
+/// Deserialize an Arrow schema from bytes
+pub fn schema_from_bytes(bytes: &[u8]) -> Result<SchemaRef> {
+    let protobuf = protobuf::Schema::decode(bytes).map_err(|e| {
+        DataFusionError::Plan(format!("Error decoding schema as protobuf: {}", e))
+    })?;
+    let schema: Schema = (&protobuf).try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error parsing protobuf into Schema: {}", e))
+    })?;
+    Ok(Arc::new(schema))
+}
+
+/// Serialize a [`DFSchema`], with the qualifiers of its fields, as bytes
+pub fn df_schema_to_bytes(schema: &DFSchemaRef) -> Result<Bytes> {
+    let protobuf: protobuf::DfSchema = schema.try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding schema as protobuf: {}", e))
+    })?;
+    let mut buffer = BytesMut::new();
+    protobuf.encode(&mut buffer).map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+    })?;
+    Ok(buffer.into())
+}
+
+/// Deserialize a [`DFSchema`] from bytes
+pub fn df_schema_from_bytes(bytes: &[u8]) -> Result<DFSchemaRef> {
+    let protobuf = protobuf::DfSchema::decode(bytes).map_err(|e| {
+        DataFusionError::Plan(format!("Error decoding schema as protobuf: {}", e))
+    })?;
+    let schema: DFSchema = (&protobuf).try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error parsing protobuf into DFSchema: {}", e))
+    })?;
+    Ok(Arc::new(schema))
+}
+
+/// Serialize a [`SessionConfig`] as bytes, such as the configuration of the
+/// session planning a query, to execute its plan with
+///
+/// The extensions of the configuration are not serialized.
+pub fn session_config_to_bytes(config: &SessionConfig) -> Result<Bytes> {
+    let protobuf: protobuf::SessionConfig = config.try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding session config as protobuf: {}", e))
+    })?;
+    let mut buffer = BytesMut::new();
+    protobuf.encode(&mut buffer).map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+    })?;
+    Ok(buffer.into())
+}
+
+/// Deserialize a [`SessionConfig`] from bytes
+pub fn session_config_from_bytes(bytes: &[u8]) -> Result<SessionConfig> {
+    let protobuf = protobuf::SessionConfig::decode(bytes).map_err(|e| {
+        DataFusionError::Plan(format!("Error decoding session config as protobuf: {}", e))
+    })?;
+    (&protobuf).try_into().map_err(|e| {
+        DataFusionError::Plan(format!("Error parsing protobuf into SessionConfig: {}", e))
+    })
+}
+
+/// Serialize the catalogs registered in `ctx` as bytes: the schemas of every
+/// catalog, and the name, schema and provider of every table, such as to
+/// replicate the catalogs of a session in another process
+///
+/// The `information_schema` schemas are not serialized. The table providers
+/// are encoded with the default extension codec, which supports the listing
+/// tables, views and memory tables.
+pub fn catalog_to_bytes(ctx: &SessionContext) -> Result<Bytes> {
+    let extension_codec = DefaultExtensionCodec {};
+    catalog_to_bytes_with_extension_codec(ctx, &extension_codec)
+}
+
+/// Serialize the catalogs registered in `ctx` as bytes, encoding the table
+/// providers with `extension_codec`
+pub fn catalog_to_bytes_with_extension_codec(
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Bytes> {
+    let catalog_list = ctx.state().catalog_list;
+    let mut catalog_names = catalog_list.catalog_names();
+    catalog_names.sort();
+    let mut catalogs = vec![];
+    for catalog_name in catalog_names {
+        let catalog = match catalog_list.catalog(&catalog_name) {
+            Some(catalog) => catalog,
+            None => continue,
+        };
+        let mut schema_names = catalog.schema_names();
+        schema_names.sort();
+        let mut schemas = vec![];
+        for schema_name in schema_names {
+            if schema_name.eq_ignore_ascii_case(INFORMATION_SCHEMA) {
+                continue;
+            }
+            let schema = match catalog.schema(&schema_name) {
+                Some(schema) => schema,
+                None => continue,
+            };
+            let mut table_names = schema.table_names();
+            table_names.sort();
+            let mut tables = vec![];
+            for table_name in table_names {
+                let table = match schema.table(&table_name) {
+                    Some(table) => table,
+                    None => continue,
+                };
+                let mut provider = vec![];
+                extension_codec
+                    .try_encode_table_provider(table.clone(), &mut provider)
+                    .map_err(|e| {
+                        DataFusionError::Plan(format!(
+                            "Error encoding table {}.{}.{}: {}",
+                            catalog_name, schema_name, table_name, e
+                        ))
+                    })?;
+                let table_schema: protobuf::Schema =
+                    table.schema().as_ref().try_into().map_err(|e| {
+                        DataFusionError::Plan(format!(
+                            "Error encoding schema as protobuf: {}",
+                            e
+                        ))
+                    })?;
+                tables.push(protobuf::CatalogTableNode {
+                    name: table_name,
+                    schema: Some(table_schema),
+                    provider,
+                });
+            }
+            schemas.push(protobuf::CatalogSchemaNode {
+                name: schema_name,
+                tables,
+            });
+        }
+        catalogs.push(protobuf::CatalogNode {
+            name: catalog_name,
+            schemas,
+        });
+    }
+
+    let protobuf = protobuf::CatalogSnapshot { catalogs };
+    let mut buffer = BytesMut::new();
+    protobuf.encode(&mut buffer).map_err(|e| {
+        DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+    })?;
+    Ok(buffer.into())
+}
+
+/// Register the catalogs serialized in `bytes` in `ctx`
+///
+/// The catalogs and schemas that are not registered in `ctx` are created in
+/// memory, and the tables replace the tables of the same name.
+pub fn catalog_from_bytes(bytes: &[u8], ctx: &SessionContext) -> Result<()> {
+    let extension_codec = DefaultExtensionCodec {};
+    catalog_from_bytes_with_extension_codec(bytes, ctx, &extension_codec)
+}
+
+/// Register the catalogs serialized in `bytes` in `ctx`, decoding the table
+/// providers with `extension_codec`
+pub fn catalog_from_bytes_with_extension_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<()> {
+    let protobuf = protobuf::CatalogSnapshot::decode(bytes).map_err(|e| {
+        DataFusionError::Plan(format!("Error decoding catalog as protobuf: {}", e))
+    })?;
+    for catalog_node in &protobuf.catalogs {
+        let catalog = match ctx.catalog(&catalog_node.name) {
+            Some(catalog) => catalog,
+            None => {
+                let catalog = Arc::new(MemoryCatalogProvider::new());
+                ctx.register_catalog(&catalog_node.name, catalog);
+                ctx.catalog(&catalog_node.name).ok_or_else(|| {
+                    DataFusionError::Internal(format!(
+                        "Catalog {} not registered",
+                        catalog_node.name
+                    ))
+                })?
+            }
+        };
+        for schema_node in &catalog_node.schemas {
+            let schema = match catalog.schema(&schema_node.name) {
+                Some(schema) => schema,
+                None => {
+                    let schema: Arc<dyn SchemaProvider> =
+                        Arc::new(MemorySchemaProvider::new());
+                    catalog.register_schema(&schema_node.name, schema.clone())?;
+                    schema
+                }
+            };
+            for table_node in &schema_node.tables {
+                let table_schema: Schema = table_node
+                    .schema
+                    .as_ref()
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(
+                            "Missing required field in protobuf".to_string(),
+                        )
+                    })?
+                    .try_into()
+                    .map_err(|e| {
+                        DataFusionError::Plan(format!(
+                            "Error parsing protobuf into Schema: {}",
+                            e
+                        ))
+                    })?;
+                let table = extension_codec.try_decode_table_provider(
+                    &table_node.provider,
+                    Arc::new(table_schema),
+                    ctx,
+                )?;
+                if schema.table_exist(&table_node.name) {
+                    schema.deregister_table(&table_node.name)?;
+                }
+                schema.register_table(table_node.name.clone(), table)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct DefaultExtensionCodec {}
+
+impl LogicalExtensionCodec for DefaultExtensionCodec {
+    fn try_decode(
+        &self,
+        _buf: &[u8],
+        _inputs: &[LogicalPlan],
+        _ctx: &SessionContext,
+    ) -> Result<Extension> {
+        Err(DataFusionError::NotImplemented(
+            "No extension codec provided".to_string(),
+        ))
+    }
+
+    fn try_encode(&self, _node: &Extension, _buf: &mut Vec<u8>) -> Result<()> {
+        Err(DataFusionError::NotImplemented(
+            "No extension codec provided".to_string(),
+        ))
+    }
+
+    fn try_decode_table_provider(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        ctx: &SessionContext,
+    ) -> std::result::Result<Arc<dyn TableProvider>, DataFusionError> {
+        DefaultLogicalExtensionCodec {}.try_decode_table_provider(buf, schema, ctx)
+    }
+
+    fn try_encode_table_provider(
+        &self,
+        node: Arc<dyn TableProvider>,
+        buf: &mut Vec<u8>,
+    ) -> std::result::Result<(), DataFusionError> {
+        DefaultLogicalExtensionCodec {}.try_encode_table_provider(node, buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use arrow::{array::ArrayRef, datatypes::DataType};
+    use datafusion::physical_plan::functions::make_scalar_function;
+    use datafusion::prelude::SessionContext;
+    use datafusion_expr::{col, create_udf, lit, Volatility};
+    use std::sync::Arc;
+
+    #[test]
+    #[should_panic(
         expected = "Error decoding expr as protobuf: failed to decode Protobuf message"
     )]
     fn bad_decode() {
         Expr::from_bytes(b"Leet").unwrap();
     }
 
+    #[test]
+    fn decode_nested_deeper_than_recursion_limit() {
+        let expr = (0..200).fold(col("a"), |expr, _| Expr::Not(Box::new(expr)));
+        let err = expr.to_bytes().unwrap_err();
+        assert!(
+            matches!(err, DataFusionError::ResourcesExhausted(_)),
+            "{}",
+            err
+        );
+        let bytes = expr.to_bytes_unchecked().unwrap();
+        let err = Expr::from_bytes(&bytes).unwrap_err();
+        assert!(
+            matches!(err, DataFusionError::ResourcesExhausted(_)),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn decode_with_recursion_limit() {
+        // each `Not` nests a `NotNode` and a `LogicalExprNode`
+        let expr = (0..10).fold(col("a"), |expr, _| Expr::Not(Box::new(expr)));
+        let bytes = expr.to_bytes().unwrap();
+        assert_eq!(bytes, expr.to_bytes_unchecked().unwrap());
+        let registry = registry::NoRegistry {};
+
+        let decoded =
+            Expr::from_bytes_with_recursion_limit(&bytes, &registry, 22).unwrap();
+        assert_eq!(expr, decoded);
+        let err =
+            Expr::from_bytes_with_recursion_limit(&bytes, &registry, 10).unwrap_err();
+        assert!(
+            matches!(err, DataFusionError::ResourcesExhausted(_)),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains("limit of 10 levels"), "{}", err);
+    }
+
+    #[test]
+    fn scalar_roundtrip() {
+        use arrow::datatypes::Field;
+
+        let list_field = Box::new(Field::new("item", DataType::Int32, true));
+        let struct_fields = Box::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("b", DataType::List(list_field.clone()), true),
+        ]);
+        let scalars = vec![
+            ScalarValue::Int64(Some(42)),
+            ScalarValue::Float64(None),
+            ScalarValue::Utf8(Some("hello".to_string())),
+            ScalarValue::TimestampNanosecond(Some(1_000), Some("UTC".to_string())),
+            ScalarValue::List(
+                Some(vec![ScalarValue::Int32(Some(1)), ScalarValue::Int32(None)]),
+                list_field.clone(),
+            ),
+            ScalarValue::List(None, list_field.clone()),
+            ScalarValue::Struct(
+                Some(vec![
+                    ScalarValue::Utf8(Some("x".to_string())),
+                    ScalarValue::List(
+                        Some(vec![ScalarValue::Int32(Some(2))]),
+                        list_field,
+                    ),
+                ]),
+                struct_fields,
+            ),
+            ScalarValue::Dictionary(
+                Box::new(DataType::Int16),
+                Box::new(ScalarValue::Utf8(Some("key".to_string()))),
+            ),
+        ];
+        for scalar in scalars {
+            let bytes = scalar.to_bytes().unwrap();
+            assert_eq!(ScalarValue::from_bytes(&bytes).unwrap(), scalar);
+        }
+    }
+
+    #[test]
+    fn schema_roundtrip() {
+        use arrow::datatypes::Field;
+
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new(
+                "b",
+                DataType::List(Box::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+        ]);
+        let bytes = schema_to_bytes(&schema).unwrap();
+        assert_eq!(schema_from_bytes(&bytes).unwrap().as_ref(), &schema);
+
+        let df_schema =
+            Arc::new(DFSchema::try_from_qualified_schema("t", &schema).unwrap());
+        let bytes = df_schema_to_bytes(&df_schema).unwrap();
+        assert_eq!(df_schema_from_bytes(&bytes).unwrap(), df_schema);
+
+        let err = schema_from_bytes(b"Leet").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Error decoding schema as protobuf"));
+    }
+
+    #[test]
+    fn session_config_roundtrip() {
+        use datafusion::config::{OPT_BATCH_SIZE, OPT_FILTER_NULL_JOIN_KEYS};
+
+        let config = SessionConfig::new()
+            .with_batch_size(1024)
+            .with_target_partitions(7)
+            .with_default_catalog_and_schema("c", "s")
+            .with_information_schema(true)
+            .with_repartition_joins(false)
+            .with_collect_statistics(true)
+            .with_time_zone("Asia/Tokyo")
+            .set_bool(OPT_FILTER_NULL_JOIN_KEYS, true);
+        let bytes = session_config_to_bytes(&config).unwrap();
+        let parsed = session_config_from_bytes(&bytes).unwrap();
+
+        assert_eq!(parsed.batch_size(), 1024);
+        assert_eq!(parsed.target_partitions, 7);
+        assert_eq!(parsed.default_catalog(), "c");
+        assert_eq!(parsed.default_schema(), "s");
+        assert!(parsed.information_schema());
+        assert!(parsed.creates_default_catalog_and_schema());
+        assert!(!parsed.repartition_joins);
+        assert!(parsed.repartition_aggregations);
+        assert!(parsed.collect_statistics);
+        assert_eq!(parsed.time_zone().unwrap().to_string(), "Asia/Tokyo");
+        assert_eq!(
+            parsed
+                .config_options
+                .read()
+                .get_bool(OPT_FILTER_NULL_JOIN_KEYS),
+            Some(true)
+        );
+        assert_eq!(
+            format!("{:?}", parsed.config_options.read()),
+            format!("{:?}", config.config_options.read())
+        );
+        assert_eq!(
+            parsed.config_options.read().get_u64(OPT_BATCH_SIZE),
+            Some(1024)
+        );
+
+        let err = session_config_from_bytes(b"Leet").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Error decoding session config as protobuf"));
+    }
+
+    #[test]
+    fn logical_plan_digests() {
+        let ctx = SessionContext::new();
+        let plan = |sql: &str| ctx.create_logical_plan(sql).unwrap();
+        let create = |options: &str| {
+            plan(&format!(
+                "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 'foo.csv' OPTIONS ({})",
+                options
+            ))
+        };
+
+        let a = create("'region' 'us-east-2', 'endpoint' 'http://localhost:9000'");
+        let b = create("'endpoint' 'http://localhost:9000', 'region' 'us-east-2'");
+        assert_eq!(
+            logical_plan_sha256(&a).unwrap(),
+            logical_plan_sha256(&b).unwrap()
+        );
+        assert_eq!(
+            logical_plan_digest(&a).unwrap(),
+            logical_plan_digest(&b).unwrap()
+        );
+
+        let c = create("'region' 'us-west-1', 'endpoint' 'http://localhost:9000'");
+        assert_ne!(
+            logical_plan_digest(&a).unwrap(),
+            logical_plan_digest(&c).unwrap()
+        );
+
+        let values = plan("VALUES (1, 'a'), (2, 'b')");
+        assert_eq!(
+            logical_plan_digest(&values).unwrap(),
+            logical_plan_digest(&plan("VALUES (1, 'a'), (2, 'b')")).unwrap()
+        );
+        assert_ne!(
+            logical_plan_digest(&values).unwrap(),
+            logical_plan_digest(&plan("VALUES (2, 'b'), (1, 'a')")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn catalog_roundtrip() {
+        use arrow::array::Int32Array;
+        use arrow::datatypes::Field;
+        use arrow::record_batch::RecordBatch;
+        use datafusion::assert_batches_sorted_eq;
+        use datafusion::catalog::catalog::CatalogProvider;
+        use datafusion::datasource::MemTable;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+        )
+        .unwrap();
+        let table = Arc::new(MemTable::try_new(schema, vec![vec![batch]]).unwrap());
+
+        let config = SessionConfig::new().with_information_schema(true);
+        let ctx = SessionContext::with_config(config.clone());
+        ctx.register_table("t", table.clone()).unwrap();
+        let schema = Arc::new(MemorySchemaProvider::new());
+        schema.register_table("u".to_owned(), table).unwrap();
+        let catalog = Arc::new(MemoryCatalogProvider::new());
+        catalog.register_schema("s", schema).unwrap();
+        ctx.register_catalog("c", catalog);
+        ctx.sql("CREATE VIEW v AS SELECT a AS b FROM t WHERE a > 1")
+            .await
+            .unwrap();
+
+        let bytes = catalog_to_bytes(&ctx).unwrap();
+        let restored = SessionContext::with_config(config);
+        catalog_from_bytes(&bytes, &restored).unwrap();
+
+        let mut catalogs = restored.state().catalog_list.catalog_names();
+        catalogs.sort();
+        assert_eq!(catalogs, vec!["c", "datafusion"]);
+        let public = restored.catalog("datafusion").unwrap().schema("public");
+        let mut tables = public.unwrap().table_names();
+        tables.sort();
+        assert_eq!(tables, vec!["t", "v"]);
+
+        let batches = restored
+            .sql("SELECT b FROM v JOIN c.s.u AS u ON v.b = u.a")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let expected = vec!["+---+", "| b |", "+---+", "| 2 |", "| 3 |", "+---+"];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // restoring again replaces the tables
+        catalog_from_bytes(&bytes, &restored).unwrap();
+
+        let err = catalog_from_bytes(b"Leet", &restored).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Error decoding catalog as protobuf"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Error decoding scalar as protobuf: failed to decode Protobuf message"
+    )]
+    fn bad_scalar_decode() {
+        ScalarValue::from_bytes(b"Leet").unwrap();
+    }
+
     #[test]
     #[cfg(feature = "json")]
     fn plan_to_json() {
@@ -289,6 +1790,629 @@ mod test {
         assert!(result, "Should parse empty relation");
     }
 
+    #[test]
+    #[cfg(feature = "text-format")]
+    fn text_proto_roundtrip() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion_expr::logical_plan::table_scan;
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let plan = table_scan(Some("t"), &schema, None)?
+            .filter(col("a").gt_eq(lit(5i32)))?
+            .build()?;
+        let text = logical_plan_to_text_proto(&plan)?;
+        assert!(text.contains("int32_value: 5"), "{}", text);
+
+        let ctx = SessionContext::new();
+        let actual = logical_plan_from_text_proto(&text, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        let err = logical_plan_from_text_proto("selection {", &ctx).unwrap_err();
+        assert!(err.to_string().contains("Error parsing text proto"));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn expr_json_roundtrip() {
+        let ctx = SessionContext::new();
+        let expr = col("a")
+            .gt_eq(lit(5i32))
+            .and(col("b").not_eq(lit("x")))
+            .or(col("c").is_null());
+        let json = expr_to_json(&expr).unwrap();
+        assert!(json.contains(r#""column":{"name":"a"}"#), "{}", json);
+        assert_eq!(expr_from_json(&json, &ctx).unwrap(), expr);
+        assert_eq!(Expr::from_json(&json).unwrap(), expr);
+
+        let scalar = ScalarValue::Utf8(Some("hello".to_string()));
+        let json = scalar.to_json().unwrap();
+        assert_eq!(ScalarValue::from_json(&json).unwrap(), scalar);
+
+        let err = Expr::from_json(r#"{"column":"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Error deserializing expr from json"));
+    }
+
+    #[test]
+    fn logical_plan_envelope() {
+        use datafusion_common::DFSchema;
+        use datafusion_expr::logical_plan::EmptyRelation;
+
+        let plan = LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: Arc::new(DFSchema::empty()),
+        });
+        let bytes = logical_plan_to_bytes(&plan).unwrap();
+        assert_eq!(&bytes[..4], b"DFLP");
+        assert_eq!(&bytes[4..8], &LOGICAL_PLAN_WIRE_VERSION.to_le_bytes());
+
+        let ctx = SessionContext::new();
+        let actual = logical_plan_from_bytes(&bytes, &ctx).unwrap();
+        assert!(matches!(actual, LogicalPlan::EmptyRelation(_)));
+
+        let mut future = bytes.to_vec();
+        future[4..8].copy_from_slice(&(LOGICAL_PLAN_WIRE_VERSION + 1).to_le_bytes());
+        let err = logical_plan_from_bytes(&future, &ctx).unwrap_err();
+        let expected = format!("incompatible version {}", LOGICAL_PLAN_WIRE_VERSION + 1);
+        assert!(err.to_string().contains(&expected));
+
+        // the protobuf bytes without the envelope
+        let err =
+            logical_plan_from_bytes(&bytes[LOGICAL_PLAN_HEADER_LEN..], &ctx).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("bytes are not a serialized DataFusion logical plan"));
+    }
+
+    #[test]
+    fn upgrade_plan_from_version_1() -> Result<()> {
+        use datafusion_expr::LogicalPlanBuilder;
+        use prost::encoding::encoded_len_varint;
+
+        let ctx = SessionContext::new();
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![lit(1) + lit(2)])?
+            .build()?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        assert_eq!(upgrade_plan_bytes(&bytes)?, bytes);
+
+        // version 1 encoded the operands of `BinaryExprNode` as the fields
+        // `l = 1` and `r = 2`, instead of `repeated operands = 1`
+        let expr = protobuf::LogicalExprNode::try_from(&(lit(1) + lit(2))).unwrap();
+        let binary = match expr.expr_type {
+            Some(protobuf::logical_expr_node::ExprType::BinaryExpr(binary)) => binary,
+            _ => panic!("expected a binary expression"),
+        };
+        let left_len = binary.operands[0].encoded_len();
+        let right_key = 1 + encoded_len_varint(left_len as u64) + left_len;
+        let binary = binary.encode_to_vec();
+        assert_eq!(binary[right_key], 0x0a);
+        let start = bytes
+            .windows(binary.len())
+            .position(|window| window == binary)
+            .unwrap();
+        let mut old = bytes.to_vec();
+        old[4..8].copy_from_slice(&1u32.to_le_bytes());
+        old[start + right_key] = 0x12;
+        let err = logical_plan_from_bytes(&old, &ctx).unwrap_err();
+        assert!(
+            err.to_string().contains("incompatible version 1"),
+            "{}",
+            err
+        );
+
+        let upgraded = upgrade_plan_bytes(&old)?;
+        assert_eq!(upgraded, bytes);
+        let actual = logical_plan_from_bytes(&upgraded, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        let mut future = bytes.to_vec();
+        future[4..8].copy_from_slice(&(LOGICAL_PLAN_WIRE_VERSION + 1).to_le_bytes());
+        let err = upgrade_plan_bytes(&future).unwrap_err();
+        assert!(err.to_string().contains("unknown version"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn logical_plan_stream_roundtrip() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion_expr::logical_plan::table_scan;
+
+        /// Returns at most 3 bytes per read
+        struct SlowReader<'a>(&'a [u8]);
+
+        impl Read for SlowReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(self.0.len()).min(3);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let fields = (0..5000)
+            .map(|i| Field::new(&format!("column_{}", i), DataType::Int64, false))
+            .collect();
+        let plan = table_scan(Some("t"), &Schema::new(fields), None)?
+            .project(vec![col("column_0") + lit(1)])?
+            .build()?;
+
+        let mut written = vec![];
+        logical_plan_to_writer(&plan, &mut written)?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        assert!(bytes.len() > 64 * 1024);
+        assert_eq!(written, bytes.to_vec());
+
+        let ctx = SessionContext::new();
+        let actual = logical_plan_from_reader(&mut SlowReader(&written), &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        let err = logical_plan_from_reader(
+            &mut SlowReader(&written[..written.len() - 10]),
+            &ctx,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("10 bytes missing"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn async_stream_roundtrip() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion_expr::logical_plan::table_scan;
+
+        let fields = (0..5000)
+            .map(|i| Field::new(&format!("column_{}", i), DataType::Int64, false))
+            .collect();
+        let plan = table_scan(Some("t"), &Schema::new(fields), None)?
+            .project(vec![col("column_0") + lit(1)])?
+            .build()?;
+        let expr = col("a").eq(lit("x".repeat(100_000)));
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(true, schema));
+
+        // several objects written to the same stream
+        let mut written = vec![];
+        logical_plan_to_async_writer(&plan, &mut written).await?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        assert!(bytes.len() > 64 * 1024);
+        assert_eq!(written, bytes.to_vec());
+        expr_to_async_writer(&expr, &mut written).await?;
+        physical_plan_to_async_writer(physical_plan.clone(), &mut written).await?;
+
+        let ctx = SessionContext::new();
+        let mut reader = written.as_slice();
+        let actual = logical_plan_from_async_reader(&mut reader, &ctx).await?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        assert_eq!(expr_from_async_reader(&mut reader, &ctx).await?, expr);
+        let actual = physical_plan_from_async_reader(&mut reader, &ctx).await?;
+        assert_eq!(format!("{:?}", physical_plan), format!("{:?}", actual));
+        assert!(reader.is_empty());
+
+        let mut truncated = &bytes[..bytes.len() - 10];
+        let err = logical_plan_from_async_reader(&mut truncated, &ctx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10 bytes missing"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream_limits() -> Result<()> {
+        use datafusion_expr::LogicalPlanBuilder;
+
+        let ctx = SessionContext::new();
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![lit(1) + lit(2)])?
+            .build()?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+
+        let limits = DeserializeLimits::new()
+            .with_max_size(bytes.len())
+            .with_max_nodes(2);
+        let actual =
+            logical_plan_from_reader_with_limits(&mut &bytes[..], &ctx, &limits)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        let actual =
+            logical_plan_from_async_reader_with_limits(&mut &bytes[..], &ctx, &limits)
+                .await?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        for limits in [
+            limits.clone().with_max_size(bytes.len() - 1),
+            limits.with_max_nodes(1),
+        ] {
+            let err =
+                logical_plan_from_reader_with_limits(&mut &bytes[..], &ctx, &limits)
+                    .unwrap_err();
+            assert!(
+                matches!(err, DataFusionError::ResourcesExhausted(_)),
+                "{:?}",
+                err
+            );
+            let err = logical_plan_from_async_reader_with_limits(
+                &mut &bytes[..],
+                &ctx,
+                &limits,
+            )
+            .await
+            .unwrap_err();
+            assert!(
+                matches!(err, DataFusionError::ResourcesExhausted(_)),
+                "{:?}",
+                err
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn compressed_stream_roundtrip() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion_expr::LogicalPlanBuilder;
+
+        let ctx = SessionContext::new();
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![lit("x".repeat(1000))])?
+            .build()?;
+        let options = SerializeOptions::new().with_compression(Compression::Zstd(3));
+        let bytes = logical_plan_to_bytes_with_options(&plan, &options)?;
+
+        let actual = logical_plan_from_reader(&mut &bytes[..], &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        let actual = logical_plan_from_async_reader(&mut &bytes[..], &ctx).await?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        // the limits apply to the decompressed plan
+        let size = logical_plan_to_bytes(&plan)?.len();
+        let limits = DeserializeLimits::new().with_max_size(size - 1);
+        let err = logical_plan_from_reader_with_limits(&mut &bytes[..], &ctx, &limits)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("decompresses into more than"),
+            "{}",
+            err
+        );
+
+        // the compressed physical plans are written after their length
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(true, schema));
+        let bytes = physical_plan_to_bytes_with_options(physical_plan.clone(), &options)?;
+        let mut written = vec![];
+        encode_varint(bytes.len() as u64, &mut written);
+        written.extend_from_slice(&bytes);
+        let mut reader = written.as_slice();
+        let actual = physical_plan_from_async_reader(&mut reader, &ctx).await?;
+        assert_eq!(format!("{:?}", physical_plan), format!("{:?}", actual));
+        assert!(reader.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn physical_plan_roundtrip() {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion::physical_plan::empty::EmptyExec;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(EmptyExec::new(true, schema));
+        let bytes = physical_plan_to_bytes(plan.clone()).unwrap();
+        let ctx = SessionContext::new();
+        let actual = physical_plan_from_bytes(&bytes, &ctx).unwrap();
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+    }
+
+    #[test]
+    fn diff_serialized_plans() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion::plan_diff::PlanChange;
+        use datafusion_expr::logical_plan::table_scan;
+
+        let ctx = SessionContext::new();
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, false)]);
+        let plan = |limit: i64| {
+            table_scan(Some("t"), &schema, None)?
+                .filter(col("a").gt(lit(limit)))?
+                .build()
+        };
+        let a = logical_plan_to_bytes(&plan(1)?)?;
+        let b = logical_plan_to_bytes(&plan(2)?)?;
+
+        assert!(diff_plans(&a, &a, &ctx)?.is_empty());
+        let diff = diff_plans(&a, &b, &ctx)?;
+        assert_eq!(
+            diff.changes,
+            vec![PlanChange::Changed {
+                path: vec![],
+                before: "Filter: t.a > Int64(1)".to_string(),
+                after: "Filter: t.a > Int64(2)".to_string(),
+            }]
+        );
+
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(true, Arc::new(schema.clone())));
+        let c = physical_plan_to_bytes(physical_plan)?;
+        assert!(diff_plans(&c, &c, &ctx)?.is_empty());
+        let err = diff_plans(&a, &c, &ctx).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cannot diff a logical plan with a physical plan"));
+        Ok(())
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_plan_roundtrip() -> Result<()> {
+        use arrow::datatypes::{Field, Schema};
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion_expr::logical_plan::table_scan;
+
+        let ctx = SessionContext::new();
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, false)]);
+        let plan = table_scan(Some("t"), &schema, None)?
+            .filter(col("a").gt(lit(1)))?
+            .project(vec![col("a") + lit(1)])?
+            .build()?;
+        let extension_codec = DefaultExtensionCodec {};
+        let codec = PostcardCodec {};
+
+        let bytes = logical_plan_to_bytes_with_codec(&plan, &extension_codec, &codec)?;
+        let actual =
+            logical_plan_from_bytes_with_codec(&bytes, &ctx, &extension_codec, &codec)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        assert!(logical_plan_from_bytes(&bytes, &ctx).is_err());
+
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(true, Arc::new(schema)));
+        let extension_codec = DefaultPhysicalExtensionCodec {};
+        let bytes = physical_plan_to_bytes_with_codec(
+            physical_plan.clone(),
+            &extension_codec,
+            &codec,
+        )?;
+        let actual =
+            physical_plan_from_bytes_with_codec(&bytes, &ctx, &extension_codec, &codec)?;
+        assert_eq!(format!("{:?}", physical_plan), format!("{:?}", actual));
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_plan_roundtrip() {
+        use arrow::datatypes::Field;
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion_common::DFSchema;
+        use datafusion_expr::logical_plan::EmptyRelation;
+
+        let ctx = SessionContext::new();
+        let plan = LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: Arc::new(DFSchema::empty()),
+        });
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(true, schema));
+
+        for compression in [Compression::None, Compression::Zstd(3), Compression::Lz4] {
+            let options = SerializeOptions::new().with_compression(compression);
+
+            let bytes = logical_plan_to_bytes_with_options(&plan, &options).unwrap();
+            let actual = logical_plan_from_bytes(&bytes, &ctx).unwrap();
+            assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+            let bytes =
+                physical_plan_to_bytes_with_options(physical_plan.clone(), &options)
+                    .unwrap();
+            let actual = physical_plan_from_bytes(&bytes, &ctx).unwrap();
+            assert_eq!(format!("{:?}", physical_plan), format!("{:?}", actual));
+        }
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn compression_requires_feature() {
+        use datafusion_common::DFSchema;
+        use datafusion_expr::logical_plan::EmptyRelation;
+
+        let plan = LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: Arc::new(DFSchema::empty()),
+        });
+        let options = SerializeOptions::new().with_compression(Compression::Zstd(3));
+        let err = logical_plan_to_bytes_with_options(&plan, &options).unwrap_err();
+        assert!(err.to_string().contains("requires the zstd feature"));
+    }
+
+    #[tokio::test]
+    async fn plan_bundle_roundtrip() -> Result<()> {
+        use arrow::array::Int64Array;
+        use arrow::datatypes::Field;
+        use arrow::record_batch::RecordBatch;
+        use datafusion::assert_batches_eq;
+        use datafusion::datasource::MemTable;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1, 2, 3]))],
+        )?;
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))?;
+        let plan = ctx.create_logical_plan(
+            "SELECT x.a FROM t AS x JOIN t AS y ON x.a = y.a ORDER BY x.a",
+        )?;
+
+        // the batches of the table scanned twice are stored once
+        let bundle = logical_plan_to_bundle(&plan)?;
+        assert!(bundle.len() < logical_plan_to_bytes(&plan)?.len());
+
+        let ctx = SessionContext::new();
+        let actual = logical_plan_from_bundle(&bundle, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        let batches = ctx.execute_logical_plan(actual).await?.collect().await?;
+        let expected = vec![
+            "+---+", "| a |", "+---+", "| 1 |", "| 2 |", "| 3 |", "+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let err =
+            logical_plan_from_bundle(&logical_plan_to_bytes(&plan)?, &ctx).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("not a serialized DataFusion plan bundle"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn plan_bundle_roundtrip_with_subqueries() -> Result<()> {
+        use arrow::array::Int64Array;
+        use arrow::datatypes::Field;
+        use arrow::record_batch::RecordBatch;
+        use datafusion::assert_batches_eq;
+        use datafusion::datasource::MemTable;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1, 2, 3]))],
+        )?;
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))?;
+        let plan = ctx.create_logical_plan(
+            "SELECT a FROM t WHERE a > (SELECT avg(a) FROM t) AND a IN (SELECT a FROM t)",
+        )?;
+
+        // the scans of the subqueries refer to the table of the bundle
+        let bundle = logical_plan_to_bundle(&plan)?;
+        let ctx = SessionContext::new();
+        let actual = logical_plan_from_bundle(&bundle, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+        let batches = ctx.execute_logical_plan(actual).await?.collect().await?;
+        let expected = vec!["+---+", "| a |", "+---+", "| 3 |", "+---+"];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[test]
+    fn logical_plan_limits() -> Result<()> {
+        use datafusion_expr::LogicalPlanBuilder;
+
+        let ctx = SessionContext::new();
+        // 2 nodes, with an expression nested 3 levels deep
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![lit(1) + lit(2) + lit(3)])?
+            .build()?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+
+        let limits = DeserializeLimits::new()
+            .with_max_size(bytes.len())
+            .with_max_depth(3)
+            .with_max_nodes(2);
+        let actual = logical_plan_from_bytes_with_limits(&bytes, &ctx, &limits)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        for (limits, message) in [
+            (limits.clone().with_max_size(bytes.len() - 1), "bytes"),
+            (limits.clone().with_max_depth(2), "limit of 2 levels"),
+            (limits.with_max_nodes(1), "limit of 1 nodes"),
+        ] {
+            let err =
+                logical_plan_from_bytes_with_limits(&bytes, &ctx, &limits).unwrap_err();
+            assert!(
+                matches!(err, DataFusionError::ResourcesExhausted(_)),
+                "{:?}",
+                err
+            );
+            assert!(err.to_string().contains(message), "{}", err);
+        }
+
+        // the limits are checked before the plan is converted, so before the
+        // functions it calls are resolved
+        let udf_ctx = context_with_udf();
+        let udf = udf_ctx.udf("dummy")?;
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![udf.call(vec![lit("a")])])?
+            .build()?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let limits = DeserializeLimits::new().with_max_nodes(1);
+        let err = logical_plan_from_bytes_with_limits(&bytes, &ctx, &limits).unwrap_err();
+        assert!(
+            matches!(err, DataFusionError::ResourcesExhausted(_)),
+            "{:?}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn physical_plan_limits() -> Result<()> {
+        use arrow::datatypes::Field;
+        use datafusion::physical_plan::empty::EmptyExec;
+        use datafusion::physical_plan::limit::GlobalLimitExec;
+
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(GlobalLimitExec::new(
+            Arc::new(EmptyExec::new(true, schema)),
+            0,
+            Some(10),
+        ));
+        let bytes = physical_plan_to_bytes(plan.clone())?;
+
+        let limits = DeserializeLimits::new().with_max_nodes(2);
+        let actual = physical_plan_from_bytes_with_limits(&bytes, &ctx, &limits)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", actual));
+
+        let limits = DeserializeLimits::new().with_max_nodes(1);
+        let err =
+            physical_plan_from_bytes_with_limits(&bytes, &ctx, &limits).unwrap_err();
+        assert!(matches!(err, DataFusionError::ResourcesExhausted(_)));
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_plan_size_limit() -> Result<()> {
+        use datafusion_expr::LogicalPlanBuilder;
+
+        let ctx = SessionContext::new();
+        let exprs = (0..20).map(|i| lit("x".repeat(100)).alias(format!("c{}", i)));
+        let plan = LogicalPlanBuilder::empty(true).project(exprs)?.build()?;
+        let size = logical_plan_to_bytes(&plan)?.len();
+        let options = SerializeOptions::new().with_compression(Compression::Zstd(3));
+        let bytes = logical_plan_to_bytes_with_options(&plan, &options)?;
+        assert!(bytes.len() < size / 2);
+
+        // the compressed bytes are within the limit, not the plan they decompress into
+        let limits = DeserializeLimits::new().with_max_size(size - 1);
+        let err = logical_plan_from_bytes_with_limits(&bytes, &ctx, &limits).unwrap_err();
+        assert!(
+            err.to_string().contains("decompresses into more than"),
+            "{}",
+            err
+        );
+
+        let limits = DeserializeLimits::new().with_max_size(size);
+        logical_plan_from_bytes_with_limits(&bytes, &ctx, &limits)?;
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Error decoding expr as protobuf: failed to decode Protobuf message"
+    )]
+    fn bad_physical_plan_decode() {
+        let ctx = SessionContext::new();
+        physical_plan_from_bytes(b"Leet", &ctx).unwrap();
+    }
+
     #[test]
     fn udf_roundtrip_with_registry() {
         let ctx = context_with_udf();
@@ -322,34 +2446,68 @@ mod test {
     }
 
     #[test]
-    fn roundtrip_deeply_nested() {
-        // we need more stack space so this doesn't overflow in dev builds
-        std::thread::Builder::new().stack_size(10_000_000).spawn(|| {
-            // don't know what "too much" is, so let's slowly try to increase complexity
-            let n_max = 100;
+    #[cfg(feature = "wasm")]
+    fn wasm_udf_roundtrip_without_registry() {
+        use datafusion::physical_plan::wasm_udf::create_wasm_udf;
+        use datafusion::test_util::WASM_ADD_MODULE;
+        use datafusion_common::ScalarValue;
+        use datafusion_expr::{ColumnarValue, WasmUdfBody};
+
+        let body = WasmUdfBody {
+            module: WASM_ADD_MODULE.to_vec(),
+            export: "add".to_string(),
+        };
+        let udf = create_wasm_udf("my_add", body.clone()).unwrap();
+        let expr = udf.call(vec![col("a"), lit(1i64)]);
+
+        let bytes = expr.to_bytes().unwrap();
+        let deserialized_expr = Expr::from_bytes(&bytes).unwrap();
+        assert_eq!(expr, deserialized_expr);
 
-            for n in 1..n_max {
-                println!("testing: {n}");
+        let fun = match deserialized_expr {
+            Expr::ScalarUDF { fun, .. } => fun,
+            _ => panic!("expected a UDF"),
+        };
+        assert_eq!(fun.wasm_body.as_deref(), Some(&body));
+        let result = (fun.fun)(&[
+            ColumnarValue::Scalar(ScalarValue::Int64(Some(41))),
+            ColumnarValue::Scalar(ScalarValue::Int64(Some(1))),
+        ])
+        .unwrap();
+        assert!(matches!(
+            result,
+            ColumnarValue::Scalar(ScalarValue::Int64(Some(42)))
+        ));
+    }
 
+    #[test]
+    fn roundtrip_deeply_nested() {
+        // comparing and dropping the expressions recurses as deep as them
+        std::thread::Builder::new()
+            .stack_size(64 * 1024 * 1024)
+            .spawn(|| {
                 let expr_base = col("a").lt(lit(5i32));
-                let expr = (0..n).fold(expr_base.clone(), |expr, _| expr.and(expr_base.clone()));
-
-                // Convert it to an opaque form
-                let bytes = match expr.to_bytes() {
-                    Ok(bytes) => bytes,
-                    Err(_) => {
-                        // found expression that is too deeply nested
-                        return;
-                    }
-                };
+                let expr = (0..10_000)
+                    .fold(expr_base.clone(), |expr, _| expr.and(expr_base.clone()));
 
-                // Decode bytes from somewhere (over network, etc.
-                let decoded_expr = Expr::from_bytes(&bytes).expect("serialization worked, so deserialization should work as well");
+                let bytes = expr.to_bytes().unwrap();
+                let decoded_expr = Expr::from_bytes(&bytes).unwrap();
                 assert_eq!(expr, decoded_expr);
-            }
 
-            panic!("did not find a 'too deeply nested' expression, tested up to a depth of {n_max}")
-        }).expect("spawning thread").join().expect("joining thread");
+                // the chains of different operators are kept apart
+                let expr = (0..20).fold(expr_base.clone(), |expr, i| {
+                    if i % 2 == 0 {
+                        expr.and(expr_base.clone())
+                    } else {
+                        expr_base.clone().or(expr)
+                    }
+                });
+                let bytes = expr.to_bytes().unwrap();
+                assert_eq!(expr, Expr::from_bytes(&bytes).unwrap());
+            })
+            .expect("spawning thread")
+            .join()
+            .expect("joining thread");
     }
 
     /// return a `SessionContext` with a `dummy` function registered as a UDF
@@ -0,0 +1,336 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Adapters encoding and decoding protobuf messages through a small buffer
+//! instead of the whole encoded message
+//!
+//! The protobuf encoder and decoder can not be suspended, so the messages
+//! written to an [`AsyncWrite`] or read from an [`AsyncRead`] are encoded
+//! and decoded on a blocking thread, which exchanges buffers with the async
+//! task over a channel.
+
+use datafusion_common::{DataFusionError, Result};
+use prost::bytes::buf::UninitSlice;
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::decode_varint;
+use prost::Message;
+use std::io::{self, ErrorKind, Read, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::{self, JoinHandle};
+
+use super::limits::decode_error;
+
+/// The size of the buffers of [`WriterBuf`] and [`ReaderBuf`]
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// The number of buffers in flight between an async task and the blocking
+/// thread encoding or decoding its message
+const CHANNEL_CAPACITY: usize = 2;
+
+/// A [`BufMut`] writing the bytes put into it to a [`Write`]
+///
+/// `BufMut` can not fail, so the first error of the writer is kept and
+/// returned by [`WriterBuf::finish`].
+pub(super) struct WriterBuf<'a, W: Write> {
+    writer: &'a mut W,
+    buffer: Box<[u8]>,
+    len: usize,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: Write> WriterBuf<'a, W> {
+    pub(super) fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            len: 0,
+            error: None,
+        }
+    }
+
+    fn flush_buffer(&mut self) {
+        if self.error.is_none() {
+            if let Err(e) = self.writer.write_all(&self.buffer[..self.len]) {
+                self.error = Some(e);
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Writes the buffered bytes, returning the first error of the writer
+    pub(super) fn finish(mut self) -> io::Result<()> {
+        self.flush_buffer();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+unsafe impl<W: Write> BufMut for WriterBuf<'_, W> {
+    fn remaining_mut(&self) -> usize {
+        isize::MAX as usize
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(self.len + cnt <= self.buffer.len());
+        self.len += cnt;
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        if self.len == self.buffer.len() {
+            self.flush_buffer();
+        }
+        let chunk = &mut self.buffer[self.len..];
+        // SAFETY: the slice is initialized, and valid for the lifetime of self
+        unsafe { UninitSlice::from_raw_parts_mut(chunk.as_mut_ptr(), chunk.len()) }
+    }
+}
+
+/// A [`Buf`] of the next `len` bytes of a [`Read`]
+///
+/// `Buf` can not fail, so when the reader fails, or ends before `len`
+/// bytes, the error is kept and returned by [`ReaderBuf::finish`], and the
+/// buffer ends after the bytes read so far.
+pub(super) struct ReaderBuf<'a, R: Read> {
+    reader: &'a mut R,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    /// The number of bytes not read from the reader yet
+    unread: u64,
+    error: Option<io::Error>,
+}
+
+impl<'a, R: Read> ReaderBuf<'a, R> {
+    pub(super) fn new(reader: &'a mut R, len: u64) -> Self {
+        let mut buf = Self {
+            reader,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            unread: len,
+            error: None,
+        };
+        buf.fill();
+        buf
+    }
+
+    /// Reads the next bytes into the buffer, which must have been consumed
+    fn fill(&mut self) {
+        self.start = 0;
+        self.end = 0;
+        while self.end == 0 && self.unread > 0 {
+            let max = self.buffer.len().min(self.unread as usize);
+            match self.reader.read(&mut self.buffer[..max]) {
+                Ok(0) => {
+                    self.error = Some(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!("{} bytes missing at the end of the stream", self.unread),
+                    ));
+                    self.unread = 0;
+                }
+                Ok(n) => {
+                    self.end = n;
+                    self.unread -= n as u64;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.error = Some(e);
+                    self.unread = 0;
+                }
+            }
+        }
+    }
+
+    /// Returns the first error of the reader
+    pub(super) fn finish(self) -> io::Result<()> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R: Read> Buf for ReaderBuf<'_, R> {
+    fn remaining(&self) -> usize {
+        (self.end - self.start).saturating_add(self.unread as usize)
+    }
+
+    fn chunk(&self) -> &[u8] {
+        &self.buffer[self.start..self.end]
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let n = cnt.min(self.end - self.start);
+            self.start += n;
+            cnt -= n;
+            if self.start == self.end {
+                self.fill();
+                if self.end == 0 {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// A [`Write`] sending the bytes written to it to an async task
+struct ChannelWriter {
+    sender: Sender<Vec<u8>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender.blocking_send(buf.to_vec()).map_err(|_| {
+            io::Error::new(ErrorKind::BrokenPipe, "the async writer was dropped")
+        })?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A [`Read`] of the bytes sent by an async task, which ends when the task
+/// drops its sender
+struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    start: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.start == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.start = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.start);
+        buf[..n].copy_from_slice(&self.chunk[self.start..self.start + n]);
+        self.start += n;
+        Ok(n)
+    }
+}
+
+/// Encodes `message` to `writer`, [`BUFFER_SIZE`] bytes at a time
+///
+/// Must be called within a Tokio runtime, whose blocking threads encode the
+/// message.
+pub(super) async fn encode_to_async_writer<M, W>(message: M, writer: &mut W) -> Result<()>
+where
+    M: Message + Send + 'static,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let (sender, mut receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    let handle = task::spawn_blocking(move || {
+        let mut writer = ChannelWriter { sender };
+        let mut buf = WriterBuf::new(&mut writer);
+        message.encode(&mut buf).map_err(|e| {
+            DataFusionError::Plan(format!("Error encoding protobuf as bytes: {}", e))
+        })?;
+        Ok(buf.finish()?)
+    });
+    // returning early drops the receiver, which stops the encoder
+    while let Some(chunk) = receiver.recv().await {
+        writer.write_all(&chunk).await?;
+    }
+    join(handle).await?;
+    Ok(writer.flush().await?)
+}
+
+/// Decodes the message `what`, such as `"expr"`, of `len` bytes, from its
+/// first bytes `head` already read and the next bytes of `reader`,
+/// [`BUFFER_SIZE`] bytes at a time
+///
+/// Must be called within a Tokio runtime, whose blocking threads decode the
+/// message.
+pub(super) async fn decode_from_async_reader<M, R>(
+    reader: &mut R,
+    head: Vec<u8>,
+    len: u64,
+    what: &'static str,
+) -> Result<M>
+where
+    M: Message + Default + Send + 'static,
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    let mut unread = len - head.len() as u64;
+    if !head.is_empty() {
+        // the channel is empty, so this does not wait
+        let _ = sender.send(head).await;
+    }
+    let handle = task::spawn_blocking(move || {
+        let mut reader = ChannelReader {
+            receiver,
+            chunk: vec![],
+            start: 0,
+        };
+        let mut buf = ReaderBuf::new(&mut reader, len);
+        let message = M::decode(&mut buf);
+        buf.finish()?;
+        message.map_err(|e| decode_error(what, e))
+    });
+    while unread > 0 {
+        let mut chunk = vec![0; unread.min(BUFFER_SIZE as u64) as usize];
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            // the decoder reports the missing bytes
+            break;
+        }
+        chunk.truncate(n);
+        unread -= n as u64;
+        if sender.send(chunk).await.is_err() {
+            // the decoder failed before the end of the message
+            break;
+        }
+    }
+    drop(sender);
+    join(handle).await
+}
+
+/// Reads the varint length prefix of a length delimited message, as
+/// written by [`Message::encode_length_delimited`]
+pub(super) async fn read_length_prefix<R>(reader: &mut R, what: &str) -> Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let mut prefix = Vec::with_capacity(10);
+    loop {
+        let byte = reader.read_u8().await?;
+        prefix.push(byte);
+        if byte < 0x80 || prefix.len() == 10 {
+            break;
+        }
+    }
+    decode_varint(&mut prefix.as_slice()).map_err(|e| decode_error(what, e))
+}
+
+async fn join<T>(handle: JoinHandle<Result<T>>) -> Result<T> {
+    handle.await.map_err(|e| {
+        DataFusionError::Internal(format!("Error joining the protobuf codec task: {}", e))
+    })?
+}
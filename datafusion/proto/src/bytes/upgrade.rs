@@ -0,0 +1,218 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Upgrade of the logical plans serialized by previous releases of
+//! DataFusion to the current version of the plan format
+//!
+//! A version of the format which renumbers fields of the protobuf messages
+//! lists them in [`MIGRATIONS`]. The upgrade walks the encoded messages of
+//! a plan, with the descriptors of the messages generated from
+//! `datafusion.proto`, and rewrites the numbers of the renumbered fields,
+//! copying everything else unchanged. Renamed fields keep their numbers, so
+//! they need no migration.
+
+use datafusion_common::{DataFusionError, Result};
+use prost::bytes::Bytes;
+use prost::encoding::{decode_key, decode_varint, encode_key, encode_varint, WireType};
+
+use super::descriptor::MESSAGE_FIELDS;
+use super::limits::PROTOBUF_RECURSION_LIMIT;
+use super::{
+    compression, LOGICAL_PLAN_HEADER_LEN, LOGICAL_PLAN_MAGIC, LOGICAL_PLAN_WIRE_VERSION,
+};
+
+/// A field of a message renumbered by a version of the plan format
+struct Renumbered {
+    /// The fully qualified name of the message
+    message: &'static str,
+    /// The number of the field in the previous version
+    from: u32,
+    /// The number of the field in this version
+    to: u32,
+}
+
+/// The fields renumbered by each version of the plan format, from the
+/// previous version
+const MIGRATIONS: &[(u32, &[Renumbered])] = &[(
+    2,
+    // `BinaryExprNode { l = 1, r = 2 }` became `repeated operands = 1`,
+    // with the two operands of version 1 in order
+    &[Renumbered {
+        message: ".datafusion.BinaryExprNode",
+        from: 2,
+        to: 1,
+    }],
+)];
+
+/// Upgrade a LogicalPlan serialized by a previous release of DataFusion to
+/// the current version of the plan format, such that
+/// [`logical_plan_from_bytes`](super::logical_plan_from_bytes) can read it
+///
+/// Plans of the current version are returned unchanged, and compressed
+/// plans are returned uncompressed. Only plans encoded as protobuf, such as
+/// by [`ProstCodec`](super::ProstCodec), can be upgraded.
+///
+/// ```
+/// use datafusion_expr::{lit, LogicalPlanBuilder};
+/// use datafusion_proto::bytes::{logical_plan_to_bytes, upgrade_plan_bytes};
+///
+/// let plan = LogicalPlanBuilder::empty(true)
+///     .project(vec![lit(1) + lit(2)])
+///     .unwrap()
+///     .build()
+///     .unwrap();
+/// let bytes = logical_plan_to_bytes(&plan).unwrap();
+/// assert_eq!(upgrade_plan_bytes(&bytes).unwrap(), bytes);
+/// ```
+pub fn upgrade_plan_bytes(old: &[u8]) -> Result<Bytes> {
+    let bytes = compression::decompress(old)?;
+    if bytes.len() < LOGICAL_PLAN_HEADER_LEN || !bytes.starts_with(LOGICAL_PLAN_MAGIC) {
+        return Err(DataFusionError::Plan(
+            "Error upgrading logical plan: bytes are not a serialized DataFusion logical plan"
+                .to_string(),
+        ));
+    }
+    let mut version = [0; 4];
+    version.copy_from_slice(&bytes[4..8]);
+    let version = u32::from_le_bytes(version);
+    if version == LOGICAL_PLAN_WIRE_VERSION {
+        return Ok(Bytes::copy_from_slice(&bytes));
+    }
+    if version == 0 || version > LOGICAL_PLAN_WIRE_VERSION {
+        return Err(DataFusionError::Plan(format!(
+            "Error upgrading logical plan: unknown version {} of the plan format, \
+             the current version is {}",
+            version, LOGICAL_PLAN_WIRE_VERSION
+        )));
+    }
+    let mut len = [0; 8];
+    len.copy_from_slice(&bytes[8..LOGICAL_PLAN_HEADER_LEN]);
+    let plan = &bytes[LOGICAL_PLAN_HEADER_LEN..];
+    if plan.len() as u64 != u64::from_le_bytes(len) {
+        return Err(DataFusionError::Plan(format!(
+            "Error upgrading logical plan: expected {} bytes after the header, found {}",
+            u64::from_le_bytes(len),
+            plan.len()
+        )));
+    }
+
+    let migrations = MIGRATIONS
+        .iter()
+        .filter(|(to_version, _)| *to_version > version)
+        .flat_map(|(_, fields)| fields.iter())
+        .collect::<Vec<_>>();
+    let upgrader = Upgrader { migrations };
+    let mut upgraded = vec![];
+    upgrader.message(".datafusion.LogicalPlanNode", plan, 0, &mut upgraded)?;
+
+    let mut buffer = Vec::with_capacity(LOGICAL_PLAN_HEADER_LEN + upgraded.len());
+    buffer.extend_from_slice(LOGICAL_PLAN_MAGIC);
+    buffer.extend_from_slice(&LOGICAL_PLAN_WIRE_VERSION.to_le_bytes());
+    buffer.extend_from_slice(&(upgraded.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(&upgraded);
+    Ok(buffer.into())
+}
+
+/// Rewrites the encoded messages of a plan with the renumbered fields
+struct Upgrader<'a> {
+    /// The fields renumbered since the version of the plan, in order
+    migrations: Vec<&'a Renumbered>,
+}
+
+impl Upgrader<'_> {
+    /// Appends the upgraded encoding of the message `name` encoded as
+    /// `bytes` to `out`
+    fn message(
+        &self,
+        name: &str,
+        mut bytes: &[u8],
+        depth: usize,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        if depth > PROTOBUF_RECURSION_LIMIT {
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "Error upgrading logical plan: messages nested deeper than the limit of {} levels",
+                PROTOBUF_RECURSION_LIMIT
+            )));
+        }
+        let fields = MESSAGE_FIELDS.get(name);
+        while !bytes.is_empty() {
+            let (mut tag, wire_type) = decode_key(&mut bytes).map_err(upgrade_error)?;
+            for migration in &self.migrations {
+                if migration.message == name && migration.from == tag {
+                    tag = migration.to;
+                }
+            }
+            encode_key(tag, wire_type, out);
+            match wire_type {
+                WireType::Varint => {
+                    encode_varint(decode_varint(&mut bytes).map_err(upgrade_error)?, out)
+                }
+                WireType::SixtyFourBit => copy_bytes(&mut bytes, 8, out)?,
+                WireType::ThirtyTwoBit => copy_bytes(&mut bytes, 4, out)?,
+                WireType::LengthDelimited => {
+                    let len = decode_varint(&mut bytes).map_err(upgrade_error)? as usize;
+                    if len > bytes.len() {
+                        return Err(truncated());
+                    }
+                    let (value, rest) = bytes.split_at(len);
+                    bytes = rest;
+                    match fields.and_then(|fields| fields.get(&tag)) {
+                        Some(field_message) => {
+                            let mut upgraded = vec![];
+                            self.message(field_message, value, depth + 1, &mut upgraded)?;
+                            encode_varint(upgraded.len() as u64, out);
+                            out.extend_from_slice(&upgraded);
+                        }
+                        // strings, bytes and packed scalars
+                        None => {
+                            encode_varint(len as u64, out);
+                            out.extend_from_slice(value);
+                        }
+                    }
+                }
+                WireType::StartGroup | WireType::EndGroup => {
+                    return Err(DataFusionError::Plan(
+                        "Error upgrading logical plan: unexpected protobuf group"
+                            .to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn copy_bytes(bytes: &mut &[u8], len: usize, out: &mut Vec<u8>) -> Result<()> {
+    if len > bytes.len() {
+        return Err(truncated());
+    }
+    let (value, rest) = bytes.split_at(len);
+    out.extend_from_slice(value);
+    *bytes = rest;
+    Ok(())
+}
+
+fn truncated() -> DataFusionError {
+    DataFusionError::Plan(
+        "Error upgrading logical plan: truncated protobuf message".to_string(),
+    )
+}
+
+fn upgrade_error(e: prost::DecodeError) -> DataFusionError {
+    DataFusionError::Plan(format!("Error upgrading logical plan: {}", e))
+}
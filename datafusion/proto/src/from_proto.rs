@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::logical_plan::{AsLogicalPlan, LogicalExtensionCodec};
 use crate::protobuf::plan_type::PlanTypeEnum::{
     FinalLogicalPlan, FinalPhysicalPlan, InitialLogicalPlan, InitialPhysicalPlan,
     OptimizedLogicalPlan, OptimizedPhysicalPlan,
@@ -27,25 +28,29 @@ use crate::protobuf::{
 use arrow::datatypes::{
     DataType, Field, IntervalMonthDayNanoType, IntervalUnit, Schema, TimeUnit, UnionMode,
 };
+use datafusion::config::ConfigOptions;
+use datafusion::execution::context::{SessionConfig, SessionContext};
 use datafusion::execution::registry::FunctionRegistry;
 use datafusion_common::{
-    Column, DFField, DFSchema, DFSchemaRef, DataFusionError, ScalarValue,
+    Column, Constraint, DFField, DFSchema, DFSchemaRef, DataFusionError, ScalarValue,
 };
 use datafusion_expr::expr::{BinaryExpr, Cast};
+use datafusion_expr::type_coercion::functions::data_types;
 use datafusion_expr::{
     abs, acos, array, ascii, asin, atan, atan2, bit_length, btrim, ceil,
-    character_length, chr, coalesce, concat_expr, concat_ws_expr, cos, date_bin,
-    date_part, date_trunc, digest, exp, floor, from_unixtime, left, ln, log10, log2,
-    logical_plan::{PlanType, StringifiedPlan},
+    character_length, chr, coalesce, concat_expr, concat_ws_expr, cos, current_date,
+    current_time, date_bin, date_part, date_trunc, digest, exp, floor, from_unixtime,
+    left, ln, log10, log2,
+    logical_plan::{PlanType, StringifiedPlan, Subquery},
     lower, lpad, ltrim, md5, now, nullif, octet_length, power, random, regexp_match,
     regexp_replace, repeat, replace, reverse, right, round, rpad, rtrim, sha224, sha256,
     sha384, sha512, signum, sin, split_part, sqrt, starts_with, strpos, substr,
     substring, tan, to_hex, to_timestamp_micros, to_timestamp_millis,
-    to_timestamp_seconds, translate, trim, trunc, upper, AggregateFunction, Between,
-    BuiltInWindowFunction, BuiltinScalarFunction, Case, Expr, GetIndexedField,
-    GroupingSet,
+    to_timestamp_seconds, translate, trim, trunc, upper, zorder, AggregateFunction,
+    AggregateUDF, Between, BuiltInWindowFunction, BuiltinScalarFunction, Case, Expr,
+    GetIndexedField, GroupingSet,
     GroupingSet::GroupingSets,
-    Like, Operator, WindowFrame, WindowFrameBound, WindowFrameUnits,
+    Like, Operator, ScalarUDF, WindowFrame, WindowFrameBound, WindowFrameUnits,
 };
 use std::sync::Arc;
 
@@ -154,6 +159,38 @@ impl From<&protobuf::Column> for Column {
     }
 }
 
+impl TryFrom<&protobuf::Constraint> for Constraint {
+    type Error = Error;
+
+    fn try_from(constraint: &protobuf::Constraint) -> Result<Self, Self::Error> {
+        use protobuf::constraint::ConstraintType;
+
+        let indices =
+            |columns: &[u64]| columns.iter().map(|i| *i as usize).collect::<Vec<_>>();
+        match constraint
+            .constraint_type
+            .as_ref()
+            .ok_or_else(|| Error::required("constraint_type"))?
+        {
+            ConstraintType::PrimaryKey(pk) => {
+                Ok(Constraint::PrimaryKey(indices(&pk.columns)))
+            }
+            ConstraintType::Unique(unique) => {
+                Ok(Constraint::Unique(indices(&unique.columns)))
+            }
+            ConstraintType::ForeignKey(fk) => Ok(Constraint::ForeignKey {
+                columns: indices(&fk.columns),
+                referenced_table: fk.referenced_table.clone(),
+                referenced_columns: fk.referenced_columns.clone(),
+            }),
+            ConstraintType::Check(check) => Ok(Constraint::Check {
+                name: Some(check.name.clone()).filter(|name| !name.is_empty()),
+                expr: check.expr.clone(),
+            }),
+        }
+    }
+}
+
 impl TryFrom<&protobuf::DfSchema> for DFSchema {
     type Error = Error;
 
@@ -165,7 +202,7 @@ impl TryFrom<&protobuf::DfSchema> for DFSchema {
             .collect::<Result<Vec<DFField>, _>>()?;
         Ok(DFSchema::new_with_metadata(
             fields,
-            df_schema.metadata.clone(),
+            df_schema.metadata.clone().into_iter().collect(),
         )?)
     }
 }
@@ -429,6 +466,8 @@ impl From<&protobuf::ScalarFunction> for BuiltinScalarFunction {
             ScalarFunction::ToTimestampMicros => Self::ToTimestampMicros,
             ScalarFunction::ToTimestampSeconds => Self::ToTimestampSeconds,
             ScalarFunction::Now => Self::Now,
+            ScalarFunction::CurrentDate => Self::CurrentDate,
+            ScalarFunction::CurrentTime => Self::CurrentTime,
             ScalarFunction::Translate => Self::Translate,
             ScalarFunction::RegexpMatch => Self::RegexpMatch,
             ScalarFunction::Coalesce => Self::Coalesce,
@@ -437,6 +476,7 @@ impl From<&protobuf::ScalarFunction> for BuiltinScalarFunction {
             ScalarFunction::FromUnixtime => Self::FromUnixtime,
             ScalarFunction::Atan2 => Self::Atan2,
             ScalarFunction::ArrowTypeof => Self::ArrowTypeof,
+            ScalarFunction::ZOrder => Self::ZOrder,
         }
     }
 }
@@ -658,6 +698,46 @@ impl TryFrom<&protobuf::ScalarValue> for ScalarValue {
     }
 }
 
+impl TryFrom<&protobuf::ConfigOptions> for ConfigOptions {
+    type Error = Error;
+
+    fn try_from(options: &protobuf::ConfigOptions) -> Result<Self, Self::Error> {
+        let mut config_options = ConfigOptions::new();
+        for (key, value) in &options.options {
+            config_options.set(key, value.try_into()?);
+        }
+        Ok(config_options)
+    }
+}
+
+impl TryFrom<&protobuf::SessionConfig> for SessionConfig {
+    type Error = Error;
+
+    fn try_from(config: &protobuf::SessionConfig) -> Result<Self, Self::Error> {
+        let config_options: ConfigOptions =
+            config.config_options.as_ref().required("config_options")?;
+        let mut session_config = SessionConfig::new()
+            .with_default_catalog_and_schema(
+                &config.default_catalog,
+                &config.default_schema,
+            )
+            .create_default_catalog_and_schema(config.create_default_catalog_and_schema)
+            .with_information_schema(config.information_schema)
+            .with_repartition_joins(config.repartition_joins)
+            .with_repartition_aggregations(config.repartition_aggregations)
+            .with_repartition_windows(config.repartition_windows)
+            .with_parquet_pruning(config.parquet_pruning)
+            .with_collect_statistics(config.collect_statistics);
+        // 0 keeps the target partitions of the process deserializing it
+        if config.target_partitions > 0 {
+            session_config =
+                session_config.with_target_partitions(config.target_partitions as usize);
+        }
+        session_config.config_options = config_options.into_shareable();
+        Ok(session_config)
+    }
+}
+
 /// Ensures that all `values` are of type DataType::List and have the
 /// same type as field
 fn validate_list_values(field: &Field, values: &[ScalarValue]) -> Result<(), Error> {
@@ -675,9 +755,74 @@ fn validate_list_values(field: &Field, values: &[ScalarValue]) -> Result<(), Err
     Ok(())
 }
 
-pub fn parse_expr(
+/// The registry of the functions called by the parsed expressions: any
+/// [`FunctionRegistry`], or the context of [`parse_expr_with_subqueries`]
+/// which also parses the plans of their subqueries
+pub trait ExprRegistry {
+    /// Returns the scalar UDF `name`
+    fn udf(&self, name: &str) -> datafusion_common::Result<Arc<ScalarUDF>>;
+
+    /// Returns the aggregate UDF `name`
+    fn udaf(&self, name: &str) -> datafusion_common::Result<Arc<AggregateUDF>>;
+
+    /// The session context and extension codec parsing the plans of the
+    /// subqueries, or `None` if the expressions can not contain subqueries
+    fn subqueries(&self) -> Option<(&SessionContext, &dyn LogicalExtensionCodec)> {
+        None
+    }
+}
+
+impl<T: FunctionRegistry + ?Sized> ExprRegistry for T {
+    fn udf(&self, name: &str) -> datafusion_common::Result<Arc<ScalarUDF>> {
+        FunctionRegistry::udf(self, name)
+    }
+
+    fn udaf(&self, name: &str) -> datafusion_common::Result<Arc<AggregateUDF>> {
+        FunctionRegistry::udaf(self, name)
+    }
+}
+
+/// The context of [`parse_expr_with_subqueries`]
+struct SubqueryContext<'a> {
+    ctx: &'a SessionContext,
+    extension_codec: &'a dyn LogicalExtensionCodec,
+}
+
+impl ExprRegistry for SubqueryContext<'_> {
+    fn udf(&self, name: &str) -> datafusion_common::Result<Arc<ScalarUDF>> {
+        FunctionRegistry::udf(self.ctx, name)
+    }
+
+    fn udaf(&self, name: &str) -> datafusion_common::Result<Arc<AggregateUDF>> {
+        FunctionRegistry::udaf(self.ctx, name)
+    }
+
+    fn subqueries(&self) -> Option<(&SessionContext, &dyn LogicalExtensionCodec)> {
+        Some((self.ctx, self.extension_codec))
+    }
+}
+
+/// Parse an expression whose subqueries are planned in `ctx`, with the
+/// extension nodes of their plans decoded by `extension_codec`
+pub fn parse_expr_with_subqueries(
+    proto: &protobuf::LogicalExprNode,
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Expr, Error> {
+    parse_expr(
+        proto,
+        &SubqueryContext {
+            ctx,
+            extension_codec,
+        },
+    )
+}
+
+/// Parse an expression calling the functions of `registry`. Expressions
+/// containing subqueries must be parsed with [`parse_expr_with_subqueries`].
+pub fn parse_expr<R: ExprRegistry + ?Sized>(
     proto: &protobuf::LogicalExprNode,
-    registry: &dyn FunctionRegistry,
+    registry: &R,
 ) -> Result<Expr, Error> {
     use protobuf::{logical_expr_node::ExprType, window_expr_node, ScalarFunction};
 
@@ -687,11 +832,26 @@ pub fn parse_expr(
         .ok_or_else(|| Error::required("expr_type"))?;
 
     match expr_type {
-        ExprType::BinaryExpr(binary_expr) => Ok(Expr::BinaryExpr(BinaryExpr::new(
-            Box::new(parse_required_expr(&binary_expr.l, registry, "l")?),
-            from_proto_binary_op(&binary_expr.op)?,
-            Box::new(parse_required_expr(&binary_expr.r, registry, "r")?),
-        ))),
+        ExprType::BinaryExpr(binary_expr) => {
+            let op = from_proto_binary_op(&binary_expr.op)?;
+            let operands = binary_expr
+                .operands
+                .iter()
+                .map(|operand| parse_expr(operand, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+            if operands.len() < 2 {
+                return Err(proto_error(
+                    "A binary expression must have at least 2 operands",
+                ));
+            }
+            // the chain is rebuilt from its innermost left operand
+            Ok(operands
+                .into_iter()
+                .reduce(|left, right| {
+                    Expr::BinaryExpr(BinaryExpr::new(Box::new(left), op, Box::new(right)))
+                })
+                .expect("at least 2 operands"))
+        }
         ExprType::GetIndexedField(field) => {
             let key = field
                 .key
@@ -742,6 +902,10 @@ pub fn parse_expr(
                     }
                 })
                 .transpose()?;
+            let mut args = vec![parse_required_expr(&expr.expr, registry, "expr")?];
+            for arg in &expr.extra_args {
+                args.push(parse_expr(arg, registry)?);
+            }
 
             match window_function {
                 window_expr_node::WindowFunction::AggrFunction(i) => {
@@ -751,10 +915,11 @@ pub fn parse_expr(
                         fun: datafusion_expr::window_function::WindowFunction::AggregateFunction(
                             aggr_function,
                         ),
-                        args: vec![parse_required_expr(&expr.expr, registry, "expr")?],
+                        args,
                         partition_by,
                         order_by,
                         window_frame,
+                        ignore_nulls: expr.ignore_nulls,
                     })
                 }
                 window_expr_node::WindowFunction::BuiltInFunction(i) => {
@@ -766,10 +931,11 @@ pub fn parse_expr(
                         fun: datafusion_expr::window_function::WindowFunction::BuiltInWindowFunction(
                             built_in_function,
                         ),
-                        args: vec![parse_required_expr(&expr.expr, registry, "expr")?],
+                        args,
                         partition_by,
                         order_by,
                         window_frame,
+                        ignore_nulls: expr.ignore_nulls,
                     })
                 }
             }
@@ -891,6 +1057,22 @@ pub fn parse_expr(
             negated: in_list.negated,
         }),
         ExprType::Wildcard(_) => Ok(Expr::Wildcard),
+        ExprType::Exists(exists) => Ok(Expr::Exists {
+            subquery: parse_required_subquery(&exists.subquery, registry)?,
+            negated: exists.negated,
+        }),
+        ExprType::InSubquery(in_subquery) => Ok(Expr::InSubquery {
+            expr: Box::new(parse_required_expr(&in_subquery.expr, registry, "expr")?),
+            subquery: parse_required_subquery(&in_subquery.subquery, registry)?,
+            negated: in_subquery.negated,
+        }),
+        ExprType::ScalarSubquery(scalar_subquery) => Ok(Expr::ScalarSubquery(
+            parse_required_subquery(&scalar_subquery.subquery, registry)?,
+        )),
+        ExprType::Placeholder(placeholder) => Ok(Expr::Placeholder {
+            id: placeholder.id.clone(),
+            data_type: placeholder.data_type.as_ref().required("data_type")?,
+        }),
         ExprType::ScalarFunction(expr) => {
             let scalar_function = protobuf::ScalarFunction::from_i32(expr.fun)
                 .ok_or_else(|| Error::unknown("ScalarFunction", expr.fun))?;
@@ -1063,6 +1245,8 @@ pub fn parse_expr(
                     Ok(to_timestamp_seconds(parse_expr(&args[0], registry)?))
                 }
                 ScalarFunction::Now => Ok(now()),
+                ScalarFunction::CurrentDate => Ok(current_date()),
+                ScalarFunction::CurrentTime => Ok(current_time()),
                 ScalarFunction::Translate => Ok(translate(
                     parse_expr(&args[0], registry)?,
                     parse_expr(&args[1], registry)?,
@@ -1085,13 +1269,25 @@ pub fn parse_expr(
                     parse_expr(&args[0], registry)?,
                     parse_expr(&args[1], registry)?,
                 )),
+                ScalarFunction::ZOrder => Ok(zorder(
+                    args.iter()
+                        .map(|expr| parse_expr(expr, registry))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )),
                 _ => Err(proto_error(
                     "Protobuf deserialization error: Unsupported scalar function",
                 )),
             }
         }
-        ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode { fun_name, args }) => {
-            let scalar_fn = registry.udf(fun_name.as_str())?;
+        ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
+            fun_name,
+            args,
+            wasm_body,
+        }) => {
+            let scalar_fn = match wasm_body {
+                Some(body) => parse_wasm_udf(fun_name, body, registry)?,
+                None => registry.udf(fun_name.as_str())?,
+            };
             Ok(Expr::ScalarUDF {
                 fun: scalar_fn,
                 args: args
@@ -1102,6 +1298,9 @@ pub fn parse_expr(
         }
         ExprType::AggregateUdfExpr(pb) => {
             let agg_fn = registry.udaf(pb.fun_name.as_str())?;
+            if let Some(signature) = &pb.signature {
+                validate_aggregate_udf(&agg_fn, signature)?;
+            }
 
             Ok(Expr::AggregateUDF {
                 fun: agg_fn,
@@ -1285,9 +1484,9 @@ pub fn from_proto_binary_op(op: &str) -> Result<Operator, Error> {
     }
 }
 
-fn parse_optional_expr(
+fn parse_optional_expr<R: ExprRegistry + ?Sized>(
     p: &Option<Box<protobuf::LogicalExprNode>>,
-    registry: &dyn FunctionRegistry,
+    registry: &R,
 ) -> Result<Option<Expr>, Error> {
     match p {
         Some(expr) => parse_expr(expr.as_ref(), registry).map(Some),
@@ -1295,9 +1494,9 @@ fn parse_optional_expr(
     }
 }
 
-fn parse_required_expr(
+fn parse_required_expr<R: ExprRegistry + ?Sized>(
     p: &Option<Box<protobuf::LogicalExprNode>>,
-    registry: &dyn FunctionRegistry,
+    registry: &R,
     field: impl Into<String>,
 ) -> Result<Expr, Error> {
     match p {
@@ -1306,9 +1505,9 @@ fn parse_required_expr(
     }
 }
 
-fn parse_required_expr_inner(
+fn parse_required_expr_inner<R: ExprRegistry + ?Sized>(
     p: &Option<protobuf::LogicalExprNode>,
-    registry: &dyn FunctionRegistry,
+    registry: &R,
     field: impl Into<String>,
 ) -> Result<Expr, Error> {
     match p {
@@ -1317,6 +1516,101 @@ fn parse_required_expr_inner(
     }
 }
 
+fn parse_required_subquery<R: ExprRegistry + ?Sized>(
+    p: &Option<Box<protobuf::LogicalPlanNode>>,
+    registry: &R,
+) -> Result<Subquery, Error> {
+    let (ctx, extension_codec) = registry.subqueries().ok_or_else(|| {
+        proto_error("Subqueries can only be parsed with parse_expr_with_subqueries")
+    })?;
+    let plan = p
+        .as_ref()
+        .ok_or_else(|| Error::required("subquery"))?
+        .try_into_logical_plan(ctx, extension_codec)?;
+    Ok(Subquery {
+        subquery: Arc::new(plan),
+    })
+}
+
+/// Checks that the UDAF `fun` of the registry has the serialized types
+/// `signature`, rather than failing when executing the plan
+fn validate_aggregate_udf(
+    fun: &AggregateUDF,
+    signature: &protobuf::UdfSignature,
+) -> Result<(), Error> {
+    let arg_types = signature
+        .arg_types
+        .iter()
+        .map(DataType::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let return_type: DataType = signature.return_type.as_ref().required("return_type")?;
+    let state_types = signature
+        .state_types
+        .iter()
+        .map(DataType::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mismatch = |reason: String| {
+        Error::General(format!(
+            "Aggregate UDF '{}' of the registry does not match the serialized one: {}",
+            fun.name, reason
+        ))
+    };
+    data_types(&arg_types, &fun.signature).map_err(|_| {
+        mismatch(format!(
+            "it does not accept arguments of types {:?}",
+            arg_types
+        ))
+    })?;
+    let actual_return_type = (fun.return_type)(&arg_types)?;
+    if actual_return_type.as_ref() != &return_type {
+        return Err(mismatch(format!(
+            "it returns {:?} instead of {:?}",
+            actual_return_type, return_type
+        )));
+    }
+    let actual_state_types = (fun.state_type)(&actual_return_type)?;
+    if actual_state_types.as_ref() != &state_types {
+        return Err(mismatch(format!(
+            "its state has types {:?} instead of {:?}",
+            actual_state_types, state_types
+        )));
+    }
+    Ok(())
+}
+
+/// Rebuilds the UDF `name` from the WebAssembly module embedded in its
+/// serialized form
+#[cfg(feature = "wasm")]
+fn parse_wasm_udf<R: ExprRegistry + ?Sized>(
+    name: &str,
+    body: &protobuf::WasmUdfBody,
+    _registry: &R,
+) -> Result<Arc<ScalarUDF>, Error> {
+    let body = datafusion_expr::WasmUdfBody {
+        module: body.module.clone(),
+        export: body.export.clone(),
+    };
+    let udf = datafusion::physical_plan::wasm_udf::create_wasm_udf(name, body)?;
+    Ok(Arc::new(udf))
+}
+
+/// Without the wasm feature, the UDF `name` can only be found in `registry`
+#[cfg(not(feature = "wasm"))]
+fn parse_wasm_udf<R: ExprRegistry + ?Sized>(
+    name: &str,
+    _body: &protobuf::WasmUdfBody,
+    registry: &R,
+) -> Result<Arc<ScalarUDF>, Error> {
+    registry.udf(name).map_err(|_| {
+        proto_error(format!(
+            "UDF {} is implemented by a WebAssembly module, \
+             which requires the wasm feature",
+            name
+        ))
+    })
+}
+
 fn proto_error<S: Into<String>>(message: S) -> Error {
     Error::General(message.into())
 }
@@ -1,8 +1,10 @@
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ColumnRelation {
     #[prost(string, tag="1")]
     pub relation: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Column {
     #[prost(string, tag="1")]
@@ -10,6 +12,7 @@ pub struct Column {
     #[prost(message, optional, tag="2")]
     pub relation: ::core::option::Option<ColumnRelation>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DfField {
     #[prost(message, optional, tag="1")]
@@ -17,22 +20,25 @@ pub struct DfField {
     #[prost(message, optional, tag="2")]
     pub qualifier: ::core::option::Option<ColumnRelation>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DfSchema {
     #[prost(message, repeated, tag="1")]
     pub columns: ::prost::alloc::vec::Vec<DfField>,
-    #[prost(map="string, string", tag="2")]
-    pub metadata: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(btree_map="string, string", tag="2")]
+    pub metadata: ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
 }
 /// logical plan
 /// LogicalPlan is a nested type
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LogicalPlanNode {
-    #[prost(oneof="logical_plan_node::LogicalPlanType", tags="1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25")]
+    #[prost(oneof="logical_plan_node::LogicalPlanType", tags="1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35")]
     pub logical_plan_type: ::core::option::Option<logical_plan_node::LogicalPlanType>,
 }
 /// Nested message and enum types in `LogicalPlanNode`.
 pub mod logical_plan_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum LogicalPlanType {
         #[prost(message, tag="1")]
@@ -83,8 +89,29 @@ pub mod logical_plan_node {
         ViewScan(::prost::alloc::boxed::Box<super::ViewTableScanNode>),
         #[prost(message, tag="25")]
         CustomScan(super::CustomTableScanNode),
+        #[prost(message, tag="26")]
+        Unnest(::prost::alloc::boxed::Box<super::UnnestNode>),
+        #[prost(message, tag="27")]
+        Subquery(::prost::alloc::boxed::Box<super::SubqueryNode>),
+        #[prost(message, tag="28")]
+        Dedup(::prost::alloc::boxed::Box<super::DedupNode>),
+        #[prost(message, tag="29")]
+        Prepare(::prost::alloc::boxed::Box<super::PrepareNode>),
+        #[prost(message, tag="30")]
+        CreateMemoryTable(::prost::alloc::boxed::Box<super::CreateMemoryTableNode>),
+        #[prost(message, tag="31")]
+        DropTable(super::DropTableNode),
+        #[prost(message, tag="32")]
+        DropView(super::DropViewNode),
+        #[prost(message, tag="33")]
+        RefreshMaterializedView(super::RefreshMaterializedViewNode),
+        #[prost(message, tag="34")]
+        SetVariable(super::SetVariableNode),
+        #[prost(message, tag="35")]
+        AnalyzeTable(super::AnalyzeTableNode),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LogicalExtensionNode {
     #[prost(bytes="vec", tag="1")]
@@ -92,11 +119,13 @@ pub struct LogicalExtensionNode {
     #[prost(message, repeated, tag="2")]
     pub inputs: ::prost::alloc::vec::Vec<LogicalPlanNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ProjectionColumns {
     #[prost(string, repeated, tag="1")]
     pub columns: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CsvFormat {
     #[prost(bool, tag="1")]
@@ -104,14 +133,17 @@ pub struct CsvFormat {
     #[prost(string, tag="2")]
     pub delimiter: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ParquetFormat {
     #[prost(bool, tag="1")]
     pub enable_pruning: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AvroFormat {
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListingTableScanNode {
     #[prost(string, tag="1")]
@@ -132,11 +164,21 @@ pub struct ListingTableScanNode {
     pub collect_stat: bool,
     #[prost(uint32, tag="9")]
     pub target_partitions: u32,
+    #[prost(bool, tag="13")]
+    pub use_index: bool,
+    /// the statistics of the table, used by the scans of the deserialized table
+    /// instead of the statistics of its files
+    #[prost(message, optional, tag="14")]
+    pub statistics: ::core::option::Option<Statistics>,
+    /// the sort expressions the table returns the rows of the scan ordered by
+    #[prost(message, repeated, tag="15")]
+    pub ordering: ::prost::alloc::vec::Vec<LogicalExprNode>,
     #[prost(oneof="listing_table_scan_node::FileFormatType", tags="10, 11, 12")]
     pub file_format_type: ::core::option::Option<listing_table_scan_node::FileFormatType>,
 }
 /// Nested message and enum types in `ListingTableScanNode`.
 pub mod listing_table_scan_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum FileFormatType {
         #[prost(message, tag="10")]
@@ -147,6 +189,7 @@ pub mod listing_table_scan_node {
         Avro(super::AvroFormat),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ViewTableScanNode {
     #[prost(string, tag="1")]
@@ -161,6 +204,7 @@ pub struct ViewTableScanNode {
     pub definition: ::prost::alloc::string::String,
 }
 /// Logical Plan to Scan a CustomTableProvider registered at runtime
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CustomTableScanNode {
     #[prost(string, tag="1")]
@@ -173,7 +217,74 @@ pub struct CustomTableScanNode {
     pub filters: ::prost::alloc::vec::Vec<LogicalExprNode>,
     #[prost(bytes="vec", tag="5")]
     pub custom_table_data: ::prost::alloc::vec::Vec<u8>,
+    /// the statistics of the table, if its provider has any
+    #[prost(message, optional, tag="6")]
+    pub statistics: ::core::option::Option<Statistics>,
+    /// the sort expressions the table returns the rows of the scan ordered by
+    #[prost(message, repeated, tag="7")]
+    pub ordering: ::prost::alloc::vec::Vec<LogicalExprNode>,
+}
+/// The built-in table providers encoded by the DefaultLogicalExtensionCodec
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DefaultTableProviderNode {
+    #[prost(oneof="default_table_provider_node::Provider", tags="1, 2")]
+    pub provider: ::core::option::Option<default_table_provider_node::Provider>,
+}
+/// Nested message and enum types in `DefaultTableProviderNode`.
+pub mod default_table_provider_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Provider {
+        /// a scan of a ListingTable or ViewTable, without projection or filters
+        #[prost(message, tag="1")]
+        Scan(super::LogicalPlanNode),
+        #[prost(message, tag="2")]
+        MemTable(super::MemTableNode),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemTableNode {
+    #[prost(message, optional, tag="1")]
+    pub schema: ::core::option::Option<Schema>,
+    /// one Arrow IPC stream per partition
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub partitions: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, repeated, tag="3")]
+    pub constraints: ::prost::alloc::vec::Vec<Constraint>,
+}
+/// A logical plan with the data of the in-memory tables it scans
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogicalPlanBundle {
+    #[prost(message, optional, tag="1")]
+    pub plan: ::core::option::Option<LogicalPlanNode>,
+    /// each table is stored once, however many scans of the plan read it
+    #[prost(message, repeated, tag="2")]
+    pub tables: ::prost::alloc::vec::Vec<MemTableNode>,
+}
+/// The table provider of a custom scan of a LogicalPlanBundle
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BundleTableProviderNode {
+    #[prost(oneof="bundle_table_provider_node::Provider", tags="1, 2")]
+    pub provider: ::core::option::Option<bundle_table_provider_node::Provider>,
 }
+/// Nested message and enum types in `BundleTableProviderNode`.
+pub mod bundle_table_provider_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Provider {
+        /// the index of an in-memory table of the bundle
+        #[prost(uint32, tag="1")]
+        Table(u32),
+        /// a table provider encoded by the extension codec of the bundle
+        #[prost(bytes, tag="2")]
+        Custom(::prost::alloc::vec::Vec<u8>),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ProjectionNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -185,19 +296,22 @@ pub struct ProjectionNode {
 }
 /// Nested message and enum types in `ProjectionNode`.
 pub mod projection_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum OptionalAlias {
         #[prost(string, tag="3")]
         Alias(::prost::alloc::string::String),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SelectionNode {
     #[prost(message, optional, boxed, tag="1")]
     pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
-    #[prost(message, optional, tag="2")]
-    pub expr: ::core::option::Option<LogicalExprNode>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SortNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -208,6 +322,7 @@ pub struct SortNode {
     #[prost(int64, tag="3")]
     pub fetch: i64,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RepartitionNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -217,6 +332,7 @@ pub struct RepartitionNode {
 }
 /// Nested message and enum types in `RepartitionNode`.
 pub mod repartition_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum PartitionMethod {
         #[prost(uint64, tag="2")]
@@ -225,6 +341,7 @@ pub mod repartition_node {
         Hash(super::HashRepartition),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HashRepartition {
     #[prost(message, repeated, tag="1")]
@@ -232,11 +349,13 @@ pub struct HashRepartition {
     #[prost(uint64, tag="2")]
     pub partition_count: u64,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EmptyRelationNode {
     #[prost(bool, tag="1")]
     pub produce_one_row: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateExternalTableNode {
     #[prost(string, tag="1")]
@@ -259,7 +378,68 @@ pub struct CreateExternalTableNode {
     pub definition: ::prost::alloc::string::String,
     #[prost(string, tag="10")]
     pub file_compression_type: ::prost::alloc::string::String,
+    #[prost(btree_map="string, string", tag="11")]
+    pub options: ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(message, repeated, tag="12")]
+    pub constraints: ::prost::alloc::vec::Vec<Constraint>,
+    #[prost(string, repeated, tag="13")]
+    pub bucket_cols: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint64, tag="14")]
+    pub num_buckets: u64,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Constraint {
+    #[prost(oneof="constraint::ConstraintType", tags="1, 2, 3, 4")]
+    pub constraint_type: ::core::option::Option<constraint::ConstraintType>,
+}
+/// Nested message and enum types in `Constraint`.
+pub mod constraint {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ConstraintType {
+        #[prost(message, tag="1")]
+        PrimaryKey(super::PrimaryKeyConstraint),
+        #[prost(message, tag="2")]
+        Unique(super::UniqueConstraint),
+        #[prost(message, tag="3")]
+        ForeignKey(super::ForeignKeyConstraint),
+        #[prost(message, tag="4")]
+        Check(super::CheckConstraint),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrimaryKeyConstraint {
+    #[prost(uint64, repeated, tag="1")]
+    pub columns: ::prost::alloc::vec::Vec<u64>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UniqueConstraint {
+    #[prost(uint64, repeated, tag="1")]
+    pub columns: ::prost::alloc::vec::Vec<u64>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForeignKeyConstraint {
+    #[prost(uint64, repeated, tag="1")]
+    pub columns: ::prost::alloc::vec::Vec<u64>,
+    #[prost(string, tag="2")]
+    pub referenced_table: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="3")]
+    pub referenced_columns: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckConstraint {
+    /// empty for the constraints declared without a name
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub expr: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateCatalogSchemaNode {
     #[prost(string, tag="1")]
@@ -268,7 +448,10 @@ pub struct CreateCatalogSchemaNode {
     pub if_not_exists: bool,
     #[prost(message, optional, tag="3")]
     pub schema: ::core::option::Option<DfSchema>,
+    #[prost(string, tag="4")]
+    pub location: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateCatalogNode {
     #[prost(string, tag="1")]
@@ -277,7 +460,10 @@ pub struct CreateCatalogNode {
     pub if_not_exists: bool,
     #[prost(message, optional, tag="3")]
     pub schema: ::core::option::Option<DfSchema>,
+    #[prost(string, tag="4")]
+    pub location: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateViewNode {
     #[prost(string, tag="1")]
@@ -288,9 +474,72 @@ pub struct CreateViewNode {
     pub or_replace: bool,
     #[prost(string, tag="4")]
     pub definition: ::prost::alloc::string::String,
+    #[prost(bool, tag="5")]
+    pub materialized: bool,
+    #[prost(string, tag="6")]
+    pub location: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateMemoryTableNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, boxed, tag="2")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+    #[prost(bool, tag="3")]
+    pub if_not_exists: bool,
+    #[prost(bool, tag="4")]
+    pub or_replace: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropTableNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag="2")]
+    pub if_exists: bool,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<DfSchema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropViewNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag="2")]
+    pub if_exists: bool,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<DfSchema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RefreshMaterializedViewNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub schema: ::core::option::Option<DfSchema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AnalyzeTableNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub schema: ::core::option::Option<DfSchema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetVariableNode {
+    #[prost(string, tag="1")]
+    pub variable: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<DfSchema>,
 }
 /// a node containing data for defining values list. unlike in SQL where it's two dimensional, here
 /// the list is flattened, and with the field n_cols it can be parsed and partitioned into rows
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ValuesNode {
     #[prost(uint64, tag="1")]
@@ -298,6 +547,7 @@ pub struct ValuesNode {
     #[prost(message, repeated, tag="2")]
     pub values_list: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AnalyzeNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -305,6 +555,7 @@ pub struct AnalyzeNode {
     #[prost(bool, tag="2")]
     pub verbose: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExplainNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -312,6 +563,7 @@ pub struct ExplainNode {
     #[prost(bool, tag="2")]
     pub verbose: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AggregateNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -321,6 +573,7 @@ pub struct AggregateNode {
     #[prost(message, repeated, tag="3")]
     pub aggr_expr: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WindowNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -328,6 +581,7 @@ pub struct WindowNode {
     #[prost(message, repeated, tag="2")]
     pub window_expr: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct JoinNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -344,19 +598,58 @@ pub struct JoinNode {
     pub right_join_column: ::prost::alloc::vec::Vec<Column>,
     #[prost(bool, tag="7")]
     pub null_equals_null: bool,
-    #[prost(message, optional, tag="8")]
-    pub filter: ::core::option::Option<LogicalExprNode>,
+    #[prost(message, optional, boxed, tag="8")]
+    pub filter: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DistinctNode {
     #[prost(message, optional, boxed, tag="1")]
     pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubqueryNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub subquery: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnnestNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+    #[prost(message, optional, tag="2")]
+    pub column: ::core::option::Option<Column>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DedupNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub on: ::prost::alloc::vec::Vec<LogicalExprNode>,
+    #[prost(enumeration="DedupKeep", tag="3")]
+    pub keep: i32,
+    #[prost(message, repeated, tag="4")]
+    pub order_by: ::prost::alloc::vec::Vec<LogicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrepareNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub data_types: ::prost::alloc::vec::Vec<ArrowType>,
+    #[prost(message, optional, boxed, tag="3")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UnionNode {
     #[prost(message, repeated, tag="1")]
     pub inputs: ::prost::alloc::vec::Vec<LogicalPlanNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CrossJoinNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -364,6 +657,7 @@ pub struct CrossJoinNode {
     #[prost(message, optional, boxed, tag="2")]
     pub right: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LimitNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -375,11 +669,13 @@ pub struct LimitNode {
     #[prost(int64, tag="3")]
     pub fetch: i64,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SelectionExecNode {
     #[prost(message, optional, tag="1")]
     pub expr: ::core::option::Option<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SubqueryAliasNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -388,13 +684,15 @@ pub struct SubqueryAliasNode {
     pub alias: ::prost::alloc::string::String,
 }
 /// logical expressions
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LogicalExprNode {
-    #[prost(oneof="logical_expr_node::ExprType", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33")]
+    #[prost(oneof="logical_expr_node::ExprType", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37")]
     pub expr_type: ::core::option::Option<logical_expr_node::ExprType>,
 }
 /// Nested message and enum types in `LogicalExprNode`.
 pub mod logical_expr_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum ExprType {
         /// column references
@@ -407,7 +705,7 @@ pub mod logical_expr_node {
         Literal(super::ScalarValue),
         /// binary expressions
         #[prost(message, tag="4")]
-        BinaryExpr(::prost::alloc::boxed::Box<super::BinaryExprNode>),
+        BinaryExpr(super::BinaryExprNode),
         /// aggregate expressions
         #[prost(message, tag="5")]
         AggregateExpr(::prost::alloc::boxed::Box<super::AggregateExprNode>),
@@ -471,28 +769,42 @@ pub mod logical_expr_node {
         Ilike(::prost::alloc::boxed::Box<super::ILikeNode>),
         #[prost(message, tag="33")]
         SimilarTo(::prost::alloc::boxed::Box<super::SimilarToNode>),
+        #[prost(message, tag="34")]
+        Placeholder(super::PlaceholderNode),
+        /// subquery expressions
+        #[prost(message, tag="35")]
+        Exists(::prost::alloc::boxed::Box<super::ExistsNode>),
+        #[prost(message, tag="36")]
+        InSubquery(::prost::alloc::boxed::Box<super::InSubqueryNode>),
+        #[prost(message, tag="37")]
+        ScalarSubquery(::prost::alloc::boxed::Box<super::ScalarSubqueryNode>),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LogicalExprList {
     #[prost(message, repeated, tag="1")]
     pub expr: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GroupingSetNode {
     #[prost(message, repeated, tag="1")]
     pub expr: ::prost::alloc::vec::Vec<LogicalExprList>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CubeNode {
     #[prost(message, repeated, tag="1")]
     pub expr: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RollupNode {
     #[prost(message, repeated, tag="1")]
     pub expr: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetIndexedField {
     #[prost(message, optional, boxed, tag="1")]
@@ -500,51 +812,61 @@ pub struct GetIndexedField {
     #[prost(message, optional, tag="2")]
     pub key: ::core::option::Option<ScalarValue>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsNull {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsNotNull {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsTrue {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsFalse {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsUnknown {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsNotTrue {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsNotFalse {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IsNotUnknown {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Not {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AliasNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -552,20 +874,24 @@ pub struct AliasNode {
     #[prost(string, tag="2")]
     pub alias: ::prost::alloc::string::String,
 }
+/// A chain of binary expressions of the same operator, such as `a AND b AND c`
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BinaryExprNode {
-    #[prost(message, optional, boxed, tag="1")]
-    pub l: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
-    #[prost(message, optional, boxed, tag="2")]
-    pub r: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
+    /// the operands of the chain, from the innermost left one: the expression
+    /// is `((operands\[0\] op operands\[1\]) op operands\[2\]) ...`
+    #[prost(message, repeated, tag="1")]
+    pub operands: ::prost::alloc::vec::Vec<LogicalExprNode>,
     #[prost(string, tag="3")]
     pub op: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NegativeNode {
     #[prost(message, optional, boxed, tag="1")]
     pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InListNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -575,6 +901,7 @@ pub struct InListNode {
     #[prost(bool, tag="3")]
     pub negated: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarFunctionNode {
     #[prost(enumeration="ScalarFunction", tag="1")]
@@ -582,6 +909,7 @@ pub struct ScalarFunctionNode {
     #[prost(message, repeated, tag="2")]
     pub args: ::prost::alloc::vec::Vec<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AggregateExprNode {
     #[prost(enumeration="AggregateFunction", tag="1")]
@@ -593,6 +921,7 @@ pub struct AggregateExprNode {
     #[prost(message, optional, boxed, tag="4")]
     pub filter: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AggregateUdfExprNode {
     #[prost(string, tag="1")]
@@ -601,14 +930,43 @@ pub struct AggregateUdfExprNode {
     pub args: ::prost::alloc::vec::Vec<LogicalExprNode>,
     #[prost(message, optional, boxed, tag="3")]
     pub filter: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
+    /// The types of the UDAF, if its signature has exact argument types, to
+    /// validate the UDAF of the same name in the registry
+    #[prost(message, optional, tag="4")]
+    pub signature: ::core::option::Option<UdfSignature>,
+}
+/// The types of a UDF for the argument types of its signature
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UdfSignature {
+    #[prost(message, repeated, tag="1")]
+    pub arg_types: ::prost::alloc::vec::Vec<ArrowType>,
+    #[prost(message, optional, tag="2")]
+    pub return_type: ::core::option::Option<ArrowType>,
+    /// The types of the state of the accumulators of aggregate UDFs
+    #[prost(message, repeated, tag="3")]
+    pub state_types: ::prost::alloc::vec::Vec<ArrowType>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarUdfExprNode {
     #[prost(string, tag="1")]
     pub fun_name: ::prost::alloc::string::String,
     #[prost(message, repeated, tag="2")]
     pub args: ::prost::alloc::vec::Vec<LogicalExprNode>,
+    /// The WebAssembly module implementing the UDF, if any
+    #[prost(message, optional, tag="3")]
+    pub wasm_body: ::core::option::Option<WasmUdfBody>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WasmUdfBody {
+    #[prost(bytes="vec", tag="1")]
+    pub module: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag="2")]
+    pub export: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WindowExprNode {
     #[prost(message, optional, boxed, tag="4")]
@@ -617,6 +975,11 @@ pub struct WindowExprNode {
     pub partition_by: ::prost::alloc::vec::Vec<LogicalExprNode>,
     #[prost(message, repeated, tag="6")]
     pub order_by: ::prost::alloc::vec::Vec<LogicalExprNode>,
+    /// arguments following `expr`, such as the offset and default of lead/lag
+    #[prost(message, repeated, tag="9")]
+    pub extra_args: ::prost::alloc::vec::Vec<LogicalExprNode>,
+    #[prost(bool, tag="10")]
+    pub ignore_nulls: bool,
     #[prost(oneof="window_expr_node::WindowFunction", tags="1, 2")]
     pub window_function: ::core::option::Option<window_expr_node::WindowFunction>,
     /// repeated LogicalExprNode filter = 7;
@@ -625,6 +988,7 @@ pub struct WindowExprNode {
 }
 /// Nested message and enum types in `WindowExprNode`.
 pub mod window_expr_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum WindowFunction {
         #[prost(enumeration="super::AggregateFunction", tag="1")]
@@ -634,12 +998,14 @@ pub mod window_expr_node {
         BuiltInFunction(i32),
     }
     /// repeated LogicalExprNode filter = 7;
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum WindowFrame {
         #[prost(message, tag="8")]
         Frame(super::WindowFrame),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BetweenNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -651,6 +1017,7 @@ pub struct BetweenNode {
     #[prost(message, optional, boxed, tag="4")]
     pub high: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LikeNode {
     #[prost(bool, tag="1")]
@@ -662,6 +1029,7 @@ pub struct LikeNode {
     #[prost(string, tag="4")]
     pub escape_char: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ILikeNode {
     #[prost(bool, tag="1")]
@@ -673,6 +1041,7 @@ pub struct ILikeNode {
     #[prost(string, tag="4")]
     pub escape_char: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimilarToNode {
     #[prost(bool, tag="1")]
@@ -684,6 +1053,7 @@ pub struct SimilarToNode {
     #[prost(string, tag="4")]
     pub escape_char: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CaseNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -693,6 +1063,7 @@ pub struct CaseNode {
     #[prost(message, optional, boxed, tag="3")]
     pub else_expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WhenThen {
     #[prost(message, optional, tag="1")]
@@ -700,6 +1071,7 @@ pub struct WhenThen {
     #[prost(message, optional, tag="2")]
     pub then_expr: ::core::option::Option<LogicalExprNode>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CastNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -707,6 +1079,7 @@ pub struct CastNode {
     #[prost(message, optional, tag="2")]
     pub arrow_type: ::core::option::Option<ArrowType>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TryCastNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -714,6 +1087,39 @@ pub struct TryCastNode {
     #[prost(message, optional, tag="2")]
     pub arrow_type: ::core::option::Option<ArrowType>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExistsNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub subquery: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+    #[prost(bool, tag="2")]
+    pub negated: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InSubqueryNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<LogicalExprNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub subquery: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+    #[prost(bool, tag="3")]
+    pub negated: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarSubqueryNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub subquery: ::core::option::Option<::prost::alloc::boxed::Box<LogicalPlanNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlaceholderNode {
+    #[prost(string, tag="1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub data_type: ::core::option::Option<ArrowType>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SortExprNode {
     #[prost(message, optional, boxed, tag="1")]
@@ -723,6 +1129,7 @@ pub struct SortExprNode {
     #[prost(bool, tag="3")]
     pub nulls_first: bool,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WindowFrame {
     #[prost(enumeration="WindowFrameUnits", tag="1")]
@@ -738,12 +1145,14 @@ pub struct WindowFrame {
 pub mod window_frame {
     /// "optional" keyword is stable in protoc 3.15 but prost is still on 3.14 (see <https://github.com/tokio-rs/prost/issues/430> and <https://github.com/tokio-rs/prost/pull/455>)
     /// this syntax is ugly but is binary compatible with the "optional" keyword (see <https://stackoverflow.com/questions/42622015/how-to-define-an-optional-field-in-protobuf-3>)
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum EndBound {
         #[prost(message, tag="3")]
         Bound(super::WindowFrameBound),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WindowFrameBound {
     #[prost(enumeration="WindowFrameBoundType", tag="1")]
@@ -755,11 +1164,13 @@ pub struct WindowFrameBound {
 // Arrow Data Types
 // /////////////////////////////////////////////////////////////////////////////////////////////////
 
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Schema {
     #[prost(message, repeated, tag="1")]
     pub columns: ::prost::alloc::vec::Vec<Field>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Field {
     /// name of the field
@@ -773,11 +1184,13 @@ pub struct Field {
     #[prost(message, repeated, tag="4")]
     pub children: ::prost::alloc::vec::Vec<Field>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FixedSizeBinary {
     #[prost(int32, tag="1")]
     pub length: i32,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Timestamp {
     #[prost(enumeration="TimeUnit", tag="1")]
@@ -785,6 +1198,7 @@ pub struct Timestamp {
     #[prost(string, tag="2")]
     pub timezone: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Decimal {
     #[prost(uint64, tag="1")]
@@ -792,11 +1206,13 @@ pub struct Decimal {
     #[prost(uint64, tag="2")]
     pub fractional: u64,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct List {
     #[prost(message, optional, boxed, tag="1")]
     pub field_type: ::core::option::Option<::prost::alloc::boxed::Box<Field>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FixedSizeList {
     #[prost(message, optional, boxed, tag="1")]
@@ -804,6 +1220,7 @@ pub struct FixedSizeList {
     #[prost(int32, tag="2")]
     pub list_size: i32,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Dictionary {
     #[prost(message, optional, boxed, tag="1")]
@@ -811,11 +1228,13 @@ pub struct Dictionary {
     #[prost(message, optional, boxed, tag="2")]
     pub value: ::core::option::Option<::prost::alloc::boxed::Box<ArrowType>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Struct {
     #[prost(message, repeated, tag="1")]
     pub sub_field_types: ::prost::alloc::vec::Vec<Field>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Union {
     #[prost(message, repeated, tag="1")]
@@ -825,6 +1244,7 @@ pub struct Union {
     #[prost(int32, repeated, tag="3")]
     pub type_ids: ::prost::alloc::vec::Vec<i32>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarListValue {
     /// encode null explicitly to distinguish a list with a null value
@@ -836,6 +1256,7 @@ pub struct ScalarListValue {
     #[prost(message, repeated, tag="2")]
     pub values: ::prost::alloc::vec::Vec<ScalarValue>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarTimestampValue {
     #[prost(string, tag="5")]
@@ -845,6 +1266,7 @@ pub struct ScalarTimestampValue {
 }
 /// Nested message and enum types in `ScalarTimestampValue`.
 pub mod scalar_timestamp_value {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Value {
         #[prost(int64, tag="1")]
@@ -857,6 +1279,7 @@ pub mod scalar_timestamp_value {
         TimeMillisecondValue(i64),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarDictionaryValue {
     #[prost(message, optional, tag="1")]
@@ -864,6 +1287,7 @@ pub struct ScalarDictionaryValue {
     #[prost(message, optional, boxed, tag="2")]
     pub value: ::core::option::Option<::prost::alloc::boxed::Box<ScalarValue>>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IntervalMonthDayNanoValue {
     #[prost(int32, tag="1")]
@@ -873,6 +1297,7 @@ pub struct IntervalMonthDayNanoValue {
     #[prost(int64, tag="3")]
     pub nanos: i64,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StructValue {
     /// Note that a null struct value must have one or more fields, so we
@@ -883,6 +1308,7 @@ pub struct StructValue {
     #[prost(message, repeated, tag="3")]
     pub fields: ::prost::alloc::vec::Vec<Field>,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarFixedSizeBinary {
     #[prost(bytes="vec", tag="1")]
@@ -890,6 +1316,7 @@ pub struct ScalarFixedSizeBinary {
     #[prost(int32, tag="2")]
     pub length: i32,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScalarValue {
     #[prost(oneof="scalar_value::Value", tags="33, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 17, 20, 21, 24, 25, 26, 27, 28, 29, 30, 31, 32, 34")]
@@ -897,6 +1324,7 @@ pub struct ScalarValue {
 }
 /// Nested message and enum types in `ScalarValue`.
 pub mod scalar_value {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Value {
         /// was PrimitiveScalarType null_value = 19;
@@ -961,6 +1389,7 @@ pub mod scalar_value {
         FixedSizeBinaryValue(super::ScalarFixedSizeBinary),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Decimal128 {
     #[prost(bytes="vec", tag="1")]
@@ -971,6 +1400,7 @@ pub struct Decimal128 {
     pub s: i64,
 }
 /// Serialized data type
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ArrowType {
     #[prost(oneof="arrow_type::ArrowTypeEnum", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 32, 15, 16, 31, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30")]
@@ -978,6 +1408,7 @@ pub struct ArrowType {
 }
 /// Nested message and enum types in `ArrowType`.
 pub mod arrow_type {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum ArrowTypeEnum {
         /// arrow::Type::NA
@@ -1060,19 +1491,23 @@ pub mod arrow_type {
 ///         i32 Two = 2;
 ///    }
 /// }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EmptyMessage {
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OptimizedLogicalPlanType {
     #[prost(string, tag="1")]
     pub optimizer_name: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OptimizedPhysicalPlanType {
     #[prost(string, tag="1")]
     pub optimizer_name: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PlanType {
     #[prost(oneof="plan_type::PlanTypeEnum", tags="1, 2, 3, 4, 5, 6")]
@@ -1080,6 +1515,7 @@ pub struct PlanType {
 }
 /// Nested message and enum types in `PlanType`.
 pub mod plan_type {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum PlanTypeEnum {
         #[prost(message, tag="1")]
@@ -1096,6 +1532,7 @@ pub mod plan_type {
         FinalPhysicalPlan(super::EmptyMessage),
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StringifiedPlan {
     #[prost(message, optional, tag="1")]
@@ -1103,73 +1540,1061 @@ pub struct StringifiedPlan {
     #[prost(string, tag="2")]
     pub plan: ::prost::alloc::string::String,
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum JoinType {
-    Inner = 0,
-    Left = 1,
-    Right = 2,
-    Full = 3,
-    Leftsemi = 4,
-    Leftanti = 5,
-    Rightsemi = 6,
+// /////////////////////////////////////////////////////////////////////////////////////////////////
+// Physical Plans
+// /////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// PhysicalPlanNode is a nested type
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalPlanNode {
+    #[prost(oneof="physical_plan_node::PhysicalPlanType", tags="1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31")]
+    pub physical_plan_type: ::core::option::Option<physical_plan_node::PhysicalPlanType>,
 }
-impl JoinType {
-    /// String value of the enum field names used in the ProtoBuf definition.
-    ///
-    /// The values are not transformed in any way and thus are considered stable
-    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-    pub fn as_str_name(&self) -> &'static str {
-        match self {
-            JoinType::Inner => "INNER",
-            JoinType::Left => "LEFT",
-            JoinType::Right => "RIGHT",
-            JoinType::Full => "FULL",
-            JoinType::Leftsemi => "LEFTSEMI",
-            JoinType::Leftanti => "LEFTANTI",
-            JoinType::Rightsemi => "RIGHTSEMI",
-        }
+/// Nested message and enum types in `PhysicalPlanNode`.
+pub mod physical_plan_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum PhysicalPlanType {
+        #[prost(message, tag="1")]
+        ParquetScan(super::ParquetScanExecNode),
+        #[prost(message, tag="2")]
+        CsvScan(super::CsvScanExecNode),
+        #[prost(message, tag="3")]
+        Empty(super::EmptyExecNode),
+        #[prost(message, tag="4")]
+        Projection(::prost::alloc::boxed::Box<super::ProjectionExecNode>),
+        #[prost(message, tag="6")]
+        GlobalLimit(::prost::alloc::boxed::Box<super::GlobalLimitExecNode>),
+        #[prost(message, tag="7")]
+        LocalLimit(::prost::alloc::boxed::Box<super::LocalLimitExecNode>),
+        #[prost(message, tag="8")]
+        Aggregate(::prost::alloc::boxed::Box<super::AggregateExecNode>),
+        #[prost(message, tag="9")]
+        HashJoin(::prost::alloc::boxed::Box<super::HashJoinExecNode>),
+        #[prost(message, tag="10")]
+        Sort(::prost::alloc::boxed::Box<super::SortExecNode>),
+        #[prost(message, tag="11")]
+        CoalesceBatches(::prost::alloc::boxed::Box<super::CoalesceBatchesExecNode>),
+        #[prost(message, tag="12")]
+        Filter(::prost::alloc::boxed::Box<super::FilterExecNode>),
+        #[prost(message, tag="13")]
+        Merge(::prost::alloc::boxed::Box<super::CoalescePartitionsExecNode>),
+        #[prost(message, tag="14")]
+        Repartition(::prost::alloc::boxed::Box<super::RepartitionExecNode>),
+        #[prost(message, tag="15")]
+        Window(::prost::alloc::boxed::Box<super::WindowAggExecNode>),
+        #[prost(message, tag="16")]
+        CrossJoin(::prost::alloc::boxed::Box<super::CrossJoinExecNode>),
+        #[prost(message, tag="17")]
+        AvroScan(super::AvroScanExecNode),
+        #[prost(message, tag="18")]
+        Extension(super::PhysicalExtensionNode),
+        #[prost(message, tag="19")]
+        Union(super::UnionExecNode),
+        #[prost(message, tag="20")]
+        Explain(super::ExplainExecNode),
+        #[prost(message, tag="21")]
+        SortPreservingMerge(::prost::alloc::boxed::Box<super::SortPreservingMergeExecNode>),
+        #[prost(message, tag="22")]
+        JsonScan(super::NdJsonScanExecNode),
+        #[prost(message, tag="23")]
+        Analyze(::prost::alloc::boxed::Box<super::AnalyzeExecNode>),
+        #[prost(message, tag="24")]
+        Values(super::ValuesExecNode),
+        #[prost(message, tag="25")]
+        Memory(super::MemoryExecNode),
+        #[prost(message, tag="26")]
+        SortMergeJoin(::prost::alloc::boxed::Box<super::SortMergeJoinExecNode>),
+        #[prost(message, tag="27")]
+        Unnest(::prost::alloc::boxed::Box<super::UnnestExecNode>),
+        #[prost(message, tag="28")]
+        Bucketed(::prost::alloc::boxed::Box<super::BucketedExecNode>),
+        #[prost(message, tag="29")]
+        Dedup(::prost::alloc::boxed::Box<super::DedupExecNode>),
+        #[prost(message, tag="30")]
+        CheckConstraints(::prost::alloc::boxed::Box<super::CheckConstraintsExecNode>),
+        #[prost(message, tag="31")]
+        ScalarSubquery(::prost::alloc::boxed::Box<super::ScalarSubqueryExecNode>),
     }
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum JoinConstraint {
-    On = 0,
-    Using = 1,
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalExtensionNode {
+    #[prost(bytes="vec", tag="1")]
+    pub node: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag="2")]
+    pub inputs: ::prost::alloc::vec::Vec<PhysicalPlanNode>,
 }
-impl JoinConstraint {
-    /// String value of the enum field names used in the ProtoBuf definition.
-    ///
-    /// The values are not transformed in any way and thus are considered stable
-    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-    pub fn as_str_name(&self) -> &'static str {
-        match self {
-            JoinConstraint::On => "ON",
-            JoinConstraint::Using => "USING",
-        }
-    }
+/// physical expressions
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalExprNode {
+    #[prost(oneof="physical_expr_node::ExprType", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19")]
+    pub expr_type: ::core::option::Option<physical_expr_node::ExprType>,
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum ScalarFunction {
-    Abs = 0,
-    Acos = 1,
-    Asin = 2,
-    Atan = 3,
-    Ascii = 4,
-    Ceil = 5,
-    Cos = 6,
-    Digest = 7,
-    Exp = 8,
-    Floor = 9,
-    Ln = 10,
-    Log = 11,
-    Log10 = 12,
-    Log2 = 13,
-    Round = 14,
-    Signum = 15,
-    Sin = 16,
-    Sqrt = 17,
+/// Nested message and enum types in `PhysicalExprNode`.
+pub mod physical_expr_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ExprType {
+        /// column references
+        #[prost(message, tag="1")]
+        Column(super::PhysicalColumn),
+        #[prost(message, tag="2")]
+        Literal(super::ScalarValue),
+        /// binary expressions
+        #[prost(message, tag="3")]
+        BinaryExpr(::prost::alloc::boxed::Box<super::PhysicalBinaryExprNode>),
+        /// aggregate expressions
+        #[prost(message, tag="4")]
+        AggregateExpr(super::PhysicalAggregateExprNode),
+        /// null checks
+        #[prost(message, tag="5")]
+        IsNullExpr(::prost::alloc::boxed::Box<super::PhysicalIsNull>),
+        #[prost(message, tag="6")]
+        IsNotNullExpr(::prost::alloc::boxed::Box<super::PhysicalIsNotNull>),
+        #[prost(message, tag="7")]
+        NotExpr(::prost::alloc::boxed::Box<super::PhysicalNot>),
+        #[prost(message, tag="8")]
+        Case(::prost::alloc::boxed::Box<super::PhysicalCaseNode>),
+        #[prost(message, tag="9")]
+        Cast(::prost::alloc::boxed::Box<super::PhysicalCastNode>),
+        #[prost(message, tag="10")]
+        Sort(::prost::alloc::boxed::Box<super::PhysicalSortExprNode>),
+        #[prost(message, tag="11")]
+        Negative(::prost::alloc::boxed::Box<super::PhysicalNegativeNode>),
+        #[prost(message, tag="12")]
+        InList(::prost::alloc::boxed::Box<super::PhysicalInListNode>),
+        #[prost(message, tag="13")]
+        ScalarFunction(super::PhysicalScalarFunctionNode),
+        #[prost(message, tag="14")]
+        TryCast(::prost::alloc::boxed::Box<super::PhysicalTryCastNode>),
+        /// window expressions
+        #[prost(message, tag="15")]
+        WindowExpr(super::PhysicalWindowExprNode),
+        #[prost(message, tag="16")]
+        ScalarUdf(super::PhysicalScalarUdfNode),
+        #[prost(message, tag="17")]
+        DateTimeIntervalExpr(::prost::alloc::boxed::Box<super::PhysicalDateTimeIntervalExprNode>),
+        #[prost(message, tag="18")]
+        GetIndexedFieldExpr(::prost::alloc::boxed::Box<super::PhysicalGetIndexedFieldExprNode>),
+        #[prost(message, tag="19")]
+        ScalarSubquery(super::PhysicalScalarSubqueryExprNode),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalScalarUdfNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub args: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalAggregateExprNode {
+    #[prost(message, repeated, tag="2")]
+    pub expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(bool, tag="3")]
+    pub distinct: bool,
+    #[prost(oneof="physical_aggregate_expr_node::AggregateFunction", tags="1, 4")]
+    pub aggregate_function: ::core::option::Option<physical_aggregate_expr_node::AggregateFunction>,
+}
+/// Nested message and enum types in `PhysicalAggregateExprNode`.
+pub mod physical_aggregate_expr_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum AggregateFunction {
+        #[prost(enumeration="super::AggregateFunction", tag="1")]
+        AggrFunction(i32),
+        #[prost(string, tag="4")]
+        UserDefinedAggrFunction(::prost::alloc::string::String),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalWindowExprNode {
+    #[prost(message, repeated, tag="3")]
+    pub args: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(message, repeated, tag="4")]
+    pub partition_by: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(message, repeated, tag="5")]
+    pub order_by: ::prost::alloc::vec::Vec<PhysicalSortExprNode>,
+    #[prost(string, tag="7")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag="8")]
+    pub ignore_nulls: bool,
+    #[prost(oneof="physical_window_expr_node::WindowFunction", tags="1, 2")]
+    pub window_function: ::core::option::Option<physical_window_expr_node::WindowFunction>,
+    #[prost(oneof="physical_window_expr_node::WindowFrame", tags="6")]
+    pub window_frame: ::core::option::Option<physical_window_expr_node::WindowFrame>,
+}
+/// Nested message and enum types in `PhysicalWindowExprNode`.
+pub mod physical_window_expr_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum WindowFunction {
+        #[prost(enumeration="super::AggregateFunction", tag="1")]
+        AggrFunction(i32),
+        #[prost(enumeration="super::BuiltInWindowFunction", tag="2")]
+        BuiltInFunction(i32),
+    }
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum WindowFrame {
+        #[prost(message, tag="6")]
+        Frame(super::WindowFrame),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalIsNull {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalIsNotNull {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalNot {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalBinaryExprNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub l: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub r: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(string, tag="3")]
+    pub op: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalDateTimeIntervalExprNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub l: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub r: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(string, tag="3")]
+    pub op: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalGetIndexedFieldExprNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub arg: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, optional, tag="2")]
+    pub key: ::core::option::Option<ScalarValue>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalSortExprNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(bool, tag="2")]
+    pub asc: bool,
+    #[prost(bool, tag="3")]
+    pub nulls_first: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalWhenThen {
+    #[prost(message, optional, tag="1")]
+    pub when_expr: ::core::option::Option<PhysicalExprNode>,
+    #[prost(message, optional, tag="2")]
+    pub then_expr: ::core::option::Option<PhysicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalInListNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub list: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(bool, tag="3")]
+    pub negated: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalCaseNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub when_then_expr: ::prost::alloc::vec::Vec<PhysicalWhenThen>,
+    #[prost(message, optional, boxed, tag="3")]
+    pub else_expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalScalarFunctionNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(enumeration="ScalarFunction", tag="2")]
+    pub fun: i32,
+    #[prost(message, repeated, tag="3")]
+    pub args: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalTryCastNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, optional, tag="2")]
+    pub arrow_type: ::core::option::Option<ArrowType>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalCastNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+    #[prost(message, optional, tag="2")]
+    pub arrow_type: ::core::option::Option<ArrowType>,
+    /// The time zone of the local times of naive timestamps cast into zoned
+    /// ones, empty for UTC
+    #[prost(string, tag="3")]
+    pub time_zone: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalNegativeNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub expr: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalExprNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalColumn {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub index: u32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FilterExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, optional, tag="2")]
+    pub expr: ::core::option::Option<PhysicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileGroup {
+    #[prost(message, repeated, tag="1")]
+    pub files: ::prost::alloc::vec::Vec<PartitionedFile>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScanLimit {
+    /// wrap into a message to make it optional
+    #[prost(uint32, tag="1")]
+    pub limit: u32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScanProjection {
+    /// wrap into a message to distinguish no projection from an empty one
+    #[prost(uint32, repeated, tag="1")]
+    pub columns: ::prost::alloc::vec::Vec<u32>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileScanExecConf {
+    #[prost(message, repeated, tag="1")]
+    pub file_groups: ::prost::alloc::vec::Vec<FileGroup>,
+    #[prost(message, optional, tag="2")]
+    pub schema: ::core::option::Option<Schema>,
+    #[prost(message, optional, tag="4")]
+    pub projection: ::core::option::Option<ScanProjection>,
+    #[prost(message, optional, tag="5")]
+    pub limit: ::core::option::Option<ScanLimit>,
+    #[prost(message, optional, tag="6")]
+    pub statistics: ::core::option::Option<Statistics>,
+    #[prost(string, repeated, tag="7")]
+    pub table_partition_cols: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag="8")]
+    pub object_store_url: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParquetScanExecNode {
+    #[prost(message, optional, tag="1")]
+    pub base_conf: ::core::option::Option<FileScanExecConf>,
+    #[prost(message, optional, tag="2")]
+    pub pruning_predicate: ::core::option::Option<LogicalExprNode>,
+    /// 0 if there is no hint
+    #[prost(uint64, tag="3")]
+    pub metadata_size_hint: u64,
+    #[prost(bool, tag="4")]
+    pub pushdown_filters: bool,
+    #[prost(bool, tag="5")]
+    pub reorder_filters: bool,
+    #[prost(bool, tag="6")]
+    pub enable_page_index: bool,
+    #[prost(message, repeated, tag="7")]
+    pub nested_projection: ::prost::alloc::vec::Vec<NestedProjectionPath>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NestedProjectionPath {
+    #[prost(string, repeated, tag="1")]
+    pub names: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CsvScanExecNode {
+    #[prost(message, optional, tag="1")]
+    pub base_conf: ::core::option::Option<FileScanExecConf>,
+    #[prost(bool, tag="2")]
+    pub has_header: bool,
+    #[prost(string, tag="3")]
+    pub delimiter: ::prost::alloc::string::String,
+    #[prost(enumeration="FileCompressionType", tag="4")]
+    pub file_compression_type: i32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvroScanExecNode {
+    #[prost(message, optional, tag="1")]
+    pub base_conf: ::core::option::Option<FileScanExecConf>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NdJsonScanExecNode {
+    #[prost(message, optional, tag="1")]
+    pub base_conf: ::core::option::Option<FileScanExecConf>,
+    #[prost(enumeration="FileCompressionType", tag="2")]
+    pub file_compression_type: i32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HashJoinExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub left: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub right: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="3")]
+    pub on: ::prost::alloc::vec::Vec<JoinOn>,
+    #[prost(enumeration="JoinType", tag="4")]
+    pub join_type: i32,
+    #[prost(enumeration="PartitionMode", tag="6")]
+    pub partition_mode: i32,
+    #[prost(bool, tag="7")]
+    pub null_equals_null: bool,
+    #[prost(message, optional, tag="8")]
+    pub filter: ::core::option::Option<JoinFilter>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SortMergeJoinExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub left: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub right: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="3")]
+    pub on: ::prost::alloc::vec::Vec<JoinOn>,
+    #[prost(enumeration="JoinType", tag="4")]
+    pub join_type: i32,
+    #[prost(message, repeated, tag="5")]
+    pub sort_options: ::prost::alloc::vec::Vec<PhysicalSortOptions>,
+    #[prost(bool, tag="6")]
+    pub null_equals_null: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalSortOptions {
+    #[prost(bool, tag="1")]
+    pub asc: bool,
+    #[prost(bool, tag="2")]
+    pub nulls_first: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnionExecNode {
+    #[prost(message, repeated, tag="1")]
+    pub inputs: ::prost::alloc::vec::Vec<PhysicalPlanNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExplainExecNode {
+    #[prost(message, optional, tag="1")]
+    pub schema: ::core::option::Option<Schema>,
+    #[prost(message, repeated, tag="2")]
+    pub stringified_plans: ::prost::alloc::vec::Vec<StringifiedPlan>,
+    #[prost(bool, tag="3")]
+    pub verbose: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AnalyzeExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(bool, tag="2")]
+    pub verbose: bool,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<Schema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CrossJoinExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub left: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, optional, boxed, tag="2")]
+    pub right: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinOn {
+    #[prost(message, optional, tag="1")]
+    pub left: ::core::option::Option<PhysicalColumn>,
+    #[prost(message, optional, tag="2")]
+    pub right: ::core::option::Option<PhysicalColumn>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EmptyExecNode {
+    #[prost(bool, tag="1")]
+    pub produce_one_row: bool,
+    #[prost(message, optional, tag="2")]
+    pub schema: ::core::option::Option<Schema>,
+    #[prost(uint32, tag="3")]
+    pub partitions: u32,
+}
+/// the record batches are encoded in the Arrow IPC streaming format
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValuesExecNode {
+    #[prost(message, optional, tag="1")]
+    pub schema: ::core::option::Option<Schema>,
+    #[prost(bytes="vec", tag="2")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryExecNode {
+    #[prost(message, optional, tag="1")]
+    pub schema: ::core::option::Option<Schema>,
+    /// one Arrow IPC stream per partition
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub partitions: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, optional, tag="3")]
+    pub projection: ::core::option::Option<ScanProjection>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProjectionExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(string, repeated, tag="3")]
+    pub expr_name: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WindowAggExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub window_expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(message, optional, tag="4")]
+    pub input_schema: ::core::option::Option<Schema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnnestExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, optional, tag="2")]
+    pub column: ::core::option::Option<PhysicalColumn>,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<Schema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DedupExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub on: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(enumeration="DedupKeep", tag="3")]
+    pub keep: i32,
+    #[prost(message, repeated, tag="4")]
+    pub order_by: ::prost::alloc::vec::Vec<PhysicalSortExprNode>,
+}
+/// The references to the same scalar subquery in a plan have the same id
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalScalarSubqueryExprNode {
+    #[prost(uint64, tag="1")]
+    pub id: u64,
+    #[prost(message, optional, tag="2")]
+    pub arrow_type: ::core::option::Option<ArrowType>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarSubqueryExecPlan {
+    #[prost(message, optional, tag="1")]
+    pub plan: ::core::option::Option<PhysicalPlanNode>,
+    #[prost(uint64, tag="2")]
+    pub id: u64,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarSubqueryExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub subqueries: ::prost::alloc::vec::Vec<ScalarSubqueryExecPlan>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalCheckConstraint {
+    #[prost(string, tag="1")]
+    pub label: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub expr: ::core::option::Option<PhysicalExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckConstraintsExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub checks: ::prost::alloc::vec::Vec<PhysicalCheckConstraint>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BucketedExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub columns: ::prost::alloc::vec::Vec<PhysicalColumn>,
+    #[prost(uint32, tag="3")]
+    pub num_buckets: u32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AggregateExecNode {
+    #[prost(message, repeated, tag="1")]
+    pub group_expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(message, repeated, tag="2")]
+    pub aggr_expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(enumeration="AggregateMode", tag="3")]
+    pub mode: i32,
+    #[prost(message, optional, boxed, tag="4")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(string, repeated, tag="5")]
+    pub group_expr_name: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag="6")]
+    pub aggr_expr_name: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// we need the input schema to the partial aggregate to pass to the final aggregate
+    #[prost(message, optional, tag="7")]
+    pub input_schema: ::core::option::Option<Schema>,
+    #[prost(message, repeated, tag="8")]
+    pub null_expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(bool, repeated, tag="9")]
+    pub groups: ::prost::alloc::vec::Vec<bool>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GlobalLimitExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    /// The number of rows to skip before fetch
+    #[prost(uint32, tag="2")]
+    pub skip: u32,
+    /// Maximum number of rows to fetch; negative means no limit
+    #[prost(int64, tag="3")]
+    pub fetch: i64,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LocalLimitExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(uint32, tag="2")]
+    pub fetch: u32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SortExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub expr: ::prost::alloc::vec::Vec<PhysicalSortExprNode>,
+    /// Maximum number of highest/lowest rows to fetch; negative means no limit
+    #[prost(int64, tag="3")]
+    pub fetch: i64,
+    #[prost(bool, tag="4")]
+    pub preserve_partitioning: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SortPreservingMergeExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(message, repeated, tag="2")]
+    pub expr: ::prost::alloc::vec::Vec<PhysicalSortExprNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoalesceBatchesExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(uint32, tag="2")]
+    pub target_batch_size: u32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoalescePartitionsExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PhysicalHashRepartition {
+    #[prost(message, repeated, tag="1")]
+    pub hash_expr: ::prost::alloc::vec::Vec<PhysicalExprNode>,
+    #[prost(uint64, tag="2")]
+    pub partition_count: u64,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RepartitionExecNode {
+    #[prost(message, optional, boxed, tag="1")]
+    pub input: ::core::option::Option<::prost::alloc::boxed::Box<PhysicalPlanNode>>,
+    #[prost(oneof="repartition_exec_node::PartitionMethod", tags="2, 3, 4")]
+    pub partition_method: ::core::option::Option<repartition_exec_node::PartitionMethod>,
+}
+/// Nested message and enum types in `RepartitionExecNode`.
+pub mod repartition_exec_node {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum PartitionMethod {
+        #[prost(uint64, tag="2")]
+        RoundRobin(u64),
+        #[prost(message, tag="3")]
+        Hash(super::PhysicalHashRepartition),
+        #[prost(uint64, tag="4")]
+        Unknown(u64),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinFilter {
+    #[prost(message, optional, tag="1")]
+    pub expression: ::core::option::Option<PhysicalExprNode>,
+    #[prost(message, repeated, tag="2")]
+    pub column_indices: ::prost::alloc::vec::Vec<ColumnIndex>,
+    #[prost(message, optional, tag="3")]
+    pub schema: ::core::option::Option<Schema>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ColumnIndex {
+    #[prost(uint32, tag="1")]
+    pub index: u32,
+    #[prost(enumeration="JoinSide", tag="2")]
+    pub side: i32,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PartitionedFile {
+    #[prost(string, tag="1")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub size: u64,
+    #[prost(uint64, tag="3")]
+    pub last_modified_ns: u64,
+    #[prost(message, repeated, tag="4")]
+    pub partition_values: ::prost::alloc::vec::Vec<ScalarValue>,
+    #[prost(message, optional, tag="5")]
+    pub range: ::core::option::Option<FileRange>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileRange {
+    #[prost(int64, tag="1")]
+    pub start: i64,
+    #[prost(int64, tag="2")]
+    pub end: i64,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Statistics {
+    /// negative if unknown
+    #[prost(int64, tag="1")]
+    pub num_rows: i64,
+    /// negative if unknown
+    #[prost(int64, tag="2")]
+    pub total_byte_size: i64,
+    #[prost(message, repeated, tag="3")]
+    pub column_stats: ::prost::alloc::vec::Vec<ColumnStats>,
+    #[prost(bool, tag="4")]
+    pub is_exact: bool,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ColumnStats {
+    #[prost(message, optional, tag="1")]
+    pub min_value: ::core::option::Option<ScalarValue>,
+    #[prost(message, optional, tag="2")]
+    pub max_value: ::core::option::Option<ScalarValue>,
+    /// negative if unknown
+    #[prost(int64, tag="3")]
+    pub null_count: i64,
+    /// negative if unknown
+    #[prost(int64, tag="4")]
+    pub distinct_count: i64,
+}
+/// The options of a session, by key
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConfigOptions {
+    #[prost(btree_map="string, message", tag="1")]
+    pub options: ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, ScalarValue>,
+}
+/// The configuration of a session, without its extensions
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionConfig {
+    #[prost(uint64, tag="1")]
+    pub target_partitions: u64,
+    #[prost(string, tag="2")]
+    pub default_catalog: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub default_schema: ::prost::alloc::string::String,
+    #[prost(bool, tag="4")]
+    pub create_default_catalog_and_schema: bool,
+    #[prost(bool, tag="5")]
+    pub information_schema: bool,
+    #[prost(bool, tag="6")]
+    pub repartition_joins: bool,
+    #[prost(bool, tag="7")]
+    pub repartition_aggregations: bool,
+    #[prost(bool, tag="8")]
+    pub repartition_windows: bool,
+    #[prost(bool, tag="9")]
+    pub parquet_pruning: bool,
+    #[prost(bool, tag="10")]
+    pub collect_statistics: bool,
+    #[prost(message, optional, tag="11")]
+    pub config_options: ::core::option::Option<ConfigOptions>,
+}
+/// The catalogs of a session, with their schemas and tables
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CatalogSnapshot {
+    #[prost(message, repeated, tag="1")]
+    pub catalogs: ::prost::alloc::vec::Vec<CatalogNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CatalogNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub schemas: ::prost::alloc::vec::Vec<CatalogSchemaNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CatalogSchemaNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub tables: ::prost::alloc::vec::Vec<CatalogTableNode>,
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CatalogTableNode {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub schema: ::core::option::Option<Schema>,
+    /// the table provider, encoded by the LogicalExtensionCodec
+    #[prost(bytes="vec", tag="3")]
+    pub provider: ::prost::alloc::vec::Vec<u8>,
+}
+/// The ticket of the Arrow Flight `DoGet` calls executing a logical plan on a
+/// server, see the `service` module
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExecutePlanRequest {
+    /// the plan, serialized by `logical_plan_to_bytes`
+    #[prost(bytes="vec", tag="1")]
+    pub plan: ::prost::alloc::vec::Vec<u8>,
+    /// the options of the session configuration, replacing those of the server
+    #[prost(btree_map="string, message", tag="2")]
+    pub config_options: ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, ScalarValue>,
+    /// 0 to use the target partitions of the server
+    #[prost(uint64, tag="3")]
+    pub target_partitions: u64,
+}
+/// The command of an Arrow Flight `DoExchange` call of the shuffle service, in
+/// the descriptor of its first message, see the `shuffle` module
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShuffleCommand {
+    #[prost(oneof="shuffle_command::Command", tags="1, 2")]
+    pub command: ::core::option::Option<shuffle_command::Command>,
+}
+/// Nested message and enum types in `ShuffleCommand`.
+pub mod shuffle_command {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Command {
+        #[prost(message, tag="1")]
+        Write(super::ShuffleWrite),
+        #[prost(message, tag="2")]
+        Read(super::ShuffleRead),
+    }
+}
+/// Writes the output partition of a map task of a stage, the first message
+/// having the schema and the next ones the batches
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShuffleWrite {
+    #[prost(string, tag="1")]
+    pub job: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub stage: u64,
+    #[prost(uint64, tag="3")]
+    pub map_task: u64,
+    #[prost(uint64, tag="4")]
+    pub partition: u64,
+}
+/// Reads an output partition of a stage, once its map tasks wrote it
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShuffleRead {
+    #[prost(string, tag="1")]
+    pub job: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub stage: u64,
+    #[prost(uint64, tag="3")]
+    pub partition: u64,
+    #[prost(uint64, tag="4")]
+    pub map_tasks: u64,
+}
+/// An Arrow Flight ticket executing a partition of a serialized plan, see the
+/// `ticket` module
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlanTicket {
+    #[prost(uint64, tag="3")]
+    pub partition: u64,
+    #[prost(oneof="plan_ticket::Plan", tags="1, 2")]
+    pub plan: ::core::option::Option<plan_ticket::Plan>,
+}
+/// Nested message and enum types in `PlanTicket`.
+pub mod plan_ticket {
+    #[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Plan {
+        /// serialized by `logical_plan_to_bytes`
+        #[prost(bytes, tag="1")]
+        LogicalPlan(::prost::alloc::vec::Vec<u8>),
+        /// serialized by `physical_plan_to_bytes`
+        #[prost(bytes, tag="2")]
+        PhysicalPlan(::prost::alloc::vec::Vec<u8>),
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JoinType {
+    Inner = 0,
+    Left = 1,
+    Right = 2,
+    Full = 3,
+    Leftsemi = 4,
+    Leftanti = 5,
+    Rightsemi = 6,
+}
+impl JoinType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JoinType::Inner => "INNER",
+            JoinType::Left => "LEFT",
+            JoinType::Right => "RIGHT",
+            JoinType::Full => "FULL",
+            JoinType::Leftsemi => "LEFTSEMI",
+            JoinType::Leftanti => "LEFTANTI",
+            JoinType::Rightsemi => "RIGHTSEMI",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JoinConstraint {
+    On = 0,
+    Using = 1,
+}
+impl JoinConstraint {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JoinConstraint::On => "ON",
+            JoinConstraint::Using => "USING",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DedupKeep {
+    KeepFirst = 0,
+    KeepLast = 1,
+}
+impl DedupKeep {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DedupKeep::KeepFirst => "KEEP_FIRST",
+            DedupKeep::KeepLast => "KEEP_LAST",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ScalarFunction {
+    Abs = 0,
+    Acos = 1,
+    Asin = 2,
+    Atan = 3,
+    Ascii = 4,
+    Ceil = 5,
+    Cos = 6,
+    Digest = 7,
+    Exp = 8,
+    Floor = 9,
+    Ln = 10,
+    Log = 11,
+    Log10 = 12,
+    Log2 = 13,
+    Round = 14,
+    Signum = 15,
+    Sin = 16,
+    Sqrt = 17,
     Tan = 18,
     Trunc = 19,
     Array = 20,
@@ -1222,6 +2647,9 @@ pub enum ScalarFunction {
     Atan2 = 67,
     DateBin = 68,
     ArrowTypeof = 69,
+    ZOrder = 70,
+    CurrentDate = 71,
+    CurrentTime = 72,
 }
 impl ScalarFunction {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -1300,9 +2728,13 @@ impl ScalarFunction {
             ScalarFunction::Atan2 => "Atan2",
             ScalarFunction::DateBin => "DateBin",
             ScalarFunction::ArrowTypeof => "ArrowTypeof",
+            ScalarFunction::ZOrder => "ZOrder",
+            ScalarFunction::CurrentDate => "CurrentDate",
+            ScalarFunction::CurrentTime => "CurrentTime",
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum AggregateFunction {
@@ -1355,6 +2787,7 @@ impl AggregateFunction {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum BuiltInWindowFunction {
@@ -1391,6 +2824,7 @@ impl BuiltInWindowFunction {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum WindowFrameUnits {
@@ -1411,6 +2845,7 @@ impl WindowFrameUnits {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum WindowFrameBoundType {
@@ -1431,6 +2866,7 @@ impl WindowFrameBoundType {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum DateUnit {
@@ -1449,6 +2885,7 @@ impl DateUnit {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum TimeUnit {
@@ -1471,6 +2908,7 @@ impl TimeUnit {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum IntervalUnit {
@@ -1491,6 +2929,7 @@ impl IntervalUnit {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum UnionMode {
@@ -1509,3 +2948,85 @@ impl UnionMode {
         }
     }
 }
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FileCompressionType {
+    Uncompressed = 0,
+    Gzip = 1,
+    Bzip2 = 2,
+}
+impl FileCompressionType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FileCompressionType::Uncompressed => "UNCOMPRESSED",
+            FileCompressionType::Gzip => "GZIP",
+            FileCompressionType::Bzip2 => "BZIP2",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PartitionMode {
+    CollectLeft = 0,
+    Partitioned = 1,
+}
+impl PartitionMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PartitionMode::CollectLeft => "COLLECT_LEFT",
+            PartitionMode::Partitioned => "PARTITIONED",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AggregateMode {
+    Partial = 0,
+    Final = 1,
+    FinalPartitioned = 2,
+    Single = 3,
+}
+impl AggregateMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AggregateMode::Partial => "PARTIAL",
+            AggregateMode::Final => "FINAL",
+            AggregateMode::FinalPartitioned => "FINAL_PARTITIONED",
+            AggregateMode::Single => "SINGLE",
+        }
+    }
+}
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum JoinSide {
+    LeftSide = 0,
+    RightSide = 1,
+}
+impl JoinSide {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            JoinSide::LeftSide => "LEFT_SIDE",
+            JoinSide::RightSide => "RIGHT_SIDE",
+        }
+    }
+}
@@ -15,14 +15,27 @@
 // specific language governing permissions and limitations
 // under the License.
 
-//! Serde code for logical plans and expressions.
+//! Serde code for logical plans, physical plans and expressions.
 
 use datafusion_common::DataFusionError;
 
+#[cfg(all(feature = "json", feature = "postcard"))]
+compile_error!(
+    "the json and postcard features implement serde differently for the protobuf types, \
+     enable only one of them"
+);
+
 pub mod bytes;
 pub mod from_proto;
 pub mod generated;
 pub mod logical_plan;
+pub mod physical_plan;
+#[cfg(feature = "flight")]
+pub mod service;
+#[cfg(feature = "flight")]
+pub mod shuffle;
+#[cfg(feature = "flight")]
+pub mod ticket;
 pub mod to_proto;
 
 pub use generated::datafusion as protobuf;
@@ -50,31 +63,43 @@ mod roundtrip_tests {
         logical_plan_from_bytes, logical_plan_from_bytes_with_extension_codec,
         logical_plan_to_bytes, logical_plan_to_bytes_with_extension_codec,
     };
-    use crate::logical_plan::LogicalExtensionCodec;
-    use arrow::datatypes::{Schema, SchemaRef};
+    use crate::logical_plan::{
+        DefaultLogicalExtensionCodec, ExtensionCodecRegistry, LogicalExtensionCodec,
+    };
+    use arrow::datatypes::{Int64Type, Schema, SchemaRef};
+    use arrow::record_batch::RecordBatch;
     use arrow::{
-        array::ArrayRef,
+        array::{ArrayRef, Int64Array, ListArray},
         datatypes::{
             DataType, Field, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit,
             TimeUnit, UnionMode,
         },
     };
     use datafusion::datasource::datasource::TableProviderFactory;
-    use datafusion::datasource::TableProvider;
+    use datafusion::datasource::file_format::csv::CsvFormat;
+    use datafusion::datasource::listing::{
+        ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+    };
+    use datafusion::datasource::{
+        provider_as_source, source_as_provider, MemTable, TableProvider,
+    };
     use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
     use datafusion::physical_plan::functions::make_scalar_function;
     use datafusion::prelude::{
         create_udf, CsvReadOptions, SessionConfig, SessionContext,
     };
     use datafusion::test_util::{TestTableFactory, TestTableProvider};
-    use datafusion_common::{DFSchemaRef, DataFusionError, ScalarValue};
+    use datafusion_common::{
+        ColumnStatistics, DFSchemaRef, DataFusionError, ScalarValue, Statistics,
+    };
     use datafusion_expr::create_udaf;
     use datafusion_expr::expr::{Between, BinaryExpr, Case, Cast, GroupingSet, Like};
-    use datafusion_expr::logical_plan::{Extension, UserDefinedLogicalNode};
+    use datafusion_expr::logical_plan::{Extension, TableScan, UserDefinedLogicalNode};
+    use datafusion_expr::window_function::{BuiltInWindowFunction, WindowFunction};
     use datafusion_expr::{
-        col, lit, Accumulator, AggregateFunction, AggregateState,
+        col, lit, placeholder, Accumulator, AggregateFunction, AggregateState,
         BuiltinScalarFunction::{Sqrt, Substr},
-        Expr, LogicalPlan, Operator, Volatility,
+        DedupKeep, Expr, LogicalPlan, LogicalPlanBuilder, Operator, Volatility,
     };
     use prost::Message;
     use std::any::Any;
@@ -223,6 +248,198 @@ mod roundtrip_tests {
         Ok(())
     }
 
+    #[test]
+    fn roundtrip_create_external_table_without_secrets() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        let sql = "CREATE EXTERNAL TABLE t STORED AS CSV LOCATION 's3://bucket/t' \
+            OPTIONS ('region' 'us-east-2', 'secret_access_key' 'abc', 'session_token' 'def')";
+        let plan = ctx.create_logical_plan(sql)?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        match logical_round_trip {
+            LogicalPlan::CreateExternalTable(cmd) => {
+                let expected =
+                    HashMap::from([("region".to_string(), "us-east-2".to_string())]);
+                assert_eq!(cmd.options, expected);
+            }
+            plan => panic!("Expected a CreateExternalTable, got {:?}", plan),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_table_scan_ordering() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        let scan = match ctx.table("t1")?.to_logical_plan()? {
+            LogicalPlan::TableScan(scan) => scan,
+            plan => panic!("Expected a table scan, got {:?}", plan),
+        };
+        // the ordering pushed into the scan by sort_push_down
+        let plan = LogicalPlan::TableScan(TableScan {
+            ordering: vec![col("t1.a").sort(false, true)],
+            ..scan
+        });
+
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+        assert!(format!("{:?}", logical_round_trip)
+            .contains("ordering=[t1.a DESC NULLS FIRST]"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_table_statistics() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Int64, false),
+        ]));
+        let config =
+            ListingTableConfig::new(ListingTableUrl::parse("testdata/test.csv")?)
+                .with_listing_options(ListingOptions::new(Arc::new(CsvFormat::default())))
+                .with_schema(schema);
+        let statistics = Statistics {
+            num_rows: Some(2),
+            total_byte_size: None,
+            column_statistics: Some(vec![
+                ColumnStatistics {
+                    null_count: Some(0),
+                    min_value: Some(ScalarValue::Int64(Some(1))),
+                    max_value: Some(ScalarValue::Int64(Some(3))),
+                    distinct_count: None,
+                },
+                ColumnStatistics::default(),
+            ]),
+            is_exact: true,
+        };
+        let table = ListingTable::try_new(config)?.with_statistics(statistics.clone());
+        let scan =
+            LogicalPlanBuilder::scan("t", provider_as_source(Arc::new(table)), None)?
+                .build()?;
+
+        let bytes = logical_plan_to_bytes(&scan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", scan), format!("{:?}", logical_round_trip));
+        let provider = match &logical_round_trip {
+            LogicalPlan::TableScan(scan) => source_as_provider(&scan.source)?,
+            plan => panic!("expected a table scan, found {:?}", plan),
+        };
+        assert!(provider.as_any().downcast_ref::<ListingTable>().is_some());
+        assert_eq!(provider.statistics(), Some(statistics));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_with_codec_registry() -> Result<(), DataFusionError> {
+        let mut table_factories: HashMap<String, Arc<dyn TableProviderFactory>> =
+            HashMap::new();
+        table_factories.insert("testtable".to_string(), Arc::new(TestTableFactory {}));
+        let cfg = RuntimeConfig::new().with_table_factories(table_factories);
+        let env = RuntimeEnv::new(cfg).unwrap();
+        let ctx = SessionContext::with_config_rt(SessionConfig::new(), Arc::new(env));
+        ctx.sql("CREATE EXTERNAL TABLE t STORED AS testtable LOCATION 's3://bucket/t';")
+            .await?;
+
+        let scan = ctx.table("t")?.to_logical_plan()?;
+        let topk_plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(TopKPlanNode::new(3, scan.clone(), col("revenue"))),
+        });
+
+        let mut registry = ExtensionCodecRegistry::new();
+        registry
+            .register_node::<TopKPlanNode>("topk", Arc::new(TopKExtensionCodec {}))?;
+        registry.register_table_provider::<TestTableProvider>(
+            "test_table",
+            Arc::new(TestTableProviderCodec {}),
+        )?;
+
+        let bytes = logical_plan_to_bytes_with_extension_codec(&topk_plan, &registry)?;
+        let logical_round_trip =
+            logical_plan_from_bytes_with_extension_codec(&bytes, &ctx, &registry)?;
+        assert_eq!(
+            format!("{:?}", topk_plan),
+            format!("{:?}", logical_round_trip)
+        );
+
+        // names and types are registered once
+        let err = registry
+            .register_node::<TopKPlanNode>("topk2", Arc::new(TopKExtensionCodec {}))
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered as 'topk'"));
+        let err = registry
+            .register_table_provider::<MemTable>(
+                "test_table",
+                Arc::new(TestTableProviderCodec {}),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "An extension codec is already registered for table provider 'test_table'"
+        ));
+
+        // the nodes are only decoded by the codec they were encoded with
+        let mut other = ExtensionCodecRegistry::new();
+        other.register_table_provider::<TestTableProvider>(
+            "test_table",
+            Arc::new(TestTableProviderCodec {}),
+        )?;
+        let err = logical_plan_from_bytes_with_extension_codec(&bytes, &ctx, &other)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No extension codec registered for logical node 'topk'"));
+        let err =
+            logical_plan_to_bytes_with_extension_codec(&topk_plan, &other).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No extension codec registered for logical node TopK"));
+
+        let bytes = logical_plan_to_bytes_with_extension_codec(&scan, &other)?;
+        let logical_round_trip =
+            logical_plan_from_bytes_with_extension_codec(&bytes, &ctx, &other)?;
+        assert_eq!(format!("{:?}", scan), format!("{:?}", logical_round_trip));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_builtin_table_providers() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        let batches = ctx.table("t1")?.collect().await?;
+        let schema = batches[0].schema();
+        ctx.register_table("t2", Arc::new(MemTable::try_new(schema, vec![batches])?))?;
+        ctx.sql("CREATE VIEW t3 AS SELECT a FROM t2 WHERE b > 0")
+            .await?;
+
+        let codec = DefaultLogicalExtensionCodec {};
+        for table in ["t1", "t2", "t3"] {
+            let provider = ctx
+                .catalog("datafusion")
+                .and_then(|catalog| catalog.schema("public"))
+                .and_then(|schema| schema.table(table))
+                .unwrap();
+            let mut buf = vec![];
+            codec.try_encode_table_provider(provider.clone(), &mut buf)?;
+            let round_trip =
+                codec.try_decode_table_provider(&buf, provider.schema(), &ctx)?;
+            assert_eq!(provider.schema(), round_trip.schema());
+
+            let expected = ctx.read_table(provider)?.collect().await?;
+            let actual = ctx.read_table(round_trip)?.collect().await?;
+            assert_eq!(expected, actual);
+        }
+
+        // the scans of memory tables are serialized with the default codec
+        let plan = ctx.table("t2")?.to_logical_plan()?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn roundtrip_logical_plan_aggregation() -> Result<(), DataFusionError> {
         let ctx = SessionContext::new();
@@ -252,6 +469,174 @@ mod roundtrip_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn roundtrip_logical_plan_dedup() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        let plan = ctx
+            .table("t1")?
+            .dedup(
+                vec![col("a")],
+                DedupKeep::Last,
+                vec![col("b").sort(false, true)],
+            )?
+            .to_logical_plan()?;
+
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_logical_plan_unnest() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new(
+                "b",
+                DataType::List(Box::new(Field::new("item", DataType::Int64, true))),
+                true,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+                    Some(vec![Some(1), Some(2)]),
+                    None,
+                ])),
+            ],
+        )?;
+        ctx.register_table(
+            "t1",
+            Arc::new(MemTable::try_new(schema, vec![vec![batch]])?),
+        )?;
+        let scan = ctx.table("t1")?.to_logical_plan()?;
+        let plan = LogicalPlanBuilder::from(scan).unnest_column("b")?.build()?;
+
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+        assert_eq!(plan.schema(), logical_round_trip.schema());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_logical_plan_prepare() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        let plan = ctx.create_logical_plan(
+            "PREPARE q(BIGINT, BIGINT) AS SELECT a FROM t1 WHERE a > $1 AND b < $2",
+        )?;
+
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+
+        // the parameters are bound after deserialization
+        let param_values = HashMap::from([
+            ("1".to_string(), ScalarValue::Int64(Some(0))),
+            ("2".to_string(), ScalarValue::Int64(Some(3))),
+        ]);
+        let bound = logical_round_trip.with_param_values(&param_values)?;
+        let batches = ctx.execute_logical_plan(bound).await?.collect().await?;
+        let expected = vec!["+---+", "| a |", "+---+", "| 1 |", "+---+"];
+        datafusion::assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_logical_plan_statements() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+
+        let statements = [
+            "EXPLAIN SELECT a FROM t1",
+            "EXPLAIN VERBOSE SELECT a FROM t1 WHERE b > 0",
+            "EXPLAIN ANALYZE SELECT a FROM t1",
+            "CREATE TABLE t2 AS SELECT a FROM t1",
+            "CREATE OR REPLACE TABLE t2 AS SELECT b FROM t1",
+            "DROP TABLE IF EXISTS t2",
+            "DROP VIEW v",
+            "REFRESH MATERIALIZED VIEW mv",
+            "ANALYZE TABLE t1",
+            "SET datafusion.execution.batch_size = 10",
+        ];
+        for statement in statements {
+            let plan = ctx.create_logical_plan(statement)?;
+            let bytes = logical_plan_to_bytes(&plan)?;
+            let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+            assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_logical_plan_with_subqueries() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        ctx.register_csv("t2", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+
+        let queries = [
+            "SELECT a FROM t1 WHERE EXISTS (SELECT b FROM t2 WHERE t2.a = t1.a)",
+            "SELECT a FROM t1 WHERE a NOT IN (SELECT a FROM t2)",
+            "SELECT a, (SELECT MAX(b) FROM t2 WHERE t2.a = t1.a) FROM t1",
+        ];
+        for query in queries {
+            // the plan with the subqueries, and the plan with the joins they
+            // are decorrelated into
+            let plan = ctx.create_logical_plan(query)?;
+            let optimized = ctx.optimize(&plan)?;
+            for plan in [plan, optimized] {
+                let bytes = logical_plan_to_bytes(&plan)?;
+                let logical_round_trip = logical_plan_from_bytes(&bytes, &ctx)?;
+                assert_eq!(format!("{:?}", plan), format!("{:?}", logical_round_trip));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replay_schema_changes() -> Result<(), DataFusionError> {
+        let ctx = SessionContext::new();
+        let statements = [
+            "CREATE EXTERNAL TABLE t1 STORED AS CSV WITH HEADER ROW LOCATION 'testdata/test.csv'",
+            "CREATE SCHEMA staging",
+            "CREATE TABLE staging.t2 AS SELECT a + b AS c FROM t1",
+            "CREATE VIEW v AS SELECT c FROM staging.t2 WHERE c > 3",
+            "CREATE VIEW dropped AS SELECT 1",
+            "DROP VIEW dropped",
+        ];
+        // record the changes as they are applied
+        let mut changes = vec![];
+        for statement in statements {
+            let plan = ctx.create_logical_plan(statement)?;
+            changes.push(logical_plan_to_bytes(&plan)?);
+            ctx.execute_logical_plan(plan).await?;
+        }
+
+        // and replay them in another session
+        let replica = SessionContext::new();
+        for change in &changes {
+            let plan = logical_plan_from_bytes(change, &replica)?;
+            replica.execute_logical_plan(plan).await?;
+        }
+        let batches = replica.sql("SELECT * FROM v").await?.collect().await?;
+        let expected = vec!["+---+", "| c |", "+---+", "| 7 |", "+---+"];
+        datafusion::assert_batches_eq!(expected, &batches);
+        assert!(replica.table("staging.t2").is_ok());
+        assert!(replica.table("dropped").is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn roundtrip_logical_plan_with_extension() -> Result<(), DataFusionError> {
         let ctx = SessionContext::new();
@@ -1215,6 +1600,51 @@ mod roundtrip_tests {
         roundtrip_expr_test(test_expr, ctx);
     }
 
+    #[test]
+    fn aggregate_udf_registry_mismatch() {
+        let udaf = |input_type: DataType, return_type: DataType| {
+            create_udaf(
+                "my_agg",
+                input_type,
+                Arc::new(return_type.clone()),
+                Volatility::Immutable,
+                Arc::new(|_| Err(DataFusionError::NotImplemented("my_agg".to_string()))),
+                Arc::new(vec![return_type]),
+            )
+        };
+        let expr = Expr::AggregateUDF {
+            fun: Arc::new(udaf(DataType::Float64, DataType::Float64)),
+            args: vec![col("a")],
+            filter: None,
+        };
+        let proto: protobuf::LogicalExprNode = (&expr).try_into().unwrap();
+
+        let mut ctx = SessionContext::new();
+        ctx.register_udaf(udaf(DataType::Float64, DataType::Float64));
+        assert_eq!(
+            format!("{:?}", expr),
+            format!("{:?}", parse_expr(&proto, &ctx).unwrap())
+        );
+
+        let mut ctx = SessionContext::new();
+        ctx.register_udaf(udaf(DataType::Int32, DataType::Float64));
+        let err = parse_expr(&proto, &ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "General error: Aggregate UDF 'my_agg' of the registry does not match \
+             the serialized one: it does not accept arguments of types [Float64]"
+        );
+
+        let mut ctx = SessionContext::new();
+        ctx.register_udaf(udaf(DataType::Float64, DataType::Int64));
+        let err = parse_expr(&proto, &ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "General error: Aggregate UDF 'my_agg' of the registry does not match \
+             the serialized one: it returns Int64 instead of Float64"
+        );
+    }
+
     #[test]
     fn roundtrip_scalar_udf() {
         let fn_impl = |args: &[ArrayRef]| Ok(Arc::new(args[0].clone()) as ArrayRef);
@@ -1268,6 +1698,14 @@ mod roundtrip_tests {
         roundtrip_expr_test(test_expr, ctx);
     }
 
+    #[test]
+    fn roundtrip_placeholder() {
+        let test_expr = col("a").gt(placeholder("min", DataType::Int64));
+
+        let ctx = SessionContext::new();
+        roundtrip_expr_test(test_expr, ctx);
+    }
+
     #[test]
     fn roundtrip_substr() {
         // substr(string, position)
@@ -1286,4 +1724,19 @@ mod roundtrip_tests {
         roundtrip_expr_test(test_expr, ctx.clone());
         roundtrip_expr_test(test_expr_with_count, ctx);
     }
+
+    #[test]
+    fn roundtrip_lead_lag_window_options() {
+        let lag = Expr::WindowFunction {
+            fun: WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::Lag),
+            args: vec![col("col1"), lit(-2_i64), col("col2")],
+            partition_by: vec![col("col3")],
+            order_by: vec![],
+            window_frame: None,
+            ignore_nulls: true,
+        };
+
+        let ctx = SessionContext::new();
+        roundtrip_expr_test(lag, ctx);
+    }
 }
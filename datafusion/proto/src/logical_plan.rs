@@ -15,20 +15,23 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::physical_plan::from_proto::ipc_to_batches;
+use crate::physical_plan::to_proto::batches_to_ipc;
+use crate::protobuf::default_table_provider_node::Provider;
 use crate::protobuf::logical_plan_node::LogicalPlanType::CustomScan;
 use crate::protobuf::CustomTableScanNode;
 use crate::{
-    from_proto::{self, parse_expr},
+    from_proto::{self, parse_expr_with_subqueries},
     protobuf::{
         self, listing_table_scan_node::FileFormatType,
         logical_plan_node::LogicalPlanType, LogicalExtensionNode, LogicalPlanNode,
     },
-    to_proto,
+    to_proto::{self, serialize_expr_with_subqueries},
 };
-use arrow::datatypes::{Schema, SchemaRef};
+use arrow::datatypes::{DataType, Schema, SchemaRef};
 use datafusion::datasource::TableProvider;
 use datafusion::execution::FunctionRegistry;
-use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_plan::{ExecutionPlan, Statistics};
 use datafusion::{
     datasource::{
         file_format::{
@@ -36,32 +39,41 @@ use datafusion::{
         },
         listing::{ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl},
         view::ViewTable,
+        MemTable,
     },
     datasource::{provider_as_source, source_as_provider},
     prelude::SessionContext,
 };
-use datafusion_common::{context, Column, DataFusionError};
+use datafusion_common::{
+    context, Column, Constraint, Constraints, DFSchema, DataFusionError,
+};
 use datafusion_expr::{
     logical_plan::{
-        Aggregate, CreateCatalog, CreateCatalogSchema, CreateExternalTable, CreateView,
-        CrossJoin, Distinct, EmptyRelation, Extension, Join, JoinConstraint, JoinType,
-        Limit, Projection, Repartition, Sort, SubqueryAlias, TableScan, Values, Window,
+        Aggregate, AnalyzeTable, CreateCatalog, CreateCatalogSchema, CreateExternalTable,
+        CreateMemoryTable, CreateView, CrossJoin, Dedup, DedupKeep, Distinct, DropTable,
+        DropView, EmptyRelation, Extension, Join, JoinConstraint, JoinType, Limit,
+        Prepare, Projection, RefreshMaterializedView, Repartition, SetVariable, Sort,
+        Subquery, SubqueryAlias, TableScan, Unnest, UserDefinedLogicalNode, Values,
+        Window,
     },
-    Expr, LogicalPlan, LogicalPlanBuilder,
+    Expr, LogicalPlan, LogicalPlanBuilder, UNNAMED_TABLE,
 };
 use prost::bytes::BufMut;
+use prost::encoding::{decode_varint, encode_varint};
 use prost::Message;
+use std::any::TypeId;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 
-fn byte_to_string(b: u8) -> Result<String, DataFusionError> {
+pub(crate) fn byte_to_string(b: u8) -> Result<String, DataFusionError> {
     let b = &[b];
     let b = std::str::from_utf8(b)
         .map_err(|_| DataFusionError::Internal("Invalid CSV delimiter".to_owned()))?;
     Ok(b.to_owned())
 }
 
-fn str_to_byte(s: &str) -> Result<u8, DataFusionError> {
+pub(crate) fn str_to_byte(s: &str) -> Result<u8, DataFusionError> {
     if s.len() != 1 {
         return Err(DataFusionError::Internal(
             "Invalid CSV delimiter".to_owned(),
@@ -74,6 +86,16 @@ pub(crate) fn proto_error<S: Into<String>>(message: S) -> DataFusionError {
     DataFusionError::Internal(message.into())
 }
 
+/// Set the sort expressions the table scan `plan` returns its rows ordered by
+fn with_scan_ordering(plan: LogicalPlan, ordering: Vec<Expr>) -> LogicalPlan {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            LogicalPlan::TableScan(TableScan { ordering, ..scan })
+        }
+        plan => plan,
+    }
+}
+
 pub trait AsLogicalPlan: Debug + Send + Sync + Clone {
     fn try_decode(buf: &[u8]) -> Result<Self, DataFusionError>
     where
@@ -98,7 +120,14 @@ pub trait AsLogicalPlan: Debug + Send + Sync + Clone {
         Self: Sized;
 }
 
+/// Encodes and decodes the user-defined [`ExecutionPlan`]s of physical plans,
+/// which have no built-in protobuf representation
+///
+/// The inputs of the plans are serialized with the rest of the physical plan,
+/// so a codec only encodes the state of a node itself.
 pub trait PhysicalExtensionCodec: Debug + Send + Sync {
+    /// Decodes a node encoded by [`try_encode`](Self::try_encode), given its
+    /// decoded `inputs`
     fn try_decode(
         &self,
         buf: &[u8],
@@ -106,6 +135,8 @@ pub trait PhysicalExtensionCodec: Debug + Send + Sync {
         registry: &dyn FunctionRegistry,
     ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError>;
 
+    /// Encodes `node` into `buf`, without its children, or returns an error
+    /// if the codec doesn't know the node
     fn try_encode(
         &self,
         node: Arc<dyn ExecutionPlan>,
@@ -134,6 +165,20 @@ pub trait LogicalExtensionCodec: Debug + Send + Sync {
         ctx: &SessionContext,
     ) -> Result<Arc<dyn TableProvider>, DataFusionError>;
 
+    /// Decodes a table provider serialized with the `statistics` of the
+    /// provider, if it had any, so that the decoded provider can return them
+    ///
+    /// The default implementation ignores the statistics.
+    fn try_decode_table_provider_with_statistics(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        _statistics: Option<Statistics>,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
+        self.try_decode_table_provider(buf, schema, ctx)
+    }
+
     fn try_encode_table_provider(
         &self,
         node: Arc<dyn TableProvider>,
@@ -141,6 +186,52 @@ pub trait LogicalExtensionCodec: Debug + Send + Sync {
     ) -> Result<(), DataFusionError>;
 }
 
+/// Encodes the schema, the batches of each partition as Arrow IPC, and the
+/// constraints of an in-memory table
+pub(crate) fn mem_table_to_proto(
+    mem_table: &MemTable,
+) -> Result<protobuf::MemTableNode, DataFusionError> {
+    let schema = mem_table.schema();
+    Ok(protobuf::MemTableNode {
+        schema: Some(schema.as_ref().try_into()?),
+        partitions: mem_table
+            .batches()
+            .iter()
+            .map(|batches| batches_to_ipc(&schema, batches))
+            .collect::<Result<Vec<_>, _>>()?,
+        constraints: mem_table
+            .constraints()
+            .map(|constraints| constraints.iter().map(|c| c.into()).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Decodes the output of [`mem_table_to_proto`]
+pub(crate) fn mem_table_from_proto(
+    mem_table: &protobuf::MemTableNode,
+) -> Result<MemTable, DataFusionError> {
+    let schema: Schema = mem_table
+        .schema
+        .as_ref()
+        .ok_or_else(|| proto_error("Missing required field in protobuf"))?
+        .try_into()?;
+    let partitions = mem_table
+        .partitions
+        .iter()
+        .map(|data| ipc_to_batches(data))
+        .collect::<Result<Vec<_>, _>>()?;
+    let constraints = mem_table
+        .constraints
+        .iter()
+        .map(Constraint::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(MemTable::try_new(Arc::new(schema), partitions)?
+        .with_constraints(Constraints::new(constraints)))
+}
+
+/// The codec used when none is provided, which encodes the built-in
+/// [`ListingTable`], [`ViewTable`] and [`MemTable`] providers but no extension
+/// node
 #[derive(Debug, Clone)]
 pub struct DefaultLogicalExtensionCodec {}
 
@@ -168,23 +259,288 @@ impl LogicalExtensionCodec for DefaultLogicalExtensionCodec {
 
     fn try_decode_table_provider(
         &self,
-        _buf: &[u8],
+        buf: &[u8],
         _schema: SchemaRef,
-        _ctx: &SessionContext,
+        ctx: &SessionContext,
     ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
-        Err(DataFusionError::NotImplemented(
-            "LogicalExtensionCodec is not provided".to_string(),
-        ))
+        let node = protobuf::DefaultTableProviderNode::decode(buf).map_err(|e| {
+            DataFusionError::Internal(format!("Error decoding table provider: {}", e))
+        })?;
+        match node.provider {
+            Some(Provider::Scan(scan)) => match scan.try_into_logical_plan(ctx, self)? {
+                LogicalPlan::TableScan(scan) => source_as_provider(&scan.source),
+                other => Err(proto_error(format!(
+                    "Expected a table scan of the table provider, got {:?}",
+                    other
+                ))),
+            },
+            Some(Provider::MemTable(mem_table)) => {
+                Ok(Arc::new(mem_table_from_proto(&mem_table)?))
+            }
+            None => Err(proto_error("Missing required field in protobuf")),
+        }
     }
 
     fn try_encode_table_provider(
         &self,
-        _node: Arc<dyn TableProvider>,
-        _buf: &mut Vec<u8>,
+        node: Arc<dyn TableProvider>,
+        buf: &mut Vec<u8>,
     ) -> Result<(), DataFusionError> {
-        Err(DataFusionError::NotImplemented(
-            "LogicalExtensionCodec is not provided".to_string(),
-        ))
+        let source = node.as_any();
+        let provider = if source.is::<ListingTable>() || source.is::<ViewTable>() {
+            // encoded as a scan of the table
+            let scan = LogicalPlanBuilder::scan(
+                UNNAMED_TABLE,
+                provider_as_source(node.clone()),
+                None,
+            )?
+            .build()?;
+            Provider::Scan(protobuf::LogicalPlanNode::try_from_logical_plan(
+                &scan, self,
+            )?)
+        } else if let Some(mem_table) = source.downcast_ref::<MemTable>() {
+            Provider::MemTable(mem_table_to_proto(mem_table)?)
+        } else {
+            return Err(DataFusionError::NotImplemented(
+                "LogicalExtensionCodec is not provided".to_string(),
+            ));
+        };
+
+        protobuf::DefaultTableProviderNode {
+            provider: Some(provider),
+        }
+        .encode(buf)
+        .map_err(|e| {
+            DataFusionError::Internal(format!("Error encoding table provider: {}", e))
+        })
+    }
+}
+
+/// A [`LogicalExtensionCodec`] and [`PhysicalExtensionCodec`] dispatching the
+/// extension nodes, table providers and execution plans to the codecs
+/// registered for their types, so that the codecs of several libraries can be
+/// used together
+///
+/// The name under which a codec is registered is written before the bytes
+/// of each node it encodes, and selects the codec decoding them.
+#[derive(Debug, Default)]
+pub struct ExtensionCodecRegistry {
+    nodes: RegisteredCodecs<dyn LogicalExtensionCodec>,
+    table_providers: RegisteredCodecs<dyn LogicalExtensionCodec>,
+    execution_plans: RegisteredCodecs<dyn PhysicalExtensionCodec>,
+}
+
+#[derive(Debug)]
+struct RegisteredCodecs<C: ?Sized> {
+    codecs: HashMap<String, Arc<C>>,
+    names: HashMap<TypeId, String>,
+}
+
+impl<C: ?Sized> Default for RegisteredCodecs<C> {
+    fn default() -> Self {
+        Self {
+            codecs: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<C: ?Sized> RegisteredCodecs<C> {
+    fn register(
+        &mut self,
+        type_id: TypeId,
+        name: String,
+        codec: Arc<C>,
+        kind: &str,
+    ) -> Result<(), DataFusionError> {
+        if self.codecs.contains_key(&name) {
+            return Err(DataFusionError::Plan(format!(
+                "An extension codec is already registered for {} '{}'",
+                kind, name
+            )));
+        }
+        if let Some(other) = self.names.get(&type_id) {
+            return Err(DataFusionError::Plan(format!(
+                "The {} '{}' is already registered as '{}'",
+                kind, name, other
+            )));
+        }
+        self.names.insert(type_id, name.clone());
+        self.codecs.insert(name, codec);
+        Ok(())
+    }
+
+    /// Writes the name of the codec of `type_id` to `buf`, and returns the
+    /// codec
+    fn encoder(&self, type_id: TypeId, buf: &mut Vec<u8>) -> Option<&C> {
+        let name = self.names.get(&type_id)?;
+        encode_varint(name.len() as u64, buf);
+        buf.extend_from_slice(name.as_bytes());
+        Some(self.codecs[name].as_ref())
+    }
+
+    /// Reads the name of the codec at the start of `buf`, and returns the
+    /// codec with the rest of the bytes
+    fn decoder<'a>(
+        &self,
+        mut buf: &'a [u8],
+        kind: &str,
+    ) -> Result<(&C, &'a [u8]), DataFusionError> {
+        let invalid = || {
+            DataFusionError::Internal(format!("Invalid encoded {}: missing name", kind))
+        };
+        let len = decode_varint(&mut buf).map_err(|_| invalid())? as usize;
+        if buf.len() < len {
+            return Err(invalid());
+        }
+        let (name, rest) = buf.split_at(len);
+        let name = std::str::from_utf8(name).map_err(|_| invalid())?;
+        match self.codecs.get(name) {
+            Some(codec) => Ok((codec.as_ref(), rest)),
+            None => Err(DataFusionError::NotImplemented(format!(
+                "No extension codec registered for {} '{}'",
+                kind, name
+            ))),
+        }
+    }
+}
+
+impl ExtensionCodecRegistry {
+    /// Creates a registry without codecs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `codec` for the extension nodes of type `T`, under `name`
+    ///
+    /// Returns an error if `name` or `T` is already registered.
+    pub fn register_node<T: UserDefinedLogicalNode + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        codec: Arc<dyn LogicalExtensionCodec>,
+    ) -> Result<(), DataFusionError> {
+        self.nodes
+            .register(TypeId::of::<T>(), name.into(), codec, "logical node")
+    }
+
+    /// Registers `codec` for the table providers of type `T`, under `name`
+    ///
+    /// Returns an error if `name` or `T` is already registered.
+    pub fn register_table_provider<T: TableProvider + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        codec: Arc<dyn LogicalExtensionCodec>,
+    ) -> Result<(), DataFusionError> {
+        self.table_providers.register(
+            TypeId::of::<T>(),
+            name.into(),
+            codec,
+            "table provider",
+        )
+    }
+
+    /// Registers `codec` for the execution plans of type `T`, under `name`
+    ///
+    /// Returns an error if `name` or `T` is already registered.
+    pub fn register_execution_plan<T: ExecutionPlan + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        codec: Arc<dyn PhysicalExtensionCodec>,
+    ) -> Result<(), DataFusionError> {
+        self.execution_plans.register(
+            TypeId::of::<T>(),
+            name.into(),
+            codec,
+            "execution plan",
+        )
+    }
+}
+
+impl LogicalExtensionCodec for ExtensionCodecRegistry {
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: &[LogicalPlan],
+        ctx: &SessionContext,
+    ) -> Result<Extension, DataFusionError> {
+        let (codec, buf) = self.nodes.decoder(buf, "logical node")?;
+        codec.try_decode(buf, inputs, ctx)
+    }
+
+    fn try_encode(
+        &self,
+        node: &Extension,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), DataFusionError> {
+        match self.nodes.encoder(node.node.as_any().type_id(), buf) {
+            Some(codec) => codec.try_encode(node, buf),
+            None => Err(DataFusionError::NotImplemented(format!(
+                "No extension codec registered for logical node {:?}",
+                node.node
+            ))),
+        }
+    }
+
+    fn try_decode_table_provider(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
+        let (codec, buf) = self.table_providers.decoder(buf, "table provider")?;
+        codec.try_decode_table_provider(buf, schema, ctx)
+    }
+
+    fn try_decode_table_provider_with_statistics(
+        &self,
+        buf: &[u8],
+        schema: SchemaRef,
+        statistics: Option<Statistics>,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
+        let (codec, buf) = self.table_providers.decoder(buf, "table provider")?;
+        codec.try_decode_table_provider_with_statistics(buf, schema, statistics, ctx)
+    }
+
+    fn try_encode_table_provider(
+        &self,
+        node: Arc<dyn TableProvider>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), DataFusionError> {
+        let type_id = node.as_any().type_id();
+        match self.table_providers.encoder(type_id, buf) {
+            Some(codec) => codec.try_encode_table_provider(node, buf),
+            None => Err(DataFusionError::NotImplemented(
+                "No extension codec registered for the table provider".to_string(),
+            )),
+        }
+    }
+}
+
+impl PhysicalExtensionCodec for ExtensionCodecRegistry {
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: &[Arc<dyn ExecutionPlan>],
+        registry: &dyn FunctionRegistry,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let (codec, buf) = self.execution_plans.decoder(buf, "execution plan")?;
+        codec.try_decode(buf, inputs, registry)
+    }
+
+    fn try_encode(
+        &self,
+        node: Arc<dyn ExecutionPlan>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), DataFusionError> {
+        let type_id = node.as_any().type_id();
+        match self.execution_plans.encoder(type_id, buf) {
+            Some(codec) => codec.try_encode(node, buf),
+            None => Err(DataFusionError::NotImplemented(format!(
+                "No extension codec registered for execution plan {:?}",
+                node
+            ))),
+        }
     }
 }
 
@@ -260,6 +616,24 @@ impl From<JoinType> for protobuf::JoinType {
     }
 }
 
+impl From<protobuf::DedupKeep> for DedupKeep {
+    fn from(t: protobuf::DedupKeep) -> Self {
+        match t {
+            protobuf::DedupKeep::KeepFirst => DedupKeep::First,
+            protobuf::DedupKeep::KeepLast => DedupKeep::Last,
+        }
+    }
+}
+
+impl From<DedupKeep> for protobuf::DedupKeep {
+    fn from(t: DedupKeep) -> Self {
+        match t {
+            DedupKeep::First => protobuf::DedupKeep::KeepFirst,
+            DedupKeep::Last => protobuf::DedupKeep::KeepLast,
+        }
+    }
+}
+
 impl From<protobuf::JoinConstraint> for JoinConstraint {
     fn from(t: protobuf::JoinConstraint) -> Self {
         match t {
@@ -312,27 +686,28 @@ impl AsLogicalPlan for LogicalPlanNode {
         match plan {
             LogicalPlanType::Values(values) => {
                 let n_cols = values.n_cols as usize;
-                let values: Vec<Vec<Expr>> =
-                    if values.values_list.is_empty() {
-                        Ok(Vec::new())
-                    } else if values.values_list.len() % n_cols != 0 {
-                        Err(DataFusionError::Internal(format!(
-                            "Invalid values list length, expect {} to be divisible by {}",
-                            values.values_list.len(),
-                            n_cols
-                        )))
-                    } else {
-                        values
-                            .values_list
-                            .chunks_exact(n_cols)
-                            .map(|r| {
-                                r.iter()
-                                    .map(|expr| parse_expr(expr, ctx))
-                                    .collect::<Result<Vec<_>, from_proto::Error>>()
-                            })
-                            .collect::<Result<Vec<_>, _>>()
-                            .map_err(|e| e.into())
-                    }?;
+                let values: Vec<Vec<Expr>> = if values.values_list.is_empty() {
+                    Ok(Vec::new())
+                } else if values.values_list.len() % n_cols != 0 {
+                    Err(DataFusionError::Internal(format!(
+                        "Invalid values list length, expect {} to be divisible by {}",
+                        values.values_list.len(),
+                        n_cols
+                    )))
+                } else {
+                    values
+                        .values_list
+                        .chunks_exact(n_cols)
+                        .map(|r| {
+                            r.iter()
+                                .map(|expr| {
+                                    parse_expr_with_subqueries(expr, ctx, extension_codec)
+                                })
+                                .collect::<Result<Vec<_>, from_proto::Error>>()
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| e.into())
+                }?;
                 LogicalPlanBuilder::values(values)?.build()
             }
             LogicalPlanType::Projection(projection) => {
@@ -341,7 +716,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let x: Vec<Expr> = projection
                     .expr
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<_>, _>>()?;
                 LogicalPlanBuilder::from(input)
                     .project_with_alias(
@@ -360,7 +735,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let expr: Expr = selection
                     .expr
                     .as_ref()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .transpose()?
                     .ok_or_else(|| {
                         DataFusionError::Internal("expression required".to_string())
@@ -374,7 +749,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let window_expr = window
                     .window_expr
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<Expr>, _>>()?;
                 LogicalPlanBuilder::from(input).window(window_expr)?.build()
             }
@@ -384,12 +759,12 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let group_expr = aggregate
                     .group_expr
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<Expr>, _>>()?;
                 let aggr_expr = aggregate
                     .aggr_expr
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<Expr>, _>>()?;
                 LogicalPlanBuilder::from(input)
                     .aggregate(group_expr, aggr_expr)?
@@ -411,7 +786,12 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let filters = scan
                     .filters
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ordering = scan
+                    .ordering
+                    .iter()
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<_>, _>>()?;
 
                 let file_format: Arc<dyn FileFormat> =
@@ -449,6 +829,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                     table_partition_cols: scan.table_partition_cols.clone(),
                     collect_stat: scan.collect_stat,
                     target_partitions: scan.target_partitions as usize,
+                    use_index: scan.use_index,
                 };
 
                 let config =
@@ -456,15 +837,19 @@ impl AsLogicalPlan for LogicalPlanNode {
                         .with_listing_options(options)
                         .with_schema(Arc::new(schema));
 
-                let provider = ListingTable::try_new(config)?;
+                let mut provider = ListingTable::try_new(config)?;
+                if let Some(statistics) = &scan.statistics {
+                    provider = provider.with_statistics(statistics.try_into()?);
+                }
 
-                LogicalPlanBuilder::scan_with_filters(
+                let plan = LogicalPlanBuilder::scan_with_filters(
                     &scan.table_name,
                     provider_as_source(Arc::new(provider)),
                     projection,
                     filters,
                 )?
-                .build()
+                .build()?;
+                Ok(with_scan_ordering(plan, ordering))
             }
             LogicalPlanType::CustomScan(scan) => {
                 let schema: Schema = convert_required!(scan.schema)?;
@@ -482,21 +867,34 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let filters = scan
                     .filters
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<_>, _>>()?;
-                let provider = extension_codec.try_decode_table_provider(
-                    &scan.custom_table_data,
-                    schema,
-                    ctx,
-                )?;
+                let ordering = scan
+                    .ordering
+                    .iter()
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let statistics = scan
+                    .statistics
+                    .as_ref()
+                    .map(|statistics| statistics.try_into())
+                    .transpose()?;
+                let provider = extension_codec
+                    .try_decode_table_provider_with_statistics(
+                        &scan.custom_table_data,
+                        schema,
+                        statistics,
+                        ctx,
+                    )?;
 
-                LogicalPlanBuilder::scan_with_filters(
+                let plan = LogicalPlanBuilder::scan_with_filters(
                     &scan.table_name,
                     provider_as_source(provider),
                     projection,
                     filters,
                 )?
-                .build()
+                .build()?;
+                Ok(with_scan_ordering(plan, ordering))
             }
             LogicalPlanType::Sort(sort) => {
                 let input: LogicalPlan =
@@ -504,7 +902,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let sort_expr: Vec<Expr> = sort
                     .expr
                     .iter()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .collect::<Result<Vec<Expr>, _>>()?;
                 LogicalPlanBuilder::from(input).sort(sort_expr)?.build()
             }
@@ -526,7 +924,9 @@ impl AsLogicalPlan for LogicalPlanNode {
                     }) => Partitioning::Hash(
                         pb_hash_expr
                             .iter()
-                            .map(|expr| parse_expr(expr, ctx))
+                            .map(|expr| {
+                                parse_expr_with_subqueries(expr, ctx, extension_codec)
+                            })
                             .collect::<Result<Vec<_>, _>>()?,
                         partition_count as usize,
                     ),
@@ -568,8 +968,15 @@ impl AsLogicalPlan for LogicalPlanNode {
                     }
                 }
 
+                let constraints = create_extern_table
+                    .constraints
+                    .iter()
+                    .map(Constraint::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+
                 Ok(LogicalPlan::CreateExternalTable(CreateExternalTable {
                     schema: pb_schema.try_into()?,
+                    constraints: Constraints::new(constraints),
                     name: create_extern_table.name.clone(),
                     location: create_extern_table.location.clone(),
                     file_type: create_extern_table.file_type.clone(),
@@ -580,9 +987,12 @@ impl AsLogicalPlan for LogicalPlanNode {
                     table_partition_cols: create_extern_table
                         .table_partition_cols
                         .clone(),
+                    bucket_cols: create_extern_table.bucket_cols.clone(),
+                    num_buckets: create_extern_table.num_buckets as usize,
                     if_not_exists: create_extern_table.if_not_exists,
                     file_compression_type: create_extern_table.file_compression_type.to_string(),
                     definition,
+                    options: create_extern_table.options.clone().into_iter().collect(),
                 }))
             }
             LogicalPlanType::CreateView(create_view) => {
@@ -596,12 +1006,19 @@ impl AsLogicalPlan for LogicalPlanNode {
                 } else {
                     None
                 };
+                let location = if !create_view.location.is_empty() {
+                    Some(create_view.location.clone())
+                } else {
+                    None
+                };
 
                 Ok(LogicalPlan::CreateView(CreateView {
                     name: create_view.name.clone(),
                     input: Arc::new(plan),
                     or_replace: create_view.or_replace,
                     definition,
+                    materialized: create_view.materialized,
+                    location,
                 }))
             }
             LogicalPlanType::CreateCatalogSchema(create_catalog_schema) => {
@@ -611,9 +1028,16 @@ impl AsLogicalPlan for LogicalPlanNode {
                     ))
                 })?;
 
+                let location = if !create_catalog_schema.location.is_empty() {
+                    Some(create_catalog_schema.location.clone())
+                } else {
+                    None
+                };
+
                 Ok(LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
                     schema_name: create_catalog_schema.schema_name.clone(),
                     if_not_exists: create_catalog_schema.if_not_exists,
+                    location,
                     schema: pb_schema.try_into()?,
                 }))
             }
@@ -624,9 +1048,16 @@ impl AsLogicalPlan for LogicalPlanNode {
                     ))
                 })?;
 
+                let location = if !create_catalog.location.is_empty() {
+                    Some(create_catalog.location.clone())
+                } else {
+                    None
+                };
+
                 Ok(LogicalPlan::CreateCatalog(CreateCatalog {
                     catalog_name: create_catalog.catalog_name.clone(),
                     if_not_exists: create_catalog.if_not_exists,
+                    location,
                     schema: pb_schema.try_into()?,
                 }))
             }
@@ -688,7 +1119,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let filter: Option<Expr> = join
                     .filter
                     .as_ref()
-                    .map(|expr| parse_expr(expr, ctx))
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
                     .map_or(Ok(None), |v| v.map(Some))?;
 
                 let builder = LogicalPlanBuilder::from(into_logical_plan!(
@@ -755,6 +1186,63 @@ impl AsLogicalPlan for LogicalPlanNode {
                     into_logical_plan!(distinct.input, ctx, extension_codec)?;
                 LogicalPlanBuilder::from(input).distinct()?.build()
             }
+            LogicalPlanType::Subquery(subquery) => {
+                let subquery: LogicalPlan =
+                    into_logical_plan!(subquery.subquery, ctx, extension_codec)?;
+                Ok(LogicalPlan::Subquery(Subquery {
+                    subquery: Arc::new(subquery),
+                }))
+            }
+            LogicalPlanType::Unnest(unnest) => {
+                let input: LogicalPlan =
+                    into_logical_plan!(unnest.input, ctx, extension_codec)?;
+                let column: Column = unnest.column.as_ref().map(|c| c.into()).ok_or_else(|| {
+                    DataFusionError::Internal(String::from(
+                        "Protobuf deserialization error, UnnestNode was missing required field 'column'",
+                    ))
+                })?;
+                LogicalPlanBuilder::from(input)
+                    .unnest_column(column)?
+                    .build()
+            }
+            LogicalPlanType::Prepare(prepare) => {
+                let input: LogicalPlan =
+                    into_logical_plan!(prepare.input, ctx, extension_codec)?;
+                let data_types = prepare
+                    .data_types
+                    .iter()
+                    .map(DataType::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(LogicalPlan::Prepare(Prepare {
+                    name: prepare.name.clone(),
+                    data_types,
+                    input: Arc::new(input),
+                }))
+            }
+            LogicalPlanType::Dedup(dedup) => {
+                let input: LogicalPlan =
+                    into_logical_plan!(dedup.input, ctx, extension_codec)?;
+                let on = dedup
+                    .on
+                    .iter()
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
+                    .collect::<Result<Vec<Expr>, _>>()?;
+                let keep =
+                    protobuf::DedupKeep::from_i32(dedup.keep).ok_or_else(|| {
+                        proto_error(format!(
+                            "Received a DedupNode message with unknown DedupKeep {}",
+                            dedup.keep
+                        ))
+                    })?;
+                let order_by = dedup
+                    .order_by
+                    .iter()
+                    .map(|expr| parse_expr_with_subqueries(expr, ctx, extension_codec))
+                    .collect::<Result<Vec<Expr>, _>>()?;
+                LogicalPlanBuilder::from(input)
+                    .dedup(on, keep.into(), order_by)?
+                    .build()
+            }
             LogicalPlanType::ViewScan(scan) => {
                 let schema: Schema = convert_required!(scan.schema)?;
 
@@ -786,6 +1274,56 @@ impl AsLogicalPlan for LogicalPlanNode {
                 )?
                 .build()
             }
+            LogicalPlanType::CreateMemoryTable(create_memory_table) => {
+                let input: LogicalPlan =
+                    into_logical_plan!(create_memory_table.input, ctx, extension_codec)?;
+                Ok(LogicalPlan::CreateMemoryTable(CreateMemoryTable {
+                    name: create_memory_table.name.clone(),
+                    input: Arc::new(input),
+                    if_not_exists: create_memory_table.if_not_exists,
+                    or_replace: create_memory_table.or_replace,
+                }))
+            }
+            LogicalPlanType::DropTable(drop_table) => {
+                let schema: DFSchema = convert_required!(drop_table.schema)?;
+                Ok(LogicalPlan::DropTable(DropTable {
+                    name: drop_table.name.clone(),
+                    if_exists: drop_table.if_exists,
+                    schema: Arc::new(schema),
+                }))
+            }
+            LogicalPlanType::DropView(drop_view) => {
+                let schema: DFSchema = convert_required!(drop_view.schema)?;
+                Ok(LogicalPlan::DropView(DropView {
+                    name: drop_view.name.clone(),
+                    if_exists: drop_view.if_exists,
+                    schema: Arc::new(schema),
+                }))
+            }
+            LogicalPlanType::RefreshMaterializedView(refresh) => {
+                let schema: DFSchema = convert_required!(refresh.schema)?;
+                Ok(LogicalPlan::RefreshMaterializedView(
+                    RefreshMaterializedView {
+                        name: refresh.name.clone(),
+                        schema: Arc::new(schema),
+                    },
+                ))
+            }
+            LogicalPlanType::AnalyzeTable(analyze) => {
+                let schema: DFSchema = convert_required!(analyze.schema)?;
+                Ok(LogicalPlan::AnalyzeTable(AnalyzeTable {
+                    name: analyze.name.clone(),
+                    schema: Arc::new(schema),
+                }))
+            }
+            LogicalPlanType::SetVariable(set_variable) => {
+                let schema: DFSchema = convert_required!(set_variable.schema)?;
+                Ok(LogicalPlan::SetVariable(SetVariable {
+                    variable: set_variable.variable.clone(),
+                    value: set_variable.value.clone(),
+                    schema: Arc::new(schema),
+                }))
+            }
         }
     }
 
@@ -806,7 +1344,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 let values_list = values
                     .iter()
                     .flatten()
-                    .map(|v| v.try_into())
+                    .map(|v| serialize_expr_with_subqueries(v, extension_codec))
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(protobuf::LogicalPlanNode {
                     logical_plan_type: Some(LogicalPlanType::Values(
@@ -821,6 +1359,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                 table_name,
                 source,
                 filters,
+                ordering,
                 projection,
                 ..
             }) => {
@@ -844,8 +1383,16 @@ impl AsLogicalPlan for LogicalPlanNode {
 
                 let filters: Vec<protobuf::LogicalExprNode> = filters
                     .iter()
-                    .map(|filter| filter.try_into())
+                    .map(|filter| serialize_expr_with_subqueries(filter, extension_codec))
                     .collect::<Result<Vec<_>, _>>()?;
+                let ordering: Vec<protobuf::LogicalExprNode> = ordering
+                    .iter()
+                    .map(|expr| serialize_expr_with_subqueries(expr, extension_codec))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let statistics = provider
+                    .statistics()
+                    .map(|statistics| (&statistics).try_into())
+                    .transpose()?;
 
                 if let Some(listing_table) = source.downcast_ref::<ListingTable>() {
                     let any = listing_table.options().format.as_any();
@@ -894,6 +1441,9 @@ impl AsLogicalPlan for LogicalPlanNode {
                                     .options()
                                     .target_partitions
                                     as u32,
+                                use_index: listing_table.options().use_index,
+                                statistics,
+                                ordering,
                             },
                         )),
                     })
@@ -928,6 +1478,8 @@ impl AsLogicalPlan for LogicalPlanNode {
                         schema: Some(schema),
                         filters,
                         custom_table_data: bytes,
+                        statistics,
+                        ordering,
                     });
                     let node = LogicalPlanNode {
                         logical_plan_type: Some(scan),
@@ -946,11 +1498,12 @@ impl AsLogicalPlan for LogicalPlanNode {
                                 extension_codec,
                             )?,
                         )),
-                        expr: expr.iter().map(|expr| expr.try_into()).collect::<Result<
-                            Vec<_>,
-                            to_proto::Error,
-                        >>(
-                        )?,
+                        expr: expr
+                            .iter()
+                            .map(|expr| {
+                                serialize_expr_with_subqueries(expr, extension_codec)
+                            })
+                            .collect::<Result<Vec<_>, to_proto::Error>>()?,
                         optional_alias: alias
                             .clone()
                             .map(protobuf::projection_node::OptionalAlias::Alias),
@@ -967,7 +1520,10 @@ impl AsLogicalPlan for LogicalPlanNode {
                     logical_plan_type: Some(LogicalPlanType::Selection(Box::new(
                         protobuf::SelectionNode {
                             input: Some(Box::new(input)),
-                            expr: Some(filter.predicate().try_into()?),
+                            expr: Some(serialize_expr_with_subqueries(
+                                filter.predicate(),
+                                extension_codec,
+                            )?),
                         },
                     ))),
                 })
@@ -986,6 +1542,76 @@ impl AsLogicalPlan for LogicalPlanNode {
                     ))),
                 })
             }
+            LogicalPlan::Unnest(Unnest { input, column, .. }) => {
+                let input: protobuf::LogicalPlanNode =
+                    protobuf::LogicalPlanNode::try_from_logical_plan(
+                        input.as_ref(),
+                        extension_codec,
+                    )?;
+                Ok(protobuf::LogicalPlanNode {
+                    logical_plan_type: Some(LogicalPlanType::Unnest(Box::new(
+                        protobuf::UnnestNode {
+                            input: Some(Box::new(input)),
+                            column: Some(column.into()),
+                        },
+                    ))),
+                })
+            }
+            LogicalPlan::Dedup(Dedup {
+                input,
+                on,
+                keep,
+                order_by,
+            }) => {
+                let input: protobuf::LogicalPlanNode =
+                    protobuf::LogicalPlanNode::try_from_logical_plan(
+                        input.as_ref(),
+                        extension_codec,
+                    )?;
+                Ok(protobuf::LogicalPlanNode {
+                    logical_plan_type: Some(LogicalPlanType::Dedup(Box::new(
+                        protobuf::DedupNode {
+                            input: Some(Box::new(input)),
+                            on: on
+                                .iter()
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
+                                .collect::<Result<Vec<_>, _>>()?,
+                            keep: protobuf::DedupKeep::from(*keep).into(),
+                            order_by: order_by
+                                .iter()
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
+                                .collect::<Result<Vec<_>, _>>()?,
+                        },
+                    ))),
+                })
+            }
+            LogicalPlan::Prepare(Prepare {
+                name,
+                data_types,
+                input,
+            }) => {
+                let input: protobuf::LogicalPlanNode =
+                    protobuf::LogicalPlanNode::try_from_logical_plan(
+                        input.as_ref(),
+                        extension_codec,
+                    )?;
+                Ok(protobuf::LogicalPlanNode {
+                    logical_plan_type: Some(LogicalPlanType::Prepare(Box::new(
+                        protobuf::PrepareNode {
+                            name: name.clone(),
+                            data_types: data_types
+                                .iter()
+                                .map(|t| t.try_into())
+                                .collect::<Result<Vec<_>, _>>()?,
+                            input: Some(Box::new(input)),
+                        },
+                    ))),
+                })
+            }
             LogicalPlan::Window(Window {
                 input, window_expr, ..
             }) => {
@@ -1000,7 +1626,9 @@ impl AsLogicalPlan for LogicalPlanNode {
                             input: Some(Box::new(input)),
                             window_expr: window_expr
                                 .iter()
-                                .map(|expr| expr.try_into())
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
                                 .collect::<Result<Vec<_>, _>>()?,
                         },
                     ))),
@@ -1023,11 +1651,15 @@ impl AsLogicalPlan for LogicalPlanNode {
                             input: Some(Box::new(input)),
                             group_expr: group_expr
                                 .iter()
-                                .map(|expr| expr.try_into())
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
                                 .collect::<Result<Vec<_>, _>>()?,
                             aggr_expr: aggr_expr
                                 .iter()
-                                .map(|expr| expr.try_into())
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
                                 .collect::<Result<Vec<_>, _>>()?,
                         },
                     ))),
@@ -1060,7 +1692,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                     join_constraint.to_owned().into();
                 let filter = filter
                     .as_ref()
-                    .map(|e| e.try_into())
+                    .map(|e| serialize_expr_with_subqueries(e, extension_codec))
                     .map_or(Ok(None), |v| v.map(Some))?;
                 Ok(protobuf::LogicalPlanNode {
                     logical_plan_type: Some(LogicalPlanType::Join(Box::new(
@@ -1077,9 +1709,20 @@ impl AsLogicalPlan for LogicalPlanNode {
                     ))),
                 })
             }
-            LogicalPlan::Subquery(_) => Err(DataFusionError::NotImplemented(
-                "LogicalPlan serde is not yet implemented for subqueries".to_string(),
-            )),
+            LogicalPlan::Subquery(Subquery { subquery }) => {
+                let subquery: protobuf::LogicalPlanNode =
+                    protobuf::LogicalPlanNode::try_from_logical_plan(
+                        subquery.as_ref(),
+                        extension_codec,
+                    )?;
+                Ok(protobuf::LogicalPlanNode {
+                    logical_plan_type: Some(LogicalPlanType::Subquery(Box::new(
+                        protobuf::SubqueryNode {
+                            subquery: Some(Box::new(subquery)),
+                        },
+                    ))),
+                })
+            }
             LogicalPlan::SubqueryAlias(SubqueryAlias { input, alias, .. }) => {
                 let input: protobuf::LogicalPlanNode =
                     protobuf::LogicalPlanNode::try_from_logical_plan(
@@ -1119,7 +1762,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                     )?;
                 let selection_expr: Vec<protobuf::LogicalExprNode> = expr
                     .iter()
-                    .map(|expr| expr.try_into())
+                    .map(|expr| serialize_expr_with_subqueries(expr, extension_codec))
                     .collect::<Result<Vec<_>, to_proto::Error>>()?;
                 Ok(protobuf::LogicalPlanNode {
                     logical_plan_type: Some(LogicalPlanType::Sort(Box::new(
@@ -1151,7 +1794,9 @@ impl AsLogicalPlan for LogicalPlanNode {
                         PartitionMethod::Hash(protobuf::HashRepartition {
                             hash_expr: exprs
                                 .iter()
-                                .map(|expr| expr.try_into())
+                                .map(|expr| {
+                                    serialize_expr_with_subqueries(expr, extension_codec)
+                                })
                                 .collect::<Result<Vec<_>, to_proto::Error>>()?,
                             partition_count: *partition_count as u64,
                         })
@@ -1184,18 +1829,24 @@ impl AsLogicalPlan for LogicalPlanNode {
                     },
                 )),
             }),
-            LogicalPlan::CreateExternalTable(CreateExternalTable {
-                name,
-                location,
-                file_type,
-                has_header,
-                delimiter,
-                schema: df_schema,
-                table_partition_cols,
-                if_not_exists,
-                definition,
-                file_compression_type,
-            }) => Ok(protobuf::LogicalPlanNode {
+            LogicalPlan::CreateExternalTable(
+                create_external_table @ CreateExternalTable {
+                    name,
+                    location,
+                    file_type,
+                    has_header,
+                    delimiter,
+                    schema: df_schema,
+                    constraints,
+                    table_partition_cols,
+                    bucket_cols,
+                    num_buckets,
+                    if_not_exists,
+                    definition,
+                    file_compression_type,
+                    ..
+                },
+            ) => Ok(protobuf::LogicalPlanNode {
                 logical_plan_type: Some(LogicalPlanType::CreateExternalTable(
                     protobuf::CreateExternalTableNode {
                         name: name.clone(),
@@ -1204,10 +1855,15 @@ impl AsLogicalPlan for LogicalPlanNode {
                         has_header: *has_header,
                         schema: Some(df_schema.try_into()?),
                         table_partition_cols: table_partition_cols.clone(),
+                        bucket_cols: bucket_cols.clone(),
+                        num_buckets: *num_buckets as u64,
                         if_not_exists: *if_not_exists,
                         delimiter: String::from(*delimiter),
                         definition: definition.clone().unwrap_or_else(|| "".to_string()),
                         file_compression_type: file_compression_type.to_string(),
+                        // the credentials are not serialized
+                        options: create_external_table.public_options(),
+                        constraints: constraints.iter().map(|c| c.into()).collect(),
                     },
                 )),
             }),
@@ -1216,6 +1872,8 @@ impl AsLogicalPlan for LogicalPlanNode {
                 input,
                 or_replace,
                 definition,
+                materialized,
+                location,
             }) => Ok(protobuf::LogicalPlanNode {
                 logical_plan_type: Some(LogicalPlanType::CreateView(Box::new(
                     protobuf::CreateViewNode {
@@ -1226,12 +1884,15 @@ impl AsLogicalPlan for LogicalPlanNode {
                         )?)),
                         or_replace: *or_replace,
                         definition: definition.clone().unwrap_or_else(|| "".to_string()),
+                        materialized: *materialized,
+                        location: location.clone().unwrap_or_else(|| "".to_string()),
                     },
                 ))),
             }),
             LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
                 schema_name,
                 if_not_exists,
+                location,
                 schema: df_schema,
             }) => Ok(protobuf::LogicalPlanNode {
                 logical_plan_type: Some(LogicalPlanType::CreateCatalogSchema(
@@ -1239,12 +1900,14 @@ impl AsLogicalPlan for LogicalPlanNode {
                         schema_name: schema_name.clone(),
                         if_not_exists: *if_not_exists,
                         schema: Some(df_schema.try_into()?),
+                        location: location.clone().unwrap_or_default(),
                     },
                 )),
             }),
             LogicalPlan::CreateCatalog(CreateCatalog {
                 catalog_name,
                 if_not_exists,
+                location,
                 schema: df_schema,
             }) => Ok(protobuf::LogicalPlanNode {
                 logical_plan_type: Some(LogicalPlanType::CreateCatalog(
@@ -1252,6 +1915,7 @@ impl AsLogicalPlan for LogicalPlanNode {
                         catalog_name: catalog_name.clone(),
                         if_not_exists: *if_not_exists,
                         schema: Some(df_schema.try_into()?),
+                        location: location.clone().unwrap_or_default(),
                     },
                 )),
             }),
@@ -1340,15 +2004,84 @@ impl AsLogicalPlan for LogicalPlanNode {
                     )),
                 })
             }
-            LogicalPlan::CreateMemoryTable(_) => Err(proto_error(
-                "LogicalPlan serde is not yet implemented for CreateMemoryTable",
-            )),
-            LogicalPlan::DropTable(_) => Err(proto_error(
-                "LogicalPlan serde is not yet implemented for DropTable",
-            )),
-            LogicalPlan::DropView(_) => Err(proto_error(
-                "LogicalPlan serde is not yet implemented for DropView",
-            )),
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable {
+                name,
+                input,
+                if_not_exists,
+                or_replace,
+            }) => Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::CreateMemoryTable(Box::new(
+                    protobuf::CreateMemoryTableNode {
+                        name: name.clone(),
+                        input: Some(Box::new(LogicalPlanNode::try_from_logical_plan(
+                            input,
+                            extension_codec,
+                        )?)),
+                        if_not_exists: *if_not_exists,
+                        or_replace: *or_replace,
+                    },
+                ))),
+            }),
+            LogicalPlan::DropTable(DropTable {
+                name,
+                if_exists,
+                schema,
+            }) => Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::DropTable(
+                    protobuf::DropTableNode {
+                        name: name.clone(),
+                        if_exists: *if_exists,
+                        schema: Some(schema.try_into()?),
+                    },
+                )),
+            }),
+            LogicalPlan::DropView(DropView {
+                name,
+                if_exists,
+                schema,
+            }) => Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::DropView(
+                    protobuf::DropViewNode {
+                        name: name.clone(),
+                        if_exists: *if_exists,
+                        schema: Some(schema.try_into()?),
+                    },
+                )),
+            }),
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView {
+                name,
+                schema,
+            }) => Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::RefreshMaterializedView(
+                    protobuf::RefreshMaterializedViewNode {
+                        name: name.clone(),
+                        schema: Some(schema.try_into()?),
+                    },
+                )),
+            }),
+            LogicalPlan::AnalyzeTable(AnalyzeTable { name, schema }) => {
+                Ok(protobuf::LogicalPlanNode {
+                    logical_plan_type: Some(LogicalPlanType::AnalyzeTable(
+                        protobuf::AnalyzeTableNode {
+                            name: name.clone(),
+                            schema: Some(schema.try_into()?),
+                        },
+                    )),
+                })
+            }
+            LogicalPlan::SetVariable(SetVariable {
+                variable,
+                value,
+                schema,
+            }) => Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::SetVariable(
+                    protobuf::SetVariableNode {
+                        variable: variable.clone(),
+                        value: value.clone(),
+                        schema: Some(schema.try_into()?),
+                    },
+                )),
+            }),
         }
     }
 }
@@ -0,0 +1,660 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Code to convert protocol buffers back to DataFusion physical expressions
+//! and the parts of physical plans they are made of.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::compute::SortOptions;
+use arrow::datatypes::Schema;
+use arrow::ipc::reader::StreamReader;
+use arrow::record_batch::RecordBatch;
+use chrono::{TimeZone, Utc};
+use datafusion::config::ConfigOptions;
+use datafusion::datasource::file_format::file_type::FileCompressionType;
+use datafusion::datasource::listing::{FileRange, PartitionedFile};
+use datafusion::datasource::object_store::ObjectStoreUrl;
+use datafusion::execution::FunctionRegistry;
+use datafusion::physical_expr::execution_props::ExecutionProps;
+use datafusion::physical_plan::expressions::{
+    create_aggregate_expr, in_list, BinaryExpr, CaseExpr, CastExpr, Column,
+    DateTimeIntervalExpr, GetIndexedFieldExpr, IsNotNullExpr, IsNullExpr, Literal,
+    NegativeExpr, NotExpr, PhysicalSortExpr, ScalarSubqueryExpr, ScalarSubqueryValue,
+    TryCastExpr, DEFAULT_DATAFUSION_CAST_OPTIONS,
+};
+use datafusion::physical_plan::file_format::FileScanConfig;
+use datafusion::physical_plan::joins::utils::{ColumnIndex, JoinFilter, JoinSide};
+use datafusion::physical_plan::windows::create_window_expr;
+use datafusion::physical_plan::{
+    functions, udaf, udf, AggregateExpr, ColumnStatistics, Partitioning, PhysicalExpr,
+    Statistics, WindowExpr,
+};
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+use datafusion_expr::{BuiltinScalarFunction, WindowFrame, WindowFunction};
+use object_store::path::Path;
+use object_store::ObjectMeta;
+
+use crate::from_proto::from_proto_binary_op;
+use crate::logical_plan::proto_error;
+use crate::protobuf::{
+    self, physical_aggregate_expr_node, physical_expr_node::ExprType,
+    physical_window_expr_node,
+};
+
+/// Parses a physical expression from its protobuf representation, resolving
+/// user defined functions with `registry`.
+///
+/// `input_schema` is the schema of the input of the expression.
+pub fn parse_physical_expr(
+    proto: &protobuf::PhysicalExprNode,
+    registry: &dyn FunctionRegistry,
+    input_schema: &Schema,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    let expr_type = proto
+        .expr_type
+        .as_ref()
+        .ok_or_else(|| proto_error("Unexpected empty physical expression"))?;
+
+    let pexpr: Arc<dyn PhysicalExpr> = match expr_type {
+        ExprType::Column(c) => Arc::new(Column::from(c)),
+        ExprType::Literal(scalar) => Arc::new(Literal::new(scalar.try_into()?)),
+        ExprType::BinaryExpr(binary_expr) => Arc::new(BinaryExpr::new(
+            parse_required_physical_expr(
+                binary_expr.l.as_deref(),
+                registry,
+                "left",
+                input_schema,
+            )?,
+            from_proto_binary_op(&binary_expr.op)?,
+            parse_required_physical_expr(
+                binary_expr.r.as_deref(),
+                registry,
+                "right",
+                input_schema,
+            )?,
+        )),
+        ExprType::DateTimeIntervalExpr(expr) => Arc::new(DateTimeIntervalExpr::try_new(
+            parse_required_physical_expr(expr.l.as_deref(), registry, "l", input_schema)?,
+            from_proto_binary_op(&expr.op)?,
+            parse_required_physical_expr(expr.r.as_deref(), registry, "r", input_schema)?,
+            input_schema,
+        )?),
+        ExprType::AggregateExpr(_) => {
+            return Err(DataFusionError::NotImplemented(
+                "Cannot convert aggregate expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::WindowExpr(_) => {
+            return Err(DataFusionError::NotImplemented(
+                "Cannot convert window expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::Sort(_) => {
+            return Err(DataFusionError::NotImplemented(
+                "Cannot convert sort expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::IsNullExpr(e) => {
+            Arc::new(IsNullExpr::new(parse_required_physical_expr(
+                e.expr.as_deref(),
+                registry,
+                "expr",
+                input_schema,
+            )?))
+        }
+        ExprType::IsNotNullExpr(e) => {
+            Arc::new(IsNotNullExpr::new(parse_required_physical_expr(
+                e.expr.as_deref(),
+                registry,
+                "expr",
+                input_schema,
+            )?))
+        }
+        ExprType::NotExpr(e) => Arc::new(NotExpr::new(parse_required_physical_expr(
+            e.expr.as_deref(),
+            registry,
+            "expr",
+            input_schema,
+        )?)),
+        ExprType::Negative(e) => {
+            Arc::new(NegativeExpr::new(parse_required_physical_expr(
+                e.expr.as_deref(),
+                registry,
+                "expr",
+                input_schema,
+            )?))
+        }
+        ExprType::InList(e) => in_list(
+            parse_required_physical_expr(
+                e.expr.as_deref(),
+                registry,
+                "expr",
+                input_schema,
+            )?,
+            e.list
+                .iter()
+                .map(|x| parse_physical_expr(x, registry, input_schema))
+                .collect::<Result<Vec<_>>>()?,
+            &e.negated,
+            input_schema,
+        )?,
+        ExprType::Case(e) => Arc::new(CaseExpr::try_new(
+            e.expr
+                .as_ref()
+                .map(|e| parse_physical_expr(e.as_ref(), registry, input_schema))
+                .transpose()?,
+            e.when_then_expr
+                .iter()
+                .map(|e| {
+                    Ok((
+                        parse_required_physical_expr(
+                            e.when_expr.as_ref(),
+                            registry,
+                            "when_expr",
+                            input_schema,
+                        )?,
+                        parse_required_physical_expr(
+                            e.then_expr.as_ref(),
+                            registry,
+                            "then_expr",
+                            input_schema,
+                        )?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            e.else_expr
+                .as_ref()
+                .map(|e| parse_physical_expr(e.as_ref(), registry, input_schema))
+                .transpose()?,
+        )?),
+        ExprType::Cast(e) => {
+            let cast = CastExpr::new(
+                parse_required_physical_expr(
+                    e.expr.as_deref(),
+                    registry,
+                    "expr",
+                    input_schema,
+                )?,
+                e.arrow_type
+                    .as_ref()
+                    .ok_or_else(|| proto_error("Missing required field arrow_type"))?
+                    .try_into()?,
+                DEFAULT_DATAFUSION_CAST_OPTIONS,
+            );
+            if e.time_zone.is_empty() {
+                Arc::new(cast)
+            } else {
+                Arc::new(cast.with_time_zone(e.time_zone.parse()?))
+            }
+        }
+        ExprType::TryCast(e) => Arc::new(TryCastExpr::new(
+            parse_required_physical_expr(
+                e.expr.as_deref(),
+                registry,
+                "expr",
+                input_schema,
+            )?,
+            e.arrow_type
+                .as_ref()
+                .ok_or_else(|| proto_error("Missing required field arrow_type"))?
+                .try_into()?,
+        )),
+        ExprType::ScalarFunction(e) => {
+            let scalar_function =
+                protobuf::ScalarFunction::from_i32(e.fun).ok_or_else(|| {
+                    proto_error(format!("Received an unknown scalar function: {}", e.fun))
+                })?;
+            let args = e
+                .args
+                .iter()
+                .map(|x| parse_physical_expr(x, registry, input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            functions::create_physical_expr(
+                &BuiltinScalarFunction::from(&scalar_function),
+                &args,
+                input_schema,
+                &ExecutionProps::new(),
+            )?
+        }
+        ExprType::ScalarUdf(e) => {
+            let udf = registry.udf(&e.name)?;
+            let args = e
+                .args
+                .iter()
+                .map(|x| parse_physical_expr(x, registry, input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            udf::create_physical_expr(udf.as_ref(), &args, input_schema)?
+        }
+        ExprType::ScalarSubquery(e) => Arc::new(ScalarSubqueryExpr::new(
+            e.arrow_type
+                .as_ref()
+                .ok_or_else(|| proto_error("Missing required field arrow_type"))?
+                .try_into()?,
+            scalar_subquery_value(e.id),
+        )),
+        ExprType::GetIndexedFieldExpr(e) => Arc::new(GetIndexedFieldExpr::new(
+            parse_required_physical_expr(
+                e.arg.as_deref(),
+                registry,
+                "arg",
+                input_schema,
+            )?,
+            e.key
+                .as_ref()
+                .ok_or_else(|| proto_error("Missing required field key"))?
+                .try_into()?,
+        )),
+    };
+
+    Ok(pexpr)
+}
+
+/// The values of scalar subqueries, by id
+type ScalarSubqueryValues = HashMap<u64, Arc<ScalarSubqueryValue>>;
+
+thread_local! {
+    /// The values of the scalar subqueries of the physical plan being
+    /// decoded on this thread
+    static SCALAR_SUBQUERY_VALUES: RefCell<Option<ScalarSubqueryValues>> =
+        RefCell::new(None);
+}
+
+/// Shares the values of the scalar subqueries between the expressions
+/// decoded while it is alive, so that the references to the same subquery
+/// in a physical plan still execute it once
+pub(crate) struct ScalarSubqueryScope {
+    /// Whether this scope started the sharing, and ends it when dropped
+    owner: bool,
+}
+
+impl ScalarSubqueryScope {
+    /// Starts sharing the values, unless an enclosing scope already does
+    pub(crate) fn enter() -> Self {
+        let owner = SCALAR_SUBQUERY_VALUES.with(|values| {
+            let mut values = values.borrow_mut();
+            let owner = values.is_none();
+            if owner {
+                *values = Some(HashMap::new());
+            }
+            owner
+        });
+        Self { owner }
+    }
+}
+
+impl Drop for ScalarSubqueryScope {
+    fn drop(&mut self) {
+        if self.owner {
+            SCALAR_SUBQUERY_VALUES.with(|values| values.borrow_mut().take());
+        }
+    }
+}
+
+/// The value of the scalar subquery with `id`, shared by its references
+/// decoded in the current [`ScalarSubqueryScope`]
+pub(crate) fn scalar_subquery_value(id: u64) -> Arc<ScalarSubqueryValue> {
+    SCALAR_SUBQUERY_VALUES.with(|values| match values.borrow_mut().as_mut() {
+        Some(values) => values.entry(id).or_default().clone(),
+        None => Arc::new(ScalarSubqueryValue::new()),
+    })
+}
+
+fn parse_required_physical_expr(
+    expr: Option<&protobuf::PhysicalExprNode>,
+    registry: &dyn FunctionRegistry,
+    field: &str,
+    input_schema: &Schema,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    expr.map(|e| parse_physical_expr(e, registry, input_schema))
+        .transpose()?
+        .ok_or_else(|| proto_error(format!("Missing required field {:?}", field)))
+}
+
+/// Parses a sort expression of a sort or window node
+pub fn parse_physical_sort_expr(
+    proto: &protobuf::PhysicalSortExprNode,
+    registry: &dyn FunctionRegistry,
+    input_schema: &Schema,
+) -> Result<PhysicalSortExpr> {
+    Ok(PhysicalSortExpr {
+        expr: parse_required_physical_expr(
+            proto.expr.as_deref(),
+            registry,
+            "expr",
+            input_schema,
+        )?,
+        options: SortOptions {
+            descending: !proto.asc,
+            nulls_first: proto.nulls_first,
+        },
+    })
+}
+
+/// Parses an aggregate expression of an aggregate node, naming it `name`
+pub fn parse_physical_aggregate_expr(
+    proto: &protobuf::PhysicalExprNode,
+    registry: &dyn FunctionRegistry,
+    name: &str,
+    input_schema: &Schema,
+) -> Result<Arc<dyn AggregateExpr>> {
+    let agg_node = match &proto.expr_type {
+        Some(ExprType::AggregateExpr(agg_node)) => agg_node,
+        _ => {
+            return Err(proto_error(
+                "Invalid aggregate expression for AggregateExec",
+            ))
+        }
+    };
+    let args = agg_node
+        .expr
+        .iter()
+        .map(|e| parse_physical_expr(e, registry, input_schema))
+        .collect::<Result<Vec<_>>>()?;
+
+    match &agg_node.aggregate_function {
+        Some(physical_aggregate_expr_node::AggregateFunction::AggrFunction(i)) => {
+            let fun = protobuf::AggregateFunction::try_from(i)?.into();
+            create_aggregate_expr(&fun, agg_node.distinct, &args, input_schema, name)
+        }
+        Some(
+            physical_aggregate_expr_node::AggregateFunction::UserDefinedAggrFunction(
+                udaf_name,
+            ),
+        ) => {
+            let udaf = registry.udaf(udaf_name)?;
+            udaf::create_aggregate_expr(udaf.as_ref(), &args, input_schema, name)
+        }
+        None => Err(proto_error("Missing required field aggregate_function")),
+    }
+}
+
+/// Parses a window expression of a window node
+pub fn parse_physical_window_expr(
+    proto: &protobuf::PhysicalExprNode,
+    registry: &dyn FunctionRegistry,
+    input_schema: &Schema,
+) -> Result<Arc<dyn WindowExpr>> {
+    let window_node = match &proto.expr_type {
+        Some(ExprType::WindowExpr(window_node)) => window_node,
+        _ => return Err(proto_error("Invalid window expression for WindowAggExec")),
+    };
+
+    let fun = match window_node.window_function.as_ref() {
+        Some(physical_window_expr_node::WindowFunction::AggrFunction(i)) => {
+            WindowFunction::AggregateFunction(
+                protobuf::AggregateFunction::try_from(i)?.into(),
+            )
+        }
+        Some(physical_window_expr_node::WindowFunction::BuiltInFunction(i)) => {
+            WindowFunction::BuiltInWindowFunction(
+                protobuf::BuiltInWindowFunction::from_i32(*i)
+                    .ok_or_else(|| {
+                        proto_error(format!(
+                            "Received an unknown window builtin function: {}",
+                            i
+                        ))
+                    })?
+                    .into(),
+            )
+        }
+        None => return Err(proto_error("Missing required field window_function")),
+    };
+    let args = window_node
+        .args
+        .iter()
+        .map(|e| parse_physical_expr(e, registry, input_schema))
+        .collect::<Result<Vec<_>>>()?;
+    let partition_by = window_node
+        .partition_by
+        .iter()
+        .map(|e| parse_physical_expr(e, registry, input_schema))
+        .collect::<Result<Vec<_>>>()?;
+    let order_by = window_node
+        .order_by
+        .iter()
+        .map(|e| parse_physical_sort_expr(e, registry, input_schema))
+        .collect::<Result<Vec<_>>>()?;
+    let window_frame = window_node
+        .window_frame
+        .as_ref()
+        .map(|physical_window_expr_node::WindowFrame::Frame(frame)| {
+            WindowFrame::try_from(frame.clone()).map(Arc::new)
+        })
+        .transpose()?;
+
+    create_window_expr(
+        &fun,
+        window_node.name.clone(),
+        &args,
+        &partition_by,
+        &order_by,
+        window_frame,
+        window_node.ignore_nulls,
+        input_schema,
+    )
+}
+
+/// Parses the hash partitioning of a repartition node
+pub fn parse_protobuf_hash_partitioning(
+    partitioning: &protobuf::PhysicalHashRepartition,
+    registry: &dyn FunctionRegistry,
+    input_schema: &Schema,
+) -> Result<Partitioning> {
+    let expr = partitioning
+        .hash_expr
+        .iter()
+        .map(|e| parse_physical_expr(e, registry, input_schema))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Partitioning::Hash(
+        expr,
+        partitioning.partition_count as usize,
+    ))
+}
+
+/// Parses the join filter of a join node, whose expression is evaluated
+/// against the intermediate schema of the filter
+pub fn parse_physical_join_filter(
+    proto: &protobuf::JoinFilter,
+    registry: &dyn FunctionRegistry,
+) -> Result<JoinFilter> {
+    let schema: Schema = proto
+        .schema
+        .as_ref()
+        .ok_or_else(|| proto_error("Missing required field schema"))?
+        .try_into()?;
+    let expression = parse_required_physical_expr(
+        proto.expression.as_ref(),
+        registry,
+        "expression",
+        &schema,
+    )?;
+    let column_indices = proto
+        .column_indices
+        .iter()
+        .map(|index| {
+            let side = match protobuf::JoinSide::from_i32(index.side) {
+                Some(protobuf::JoinSide::LeftSide) => JoinSide::Left,
+                Some(protobuf::JoinSide::RightSide) => JoinSide::Right,
+                None => {
+                    return Err(proto_error(format!(
+                        "Received an unknown join side: {}",
+                        index.side
+                    )))
+                }
+            };
+            Ok(ColumnIndex {
+                index: index.index as usize,
+                side,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(JoinFilter::new(expression, column_indices, schema))
+}
+
+/// Parses the file scan configuration shared by the file scan nodes
+pub fn parse_protobuf_file_scan_config(
+    proto: &protobuf::FileScanExecConf,
+) -> Result<FileScanConfig> {
+    let schema: Arc<Schema> = Arc::new(
+        proto
+            .schema
+            .as_ref()
+            .ok_or_else(|| proto_error("Missing required field schema"))?
+            .try_into()?,
+    );
+    let projection = proto
+        .projection
+        .as_ref()
+        .map(|projection| projection.columns.iter().map(|c| *c as usize).collect());
+    let statistics = proto
+        .statistics
+        .as_ref()
+        .map(|statistics| statistics.try_into())
+        .transpose()?
+        .unwrap_or_default();
+    let file_groups = proto
+        .file_groups
+        .iter()
+        .map(|group| {
+            group
+                .files
+                .iter()
+                .map(|file| file.try_into())
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(FileScanConfig {
+        object_store_url: ObjectStoreUrl::parse(&proto.object_store_url)?,
+        file_schema: schema,
+        file_groups,
+        statistics,
+        projection,
+        limit: proto.limit.as_ref().map(|sl| sl.limit as usize),
+        table_partition_cols: proto.table_partition_cols.clone(),
+        config_options: ConfigOptions::new().into_shareable(),
+    })
+}
+
+impl From<&protobuf::PhysicalColumn> for Column {
+    fn from(c: &protobuf::PhysicalColumn) -> Column {
+        Column::new(&c.name, c.index as usize)
+    }
+}
+
+impl TryFrom<&protobuf::PartitionedFile> for PartitionedFile {
+    type Error = DataFusionError;
+
+    fn try_from(val: &protobuf::PartitionedFile) -> Result<Self> {
+        Ok(PartitionedFile {
+            object_meta: ObjectMeta {
+                location: Path::parse(&val.path)
+                    .map_err(|e| DataFusionError::External(Box::new(e)))?,
+                last_modified: Utc.timestamp_nanos(val.last_modified_ns as i64),
+                size: val.size as usize,
+            },
+            partition_values: val
+                .partition_values
+                .iter()
+                .map(|v| v.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            range: val.range.as_ref().map(|range| range.into()),
+            extensions: None,
+        })
+    }
+}
+
+impl From<&protobuf::FileRange> for FileRange {
+    fn from(value: &protobuf::FileRange) -> Self {
+        FileRange {
+            start: value.start,
+            end: value.end,
+        }
+    }
+}
+
+impl From<protobuf::FileCompressionType> for FileCompressionType {
+    fn from(compression: protobuf::FileCompressionType) -> Self {
+        match compression {
+            protobuf::FileCompressionType::Uncompressed => {
+                FileCompressionType::UNCOMPRESSED
+            }
+            protobuf::FileCompressionType::Gzip => FileCompressionType::GZIP,
+            protobuf::FileCompressionType::Bzip2 => FileCompressionType::BZIP2,
+        }
+    }
+}
+
+/// Negative counts are decoded as unknown
+fn optional_count(count: i64) -> Option<usize> {
+    if count < 0 {
+        None
+    } else {
+        Some(count as usize)
+    }
+}
+
+impl TryFrom<&protobuf::ColumnStats> for ColumnStatistics {
+    type Error = DataFusionError;
+
+    fn try_from(stats: &protobuf::ColumnStats) -> Result<Self> {
+        let scalar =
+            |value: &Option<protobuf::ScalarValue>| -> Result<Option<ScalarValue>> {
+                Ok(value.as_ref().map(|v| v.try_into()).transpose()?)
+            };
+        Ok(ColumnStatistics {
+            null_count: optional_count(stats.null_count),
+            max_value: scalar(&stats.max_value)?,
+            min_value: scalar(&stats.min_value)?,
+            distinct_count: optional_count(stats.distinct_count),
+        })
+    }
+}
+
+impl TryFrom<&protobuf::Statistics> for Statistics {
+    type Error = DataFusionError;
+
+    fn try_from(stats: &protobuf::Statistics) -> Result<Self> {
+        let column_statistics = stats
+            .column_stats
+            .iter()
+            .map(|s| s.try_into())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Statistics {
+            num_rows: optional_count(stats.num_rows),
+            total_byte_size: optional_count(stats.total_byte_size),
+            // No column statistic (None) is encoded with empty array
+            column_statistics: if column_statistics.is_empty() {
+                None
+            } else {
+                Some(column_statistics)
+            },
+            is_exact: stats.is_exact,
+        })
+    }
+}
+
+/// Decodes record batches from the Arrow IPC streaming format
+pub(crate) fn ipc_to_batches(data: &[u8]) -> Result<Vec<RecordBatch>> {
+    let reader = StreamReader::try_new(data, None)?;
+    reader
+        .map(|batch| batch.map_err(DataFusionError::from))
+        .collect()
+}
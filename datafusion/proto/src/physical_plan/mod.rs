@@ -0,0 +1,1635 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Serde code for physical plans, so that a plan can be planned in one
+//! process and executed in another.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use datafusion::datasource::file_format::file_type::FileCompressionType;
+use datafusion::execution::FunctionRegistry;
+use datafusion::physical_plan::aggregates::{
+    AggregateExec, AggregateMode, PhysicalGroupBy,
+};
+use datafusion::physical_plan::analyze::AnalyzeExec;
+use datafusion::physical_plan::bucketed::BucketedExec;
+use datafusion::physical_plan::check_constraints::{
+    CheckConstraints, CheckConstraintsExec,
+};
+use datafusion::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::dedup::DedupExec;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::explain::ExplainExec;
+use datafusion::physical_plan::expressions::Column;
+use datafusion::physical_plan::file_format::{
+    AvroExec, CsvExec, NdJsonExec, ParquetExec,
+};
+use datafusion::physical_plan::filter::FilterExec;
+use datafusion::physical_plan::joins::{
+    CrossJoinExec, HashJoinExec, PartitionMode, SortMergeJoinExec,
+};
+use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::projection::ProjectionExec;
+use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::scalar_subquery::ScalarSubqueryExec;
+use datafusion::physical_plan::sorts::sort::SortExec;
+use datafusion::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeExec;
+use datafusion::physical_plan::union::UnionExec;
+use datafusion::physical_plan::unnest::UnnestExec;
+use datafusion::physical_plan::values::ValuesExec;
+use datafusion::physical_plan::windows::WindowAggExec;
+use datafusion::physical_plan::{ExecutionPlan, Partitioning};
+use datafusion_common::{DataFusionError, Result};
+use datafusion_expr::JoinType;
+use prost::bytes::BufMut;
+use prost::Message;
+
+use crate::logical_plan::{
+    byte_to_string, proto_error, str_to_byte, PhysicalExtensionCodec,
+};
+use crate::protobuf::physical_plan_node::PhysicalPlanType;
+use crate::protobuf::repartition_exec_node::PartitionMethod;
+use crate::protobuf::{self, PhysicalExtensionNode, PhysicalPlanNode};
+
+use self::from_proto::{
+    ipc_to_batches, parse_physical_aggregate_expr, parse_physical_expr,
+    parse_physical_join_filter, parse_physical_sort_expr, parse_physical_window_expr,
+    parse_protobuf_file_scan_config, parse_protobuf_hash_partitioning,
+    scalar_subquery_value, ScalarSubqueryScope,
+};
+use self::to_proto::{batches_to_ipc, scalar_subquery_id};
+
+pub mod from_proto;
+pub mod to_proto;
+
+pub trait AsExecutionPlan: Debug + Send + Sync + Clone {
+    fn try_decode(buf: &[u8]) -> Result<Self>
+    where
+        Self: Sized;
+
+    fn try_encode<B>(&self, buf: &mut B) -> Result<()>
+    where
+        B: BufMut,
+        Self: Sized;
+
+    fn try_into_physical_plan(
+        &self,
+        registry: &dyn FunctionRegistry,
+        extension_codec: &dyn PhysicalExtensionCodec,
+    ) -> Result<Arc<dyn ExecutionPlan>>;
+
+    fn try_from_physical_plan(
+        plan: Arc<dyn ExecutionPlan>,
+        extension_codec: &dyn PhysicalExtensionCodec,
+    ) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+/// The codec used when none is provided, which encodes no user-defined
+/// execution plan
+#[derive(Debug, Clone)]
+pub struct DefaultPhysicalExtensionCodec {}
+
+impl PhysicalExtensionCodec for DefaultPhysicalExtensionCodec {
+    fn try_decode(
+        &self,
+        _buf: &[u8],
+        _inputs: &[Arc<dyn ExecutionPlan>],
+        _registry: &dyn FunctionRegistry,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::NotImplemented(
+            "PhysicalExtensionCodec is not provided".to_string(),
+        ))
+    }
+
+    fn try_encode(
+        &self,
+        _node: Arc<dyn ExecutionPlan>,
+        _buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        Err(DataFusionError::NotImplemented(
+            "PhysicalExtensionCodec is not provided".to_string(),
+        ))
+    }
+}
+
+macro_rules! into_physical_plan {
+    ($PB:expr, $REG:expr, $CODEC:expr) => {{
+        if let Some(field) = $PB.as_ref() {
+            field.as_ref().try_into_physical_plan($REG, $CODEC)
+        } else {
+            Err(proto_error("Missing required field in protobuf"))
+        }
+    }};
+}
+
+fn parse_required_schema(schema: &Option<protobuf::Schema>) -> Result<SchemaRef> {
+    let schema: Schema = schema
+        .as_ref()
+        .ok_or_else(|| proto_error("Missing required field schema"))?
+        .try_into()?;
+    Ok(Arc::new(schema))
+}
+
+fn parse_required_column(column: &Option<protobuf::PhysicalColumn>) -> Result<Column> {
+    column
+        .as_ref()
+        .map(Column::from)
+        .ok_or_else(|| proto_error("Missing required field column"))
+}
+
+fn parse_join_on(on: &[protobuf::JoinOn]) -> Result<Vec<(Column, Column)>> {
+    on.iter()
+        .map(|col| {
+            Ok((
+                parse_required_column(&col.left)?,
+                parse_required_column(&col.right)?,
+            ))
+        })
+        .collect()
+}
+
+fn parse_join_type(join_type: i32) -> Result<JoinType> {
+    protobuf::JoinType::from_i32(join_type)
+        .map(|t| t.into())
+        .ok_or_else(|| {
+            proto_error(format!(
+                "Received a join node with unknown JoinType {}",
+                join_type
+            ))
+        })
+}
+
+fn parse_file_compression_type(
+    file_compression_type: i32,
+) -> Result<FileCompressionType> {
+    protobuf::FileCompressionType::from_i32(file_compression_type)
+        .map(|t| t.into())
+        .ok_or_else(|| {
+            proto_error(format!(
+                "Received a scan node with unknown FileCompressionType {}",
+                file_compression_type
+            ))
+        })
+}
+
+/// Unknown limits are encoded as negative values
+fn parse_fetch(fetch: i64) -> Option<usize> {
+    if fetch < 0 {
+        None
+    } else {
+        Some(fetch as usize)
+    }
+}
+
+impl AsExecutionPlan for PhysicalPlanNode {
+    fn try_decode(buf: &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        PhysicalPlanNode::decode(buf).map_err(|e| {
+            DataFusionError::Internal(format!("failed to decode physical plan: {:?}", e))
+        })
+    }
+
+    fn try_encode<B>(&self, buf: &mut B) -> Result<()>
+    where
+        B: BufMut,
+        Self: Sized,
+    {
+        self.encode(buf).map_err(|e| {
+            DataFusionError::Internal(format!("failed to encode physical plan: {:?}", e))
+        })
+    }
+
+    fn try_into_physical_plan(
+        &self,
+        registry: &dyn FunctionRegistry,
+        extension_codec: &dyn PhysicalExtensionCodec,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let _scope = ScalarSubqueryScope::enter();
+        let plan = self.physical_plan_type.as_ref().ok_or_else(|| {
+            proto_error(format!(
+                "physical_plan::from_proto() Unsupported physical plan '{:?}'",
+                self
+            ))
+        })?;
+        match plan {
+            PhysicalPlanType::Explain(explain) => Ok(Arc::new(ExplainExec::new(
+                parse_required_schema(&explain.schema)?,
+                explain
+                    .stringified_plans
+                    .iter()
+                    .map(|plan| plan.into())
+                    .collect(),
+                explain.verbose,
+            ))),
+            PhysicalPlanType::Analyze(analyze) => {
+                let input =
+                    into_physical_plan!(analyze.input, registry, extension_codec)?;
+                Ok(Arc::new(AnalyzeExec::new(
+                    analyze.verbose,
+                    input,
+                    parse_required_schema(&analyze.schema)?,
+                )))
+            }
+            PhysicalPlanType::Projection(projection) => {
+                let input =
+                    into_physical_plan!(projection.input, registry, extension_codec)?;
+                let exprs = projection
+                    .expr
+                    .iter()
+                    .zip(projection.expr_name.iter())
+                    .map(|(expr, name)| {
+                        Ok((
+                            parse_physical_expr(expr, registry, input.schema().as_ref())?,
+                            name.to_string(),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+            }
+            PhysicalPlanType::Filter(filter) => {
+                let input = into_physical_plan!(filter.input, registry, extension_codec)?;
+                let predicate = filter
+                    .expr
+                    .as_ref()
+                    .ok_or_else(|| proto_error("Missing required field expr"))?;
+                let predicate =
+                    parse_physical_expr(predicate, registry, input.schema().as_ref())?;
+                Ok(Arc::new(FilterExec::try_new(predicate, input)?))
+            }
+            PhysicalPlanType::CsvScan(scan) => Ok(Arc::new(CsvExec::new(
+                parse_protobuf_file_scan_config(
+                    scan.base_conf
+                        .as_ref()
+                        .ok_or_else(|| proto_error("Missing required field base_conf"))?,
+                )?,
+                scan.has_header,
+                str_to_byte(&scan.delimiter)?,
+                parse_file_compression_type(scan.file_compression_type)?,
+            ))),
+            PhysicalPlanType::ParquetScan(scan) => {
+                let base_config = parse_protobuf_file_scan_config(
+                    scan.base_conf
+                        .as_ref()
+                        .ok_or_else(|| proto_error("Missing required field base_conf"))?,
+                )?;
+                let predicate = scan
+                    .pruning_predicate
+                    .as_ref()
+                    .map(|expr| crate::from_proto::parse_expr(expr, registry))
+                    .transpose()?;
+                let metadata_size_hint = if scan.metadata_size_hint == 0 {
+                    None
+                } else {
+                    Some(scan.metadata_size_hint as usize)
+                };
+                let nested_projection = scan
+                    .nested_projection
+                    .iter()
+                    .map(|path| path.names.clone())
+                    .collect();
+                Ok(Arc::new(
+                    ParquetExec::new(base_config, predicate, metadata_size_hint)
+                        .with_nested_projection(nested_projection)
+                        .with_pushdown_filters(scan.pushdown_filters)
+                        .with_reorder_filters(scan.reorder_filters)
+                        .with_enable_page_index(scan.enable_page_index),
+                ))
+            }
+            PhysicalPlanType::AvroScan(scan) => {
+                Ok(Arc::new(AvroExec::new(parse_protobuf_file_scan_config(
+                    scan.base_conf
+                        .as_ref()
+                        .ok_or_else(|| proto_error("Missing required field base_conf"))?,
+                )?)))
+            }
+            PhysicalPlanType::JsonScan(scan) => Ok(Arc::new(NdJsonExec::new(
+                parse_protobuf_file_scan_config(
+                    scan.base_conf
+                        .as_ref()
+                        .ok_or_else(|| proto_error("Missing required field base_conf"))?,
+                )?,
+                parse_file_compression_type(scan.file_compression_type)?,
+            ))),
+            PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
+                let input = into_physical_plan!(
+                    coalesce_batches.input,
+                    registry,
+                    extension_codec
+                )?;
+                Ok(Arc::new(CoalesceBatchesExec::new(
+                    input,
+                    coalesce_batches.target_batch_size as usize,
+                )))
+            }
+            PhysicalPlanType::Merge(merge) => {
+                let input = into_physical_plan!(merge.input, registry, extension_codec)?;
+                Ok(Arc::new(CoalescePartitionsExec::new(input)))
+            }
+            PhysicalPlanType::Repartition(repart) => {
+                let input = into_physical_plan!(repart.input, registry, extension_codec)?;
+                let partitioning = match repart.partition_method.as_ref() {
+                    Some(PartitionMethod::Hash(hash)) => {
+                        parse_protobuf_hash_partitioning(
+                            hash,
+                            registry,
+                            input.schema().as_ref(),
+                        )?
+                    }
+                    Some(PartitionMethod::RoundRobin(partition_count)) => {
+                        Partitioning::RoundRobinBatch(*partition_count as usize)
+                    }
+                    Some(PartitionMethod::Unknown(partition_count)) => {
+                        Partitioning::UnknownPartitioning(*partition_count as usize)
+                    }
+                    None => {
+                        return Err(proto_error(
+                            "Missing required field partition_method",
+                        ))
+                    }
+                };
+                Ok(Arc::new(RepartitionExec::try_new(input, partitioning)?))
+            }
+            PhysicalPlanType::GlobalLimit(limit) => {
+                let input = into_physical_plan!(limit.input, registry, extension_codec)?;
+                Ok(Arc::new(GlobalLimitExec::new(
+                    input,
+                    limit.skip as usize,
+                    parse_fetch(limit.fetch),
+                )))
+            }
+            PhysicalPlanType::LocalLimit(limit) => {
+                let input = into_physical_plan!(limit.input, registry, extension_codec)?;
+                Ok(Arc::new(LocalLimitExec::new(input, limit.fetch as usize)))
+            }
+            PhysicalPlanType::Window(window_agg) => {
+                let input =
+                    into_physical_plan!(window_agg.input, registry, extension_codec)?;
+                let input_schema = parse_required_schema(&window_agg.input_schema)?;
+                let window_expr = window_agg
+                    .window_expr
+                    .iter()
+                    .map(|expr| {
+                        parse_physical_window_expr(expr, registry, input_schema.as_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(WindowAggExec::try_new(
+                    window_expr,
+                    input,
+                    input_schema,
+                )?))
+            }
+            PhysicalPlanType::Aggregate(hash_agg) => {
+                let input =
+                    into_physical_plan!(hash_agg.input, registry, extension_codec)?;
+                let mode = match protobuf::AggregateMode::from_i32(hash_agg.mode) {
+                    Some(protobuf::AggregateMode::Partial) => AggregateMode::Partial,
+                    Some(protobuf::AggregateMode::Final) => AggregateMode::Final,
+                    Some(protobuf::AggregateMode::FinalPartitioned) => {
+                        AggregateMode::FinalPartitioned
+                    }
+                    Some(protobuf::AggregateMode::Single) => AggregateMode::Single,
+                    None => return Err(proto_error(format!(
+                        "Received a AggregateNode message with unknown AggregateMode {}",
+                        hash_agg.mode
+                    ))),
+                };
+
+                let num_expr = hash_agg.group_expr.len();
+                let group_expr = hash_agg
+                    .group_expr
+                    .iter()
+                    .zip(hash_agg.group_expr_name.iter())
+                    .map(|(expr, name)| {
+                        Ok((
+                            parse_physical_expr(expr, registry, input.schema().as_ref())?,
+                            name.to_string(),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let null_expr = hash_agg
+                    .null_expr
+                    .iter()
+                    .zip(hash_agg.group_expr_name.iter())
+                    .map(|(expr, name)| {
+                        Ok((
+                            parse_physical_expr(expr, registry, input.schema().as_ref())?,
+                            name.to_string(),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                // the null masks of all groups are flattened into `groups`
+                let groups = if num_expr == 0 {
+                    vec![vec![]]
+                } else {
+                    hash_agg
+                        .groups
+                        .chunks(num_expr)
+                        .map(|group| group.to_vec())
+                        .collect()
+                };
+
+                // the aggregate expressions are evaluated against the input of
+                // the partial aggregate, also in final aggregates
+                let input_schema = parse_required_schema(&hash_agg.input_schema)?;
+                let aggr_expr = hash_agg
+                    .aggr_expr
+                    .iter()
+                    .zip(hash_agg.aggr_expr_name.iter())
+                    .map(|(expr, name)| {
+                        parse_physical_aggregate_expr(
+                            expr,
+                            registry,
+                            name,
+                            input_schema.as_ref(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(Arc::new(AggregateExec::try_new(
+                    mode,
+                    PhysicalGroupBy::new(group_expr, null_expr, groups),
+                    aggr_expr,
+                    input,
+                    input_schema,
+                )?))
+            }
+            PhysicalPlanType::HashJoin(hashjoin) => {
+                let left = into_physical_plan!(hashjoin.left, registry, extension_codec)?;
+                let right =
+                    into_physical_plan!(hashjoin.right, registry, extension_codec)?;
+                let partition_mode = match protobuf::PartitionMode::from_i32(
+                    hashjoin.partition_mode,
+                ) {
+                    Some(protobuf::PartitionMode::CollectLeft) => {
+                        PartitionMode::CollectLeft
+                    }
+                    Some(protobuf::PartitionMode::Partitioned) => {
+                        PartitionMode::Partitioned
+                    }
+                    None => return Err(proto_error(format!(
+                        "Received a HashJoinNode message with unknown PartitionMode {}",
+                        hashjoin.partition_mode
+                    ))),
+                };
+                let filter = hashjoin
+                    .filter
+                    .as_ref()
+                    .map(|filter| parse_physical_join_filter(filter, registry))
+                    .transpose()?;
+                Ok(Arc::new(HashJoinExec::try_new(
+                    left,
+                    right,
+                    parse_join_on(&hashjoin.on)?,
+                    filter,
+                    &parse_join_type(hashjoin.join_type)?,
+                    partition_mode,
+                    &hashjoin.null_equals_null,
+                )?))
+            }
+            PhysicalPlanType::SortMergeJoin(join) => {
+                let left = into_physical_plan!(join.left, registry, extension_codec)?;
+                let right = into_physical_plan!(join.right, registry, extension_codec)?;
+                let sort_options = join
+                    .sort_options
+                    .iter()
+                    .map(|options| arrow::compute::SortOptions {
+                        descending: !options.asc,
+                        nulls_first: options.nulls_first,
+                    })
+                    .collect();
+                Ok(Arc::new(SortMergeJoinExec::try_new(
+                    left,
+                    right,
+                    parse_join_on(&join.on)?,
+                    parse_join_type(join.join_type)?,
+                    sort_options,
+                    join.null_equals_null,
+                )?))
+            }
+            PhysicalPlanType::CrossJoin(crossjoin) => {
+                let left =
+                    into_physical_plan!(crossjoin.left, registry, extension_codec)?;
+                let right =
+                    into_physical_plan!(crossjoin.right, registry, extension_codec)?;
+                Ok(Arc::new(CrossJoinExec::try_new(left, right)?))
+            }
+            PhysicalPlanType::Union(union) => {
+                let inputs = union
+                    .inputs
+                    .iter()
+                    .map(|input| input.try_into_physical_plan(registry, extension_codec))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(UnionExec::new(inputs)))
+            }
+            PhysicalPlanType::Empty(empty) => Ok(Arc::new(
+                EmptyExec::new(
+                    empty.produce_one_row,
+                    parse_required_schema(&empty.schema)?,
+                )
+                .with_partitions(empty.partitions as usize),
+            )),
+            PhysicalPlanType::Sort(sort) => {
+                let input = into_physical_plan!(sort.input, registry, extension_codec)?;
+                let exprs = sort
+                    .expr
+                    .iter()
+                    .map(|expr| {
+                        parse_physical_sort_expr(expr, registry, input.schema().as_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(SortExec::new_with_partitioning(
+                    exprs,
+                    input,
+                    sort.preserve_partitioning,
+                    parse_fetch(sort.fetch),
+                )))
+            }
+            PhysicalPlanType::SortPreservingMerge(sort) => {
+                let input = into_physical_plan!(sort.input, registry, extension_codec)?;
+                let exprs = sort
+                    .expr
+                    .iter()
+                    .map(|expr| {
+                        parse_physical_sort_expr(expr, registry, input.schema().as_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(SortPreservingMergeExec::new(exprs, input)))
+            }
+            PhysicalPlanType::Values(values) => {
+                Ok(Arc::new(ValuesExec::try_new_from_batches(
+                    parse_required_schema(&values.schema)?,
+                    ipc_to_batches(&values.data)?,
+                )?))
+            }
+            PhysicalPlanType::Memory(memory) => {
+                let partitions = memory
+                    .partitions
+                    .iter()
+                    .map(|data| ipc_to_batches(data))
+                    .collect::<Result<Vec<_>>>()?;
+                let projection = memory.projection.as_ref().map(|projection| {
+                    projection.columns.iter().map(|c| *c as usize).collect()
+                });
+                Ok(Arc::new(MemoryExec::try_new(
+                    &partitions,
+                    parse_required_schema(&memory.schema)?,
+                    projection,
+                )?))
+            }
+            PhysicalPlanType::Unnest(unnest) => {
+                let input = into_physical_plan!(unnest.input, registry, extension_codec)?;
+                Ok(Arc::new(UnnestExec::try_new(
+                    input,
+                    parse_required_column(&unnest.column)?,
+                    parse_required_schema(&unnest.schema)?,
+                )?))
+            }
+            PhysicalPlanType::Dedup(dedup) => {
+                let input = into_physical_plan!(dedup.input, registry, extension_codec)?;
+                let on = dedup
+                    .on
+                    .iter()
+                    .map(|expr| {
+                        parse_physical_expr(expr, registry, input.schema().as_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let keep =
+                    protobuf::DedupKeep::from_i32(dedup.keep).ok_or_else(|| {
+                        proto_error(format!(
+                            "Received a DedupExecNode message with unknown DedupKeep {}",
+                            dedup.keep
+                        ))
+                    })?;
+                let order_by = dedup
+                    .order_by
+                    .iter()
+                    .map(|expr| {
+                        parse_physical_sort_expr(expr, registry, input.schema().as_ref())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(DedupExec::new(input, on, keep.into(), order_by)))
+            }
+            PhysicalPlanType::ScalarSubquery(scalar_subquery) => {
+                let input = into_physical_plan!(
+                    scalar_subquery.input,
+                    registry,
+                    extension_codec
+                )?;
+                let subqueries = scalar_subquery
+                    .subqueries
+                    .iter()
+                    .map(|subquery| {
+                        let plan = subquery
+                            .plan
+                            .as_ref()
+                            .ok_or_else(|| proto_error("Missing required field plan"))?;
+                        Ok((
+                            plan.try_into_physical_plan(registry, extension_codec)?,
+                            scalar_subquery_value(subquery.id),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(ScalarSubqueryExec::new(input, subqueries)))
+            }
+            PhysicalPlanType::CheckConstraints(check) => {
+                let input = into_physical_plan!(check.input, registry, extension_codec)?;
+                let checks = check
+                    .checks
+                    .iter()
+                    .map(|check| {
+                        let expr = check
+                            .expr
+                            .as_ref()
+                            .ok_or_else(|| proto_error("Missing required field expr"))?;
+                        let expr =
+                            parse_physical_expr(expr, registry, input.schema().as_ref())?;
+                        Ok((check.label.clone(), expr))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(CheckConstraintsExec::new(
+                    input,
+                    CheckConstraints::new(checks),
+                )))
+            }
+            PhysicalPlanType::Bucketed(bucketed) => {
+                let input =
+                    into_physical_plan!(bucketed.input, registry, extension_codec)?;
+                Ok(Arc::new(BucketedExec::new(
+                    input,
+                    bucketed.columns.iter().map(Column::from).collect(),
+                    bucketed.num_buckets as usize,
+                )))
+            }
+            PhysicalPlanType::Extension(extension) => {
+                let inputs = extension
+                    .inputs
+                    .iter()
+                    .map(|input| input.try_into_physical_plan(registry, extension_codec))
+                    .collect::<Result<Vec<_>>>()?;
+                extension_codec.try_decode(&extension.node, &inputs, registry)
+            }
+        }
+    }
+
+    fn try_from_physical_plan(
+        plan: Arc<dyn ExecutionPlan>,
+        extension_codec: &dyn PhysicalExtensionCodec,
+    ) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let to_input = |input: &Arc<dyn ExecutionPlan>| -> Result<Box<Self>> {
+            Ok(Box::new(PhysicalPlanNode::try_from_physical_plan(
+                input.clone(),
+                extension_codec,
+            )?))
+        };
+        let node = |plan_type: PhysicalPlanType| -> Result<Self> {
+            Ok(PhysicalPlanNode {
+                physical_plan_type: Some(plan_type),
+            })
+        };
+
+        let any = plan.as_any();
+        if let Some(exec) = any.downcast_ref::<ExplainExec>() {
+            node(PhysicalPlanType::Explain(protobuf::ExplainExecNode {
+                schema: Some(exec.schema().as_ref().try_into()?),
+                stringified_plans: exec
+                    .stringified_plans()
+                    .iter()
+                    .map(|plan| plan.into())
+                    .collect(),
+                verbose: exec.verbose(),
+            }))
+        } else if let Some(exec) = any.downcast_ref::<AnalyzeExec>() {
+            node(PhysicalPlanType::Analyze(Box::new(
+                protobuf::AnalyzeExecNode {
+                    input: Some(to_input(exec.input())?),
+                    verbose: exec.verbose(),
+                    schema: Some(exec.schema().as_ref().try_into()?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<ProjectionExec>() {
+            node(PhysicalPlanType::Projection(Box::new(
+                protobuf::ProjectionExecNode {
+                    input: Some(to_input(exec.input())?),
+                    expr: exec
+                        .expr()
+                        .iter()
+                        .map(|(expr, _)| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    expr_name: exec.expr().iter().map(|(_, name)| name.clone()).collect(),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<FilterExec>() {
+            node(PhysicalPlanType::Filter(Box::new(
+                protobuf::FilterExecNode {
+                    input: Some(to_input(exec.input())?),
+                    expr: Some(exec.predicate().clone().try_into()?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<GlobalLimitExec>() {
+            node(PhysicalPlanType::GlobalLimit(Box::new(
+                protobuf::GlobalLimitExecNode {
+                    input: Some(to_input(exec.input())?),
+                    skip: exec.skip() as u32,
+                    fetch: exec.fetch().map(|fetch| *fetch as i64).unwrap_or(-1),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<LocalLimitExec>() {
+            node(PhysicalPlanType::LocalLimit(Box::new(
+                protobuf::LocalLimitExecNode {
+                    input: Some(to_input(exec.input())?),
+                    fetch: exec.fetch() as u32,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<HashJoinExec>() {
+            let partition_mode = match exec.partition_mode() {
+                PartitionMode::CollectLeft => protobuf::PartitionMode::CollectLeft,
+                PartitionMode::Partitioned => protobuf::PartitionMode::Partitioned,
+            };
+            node(PhysicalPlanType::HashJoin(Box::new(
+                protobuf::HashJoinExecNode {
+                    left: Some(to_input(exec.left())?),
+                    right: Some(to_input(exec.right())?),
+                    on: join_on_to_proto(exec.on()),
+                    join_type: protobuf::JoinType::from(*exec.join_type()) as i32,
+                    partition_mode: partition_mode as i32,
+                    null_equals_null: *exec.null_equals_null(),
+                    filter: exec
+                        .filter()
+                        .as_ref()
+                        .map(|filter| filter.try_into())
+                        .transpose()?,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<SortMergeJoinExec>() {
+            node(PhysicalPlanType::SortMergeJoin(Box::new(
+                protobuf::SortMergeJoinExecNode {
+                    left: Some(to_input(exec.left())?),
+                    right: Some(to_input(exec.right())?),
+                    on: join_on_to_proto(exec.on()),
+                    join_type: protobuf::JoinType::from(exec.join_type()) as i32,
+                    sort_options: exec
+                        .sort_options()
+                        .iter()
+                        .map(|options| protobuf::PhysicalSortOptions {
+                            asc: !options.descending,
+                            nulls_first: options.nulls_first,
+                        })
+                        .collect(),
+                    null_equals_null: exec.null_equals_null(),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<CrossJoinExec>() {
+            node(PhysicalPlanType::CrossJoin(Box::new(
+                protobuf::CrossJoinExecNode {
+                    left: Some(to_input(exec.left())?),
+                    right: Some(to_input(exec.right())?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<AggregateExec>() {
+            let mode = match exec.mode() {
+                AggregateMode::Partial => protobuf::AggregateMode::Partial,
+                AggregateMode::Final => protobuf::AggregateMode::Final,
+                AggregateMode::FinalPartitioned => {
+                    protobuf::AggregateMode::FinalPartitioned
+                }
+                AggregateMode::Single => protobuf::AggregateMode::Single,
+            };
+            let group_by = exec.group_expr();
+            node(PhysicalPlanType::Aggregate(Box::new(
+                protobuf::AggregateExecNode {
+                    group_expr: group_by
+                        .expr()
+                        .iter()
+                        .map(|(expr, _)| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    aggr_expr: exec
+                        .aggr_expr()
+                        .iter()
+                        .map(|expr| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    mode: mode as i32,
+                    input: Some(to_input(exec.input())?),
+                    group_expr_name: group_by
+                        .expr()
+                        .iter()
+                        .map(|(_, name)| name.clone())
+                        .collect(),
+                    aggr_expr_name: exec
+                        .aggr_expr()
+                        .iter()
+                        .map(|expr| expr.name().to_string())
+                        .collect(),
+                    input_schema: Some(exec.input_schema().as_ref().try_into()?),
+                    null_expr: group_by
+                        .null_expr()
+                        .iter()
+                        .map(|(expr, _)| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    groups: group_by.groups().iter().flatten().copied().collect(),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<EmptyExec>() {
+            node(PhysicalPlanType::Empty(protobuf::EmptyExecNode {
+                produce_one_row: exec.produce_one_row(),
+                schema: Some(exec.schema().as_ref().try_into()?),
+                partitions: exec.output_partitioning().partition_count() as u32,
+            }))
+        } else if let Some(exec) = any.downcast_ref::<CoalesceBatchesExec>() {
+            node(PhysicalPlanType::CoalesceBatches(Box::new(
+                protobuf::CoalesceBatchesExecNode {
+                    input: Some(to_input(exec.input())?),
+                    target_batch_size: exec.target_batch_size() as u32,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<CsvExec>() {
+            node(PhysicalPlanType::CsvScan(protobuf::CsvScanExecNode {
+                base_conf: Some(exec.base_config().try_into()?),
+                has_header: exec.has_header(),
+                delimiter: byte_to_string(exec.delimiter())?,
+                file_compression_type: protobuf::FileCompressionType::from(
+                    &exec.file_compression_type(),
+                ) as i32,
+            }))
+        } else if let Some(exec) = any.downcast_ref::<ParquetExec>() {
+            node(PhysicalPlanType::ParquetScan(
+                protobuf::ParquetScanExecNode {
+                    base_conf: Some(exec.base_config().try_into()?),
+                    pruning_predicate: exec
+                        .pruning_predicate()
+                        .map(|predicate| predicate.logical_expr().try_into())
+                        .transpose()?,
+                    metadata_size_hint: exec.metadata_size_hint().unwrap_or(0) as u64,
+                    pushdown_filters: exec.pushdown_filters(),
+                    reorder_filters: exec.reorder_filters(),
+                    enable_page_index: exec.enable_page_index(),
+                    nested_projection: exec
+                        .nested_projection()
+                        .iter()
+                        .map(|names| protobuf::NestedProjectionPath {
+                            names: names.clone(),
+                        })
+                        .collect(),
+                },
+            ))
+        } else if let Some(exec) = any.downcast_ref::<AvroExec>() {
+            node(PhysicalPlanType::AvroScan(protobuf::AvroScanExecNode {
+                base_conf: Some(exec.base_config().try_into()?),
+            }))
+        } else if let Some(exec) = any.downcast_ref::<NdJsonExec>() {
+            node(PhysicalPlanType::JsonScan(protobuf::NdJsonScanExecNode {
+                base_conf: Some(exec.base_config().try_into()?),
+                file_compression_type: protobuf::FileCompressionType::from(
+                    &exec.file_compression_type(),
+                ) as i32,
+            }))
+        } else if let Some(exec) = any.downcast_ref::<CoalescePartitionsExec>() {
+            node(PhysicalPlanType::Merge(Box::new(
+                protobuf::CoalescePartitionsExecNode {
+                    input: Some(to_input(exec.input())?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<RepartitionExec>() {
+            let partition_method = match exec.partitioning() {
+                Partitioning::Hash(exprs, partition_count) => {
+                    PartitionMethod::Hash(protobuf::PhysicalHashRepartition {
+                        hash_expr: exprs
+                            .iter()
+                            .map(|expr| expr.clone().try_into())
+                            .collect::<Result<Vec<_>>>()?,
+                        partition_count: *partition_count as u64,
+                    })
+                }
+                Partitioning::RoundRobinBatch(partition_count) => {
+                    PartitionMethod::RoundRobin(*partition_count as u64)
+                }
+                Partitioning::UnknownPartitioning(partition_count) => {
+                    PartitionMethod::Unknown(*partition_count as u64)
+                }
+            };
+            node(PhysicalPlanType::Repartition(Box::new(
+                protobuf::RepartitionExecNode {
+                    input: Some(to_input(exec.input())?),
+                    partition_method: Some(partition_method),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<SortExec>() {
+            node(PhysicalPlanType::Sort(Box::new(protobuf::SortExecNode {
+                input: Some(to_input(exec.input())?),
+                expr: exec
+                    .expr()
+                    .iter()
+                    .map(|expr| expr.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                fetch: exec.fetch().map(|fetch| fetch as i64).unwrap_or(-1),
+                preserve_partitioning: exec.preserve_partitioning(),
+            })))
+        } else if let Some(exec) = any.downcast_ref::<UnionExec>() {
+            node(PhysicalPlanType::Union(protobuf::UnionExecNode {
+                inputs: exec
+                    .inputs()
+                    .iter()
+                    .map(|input| {
+                        PhysicalPlanNode::try_from_physical_plan(
+                            input.clone(),
+                            extension_codec,
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            }))
+        } else if let Some(exec) = any.downcast_ref::<SortPreservingMergeExec>() {
+            node(PhysicalPlanType::SortPreservingMerge(Box::new(
+                protobuf::SortPreservingMergeExecNode {
+                    input: Some(to_input(exec.input())?),
+                    expr: exec
+                        .expr()
+                        .iter()
+                        .map(|expr| expr.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<WindowAggExec>() {
+            node(PhysicalPlanType::Window(Box::new(
+                protobuf::WindowAggExecNode {
+                    input: Some(to_input(exec.input())?),
+                    window_expr: exec
+                        .window_expr()
+                        .iter()
+                        .map(|expr| expr.clone().try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                    input_schema: Some(exec.input_schema().as_ref().try_into()?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<ValuesExec>() {
+            let schema = exec.schema();
+            node(PhysicalPlanType::Values(protobuf::ValuesExecNode {
+                schema: Some(schema.as_ref().try_into()?),
+                data: batches_to_ipc(schema.as_ref(), &exec.data())?,
+            }))
+        } else if let Some(exec) = any.downcast_ref::<MemoryExec>() {
+            let schema = exec.original_schema();
+            node(PhysicalPlanType::Memory(protobuf::MemoryExecNode {
+                schema: Some(schema.as_ref().try_into()?),
+                partitions: exec
+                    .partitions()
+                    .iter()
+                    .map(|batches| batches_to_ipc(schema.as_ref(), batches))
+                    .collect::<Result<Vec<_>>>()?,
+                projection: exec.projection().as_ref().map(|columns| {
+                    protobuf::ScanProjection {
+                        columns: columns.iter().map(|c| *c as u32).collect(),
+                    }
+                }),
+            }))
+        } else if let Some(exec) = any.downcast_ref::<UnnestExec>() {
+            node(PhysicalPlanType::Unnest(Box::new(
+                protobuf::UnnestExecNode {
+                    input: Some(to_input(exec.input())?),
+                    column: Some(exec.column().into()),
+                    schema: Some(exec.schema().as_ref().try_into()?),
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<DedupExec>() {
+            node(PhysicalPlanType::Dedup(Box::new(protobuf::DedupExecNode {
+                input: Some(to_input(exec.input())?),
+                on: exec
+                    .on()
+                    .iter()
+                    .map(|expr| expr.clone().try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                keep: protobuf::DedupKeep::from(exec.keep()).into(),
+                order_by: exec
+                    .order_by()
+                    .iter()
+                    .map(|expr| expr.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+            })))
+        } else if let Some(exec) = any.downcast_ref::<ScalarSubqueryExec>() {
+            node(PhysicalPlanType::ScalarSubquery(Box::new(
+                protobuf::ScalarSubqueryExecNode {
+                    input: Some(to_input(exec.input())?),
+                    subqueries: exec
+                        .subqueries()
+                        .iter()
+                        .map(|(plan, value)| {
+                            Ok(protobuf::ScalarSubqueryExecPlan {
+                                plan: Some(PhysicalPlanNode::try_from_physical_plan(
+                                    plan.clone(),
+                                    extension_codec,
+                                )?),
+                                id: scalar_subquery_id(value),
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<CheckConstraintsExec>() {
+            node(PhysicalPlanType::CheckConstraints(Box::new(
+                protobuf::CheckConstraintsExecNode {
+                    input: Some(to_input(exec.input())?),
+                    checks: exec
+                        .checks()
+                        .checks()
+                        .iter()
+                        .map(|(label, expr)| {
+                            Ok(protobuf::PhysicalCheckConstraint {
+                                label: label.clone(),
+                                expr: Some(expr.clone().try_into()?),
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                },
+            )))
+        } else if let Some(exec) = any.downcast_ref::<BucketedExec>() {
+            node(PhysicalPlanType::Bucketed(Box::new(
+                protobuf::BucketedExecNode {
+                    input: Some(to_input(exec.input())?),
+                    columns: exec.columns().iter().map(|c| c.into()).collect(),
+                    num_buckets: exec.num_buckets() as u32,
+                },
+            )))
+        } else {
+            let mut buf: Vec<u8> = vec![];
+            extension_codec.try_encode(plan.clone(), &mut buf)?;
+
+            let inputs = plan
+                .children()
+                .into_iter()
+                .map(|input| {
+                    PhysicalPlanNode::try_from_physical_plan(input, extension_codec)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            node(PhysicalPlanType::Extension(PhysicalExtensionNode {
+                node: buf,
+                inputs,
+            }))
+        }
+    }
+}
+
+fn join_on_to_proto(on: &[(Column, Column)]) -> Vec<protobuf::JoinOn> {
+    on.iter()
+        .map(|(left, right)| protobuf::JoinOn {
+            left: Some(left.into()),
+            right: Some(right.into()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use super::*;
+    use crate::logical_plan::ExtensionCodecRegistry;
+    use arrow::array::{ArrayRef, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+    use arrow::record_batch::RecordBatch;
+    use arrow::util::pretty::pretty_format_batches;
+    use datafusion::common::{Constraint, Constraints};
+    use datafusion::config::OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS;
+    use datafusion::datasource::MemTable;
+    use datafusion::execution::context::TaskContext;
+    use datafusion::logical_expr::{create_udaf, create_udf, DedupKeep, Volatility};
+    use datafusion::physical_plan::expressions::{
+        MaxAccumulator, PhysicalSortExpr, ScalarSubqueryExpr, ScalarSubqueryValue,
+    };
+    use datafusion::physical_plan::functions::make_scalar_function;
+    use datafusion::physical_plan::{
+        collect, displayable, DisplayFormatType, PhysicalExpr, SendableRecordBatchStream,
+        Statistics,
+    };
+    use datafusion::prelude::{col, CsvReadOptions, SessionConfig, SessionContext};
+    use std::any::Any;
+    use std::sync::Arc;
+
+    fn roundtrip_test_with_context(
+        exec_plan: Arc<dyn ExecutionPlan>,
+        ctx: &SessionContext,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        roundtrip_test_with_codec(exec_plan, ctx, &DefaultPhysicalExtensionCodec {})
+    }
+
+    fn roundtrip_test_with_codec(
+        exec_plan: Arc<dyn ExecutionPlan>,
+        ctx: &SessionContext,
+        codec: &dyn PhysicalExtensionCodec,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let proto =
+            protobuf::PhysicalPlanNode::try_from_physical_plan(exec_plan.clone(), codec)?;
+        let mut buf = vec![];
+        proto.try_encode(&mut buf)?;
+        let result_exec_plan = protobuf::PhysicalPlanNode::try_decode(&buf)?
+            .try_into_physical_plan(ctx, codec)?;
+        // file scans hold the session config, whose debug output is not stable
+        assert_eq!(
+            displayable(exec_plan.as_ref()).indent().to_string(),
+            displayable(result_exec_plan.as_ref()).indent().to_string()
+        );
+        Ok(result_exec_plan)
+    }
+
+    async fn roundtrip_sql(ctx: &SessionContext, sql: &str) -> Result<()> {
+        let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+        roundtrip_test_with_context(plan, ctx)?;
+        Ok(())
+    }
+
+    async fn test_context() -> Result<SessionContext> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        ctx.register_csv("t2", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        Ok(ctx)
+    }
+
+    #[test]
+    fn roundtrip_empty() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(false, schema).with_partitions(3));
+        let result = roundtrip_test_with_context(plan, &SessionContext::new())?;
+        assert_eq!(result.output_partitioning().partition_count(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_projection_filter_limit() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT a + b AS c, -a, CAST(b AS VARCHAR), abs(a) FROM t1 \
+             WHERE a IN (1, 2) AND NOT (b IS NULL) AND a IS NOT NULL LIMIT 1 OFFSET 1",
+        )
+        .await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT CASE WHEN a > 1 THEN 'x' WHEN a > 2 THEN 'y' ELSE 'z' END, \
+             CASE b WHEN 2 THEN 1 END, TRY_CAST(a AS INT) FROM t1",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn roundtrip_cast_in_time_zone() -> Result<()> {
+        let config = SessionConfig::new().with_time_zone("Asia/Tokyo");
+        let ctx = SessionContext::with_config(config);
+        ctx.register_csv("t1", "testdata/test.csv", CsvReadOptions::default())
+            .await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT CAST(CAST(a AS TIMESTAMP) AS TIMESTAMP WITH TIME ZONE) FROM t1",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn roundtrip_sort_union() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT a, b FROM t1 ORDER BY a DESC NULLS LAST, b LIMIT 2",
+        )
+        .await?;
+        roundtrip_sql(&ctx, "SELECT a FROM t1 UNION ALL SELECT b FROM t2").await?;
+        roundtrip_sql(&ctx, "SELECT a FROM t1 UNION SELECT b FROM t2").await
+    }
+
+    #[tokio::test]
+    async fn roundtrip_dedup() -> Result<()> {
+        let ctx = test_context().await?;
+        for order_by in [vec![], vec![col("b").sort(false, true)]] {
+            let plan = ctx
+                .table("t1")?
+                .dedup(vec![col("a")], DedupKeep::Last, order_by)?
+                .create_physical_plan()
+                .await?;
+            roundtrip_test_with_context(plan, &ctx)?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_check_constraints() -> Result<()> {
+        let config =
+            SessionConfig::new().set_bool(OPT_CHECK_CONSTRAINTS_VALIDATE_SCANS, true);
+        let ctx = SessionContext::with_config(config);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, -2, 3])),
+                Arc::new(Int64Array::from(vec![4, 5, 6])),
+            ],
+        )?;
+        let table = MemTable::try_new(schema, vec![vec![batch]])?.with_constraints(
+            Constraints::new(vec![
+                Constraint::Check {
+                    name: Some("a_positive".to_string()),
+                    expr: "a > 0".to_string(),
+                },
+                Constraint::Check {
+                    name: None,
+                    expr: "a + b > 4".to_string(),
+                },
+            ]),
+        );
+        ctx.register_table("t", Arc::new(table))?;
+
+        let plan = ctx.table("t")?.create_physical_plan().await?;
+        assert!(plan.as_any().is::<CheckConstraintsExec>());
+        let plan = roundtrip_test_with_context(plan, &ctx)?;
+
+        collect(plan.clone(), ctx.task_ctx()).await?;
+        let violations = plan
+            .metrics()
+            .unwrap()
+            .sum(|m| m.value().name() == "check_violations")
+            .map(|v| v.as_usize());
+        assert_eq!(violations, Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_scalar_subquery() -> Result<()> {
+        let ctx = test_context().await?;
+        let plan = ctx
+            .sql(
+                "SELECT a, (SELECT MAX(b) FROM t2) FROM t1 \
+                 WHERE a < (SELECT MIN(b) FROM t2)",
+            )
+            .await?
+            .create_physical_plan()
+            .await?;
+        let display = displayable(plan.as_ref()).indent().to_string();
+        assert!(display.contains("ScalarSubqueryExec"), "{}", display);
+        let result = roundtrip_test_with_context(plan.clone(), &ctx)?;
+        assert_eq!(
+            pretty_format_batches(&collect(plan, ctx.task_ctx()).await?)?.to_string(),
+            pretty_format_batches(&collect(result, ctx.task_ctx()).await?)?.to_string()
+        );
+
+        // the references to the same subquery still share its value
+        let batch = RecordBatch::try_from_iter(vec![(
+            "a",
+            Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+        )])?;
+        let subquery = RecordBatch::try_from_iter(vec![(
+            "b",
+            Arc::new(Int64Array::from(vec![7])) as ArrayRef,
+        )])?;
+        let value = Arc::new(ScalarSubqueryValue::new());
+        let input = Arc::new(ScalarSubqueryExec::new(
+            Arc::new(MemoryExec::try_new(
+                &[vec![batch.clone()]],
+                batch.schema(),
+                None,
+            )?),
+            vec![(
+                Arc::new(MemoryExec::try_new(
+                    &[vec![subquery.clone()]],
+                    subquery.schema(),
+                    None,
+                )?),
+                value.clone(),
+            )],
+        ));
+        let expr = || -> Arc<dyn PhysicalExpr> {
+            Arc::new(ScalarSubqueryExpr::new(DataType::Int64, value.clone()))
+        };
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(ProjectionExec::try_new(
+            vec![(expr(), "x".to_string()), (expr(), "y".to_string())],
+            input,
+        )?);
+        let result = roundtrip_test_with_context(plan, &ctx)?;
+        let projection = result.as_any().downcast_ref::<ProjectionExec>().unwrap();
+        let values = projection
+            .expr()
+            .iter()
+            .map(|(expr, _)| {
+                expr.as_any()
+                    .downcast_ref::<ScalarSubqueryExpr>()
+                    .unwrap()
+                    .value()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        let exec = projection
+            .input()
+            .as_any()
+            .downcast_ref::<ScalarSubqueryExec>()
+            .unwrap();
+        assert!(Arc::ptr_eq(&values[0], &values[1]));
+        assert!(Arc::ptr_eq(&values[0], &exec.subqueries()[0].1));
+        assert!(!Arc::ptr_eq(&values[0], &value));
+
+        let expected = vec![
+            "+---+---+",
+            "| x | y |",
+            "+---+---+",
+            "| 7 | 7 |",
+            "| 7 | 7 |",
+            "+---+---+",
+        ];
+        datafusion::assert_batches_eq!(expected, &collect(result, ctx.task_ctx()).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_aggregate() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT b, COUNT(a), SUM(a), MIN(a), MAX(a), AVG(a), COUNT(DISTINCT a), \
+             STDDEV(a), APPROX_PERCENTILE_CONT(a, 0.5) FROM t1 GROUP BY b",
+        )
+        .await?;
+        roundtrip_sql(&ctx, "SELECT COUNT(*) FROM t1").await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT a, b, COUNT(*) FROM t1 GROUP BY GROUPING SETS ((a), (a, b), ())",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn roundtrip_window() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT a, \
+             ROW_NUMBER() OVER (ORDER BY b), \
+             RANK() OVER (PARTITION BY b ORDER BY a), \
+             DENSE_RANK() OVER (ORDER BY a), \
+             PERCENT_RANK() OVER (ORDER BY a), \
+             CUME_DIST() OVER (ORDER BY a), \
+             LAG(a, 2, 0) OVER (ORDER BY a), \
+             LEAD(a) OVER (ORDER BY a), \
+             FIRST_VALUE(a) OVER (ORDER BY b), \
+             NTH_VALUE(a, 2) OVER (ORDER BY b), \
+             SUM(a) OVER (ORDER BY b ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) \
+             FROM t1",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn roundtrip_joins() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(
+            &ctx,
+            "SELECT * FROM t1 JOIN t2 ON t1.a = t2.a AND t1.b < t2.b",
+        )
+        .await?;
+        roundtrip_sql(&ctx, "SELECT * FROM t1 LEFT JOIN t2 ON t1.a = t2.b").await?;
+        roundtrip_sql(&ctx, "SELECT * FROM t1 CROSS JOIN t2").await?;
+
+        let left = ctx.table("t1")?.create_physical_plan().await?;
+        let right = ctx.table("t2")?.create_physical_plan().await?;
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(SortMergeJoinExec::try_new(
+            left,
+            right,
+            vec![(Column::new("a", 0), Column::new("a", 0))],
+            JoinType::Inner,
+            vec![arrow::compute::SortOptions::default()],
+            false,
+        )?);
+        roundtrip_test_with_context(plan, &ctx)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_values_and_memory() -> Result<()> {
+        let ctx = SessionContext::new();
+        let plan = ctx
+            .sql("SELECT * FROM (VALUES (1, 'a'), (2, NULL))")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let result = roundtrip_test_with_context(plan.clone(), &ctx)?;
+        assert_eq!(
+            pretty_format_batches(&collect(plan, ctx.task_ctx()).await?)?.to_string(),
+            pretty_format_batches(&collect(result, ctx.task_ctx()).await?)?.to_string()
+        );
+
+        let batch = RecordBatch::try_from_iter(vec![
+            ("a", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+            (
+                "b",
+                Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])) as ArrayRef,
+            ),
+        ])?;
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch.clone(), batch.clone()]],
+            batch.schema(),
+            Some(vec![1]),
+        )?);
+        let result = roundtrip_test_with_context(plan.clone(), &ctx)?;
+        assert_eq!(
+            pretty_format_batches(&collect(plan, ctx.task_ctx()).await?)?.to_string(),
+            pretty_format_batches(&collect(result, ctx.task_ctx()).await?)?.to_string()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_user_defined_functions() -> Result<()> {
+        let mut ctx = test_context().await?;
+        let fn_impl = |args: &[ArrayRef]| Ok(args[0].clone());
+        ctx.register_udf(create_udf(
+            "dummy",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            make_scalar_function(fn_impl),
+        ));
+        ctx.register_udaf(create_udaf(
+            "dummy_agg",
+            DataType::Int64,
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            Arc::new(|data_type| Ok(Box::new(MaxAccumulator::try_new(data_type)?))),
+            Arc::new(vec![DataType::Int64]),
+        ));
+        roundtrip_sql(&ctx, "SELECT dummy(a), dummy_agg(b) FROM t1 GROUP BY a").await?;
+
+        // the functions must be known to the registry used for decoding
+        let plan = ctx
+            .sql("SELECT dummy(a) FROM t1")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let result = roundtrip_test_with_context(plan, &SessionContext::new());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip_explain() -> Result<()> {
+        let ctx = test_context().await?;
+        roundtrip_sql(&ctx, "EXPLAIN SELECT a FROM t1").await?;
+        roundtrip_sql(&ctx, "EXPLAIN ANALYZE SELECT a FROM t1").await
+    }
+
+    /// A user-defined execution plan passing on the batches of its input
+    #[derive(Debug)]
+    struct TagExec {
+        input: Arc<dyn ExecutionPlan>,
+        tag: String,
+    }
+
+    impl ExecutionPlan for TagExec {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.input.schema()
+        }
+
+        fn output_partitioning(&self) -> Partitioning {
+            self.input.output_partitioning()
+        }
+
+        fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+            self.input.output_ordering()
+        }
+
+        fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+            vec![self.input.clone()]
+        }
+
+        fn with_new_children(
+            self: Arc<Self>,
+            children: Vec<Arc<dyn ExecutionPlan>>,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            Ok(Arc::new(TagExec {
+                input: children[0].clone(),
+                tag: self.tag.clone(),
+            }))
+        }
+
+        fn execute(
+            &self,
+            partition: usize,
+            context: Arc<TaskContext>,
+        ) -> Result<SendableRecordBatchStream> {
+            self.input.execute(partition, context)
+        }
+
+        fn fmt_as(
+            &self,
+            _t: DisplayFormatType,
+            f: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            write!(f, "TagExec: tag={}", self.tag)
+        }
+
+        fn statistics(&self) -> Statistics {
+            self.input.statistics()
+        }
+    }
+
+    #[derive(Debug)]
+    struct TagCodec {}
+
+    impl PhysicalExtensionCodec for TagCodec {
+        fn try_decode(
+            &self,
+            buf: &[u8],
+            inputs: &[Arc<dyn ExecutionPlan>],
+            _registry: &dyn FunctionRegistry,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            let tag = String::from_utf8(buf.to_vec())
+                .map_err(|e| DataFusionError::Internal(e.to_string()))?;
+            Ok(Arc::new(TagExec {
+                input: inputs[0].clone(),
+                tag,
+            }))
+        }
+
+        fn try_encode(
+            &self,
+            node: Arc<dyn ExecutionPlan>,
+            buf: &mut Vec<u8>,
+        ) -> Result<()> {
+            match node.as_any().downcast_ref::<TagExec>() {
+                Some(exec) => {
+                    buf.extend_from_slice(exec.tag.as_bytes());
+                    Ok(())
+                }
+                None => Err(DataFusionError::NotImplemented(format!(
+                    "TagCodec can't encode {:?}",
+                    node
+                ))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn roundtrip_extension() -> Result<()> {
+        let ctx = test_context().await?;
+        let input = ctx
+            .sql("SELECT a FROM t1 WHERE b > 1")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(TagExec {
+            input,
+            tag: "tenant-a".to_string(),
+        });
+
+        // the user-defined plan makes the whole plan unserializable without
+        // its codec
+        let err = roundtrip_test_with_context(plan.clone(), &ctx).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("PhysicalExtensionCodec is not provided"));
+
+        let result = roundtrip_test_with_codec(plan.clone(), &ctx, &TagCodec {})?;
+        assert_eq!(
+            pretty_format_batches(&collect(plan.clone(), ctx.task_ctx()).await?)?
+                .to_string(),
+            pretty_format_batches(&collect(result, ctx.task_ctx()).await?)?.to_string()
+        );
+
+        let mut registry = ExtensionCodecRegistry::new();
+        registry.register_execution_plan::<TagExec>("tag", Arc::new(TagCodec {}))?;
+        let result = roundtrip_test_with_codec(plan.clone(), &ctx, &registry)?;
+        assert!(result.as_any().is::<TagExec>());
+
+        let err = roundtrip_test_with_codec(plan, &ctx, &ExtensionCodecRegistry::new())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No extension codec registered for execution plan"));
+        Ok(())
+    }
+}
@@ -0,0 +1,594 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Code to convert DataFusion physical expressions and the parts of physical
+//! plans they are made of to protocol buffer format.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::datatypes::Schema;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use datafusion::datasource::file_format::file_type::FileCompressionType;
+use datafusion::datasource::listing::{FileRange, PartitionedFile};
+use datafusion::physical_expr::window::BuiltInWindowFunctionExpr;
+use datafusion::physical_expr::ScalarFunctionExpr;
+use datafusion::physical_plan::expressions::{
+    ApproxDistinct, ApproxMedian, ApproxPercentileCont, ApproxPercentileContWithWeight,
+    ArrayAgg, Avg, BinaryExpr, CaseExpr, CastExpr, Column, Correlation, Count,
+    Covariance, CovariancePop, CumeDist, DateTimeIntervalExpr, DistinctArrayAgg,
+    DistinctCount, DistinctSum, GetIndexedFieldExpr, Grouping, InListExpr, IsNotNullExpr,
+    IsNullExpr, Literal, Max, Median, MemoizedExpr, Min, NegativeExpr, NotExpr, NthValue,
+    NthValueKind, PhysicalSortExpr, Rank, RankType, RowNumber, ScalarSubqueryExpr,
+    ScalarSubqueryValue, Stddev, StddevPop, Sum, TryCastExpr, Variance, VariancePop,
+    WindowShift,
+};
+use datafusion::physical_plan::file_format::FileScanConfig;
+use datafusion::physical_plan::joins::utils::{JoinFilter, JoinSide};
+use datafusion::physical_plan::udaf::AggregateFunctionExpr;
+use datafusion::physical_plan::windows::{AggregateWindowExpr, BuiltInWindowExpr};
+use datafusion::physical_plan::{
+    AggregateExpr, ColumnStatistics, PhysicalExpr, Statistics, WindowExpr,
+};
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+use datafusion_expr::{AggregateFunction, BuiltInWindowFunction, BuiltinScalarFunction};
+
+use crate::protobuf::{
+    self, physical_aggregate_expr_node, physical_expr_node::ExprType,
+    physical_window_expr_node,
+};
+
+impl TryFrom<Arc<dyn AggregateExpr>> for protobuf::PhysicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(a: Arc<dyn AggregateExpr>) -> Result<Self> {
+        let expr = a
+            .expressions()
+            .into_iter()
+            .map(|e| e.try_into())
+            .collect::<Result<Vec<_>>>()?;
+
+        let (aggregate_function, distinct) = if let Some((fun, distinct)) =
+            aggregate_function(a.as_ref())
+        {
+            let fun = protobuf::AggregateFunction::from(&fun) as i32;
+            (
+                physical_aggregate_expr_node::AggregateFunction::AggrFunction(fun),
+                distinct,
+            )
+        } else if let Some(udaf) = a.as_any().downcast_ref::<AggregateFunctionExpr>() {
+            (
+                physical_aggregate_expr_node::AggregateFunction::UserDefinedAggrFunction(
+                    udaf.fun().name.clone(),
+                ),
+                false,
+            )
+        } else {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Aggregate function not supported: {:?}",
+                a
+            )));
+        };
+
+        Ok(protobuf::PhysicalExprNode {
+            expr_type: Some(ExprType::AggregateExpr(
+                protobuf::PhysicalAggregateExprNode {
+                    aggregate_function: Some(aggregate_function),
+                    expr,
+                    distinct,
+                },
+            )),
+        })
+    }
+}
+
+/// Returns the built-in aggregate function evaluated by `expr` and whether it
+/// is a `DISTINCT` aggregate, or `None` if `expr` is not a built-in aggregate
+fn aggregate_function(expr: &dyn AggregateExpr) -> Option<(AggregateFunction, bool)> {
+    let expr = expr.as_any();
+    let function = if expr.is::<Count>() {
+        (AggregateFunction::Count, false)
+    } else if expr.is::<DistinctCount>() {
+        (AggregateFunction::Count, true)
+    } else if expr.is::<Grouping>() {
+        (AggregateFunction::Grouping, false)
+    } else if expr.is::<Sum>() {
+        (AggregateFunction::Sum, false)
+    } else if expr.is::<DistinctSum>() {
+        (AggregateFunction::Sum, true)
+    } else if expr.is::<ApproxDistinct>() {
+        (AggregateFunction::ApproxDistinct, false)
+    } else if expr.is::<ArrayAgg>() {
+        (AggregateFunction::ArrayAgg, false)
+    } else if expr.is::<DistinctArrayAgg>() {
+        (AggregateFunction::ArrayAgg, true)
+    } else if expr.is::<Min>() {
+        (AggregateFunction::Min, false)
+    } else if expr.is::<Max>() {
+        (AggregateFunction::Max, false)
+    } else if expr.is::<Avg>() {
+        (AggregateFunction::Avg, false)
+    } else if expr.is::<Variance>() {
+        (AggregateFunction::Variance, false)
+    } else if expr.is::<VariancePop>() {
+        (AggregateFunction::VariancePop, false)
+    } else if expr.is::<Covariance>() {
+        (AggregateFunction::Covariance, false)
+    } else if expr.is::<CovariancePop>() {
+        (AggregateFunction::CovariancePop, false)
+    } else if expr.is::<Stddev>() {
+        (AggregateFunction::Stddev, false)
+    } else if expr.is::<StddevPop>() {
+        (AggregateFunction::StddevPop, false)
+    } else if expr.is::<Correlation>() {
+        (AggregateFunction::Correlation, false)
+    } else if expr.is::<ApproxPercentileCont>() {
+        (AggregateFunction::ApproxPercentileCont, false)
+    } else if expr.is::<ApproxPercentileContWithWeight>() {
+        (AggregateFunction::ApproxPercentileContWithWeight, false)
+    } else if expr.is::<ApproxMedian>() {
+        (AggregateFunction::ApproxMedian, false)
+    } else if expr.is::<Median>() {
+        (AggregateFunction::Median, false)
+    } else {
+        return None;
+    };
+    Some(function)
+}
+
+impl TryFrom<Arc<dyn WindowExpr>> for protobuf::PhysicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(window_expr: Arc<dyn WindowExpr>) -> Result<Self> {
+        let expr = window_expr.as_any();
+
+        let (window_function, args, window_frame, ignore_nulls) =
+            if let Some(built_in) = expr.downcast_ref::<BuiltInWindowExpr>() {
+                let (fun, args, ignore_nulls) =
+                    built_in_window_function(built_in.get_built_in_func_expr().as_ref())?;
+                let fun = protobuf::BuiltInWindowFunction::from(&fun) as i32;
+                (
+                    physical_window_expr_node::WindowFunction::BuiltInFunction(fun),
+                    args,
+                    None,
+                    ignore_nulls,
+                )
+            } else if let Some(aggregate) = expr.downcast_ref::<AggregateWindowExpr>() {
+                let aggregate_expr = aggregate.get_aggregate_expr();
+                let fun = match aggregate_function(aggregate_expr.as_ref()) {
+                    Some((fun, false)) => fun,
+                    _ => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "Aggregate window function not supported: {:?}",
+                            aggregate_expr
+                        )))
+                    }
+                };
+                let fun = protobuf::AggregateFunction::from(&fun) as i32;
+                let window_frame = aggregate
+                    .get_window_frame()
+                    .as_ref()
+                    .map(|frame| frame.as_ref().try_into())
+                    .transpose()?
+                    .map(physical_window_expr_node::WindowFrame::Frame);
+                (
+                    physical_window_expr_node::WindowFunction::AggrFunction(fun),
+                    aggregate_expr.expressions(),
+                    window_frame,
+                    false,
+                )
+            } else {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "WindowExpr not supported: {:?}",
+                    window_expr
+                )));
+            };
+
+        let args = args
+            .into_iter()
+            .map(|e| e.try_into())
+            .collect::<Result<Vec<_>>>()?;
+        let partition_by = window_expr
+            .partition_by()
+            .iter()
+            .map(|e| e.clone().try_into())
+            .collect::<Result<Vec<_>>>()?;
+        let order_by = window_expr
+            .order_by()
+            .iter()
+            .map(|e| e.try_into())
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(protobuf::PhysicalExprNode {
+            expr_type: Some(ExprType::WindowExpr(protobuf::PhysicalWindowExprNode {
+                window_function: Some(window_function),
+                args,
+                partition_by,
+                order_by,
+                window_frame,
+                name: window_expr.name().to_string(),
+                ignore_nulls,
+            })),
+        })
+    }
+}
+
+/// Returns the built-in window function evaluated by `expr`, together with
+/// the arguments it has to be created with and whether it ignores nulls
+fn built_in_window_function(
+    expr: &dyn BuiltInWindowFunctionExpr,
+) -> Result<(BuiltInWindowFunction, Vec<Arc<dyn PhysicalExpr>>, bool)> {
+    let any = expr.as_any();
+    if any.is::<RowNumber>() {
+        Ok((BuiltInWindowFunction::RowNumber, vec![], false))
+    } else if let Some(rank) = any.downcast_ref::<Rank>() {
+        let fun = match rank.rank_type() {
+            RankType::Basic => BuiltInWindowFunction::Rank,
+            RankType::Dense => BuiltInWindowFunction::DenseRank,
+            RankType::Percent => BuiltInWindowFunction::PercentRank,
+        };
+        Ok((fun, vec![], false))
+    } else if any.is::<CumeDist>() {
+        Ok((BuiltInWindowFunction::CumeDist, vec![], false))
+    } else if let Some(nth_value) = any.downcast_ref::<NthValue>() {
+        let mut args = nth_value.expressions();
+        let fun = match nth_value.kind() {
+            NthValueKind::First => BuiltInWindowFunction::FirstValue,
+            NthValueKind::Last => BuiltInWindowFunction::LastValue,
+            NthValueKind::Nth(n) => {
+                args.push(Arc::new(Literal::new(ScalarValue::Int64(Some(n as i64)))));
+                BuiltInWindowFunction::NthValue
+            }
+        };
+        Ok((fun, args, false))
+    } else if let Some(shift) = any.downcast_ref::<WindowShift>() {
+        let mut expressions = shift.expressions().into_iter();
+        let mut args = expressions.next().into_iter().collect::<Vec<_>>();
+        let (fun, offset) = if shift.shift_offset() >= 0 {
+            (BuiltInWindowFunction::Lag, shift.shift_offset())
+        } else {
+            (BuiltInWindowFunction::Lead, -shift.shift_offset())
+        };
+        args.push(Arc::new(Literal::new(ScalarValue::Int64(Some(offset)))));
+        if let Some(default_value) = shift.default_value() {
+            args.push(Arc::new(Literal::new(default_value.clone())));
+        } else {
+            args.extend(expressions);
+        }
+        Ok((fun, args, shift.ignore_nulls()))
+    } else {
+        Err(DataFusionError::NotImplemented(format!(
+            "BuiltInWindowFunctionExpr not supported: {}",
+            expr.name()
+        )))
+    }
+}
+
+impl TryFrom<Arc<dyn PhysicalExpr>> for protobuf::PhysicalExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(value: Arc<dyn PhysicalExpr>) -> Result<Self> {
+        let expr = value.as_any();
+
+        // the memoization of constant subexpressions is not serialized
+        if let Some(expr) = expr.downcast_ref::<MemoizedExpr>() {
+            return expr.expr().clone().try_into();
+        }
+
+        let expr_type = if let Some(expr) = expr.downcast_ref::<Column>() {
+            ExprType::Column(expr.into())
+        } else if let Some(expr) = expr.downcast_ref::<Literal>() {
+            ExprType::Literal(expr.value().try_into()?)
+        } else if let Some(expr) = expr.downcast_ref::<BinaryExpr>() {
+            ExprType::BinaryExpr(Box::new(protobuf::PhysicalBinaryExprNode {
+                l: Some(Box::new(expr.left().clone().try_into()?)),
+                r: Some(Box::new(expr.right().clone().try_into()?)),
+                op: format!("{:?}", expr.op()),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<IsNullExpr>() {
+            ExprType::IsNullExpr(Box::new(protobuf::PhysicalIsNull {
+                expr: Some(Box::new(expr.arg().clone().try_into()?)),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<IsNotNullExpr>() {
+            ExprType::IsNotNullExpr(Box::new(protobuf::PhysicalIsNotNull {
+                expr: Some(Box::new(expr.arg().clone().try_into()?)),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<NotExpr>() {
+            ExprType::NotExpr(Box::new(protobuf::PhysicalNot {
+                expr: Some(Box::new(expr.arg().clone().try_into()?)),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<CaseExpr>() {
+            ExprType::Case(Box::new(protobuf::PhysicalCaseNode {
+                expr: expr
+                    .expr()
+                    .as_ref()
+                    .map(|e| e.clone().try_into().map(Box::new))
+                    .transpose()?,
+                when_then_expr: expr
+                    .when_then_expr()
+                    .iter()
+                    .map(|(when_expr, then_expr)| {
+                        Ok(protobuf::PhysicalWhenThen {
+                            when_expr: Some(when_expr.clone().try_into()?),
+                            then_expr: Some(then_expr.clone().try_into()?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                else_expr: expr
+                    .else_expr()
+                    .map(|e| e.clone().try_into().map(Box::new))
+                    .transpose()?,
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<CastExpr>() {
+            ExprType::Cast(Box::new(protobuf::PhysicalCastNode {
+                expr: Some(Box::new(expr.expr().clone().try_into()?)),
+                arrow_type: Some(expr.cast_type().try_into()?),
+                time_zone: expr
+                    .time_zone()
+                    .map(|time_zone| time_zone.to_string())
+                    .unwrap_or_default(),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<TryCastExpr>() {
+            ExprType::TryCast(Box::new(protobuf::PhysicalTryCastNode {
+                expr: Some(Box::new(expr.expr().clone().try_into()?)),
+                arrow_type: Some(expr.cast_type().try_into()?),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<NegativeExpr>() {
+            ExprType::Negative(Box::new(protobuf::PhysicalNegativeNode {
+                expr: Some(Box::new(expr.arg().clone().try_into()?)),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<InListExpr>() {
+            ExprType::InList(Box::new(protobuf::PhysicalInListNode {
+                expr: Some(Box::new(expr.expr().clone().try_into()?)),
+                list: expr
+                    .list()
+                    .iter()
+                    .map(|e| e.clone().try_into())
+                    .collect::<Result<Vec<_>>>()?,
+                negated: expr.negated(),
+            }))
+        } else if let Some(expr) = expr.downcast_ref::<ScalarFunctionExpr>() {
+            let args = expr
+                .args()
+                .iter()
+                .map(|e| e.clone().try_into())
+                .collect::<Result<Vec<_>>>()?;
+            if let Ok(fun) = BuiltinScalarFunction::from_str(expr.name()) {
+                let fun: protobuf::ScalarFunction = (&fun).try_into()?;
+                ExprType::ScalarFunction(protobuf::PhysicalScalarFunctionNode {
+                    name: expr.name().to_string(),
+                    fun: fun as i32,
+                    args,
+                })
+            } else {
+                ExprType::ScalarUdf(protobuf::PhysicalScalarUdfNode {
+                    name: expr.name().to_string(),
+                    args,
+                })
+            }
+        } else if let Some(expr) = expr.downcast_ref::<DateTimeIntervalExpr>() {
+            ExprType::DateTimeIntervalExpr(Box::new(
+                protobuf::PhysicalDateTimeIntervalExprNode {
+                    l: Some(Box::new(expr.lhs().clone().try_into()?)),
+                    r: Some(Box::new(expr.rhs().clone().try_into()?)),
+                    op: format!("{:?}", expr.op()),
+                },
+            ))
+        } else if let Some(expr) = expr.downcast_ref::<ScalarSubqueryExpr>() {
+            ExprType::ScalarSubquery(protobuf::PhysicalScalarSubqueryExprNode {
+                id: scalar_subquery_id(expr.value()),
+                arrow_type: Some(expr.data_type(&Schema::empty())?.try_into()?),
+            })
+        } else if let Some(expr) = expr.downcast_ref::<GetIndexedFieldExpr>() {
+            ExprType::GetIndexedFieldExpr(Box::new(
+                protobuf::PhysicalGetIndexedFieldExprNode {
+                    arg: Some(Box::new(expr.arg().clone().try_into()?)),
+                    key: Some(expr.key().try_into()?),
+                },
+            ))
+        } else {
+            return Err(DataFusionError::NotImplemented(format!(
+                "physical_plan::to_proto() unsupported expression {:?}",
+                value
+            )));
+        };
+
+        Ok(protobuf::PhysicalExprNode {
+            expr_type: Some(expr_type),
+        })
+    }
+}
+
+/// The id of the scalar subquery whose value is `value`, which is the same
+/// for all its references
+pub(crate) fn scalar_subquery_id(value: &Arc<ScalarSubqueryValue>) -> u64 {
+    Arc::as_ptr(value) as usize as u64
+}
+
+impl From<&Column> for protobuf::PhysicalColumn {
+    fn from(c: &Column) -> Self {
+        protobuf::PhysicalColumn {
+            name: c.name().to_string(),
+            index: c.index() as u32,
+        }
+    }
+}
+
+impl TryFrom<&PhysicalSortExpr> for protobuf::PhysicalSortExprNode {
+    type Error = DataFusionError;
+
+    fn try_from(sort_expr: &PhysicalSortExpr) -> Result<Self> {
+        Ok(protobuf::PhysicalSortExprNode {
+            expr: Some(Box::new(sort_expr.expr.clone().try_into()?)),
+            asc: !sort_expr.options.descending,
+            nulls_first: sort_expr.options.nulls_first,
+        })
+    }
+}
+
+impl TryFrom<&JoinFilter> for protobuf::JoinFilter {
+    type Error = DataFusionError;
+
+    fn try_from(filter: &JoinFilter) -> Result<Self> {
+        let column_indices = filter
+            .column_indices()
+            .iter()
+            .map(|index| {
+                let side = match index.side {
+                    JoinSide::Left => protobuf::JoinSide::LeftSide,
+                    JoinSide::Right => protobuf::JoinSide::RightSide,
+                };
+                protobuf::ColumnIndex {
+                    index: index.index as u32,
+                    side: side as i32,
+                }
+            })
+            .collect();
+        Ok(protobuf::JoinFilter {
+            expression: Some(filter.expression().clone().try_into()?),
+            column_indices,
+            schema: Some(filter.schema().try_into()?),
+        })
+    }
+}
+
+impl TryFrom<&PartitionedFile> for protobuf::PartitionedFile {
+    type Error = DataFusionError;
+
+    fn try_from(file: &PartitionedFile) -> Result<Self> {
+        Ok(protobuf::PartitionedFile {
+            path: file.object_meta.location.as_ref().to_string(),
+            size: file.object_meta.size as u64,
+            last_modified_ns: file.object_meta.last_modified.timestamp_nanos() as u64,
+            partition_values: file
+                .partition_values
+                .iter()
+                .map(|v| v.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            range: file.range.as_ref().map(|range| range.into()),
+        })
+    }
+}
+
+impl From<&FileRange> for protobuf::FileRange {
+    fn from(range: &FileRange) -> Self {
+        protobuf::FileRange {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+/// Unknown counts are encoded as negative values
+fn optional_count(count: Option<usize>) -> i64 {
+    count.map(|c| c as i64).unwrap_or(-1)
+}
+
+impl TryFrom<&ColumnStatistics> for protobuf::ColumnStats {
+    type Error = DataFusionError;
+
+    fn try_from(stats: &ColumnStatistics) -> Result<Self> {
+        Ok(protobuf::ColumnStats {
+            min_value: stats.min_value.as_ref().map(|v| v.try_into()).transpose()?,
+            max_value: stats.max_value.as_ref().map(|v| v.try_into()).transpose()?,
+            null_count: optional_count(stats.null_count),
+            distinct_count: optional_count(stats.distinct_count),
+        })
+    }
+}
+
+impl TryFrom<&Statistics> for protobuf::Statistics {
+    type Error = DataFusionError;
+
+    fn try_from(stats: &Statistics) -> Result<Self> {
+        Ok(protobuf::Statistics {
+            num_rows: optional_count(stats.num_rows),
+            total_byte_size: optional_count(stats.total_byte_size),
+            column_stats: stats
+                .column_statistics
+                .iter()
+                .flatten()
+                .map(|s| s.try_into())
+                .collect::<Result<Vec<_>>>()?,
+            is_exact: stats.is_exact,
+        })
+    }
+}
+
+impl TryFrom<&FileScanConfig> for protobuf::FileScanExecConf {
+    type Error = DataFusionError;
+
+    fn try_from(conf: &FileScanConfig) -> Result<Self> {
+        let file_groups = conf
+            .file_groups
+            .iter()
+            .map(|files| {
+                Ok(protobuf::FileGroup {
+                    files: files
+                        .iter()
+                        .map(|f| f.try_into())
+                        .collect::<Result<Vec<_>>>()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(protobuf::FileScanExecConf {
+            file_groups,
+            schema: Some(conf.file_schema.as_ref().try_into()?),
+            projection: conf.projection.as_ref().map(|columns| {
+                protobuf::ScanProjection {
+                    columns: columns.iter().map(|c| *c as u32).collect(),
+                }
+            }),
+            limit: conf.limit.map(|limit| protobuf::ScanLimit {
+                limit: limit as u32,
+            }),
+            statistics: Some((&conf.statistics).try_into()?),
+            table_partition_cols: conf.table_partition_cols.clone(),
+            object_store_url: conf.object_store_url.as_str().to_string(),
+        })
+    }
+}
+
+impl From<&FileCompressionType> for protobuf::FileCompressionType {
+    fn from(compression: &FileCompressionType) -> Self {
+        match compression {
+            FileCompressionType::UNCOMPRESSED => {
+                protobuf::FileCompressionType::Uncompressed
+            }
+            FileCompressionType::GZIP => protobuf::FileCompressionType::Gzip,
+            FileCompressionType::BZIP2 => protobuf::FileCompressionType::Bzip2,
+        }
+    }
+}
+
+/// Encodes `batches` in the Arrow IPC streaming format
+pub(crate) fn batches_to_ipc(
+    schema: &Schema,
+    batches: &[RecordBatch],
+) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    {
+        let mut writer = StreamWriter::try_new(&mut buf, schema)?;
+        for batch in batches {
+            writer.write(batch)?;
+        }
+        writer.finish()?;
+    }
+    Ok(buf)
+}
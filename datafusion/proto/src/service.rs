@@ -0,0 +1,743 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A gRPC service executing the logical plans serialized by
+//! [`logical_plan_to_bytes`] on a server, and streaming their results back
+//! as Arrow IPC.
+//!
+//! The service is an [Arrow Flight](arrow_flight) service whose `DoGet`
+//! tickets are [`protobuf::ExecutePlanRequest`]s: the serialized plan and the
+//! session configuration to run it with. The plans are deserialized within
+//! the [`DeserializeLimits`] of the service, and the clients may only change
+//! the configuration options it allows.
+//!
+//! ```no_run
+//! use datafusion::prelude::*;
+//! use datafusion_common::Result;
+//! use datafusion_proto::service::{PlanClient, PlanService};
+//! use futures::StreamExt;
+//!
+//! # async fn example() -> Result<()> {
+//! // the server resolves the functions and the object stores of the plans
+//! let ctx = SessionContext::new();
+//! tokio::spawn(
+//!     tonic::transport::Server::builder()
+//!         .add_service(PlanService::new(ctx).into_server())
+//!         .serve("127.0.0.1:50051".parse().unwrap()),
+//! );
+//!
+//! // the client plans the queries
+//! let ctx = SessionContext::new();
+//! ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new()).await?;
+//! let plan = ctx.sql("SELECT a FROM t").await?.to_logical_plan()?;
+//! let mut client = PlanClient::connect("http://127.0.0.1:50051").await?;
+//! let mut results = client.execute(&plan, &SessionConfig::new()).await?;
+//! while let Some(batch) = results.next().await {
+//!     println!("{} rows", batch?.num_rows());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow::array::ArrayRef;
+use arrow::buffer::Buffer;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::read_dictionary;
+use arrow::ipc::writer::{DictionaryTracker, IpcDataGenerator, IpcWriteOptions};
+use arrow::ipc::{root_as_message, MessageHeader};
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_service_client::FlightServiceClient;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::utils::flight_data_to_arrow_batch;
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use datafusion::config::{
+    OPT_BATCH_SIZE, OPT_COALESCE_BATCHES, OPT_COALESCE_TARGET_BATCH_SIZE,
+    OPT_EXPLAIN_LOGICAL_PLAN_ONLY, OPT_EXPLAIN_PHYSICAL_PLAN_ONLY, OPT_OUTPUT_COERCION,
+    OPT_TIME_ZONE,
+};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{execute_stream, SendableRecordBatchStream};
+use datafusion::prelude::{SessionConfig, SessionContext};
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+use datafusion_expr::LogicalPlan;
+use futures::{Stream, StreamExt};
+use prost::Message;
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::bytes::{
+    logical_plan_from_bytes_with_limits, logical_plan_to_bytes, DeserializeLimits,
+};
+use crate::protobuf;
+
+/// The Flight service executing serialized logical plans with a
+/// [`SessionContext`]
+///
+/// Each plan runs in a session of its own, sharing the catalogs, functions
+/// and runtime of the context but configured by the request. Only `DoGet` is
+/// implemented, streaming the results encoded by a [`ResultEncoder`].
+///
+/// The requests may only change the options of [`DEFAULT_ALLOWED_OPTIONS`]
+/// by default, which do not affect the resources of the server beyond the
+/// execution of their plan, and their plans are limited by
+/// [`PlanService::default_limits`].
+#[derive(Clone)]
+pub struct PlanService {
+    ctx: SessionContext,
+    limits: DeserializeLimits,
+    allowed_options: HashSet<String>,
+}
+
+/// The configuration options the requests may change by default
+pub const DEFAULT_ALLOWED_OPTIONS: &[&str] = &[
+    OPT_BATCH_SIZE,
+    OPT_COALESCE_BATCHES,
+    OPT_COALESCE_TARGET_BATCH_SIZE,
+    OPT_EXPLAIN_LOGICAL_PLAN_ONLY,
+    OPT_EXPLAIN_PHYSICAL_PLAN_ONLY,
+    OPT_OUTPUT_COERCION,
+    OPT_TIME_ZONE,
+];
+
+impl PlanService {
+    /// Creates a service executing the plans with `ctx`
+    pub fn new(ctx: SessionContext) -> Self {
+        Self {
+            ctx,
+            limits: Self::default_limits(),
+            allowed_options: DEFAULT_ALLOWED_OPTIONS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+        }
+    }
+
+    /// The limits of the plans of the requests by default: 16 MiB, 10,000
+    /// nodes and 128 levels of nested expressions
+    pub fn default_limits() -> DeserializeLimits {
+        DeserializeLimits::new()
+            .with_max_size(16 << 20)
+            .with_max_nodes(10_000)
+            .with_max_depth(128)
+    }
+
+    /// Deserializes the plans of the requests within `limits`
+    pub fn with_limits(mut self, limits: DeserializeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Only allows the requests to change the configuration options of
+    /// `keys`, instead of those of [`DEFAULT_ALLOWED_OPTIONS`]
+    pub fn with_allowed_options(
+        mut self,
+        keys: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.allowed_options = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Wraps the service in a server, to add to a
+    /// [`tonic::transport::Server`]
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Plans and executes the plan of `request`, failing if it exceeds the
+    /// limits of the service or changes options it does not allow
+    pub async fn execute(
+        &self,
+        request: &protobuf::ExecutePlanRequest,
+    ) -> Result<SendableRecordBatchStream> {
+        let mut state = self.ctx.state();
+
+        // the options of the request must not change those of the server
+        let mut options = state.config.config_options.read().clone();
+        for (key, value) in &request.config_options {
+            let value: ScalarValue = value.try_into()?;
+            // the clients send all their options, which only matter when
+            // they differ from those of the server
+            if options.get(key).as_ref() == Some(&value) {
+                continue;
+            }
+            if !self.allowed_options.contains(key) {
+                return Err(DataFusionError::Plan(format!(
+                    "The configuration option {} can not be set by the requests",
+                    key
+                )));
+            }
+            options.set(key, value);
+        }
+        state.config.config_options = options.into_shareable();
+        if request.target_partitions > 0 {
+            state.config.target_partitions = request.target_partitions as usize;
+        }
+
+        let ctx = SessionContext::with_state(state);
+        let plan =
+            logical_plan_from_bytes_with_limits(&request.plan, &ctx, &self.limits)?;
+        let plan = ctx.create_physical_plan(&plan).await?;
+        execute_stream(plan, ctx.task_ctx()).await
+    }
+}
+
+type BoxedFlightStream<T> =
+    Pin<Box<dyn Stream<Item = std::result::Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl FlightService for PlanService {
+    type HandshakeStream = BoxedFlightStream<HandshakeResponse>;
+    type ListFlightsStream = BoxedFlightStream<FlightInfo>;
+    type DoGetStream = BoxedFlightStream<FlightData>;
+    type DoPutStream = BoxedFlightStream<PutResult>;
+    type DoActionStream = BoxedFlightStream<arrow_flight::Result>;
+    type ListActionsStream = BoxedFlightStream<ActionType>;
+    type DoExchangeStream = BoxedFlightStream<FlightData>;
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> std::result::Result<Response<Self::DoGetStream>, Status> {
+        let ticket = request.into_inner().ticket;
+        let request = protobuf::ExecutePlanRequest::decode(&ticket[..])
+            .map_err(|e| Status::invalid_argument(format!("Invalid ticket: {}", e)))?;
+        let stream = self.execute(&request).await.map_err(to_status)?;
+
+        // the schema is sent first, followed by the batches
+        let mut encoder = ResultEncoder::new(&stream.schema());
+        let schema = encoder.encode_schema();
+        let batches = stream.flat_map(move |batch| {
+            let data = batch
+                .map_err(|e| to_status(e.into()))
+                .and_then(|batch| encoder.encode(&batch).map_err(to_status));
+            let data = match data {
+                Ok(data) => data.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            futures::stream::iter(data)
+        });
+        let output = futures::stream::once(async { Ok(schema) }).chain(batches);
+        Ok(Response::new(Box::pin(output) as Self::DoGetStream))
+    }
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> std::result::Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> std::result::Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn get_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> std::result::Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> std::result::Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+}
+
+/// Encodes the record batches of a stream of results as Flight data
+///
+/// Every dictionary array of the schema is given a dictionary id of its own,
+/// and its dictionary is only sent before the first batch and when it changes,
+/// replacing the one sent before, instead of with every batch.
+pub struct ResultEncoder {
+    schema: SchemaRef,
+    options: IpcWriteOptions,
+    generator: IpcDataGenerator,
+    dictionaries: DictionaryTracker,
+}
+
+impl ResultEncoder {
+    /// Creates an encoder of the batches of `schema`
+    pub fn new(schema: &Schema) -> Self {
+        let mut next_id = 0;
+        let fields = with_dictionary_ids(schema.fields(), &mut next_id);
+        Self {
+            schema: Arc::new(Schema::new_with_metadata(
+                fields,
+                schema.metadata().clone(),
+            )),
+            options: IpcWriteOptions::default(),
+            generator: IpcDataGenerator::default(),
+            dictionaries: DictionaryTracker::new(false),
+        }
+    }
+
+    /// The Flight data of the schema, sent before the batches
+    pub fn encode_schema(&self) -> FlightData {
+        SchemaAsIpc::new(&self.schema, &self.options).into()
+    }
+
+    /// The Flight data of `batch`: the dictionaries that changed since the
+    /// previous batch, followed by the batch
+    pub fn encode(&mut self, batch: &RecordBatch) -> Result<Vec<FlightData>> {
+        let batch = RecordBatch::try_new(self.schema.clone(), batch.columns().to_vec())?;
+        let (dictionaries, data) = self.generator.encoded_batch(
+            &batch,
+            &mut self.dictionaries,
+            &self.options,
+        )?;
+        Ok(dictionaries
+            .into_iter()
+            .chain(std::iter::once(data))
+            .map(FlightData::from)
+            .collect())
+    }
+}
+
+/// `fields`, with the dictionary ids from `next_id` onwards
+fn with_dictionary_ids(fields: &[Field], next_id: &mut i64) -> Vec<Field> {
+    fields
+        .iter()
+        .map(|field| {
+            let data_type = match field.data_type() {
+                DataType::Dictionary(_, _) => {
+                    let dict_id = *next_id;
+                    *next_id += 1;
+                    return Field::new_dict(
+                        field.name(),
+                        field.data_type().clone(),
+                        field.is_nullable(),
+                        dict_id,
+                        field.dict_is_ordered().unwrap_or_default(),
+                    )
+                    .with_metadata(field.metadata().cloned());
+                }
+                DataType::Struct(children) => {
+                    DataType::Struct(with_dictionary_ids(children, next_id))
+                }
+                DataType::List(child) => DataType::List(Box::new(
+                    with_dictionary_ids(std::slice::from_ref(child), next_id).remove(0),
+                )),
+                DataType::LargeList(child) => DataType::LargeList(Box::new(
+                    with_dictionary_ids(std::slice::from_ref(child), next_id).remove(0),
+                )),
+                data_type => data_type.clone(),
+            };
+            Field::new(field.name(), data_type, field.is_nullable())
+                .with_metadata(field.metadata().cloned())
+        })
+        .collect()
+}
+
+/// Decodes the Flight data encoded by a [`ResultEncoder`]
+#[derive(Debug, Default)]
+pub struct ResultDecoder {
+    schema: Option<SchemaRef>,
+    dictionaries: HashMap<i64, ArrayRef>,
+}
+
+impl ResultDecoder {
+    /// Creates a decoder expecting the schema first
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The schema of the batches, once decoded
+    pub fn schema(&self) -> Option<SchemaRef> {
+        self.schema.clone()
+    }
+
+    /// Decodes `data`, returning the record batch it holds, if any
+    pub fn decode(&mut self, data: &FlightData) -> Result<Option<RecordBatch>> {
+        let message = root_as_message(&data.data_header).map_err(|e| {
+            DataFusionError::Execution(format!("Invalid Flight data header: {}", e))
+        })?;
+        match message.header_type() {
+            MessageHeader::Schema => {
+                self.schema = Some(Arc::new(Schema::try_from(data)?));
+                self.dictionaries.clear();
+                Ok(None)
+            }
+            MessageHeader::DictionaryBatch => {
+                let batch = message.header_as_dictionary_batch().ok_or_else(|| {
+                    DataFusionError::Execution(
+                        "Invalid Flight dictionary batch".to_string(),
+                    )
+                })?;
+                read_dictionary(
+                    &Buffer::from(&data.data_body),
+                    batch,
+                    &self.expect_schema()?,
+                    &mut self.dictionaries,
+                    &message.version(),
+                )?;
+                Ok(None)
+            }
+            MessageHeader::RecordBatch => Ok(Some(flight_data_to_arrow_batch(
+                data,
+                self.expect_schema()?,
+                &self.dictionaries,
+            )?)),
+            other => Err(DataFusionError::Execution(format!(
+                "Unexpected Flight data of type {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn expect_schema(&self) -> Result<SchemaRef> {
+        self.schema.clone().ok_or_else(|| {
+            DataFusionError::Execution("The Flight data has no schema".to_string())
+        })
+    }
+}
+
+fn to_status(e: DataFusionError) -> Status {
+    match e {
+        DataFusionError::Plan(_)
+        | DataFusionError::SQL(_)
+        | DataFusionError::SchemaError(_) => Status::invalid_argument(e.to_string()),
+        DataFusionError::NotImplemented(_) => Status::unimplemented(e.to_string()),
+        DataFusionError::ResourcesExhausted(_) => {
+            Status::resource_exhausted(e.to_string())
+        }
+        e => Status::internal(e.to_string()),
+    }
+}
+
+fn from_status(status: Status) -> DataFusionError {
+    DataFusionError::External(Box::new(status))
+}
+
+/// A client of a [`PlanService`]
+#[derive(Debug, Clone)]
+pub struct PlanClient {
+    client: FlightServiceClient<Channel>,
+}
+
+impl PlanClient {
+    /// Connects to the service at `dst`, such as `http://localhost:50051`
+    pub async fn connect(dst: impl Into<String>) -> Result<Self> {
+        let client = FlightServiceClient::connect(dst.into())
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        Ok(Self { client })
+    }
+
+    /// Executes `plan` on the server with the options and the target
+    /// partitions of `config`, returning the stream of its results
+    ///
+    /// The files scanned by the plan must be readable by the server.
+    pub async fn execute(
+        &mut self,
+        plan: &LogicalPlan,
+        config: &SessionConfig,
+    ) -> Result<SendableRecordBatchStream> {
+        let request = execute_plan_request(plan, config)?;
+        let ticket = Ticket {
+            ticket: request.encode_to_vec(),
+        };
+        let stream = self
+            .client
+            .do_get(ticket)
+            .await
+            .map_err(from_status)?
+            .into_inner();
+        read_results(stream).await
+    }
+}
+
+/// The request executing `plan` with the session configuration `config`
+pub fn execute_plan_request(
+    plan: &LogicalPlan,
+    config: &SessionConfig,
+) -> Result<protobuf::ExecutePlanRequest> {
+    let config_options = config
+        .config_options
+        .read()
+        .options()
+        .iter()
+        .map(|(key, value)| {
+            let value: protobuf::ScalarValue = value.try_into()?;
+            Ok((key.clone(), value))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    Ok(protobuf::ExecutePlanRequest {
+        plan: logical_plan_to_bytes(plan)?.to_vec(),
+        config_options,
+        target_partitions: config.target_partitions as u64,
+    })
+}
+
+/// Reads the schema then the record batches sent by [`PlanService::do_get`]
+async fn read_results(
+    mut stream: impl Stream<Item = std::result::Result<FlightData, Status>>
+        + Send
+        + Unpin
+        + 'static,
+) -> Result<SendableRecordBatchStream> {
+    let mut decoder = ResultDecoder::new();
+    let schema = match stream.next().await {
+        Some(data) => {
+            decoder.decode(&data.map_err(from_status)?)?;
+            decoder.expect_schema()?
+        }
+        None => {
+            return Err(DataFusionError::Execution(
+                "The plan service returned no schema".to_string(),
+            ))
+        }
+    };
+
+    let batches = stream.filter_map(move |data| {
+        let batch = data
+            .map_err(from_status)
+            .and_then(|data| decoder.decode(&data))
+            .map_err(ArrowError::from);
+        futures::future::ready(batch.transpose())
+    });
+    Ok(Box::pin(RecordBatchStreamAdapter::new(schema, batches)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+    use datafusion::config::OPT_OPTIMIZER_MAX_PASSES;
+    use datafusion::prelude::CsvReadOptions;
+
+    async fn context() -> Result<SessionContext> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new())
+            .await?;
+        Ok(ctx)
+    }
+
+    async fn do_get(
+        service: &PlanService,
+        plan: &LogicalPlan,
+        config: &SessionConfig,
+    ) -> Result<Vec<arrow::record_batch::RecordBatch>> {
+        let request = execute_plan_request(plan, config)?;
+        let ticket = Ticket {
+            ticket: request.encode_to_vec(),
+        };
+        let stream = service
+            .do_get(Request::new(ticket))
+            .await
+            .map_err(from_status)?
+            .into_inner();
+        let results = read_results(stream).await?;
+        datafusion::physical_plan::common::collect(results).await
+    }
+
+    #[tokio::test]
+    async fn execute_plan() -> Result<()> {
+        let service = PlanService::new(context().await?);
+
+        let client = context().await?;
+        let plan = client
+            .sql("SELECT a, b * 2 AS c FROM t WHERE a > 0 ORDER BY a")
+            .await?
+            .to_logical_plan()?;
+        let expected = client
+            .sql("SELECT a, b * 2 AS c FROM t WHERE a > 0 ORDER BY a")
+            .await?;
+        let expected = pretty_format_batches(&expected.collect().await?)?.to_string();
+
+        // the configuration of the request applies to its execution only
+        let config = SessionConfig::new()
+            .with_batch_size(1)
+            .with_target_partitions(3);
+        let batches = do_get(&service, &plan, &config).await?;
+        assert_eq!(pretty_format_batches(&batches)?.to_string(), expected);
+        assert_eq!(
+            service
+                .ctx
+                .state()
+                .config
+                .config_options
+                .read()
+                .get_u64(OPT_BATCH_SIZE),
+            Some(8192)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn execute_plan_within_limits() -> Result<()> {
+        let client = context().await?;
+        let plan = client
+            .sql("SELECT a FROM t WHERE a > 0")
+            .await?
+            .to_logical_plan()?;
+
+        // the options not allowed can not be changed
+        let service = PlanService::new(context().await?);
+        let config = SessionConfig::new();
+        config
+            .config_options
+            .write()
+            .set_u64(OPT_OPTIMIZER_MAX_PASSES, 1);
+        let err = do_get(&service, &plan, &config).await.unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "The configuration option datafusion.optimizer.max_passes \
+                 can not be set by the requests"
+            ),
+            "{}",
+            err
+        );
+        let service = service.with_allowed_options([OPT_OPTIMIZER_MAX_PASSES]);
+        do_get(&service, &plan, &config).await?;
+
+        // the plans exceeding the limits are rejected
+        let service = service.with_limits(DeserializeLimits::new().with_max_nodes(2));
+        let err = do_get(&service, &plan, &SessionConfig::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds the limit"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn dictionary_results() -> Result<()> {
+        use arrow::array::{DictionaryArray, Int32Array};
+        use arrow::datatypes::Int32Type;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "d1",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new(
+                "d2",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("i", DataType::Int32, false),
+        ]));
+        let batch = |d1: Vec<&str>, d2: Vec<&str>| {
+            let i = Int32Array::from_iter_values(0..d1.len() as i32);
+            let d1: DictionaryArray<Int32Type> = d1.into_iter().collect();
+            let d2: DictionaryArray<Int32Type> = d2.into_iter().collect();
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(d1) as ArrayRef, Arc::new(d2), Arc::new(i)],
+            )
+            .unwrap()
+        };
+        let batches = vec![
+            batch(vec!["a", "b", "a"], vec!["x", "y", "x"]),
+            batch(vec!["a", "b"], vec!["x", "y"]),
+            batch(vec!["c", "c"], vec!["x", "y"]),
+        ];
+
+        // the dictionaries are only sent when they change
+        let mut encoder = ResultEncoder::new(&schema);
+        let mut decoder = ResultDecoder::new();
+        assert!(decoder.decode(&encoder.encode_schema())?.is_none());
+        let mut decoded = vec![];
+        let mut lengths = vec![];
+        for batch in &batches {
+            let data = encoder.encode(batch)?;
+            lengths.push(data.len());
+            for data in &data {
+                decoded.extend(decoder.decode(data)?);
+            }
+        }
+        assert_eq!(lengths, vec![3, 1, 2]);
+        assert_eq!(
+            pretty_format_batches(&decoded)?.to_string(),
+            pretty_format_batches(&batches)?.to_string()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn execute_invalid_request() -> Result<()> {
+        let service = PlanService::new(SessionContext::new());
+        let execute = |request: protobuf::ExecutePlanRequest| {
+            service.do_get(Request::new(Ticket {
+                ticket: request.encode_to_vec(),
+            }))
+        };
+
+        let request = protobuf::ExecutePlanRequest {
+            plan: b"Leet".to_vec(),
+            ..Default::default()
+        };
+        let err = execute(request).await.err().unwrap();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err
+            .message()
+            .contains("bytes are not a serialized DataFusion logical plan"));
+
+        let err = service
+            .do_get(Request::new(Ticket {
+                ticket: b"Leet".to_vec(),
+            }))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("Invalid ticket"));
+        Ok(())
+    }
+}
@@ -0,0 +1,803 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! A shuffle transport over Arrow Flight `DoExchange` calls, from which a
+//! minimal multi-node execution can be assembled:
+//!
+//! * the physical plan is split into stages at its hash [`RepartitionExec`]s
+//! * the map tasks of a stage run the input partitions of the
+//!   `RepartitionExec`, and write each output partition to a
+//!   [`ShuffleService`], acknowledging batches to bound the batches in flight
+//! * the `RepartitionExec` is then replaced by a [`ShuffleReaderExec`], reading
+//!   the partitions back once all the map tasks have written them
+//!
+//! The exchanges are retried with an exponential backoff by the
+//! [`ShuffleClient`], and their commands are [`protobuf::ShuffleCommand`]s.
+//!
+//! ```no_run
+//! use datafusion::physical_plan::collect;
+//! use datafusion::prelude::*;
+//! use datafusion_common::Result;
+//! use datafusion_proto::shuffle::{run_stages, ShuffleClient, ShuffleService};
+//!
+//! # async fn example() -> Result<()> {
+//! // the service would run on another node of a cluster
+//! tokio::spawn(
+//!     tonic::transport::Server::builder()
+//!         .add_service(ShuffleService::default().into_server())
+//!         .serve("127.0.0.1:50052".parse().unwrap()),
+//! );
+//! let client = ShuffleClient::new("http://127.0.0.1:50052");
+//!
+//! let ctx = SessionContext::new();
+//! ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new()).await?;
+//! let plan = ctx
+//!     .sql("SELECT a, COUNT(*) FROM t GROUP BY a")
+//!     .await?
+//!     .create_physical_plan()
+//!     .await?;
+//! let plan = run_stages(plan, &client, "job-1", ctx.task_ctx()).await?;
+//! let results = collect(plan, ctx.task_ctx()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_descriptor::DescriptorType;
+use arrow_flight::flight_service_client::FlightServiceClient;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::utils::{flight_data_from_arrow_batch, flight_data_to_arrow_batch};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use datafusion::execution::context::TaskContext;
+use datafusion::physical_plan::expressions::PhysicalSortExpr;
+use datafusion::physical_plan::metrics;
+use datafusion::physical_plan::repartition::{BatchPartitioner, RepartitionExec};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+};
+use datafusion_common::{DataFusionError, Result};
+use futures::{SinkExt, Stream, StreamExt};
+use log::{debug, warn};
+use prost::Message;
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::protobuf::{self, shuffle_command::Command};
+
+/// The acknowledgement of a batch written to the service
+const BATCH_ACK: &[u8] = b"ack";
+/// The acknowledgement of the output of a map task, once stored
+const COMMIT_ACK: &[u8] = b"commit";
+
+/// The outputs of the map tasks of a stage partition, by job, stage and
+/// partition
+type Partitions =
+    HashMap<(String, usize, usize), BTreeMap<usize, (SchemaRef, Vec<RecordBatch>)>>;
+
+/// The descriptor of the first message of an exchange running `command`
+fn descriptor(command: Command) -> FlightDescriptor {
+    let command = protobuf::ShuffleCommand {
+        command: Some(command),
+    };
+    FlightDescriptor {
+        r#type: DescriptorType::Cmd as i32,
+        cmd: command.encode_to_vec().into(),
+        path: vec![],
+    }
+}
+
+/// A Flight service storing the shuffled partitions in memory
+///
+/// Only `DoExchange` is implemented: the first message of each exchange has
+/// the [`protobuf::ShuffleCommand`] in its descriptor.
+#[derive(Clone, Default)]
+pub struct ShuffleService {
+    partitions: Arc<Mutex<Partitions>>,
+}
+
+impl ShuffleService {
+    /// Wraps the service in a server, to add to a
+    /// [`tonic::transport::Server`]
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+}
+
+type BoxedFlightStream<T> =
+    Pin<Box<dyn Stream<Item = std::result::Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl FlightService for ShuffleService {
+    type HandshakeStream = BoxedFlightStream<HandshakeResponse>;
+    type ListFlightsStream = BoxedFlightStream<FlightInfo>;
+    type DoGetStream = BoxedFlightStream<FlightData>;
+    type DoPutStream = BoxedFlightStream<PutResult>;
+    type DoActionStream = BoxedFlightStream<arrow_flight::Result>;
+    type ListActionsStream = BoxedFlightStream<ActionType>;
+    type DoExchangeStream = BoxedFlightStream<FlightData>;
+
+    async fn do_exchange(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoExchangeStream>, Status> {
+        let mut incoming = request.into_inner();
+        let first = incoming
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("Empty exchange"))?;
+        let command = first
+            .flight_descriptor
+            .as_ref()
+            .and_then(|d| protobuf::ShuffleCommand::decode(&d.cmd[..]).ok())
+            .and_then(|command| command.command)
+            .ok_or_else(|| Status::invalid_argument("Invalid shuffle command"))?;
+
+        match command {
+            Command::Write(write) => {
+                let schema = Arc::new(Schema::try_from(&first).map_err(to_tonic_err)?);
+                let partitions = self.partitions.clone();
+                let map_task = write.map_task as usize;
+                let key = (write.job, write.stage as usize, write.partition as usize);
+                let output =
+                    futures::stream::try_unfold(Some((incoming, vec![])), move |state| {
+                        let partitions = partitions.clone();
+                        let schema = schema.clone();
+                        let key = key.clone();
+                        async move {
+                            let (mut incoming, mut batches) = match state {
+                                Some(state) => state,
+                                None => return Ok::<_, Status>(None),
+                            };
+                            match incoming.message().await? {
+                                Some(data) => {
+                                    let batch = flight_data_to_arrow_batch(
+                                        &data,
+                                        schema,
+                                        &HashMap::new(),
+                                    )
+                                    .map_err(to_tonic_err)?;
+                                    batches.push(batch);
+                                    Ok(Some((ack(BATCH_ACK), Some((incoming, batches)))))
+                                }
+                                None => {
+                                    // the output of the map task replaces the
+                                    // output of its previous attempts, if any
+                                    partitions
+                                        .lock()
+                                        .unwrap()
+                                        .entry(key)
+                                        .or_default()
+                                        .insert(map_task, (schema, batches));
+                                    Ok(Some((ack(COMMIT_ACK), None)))
+                                }
+                            }
+                        }
+                    });
+                Ok(Response::new(Box::pin(output) as Self::DoExchangeStream))
+            }
+            Command::Read(read) => {
+                let (stage, partition) = (read.stage as usize, read.partition as usize);
+                let outputs = self
+                    .partitions
+                    .lock()
+                    .unwrap()
+                    .get(&(read.job, stage, partition))
+                    .cloned()
+                    .unwrap_or_default();
+                if outputs.len() < read.map_tasks as usize {
+                    return Err(Status::unavailable(format!(
+                        "{} of the {} map tasks wrote partition {} of stage {}",
+                        outputs.len(),
+                        read.map_tasks,
+                        partition,
+                        stage
+                    )));
+                }
+
+                let options = IpcWriteOptions::default();
+                let schema = outputs
+                    .values()
+                    .next()
+                    .map(|(schema, _)| SchemaAsIpc::new(schema, &options).into());
+                let batches = outputs
+                    .into_values()
+                    .flat_map(|(_, batches)| batches)
+                    .collect::<Vec<_>>();
+                // the batches are encoded as they are sent, so that HTTP/2 flow
+                // control bounds the encoded batches in memory
+                let output = futures::stream::iter(schema).chain(
+                    futures::stream::iter(batches).map(move |batch| {
+                        flight_data_from_arrow_batch(&batch, &options).1
+                    }),
+                );
+                Ok(Response::new(
+                    Box::pin(output.map(Ok)) as Self::DoExchangeStream
+                ))
+            }
+        }
+    }
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> std::result::Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> std::result::Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn get_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_get(
+        &self,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<Self::DoGetStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> std::result::Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> std::result::Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
+}
+
+fn ack(kind: &[u8]) -> FlightData {
+    FlightData {
+        app_metadata: kind.to_vec().into(),
+        ..Default::default()
+    }
+}
+
+fn to_tonic_err(e: ArrowError) -> Status {
+    Status::internal(format!("{:?}", e))
+}
+
+fn to_datafusion_err(
+    e: impl std::error::Error + Send + Sync + 'static,
+) -> DataFusionError {
+    DataFusionError::External(Box::new(e))
+}
+
+/// A client of [`ShuffleService`]s, retrying the failed exchanges
+#[derive(Debug, Clone)]
+pub struct ShuffleClient {
+    endpoint: String,
+    /// The maximum number of batches written and not acknowledged yet
+    max_in_flight: usize,
+    /// The maximum number of attempts of an exchange
+    max_attempts: usize,
+    /// The delay before the first retry, doubled for each of the next ones
+    backoff: Duration,
+}
+
+impl ShuffleClient {
+    /// Creates a client of the service at `endpoint`, such as
+    /// `http://localhost:50052`
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            max_in_flight: 8,
+            max_attempts: 6,
+            backoff: Duration::from_millis(50),
+        }
+    }
+
+    /// Sets the maximum number of batches written and not acknowledged yet
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Sets the maximum number of attempts of an exchange
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry of an exchange, doubled for
+    /// each of the next ones
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    async fn connect(&self) -> Result<FlightServiceClient<Channel>> {
+        FlightServiceClient::connect(self.endpoint.clone())
+            .await
+            .map_err(to_datafusion_err)
+    }
+
+    /// Runs `f` until it succeeds, at most `max_attempts` times
+    async fn retry<T, F, Fut>(&self, what: &str, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if attempt < self.max_attempts => {
+                    warn!(
+                        "{} failed (attempt {}), retrying in {:?}: {}",
+                        what, attempt, backoff, e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Writes the output `partition` of the map task `map_task` of a stage
+    pub async fn write_partition(
+        &self,
+        job: &str,
+        stage: usize,
+        map_task: usize,
+        partition: usize,
+        schema: &SchemaRef,
+        batches: &[RecordBatch],
+    ) -> Result<()> {
+        let what = format!("writing partition {} of map task {}", partition, map_task);
+        self.retry(&what, || async move {
+            let command = Command::Write(protobuf::ShuffleWrite {
+                job: job.to_owned(),
+                stage: stage as u64,
+                map_task: map_task as u64,
+                partition: partition as u64,
+            });
+            self.try_write_partition(command, schema, batches).await
+        })
+        .await
+    }
+
+    async fn try_write_partition(
+        &self,
+        command: Command,
+        schema: &SchemaRef,
+        batches: &[RecordBatch],
+    ) -> Result<()> {
+        let mut client = self.connect().await?;
+        let options = IpcWriteOptions::default();
+        let (mut sender, receiver) = futures::channel::mpsc::channel(self.max_in_flight);
+
+        // the service reads the command before responding, so it is sent first
+        let mut first: FlightData = SchemaAsIpc::new(schema, &options).into();
+        first.flight_descriptor = Some(descriptor(command));
+        sender.send(first).await.map_err(to_datafusion_err)?;
+        let mut acks = client
+            .do_exchange(receiver)
+            .await
+            .map_err(to_datafusion_err)?
+            .into_inner();
+
+        let mut in_flight = 0;
+        for batch in batches {
+            // flow control: wait for the oldest batches to be acknowledged
+            while in_flight >= self.max_in_flight {
+                expect_ack(&mut acks, BATCH_ACK).await?;
+                in_flight -= 1;
+            }
+            let (dictionaries, data) = flight_data_from_arrow_batch(batch, &options);
+            if !dictionaries.is_empty() {
+                return Err(DataFusionError::NotImplemented(
+                    "Shuffling dictionary arrays is not supported".to_string(),
+                ));
+            }
+            sender.send(data).await.map_err(to_datafusion_err)?;
+            in_flight += 1;
+        }
+        drop(sender);
+
+        for _ in 0..in_flight {
+            expect_ack(&mut acks, BATCH_ACK).await?;
+        }
+        expect_ack(&mut acks, COMMIT_ACK).await
+    }
+
+    /// Reads the output `partition` of a stage, waiting for its `map_tasks`
+    /// to write it
+    pub async fn read_partition(
+        &self,
+        job: &str,
+        stage: usize,
+        partition: usize,
+        map_tasks: usize,
+        schema: SchemaRef,
+    ) -> Result<Vec<RecordBatch>> {
+        let what = format!("reading partition {} of stage {}", partition, stage);
+        let schema = &schema;
+        self.retry(&what, || async move {
+            let command = Command::Read(protobuf::ShuffleRead {
+                job: job.to_owned(),
+                stage: stage as u64,
+                partition: partition as u64,
+                map_tasks: map_tasks as u64,
+            });
+            self.try_read_partition(command, schema.clone()).await
+        })
+        .await
+    }
+
+    async fn try_read_partition(
+        &self,
+        command: Command,
+        schema: SchemaRef,
+    ) -> Result<Vec<RecordBatch>> {
+        let mut client = self.connect().await?;
+        let request = FlightData {
+            flight_descriptor: Some(descriptor(command)),
+            ..Default::default()
+        };
+        let mut stream = client
+            .do_exchange(futures::stream::iter(vec![request]))
+            .await
+            .map_err(to_datafusion_err)?
+            .into_inner();
+
+        // the first message, if any, is the schema
+        let mut batches = vec![];
+        let mut first = true;
+        while let Some(data) = stream.message().await.map_err(to_datafusion_err)? {
+            if !std::mem::take(&mut first) {
+                batches.push(flight_data_to_arrow_batch(
+                    &data,
+                    schema.clone(),
+                    &HashMap::new(),
+                )?);
+            }
+        }
+        Ok(batches)
+    }
+}
+
+async fn expect_ack(acks: &mut Streaming<FlightData>, expected: &[u8]) -> Result<()> {
+    match acks.message().await.map_err(to_datafusion_err)? {
+        Some(data) if &data.app_metadata[..] == expected => Ok(()),
+        _ => Err(DataFusionError::Execution(format!(
+            "Shuffle exchange ended without the {:?} acknowledgement",
+            String::from_utf8_lossy(expected)
+        ))),
+    }
+}
+
+/// Reads the output partitions of a shuffled stage, in place of the
+/// [`RepartitionExec`] which ended the stage
+#[derive(Debug)]
+pub struct ShuffleReaderExec {
+    client: ShuffleClient,
+    job: String,
+    stage: usize,
+    map_tasks: usize,
+    schema: SchemaRef,
+    partitioning: Partitioning,
+}
+
+impl ShuffleReaderExec {
+    /// Create a ShuffleReaderExec reading the output partitions of `stage`
+    /// of `job`, once its `map_tasks` have written them
+    pub fn new(
+        client: ShuffleClient,
+        job: impl Into<String>,
+        stage: usize,
+        map_tasks: usize,
+        schema: SchemaRef,
+        partitioning: Partitioning,
+    ) -> Self {
+        Self {
+            client,
+            job: job.into(),
+            stage,
+            map_tasks,
+            schema,
+            partitioning,
+        }
+    }
+}
+
+impl ExecutionPlan for ShuffleReaderExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.partitioning.clone()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let client = self.client.clone();
+        let (job, stage, map_tasks) = (self.job.clone(), self.stage, self.map_tasks);
+        let schema = self.schema.clone();
+        let batches = futures::stream::once(async move {
+            client
+                .read_partition(&job, stage, partition, map_tasks, schema)
+                .await
+        });
+        let stream = batches.flat_map(|result| {
+            let batches = match result {
+                Ok(batches) => batches.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(ArrowError::ExternalError(Box::new(e)))],
+            };
+            futures::stream::iter(batches)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream,
+        )))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(
+                f,
+                "ShuffleReaderExec: job={}, stage={}, map_tasks={}, partitioning={:?}",
+                self.job, self.stage, self.map_tasks, self.partitioning
+            ),
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// Returns a hash [`RepartitionExec`] of `plan` with no other one below it:
+/// the end of a stage which can run
+fn next_stage(plan: &Arc<dyn ExecutionPlan>) -> Option<Arc<dyn ExecutionPlan>> {
+    plan.children().iter().find_map(next_stage).or_else(|| {
+        let repartition = plan.as_any().downcast_ref::<RepartitionExec>()?;
+        match repartition.partitioning() {
+            Partitioning::Hash(_, _) => Some(plan.clone()),
+            _ => None,
+        }
+    })
+}
+
+/// Replaces the node `old` of `plan` by `new`
+fn replace_node(
+    plan: Arc<dyn ExecutionPlan>,
+    old: &Arc<dyn ExecutionPlan>,
+    new: &Arc<dyn ExecutionPlan>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if Arc::as_ptr(&plan) as *const () == Arc::as_ptr(old) as *const () {
+        return Ok(new.clone());
+    }
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(plan);
+    }
+    let children = children
+        .into_iter()
+        .map(|child| replace_node(child, old, new))
+        .collect::<Result<Vec<_>>>()?;
+    plan.with_new_children(children)
+}
+
+/// Runs the map task `map_task` of the stage ending with `repartition`:
+/// partitions its input partition, and writes the output partitions
+pub async fn run_map_task(
+    repartition: &RepartitionExec,
+    client: &ShuffleClient,
+    job: &str,
+    stage: usize,
+    map_task: usize,
+    context: Arc<TaskContext>,
+) -> Result<()> {
+    let partitioning = repartition.partitioning().clone();
+    let mut outputs = vec![vec![]; partitioning.partition_count()];
+    let mut partitioner = BatchPartitioner::try_new(partitioning, metrics::Time::new())?;
+
+    let mut input = repartition.input().execute(map_task, context)?;
+    while let Some(batch) = input.next().await {
+        partitioner.partition(batch?, |partition, batch| {
+            outputs[partition].push(batch);
+            Ok(())
+        })?;
+    }
+
+    let schema = repartition.schema();
+    let writes = outputs.iter().enumerate().map(|(partition, batches)| {
+        client.write_partition(job, stage, map_task, partition, &schema, batches)
+    });
+    futures::future::try_join_all(writes).await?;
+    Ok(())
+}
+
+/// Runs the stages of `plan` through the shuffle service of `client`, and
+/// returns the final stage, reading their outputs with
+/// [`ShuffleReaderExec`]s
+///
+/// The map tasks run in this process: a cluster would run them with
+/// [`run_map_task`] on its executors.
+pub async fn run_stages(
+    mut plan: Arc<dyn ExecutionPlan>,
+    client: &ShuffleClient,
+    job: &str,
+    context: Arc<TaskContext>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let mut stage = 0;
+    while let Some(stage_plan) = next_stage(&plan) {
+        let repartition = stage_plan
+            .as_any()
+            .downcast_ref::<RepartitionExec>()
+            .unwrap();
+        let map_tasks = repartition.input().output_partitioning().partition_count();
+        debug!("Running stage {} with {} map tasks", stage, map_tasks);
+
+        let tasks = (0..map_tasks).map(|map_task| {
+            run_map_task(repartition, client, job, stage, map_task, context.clone())
+        });
+        futures::future::try_join_all(tasks).await?;
+
+        let reader: Arc<dyn ExecutionPlan> = Arc::new(ShuffleReaderExec::new(
+            client.clone(),
+            job,
+            stage,
+            map_tasks,
+            repartition.schema(),
+            repartition.partitioning().clone(),
+        ));
+        plan = replace_node(plan, &stage_plan, &reader)?;
+        stage += 1;
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+    use datafusion::physical_plan::{collect, displayable};
+    use datafusion::prelude::{CsvReadOptions, SessionConfig, SessionContext};
+
+    /// Serves a [`ShuffleService`] on a free local port, and returns a client
+    /// of it
+    async fn serve() -> Result<ShuffleClient> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(ShuffleService::default().into_server())
+                .serve_with_incoming(incoming),
+        );
+        Ok(ShuffleClient::new(format!("http://{}", addr)))
+    }
+
+    #[tokio::test]
+    async fn shuffle_stages() -> Result<()> {
+        let client = serve().await?;
+        let ctx =
+            SessionContext::with_config(SessionConfig::new().with_target_partitions(4));
+        ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new())
+            .await?;
+        let sql = "SELECT a, COUNT(*) AS n FROM t GROUP BY a ORDER BY a";
+        let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+        let expected =
+            pretty_format_batches(&collect(plan.clone(), ctx.task_ctx()).await?)?
+                .to_string();
+
+        let plan = run_stages(plan, &client, "job-1", ctx.task_ctx()).await?;
+        let display = displayable(plan.as_ref()).indent().to_string();
+        assert!(display.contains("ShuffleReaderExec: job=job-1, stage=0"));
+        assert!(!display.contains("RepartitionExec: partitioning=Hash"));
+
+        let results = collect(plan, ctx.task_ctx()).await?;
+        assert_eq!(pretty_format_batches(&results)?.to_string(), expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_missing_partition() -> Result<()> {
+        let client = serve()
+            .await?
+            .with_max_attempts(2)
+            .with_backoff(Duration::from_millis(1));
+        let schema = Arc::new(Schema::empty());
+        let err = client
+            .read_partition("job-1", 0, 0, 1, schema)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("0 of the 1 map tasks wrote partition 0"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
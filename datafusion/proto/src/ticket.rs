@@ -0,0 +1,242 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Arrow Flight tickets executing a partition of a serialized plan, for
+//! executors exchanging plans over Flight `DoGet` calls.
+//!
+//! The plans are the bytes of [`logical_plan_to_bytes`],
+//! [`physical_plan_to_bytes`] or their variants, so they can be compressed or
+//! use extension codecs, and the tickets are [`protobuf::PlanTicket`]s.
+//!
+//! ```
+//! use datafusion::prelude::*;
+//! use datafusion_common::Result;
+//! use datafusion_proto::ticket::PlanTicket;
+//!
+//! # async fn example() -> Result<()> {
+//! let ctx = SessionContext::new();
+//! ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new()).await?;
+//! let plan = ctx.sql("SELECT a FROM t").await?.create_physical_plan().await?;
+//!
+//! // the client asks for the first partition of the plan
+//! let ticket = PlanTicket::try_new_physical(plan, 0)?.to_ticket();
+//!
+//! // the executor gets it back
+//! let ticket = PlanTicket::try_from_ticket(&ticket)?;
+//! let plan = ticket.physical_plan(&ctx)?;
+//! let results = plan.execute(ticket.partition, ctx.task_ctx())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+use arrow_flight::Ticket;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::SessionContext;
+use datafusion_common::{DataFusionError, Result};
+use datafusion_expr::LogicalPlan;
+use prost::bytes::Bytes;
+use prost::Message;
+
+use crate::bytes::{
+    logical_plan_from_bytes, logical_plan_to_bytes, physical_plan_from_bytes,
+    physical_plan_to_bytes,
+};
+use crate::protobuf;
+use crate::protobuf::plan_ticket::Plan;
+
+/// A serialized plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TicketPlan {
+    /// A logical plan, serialized by [`logical_plan_to_bytes`] or its
+    /// variants
+    Logical(Bytes),
+    /// A physical plan, serialized by [`physical_plan_to_bytes`] or its
+    /// variants
+    Physical(Bytes),
+}
+
+/// The content of a ticket executing a partition of a serialized plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanTicket {
+    /// The plan
+    pub plan: TicketPlan,
+    /// The partition of the plan to execute
+    pub partition: usize,
+}
+
+impl PlanTicket {
+    /// Create a ticket executing `partition` of the serialized `plan`
+    pub fn new(plan: TicketPlan, partition: usize) -> Self {
+        Self { plan, partition }
+    }
+
+    /// Create a ticket executing `partition` of the physical plan of a
+    /// logical plan, serialized by [`logical_plan_to_bytes`]
+    pub fn try_new_logical(plan: &LogicalPlan, partition: usize) -> Result<Self> {
+        let plan = TicketPlan::Logical(logical_plan_to_bytes(plan)?);
+        Ok(Self::new(plan, partition))
+    }
+
+    /// Create a ticket executing `partition` of a physical plan, serialized by
+    /// [`physical_plan_to_bytes`]
+    pub fn try_new_physical(
+        plan: Arc<dyn ExecutionPlan>,
+        partition: usize,
+    ) -> Result<Self> {
+        let plan = TicketPlan::Physical(physical_plan_to_bytes(plan)?);
+        Ok(Self::new(plan, partition))
+    }
+
+    /// Deserialize the logical plan of the ticket with
+    /// [`logical_plan_from_bytes`]
+    ///
+    /// Plans serialized with extension codecs are deserialized from
+    /// [`plan`](Self::plan) instead.
+    pub fn logical_plan(&self, ctx: &SessionContext) -> Result<LogicalPlan> {
+        match &self.plan {
+            TicketPlan::Logical(bytes) => logical_plan_from_bytes(bytes, ctx),
+            TicketPlan::Physical(_) => Err(DataFusionError::Plan(
+                "The ticket holds a physical plan, not a logical plan".to_string(),
+            )),
+        }
+    }
+
+    /// Deserialize the physical plan of the ticket with
+    /// [`physical_plan_from_bytes`]
+    ///
+    /// Plans serialized with extension codecs are deserialized from
+    /// [`plan`](Self::plan) instead.
+    pub fn physical_plan(&self, ctx: &SessionContext) -> Result<Arc<dyn ExecutionPlan>> {
+        match &self.plan {
+            TicketPlan::Physical(bytes) => physical_plan_from_bytes(bytes, ctx),
+            TicketPlan::Logical(_) => Err(DataFusionError::Plan(
+                "The ticket holds a logical plan, not a physical plan".to_string(),
+            )),
+        }
+    }
+
+    /// Package the ticket into a Flight ticket
+    pub fn to_ticket(&self) -> Ticket {
+        let plan = match &self.plan {
+            TicketPlan::Logical(bytes) => Plan::LogicalPlan(bytes.to_vec()),
+            TicketPlan::Physical(bytes) => Plan::PhysicalPlan(bytes.to_vec()),
+        };
+        let ticket = protobuf::PlanTicket {
+            plan: Some(plan),
+            partition: self.partition as u64,
+        };
+        Ticket {
+            ticket: ticket.encode_to_vec(),
+        }
+    }
+
+    /// Parse a Flight ticket packaged by [`to_ticket`](Self::to_ticket)
+    pub fn try_from_ticket(ticket: &Ticket) -> Result<Self> {
+        let ticket = protobuf::PlanTicket::decode(ticket.ticket.as_slice())
+            .map_err(|e| DataFusionError::Plan(format!("Invalid plan ticket: {}", e)))?;
+        let plan = match ticket.plan {
+            Some(Plan::LogicalPlan(bytes)) => TicketPlan::Logical(bytes.into()),
+            Some(Plan::PhysicalPlan(bytes)) => TicketPlan::Physical(bytes.into()),
+            None => {
+                return Err(DataFusionError::Plan(
+                    "Invalid plan ticket: missing plan".to_string(),
+                ))
+            }
+        };
+        let partition = usize::try_from(ticket.partition).map_err(|_| {
+            DataFusionError::Plan(format!(
+                "Invalid plan ticket: partition {} out of range",
+                ticket.partition
+            ))
+        })?;
+        Ok(Self::new(plan, partition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::CsvReadOptions;
+
+    async fn context() -> Result<SessionContext> {
+        let ctx = SessionContext::new();
+        ctx.register_csv("t", "testdata/test.csv", CsvReadOptions::new())
+            .await?;
+        Ok(ctx)
+    }
+
+    #[tokio::test]
+    async fn logical_plan_ticket() -> Result<()> {
+        let ctx = context().await?;
+        let plan = ctx.sql("SELECT a FROM t").await?.to_logical_plan()?;
+
+        let ticket = PlanTicket::try_new_logical(&plan, 2)?;
+        let parsed = PlanTicket::try_from_ticket(&ticket.to_ticket())?;
+        assert_eq!(parsed, ticket);
+        assert_eq!(parsed.partition, 2);
+        assert_eq!(
+            format!("{:?}", parsed.logical_plan(&ctx)?),
+            format!("{:?}", plan)
+        );
+
+        let err = parsed.physical_plan(&ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: The ticket holds a logical plan, not a physical plan"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn physical_plan_ticket() -> Result<()> {
+        let ctx = context().await?;
+        let plan = ctx
+            .sql("SELECT a FROM t")
+            .await?
+            .create_physical_plan()
+            .await?;
+
+        let ticket = PlanTicket::try_new_physical(plan.clone(), 1)?;
+        let parsed = PlanTicket::try_from_ticket(&ticket.to_ticket())?;
+        assert_eq!(parsed, ticket);
+        assert_eq!(parsed.partition, 1);
+        assert_eq!(
+            format!("{:?}", parsed.physical_plan(&ctx)?),
+            format!("{:?}", plan)
+        );
+
+        assert!(parsed.logical_plan(&ctx).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_ticket() {
+        let err = PlanTicket::try_from_ticket(&Ticket {
+            ticket: b"Leet".to_vec(),
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid plan ticket"), "{}", err);
+
+        let err = PlanTicket::try_from_ticket(&Ticket { ticket: vec![] }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Invalid plan ticket: missing plan"
+        );
+    }
+}
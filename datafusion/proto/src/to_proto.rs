@@ -19,6 +19,9 @@
 //! DataFusion logical plans to be serialized and transmitted between
 //! processes.
 
+use crate::logical_plan::{
+    AsLogicalPlan, DefaultLogicalExtensionCodec, LogicalExtensionCodec,
+};
 use crate::protobuf::{
     self,
     arrow_type::ArrowTypeEnum,
@@ -33,14 +36,16 @@ use arrow::datatypes::{
     DataType, Field, IntervalMonthDayNanoType, IntervalUnit, Schema, SchemaRef, TimeUnit,
     UnionMode,
 };
-use datafusion_common::{Column, DFField, DFSchemaRef, ScalarValue};
+use datafusion::config::ConfigOptions;
+use datafusion::execution::context::SessionConfig;
+use datafusion_common::{Column, Constraint, DFField, DFSchemaRef, ScalarValue};
 use datafusion_expr::expr::{
     Between, BinaryExpr, Cast, GetIndexedField, GroupingSet, Like,
 };
 use datafusion_expr::{
-    logical_plan::PlanType, logical_plan::StringifiedPlan, AggregateFunction,
-    BuiltInWindowFunction, BuiltinScalarFunction, Expr, WindowFrame, WindowFrameBound,
-    WindowFrameUnits, WindowFunction,
+    logical_plan::PlanType, logical_plan::StringifiedPlan, logical_plan::Subquery,
+    AggregateFunction, AggregateUDF, BuiltInWindowFunction, BuiltinScalarFunction, Expr,
+    TypeSignature, WindowFrame, WindowFrameBound, WindowFrameUnits, WindowFunction,
 };
 
 #[derive(Debug)]
@@ -103,6 +108,41 @@ impl std::fmt::Display for Error {
     }
 }
 
+/// The argument types of `signature`, if they are exact
+fn exact_arg_types(signature: &TypeSignature) -> Option<Vec<DataType>> {
+    match signature {
+        TypeSignature::Exact(types) => Some(types.clone()),
+        TypeSignature::Uniform(n, types) if types.len() == 1 => {
+            Some(vec![types[0].clone(); *n])
+        }
+        _ => None,
+    }
+}
+
+/// The types of the UDAF `fun` for the exact argument types of its signature
+fn aggregate_udf_signature(
+    fun: &AggregateUDF,
+) -> Result<Option<protobuf::UdfSignature>, Error> {
+    let arg_types = match exact_arg_types(&fun.signature.type_signature) {
+        Some(arg_types) => arg_types,
+        None => return Ok(None),
+    };
+    let general = |e: datafusion_common::DataFusionError| Error::General(e.to_string());
+    let return_type = (fun.return_type)(&arg_types).map_err(general)?;
+    let state_types = (fun.state_type)(&return_type).map_err(general)?;
+    Ok(Some(protobuf::UdfSignature {
+        arg_types: arg_types
+            .iter()
+            .map(|t| t.try_into())
+            .collect::<Result<_, _>>()?,
+        return_type: Some(return_type.as_ref().try_into()?),
+        state_types: state_types
+            .iter()
+            .map(|t| t.try_into())
+            .collect::<Result<_, _>>()?,
+    }))
+}
+
 impl TryFrom<&Field> for protobuf::Field {
     type Error = Error;
 
@@ -244,6 +284,44 @@ impl From<&Column> for protobuf::Column {
     }
 }
 
+impl From<&Constraint> for protobuf::Constraint {
+    fn from(constraint: &Constraint) -> Self {
+        use protobuf::constraint::ConstraintType;
+
+        let indices = |columns: &[usize]| columns.iter().map(|i| *i as u64).collect();
+        let constraint_type = match constraint {
+            Constraint::PrimaryKey(columns) => {
+                ConstraintType::PrimaryKey(protobuf::PrimaryKeyConstraint {
+                    columns: indices(columns),
+                })
+            }
+            Constraint::Unique(columns) => {
+                ConstraintType::Unique(protobuf::UniqueConstraint {
+                    columns: indices(columns),
+                })
+            }
+            Constraint::ForeignKey {
+                columns,
+                referenced_table,
+                referenced_columns,
+            } => ConstraintType::ForeignKey(protobuf::ForeignKeyConstraint {
+                columns: indices(columns),
+                referenced_table: referenced_table.clone(),
+                referenced_columns: referenced_columns.clone(),
+            }),
+            Constraint::Check { name, expr } => {
+                ConstraintType::Check(protobuf::CheckConstraint {
+                    name: name.clone().unwrap_or_default(),
+                    expr: expr.clone(),
+                })
+            }
+        };
+        Self {
+            constraint_type: Some(constraint_type),
+        }
+    }
+}
+
 impl TryFrom<&Schema> for protobuf::Schema {
     type Error = Error;
 
@@ -296,7 +374,7 @@ impl TryFrom<&DFSchemaRef> for protobuf::DfSchema {
             .collect::<Result<Vec<_>, Error>>()?;
         Ok(Self {
             columns,
-            metadata: s.metadata().clone(),
+            metadata: s.metadata().clone().into_iter().collect(),
         })
     }
 }
@@ -432,449 +510,543 @@ impl TryFrom<&WindowFrame> for protobuf::WindowFrame {
 impl TryFrom<&Expr> for protobuf::LogicalExprNode {
     type Error = Error;
 
+    /// Serializes `expr`, whose subqueries can't contain extension nodes: see
+    /// [`serialize_expr_with_subqueries`]
     fn try_from(expr: &Expr) -> Result<Self, Self::Error> {
-        use protobuf::logical_expr_node::ExprType;
+        serialize_expr(expr, &DefaultLogicalExtensionCodec {})
+    }
+}
 
-        let expr_node = match expr {
-            Expr::Column(c) => Self {
-                expr_type: Some(ExprType::Column(c.into())),
-            },
-            Expr::Alias(expr, alias) => {
-                let alias = Box::new(protobuf::AliasNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    alias: alias.to_owned(),
-                });
-                Self {
-                    expr_type: Some(ExprType::Alias(alias)),
-                }
+/// Serialize an expression, encoding the extension nodes and table providers
+/// of the plans of its subqueries with `extension_codec`, as
+/// [`parse_expr_with_subqueries`](crate::from_proto::parse_expr_with_subqueries)
+/// decodes them
+pub fn serialize_expr_with_subqueries(
+    expr: &Expr,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<protobuf::LogicalExprNode, Error> {
+    serialize_expr(expr, extension_codec)
+}
+
+fn serialize_expr(
+    expr: &Expr,
+    codec: &dyn LogicalExtensionCodec,
+) -> Result<protobuf::LogicalExprNode, Error> {
+    use protobuf::logical_expr_node::ExprType;
+
+    let expr_node = match expr {
+        Expr::Column(c) => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Column(c.into())),
+        },
+        Expr::Alias(expr, alias) => {
+            let alias = Box::new(protobuf::AliasNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                alias: alias.to_owned(),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Alias(alias)),
             }
-            Expr::Literal(value) => {
-                let pb_value: protobuf::ScalarValue = value.try_into()?;
-                Self {
-                    expr_type: Some(ExprType::Literal(pb_value)),
-                }
+        }
+        Expr::Literal(value) => {
+            let pb_value: protobuf::ScalarValue = value.try_into()?;
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Literal(pb_value)),
             }
-            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
-                let binary_expr = Box::new(protobuf::BinaryExprNode {
-                    l: Some(Box::new(left.as_ref().try_into()?)),
-                    r: Some(Box::new(right.as_ref().try_into()?)),
-                    op: format!("{:?}", op),
-                });
-                Self {
-                    expr_type: Some(ExprType::BinaryExpr(binary_expr)),
+        }
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            // the chains of binary expressions of the same operator, such
+            // as long `AND` chains, are left-deep trees: their operands are
+            // collected in a loop instead of being converted recursively,
+            // so that the messages are not nested as deep as the chain
+            let mut operands = vec![right.as_ref()];
+            let mut current = left.as_ref();
+            while let Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: current_op,
+                right,
+            }) = current
+            {
+                if current_op != op {
+                    break;
                 }
+                operands.push(right.as_ref());
+                current = left.as_ref();
+            }
+            operands.push(current);
+            let operands = operands
+                .into_iter()
+                .rev()
+                .map(|operand| serialize_expr(operand, codec))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let binary_expr = protobuf::BinaryExprNode {
+                operands,
+                op: format!("{:?}", op),
+            };
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::BinaryExpr(binary_expr)),
             }
-            Expr::Like(Like { negated, expr, pattern, escape_char }) => {
-                let pb = Box::new(protobuf::LikeNode {
-                    negated: *negated,
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    pattern: Some(Box::new(pattern.as_ref().try_into()?)),
-                    escape_char: escape_char
-                        .map(|ch| ch.to_string())
-                        .unwrap_or_else(|| "".to_string()),
-                });
-                Self {
-                    expr_type: Some(ExprType::Like(pb)),
-                }
+        }
+        Expr::Like(Like { negated, expr, pattern, escape_char }) => {
+            let pb = Box::new(protobuf::LikeNode {
+                negated: *negated,
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                pattern: Some(Box::new(serialize_expr(pattern, codec)?)),
+                escape_char: escape_char
+                    .map(|ch| ch.to_string())
+                    .unwrap_or_else(|| "".to_string()),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Like(pb)),
             }
-            Expr::ILike(Like { negated, expr, pattern, escape_char }) => {
-                let pb = Box::new(protobuf::ILikeNode {
-                    negated: *negated,
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    pattern: Some(Box::new(pattern.as_ref().try_into()?)),
-                    escape_char: escape_char
-                        .map(|ch| ch.to_string())
-                        .unwrap_or_else(|| "".to_string()),
-                });
-                Self {
-                    expr_type: Some(ExprType::Ilike(pb)),
-                }
+        }
+        Expr::ILike(Like { negated, expr, pattern, escape_char }) => {
+            let pb = Box::new(protobuf::ILikeNode {
+                negated: *negated,
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                pattern: Some(Box::new(serialize_expr(pattern, codec)?)),
+                escape_char: escape_char
+                    .map(|ch| ch.to_string())
+                    .unwrap_or_else(|| "".to_string()),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Ilike(pb)),
             }
-            Expr::SimilarTo(Like { negated, expr, pattern, escape_char }) => {
-                let pb = Box::new(protobuf::SimilarToNode {
-                    negated: *negated,
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    pattern: Some(Box::new(pattern.as_ref().try_into()?)),
-                    escape_char: escape_char
-                        .map(|ch| ch.to_string())
-                        .unwrap_or_else(|| "".to_string()),
-                });
-                Self {
-                    expr_type: Some(ExprType::SimilarTo(pb)),
-                }
+        }
+        Expr::SimilarTo(Like { negated, expr, pattern, escape_char }) => {
+            let pb = Box::new(protobuf::SimilarToNode {
+                negated: *negated,
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                pattern: Some(Box::new(serialize_expr(pattern, codec)?)),
+                escape_char: escape_char
+                    .map(|ch| ch.to_string())
+                    .unwrap_or_else(|| "".to_string()),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::SimilarTo(pb)),
             }
-            Expr::WindowFunction {
-                ref fun,
-                ref args,
-                ref partition_by,
-                ref order_by,
-                ref window_frame,
-            } => {
-                let window_function = match fun {
-                    WindowFunction::AggregateFunction(fun) => {
-                        protobuf::window_expr_node::WindowFunction::AggrFunction(
-                            protobuf::AggregateFunction::from(fun).into(),
-                        )
-                    }
-                    WindowFunction::BuiltInWindowFunction(fun) => {
-                        protobuf::window_expr_node::WindowFunction::BuiltInFunction(
-                            protobuf::BuiltInWindowFunction::from(fun).into(),
-                        )
-                    }
-                };
-                let arg_expr: Option<Box<Self>> = if !args.is_empty() {
-                    let arg = &args[0];
-                    Some(Box::new(arg.try_into()?))
-                } else {
-                    None
-                };
-                let partition_by = partition_by
-                    .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
-                let order_by = order_by
-                    .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let window_frame = match window_frame {
-                    Some(frame) => Some(
-                        protobuf::window_expr_node::WindowFrame::Frame(frame.try_into()?)
-                    ),
-                    None => None
-                };
-                let window_expr = Box::new(protobuf::WindowExprNode {
-                    expr: arg_expr,
-                    window_function: Some(window_function),
-                    partition_by,
-                    order_by,
-                    window_frame,
-                });
-                Self {
-                    expr_type: Some(ExprType::WindowExpr(window_expr)),
+        }
+        Expr::WindowFunction {
+            ref fun,
+            ref args,
+            ref partition_by,
+            ref order_by,
+            ref window_frame,
+            ignore_nulls,
+        } => {
+            let window_function = match fun {
+                WindowFunction::AggregateFunction(fun) => {
+                    protobuf::window_expr_node::WindowFunction::AggrFunction(
+                        protobuf::AggregateFunction::from(fun).into(),
+                    )
+                }
+                WindowFunction::BuiltInWindowFunction(fun) => {
+                    protobuf::window_expr_node::WindowFunction::BuiltInFunction(
+                        protobuf::BuiltInWindowFunction::from(fun).into(),
+                    )
                 }
+            };
+            let arg_expr = if !args.is_empty() {
+                let arg = &args[0];
+                Some(Box::new(serialize_expr(arg, codec)?))
+            } else {
+                None
+            };
+            let extra_args = args
+                .iter()
+                .skip(1)
+                .map(|e| serialize_expr(e, codec))
+                .collect::<Result<Vec<_>, _>>()?;
+            let partition_by = partition_by
+                .iter()
+                .map(|e| serialize_expr(e, codec))
+                .collect::<Result<Vec<_>, _>>()?;
+            let order_by = order_by
+                .iter()
+                .map(|e| serialize_expr(e, codec))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let window_frame = match window_frame {
+                Some(frame) => Some(
+                    protobuf::window_expr_node::WindowFrame::Frame(frame.try_into()?)
+                ),
+                None => None
+            };
+            let window_expr = Box::new(protobuf::WindowExprNode {
+                expr: arg_expr,
+                window_function: Some(window_function),
+                partition_by,
+                order_by,
+                window_frame,
+                extra_args,
+                ignore_nulls: *ignore_nulls,
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::WindowExpr(window_expr)),
             }
-            Expr::AggregateFunction {
-                ref fun,
-                ref args,
-                ref distinct,
-                ref filter
-            } => {
-                let aggr_function = match fun {
-                    AggregateFunction::ApproxDistinct => {
-                        protobuf::AggregateFunction::ApproxDistinct
-                    }
-                    AggregateFunction::ApproxPercentileCont => {
-                        protobuf::AggregateFunction::ApproxPercentileCont
-                    }
-                    AggregateFunction::ApproxPercentileContWithWeight => {
-                        protobuf::AggregateFunction::ApproxPercentileContWithWeight
-                    }
-                    AggregateFunction::ArrayAgg => protobuf::AggregateFunction::ArrayAgg,
-                    AggregateFunction::Min => protobuf::AggregateFunction::Min,
-                    AggregateFunction::Max => protobuf::AggregateFunction::Max,
-                    AggregateFunction::Sum => protobuf::AggregateFunction::Sum,
-                    AggregateFunction::Avg => protobuf::AggregateFunction::Avg,
-                    AggregateFunction::Count => protobuf::AggregateFunction::Count,
-                    AggregateFunction::Variance => protobuf::AggregateFunction::Variance,
-                    AggregateFunction::VariancePop => {
-                        protobuf::AggregateFunction::VariancePop
-                    }
-                    AggregateFunction::Covariance => {
-                        protobuf::AggregateFunction::Covariance
-                    }
-                    AggregateFunction::CovariancePop => {
-                        protobuf::AggregateFunction::CovariancePop
-                    }
-                    AggregateFunction::Stddev => protobuf::AggregateFunction::Stddev,
-                    AggregateFunction::StddevPop => {
-                        protobuf::AggregateFunction::StddevPop
-                    }
-                    AggregateFunction::Correlation => {
-                        protobuf::AggregateFunction::Correlation
-                    }
-                    AggregateFunction::ApproxMedian => {
-                        protobuf::AggregateFunction::ApproxMedian
-                    }
-                    AggregateFunction::Grouping => protobuf::AggregateFunction::Grouping,
-                    AggregateFunction::Median => protobuf::AggregateFunction::Median,
-                };
+        }
+        Expr::AggregateFunction {
+            ref fun,
+            ref args,
+            ref distinct,
+            ref filter
+        } => {
+            let aggr_function = match fun {
+                AggregateFunction::ApproxDistinct => {
+                    protobuf::AggregateFunction::ApproxDistinct
+                }
+                AggregateFunction::ApproxPercentileCont => {
+                    protobuf::AggregateFunction::ApproxPercentileCont
+                }
+                AggregateFunction::ApproxPercentileContWithWeight => {
+                    protobuf::AggregateFunction::ApproxPercentileContWithWeight
+                }
+                AggregateFunction::ArrayAgg => protobuf::AggregateFunction::ArrayAgg,
+                AggregateFunction::Min => protobuf::AggregateFunction::Min,
+                AggregateFunction::Max => protobuf::AggregateFunction::Max,
+                AggregateFunction::Sum => protobuf::AggregateFunction::Sum,
+                AggregateFunction::Avg => protobuf::AggregateFunction::Avg,
+                AggregateFunction::Count => protobuf::AggregateFunction::Count,
+                AggregateFunction::Variance => protobuf::AggregateFunction::Variance,
+                AggregateFunction::VariancePop => {
+                    protobuf::AggregateFunction::VariancePop
+                }
+                AggregateFunction::Covariance => {
+                    protobuf::AggregateFunction::Covariance
+                }
+                AggregateFunction::CovariancePop => {
+                    protobuf::AggregateFunction::CovariancePop
+                }
+                AggregateFunction::Stddev => protobuf::AggregateFunction::Stddev,
+                AggregateFunction::StddevPop => {
+                    protobuf::AggregateFunction::StddevPop
+                }
+                AggregateFunction::Correlation => {
+                    protobuf::AggregateFunction::Correlation
+                }
+                AggregateFunction::ApproxMedian => {
+                    protobuf::AggregateFunction::ApproxMedian
+                }
+                AggregateFunction::Grouping => protobuf::AggregateFunction::Grouping,
+                AggregateFunction::Median => protobuf::AggregateFunction::Median,
+            };
 
-                let aggregate_expr = protobuf::AggregateExprNode {
-                    aggr_function: aggr_function.into(),
-                    expr: args
-                        .iter()
-                        .map(|v| v.try_into())
-                        .collect::<Result<Vec<_>, _>>()?,
-                    distinct: *distinct,
-                    filter: match filter {
-                        Some(e) => Some(Box::new(e.as_ref().try_into()?)),
-                        None => None,
+            let aggregate_expr = protobuf::AggregateExprNode {
+                aggr_function: aggr_function.into(),
+                expr: args
+                    .iter()
+                    .map(|v| serialize_expr(v, codec))
+                    .collect::<Result<Vec<_>, _>>()?,
+                distinct: *distinct,
+                filter: match filter {
+                    Some(e) => Some(Box::new(serialize_expr(e, codec)?)),
+                    None => None,
+                },
+            };
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::AggregateExpr(Box::new(aggregate_expr))),
+            }
+        }
+        Expr::ScalarVariable(_, _) => return Err(Error::General("Proto serialization error: Scalar Variable not supported".to_string())),
+        Expr::ScalarFunction { ref fun, ref args } => {
+            let fun: protobuf::ScalarFunction = fun.try_into()?;
+            let args: Vec<protobuf::LogicalExprNode> = args
+                .iter()
+                .map(|e| serialize_expr(e, codec))
+                .collect::<Result<Vec<protobuf::LogicalExprNode>, Error>>()?;
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::ScalarFunction(
+                    protobuf::ScalarFunctionNode {
+                        fun: fun.into(),
+                        args,
                     },
-                };
-                Self {
-                    expr_type: Some(ExprType::AggregateExpr(Box::new(aggregate_expr))),
-                }
+                )),
             }
-            Expr::ScalarVariable(_, _) => return Err(Error::General("Proto serialization error: Scalar Variable not supported".to_string())),
-            Expr::ScalarFunction { ref fun, ref args } => {
-                let fun: protobuf::ScalarFunction = fun.try_into()?;
-                let args: Vec<Self> = args
+        }
+        Expr::ScalarUDF { fun, args } => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
+                fun_name: fun.name.clone(),
+                args: args
                     .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<Self>, Error>>()?;
-                Self {
-                    expr_type: Some(ExprType::ScalarFunction(
-                        protobuf::ScalarFunctionNode {
-                            fun: fun.into(),
-                            args,
-                        },
-                    )),
-                }
-            }
-            Expr::ScalarUDF { fun, args } => Self {
-                expr_type: Some(ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
-                    fun_name: fun.name.clone(),
-                    args: args
-                        .iter()
-                        .map(|expr| expr.try_into())
-                        .collect::<Result<Vec<_>, Error>>()?,
-                })),
-            },
-            Expr::AggregateUDF { fun, args, filter } => {
-                Self {
-                    expr_type: Some(ExprType::AggregateUdfExpr(
-                        Box::new(protobuf::AggregateUdfExprNode {
-                            fun_name: fun.name.clone(),
-                            args: args.iter().map(|expr| expr.try_into()).collect::<Result<
-                                Vec<_>,
-                                Error,
-                            >>()?,
-                            filter: match filter {
-                                Some(e) => Some(Box::new(e.as_ref().try_into()?)),
-                                None => None,
-                            },
+                    .map(|expr| serialize_expr(expr, codec))
+                    .collect::<Result<Vec<_>, Error>>()?,
+                wasm_body: fun.wasm_body.as_ref().map(|body| {
+                    protobuf::WasmUdfBody {
+                        module: body.module.clone(),
+                        export: body.export.clone(),
+                    }
+                }),
+            })),
+        },
+        Expr::AggregateUDF { fun, args, filter } => {
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::AggregateUdfExpr(
+                    Box::new(protobuf::AggregateUdfExprNode {
+                        fun_name: fun.name.clone(),
+                        args: args.iter().map(|expr| serialize_expr(expr, codec)).collect::<Result<
+                            Vec<_>,
+                            Error,
+                        >>()?,
+                        filter: match filter {
+                            Some(e) => Some(Box::new(serialize_expr(e, codec)?)),
+                            None => None,
                         },
-                        ))),
-                }
+                        signature: aggregate_udf_signature(fun)?,
+                    },
+                    ))),
             }
-            Expr::Not(expr) => {
-                let expr = Box::new(protobuf::Not {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::NotExpr(expr)),
-                }
+        }
+        Expr::Not(expr) => {
+            let expr = Box::new(protobuf::Not {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::NotExpr(expr)),
             }
-            Expr::IsNull(expr) => {
-                let expr = Box::new(protobuf::IsNull {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsNullExpr(expr)),
-                }
+        }
+        Expr::IsNull(expr) => {
+            let expr = Box::new(protobuf::IsNull {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsNullExpr(expr)),
             }
-            Expr::IsNotNull(expr) => {
-                let expr = Box::new(protobuf::IsNotNull {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsNotNullExpr(expr)),
-                }
+        }
+        Expr::IsNotNull(expr) => {
+            let expr = Box::new(protobuf::IsNotNull {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsNotNullExpr(expr)),
             }
-            Expr::IsTrue(expr) => {
-                let expr = Box::new(protobuf::IsTrue {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsTrue(expr)),
-                }
+        }
+        Expr::IsTrue(expr) => {
+            let expr = Box::new(protobuf::IsTrue {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsTrue(expr)),
             }
-            Expr::IsFalse(expr) => {
-                let expr = Box::new(protobuf::IsFalse {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsFalse(expr)),
-                }
+        }
+        Expr::IsFalse(expr) => {
+            let expr = Box::new(protobuf::IsFalse {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsFalse(expr)),
             }
-            Expr::IsUnknown(expr) => {
-                let expr = Box::new(protobuf::IsUnknown {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsUnknown(expr)),
-                }
+        }
+        Expr::IsUnknown(expr) => {
+            let expr = Box::new(protobuf::IsUnknown {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsUnknown(expr)),
             }
-            Expr::IsNotTrue(expr) => {
-                let expr = Box::new(protobuf::IsNotTrue {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsNotTrue(expr)),
-                }
+        }
+        Expr::IsNotTrue(expr) => {
+            let expr = Box::new(protobuf::IsNotTrue {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsNotTrue(expr)),
             }
-            Expr::IsNotFalse(expr) => {
-                let expr = Box::new(protobuf::IsNotFalse {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsNotFalse(expr)),
-                }
+        }
+        Expr::IsNotFalse(expr) => {
+            let expr = Box::new(protobuf::IsNotFalse {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsNotFalse(expr)),
             }
-            Expr::IsNotUnknown(expr) => {
-                let expr = Box::new(protobuf::IsNotUnknown {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::IsNotUnknown(expr)),
-                }
+        }
+        Expr::IsNotUnknown(expr) => {
+            let expr = Box::new(protobuf::IsNotUnknown {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::IsNotUnknown(expr)),
             }
-            Expr::Between(Between {
-                expr,
-                negated,
-                low,
-                high,
-            }) => {
-                let expr = Box::new(protobuf::BetweenNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    negated: *negated,
-                    low: Some(Box::new(low.as_ref().try_into()?)),
-                    high: Some(Box::new(high.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::Between(expr)),
-                }
+        }
+        Expr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => {
+            let expr = Box::new(protobuf::BetweenNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                negated: *negated,
+                low: Some(Box::new(serialize_expr(low, codec)?)),
+                high: Some(Box::new(serialize_expr(high, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Between(expr)),
             }
-            Expr::Case(case) => {
-                let when_then_expr = case.when_then_expr
-                    .iter()
-                    .map(|(w, t)| {
-                        Ok(protobuf::WhenThen {
-                            when_expr: Some(w.as_ref().try_into()?),
-                            then_expr: Some(t.as_ref().try_into()?),
-                        })
+        }
+        Expr::Case(case) => {
+            let when_then_expr = case.when_then_expr
+                .iter()
+                .map(|(w, t)| {
+                    Ok(protobuf::WhenThen {
+                        when_expr: Some(serialize_expr(w, codec)?),
+                        then_expr: Some(serialize_expr(t, codec)?),
                     })
-                    .collect::<Result<Vec<protobuf::WhenThen>, Error>>()?;
-                let expr = Box::new(protobuf::CaseNode {
-                    expr: match &case.expr {
-                        Some(e) => Some(Box::new(e.as_ref().try_into()?)),
-                        None => None,
-                    },
-                    when_then_expr,
-                    else_expr: match &case.else_expr {
-                        Some(e) => Some(Box::new(e.as_ref().try_into()?)),
-                        None => None,
-                    },
-                });
-                Self {
-                    expr_type: Some(ExprType::Case(expr)),
-                }
+                })
+                .collect::<Result<Vec<protobuf::WhenThen>, Error>>()?;
+            let expr = Box::new(protobuf::CaseNode {
+                expr: match &case.expr {
+                    Some(e) => Some(Box::new(serialize_expr(e, codec)?)),
+                    None => None,
+                },
+                when_then_expr,
+                else_expr: match &case.else_expr {
+                    Some(e) => Some(Box::new(serialize_expr(e, codec)?)),
+                    None => None,
+                },
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Case(expr)),
             }
-            Expr::Cast(Cast { expr, data_type }) => {
-                let expr = Box::new(protobuf::CastNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    arrow_type: Some(data_type.try_into()?),
-                });
-                Self {
-                    expr_type: Some(ExprType::Cast(expr)),
-                }
+        }
+        Expr::Cast(Cast { expr, data_type }) => {
+            let expr = Box::new(protobuf::CastNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                arrow_type: Some(data_type.try_into()?),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Cast(expr)),
             }
-            Expr::Sort {
-                expr,
-                asc,
-                nulls_first,
-            } => {
-                let expr = Box::new(protobuf::SortExprNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    asc: *asc,
-                    nulls_first: *nulls_first,
-                });
-                Self {
-                    expr_type: Some(ExprType::Sort(expr)),
-                }
+        }
+        Expr::Sort {
+            expr,
+            asc,
+            nulls_first,
+        } => {
+            let expr = Box::new(protobuf::SortExprNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                asc: *asc,
+                nulls_first: *nulls_first,
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Sort(expr)),
             }
-            Expr::Negative(expr) => {
-                let expr = Box::new(protobuf::NegativeNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                });
-                Self {
-                    expr_type: Some(ExprType::Negative(expr)),
-                }
+        }
+        Expr::Negative(expr) => {
+            let expr = Box::new(protobuf::NegativeNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::Negative(expr)),
             }
-            Expr::InList {
-                expr,
-                list,
-                negated,
-            } => {
-                let expr = Box::new(protobuf::InListNode {
-                    expr: Some(Box::new(expr.as_ref().try_into()?)),
-                    list: list
-                        .iter()
-                        .map(|expr| expr.try_into())
-                        .collect::<Result<Vec<_>, Error>>()?,
-                    negated: *negated,
-                });
-                Self {
-                    expr_type: Some(ExprType::InList(expr)),
-                }
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let expr = Box::new(protobuf::InListNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                list: list
+                    .iter()
+                    .map(|expr| serialize_expr(expr, codec))
+                    .collect::<Result<Vec<_>, Error>>()?,
+                negated: *negated,
+            });
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::InList(expr)),
             }
-            Expr::Wildcard => Self {
-                expr_type: Some(ExprType::Wildcard(true)),
-            },
-            Expr::ScalarSubquery(_) | Expr::InSubquery { .. } | Expr::Exists { .. } => {
-                // we would need to add logical plan operators to datafusion.proto to support this
-                // see discussion in https://github.com/apache/arrow-datafusion/issues/2565
-                return Err(Error::General("Proto serialization error: Expr::ScalarSubquery(_) | Expr::InSubquery { .. } | Expr::Exists { .. } not supported".to_string()));
-            }
-            Expr::GetIndexedField(GetIndexedField { key, expr }) =>
-                Self {
-                    expr_type: Some(ExprType::GetIndexedField(Box::new(
-                        protobuf::GetIndexedField {
-                            key: Some(key.try_into()?),
-                            expr: Some(Box::new(expr.as_ref().try_into()?)),
-                        },
-                    ))),
+        }
+        Expr::Wildcard => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Wildcard(true)),
+        },
+        Expr::Placeholder { id, data_type } => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Placeholder(protobuf::PlaceholderNode {
+                id: id.clone(),
+                data_type: Some(data_type.try_into()?),
+            })),
+        },
+        Expr::Exists { subquery, negated } => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Exists(Box::new(protobuf::ExistsNode {
+                subquery: Some(subquery_to_proto(subquery, codec)?),
+                negated: *negated,
+            }))),
+        },
+        Expr::InSubquery {
+            expr,
+            subquery,
+            negated,
+        } => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::InSubquery(Box::new(protobuf::InSubqueryNode {
+                expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                subquery: Some(subquery_to_proto(subquery, codec)?),
+                negated: *negated,
+            }))),
+        },
+        Expr::ScalarSubquery(subquery) => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::ScalarSubquery(Box::new(
+                protobuf::ScalarSubqueryNode {
+                    subquery: Some(subquery_to_proto(subquery, codec)?),
                 },
-
-            Expr::GroupingSet(GroupingSet::Cube(exprs)) => Self {
-                expr_type: Some(ExprType::Cube(CubeNode {
-                    expr: exprs.iter().map(|expr| expr.try_into()).collect::<Result<
-                        Vec<_>,
-                        Self::Error,
-                    >>()?,
-                })),
-            },
-            Expr::GroupingSet(GroupingSet::Rollup(exprs)) => Self {
-                expr_type: Some(ExprType::Rollup(RollupNode {
-                    expr: exprs.iter().map(|expr| expr.try_into()).collect::<Result<
-                        Vec<_>,
-                        Self::Error,
-                    >>()?,
-                })),
+            ))),
+        },
+        Expr::GetIndexedField(GetIndexedField { key, expr }) =>
+            protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::GetIndexedField(Box::new(
+                    protobuf::GetIndexedField {
+                        key: Some(key.try_into()?),
+                        expr: Some(Box::new(serialize_expr(expr, codec)?)),
+                    },
+                ))),
             },
-            Expr::GroupingSet(GroupingSet::GroupingSets(exprs)) => Self {
-                expr_type: Some(ExprType::GroupingSet(GroupingSetNode {
-                    expr: exprs
-                        .iter()
-                        .map(|expr_list| {
-                            Ok(LogicalExprList {
-                                expr: expr_list
-                                    .iter()
-                                    .map(|expr| expr.try_into())
-                                    .collect::<Result<Vec<_>, Self::Error>>()?,
-                            })
+
+        Expr::GroupingSet(GroupingSet::Cube(exprs)) => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Cube(CubeNode {
+                expr: exprs.iter().map(|expr| serialize_expr(expr, codec)).collect::<Result<
+                    Vec<_>,
+                    Error,
+                >>()?,
+            })),
+        },
+        Expr::GroupingSet(GroupingSet::Rollup(exprs)) => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::Rollup(RollupNode {
+                expr: exprs.iter().map(|expr| serialize_expr(expr, codec)).collect::<Result<
+                    Vec<_>,
+                    Error,
+                >>()?,
+            })),
+        },
+        Expr::GroupingSet(GroupingSet::GroupingSets(exprs)) => protobuf::LogicalExprNode {
+            expr_type: Some(ExprType::GroupingSet(GroupingSetNode {
+                expr: exprs
+                    .iter()
+                    .map(|expr_list| {
+                        Ok(LogicalExprList {
+                            expr: expr_list
+                                .iter()
+                                .map(|expr| serialize_expr(expr, codec))
+                                .collect::<Result<Vec<_>, Error>>()?,
                         })
-                        .collect::<Result<Vec<_>, Self::Error>>()?,
-                })),
-            },
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?,
+            })),
+        },
 
-            Expr::QualifiedWildcard { .. } | Expr::TryCast { .. } =>
-                return Err(Error::General("Proto serialization error: Expr::QualifiedWildcard { .. } | Expr::TryCast { .. } not supported".to_string())),
-        };
+        Expr::QualifiedWildcard { .. } | Expr::TryCast { .. } =>
+            return Err(Error::General("Proto serialization error: Expr::QualifiedWildcard { .. } | Expr::TryCast { .. } not supported".to_string())),
+    };
 
-        Ok(expr_node)
-    }
+    Ok(expr_node)
+}
+
+/// Serialize the plan of a subquery, encoding its extension nodes and table
+/// providers with `codec`
+fn subquery_to_proto(
+    subquery: &Subquery,
+    codec: &dyn LogicalExtensionCodec,
+) -> Result<Box<protobuf::LogicalPlanNode>, Error> {
+    protobuf::LogicalPlanNode::try_from_logical_plan(&subquery.subquery, codec)
+        .map(Box::new)
+        .map_err(|e| Error::General(e.to_string()))
 }
 
 impl TryFrom<&ScalarValue> for protobuf::ScalarValue {
@@ -1178,6 +1350,8 @@ impl TryFrom<&BuiltinScalarFunction> for protobuf::ScalarFunction {
             BuiltinScalarFunction::ToTimestampMicros => Self::ToTimestampMicros,
             BuiltinScalarFunction::ToTimestampSeconds => Self::ToTimestampSeconds,
             BuiltinScalarFunction::Now => Self::Now,
+            BuiltinScalarFunction::CurrentDate => Self::CurrentDate,
+            BuiltinScalarFunction::CurrentTime => Self::CurrentTime,
             BuiltinScalarFunction::Translate => Self::Translate,
             BuiltinScalarFunction::RegexpMatch => Self::RegexpMatch,
             BuiltinScalarFunction::Coalesce => Self::Coalesce,
@@ -1186,6 +1360,7 @@ impl TryFrom<&BuiltinScalarFunction> for protobuf::ScalarFunction {
             BuiltinScalarFunction::FromUnixtime => Self::FromUnixtime,
             BuiltinScalarFunction::Atan2 => Self::Atan2,
             BuiltinScalarFunction::ArrowTypeof => Self::ArrowTypeof,
+            BuiltinScalarFunction::ZOrder => Self::ZOrder,
         };
 
         Ok(scalar_function)
@@ -1213,6 +1388,44 @@ impl From<&IntervalUnit> for protobuf::IntervalUnit {
     }
 }
 
+impl TryFrom<&ConfigOptions> for protobuf::ConfigOptions {
+    type Error = Error;
+
+    fn try_from(options: &ConfigOptions) -> Result<Self, Self::Error> {
+        let options = options
+            .options()
+            .iter()
+            .map(|(key, value)| {
+                let value: protobuf::ScalarValue = value.try_into()?;
+                Ok((key.clone(), value))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { options })
+    }
+}
+
+impl TryFrom<&SessionConfig> for protobuf::SessionConfig {
+    type Error = Error;
+
+    fn try_from(config: &SessionConfig) -> Result<Self, Self::Error> {
+        let config_options = (&*config.config_options.read()).try_into()?;
+        Ok(Self {
+            target_partitions: config.target_partitions as u64,
+            default_catalog: config.default_catalog().to_owned(),
+            default_schema: config.default_schema().to_owned(),
+            create_default_catalog_and_schema: config
+                .creates_default_catalog_and_schema(),
+            information_schema: config.information_schema(),
+            repartition_joins: config.repartition_joins,
+            repartition_aggregations: config.repartition_aggregations,
+            repartition_windows: config.repartition_windows,
+            parquet_pruning: config.parquet_pruning,
+            collect_statistics: config.collect_statistics,
+            config_options: Some(config_options),
+        })
+    }
+}
+
 /// Creates a scalar protobuf value from an optional value (T), and
 /// encoding None as the appropriate datatype
 fn create_proto_scalar<I, T: FnOnce(&I) -> protobuf::scalar_value::Value>(
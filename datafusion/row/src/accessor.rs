@@ -20,7 +20,7 @@
 use crate::layout::{RowLayout, RowType};
 use crate::validity::NullBitsFormatter;
 use crate::{fn_get_idx, fn_get_idx_opt, fn_set_idx};
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::util::bit_util::{get_bit_raw, set_bit_raw};
 use datafusion_common::ScalarValue;
 use std::sync::Arc;
@@ -231,6 +231,20 @@ impl<'a> RowAccessor<'a> {
             DataType::UInt64 => self.get_u64_scalar(index),
             DataType::Float32 => self.get_f32_scalar(index),
             DataType::Float64 => self.get_f64_scalar(index),
+            DataType::Date32 => ScalarValue::Date32(self.get_i32_opt(index)),
+            DataType::Date64 => ScalarValue::Date64(self.get_i64_opt(index)),
+            DataType::Timestamp(TimeUnit::Second, tz) => {
+                ScalarValue::TimestampSecond(self.get_i64_opt(index), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, tz) => {
+                ScalarValue::TimestampMillisecond(self.get_i64_opt(index), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+                ScalarValue::TimestampMicrosecond(self.get_i64_opt(index), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, tz) => {
+                ScalarValue::TimestampNanosecond(self.get_i64_opt(index), tz.clone())
+            }
             _ => unreachable!(),
         }
     }
@@ -24,7 +24,7 @@ use crate::reader::*;
 use crate::reg_fn;
 use crate::MutableRecordBatch;
 use arrow::array::ArrayBuilder;
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use datafusion_common::{DataFusionError, Result};
 use datafusion_jit::api::Assembler;
@@ -84,6 +84,25 @@ fn register_read_functions(asm: &Assembler) -> Result<()> {
     reg_fn!(asm, read_field_f64, reader_param.clone(), None);
     reg_fn!(asm, read_field_date32, reader_param.clone(), None);
     reg_fn!(asm, read_field_date64, reader_param.clone(), None);
+    reg_fn!(asm, read_field_timestamp_second, reader_param.clone(), None);
+    reg_fn!(
+        asm,
+        read_field_timestamp_millisecond,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        read_field_timestamp_microsecond,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        read_field_timestamp_nanosecond,
+        reader_param.clone(),
+        None
+    );
     reg_fn!(asm, read_field_utf8, reader_param.clone(), None);
     reg_fn!(asm, read_field_binary, reader_param.clone(), None);
     reg_fn!(asm, read_field_bool_null_free, reader_param.clone(), None);
@@ -99,6 +118,30 @@ fn register_read_functions(asm: &Assembler) -> Result<()> {
     reg_fn!(asm, read_field_f64_null_free, reader_param.clone(), None);
     reg_fn!(asm, read_field_date32_null_free, reader_param.clone(), None);
     reg_fn!(asm, read_field_date64_null_free, reader_param.clone(), None);
+    reg_fn!(
+        asm,
+        read_field_timestamp_second_null_free,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        read_field_timestamp_millisecond_null_free,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        read_field_timestamp_microsecond_null_free,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        read_field_timestamp_nanosecond_null_free,
+        reader_param.clone(),
+        None
+    );
     reg_fn!(asm, read_field_utf8_null_free, reader_param.clone(), None);
     reg_fn!(asm, read_field_binary_null_free, reader_param, None);
     Ok(())
@@ -106,6 +149,7 @@ fn register_read_functions(asm: &Assembler) -> Result<()> {
 
 fn gen_read_row(schema: &Schema, assembler: &Assembler) -> Result<GeneratedFunction> {
     use DataType::*;
+    use TimeUnit::*;
     let mut builder = assembler
         .new_func_builder("read_row")
         .param("row", PTR)
@@ -134,6 +178,18 @@ fn gen_read_row(schema: &Schema, assembler: &Assembler) -> Result<GeneratedFunct
                 Float64 => b.call_stmt("read_field_f64", params)?,
                 Date32 => b.call_stmt("read_field_date32", params)?,
                 Date64 => b.call_stmt("read_field_date64", params)?,
+                Timestamp(Second, None) => {
+                    b.call_stmt("read_field_timestamp_second", params)?
+                }
+                Timestamp(Millisecond, None) => {
+                    b.call_stmt("read_field_timestamp_millisecond", params)?
+                }
+                Timestamp(Microsecond, None) => {
+                    b.call_stmt("read_field_timestamp_microsecond", params)?
+                }
+                Timestamp(Nanosecond, None) => {
+                    b.call_stmt("read_field_timestamp_nanosecond", params)?
+                }
                 Utf8 => b.call_stmt("read_field_utf8", params)?,
                 Binary => b.call_stmt("read_field_binary", params)?,
                 _ => unimplemented!(),
@@ -153,6 +209,18 @@ fn gen_read_row(schema: &Schema, assembler: &Assembler) -> Result<GeneratedFunct
                 Float64 => b.call_stmt("read_field_f64_null_free", params)?,
                 Date32 => b.call_stmt("read_field_date32_null_free", params)?,
                 Date64 => b.call_stmt("read_field_date64_null_free", params)?,
+                Timestamp(Second, None) => {
+                    b.call_stmt("read_field_timestamp_second_null_free", params)?
+                }
+                Timestamp(Millisecond, None) => {
+                    b.call_stmt("read_field_timestamp_millisecond_null_free", params)?
+                }
+                Timestamp(Microsecond, None) => {
+                    b.call_stmt("read_field_timestamp_microsecond_null_free", params)?
+                }
+                Timestamp(Nanosecond, None) => {
+                    b.call_stmt("read_field_timestamp_nanosecond_null_free", params)?
+                }
                 Utf8 => b.call_stmt("read_field_utf8_null_free", params)?,
                 Binary => b.call_stmt("read_field_binary_null_free", params)?,
                 _ => unimplemented!(),
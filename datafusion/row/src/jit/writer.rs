@@ -24,7 +24,7 @@ use crate::schema_null_free;
 use crate::writer::RowWriter;
 use crate::writer::*;
 use arrow::array::Array;
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use datafusion_common::Result;
 use datafusion_jit::api::CodeBlock;
@@ -124,6 +124,30 @@ fn register_write_functions(asm: &Assembler) -> Result<()> {
     reg_fn!(asm, write_field_f64, reader_param.clone(), None);
     reg_fn!(asm, write_field_date32, reader_param.clone(), None);
     reg_fn!(asm, write_field_date64, reader_param.clone(), None);
+    reg_fn!(
+        asm,
+        write_field_timestamp_second,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        write_field_timestamp_millisecond,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        write_field_timestamp_microsecond,
+        reader_param.clone(),
+        None
+    );
+    reg_fn!(
+        asm,
+        write_field_timestamp_nanosecond,
+        reader_param.clone(),
+        None
+    );
     reg_fn!(asm, write_field_utf8, reader_param.clone(), None);
     reg_fn!(asm, write_field_binary, reader_param, None);
     Ok(())
@@ -188,6 +212,7 @@ fn write_typed_field_stmt<'a>(
     params: Vec<Expr>,
 ) -> Result<()> {
     use DataType::*;
+    use TimeUnit::*;
     match dt {
         Boolean => b.call_stmt("write_field_bool", params)?,
         UInt8 => b.call_stmt("write_field_u8", params)?,
@@ -202,6 +227,16 @@ fn write_typed_field_stmt<'a>(
         Float64 => b.call_stmt("write_field_f64", params)?,
         Date32 => b.call_stmt("write_field_date32", params)?,
         Date64 => b.call_stmt("write_field_date64", params)?,
+        Timestamp(Second, None) => b.call_stmt("write_field_timestamp_second", params)?,
+        Timestamp(Millisecond, None) => {
+            b.call_stmt("write_field_timestamp_millisecond", params)?
+        }
+        Timestamp(Microsecond, None) => {
+            b.call_stmt("write_field_timestamp_microsecond", params)?
+        }
+        Timestamp(Nanosecond, None) => {
+            b.call_stmt("write_field_timestamp_nanosecond", params)?
+        }
         Utf8 => b.call_stmt("write_field_utf8", params)?,
         Binary => b.call_stmt("write_field_binary", params)?,
         _ => unimplemented!(),
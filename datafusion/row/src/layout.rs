@@ -156,7 +156,7 @@ fn compact_type_width(dt: &DataType) -> usize {
         Boolean | UInt8 | Int8 => 1,
         UInt16 | Int16 => 2,
         UInt32 | Int32 | Float32 | Date32 => 4,
-        UInt64 | Int64 | Float64 | Date64 => 8,
+        UInt64 | Int64 | Float64 | Date64 | Timestamp(_, None) => 8,
         _ => unreachable!(),
     }
 }
@@ -202,6 +202,8 @@ pub fn row_supported(schema: &Schema, row_type: RowType) -> bool {
         .all(|f| supported_type(f.data_type(), row_type))
 }
 
+/// Timestamps with a time zone are not supported, since the arrays read from
+/// the rows would lose their time zone.
 fn supported_type(dt: &DataType, row_type: RowType) -> bool {
     use DataType::*;
 
@@ -222,6 +224,7 @@ fn supported_type(dt: &DataType, row_type: RowType) -> bool {
                     | Float64
                     | Date32
                     | Date64
+                    | Timestamp(_, None)
                     | Utf8
                     | Binary
             )
@@ -243,6 +246,7 @@ fn supported_type(dt: &DataType, row_type: RowType) -> bool {
                     | Float64
                     | Date32
                     | Date64
+                    | Timestamp(_, None)
             )
         }
     }
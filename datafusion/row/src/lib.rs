@@ -334,6 +334,38 @@ mod tests {
         Compact
     );
 
+    #[test]
+    fn test_single_timestamp() -> Result<()> {
+        let values = vec![Some(5), Some(7), None, Some(0), Some(111)];
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(TimestampSecondArray::from(values.clone())),
+            Arc::new(TimestampMillisecondArray::from(values.clone())),
+            Arc::new(TimestampMicrosecondArray::from(values.clone())),
+            Arc::new(TimestampNanosecondArray::from(values)),
+        ];
+        for a in arrays {
+            for row_type in [Compact, WordAligned] {
+                let batch = RecordBatch::try_from_iter(vec![("a", a.clone())])?;
+                let schema = batch.schema();
+                let mut vector = vec![0; 1024];
+                let row_offsets = {
+                    write_batch_unchecked(
+                        &mut vector,
+                        0,
+                        &batch,
+                        0,
+                        schema.clone(),
+                        row_type,
+                    )
+                };
+                let output_batch =
+                    { read_as_batch(&vector, schema, &row_offsets, row_type)? };
+                assert_eq!(batch, output_batch);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     #[should_panic(expected = "not supported yet")]
     fn test_unsupported_word_aligned_type() {
@@ -376,7 +408,11 @@ mod tests {
     #[test]
     #[should_panic(expected = "not supported yet")]
     fn test_unsupported_type_write() {
-        let a: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![8, 7, 6, 5, 8]));
+        // the time zone would be lost in the arrays read from the rows
+        let a: ArrayRef = Arc::new(TimestampNanosecondArray::from_vec(
+            vec![8, 7, 6, 5, 8],
+            Some("UTC".to_string()),
+        ));
         let batch = RecordBatch::try_from_iter(vec![("a", a)]).unwrap();
         let schema = batch.schema();
         let mut vector = vec![0; 1024];
@@ -21,7 +21,7 @@ use crate::layout::{RowLayout, RowType};
 use crate::validity::{all_valid, NullBitsFormatter};
 use crate::MutableRecordBatch;
 use arrow::array::*;
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use arrow::util::bit_util::get_bit_raw;
 use datafusion_common::{DataFusionError, Result};
@@ -329,6 +329,33 @@ fn_read_field!(date32, Date32Builder);
 fn_read_field!(date64, Date64Builder);
 fn_read_field!(utf8, StringBuilder);
 
+macro_rules! fn_read_field_timestamp {
+    ($UNIT: ident, $ARRAY: ident) => {
+        paste::item! {
+            pub(crate) fn [<read_field_timestamp_ $UNIT>](to: &mut Box<dyn ArrayBuilder>, col_idx: usize, row: &RowReader) {
+                let to = to
+                    .as_any_mut()
+                    .downcast_mut::<$ARRAY>()
+                    .unwrap();
+                to.append_option(row.get_i64_opt(col_idx));
+            }
+
+            pub(crate) fn [<read_field_timestamp_ $UNIT _null_free>](to: &mut Box<dyn ArrayBuilder>, col_idx: usize, row: &RowReader) {
+                let to = to
+                    .as_any_mut()
+                    .downcast_mut::<$ARRAY>()
+                    .unwrap();
+                to.append_value(row.get_i64(col_idx));
+            }
+        }
+    };
+}
+
+fn_read_field_timestamp!(second, TimestampSecondBuilder);
+fn_read_field_timestamp!(millisecond, TimestampMillisecondBuilder);
+fn_read_field_timestamp!(microsecond, TimestampMicrosecondBuilder);
+fn_read_field_timestamp!(nanosecond, TimestampNanosecondBuilder);
+
 pub(crate) fn read_field_binary(
     to: &mut Box<dyn ArrayBuilder>,
     col_idx: usize,
@@ -358,6 +385,7 @@ fn read_field(
     row: &RowReader,
 ) {
     use DataType::*;
+    use TimeUnit::*;
     match dt {
         Boolean => read_field_bool(to, col_idx, row),
         UInt8 => read_field_u8(to, col_idx, row),
@@ -372,6 +400,14 @@ fn read_field(
         Float64 => read_field_f64(to, col_idx, row),
         Date32 => read_field_date32(to, col_idx, row),
         Date64 => read_field_date64(to, col_idx, row),
+        Timestamp(Second, None) => read_field_timestamp_second(to, col_idx, row),
+        Timestamp(Millisecond, None) => {
+            read_field_timestamp_millisecond(to, col_idx, row)
+        }
+        Timestamp(Microsecond, None) => {
+            read_field_timestamp_microsecond(to, col_idx, row)
+        }
+        Timestamp(Nanosecond, None) => read_field_timestamp_nanosecond(to, col_idx, row),
         Utf8 => read_field_utf8(to, col_idx, row),
         Binary => read_field_binary(to, col_idx, row),
         _ => unimplemented!(),
@@ -385,6 +421,7 @@ fn read_field_null_free(
     row: &RowReader,
 ) {
     use DataType::*;
+    use TimeUnit::*;
     match dt {
         Boolean => read_field_bool_null_free(to, col_idx, row),
         UInt8 => read_field_u8_null_free(to, col_idx, row),
@@ -399,6 +436,18 @@ fn read_field_null_free(
         Float64 => read_field_f64_null_free(to, col_idx, row),
         Date32 => read_field_date32_null_free(to, col_idx, row),
         Date64 => read_field_date64_null_free(to, col_idx, row),
+        Timestamp(Second, None) => {
+            read_field_timestamp_second_null_free(to, col_idx, row)
+        }
+        Timestamp(Millisecond, None) => {
+            read_field_timestamp_millisecond_null_free(to, col_idx, row)
+        }
+        Timestamp(Microsecond, None) => {
+            read_field_timestamp_microsecond_null_free(to, col_idx, row)
+        }
+        Timestamp(Nanosecond, None) => {
+            read_field_timestamp_nanosecond_null_free(to, col_idx, row)
+        }
         Utf8 => read_field_utf8_null_free(to, col_idx, row),
         Binary => read_field_binary_null_free(to, col_idx, row),
         _ => unimplemented!(),
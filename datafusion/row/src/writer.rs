@@ -19,7 +19,7 @@
 
 use crate::layout::{estimate_row_width, RowLayout, RowType};
 use arrow::array::*;
-use arrow::datatypes::{DataType, Schema};
+use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use arrow::util::bit_util::{round_upto_power_of_2, set_bit_raw, unset_bit_raw};
 use datafusion_common::Result;
@@ -340,6 +340,25 @@ pub(crate) fn write_field_date64(
     to.set_date64(col_idx, from.value(row_idx));
 }
 
+macro_rules! fn_write_field_timestamp {
+    ($UNIT: ident, $ARRAY: ident) => {
+        paste::item! {
+            pub(crate) fn [<write_field_timestamp_ $UNIT>](to: &mut RowWriter, from: &Arc<dyn Array>, col_idx: usize, row_idx: usize) {
+                let from = from
+                    .as_any()
+                    .downcast_ref::<$ARRAY>()
+                    .unwrap();
+                to.set_i64(col_idx, from.value(row_idx));
+            }
+        }
+    };
+}
+
+fn_write_field_timestamp!(second, TimestampSecondArray);
+fn_write_field_timestamp!(millisecond, TimestampMillisecondArray);
+fn_write_field_timestamp!(microsecond, TimestampMicrosecondArray);
+fn_write_field_timestamp!(nanosecond, TimestampNanosecondArray);
+
 pub(crate) fn write_field_utf8(
     to: &mut RowWriter,
     from: &Arc<dyn Array>,
@@ -378,6 +397,7 @@ fn write_field(
     row: &mut RowWriter,
 ) {
     use DataType::*;
+    use TimeUnit::*;
     match dt {
         Boolean => write_field_bool(row, col, col_idx, row_idx),
         UInt8 => write_field_u8(row, col, col_idx, row_idx),
@@ -392,6 +412,18 @@ fn write_field(
         Float64 => write_field_f64(row, col, col_idx, row_idx),
         Date32 => write_field_date32(row, col, col_idx, row_idx),
         Date64 => write_field_date64(row, col, col_idx, row_idx),
+        Timestamp(Second, None) => {
+            write_field_timestamp_second(row, col, col_idx, row_idx)
+        }
+        Timestamp(Millisecond, None) => {
+            write_field_timestamp_millisecond(row, col, col_idx, row_idx)
+        }
+        Timestamp(Microsecond, None) => {
+            write_field_timestamp_microsecond(row, col, col_idx, row_idx)
+        }
+        Timestamp(Nanosecond, None) => {
+            write_field_timestamp_nanosecond(row, col, col_idx, row_idx)
+        }
         Utf8 => write_field_utf8(row, col, col_idx, row_idx),
         Binary => write_field_binary(row, col, col_idx, row_idx),
         _ => unimplemented!(),
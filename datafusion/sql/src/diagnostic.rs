@@ -0,0 +1,409 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Source locations of planning errors in the SQL text.
+//!
+//! The SQL AST does not keep the position of its nodes, so the planner
+//! records the text of the expression whose planning failed, which is found
+//! by comparing its tokens with the tokens of the SQL text. The column an
+//! error is about is then looked up in that expression, as a reference with
+//! the qualifier of the error.
+
+use datafusion_common::{DataFusionError, SchemaError};
+
+/// A range of bytes in a SQL text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Offset of the first byte
+    pub start: usize,
+    /// Offset after the last byte
+    pub end: usize,
+}
+
+impl Span {
+    /// Returns the 1-based line and column, counted in characters, of the
+    /// start of this span in `sql`
+    pub fn line_and_column(&self, sql: &str) -> (usize, usize) {
+        let before = &sql[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = sql[line_start..self.start].chars().count() + 1;
+        (line, column)
+    }
+
+    fn contains(&self, other: &Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// An identifier or a keyword, lower cased unless it was quoted
+    Ident(String),
+    /// The value of a string literal
+    Literal(String),
+    Period,
+    Symbol(char),
+}
+
+/// Splits `sql` into tokens, skipping comments
+fn tokenize(sql: &str) -> Vec<(Token, Span)> {
+    let mut tokens = vec![];
+    let mut chars = sql.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '\'' => {
+                // a quote inside a literal is escaped by doubling it
+                let mut value = String::new();
+                while let Some((_, c)) = chars.next() {
+                    if c == '\'' && chars.next_if(|(_, c)| *c == '\'').is_none() {
+                        break;
+                    }
+                    value.push(c);
+                }
+                Token::Literal(value)
+            }
+            '"' => {
+                let mut ident = String::new();
+                while let Some((_, c)) = chars.next() {
+                    if c == '"' && chars.next_if(|(_, c)| *c == '"').is_none() {
+                        break;
+                    }
+                    ident.push(c);
+                }
+                Token::Ident(ident)
+            }
+            '-' if chars.next_if(|(_, c)| *c == '-').is_some() => {
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if(|(_, c)| *c == '*').is_some() => {
+                while let Some((_, c)) = chars.next() {
+                    if c == '*' && chars.next_if(|(_, c)| *c == '/').is_some() {
+                        break;
+                    }
+                }
+                continue;
+            }
+            '.' => Token::Period,
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_lowercase().to_string();
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_' || *c == '$')
+                {
+                    ident.extend(c.to_lowercase());
+                }
+                Token::Ident(ident)
+            }
+            c if c.is_whitespace() => continue,
+            c => Token::Symbol(c),
+        };
+        let end = chars.peek().map(|(i, _)| *i).unwrap_or(sql.len());
+        tokens.push((token, Span { start, end }));
+    }
+    tokens
+}
+
+/// Returns the span in `sql` of the first occurrence of the SQL expression
+/// `expr`, comparing their tokens so that the case of the keywords and the
+/// spacing do not matter
+pub fn find_expr(sql: &str, expr: &str) -> Option<Span> {
+    let pattern = tokenize(expr)
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect::<Vec<_>>();
+    if pattern.is_empty() {
+        return None;
+    }
+    tokenize(sql)
+        .windows(pattern.len())
+        .find(|window| {
+            window
+                .iter()
+                .zip(pattern.iter())
+                .all(|((token, _), expected)| token == expected)
+        })
+        .map(|window| Span {
+            start: window[0].1.start,
+            end: window[window.len() - 1].1.end,
+        })
+}
+
+/// Returns the span of the first reference to the column `name`, qualified
+/// by `qualifier` if any, in `sql`, or in the part of `sql` in `scope`
+///
+/// Identifiers that are part of a longer compound identifier, aliases
+/// introduced by `AS`, tables following `FROM` or `JOIN` and function names
+/// are not column references.
+pub fn find_column(
+    sql: &str,
+    scope: Option<Span>,
+    qualifier: Option<&str>,
+    name: &str,
+) -> Option<Span> {
+    let mut pattern = vec![];
+    if let Some(qualifier) = qualifier {
+        for part in qualifier.split('.') {
+            pattern.push(Token::Ident(part.to_string()));
+            pattern.push(Token::Period);
+        }
+    }
+    pattern.push(Token::Ident(name.to_string()));
+
+    let tokens = tokenize(sql)
+        .into_iter()
+        .filter(|(_, span)| scope.map(|scope| scope.contains(span)).unwrap_or(true))
+        .collect::<Vec<_>>();
+    tokens
+        .windows(pattern.len())
+        .enumerate()
+        .find(|(i, window)| {
+            let before = i.checked_sub(1).map(|i| &tokens[i].0);
+            let after = tokens.get(i + pattern.len()).map(|(token, _)| token);
+            window
+                .iter()
+                .zip(pattern.iter())
+                .all(|((token, _), expected)| token == expected)
+                && !matches!(before, Some(Token::Period))
+                && !matches!(before, Some(Token::Ident(keyword))
+                    if ["as", "from", "join"].contains(&keyword.as_str()))
+                && !matches!(after, Some(Token::Period | Token::Symbol('(')))
+        })
+        .map(|(_, window)| Span {
+            start: window[0].1.start,
+            end: window[window.len() - 1].1.end,
+        })
+}
+
+/// Returns the span in `sql` of the column an error is about, for errors
+/// that reference a column, looking for it in the part of `sql` in `scope`
+/// if any
+fn locate_column(err: &DataFusionError, sql: &str, scope: Option<Span>) -> Option<Span> {
+    match err.find_root() {
+        DataFusionError::SchemaError(SchemaError::FieldNotFound {
+            qualifier,
+            name,
+            ..
+        })
+        | DataFusionError::SchemaError(SchemaError::AmbiguousReference {
+            qualifier,
+            name,
+        }) => find_column(sql, scope, qualifier.as_deref(), name),
+        _ => None,
+    }
+}
+
+/// Renders the line of `sql` containing `span`, with a caret underlining the
+/// span, like
+///
+/// ```text
+///  --> line 1, column 8
+///   |
+/// 1 | SELECT x FROM t
+///   |        ^
+/// ```
+pub fn render_snippet(sql: &str, span: Span) -> String {
+    let (line, column) = span.line_and_column(sql);
+    let line_start = sql[..span.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = sql[span.start..]
+        .find('\n')
+        .map(|i| span.start + i)
+        .unwrap_or(sql.len());
+    let text = sql[line_start..line_end].trim_end();
+    let width = sql[span.start..span.end.min(line_end)]
+        .chars()
+        .count()
+        .max(1);
+
+    let gutter = " ".repeat(line.to_string().len());
+    format!(
+        "{gutter}--> line {line}, column {column}\n\
+         {gutter} |\n\
+         {line} | {text}\n\
+         {gutter} | {padding}{carets}",
+        gutter = gutter,
+        line = line,
+        column = column,
+        text = text,
+        padding = " ".repeat(column - 1),
+        carets = "^".repeat(width),
+    )
+}
+
+/// Whether `err` has a location, added by [`with_source_location`] or
+/// [`with_expr_location`]
+fn is_located(mut err: &DataFusionError) -> bool {
+    loop {
+        err = match err {
+            DataFusionError::Context(desc, _)
+                if desc.trim_start().starts_with("--> line ") =>
+            {
+                return true
+            }
+            DataFusionError::Context(_, err)
+            | DataFusionError::RuntimeContext(_, err) => err.as_ref(),
+            _ => return false,
+        }
+    }
+}
+
+/// Adds the location of `err` in `sql`, if known, to the error: the first
+/// reference in `sql` to the column the error is about
+pub fn with_source_location(err: DataFusionError, sql: &str) -> DataFusionError {
+    if is_located(&err) {
+        return err;
+    }
+    match locate_column(&err, sql, None) {
+        Some(span) => err.context(render_snippet(sql, span)),
+        None => err,
+    }
+}
+
+/// Adds the location of `err`, raised by the planning of the expression
+/// `expr` of `sql`, to the error: the reference to the column the error is
+/// about in the expression, or the expression for the other errors
+pub fn with_expr_location(
+    err: DataFusionError,
+    sql: &str,
+    expr: &str,
+) -> DataFusionError {
+    if is_located(&err) {
+        return err;
+    }
+    let span = find_expr(sql, expr).and_then(|scope| match err.find_root() {
+        DataFusionError::SchemaError(_) => {
+            locate_column(&err, sql, Some(scope)).or(Some(scope))
+        }
+        _ => Some(scope),
+    });
+    match span {
+        Some(span) => err.context(render_snippet(sql, span)),
+        None => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_columns() {
+        let sql = "SELECT 'a', \"A\", -- a\n /* a */ t.A FROM t";
+        let span = find_column(sql, None, None, "a").unwrap();
+        assert_eq!(&sql[span.start..span.end], "A");
+        assert_eq!(span.line_and_column(sql), (2, 12));
+
+        let span = find_column(sql, None, None, "A").unwrap();
+        assert_eq!(&sql[span.start..span.end], "\"A\"");
+
+        let span = find_column(sql, None, Some("t"), "a").unwrap();
+        assert_eq!(&sql[span.start..span.end], "t.A");
+
+        assert_eq!(find_column(sql, None, Some("s"), "a"), None);
+        assert_eq!(find_column("SELECT 'it''s b'", None, None, "b"), None);
+    }
+
+    #[test]
+    fn find_column_references() {
+        // not an alias, a qualifier, a qualified column or a function
+        let sql = "SELECT a(1) AS a, a.x, s.a.y, t.a, a FROM a";
+        let span = find_column(sql, None, None, "a").unwrap();
+        assert_eq!(span.line_and_column(sql), (1, 36));
+
+        // the qualified references to b are not references to t.b
+        let sql = "SELECT s.t.b, t.b FROM t";
+        let span = find_column(sql, None, Some("t"), "b").unwrap();
+        assert_eq!(span.line_and_column(sql), (1, 15));
+    }
+
+    #[test]
+    fn find_in_expression() {
+        let sql = "SELECT b + 1 FROM t WHERE B   >  1 AND b < 'x'";
+        let scope = find_expr(sql, "b > 1").unwrap();
+        assert_eq!(&sql[scope.start..scope.end], "B   >  1");
+        let span = find_column(sql, Some(scope), None, "b").unwrap();
+        assert_eq!(span.line_and_column(sql), (1, 27));
+
+        let scope = find_expr(sql, "b < 'x'").unwrap();
+        assert_eq!(&sql[scope.start..scope.end], "b < 'x'");
+        assert_eq!(find_expr(sql, "b < 'y'"), None);
+    }
+
+    #[test]
+    fn render() {
+        let sql = "SELECT a,\n       foo.bar\nFROM foo";
+        let span = find_column(sql, None, Some("foo"), "bar").unwrap();
+        assert_eq!(
+            render_snippet(sql, span),
+            " --> line 2, column 8\n  |\n2 |        foo.bar\n  |        ^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let sql = "SELECT b FROM t";
+        let err = DataFusionError::SchemaError(SchemaError::FieldNotFound {
+            qualifier: None,
+            name: "b".to_string(),
+            valid_fields: None,
+        });
+        let err = with_source_location(err, sql);
+        assert_eq!(
+            err.to_string(),
+            " --> line 1, column 8\n  |\n1 | SELECT b FROM t\n  |        ^\n\
+             caused by\nSchema error: No field named 'b'."
+        );
+        // located once
+        let located = err.to_string();
+        assert_eq!(with_source_location(err, sql).to_string(), located);
+
+        let err = DataFusionError::Plan("foo".to_string());
+        assert_eq!(
+            with_source_location(err, sql).to_string(),
+            "Error during planning: foo"
+        );
+    }
+
+    #[test]
+    fn expr_error_location() {
+        let sql = "SELECT b + 1 AS x, b FROM t WHERE b > 1";
+        let err = DataFusionError::SchemaError(SchemaError::FieldNotFound {
+            qualifier: None,
+            name: "b".to_string(),
+            valid_fields: None,
+        });
+        let err = with_expr_location(err, sql, "b > 1");
+        assert_eq!(
+            err.to_string(),
+            " --> line 1, column 35\n  |\n1 | SELECT b + 1 AS x, b FROM t WHERE b > 1\n  |                                   ^\n\
+             caused by\nSchema error: No field named 'b'."
+        );
+
+        let err = DataFusionError::Plan("foo".to_string());
+        let err = with_expr_location(err, sql, "b + 1");
+        assert_eq!(
+            err.to_string(),
+            " --> line 1, column 8\n  |\n1 | SELECT b + 1 AS x, b FROM t WHERE b > 1\n  |        ^^^^^\n\
+             caused by\nError during planning: foo"
+        );
+
+        // the text of the planned statement is not the SQL text
+        let err = DataFusionError::Plan("foo".to_string());
+        let err = with_expr_location(err, sql, "c + 1");
+        assert_eq!(err.to_string(), "Error during planning: foo");
+    }
+}
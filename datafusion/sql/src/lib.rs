@@ -18,6 +18,7 @@
 //! This module provides a SQL parser that translates SQL queries into an abstract syntax
 //! tree (AST), and a SQL query planner that creates a logical plan from the AST.
 
+pub mod diagnostic;
 pub mod parser;
 pub mod planner;
 mod table_reference;
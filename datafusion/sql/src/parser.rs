@@ -19,13 +19,21 @@
 //!
 //! Declares a SQL parser based on sqlparser that handles custom formats that we need.
 
+use crate::utils::{normalize_ident, normalize_sql_object_name};
+use datafusion_expr::is_secret_option;
 use sqlparser::{
-    ast::{ColumnDef, ColumnOptionDef, Statement as SQLStatement, TableConstraint},
+    ast::{
+        ColumnDef, ColumnOptionDef, Expr as SQLExpr, Function, Ident, ObjectName,
+        Statement as SQLStatement, TableConstraint,
+    },
     dialect::{keywords::Keyword, Dialect, GenericDialect},
     parser::{Parser, ParserError},
     tokenizer::{Token, Tokenizer},
 };
-use std::{collections::VecDeque, fmt};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
 
 // Use `Parser::expected` instead, if possible
 macro_rules! parser_err {
@@ -43,26 +51,68 @@ fn parse_file_compression_type(s: &str) -> Result<String, ParserError> {
 }
 
 /// DataFusion extension DDL for `CREATE EXTERNAL TABLE`
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct CreateExternalTable {
-    /// Table name
+    /// Table name, optionally qualified by its schema and catalog
     pub name: String,
     /// Optional schema
     pub columns: Vec<ColumnDef>,
+    /// Constraints declared on the columns, such as a primary key
+    pub constraints: Vec<TableConstraint>,
     /// File type (Parquet, NDJSON, CSV, etc)
     pub file_type: String,
     /// CSV Header row?
     pub has_header: bool,
     /// User defined delimiter for CSVs
     pub delimiter: char,
-    /// Path to file
+    /// Path to file, or empty for the default location of the table in its
+    /// schema
     pub location: String,
     /// Partition Columns
     pub table_partition_cols: Vec<String>,
+    /// Bucket columns, that the rows of the files are hashed on
+    pub bucket_cols: Vec<String>,
+    /// Number of buckets of the rows, 0 if the table is not bucketed
+    pub num_buckets: usize,
     /// Option to not error if table already exists
     pub if_not_exists: bool,
     /// File compression type (GZIP, BZIP2)
     pub file_compression_type: String,
+    /// Table(provider) specific options
+    pub options: HashMap<String, String>,
+}
+
+impl fmt::Debug for CreateExternalTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the values of the secret options are redacted
+        let options: HashMap<&String, &str> = self
+            .options
+            .iter()
+            .map(|(key, value)| {
+                let value = if is_secret_option(key) {
+                    "<redacted>"
+                } else {
+                    value.as_str()
+                };
+                (key, value)
+            })
+            .collect();
+        f.debug_struct("CreateExternalTable")
+            .field("name", &self.name)
+            .field("columns", &self.columns)
+            .field("constraints", &self.constraints)
+            .field("file_type", &self.file_type)
+            .field("has_header", &self.has_header)
+            .field("delimiter", &self.delimiter)
+            .field("location", &self.location)
+            .field("table_partition_cols", &self.table_partition_cols)
+            .field("bucket_cols", &self.bucket_cols)
+            .field("num_buckets", &self.num_buckets)
+            .field("if_not_exists", &self.if_not_exists)
+            .field("file_compression_type", &self.file_compression_type)
+            .field("options", &options)
+            .finish()
+    }
 }
 
 impl fmt::Display for CreateExternalTable {
@@ -73,10 +123,25 @@ impl fmt::Display for CreateExternalTable {
         }
         write!(f, "{} ", self.name)?;
         write!(f, "STORED AS {} ", self.file_type)?;
-        write!(f, "LOCATION {} ", self.location)
+        if !self.location.is_empty() {
+            write!(f, "LOCATION {} ", self.location)?;
+        }
+        Ok(())
     }
 }
 
+/// DataFusion extension DDL for `CREATE SCHEMA`, which can set the default
+/// location of the tables of the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateSchema {
+    /// Schema name, optionally qualified by its catalog
+    pub name: String,
+    /// Option to not error if the schema already exists
+    pub if_not_exists: bool,
+    /// The directory of the tables created in the schema without a location
+    pub location: Option<String>,
+}
+
 /// DataFusion extension DDL for `DESCRIBE TABLE`
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DescribeTable {
@@ -84,22 +149,297 @@ pub struct DescribeTable {
     pub table_name: String,
 }
 
+/// DataFusion extension DDL for `REFRESH MATERIALIZED VIEW`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshMaterializedView {
+    /// View name
+    pub name: String,
+}
+
+/// DataFusion extension DDL for `ANALYZE TABLE`, which builds the index files
+/// of the data files of a listing table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzeTable {
+    /// Table name
+    pub table_name: String,
+}
+
+/// DataFusion extension for `COPY ... FROM STDIN`, which loads the data sent
+/// by the client into a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyFrom {
+    /// Table name
+    pub table_name: String,
+    /// The columns of the data, or empty for all the columns of the table
+    pub columns: Vec<String>,
+    /// File type of the data (CSV or NDJSON)
+    pub file_type: String,
+    /// CSV Header row?
+    pub has_header: bool,
+    /// User defined delimiter for CSVs
+    pub delimiter: char,
+}
+
 /// DataFusion Statement representations.
 ///
 /// Tokens parsed by `DFParser` are converted into these values.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Statement {
-    /// ANSI SQL AST node
-    Statement(Box<SQLStatement>),
+    /// ANSI SQL AST node, with the null treatment of its window function
+    /// calls
+    Statement(Box<SQLStatement>, NullTreatments),
     /// Extension: `CREATE EXTERNAL TABLE`
     CreateExternalTable(CreateExternalTable),
     /// Extension: `DESCRIBE TABLE`
     DescribeTable(DescribeTable),
+    /// Extension: `REFRESH MATERIALIZED VIEW`
+    RefreshMaterializedView(RefreshMaterializedView),
+    /// Extension: `ANALYZE TABLE`
+    AnalyzeTable(AnalyzeTable),
+    /// Extension: `CREATE SCHEMA`, with an optional `LOCATION`
+    CreateSchema(CreateSchema),
+    /// Extension: `COPY ... FROM STDIN`
+    CopyFrom(CopyFrom),
+}
+
+/// The default maximum nesting of the SQL statements parsed by [`DFParser`],
+/// beyond which parsing fails instead of overflowing the stack
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// The maximum nesting of the parentheses, brackets, `CASE` expressions and
+/// prefix operators of `tokens`, which the parser recurses into
+fn nesting_depth(tokens: &[Token]) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+    // the prefix operators preceding the current token, such as `NOT NOT`
+    let mut prefix = 0;
+    for token in tokens {
+        match token {
+            Token::Whitespace(_) => continue,
+            Token::LParen | Token::LBracket => depth += 1,
+            Token::RParen | Token::RBracket => depth = depth.saturating_sub(1),
+            Token::Word(w) if w.keyword == Keyword::CASE => depth += 1,
+            Token::Word(w) if w.keyword == Keyword::END => {
+                depth = depth.saturating_sub(1)
+            }
+            Token::Word(w) if w.keyword == Keyword::NOT => {
+                prefix += 1;
+                max_depth = max_depth.max(depth + prefix);
+                continue;
+            }
+            Token::Minus | Token::Plus => {
+                prefix += 1;
+                max_depth = max_depth.max(depth + prefix);
+                continue;
+            }
+            _ => {}
+        }
+        prefix = 0;
+        max_depth = max_depth.max(depth);
+    }
+    max_depth
+}
+
+/// The null treatment of the window function calls of a statement, which
+/// sqlparser can not parse.
+///
+/// [`DFParser`] removes the `IGNORE NULLS` and `RESPECT NULLS` options
+/// between the arguments of a window function call and its `OVER` clause,
+/// and records the calls ignoring the nulls by their SQL text without the
+/// option: `lag(a) IGNORE NULLS OVER ()` is parsed as `lag(a) OVER ()`,
+/// ignoring the nulls. As the calls are told apart by their text, the same
+/// call can not both ignore and respect the nulls in a statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NullTreatments {
+    /// The SQL text of the calls ignoring the nulls
+    ignore_nulls: HashSet<String>,
+}
+
+impl NullTreatments {
+    /// Whether the window function call `function` ignores the nulls
+    pub fn ignore_nulls(&self, function: &Function) -> bool {
+        !self.ignore_nulls.is_empty() && self.ignore_nulls.contains(&function.to_string())
+    }
+}
+
+/// Whether `token` is the unquoted word `value`
+fn is_word(token: &Token, value: &str) -> bool {
+    matches!(token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(value))
+}
+
+/// The index of the first token after `index` that is not a whitespace
+fn next_token(tokens: &[Token], index: usize) -> Option<usize> {
+    (index + 1..tokens.len()).find(|i| !matches!(tokens[*i], Token::Whitespace(_)))
+}
+
+/// The index of the last token before `index` that is not a whitespace
+fn previous_token(tokens: &[Token], index: usize) -> Option<usize> {
+    (0..index)
+        .rev()
+        .find(|i| !matches!(tokens[*i], Token::Whitespace(_)))
+}
+
+/// A window function call in the tokens of a statement
+struct WindowCall {
+    /// The index of the first token of the function name
+    start: usize,
+    /// The index of the closing parenthesis of the `OVER` clause
+    end: usize,
+    /// The indexes of the first and last tokens of the `IGNORE NULLS` or
+    /// `RESPECT NULLS` option, and whether it ignores the nulls
+    option: Option<(usize, usize, bool)>,
+}
+
+impl WindowCall {
+    /// The call whose name starts at `start` and whose arguments end with
+    /// the closing parenthesis at `close`, if it is followed by an optional
+    /// null treatment and an `OVER` clause
+    fn try_new(tokens: &[Token], start: usize, close: usize) -> Option<Self> {
+        let mut over = next_token(tokens, close)?;
+        let mut option = None;
+        if is_word(&tokens[over], "IGNORE") || is_word(&tokens[over], "RESPECT") {
+            let nulls =
+                next_token(tokens, over).filter(|i| is_word(&tokens[*i], "NULLS"))?;
+            option = Some((over, nulls, is_word(&tokens[over], "IGNORE")));
+            over = next_token(tokens, nulls)?;
+        }
+        let open = next_token(tokens, over)?;
+        if !is_word(&tokens[over], "OVER") || tokens[open] != Token::LParen {
+            return None;
+        }
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(open) {
+            match token {
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 1 => {
+                    return Some(Self {
+                        start,
+                        end: i,
+                        option,
+                    })
+                }
+                Token::RParen => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// The index of the first token of the possibly qualified function name
+/// ending at `index`, if it is a name
+fn function_name_start(tokens: &[Token], index: usize) -> Option<usize> {
+    if !matches!(tokens[index], Token::Word(_)) {
+        return None;
+    }
+    let mut start = index;
+    while let Some(period) = previous_token(tokens, start) {
+        match previous_token(tokens, period) {
+            Some(name)
+                if tokens[period] == Token::Period
+                    && matches!(tokens[name], Token::Word(_)) =>
+            {
+                start = name
+            }
+            _ => break,
+        }
+    }
+    Some(start)
+}
+
+/// Remove the `IGNORE NULLS` and `RESPECT NULLS` options of the window
+/// function calls of `tokens`, returning the [`NullTreatments`] of each of
+/// the non empty statements. The options that do not follow the arguments
+/// of a window function call are left for the parser to report.
+fn take_null_treatments(
+    tokens: &mut Vec<Token>,
+    dialect: &dyn Dialect,
+) -> Result<VecDeque<NullTreatments>, ParserError> {
+    // the window function calls of each statement
+    let mut statements = vec![];
+    let mut calls = vec![];
+    // the start of the function name preceding each open parenthesis, if any
+    let mut names = vec![];
+    let mut previous = None;
+    for i in 0..tokens.len() {
+        match tokens[i] {
+            Token::Whitespace(_) => continue,
+            Token::SemiColon => {
+                if previous.is_some() {
+                    statements.push(std::mem::take(&mut calls));
+                }
+                names.clear();
+                previous = None;
+                continue;
+            }
+            Token::LParen => names.push(
+                previous.and_then(|previous| function_name_start(tokens, previous)),
+            ),
+            Token::RParen => {
+                if let Some(Some(start)) = names.pop() {
+                    calls.extend(WindowCall::try_new(tokens, start, i));
+                }
+            }
+            _ => {}
+        }
+        previous = Some(i);
+    }
+    if previous.is_some() {
+        statements.push(calls);
+    }
+
+    let removed = statements
+        .iter()
+        .flatten()
+        .filter_map(|call| call.option)
+        .flat_map(|(first, last, _)| first..=last)
+        .collect::<HashSet<_>>();
+    let mut null_treatments = VecDeque::new();
+    for calls in statements {
+        let mut ignore_nulls = HashSet::new();
+        let mut respect_nulls = HashSet::new();
+        for call in calls {
+            let call_tokens = (call.start..=call.end)
+                .filter(|i| !removed.contains(i))
+                .map(|i| tokens[i].clone())
+                .collect();
+            let text = match Parser::new(call_tokens, dialect).parse_expr() {
+                Ok(expr @ SQLExpr::Function(_)) => expr.to_string(),
+                _ if call.option.is_none() => continue,
+                _ => {
+                    return parser_err!(
+                        "Expected a window function call before IGNORE NULLS or RESPECT NULLS"
+                    )
+                }
+            };
+            if matches!(call.option, Some((_, _, true))) {
+                ignore_nulls.insert(text);
+            } else {
+                respect_nulls.insert(text);
+            }
+        }
+        if let Some(text) = ignore_nulls.intersection(&respect_nulls).next() {
+            return parser_err!(format!(
+                "The window function call {} both ignores and respects the nulls, which is not supported in one statement",
+                text
+            ));
+        }
+        null_treatments.push_back(NullTreatments { ignore_nulls });
+    }
+
+    let mut index = 0;
+    tokens.retain(|_| {
+        index += 1;
+        !removed.contains(&(index - 1))
+    });
+    Ok(null_treatments)
 }
 
 /// SQL Parser
 pub struct DFParser<'a> {
     parser: Parser<'a>,
+    /// The null treatments of the statements left to parse
+    null_treatments: VecDeque<NullTreatments>,
 }
 
 impl<'a> DFParser<'a> {
@@ -113,12 +453,31 @@ impl<'a> DFParser<'a> {
     pub fn new_with_dialect(
         sql: &str,
         dialect: &'a dyn Dialect,
+    ) -> Result<Self, ParserError> {
+        DFParser::new_with_max_depth(sql, dialect, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Parse the specified tokens with dialect, failing for the statements
+    /// nesting parentheses, brackets, `CASE` expressions and prefix
+    /// operators deeper than `max_depth` levels
+    pub fn new_with_max_depth(
+        sql: &str,
+        dialect: &'a dyn Dialect,
+        max_depth: usize,
     ) -> Result<Self, ParserError> {
         let mut tokenizer = Tokenizer::new(dialect, sql);
-        let tokens = tokenizer.tokenize()?;
+        let mut tokens = tokenizer.tokenize()?;
+        if nesting_depth(&tokens) > max_depth {
+            return parser_err!(format!(
+                "SQL statement nested deeper than the limit of {} levels",
+                max_depth
+            ));
+        }
+        let null_treatments = take_null_treatments(&mut tokens, dialect)?;
 
         Ok(DFParser {
             parser: Parser::new(tokens, dialect),
+            null_treatments,
         })
     }
 
@@ -133,7 +492,17 @@ impl<'a> DFParser<'a> {
         sql: &str,
         dialect: &dyn Dialect,
     ) -> Result<VecDeque<Statement>, ParserError> {
-        let mut parser = DFParser::new_with_dialect(sql, dialect)?;
+        DFParser::parse_sql_with_max_depth(sql, dialect, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Parse a SQL statement and produce a set of statements, failing for
+    /// the statements nested deeper than `max_depth` levels
+    pub fn parse_sql_with_max_depth(
+        sql: &str,
+        dialect: &dyn Dialect,
+        max_depth: usize,
+    ) -> Result<VecDeque<Statement>, ParserError> {
+        let mut parser = DFParser::new_with_max_depth(sql, dialect, max_depth)?;
         let mut stmts = VecDeque::new();
         let mut expecting_statement_delimiter = false;
         loop {
@@ -156,6 +525,29 @@ impl<'a> DFParser<'a> {
         Ok(stmts)
     }
 
+    /// Parse a single SQL expression, such as the expression of a `CHECK`
+    /// constraint, failing for the expressions nested deeper than
+    /// `max_depth` levels
+    pub fn parse_sql_expr_with_max_depth(
+        sql: &str,
+        dialect: &dyn Dialect,
+        max_depth: usize,
+    ) -> Result<SQLExpr, ParserError> {
+        let mut parser = DFParser::new_with_max_depth(sql, dialect, max_depth)?;
+        if parser
+            .null_treatments
+            .iter()
+            .any(|null_treatments| !null_treatments.ignore_nulls.is_empty())
+        {
+            return parser_err!("IGNORE NULLS is only supported in statements");
+        }
+        let expr = parser.parser.parse_expr()?;
+        if parser.parser.peek_token() != Token::EOF {
+            return parser.expected("end of expression", parser.parser.peek_token());
+        }
+        Ok(expr)
+    }
+
     /// Report unexpected token
     fn expected<T>(&self, expected: &str, found: Token) -> Result<T, ParserError> {
         parser_err!(format!("Expected {}, found: {}", expected, found))
@@ -163,6 +555,18 @@ impl<'a> DFParser<'a> {
 
     /// Parse a new expression
     pub fn parse_statement(&mut self) -> Result<Statement, ParserError> {
+        let null_treatments = self.null_treatments.pop_front().unwrap_or_default();
+        match self.parse_statement_ast()? {
+            Statement::Statement(statement, _) => {
+                Ok(Statement::Statement(statement, null_treatments))
+            }
+            statement => Ok(statement),
+        }
+    }
+
+    /// Parse a new statement, without the null treatment of its window
+    /// function calls
+    fn parse_statement_ast(&mut self) -> Result<Statement, ParserError> {
         match self.parser.peek_token() {
             Token::Word(w) => {
                 match w.keyword {
@@ -178,19 +582,54 @@ impl<'a> DFParser<'a> {
                         // use custom parsing
                         self.parse_describe()
                     }
+                    _ if w.value.eq_ignore_ascii_case("REFRESH") => {
+                        // move one token forward
+                        self.parser.next_token();
+                        // use custom parsing
+                        self.parse_refresh()
+                    }
+                    Keyword::COPY => {
+                        // move one token forward
+                        self.parser.next_token();
+                        // use custom parsing
+                        self.parse_copy()
+                    }
+                    Keyword::ANALYZE => {
+                        // move one token forward
+                        self.parser.next_token();
+                        // use custom parsing
+                        self.parse_analyze()
+                    }
+                    Keyword::SET => {
+                        // move one token forward
+                        self.parser.next_token();
+                        if self.parser.parse_keywords(&[Keyword::TIME, Keyword::ZONE]) {
+                            // use custom parsing
+                            self.parse_set_time_zone()
+                        } else {
+                            // use the native parser
+                            self.parser.prev_token();
+                            Ok(Statement::Statement(
+                                Box::from(self.parser.parse_statement()?),
+                                NullTreatments::default(),
+                            ))
+                        }
+                    }
                     _ => {
                         // use the native parser
-                        Ok(Statement::Statement(Box::from(
-                            self.parser.parse_statement()?,
-                        )))
+                        Ok(Statement::Statement(
+                            Box::from(self.parser.parse_statement()?),
+                            NullTreatments::default(),
+                        ))
                     }
                 }
             }
             _ => {
                 // use the native parser
-                Ok(Statement::Statement(Box::from(
-                    self.parser.parse_statement()?,
-                )))
+                Ok(Statement::Statement(
+                    Box::from(self.parser.parse_statement()?),
+                    NullTreatments::default(),
+                ))
             }
         }
     }
@@ -199,17 +638,127 @@ impl<'a> DFParser<'a> {
         let table_name = self.parser.parse_object_name()?;
 
         let des = DescribeTable {
-            table_name: table_name.to_string(),
+            table_name: normalize_sql_object_name(&table_name),
         };
         Ok(Statement::DescribeTable(des))
     }
 
+    /// Parse a SQL REFRESH MATERIALIZED VIEW statement
+    pub fn parse_refresh(&mut self) -> Result<Statement, ParserError> {
+        self.parser
+            .expect_keywords(&[Keyword::MATERIALIZED, Keyword::VIEW])?;
+        let name = self.parser.parse_object_name()?;
+
+        Ok(Statement::RefreshMaterializedView(
+            RefreshMaterializedView {
+                name: normalize_sql_object_name(&name),
+            },
+        ))
+    }
+
+    /// Parse a SQL ANALYZE TABLE statement
+    pub fn parse_analyze(&mut self) -> Result<Statement, ParserError> {
+        self.parser.expect_keyword(Keyword::TABLE)?;
+        let table_name = self.parser.parse_object_name()?;
+
+        Ok(Statement::AnalyzeTable(AnalyzeTable {
+            table_name: normalize_sql_object_name(&table_name),
+        }))
+    }
+
+    /// Parse a SQL COPY ... FROM STDIN statement, with the options
+    /// `FORMAT CSV|JSON`, `HEADER [TRUE|FALSE]` and `DELIMITER 'c'`
+    /// Parses `SET TIME ZONE <value>`, the Postgres alias of
+    /// `SET TIMEZONE TO <value>`
+    pub fn parse_set_time_zone(&mut self) -> Result<Statement, ParserError> {
+        let value = self.parser.parse_expr()?;
+        Ok(Statement::Statement(
+            Box::new(SQLStatement::SetVariable {
+                local: false,
+                hivevar: false,
+                variable: ObjectName(vec![Ident::new("timezone")]),
+                value: vec![value],
+            }),
+            NullTreatments::default(),
+        ))
+    }
+
+    pub fn parse_copy(&mut self) -> Result<Statement, ParserError> {
+        let table_name = self.parser.parse_object_name()?;
+        let columns = if self.parser.consume_token(&Token::LParen) {
+            let columns = self
+                .parser
+                .parse_comma_separated(|parser| parser.parse_identifier())?;
+            self.parser.expect_token(&Token::RParen)?;
+            columns.iter().map(normalize_ident).collect()
+        } else {
+            vec![]
+        };
+        self.parser.expect_keyword(Keyword::FROM)?;
+        if !self.consume_token(&Token::make_keyword("STDIN")) {
+            return self.expected("STDIN", self.parser.peek_token());
+        }
+
+        let mut copy = CopyFrom {
+            table_name: normalize_sql_object_name(&table_name),
+            columns,
+            file_type: "CSV".to_string(),
+            has_header: false,
+            delimiter: ',',
+        };
+        self.parser.parse_keyword(Keyword::WITH);
+        if !self.parser.consume_token(&Token::LParen) {
+            return Ok(Statement::CopyFrom(copy));
+        }
+        loop {
+            if self.consume_token(&Token::make_keyword("FORMAT")) {
+                copy.file_type = match self.parse_file_format()?.as_str() {
+                    "JSON" => "NDJSON".to_string(),
+                    file_type @ ("CSV" | "NDJSON") => file_type.to_string(),
+                    file_type => {
+                        return parser_err!(format!(
+                            "Unsupported COPY format {}, expected CSV or JSON",
+                            file_type
+                        ))
+                    }
+                };
+            } else if self.consume_token(&Token::make_keyword("HEADER")) {
+                copy.has_header = if self.parser.parse_keyword(Keyword::FALSE) {
+                    false
+                } else {
+                    self.parser.parse_keyword(Keyword::TRUE);
+                    true
+                };
+            } else if self.parse_has_delimiter() {
+                copy.delimiter = self.parse_delimiter()?;
+            } else {
+                return self.expected(
+                    "one of FORMAT, HEADER or DELIMITER",
+                    self.parser.peek_token(),
+                );
+            }
+            let comma = self.parser.consume_token(&Token::Comma);
+            if self.parser.consume_token(&Token::RParen) {
+                break;
+            } else if !comma {
+                return self
+                    .expected("',' or ')' after COPY option", self.parser.peek_token());
+            }
+        }
+        Ok(Statement::CopyFrom(copy))
+    }
+
     /// Parse a SQL CREATE statement
     pub fn parse_create(&mut self) -> Result<Statement, ParserError> {
         if self.parser.parse_keyword(Keyword::EXTERNAL) {
             self.parse_create_external_table()
+        } else if self.parser.parse_keyword(Keyword::SCHEMA) {
+            self.parse_create_schema()
         } else {
-            Ok(Statement::Statement(Box::from(self.parser.parse_create()?)))
+            Ok(Statement::Statement(
+                Box::from(self.parser.parse_create()?),
+                NullTreatments::default(),
+            ))
         }
     }
 
@@ -315,13 +864,32 @@ impl<'a> DFParser<'a> {
         })
     }
 
+    /// Parse a SQL CREATE SCHEMA statement
+    fn parse_create_schema(&mut self) -> Result<Statement, ParserError> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = self.parser.parse_object_name()?;
+        let location = if self.parser.parse_keyword(Keyword::LOCATION) {
+            Some(self.parser.parse_literal_string()?)
+        } else {
+            None
+        };
+
+        Ok(Statement::CreateSchema(CreateSchema {
+            name: normalize_sql_object_name(&name),
+            if_not_exists,
+            location,
+        }))
+    }
+
     fn parse_create_external_table(&mut self) -> Result<Statement, ParserError> {
         self.parser.expect_keyword(Keyword::TABLE)?;
         let if_not_exists =
             self.parser
                 .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
         let table_name = self.parser.parse_object_name()?;
-        let (columns, _) = self.parse_columns()?;
+        let (columns, constraints) = self.parse_columns()?;
         self.parser
             .expect_keywords(&[Keyword::STORED, Keyword::AS])?;
 
@@ -348,19 +916,38 @@ impl<'a> DFParser<'a> {
             vec![]
         };
 
-        self.parser.expect_keyword(Keyword::LOCATION)?;
-        let location = self.parser.parse_literal_string()?;
+        let (bucket_cols, num_buckets) = if self.parse_has_clustered_by() {
+            self.parse_buckets()?
+        } else {
+            (vec![], 0)
+        };
+
+        let location = if self.parser.parse_keyword(Keyword::LOCATION) {
+            self.parser.parse_literal_string()?
+        } else {
+            String::new()
+        };
+
+        let options = if self.parse_has_options() {
+            self.parse_options()?
+        } else {
+            HashMap::new()
+        };
 
         let create = CreateExternalTable {
-            name: table_name.to_string(),
+            name: normalize_sql_object_name(&table_name),
             columns,
+            constraints,
             file_type,
             has_header,
             delimiter,
             location,
             table_partition_cols,
+            bucket_cols,
+            num_buckets,
             if_not_exists,
             file_compression_type,
+            options,
         };
         Ok(Statement::CreateExternalTable(create))
     }
@@ -420,6 +1007,54 @@ impl<'a> DFParser<'a> {
         self.consume_token(&Token::make_keyword("PARTITIONED"))
             & self.consume_token(&Token::make_keyword("BY"))
     }
+
+    fn parse_has_clustered_by(&mut self) -> bool {
+        self.consume_token(&Token::make_keyword("CLUSTERED"))
+            & self.consume_token(&Token::make_keyword("BY"))
+    }
+
+    /// Parses `(c1, c2, ...) INTO n BUCKETS`
+    fn parse_buckets(&mut self) -> Result<(Vec<String>, usize), ParserError> {
+        let bucket_cols = self.parse_partitions()?;
+        self.parser.expect_keyword(Keyword::INTO)?;
+        let num_buckets = self.parser.parse_literal_uint()?;
+        if !self.consume_token(&Token::make_keyword("BUCKETS")) {
+            return self.expected("BUCKETS", self.parser.peek_token());
+        }
+        if bucket_cols.is_empty() || num_buckets == 0 {
+            return Err(ParserError::ParserError(
+                "CLUSTERED BY requires at least one column and one bucket".to_string(),
+            ));
+        }
+        Ok((bucket_cols, num_buckets as usize))
+    }
+
+    fn parse_has_options(&mut self) -> bool {
+        self.consume_token(&Token::make_keyword("OPTIONS"))
+    }
+
+    /// Parses `('key' 'value', ...)` pairs
+    fn parse_options(&mut self) -> Result<HashMap<String, String>, ParserError> {
+        let mut options = HashMap::new();
+        self.parser.expect_token(&Token::LParen)?;
+
+        loop {
+            let key = self.parser.parse_literal_string()?;
+            let value = self.parser.parse_literal_string()?;
+            options.insert(key, value);
+            let comma = self.parser.consume_token(&Token::Comma);
+            if self.parser.consume_token(&Token::RParen) {
+                // allow a trailing comma, even though it's not in standard
+                break;
+            } else if !comma {
+                return self.expected(
+                    "',' or ')' after option definition",
+                    self.parser.peek_token(),
+                );
+            }
+        }
+        Ok(options)
+    }
 }
 
 #[cfg(test)]
@@ -479,13 +1114,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![make_column_def("c1", DataType::Int(display))],
+            constraints: vec![],
             file_type: "CSV".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.csv".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -495,13 +1134,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![make_column_def("c1", DataType::Int(display))],
+            constraints: vec![],
             file_type: "CSV".to_string(),
             has_header: false,
             delimiter: '|',
             location: "foo.csv".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -511,13 +1154,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![make_column_def("c1", DataType::Int(display))],
+            constraints: vec![],
             file_type: "CSV".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.csv".into(),
             table_partition_cols: vec!["p1".to_string(), "p2".to_string()],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -530,13 +1177,17 @@ mod tests {
             let expected = Statement::CreateExternalTable(CreateExternalTable {
                 name: "t".into(),
                 columns: vec![make_column_def("c1", DataType::Int(display))],
+                constraints: vec![],
                 file_type: "CSV".to_string(),
                 has_header: true,
                 delimiter: ',',
                 location: "foo.csv".into(),
                 table_partition_cols: vec![],
+                bucket_cols: vec![],
+                num_buckets: 0,
                 if_not_exists: false,
                 file_compression_type: "".to_string(),
+                options: HashMap::new(),
             });
             expect_parse_ok(sql, expected)?;
         }
@@ -550,13 +1201,17 @@ mod tests {
             let expected = Statement::CreateExternalTable(CreateExternalTable {
                 name: "t".into(),
                 columns: vec![make_column_def("c1", DataType::Int(display))],
+                constraints: vec![],
                 file_type: "CSV".to_string(),
                 has_header: false,
                 delimiter: ',',
                 location: "foo.csv".into(),
                 table_partition_cols: vec![],
+                bucket_cols: vec![],
+                num_buckets: 0,
                 if_not_exists: false,
                 file_compression_type: file_compression_type.to_owned(),
+                options: HashMap::new(),
             });
             expect_parse_ok(sql, expected)?;
         }
@@ -566,13 +1221,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![],
+            constraints: vec![],
             file_type: "PARQUET".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.parquet".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -581,13 +1240,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![],
+            constraints: vec![],
             file_type: "PARQUET".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.parquet".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -596,13 +1259,17 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![],
+            constraints: vec![],
             file_type: "AVRO".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.avro".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: false,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
@@ -612,21 +1279,358 @@ mod tests {
         let expected = Statement::CreateExternalTable(CreateExternalTable {
             name: "t".into(),
             columns: vec![],
+            constraints: vec![],
             file_type: "PARQUET".to_string(),
             has_header: false,
             delimiter: ',',
             location: "foo.parquet".into(),
             table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
             if_not_exists: true,
             file_compression_type: "".to_string(),
+            options: HashMap::new(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: object store options
+        let sql = "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION 's3://bucket/foo.parquet' \
+                   OPTIONS ('region' 'us-east-2', 'endpoint' 'http://localhost:9000')";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![],
+            constraints: vec![],
+            file_type: "PARQUET".to_string(),
+            has_header: false,
+            delimiter: ',',
+            location: "s3://bucket/foo.parquet".into(),
+            table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
+            if_not_exists: false,
+            file_compression_type: "".to_string(),
+            options: HashMap::from([
+                ("region".to_string(), "us-east-2".to_string()),
+                ("endpoint".to_string(), "http://localhost:9000".to_string()),
+            ]),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: table constraints
+        let sql =
+            "CREATE EXTERNAL TABLE t(c1 int, c2 int, PRIMARY KEY (c1), UNIQUE (c2)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![
+                make_column_def("c1", DataType::Int(None)),
+                make_column_def("c2", DataType::Int(None)),
+            ],
+            constraints: vec![
+                TableConstraint::Unique {
+                    name: None,
+                    columns: vec![Ident::new("c1")],
+                    is_primary: true,
+                },
+                TableConstraint::Unique {
+                    name: None,
+                    columns: vec![Ident::new("c2")],
+                    is_primary: false,
+                },
+            ],
+            file_type: "CSV".to_string(),
+            has_header: false,
+            delimiter: ',',
+            location: "foo.csv".into(),
+            table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
+            if_not_exists: false,
+            file_compression_type: "".to_string(),
+            options: HashMap::new(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: clustered by
+        let sql = "CREATE EXTERNAL TABLE t(c1 int, c2 int) STORED AS CSV PARTITIONED BY (p1) CLUSTERED BY (c1, c2) INTO 8 BUCKETS LOCATION 'foo.csv'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![
+                make_column_def("c1", DataType::Int(None)),
+                make_column_def("c2", DataType::Int(None)),
+            ],
+            constraints: vec![],
+            file_type: "CSV".to_string(),
+            has_header: false,
+            delimiter: ',',
+            location: "foo.csv".into(),
+            table_partition_cols: vec!["p1".to_string()],
+            bucket_cols: vec!["c1".to_string(), "c2".to_string()],
+            num_buckets: 8,
+            if_not_exists: false,
+            file_compression_type: "".to_string(),
+            options: HashMap::new(),
         });
         expect_parse_ok(sql, expected)?;
 
+        // Error cases: clustered by without buckets
+        let sql =
+            "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV CLUSTERED BY (c1) INTO 0 BUCKETS LOCATION 'foo.csv'";
+        expect_parse_error(
+            sql,
+            "sql parser error: CLUSTERED BY requires at least one column and one bucket",
+        );
+
         // Error cases: partition column does not support type
         let sql =
             "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV PARTITIONED BY (p1 int) LOCATION 'foo.csv'";
         expect_parse_error(sql, "sql parser error: Expected ',' or ')' after partition definition, found: int");
 
+        // Error cases: option without value
+        let sql =
+            "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION 'foo.parquet' OPTIONS ('region')";
+        expect_parse_error(sql, "sql parser error: Expected literal string, found: )");
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_external_table_debug_redacts_secrets() -> Result<(), ParserError> {
+        let sql = "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION 's3://bucket/foo.parquet' \
+                   OPTIONS ('region' 'us-east-2', 'secret_access_key' 'very-secret')";
+        let statements = DFParser::parse_sql(sql)?;
+        let debug = format!("{:?}", statements[0]);
+        assert!(debug.contains("us-east-2"), "{}", debug);
+        assert!(debug.contains("<redacted>"), "{}", debug);
+        assert!(!debug.contains("very-secret"), "{}", debug);
+        Ok(())
+    }
+
+    #[test]
+    fn copy_from() -> Result<(), ParserError> {
+        let sql = "COPY t FROM STDIN";
+        let expected = Statement::CopyFrom(CopyFrom {
+            table_name: "t".into(),
+            columns: vec![],
+            file_type: "CSV".to_string(),
+            has_header: false,
+            delimiter: ',',
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql =
+            "COPY s.T (a, \"B\") FROM STDIN WITH (FORMAT csv, HEADER, DELIMITER '|')";
+        let expected = Statement::CopyFrom(CopyFrom {
+            table_name: "s.t".into(),
+            columns: vec!["a".to_string(), "B".to_string()],
+            file_type: "CSV".to_string(),
+            has_header: true,
+            delimiter: '|',
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "COPY t FROM STDIN (FORMAT JSON)";
+        let expected = Statement::CopyFrom(CopyFrom {
+            table_name: "t".into(),
+            columns: vec![],
+            file_type: "NDJSON".to_string(),
+            has_header: false,
+            delimiter: ',',
+        });
+        expect_parse_ok(sql, expected)?;
+
+        expect_parse_error(
+            "COPY t FROM STDIN (FORMAT PARQUET)",
+            "Unsupported COPY format PARQUET",
+        );
+        expect_parse_error("COPY t FROM 'foo.csv'", "Expected STDIN");
+        Ok(())
+    }
+
+    #[test]
+    fn refresh_materialized_view() -> Result<(), ParserError> {
+        let sql = "REFRESH MATERIALIZED VIEW mv";
+        let expected = Statement::RefreshMaterializedView(RefreshMaterializedView {
+            name: "mv".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "refresh materialized view s.mv";
+        let expected = Statement::RefreshMaterializedView(RefreshMaterializedView {
+            name: "s.mv".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // Error cases: only materialized views can be refreshed
+        let sql = "REFRESH VIEW mv";
+        expect_parse_error(sql, "sql parser error: Expected MATERIALIZED, found: VIEW");
+
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_table() -> Result<(), ParserError> {
+        let sql = "ANALYZE TABLE t";
+        let expected = Statement::AnalyzeTable(AnalyzeTable {
+            table_name: "t".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "analyze table s.t";
+        let expected = Statement::AnalyzeTable(AnalyzeTable {
+            table_name: "s.t".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // Error cases: only tables can be analyzed
+        let sql = "ANALYZE t";
+        expect_parse_error(sql, "sql parser error: Expected TABLE, found: t");
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_schema() -> Result<(), ParserError> {
+        let sql = "CREATE SCHEMA s";
+        let expected = Statement::CreateSchema(CreateSchema {
+            name: "s".into(),
+            if_not_exists: false,
+            location: None,
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "CREATE SCHEMA IF NOT EXISTS Cat.\"S\" LOCATION '/data/s'";
+        let expected = Statement::CreateSchema(CreateSchema {
+            name: "cat.S".into(),
+            if_not_exists: true,
+            location: Some("/data/s".into()),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // Error cases: missing location
+        let sql = "CREATE SCHEMA s LOCATION";
+        expect_parse_error(sql, "sql parser error: Expected literal string, found: EOF");
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_external_table_qualified() -> Result<(), ParserError> {
+        // names are normalized as in queries, and the location is optional
+        let sql = "CREATE EXTERNAL TABLE Cat.S.\"T\" STORED AS PARQUET";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "cat.s.T".into(),
+            columns: vec![],
+            constraints: vec![],
+            file_type: "PARQUET".to_string(),
+            has_header: false,
+            delimiter: ',',
+            location: "".into(),
+            table_partition_cols: vec![],
+            bucket_cols: vec![],
+            num_buckets: 0,
+            if_not_exists: false,
+            file_compression_type: "".to_string(),
+            options: HashMap::new(),
+        });
+        expect_parse_ok(sql, expected)
+    }
+
+    #[test]
+    fn nested_deeper_than_max_depth() -> Result<(), ParserError> {
+        let dialect = &GenericDialect {};
+        let nested =
+            |depth| format!("SELECT {}1{}", "(".repeat(depth), ")".repeat(depth));
+        DFParser::parse_sql_with_max_depth(&nested(10), dialect, 10)?;
+        let err = DFParser::parse_sql_with_max_depth(&nested(11), dialect, 10)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("nested deeper than the limit of 10 levels"),
+            "{}",
+            err
+        );
+
+        let sql = format!("SELECT {}true", "NOT ".repeat(11));
+        assert!(DFParser::parse_sql_with_max_depth(&sql, dialect, 10).is_err());
+        let sql = "SELECT CASE WHEN (a) THEN (b) END, CASE WHEN c THEN d END";
+        DFParser::parse_sql_with_max_depth(sql, dialect, 2)?;
+        assert!(DFParser::parse_sql_with_max_depth(sql, dialect, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_sql_expr() -> Result<(), ParserError> {
+        let dialect = &GenericDialect {};
+        let expr =
+            DFParser::parse_sql_expr_with_max_depth("a > 0 AND b <> 'x'", dialect, 10)?;
+        assert_eq!(expr.to_string(), "a > 0 AND b <> 'x'");
+
+        let err = DFParser::parse_sql_expr_with_max_depth("a > 0 b", dialect, 10)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Expected end of expression"), "{}", err);
+        assert!(DFParser::parse_sql_expr_with_max_depth("((a))", dialect, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn null_treatment() -> Result<(), ParserError> {
+        let dialect = &GenericDialect {};
+        let call =
+            |sql: &str| match DFParser::parse_sql_expr_with_max_depth(sql, dialect, 10) {
+                Ok(SQLExpr::Function(function)) => function,
+                expr => panic!("Expected a function call, got {:?}", expr),
+            };
+        let mut statements = DFParser::parse_sql(
+            "SELECT lag(a, 1) IGNORE NULLS OVER (ORDER BY b) + lead(a) respect nulls OVER () FROM t; \
+             SELECT lag(a, 1) OVER (ORDER BY b) FROM t",
+        )?;
+        let null_treatments = match statements.pop_front() {
+            Some(Statement::Statement(statement, null_treatments)) => {
+                assert_eq!(
+                    statement.to_string(),
+                    "SELECT lag(a, 1) OVER (ORDER BY b) + lead(a) OVER () FROM t"
+                );
+                null_treatments
+            }
+            statement => panic!("Expected a SQL statement, got {:?}", statement),
+        };
+        assert!(null_treatments.ignore_nulls(&call("lag(a, 1) OVER (ORDER BY b)")));
+        assert!(!null_treatments.ignore_nulls(&call("lead(a) OVER ()")));
+        // the null treatments are the ones of each statement
+        match statements.pop_front() {
+            Some(Statement::Statement(_, null_treatments)) => {
+                assert_eq!(null_treatments, NullTreatments::default())
+            }
+            statement => panic!("Expected a SQL statement, got {:?}", statement),
+        }
+
+        // the options that do not follow a window function call are left
+        // for the parser to report
+        assert!(DFParser::parse_sql("SELECT (a) IGNORE NULLS OVER () FROM t").is_err());
+        assert!(DFParser::parse_sql("SELECT lag(a) IGNORE NULLS FROM t").is_err());
+        assert!(DFParser::parse_sql("SELECT lag(a) RESPECT NULLS FROM t").is_err());
+
+        let err = DFParser::parse_sql(
+            "SELECT lag(a) IGNORE NULLS OVER (), lag(a) OVER () FROM t",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains("both ignores and respects the nulls"),
+            "{}",
+            err
+        );
+
+        let err = DFParser::parse_sql_expr_with_max_depth(
+            "lag(a) IGNORE NULLS OVER ()",
+            dialect,
+            10,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("only supported in statements"), "{}", err);
         Ok(())
     }
 }
@@ -17,22 +17,28 @@
 
 //! SQL Query Planner (produces logical plan from SQL AST)
 
-use crate::parser::{CreateExternalTable, DescribeTable, Statement as DFStatement};
+use crate::diagnostic::with_expr_location;
+use crate::parser::{
+    AnalyzeTable, CreateExternalTable, CreateSchema, DFParser, DescribeTable,
+    NullTreatments, RefreshMaterializedView, Statement as DFStatement, DEFAULT_MAX_DEPTH,
+};
 use arrow::datatypes::*;
 use datafusion_common::parsers::parse_interval;
 use datafusion_common::{context, ToDFSchema};
 use datafusion_expr::expr_rewriter::normalize_col;
 use datafusion_expr::expr_rewriter::normalize_col_with_schemas;
 use datafusion_expr::logical_plan::{
-    Analyze, CreateCatalog, CreateCatalogSchema,
+    Analyze, AnalyzeTable as PlanAnalyzeTable, CreateCatalog, CreateCatalogSchema,
     CreateExternalTable as PlanCreateExternalTable, CreateMemoryTable, CreateView,
     DropTable, DropView, Explain, JoinType, LogicalPlan, LogicalPlanBuilder,
-    Partitioning, PlanType, ToStringifiedPlan,
+    Partitioning, PlanType, Prepare,
+    RefreshMaterializedView as PlanRefreshMaterializedView, SetVariable,
+    ToStringifiedPlan,
 };
 use datafusion_expr::utils::{
-    can_hash, expand_qualified_wildcard, expand_wildcard, expr_as_column_expr,
-    expr_to_columns, find_aggregate_exprs, find_column_exprs, find_window_exprs,
-    COUNT_STAR_EXPANSION,
+    can_hash, determined_columns, expand_qualified_wildcard, expand_wildcard,
+    expr_as_column_expr, expr_to_columns, find_aggregate_exprs, find_column_exprs,
+    find_window_exprs, COUNT_STAR_EXPANSION,
 };
 use datafusion_expr::{
     and, col, lit, AggregateFunction, AggregateUDF, Expr, ExprSchemable, GetIndexedField,
@@ -41,15 +47,19 @@ use datafusion_expr::{
 use datafusion_expr::{
     window_function::WindowFunction, BuiltinScalarFunction, TableSource,
 };
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{convert::TryInto, vec};
 
 use crate::table_reference::TableReference;
-use crate::utils::{make_decimal_type, normalize_ident, resolve_columns};
+use crate::utils::{
+    make_decimal_type, normalize_ident, normalize_sql_object_name, resolve_columns,
+};
 use datafusion_common::{
-    field_not_found, Column, DFSchema, DFSchemaRef, DataFusionError, Result, ScalarValue,
+    field_not_found, Column, Constraint, Constraints, DFSchema, DFSchemaRef,
+    DataFusionError, Result, ScalarValue,
 };
 use datafusion_expr::expr::{Between, BinaryExpr, Case, Cast, GroupingSet, Like};
 use datafusion_expr::logical_plan::builder::project_with_alias;
@@ -63,8 +73,9 @@ use sqlparser::ast::{
     ShowCreateObject, ShowStatementFilter, TableAlias, TableFactor, TableWithJoins,
     TrimWhereField, UnaryOperator, Value, Values as SQLValues,
 };
-use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
-use sqlparser::ast::{ObjectType, OrderByExpr, Statement};
+use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption, TableConstraint};
+use sqlparser::ast::{ObjectType, OrderByExpr, SchemaName, Statement};
+use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::ParserError::ParserError;
 
 use sqlparser::ast::ExactNumberInfo;
@@ -93,6 +104,53 @@ pub trait ContextProvider {
 /// SQL query planner
 pub struct SqlToRel<'a, S: ContextProvider> {
     schema_provider: &'a S,
+    /// Errors of the expressions left out of the plan, when planning with
+    /// recovery
+    recovered_errors: Option<RefCell<Vec<DataFusionError>>>,
+    /// The types of the parameters `$1`, `$2`... when planning a prepared
+    /// statement
+    param_data_types: Vec<DataType>,
+    /// The maximum nesting of the planned SQL expressions
+    max_depth: usize,
+    /// The nesting of the SQL expression being planned
+    depth: Cell<usize>,
+    /// The SQL text of the planned statement, to locate the errors in it
+    source: Option<String>,
+    /// The null treatment of the window function calls of the planned
+    /// statement
+    null_treatments: RefCell<NullTreatments>,
+}
+
+fn parse_sql_binary_operator(op: BinaryOperator) -> Result<Operator> {
+    match op {
+        BinaryOperator::Gt => Ok(Operator::Gt),
+        BinaryOperator::GtEq => Ok(Operator::GtEq),
+        BinaryOperator::Lt => Ok(Operator::Lt),
+        BinaryOperator::LtEq => Ok(Operator::LtEq),
+        BinaryOperator::Eq => Ok(Operator::Eq),
+        BinaryOperator::NotEq => Ok(Operator::NotEq),
+        BinaryOperator::Plus => Ok(Operator::Plus),
+        BinaryOperator::Minus => Ok(Operator::Minus),
+        BinaryOperator::Multiply => Ok(Operator::Multiply),
+        BinaryOperator::Divide => Ok(Operator::Divide),
+        BinaryOperator::Modulo => Ok(Operator::Modulo),
+        BinaryOperator::And => Ok(Operator::And),
+        BinaryOperator::Or => Ok(Operator::Or),
+        BinaryOperator::PGRegexMatch => Ok(Operator::RegexMatch),
+        BinaryOperator::PGRegexIMatch => Ok(Operator::RegexIMatch),
+        BinaryOperator::PGRegexNotMatch => Ok(Operator::RegexNotMatch),
+        BinaryOperator::PGRegexNotIMatch => Ok(Operator::RegexNotIMatch),
+        BinaryOperator::BitwiseAnd => Ok(Operator::BitwiseAnd),
+        BinaryOperator::BitwiseOr => Ok(Operator::BitwiseOr),
+        BinaryOperator::BitwiseXor => Ok(Operator::BitwiseXor),
+        BinaryOperator::PGBitwiseShiftRight => Ok(Operator::BitwiseShiftRight),
+        BinaryOperator::PGBitwiseShiftLeft => Ok(Operator::BitwiseShiftLeft),
+        BinaryOperator::StringConcat => Ok(Operator::StringConcat),
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported SQL binary operator {:?}",
+            op
+        ))),
+    }
 }
 
 fn plan_key(key: SQLExpr) -> Result<ScalarValue> {
@@ -135,15 +193,122 @@ fn plan_indexed(expr: Expr, mut keys: Vec<SQLExpr>) -> Result<Expr> {
 impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     /// Create a new query planner
     pub fn new(schema_provider: &'a S) -> Self {
-        SqlToRel { schema_provider }
+        SqlToRel {
+            schema_provider,
+            recovered_errors: None,
+            param_data_types: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            depth: Cell::new(0),
+            source: None,
+            null_treatments: RefCell::default(),
+        }
+    }
+
+    /// Limits the nesting of the planned SQL expressions to `max_depth`
+    /// levels, failing with [`DataFusionError::Plan`] for the deeper
+    /// expressions. Chains of binary operators such as `a OR b OR c` count
+    /// as one level.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Points the errors about a column, and the type errors of the SELECT
+    /// list, at their location in `sql`, the text of the planned statement.
+    ///
+    /// See [`crate::diagnostic`] for how the locations are found.
+    pub fn with_source(mut self, sql: &str) -> Self {
+        self.source = Some(sql.to_string());
+        self
+    }
+
+    /// Generate a logical plan from an DataFusion SQL statement, reporting
+    /// all the errors found instead of stopping at the first one.
+    ///
+    /// Expressions of the SELECT list and of the WHERE, GROUP BY, HAVING and
+    /// ORDER BY clauses that reference unknown columns or call functions with
+    /// invalid arguments are left out of the plan and planning continues, so
+    /// that the errors of independent expressions are reported together.
+    /// Planning still stops at the first error that can not be recovered
+    /// from, which is reported last and may be caused by the previous ones.
+    pub fn statement_to_plan_with_recovery(
+        &self,
+        statement: DFStatement,
+    ) -> std::result::Result<LogicalPlan, Vec<DataFusionError>> {
+        let planner = SqlToRel {
+            schema_provider: self.schema_provider,
+            recovered_errors: Some(RefCell::new(vec![])),
+            param_data_types: vec![],
+            max_depth: self.max_depth,
+            depth: Cell::new(0),
+            source: self.source.clone(),
+            null_treatments: self.null_treatments.clone(),
+        };
+        let result = planner.statement_to_plan(statement);
+        let mut errors = planner
+            .recovered_errors
+            .map(RefCell::into_inner)
+            .unwrap_or_default();
+        match result {
+            Ok(plan) if errors.is_empty() => Ok(plan),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                errors.push(e);
+                Err(errors)
+            }
+        }
+    }
+
+    /// When planning with recovery, records the error of an expression that
+    /// can be left out of the plan and returns `None`
+    fn recover<T>(&self, result: Result<T>) -> Result<Option<T>> {
+        match (result, &self.recovered_errors) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(e), Some(errors)) if is_recoverable(&e) => {
+                errors.borrow_mut().push(e);
+                Ok(None)
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// When planning with recovery, checks that the types of `exprs` can be
+    /// computed, which validates the arguments of the functions they call.
+    ///
+    /// Without recovery the same errors are raised when the plan is built.
+    fn check_types(&self, exprs: &[Expr], schema: &DFSchema) -> Result<()> {
+        if self.recovered_errors.is_none() {
+            return Ok(());
+        }
+        for expr in exprs {
+            match expr.get_type(schema) {
+                Err(e) if is_recoverable(&e) => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
     }
 
     /// Generate a logical plan from an DataFusion SQL statement
     pub fn statement_to_plan(&self, statement: DFStatement) -> Result<LogicalPlan> {
         match statement {
             DFStatement::CreateExternalTable(s) => self.external_table_to_plan(s),
-            DFStatement::Statement(s) => self.sql_statement_to_plan(*s),
+            DFStatement::Statement(s, null_treatments) => {
+                let previous = self.null_treatments.replace(null_treatments);
+                let plan = self.sql_statement_to_plan(*s);
+                self.null_treatments.replace(previous);
+                plan
+            }
             DFStatement::DescribeTable(s) => self.describe_table_to_plan(s),
+            DFStatement::RefreshMaterializedView(s) => {
+                self.refresh_materialized_view_to_plan(s)
+            }
+            DFStatement::AnalyzeTable(s) => self.analyze_table_to_plan(s),
+            DFStatement::CreateSchema(s) => self.create_schema_to_plan(s),
+            DFStatement::CopyFrom(s) => Err(DataFusionError::Plan(format!(
+                "COPY {} FROM STDIN needs the data sent by the client, use SessionContext::copy_from",
+                s.table_name
+            ))),
         }
     }
 
@@ -159,8 +324,19 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 describe_alias: _,
                 ..
             } => self.explain_statement_to_plan(verbose, analyze, *statement),
+            Statement::Prepare {
+                name,
+                data_types,
+                statement,
+            } => self.prepare_statement_to_plan(&name, &data_types, *statement),
             Statement::Query(query) => self.query_to_plan(*query, &mut HashMap::new()),
             Statement::ShowVariable { variable } => self.show_variable_to_plan(&variable),
+            Statement::SetVariable {
+                local,
+                hivevar,
+                variable,
+                value,
+            } => self.set_variable_to_plan(local, hivevar, &variable, value),
             Statement::CreateTable {
                 query: Some(query),
                 name,
@@ -179,7 +355,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 let plan = self.query_to_plan(*query, &mut HashMap::new())?;
 
                 Ok(LogicalPlan::CreateMemoryTable(CreateMemoryTable {
-                    name: name.to_string(),
+                    name: normalize_sql_object_name(&name),
                     input: Arc::new(plan),
                     if_not_exists,
                     or_replace,
@@ -187,12 +363,28 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             }
             Statement::CreateView {
                 or_replace,
+                materialized,
                 name,
                 columns,
                 query,
                 with_options,
                 ..
-            } if with_options.is_empty() => {
+            } if with_options.is_empty() || materialized => {
+                let mut location = None;
+                for option in with_options {
+                    match (normalize_ident(&option.name).as_str(), option.value) {
+                        ("location", Value::SingleQuotedString(value)) => {
+                            location = Some(value)
+                        }
+                        (name, value) => {
+                            return Err(DataFusionError::NotImplemented(format!(
+                                "Unsupported option for materialized views: {} = {}",
+                                name, value
+                            )))
+                        }
+                    }
+                }
+
                 let mut plan = self.query_to_plan(*query, &mut HashMap::new())?;
 
                 if !columns.is_empty() {
@@ -214,10 +406,12 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 }
 
                 Ok(LogicalPlan::CreateView(CreateView {
-                    name: name.to_string(),
+                    name: normalize_sql_object_name(&name),
                     input: Arc::new(plan),
                     or_replace,
                     definition: sql,
+                    materialized,
+                    location,
                 }))
             }
             Statement::CreateTable { .. } => Err(DataFusionError::NotImplemented(
@@ -231,22 +425,35 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 )),
             },
             Statement::CreateSchema {
-                schema_name,
+                schema_name: SchemaName::Simple(name),
                 if_not_exists,
             } => Ok(LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
-                schema_name: schema_name.to_string(),
+                schema_name: normalize_sql_object_name(&name),
                 if_not_exists,
+                location: None,
                 schema: Arc::new(DFSchema::empty()),
             })),
+            Statement::CreateSchema { .. } => Err(DataFusionError::NotImplemented(
+                "Only `CREATE SCHEMA schema_name` statement is supported".to_string(),
+            )),
             Statement::CreateDatabase {
                 db_name,
                 if_not_exists,
-                ..
-            } => Ok(LogicalPlan::CreateCatalog(CreateCatalog {
-                catalog_name: db_name.to_string(),
-                if_not_exists,
-                schema: Arc::new(DFSchema::empty()),
-            })),
+                location,
+                managed_location,
+            } => {
+                if managed_location.is_some() {
+                    return Err(DataFusionError::NotImplemented(
+                        "MANAGEDLOCATION is not supported for databases".to_string(),
+                    ));
+                }
+                Ok(LogicalPlan::CreateCatalog(CreateCatalog {
+                    catalog_name: normalize_sql_object_name(&db_name),
+                    if_not_exists,
+                    location,
+                    schema: Arc::new(DFSchema::empty()),
+                }))
+            }
             Statement::Drop {
                 object_type,
                 if_exists,
@@ -260,16 +467,16 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 ObjectType::Table => Ok(LogicalPlan::DropTable(DropTable {
                     name: names
                         .get(0)
-                        .ok_or_else(|| ParserError("Missing table name.".to_string()))?
-                        .to_string(),
+                        .ok_or_else(|| ParserError("Missing table name.".to_string()))
+                        .map(normalize_sql_object_name)?,
                     if_exists,
                     schema: DFSchemaRef::new(DFSchema::empty()),
                 })),
                 ObjectType::View => Ok(LogicalPlan::DropView(DropView {
                     name: names
                         .get(0)
-                        .ok_or_else(|| ParserError("Missing table name.".to_string()))?
-                        .to_string(),
+                        .ok_or_else(|| ParserError("Missing table name.".to_string()))
+                        .map(normalize_sql_object_name)?,
                     if_exists,
                     schema: DFSchemaRef::new(DFSchema::empty()),
                 })),
@@ -456,8 +663,11 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         let _ = self.schema_provider.get_table_provider(table_ref)?;
 
         if self.has_table("information_schema", "tables") {
-            let sql = format!("SELECT column_name, data_type, is_nullable \
-                                FROM information_schema.columns WHERE table_name = '{table_name}';");
+            let where_clause = information_schema_filter(table_ref);
+            let sql = format!(
+                "SELECT column_name, data_type, is_nullable \
+                 FROM information_schema.columns WHERE {where_clause};"
+            );
             let mut rewrite = DFParser::parse_sql(&sql[..])?;
             self.statement_to_plan(rewrite.pop_front().unwrap())
         } else {
@@ -468,6 +678,37 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Generate a logical plan from a REFRESH MATERIALIZED VIEW statement
+    pub fn refresh_materialized_view_to_plan(
+        &self,
+        statement: RefreshMaterializedView,
+    ) -> Result<LogicalPlan> {
+        Ok(LogicalPlan::RefreshMaterializedView(
+            PlanRefreshMaterializedView {
+                name: statement.name,
+                schema: DFSchemaRef::new(DFSchema::empty()),
+            },
+        ))
+    }
+
+    /// Generate a logical plan from an ANALYZE TABLE statement
+    pub fn analyze_table_to_plan(&self, statement: AnalyzeTable) -> Result<LogicalPlan> {
+        Ok(LogicalPlan::AnalyzeTable(PlanAnalyzeTable {
+            name: statement.table_name,
+            schema: DFSchemaRef::new(DFSchema::empty()),
+        }))
+    }
+
+    /// Generate a logical plan from a CREATE SCHEMA statement
+    pub fn create_schema_to_plan(&self, statement: CreateSchema) -> Result<LogicalPlan> {
+        Ok(LogicalPlan::CreateCatalogSchema(CreateCatalogSchema {
+            schema_name: statement.name,
+            if_not_exists: statement.if_not_exists,
+            location: statement.location,
+            schema: Arc::new(DFSchema::empty()),
+        }))
+    }
+
     /// Generate a logical plan from a CREATE EXTERNAL TABLE statement
     pub fn external_table_to_plan(
         &self,
@@ -477,13 +718,17 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         let CreateExternalTable {
             name,
             columns,
+            constraints,
             file_type,
             has_header,
             delimiter,
             location,
             table_partition_cols,
+            bucket_cols,
+            num_buckets,
             if_not_exists,
             file_compression_type,
+            options,
         } = statement;
 
         // semantic checks
@@ -500,22 +745,92 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             ))?;
         }
 
+        let constraints = build_constraints(&columns, &constraints)?;
         let schema = self.build_schema(columns)?;
+        constraints.validate(&schema)?;
+        self.check_constraint_exprs(&constraints, &schema)?;
 
         Ok(LogicalPlan::CreateExternalTable(PlanCreateExternalTable {
             schema: schema.to_dfschema_ref()?,
+            constraints,
             name,
             location,
             file_type,
             has_header,
             delimiter,
             table_partition_cols,
+            bucket_cols,
+            num_buckets,
             if_not_exists,
             definition,
             file_compression_type,
+            options,
         }))
     }
 
+    /// Generate a plan for a `PREPARE` statement, whose parameters `$1`, `$2`...
+    /// have the types of `data_types`
+    fn prepare_statement_to_plan(
+        &self,
+        name: &Ident,
+        data_types: &[SQLDataType],
+        statement: Statement,
+    ) -> Result<LogicalPlan> {
+        let data_types = data_types
+            .iter()
+            .map(convert_data_type)
+            .collect::<Result<Vec<_>>>()?;
+        let planner = SqlToRel {
+            schema_provider: self.schema_provider,
+            recovered_errors: self
+                .recovered_errors
+                .as_ref()
+                .map(|_| RefCell::new(vec![])),
+            param_data_types: data_types.clone(),
+            max_depth: self.max_depth,
+            depth: Cell::new(self.depth.get()),
+            source: self.source.clone(),
+            null_treatments: self.null_treatments.clone(),
+        };
+        let result = planner.sql_statement_to_plan(statement);
+        if let (Some(errors), Some(recovered)) =
+            (&self.recovered_errors, planner.recovered_errors)
+        {
+            errors.borrow_mut().extend(recovered.into_inner());
+        }
+        Ok(LogicalPlan::Prepare(Prepare {
+            name: normalize_ident(name),
+            data_types,
+            input: Arc::new(result?),
+        }))
+    }
+
+    /// Generate an expression for the placeholder `param`, such as `$1`, of
+    /// the statement being prepared
+    fn placeholder_to_expr(&self, param: &str) -> Result<Expr> {
+        let index = param
+            .strip_prefix('$')
+            .and_then(|index| index.parse::<usize>().ok())
+            .filter(|index| *index > 0)
+            .ok_or_else(|| {
+                DataFusionError::NotImplemented(format!(
+                    "Unsupported placeholder {}, only $1, $2... are supported",
+                    param
+                ))
+            })?;
+        let data_type = self.param_data_types.get(index - 1).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "Placeholder {} has no type, declare the types of the parameters \
+                 with PREPARE",
+                param
+            ))
+        })?;
+        Ok(Expr::Placeholder {
+            id: index.to_string(),
+            data_type: data_type.clone(),
+        })
+    }
+
     /// Generate a plan for EXPLAIN ... that will print out a plan
     ///
     pub fn explain_statement_to_plan(
@@ -566,6 +881,32 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         Ok(Schema::new(fields))
     }
 
+    /// Checks that the expressions of the check constraints of a table with
+    /// `schema` are boolean expressions on its columns
+    fn check_constraint_exprs(
+        &self,
+        constraints: &Constraints,
+        schema: &Schema,
+    ) -> Result<()> {
+        let schema = schema.clone().to_dfschema()?;
+        for (_, sql) in constraints.checks() {
+            let sql_expr = DFParser::parse_sql_expr_with_max_depth(
+                sql,
+                &GenericDialect {},
+                self.max_depth,
+            )?;
+            let expr = self.sql_to_rex(sql_expr, &schema, &mut HashMap::new())?;
+            let data_type = expr.get_type(&schema)?;
+            if data_type != DataType::Boolean {
+                return Err(DataFusionError::Plan(format!(
+                    "Check constraint {} must be a boolean expression, got {}",
+                    sql, data_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn plan_from_tables(
         &self,
         from: Vec<TableWithJoins>,
@@ -738,7 +1079,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             } => {
                 // normalize name and alias
                 let table_name = normalize_sql_object_name(sql_object_name);
-                let table_ref: TableReference = table_name.as_str().into();
+                let table_ref: TableReference = match sql_object_name.0.as_slice() {
+                    // a single quoted name, such as a file path, is never split on '.'
+                    [ident] if ident.quote_style == Some('\'') => {
+                        TableReference::Bare { table: &table_name }
+                    }
+                    _ => table_name.as_str().into(),
+                };
                 let table_alias = alias.as_ref().map(|a| normalize_ident(&a.name));
                 let cte = ctes.get(&table_name);
                 (
@@ -861,7 +1208,18 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     join_schema.merge(outer);
                 }
 
-                let filter_expr = self.sql_to_rex(predicate_expr, &join_schema, ctes)?;
+                let filter_expr = self
+                    .sql_to_rex(predicate_expr, &join_schema, ctes)
+                    .and_then(|expr| {
+                        self.check_types(&[expr.clone()], &join_schema)?;
+                        Ok(expr)
+                    });
+                let filter_expr = match self.recover(filter_expr)? {
+                    Some(filter_expr) => filter_expr,
+                    None => {
+                        return self.plan_selection(None, plans, outer_query_schema, ctes)
+                    }
+                };
 
                 // look for expressions of the form `<column> = <column>`
                 let mut possible_join_keys = vec![];
@@ -1081,7 +1439,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 let having_expr = resolve_aliases_to_exprs(&having_expr, &alias_map)?;
                 normalize_col(having_expr, &projected_plan)
             })
-            .transpose()?;
+            .map(|having_expr| self.recover(having_expr))
+            .transpose()?
+            .flatten();
 
         // The outer expressions we will search through for
         // aggregates. Aggregates may be sourced from the SELECT...
@@ -1117,6 +1477,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 )?;
                 Ok(group_by_expr)
             })
+            .filter_map(|group_by_expr| self.recover(group_by_expr).transpose())
             .collect::<Result<Vec<Expr>>>()?;
 
         // process group by, aggregation or having
@@ -1209,11 +1570,20 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         outer_query_schema: Option<&DFSchema>,
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<Vec<Expr>> {
+        let mut input_schema = plan.schema().as_ref().clone();
+        if let Some(outer_query_schema) = outer_query_schema {
+            input_schema.merge(outer_query_schema);
+        }
         projection
             .into_iter()
             .map(|expr| {
                 self.sql_select_to_rex(expr, plan, empty_from, outer_query_schema, ctes)
+                    .and_then(|exprs| {
+                        self.check_types(&exprs, &input_schema)?;
+                        Ok(exprs)
+                    })
             })
+            .filter_map(|result| self.recover(result).transpose())
             .flat_map(|result| match result {
                 Ok(vec) => vec.into_iter().map(Ok).collect(),
                 Err(err) => vec![Err(err)],
@@ -1259,6 +1629,16 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         group_by_exprs: Vec<Expr>,
         aggr_exprs: Vec<Expr>,
     ) -> Result<(LogicalPlan, Vec<Expr>, Option<Expr>)> {
+        // columns determined by the grouping columns, such as the columns of a
+        // table grouped by its primary key, may be used without aggregation
+        let group_by_exprs = self.add_dependent_group_columns(
+            &input,
+            group_by_exprs,
+            select_exprs,
+            having_expr_opt,
+            &aggr_exprs,
+        )?;
+
         // create the aggregate plan
         let plan = LogicalPlanBuilder::from(input.clone())
             .aggregate(group_by_exprs.clone(), aggr_exprs.clone())?
@@ -1337,6 +1717,56 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         Ok((plan, select_exprs_post_aggr, having_expr_post_aggr))
     }
 
+    /// Adds to `group_by_exprs` the columns of `input` used outside of
+    /// aggregates by `select_exprs` or `having_expr_opt` which are
+    /// functionally determined by the grouping columns. They do not change the
+    /// groups, but make the columns available to the projection.
+    fn add_dependent_group_columns(
+        &self,
+        input: &LogicalPlan,
+        mut group_by_exprs: Vec<Expr>,
+        select_exprs: &[Expr],
+        having_expr_opt: &Option<Expr>,
+        aggr_exprs: &[Expr],
+    ) -> Result<Vec<Expr>> {
+        if group_by_exprs.is_empty()
+            || group_by_exprs
+                .iter()
+                .any(|expr| matches!(expr, Expr::GroupingSet(_)))
+        {
+            return Ok(group_by_exprs);
+        }
+
+        let schema = input.schema();
+        let group_columns = group_by_exprs
+            .iter()
+            .filter_map(|expr| match expr {
+                Expr::Column(c) => schema.index_of_column(c).ok(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let determined = determined_columns(input, &group_columns);
+
+        let mut used_columns = HashSet::new();
+        for expr in select_exprs.iter().chain(having_expr_opt) {
+            expr_to_columns(&rebase_expr(expr, aggr_exprs, input)?, &mut used_columns)?;
+        }
+        let used_columns = used_columns
+            .iter()
+            .filter_map(|c| schema.index_of_column(c).ok())
+            .collect::<HashSet<_>>();
+
+        for i in 0..schema.fields().len() {
+            if determined.contains(&i)
+                && used_columns.contains(&i)
+                && !group_columns.contains(&i)
+            {
+                group_by_exprs.push(Expr::Column(schema.field(i).qualified_column()));
+            }
+        }
+        Ok(group_by_exprs)
+    }
+
     /// Wrap a plan in a limit
     fn limit(
         &self,
@@ -1403,7 +1833,11 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         let order_by_rex = order_by
             .into_iter()
             .map(|e| self.order_by_to_sort_expr(e, plan.schema()))
+            .filter_map(|e| self.recover(e).transpose())
             .collect::<Result<Vec<_>>>()?;
+        if order_by_rex.is_empty() {
+            return Ok(plan);
+        }
 
         LogicalPlanBuilder::from(plan).sort(order_by_rex)?.build()
     }
@@ -1504,12 +1938,12 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
 
         match sql {
             SelectItem::UnnamedExpr(expr) => {
-                let expr = self.sql_to_rex(expr, &input_schema, ctes)?;
+                let expr = self.sql_to_located_rex(expr, &input_schema, ctes, true)?;
                 Ok(vec![normalize_col(expr, plan)?])
             }
             SelectItem::ExprWithAlias { expr, alias } => {
                 let expr = Alias(
-                    Box::new(self.sql_to_rex(expr, &input_schema, ctes)?),
+                    Box::new(self.sql_to_located_rex(expr, &input_schema, ctes, true)?),
                     normalize_ident(&alias),
                 );
                 Ok(vec![normalize_col(expr, plan)?])
@@ -1537,6 +1971,51 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         sql: SQLExpr,
         schema: &DFSchema,
         ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<Expr> {
+        self.sql_to_located_rex(sql, schema, ctes, false)
+    }
+
+    /// Generate a relational expression from a SQL expression, pointing its
+    /// errors about a column at the column in the expression when the SQL
+    /// text is known.
+    ///
+    /// With `check_type`, the type of the expression is also computed, so
+    /// that its type errors are raised, and located, here rather than when
+    /// the plan using it is built.
+    fn sql_to_located_rex(
+        &self,
+        sql: SQLExpr,
+        schema: &DFSchema,
+        ctes: &mut HashMap<String, LogicalPlan>,
+        check_type: bool,
+    ) -> Result<Expr> {
+        let (source, text) = match &self.source {
+            Some(source) => (source, sql.to_string()),
+            None => return self.sql_to_rex_internal(sql, schema, ctes),
+        };
+        let expr = self.sql_to_rex_internal(sql, schema, ctes).map_err(|e| {
+            if matches!(e.find_root(), DataFusionError::SchemaError(_)) {
+                with_expr_location(e, source, &text)
+            } else {
+                e
+            }
+        })?;
+        if check_type {
+            match expr.get_type(schema) {
+                Err(e) if matches!(e.find_root(), DataFusionError::Plan(_)) => {
+                    return Err(with_expr_location(e, source, &text));
+                }
+                _ => {}
+            }
+        }
+        Ok(expr)
+    }
+
+    fn sql_to_rex_internal(
+        &self,
+        sql: SQLExpr,
+        schema: &DFSchema,
+        ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<Expr> {
         let mut expr = self.sql_expr_to_logical_expr(sql, schema, ctes)?;
         expr = self.rewrite_partial_qualifier(expr, schema);
@@ -1598,6 +2077,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Plans a binary operation, iterating over the left operands that are
+    /// themselves binary operations, such that long chains of operators like
+    /// `a OR b OR c ...` do not recurse as deep as their length
     fn parse_sql_binary_op(
         &self,
         left: SQLExpr,
@@ -1606,41 +2088,30 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         schema: &DFSchema,
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<Expr> {
-        let operator = match op {
-            BinaryOperator::Gt => Ok(Operator::Gt),
-            BinaryOperator::GtEq => Ok(Operator::GtEq),
-            BinaryOperator::Lt => Ok(Operator::Lt),
-            BinaryOperator::LtEq => Ok(Operator::LtEq),
-            BinaryOperator::Eq => Ok(Operator::Eq),
-            BinaryOperator::NotEq => Ok(Operator::NotEq),
-            BinaryOperator::Plus => Ok(Operator::Plus),
-            BinaryOperator::Minus => Ok(Operator::Minus),
-            BinaryOperator::Multiply => Ok(Operator::Multiply),
-            BinaryOperator::Divide => Ok(Operator::Divide),
-            BinaryOperator::Modulo => Ok(Operator::Modulo),
-            BinaryOperator::And => Ok(Operator::And),
-            BinaryOperator::Or => Ok(Operator::Or),
-            BinaryOperator::PGRegexMatch => Ok(Operator::RegexMatch),
-            BinaryOperator::PGRegexIMatch => Ok(Operator::RegexIMatch),
-            BinaryOperator::PGRegexNotMatch => Ok(Operator::RegexNotMatch),
-            BinaryOperator::PGRegexNotIMatch => Ok(Operator::RegexNotIMatch),
-            BinaryOperator::BitwiseAnd => Ok(Operator::BitwiseAnd),
-            BinaryOperator::BitwiseOr => Ok(Operator::BitwiseOr),
-            BinaryOperator::BitwiseXor => Ok(Operator::BitwiseXor),
-            BinaryOperator::PGBitwiseShiftRight => Ok(Operator::BitwiseShiftRight),
-            BinaryOperator::PGBitwiseShiftLeft => Ok(Operator::BitwiseShiftLeft),
-            BinaryOperator::StringConcat => Ok(Operator::StringConcat),
-            _ => Err(DataFusionError::NotImplemented(format!(
-                "Unsupported SQL binary operator {:?}",
-                op
-            ))),
-        }?;
+        let mut operations = vec![(parse_sql_binary_operator(op)?, right)];
+        let mut left = left;
+        loop {
+            match left {
+                SQLExpr::BinaryOp { left: l, op, right } => {
+                    operations.push((parse_sql_binary_operator(op)?, *right));
+                    left = *l;
+                }
+                other => {
+                    left = other;
+                    break;
+                }
+            }
+        }
 
-        Ok(Expr::BinaryExpr(BinaryExpr::new(
-            Box::new(self.sql_expr_to_logical_expr(left, schema, ctes)?),
-            operator,
-            Box::new(self.sql_expr_to_logical_expr(right, schema, ctes)?),
-        )))
+        let mut expr = self.sql_expr_to_logical_expr(left, schema, ctes)?;
+        for (operator, right) in operations.into_iter().rev() {
+            expr = Expr::BinaryExpr(BinaryExpr::new(
+                Box::new(expr),
+                operator,
+                Box::new(self.sql_expr_to_logical_expr(right, schema, ctes)?),
+            ));
+        }
+        Ok(expr)
     }
 
     fn parse_sql_unary_op(
@@ -1697,6 +2168,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                             Ok(Expr::Literal(ScalarValue::Null))
                         }
                         SQLExpr::Value(Value::Boolean(n)) => Ok(lit(n)),
+                        SQLExpr::Value(Value::Placeholder(param)) => {
+                            self.placeholder_to_expr(&param)
+                        }
                         SQLExpr::UnaryOp { op, expr } => self.parse_sql_unary_op(
                             op,
                             *expr,
@@ -1741,12 +2215,32 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         sql: SQLExpr,
         schema: &DFSchema,
         ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<Expr> {
+        let depth = self.depth.get() + 1;
+        if depth > self.max_depth {
+            return Err(DataFusionError::Plan(format!(
+                "SQL expression nested deeper than the limit of {} levels",
+                self.max_depth
+            )));
+        }
+        self.depth.set(depth);
+        let result = self.sql_expr_to_logical_expr_internal(sql, schema, ctes);
+        self.depth.set(depth - 1);
+        result
+    }
+
+    fn sql_expr_to_logical_expr_internal(
+        &self,
+        sql: SQLExpr,
+        schema: &DFSchema,
+        ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<Expr> {
         match sql {
             SQLExpr::Value(Value::Number(n, _)) => parse_sql_number(&n),
             SQLExpr::Value(Value::SingleQuotedString(ref s) | Value::DoubleQuotedString(ref s)) => Ok(lit(s.clone())),
             SQLExpr::Value(Value::Boolean(n)) => Ok(lit(n)),
             SQLExpr::Value(Value::Null) => Ok(Expr::Literal(ScalarValue::Null)),
+            SQLExpr::Value(Value::Placeholder(param)) => self.placeholder_to_expr(&param),
             SQLExpr::Extract { field, expr } => Ok(Expr::ScalarFunction {
                 fun: BuiltinScalarFunction::DatePart,
                 args: vec![
@@ -1853,6 +2347,15 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                                 }
                             }
                         }
+                        // a column of a table qualified by its schema and catalog,
+                        // such as `catalog.schema.table.column`
+                        (Some(name), Some(table)) if var_names.len() <= 2 => {
+                            var_names.push(table);
+                            Ok(Expr::Column(Column {
+                                relation: Some(var_names.join(".")),
+                                name,
+                            }))
+                        }
                         _ => Err(DataFusionError::NotImplemented(format!(
                             "Unsupported compound identifier '{:?}'",
                             var_names,
@@ -2130,6 +2633,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             }
 
             SQLExpr::Function(mut function) => {
+                let ignore_nulls = self.null_treatments.borrow().ignore_nulls(&function);
                 let name = if function.name.0.len() > 1 {
                     // DF doesn't handle compound identifiers
                     // (e.g. "foo.bar") for function names yet
@@ -2199,6 +2703,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                                 partition_by,
                                 order_by,
                                 window_frame,
+                                ignore_nulls,
                             });
                         }
                         WindowFunction::BuiltInWindowFunction(
@@ -2212,6 +2717,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                                 partition_by,
                                 order_by,
                                 window_frame,
+                                ignore_nulls,
                             });
                         }
                     }
@@ -2435,13 +2941,10 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             String::from(
                 "SELECT name, setting FROM information_schema.df_settings ORDER BY name",
             )
-        } else if variable_lower == "timezone" || variable_lower == "time.zone" {
-            // we could introduce alias in OptionDefinition if this string matching thing grows
-            String::from("SELECT name, setting FROM information_schema.df_settings WHERE name = 'datafusion.execution.time_zone'")
         } else {
             format!(
                 "SELECT name, setting FROM information_schema.df_settings WHERE name = '{}'",
-                variable
+                config_option_name(&variable)
             )
         };
 
@@ -2451,6 +2954,51 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         self.statement_to_plan(rewrite.pop_front().unwrap())
     }
 
+    fn set_variable_to_plan(
+        &self,
+        local: bool,
+        hivevar: bool,
+        variable: &ObjectName,
+        value: Vec<SQLExpr>,
+    ) -> Result<LogicalPlan> {
+        if local {
+            return Err(DataFusionError::NotImplemented(
+                "LOCAL is not supported".to_string(),
+            ));
+        }
+        if hivevar {
+            return Err(DataFusionError::NotImplemented(
+                "HIVEVAR is not supported".to_string(),
+            ));
+        }
+
+        let variable = config_option_name(&variable.to_string());
+        // lists of values, such as the schemas of the search path, are
+        // separated with commas
+        let value = value
+            .into_iter()
+            .map(|expr| match expr {
+                SQLExpr::Identifier(ident) => Ok(normalize_ident(&ident)),
+                SQLExpr::Value(
+                    Value::SingleQuotedString(s) | Value::DoubleQuotedString(s),
+                ) => Ok(s),
+                SQLExpr::Value(Value::Number(n, _)) => Ok(n),
+                SQLExpr::Value(Value::Boolean(b)) => Ok(b.to_string()),
+                expr => Err(DataFusionError::Plan(format!(
+                    "Unsupported value for SET {}: {}",
+                    variable, expr
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join(", ");
+
+        Ok(LogicalPlan::SetVariable(SetVariable {
+            variable,
+            value,
+            schema: DFSchemaRef::new(DFSchema::empty()),
+        }))
+    }
+
     fn show_columns_to_plan(
         &self,
         extended: bool,
@@ -2475,18 +3023,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
 
         let _ = self.schema_provider.get_table_provider(table_ref)?;
 
-        // Figure out the where clause
-        let columns = vec!["table_name", "table_schema", "table_catalog"].into_iter();
-        let where_clause = sql_table_name
-            .0
-            .iter()
-            .rev()
-            .zip(columns)
-            .map(|(ident, column_name)| {
-                format!(r#"{} = '{}'"#, column_name, normalize_ident(ident))
-            })
-            .collect::<Vec<_>>()
-            .join(" AND ");
+        let where_clause = information_schema_filter(table_ref);
 
         // treat both FULL and EXTENDED as the same
         let select_list = if full || extended {
@@ -2520,18 +3057,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
 
         let _ = self.schema_provider.get_table_provider(table_ref)?;
 
-        // Figure out the where clause
-        let columns = vec!["table_name", "table_schema", "table_catalog"].into_iter();
-        let where_clause = sql_table_name
-            .0
-            .iter()
-            .rev()
-            .zip(columns)
-            .map(|(ident, column_name)| {
-                format!(r#"{} = '{}'"#, column_name, normalize_ident(ident))
-            })
-            .collect::<Vec<_>>()
-            .join(" AND ");
+        let where_clause = information_schema_filter(table_ref);
 
         let query = format!(
             "SELECT table_catalog, table_schema, table_name, definition FROM information_schema.views WHERE {}",
@@ -2592,14 +3118,37 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     }
 }
 
-/// Normalize a SQL object name
-fn normalize_sql_object_name(sql_object_name: &ObjectName) -> String {
-    sql_object_name
-        .0
-        .iter()
-        .map(normalize_ident)
-        .collect::<Vec<String>>()
-        .join(".")
+/// The condition selecting the rows of the table of `table_ref` in the tables
+/// of `information_schema`
+fn information_schema_filter(table_ref: TableReference) -> String {
+    let (catalog, schema, table) = match table_ref {
+        TableReference::Bare { table } => (None, None, table),
+        TableReference::Partial { schema, table } => (None, Some(schema), table),
+        TableReference::Full {
+            catalog,
+            schema,
+            table,
+        } => (Some(catalog), Some(schema), table),
+    };
+    [
+        ("table_name", Some(table)),
+        ("table_schema", schema),
+        ("table_catalog", catalog),
+    ]
+    .into_iter()
+    .filter_map(|(column_name, value)| {
+        value.map(|value| format!("{} = '{}'", column_name, value))
+    })
+    .collect::<Vec<_>>()
+    .join(" AND ")
+}
+
+/// Whether planning can continue without the expression that raised `err`
+fn is_recoverable(err: &DataFusionError) -> bool {
+    matches!(
+        err.find_root(),
+        DataFusionError::SchemaError(_) | DataFusionError::Plan(_)
+    )
 }
 
 /// Remove join expressions from a filter expression
@@ -2841,6 +3390,114 @@ fn parse_sql_number(n: &str) -> Result<Expr> {
         })
 }
 
+/// Converts the constraints declared on the `columns` of a table, and on the
+/// table itself, to [`Constraints`] on the indices of the columns
+fn build_constraints(
+    columns: &[SQLColumnDef],
+    constraints: &[TableConstraint],
+) -> Result<Constraints> {
+    let names = columns
+        .iter()
+        .map(|column| normalize_ident(&column.name))
+        .collect::<Vec<_>>();
+    let indices = |idents: &[Ident]| {
+        idents
+            .iter()
+            .map(|ident| {
+                let name = normalize_ident(ident);
+                names.iter().position(|n| *n == name).ok_or_else(|| {
+                    DataFusionError::Plan(format!(
+                        "Constraint on unknown column {}",
+                        name
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+
+    let mut result = vec![];
+    for (index, column) in columns.iter().enumerate() {
+        for option in &column.options {
+            match &option.option {
+                ColumnOption::Unique { is_primary: true } => {
+                    result.push(Constraint::PrimaryKey(vec![index]))
+                }
+                ColumnOption::Unique { is_primary: false } => {
+                    result.push(Constraint::Unique(vec![index]))
+                }
+                ColumnOption::ForeignKey {
+                    foreign_table,
+                    referred_columns,
+                    ..
+                } => result.push(Constraint::ForeignKey {
+                    columns: vec![index],
+                    referenced_table: normalize_sql_object_name(foreign_table),
+                    referenced_columns: referred_columns
+                        .iter()
+                        .map(normalize_ident)
+                        .collect(),
+                }),
+                ColumnOption::Check(expr) => result.push(Constraint::Check {
+                    name: option.name.as_ref().map(normalize_ident),
+                    expr: expr.to_string(),
+                }),
+                _ => {}
+            }
+        }
+    }
+    for constraint in constraints {
+        match constraint {
+            TableConstraint::Unique {
+                columns,
+                is_primary,
+                ..
+            } => {
+                let columns = indices(columns)?;
+                result.push(if *is_primary {
+                    Constraint::PrimaryKey(columns)
+                } else {
+                    Constraint::Unique(columns)
+                })
+            }
+            TableConstraint::ForeignKey {
+                columns,
+                foreign_table,
+                referred_columns,
+                ..
+            } => result.push(Constraint::ForeignKey {
+                columns: indices(columns)?,
+                referenced_table: normalize_sql_object_name(foreign_table),
+                referenced_columns: referred_columns
+                    .iter()
+                    .map(normalize_ident)
+                    .collect(),
+            }),
+            TableConstraint::Check { name, expr } => result.push(Constraint::Check {
+                name: name.as_ref().map(normalize_ident),
+                expr: expr.to_string(),
+            }),
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported table constraint {}",
+                    other
+                )))
+            }
+        }
+    }
+    Ok(Constraints::new(result))
+}
+
+/// The name of the configuration option of a session variable, which is the
+/// variable itself unless it is named after a Postgres setting
+fn config_option_name(variable: &str) -> String {
+    // we could introduce alias in OptionDefinition if this string matching thing grows
+    match variable.to_lowercase().as_str() {
+        "timezone" | "time.zone" => "datafusion.execution.time_zone".to_string(),
+        "search_path" => "datafusion.catalog.search_path".to_string(),
+        _ => variable.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2931,6 +3588,33 @@ mod tests {
         assert_field_not_found(err, "doesnotexist");
     }
 
+    #[test]
+    fn plan_with_recovery() {
+        let planner = SqlToRel::new(&MockContextProvider {});
+        let plan = |sql: &str| {
+            let mut ast = DFParser::parse_sql(sql).unwrap();
+            planner.statement_to_plan_with_recovery(ast.pop_front().unwrap())
+        };
+
+        let sql = "SELECT doesnotexist, age, sqrt(first_name) FROM person \
+                   WHERE nope > 1 GROUP BY age, missing ORDER BY age";
+        let errors = plan(sql).expect_err("query should have failed");
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        let mut errors = errors.into_iter();
+        assert_field_not_found(errors.next().unwrap(), "nope");
+        assert_field_not_found(errors.next().unwrap(), "doesnotexist");
+        let err = errors.next().unwrap();
+        assert!(matches!(err, DataFusionError::Plan(_)), "{}", err);
+        assert_field_not_found(errors.next().unwrap(), "missing");
+
+        // without errors, the plan is the same as without recovery
+        let sql = "SELECT id, sqrt(age) FROM person WHERE age > 1 ORDER BY id";
+        assert_eq!(
+            format!("{:?}", plan(sql).unwrap()),
+            format!("{:?}", logical_plan(sql).unwrap())
+        );
+    }
+
     #[test]
     fn select_repeated_column() {
         let sql = "SELECT age, age FROM person";
@@ -4046,6 +4730,220 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn create_materialized_view() -> Result<()> {
+        let sql = "CREATE MATERIALIZED VIEW mv WITH (location = '/tmp/mv') AS \
+                   SELECT id, age FROM person";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateView(create) => {
+                assert!(create.materialized);
+                assert_eq!(create.location, Some("/tmp/mv".to_string()));
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let sql = "CREATE MATERIALIZED VIEW mv WITH (format = 'csv') AS \
+                   SELECT id FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "NotImplemented(\"Unsupported option for materialized views: format = 'csv'\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "REFRESH MATERIALIZED VIEW mv";
+        let expected = "RefreshMaterializedView: \"mv\"";
+        quick_test(sql, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_table() {
+        let sql = "ANALYZE TABLE person";
+        let expected = "AnalyzeTable: \"person\"";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn prepare_statement() {
+        let sql = "PREPARE my_plan(INT, STRING) AS \
+                   SELECT id FROM person WHERE age = $1 AND first_name = $2";
+        let expected = "Prepare: \"my_plan\" [Int32, Utf8]\
+                        \n  Projection: person.id\
+                        \n    Filter: person.age = $1 AND person.first_name = $2\
+                        \n      TableScan: person";
+        quick_test(sql, expected);
+
+        let plan = logical_plan(sql).unwrap();
+        let param_values = HashMap::from([
+            ("1".to_string(), ScalarValue::Int32(Some(21))),
+            (
+                "2".to_string(),
+                ScalarValue::Utf8(Some("Alice".to_string())),
+            ),
+        ]);
+        let expected = "Projection: person.id\
+                        \n  Filter: person.age = Int32(21) AND person.first_name = Utf8(\"Alice\")\
+                        \n    TableScan: person";
+        assert_eq!(
+            format!("{:?}", plan.with_param_values(&param_values).unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn placeholder_without_type() {
+        let sql = "SELECT id FROM person WHERE age = $1";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Placeholder $1 has no type, declare the types of the parameters with PREPARE\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "PREPARE my_plan(INT) AS SELECT id FROM person WHERE age = $2";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Placeholder $2 has no type, declare the types of the parameters with PREPARE\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn set_variable() {
+        let sql = "SET search_path = tenant_a, \"Shared\", public";
+        let expected = "SetVariable: set \"datafusion.catalog.search_path\" \
+                        to \"tenant_a, Shared, public\"";
+        quick_test(sql, expected);
+
+        let sql = "SET datafusion.execution.batch_size TO 1024";
+        let expected = "SetVariable: set \"datafusion.execution.batch_size\" to \"1024\"";
+        quick_test(sql, expected);
+
+        let sql = "SET TIME ZONE 'Asia/Tokyo'";
+        let expected =
+            "SetVariable: set \"datafusion.execution.time_zone\" to \"Asia/Tokyo\"";
+        quick_test(sql, expected);
+
+        let sql = "SET LOCAL search_path = public";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "NotImplemented(\"LOCAL is not supported\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn create_external_table_with_constraints() -> Result<()> {
+        let sql = "CREATE EXTERNAL TABLE orders(id int PRIMARY KEY, \
+                   customer_id int REFERENCES customers(id), note varchar NULL, \
+                   UNIQUE (customer_id, note)) STORED AS CSV LOCATION 'foo.csv'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateExternalTable(create) => assert_eq!(
+                create.constraints,
+                Constraints::new(vec![
+                    Constraint::PrimaryKey(vec![0]),
+                    Constraint::ForeignKey {
+                        columns: vec![1],
+                        referenced_table: "customers".to_string(),
+                        referenced_columns: vec!["id".to_string()],
+                    },
+                    Constraint::Unique(vec![1, 2]),
+                ])
+            ),
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int, PRIMARY KEY (c2)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Constraint on unknown column c2\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int CHECK (c1 > 0), c2 int, \
+                   CONSTRAINT C1_Below_C2 CHECK (c1 < c2)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateExternalTable(create) => assert_eq!(
+                create.constraints,
+                Constraints::new(vec![
+                    Constraint::Check {
+                        name: None,
+                        expr: "c1 > 0".to_string(),
+                    },
+                    Constraint::Check {
+                        name: Some("c1_below_c2".to_string()),
+                        expr: "c1 < c2".to_string(),
+                    },
+                ])
+            ),
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int NULL PRIMARY KEY) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Primary key column c1 can not be nullable\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int, c2 int NULL, PRIMARY KEY (c1, c2)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Primary key column c2 can not be nullable\")",
+            format!("{:?}", err)
+        );
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int CHECK (c3 > 0)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_contains!(err.to_string(), "No field named 'c3'");
+
+        let sql = "CREATE EXTERNAL TABLE t(c1 int CHECK (c1 + 1)) \
+                   STORED AS CSV LOCATION 'foo.csv'";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_contains!(
+            err.to_string(),
+            "Check constraint c1 + 1 must be a boolean expression, got Int64"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn create_catalog_with_location() -> Result<()> {
+        let sql = "CREATE DATABASE IF NOT EXISTS Sales LOCATION '/data/sales'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateCatalog(create) => {
+                assert_eq!(create.catalog_name, "sales");
+                assert!(create.if_not_exists);
+                assert_eq!(create.location, Some("/data/sales".to_string()));
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        let sql = "CREATE SCHEMA sales.\"EU\" LOCATION '/data/eu'";
+        match logical_plan(sql)? {
+            LogicalPlan::CreateCatalogSchema(create) => {
+                assert_eq!(create.schema_name, "sales.EU");
+                assert_eq!(create.location, Some("/data/eu".to_string()));
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn select_column_qualified_by_catalog() {
+        let sql = "SELECT datafusion.public.person.id, age \
+                   FROM Datafusion.Public.Person";
+        let expected =
+            "Projection: datafusion.public.person.id, datafusion.public.person.age\
+                        \n  TableScan: datafusion.public.person";
+        quick_test(sql, expected);
+    }
+
     #[test]
     fn create_external_table_custom() {
         let sql = "CREATE EXTERNAL TABLE dt STORED AS DELTATABLE LOCATION 's3://bucket/schema/table';";
@@ -4528,6 +5426,27 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn over_order_by_ignore_nulls() {
+        let sql = "SELECT order_id, LAG(qty, 1) IGNORE NULLS OVER (ORDER BY order_id), LAG(qty, 2) RESPECT NULLS OVER (ORDER BY order_id) from orders";
+        let expected = "\
+        Projection: orders.order_id, LAG(orders.qty,Int64(1)) IGNORE NULLS ORDER BY [orders.order_id ASC NULLS LAST], LAG(orders.qty,Int64(2)) ORDER BY [orders.order_id ASC NULLS LAST]\
+        \n  WindowAggr: windowExpr=[[LAG(orders.qty, Int64(1)) IGNORE NULLS ORDER BY [orders.order_id ASC NULLS LAST], LAG(orders.qty, Int64(2)) ORDER BY [orders.order_id ASC NULLS LAST]]]\
+        \n    TableScan: orders";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn ignore_nulls_without_over() {
+        let sql = "SELECT lag(qty) IGNORE NULLS from orders";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(
+            matches!(err, DataFusionError::SQL(ParserError(_))),
+            "{:?}",
+            err
+        );
+    }
+
     #[test]
     fn over_order_by_with_window_frame_double_end() {
         let sql = "SELECT order_id, MAX(qty) OVER (ORDER BY order_id ROWS BETWEEN 3 PRECEDING and 3 FOLLOWING), MIN(qty) OVER (ORDER BY order_id DESC) from orders";
@@ -4813,6 +5732,29 @@ mod tests {
         logical_plan_with_dialect(sql, dialect)
     }
 
+    #[test]
+    fn expr_nested_deeper_than_max_depth() {
+        let planner = SqlToRel::new(&MockContextProvider {}).with_max_depth(5);
+        let mut ast = DFParser::parse_sql("SELECT ((((((1))))))").unwrap();
+        let err = planner
+            .statement_to_plan(ast.pop_front().unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: SQL expression nested deeper than the limit of 5 levels"
+        );
+    }
+
+    #[test]
+    fn long_chain_of_binary_operators() {
+        // the chain is planned without recursing as deep as it
+        let predicate = vec!["age = 1"; 1000].join(" OR ");
+        let sql = format!("SELECT id FROM person WHERE {}", predicate);
+        let planner = SqlToRel::new(&MockContextProvider {}).with_max_depth(5);
+        let mut ast = DFParser::parse_sql(&sql).unwrap();
+        planner.statement_to_plan(ast.pop_front().unwrap()).unwrap();
+    }
+
     fn logical_plan_with_dialect(
         sql: &str,
         dialect: &dyn Dialect,
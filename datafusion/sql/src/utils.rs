@@ -18,7 +18,7 @@
 //! SQL Utility Functions
 
 use arrow::datatypes::{DataType, DECIMAL128_MAX_PRECISION, DECIMAL_DEFAULT_SCALE};
-use sqlparser::ast::Ident;
+use sqlparser::ast::{Ident, ObjectName};
 
 use datafusion_common::{DataFusionError, Result, ScalarValue};
 use datafusion_expr::expr::Cast;
@@ -182,6 +182,7 @@ where
                 partition_by,
                 order_by,
                 window_frame,
+                ignore_nulls,
             } => Ok(Expr::WindowFunction {
                 fun: fun.clone(),
                 args: args
@@ -197,6 +198,7 @@ where
                     .map(|e| clone_with_replacement(e, replacement_fn))
                     .collect::<Result<Vec<_>>>()?,
                 window_frame: window_frame.clone(),
+                ignore_nulls: *ignore_nulls,
             }),
             Expr::AggregateUDF { fun, args, filter } => Ok(Expr::AggregateUDF {
                 fun: fun.clone(),
@@ -364,6 +366,7 @@ where
             Expr::Column { .. }
             | Expr::Literal(_)
             | Expr::ScalarVariable(_, _)
+            | Expr::Placeholder { .. }
             | Expr::Exists { .. }
             | Expr::ScalarSubquery(_) => Ok(expr.clone()),
             Expr::InSubquery {
@@ -539,3 +542,13 @@ pub(crate) fn normalize_ident(id: &Ident) -> String {
         None => id.value.to_ascii_lowercase(),
     }
 }
+
+/// Normalize a SQL object name
+pub(crate) fn normalize_sql_object_name(sql_object_name: &ObjectName) -> String {
+    sql_object_name
+        .0
+        .iter()
+        .map(normalize_ident)
+        .collect::<Vec<String>>()
+        .join(".")
+}
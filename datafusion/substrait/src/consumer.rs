@@ -0,0 +1,684 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversion of Substrait plans to DataFusion logical plans
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::{DFSchema, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::expr::{BinaryExpr, Case, Cast};
+use datafusion::logical_expr::{
+    AggregateFunction, BuiltinScalarFunction, Expr, JoinType, LogicalPlan,
+    LogicalPlanBuilder, Operator,
+};
+use datafusion::optimizer::utils::{conjunction, split_conjunction};
+use datafusion::prelude::SessionContext;
+use datafusion_proto::logical_plan::LogicalExtensionCodec;
+use substrait::protobuf::{
+    aggregate_function::AggregationInvocation,
+    expression::{
+        field_reference::ReferenceType, literal::LiteralType, reference_segment, RexType,
+    },
+    extensions::simple_extension_declaration::MappingType,
+    function_argument::ArgType,
+    join_rel, plan_rel,
+    r#type::Kind,
+    read_rel::ReadType,
+    rel::RelType,
+    rel_common::EmitKind,
+    set_rel::SetOp,
+    sort_field::{SortDirection, SortKind},
+    Expression, FunctionArgument, Plan, ReadRel, Rel, SortField, Type,
+};
+
+/// Names of the functions declared by a plan, by anchor
+type FunctionNames = HashMap<u32, String>;
+
+/// Converts a Substrait plan to a logical plan, resolving tables and
+/// functions in `ctx` and decoding extension relations with `extension_codec`
+pub fn from_substrait_plan(
+    ctx: &SessionContext,
+    plan: &Plan,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan> {
+    let functions = plan
+        .extensions
+        .iter()
+        .filter_map(|extension| match &extension.mapping_type {
+            Some(MappingType::ExtensionFunction(function)) => {
+                Some((function.function_anchor, function.name.clone()))
+            }
+            _ => None,
+        })
+        .collect::<FunctionNames>();
+
+    match plan.relations.as_slice() {
+        [relation] => match &relation.rel_type {
+            Some(plan_rel::RelType::Root(root)) => {
+                let plan = from_substrait_rel(
+                    ctx,
+                    required(&root.input, "RelRoot.input")?,
+                    &functions,
+                    extension_codec,
+                )?;
+                rename_fields(plan, &root.names)
+            }
+            Some(plan_rel::RelType::Rel(rel)) => {
+                from_substrait_rel(ctx, rel, &functions, extension_codec)
+            }
+            None => Err(substrait_error("PlanRel without a relation")),
+        },
+        relations => Err(DataFusionError::NotImplemented(format!(
+            "Substrait plans with {} relations are not supported",
+            relations.len()
+        ))),
+    }
+}
+
+fn substrait_error(message: &str) -> DataFusionError {
+    DataFusionError::Plan(format!("Invalid Substrait plan: {}", message))
+}
+
+fn required<'a, T>(field: &'a Option<T>, name: &str) -> Result<&'a T> {
+    field
+        .as_ref()
+        .ok_or_else(|| substrait_error(&format!("missing {}", name)))
+}
+
+/// Renames the output fields of `plan` to `names`, if they differ
+fn rename_fields(plan: LogicalPlan, names: &[String]) -> Result<LogicalPlan> {
+    let fields = plan.schema().fields();
+    if names.is_empty()
+        || fields.len() != names.len()
+        || fields.iter().zip(names).all(|(f, name)| f.name() == name)
+    {
+        return Ok(plan);
+    }
+    let exprs = fields
+        .iter()
+        .zip(names)
+        .map(|(f, name)| Expr::Column(f.qualified_column()).alias(name))
+        .collect::<Vec<_>>();
+    LogicalPlanBuilder::from(plan).project(exprs)?.build()
+}
+
+fn from_substrait_rel(
+    ctx: &SessionContext,
+    rel: &Rel,
+    functions: &FunctionNames,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan> {
+    let input = |input: &Option<Box<Rel>>, name: &str| -> Result<LogicalPlan> {
+        from_substrait_rel(
+            ctx,
+            required(input, name)?.as_ref(),
+            functions,
+            extension_codec,
+        )
+    };
+
+    match required(&rel.rel_type, "Rel.rel_type")? {
+        RelType::Read(read) => from_substrait_read(ctx, read, functions),
+        RelType::Project(project) => {
+            let input = input(&project.input, "ProjectRel.input")?;
+            let schema = input.schema();
+            let mut exprs = schema
+                .fields()
+                .iter()
+                .map(|f| Expr::Column(f.qualified_column()))
+                .collect::<Vec<_>>();
+            for expression in &project.expressions {
+                exprs.push(from_substrait_rex(expression, schema, functions)?);
+            }
+            let emit = project
+                .common
+                .as_ref()
+                .and_then(|common| common.emit_kind.as_ref());
+            let exprs = match emit {
+                Some(EmitKind::Emit(emit)) => emit
+                    .output_mapping
+                    .iter()
+                    .map(|i| {
+                        exprs.get(*i as usize).cloned().ok_or_else(|| {
+                            substrait_error("ProjectRel emits an unknown field")
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                _ => exprs,
+            };
+            LogicalPlanBuilder::from(input).project(exprs)?.build()
+        }
+        RelType::Filter(filter) => {
+            let input = input(&filter.input, "FilterRel.input")?;
+            let condition = from_substrait_rex(
+                required(&filter.condition, "FilterRel.condition")?,
+                input.schema(),
+                functions,
+            )?;
+            LogicalPlanBuilder::from(input).filter(condition)?.build()
+        }
+        RelType::Fetch(fetch) => {
+            let input = input(&fetch.input, "FetchRel.input")?;
+            let count = if fetch.count < 0 {
+                None
+            } else {
+                Some(fetch.count as usize)
+            };
+            LogicalPlanBuilder::from(input)
+                .limit(fetch.offset as usize, count)?
+                .build()
+        }
+        RelType::Sort(sort) => {
+            let input = input(&sort.input, "SortRel.input")?;
+            let exprs = sort
+                .sorts
+                .iter()
+                .map(|sort| from_substrait_sort_field(sort, input.schema(), functions))
+                .collect::<Result<Vec<_>>>()?;
+            LogicalPlanBuilder::from(input).sort(exprs)?.build()
+        }
+        RelType::Aggregate(aggregate) => {
+            let input = input(&aggregate.input, "AggregateRel.input")?;
+            let schema = input.schema();
+            let group_expr =
+                match aggregate.groupings.as_slice() {
+                    [] => vec![],
+                    [grouping] => grouping
+                        .grouping_expressions
+                        .iter()
+                        .map(|e| from_substrait_rex(e, schema, functions))
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => return Err(DataFusionError::NotImplemented(
+                        "Substrait aggregates with several groupings are not supported"
+                            .to_string(),
+                    )),
+                };
+            let aggr_expr = aggregate
+                .measures
+                .iter()
+                .map(|measure| {
+                    let function = required(&measure.measure, "Measure.measure")?;
+                    let name = function_name(functions, function.function_reference)?;
+                    let fun = AggregateFunction::from_str(name)?;
+                    let args = function
+                        .arguments
+                        .iter()
+                        .map(|arg| from_substrait_argument(arg, schema, functions))
+                        .collect::<Result<Vec<_>>>()?;
+                    let filter = measure
+                        .filter
+                        .as_ref()
+                        .map(|filter| from_substrait_rex(filter, schema, functions))
+                        .transpose()?
+                        .map(Box::new);
+                    Ok(Expr::AggregateFunction {
+                        fun,
+                        args,
+                        distinct: function.invocation
+                            == AggregationInvocation::Distinct as i32,
+                        filter,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            LogicalPlanBuilder::from(input)
+                .aggregate(group_expr, aggr_expr)?
+                .build()
+        }
+        RelType::Join(join) => {
+            let left = input(&join.left, "JoinRel.left")?;
+            let right = input(&join.right, "JoinRel.right")?;
+            let join_type = match join_rel::JoinType::from_i32(join.r#type) {
+                Some(join_rel::JoinType::Inner) => JoinType::Inner,
+                Some(join_rel::JoinType::Left) => JoinType::Left,
+                Some(join_rel::JoinType::Right) => JoinType::Right,
+                Some(join_rel::JoinType::Outer) => JoinType::Full,
+                Some(join_rel::JoinType::Semi) => JoinType::LeftSemi,
+                Some(join_rel::JoinType::Anti) => JoinType::LeftAnti,
+                _ => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported Substrait join type {}",
+                        join.r#type
+                    )))
+                }
+            };
+            let mut join_schema = left.schema().as_ref().clone();
+            join_schema.merge(right.schema());
+            let expression = join
+                .expression
+                .as_ref()
+                .map(|e| from_substrait_rex(e, &join_schema, functions))
+                .transpose()?;
+            let post_join_filter = join
+                .post_join_filter
+                .as_ref()
+                .map(|e| from_substrait_rex(e, &join_schema, functions))
+                .transpose()?;
+            if post_join_filter.is_some() {
+                return Err(DataFusionError::NotImplemented(
+                    "Substrait joins with a post join filter are not supported"
+                        .to_string(),
+                ));
+            }
+
+            // equalities between the two inputs become the join keys, the
+            // rest of the condition filters the joined rows
+            let mut left_keys = vec![];
+            let mut right_keys = vec![];
+            let mut filters = vec![];
+            for predicate in expression.iter().flat_map(split_conjunction) {
+                match predicate {
+                    Expr::BinaryExpr(BinaryExpr {
+                        left: l,
+                        op: Operator::Eq,
+                        right: r,
+                    }) => match (l.as_ref(), r.as_ref()) {
+                        (Expr::Column(l), Expr::Column(r))
+                            if left.schema().index_of_column(l).is_ok()
+                                && right.schema().index_of_column(r).is_ok() =>
+                        {
+                            left_keys.push(l.clone());
+                            right_keys.push(r.clone());
+                        }
+                        (Expr::Column(l), Expr::Column(r))
+                            if right.schema().index_of_column(l).is_ok()
+                                && left.schema().index_of_column(r).is_ok() =>
+                        {
+                            left_keys.push(r.clone());
+                            right_keys.push(l.clone());
+                        }
+                        _ => filters.push(predicate.clone()),
+                    },
+                    _ => filters.push(predicate.clone()),
+                }
+            }
+            if left_keys.is_empty() && join_type == JoinType::Inner {
+                let builder = LogicalPlanBuilder::from(left).cross_join(&right)?;
+                return match conjunction(filters) {
+                    Some(filter) => builder.filter(filter)?.build(),
+                    None => builder.build(),
+                };
+            }
+            LogicalPlanBuilder::from(left)
+                .join(
+                    &right,
+                    join_type,
+                    (left_keys, right_keys),
+                    conjunction(filters),
+                )?
+                .build()
+        }
+        RelType::Cross(cross) => {
+            let left = input(&cross.left, "CrossRel.left")?;
+            let right = input(&cross.right, "CrossRel.right")?;
+            LogicalPlanBuilder::from(left).cross_join(&right)?.build()
+        }
+        RelType::Set(set) => {
+            if set.op != SetOp::UnionAll as i32 {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported Substrait set operation {}",
+                    set.op
+                )));
+            }
+            let mut inputs = set
+                .inputs
+                .iter()
+                .map(|rel| from_substrait_rel(ctx, rel, functions, extension_codec));
+            let mut builder = LogicalPlanBuilder::from(
+                inputs
+                    .next()
+                    .ok_or_else(|| substrait_error("SetRel without inputs"))??,
+            );
+            for input in inputs {
+                builder = builder.union(input?)?;
+            }
+            builder.build()
+        }
+        RelType::ExtensionLeaf(extension) => {
+            let detail = required(&extension.detail, "ExtensionLeafRel.detail")?;
+            let extension = extension_codec.try_decode(&detail.value, &[], ctx)?;
+            Ok(LogicalPlan::Extension(extension))
+        }
+        RelType::ExtensionSingle(extension) => {
+            let detail = required(&extension.detail, "ExtensionSingleRel.detail")?;
+            let inputs = [input(&extension.input, "ExtensionSingleRel.input")?];
+            let extension = extension_codec.try_decode(&detail.value, &inputs, ctx)?;
+            Ok(LogicalPlan::Extension(extension))
+        }
+        RelType::ExtensionMulti(extension) => {
+            let detail = required(&extension.detail, "ExtensionMultiRel.detail")?;
+            let inputs = extension
+                .inputs
+                .iter()
+                .map(|rel| from_substrait_rel(ctx, rel, functions, extension_codec))
+                .collect::<Result<Vec<_>>>()?;
+            let extension = extension_codec.try_decode(&detail.value, &inputs, ctx)?;
+            Ok(LogicalPlan::Extension(extension))
+        }
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported Substrait relation: {:?}",
+            rel
+        ))),
+    }
+}
+
+fn from_substrait_read(
+    ctx: &SessionContext,
+    read: &ReadRel,
+    functions: &FunctionNames,
+) -> Result<LogicalPlan> {
+    let table_name = match required(&read.read_type, "ReadRel.read_type")? {
+        ReadType::NamedTable(table) => table.names.join("."),
+        _ => {
+            return Err(DataFusionError::NotImplemented(
+                "Only named tables can be read from Substrait plans".to_string(),
+            ))
+        }
+    };
+    let projection = read
+        .projection
+        .as_ref()
+        .and_then(|mask| mask.select.as_ref())
+        .map(|select| {
+            select
+                .struct_items
+                .iter()
+                .map(|item| item.field as usize)
+                .collect::<Vec<_>>()
+        });
+
+    let plan = ctx.table(table_name.as_str())?.to_unoptimized_plan();
+    let scan = match plan {
+        LogicalPlan::TableScan(scan) => scan,
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Table {} can not be read from Substrait plans",
+                table_name
+            )))
+        }
+    };
+    let table_schema =
+        DFSchema::try_from_qualified_schema(&scan.table_name, &scan.source.schema())?;
+    let filters = read
+        .filter
+        .as_ref()
+        .map(|filter| from_substrait_rex(filter, &table_schema, functions))
+        .transpose()?
+        .map(|filter| split_conjunction(&filter).into_iter().cloned().collect())
+        .unwrap_or_default();
+
+    LogicalPlanBuilder::scan_with_filters(
+        scan.table_name,
+        scan.source,
+        projection,
+        filters,
+    )?
+    .build()
+}
+
+fn function_name(functions: &FunctionNames, anchor: u32) -> Result<&str> {
+    functions
+        .get(&anchor)
+        .map(|name| name.as_str())
+        .ok_or_else(|| {
+            substrait_error(&format!("function anchor {} is not declared", anchor))
+        })
+}
+
+/// Returns the operator evaluated by the Substrait function `name`
+fn name_to_operator(name: &str) -> Option<Operator> {
+    let op = match name {
+        "equal" => Operator::Eq,
+        "not_equal" => Operator::NotEq,
+        "lt" => Operator::Lt,
+        "lte" => Operator::LtEq,
+        "gt" => Operator::Gt,
+        "gte" => Operator::GtEq,
+        "add" => Operator::Plus,
+        "subtract" => Operator::Minus,
+        "multiply" => Operator::Multiply,
+        "divide" => Operator::Divide,
+        "modulus" => Operator::Modulo,
+        "and" => Operator::And,
+        "or" => Operator::Or,
+        "is_distinct_from" => Operator::IsDistinctFrom,
+        "is_not_distinct_from" => Operator::IsNotDistinctFrom,
+        "concat" => Operator::StringConcat,
+        _ => return None,
+    };
+    Some(op)
+}
+
+fn from_substrait_sort_field(
+    sort: &SortField,
+    schema: &DFSchema,
+    functions: &FunctionNames,
+) -> Result<Expr> {
+    let expr =
+        from_substrait_rex(required(&sort.expr, "SortField.expr")?, schema, functions)?;
+    let direction = match required(&sort.sort_kind, "SortField.sort_kind")? {
+        SortKind::Direction(direction) => SortDirection::from_i32(*direction),
+        SortKind::ComparisonFunctionReference(_) => None,
+    };
+    let (asc, nulls_first) = match direction {
+        Some(SortDirection::AscNullsFirst) => (true, true),
+        Some(SortDirection::AscNullsLast) => (true, false),
+        Some(SortDirection::DescNullsFirst) => (false, true),
+        Some(SortDirection::DescNullsLast) => (false, false),
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported Substrait sort kind {:?}",
+                sort.sort_kind
+            )))
+        }
+    };
+    Ok(Expr::Sort {
+        expr: Box::new(expr),
+        asc,
+        nulls_first,
+    })
+}
+
+fn from_substrait_argument(
+    arg: &FunctionArgument,
+    schema: &DFSchema,
+    functions: &FunctionNames,
+) -> Result<Expr> {
+    match required(&arg.arg_type, "FunctionArgument.arg_type")? {
+        ArgType::Value(expr) => from_substrait_rex(expr, schema, functions),
+        _ => Err(DataFusionError::NotImplemented(
+            "Only value arguments are supported in Substrait functions".to_string(),
+        )),
+    }
+}
+
+/// Converts a Substrait expression, referring to the fields of `schema` by
+/// position, to an expression
+fn from_substrait_rex(
+    expr: &Expression,
+    schema: &DFSchema,
+    functions: &FunctionNames,
+) -> Result<Expr> {
+    match required(&expr.rex_type, "Expression.rex_type")? {
+        RexType::Selection(field_ref) => {
+            let segment = match required(&field_ref.reference_type, "reference_type")? {
+                ReferenceType::DirectReference(segment) => segment,
+                _ => {
+                    return Err(DataFusionError::NotImplemented(
+                        "Only direct field references are supported in Substrait"
+                            .to_string(),
+                    ))
+                }
+            };
+            match required(&segment.reference_type, "ReferenceSegment.reference_type")? {
+                reference_segment::ReferenceType::StructField(field)
+                    if field.child.is_none() =>
+                {
+                    let index = field.field as usize;
+                    if index >= schema.fields().len() {
+                        return Err(substrait_error(&format!(
+                            "field reference {} out of bounds",
+                            index
+                        )));
+                    }
+                    Ok(Expr::Column(schema.field(index).qualified_column()))
+                }
+                _ => Err(DataFusionError::NotImplemented(
+                    "Only top level fields can be referenced in Substrait".to_string(),
+                )),
+            }
+        }
+        RexType::Literal(literal) => {
+            let value = match required(&literal.literal_type, "Literal.literal_type")? {
+                LiteralType::Boolean(v) => ScalarValue::Boolean(Some(*v)),
+                LiteralType::I8(v) => ScalarValue::Int8(Some(*v as i8)),
+                LiteralType::I16(v) => ScalarValue::Int16(Some(*v as i16)),
+                LiteralType::I32(v) => ScalarValue::Int32(Some(*v)),
+                LiteralType::I64(v) => ScalarValue::Int64(Some(*v)),
+                LiteralType::Fp32(v) => ScalarValue::Float32(Some(*v)),
+                LiteralType::Fp64(v) => ScalarValue::Float64(Some(*v)),
+                LiteralType::String(v) => ScalarValue::Utf8(Some(v.clone())),
+                LiteralType::Binary(v) => ScalarValue::Binary(Some(v.clone())),
+                LiteralType::Date(v) => ScalarValue::Date32(Some(*v)),
+                LiteralType::Decimal(d) => {
+                    let value: [u8; 16] =
+                        d.value.as_slice().try_into().map_err(|_| {
+                            substrait_error("decimal literals must have 16 bytes")
+                        })?;
+                    ScalarValue::Decimal128(
+                        Some(i128::from_le_bytes(value)),
+                        d.precision as u8,
+                        d.scale as u8,
+                    )
+                }
+                LiteralType::Null(data_type) => {
+                    ScalarValue::try_from(&from_substrait_type(data_type)?)?
+                }
+                literal_type => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported Substrait literal {:?}",
+                        literal_type
+                    )))
+                }
+            };
+            Ok(Expr::Literal(value))
+        }
+        RexType::ScalarFunction(function) => {
+            let name = function_name(functions, function.function_reference)?;
+            let mut args = function
+                .arguments
+                .iter()
+                .map(|arg| from_substrait_argument(arg, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            match (name, args.len()) {
+                ("not", 1) => Ok(Expr::Not(Box::new(args.remove(0)))),
+                ("is_null", 1) => Ok(Expr::IsNull(Box::new(args.remove(0)))),
+                ("is_not_null", 1) => Ok(Expr::IsNotNull(Box::new(args.remove(0)))),
+                ("negate", 1) => Ok(Expr::Negative(Box::new(args.remove(0)))),
+                (name, 2) if name_to_operator(name).is_some() => {
+                    let right = args.remove(1);
+                    let left = args.remove(0);
+                    Ok(Expr::BinaryExpr(BinaryExpr::new(
+                        Box::new(left),
+                        name_to_operator(name).unwrap(),
+                        Box::new(right),
+                    )))
+                }
+                (name, _) => Ok(Expr::ScalarFunction {
+                    fun: BuiltinScalarFunction::from_str(name)?,
+                    args,
+                }),
+            }
+        }
+        RexType::IfThen(if_then) => {
+            let when_then_expr = if_then
+                .ifs
+                .iter()
+                .map(|clause| {
+                    Ok((
+                        Box::new(from_substrait_rex(
+                            required(&clause.r#if, "IfClause.if")?,
+                            schema,
+                            functions,
+                        )?),
+                        Box::new(from_substrait_rex(
+                            required(&clause.then, "IfClause.then")?,
+                            schema,
+                            functions,
+                        )?),
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let else_expr = if_then
+                .r#else
+                .as_ref()
+                .map(|e| from_substrait_rex(e, schema, functions))
+                .transpose()?
+                .map(Box::new);
+            Ok(Expr::Case(Case::new(None, when_then_expr, else_expr)))
+        }
+        RexType::Cast(cast) => Ok(Expr::Cast(Cast::new(
+            Box::new(from_substrait_rex(
+                required(&cast.input, "Cast.input")?,
+                schema,
+                functions,
+            )?),
+            from_substrait_type(required(&cast.r#type, "Cast.type")?)?,
+        ))),
+        RexType::SingularOrList(in_list) => Ok(Expr::InList {
+            expr: Box::new(from_substrait_rex(
+                required(&in_list.value, "SingularOrList.value")?,
+                schema,
+                functions,
+            )?),
+            list: in_list
+                .options
+                .iter()
+                .map(|e| from_substrait_rex(e, schema, functions))
+                .collect::<Result<Vec<_>>>()?,
+            negated: false,
+        }),
+        rex_type => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported Substrait expression {:?}",
+            rex_type
+        ))),
+    }
+}
+
+fn from_substrait_type(data_type: &Type) -> Result<DataType> {
+    let data_type = match required(&data_type.kind, "Type.kind")? {
+        Kind::Bool(_) => DataType::Boolean,
+        Kind::I8(_) => DataType::Int8,
+        Kind::I16(_) => DataType::Int16,
+        Kind::I32(_) => DataType::Int32,
+        Kind::I64(_) => DataType::Int64,
+        Kind::Fp32(_) => DataType::Float32,
+        Kind::Fp64(_) => DataType::Float64,
+        Kind::String(_) => DataType::Utf8,
+        Kind::Binary(_) => DataType::Binary,
+        Kind::Date(_) => DataType::Date32,
+        Kind::Decimal(d) => DataType::Decimal128(d.precision as u8, d.scale as u8),
+        kind => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported Substrait type {:?}",
+                kind
+            )))
+        }
+    };
+    Ok(data_type)
+}
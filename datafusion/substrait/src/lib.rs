@@ -0,0 +1,176 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversion of logical plans to and from [Substrait](https://substrait.io)
+//! plans.
+//!
+//! User defined logical nodes are carried in Substrait extension relations,
+//! encoded with the same [`LogicalExtensionCodec`] used by `datafusion-proto`.
+//!
+//! [`LogicalExtensionCodec`]: datafusion_proto::logical_plan::LogicalExtensionCodec
+
+pub mod consumer;
+pub mod producer;
+
+pub use substrait;
+
+use datafusion::common::{DataFusionError, Result};
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::SessionContext;
+use datafusion_proto::logical_plan::{
+    DefaultLogicalExtensionCodec, LogicalExtensionCodec,
+};
+use prost::Message;
+use substrait::protobuf::Plan;
+
+/// Serializes a logical plan to the bytes of a Substrait plan
+pub fn logical_plan_to_bytes(plan: &LogicalPlan) -> Result<Vec<u8>> {
+    logical_plan_to_bytes_with_extension_codec(plan, &DefaultLogicalExtensionCodec {})
+}
+
+/// Serializes a logical plan to the bytes of a Substrait plan, encoding
+/// extension nodes with `extension_codec`
+pub fn logical_plan_to_bytes_with_extension_codec(
+    plan: &LogicalPlan,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Vec<u8>> {
+    let plan = producer::to_substrait_plan(plan, extension_codec)?;
+    let mut buf = Vec::with_capacity(plan.encoded_len());
+    plan.encode(&mut buf).map_err(|e| {
+        DataFusionError::Internal(format!("Error encoding Substrait plan: {}", e))
+    })?;
+    Ok(buf)
+}
+
+/// Deserializes a logical plan from the bytes of a Substrait plan
+pub fn logical_plan_from_bytes(
+    bytes: &[u8],
+    ctx: &SessionContext,
+) -> Result<LogicalPlan> {
+    logical_plan_from_bytes_with_extension_codec(
+        bytes,
+        ctx,
+        &DefaultLogicalExtensionCodec {},
+    )
+}
+
+/// Deserializes a logical plan from the bytes of a Substrait plan, decoding
+/// extension relations with `extension_codec`
+pub fn logical_plan_from_bytes_with_extension_codec(
+    bytes: &[u8],
+    ctx: &SessionContext,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<LogicalPlan> {
+    let plan = Plan::decode(bytes).map_err(|e| {
+        DataFusionError::Plan(format!("Error decoding Substrait plan: {}", e))
+    })?;
+    consumer::from_substrait_plan(ctx, &plan, extension_codec)
+}
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use super::*;
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use std::sync::Arc;
+
+    fn context() -> Result<SessionContext> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+            ],
+        )?;
+        ctx.register_batch("data", batch.clone())?;
+        ctx.register_batch("data2", batch)?;
+        Ok(ctx)
+    }
+
+    fn roundtrip(sql: &str) -> Result<()> {
+        let ctx = context()?;
+        let plan = ctx.create_logical_plan(sql)?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        let plan2 = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(format!("{:?}", plan), format!("{:?}", plan2));
+
+        let optimized = ctx.optimize(&plan)?;
+        let bytes = logical_plan_to_bytes(&optimized)?;
+        let optimized2 = logical_plan_from_bytes(&bytes, &ctx)?;
+        assert_eq!(optimized.schema(), optimized2.schema());
+        Ok(())
+    }
+
+    #[test]
+    fn simple_select() -> Result<()> {
+        roundtrip("SELECT a, b FROM data")
+    }
+
+    #[test]
+    fn expressions() -> Result<()> {
+        roundtrip(
+            "SELECT a + 1, -a, NOT (a > 2), b IS NULL, \
+             CASE WHEN a = 1 THEN 'one' ELSE b END, CAST(a AS DOUBLE), \
+             upper(b) FROM data",
+        )
+    }
+
+    #[test]
+    fn filter() -> Result<()> {
+        roundtrip("SELECT a FROM data WHERE a > 1 AND b IS NOT NULL AND a IN (1, 2)")
+    }
+
+    #[test]
+    fn aggregate() -> Result<()> {
+        roundtrip("SELECT b, count(a), sum(DISTINCT a) FROM data GROUP BY b")
+    }
+
+    #[test]
+    fn join() -> Result<()> {
+        roundtrip(
+            "SELECT data.a, data2.b FROM data JOIN data2 \
+             ON data.a = data2.a AND data.b <> data2.b",
+        )?;
+        roundtrip("SELECT data.a FROM data LEFT JOIN data2 ON data.a = data2.a")
+    }
+
+    #[test]
+    fn limit_and_sort() -> Result<()> {
+        roundtrip("SELECT a FROM data ORDER BY a DESC NULLS LAST LIMIT 2 OFFSET 1")
+    }
+
+    #[test]
+    fn union_all() -> Result<()> {
+        roundtrip("SELECT a FROM data UNION ALL SELECT a FROM data2")
+    }
+
+    #[test]
+    fn unknown_table() -> Result<()> {
+        let ctx = context()?;
+        let plan = ctx.create_logical_plan("SELECT a FROM data")?;
+        let bytes = logical_plan_to_bytes(&plan)?;
+        ctx.deregister_table("data")?;
+        assert!(logical_plan_from_bytes(&bytes, &ctx).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,776 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Conversion of DataFusion logical plans to Substrait plans
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::common::{DFSchema, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::expr::{BinaryExpr, Case, Cast};
+use datafusion::logical_expr::{Between, Expr, JoinType, LogicalPlan, Operator};
+use datafusion::optimizer::utils::conjunction;
+use datafusion_proto::logical_plan::LogicalExtensionCodec;
+use substrait::protobuf::{
+    aggregate_function::AggregationInvocation,
+    aggregate_rel::{Grouping, Measure},
+    expression::{
+        field_reference::{ReferenceType, RootReference, RootType},
+        if_then::IfClause,
+        literal::{Decimal, LiteralType},
+        mask_expression::{StructItem, StructSelect},
+        reference_segment, FieldReference, IfThen, Literal, MaskExpression,
+        ReferenceSegment, RexType, SingularOrList,
+    },
+    extensions::{
+        simple_extension_declaration::{ExtensionFunction, MappingType},
+        SimpleExtensionDeclaration,
+    },
+    function_argument::ArgType,
+    join_rel, plan_rel,
+    r#type::{self, Kind, Nullability},
+    read_rel::{NamedTable, ReadType},
+    rel::RelType,
+    rel_common::{Emit, EmitKind},
+    set_rel::SetOp,
+    sort_field::{SortDirection, SortKind},
+    AggregateFunction, AggregateRel, CrossRel, Expression, ExtensionLeafRel,
+    ExtensionMultiRel, ExtensionSingleRel, FetchRel, FilterRel, FunctionArgument,
+    JoinRel, NamedStruct, Plan, PlanRel, ProjectRel, ReadRel, Rel, RelCommon, RelRoot,
+    ScalarFunction, SetRel, SortField, SortRel, Type,
+};
+
+/// Names of the functions referenced by a plan, in the order of their anchors
+#[derive(Debug, Default)]
+struct FunctionExtensions {
+    anchors: HashMap<String, u32>,
+    names: Vec<String>,
+}
+
+impl FunctionExtensions {
+    /// Returns the anchor of the function `name`, declaring it if needed
+    fn register(&mut self, name: &str) -> u32 {
+        if let Some(anchor) = self.anchors.get(name) {
+            return *anchor;
+        }
+        let anchor = self.names.len() as u32;
+        self.anchors.insert(name.to_string(), anchor);
+        self.names.push(name.to_string());
+        anchor
+    }
+
+    fn declarations(self) -> Vec<SimpleExtensionDeclaration> {
+        self.names
+            .into_iter()
+            .enumerate()
+            .map(|(anchor, name)| SimpleExtensionDeclaration {
+                mapping_type: Some(MappingType::ExtensionFunction(ExtensionFunction {
+                    extension_uri_reference: 0,
+                    function_anchor: anchor as u32,
+                    name,
+                })),
+            })
+            .collect()
+    }
+}
+
+/// Converts a logical plan to a Substrait plan, encoding extension nodes
+/// with `extension_codec`
+pub fn to_substrait_plan(
+    plan: &LogicalPlan,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Plan> {
+    let mut functions = FunctionExtensions::default();
+    let rel = to_substrait_rel(plan, &mut functions, extension_codec)?;
+    let names = plan
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().to_string())
+        .collect();
+
+    Ok(Plan {
+        extensions: functions.declarations(),
+        relations: vec![PlanRel {
+            rel_type: Some(plan_rel::RelType::Root(RelRoot {
+                input: Some(rel),
+                names,
+            })),
+        }],
+        ..Default::default()
+    })
+}
+
+fn rel(rel_type: RelType) -> Rel {
+    Rel {
+        rel_type: Some(rel_type),
+    }
+}
+
+fn boxed_rel(
+    plan: &LogicalPlan,
+    functions: &mut FunctionExtensions,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Box<Rel>> {
+    Ok(Box::new(to_substrait_rel(
+        plan,
+        functions,
+        extension_codec,
+    )?))
+}
+
+fn to_substrait_rel(
+    plan: &LogicalPlan,
+    functions: &mut FunctionExtensions,
+    extension_codec: &dyn LogicalExtensionCodec,
+) -> Result<Rel> {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            let table_schema = scan.source.schema();
+            let projection = scan.projection.as_ref().map(|projection| MaskExpression {
+                select: Some(StructSelect {
+                    struct_items: projection
+                        .iter()
+                        .map(|i| StructItem {
+                            field: *i as i32,
+                            child: None,
+                        })
+                        .collect(),
+                }),
+                maintain_singular_struct: false,
+            });
+            let table_df_schema =
+                DFSchema::try_from_qualified_schema(&scan.table_name, &table_schema)?;
+            let filter = conjunction(scan.filters.clone())
+                .map(|filter| {
+                    to_substrait_rex(&filter, &table_df_schema, functions).map(Box::new)
+                })
+                .transpose()?;
+
+            let read = rel(RelType::Read(Box::new(ReadRel {
+                base_schema: Some(to_substrait_named_struct(&table_schema)?),
+                filter,
+                projection,
+                read_type: Some(ReadType::NamedTable(NamedTable {
+                    names: scan.table_name.split('.').map(|s| s.to_string()).collect(),
+                    advanced_extension: None,
+                })),
+                ..Default::default()
+            })));
+            match scan.fetch {
+                Some(fetch) => Ok(rel(RelType::Fetch(Box::new(FetchRel {
+                    input: Some(Box::new(read)),
+                    offset: 0,
+                    count: fetch as i64,
+                    ..Default::default()
+                })))),
+                None => Ok(read),
+            }
+        }
+        LogicalPlan::Projection(projection) => {
+            let schema = projection.input.schema();
+            let expressions = projection
+                .expr
+                .iter()
+                .map(|e| to_substrait_rex(e, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            // a project relation appends its expressions to its input fields
+            let input_len = schema.fields().len() as i32;
+            let output_mapping =
+                (input_len..input_len + expressions.len() as i32).collect();
+            Ok(rel(RelType::Project(Box::new(ProjectRel {
+                common: Some(RelCommon {
+                    emit_kind: Some(EmitKind::Emit(Emit { output_mapping })),
+                    ..Default::default()
+                }),
+                input: Some(boxed_rel(&projection.input, functions, extension_codec)?),
+                expressions,
+                advanced_extension: None,
+            }))))
+        }
+        LogicalPlan::Filter(filter) => {
+            let condition =
+                to_substrait_rex(filter.predicate(), filter.input().schema(), functions)?;
+            Ok(rel(RelType::Filter(Box::new(FilterRel {
+                input: Some(boxed_rel(filter.input(), functions, extension_codec)?),
+                condition: Some(Box::new(condition)),
+                ..Default::default()
+            }))))
+        }
+        LogicalPlan::Limit(limit) => Ok(rel(RelType::Fetch(Box::new(FetchRel {
+            input: Some(boxed_rel(&limit.input, functions, extension_codec)?),
+            offset: limit.skip as i64,
+            count: limit.fetch.map(|fetch| fetch as i64).unwrap_or(-1),
+            ..Default::default()
+        })))),
+        LogicalPlan::Sort(sort) => {
+            let schema = sort.input.schema();
+            let sorts = sort
+                .expr
+                .iter()
+                .map(|e| to_substrait_sort_field(e, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            let sort_rel = rel(RelType::Sort(Box::new(SortRel {
+                input: Some(boxed_rel(&sort.input, functions, extension_codec)?),
+                sorts,
+                ..Default::default()
+            })));
+            match sort.fetch {
+                Some(fetch) => Ok(rel(RelType::Fetch(Box::new(FetchRel {
+                    input: Some(Box::new(sort_rel)),
+                    offset: 0,
+                    count: fetch as i64,
+                    ..Default::default()
+                })))),
+                None => Ok(sort_rel),
+            }
+        }
+        LogicalPlan::Aggregate(aggregate) => {
+            let schema = aggregate.input.schema();
+            let grouping_expressions = aggregate
+                .group_expr
+                .iter()
+                .map(|e| to_substrait_rex(e, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            let measures = aggregate
+                .aggr_expr
+                .iter()
+                .map(|e| to_substrait_measure(e, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(rel(RelType::Aggregate(Box::new(AggregateRel {
+                input: Some(boxed_rel(&aggregate.input, functions, extension_codec)?),
+                groupings: vec![Grouping {
+                    grouping_expressions,
+                }],
+                measures,
+                ..Default::default()
+            }))))
+        }
+        LogicalPlan::Join(join) => {
+            let join_type = match join.join_type {
+                JoinType::Inner => join_rel::JoinType::Inner,
+                JoinType::Left => join_rel::JoinType::Left,
+                JoinType::Right => join_rel::JoinType::Right,
+                JoinType::Full => join_rel::JoinType::Outer,
+                JoinType::LeftSemi => join_rel::JoinType::Semi,
+                JoinType::LeftAnti => join_rel::JoinType::Anti,
+                join_type => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported join type in Substrait: {:?}",
+                        join_type
+                    )))
+                }
+            };
+            // the join condition is evaluated against the concatenated inputs
+            let mut join_schema = join.left.schema().as_ref().clone();
+            join_schema.merge(join.right.schema());
+            let condition = join
+                .on
+                .iter()
+                .map(|(left, right)| {
+                    Expr::Column(left.clone()).eq(Expr::Column(right.clone()))
+                })
+                .chain(join.filter.clone());
+            let expression = conjunction(condition)
+                .map(|e| to_substrait_rex(&e, &join_schema, functions).map(Box::new))
+                .transpose()?;
+            Ok(rel(RelType::Join(Box::new(JoinRel {
+                left: Some(boxed_rel(&join.left, functions, extension_codec)?),
+                right: Some(boxed_rel(&join.right, functions, extension_codec)?),
+                expression,
+                r#type: join_type as i32,
+                ..Default::default()
+            }))))
+        }
+        LogicalPlan::CrossJoin(cross_join) => {
+            Ok(rel(RelType::Cross(Box::new(CrossRel {
+                left: Some(boxed_rel(&cross_join.left, functions, extension_codec)?),
+                right: Some(boxed_rel(&cross_join.right, functions, extension_codec)?),
+                ..Default::default()
+            }))))
+        }
+        LogicalPlan::Union(union) => {
+            let inputs = union
+                .inputs
+                .iter()
+                .map(|plan| to_substrait_rel(plan, functions, extension_codec))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(rel(RelType::Set(SetRel {
+                inputs,
+                op: SetOp::UnionAll as i32,
+                ..Default::default()
+            })))
+        }
+        // Substrait refers to fields by position, so aliases are not needed
+        LogicalPlan::SubqueryAlias(alias) => {
+            to_substrait_rel(&alias.input, functions, extension_codec)
+        }
+        LogicalPlan::Extension(extension) => {
+            let mut buf = vec![];
+            extension_codec.try_encode(extension, &mut buf)?;
+            let detail = Some(prost_types::Any {
+                type_url: String::new(),
+                value: buf,
+            });
+            let mut inputs = extension
+                .node
+                .inputs()
+                .into_iter()
+                .map(|plan| to_substrait_rel(plan, functions, extension_codec))
+                .collect::<Result<Vec<_>>>()?;
+            let rel_type = match inputs.len() {
+                0 => RelType::ExtensionLeaf(ExtensionLeafRel {
+                    common: None,
+                    detail,
+                }),
+                1 => RelType::ExtensionSingle(Box::new(ExtensionSingleRel {
+                    common: None,
+                    input: Some(Box::new(inputs.remove(0))),
+                    detail,
+                })),
+                _ => RelType::ExtensionMulti(ExtensionMultiRel {
+                    common: None,
+                    inputs,
+                    detail,
+                }),
+            };
+            Ok(rel(rel_type))
+        }
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported plan in Substrait: {:?}",
+            plan
+        ))),
+    }
+}
+
+fn to_substrait_named_struct(schema: &Schema) -> Result<NamedStruct> {
+    Ok(NamedStruct {
+        names: schema.fields().iter().map(|f| f.name().clone()).collect(),
+        r#struct: Some(r#type::Struct {
+            types: schema
+                .fields()
+                .iter()
+                .map(|f| to_substrait_type(f.data_type(), f.is_nullable()))
+                .collect::<Result<Vec<_>>>()?,
+            type_variation_reference: 0,
+            nullability: Nullability::Required as i32,
+        }),
+    })
+}
+
+fn to_substrait_sort_field(
+    expr: &Expr,
+    schema: &DFSchema,
+    functions: &mut FunctionExtensions,
+) -> Result<SortField> {
+    match expr {
+        Expr::Sort {
+            expr,
+            asc,
+            nulls_first,
+        } => {
+            let direction = match (asc, nulls_first) {
+                (true, true) => SortDirection::AscNullsFirst,
+                (true, false) => SortDirection::AscNullsLast,
+                (false, true) => SortDirection::DescNullsFirst,
+                (false, false) => SortDirection::DescNullsLast,
+            };
+            Ok(SortField {
+                expr: Some(to_substrait_rex(expr, schema, functions)?),
+                sort_kind: Some(SortKind::Direction(direction as i32)),
+            })
+        }
+        _ => Err(DataFusionError::Internal(format!(
+            "Expected a sort expression, got {:?}",
+            expr
+        ))),
+    }
+}
+
+fn to_substrait_measure(
+    expr: &Expr,
+    schema: &DFSchema,
+    functions: &mut FunctionExtensions,
+) -> Result<Measure> {
+    match expr {
+        Expr::AggregateFunction {
+            fun,
+            args,
+            distinct,
+            filter,
+        } => {
+            let arguments = args
+                .iter()
+                .map(|arg| to_substrait_argument(arg, schema, functions))
+                .collect::<Result<Vec<_>>>()?;
+            let invocation = if *distinct {
+                AggregationInvocation::Distinct
+            } else {
+                AggregationInvocation::All
+            };
+            Ok(Measure {
+                measure: Some(AggregateFunction {
+                    function_reference: functions.register(&function_name(fun)?),
+                    arguments,
+                    invocation: invocation as i32,
+                    ..Default::default()
+                }),
+                filter: filter
+                    .as_ref()
+                    .map(|filter| to_substrait_rex(filter, schema, functions))
+                    .transpose()?,
+            })
+        }
+        Expr::Alias(expr, _) => to_substrait_measure(expr, schema, functions),
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported aggregate expression in Substrait: {:?}",
+            expr
+        ))),
+    }
+}
+
+/// Names of built-in functions that are not derived from their variant
+const ABBREVIATED_FUNCTION_NAMES: &[&str] =
+    &["var", "var_pop", "covar", "covar_pop", "corr", "concat_ws"];
+
+/// Returns a name of the built-in function `fun` that parses back to it: the
+/// variant name in snake case or lower case, or an abbreviation
+fn function_name<F: FromStr + Debug + PartialEq>(fun: &F) -> Result<String> {
+    let variant = format!("{:?}", fun);
+    let mut snake_case = String::new();
+    let mut previous_lowercase = false;
+    for c in variant.chars() {
+        if c.is_uppercase() && previous_lowercase {
+            snake_case.push('_');
+        }
+        previous_lowercase = c.is_lowercase();
+        snake_case.extend(c.to_lowercase());
+    }
+    let lowercase = variant.to_lowercase();
+    [snake_case.as_str(), lowercase.as_str()]
+        .into_iter()
+        .chain(ABBREVIATED_FUNCTION_NAMES.iter().copied())
+        .find(|name| F::from_str(name).ok().as_ref() == Some(fun))
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "Unsupported function in Substrait: {}",
+                variant
+            ))
+        })
+}
+
+fn to_substrait_argument(
+    expr: &Expr,
+    schema: &DFSchema,
+    functions: &mut FunctionExtensions,
+) -> Result<FunctionArgument> {
+    Ok(FunctionArgument {
+        arg_type: Some(ArgType::Value(to_substrait_rex(expr, schema, functions)?)),
+    })
+}
+
+fn scalar_function(
+    name: &str,
+    args: &[&Expr],
+    schema: &DFSchema,
+    functions: &mut FunctionExtensions,
+) -> Result<Expression> {
+    let arguments = args
+        .iter()
+        .map(|arg| to_substrait_argument(arg, schema, functions))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Expression {
+        rex_type: Some(RexType::ScalarFunction(ScalarFunction {
+            function_reference: functions.register(name),
+            arguments,
+            ..Default::default()
+        })),
+    })
+}
+
+/// Returns the name of the Substrait function evaluating `op`
+pub(crate) fn operator_to_name(op: Operator) -> Result<&'static str> {
+    let name = match op {
+        Operator::Eq => "equal",
+        Operator::NotEq => "not_equal",
+        Operator::Lt => "lt",
+        Operator::LtEq => "lte",
+        Operator::Gt => "gt",
+        Operator::GtEq => "gte",
+        Operator::Plus => "add",
+        Operator::Minus => "subtract",
+        Operator::Multiply => "multiply",
+        Operator::Divide => "divide",
+        Operator::Modulo => "modulus",
+        Operator::And => "and",
+        Operator::Or => "or",
+        Operator::IsDistinctFrom => "is_distinct_from",
+        Operator::IsNotDistinctFrom => "is_not_distinct_from",
+        Operator::StringConcat => "concat",
+        op => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported operator in Substrait: {:?}",
+                op
+            )))
+        }
+    };
+    Ok(name)
+}
+
+/// Converts `expr`, referring to the fields of `schema`, to a Substrait
+/// expression
+fn to_substrait_rex(
+    expr: &Expr,
+    schema: &DFSchema,
+    functions: &mut FunctionExtensions,
+) -> Result<Expression> {
+    match expr {
+        Expr::Column(col) => {
+            let index = schema.index_of_column(col)?;
+            Ok(field_reference(index))
+        }
+        Expr::Literal(value) => to_substrait_literal(value),
+        Expr::Alias(expr, _) => to_substrait_rex(expr, schema, functions),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => scalar_function(
+            operator_to_name(*op)?,
+            &[left.as_ref(), right.as_ref()],
+            schema,
+            functions,
+        ),
+        Expr::Not(expr) => scalar_function("not", &[expr.as_ref()], schema, functions),
+        Expr::IsNull(expr) => {
+            scalar_function("is_null", &[expr.as_ref()], schema, functions)
+        }
+        Expr::IsNotNull(expr) => {
+            scalar_function("is_not_null", &[expr.as_ref()], schema, functions)
+        }
+        Expr::Negative(expr) => {
+            scalar_function("negate", &[expr.as_ref()], schema, functions)
+        }
+        Expr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => {
+            let between = expr
+                .as_ref()
+                .clone()
+                .gt_eq(low.as_ref().clone())
+                .and(expr.as_ref().clone().lt_eq(high.as_ref().clone()));
+            let between = if *negated {
+                Expr::Not(Box::new(between))
+            } else {
+                between
+            };
+            to_substrait_rex(&between, schema, functions)
+        }
+        Expr::Case(Case {
+            expr,
+            when_then_expr,
+            else_expr,
+        }) => {
+            let ifs = when_then_expr
+                .iter()
+                .map(|(when, then)| {
+                    // a simple case compares its operand to each value
+                    let when = match expr {
+                        Some(expr) => expr.as_ref().clone().eq(when.as_ref().clone()),
+                        None => when.as_ref().clone(),
+                    };
+                    Ok(IfClause {
+                        r#if: Some(to_substrait_rex(&when, schema, functions)?),
+                        then: Some(to_substrait_rex(then, schema, functions)?),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let r#else = else_expr
+                .as_ref()
+                .map(|e| to_substrait_rex(e, schema, functions).map(Box::new))
+                .transpose()?;
+            Ok(Expression {
+                rex_type: Some(RexType::IfThen(Box::new(IfThen { ifs, r#else }))),
+            })
+        }
+        Expr::Cast(Cast { expr, data_type }) => Ok(Expression {
+            rex_type: Some(RexType::Cast(Box::new(
+                substrait::protobuf::expression::Cast {
+                    r#type: Some(to_substrait_type(data_type, true)?),
+                    input: Some(Box::new(to_substrait_rex(expr, schema, functions)?)),
+                    ..Default::default()
+                },
+            ))),
+        }),
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let in_list = Expression {
+                rex_type: Some(RexType::SingularOrList(Box::new(SingularOrList {
+                    value: Some(Box::new(to_substrait_rex(expr, schema, functions)?)),
+                    options: list
+                        .iter()
+                        .map(|e| to_substrait_rex(e, schema, functions))
+                        .collect::<Result<Vec<_>>>()?,
+                }))),
+            };
+            if *negated {
+                let arguments = vec![FunctionArgument {
+                    arg_type: Some(ArgType::Value(in_list)),
+                }];
+                Ok(Expression {
+                    rex_type: Some(RexType::ScalarFunction(ScalarFunction {
+                        function_reference: functions.register("not"),
+                        arguments,
+                        ..Default::default()
+                    })),
+                })
+            } else {
+                Ok(in_list)
+            }
+        }
+        Expr::ScalarFunction { fun, args } => {
+            let args = args.iter().collect::<Vec<_>>();
+            scalar_function(&function_name(fun)?, &args, schema, functions)
+        }
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported expression in Substrait: {:?}",
+            expr
+        ))),
+    }
+}
+
+fn field_reference(index: usize) -> Expression {
+    Expression {
+        rex_type: Some(RexType::Selection(Box::new(FieldReference {
+            reference_type: Some(ReferenceType::DirectReference(ReferenceSegment {
+                reference_type: Some(reference_segment::ReferenceType::StructField(
+                    Box::new(reference_segment::StructField {
+                        field: index as i32,
+                        child: None,
+                    }),
+                )),
+            })),
+            root_type: Some(RootType::RootReference(RootReference {})),
+        }))),
+    }
+}
+
+fn to_substrait_literal(value: &ScalarValue) -> Result<Expression> {
+    let literal_type = match value {
+        ScalarValue::Boolean(Some(v)) => LiteralType::Boolean(*v),
+        ScalarValue::Int8(Some(v)) => LiteralType::I8(*v as i32),
+        ScalarValue::Int16(Some(v)) => LiteralType::I16(*v as i32),
+        ScalarValue::Int32(Some(v)) => LiteralType::I32(*v),
+        ScalarValue::Int64(Some(v)) => LiteralType::I64(*v),
+        ScalarValue::Float32(Some(v)) => LiteralType::Fp32(*v),
+        ScalarValue::Float64(Some(v)) => LiteralType::Fp64(*v),
+        ScalarValue::Utf8(Some(v)) => LiteralType::String(v.clone()),
+        ScalarValue::Binary(Some(v)) => LiteralType::Binary(v.clone()),
+        ScalarValue::Date32(Some(v)) => LiteralType::Date(*v),
+        ScalarValue::Decimal128(Some(v), precision, scale) => {
+            LiteralType::Decimal(Decimal {
+                value: v.to_le_bytes().to_vec(),
+                precision: *precision as i32,
+                scale: *scale as i32,
+            })
+        }
+        value if value.is_null() => {
+            LiteralType::Null(to_substrait_type(&value.get_datatype(), true)?)
+        }
+        value => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported literal in Substrait: {:?}",
+                value
+            )))
+        }
+    };
+    Ok(Expression {
+        rex_type: Some(RexType::Literal(Literal {
+            nullable: false,
+            type_variation_reference: 0,
+            literal_type: Some(literal_type),
+        })),
+    })
+}
+
+fn to_substrait_type(data_type: &DataType, nullable: bool) -> Result<Type> {
+    let nullability = if nullable {
+        Nullability::Nullable as i32
+    } else {
+        Nullability::Required as i32
+    };
+    let kind = match data_type {
+        DataType::Boolean => Kind::Bool(r#type::Boolean {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Int8 => Kind::I8(r#type::I8 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Int16 => Kind::I16(r#type::I16 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Int32 => Kind::I32(r#type::I32 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Int64 => Kind::I64(r#type::I64 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Float32 => Kind::Fp32(r#type::Fp32 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Float64 => Kind::Fp64(r#type::Fp64 {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Utf8 => Kind::String(r#type::String {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Binary => Kind::Binary(r#type::Binary {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Date32 => Kind::Date(r#type::Date {
+            type_variation_reference: 0,
+            nullability,
+        }),
+        DataType::Decimal128(precision, scale) => Kind::Decimal(r#type::Decimal {
+            scale: *scale as i32,
+            precision: *precision as i32,
+            type_variation_reference: 0,
+            nullability,
+        }),
+        data_type => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Unsupported type in Substrait: {:?}",
+                data_type
+            )))
+        }
+    };
+    Ok(Type { kind: Some(kind) })
+}